@@ -22,7 +22,7 @@
 use crate::error::{ParseError, XrefError};
 use crate::object_stream::ObjectStream;
 use crate::parser::{self, ParserInput};
-use crate::xref::XrefEntry;
+use crate::xref::{Xref, XrefEntry, XrefType};
 use crate::{Document, Error, IncrementalDocument, Object, ObjectId, Result};
 
 type FilterFunc = fn((u32, u16), &mut Object) -> Option<((u32, u16), Object)>;
@@ -146,6 +146,23 @@ pub fn load_mem(buffer: &[u8]) -> Result<Document> {
     }
 }
 
+impl Document {
+    /// Load a PDF whose cross-reference table is missing or damaged by
+    /// rebuilding it from the object headers in the file.
+    ///
+    /// Later definitions of an object win, as they do in incremental updates.
+    pub fn load_mem_rebuilding_xref(buffer: &[u8]) -> Result<Document> {
+        Reader {
+            buffer,
+            document: Document::new(),
+            encryption_state: None,
+            raw_objects: BTreeMap::new(),
+            password: None,
+        }
+        .read_rebuilding_xref(None)
+    }
+}
+
 impl TryInto<Document> for &[u8] {
     type Error = Error;
 
@@ -357,6 +374,150 @@ pub fn read(mut self, filter_func: Option<FilterFunc>) -> Result<Document> {
         Ok(self.document)
     }
     
+    /// Read whole document, ignoring its cross-reference table and scanning
+    /// for objects instead.
+    pub fn read_rebuilding_xref(mut self, filter_func: Option<FilterFunc>) -> Result<Document> {
+        let offset = self.buffer.windows(5).position(|w| w == b"%PDF-").unwrap_or(0);
+        self.buffer = &self.buffer[offset..];
+        let version =
+            parser::header(ParserInput::new_extra(self.buffer, "header")).ok_or(ParseError::InvalidFileHeader)?;
+
+        let xref = Self::scan_objects(self.buffer);
+        if xref.entries.is_empty() {
+            return Err(Error::Parse(ParseError::InvalidXref));
+        }
+        let mut trailer = Self::scan_trailer(self.buffer).unwrap_or_default();
+        trailer.remove(b"Prev");
+        trailer.remove(b"XRefStm");
+        warn!("Rebuilt cross-reference table with {} objects.", xref.entries.len());
+
+        self.document.version = version;
+        self.document.max_id = xref.max_id();
+        self.document.trailer = trailer;
+        self.document.reference_table = xref;
+
+        if self.document.trailer.get(b"Encrypt").is_ok() {
+            self.load_encrypted_document(filter_func)?;
+        } else {
+            self.load_objects_raw(filter_func)?;
+        }
+
+        // Cross-reference streams are stale now; the newest one carries the
+        // trailer entries of files without a trailer dictionary
+        let xref_streams: Vec<ObjectId> = self
+            .document
+            .objects
+            .iter()
+            .filter(|(_, object)| object.as_stream().is_ok_and(|s| s.dict.has_type(b"XRef")))
+            .map(|(&id, _)| id)
+            .collect();
+        for id in xref_streams {
+            if let Some(Object::Stream(stream)) = self.document.objects.remove(&id) {
+                for key in [&b"Root"[..], b"Info", b"ID"] {
+                    if let (Err(_), Ok(value)) = (self.document.trailer.get(key), stream.dict.get(key)) {
+                        self.document.trailer.set(key, value.clone());
+                    }
+                }
+            }
+        }
+        if self.document.trailer.get(b"Root").is_err() {
+            let catalog = self
+                .document
+                .objects
+                .iter()
+                .rev()
+                .find(|(_, object)| object.as_dict().is_ok_and(|d| d.has_type(b"Catalog")))
+                .map(|(&id, _)| id)
+                .ok_or(Error::Parse(ParseError::InvalidXref))?;
+            self.document.trailer.set("Root", catalog);
+        }
+        self.document.trailer.set("Size", i64::from(self.document.max_id) + 1);
+        self.document.reference_table.size = self.document.max_id + 1;
+
+        Ok(self.document)
+    }
+
+    /// Cross-reference table of every `N G obj` header in the buffer
+    fn scan_objects(buffer: &[u8]) -> Xref {
+        let mut xref = Xref::new(0, XrefType::CrossReferenceTable);
+        let mut pos = 0;
+        while let Some(found) = Self::find(buffer, b"obj", pos) {
+            let after = found + 3;
+            pos = after;
+            if buffer.get(after).is_some_and(u8::is_ascii_alphanumeric) {
+                continue;
+            }
+            let Some((start, id, generation)) = Self::object_header_before(buffer, found) else {
+                continue;
+            };
+            if let Ok(offset) = u32::try_from(start) {
+                xref.insert(id, XrefEntry::Normal { offset, generation });
+            }
+
+            // Skip the object body so stream data cannot pass for a header
+            let end = Self::find(buffer, b"endobj", after);
+            match Self::find(buffer, b"stream", after).filter(|&s| end.map_or(true, |e| s < e)) {
+                Some(stream) => {
+                    pos = Self::find(buffer, b"endstream", stream).map_or(buffer.len(), |e| e + b"endstream".len());
+                }
+                None => {
+                    if let Some(end) = end {
+                        pos = end + b"endobj".len();
+                    }
+                }
+            }
+        }
+        xref.size = xref.max_id() + 1;
+        xref
+    }
+
+    /// Start, object number and generation of the header ending at `obj_pos`
+    fn object_header_before(buffer: &[u8], obj_pos: usize) -> Option<(usize, u32, u16)> {
+        let is_space = |b: u8| b.is_ascii_whitespace() || b == 0;
+        let mut i = obj_pos;
+        let skip_space = |i: &mut usize| {
+            let before = *i;
+            while *i > 0 && is_space(buffer[*i - 1]) {
+                *i -= 1;
+            }
+            *i < before
+        };
+        let digits = |i: &mut usize| {
+            let end = *i;
+            while *i > 0 && buffer[*i - 1].is_ascii_digit() {
+                *i -= 1;
+            }
+            std::str::from_utf8(&buffer[*i..end]).ok().filter(|s| !s.is_empty()).map(str::to_string)
+        };
+
+        if !skip_space(&mut i) {
+            return None;
+        }
+        let generation = digits(&mut i)?.parse().ok()?;
+        if !skip_space(&mut i) {
+            return None;
+        }
+        let id = digits(&mut i)?.parse().ok()?;
+        let starts_token = i == 0 || is_space(buffer[i - 1]) || b"<>[]()/%".contains(&buffer[i - 1]);
+        starts_token.then_some((i, id, generation))
+    }
+
+    /// The last trailer dictionary in the buffer
+    fn scan_trailer(buffer: &[u8]) -> Option<crate::Dictionary> {
+        let pos = Self::search_substring(buffer, b"trailer", 0)? + b"trailer".len();
+        let start = pos + buffer[pos..].iter().take_while(|b| b.is_ascii_whitespace()).count();
+        parser::direct_object(ParserInput::new_extra(&buffer[start..], "trailer"))
+            .and_then(|object| object.as_dict().ok().cloned())
+    }
+
+    fn find(buffer: &[u8], pattern: &[u8], start_pos: usize) -> Option<usize> {
+        buffer
+            .get(start_pos..)?
+            .windows(pattern.len())
+            .position(|window| window == pattern)
+            .map(|pos| start_pos + pos)
+    }
+
     fn load_encrypted_document(&mut self, _filter_func: Option<FilterFunc>) -> Result<()> {
         // First, extract all raw object bytes without parsing
         let entries: Vec<_> = self.document.reference_table.entries.iter().map(|(k, v)| (*k, v.clone())).collect();
@@ -914,3 +1075,39 @@ fn search_substring_finds_last_occurrence() {
         Some(27)
     );
 }
+
+#[cfg(all(test, not(feature = "async")))]
+#[test]
+fn load_document_rebuilding_xref() {
+    // Offsets in the table are all wrong and the catalog is only in the trailer
+    let doc = b"%PDF-1.4
+1 0 obj<</Type/Pages/Kids[3 0 R]/Count 1/MediaBox[0 0 595 842]>>endobj
+2 0 obj<</Type/Catalog/Pages 1 0 R>>endobj
+3 0 obj<</Type/Page/Parent 1 0 R/Contents 4 0 R>>endobj
+4 0 obj<</Length 5 0 R>>stream
+BT (9 0 obj) Tj ET
+endstream
+endobj
+5 0 obj 18 endobj
+xref
+0 6
+0000000000 65535 f 
+0000000999 00000 n 
+0000000999 00000 n 
+0000000999 00000 n 
+0000000999 00000 n 
+0000000999 00000 n 
+trailer
+<</Root 2 0 R/Size 6>>
+startxref
+9999
+%%EOF";
+    assert!(Document::load_mem(doc).is_err());
+
+    let doc = Document::load_mem_rebuilding_xref(doc).unwrap();
+    assert_eq!(doc.get_pages().len(), 1);
+    assert_eq!(doc.trailer.get(b"Root").and_then(Object::as_reference).unwrap(), (2, 0));
+    assert!(doc.get_object((9, 0)).is_err());
+    let page_id = doc.page_iter().next().unwrap();
+    assert_eq!(doc.get_page_content(page_id).unwrap(), b"BT (9 0 obj) Tj ET");
+}
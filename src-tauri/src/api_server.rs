@@ -0,0 +1,592 @@
+//! API Server Module (feature `api-server`)
+//!
+//! Optional local REST API so publishing pipelines and scripts can drive the
+//! converter without the GUI or the wasm bindings. Bodies are JSON, one
+//! request per connection, and every route but `/api/health` needs an
+//! `Authorization: Bearer <token>` header.
+//!
+//! | Route               | Body                                  | Response           |
+//! |---------------------|---------------------------------------|--------------------|
+//! | `GET /api/health`   |                                       | `{ "ok": true }`   |
+//! | `POST /api/import`  | `{ filePath, fileType?, options? }`   | `DocumentResponse` |
+//! | `POST /api/export`  | `{ pages, metadata, options }`        | `ExportResult`     |
+//! | `POST /api/search`  | `{ filePath \| pages, query, options? }` | `SearchHit[]`   |
+//! | `POST /api/stats`   | `{ filePath \| pages }`               | `DocumentStats`    |
+//!
+//! The server listens on loopback unless LAN access is asked for. The route and
+//! token are checked as soon as the headers are in, before any body is read;
+//! reads time out and at most `MAX_CONNECTIONS` requests are served at once.
+//! Exports are only written inside the server's export directory: a relative
+//! `outputPath` is taken from there.
+
+use crate::document_parser::{self, ImportOptions};
+use crate::export_handler::{self, ExportOptions};
+use crate::models::{DocumentMetadata, PageData};
+use ring::rand::SecureRandom;
+use serde::{Deserialize, Serialize};
+use std::path::{Component, Path, PathBuf};
+use std::sync::{Arc, RwLock};
+use std::time::Duration;
+use tauri::{AppHandle, Manager};
+use tokio::io::{AsyncRead, AsyncReadExt, AsyncWriteExt};
+use tokio::net::{TcpListener, TcpStream};
+use tokio::sync::{watch, Semaphore};
+use tokio::time::timeout;
+use vortex_core::document_query::{self, SearchOptions};
+
+/// Default port for the API server
+pub const DEFAULT_API_PORT: u16 = 47810;
+
+/// Largest request head (request line and headers)
+const MAX_HEAD_BYTES: usize = 16 * 1024;
+/// Largest request body; exports carry whole documents
+const MAX_BODY_BYTES: usize = 256 * 1024 * 1024;
+/// Time allowed to send the request head
+const HEAD_TIMEOUT: Duration = Duration::from_secs(10);
+/// Time allowed to send the body once the head is accepted
+const BODY_TIMEOUT: Duration = Duration::from_secs(120);
+/// Requests served at once; further connections get a 503
+const MAX_CONNECTIONS: usize = 16;
+/// Export directory under the app data dir when none is configured
+const EXPORTS_DIR: &str = "api-exports";
+
+/// Address and credentials of the running server
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ApiServerInfo {
+    pub port: u16,
+    pub url: String,
+    /// Bearer token clients must send
+    pub token: String,
+    pub allow_lan: bool,
+    /// Directory exports are written to
+    pub export_dir: String,
+}
+
+struct ServerHandle {
+    info: ApiServerInfo,
+    shutdown: watch::Sender<bool>,
+}
+
+lazy_static::lazy_static! {
+    static ref API_SERVER: Arc<RwLock<Option<ServerHandle>>> = Arc::new(RwLock::new(None));
+}
+
+/// Parsed request line and the headers the server uses
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct RequestHead {
+    method: String,
+    path: String,
+    content_length: usize,
+    authorization: Option<String>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Route {
+    Health,
+    Import,
+    Export,
+    Search,
+    Stats,
+}
+
+/// An error answered with a status code
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct ApiError {
+    status: u16,
+    message: String,
+}
+
+impl ApiError {
+    fn new(status: u16, message: impl Into<String>) -> Self {
+        Self { status, message: message.into() }
+    }
+}
+
+impl From<String> for ApiError {
+    fn from(message: String) -> Self {
+        Self::new(500, message)
+    }
+}
+
+/// The document a search or stats request works on
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct DocumentSource {
+    file_path: Option<String>,
+    file_type: Option<String>,
+    pages: Option<Vec<PageData>>,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct ImportRequest {
+    file_path: String,
+    file_type: Option<String>,
+    options: Option<ImportOptions>,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct ExportRequest {
+    pages: Vec<PageData>,
+    metadata: DocumentMetadata,
+    options: ExportOptions,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct SearchRequest {
+    #[serde(flatten)]
+    source: DocumentSource,
+    query: String,
+    #[serde(default)]
+    options: SearchOptions,
+}
+
+/// Parse the request head, everything before the blank line
+fn parse_head(head: &str) -> Result<RequestHead, ApiError> {
+    let mut lines = head.split("\r\n");
+    let mut request_line = lines.next().unwrap_or_default().split(' ');
+    let (Some(method), Some(target), Some(version)) = (request_line.next(), request_line.next(), request_line.next())
+    else {
+        return Err(ApiError::new(400, "Malformed request line"));
+    };
+    if !version.starts_with("HTTP/1.") {
+        return Err(ApiError::new(400, format!("Unsupported protocol {}", version)));
+    }
+
+    let mut parsed = RequestHead {
+        method: method.to_string(),
+        // Query strings are not used by any route
+        path: target.split('?').next().unwrap_or(target).to_string(),
+        content_length: 0,
+        authorization: None,
+    };
+    for line in lines.filter(|l| !l.is_empty()) {
+        let Some((name, value)) = line.split_once(':') else {
+            return Err(ApiError::new(400, "Malformed header"));
+        };
+        let value = value.trim();
+        if name.eq_ignore_ascii_case("content-length") {
+            parsed.content_length = value.parse().map_err(|_| ApiError::new(400, "Invalid Content-Length"))?;
+        } else if name.eq_ignore_ascii_case("authorization") {
+            parsed.authorization = Some(value.to_string());
+        }
+    }
+    Ok(parsed)
+}
+
+fn route(method: &str, path: &str) -> Result<Route, ApiError> {
+    let route = match path.trim_end_matches('/') {
+        "/api/health" => Route::Health,
+        "/api/import" => Route::Import,
+        "/api/export" => Route::Export,
+        "/api/search" => Route::Search,
+        "/api/stats" => Route::Stats,
+        _ => return Err(ApiError::new(404, format!("No route for {}", path))),
+    };
+    let expected = if route == Route::Health { "GET" } else { "POST" };
+    if method != expected {
+        return Err(ApiError::new(405, format!("{} expects {}", path, expected)));
+    }
+    Ok(route)
+}
+
+/// Compare without leaking how much of the token matched
+fn token_matches(authorization: Option<&str>, token: &str) -> bool {
+    let Some(given) = authorization.and_then(|a| a.strip_prefix("Bearer ")) else {
+        return false;
+    };
+    given.len() == token.len() && given.bytes().zip(token.bytes()).fold(0u8, |acc, (a, b)| acc | (a ^ b)) == 0
+}
+
+fn new_token() -> Result<String, String> {
+    let mut bytes = [0u8; 24];
+    ring::rand::SystemRandom::new()
+        .fill(&mut bytes)
+        .map_err(|_| "Failed to generate API token".to_string())?;
+    Ok(bytes.iter().map(|b| format!("{:02x}", b)).collect())
+}
+
+fn parse_body<T: serde::de::DeserializeOwned>(body: &[u8]) -> Result<T, ApiError> {
+    serde_json::from_slice(body).map_err(|e| ApiError::new(400, format!("Invalid request body: {}", e)))
+}
+
+/// Where an export may be written: `output_path` relative to `export_dir`, or
+/// an absolute path already inside it, never climbing out
+fn export_target(export_dir: &Path, output_path: &str) -> Result<PathBuf, ApiError> {
+    let path = Path::new(output_path);
+    let outside = || ApiError::new(403, format!("Exports must be written inside {}", export_dir.display()));
+    let relative = if path.is_absolute() {
+        path.strip_prefix(export_dir).map_err(|_| outside())?
+    } else {
+        path
+    };
+    if relative.as_os_str().is_empty() || !relative.components().all(|c| matches!(c, Component::Normal(_))) {
+        return Err(outside());
+    }
+    Ok(export_dir.join(relative))
+}
+
+fn file_type_for(path: &str) -> String {
+    if path.to_lowercase().ends_with(".docx") {
+        "docx".to_string()
+    } else {
+        "pdf".to_string()
+    }
+}
+
+/// Pages sent with the request, or imported from its file
+async fn source_pages(source: DocumentSource, app_handle: &AppHandle) -> Result<Vec<PageData>, ApiError> {
+    let file_path = match (source.pages, source.file_path) {
+        (Some(pages), _) => return Ok(pages),
+        (None, Some(path)) => path,
+        (None, None) => return Err(ApiError::new(400, "Request needs filePath or pages")),
+    };
+    let file_type = source.file_type.unwrap_or_else(|| file_type_for(&file_path));
+    let response = document_parser::import_document(file_path, file_type, None, app_handle.clone()).await?;
+    match response.data {
+        Some(data) if response.success => Ok(data.pages),
+        _ => Err(ApiError::new(422, response.message)),
+    }
+}
+
+async fn handle(
+    route: Route,
+    body: &[u8],
+    export_dir: &Path,
+    app_handle: &AppHandle,
+) -> Result<serde_json::Value, ApiError> {
+    let to_json = |value: Result<serde_json::Value, serde_json::Error>| value.map_err(|e| ApiError::from(e.to_string()));
+    match route {
+        Route::Health => Ok(serde_json::json!({ "ok": true })),
+        Route::Import => {
+            let request: ImportRequest = parse_body(body)?;
+            let file_type = request.file_type.unwrap_or_else(|| file_type_for(&request.file_path));
+            let response =
+                document_parser::import_document(request.file_path, file_type, request.options, app_handle.clone())
+                    .await?;
+            to_json(serde_json::to_value(response))
+        }
+        Route::Export => {
+            let mut request: ExportRequest = parse_body(body)?;
+            let target = export_target(export_dir, &request.options.output_path)?;
+            if let Some(parent) = target.parent() {
+                std::fs::create_dir_all(parent).map_err(|e| e.to_string())?;
+            }
+            request.options.output_path = target.to_string_lossy().into_owned();
+            let format = request.options.format.as_str().to_string();
+            let output_path = request.options.output_path.clone();
+            let result =
+                export_handler::export_document(format, request.pages, output_path, request.metadata, request.options)
+                    .await?;
+            to_json(serde_json::to_value(result))
+        }
+        Route::Search => {
+            let request: SearchRequest = parse_body(body)?;
+            if request.query.is_empty() {
+                return Err(ApiError::new(400, "Search query is empty"));
+            }
+            let pages = source_pages(request.source, app_handle).await?;
+            to_json(serde_json::to_value(document_query::search_pages(&pages, &request.query, &request.options)))
+        }
+        Route::Stats => {
+            let pages = source_pages(parse_body(body)?, app_handle).await?;
+            to_json(serde_json::to_value(document_query::document_stats(&pages)))
+        }
+    }
+}
+
+/// Read the head of one request, returning it with any body bytes read past it
+async fn read_head<S: AsyncRead + Unpin>(stream: &mut S) -> Result<(RequestHead, Vec<u8>), ApiError> {
+    let mut buffer = Vec::with_capacity(4096);
+    let mut chunk = [0u8; 4096];
+    let head_end = loop {
+        if let Some(pos) = buffer.windows(4).position(|w| w == b"\r\n\r\n") {
+            break pos;
+        }
+        if buffer.len() > MAX_HEAD_BYTES {
+            return Err(ApiError::new(431, "Request headers too large"));
+        }
+        let read = stream.read(&mut chunk).await.map_err(|e| ApiError::new(400, e.to_string()))?;
+        if read == 0 {
+            return Err(ApiError::new(400, "Connection closed mid-request"));
+        }
+        buffer.extend_from_slice(&chunk[..read]);
+    };
+
+    let head = parse_head(&String::from_utf8_lossy(&buffer[..head_end]))?;
+    let body = buffer.split_off(head_end + 4);
+    Ok((head, body))
+}
+
+/// Read the rest of a request body after `read_head`
+async fn read_body<S: AsyncRead + Unpin>(
+    stream: &mut S,
+    head: &RequestHead,
+    mut body: Vec<u8>,
+) -> Result<Vec<u8>, ApiError> {
+    if head.content_length > MAX_BODY_BYTES {
+        return Err(ApiError::new(413, "Request body too large"));
+    }
+    if body.len() < head.content_length {
+        // Grown as the data arrives, so a Content-Length alone reserves nothing
+        let remaining = (head.content_length - body.len()) as u64;
+        (&mut *stream)
+            .take(remaining)
+            .read_to_end(&mut body)
+            .await
+            .map_err(|e| ApiError::new(400, format!("Incomplete request body: {}", e)))?;
+        if body.len() < head.content_length {
+            return Err(ApiError::new(400, "Incomplete request body: connection closed"));
+        }
+    }
+    body.truncate(head.content_length);
+    Ok(body)
+}
+
+async fn write_response(stream: &mut TcpStream, status: u16, body: &serde_json::Value) {
+    let reason = match status {
+        200 => "OK",
+        400 => "Bad Request",
+        401 => "Unauthorized",
+        403 => "Forbidden",
+        404 => "Not Found",
+        405 => "Method Not Allowed",
+        408 => "Request Timeout",
+        413 => "Payload Too Large",
+        422 => "Unprocessable Entity",
+        431 => "Request Header Fields Too Large",
+        503 => "Service Unavailable",
+        _ => "Internal Server Error",
+    };
+    let body = body.to_string();
+    let head = format!(
+        "HTTP/1.1 {} {}\r\nContent-Type: application/json\r\nContent-Length: {}\r\nConnection: close\r\n\r\n",
+        status,
+        reason,
+        body.len()
+    );
+    let _ = stream.write_all(head.as_bytes()).await;
+    let _ = stream.write_all(body.as_bytes()).await;
+    let _ = stream.shutdown().await;
+}
+
+async fn handle_connection(mut stream: TcpStream, token: Arc<str>, export_dir: Arc<Path>, app_handle: AppHandle) {
+    let timed_out = |_| ApiError::new(408, "Timed out reading the request");
+    let result = async {
+        let (head, read_ahead) = timeout(HEAD_TIMEOUT, read_head(&mut stream)).await.map_err(timed_out)??;
+        let matched = route(&head.method, &head.path)?;
+        if matched != Route::Health && !token_matches(head.authorization.as_deref(), &token) {
+            return Err(ApiError::new(401, "Missing or invalid API token"));
+        }
+        let body = timeout(BODY_TIMEOUT, read_body(&mut stream, &head, read_ahead)).await.map_err(timed_out)??;
+        tracing::info!(method = %head.method, path = %head.path, "api request");
+        handle(matched, &body, &export_dir, &app_handle).await
+    }
+    .await;
+
+    match result {
+        Ok(value) => write_response(&mut stream, 200, &value).await,
+        Err(e) => {
+            if e.status >= 500 {
+                tracing::error!("api request failed: {}", e.message);
+            }
+            write_response(&mut stream, e.status, &serde_json::json!({ "error": e.message })).await;
+        }
+    }
+}
+
+async fn run_server(
+    listener: TcpListener,
+    token: Arc<str>,
+    export_dir: Arc<Path>,
+    app_handle: AppHandle,
+    mut shutdown: watch::Receiver<bool>,
+) {
+    let connections = Arc::new(Semaphore::new(MAX_CONNECTIONS));
+    loop {
+        tokio::select! {
+            _ = shutdown.changed() => break,
+            accepted = listener.accept() => {
+                let Ok((mut stream, _)) = accepted else {
+                    continue;
+                };
+                match connections.clone().try_acquire_owned() {
+                    Ok(permit) => {
+                        let (token, export_dir, app_handle) = (token.clone(), export_dir.clone(), app_handle.clone());
+                        tokio::spawn(async move {
+                            handle_connection(stream, token, export_dir, app_handle).await;
+                            drop(permit);
+                        });
+                    }
+                    Err(_) => {
+                        tokio::spawn(async move {
+                            let busy = serde_json::json!({ "error": "Too many concurrent requests" });
+                            let _ = timeout(HEAD_TIMEOUT, write_response(&mut stream, 503, &busy)).await;
+                        });
+                    }
+                }
+            }
+        }
+    }
+}
+
+/// Start the API server; a token is generated when none is given, and
+/// exports go to `api-exports` in the app data dir unless `export_dir` is set
+#[tauri::command]
+pub async fn start_api_server(
+    port: Option<u16>,
+    token: Option<String>,
+    allow_lan: Option<bool>,
+    export_dir: Option<String>,
+    app_handle: AppHandle,
+) -> Result<ApiServerInfo, String> {
+    if let Some(handle) = API_SERVER.read().map_err(|e| e.to_string())?.as_ref() {
+        return Err(format!("API server already running on port {}", handle.info.port));
+    }
+    let token = match token.filter(|t| !t.trim().is_empty()) {
+        Some(token) => token,
+        None => new_token()?,
+    };
+    let export_dir = match export_dir.filter(|d| !d.trim().is_empty()) {
+        Some(dir) => PathBuf::from(dir),
+        None => app_handle.path().app_data_dir().map_err(|e| e.to_string())?.join(EXPORTS_DIR),
+    };
+    std::fs::create_dir_all(&export_dir).map_err(|e| format!("Failed to create export directory: {}", e))?;
+    // Resolved, so absolute output paths compare against the real location
+    let export_dir = export_dir.canonicalize().map_err(|e| e.to_string())?;
+    let allow_lan = allow_lan.unwrap_or(false);
+    let host = if allow_lan { "0.0.0.0" } else { "127.0.0.1" };
+
+    let listener = TcpListener::bind((host, port.unwrap_or(DEFAULT_API_PORT)))
+        .await
+        .map_err(|e| format!("Failed to bind API server: {}", e))?;
+    let port = listener.local_addr().map_err(|e| e.to_string())?.port();
+    let info = ApiServerInfo {
+        port,
+        url: format!("http://127.0.0.1:{}/api", port),
+        token,
+        allow_lan,
+        export_dir: export_dir.to_string_lossy().into_owned(),
+    };
+
+    let (shutdown, shutdown_rx) = watch::channel(false);
+    {
+        let mut server = API_SERVER.write().map_err(|e| e.to_string())?;
+        if server.is_some() {
+            return Err("API server already running".to_string());
+        }
+        *server = Some(ServerHandle { info: info.clone(), shutdown });
+    }
+
+    tracing::info!(port, allow_lan, "api server started");
+    let (token, export_dir) = (Arc::from(info.token.as_str()), Arc::from(export_dir.as_path()));
+    tauri::async_runtime::spawn(run_server(listener, token, export_dir, app_handle, shutdown_rx));
+    Ok(info)
+}
+
+/// Stop the API server, returning whether one was running
+#[tauri::command]
+pub fn stop_api_server() -> Result<bool, String> {
+    let handle = API_SERVER.write().map_err(|e| e.to_string())?.take();
+    match handle {
+        Some(handle) => {
+            let _ = handle.shutdown.send(true);
+            Ok(true)
+        }
+        None => Ok(false),
+    }
+}
+
+/// Get info about the running API server
+#[tauri::command]
+pub fn get_api_server_info() -> Result<Option<ApiServerInfo>, String> {
+    let server = API_SERVER.read().map_err(|e| e.to_string())?;
+    Ok(server.as_ref().map(|h| h.info.clone()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_head() {
+        let head = parse_head(
+            "POST /api/search?x=1 HTTP/1.1\r\nHost: localhost\r\ncontent-length: 42\r\nAuthorization: Bearer abc",
+        )
+        .unwrap();
+        assert_eq!(head.method, "POST");
+        assert_eq!(head.path, "/api/search");
+        assert_eq!(head.content_length, 42);
+        assert!(token_matches(head.authorization.as_deref(), "abc"));
+        assert!(!token_matches(head.authorization.as_deref(), "abd"));
+        assert!(!token_matches(None, "abc"));
+
+        assert_eq!(parse_head("GARBAGE").unwrap_err().status, 400);
+        assert_eq!(parse_head("GET / SPDY/3\r\n").unwrap_err().status, 400);
+    }
+
+    #[tokio::test]
+    async fn test_head_is_read_without_the_body() {
+        let (mut client, mut server) = tokio::io::duplex(1024);
+        client
+            .write_all(b"POST /api/export HTTP/1.1\r\nContent-Length: 268435456\r\n\r\n{\"pa")
+            .await
+            .unwrap();
+
+        // Returns as soon as the head is in, though the body never arrives
+        let (head, read_ahead) = timeout(Duration::from_secs(5), read_head(&mut server)).await.unwrap().unwrap();
+        assert_eq!(head.path, "/api/export");
+        assert!(!token_matches(head.authorization.as_deref(), "abc"));
+        assert_eq!(read_ahead, b"{\"pa");
+
+        let oversized = RequestHead { content_length: MAX_BODY_BYTES + 1, ..head };
+        assert_eq!(read_body(&mut server, &oversized, Vec::new()).await.unwrap_err().status, 413);
+    }
+
+    #[tokio::test]
+    async fn test_body_is_read_as_it_arrives() {
+        let (mut client, mut server) = tokio::io::duplex(1024);
+        let head = RequestHead {
+            method: "POST".to_string(),
+            path: "/api/stats".to_string(),
+            content_length: 10,
+            authorization: None,
+        };
+        client.write_all(b"3456789 and more").await.unwrap();
+        let body = read_body(&mut server, &head, b"012".to_vec()).await.unwrap();
+        assert_eq!(body, b"0123456789");
+
+        // A client that stops short of its Content-Length is refused
+        let (mut client, mut server) = tokio::io::duplex(1024);
+        let large = RequestHead { content_length: MAX_BODY_BYTES, ..head };
+        client.write_all(b"{}").await.unwrap();
+        drop(client);
+        let error = read_body(&mut server, &large, Vec::new()).await.unwrap_err();
+        assert_eq!(error.status, 400);
+    }
+
+    #[test]
+    fn test_exports_stay_in_the_export_dir() {
+        let dir = std::env::temp_dir().join("rook-api-exports");
+        assert_eq!(export_target(&dir, "book.pdf"), Ok(dir.join("book.pdf")));
+        assert_eq!(export_target(&dir, "2026/book.epub"), Ok(dir.join("2026").join("book.epub")));
+        let inside = dir.join("book.docx");
+        assert_eq!(export_target(&dir, &inside.to_string_lossy()), Ok(inside));
+
+        let elsewhere = std::env::temp_dir().join("book.pdf");
+        for path in ["", "../book.pdf", "2026/../../book.pdf", "./book.pdf", &elsewhere.to_string_lossy()] {
+            assert_eq!(export_target(&dir, path).unwrap_err().status, 403, "{}", path);
+        }
+    }
+
+    #[test]
+    fn test_route() {
+        assert_eq!(route("GET", "/api/health/"), Ok(Route::Health));
+        assert_eq!(route("POST", "/api/stats"), Ok(Route::Stats));
+        assert_eq!(route("GET", "/api/export").unwrap_err().status, 405);
+        assert_eq!(route("POST", "/api/unknown").unwrap_err().status, 404);
+    }
+}
@@ -0,0 +1,269 @@
+//! Asset Manager Module
+//!
+//! The images a project uses, for an assets panel: which layers show each
+//! one, which cached images no layer shows any more, and which have lost
+//! their file. An asset is the id an image layer's bytes are cached under,
+//! its `image://` id or else its image path, so layers sharing an image
+//! share an asset.
+//!
+//! Like the other page commands these take the pages and hand back updated
+//! copies for the frontend to apply.
+
+use crate::image_handler;
+use crate::linked_images;
+use crate::models::{LayerObject, LayerType, PageData};
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use std::path::{Path, PathBuf};
+use vortex_core::image_place;
+
+/// Folder levels searched below the folder given to `relink_missing_assets`
+const RELINK_SEARCH_DEPTH: usize = 4;
+
+/// A layer showing an asset
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct AssetUsage {
+    pub page_index: usize,
+    pub layer_id: String,
+}
+
+/// An image asset and the layers that use it
+#[derive(Debug, Clone, Default, Serialize, Deserialize, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct AssetInfo {
+    pub id: String,
+    /// File the image came from or is linked to
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub path: Option<String>,
+    pub linked: bool,
+    pub width: u32,
+    pub height: u32,
+    /// Encoded size in bytes; `None` when not in the cache
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub size: Option<usize>,
+    pub usages: Vec<AssetUsage>,
+    /// Neither cached nor readable from its file, or a linked file is gone
+    pub missing: bool,
+}
+
+/// Pages with an asset replaced or relinked
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct AssetUpdate {
+    pub pages: Vec<PageData>,
+    /// Layers now showing the new image
+    pub updated: Vec<String>,
+    /// Missing assets no file was found for
+    pub unresolved: Vec<String>,
+}
+
+/// Cache id of an image layer's bytes
+fn asset_id(layer: &LayerObject) -> Option<&str> {
+    if layer.layer_type != LayerType::Image {
+        return None;
+    }
+    layer.image_url.as_deref().and_then(|url| url.strip_prefix("image://")).or(layer.image_path.as_deref())
+}
+
+/// File an image layer was read from
+fn source_path(layer: &LayerObject) -> Option<&str> {
+    layer.image_link.as_ref().map(|l| l.path.as_str()).or(layer.image_path.as_deref())
+}
+
+/// Assets referenced by the pages, by id; `available` tells whether an
+/// image id can be served and `exists` whether a file is there
+fn collect_assets(
+    pages: &[PageData],
+    available: impl Fn(&str) -> bool,
+    exists: impl Fn(&str) -> bool,
+) -> BTreeMap<String, AssetInfo> {
+    let mut assets: BTreeMap<String, AssetInfo> = BTreeMap::new();
+    for page in pages {
+        for layer in &page.layers {
+            let Some(id) = asset_id(layer) else {
+                continue;
+            };
+            let asset =
+                assets.entry(id.to_string()).or_insert_with(|| AssetInfo { id: id.to_string(), ..Default::default() });
+            asset.usages.push(AssetUsage { page_index: page.page_index, layer_id: layer.id.clone() });
+            asset.linked |= layer.image_link.is_some();
+            if asset.path.is_none() {
+                asset.path = source_path(layer).map(str::to_string);
+            }
+            if let Some(metadata) = layer.image_data.as_ref().filter(|_| asset.width == 0) {
+                (asset.width, asset.height) = (metadata.width, metadata.height);
+            }
+        }
+    }
+    for asset in assets.values_mut() {
+        let file_ok = asset.path.as_deref().map(&exists);
+        asset.missing = match file_ok {
+            Some(false) if asset.linked => true,
+            Some(ok) => !ok && !available(&asset.id),
+            None => !available(&asset.id),
+        };
+    }
+    assets
+}
+
+/// Cached images no layer on the pages uses
+fn unused_ids(pages: &[PageData], cached: Vec<String>) -> Vec<String> {
+    let used = collect_assets(pages, |_| true, |_| true);
+    cached.into_iter().filter(|id| !used.contains_key(id) && !used.values().any(|a| a.path.as_deref() == Some(id))).collect()
+}
+
+/// Point every layer using `old_id` at `new_id`, read from `path`
+fn retarget_layers(pages: &mut [PageData], old_id: &str, new_id: &str, path: &str, size: (u32, u32)) -> Vec<String> {
+    let mut updated = Vec::new();
+    for layer in pages.iter_mut().flat_map(|p| &mut p.layers) {
+        if asset_id(layer) != Some(old_id) {
+            continue;
+        }
+        if layer.image_link.is_some() {
+            layer.image_link = linked_images::read_link(path).ok().map(|(link, _)| link);
+        }
+        if layer.image_path.is_some() {
+            layer.image_path = Some(path.to_string());
+        }
+        layer.image_url = Some(format!("image://{}", new_id));
+        linked_images::set_image_size(layer, size.0, size.1);
+        updated.push(layer.id.clone());
+    }
+    updated
+}
+
+/// Read an image file with its pixel size
+fn read_image(path: &str) -> Result<(Vec<u8>, (u32, u32)), String> {
+    let data = std::fs::read(path).map_err(|e| format!("Failed to read {}: {}", path, e))?;
+    let header = image_place::read_header(&data).ok_or_else(|| format!("Unsupported image format: {}", path))?;
+    Ok((data, (header.width, header.height)))
+}
+
+/// A file named `name` in `dir` or up to `depth` folders below it
+fn find_file(dir: &Path, name: &str, depth: usize) -> Option<PathBuf> {
+    let candidate = dir.join(name);
+    if candidate.is_file() {
+        return Some(candidate);
+    }
+    if depth == 0 {
+        return None;
+    }
+    let mut subdirs: Vec<PathBuf> =
+        std::fs::read_dir(dir).ok()?.filter_map(|e| e.ok()).map(|e| e.path()).filter(|p| p.is_dir()).collect();
+    subdirs.sort();
+    subdirs.iter().find_map(|sub| find_file(sub, name, depth - 1))
+}
+
+/// Image assets with the layers using them, sorted by id
+#[tauri::command]
+pub fn list_assets(pages: Vec<PageData>) -> Vec<AssetInfo> {
+    let mut assets: Vec<AssetInfo> =
+        collect_assets(&pages, image_handler::image_available, |path| Path::new(path).is_file()).into_values().collect();
+    for asset in &mut assets {
+        asset.size = image_handler::cached_image_size(&asset.id);
+    }
+    assets
+}
+
+/// Cached images no layer uses, e.g. left behind by deleted layers; with
+/// `remove` they are also dropped from the cache
+#[tauri::command]
+pub fn find_unused_assets(pages: Vec<PageData>, remove: Option<bool>) -> Vec<String> {
+    let unused = unused_ids(&pages, image_handler::cached_image_ids());
+    if remove.unwrap_or(false) {
+        for id in &unused {
+            image_handler::forget_image(id);
+        }
+    }
+    unused
+}
+
+/// Replace an asset with an image file on every layer that uses it
+///
+/// The file is cached under a new id derived from its contents, so undoing
+/// the replacement brings the old image back. Layers keep their placement.
+#[tauri::command]
+pub async fn replace_asset(pages: Vec<PageData>, asset_id: String, path: String) -> Result<AssetUpdate, String> {
+    tokio::task::spawn_blocking(move || {
+        let (data, size) = read_image(&path)?;
+        let new_id = format!("asset-{}", &linked_images::file_hash(&data)[..16]);
+        image_handler::cache_image_with_dimensions(&new_id, data, size.0, size.1);
+
+        let mut pages = pages;
+        let updated = retarget_layers(&mut pages, &asset_id, &new_id, &path, size);
+        if updated.is_empty() {
+            return Err(format!("Asset not used on these pages: {}", asset_id));
+        }
+        Ok(AssetUpdate { pages, updated, unresolved: Vec::new() })
+    })
+    .await
+    .map_err(|e| format!("Replace task failed: {}", e))?
+}
+
+/// Look for the files of missing assets in `folder`
+///
+/// Files are matched by name, in the folder or a few levels below it. Found
+/// files are cached under the asset's id and the layers' paths and links
+/// updated; assets still without a file are listed as unresolved.
+#[tauri::command]
+pub async fn relink_missing_assets(pages: Vec<PageData>, folder: String) -> Result<AssetUpdate, String> {
+    tokio::task::spawn_blocking(move || {
+        let missing: Vec<AssetInfo> =
+            collect_assets(&pages, image_handler::image_available, |path| Path::new(path).is_file())
+                .into_values()
+                .filter(|a| a.missing)
+                .collect();
+
+        let mut update = AssetUpdate { pages, ..Default::default() };
+        for asset in missing {
+            let name = asset.path.as_deref().and_then(|p| Path::new(p).file_name()).and_then(|n| n.to_str());
+            let found = name.and_then(|name| find_file(Path::new(&folder), name, RELINK_SEARCH_DEPTH));
+            let Some(found) = found.map(|p| p.to_string_lossy().to_string()) else {
+                update.unresolved.push(asset.id);
+                continue;
+            };
+            let (data, size) = read_image(&found)?;
+            image_handler::cache_image_with_dimensions(&asset.id, data, size.0, size.1);
+            update.updated.extend(retarget_layers(&mut update.pages, &asset.id, &asset.id, &found, size));
+        }
+        Ok(update)
+    })
+    .await
+    .map_err(|e| format!("Relink task failed: {}", e))?
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::ImageLink;
+    use vortex_core::test_util::{layer, page};
+
+    fn image(id: &str, url: Option<&str>, path: Option<&str>) -> LayerObject {
+        layer(id, "image").fields(serde_json::json!({ "imageUrl": url, "imagePath": path })).build()
+    }
+
+    #[test]
+    fn test_collect_assets() {
+        let mut linked = image("c", Some("image://c"), None);
+        linked.image_link = Some(ImageLink { path: "/gone/c.png".to_string(), modified: 0, hash: String::new() });
+        let pages = vec![
+            page(0, vec![image("a", Some("image://shared"), None), image("b", Some("image://shared"), None), linked]),
+            page(1, vec![image("d", None, Some("/figs/d.png"))]),
+        ];
+
+        let assets = collect_assets(&pages, |id| id == "shared" || id == "c", |path| path == "/figs/d.png");
+        assert_eq!(assets.keys().collect::<Vec<_>>(), ["/figs/d.png", "c", "shared"]);
+        assert_eq!(assets["shared"].usages.iter().map(|u| u.layer_id.as_str()).collect::<Vec<_>>(), ["a", "b"]);
+        assert!(!assets["shared"].missing && !assets["/figs/d.png"].missing);
+        // Cached, but its linked file is gone
+        assert!(assets["c"].missing && assets["c"].linked);
+
+        let cached = vec!["shared".to_string(), "stale".to_string(), "c".to_string()];
+        assert_eq!(unused_ids(&pages, cached), ["stale"]);
+
+        let mut pages = pages;
+        assert_eq!(retarget_layers(&mut pages, "shared", "asset-1", "/new.png", (10, 10)), ["a", "b"]);
+        assert_eq!(pages[0].layers[1].image_url.as_deref(), Some("image://asset-1"));
+    }
+}
@@ -0,0 +1,397 @@
+//! Change Tracker Module
+//!
+//! Track-changes mode for collaborative editing. While enabled, every layer
+//! modification is recorded as a pending [`TrackedChange`] with author and
+//! timestamp. Pages always hold the current state; each change keeps the
+//! layer as it was before so it can be rejected later.
+//!
+//! Like the layer processor, these commands are stateless: the frontend owns
+//! the page and change lists (persisted in `BookProjectData::changes`) and
+//! passes them in, receiving the updated lists back.
+
+use crate::models::{
+    ChangeKind, LayerObject, LayerRole, LayerType, PageData, ShapeType, TrackedChange,
+};
+use serde::{Deserialize, Serialize};
+use std::sync::atomic::{AtomicU64, Ordering};
+
+/// Review colors, matching the insert/delete palette of word processors
+pub const INSERT_COLOR: &str = "#1565C0";
+pub const DELETE_COLOR: &str = "#C62828";
+pub const UPDATE_COLOR: &str = "#6A1B9A";
+
+/// Width of the change bar drawn in the left margin for updated layers
+const CHANGE_BAR_WIDTH: f32 = 2.0;
+const CHANGE_BAR_OFFSET: f32 = 6.0;
+
+static CHANGE_COUNTER: AtomicU64 = AtomicU64::new(0);
+
+/// Pages and pending changes after a review action
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ChangeReviewResult {
+    pub pages: Vec<PageData>,
+    pub changes: Vec<TrackedChange>,
+}
+
+fn next_change_id() -> String {
+    let millis = std::time::SystemTime::now()
+        .duration_since(std::time::UNIX_EPOCH)
+        .map(|d| d.as_millis())
+        .unwrap_or(0);
+    let n = CHANGE_COUNTER.fetch_add(1, Ordering::Relaxed);
+    format!("change-{}-{}", millis, n)
+}
+
+/// Record a layer modification into the pending change list
+///
+/// Successive edits to the same layer are coalesced so each layer has at most
+/// one pending change: the original `before` is kept and `after` is replaced.
+/// Inserting and then deleting a layer cancels out entirely, as does an
+/// update that restores the original layer.
+pub fn record_change(
+    changes: &mut Vec<TrackedChange>,
+    page_index: usize,
+    before: Option<LayerObject>,
+    after: Option<LayerObject>,
+    author: &str,
+) -> Result<(), String> {
+    let layer_id = after
+        .as_ref()
+        .or(before.as_ref())
+        .map(|l| l.id.clone())
+        .ok_or("A change needs a before or after layer")?;
+
+    let existing = changes
+        .iter()
+        .position(|c| c.page_index == page_index && c.layer_id == layer_id);
+
+    let original = match existing {
+        Some(idx) => changes.remove(idx).before,
+        None => before,
+    };
+
+    let kind = match (&original, &after) {
+        (None, Some(_)) => ChangeKind::Insert,
+        (Some(_), Some(_)) => ChangeKind::Update,
+        (Some(_), None) => ChangeKind::Delete,
+        // Inserted then deleted under review: nothing left to track
+        (None, None) => return Ok(()),
+    };
+
+    if kind == ChangeKind::Update && original == after {
+        return Ok(());
+    }
+
+    changes.push(TrackedChange {
+        id: next_change_id(),
+        kind,
+        page_index,
+        layer_id,
+        author: author.to_string(),
+        timestamp: crate::models::iso8601_now(),
+        before: original,
+        after,
+    });
+    Ok(())
+}
+
+/// Revert a single change on the given pages
+fn revert_change(pages: &mut [PageData], change: &TrackedChange) -> Result<(), String> {
+    let page = pages
+        .iter_mut()
+        .find(|p| p.page_index == change.page_index)
+        .ok_or_else(|| format!("Page {} not found", change.page_index))?;
+    let pos = page.layers.iter().position(|l| l.id == change.layer_id);
+
+    match (change.kind, pos) {
+        (ChangeKind::Insert, Some(idx)) => {
+            page.layers.remove(idx);
+        }
+        (ChangeKind::Update, Some(idx)) => {
+            if let Some(before) = &change.before {
+                page.layers[idx] = before.clone();
+            }
+        }
+        (ChangeKind::Delete, None) | (ChangeKind::Update, None) => {
+            if let Some(before) = &change.before {
+                page.layers.push(before.clone());
+            }
+        }
+        (ChangeKind::Delete, Some(_)) | (ChangeKind::Insert, None) => {
+            // Already in the reverted state
+        }
+    }
+    Ok(())
+}
+
+fn take_change(changes: &mut Vec<TrackedChange>, change_id: &str) -> Result<TrackedChange, String> {
+    let idx = changes
+        .iter()
+        .position(|c| c.id == change_id)
+        .ok_or_else(|| format!("Change {} not found", change_id))?;
+    Ok(changes.remove(idx))
+}
+
+/// Render pending changes as review markup on a copy of the pages
+///
+/// Insertions are underlined in [`INSERT_COLOR`], deletions are restored and
+/// struck through in [`DELETE_COLOR`], and updated layers get a change bar in
+/// the left margin.
+pub fn apply_review_markup(pages: &[PageData], changes: &[TrackedChange]) -> Vec<PageData> {
+    let mut pages = pages.to_vec();
+
+    for change in changes {
+        let Some(page) = pages.iter_mut().find(|p| p.page_index == change.page_index) else {
+            continue;
+        };
+
+        match change.kind {
+            ChangeKind::Insert => {
+                if let Some(layer) = page.layers.iter_mut().find(|l| l.id == change.layer_id) {
+                    mark_layer(layer, INSERT_COLOR, "underline");
+                }
+            }
+            ChangeKind::Delete => {
+                if let Some(before) = &change.before {
+                    let mut ghost = before.clone();
+                    ghost.visible = true;
+                    mark_layer(&mut ghost, DELETE_COLOR, "line-through");
+                    page.layers.push(ghost);
+                }
+            }
+            ChangeKind::Update => {
+                let Some(layer) = page.layers.iter().find(|l| l.id == change.layer_id) else {
+                    continue;
+                };
+                let mut bar = layer.clone();
+                bar.id = format!("{}-change-bar", change.id);
+                bar.layer_type = LayerType::Shape;
+                bar.bounds.x = (layer.bounds.x - CHANGE_BAR_OFFSET).max(0.0);
+                bar.bounds.width = CHANGE_BAR_WIDTH;
+                bar.visible = true;
+                bar.opacity = 1.0;
+                bar.content = None;
+                bar.fill_color = Some(UPDATE_COLOR.to_string());
+                bar.stroke_color = Some(UPDATE_COLOR.to_string());
+                bar.stroke_width = Some(0.0);
+                bar.shape_type = Some(ShapeType::Rectangle);
+                bar.role = LayerRole::Annotation;
+                page.layers.push(bar);
+            }
+        }
+    }
+
+    pages
+}
+
+fn mark_layer(layer: &mut LayerObject, color: &str, decoration: &str) {
+    match layer.layer_type {
+        LayerType::Text => {
+            layer.color = Some(color.to_string());
+            layer.text_decoration = Some(decoration.to_string());
+        }
+        _ => {
+            layer.stroke_color = Some(color.to_string());
+            layer.stroke_width = Some(layer.stroke_width.unwrap_or(1.0).max(1.0));
+        }
+    }
+}
+
+/// Record a layer modification while track-changes mode is on
+///
+/// Pass `before: None` for an insertion and `after: None` for a deletion.
+/// Returns the updated pending change list.
+#[tauri::command]
+pub fn record_layer_change(
+    changes: Vec<TrackedChange>,
+    page_index: usize,
+    before: Option<LayerObject>,
+    after: Option<LayerObject>,
+    author: String,
+) -> Result<Vec<TrackedChange>, String> {
+    let mut changes = changes;
+    record_change(&mut changes, page_index, before, after, &author)?;
+    Ok(changes)
+}
+
+/// Accept a single change, keeping the current layer state
+#[tauri::command]
+pub fn accept_change(
+    pages: Vec<PageData>,
+    changes: Vec<TrackedChange>,
+    change_id: String,
+) -> Result<ChangeReviewResult, String> {
+    let mut changes = changes;
+    take_change(&mut changes, &change_id)?;
+    Ok(ChangeReviewResult { pages, changes })
+}
+
+/// Reject a single change, restoring the layer to its prior state
+#[tauri::command]
+pub fn reject_change(
+    pages: Vec<PageData>,
+    changes: Vec<TrackedChange>,
+    change_id: String,
+) -> Result<ChangeReviewResult, String> {
+    let (mut pages, mut changes) = (pages, changes);
+    let change = take_change(&mut changes, &change_id)?;
+    revert_change(&mut pages, &change)?;
+    Ok(ChangeReviewResult { pages, changes })
+}
+
+/// Accept every pending change
+#[tauri::command]
+pub fn accept_all_changes(pages: Vec<PageData>) -> Result<ChangeReviewResult, String> {
+    Ok(ChangeReviewResult {
+        pages,
+        changes: Vec::new(),
+    })
+}
+
+/// Reject every pending change, newest first
+#[tauri::command]
+pub fn reject_all_changes(
+    pages: Vec<PageData>,
+    changes: Vec<TrackedChange>,
+) -> Result<ChangeReviewResult, String> {
+    let mut pages = pages;
+    for change in changes.iter().rev() {
+        revert_change(&mut pages, change)?;
+    }
+    Ok(ChangeReviewResult {
+        pages,
+        changes: Vec::new(),
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::{Bounds, SourceType};
+
+    fn text_layer(id: &str, content: &str) -> LayerObject {
+        LayerObject {
+            id: id.to_string(),
+            layer_type: LayerType::Text,
+            bounds: Bounds::new(72.0, 72.0, 200.0, 20.0),
+            visible: true,
+            locked: false,
+            z_index: 1,
+            opacity: 1.0,
+            blend_mode: None,
+            content: Some(content.to_string()),
+            font_family: None,
+            font_size: Some(12.0),
+            font_weight: None,
+            font_style: None,
+            color: Some("#000000".to_string()),
+            text_align: None,
+            text_decoration: None,
+            text_transform: None,
+            line_height: None,
+            letter_spacing: None,
+            background_color: None,
+            text_path: None,
+            text_outline: None,
+            text_shadow: None,
+            drop_cap: None,
+            notes: Vec::new(),
+            pagination: None,
+            image_url: None,
+            image_path: None,
+            image_data: None,
+            image_link: None,
+            shape_type: None,
+            stroke_color: None,
+            stroke_width: None,
+            fill_color: None,
+            text_wrap: None,
+            path_data: None,
+            transform: None,
+            source_type: SourceType::Manual,
+            role: LayerRole::Content,
+            ocr: None,
+            tags: Vec::new(),
+        }
+    }
+
+    fn page(layers: Vec<LayerObject>) -> PageData {
+        PageData {
+            page_index: 0,
+            width: 612.0,
+            height: 792.0,
+            dpi: Some(72),
+            layers,
+            metadata: None,
+            background: None,
+        }
+    }
+
+    #[test]
+    fn test_record_coalesces_edits() {
+        let mut changes = Vec::new();
+        let v1 = text_layer("t1", "one");
+        let v2 = text_layer("t1", "two");
+        let v3 = text_layer("t1", "three");
+        record_change(&mut changes, 0, Some(v1.clone()), Some(v2), "alice").unwrap();
+        record_change(&mut changes, 0, Some(text_layer("t1", "two")), Some(v3.clone()), "bob").unwrap();
+
+        assert_eq!(changes.len(), 1);
+        assert_eq!(changes[0].kind, ChangeKind::Update);
+        assert_eq!(changes[0].before, Some(v1));
+        assert_eq!(changes[0].after, Some(v3));
+        assert_eq!(changes[0].author, "bob");
+    }
+
+    #[test]
+    fn test_insert_then_delete_cancels() {
+        let mut changes = Vec::new();
+        let layer = text_layer("t2", "new");
+        record_change(&mut changes, 0, None, Some(layer.clone()), "alice").unwrap();
+        record_change(&mut changes, 0, Some(layer), None, "alice").unwrap();
+        assert!(changes.is_empty());
+    }
+
+    #[test]
+    fn test_reject_all_restores_original() {
+        let original = page(vec![text_layer("t1", "keep"), text_layer("t2", "gone")]);
+        let mut pages = vec![original.clone()];
+        let mut changes = Vec::new();
+
+        // Update t1, delete t2, insert t3
+        let edited = text_layer("t1", "edited");
+        record_change(&mut changes, 0, Some(pages[0].layers[0].clone()), Some(edited.clone()), "a").unwrap();
+        pages[0].layers[0] = edited;
+        let removed = pages[0].layers.remove(1);
+        record_change(&mut changes, 0, Some(removed), None, "a").unwrap();
+        let inserted = text_layer("t3", "added");
+        record_change(&mut changes, 0, None, Some(inserted.clone()), "a").unwrap();
+        pages[0].layers.push(inserted);
+
+        let result = reject_all_changes(pages, changes).unwrap();
+        assert!(result.changes.is_empty());
+        let mut ids: Vec<_> = result.pages[0].layers.iter().map(|l| l.id.as_str()).collect();
+        ids.sort();
+        assert_eq!(ids, vec!["t1", "t2"]);
+        assert_eq!(result.pages[0].layers[0].content.as_deref(), Some("keep"));
+    }
+
+    #[test]
+    fn test_review_markup() {
+        let current = page(vec![text_layer("t1", "edited"), text_layer("t3", "added")]);
+        let mut changes = Vec::new();
+        record_change(&mut changes, 0, Some(text_layer("t1", "orig")), Some(text_layer("t1", "edited")), "a").unwrap();
+        record_change(&mut changes, 0, Some(text_layer("t2", "gone")), None, "a").unwrap();
+        record_change(&mut changes, 0, None, Some(text_layer("t3", "added")), "a").unwrap();
+
+        let marked = apply_review_markup(&[current], &changes);
+        let layers = &marked[0].layers;
+        let inserted = layers.iter().find(|l| l.id == "t3").unwrap();
+        assert_eq!(inserted.text_decoration.as_deref(), Some("underline"));
+        let deleted = layers.iter().find(|l| l.id == "t2").unwrap();
+        assert_eq!(deleted.text_decoration.as_deref(), Some("line-through"));
+        assert_eq!(deleted.color.as_deref(), Some(DELETE_COLOR));
+        assert!(layers.iter().any(|l| l.id.ends_with("-change-bar")));
+    }
+}
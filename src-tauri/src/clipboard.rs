@@ -0,0 +1,239 @@
+//! Clipboard Module
+//!
+//! Copy and paste between Rook and other apps. Copying serializes selected
+//! layers to SVG, plain text and a rendered PNG, which the frontend places
+//! on the system clipboard together; pasting turns SVG, image data or plain
+//! text back into layers. The SVG and text conversions live in
+//! `vortex_core::clipboard`, shared with the wasm build.
+
+use crate::export_handler;
+use crate::image_handler;
+use crate::models::{Bounds, ImageMetadata, LayerObject, LayerType, PageData};
+use crate::scanner;
+use base64::{engine::general_purpose::STANDARD as BASE64, Engine};
+use serde::{Deserialize, Serialize};
+use vortex_core::clipboard;
+use vortex_core::layers::selection_bounds;
+use vortex_core::units::{DEFAULT_PX_DPI, POINTS_PER_INCH};
+
+/// Default PNG resolution, in pixels per point (144 dpi)
+const DEFAULT_PNG_SCALE: f32 = 2.0;
+/// Largest PNG side, in pixels
+const MAX_PNG_SIZE: f32 = 4096.0;
+/// Where pasted content lands when no position is given
+const DEFAULT_PASTE_POSITION: (f32, f32) = (72.0, 72.0);
+
+/// Copied layers in every clipboard format
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ClipboardContent {
+    /// SVG document; carries the layers themselves for pasting back into Rook
+    pub svg: String,
+    /// Text layers in reading order; empty when none were copied
+    pub text: String,
+    /// PNG data URL; `None` when rendering was not possible
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub png: Option<String>,
+}
+
+/// Clipboard contents offered for pasting, richest format first
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PasteContent {
+    #[serde(default)]
+    pub svg: Option<String>,
+    /// Image as a data URL or bare base64
+    #[serde(default)]
+    pub image: Option<String>,
+    #[serde(default)]
+    pub text: Option<String>,
+}
+
+fn data_url(bytes: &[u8]) -> String {
+    format!("data:{};base64,{}", image_handler::mime_type(bytes), BASE64.encode(bytes))
+}
+
+/// Bytes of a data URL or bare base64 string
+fn decode_data_url(url: &str) -> Result<Vec<u8>, String> {
+    let encoded = match url.split_once(',') {
+        Some((header, data)) if header.starts_with("data:") => {
+            if !header.ends_with(";base64") {
+                return Err("Only base64 data URLs are supported".to_string());
+            }
+            data
+        }
+        _ => url,
+    };
+    BASE64.decode(encoded.trim()).map_err(|e| format!("Invalid image data: {}", e))
+}
+
+/// Selected layers on a page sized to them, for rendering
+fn selection_page(page: &PageData, layers: &[LayerObject], bounds: Bounds) -> PageData {
+    let mut layers = layers.to_vec();
+    clipboard::offset_layers(&mut layers, -bounds.x, -bounds.y);
+    PageData {
+        width: bounds.width.max(1.0),
+        height: bounds.height.max(1.0),
+        layers,
+        ..page.clone()
+    }
+}
+
+/// Serialize layers of `page` for the clipboard
+///
+/// `scale` is the PNG resolution in pixels per point (default 2, 144 dpi),
+/// reduced when the PNG would exceed `MAX_PNG_SIZE` pixels on a side.
+#[tauri::command]
+pub async fn copy_layers(page: PageData, layer_ids: Vec<String>, scale: Option<f32>) -> Result<ClipboardContent, String> {
+    tokio::task::spawn_blocking(move || {
+        let layers: Vec<LayerObject> = page.layers.iter().filter(|l| layer_ids.contains(&l.id)).cloned().collect();
+        let bounds = selection_bounds(&layers, &layer_ids).ok_or("No layers to copy")?;
+        let svg = clipboard::layers_to_svg(&layers, |layer| image_handler::layer_image_bytes(layer).map(|b| data_url(&b)))
+            .ok_or("No layers to copy")?;
+
+        let longest = bounds.width.max(bounds.height).max(1.0);
+        let scale = scale.unwrap_or(DEFAULT_PNG_SCALE).clamp(0.1, MAX_PNG_SIZE / longest);
+        let png = export_handler::render_page_image(&selection_page(&page, &layers, bounds), scale).and_then(|image| {
+            let mut png = std::io::Cursor::new(Vec::new());
+            image.write_to(&mut png, image::ImageFormat::Png).map_err(|e| e.to_string())?;
+            Ok(data_url(png.get_ref()))
+        });
+        if let Err(e) = &png {
+            tracing::warn!("clipboard PNG not rendered: {}", e);
+        }
+
+        Ok(ClipboardContent { svg, text: clipboard::layers_to_text(&layers), png: png.ok() })
+    })
+    .await
+    .map_err(|e| format!("Copy task failed: {}", e))?
+}
+
+/// Cache an image layer's pasted data URL and point the layer at the cache
+fn cache_pasted_image(layer: &mut LayerObject) -> Result<(), String> {
+    let Some(url) = layer.image_url.as_deref().filter(|u| u.starts_with("data:")) else {
+        return Ok(());
+    };
+    let bytes = decode_data_url(url)?;
+    let image = image::load_from_memory(&bytes).map_err(|e| format!("Unsupported image: {}", e))?;
+    image_handler::cache_image_with_dimensions(&layer.id, bytes, image.width(), image.height());
+    let dpi = (image.width() as f32 / (layer.bounds.width.max(1.0) / POINTS_PER_INCH)).round() as u32;
+    layer.image_url = Some(format!("image://{}", layer.id));
+    layer.image_data = Some(ImageMetadata {
+        width: image.width(),
+        height: image.height(),
+        color_space: "RGBA".to_string(),
+        dpi: dpi.max(1),
+        icc_profile: None,
+    });
+    Ok(())
+}
+
+/// Layers from pasted content for page `page_index`
+///
+/// SVG is preferred over an image, and an image over text. Pasted layers
+/// are moved so their top-left corner lands at (`x`, `y`); without a
+/// position, SVG keeps its own coordinates (a copy from Rook pastes in
+/// place) and images and text land at one inch from the corner. Images are
+/// sized at screen resolution (96 dpi).
+#[tauri::command]
+pub async fn paste_layers(
+    page_index: usize,
+    content: PasteContent,
+    x: Option<f32>,
+    y: Option<f32>,
+) -> Result<Vec<LayerObject>, String> {
+    tokio::task::spawn_blocking(move || {
+        let stamp = std::time::SystemTime::now()
+            .duration_since(std::time::UNIX_EPOCH)
+            .map(|d| d.as_millis())
+            .unwrap_or_default();
+        let id_prefix = format!("paste-{}-{}", page_index, stamp);
+        let position = x.zip(y);
+
+        let svg = content.svg.as_deref().filter(|s| !s.trim().is_empty());
+        let mut layers = if let Some(svg) = svg {
+            clipboard::svg_to_layers(svg, &id_prefix)?
+        } else if let Some(image) = content.image.as_deref().filter(|s| !s.trim().is_empty()) {
+            let bytes = decode_data_url(image)?;
+            let decoded = image::load_from_memory(&bytes).map_err(|e| format!("Unsupported image: {}", e))?;
+            let id = format!("{}-0", id_prefix);
+            let mut layer = scanner::image_page(id.clone(), page_index, decoded.width(), decoded.height(), DEFAULT_PX_DPI)
+                .layers
+                .pop()
+                .ok_or("Failed to create image layer")?;
+            image_handler::cache_image_with_dimensions(&id, bytes, decoded.width(), decoded.height());
+            layer.source_type = crate::models::SourceType::Imported;
+            vec![layer]
+        } else if let Some(text) = content.text.as_deref() {
+            let (x, y) = position.unwrap_or(DEFAULT_PASTE_POSITION);
+            clipboard::text_to_layer(text, format!("{}-0", id_prefix), x, y).into_iter().collect()
+        } else {
+            Vec::new()
+        };
+        if layers.is_empty() {
+            return Err("Nothing to paste".to_string());
+        }
+
+        for layer in layers.iter_mut().filter(|l| l.layer_type == LayerType::Image) {
+            cache_pasted_image(layer)?;
+        }
+        let ids: Vec<String> = layers.iter().map(|l| l.id.clone()).collect();
+        let target = match (position, svg.is_some()) {
+            (Some(target), _) => Some(target),
+            (None, false) => Some(DEFAULT_PASTE_POSITION),
+            (None, true) => None,
+        };
+        if let (Some((x, y)), Some(bounds)) = (target, selection_bounds(&layers, &ids)) {
+            clipboard::offset_layers(&mut layers, x - bounds.x, y - bounds.y);
+        }
+        Ok(layers)
+    })
+    .await
+    .map_err(|e| format!("Paste task failed: {}", e))?
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use image::{Rgba, RgbaImage};
+
+    fn png_data_url() -> String {
+        let mut png = std::io::Cursor::new(Vec::new());
+        RgbaImage::from_pixel(96, 48, Rgba([10, 20, 30, 255])).write_to(&mut png, image::ImageFormat::Png).unwrap();
+        data_url(png.get_ref())
+    }
+
+    #[tokio::test]
+    async fn test_paste_image_and_text() {
+        let content = PasteContent { image: Some(png_data_url()), text: Some("ignored".to_string()), ..Default::default() };
+        let layers = paste_layers(2, content, Some(100.0), Some(50.0)).await.unwrap();
+        assert_eq!(layers.len(), 1);
+        let layer = &layers[0];
+        assert_eq!(layer.layer_type, LayerType::Image);
+        // 96 px at 96 dpi is one inch
+        assert_eq!(layer.bounds, Bounds::new(100.0, 50.0, 72.0, 36.0));
+        let id = layer.image_url.as_deref().unwrap().strip_prefix("image://").unwrap();
+        assert!(image_handler::get_image_bytes(id).is_some());
+
+        let text = PasteContent { text: Some("Hello\r\nworld".to_string()), ..Default::default() };
+        let layers = paste_layers(0, text, None, None).await.unwrap();
+        assert_eq!(layers[0].content.as_deref(), Some("Hello\nworld"));
+        assert_eq!((layers[0].bounds.x, layers[0].bounds.y), DEFAULT_PASTE_POSITION);
+
+        assert!(paste_layers(0, PasteContent::default(), None, None).await.is_err());
+    }
+
+    #[tokio::test]
+    async fn test_paste_svg_image_is_cached() {
+        let svg = format!(
+            "<svg xmlns=\"http://www.w3.org/2000/svg\"><image x=\"10\" y=\"10\" width=\"48\" height=\"24\" href=\"{}\"/></svg>",
+            png_data_url()
+        );
+        let content = PasteContent { svg: Some(svg), ..Default::default() };
+        let layers = paste_layers(0, content, None, None).await.unwrap();
+        let layer = &layers[0];
+        assert_eq!(layer.bounds, Bounds::new(10.0, 10.0, 48.0, 24.0));
+        assert!(layer.image_url.as_deref().unwrap().starts_with("image://"));
+        assert_eq!(layer.image_data.as_ref().map(|d| d.dpi), Some(144));
+    }
+}
@@ -0,0 +1,1005 @@
+//! Cloud Import Module
+//!
+//! Imports documents straight from Google Drive, OneDrive and Dropbox.
+//! - Google and Microsoft sign in with the OAuth device flow: the user enters
+//!   a short code on the provider's page while the app polls for a token.
+//!   Dropbox has no device flow, so it uses PKCE without a redirect: the user
+//!   approves in the browser and pastes back the code Dropbox shows.
+//! - Tokens and client secrets are kept in the OS keychain; the app data dir
+//!   (`cloud_accounts.json`, owner-only) only lists the signed-in providers.
+//!   Tokens are refreshed when they expire.
+//! - Google Docs are exported as DOCX, other files downloaded as-is, then
+//!   imported through `document_parser::import_document`.
+
+use crate::document_parser::{self, ImportOptions};
+use crate::models::DocumentResponse;
+use base64::{engine::general_purpose::URL_SAFE_NO_PAD, Engine};
+use ring::rand::SecureRandom;
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use std::collections::HashMap;
+use std::fs;
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, RwLock};
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
+use tauri::{AppHandle, Emitter};
+
+const ACCOUNTS_FILE: &str = "cloud_accounts.json";
+/// Keychain service the secrets are filed under, one entry per provider
+const KEYCHAIN_SERVICE: &str = "rook-cloud-import";
+const DOWNLOADS_DIR: &str = "downloads";
+
+const DOCX_MIME: &str = "application/vnd.openxmlformats-officedocument.wordprocessingml.document";
+const GOOGLE_DOC_MIME: &str = "application/vnd.google-apps.document";
+
+/// Refresh tokens this long before they expire
+const EXPIRY_MARGIN_SECS: u64 = 60;
+const DEFAULT_LIST_LIMIT: usize = 25;
+/// Dropbox listing pages read per request, 2000 entries each
+const DROPBOX_MAX_PAGES: usize = 10;
+
+/// Cloud storage service
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq, Hash)]
+#[serde(rename_all = "lowercase")]
+pub enum CloudProvider {
+    #[serde(rename = "google-drive")]
+    GoogleDrive,
+    OneDrive,
+    Dropbox,
+}
+
+impl CloudProvider {
+    fn name(self) -> &'static str {
+        match self {
+            Self::GoogleDrive => "Google Drive",
+            Self::OneDrive => "OneDrive",
+            Self::Dropbox => "Dropbox",
+        }
+    }
+
+    /// Stable identifier, as serialized
+    fn slug(self) -> &'static str {
+        match self {
+            Self::GoogleDrive => "google-drive",
+            Self::OneDrive => "onedrive",
+            Self::Dropbox => "dropbox",
+        }
+    }
+
+    fn token_url(self) -> &'static str {
+        match self {
+            Self::GoogleDrive => "https://oauth2.googleapis.com/token",
+            Self::OneDrive => "https://login.microsoftonline.com/common/oauth2/v2.0/token",
+            Self::Dropbox => "https://api.dropboxapi.com/oauth2/token",
+        }
+    }
+
+    /// Device authorization endpoint; Dropbox has none
+    fn device_code_url(self) -> Option<&'static str> {
+        match self {
+            Self::GoogleDrive => Some("https://oauth2.googleapis.com/device/code"),
+            Self::OneDrive => Some("https://login.microsoftonline.com/common/oauth2/v2.0/devicecode"),
+            Self::Dropbox => None,
+        }
+    }
+
+    /// Read-only access to the user's files
+    fn scope(self) -> &'static str {
+        match self {
+            Self::GoogleDrive => "https://www.googleapis.com/auth/drive.readonly",
+            Self::OneDrive => "Files.Read offline_access",
+            Self::Dropbox => "files.content.read files.metadata.read",
+        }
+    }
+}
+
+/// OAuth app registration for a provider
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct CloudClient {
+    pub client_id: String,
+    /// Required by Google for "TV and limited input" clients
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub client_secret: Option<String>,
+}
+
+/// Sign-in the user has to complete at the provider
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct CloudAuthorization {
+    pub provider: CloudProvider,
+    /// Code to enter at `verification_uri`; none for Dropbox, which shows
+    /// the user a code to paste back instead
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub user_code: Option<String>,
+    pub verification_uri: String,
+    /// Seconds between polls
+    pub interval: u64,
+    /// Seconds until the sign-in expires
+    pub expires_in: u64,
+}
+
+/// Outcome of polling a pending sign-in
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum CloudAuthStatus {
+    Pending,
+    Authorized,
+    Denied,
+    Expired,
+}
+
+/// A document in cloud storage
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct CloudDocument {
+    pub provider: CloudProvider,
+    pub id: String,
+    pub name: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub mime_type: Option<String>,
+    /// Last modification, RFC 3339
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub modified: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub size: Option<u64>,
+    /// Drive holding the item, for OneDrive files shared from another drive
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub drive_id: Option<String>,
+}
+
+impl CloudDocument {
+    /// Import file type ("pdf" or "docx"), or None if it cannot be imported
+    fn file_type(&self) -> Option<&'static str> {
+        file_type(&self.name, self.mime_type.as_deref())
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct CloudAccount {
+    provider: CloudProvider,
+    client: CloudClient,
+    access_token: String,
+    #[serde(default)]
+    refresh_token: Option<String>,
+    /// Unix seconds
+    expires_at: u64,
+}
+
+/// What `cloud_accounts.json` keeps of an account
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct SavedAccount {
+    provider: CloudProvider,
+    client_id: String,
+    expires_at: u64,
+}
+
+/// What the keychain keeps of an account
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct AccountSecrets {
+    access_token: String,
+    #[serde(default)]
+    refresh_token: Option<String>,
+    #[serde(default)]
+    client_secret: Option<String>,
+}
+
+impl CloudAccount {
+    fn split(&self) -> (SavedAccount, AccountSecrets) {
+        (
+            SavedAccount {
+                provider: self.provider,
+                client_id: self.client.client_id.clone(),
+                expires_at: self.expires_at,
+            },
+            AccountSecrets {
+                access_token: self.access_token.clone(),
+                refresh_token: self.refresh_token.clone(),
+                client_secret: self.client.client_secret.clone(),
+            },
+        )
+    }
+
+    fn join(saved: SavedAccount, secrets: AccountSecrets) -> Self {
+        Self {
+            provider: saved.provider,
+            client: CloudClient { client_id: saved.client_id, client_secret: secrets.client_secret },
+            access_token: secrets.access_token,
+            refresh_token: secrets.refresh_token,
+            expires_at: saved.expires_at,
+        }
+    }
+}
+
+fn keychain_entry(provider: CloudProvider) -> Result<keyring::Entry, String> {
+    keyring::Entry::new(KEYCHAIN_SERVICE, provider.slug()).map_err(|e| e.to_string())
+}
+
+fn store_secrets(provider: CloudProvider, secrets: &AccountSecrets) -> Result<(), String> {
+    let json = serde_json::to_string(secrets).map_err(|e| e.to_string())?;
+    keychain_entry(provider)?
+        .set_password(&json)
+        .map_err(|e| format!("Failed to save {} sign-in to the keychain: {}", provider.name(), e))
+}
+
+fn load_secrets(provider: CloudProvider) -> Option<AccountSecrets> {
+    let json = keychain_entry(provider).ok()?.get_password().ok()?;
+    serde_json::from_str(&json).ok()
+}
+
+fn delete_secrets(provider: CloudProvider) {
+    if let Ok(entry) = keychain_entry(provider) {
+        let _ = entry.delete_credential();
+    }
+}
+
+/// Sign-in started by `cloud_begin_auth`
+#[derive(Debug, Clone)]
+struct PendingAuth {
+    client: CloudClient,
+    /// Device code, or the PKCE verifier for Dropbox
+    secret: String,
+    expires_at: u64,
+}
+
+#[derive(Debug, Default)]
+struct CloudStore {
+    accounts: Vec<CloudAccount>,
+    pending: HashMap<CloudProvider, PendingAuth>,
+    dir: Option<PathBuf>,
+}
+
+lazy_static::lazy_static! {
+    static ref CLOUD_STORE: Arc<RwLock<CloudStore>> = Arc::new(RwLock::new(CloudStore::default()));
+}
+
+/// Load saved accounts from `dir` and keep downloads there
+///
+/// Accounts whose secrets are missing from the keychain are dropped and need
+/// signing in again. Files from before the keychain, which held the tokens
+/// themselves, are moved over and rewritten without them.
+pub fn init_cloud_import(dir: PathBuf) -> Result<(), String> {
+    fs::create_dir_all(&dir).map_err(|e| e.to_string())?;
+    let data = fs::read(dir.join(ACCOUNTS_FILE)).unwrap_or_default();
+    let accounts = match serde_json::from_slice::<Vec<CloudAccount>>(&data) {
+        Ok(legacy) => legacy
+            .into_iter()
+            .filter(|account| store_secrets(account.provider, &account.split().1).is_ok())
+            .collect(),
+        Err(_) => serde_json::from_slice::<Vec<SavedAccount>>(&data)
+            .unwrap_or_default()
+            .into_iter()
+            .filter_map(|saved| load_secrets(saved.provider).map(|secrets| CloudAccount::join(saved, secrets)))
+            .collect(),
+    };
+
+    let mut store = CLOUD_STORE.write().map_err(|e| e.to_string())?;
+    store.accounts = accounts;
+    store.dir = Some(dir);
+    persist(&store)
+}
+
+/// Write a file only its owner can read
+fn write_private(path: &Path, data: &[u8]) -> std::io::Result<()> {
+    // The mode only applies when the file is created
+    let _ = fs::remove_file(path);
+    let mut options = fs::OpenOptions::new();
+    options.write(true).create_new(true);
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::OpenOptionsExt;
+        options.mode(0o600);
+    }
+    options.open(path)?.write_all(data)
+}
+
+fn persist(store: &CloudStore) -> Result<(), String> {
+    let Some(dir) = &store.dir else {
+        return Ok(());
+    };
+    let path = dir.join(ACCOUNTS_FILE);
+    let saved: Vec<SavedAccount> = store.accounts.iter().map(|a| a.split().0).collect();
+    let data = serde_json::to_vec_pretty(&saved).map_err(|e| e.to_string())?;
+    let tmp = path.with_extension("json.tmp");
+    write_private(&tmp, &data).map_err(|e| e.to_string())?;
+    fs::rename(&tmp, path).map_err(|e| e.to_string())
+}
+
+fn save_account(account: CloudAccount) -> Result<(), String> {
+    store_secrets(account.provider, &account.split().1)?;
+    let mut store = CLOUD_STORE.write().map_err(|e| e.to_string())?;
+    store.accounts.retain(|a| a.provider != account.provider);
+    store.accounts.push(account);
+    persist(&store)
+}
+
+fn now_secs() -> u64 {
+    SystemTime::now().duration_since(UNIX_EPOCH).map(|d| d.as_secs()).unwrap_or(0)
+}
+
+fn http_client() -> Result<reqwest::Client, String> {
+    reqwest::Client::builder()
+        .timeout(Duration::from_secs(120))
+        .build()
+        .map_err(|e| e.to_string())
+}
+
+/// Import file type from a file name and MIME type
+fn file_type(name: &str, mime_type: Option<&str>) -> Option<&'static str> {
+    match mime_type {
+        Some("application/pdf") => return Some("pdf"),
+        Some(DOCX_MIME | GOOGLE_DOC_MIME) => return Some("docx"),
+        _ => {}
+    }
+    let lower = name.to_lowercase();
+    if lower.ends_with(".pdf") {
+        Some("pdf")
+    } else if lower.ends_with(".docx") {
+        Some("docx")
+    } else {
+        None
+    }
+}
+
+fn hex_digest(data: &[u8]) -> String {
+    Sha256::digest(data).iter().map(|b| format!("{:02x}", b)).collect()
+}
+
+/// PKCE (RFC 7636) S256 challenge for a verifier
+fn pkce_challenge(verifier: &str) -> String {
+    URL_SAFE_NO_PAD.encode(Sha256::digest(verifier.as_bytes()))
+}
+
+fn pkce_verifier() -> Result<String, String> {
+    let mut bytes = [0u8; 32];
+    ring::rand::SystemRandom::new()
+        .fill(&mut bytes)
+        .map_err(|_| "Failed to generate sign-in verifier".to_string())?;
+    Ok(URL_SAFE_NO_PAD.encode(bytes))
+}
+
+#[derive(Debug, Deserialize)]
+struct DeviceCodeResponse {
+    device_code: String,
+    user_code: String,
+    /// Google calls it `verification_url`
+    #[serde(alias = "verification_url")]
+    verification_uri: String,
+    expires_in: u64,
+    #[serde(default = "default_interval")]
+    interval: u64,
+}
+
+const fn default_interval() -> u64 {
+    5
+}
+
+#[derive(Debug, Default, Deserialize)]
+struct TokenResponse {
+    access_token: Option<String>,
+    refresh_token: Option<String>,
+    expires_in: Option<u64>,
+    error: Option<String>,
+    error_description: Option<String>,
+}
+
+/// Start signing in to a provider
+#[tauri::command]
+pub async fn cloud_begin_auth(provider: CloudProvider, client: CloudClient) -> Result<CloudAuthorization, String> {
+    let (authorization, secret) = match provider.device_code_url() {
+        Some(url) => {
+            let response = http_client()?
+                .post(url)
+                .form(&[("client_id", client.client_id.as_str()), ("scope", provider.scope())])
+                .send()
+                .await
+                .map_err(|e| format!("{} sign-in failed: {}", provider.name(), e))?
+                .error_for_status()
+                .map_err(|e| format!("{} sign-in failed: {}", provider.name(), e))?;
+            let device: DeviceCodeResponse = response.json().await.map_err(|e| e.to_string())?;
+            let authorization = CloudAuthorization {
+                provider,
+                user_code: Some(device.user_code),
+                verification_uri: device.verification_uri,
+                interval: device.interval,
+                expires_in: device.expires_in,
+            };
+            (authorization, device.device_code)
+        }
+        None => {
+            let verifier = pkce_verifier()?;
+            let challenge = pkce_challenge(&verifier);
+            let url = reqwest::Url::parse_with_params(
+                "https://www.dropbox.com/oauth2/authorize",
+                &[
+                    ("client_id", client.client_id.as_str()),
+                    ("response_type", "code"),
+                    ("token_access_type", "offline"),
+                    ("code_challenge_method", "S256"),
+                    ("code_challenge", challenge.as_str()),
+                    ("scope", provider.scope()),
+                ],
+            )
+            .map_err(|e| e.to_string())?;
+            let authorization = CloudAuthorization {
+                provider,
+                user_code: None,
+                verification_uri: url.to_string(),
+                interval: default_interval(),
+                expires_in: 600,
+            };
+            (authorization, verifier)
+        }
+    };
+
+    let pending = PendingAuth { client, secret, expires_at: now_secs() + authorization.expires_in };
+    CLOUD_STORE.write().map_err(|e| e.to_string())?.pending.insert(provider, pending);
+    Ok(authorization)
+}
+
+/// Check a pending sign-in; for Dropbox, pass the code the user pasted
+#[tauri::command]
+pub async fn cloud_poll_auth(provider: CloudProvider, code: Option<String>) -> Result<CloudAuthStatus, String> {
+    let pending = CLOUD_STORE
+        .read()
+        .map_err(|e| e.to_string())?
+        .pending
+        .get(&provider)
+        .cloned()
+        .ok_or_else(|| format!("No {} sign-in in progress", provider.name()))?;
+    if now_secs() >= pending.expires_at {
+        CLOUD_STORE.write().map_err(|e| e.to_string())?.pending.remove(&provider);
+        return Ok(CloudAuthStatus::Expired);
+    }
+
+    let client_id = pending.client.client_id.as_str();
+    let mut form = vec![("client_id", client_id)];
+    if let Some(secret) = &pending.client.client_secret {
+        form.push(("client_secret", secret.as_str()));
+    }
+    if provider == CloudProvider::Dropbox {
+        let Some(code) = code.as_deref().map(str::trim).filter(|c| !c.is_empty()) else {
+            return Ok(CloudAuthStatus::Pending);
+        };
+        form.extend([("grant_type", "authorization_code"), ("code", code), ("code_verifier", pending.secret.as_str())]);
+    } else {
+        form.extend([("grant_type", "urn:ietf:params:oauth:grant-type:device_code"), ("device_code", pending.secret.as_str())]);
+    }
+
+    // Pending sign-ins come back as 400 errors, so the body is read either way
+    let token: TokenResponse = http_client()?
+        .post(provider.token_url())
+        .form(&form)
+        .send()
+        .await
+        .map_err(|e| format!("{} sign-in failed: {}", provider.name(), e))?
+        .json()
+        .await
+        .map_err(|e| e.to_string())?;
+
+    let status = match (token.access_token, token.error.as_deref()) {
+        (Some(access_token), _) => {
+            save_account(CloudAccount {
+                provider,
+                client: pending.client,
+                access_token,
+                refresh_token: token.refresh_token,
+                expires_at: now_secs() + token.expires_in.unwrap_or(3600),
+            })?;
+            CloudAuthStatus::Authorized
+        }
+        (None, Some("authorization_pending" | "slow_down")) => return Ok(CloudAuthStatus::Pending),
+        (None, Some("access_denied" | "authorization_declined")) => CloudAuthStatus::Denied,
+        (None, Some("expired_token" | "code_expired")) => CloudAuthStatus::Expired,
+        (None, error) => {
+            return Err(format!(
+                "{} sign-in failed: {}",
+                provider.name(),
+                token.error_description.as_deref().or(error).unwrap_or("no token returned")
+            ))
+        }
+    };
+    tracing::info!(provider = provider.name(), ?status, "cloud sign-in finished");
+    CLOUD_STORE.write().map_err(|e| e.to_string())?.pending.remove(&provider);
+    Ok(status)
+}
+
+/// Providers the user is signed in to
+#[tauri::command]
+pub fn list_cloud_accounts() -> Result<Vec<CloudProvider>, String> {
+    let store = CLOUD_STORE.read().map_err(|e| e.to_string())?;
+    Ok(store.accounts.iter().map(|a| a.provider).collect())
+}
+
+/// Forget a provider's saved sign-in
+#[tauri::command]
+pub fn cloud_sign_out(provider: CloudProvider) -> Result<(), String> {
+    let mut store = CLOUD_STORE.write().map_err(|e| e.to_string())?;
+    store.accounts.retain(|a| a.provider != provider);
+    store.pending.remove(&provider);
+    delete_secrets(provider);
+    persist(&store)
+}
+
+/// A valid access token for a provider, refreshing it if it has expired
+async fn access_token(provider: CloudProvider) -> Result<String, String> {
+    let account = CLOUD_STORE
+        .read()
+        .map_err(|e| e.to_string())?
+        .accounts
+        .iter()
+        .find(|a| a.provider == provider)
+        .cloned()
+        .ok_or_else(|| format!("Not signed in to {}", provider.name()))?;
+    if account.expires_at > now_secs() + EXPIRY_MARGIN_SECS {
+        return Ok(account.access_token);
+    }
+    let refresh_token = account
+        .refresh_token
+        .clone()
+        .ok_or_else(|| format!("{} sign-in expired; sign in again", provider.name()))?;
+
+    let mut form = vec![
+        ("grant_type", "refresh_token"),
+        ("refresh_token", refresh_token.as_str()),
+        ("client_id", account.client.client_id.as_str()),
+    ];
+    if let Some(secret) = &account.client.client_secret {
+        form.push(("client_secret", secret.as_str()));
+    }
+    let token: TokenResponse = http_client()?
+        .post(provider.token_url())
+        .form(&form)
+        .send()
+        .await
+        .map_err(|e| format!("{} sign-in refresh failed: {}", provider.name(), e))?
+        .json()
+        .await
+        .map_err(|e| e.to_string())?;
+    let access_token = token
+        .access_token
+        .ok_or_else(|| format!("{} sign-in expired; sign in again", provider.name()))?;
+
+    save_account(CloudAccount {
+        access_token: access_token.clone(),
+        // Providers only sometimes rotate the refresh token
+        refresh_token: token.refresh_token.or(Some(refresh_token)),
+        expires_at: now_secs() + token.expires_in.unwrap_or(3600),
+        ..account
+    })?;
+    Ok(access_token)
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct GoogleFileList {
+    #[serde(default)]
+    files: Vec<GoogleFile>,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct GoogleFile {
+    id: String,
+    name: String,
+    mime_type: Option<String>,
+    modified_time: Option<String>,
+    /// Drive returns sizes as strings; native Docs have none
+    size: Option<String>,
+}
+
+fn google_documents(list: GoogleFileList) -> Vec<CloudDocument> {
+    list.files
+        .into_iter()
+        .map(|f| CloudDocument {
+            provider: CloudProvider::GoogleDrive,
+            id: f.id,
+            name: f.name,
+            mime_type: f.mime_type,
+            modified: f.modified_time,
+            size: f.size.and_then(|s| s.parse().ok()),
+            drive_id: None,
+        })
+        .filter(|d| d.file_type().is_some())
+        .collect()
+}
+
+#[derive(Debug, Deserialize)]
+struct GraphItemList {
+    #[serde(default)]
+    value: Vec<GraphItem>,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct GraphItem {
+    id: String,
+    name: String,
+    size: Option<u64>,
+    last_modified_date_time: Option<String>,
+    file: Option<GraphFile>,
+    /// Set for items shared from another drive
+    remote_item: Option<GraphRemoteItem>,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct GraphFile {
+    mime_type: Option<String>,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct GraphRemoteItem {
+    id: Option<String>,
+    file: Option<GraphFile>,
+    parent_reference: Option<GraphParentReference>,
+}
+
+#[derive(Debug, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct GraphParentReference {
+    drive_id: Option<String>,
+}
+
+fn onedrive_documents(list: GraphItemList) -> Vec<CloudDocument> {
+    list.value
+        .into_iter()
+        .filter_map(|item| {
+            let remote = item.remote_item;
+            let file = item.file.or_else(|| remote.as_ref().and_then(|r| r.file.clone()))?;
+            let (id, drive_id) = match remote {
+                Some(r) => (
+                    r.id.unwrap_or(item.id),
+                    r.parent_reference.and_then(|p| p.drive_id),
+                ),
+                None => (item.id, None),
+            };
+            Some(CloudDocument {
+                provider: CloudProvider::OneDrive,
+                id,
+                name: item.name,
+                mime_type: file.mime_type,
+                modified: item.last_modified_date_time,
+                size: item.size,
+                drive_id,
+            })
+        })
+        .filter(|d| d.file_type().is_some())
+        .collect()
+}
+
+#[derive(Debug, Deserialize)]
+struct DropboxFolderList {
+    #[serde(default)]
+    entries: Vec<DropboxEntry>,
+    cursor: Option<String>,
+    #[serde(default)]
+    has_more: bool,
+}
+
+impl DropboxFolderList {
+    /// Add the next page of the listing
+    fn append(&mut self, next: DropboxFolderList) {
+        self.entries.extend(next.entries);
+        self.cursor = next.cursor;
+        self.has_more = next.has_more;
+    }
+}
+
+#[derive(Debug, Deserialize)]
+struct DropboxEntry {
+    #[serde(rename = ".tag")]
+    tag: String,
+    id: String,
+    name: String,
+    server_modified: Option<String>,
+    size: Option<u64>,
+}
+
+/// One page of a Dropbox folder listing from `files/{endpoint}`
+async fn dropbox_list(
+    client: &reqwest::Client,
+    token: &str,
+    endpoint: &str,
+    body: serde_json::Value,
+) -> Result<DropboxFolderList, reqwest::Error> {
+    client
+        .post(format!("https://api.dropboxapi.com/2/files/{}", endpoint))
+        .bearer_auth(token)
+        .json(&body)
+        .send()
+        .await?
+        .error_for_status()?
+        .json()
+        .await
+}
+
+/// Dropbox has no "recent" listing, so files are sorted by modification
+fn dropbox_documents(list: DropboxFolderList, limit: usize) -> Vec<CloudDocument> {
+    let mut documents: Vec<CloudDocument> = list
+        .entries
+        .into_iter()
+        .filter(|e| e.tag == "file")
+        .map(|e| CloudDocument {
+            provider: CloudProvider::Dropbox,
+            id: e.id,
+            name: e.name,
+            mime_type: None,
+            modified: e.server_modified,
+            size: e.size,
+            drive_id: None,
+        })
+        .filter(|d| d.file_type().is_some())
+        .collect();
+    // RFC 3339 UTC timestamps sort chronologically as strings
+    documents.sort_by(|a, b| b.modified.cmp(&a.modified));
+    documents.truncate(limit);
+    documents
+}
+
+/// Recently modified PDF and Word documents in a provider, newest first
+#[tauri::command]
+pub async fn list_cloud_documents(provider: CloudProvider, limit: Option<usize>) -> Result<Vec<CloudDocument>, String> {
+    let limit = limit.unwrap_or(DEFAULT_LIST_LIMIT).clamp(1, 200);
+    let token = access_token(provider).await?;
+    let client = http_client()?;
+    let failed = |e: reqwest::Error| format!("Failed to list {} documents: {}", provider.name(), e);
+    let page_size = limit.to_string();
+
+    let documents = match provider {
+        CloudProvider::GoogleDrive => {
+            let query = format!(
+                "trashed = false and (mimeType = 'application/pdf' or mimeType = '{}' or mimeType = '{}')",
+                DOCX_MIME, GOOGLE_DOC_MIME
+            );
+            let list: GoogleFileList = client
+                .get("https://www.googleapis.com/drive/v3/files")
+                .bearer_auth(&token)
+                .query(&[
+                    ("q", query.as_str()),
+                    ("orderBy", "modifiedTime desc"),
+                    ("pageSize", page_size.as_str()),
+                    ("fields", "files(id,name,mimeType,modifiedTime,size)"),
+                ])
+                .send()
+                .await
+                .and_then(reqwest::Response::error_for_status)
+                .map_err(failed)?
+                .json()
+                .await
+                .map_err(failed)?;
+            google_documents(list)
+        }
+        CloudProvider::OneDrive => {
+            // The recent list holds folders and other files too, so ask for extra
+            let list: GraphItemList = client
+                .get("https://graph.microsoft.com/v1.0/me/drive/recent")
+                .bearer_auth(&token)
+                .query(&[("$top", (limit * 4).to_string())])
+                .send()
+                .await
+                .and_then(reqwest::Response::error_for_status)
+                .map_err(failed)?
+                .json()
+                .await
+                .map_err(failed)?;
+            let mut documents = onedrive_documents(list);
+            documents.truncate(limit);
+            documents
+        }
+        CloudProvider::Dropbox => {
+            // Entries come in no particular order, so the newest may be on any page
+            let body = serde_json::json!({ "path": "", "recursive": true, "limit": 2000 });
+            let mut list = dropbox_list(&client, &token, "list_folder", body).await.map_err(failed)?;
+            for _ in 1..DROPBOX_MAX_PAGES {
+                if !list.has_more {
+                    break;
+                }
+                let Some(cursor) = list.cursor.take() else { break };
+                let body = serde_json::json!({ "cursor": cursor });
+                list.append(dropbox_list(&client, &token, "list_folder/continue", body).await.map_err(failed)?);
+            }
+            dropbox_documents(list, limit)
+        }
+    };
+    Ok(documents)
+}
+
+/// Download a document's content; Google Docs are exported as DOCX
+async fn download(document: &CloudDocument, token: &str) -> Result<Vec<u8>, String> {
+    let client = http_client()?;
+    let request = match document.provider {
+        CloudProvider::GoogleDrive if document.mime_type.as_deref() == Some(GOOGLE_DOC_MIME) => client
+            .get(format!("https://www.googleapis.com/drive/v3/files/{}/export", document.id))
+            .query(&[("mimeType", DOCX_MIME)]),
+        CloudProvider::GoogleDrive => client
+            .get(format!("https://www.googleapis.com/drive/v3/files/{}", document.id))
+            .query(&[("alt", "media")]),
+        CloudProvider::OneDrive => match &document.drive_id {
+            Some(drive) => client.get(format!(
+                "https://graph.microsoft.com/v1.0/drives/{}/items/{}/content",
+                drive, document.id
+            )),
+            None => client.get(format!("https://graph.microsoft.com/v1.0/me/drive/items/{}/content", document.id)),
+        },
+        CloudProvider::Dropbox => client
+            .post("https://content.dropboxapi.com/2/files/download")
+            .header("Dropbox-API-Arg", serde_json::json!({ "path": document.id }).to_string()),
+    };
+    let failed = |e: reqwest::Error| format!("Failed to download {}: {}", document.name, e);
+    let bytes = request
+        .bearer_auth(token)
+        .send()
+        .await
+        .and_then(reqwest::Response::error_for_status)
+        .map_err(failed)?
+        .bytes()
+        .await
+        .map_err(failed)?;
+    Ok(bytes.to_vec())
+}
+
+/// Local file name for a download, with the extension the importer expects
+fn download_name(document: &CloudDocument, file_type: &str) -> String {
+    let stem: String = document
+        .name
+        .trim_end_matches(".pdf")
+        .trim_end_matches(".docx")
+        .chars()
+        .map(|c| if c.is_alphanumeric() || matches!(c, ' ' | '-' | '_' | '.') { c } else { '_' })
+        .collect();
+    let stem = if stem.trim().is_empty() { "document" } else { stem.trim() };
+    format!("{}.{}", stem, file_type)
+}
+
+/// Download a cloud document and import it
+///
+/// The file is kept in the app data dir: lazily loaded images and the
+/// source watcher read it after the import.
+#[tauri::command]
+pub async fn import_cloud_document(
+    document: CloudDocument,
+    options: Option<ImportOptions>,
+    app_handle: AppHandle,
+) -> Result<DocumentResponse, String> {
+    let file_type = document
+        .file_type()
+        .ok_or_else(|| format!("{} is not a PDF or Word document", document.name))?;
+    let dir = CLOUD_STORE
+        .read()
+        .map_err(|e| e.to_string())?
+        .dir
+        .clone()
+        .unwrap_or_else(|| std::env::temp_dir().join("rook-cloud"))
+        .join(DOWNLOADS_DIR)
+        .join(format!("{}-{}", document.provider.slug(), &hex_digest(document.id.as_bytes())[..16]));
+    fs::create_dir_all(&dir).map_err(|e| format!("Failed to create download directory: {}", e))?;
+
+    let _ = app_handle.emit(
+        "parse_progress",
+        serde_json::json!({
+            "currentPage": 0,
+            "totalPages": 0,
+            "status": format!("Downloading from {}...", document.provider.name())
+        }),
+    );
+    let token = access_token(document.provider).await?;
+    let data = download(&document, &token).await?;
+    let path = dir.join(download_name(&document, file_type));
+    fs::write(&path, data).map_err(|e| format!("Failed to save {}: {}", document.name, e))?;
+    tracing::info!(provider = document.provider.name(), path = %path.display(), "downloaded cloud document");
+
+    document_parser::import_document(path.to_string_lossy().into_owned(), file_type.to_string(), options, app_handle)
+        .await
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_pkce_challenge() {
+        // RFC 7636 appendix B
+        assert_eq!(
+            pkce_challenge("dBjftJeZ4CVP-mB92K27uhbUJU1p1r_wW1gFWFOEjXk"),
+            "E9Melhoa2OwvFrEMTJguCHaoeK1t8URWbuGJSstw-cM"
+        );
+    }
+
+    #[test]
+    fn test_file_type() {
+        assert_eq!(file_type("Manuscript", Some(GOOGLE_DOC_MIME)), Some("docx"));
+        assert_eq!(file_type("scan.PDF", None), Some("pdf"));
+        assert_eq!(file_type("notes.txt", Some("text/plain")), None);
+    }
+
+    #[test]
+    fn test_provider_listings() {
+        let google: GoogleFileList = serde_json::from_value(serde_json::json!({ "files": [
+            { "id": "1", "name": "Draft", "mimeType": GOOGLE_DOC_MIME, "modifiedTime": "2026-01-02T00:00:00Z" },
+            { "id": "2", "name": "cover.png", "mimeType": "image/png", "size": "100" },
+            { "id": "3", "name": "Book.pdf", "mimeType": "application/pdf", "size": "2048" }
+        ]}))
+        .unwrap();
+        let docs = google_documents(google);
+        assert_eq!(docs.iter().map(|d| d.id.as_str()).collect::<Vec<_>>(), ["1", "3"]);
+        assert_eq!(docs[1].size, Some(2048));
+
+        let onedrive: GraphItemList = serde_json::from_value(serde_json::json!({ "value": [
+            { "id": "a", "name": "Chapter.docx", "file": { "mimeType": DOCX_MIME } },
+            { "id": "b", "name": "Photos" },
+            { "id": "c", "name": "Shared.pdf", "remoteItem": {
+                "id": "remote-c", "file": { "mimeType": "application/pdf" },
+                "parentReference": { "driveId": "drive-9" } } }
+        ]}))
+        .unwrap();
+        let docs = onedrive_documents(onedrive);
+        assert_eq!(docs.len(), 2);
+        assert_eq!((docs[1].id.as_str(), docs[1].drive_id.as_deref()), ("remote-c", Some("drive-9")));
+
+        let mut dropbox: DropboxFolderList = serde_json::from_value(serde_json::json!({ "entries": [
+            { ".tag": "file", "id": "id:old", "name": "old.pdf", "server_modified": "2025-01-01T00:00:00Z" },
+            { ".tag": "folder", "id": "id:dir", "name": "Books" }
+        ], "cursor": "page-2", "has_more": true }))
+        .unwrap();
+        assert!(dropbox.has_more);
+        dropbox.append(
+            serde_json::from_value(serde_json::json!({ "entries": [
+                { ".tag": "file", "id": "id:new", "name": "new.docx", "server_modified": "2026-03-01T00:00:00Z" },
+                { ".tag": "file", "id": "id:img", "name": "art.jpg", "server_modified": "2026-04-01T00:00:00Z" }
+            ], "cursor": "end", "has_more": false }))
+            .unwrap(),
+        );
+        assert!(!dropbox.has_more);
+        let docs = dropbox_documents(dropbox, 10);
+        assert_eq!(docs.iter().map(|d| d.id.as_str()).collect::<Vec<_>>(), ["id:new", "id:old"]);
+    }
+
+    #[test]
+    fn test_saved_accounts_hold_no_secrets() {
+        let account = CloudAccount {
+            provider: CloudProvider::GoogleDrive,
+            client: CloudClient { client_id: "client".into(), client_secret: Some("client-secret".into()) },
+            access_token: "access".into(),
+            refresh_token: Some("refresh".into()),
+            expires_at: 42,
+        };
+        let (saved, secrets) = account.split();
+        let json = serde_json::to_string(&saved).unwrap();
+        assert!(!json.contains("secret") && !json.contains("access") && !json.contains("refresh"));
+        // The new file format doesn't parse as the legacy one
+        assert!(serde_json::from_str::<Vec<CloudAccount>>(&format!("[{}]", json)).is_err());
+
+        let joined = CloudAccount::join(saved, secrets);
+        assert_eq!(joined.client.client_secret.as_deref(), Some("client-secret"));
+        assert_eq!((joined.access_token.as_str(), joined.expires_at), ("access", 42));
+
+        #[cfg(unix)]
+        {
+            use std::os::unix::fs::PermissionsExt;
+            let path = std::env::temp_dir().join(format!("rook-cloud-{}.json", std::process::id()));
+            write_private(&path, b"[]").unwrap();
+            assert_eq!(fs::metadata(&path).unwrap().permissions().mode() & 0o777, 0o600);
+            let _ = fs::remove_file(&path);
+        }
+    }
+
+    #[test]
+    fn test_download_name() {
+        let doc = CloudDocument {
+            provider: CloudProvider::GoogleDrive,
+            id: "1".into(),
+            name: "My Book: Draft/2".into(),
+            mime_type: Some(GOOGLE_DOC_MIME.into()),
+            modified: None,
+            size: None,
+            drive_id: None,
+        };
+        assert_eq!(download_name(&doc, "docx"), "My Book_ Draft_2.docx");
+    }
+}
@@ -0,0 +1,304 @@
+//! Color Conversion Module
+//!
+//! Convert a whole document's colors for a different print run, typically a
+//! grayscale interior edition of a color book. Layer colors (text, fill,
+//! stroke, backgrounds, outlines and shadows) and page background colors are
+//! converted in place; images optionally too, cached under a new id so undo
+//! brings the color originals back.
+//!
+//! Colors stay stored as RGB. Grayscale maps each color to its luma; CMYK
+//! runs it through the same device conversion as CMYK export and caps the
+//! total ink, so only colors too heavy for the press change.
+
+use crate::export_handler::{parse_hex_color, rgb_to_cmyk};
+use crate::image_handler;
+use crate::models::{LayerObject, LayerType, PageData};
+use image::{DynamicImage, RgbaImage};
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeSet, HashMap};
+
+/// Total ink (all four channels) allowed by default, in percent; uncoated book stock
+const DEFAULT_MAX_INK: f32 = 260.0;
+
+/// Color space a document is converted to
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum ColorTarget {
+    Grayscale,
+    Cmyk,
+}
+
+impl ColorTarget {
+    /// Suffix of the cache ids converted images are stored under
+    fn suffix(self) -> &'static str {
+        match self {
+            ColorTarget::Grayscale => "-gray",
+            ColorTarget::Cmyk => "-cmyk",
+        }
+    }
+}
+
+/// Settings for `convert_document_colors`
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ColorConversionOptions {
+    pub target: ColorTarget,
+    /// Convert image pixels as well as layer colors
+    #[serde(default)]
+    pub include_images: bool,
+    /// Total ink limit for CMYK, 100-400 percent (default 260)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub max_ink: Option<f32>,
+    /// Only count what would change; pages come back as given
+    #[serde(default)]
+    pub preview: bool,
+}
+
+/// Result of `convert_document_colors`
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ColorConversion {
+    pub pages: Vec<PageData>,
+    /// Layers with a color or image changed
+    pub layers_changed: usize,
+    /// Distinct images converted
+    pub images_changed: usize,
+    /// Distinct colors converted
+    pub colors_changed: usize,
+}
+
+/// Converts single colors and tallies the distinct ones that changed
+struct Converter {
+    target: ColorTarget,
+    /// Ink limit as a fraction, 1-4
+    max_ink: f32,
+    changed: BTreeSet<(u8, u8, u8)>,
+}
+
+impl Converter {
+    fn new(target: ColorTarget, max_ink: Option<f32>) -> Self {
+        let max_ink = max_ink.unwrap_or(DEFAULT_MAX_INK).clamp(100.0, 400.0) / 100.0;
+        Converter { target, max_ink, changed: BTreeSet::new() }
+    }
+
+    /// Converted RGB of a pixel or color
+    fn rgb(&self, r: u8, g: u8, b: u8) -> (u8, u8, u8) {
+        match self.target {
+            ColorTarget::Grayscale => {
+                let luma = (0.2126 * r as f32 + 0.7152 * g as f32 + 0.0722 * b as f32).round() as u8;
+                (luma, luma, luma)
+            }
+            ColorTarget::Cmyk => {
+                let (c, m, y, k) = rgb_to_cmyk(r as f32 / 255.0, g as f32 / 255.0, b as f32 / 255.0);
+                let cmy = c + m + y;
+                if cmy + k <= self.max_ink {
+                    return (r, g, b);
+                }
+                // Take the excess out of the colored inks; black alone never exceeds the limit
+                let scale = (self.max_ink - k) / cmy;
+                let channel = |ink: f32| ((1.0 - ink * scale) * (1.0 - k) * 255.0).round() as u8;
+                (channel(c), channel(m), channel(y))
+            }
+        }
+    }
+
+    /// Converted "#RRGGBB" of a color string; `None` when it stays as it is
+    /// or is not a hex color
+    fn color(&mut self, color: &str) -> Option<String> {
+        let (r, g, b) = parse_hex_color(color)?;
+        let converted = self.rgb(r, g, b);
+        if converted == (r, g, b) {
+            return None;
+        }
+        self.changed.insert((r, g, b));
+        Some(format!("#{:02X}{:02X}{:02X}", converted.0, converted.1, converted.2))
+    }
+
+    /// Convert a color field in place; true when it changed
+    fn field(&mut self, color: &mut Option<String>) -> bool {
+        match color.as_deref().and_then(|c| self.color(c)) {
+            Some(converted) => {
+                *color = Some(converted);
+                true
+            }
+            None => false,
+        }
+    }
+
+    /// Convert every color of a layer; true when any changed
+    fn layer(&mut self, layer: &mut LayerObject) -> bool {
+        let mut changed = false;
+        for color in [&mut layer.color, &mut layer.background_color, &mut layer.fill_color, &mut layer.stroke_color] {
+            changed |= self.field(color);
+        }
+        for color in [
+            layer.text_outline.as_mut().map(|o| &mut o.color),
+            layer.text_shadow.as_mut().map(|s| &mut s.color),
+        ]
+        .into_iter()
+        .flatten()
+        {
+            if let Some(converted) = self.color(color) {
+                *color = converted;
+                changed = true;
+            }
+        }
+        changed
+    }
+
+    /// Convert a bitmap; `None` when no pixel changes. Grayscale results are
+    /// gray images, so they encode smaller.
+    fn image(&self, image: &RgbaImage) -> Option<DynamicImage> {
+        let mut converted = image.clone();
+        let mut changed = false;
+        for px in converted.pixels_mut() {
+            let (r, g, b) = self.rgb(px[0], px[1], px[2]);
+            changed |= (r, g, b) != (px[0], px[1], px[2]);
+            (px[0], px[1], px[2]) = (r, g, b);
+        }
+        if !changed {
+            return None;
+        }
+        let converted = DynamicImage::ImageRgba8(converted);
+        let opaque = image.pixels().all(|px| px[3] == 255);
+        Some(match self.target {
+            ColorTarget::Grayscale if opaque => DynamicImage::ImageLuma8(converted.to_luma8()),
+            ColorTarget::Grayscale => DynamicImage::ImageLumaA8(converted.to_luma_alpha8()),
+            ColorTarget::Cmyk => converted,
+        })
+    }
+}
+
+/// Convert an image layer's bitmap, once per source image
+///
+/// `converted` maps source images to the id their conversion is cached
+/// under, or `None` when they needed no change. Returns whether the layer
+/// now shows a converted image.
+fn convert_image_layer(
+    converter: &Converter,
+    layer: &mut LayerObject,
+    converted: &mut HashMap<String, Option<String>>,
+    preview: bool,
+) -> Result<bool, String> {
+    let url_id = layer.image_url.as_deref().and_then(|url| url.strip_prefix("image://"));
+    let Some(source) = url_id.or(layer.image_path.as_deref()).map(str::to_string) else {
+        return Ok(false);
+    };
+    if !converted.contains_key(&source) {
+        let bytes = image_handler::layer_image_bytes(layer).ok_or_else(|| format!("Image not found: {}", layer.id))?;
+        let image = image::load_from_memory(&bytes).map_err(|e| format!("Failed to decode image: {}", e))?;
+        let id = match converter.image(&image.to_rgba8()) {
+            Some(_) if preview => Some(String::new()),
+            Some(result) => {
+                let base = url_id.unwrap_or(&layer.id);
+                let id = format!("{}{}", base.trim_end_matches(converter.target.suffix()), converter.target.suffix());
+                let data = image_handler::encode_like(&bytes, &result)?;
+                image_handler::cache_image_with_dimensions(&id, data, result.width(), result.height());
+                Some(id)
+            }
+            None => None,
+        };
+        converted.insert(source.clone(), id);
+    }
+    let Some(id) = converted[&source].as_ref() else {
+        return Ok(false);
+    };
+    if !preview {
+        layer.image_url = Some(format!("image://{}", id));
+        // The bitmap no longer matches the linked file
+        layer.image_link = None;
+        if let (Some(metadata), ColorTarget::Grayscale) = (layer.image_data.as_mut(), converter.target) {
+            metadata.color_space = "Gray".to_string();
+        }
+    }
+    Ok(true)
+}
+
+/// Convert the colors of `pages`, counting layers and images that change
+fn convert_pages(pages: Vec<PageData>, options: &ColorConversionOptions) -> Result<ColorConversion, String> {
+    let mut converter = Converter::new(options.target, options.max_ink);
+    let mut converted_images = HashMap::new();
+    let mut result = ColorConversion { pages, ..Default::default() };
+    let mut pages = result.pages.clone();
+
+    for page in &mut pages {
+        if let Some(background) = page.background.as_mut() {
+            converter.field(&mut background.color);
+        }
+        for layer in &mut page.layers {
+            let mut changed = converter.layer(layer);
+            if options.include_images && layer.layer_type == LayerType::Image {
+                changed |= convert_image_layer(&converter, layer, &mut converted_images, options.preview)?;
+            }
+            result.layers_changed += changed as usize;
+        }
+    }
+
+    result.images_changed = converted_images.values().filter(|id| id.is_some()).count();
+    result.colors_changed = converter.changed.len();
+    if !options.preview {
+        result.pages = pages;
+    }
+    Ok(result)
+}
+
+/// Convert every layer color, and optionally every image, of a document to
+/// grayscale or ink-limited CMYK
+///
+/// With `preview` nothing is converted or cached; the counts tell what would change.
+#[tauri::command]
+pub async fn convert_document_colors(
+    pages: Vec<PageData>,
+    options: ColorConversionOptions,
+) -> Result<ColorConversion, String> {
+    tokio::task::spawn_blocking(move || convert_pages(pages, &options))
+        .await
+        .map_err(|e| format!("Color conversion task failed: {}", e))?
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::{PageBackground, TextShadow};
+    use vortex_core::test_util;
+
+    #[test]
+    fn test_convert_colors() {
+        let mut gray = Converter::new(ColorTarget::Grayscale, None);
+        assert_eq!(gray.color("#FF0000").as_deref(), Some("#363636"));
+        assert_eq!(gray.color("#808080"), None);
+        assert_eq!(gray.color("red"), None);
+
+        let mut cmyk = Converter::new(ColorTarget::Cmyk, Some(260.0));
+        // Pure red is 200% ink, within the limit
+        assert_eq!(cmyk.color("#FF0000"), None);
+        // Dark red is 0/100/100/75, 275% ink; the colored inks give way, black stays
+        let (r, g, b) = parse_hex_color(&cmyk.color("#400000").unwrap()).unwrap();
+        let (c, m, y, k) = rgb_to_cmyk(r as f32 / 255.0, g as f32 / 255.0, b as f32 / 255.0);
+        assert!(c + m + y + k <= 2.61 && (k - 0.749).abs() < 0.01, "{:?}", (c, m, y, k));
+
+        let mut layer = test_util::layer("t", "text")
+            .bounds(0.0, 0.0, 100.0, 20.0)
+            .fields(serde_json::json!({ "content": "Hi", "color": "#0000FF", "fillColor": "#FFFFFF" }))
+            .build();
+        layer.text_shadow = Some(TextShadow { color: "#00FF00".to_string(), offset_x: 1.0, offset_y: 1.0, blur: 0.0 });
+        let mut page = test_util::page(0, vec![layer]);
+        page.background = Some(PageBackground { color: Some("#0000FF".to_string()), image: None });
+        let options =
+            ColorConversionOptions { target: ColorTarget::Grayscale, include_images: false, max_ink: None, preview: true };
+        let preview = convert_pages(vec![page.clone()], &options).unwrap();
+        assert_eq!((preview.layers_changed, preview.colors_changed), (1, 2));
+        assert_eq!(preview.pages[0], page);
+
+        let result = convert_pages(vec![page], &ColorConversionOptions { preview: false, ..options }).unwrap();
+        let layer = &result.pages[0].layers[0];
+        assert_eq!((layer.color.as_deref(), layer.fill_color.as_deref()), (Some("#121212"), Some("#FFFFFF")));
+        assert_eq!(layer.text_shadow.as_ref().unwrap().color, "#B6B6B6");
+        assert_eq!(result.pages[0].background.as_ref().unwrap().color.as_deref(), Some("#121212"));
+
+        let pixels = RgbaImage::from_pixel(2, 2, image::Rgba([0, 0, 255, 255]));
+        let converted = gray.image(&pixels).unwrap();
+        assert_eq!((converted.color(), converted.to_rgba8().get_pixel(0, 0).0), (image::ColorType::L8, [18, 18, 18, 255]));
+        assert!(gray.image(&RgbaImage::from_pixel(1, 1, image::Rgba([7, 7, 7, 255]))).is_none());
+    }
+}
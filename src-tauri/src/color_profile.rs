@@ -0,0 +1,174 @@
+//! Color Profile Module
+//!
+//! ICC profiles embedded on imported images. Pixels keep the values of
+//! their profile's color space so PDF export can re-embed the profile
+//! unchanged; only the on-screen preview is converted to sRGB. Profiles are
+//! stored once by content and referenced from `ImageMetadata::icc_profile`.
+
+use crate::models::PageData;
+use image::RgbaImage;
+use moxcms::{ColorProfile, DataColorSpace, Layout, TransformOptions};
+use std::collections::hash_map::DefaultHasher;
+use std::collections::HashMap;
+use std::hash::{Hash, Hasher};
+use std::sync::{Arc, RwLock};
+
+/// Prefix of profile ids, which share the project archive with image ids
+const ID_PREFIX: &str = "icc-";
+
+lazy_static::lazy_static! {
+    static ref PROFILES: RwLock<HashMap<String, Arc<Vec<u8>>>> = RwLock::new(HashMap::new());
+    // Cached image id -> profile id, for converting previews
+    static ref IMAGE_PROFILES: RwLock<HashMap<String, String>> = RwLock::new(HashMap::new());
+}
+
+/// Store a profile, returning its id; identical profiles share one id
+pub fn store_profile(data: Vec<u8>) -> String {
+    let mut hasher = DefaultHasher::new();
+    data.hash(&mut hasher);
+    let id = format!("{}{:016x}", ID_PREFIX, hasher.finish());
+    if let Ok(mut profiles) = PROFILES.write() {
+        profiles.entry(id.clone()).or_insert_with(|| Arc::new(data));
+    }
+    id
+}
+
+/// Restore a profile saved under `id`
+pub fn insert_profile(id: String, data: Vec<u8>) {
+    if let Ok(mut profiles) = PROFILES.write() {
+        profiles.insert(id, Arc::new(data));
+    }
+}
+
+/// Whether an archived asset id is a profile rather than an image
+#[inline]
+pub fn is_profile_id(id: &str) -> bool {
+    id.starts_with(ID_PREFIX)
+}
+
+/// Profile bytes by id
+pub fn profile(id: &str) -> Option<Arc<Vec<u8>>> {
+    PROFILES.read().ok()?.get(id).cloned()
+}
+
+/// Color components of a gray or RGB profile; `None` for anything else
+pub fn components(data: &[u8]) -> Option<u8> {
+    match ColorProfile::new_from_slice(data).ok()?.color_space {
+        DataColorSpace::Gray => Some(1),
+        DataColorSpace::Rgb => Some(3),
+        _ => None,
+    }
+}
+
+/// Mark a cached image as being in a stored profile's color space
+pub fn set_image_profile(image_id: &str, profile_id: &str) {
+    if let Ok(mut images) = IMAGE_PROFILES.write() {
+        images.insert(image_id.to_string(), profile_id.to_string());
+    }
+}
+
+/// Profiles the image layers of `pages` reference, for saving with a project
+pub fn referenced_profiles(pages: &[PageData]) -> Vec<(String, Arc<Vec<u8>>)> {
+    let mut found: Vec<(String, Arc<Vec<u8>>)> = Vec::new();
+    for id in pages.iter().flat_map(|p| &p.layers).filter_map(|l| l.image_data.as_ref()?.icc_profile.as_ref()) {
+        if !found.iter().any(|(known, _)| known == id) {
+            if let Some(data) = profile(id) {
+                found.push((id.clone(), data));
+            }
+        }
+    }
+    found
+}
+
+/// Register the profiles of loaded image layers for preview conversion
+pub fn tag_images(pages: &[PageData]) {
+    for layer in pages.iter().flat_map(|p| &p.layers) {
+        let image_id = layer.image_url.as_deref().and_then(|url| url.strip_prefix("image://"));
+        if let (Some(image_id), Some(profile_id)) = (image_id, layer.image_data.as_ref().and_then(|m| m.icc_profile.as_deref())) {
+            set_image_profile(image_id, profile_id);
+        }
+    }
+}
+
+/// Forget all profiles (on document close, with the image cache)
+pub fn clear() {
+    if let Ok(mut profiles) = PROFILES.write() {
+        profiles.clear();
+    }
+    if let Ok(mut images) = IMAGE_PROFILES.write() {
+        images.clear();
+    }
+}
+
+/// Convert pixels from a gray or RGB profile to sRGB in place; alpha is kept
+pub fn to_srgb(image: &mut RgbaImage, profile: &[u8]) -> Result<(), String> {
+    let source = ColorProfile::new_from_slice(profile).map_err(|e| format!("Invalid ICC profile: {}", e))?;
+    let (layout, pixels): (_, Vec<u8>) = match source.color_space {
+        DataColorSpace::Rgb => (Layout::Rgb, image.pixels().flat_map(|p| [p.0[0], p.0[1], p.0[2]]).collect()),
+        // Gray pixels have equal channels, so red stands for the level
+        DataColorSpace::Gray => (Layout::Gray, image.pixels().map(|p| p.0[0]).collect()),
+        _ => return Err("Only gray and RGB profiles can be previewed".to_string()),
+    };
+    let transform = source
+        .create_transform_8bit(layout, &ColorProfile::new_srgb(), Layout::Rgb, TransformOptions::default())
+        .map_err(|e| format!("ICC transform failed: {}", e))?;
+    let mut rgb = vec![0u8; image.width() as usize * image.height() as usize * 3];
+    transform.transform(&pixels, &mut rgb).map_err(|e| format!("ICC transform failed: {}", e))?;
+    for (pixel, converted) in image.pixels_mut().zip(rgb.chunks_exact(3)) {
+        pixel.0[..3].copy_from_slice(converted);
+    }
+    Ok(())
+}
+
+/// Encoded image bytes for display: converted to sRGB and re-encoded as PNG
+/// when the image has a profile, unchanged otherwise
+pub fn preview_bytes(image_id: &str, data: Vec<u8>) -> Vec<u8> {
+    let Some(profile) = IMAGE_PROFILES.read().ok().and_then(|images| images.get(image_id).cloned()).and_then(|id| profile(&id))
+    else {
+        return data;
+    };
+    let converted = image::load_from_memory(&data).map_err(|e| e.to_string()).and_then(|image| {
+        let mut rgba = image.to_rgba8();
+        to_srgb(&mut rgba, &profile)?;
+        let mut png = std::io::Cursor::new(Vec::new());
+        rgba.write_to(&mut png, image::ImageFormat::Png).map_err(|e| e.to_string())?;
+        Ok(png.into_inner())
+    });
+    match converted {
+        Ok(png) => png,
+        Err(e) => {
+            tracing::warn!(image = image_id, "preview color conversion failed: {}", e);
+            data
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use image::Rgba;
+
+    #[test]
+    fn test_store_and_convert() {
+        let adobe = ColorProfile::new_adobe_rgb().encode().unwrap();
+        let id = store_profile(adobe.clone());
+        assert_eq!(store_profile(adobe.clone()), id);
+        assert_eq!(profile(&id).unwrap().as_slice(), adobe.as_slice());
+        assert_eq!(components(&adobe), Some(3));
+
+        // Adobe RGB's pure green is outside sRGB: red clips, blue is near zero
+        let mut image = RgbaImage::from_pixel(1, 1, Rgba([0, 255, 0, 128]));
+        to_srgb(&mut image, &adobe).unwrap();
+        let [r, g, b, a] = image.get_pixel(0, 0).0;
+        assert!(r < 10 && g > 240 && b < 80, "{:?}", (r, g, b));
+        assert_eq!(a, 128);
+
+        // Gray and white stay neutral
+        let gray = ColorProfile::new_gray_with_gamma(2.2).encode().unwrap();
+        assert_eq!(components(&gray), Some(1));
+        let mut white = RgbaImage::from_pixel(1, 1, Rgba([255, 255, 255, 255]));
+        to_srgb(&mut white, &gray).unwrap();
+        let [r, g, b, _] = white.get_pixel(0, 0).0;
+        assert!(r == g && g == b && r > 250);
+    }
+}
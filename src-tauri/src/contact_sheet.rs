@@ -0,0 +1,85 @@
+//! Contact Sheet Module
+//!
+//! The "contactsheet" export format: pages rendered as thumbnails and laid
+//! out N-up on letter or A4 sheets for review printing. The grid lives in
+//! `vortex_core::contact_sheet`; this renders each page with the export
+//! renderer, holds the thumbnails in the image cache while the sheets are
+//! exported as a PDF, and reports progress per page.
+
+use crate::export_handler::{self, ExportError};
+use crate::image_handler;
+use crate::job_manager::JobHandle;
+use crate::models::{DocumentMetadata, ExportResult, PageData};
+use vortex_core::contact_sheet::{contact_sheet_layout, contact_sheet_pages};
+use vortex_core::export::{ExportFormat, ExportOptions, DEFAULT_IMAGE_DPI};
+use vortex_core::units::POINTS_PER_INCH;
+
+/// Render a thumbnail of every page into the image cache, caching under
+/// the ids in `cached` as it goes; returns their image URLs
+fn render_thumbnails(
+    pages: &[PageData],
+    widths: &[f32],
+    dpi: u32,
+    job: &JobHandle,
+    cached: &mut Vec<String>,
+) -> Result<Vec<String>, ExportError> {
+    let mut urls = Vec::with_capacity(pages.len());
+    for (i, (page, width)) in pages.iter().zip(widths).enumerate() {
+        job.check_cancelled().map_err(ExportError::ContactSheet)?;
+        let scale = dpi as f32 / POINTS_PER_INCH * width / page.width.max(1.0);
+        let image = export_handler::render_page_image(page, scale).map_err(ExportError::ContactSheet)?;
+        let mut png = std::io::Cursor::new(Vec::new());
+        image.write_to(&mut png, image::ImageFormat::Png).map_err(|e| ExportError::ContactSheet(e.to_string()))?;
+
+        let id = format!("contact-sheet-{}-{}", std::process::id(), i);
+        image_handler::cache_image_with_dimensions(&id, png.into_inner(), image.width(), image.height());
+        urls.push(format!("image://{}", id));
+        cached.push(id);
+        job.progress((i + 1) as f32 / pages.len() as f32, Some(format!("Page {} of {}", i + 1, pages.len())));
+    }
+    Ok(urls)
+}
+
+/// Export the contact sheets of `pages` as a PDF at `output_path`
+pub(crate) fn export_contact_sheet_sync(
+    pages: &[PageData],
+    output_path: &str,
+    metadata: &DocumentMetadata,
+    options: &ExportOptions,
+    job: &JobHandle,
+) -> Result<ExportResult, ExportError> {
+    let sheet_options = options.contact_sheet.unwrap_or_default();
+    let pages = export_handler::selected_pages(pages, options)?;
+    if pages.is_empty() {
+        return Err(ExportError::NoPages);
+    }
+    let sizes: Vec<(f32, f32)> = pages.iter().map(|p| (p.width, p.height)).collect();
+    let cells = contact_sheet_layout(&sizes, &sheet_options).map_err(ExportError::ContactSheet)?;
+    let widths: Vec<f32> = cells.iter().map(|c| c.thumbnail.width).collect();
+    let dpi = options.dpi.unwrap_or(DEFAULT_IMAGE_DPI).clamp(36, 600);
+
+    let mut cached = Vec::with_capacity(pages.len());
+    let result = render_thumbnails(pages, &widths, dpi, job, &mut cached).and_then(|thumbnails| {
+        let sheets = contact_sheet_pages(pages, &thumbnails, &sheet_options).map_err(ExportError::ContactSheet)?;
+        let pdf_options = ExportOptions {
+            format: ExportFormat::Pdf,
+            page_range: None,
+            bleed: 0.0,
+            create_layers: false,
+            ..options.clone()
+        };
+        export_handler::export_pdf_sync(&sheets, output_path, metadata, &pdf_options)?;
+        Ok(sheets.len())
+    });
+    for id in &cached {
+        image_handler::remove_cached_image(id);
+    }
+    let sheets = result?;
+
+    Ok(ExportResult {
+        success: true,
+        message: format!("Exported {} pages on {} contact sheets", pages.len(), sheets),
+        output_path: Some(output_path.to_string()),
+        data: None,
+    })
+}
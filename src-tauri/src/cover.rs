@@ -0,0 +1,89 @@
+//! Cover Module
+//!
+//! Wraparound cover templates: back, spine and front on one sheet, the spine
+//! sized from the page count and paper caliper. The geometry lives in
+//! `vortex_core::cover`; this adds the print-ready PDF, whose TrimBox and
+//! BleedBox tell the printer where to cut.
+
+use crate::export_handler;
+use crate::models::PageData;
+use crate::pdf_tools;
+use lopdf::{Document, Object};
+use serde::{Deserialize, Serialize};
+
+pub use vortex_core::cover::{cover_layout, cover_page, CoverLayout, CoverOptions};
+
+/// A cover template page and its panel geometry
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct CoverTemplate {
+    pub layout: CoverLayout,
+    pub page: PageData,
+}
+
+/// Export a cover page to `output_path`, with the trim and bleed boxes
+/// recorded in its metadata
+fn export_cover(page: &PageData, output_path: &str) -> Result<(), String> {
+    export_handler::export_plain_pdf(std::slice::from_ref(page), output_path)?;
+    let Some(metadata) = &page.metadata else {
+        return Ok(());
+    };
+    let mut doc = Document::load(output_path).map_err(|e| format!("Failed to read {}: {}", output_path, e))?;
+    let page_ids: Vec<_> = doc.get_pages().into_values().collect();
+    for page_id in page_ids {
+        let dict = doc.get_dictionary_mut(page_id).map_err(|e| e.to_string())?;
+        for (key, value) in [("TrimBox", metadata.trim_box), ("BleedBox", metadata.bleed_box)] {
+            if let Some(b) = value {
+                dict.set(key, Object::Array(b.iter().map(|&v| Object::Real(v)).collect()));
+            }
+        }
+    }
+    pdf_tools::save(&mut doc, output_path)
+}
+
+/// Compute a cover's spine width and panels and build its template page;
+/// with `output_path` the template is also exported as a print-ready PDF
+#[tauri::command]
+pub async fn create_cover_template(options: CoverOptions, output_path: Option<String>) -> Result<CoverTemplate, String> {
+    tokio::task::spawn_blocking(move || {
+        let layout = cover_layout(&options)?;
+        let page = cover_page(&layout, options.safety);
+        if let Some(path) = &output_path {
+            export_cover(&page, path)?;
+        }
+        Ok(CoverTemplate { layout, page })
+    })
+    .await
+    .map_err(|e| format!("Cover task failed: {}", e))?
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_export_cover() {
+        let options = CoverOptions {
+            page_count: 100,
+            paper_thickness_mm: 0.1,
+            trim_width: 360.0,
+            trim_height: 576.0,
+            bleed: 9.0,
+            safety: 18.0,
+            cover_thickness_mm: 0.0,
+        };
+        let page = cover_page(&cover_layout(&options).unwrap(), options.safety);
+        let path = std::env::temp_dir().join(format!("rook-cover-test-{}.pdf", std::process::id()));
+        let output_path = path.to_string_lossy().to_string();
+        export_cover(&page, &output_path).unwrap();
+
+        let doc = Document::load(&path).unwrap();
+        let page_id = doc.get_pages()[&1];
+        let trim = pdf_tools::read_page_box(&doc, page_id, b"TrimBox").unwrap();
+        let media = pdf_tools::read_page_box(&doc, page_id, b"MediaBox").unwrap();
+        let _ = std::fs::remove_file(&path);
+        assert_eq!(trim[0], 9.0);
+        assert!((trim[2] - (page.width - 9.0)).abs() < 0.01);
+        assert!((media[2] - page.width).abs() < 0.1);
+    }
+}
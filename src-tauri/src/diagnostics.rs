@@ -0,0 +1,285 @@
+//! Diagnostics Module
+//!
+//! Local-only structured logging via `tracing`: daily-rotated log files in the
+//! app data dir, span timings for imports and exports, and a diagnostics
+//! bundle (recent logs, last error, environment) users can attach to bug
+//! reports. Nothing is ever sent anywhere.
+
+use serde::{Deserialize, Serialize};
+use std::fs::{self, File};
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex, RwLock};
+use tracing::field::{Field, Visit};
+use tracing::{Event, Level, Subscriber};
+use tracing_subscriber::layer::{Context, Layer, SubscriberExt};
+use tracing_subscriber::util::SubscriberInitExt;
+
+const LOG_PREFIX: &str = "rook";
+const LOG_SUFFIX: &str = "log";
+/// Rotated files kept on disk
+const MAX_LOG_FILES: usize = 7;
+/// Most recent log files included in a diagnostics bundle
+const BUNDLE_LOG_FILES: usize = 3;
+
+/// The most recent error-level event
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct LastError {
+    pub timestamp: String,
+    pub target: String,
+    pub message: String,
+}
+
+/// Environment details included in diagnostics bundles
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct EnvironmentInfo {
+    pub app_version: String,
+    pub os: String,
+    pub arch: String,
+    pub family: String,
+    pub cpu_count: usize,
+    pub debug_build: bool,
+    pub ocr_enabled: bool,
+    pub generated_at: String,
+}
+
+impl EnvironmentInfo {
+    pub fn collect() -> Self {
+        Self {
+            app_version: env!("CARGO_PKG_VERSION").to_string(),
+            os: std::env::consts::OS.to_string(),
+            arch: std::env::consts::ARCH.to_string(),
+            family: std::env::consts::FAMILY.to_string(),
+            cpu_count: std::thread::available_parallelism().map_or(1, |n| n.get()),
+            debug_build: cfg!(debug_assertions),
+            ocr_enabled: cfg!(feature = "ocr"),
+            generated_at: crate::models::iso8601_now(),
+        }
+    }
+}
+
+#[derive(Default)]
+struct LoggingState {
+    log_dir: Option<PathBuf>,
+    _guard: Option<tracing_appender::non_blocking::WorkerGuard>,
+}
+
+lazy_static::lazy_static! {
+    static ref LOGGING: Arc<RwLock<LoggingState>> = Arc::new(RwLock::new(LoggingState::default()));
+    // Kept apart from LOGGING so recording an error never contends with setup
+    static ref LAST_ERROR: Mutex<Option<LastError>> = Mutex::new(None);
+}
+
+/// Collects an event's message and fields into one line
+#[derive(Default)]
+struct MessageVisitor {
+    message: String,
+    fields: Vec<String>,
+}
+
+impl Visit for MessageVisitor {
+    fn record_debug(&mut self, field: &Field, value: &dyn std::fmt::Debug) {
+        if field.name() == "message" {
+            self.message = format!("{:?}", value);
+        } else {
+            self.fields.push(format!("{}={:?}", field.name(), value));
+        }
+    }
+
+    fn record_str(&mut self, field: &Field, value: &str) {
+        if field.name() == "message" {
+            self.message = value.to_string();
+        } else {
+            self.fields.push(format!("{}={}", field.name(), value));
+        }
+    }
+}
+
+/// Layer remembering the latest error-level event
+struct LastErrorLayer;
+
+impl<S: Subscriber> Layer<S> for LastErrorLayer {
+    fn on_event(&self, event: &Event<'_>, _ctx: Context<'_, S>) {
+        if *event.metadata().level() != Level::ERROR {
+            return;
+        }
+        let mut visitor = MessageVisitor::default();
+        event.record(&mut visitor);
+        let mut message = visitor.message;
+        if !visitor.fields.is_empty() {
+            message = format!("{} {}", message, visitor.fields.join(" "));
+        }
+        if let Ok(mut last) = LAST_ERROR.lock() {
+            *last = Some(LastError {
+                timestamp: crate::models::iso8601_now(),
+                target: event.metadata().target().to_string(),
+                message,
+            });
+        }
+    }
+}
+
+/// Install the global subscriber writing rotated logs into `log_dir`
+///
+/// Import and export spans are logged on close with their timings.
+/// Debug builds also log to stderr.
+pub fn init_logging(log_dir: PathBuf) -> Result<(), String> {
+    use tracing_subscriber::fmt::format::FmtSpan;
+
+    fs::create_dir_all(&log_dir).map_err(|e| e.to_string())?;
+    let appender = tracing_appender::rolling::Builder::new()
+        .rotation(tracing_appender::rolling::Rotation::DAILY)
+        .filename_prefix(LOG_PREFIX)
+        .filename_suffix(LOG_SUFFIX)
+        .max_log_files(MAX_LOG_FILES)
+        .build(&log_dir)
+        .map_err(|e| e.to_string())?;
+    let (writer, guard) = tracing_appender::non_blocking(appender);
+
+    let file_layer = tracing_subscriber::fmt::layer()
+        .with_writer(writer)
+        .with_ansi(false)
+        .with_span_events(FmtSpan::CLOSE);
+    let stderr_layer = cfg!(debug_assertions).then(|| {
+        tracing_subscriber::fmt::layer()
+            .with_writer(std::io::stderr)
+            .with_span_events(FmtSpan::CLOSE)
+    });
+    let level = if cfg!(debug_assertions) {
+        tracing_subscriber::filter::LevelFilter::DEBUG
+    } else {
+        tracing_subscriber::filter::LevelFilter::INFO
+    };
+
+    tracing_subscriber::registry()
+        .with(level)
+        .with(file_layer)
+        .with(stderr_layer)
+        .with(LastErrorLayer)
+        .try_init()
+        .map_err(|e| e.to_string())?;
+
+    let mut state = LOGGING.write().map_err(|e| e.to_string())?;
+    state.log_dir = Some(log_dir);
+    state._guard = Some(guard);
+    Ok(())
+}
+
+/// Latest error-level event, if any
+pub fn last_error() -> Option<LastError> {
+    LAST_ERROR.lock().ok().and_then(|e| e.clone())
+}
+
+/// Log files in `dir`, newest first
+fn recent_log_files(dir: &Path, limit: usize) -> Vec<PathBuf> {
+    let Ok(entries) = fs::read_dir(dir) else {
+        return Vec::new();
+    };
+    let mut files: Vec<(std::time::SystemTime, PathBuf)> = entries
+        .flatten()
+        .filter(|e| e.file_name().to_string_lossy().starts_with(LOG_PREFIX))
+        .filter_map(|e| {
+            let modified = e.metadata().ok()?.modified().ok()?;
+            Some((modified, e.path()))
+        })
+        .collect();
+    files.sort_by_key(|f| std::cmp::Reverse(f.0));
+    files.into_iter().take(limit).map(|(_, p)| p).collect()
+}
+
+/// Write a diagnostics zip with recent logs, the last error and environment info
+pub fn write_bundle(
+    output_path: &Path,
+    log_dir: Option<&Path>,
+    last_error: Option<&LastError>,
+) -> Result<usize, String> {
+    let file = File::create(output_path).map_err(|e| e.to_string())?;
+    let mut zip = zip::ZipWriter::new(file);
+    let options = zip::write::SimpleFileOptions::default()
+        .compression_method(zip::CompressionMethod::Deflated);
+    let mut entries = 0;
+
+    let env = serde_json::to_vec_pretty(&EnvironmentInfo::collect()).map_err(|e| e.to_string())?;
+    zip.start_file("environment.json", options).map_err(|e| e.to_string())?;
+    zip.write_all(&env).map_err(|e| e.to_string())?;
+    entries += 1;
+
+    if let Some(err) = last_error {
+        let data = serde_json::to_vec_pretty(err).map_err(|e| e.to_string())?;
+        zip.start_file("last_error.json", options).map_err(|e| e.to_string())?;
+        zip.write_all(&data).map_err(|e| e.to_string())?;
+        entries += 1;
+    }
+
+    for path in log_dir.map(|d| recent_log_files(d, BUNDLE_LOG_FILES)).unwrap_or_default() {
+        let Ok(data) = fs::read(&path) else {
+            continue;
+        };
+        let name = path.file_name().map(|n| n.to_string_lossy().into_owned()).unwrap_or_default();
+        zip.start_file(format!("logs/{}", name), options).map_err(|e| e.to_string())?;
+        zip.write_all(&data).map_err(|e| e.to_string())?;
+        entries += 1;
+    }
+
+    zip.finish().map_err(|e| e.to_string())?;
+    Ok(entries)
+}
+
+/// Get the latest logged error
+#[tauri::command]
+pub fn get_last_error() -> Option<LastError> {
+    last_error()
+}
+
+/// Zip recent logs, the last error and environment info for a bug report
+#[tauri::command]
+pub async fn create_diagnostics_bundle(output_path: String) -> Result<String, String> {
+    let log_dir = LOGGING.read().map_err(|e| e.to_string())?.log_dir.clone();
+    let last = last_error();
+    let path = PathBuf::from(&output_path);
+    let entries = tokio::task::spawn_blocking(move || {
+        write_bundle(&path, log_dir.as_deref(), last.as_ref())
+    })
+    .await
+    .map_err(|e| format!("Diagnostics task failed: {}", e))??;
+    tracing::info!(entries, path = %output_path, "diagnostics bundle created");
+    Ok(output_path)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::io::Read;
+
+    #[test]
+    fn test_bundle_contents() {
+        let base = std::env::temp_dir().join(format!("rook-diag-{}", std::process::id()));
+        let log_dir = base.join("logs");
+        fs::create_dir_all(&log_dir).unwrap();
+        fs::write(log_dir.join("rook.2026-01-01.log"), "INFO started\n").unwrap();
+        fs::write(log_dir.join("unrelated.txt"), "skip").unwrap();
+
+        let err = LastError {
+            timestamp: "2026-01-01T00:00:00Z".to_string(),
+            target: "rook::export_handler".to_string(),
+            message: "export failed".to_string(),
+        };
+        let out = base.join("bundle.zip");
+        let entries = write_bundle(&out, Some(&log_dir), Some(&err)).unwrap();
+        assert_eq!(entries, 3);
+
+        let mut archive = zip::ZipArchive::new(File::open(&out).unwrap()).unwrap();
+        let mut log = String::new();
+        archive
+            .by_name("logs/rook.2026-01-01.log")
+            .unwrap()
+            .read_to_string(&mut log)
+            .unwrap();
+        assert_eq!(log, "INFO started\n");
+        assert!(archive.by_name("environment.json").is_ok());
+
+        let _ = fs::remove_dir_all(&base);
+    }
+}
@@ -0,0 +1,555 @@
+//! Document Diff Module
+//!
+//! Structured comparison of two projects (or a project and its autosave):
+//! added/removed/moved pages, changed layers with field-level differences,
+//! and word-level text diffs for content changes.
+//!
+//! Pages carry no stable identity, so they are paired by similarity of
+//! their layer ids and text. Pairs whose relative order changed are reported
+//! as moved (everything outside the longest order-preserving run).
+
+use crate::models::{BookProjectData, DocumentData, LayerObject, PageData};
+use serde::{Deserialize, Serialize};
+use std::collections::HashSet;
+
+/// Minimum similarity for two pages to be considered the same page
+const PAGE_MATCH_THRESHOLD: f32 = 0.5;
+
+/// Token limit for word diffs; larger texts fall back to delete + insert
+const MAX_TEXT_DIFF_TOKENS: usize = 4000;
+
+/// Kind of page-level change
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+#[repr(u8)]
+pub enum PageChangeKind {
+    Added = 0,
+    Removed = 1,
+    Moved = 2,
+    Modified = 3,
+}
+
+/// Kind of layer-level change
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+#[repr(u8)]
+pub enum LayerChangeKind {
+    Added = 0,
+    Removed = 1,
+    Modified = 2,
+}
+
+/// Text diff operation
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+#[repr(u8)]
+pub enum TextOp {
+    Equal = 0,
+    Insert = 1,
+    Delete = 2,
+}
+
+/// A run of text sharing one diff operation
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct TextSegment {
+    pub op: TextOp,
+    pub text: String,
+}
+
+/// A single changed layer field (camelCase name, JSON values)
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+pub struct FieldChange {
+    pub field: String,
+    pub old: serde_json::Value,
+    pub new: serde_json::Value,
+}
+
+/// Difference for one layer
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct LayerDiff {
+    pub layer_id: String,
+    pub kind: LayerChangeKind,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub fields: Vec<FieldChange>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub text_diff: Option<Vec<TextSegment>>,
+}
+
+/// Difference for one page
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct PageDiff {
+    pub kind: PageChangeKind,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub old_index: Option<usize>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub new_index: Option<usize>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub layers: Vec<LayerDiff>,
+}
+
+/// Counts for a quick overview of a diff
+#[derive(Debug, Clone, Default, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub struct DiffSummary {
+    pub pages_added: usize,
+    pub pages_removed: usize,
+    pub pages_moved: usize,
+    pub pages_modified: usize,
+    pub layers_added: usize,
+    pub layers_removed: usize,
+    pub layers_modified: usize,
+}
+
+/// Structured diff between two documents
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct DocumentDiff {
+    pub summary: DiffSummary,
+    pub pages: Vec<PageDiff>,
+}
+
+impl DocumentDiff {
+    /// True when both documents are identical
+    #[inline]
+    pub fn is_empty(&self) -> bool {
+        self.pages.is_empty()
+    }
+}
+
+/// Compare two documents
+pub fn diff_documents(old: &DocumentData, new: &DocumentData) -> DocumentDiff {
+    let pairs = match_pages(&old.pages, &new.pages);
+    let moved = moved_pairs(&pairs);
+
+    let mut pages = Vec::new();
+    let mut summary = DiffSummary::default();
+    let matched_old: HashSet<usize> = pairs.iter().map(|&(o, _)| o).collect();
+    let matched_new: HashSet<usize> = pairs.iter().map(|&(_, n)| n).collect();
+
+    for (i, _) in old.pages.iter().enumerate() {
+        if !matched_old.contains(&i) {
+            summary.pages_removed += 1;
+            pages.push(PageDiff {
+                kind: PageChangeKind::Removed,
+                old_index: Some(i),
+                new_index: None,
+                layers: Vec::new(),
+            });
+        }
+    }
+
+    for (pair_idx, &(o, n)) in pairs.iter().enumerate() {
+        let layers = diff_layers(&old.pages[o].layers, &new.pages[n].layers);
+        let is_moved = moved.contains(&pair_idx);
+        if layers.is_empty() && !is_moved {
+            continue;
+        }
+        for layer in &layers {
+            match layer.kind {
+                LayerChangeKind::Added => summary.layers_added += 1,
+                LayerChangeKind::Removed => summary.layers_removed += 1,
+                LayerChangeKind::Modified => summary.layers_modified += 1,
+            }
+        }
+        let kind = if is_moved {
+            summary.pages_moved += 1;
+            PageChangeKind::Moved
+        } else {
+            summary.pages_modified += 1;
+            PageChangeKind::Modified
+        };
+        pages.push(PageDiff {
+            kind,
+            old_index: Some(o),
+            new_index: Some(n),
+            layers,
+        });
+    }
+
+    for (i, _) in new.pages.iter().enumerate() {
+        if !matched_new.contains(&i) {
+            summary.pages_added += 1;
+            pages.push(PageDiff {
+                kind: PageChangeKind::Added,
+                old_index: None,
+                new_index: Some(i),
+                layers: Vec::new(),
+            });
+        }
+    }
+
+    DocumentDiff { summary, pages }
+}
+
+/// Similarity of two pages from their layer ids and text content (0..=1)
+fn page_similarity(a: &PageData, b: &PageData) -> f32 {
+    let keys = |p: &PageData| -> HashSet<String> {
+        p.layers
+            .iter()
+            .flat_map(|l| {
+                let mut k = vec![format!("id:{}", l.id)];
+                if let Some(c) = &l.content {
+                    k.extend(c.split_whitespace().map(|w| format!("w:{}", w)));
+                }
+                k
+            })
+            .collect()
+    };
+    let (ka, kb) = (keys(a), keys(b));
+    if ka.is_empty() && kb.is_empty() {
+        return if a.width == b.width && a.height == b.height { 1.0 } else { 0.0 };
+    }
+    let inter = ka.intersection(&kb).count() as f32;
+    let union = ka.union(&kb).count() as f32;
+    inter / union
+}
+
+/// Pair old and new pages, preferring identical positions on ties
+fn match_pages(old: &[PageData], new: &[PageData]) -> Vec<(usize, usize)> {
+    let mut candidates = Vec::new();
+    for (o, op) in old.iter().enumerate() {
+        for (n, np) in new.iter().enumerate() {
+            let score = page_similarity(op, np);
+            if score >= PAGE_MATCH_THRESHOLD {
+                candidates.push((score, o.abs_diff(n), o, n));
+            }
+        }
+    }
+    candidates.sort_by(|a, b| b.0.total_cmp(&a.0).then(a.1.cmp(&b.1)));
+
+    let mut used_old = HashSet::new();
+    let mut used_new = HashSet::new();
+    let mut pairs = Vec::new();
+    for (_, _, o, n) in candidates {
+        if used_old.contains(&o) || used_new.contains(&n) {
+            continue;
+        }
+        used_old.insert(o);
+        used_new.insert(n);
+        pairs.push((o, n));
+    }
+    pairs.sort_by_key(|&(_, n)| n);
+    pairs
+}
+
+/// Indices (into `pairs`, sorted by new index) of pairs outside the longest
+/// run that keeps its old order
+fn moved_pairs(pairs: &[(usize, usize)]) -> HashSet<usize> {
+    let len = pairs.len();
+    if len == 0 {
+        return HashSet::new();
+    }
+    // O(n^2) LIS over old indices; page counts are small
+    let mut best = vec![1usize; len];
+    let mut prev = vec![usize::MAX; len];
+    for i in 0..len {
+        for j in 0..i {
+            if pairs[j].0 < pairs[i].0 && best[j] + 1 > best[i] {
+                best[i] = best[j] + 1;
+                prev[i] = j;
+            }
+        }
+    }
+    let mut idx = (0..len).max_by_key(|&i| best[i]).unwrap_or(0);
+    let mut keep = HashSet::new();
+    loop {
+        keep.insert(idx);
+        if prev[idx] == usize::MAX {
+            break;
+        }
+        idx = prev[idx];
+    }
+    (0..len).filter(|i| !keep.contains(i)).collect()
+}
+
+/// Compare layer lists by id
+fn diff_layers(old: &[LayerObject], new: &[LayerObject]) -> Vec<LayerDiff> {
+    let mut diffs = Vec::new();
+
+    for layer in old {
+        if !new.iter().any(|l| l.id == layer.id) {
+            diffs.push(LayerDiff {
+                layer_id: layer.id.clone(),
+                kind: LayerChangeKind::Removed,
+                fields: Vec::new(),
+                text_diff: None,
+            });
+        }
+    }
+
+    for layer in new {
+        match old.iter().find(|l| l.id == layer.id) {
+            None => diffs.push(LayerDiff {
+                layer_id: layer.id.clone(),
+                kind: LayerChangeKind::Added,
+                fields: Vec::new(),
+                text_diff: None,
+            }),
+            Some(prev) if prev != layer => {
+                let fields = diff_fields(prev, layer);
+                let text_diff = match (&prev.content, &layer.content) {
+                    (a, b) if a == b => None,
+                    (a, b) => Some(diff_text(
+                        a.as_deref().unwrap_or(""),
+                        b.as_deref().unwrap_or(""),
+                    )),
+                };
+                diffs.push(LayerDiff {
+                    layer_id: layer.id.clone(),
+                    kind: LayerChangeKind::Modified,
+                    fields,
+                    text_diff,
+                });
+            }
+            Some(_) => {}
+        }
+    }
+
+    diffs
+}
+
+/// Field-level differences using the serialized (camelCase) layer shape
+fn diff_fields(old: &LayerObject, new: &LayerObject) -> Vec<FieldChange> {
+    let (Ok(serde_json::Value::Object(a)), Ok(serde_json::Value::Object(b))) =
+        (serde_json::to_value(old), serde_json::to_value(new))
+    else {
+        return Vec::new();
+    };
+
+    let mut fields: Vec<&String> = a.keys().chain(b.keys()).collect();
+    fields.sort();
+    fields.dedup();
+
+    fields
+        .into_iter()
+        .filter_map(|field| {
+            let old_val = a.get(field).cloned().unwrap_or(serde_json::Value::Null);
+            let new_val = b.get(field).cloned().unwrap_or(serde_json::Value::Null);
+            (old_val != new_val).then(|| FieldChange {
+                field: field.clone(),
+                old: old_val,
+                new: new_val,
+            })
+        })
+        .collect()
+}
+
+/// Split text into words and the whitespace following them
+fn tokenize(text: &str) -> Vec<&str> {
+    let mut tokens = Vec::new();
+    let mut start = 0;
+    let mut in_space = false;
+    for (i, c) in text.char_indices() {
+        let space = c.is_whitespace();
+        if i > start && space != in_space && !space {
+            tokens.push(&text[start..i]);
+            start = i;
+        }
+        in_space = space;
+    }
+    if start < text.len() {
+        tokens.push(&text[start..]);
+    }
+    tokens
+}
+
+/// Word-level diff via longest common subsequence
+pub fn diff_text(old: &str, new: &str) -> Vec<TextSegment> {
+    let a = tokenize(old);
+    let b = tokenize(new);
+
+    let mut ops: Vec<(TextOp, &str)> = Vec::new();
+    if a.len() > MAX_TEXT_DIFF_TOKENS || b.len() > MAX_TEXT_DIFF_TOKENS {
+        ops.push((TextOp::Delete, old));
+        ops.push((TextOp::Insert, new));
+    } else {
+        // lcs[i][j] = LCS length of a[i..] and b[j..]
+        let mut lcs = vec![vec![0u32; b.len() + 1]; a.len() + 1];
+        for i in (0..a.len()).rev() {
+            for j in (0..b.len()).rev() {
+                lcs[i][j] = if a[i] == b[j] {
+                    lcs[i + 1][j + 1] + 1
+                } else {
+                    lcs[i + 1][j].max(lcs[i][j + 1])
+                };
+            }
+        }
+        let (mut i, mut j) = (0, 0);
+        while i < a.len() || j < b.len() {
+            if i < a.len() && j < b.len() && a[i] == b[j] {
+                ops.push((TextOp::Equal, a[i]));
+                i += 1;
+                j += 1;
+            } else if j < b.len() && (i == a.len() || lcs[i][j + 1] >= lcs[i + 1][j]) {
+                ops.push((TextOp::Insert, b[j]));
+                j += 1;
+            } else {
+                ops.push((TextOp::Delete, a[i]));
+                i += 1;
+            }
+        }
+    }
+
+    // Merge adjacent runs of the same op
+    let mut segments: Vec<TextSegment> = Vec::new();
+    for (op, text) in ops {
+        if text.is_empty() {
+            continue;
+        }
+        match segments.last_mut() {
+            Some(last) if last.op == op => last.text.push_str(text),
+            _ => segments.push(TextSegment {
+                op,
+                text: text.to_string(),
+            }),
+        }
+    }
+    segments
+}
+
+fn read_project(path: &str) -> Result<BookProjectData, String> {
+    crate::export_handler::read_project_file(path)
+        .map(|(project, _)| project)
+        .map_err(|e| format!("Failed to read {}: {}", path, e))
+}
+
+/// Compare two .bookproj files (e.g. a project and its autosave)
+#[tauri::command]
+pub async fn compare_documents(old_path: String, new_path: String) -> Result<DocumentDiff, String> {
+    tokio::task::spawn_blocking(move || {
+        let old = read_project(&old_path)?;
+        let new = read_project(&new_path)?;
+        Ok(diff_documents(&old.document, &new.document))
+    })
+    .await
+    .map_err(|e| format!("Compare task failed: {}", e))?
+}
+
+/// Compare two in-memory projects
+#[tauri::command]
+pub fn compare_projects(old: BookProjectData, new: BookProjectData) -> DocumentDiff {
+    diff_documents(&old.document, &new.document)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::{Bounds, LayerRole, LayerType, SourceType};
+
+    fn text_layer(id: &str, content: &str) -> LayerObject {
+        LayerObject {
+            id: id.to_string(),
+            layer_type: LayerType::Text,
+            bounds: Bounds::new(10.0, 10.0, 200.0, 20.0),
+            visible: true,
+            locked: false,
+            z_index: 1,
+            opacity: 1.0,
+            blend_mode: None,
+            content: Some(content.to_string()),
+            font_family: None,
+            font_size: Some(12.0),
+            font_weight: None,
+            font_style: None,
+            color: None,
+            text_align: None,
+            text_decoration: None,
+            text_transform: None,
+            line_height: None,
+            letter_spacing: None,
+            background_color: None,
+            text_path: None,
+            text_outline: None,
+            text_shadow: None,
+            drop_cap: None,
+            notes: Vec::new(),
+            pagination: None,
+            image_url: None,
+            image_path: None,
+            image_data: None,
+            image_link: None,
+            shape_type: None,
+            stroke_color: None,
+            stroke_width: None,
+            fill_color: None,
+            text_wrap: None,
+            path_data: None,
+            transform: None,
+            source_type: SourceType::Extracted,
+            role: LayerRole::Content,
+            ocr: None,
+            tags: Vec::new(),
+        }
+    }
+
+    fn doc(pages: Vec<Vec<LayerObject>>) -> DocumentData {
+        let pages = pages
+            .into_iter()
+            .enumerate()
+            .map(|(i, layers)| PageData {
+                page_index: i,
+                width: 612.0,
+                height: 792.0,
+                dpi: None,
+                layers,
+                metadata: None,
+                background: None,
+            })
+            .collect();
+        DocumentData::new(612.0, 792.0, pages)
+    }
+
+    #[test]
+    fn test_identical_documents() {
+        let d = doc(vec![vec![text_layer("a", "hello world")]]);
+        assert!(diff_documents(&d, &d).is_empty());
+    }
+
+    #[test]
+    fn test_added_removed_and_moved_pages() {
+        let p1 = vec![text_layer("p1", "chapter one begins")];
+        let p2 = vec![text_layer("p2", "chapter two begins")];
+        let p3 = vec![text_layer("p3", "chapter three begins")];
+        let p4 = vec![text_layer("p4", "an entirely new page")];
+        let old = doc(vec![p1.clone(), p2.clone(), p3.clone()]);
+        let new = doc(vec![p3, p1, p4]);
+
+        let diff = diff_documents(&old, &new);
+        assert_eq!(diff.summary.pages_removed, 1);
+        assert_eq!(diff.summary.pages_added, 1);
+        assert_eq!(diff.summary.pages_moved, 1);
+        let moved = diff.pages.iter().find(|p| p.kind == PageChangeKind::Moved).unwrap();
+        assert_eq!((moved.old_index, moved.new_index), (Some(2), Some(0)));
+    }
+
+    #[test]
+    fn test_field_and_text_changes() {
+        let mut edited = text_layer("a", "the quick brown fox");
+        edited.font_size = Some(14.0);
+        let old = doc(vec![vec![text_layer("a", "the quick red fox"), text_layer("b", "x")]]);
+        let new = doc(vec![vec![edited, text_layer("b", "x"), text_layer("c", "y")]]);
+
+        let diff = diff_documents(&old, &new);
+        assert_eq!(diff.summary.layers_added, 1);
+        assert_eq!(diff.summary.layers_modified, 1);
+        let layer = diff.pages[0].layers.iter().find(|l| l.layer_id == "a").unwrap();
+        let fields: Vec<_> = layer.fields.iter().map(|f| f.field.as_str()).collect();
+        assert_eq!(fields, vec!["content", "fontSize"]);
+        let text = layer.text_diff.as_ref().unwrap();
+        assert!(text.contains(&TextSegment { op: TextOp::Delete, text: "red ".to_string() }));
+        assert!(text.contains(&TextSegment { op: TextOp::Insert, text: "brown ".to_string() }));
+    }
+
+    #[test]
+    fn test_diff_text_reconstructs_both_sides() {
+        let (old, new) = ("one two  three", "zero one three four");
+        let segments = diff_text(old, new);
+        let side = |skip: TextOp| -> String {
+            segments.iter().filter(|s| s.op != skip).map(|s| s.text.as_str()).collect()
+        };
+        assert_eq!(side(TextOp::Insert), old);
+        assert_eq!(side(TextOp::Delete), new);
+    }
+}
@@ -1,7 +1,8 @@
 //! Document Parser Module
 //!
 //! Optimized PDF parsing using pdfium-only approach for speed.
-//! Falls back to lopdf only when pdfium text extraction fails.
+//! Falls back to lopdf only when pdfium text extraction fails, or when no
+//! pdfium library can be loaded (degraded mode: no OCR or rasterizing).
 //!
 //! ## Performance Optimizations
 //! - Parallel page processing with rayon
@@ -9,20 +10,195 @@
 //! - Batched image encoding with fast PNG compression
 //! - Global font metrics cache
 //! - Pre-filtered object iteration
+//! - Memory budget: images past it are decoded lazily via the image protocol
 
 use crate::font_manager::normalizer;
 use crate::models::{
-    Bounds, DocumentData, DocumentResponse, ImageMetadata, LayerObject, LayerRole, LayerType,
-    PageData, PageMetadata, SourceType, TextAlign,
+    BlendMode, Bounds, DocumentData, DocumentResponse, ImageMetadata, LayerObject, LayerRole, LayerType,
+    PageData, PageLabel, PageMetadata, PageRepair, PageRepairStatus, Pagination, RepairReport, SourceType,
+    TextAlign,
 };
+use crate::color_profile;
+use crate::content_parser;
+use crate::image_handler::{self, LazyImageSource};
+use crate::import_mappings;
+use crate::job_manager::{self, JobHandle, JobKind, JobPriority};
+use crate::ocr_handler::{self, OcrEngine};
+use crate::page_setup::PageSetup;
+use crate::pdf_analyzer::{self, VECTOR_HEAVY_OPERATORS};
+use crate::pdf_engine::load_pdfium;
+use crate::pdf_images::{self, ImageIndex};
+use crate::pdf_repair;
+use crate::pdf_tools;
+use crate::photo_correction;
+use crate::scanner;
+use crate::settings;
 use pdfium_render::prelude::*;
 use rayon::prelude::*;
+use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
 use std::sync::atomic::{AtomicUsize, Ordering};
-use std::sync::{Arc, Mutex};
+use std::sync::{mpsc, Arc, Mutex};
 use tauri::{AppHandle, Emitter};
+use tracing::Instrument;
+use vortex_core::doc_metadata;
+use vortex_core::import_mapping;
+use vortex_core::layer_cleanup::{self, PruneOptions};
+use vortex_core::layer_ids::LayerIds;
+use vortex_core::page_labels;
+use vortex_core::page_setup::translate_layers;
+use vortex_core::reflow;
+use vortex_core::text_structure::{self, MergeLevel};
+use vortex_core::typography::{self, TypographyOptions};
+use vortex_core::units::POINTS_PER_INCH;
+
+/// Resolution for OCR and rasterized pages
+const RENDER_DPI: u32 = 300;
+
+/// Options controlling how a document is imported
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ImportOptions {
+    /// Downsample images so neither side exceeds this many pixels
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub max_image_dimension: Option<u32>,
+    /// Defer all image decoding until an image is first requested
+    #[serde(default)]
+    pub lazy_images: bool,
+    /// Budget for this import's decoded images in MB; images past it are
+    /// loaded lazily. Defaults to, and is capped at, the image cache size
+    /// setting, which it leaves unchanged.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub memory_budget_mb: Option<usize>,
+    /// Page size and margins for reflowed formats (DOCX); US Letter with
+    /// 1 in margins when absent. PDF pages keep their own size.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub page_setup: Option<PageSetup>,
+    /// Straighten photo imports (JPEG/PNG): detect the page, correct
+    /// perspective and normalize contrast
+    #[serde(default)]
+    pub correct_photos: bool,
+    /// Run OCR on photo imports and add text layers
+    #[serde(default)]
+    pub ocr_photos: bool,
+    /// First and last page to import, 0-based and inclusive; all pages
+    /// when absent (PDF only)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub page_range: Option<(usize, usize)>,
+    /// Import text layers only
+    #[serde(default)]
+    pub text_only: bool,
+    #[serde(default)]
+    pub skip_images: bool,
+    /// Skip vector paths (only extracted without pdfium)
+    #[serde(default)]
+    pub skip_vectors: bool,
+    /// Drop images smaller than this many pixels on either side (default:
+    /// the minimum image size setting)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub min_image_size: Option<u32>,
+    /// OCR PDF pages that have images but no text
+    #[serde(default)]
+    pub auto_ocr: bool,
+    /// Whether vector-heavy PDF pages are rendered as a single image layer
+    #[serde(default)]
+    pub rasterize: RasterizeFallback,
+    /// Operators above which a page is vector-heavy (default
+    /// `VECTOR_HEAVY_OPERATORS`); pdfium imports count page objects
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub rasterize_threshold: Option<usize>,
+    /// Source pages to rasterize whatever their content
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub rasterize_pages: Vec<usize>,
+    /// Merge extracted text runs into lines or blocks
+    #[serde(default)]
+    pub merge_level: MergeLevel,
+    /// Drop layers that paint nothing (zero-area, transparent, blank text,
+    /// off the page) once the PDF is imported
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub prune: Option<PruneOptions>,
+    /// Smart quotes, dashes and ellipses in the imported text, for the
+    /// document's language unless the options name one
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub typography: Option<TypographyOptions>,
+    /// Saved import mapping giving the extracted layers their roles, tags
+    /// and styles
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub import_mapping: Option<String>,
+}
+
+/// Fallback for pages too heavy to import as vectors
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum RasterizeFallback {
+    /// Rasterize pages over the threshold
+    #[default]
+    Auto,
+    /// Only rasterize the pages listed in `rasterize_pages`
+    OptIn,
+}
+
+impl ImportOptions {
+    /// Whether a page with `operators` content operators is rasterized
+    fn should_rasterize(&self, page_index: usize, operators: usize) -> bool {
+        self.rasterize_pages.contains(&page_index)
+            || (self.rasterize == RasterizeFallback::Auto
+                && operators > self.rasterize_threshold.unwrap_or(VECTOR_HEAVY_OPERATORS))
+    }
+
+    fn import_images(&self) -> bool {
+        !self.text_only && !self.skip_images
+    }
 
-static LAYER_COUNTER: AtomicUsize = AtomicUsize::new(0);
+    fn import_vectors(&self) -> bool {
+        !self.text_only && !self.skip_vectors
+    }
+
+    fn min_image_size(&self) -> u32 {
+        self.min_image_size.unwrap_or_else(|| settings::current().min_image_size)
+    }
+
+    /// Source page indices to import from a document of `total` pages
+    fn page_indices(&self, total: usize) -> Result<Vec<usize>, String> {
+        match self.page_range {
+            None => Ok((0..total).collect()),
+            Some((first, last)) if first <= last && last < total => Ok((first..=last).collect()),
+            Some((first, last)) => Err(format!(
+                "Page range {}-{} is outside the document ({} pages)",
+                first + 1,
+                last + 1,
+                total
+            )),
+        }
+    }
+}
+
+/// Image decoding state shared by the page workers of one import
+struct ImageImportContext<'a> {
+    file_path: &'a str,
+    enabled: bool,
+    min_size: u32,
+    max_dimension: Option<u32>,
+    lazy: bool,
+    budget_bytes: usize,
+    decoded_bytes: AtomicUsize,
+    /// Soft masks and ICC profiles pdfium leaves out of decoded images
+    index: Option<Arc<ImageIndex>>,
+}
+
+/// Memory an image takes once decoded to RGBA, which is what displaying it costs
+#[inline]
+fn decoded_size(width: u32, height: u32) -> usize {
+    width as usize * height as usize * 4
+}
+
+impl ImageImportContext<'_> {
+    /// Whether the next image should be registered instead of decoded
+    #[inline]
+    fn should_defer(&self) -> bool {
+        self.lazy || self.decoded_bytes.load(Ordering::Relaxed) >= self.budget_bytes
+    }
+}
 
 /// Global font metrics cache (shared across pages)
 type FontCache = Arc<Mutex<HashMap<String, CachedFontMetrics>>>;
@@ -33,16 +209,12 @@ struct CachedFontMetrics {
     ascent: f32,
 }
 
-#[inline]
-fn reset_layer_counter() {
-    LAYER_COUNTER.store(0, Ordering::SeqCst);
-}
-
 /// Import a document from the specified file path
 #[tauri::command]
 pub async fn import_document(
     file_path: String,
     file_type: String,
+    options: Option<ImportOptions>,
     app_handle: AppHandle,
 ) -> Result<DocumentResponse, String> {
     if !std::path::Path::new(&file_path).exists() {
@@ -50,11 +222,61 @@ pub async fn import_document(
             success: false,
             message: format!("File not found: {}", file_path),
             data: None,
+            repair: None,
+            metadata: None,
         });
     }
 
-    crate::image_handler::clear_image_cache();
-    reset_layer_counter();
+    let options = options.unwrap_or_default();
+    image_handler::clear_image_cache();
+    parse_document(file_path, file_type, options, app_handle).await
+}
+
+/// Re-parse one page of a source file, by its index in the source
+///
+/// Unlike `import_document` this leaves the image cache alone: the rest of
+/// the project still refers to it. Extracted layer ids are derived from the
+/// source page's content (see `vortex_core::layer_ids`), so they come back
+/// unchanged.
+pub async fn import_source_page(
+    file_path: String,
+    file_type: String,
+    source_index: usize,
+    mut options: ImportOptions,
+    app_handle: AppHandle,
+) -> Result<PageData, String> {
+    if !std::path::Path::new(&file_path).exists() {
+        return Err(format!("Source file not found: {}", file_path));
+    }
+    let paged = file_type.eq_ignore_ascii_case("pdf");
+    if paged {
+        options.page_range = Some((source_index, source_index));
+    }
+    let response = parse_document(file_path, file_type, options, app_handle).await?;
+    let pages = match response.data {
+        Some(data) if response.success => data.pages,
+        _ => return Err(response.message),
+    };
+    let source_of = |p: &PageData| p.metadata.as_ref().and_then(|m| m.original_page_index).unwrap_or(p.page_index);
+    pages
+        .into_iter()
+        .find(|p| paged || source_of(p) == source_index)
+        .ok_or_else(|| format!("Page {} is not in the source file", source_index + 1))
+}
+
+/// Parse a source file into pages with the current image cache
+async fn parse_document(
+    file_path: String,
+    file_type: String,
+    options: ImportOptions,
+    app_handle: AppHandle,
+) -> Result<DocumentResponse, String> {
+    // Images decoded past the cache size would be evicted with nothing to reload them from
+    let cache_bytes = settings::current().image_cache_bytes();
+    let budget_bytes = options
+        .memory_budget_mb
+        .map_or(cache_bytes, |mb| mb.saturating_mul(1024 * 1024).min(cache_bytes));
+    image_handler::set_lazy_image_loader(load_lazy_image);
 
     let _ = app_handle.emit(
         "parse_progress",
@@ -65,37 +287,93 @@ pub async fn import_document(
         }),
     );
 
-    match file_type.to_lowercase().as_str() {
-        "pdf" => parse_pdf_optimized(&file_path, &app_handle).await,
-        "docx" => parse_docx(&file_path, &app_handle).await,
-        _ => Ok(DocumentResponse {
-            success: false,
-            message: format!("Unsupported file type: {}", file_type),
-            data: None,
-        }),
+    // The user is waiting on imports, so they go ahead of other jobs
+    let path = file_path.clone();
+    let typography = options.typography.clone();
+    let mapping = match &options.import_mapping {
+        Some(name) => Some(
+            import_mappings::resolve_mapping(name).ok_or_else(|| format!("Import mapping '{}' not found", name))?,
+        ),
+        None => None,
+    };
+    let mut result = job_manager::run(JobKind::Import, job_manager::file_label(&file_path), JobPriority::High, move |job| {
+        let span = tracing::info_span!("import", file_type = %file_type, path = %path);
+        tauri::async_runtime::block_on(
+            async {
+                match file_type.to_lowercase().as_str() {
+                    "pdf" => parse_pdf_optimized(&path, &options, budget_bytes, &app_handle, job).await,
+                    "docx" => parse_docx(&path, &options.page_setup.unwrap_or_default(), &app_handle, job).await,
+                    "png" | "jpg" | "jpeg" => job.check_cancelled().and_then(|()| photo_correction::import_photo(&path, &options)),
+                    _ => Ok(DocumentResponse {
+                        success: false,
+                        message: format!("Unsupported file type: {}", file_type),
+                        data: None,
+                        repair: None,
+                        metadata: None,
+                    }),
+                }
+            }
+            .instrument(span),
+        )
+    })
+    .await;
+    if let (Ok(response), Some(typography)) = (&mut result, &typography) {
+        clean_imported_typography(response, typography);
+    }
+    if let (Ok(response), Some(mapping)) = (&mut result, &mapping) {
+        if let Some(data) = response.data.as_mut() {
+            let changed = import_mapping::apply_mapping(&mut data.pages, mapping);
+            tracing::info!(changed, mapping = %mapping.name, "applied import mapping");
+        }
+    }
+
+    match &result {
+        Ok(r) if !r.success => tracing::warn!(path = %file_path, "import failed: {}", r.message),
+        Err(e) => tracing::error!(path = %file_path, "import failed: {}", e),
+        Ok(_) => {}
     }
+    result
 }
 
 /// Optimized PDF parsing using pdfium only
 async fn parse_pdf_optimized(
     file_path: &str,
+    options: &ImportOptions,
+    budget_bytes: usize,
     app_handle: &AppHandle,
+    job: &JobHandle,
 ) -> Result<DocumentResponse, String> {
-    let pdfium = load_pdfium()?;
-    let pdfium_doc = pdfium
-        .load_pdf_from_file(file_path, None)
-        .map_err(|e| format!("Failed to load PDF: {}", e))?;
+    let pdfium = match load_pdfium() {
+        Ok(pdfium) => pdfium,
+        Err(e) => return parse_pdf_degraded(file_path, options, &e, app_handle, job),
+    };
+    // A damaged file is repaired into a temp copy that stands in for it from here on
+    let mut repair = None;
+    let repaired_path;
+    let (pdfium_doc, file_path) = match pdfium.load_pdf_from_file(file_path, None) {
+        Ok(doc) => (doc, file_path),
+        Err(e) => {
+            tracing::warn!(path = %file_path, "PDF failed to load, repairing: {}", e);
+            let (path, mut report) = pdf_repair::repair_to_temp(file_path)
+                .map_err(|repair_error| format!("Failed to load PDF: {} ({})", e, repair_error))?;
+            report.load_error.get_or_insert_with(|| e.to_string());
+            repair = Some(report);
+            repaired_path = path.to_string_lossy().to_string();
+            let doc = pdfium
+                .load_pdf_from_file(&repaired_path, None)
+                .map_err(|e| format!("Failed to load repaired PDF: {}", e))?;
+            (doc, repaired_path.as_str())
+        }
+    };
 
     let total_pages = pdfium_doc.pages().len();
     if total_pages == 0 {
         return Ok(DocumentResponse {
             success: true,
             message: "PDF has no pages".to_string(),
-            data: Some(DocumentData {
-                page_width: 612.0,
-                page_height: 792.0,
-                pages: vec![],
-            }),
+            data: Some(DocumentData::new(612.0, 792.0, vec![])),
+            repair,
+            metadata: None,
         });
     }
 
@@ -107,104 +385,470 @@ async fn parse_pdf_optimized(
     // Shared font cache
     let font_cache: FontCache = Arc::new(Mutex::new(HashMap::with_capacity(32)));
 
-    // Collect page data for parallel processing
-    let page_indices: Vec<u16> = (0..total_pages).collect();
+    let images = ImageImportContext {
+        file_path,
+        enabled: options.import_images(),
+        min_size: options.min_image_size(),
+        max_dimension: options.max_image_dimension,
+        lazy: options.lazy_images,
+        budget_bytes,
+        decoded_bytes: AtomicUsize::new(0),
+        index: if options.import_images() && !options.lazy_images {
+            ImageIndex::for_file(file_path)
+        } else {
+            None
+        },
+    };
+
+    // Collect page data for parallel processing, leaving out pages the repair gave up on
+    let failed_pages = repair.as_ref().map(RepairReport::failed_pages).unwrap_or_default();
+    let page_indices: Vec<usize> = options
+        .page_indices(total_pages as usize)?
+        .into_iter()
+        .filter(|i| !failed_pages.contains(i))
+        .collect();
+    let rasterized_pages = AtomicUsize::new(0);
+    let done_pages = AtomicUsize::new(0);
 
-    // Process pages in parallel
-    let pages: Vec<PageData> = page_indices
+    // Process pages in parallel; imported pages are numbered from 0 and
+    // keep their source index in the metadata. Cancelling stops the pages
+    // not yet started.
+    let results: Vec<Result<PageData, PageRepair>> = page_indices
         .par_iter()
-        .map(|&page_index| {
-            let page = match pdfium_doc.pages().get(page_index) {
+        .enumerate()
+        .map(|(position, &page_index)| -> Result<Result<PageData, PageRepair>, String> {
+            job.check_cancelled()?;
+            let page = match pdfium_doc.pages().get(page_index as u16) {
                 Ok(p) => p,
-                Err(_) => return None,
+                Err(e) => {
+                    return Ok(Err(PageRepair {
+                        page_index,
+                        status: PageRepairStatus::Failed,
+                        error: Some(e.to_string()),
+                    }))
+                }
             };
 
             let width = page.width().value as f32;
             let height = page.height().value as f32;
-
-            // Extract text and images
-            let mut layers = extract_page_content_fast(&page, page_index as usize, height, &font_cache);
+            let boxes = PageBoxes::read(&page, width, height);
+
+            // Each page object is at least one painting operator
+            let rasterized = options.should_rasterize(page_index, page.objects().len())
+                .then(|| rasterize_page(&page, page_index, width, height))
+                .flatten();
+            let vector_heavy = rasterized.is_some();
+            if vector_heavy {
+                rasterized_pages.fetch_add(1, Ordering::Relaxed);
+            }
+            let mut layers = match rasterized {
+                Some(layer) => vec![layer],
+                // Extract text and images
+                None => extract_page_content_fast(&page, page_index, boxes.origin(), &font_cache, &images),
+            };
 
             // Sort by z-index
             layers.sort_by_key(|l| l.z_index);
+            let layers = text_structure::merge_text_layers(layers, options.merge_level);
 
-            Some(PageData {
-                page_index: page_index as usize,
+            let mut page_data = PageData {
+                page_index: position,
                 width,
                 height,
                 dpi: Some(72),
                 layers,
-                metadata: Some(PageMetadata {
-                    original_page_index: Some(page_index as usize),
-                    rotation: None,
-                    media_box: Some([0.0, 0.0, width, height]),
-                }),
-            })
+                metadata: Some(boxes.metadata(page_index, None)),
+                background: None,
+            };
+
+            // Scans carry their text only as pixels
+            if options.auto_ocr
+                && !page_data.layers.iter().any(|l| l.layer_type == LayerType::Text)
+                && (vector_heavy || page.objects().iter().any(|o| o.object_type() == PdfPageObjectType::Image))
+            {
+                let scale = RENDER_DPI as f32 / POINTS_PER_INCH;
+                match ocr_handler::render_page_for_ocr(&page, scale) {
+                    Ok(image) => scanner::add_ocr_layers(&mut OcrEngine::new(), &mut page_data, &image, scale),
+                    Err(e) => tracing::warn!(page = page_index, "OCR render failed: {}", e),
+                }
+            }
+            let done = done_pages.fetch_add(1, Ordering::Relaxed) + 1;
+            job.progress(
+                done as f32 / page_indices.len() as f32,
+                Some(format!("Page {} of {}", done, page_indices.len())),
+            );
+            Ok(Ok(page_data))
         })
-        .filter_map(|p| p)
-        .collect();
+        .collect::<Result<_, String>>()?;
+
+    // Unreadable pages are reported and skipped, closing the gaps they leave
+    let mut pages = Vec::with_capacity(results.len());
+    for result in results {
+        match result {
+            Ok(page) => pages.push(page),
+            Err(failed) => {
+                tracing::warn!(page = failed.page_index, "skipping unreadable page: {:?}", failed.error);
+                let report = repair.get_or_insert_with(RepairReport::default);
+                report.pages.retain(|p| p.page_index != failed.page_index);
+                report.pages.push(failed);
+                report.pages.sort_by_key(|p| p.page_index);
+            }
+        }
+    }
+    for (position, page) in pages.iter_mut().enumerate() {
+        page.page_index = position;
+    }
+    prune_imported(&mut pages, options);
+
+    // Pdfium only reports label text and Info strings; label style and
+    // numbering, XMP and custom Info keys come from the lopdf object tree
+    let document_metadata = match lopdf::Document::load_filtered(file_path, document_structure_filter) {
+        Ok(doc) => {
+            if first_page.label().is_some() {
+                let labels = page_labels::read_page_labels(&doc, total_pages as usize);
+                for page in &mut pages {
+                    if let Some(metadata) = page.metadata.as_mut() {
+                        let source = metadata.original_page_index.unwrap_or(page.page_index);
+                        metadata.page_label = labels.get(source).cloned().flatten();
+                    }
+                }
+            }
+            Some(doc_metadata::read_pdf_metadata(&doc))
+        }
+        Err(e) => {
+            tracing::warn!(path = %file_path, "page labels and metadata unreadable: {}", e);
+            None
+        }
+    };
+
+    let lazy_images = image_handler::lazy_image_count();
+    if lazy_images > 0 {
+        tracing::info!(
+            lazy_images,
+            decoded_bytes = images.decoded_bytes.load(Ordering::Relaxed),
+            "deferred image decoding past memory budget"
+        );
+    }
 
     // Emit progress
     let _ = app_handle.emit(
         "parse_progress",
         serde_json::json!({
-            "currentPage": total_pages,
-            "totalPages": total_pages,
+            "currentPage": pages.len(),
+            "totalPages": pages.len(),
             "status": "Import complete"
         }),
     );
 
     Ok(DocumentResponse {
         success: true,
-        message: format!("Successfully imported {} pages", pages.len()),
-        data: Some(DocumentData {
-            page_width: default_width,
-            page_height: default_height,
-            pages,
-        }),
+        message: match rasterized_pages.into_inner() {
+            0 => format!("Successfully imported {} pages{}", pages.len(), repair_note(repair.as_ref())),
+            n => format!(
+                "Successfully imported {} pages ({} rasterized as images){}",
+                pages.len(),
+                n,
+                repair_note(repair.as_ref())
+            ),
+        },
+        data: Some(DocumentData::new(default_width, default_height, pages)),
+        repair,
+        metadata: document_metadata,
     })
 }
 
-/// Load pdfium library with fallback paths
-fn load_pdfium() -> Result<Pdfium, String> {
-    Pdfium::new(
-        Pdfium::bind_to_library(Pdfium::pdfium_platform_library_name_at_path(
-            "./lib/pdfium-v8-linux/lib/",
-        ))
-        .or_else(|_| {
-            Pdfium::bind_to_library(Pdfium::pdfium_platform_library_name_at_path(
-                "../lib/pdfium-v8-linux/lib/",
-            ))
-        })
-        .or_else(|_| Pdfium::bind_to_system_library())
-        .map_err(|e| format!("Failed to load pdfium: {}", e))?,
-    )
+/// Keep the document structure (dictionaries, arrays, object streams) and
+/// the XMP stream, dropping content and image streams
+fn document_structure_filter(
+    id: lopdf::ObjectId,
+    object: &mut lopdf::Object,
+) -> Option<(lopdf::ObjectId, lopdf::Object)> {
+    let keep = match object {
+        lopdf::Object::Stream(stream) => stream.dict.has_type(b"ObjStm") || stream.dict.has_type(b"Metadata"),
+        _ => true,
+    };
+    keep.then(|| (id, object.clone()))
+}
+
+/// Optional cleanup pass over imported pages
+fn prune_imported(pages: &mut [PageData], options: &ImportOptions) {
+    let Some(prune) = &options.prune else {
+        return;
+    };
+    let removed: usize = pages.iter_mut().map(|page| layer_cleanup::prune_page(page, prune).total()).sum();
+    if removed > 0 {
+        tracing::info!(removed, "pruned layers that paint nothing");
+    }
+}
+
+/// Optional typography cleanup of an imported document; pages without a
+/// language of their own use the document's
+fn clean_imported_typography(response: &mut DocumentResponse, options: &TypographyOptions) {
+    let Some(data) = response.data.as_mut() else {
+        return;
+    };
+    let document_language = response.metadata.as_ref().and_then(|m| m.language.as_deref());
+    let mut changed = 0;
+    for page in &mut data.pages {
+        let page_language = page.metadata.as_ref().and_then(|m| m.language.as_deref());
+        let locale = options.locale.as_deref().or(page_language).or(document_language).map(str::to_string);
+        changed += typography::clean_page(page, &TypographyOptions { locale, ..options.clone() }).len();
+    }
+    if changed > 0 {
+        tracing::info!(changed, "cleaned up typography");
+    }
+}
+
+/// Import message suffix for a repaired file
+fn repair_note(repair: Option<&RepairReport>) -> String {
+    match repair.map(|r| r.failed_pages().len()) {
+        None => String::new(),
+        Some(0) => "; damaged file repaired".to_string(),
+        Some(n) => format!("; damaged file repaired, {} unreadable pages skipped", n),
+    }
+}
+
+/// Degraded PDF parsing with lopdf when pdfium is unavailable
+///
+/// Extracts text, vector paths and images from the content streams; OCR and
+/// rasterizing are skipped, as they need pdfium to render.
+fn parse_pdf_degraded(
+    file_path: &str,
+    options: &ImportOptions,
+    reason: &str,
+    app_handle: &AppHandle,
+    job: &JobHandle,
+) -> Result<DocumentResponse, String> {
+    tracing::warn!(path = %file_path, "importing without pdfium: {}", reason);
+    let data = std::fs::read(file_path).map_err(|e| format!("Failed to read PDF: {}", e))?;
+    let (doc, report) = pdf_repair::load_repaired(&data)?;
+    let page_ids: Vec<lopdf::ObjectId> = doc.get_pages().into_values().collect();
+    let total_pages = page_ids.len();
+    let labels = page_labels::read_page_labels(&doc, total_pages);
+    let failed_pages = report.failed_pages();
+    let page_indices: Vec<usize> = options
+        .page_indices(total_pages)?
+        .into_iter()
+        .filter(|i| !failed_pages.contains(i))
+        .collect();
+    let repair = (!report.is_clean()).then_some(report);
+
+    let mut pages = Vec::with_capacity(page_indices.len());
+    for (position, &page_index) in page_indices.iter().enumerate() {
+        job.check_cancelled()?;
+        let page_id = page_ids[page_index];
+        let boxes = PageBoxes::read_lopdf(&doc, page_id);
+        let origin = boxes.origin();
+        let [x0, y0, x1, y1] = boxes.visible();
+        let (width, height) = (x1 - x0, y1 - y0);
+        // Without pdfium there is no rendering, so heavy pages lose their vectors
+        let operators = doc.get_page_content(page_id).map_or(0, |c| pdf_analyzer::count_operators(&c));
+        let vector_heavy = options.should_rasterize(page_index, operators);
+        if vector_heavy {
+            tracing::warn!(page = page_index, operators, "skipping vectors of heavy page, pdfium unavailable to rasterize");
+        }
+        // Flipping at the top of the visible box puts y in page coordinates;
+        // x is shifted once all layers are built
+        let mut layers = match content_parser::parse_page(&doc, page_id, origin.top) {
+            Ok(parsed) => {
+                let paths = if options.import_vectors() && !vector_heavy { parsed.paths } else { Vec::new() };
+                let mut layers = content_parser::to_layer_objects(parsed.texts, paths, page_index);
+                if options.import_images() {
+                    // Painting order is not tracked, so images go beneath the rest
+                    let images = degraded_image_layers(&doc, &parsed.images, page_index, options);
+                    for layer in &mut layers {
+                        layer.z_index += images.len() as i32;
+                    }
+                    layers.splice(0..0, images);
+                }
+                layers
+            }
+            Err(e) => {
+                tracing::warn!(page = page_index, "degraded page parse failed: {}", e);
+                Vec::new()
+            }
+        };
+        translate_layers(&mut layers, -origin.left, 0.0);
+        pages.push(PageData {
+            page_index: position,
+            layers: text_structure::merge_text_layers(layers, options.merge_level),
+            width,
+            height,
+            dpi: Some(72),
+            metadata: Some(boxes.metadata(page_index, labels[page_index].clone())),
+            background: None,
+        });
+
+        let _ = app_handle.emit(
+            "parse_progress",
+            serde_json::json!({
+                "currentPage": position + 1,
+                "totalPages": page_indices.len(),
+                "status": "Importing without pdfium"
+            }),
+        );
+        job.progress(
+            (position + 1) as f32 / page_indices.len() as f32,
+            Some(format!("Page {} of {}", position + 1, page_indices.len())),
+        );
+    }
+
+    prune_imported(&mut pages, options);
+
+    let (page_width, page_height) = pages.first().map_or((612.0, 792.0), |p| (p.width, p.height));
+    Ok(DocumentResponse {
+        success: true,
+        message: format!(
+            "Imported {} pages in degraded mode (pdfium unavailable){}",
+            pages.len(),
+            repair_note(repair.as_ref())
+        ),
+        data: Some(DocumentData::new(page_width, page_height, pages)),
+        repair,
+        metadata: Some(doc_metadata::read_pdf_metadata(&doc)),
+    })
+}
+
+/// Decode the images placed on a page with lopdf, soft masks included and
+/// colors left in their ICC profile's space
+fn degraded_image_layers(
+    doc: &lopdf::Document,
+    placed: &[content_parser::PlacedImage],
+    page_index: usize,
+    options: &ImportOptions,
+) -> Vec<LayerObject> {
+    let min_size = options.min_image_size();
+    let mut layers = Vec::with_capacity(placed.len());
+    let mut ids = LayerIds::new(page_index);
+    for image in placed {
+        let decoded = match pdf_images::decode_with_mask(doc, image.id) {
+            Ok(decoded) if decoded.width() >= min_size && decoded.height() >= min_size => decoded,
+            Ok(_) => continue,
+            Err(e) => {
+                tracing::warn!(page = page_index, object = image.id.0, "image skipped: {}", e);
+                continue;
+            }
+        };
+        let Some((png_data, width, height)) = encode_image(decoded.into(), options.max_image_dimension) else {
+            continue;
+        };
+        let id = ids.next("image", &format!("{}x{}", width, height), Some(&image.bounds));
+        image_handler::cache_image_with_dimensions(&id, png_data, width, height);
+
+        let Some(mut layer) = scanner::image_page(id, page_index, width, height, 72).layers.pop() else {
+            continue;
+        };
+        layer.bounds = image.bounds;
+        layer.z_index = layers.len() as i32;
+        layer.opacity = image.opacity;
+        layer.blend_mode = (image.blend_mode != BlendMode::Normal).then_some(image.blend_mode);
+        layer.source_type = SourceType::Extracted;
+        if let Some(meta) = layer.image_data.as_mut() {
+            meta.dpi = meta.effective_dpi(&image.bounds).map_or(72, |dpi| dpi.round() as u32);
+            let stream = doc.get_object(image.id).and_then(lopdf::Object::as_stream).ok();
+            let profile = stream.and_then(|stream| pdf_images::icc_profile(doc, stream));
+            meta.icc_profile = profile.map(|profile| tag_profile(&layer.id, profile));
+        }
+        layers.push(layer);
+    }
+    layers
+}
+
+/// Top-left corner of a page's visible box in PDF user space; imported
+/// layers are positioned from it
+#[derive(Debug, Clone, Copy)]
+struct PageOrigin {
+    left: f32,
+    top: f32,
+}
+
+/// Boundary boxes of a source page as `[x0, y0, x1, y1]` in PDF user space
+#[derive(Debug, Clone, Copy)]
+struct PageBoxes {
+    media: [f32; 4],
+    crop: Option<[f32; 4]>,
+    trim: Option<[f32; 4]>,
+    bleed: Option<[f32; 4]>,
+}
+
+impl PageBoxes {
+    /// Read with pdfium; `width`/`height` stand in for a missing MediaBox
+    fn read(page: &PdfPage, width: f32, height: f32) -> Self {
+        let boundaries = page.boundaries();
+        let coords = |boundary: Result<PdfPageBoundaryBox, PdfiumError>| {
+            let rect = boundary.ok()?.bounds;
+            Some([rect.left().value, rect.bottom().value, rect.right().value, rect.top().value])
+        };
+        let media = coords(boundaries.media()).unwrap_or([0.0, 0.0, width, height]);
+        Self {
+            media,
+            crop: coords(boundaries.crop()).and_then(|b| pdf_tools::clip_to_media(b, media)),
+            trim: coords(boundaries.trim()),
+            bleed: coords(boundaries.bleed()),
+        }
+    }
+
+    /// Read with lopdf, for degraded imports
+    fn read_lopdf(doc: &lopdf::Document, page_id: lopdf::ObjectId) -> Self {
+        let media = pdf_tools::read_page_box(doc, page_id, b"MediaBox").unwrap_or([0.0, 0.0, 612.0, 792.0]);
+        Self {
+            media,
+            crop: pdf_tools::crop_box(doc, page_id, media),
+            trim: pdf_tools::read_page_box(doc, page_id, b"TrimBox"),
+            bleed: pdf_tools::read_page_box(doc, page_id, b"BleedBox"),
+        }
+    }
+
+    /// The box pdfium renders and layers are positioned in
+    fn visible(&self) -> [f32; 4] {
+        self.crop.unwrap_or(self.media)
+    }
+
+    fn origin(&self) -> PageOrigin {
+        let [left, _, _, top] = self.visible();
+        PageOrigin { left, top }
+    }
+
+    fn metadata(&self, page_index: usize, page_label: Option<PageLabel>) -> PageMetadata {
+        PageMetadata {
+            original_page_index: Some(page_index),
+            rotation: None,
+            media_box: Some(self.media),
+            crop_box: self.crop,
+            trim_box: self.trim,
+            bleed_box: self.bleed,
+            page_label,
+            language: None,
+            guides: None,
+            custom: Default::default(),
+        }
+    }
 }
 
 /// Fast content extraction using pdfium only
 fn extract_page_content_fast(
     page: &PdfPage,
     page_index: usize,
-    page_height: f32,
+    origin: PageOrigin,
     font_cache: &FontCache,
+    images: &ImageImportContext,
 ) -> Vec<LayerObject> {
     let mut layers = Vec::with_capacity(64);
-    let mut text_idx = 0;
-    let mut image_idx = 0;
+    let mut ids = LayerIds::new(page_index);
 
     // Single pass through objects
-    for object in page.objects().iter() {
+    for (object_index, object) in page.objects().iter().enumerate() {
         match object.object_type() {
             PdfPageObjectType::Text => {
                 if let Some(text_obj) = object.as_text_object() {
-                    if let Some(layer) = extract_text_object(&text_obj, page_index, page_height, &mut text_idx, font_cache) {
+                    if let Some(layer) = extract_text_object(&text_obj, origin, &mut ids, object_index, font_cache) {
                         layers.push(layer);
                     }
                 }
             }
-            PdfPageObjectType::Image => {
+            PdfPageObjectType::Image if images.enabled => {
                 if let Some(image_obj) = object.as_image_object() {
-                    if let Some(layer) = extract_image_object(&image_obj, page_index, page_height, &mut image_idx) {
+                    if let Some(layer) = extract_image_object(&image_obj, page_index, origin, &mut ids, object_index, images) {
                         layers.push(layer);
                     }
                 }
@@ -219,9 +863,9 @@ fn extract_page_content_fast(
 /// Extract text object with improved detection
 fn extract_text_object(
     text_obj: &PdfPageTextObject,
-    page_index: usize,
-    page_height: f32,
-    idx: &mut usize,
+    origin: PageOrigin,
+    ids: &mut LayerIds,
+    object_index: usize,
     font_cache: &FontCache,
 ) -> Option<LayerObject> {
     let text = text_obj.text();
@@ -245,30 +889,33 @@ fn extract_text_object(
         }).clone()
     };
 
-    let color = text_obj
-        .fill_color()
+    let fill = text_obj.fill_color().ok();
+    let color = fill
+        .as_ref()
         .map(|c| format!("#{:02x}{:02x}{:02x}", c.red(), c.green(), c.blue()))
-        .unwrap_or_else(|_| "#000000".to_string());
+        .unwrap_or_else(|| "#000000".to_string());
+    // Fill alpha carries the ExtGState constant alpha (`ca`)
+    let opacity = fill.map_or(1.0, |c| c.alpha() as f32 / 255.0);
 
-    let x = bounds.left().value as f32;
+    let x = bounds.left().value as f32 - origin.left;
     let width = (bounds.right().value - bounds.left().value) as f32;
     let height = (bounds.top().value - bounds.bottom().value) as f32;
-    let y = page_height - bounds.top().value as f32 + metrics.descent;
+    let y = origin.top - bounds.top().value as f32 + metrics.descent;
+    let bounds = Bounds::new(x, y, width.max(1.0), height.max(1.0));
 
-    let z_index = LAYER_COUNTER.fetch_add(1, Ordering::Relaxed) as i32;
     let parsed = normalizer::parse_font_name(&font_name);
     let canonical_name = normalizer::get_canonical_name(&font_name);
 
-    *idx += 1;
-
     Some(LayerObject {
-        id: format!("text-{}-{}", page_index, *idx - 1),
+        id: ids.next("text", &text, Some(&bounds)),
         layer_type: LayerType::Text,
-        bounds: Bounds::new(x, y, width.max(1.0), height.max(1.0)),
+        bounds,
         visible: true,
         locked: false,
-        z_index,
-        opacity: 1.0,
+        // Painting order
+        z_index: object_index as i32,
+        opacity,
+        blend_mode: None,
         content: Some(text),
         font_family: Some(canonical_name),
         font_size: Some(font_size),
@@ -281,17 +928,27 @@ fn extract_text_object(
         line_height: None,
         letter_spacing: None,
         background_color: None,
+        text_path: None,
+        text_outline: None,
+        text_shadow: None,
+        drop_cap: None,
+        notes: Vec::new(),
+        pagination: None,
         image_url: None,
         image_path: None,
         image_data: None,
+        image_link: None,
         shape_type: None,
         stroke_color: None,
         stroke_width: None,
         fill_color: None,
+        text_wrap: None,
         path_data: None,
         transform: None,
         source_type: SourceType::Extracted,
         role: LayerRole::Content,
+        ocr: None,
+        tags: Vec::new(),
     })
 }
 
@@ -299,33 +956,65 @@ fn extract_text_object(
 fn extract_image_object(
     image_obj: &PdfPageImageObject,
     page_index: usize,
-    page_height: f32,
-    idx: &mut usize,
+    origin: PageOrigin,
+    ids: &mut LayerIds,
+    object_index: usize,
+    images: &ImageImportContext,
 ) -> Option<LayerObject> {
     let bounds = image_obj.bounds().ok()?;
-    let raw_image = image_obj.get_raw_image().ok()?;
-
-    let img_width = raw_image.width();
-    let img_height = raw_image.height();
-
-    // Skip tiny images (artifacts)
-    if img_width < 4 || img_height < 4 {
-        return None;
-    }
-
-    let layer_id = format!("image-{}-{}", page_index, *idx);
-    *idx += 1;
-
-    // Fast PNG encoding
-    let rgba_data = raw_image.to_rgba8();
-    if let Some(png_data) = encode_png_fast(&rgba_data, img_width, img_height) {
-        crate::image_handler::cache_image(&layer_id, png_data);
-    }
-
-    let x = bounds.left().value as f32;
+    let x = bounds.left().value as f32 - origin.left;
     let obj_width = (bounds.right().value - bounds.left().value) as f32;
     let obj_height = (bounds.top().value - bounds.bottom().value) as f32;
-    let y = page_height - bounds.top().value as f32;
+    let y = origin.top - bounds.top().value as f32;
+    let bounds = Bounds::new(x, y, obj_width.max(1.0), obj_height.max(1.0));
+
+    // Keyed by pixel size and placement, known before anything is decoded
+    let pixels = format!("{}x{}", image_obj.width().unwrap_or(0), image_obj.height().unwrap_or(0));
+    let layer_id = ids.next("image", &pixels, Some(&bounds));
+    let mut color_space = "RGBA";
+    let mut icc_profile = None;
+
+    let (img_width, img_height) = if images.should_defer() {
+        // Pixel size comes from the image metadata, nothing is decoded
+        let width = image_obj.width().ok()?.max(0) as u32;
+        let height = image_obj.height().ok()?.max(0) as u32;
+        if width < images.min_size || height < images.min_size {
+            return None;
+        }
+        image_handler::register_lazy_image(
+            &layer_id,
+            LazyImageSource {
+                file_path: images.file_path.to_string(),
+                page_index: page_index as u16,
+                object_index,
+                max_dimension: images.max_dimension,
+            },
+        );
+        image_handler::fit_dimensions(width, height, images.max_dimension)
+    } else if let Some((jpeg_data, info)) = passthrough_jpeg(image_obj, images.max_dimension, images.index.as_deref()) {
+        if info.width < images.min_size || info.height < images.min_size {
+            return None;
+        }
+        icc_profile = images.index.as_ref().and_then(|index| index.profile_for(&jpeg_data));
+        images.decoded_bytes.fetch_add(decoded_size(info.width, info.height), Ordering::Relaxed);
+        image_handler::cache_image_with_dimensions(&layer_id, jpeg_data, info.width, info.height);
+        color_space = if info.components == 1 { "Gray" } else { "RGB" };
+        (info.width, info.height)
+    } else {
+        let (raw_image, profile) = decode_image_object(image_obj, images.index.as_deref(), true)?;
+        icc_profile = profile;
+
+        // Skip tiny images (artifacts)
+        if raw_image.width() < images.min_size || raw_image.height() < images.min_size {
+            return None;
+        }
+
+        let (png_data, width, height) = encode_image(raw_image, images.max_dimension)?;
+        images.decoded_bytes.fetch_add(decoded_size(width, height), Ordering::Relaxed);
+        image_handler::cache_image_with_dimensions(&layer_id, png_data, width, height);
+        (width, height)
+    };
+    let icc_profile = icc_profile.map(|profile| tag_profile(&layer_id, profile));
 
     // Calculate DPI
     let dpi = if obj_width > 0.0 && obj_height > 0.0 {
@@ -336,16 +1025,16 @@ fn extract_image_object(
         72
     };
 
-    let z_index = LAYER_COUNTER.fetch_add(1, Ordering::Relaxed) as i32;
-
     Some(LayerObject {
         id: layer_id.clone(),
         layer_type: LayerType::Image,
-        bounds: Bounds::new(x, y, obj_width.max(1.0), obj_height.max(1.0)),
+        bounds,
         visible: true,
         locked: false,
-        z_index,
+        // Painting order
+        z_index: object_index as i32,
         opacity: 1.0,
+        blend_mode: None,
         content: None,
         font_family: None,
         font_size: None,
@@ -358,25 +1047,228 @@ fn extract_image_object(
         line_height: None,
         letter_spacing: None,
         background_color: None,
+        text_path: None,
+        text_outline: None,
+        text_shadow: None,
+        drop_cap: None,
+        notes: Vec::new(),
+        pagination: None,
         image_url: Some(format!("image://{}", layer_id)),
         image_path: None,
         image_data: Some(ImageMetadata {
             width: img_width,
             height: img_height,
-            color_space: "RGBA".to_string(),
+            color_space: color_space.to_string(),
             dpi,
+            icc_profile,
         }),
+        image_link: None,
         shape_type: None,
         stroke_color: None,
         stroke_width: None,
         fill_color: None,
+        text_wrap: None,
         path_data: None,
         transform: None,
         source_type: SourceType::Extracted,
         role: LayerRole::Content,
+        ocr: None,
+        tags: Vec::new(),
     })
 }
 
+/// Render a whole page as one image layer
+fn rasterize_page(page: &PdfPage, page_index: usize, width: f32, height: f32) -> Option<LayerObject> {
+    let image = match ocr_handler::render_page_for_ocr(page, RENDER_DPI as f32 / POINTS_PER_INCH) {
+        Ok(image) => image,
+        Err(e) => {
+            tracing::warn!(page = page_index, "rasterizing page failed: {}", e);
+            return None;
+        }
+    };
+    let png_data = encode_png_fast(&image, image.width(), image.height())?;
+    let id = format!("raster-{}", page_index);
+    image_handler::cache_image_with_dimensions(&id, png_data, image.width(), image.height());
+
+    let mut layer = scanner::image_page(id, page_index, image.width(), image.height(), RENDER_DPI).layers.pop()?;
+    layer.bounds = Bounds::new(0.0, 0.0, width, height);
+    layer.z_index = 0;
+    layer.source_type = SourceType::Extracted;
+    Some(layer)
+}
+
+/// The original bytes of a DCT-encoded (JPEG) image, when they can be kept as-is
+///
+/// Re-encoding photos as PNG makes them several times larger and loses the
+/// original compression. Only plain RGB or gray JPEGs that need no
+/// downsampling and have no soft mask qualify; anything else still goes
+/// through PNG.
+fn passthrough_jpeg(
+    image_obj: &PdfPageImageObject,
+    max_dimension: Option<u32>,
+    index: Option<&ImageIndex>,
+) -> Option<(Vec<u8>, image_handler::JpegInfo)> {
+    let filters = image_obj.filters();
+    if filters.len() != 1 || filters.get(0).ok()?.name() != "DCTDecode" {
+        return None;
+    }
+    if !matches!(image_obj.color_space().ok()?, PdfColorSpace::DeviceRGB | PdfColorSpace::DeviceGray) {
+        return None;
+    }
+    let data = image_obj.get_raw_image_data().ok()?;
+    // A JPEG cannot carry the mask
+    if index.is_some_and(|index| index.has_mask(&data)) {
+        return None;
+    }
+    let info = image_handler::jpeg_info(&data)?;
+    let unscaled = image_handler::fit_dimensions(info.width, info.height, max_dimension) == (info.width, info.height);
+    (unscaled && info.precision == 8 && matches!(info.components, 1 | 3)).then_some((data, info))
+}
+
+/// Decode an image object, with its soft mask (if any) as the alpha channel
+///
+/// pdfium converts colors with an embedded ICC profile to sRGB; with
+/// `keep_profile`, such images are decoded by lopdf in the profile's color
+/// space instead and the profile is returned with them.
+fn decode_image_object(
+    image_obj: &PdfPageImageObject,
+    index: Option<&ImageIndex>,
+    keep_profile: bool,
+) -> Option<(image::DynamicImage, Option<Vec<u8>>)> {
+    let raw = index.and_then(|_| image_obj.get_raw_image_data().ok());
+    let indexed = index.zip(raw.as_deref());
+    if keep_profile {
+        if let Some((index, raw)) = indexed {
+            if let Some((image, profile)) = index.decode_profiled(raw).zip(index.profile_for(raw)) {
+                return Some((image::DynamicImage::ImageRgba8(image), Some(profile)));
+            }
+        }
+    }
+    let image = image_obj.get_raw_image().ok()?;
+    let image = match indexed.and_then(|(index, raw)| index.mask_for(raw)) {
+        Some(mask) => {
+            let mut rgba = image.to_rgba8();
+            pdf_images::apply_mask(&mut rgba, &mask);
+            image::DynamicImage::ImageRgba8(rgba)
+        }
+        None => image,
+    };
+    Some((image, None))
+}
+
+/// Store an image's ICC profile and tag the cached image with it, for the
+/// preview conversion; returns the profile id
+fn tag_profile(image_id: &str, profile: Vec<u8>) -> String {
+    let profile_id = color_profile::store_profile(profile);
+    color_profile::set_image_profile(image_id, &profile_id);
+    profile_id
+}
+
+/// Downsample to `max_dimension` if needed and encode as PNG
+fn encode_image(
+    image: image::DynamicImage,
+    max_dimension: Option<u32>,
+) -> Option<(Vec<u8>, u32, u32)> {
+    let (width, height) = image_handler::fit_dimensions(image.width(), image.height(), max_dimension);
+    let image = if (width, height) != (image.width(), image.height()) {
+        image.resize_exact(width, height, image::imageops::FilterType::Triangle)
+    } else {
+        image
+    };
+    let rgba_data = image.to_rgba8();
+    let png_data = encode_png_fast(&rgba_data, width, height)?;
+    Some((png_data, width, height))
+}
+
+/// Decode a deferred image by reopening its source PDF
+fn load_lazy_image(source: &LazyImageSource) -> Option<Vec<u8>> {
+    let (reply, result) = mpsc::channel();
+    {
+        let mut worker = LAZY_WORKER.lock().ok()?;
+        let sender = worker.get_or_insert_with(spawn_lazy_worker);
+        if let Err(mpsc::SendError((source, reply))) = sender.send((source.clone(), reply)) {
+            // The worker stopped (pdfium failed to load); start another
+            worker.insert(spawn_lazy_worker()).send((source, reply)).ok()?;
+        }
+    }
+    result.recv().ok().flatten()
+}
+
+/// Documents the lazy image worker keeps open
+const LAZY_DOCUMENTS: usize = 4;
+
+type LazyRequest = (LazyImageSource, mpsc::Sender<Option<Vec<u8>>>);
+
+lazy_static::lazy_static! {
+    /// Lazy images are decoded on one thread that keeps recently used
+    /// documents open, instead of reopening the file for every image
+    static ref LAZY_WORKER: Mutex<Option<mpsc::Sender<LazyRequest>>> = Mutex::new(None);
+}
+
+struct OpenDocument<'a> {
+    path: String,
+    modified: Option<std::time::SystemTime>,
+    document: PdfDocument<'a>,
+}
+
+fn spawn_lazy_worker() -> mpsc::Sender<LazyRequest> {
+    let (sender, requests) = mpsc::channel::<LazyRequest>();
+    let spawned = std::thread::Builder::new().name("lazy-images".to_string()).spawn(move || {
+        let pdfium = match load_pdfium() {
+            Ok(pdfium) => pdfium,
+            Err(e) => {
+                tracing::warn!("lazy image decoding unavailable: {}", e);
+                return;
+            }
+        };
+        // Least recently used first
+        let mut documents: Vec<OpenDocument> = Vec::with_capacity(LAZY_DOCUMENTS);
+        for (source, reply) in requests {
+            let _ = reply.send(decode_lazy_image(&pdfium, &mut documents, &source));
+        }
+    });
+    if let Err(e) = spawned {
+        tracing::warn!("failed to start lazy image worker: {}", e);
+    }
+    sender
+}
+
+fn decode_lazy_image<'a>(
+    pdfium: &'a Pdfium,
+    documents: &mut Vec<OpenDocument<'a>>,
+    source: &LazyImageSource,
+) -> Option<Vec<u8>> {
+    // A file rewritten since it was opened is opened again
+    let modified = std::fs::metadata(&source.file_path).and_then(|m| m.modified()).ok();
+    let open = match documents.iter().position(|d| d.path == source.file_path && d.modified == modified) {
+        Some(i) => documents.remove(i),
+        None => {
+            documents.retain(|d| d.path != source.file_path);
+            OpenDocument {
+                path: source.file_path.clone(),
+                modified,
+                document: pdfium.load_pdf_from_file(&source.file_path, None).ok()?,
+            }
+        }
+    };
+    if documents.len() >= LAZY_DOCUMENTS {
+        documents.remove(0);
+    }
+    documents.push(open);
+    let document = &documents.last()?.document;
+
+    let page = document.pages().get(source.page_index).ok()?;
+    let object = page.objects().get(source.object_index).ok()?;
+    let image_obj = object.as_image_object()?;
+    let index = ImageIndex::for_file(&source.file_path);
+    if let Some((jpeg_data, _)) = passthrough_jpeg(image_obj, source.max_dimension, index.as_deref()) {
+        return Some(jpeg_data);
+    }
+    // Lazy layers carry no profile, so keep pdfium's sRGB
+    let (image, _) = decode_image_object(image_obj, index.as_deref(), false)?;
+    encode_image(image, source.max_dimension).map(|(png_data, _, _)| png_data)
+}
+
 /// Fast PNG encoding with minimal compression
 fn encode_png_fast(rgba_data: &image::RgbaImage, width: u32, height: u32) -> Option<Vec<u8>> {
     use image::ImageEncoder;
@@ -404,7 +1296,12 @@ use crate::font_manager::docx_extractor;
 use crate::models::ShapeType;
 
 /// Parse DOCX document
-async fn parse_docx(file_path: &str, app_handle: &AppHandle) -> Result<DocumentResponse, String> {
+async fn parse_docx(
+    file_path: &str,
+    page_setup: &PageSetup,
+    app_handle: &AppHandle,
+    job: &JobHandle,
+) -> Result<DocumentResponse, String> {
     use docx_rust::DocxFile;
     use docx_rust::document::BodyContent;
 
@@ -424,28 +1321,33 @@ async fn parse_docx(file_path: &str, app_handle: &AppHandle) -> Result<DocumentR
 
     let mut layers: Vec<LayerObject> = Vec::new();
     let mut layer_counter = 0;
-    let mut current_y: f32 = 72.0;
+    // Text flows, so ids come from content alone
+    let mut ids = LayerIds::new(0);
+    let content = page_setup.content_bounds();
+    let mut current_y: f32 = content.y;
 
-    let page_width: f32 = 612.0;
-    let page_margin: f32 = 72.0;
-    let content_width: f32 = page_width - (page_margin * 2.0);
+    let (page_width, page_height) = (page_setup.width, page_setup.height);
+    let page_margin: f32 = content.x;
+    let content_width: f32 = content.width;
 
     let default_font = docx_extractor::get_default_font(&docx);
 
     let body = &docx.document.body;
-    for content in &body.content {
+    for (i, content) in body.content.iter().enumerate() {
+        job.check_cancelled()?;
+        job.progress(i as f32 / body.content.len() as f32, None);
         match content {
             BodyContent::Paragraph(para) => {
                 let para_layers = parse_docx_paragraph(
                     para, &default_font, page_margin, &mut current_y,
-                    content_width, &mut layer_counter
+                    content_width, &mut layer_counter, &mut ids
                 );
                 layers.extend(para_layers);
             }
             BodyContent::Table(table) => {
                 let table_layers = parse_docx_table(
                     table, &default_font, page_margin, &mut current_y,
-                    content_width, &mut layer_counter
+                    content_width, &mut layer_counter, &mut ids
                 );
                 layers.extend(table_layers);
             }
@@ -462,24 +1364,40 @@ async fn parse_docx(file_path: &str, app_handle: &AppHandle) -> Result<DocumentR
         }),
     );
 
+    // Laid out as one long page, then paginated
+    let flow = PageData {
+        page_index: 0,
+        width: page_width,
+        height: page_height,
+        dpi: Some(72),
+        layers,
+        metadata: None,
+        background: None,
+    };
+    let pages = reflow::reflow(&[flow], page_setup);
+
     Ok(DocumentResponse {
         success: true,
-        message: format!("Successfully imported DOCX with {} layers", layer_counter),
-        data: Some(DocumentData {
-            page_width,
-            page_height: 792.0,
-            pages: vec![PageData {
-                page_index: 0,
-                width: page_width,
-                height: 792.0,
-                dpi: Some(72),
-                layers,
-                metadata: None,
-            }],
-        }),
+        message: format!("Successfully imported DOCX with {} layers on {} pages", layer_counter, pages.len()),
+        data: Some(DocumentData::new(page_width, page_height, pages)),
+        repair: None,
+        metadata: None,
     })
 }
 
+/// Keep and widow/orphan controls of a DOCX paragraph; `None` for Word's
+/// defaults (widow control on)
+fn docx_pagination(props: &docx_extractor::ParagraphInfo) -> Option<Pagination> {
+    let lines = if props.widow_control == Some(false) { 1 } else { 2 };
+    let pagination = Pagination {
+        keep_together: props.keep_lines,
+        keep_with_next: props.keep_next,
+        widows: lines,
+        orphans: lines,
+    };
+    (pagination != Pagination::default()).then_some(pagination)
+}
+
 fn parse_docx_paragraph(
     para: &docx_rust::document::Paragraph,
     default_font: &str,
@@ -487,6 +1405,7 @@ fn parse_docx_paragraph(
     current_y: &mut f32,
     max_width: f32,
     counter: &mut usize,
+    ids: &mut LayerIds,
 ) -> Vec<LayerObject> {
     use docx_rust::document::{ParagraphContent, RunContent};
 
@@ -541,13 +1460,14 @@ fn parse_docx_paragraph(
         };
 
         layers.push(LayerObject {
-            id: format!("text-0-{}", *counter),
+            id: ids.next("text", &text, None),
             layer_type: LayerType::Text,
             bounds: Bounds::new(run_x, *current_y, text_width.max(1.0), text_height),
             visible: true,
             locked: false,
             z_index: *counter as i32,
             opacity: 1.0,
+            blend_mode: None,
             content: Some(text),
             font_family: Some(canonical_font),
             font_size: Some(font_size),
@@ -562,17 +1482,27 @@ fn parse_docx_paragraph(
             line_height: para_props.line_spacing,
             letter_spacing: None,
             background_color: None,
+            text_path: None,
+            text_outline: None,
+            text_shadow: None,
+            drop_cap: None,
+            notes: Vec::new(),
+            pagination: docx_pagination(&para_props),
             image_url: None,
             image_path: None,
             image_data: None,
+            image_link: None,
             shape_type: None,
             stroke_color: None,
             stroke_width: None,
             fill_color: None,
+            text_wrap: None,
             path_data: None,
             transform: None,
             source_type: SourceType::Extracted,
             role: LayerRole::Content,
+            ocr: None,
+            tags: Vec::new(),
         });
 
         run_x += text_width;
@@ -593,6 +1523,7 @@ fn parse_docx_table(
     current_y: &mut f32,
     max_width: f32,
     counter: &mut usize,
+    ids: &mut LayerIds,
 ) -> Vec<LayerObject> {
     use docx_rust::document::{TableRowContent, TableCellContent, ParagraphContent, RunContent};
 
@@ -669,13 +1600,14 @@ fn parse_docx_table(
                         let color = font_info.color.unwrap_or_else(|| "#000000".to_string());
 
                         cell_layers.push(LayerObject {
-                            id: format!("text-0-{}", *counter),
+                            id: ids.next("text", &cell_text, None),
                             layer_type: LayerType::Text,
                             bounds: Bounds::new(cell_x + 4.0, cell_content_y, (cell_width - 8.0).max(1.0), text_height),
                             visible: true,
                             locked: false,
                             z_index: *counter as i32,
                             opacity: 1.0,
+                            blend_mode: None,
                             content: Some(cell_text),
                             font_family: Some(canonical_font),
                             font_size: Some(font_size),
@@ -692,17 +1624,27 @@ fn parse_docx_table(
                             line_height: None,
                             letter_spacing: None,
                             background_color: None,
+                            text_path: None,
+                            text_outline: None,
+                            text_shadow: None,
+                            drop_cap: None,
+                            notes: Vec::new(),
+                            pagination: None,
                             image_url: None,
                             image_path: None,
                             image_data: None,
+                            image_link: None,
                             shape_type: None,
                             stroke_color: None,
                             stroke_width: None,
                             fill_color: None,
+                            text_wrap: None,
                             path_data: None,
                             transform: None,
                             source_type: SourceType::Extracted,
                             role: LayerRole::Content,
+                            ocr: None,
+                            tags: Vec::new(),
                         });
 
                         cell_content_y += text_height + 2.0;
@@ -724,13 +1666,14 @@ fn parse_docx_table(
     let table_height = row_y - table_start_y;
     if table_height > 0.0 {
         layers.insert(0, LayerObject {
-            id: format!("table-border-0-{}", *counter),
+            id: ids.next("table-border", &format!("{} columns", num_cols), None),
             layer_type: LayerType::Shape,
             bounds: Bounds::new(x_offset, table_start_y, total_width, table_height),
             visible: true,
             locked: false,
             z_index: 0,
             opacity: 1.0,
+            blend_mode: None,
             content: None,
             font_family: None,
             font_size: None,
@@ -743,17 +1686,27 @@ fn parse_docx_table(
             line_height: None,
             letter_spacing: None,
             background_color: None,
+            text_path: None,
+            text_outline: None,
+            text_shadow: None,
+            drop_cap: None,
+            notes: Vec::new(),
+            pagination: None,
             image_url: None,
             image_path: None,
             image_data: None,
+            image_link: None,
             shape_type: Some(ShapeType::Rectangle),
             stroke_color: Some("#000000".to_string()),
             stroke_width: Some(1.0),
             fill_color: None,
+            text_wrap: None,
             path_data: None,
             transform: None,
             source_type: SourceType::Extracted,
             role: LayerRole::Content,
+            ocr: None,
+            tags: Vec::new(),
         });
         *counter += 1;
     }
@@ -761,8 +1714,3 @@ fn parse_docx_table(
     *current_y = row_y + 8.0;
     layers
 }
-
-#[inline]
-pub fn generate_layer_id(layer_type: &str, page_index: usize, seq_number: usize) -> String {
-    format!("{}-{}-{}", layer_type, page_index, seq_number)
-}
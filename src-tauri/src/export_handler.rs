@@ -1,17 +1,47 @@
 //! Export Handler Module
 //!
-//! Handles exporting documents to PDF, DOCX, and BookProject formats.
+//! Handles exporting documents to PDF, DOCX, and BookProject formats, audio
+//! proofs read by the platform speech engine, page image sequences, contact
+//! sheets and fixed-layout EPUBs.
 //!
 //! ## Optimizations
 //! - Uses `BufWriter` for efficient file I/O
 //! - Pre-sorted layers to avoid repeated sorting
 //! - Inline hints for hot paths
 
-use crate::models::{BookProjectData, DocumentMetadata, ExportResult, PageData};
+use crate::color_profile;
+use crate::contact_sheet;
+use crate::image_handler;
+use crate::image_sequence;
+use crate::job_manager::{self, JobKind, JobPriority};
+use crate::models::{
+    BlendMode, BookProjectData, Bounds, DocumentMetadata, ExportResult, FillRule, LayerObject, LayerRole, LayerType,
+    LinkedImages, Note, NoteKind, PageData, ProjectEncoding, SourceType, TextAlign,
+};
+use crate::linked_images;
+use crate::recent_projects;
 use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
 use std::fs::File;
-use std::io::{BufWriter, Write};
+use std::io::{BufRead, BufReader, BufWriter, Read, Write};
+use std::sync::Arc;
+use tauri::{AppHandle, Emitter};
 use thiserror::Error;
+use vortex_core::archive::{self, ArchiveImage, ProjectArchiveReader};
+use vortex_core::doc_metadata;
+use vortex_core::epub;
+use vortex_core::export;
+use vortex_core::msgpack;
+use vortex_core::notes;
+use vortex_core::page_labels;
+use vortex_core::page_setup;
+use vortex_core::sections;
+use vortex_core::path_ops;
+use vortex_core::speech::{self, SpeechOutput};
+use vortex_core::text_path;
+use vortex_core::text_wrap::{self, helvetica_width, HELVETICA_AVG_WIDTH};
+use vortex_core::units::{self, pt_to_mm};
+use lopdf::dictionary;
 
 /// Export-specific errors
 #[derive(Debug, Error)]
@@ -26,6 +56,14 @@ pub enum ExportError {
     PdfGeneration(String),
     #[error("DOCX generation failed: {0}")]
     DocxGeneration(String),
+    #[error("Speech export failed: {0}")]
+    Speech(String),
+    #[error("Image export failed: {0}")]
+    ImageExport(String),
+    #[error("Contact sheet export failed: {0}")]
+    ContactSheet(String),
+    #[error("EPUB export failed: {0}")]
+    Epub(String),
     #[error("JSON serialization failed: {0}")]
     Serialization(#[from] serde_json::Error),
     #[error("Unsupported export format: {0}")]
@@ -38,34 +76,7 @@ impl From<ExportError> for String {
     }
 }
 
-/// Export format options
-#[derive(Debug, Clone, Serialize, Deserialize)]
-#[serde(rename_all = "lowercase")]
-pub enum ExportFormat {
-    Pdf,
-    Docx,
-    BookProj,
-}
-
-/// Export options
-#[derive(Debug, Clone, Serialize, Deserialize)]
-#[serde(rename_all = "camelCase")]
-pub struct ExportOptions {
-    pub format: ExportFormat,
-    pub output_path: String,
-    #[serde(skip_serializing_if = "Option::is_none")]
-    pub page_range: Option<(usize, usize)>,
-    #[serde(default = "default_image_quality")]
-    pub image_quality: u8,
-    #[serde(default)]
-    pub compress_text: bool,
-    #[serde(default)]
-    pub create_layers: bool,
-}
-
-fn default_image_quality() -> u8 {
-    100
-}
+pub use vortex_core::export::{ExportColorSpace, ExportFormat, ExportOptions, Stamp, StampPosition, Watermark};
 
 /// Export a document to the specified format
 #[tauri::command]
@@ -76,30 +87,130 @@ pub async fn export_document(
     metadata: DocumentMetadata,
     options: ExportOptions,
 ) -> Result<ExportResult, String> {
-    // Spawn blocking task for CPU-intensive export operations
-    let result = tokio::task::spawn_blocking(move || {
-        match format.to_lowercase().as_str() {
+    // Queue the CPU-intensive export as a background job
+    let label = job_manager::file_label(&output_path);
+    let result = job_manager::run(JobKind::Export, label, JobPriority::Normal, move |job| {
+        let _span = tracing::info_span!("export", format = %format, path = %output_path).entered();
+        let pages = if options.show_changes && format.to_lowercase() != "bookproj" {
+            crate::change_tracker::apply_review_markup(&pages, &options.changes)
+        } else {
+            pages
+        };
+        // Filtered layers stay in the project; only this output leaves them out
+        let pages = match &options.layer_filter {
+            Some(filter) if format.to_lowercase() != "bookproj" => filter.apply(&pages),
+            _ => pages,
+        };
+        // Formats without notes of their own get them set as layers; endnote
+        // pages shift the sections that follow
+        let mut options = options;
+        let pages = match format.to_lowercase().as_str() {
+            "bookproj" | "docx" | "speech" => pages,
+            _ => {
+                let resolved = notes::resolve_notes(&pages, &options.notes.clone().unwrap_or_default());
+                options.sections = sections::remap_sections(&options.sections, &pages, &resolved);
+                resolved
+            }
+        };
+        let pages = match format.to_lowercase().as_str() {
+            "bookproj" => pages,
+            _ if options.sections.is_empty() => pages,
+            _ => sections::fill_running_heads(&pages, &options.sections),
+        };
+        let split = options.split_sections && !options.sections.is_empty();
+        let result = match format.to_lowercase().as_str() {
+            "pdf" | "docx" | "epub" if split => export_sections_sync(&format, &pages, &output_path, &metadata, &options),
             "pdf" => export_pdf_sync(&pages, &output_path, &metadata, &options),
             "docx" => export_docx_sync(&pages, &output_path, &metadata, &options),
             "bookproj" => export_bookproj_sync(&pages, &output_path, &metadata, &options),
+            "speech" => export_speech_sync(&pages, &output_path, &metadata, &options),
+            "images" => image_sequence::export_images_sync(&pages, &output_path, &metadata, &options, job),
+            "contactsheet" => contact_sheet::export_contact_sheet_sync(&pages, &output_path, &metadata, &options, job),
+            "epub" => export_epub_sync(&pages, &output_path, &metadata, &options),
             _ => Err(ExportError::UnsupportedFormat(format)),
-        }
+        };
+        // A cancelled export ends the job as cancelled, not as a failed export
+        job.check_cancelled()?;
+        Ok(result)
     })
-    .await
-    .map_err(|e| format!("Export task failed: {}", e))?;
+    .await?;
 
     match result {
         Ok(r) => Ok(r),
-        Err(e) => Ok(ExportResult {
-            success: false,
-            message: e.to_string(),
-            output_path: None,
-        }),
+        Err(e) => {
+            tracing::error!("export failed: {}", e);
+            Ok(ExportResult {
+                success: false,
+                message: e.to_string(),
+                output_path: None,
+                data: None,
+            })
+        }
+    }
+}
+
+/// Export each section to its own file, numbered after `output_path`
+/// (`book-01.pdf`, `book-02.pdf`, …); the page range is ignored
+fn export_sections_sync(
+    format: &str,
+    pages: &[PageData],
+    output_path: &str,
+    metadata: &DocumentMetadata,
+    options: &ExportOptions,
+) -> Result<ExportResult, ExportError> {
+    let infos = sections::section_infos(pages, &options.sections);
+    if infos.is_empty() {
+        return Err(ExportError::NoPages);
+    }
+    let extension = format.to_lowercase();
+    let output = std::path::Path::new(output_path).with_extension(&extension);
+    let stem = output.file_stem().map(|s| s.to_string_lossy().into_owned()).unwrap_or_default();
+
+    let mut written = Vec::with_capacity(infos.len());
+    for (i, info) in infos.iter().enumerate() {
+        let path = output.with_file_name(format!("{}-{:02}.{}", stem, i + 1, extension));
+        let path = path.to_string_lossy().into_owned();
+        let part = &pages[info.start_page..=info.end_page];
+        let part_options = ExportOptions {
+            page_range: None,
+            sections: sections::slice_sections(&options.sections, info.start_page, info.end_page),
+            ..options.clone()
+        };
+        match extension.as_str() {
+            "pdf" => export_pdf_sync(part, &path, metadata, &part_options)?,
+            "docx" => export_docx_sync(part, &path, metadata, &part_options)?,
+            _ => export_epub_sync(part, &path, metadata, &part_options)?,
+        };
+        written.push(path);
+    }
+
+    Ok(ExportResult {
+        success: true,
+        message: format!("Exported {} sections to {} files", infos.len(), written.len()),
+        output_path: written.into_iter().next(),
+        data: None,
+    })
+}
+
+/// The pages in the export's page range, or all of them
+pub(crate) fn selected_pages<'a>(
+    pages: &'a [PageData],
+    options: &ExportOptions,
+) -> Result<&'a [PageData], ExportError> {
+    match options.page_range {
+        Some((start, end)) if start > end || end >= pages.len() => Err(ExportError::InvalidPageRange(format!(
+            "Range {}-{} is invalid for {} pages",
+            start,
+            end,
+            pages.len()
+        ))),
+        Some((start, end)) => Ok(&pages[start..=end]),
+        None => Ok(pages),
     }
 }
 
 /// Synchronous PDF export (runs in blocking task)
-fn export_pdf_sync(
+pub(crate) fn export_pdf_sync(
     pages: &[PageData],
     output_path: &str,
     metadata: &DocumentMetadata,
@@ -121,23 +232,32 @@ fn export_pdf_sync(
         )));
     }
 
-    let pages_to_export: Vec<_> = pages
+    let mut pages_to_export: Vec<PageData> = pages
         .iter()
         .enumerate()
         .filter(|(i, _)| *i >= page_range.0 && *i <= page_range.1)
-        .map(|(_, p)| p)
+        .map(|(_, p)| page_setup::with_page_box(p, options.page_box))
+        .collect();
+    // Pages are first framed to the requested source box. Stamps go on the
+    // export copies (inside the trim) so the project stays clean;
+    // backgrounds become the bottom layers, then bleed grows each page and
+    // pushes edge-touching art (backgrounds included) out to the new edge
+    apply_stamps(&mut pages_to_export, &options.stamps);
+    let pages_to_export: Vec<PageData> = pages_to_export
+        .iter()
+        .map(|p| page_setup::with_bleed(&page_setup::with_background(p), options.bleed))
         .collect();
 
     if pages_to_export.is_empty() {
         return Err(ExportError::NoPages);
     }
 
-    let first_page = pages_to_export[0];
+    let first_page = &pages_to_export[0];
     let (doc, page1, layer1) = PdfDocument::new(
         &metadata.title,
-        Mm(first_page.width as f32 * 0.352778),
-        Mm(first_page.height as f32 * 0.352778),
-        "Layer 1",
+        Mm(pt_to_mm(first_page.width)),
+        Mm(pt_to_mm(first_page.height)),
+        base_layer_name(first_page, options.create_layers),
     );
 
     let mut doc = doc;
@@ -148,43 +268,373 @@ fn export_pdf_sync(
         doc = doc.with_author(&metadata.author);
     }
 
+    // Decode the watermark logo once for all pages
+    let watermark_logo = match options.watermark.as_ref().and_then(|w| w.image_id.as_deref()) {
+        Some(id) => Some(watermark_logo(id, options.watermark.as_ref().map_or(1.0, |w| w.opacity))?),
+        None => None,
+    };
+
     // Render first page
-    render_page_to_pdf(&doc, page1, layer1, first_page)
+    let mut transparency = TransparencyStates::default();
+    let mut images = ImagePatches::default();
+    render_page_to_pdf(&doc, page1, layer1, first_page, options, &mut transparency, &mut images)
         .map_err(ExportError::PdfGeneration)?;
+    if let Some(watermark) = &options.watermark {
+        let layer1 = watermark_layer(&doc, page1, layer1, options.create_layers);
+        render_watermark(&doc, page1, layer1, first_page, watermark, watermark_logo.as_ref(), options.color_space);
+    }
 
     // Add remaining pages
+    let mut page_indices = vec![page1];
     for page_data in pages_to_export.iter().skip(1) {
         let (page_idx, layer_idx) = doc.add_page(
-            Mm(page_data.width as f32 * 0.352778),
-            Mm(page_data.height as f32 * 0.352778),
-            "Layer 1",
+            Mm(pt_to_mm(page_data.width)),
+            Mm(pt_to_mm(page_data.height)),
+            base_layer_name(page_data, options.create_layers),
         );
-        render_page_to_pdf(&doc, page_idx, layer_idx, page_data)
+        page_indices.push(page_idx);
+        render_page_to_pdf(&doc, page_idx, layer_idx, page_data, options, &mut transparency, &mut images)
             .map_err(ExportError::PdfGeneration)?;
+        if let Some(watermark) = &options.watermark {
+            let layer_idx = watermark_layer(&doc, page_idx, layer_idx, options.create_layers);
+            render_watermark(&doc, page_idx, layer_idx, page_data, watermark, watermark_logo.as_ref(), options.color_space);
+        }
     }
 
-    // Save to file with buffered writer
-    let file = File::create(output_path)?;
-    let mut writer = BufWriter::with_capacity(64 * 1024, file);
-    doc.save(&mut writer)
-        .map_err(|e| ExportError::PdfGeneration(e.to_string()))?;
+    // Bookmark each titled section of the exported range
+    let exported_sections = sections::slice_sections(&options.sections, page_range.0, page_range.1);
+    for entry in sections::table_of_contents(&pages_to_export, &exported_sections) {
+        doc.add_bookmark(entry.title, page_indices[entry.start_page]);
+    }
+
+    let labels: Vec<_> = pages_to_export.iter().map(|p| p.metadata.as_ref().and_then(|m| m.page_label.clone())).collect();
+    let languages: Vec<_> = pages_to_export.iter().map(|p| p.metadata.as_ref().and_then(|m| m.language.clone())).collect();
+    let has_labels = labels.iter().any(Option::is_some);
+    let has_metadata = metadata.has_extended_fields() || languages.iter().any(Option::is_some);
+    let needs_finish =
+        !transparency.0.is_empty() || !images.is_empty() || has_labels || has_metadata || options.create_layers;
+    if needs_finish || options.encryption.is_some() || options.signature.is_some() {
+        // Transparency, soft masks, ICC profiles, page labels, metadata and security are
+        // applied to the finished file, so render into memory first
+        let mut writer = BufWriter::new(Vec::new());
+        doc.save(&mut writer)
+            .map_err(|e| ExportError::PdfGeneration(e.to_string()))?;
+        let mut pdf = writer.into_inner().map_err(|e| ExportError::FileCreation(e.into_error()))?;
+        if needs_finish {
+            pdf = finish_document(pdf, &transparency, &images, &labels, &languages, metadata, options.create_layers)
+                .map_err(ExportError::PdfGeneration)?;
+        }
+        let secured = crate::pdf_security::secure(pdf, options.encryption.as_ref(), options.signature.as_ref())
+            .map_err(ExportError::PdfGeneration)?;
+        std::fs::write(output_path, secured)?;
+    } else {
+        // Save to file with buffered writer
+        let file = File::create(output_path)?;
+        let mut writer = BufWriter::with_capacity(64 * 1024, file);
+        doc.save(&mut writer)
+            .map_err(|e| ExportError::PdfGeneration(e.to_string()))?;
+    }
 
     Ok(ExportResult {
         success: true,
         message: format!("Exported {} pages to PDF", pages_to_export.len()),
         output_path: Some(output_path.to_string()),
+        data: None,
     })
 }
 
+/// Opacity and blend mode combinations used by exported layers, as ExtGState
+/// names; printpdf has no alpha support, so the dictionaries are added to the
+/// saved file by `finish_document`
+#[derive(Default)]
+struct TransparencyStates(Vec<(f32, BlendMode)>);
+
+impl TransparencyStates {
+    /// ExtGState name for a layer, `None` when it is opaque and unblended
+    fn name(&mut self, layer: &LayerObject) -> Option<String> {
+        let blend_mode = layer.blend_mode.unwrap_or_default();
+        if layer.opacity >= 1.0 && blend_mode == BlendMode::Normal {
+            return None;
+        }
+        let opacity = layer.opacity.clamp(0.0, 1.0);
+        let index = match self.0.iter().position(|(a, m)| (*a - opacity).abs() < 0.005 && *m == blend_mode) {
+            Some(index) => index,
+            None => {
+                self.0.push((opacity, blend_mode));
+                self.0.len() - 1
+            }
+        };
+        Some(format!("RookT{}", index + 1))
+    }
+}
+
+/// Alpha channel of an exported image
+struct AlphaMask {
+    /// Exported page index
+    page: usize,
+    /// XObject resource name on that page
+    name: String,
+    width: u32,
+    height: u32,
+    alpha: Vec<u8>,
+}
+
+/// ICC profile an exported image's pixels are in
+struct ImageProfile {
+    /// Exported page index
+    page: usize,
+    /// XObject resource name on that page
+    name: String,
+    profile: Arc<Vec<u8>>,
+    /// Color components of the image and profile
+    components: u8,
+}
+
+/// Soft masks and ICC profiles for exported images; printpdf writes masks
+/// inline, which readers reject, and has no ICC-based image color spaces, so
+/// `finish_document` attaches both to the saved images
+#[derive(Default)]
+struct ImagePatches {
+    masks: Vec<AlphaMask>,
+    profiles: Vec<ImageProfile>,
+    pages: usize,
+}
+
+impl ImagePatches {
+    /// Index of the page about to be rendered
+    fn start_page(&mut self) -> usize {
+        self.pages += 1;
+        self.pages - 1
+    }
+
+    fn is_empty(&self) -> bool {
+        self.masks.is_empty() && self.profiles.is_empty()
+    }
+}
+
+/// Export pages to a PDF with default options, for tools that work on the result
+pub(crate) fn export_plain_pdf(pages: &[PageData], output_path: &str) -> Result<(), String> {
+    let options: ExportOptions = serde_json::from_value(serde_json::json!({ "format": "pdf", "outputPath": output_path }))
+        .map_err(|e| e.to_string())?;
+    export_pdf_sync(pages, output_path, &DocumentMetadata::default(), &options).map(|_| ()).map_err(|e| e.to_string())
+}
+
+/// Render one page to pixels, `scale` pixels per point, by exporting it to
+/// a temporary PDF and rasterizing that with pdfium
+pub(crate) fn render_page_image(page: &PageData, scale: f32) -> Result<image::RgbaImage, String> {
+    let path = std::env::temp_dir().join(format!("rook-render-{}-{:?}.pdf", std::process::id(), std::thread::current().id()));
+    let output_path = path.to_string_lossy().to_string();
+    let page = PageData { page_index: 0, ..page.clone() };
+    export_plain_pdf(std::slice::from_ref(&page), &output_path).map_err(|e| format!("Failed to render page: {}", e))?;
+
+    let rendered = crate::pdf_engine::load_pdfium().and_then(|pdfium| {
+        let doc = pdfium.load_pdf_from_file(&output_path, None).map_err(|e| e.to_string())?;
+        let pdf_page = doc.pages().get(0).map_err(|e| e.to_string())?;
+        crate::ocr_handler::render_page_for_ocr(&pdf_page, scale)
+    });
+    let _ = std::fs::remove_file(&path);
+    rendered
+}
+
+/// Add what printpdf cannot write (transparency states, soft masks, ICC
+/// profiles, page labels, extended metadata, page languages and document-wide
+/// optional content groups) to a saved PDF
+fn finish_document(
+    pdf: Vec<u8>,
+    states: &TransparencyStates,
+    images: &ImagePatches,
+    labels: &[Option<crate::models::PageLabel>],
+    languages: &[Option<String>],
+    metadata: &DocumentMetadata,
+    create_layers: bool,
+) -> Result<Vec<u8>, String> {
+    let mut doc = lopdf::Document::load_mem(&pdf).map_err(|e| format!("Failed to reload PDF: {}", e))?;
+    if !states.0.is_empty() {
+        add_transparency_states(&mut doc, states)?;
+    }
+    if !images.masks.is_empty() {
+        add_soft_masks(&mut doc, &images.masks)?;
+    }
+    if !images.profiles.is_empty() {
+        add_icc_profiles(&mut doc, &images.profiles)?;
+    }
+    page_labels::write_page_labels(&mut doc, labels)?;
+    if metadata.has_extended_fields() {
+        doc_metadata::write_pdf_metadata(&mut doc, metadata)?;
+    }
+    doc_metadata::write_page_languages(&mut doc, languages, metadata.language.as_deref())?;
+    if create_layers {
+        merge_optional_content(&mut doc)?;
+    }
+    let mut out = Vec::new();
+    doc.save_to(&mut out).map_err(|e| format!("Failed to write PDF: {}", e))?;
+    Ok(out)
+}
+
+/// Point every reference to a merged object at the object it merged into
+fn relink(object: &mut lopdf::Object, merged: &HashMap<lopdf::ObjectId, lopdf::ObjectId>) {
+    match object {
+        lopdf::Object::Reference(id) => {
+            if let Some(target) = merged.get(id) {
+                *id = *target;
+            }
+        }
+        lopdf::Object::Array(items) => items.iter_mut().for_each(|o| relink(o, merged)),
+        lopdf::Object::Dictionary(dict) => dict.iter_mut().for_each(|(_, o)| relink(o, merged)),
+        lopdf::Object::Stream(stream) => stream.dict.iter_mut().for_each(|(_, o)| relink(o, merged)),
+        _ => {}
+    }
+}
+
+/// printpdf gives every page its own optional content group per layer;
+/// merge groups of the same name so one switch in the reader's layer panel
+/// shows or hides that layer on every page
+fn merge_optional_content(doc: &mut lopdf::Document) -> Result<(), String> {
+    let ocgs: Vec<lopdf::ObjectId> = doc
+        .catalog()
+        .and_then(|c| c.get(b"OCProperties"))
+        .and_then(lopdf::Object::as_dict)
+        .and_then(|p| p.get(b"OCGs"))
+        .and_then(lopdf::Object::as_array)
+        .map(|refs| refs.iter().filter_map(|r| r.as_reference().ok()).collect())
+        .unwrap_or_default();
+    let mut by_name: HashMap<Vec<u8>, lopdf::ObjectId> = HashMap::new();
+    let mut groups = Vec::new();
+    let mut merged = HashMap::new();
+    for id in ocgs {
+        let name = doc.get_dictionary(id).and_then(|d| d.get(b"Name")).and_then(lopdf::Object::as_str);
+        let name = name.map(<[u8]>::to_vec).unwrap_or_default();
+        match by_name.get(&name) {
+            Some(&target) => {
+                merged.insert(id, target);
+            }
+            None => {
+                by_name.insert(name, id);
+                groups.push(lopdf::Object::Reference(id));
+            }
+        }
+    }
+    if merged.is_empty() {
+        return Ok(());
+    }
+    for id in merged.keys() {
+        doc.objects.remove(id);
+    }
+    for object in doc.objects.values_mut() {
+        relink(object, &merged);
+    }
+    let properties = dictionary! {
+        "OCGs" => groups.clone(),
+        "D" => dictionary! { "Order" => groups.clone(), "ON" => groups, "RBGroups" => Vec::<lopdf::Object>::new() },
+    };
+    doc.catalog_mut().map_err(|e| e.to_string())?.set("OCProperties", properties);
+    Ok(())
+}
+
+/// Constant alpha, blend modes and soft masks need PDF 1.4
+fn require_transparency(doc: &mut lopdf::Document) {
+    if doc.version.as_str() < "1.4" {
+        doc.version = "1.4".to_string();
+    }
+}
+
+/// Register the transparency graphics states on every page
+fn add_transparency_states(doc: &mut lopdf::Document, states: &TransparencyStates) -> Result<(), String> {
+    require_transparency(doc);
+    let ids: Vec<_> = states
+        .0
+        .iter()
+        .map(|&(opacity, blend_mode)| doc.add_object(crate::pdf_tools::transparency_state(opacity, blend_mode)))
+        .collect();
+    let pages: Vec<_> = doc.get_pages().into_values().collect();
+    for page_id in pages {
+        for (i, id) in ids.iter().enumerate() {
+            crate::pdf_tools::add_resource(doc, page_id, "ExtGState", &format!("RookT{}", i + 1), *id)?;
+        }
+    }
+    Ok(())
+}
+
+/// Saved image stream behind an exported image's resource name
+fn exported_image_id(doc: &lopdf::Document, pages: &[lopdf::ObjectId], page: usize, name: &str) -> Result<lopdf::ObjectId, String> {
+    pages
+        .get(page)
+        .and_then(|&page_id| crate::pdf_tools::resource_id(doc, page_id, "XObject", name))
+        .ok_or_else(|| format!("Image {} on page {} not found", name, page + 1))
+}
+
+/// Attach each image's alpha channel to it as an SMask
+fn add_soft_masks(doc: &mut lopdf::Document, masks: &[AlphaMask]) -> Result<(), String> {
+    require_transparency(doc);
+    let pages: Vec<_> = doc.get_pages().into_values().collect();
+    for mask in masks {
+        let image_id = exported_image_id(doc, &pages, mask.page, &mask.name)?;
+        let mut smask = lopdf::Stream::new(
+            dictionary! {
+                "Type" => "XObject",
+                "Subtype" => "Image",
+                "Width" => mask.width as i64,
+                "Height" => mask.height as i64,
+                "ColorSpace" => "DeviceGray",
+                "BitsPerComponent" => 8,
+            },
+            mask.alpha.clone(),
+        );
+        let _ = smask.compress();
+        let smask_id = doc.add_object(smask);
+        doc.get_object_mut(image_id)
+            .and_then(lopdf::Object::as_stream_mut)
+            .map_err(|e| e.to_string())?
+            .dict
+            .set("SMask", smask_id);
+    }
+    Ok(())
+}
+
+/// Give each profiled image an ICCBased color space; identical profiles
+/// share one stream
+fn add_icc_profiles(doc: &mut lopdf::Document, profiles: &[ImageProfile]) -> Result<(), String> {
+    let pages: Vec<_> = doc.get_pages().into_values().collect();
+    let mut streams: Vec<(&Arc<Vec<u8>>, lopdf::ObjectId)> = Vec::new();
+    for image in profiles {
+        let image_id = exported_image_id(doc, &pages, image.page, &image.name)?;
+        let stream_id = match streams.iter().find(|(profile, _)| Arc::ptr_eq(profile, &image.profile)) {
+            Some(&(_, id)) => id,
+            None => {
+                let alternate = if image.components == 1 { "DeviceGray" } else { "DeviceRGB" };
+                let mut stream = lopdf::Stream::new(
+                    dictionary! { "N" => image.components as i64, "Alternate" => alternate },
+                    image.profile.to_vec(),
+                );
+                let _ = stream.compress();
+                let id = doc.add_object(stream);
+                streams.push((&image.profile, id));
+                id
+            }
+        };
+        doc.get_object_mut(image_id)
+            .and_then(lopdf::Object::as_stream_mut)
+            .map_err(|e| e.to_string())?
+            .dict
+            .set("ColorSpace", vec![lopdf::Object::from("ICCBased"), stream_id.into()]);
+    }
+    Ok(())
+}
+
 fn render_page_to_pdf(
     doc: &printpdf::PdfDocumentReference,
     page_idx: printpdf::PdfPageIndex,
     layer_idx: printpdf::PdfLayerIndex,
     page: &PageData,
+    options: &ExportOptions,
+    states: &mut TransparencyStates,
+    images: &mut ImagePatches,
 ) -> Result<(), String> {
     use printpdf::*;
 
-    let layer = doc.get_page(page_idx).get_layer(layer_idx);
+    let color_space = options.color_space;
+    let mut layer = doc.get_page(page_idx).get_layer(layer_idx);
+    let mut pdf_layer = base_layer_name(page, options.create_layers);
     let font = doc
         .add_builtin_font(BuiltinFont::Helvetica)
         .map_err(|e| e.to_string())?;
@@ -195,14 +645,36 @@ fn render_page_to_pdf(
     // Sort layers by z-index for proper rendering order
     let mut sorted_layers: Vec<_> = page.layers.iter().filter(|l| l.visible).collect();
     sorted_layers.sort_by_key(|l| l.z_index);
+    // printpdf names a page's XObjects X0, X1, ... in the order they are added
+    let page_number = images.start_page();
+    let mut xobjects = 0;
 
     for layer_obj in sorted_layers {
+        // Runs of layers in the same group share a PDF layer; the first run
+        // goes into the page's base layer, which is named after it
+        if options.create_layers {
+            let name = export::pdf_layer_name(layer_obj);
+            if name != pdf_layer {
+                layer = doc.get_page(page_idx).add_layer(name.as_str());
+                pdf_layer = name;
+            }
+        }
+
+        // Each transparent layer gets its own graphics state scope
+        let transparency = states.name(layer_obj);
+        if let Some(name) = &transparency {
+            layer.save_graphics_state();
+            layer.add_operation(printpdf::lopdf::content::Operation::new(
+                "gs",
+                vec![printpdf::lopdf::Object::Name(name.clone().into_bytes())],
+            ));
+        }
+
         match layer_obj.layer_type.to_string().as_str() {
             "text" => {
                 if let Some(content) = &layer_obj.content {
                     let font_size = layer_obj.font_size.unwrap_or(12.0);
-                    let x = Mm(layer_obj.bounds.x as f32 * 0.352778);
-                    let y = Mm((page.height - layer_obj.bounds.y - font_size as f32) * 0.352778);
+                    let x = Mm(pt_to_mm(layer_obj.bounds.x));
 
                     // Use bold font if weight >= 700
                     let use_font = if layer_obj.font_weight.unwrap_or(400) >= 700 {
@@ -211,87 +683,592 @@ fn render_page_to_pdf(
                         &font
                     };
 
+                    let exclusions = text_wrap::page_exclusions(page, layer_obj);
+                    // A drop cap is written on its own, at its own size
+                    let drop_cap = match &layer_obj.text_path {
+                        Some(_) => None,
+                        None => text_wrap::layout_drop_cap(layer_obj, content, &exclusions),
+                    };
+
+                    // (text, origin x, baseline y, rotation in degrees) per run: one
+                    // run for straight text, one per glyph when following a path
+                    let runs: Vec<(String, f32, f32, f32)> = match &layer_obj.text_path {
+                        Some(text_path) => {
+                            let spacing = layer_obj.letter_spacing.unwrap_or(0.0);
+                            let chars: Vec<char> = content.chars().filter(|c| !c.is_control()).collect();
+                            let advances: Vec<f32> =
+                                chars.iter().map(|&c| helvetica_width(c) * font_size + spacing).collect();
+                            let align = layer_obj.text_align.unwrap_or_default();
+                            text_path::place_glyphs(&text_path.path, &advances, text_path.start_offset, align)
+                                .into_iter()
+                                .zip(chars)
+                                .filter_map(|(glyph, c)| {
+                                    let g = glyph?;
+                                    Some((c.to_string(), g.x, page.height - g.y, -g.angle.to_degrees()))
+                                })
+                                .collect()
+                        }
+                        None => {
+                            if let Some((_, layout)) = &drop_cap {
+                                layout
+                                    .lines
+                                    .iter()
+                                    .map(|line| (line.text.clone(), line.x, page.height - line.y - font_size, 0.0))
+                                    .collect()
+                            } else if exclusions.is_empty() {
+                                vec![(content.clone(), layer_obj.bounds.x, page.height - layer_obj.bounds.y - font_size, 0.0)]
+                            } else {
+                                // Flow the text line by line around wrapping layers
+                                let spacing = layer_obj.letter_spacing.unwrap_or(0.0);
+                                let measure =
+                                    |s: &str| s.chars().map(|c| helvetica_width(c) * font_size + spacing).sum::<f32>();
+                                let line_height = font_size * layer_obj.line_height.unwrap_or(1.2);
+                                let align = layer_obj.text_align.unwrap_or_default();
+                                text_wrap::layout_frame(content, layer_obj.bounds, line_height, align, &exclusions, measure)
+                                    .lines
+                                    .into_iter()
+                                    .map(|line| (line.text, line.x, page.height - line.y - font_size, 0.0))
+                                    .collect()
+                            }
+                        }
+                    };
+                    let draw_runs = |dx: f32, dy: f32| {
+                        layer.begin_text_section();
+                        layer.set_font(use_font, font_size);
+                        for (text, x, y, rotation) in &runs {
+                            let (x, y) = (Pt(x + dx), Pt(y + dy));
+                            layer.set_text_matrix(match *rotation {
+                                0.0 => TextMatrix::Translate(x, y),
+                                _ => TextMatrix::TranslateRotate(x, y, *rotation),
+                            });
+                            write_marked_text(&layer, text, use_font, font_size);
+                        }
+                        if let Some((cap, _)) = &drop_cap {
+                            layer.set_font(use_font, cap.font_size);
+                            layer.set_text_matrix(TextMatrix::Translate(Pt(cap.x + dx), Pt(page.height - cap.baseline + dy)));
+                            layer.write_text(cap.text.as_str(), use_font);
+                        }
+                        layer.end_text_section();
+                    };
+
+                    // Drop shadow underneath; PDF has no blur, so it is a hard offset copy
+                    if let Some(shadow) = &layer_obj.text_shadow {
+                        if let Some((r, g, b)) = parse_hex_color(&shadow.color) {
+                            layer.set_fill_color(pdf_color(r, g, b, color_space));
+                            draw_runs(shadow.offset_x, -shadow.offset_y);
+                        }
+                    }
+
                     // Set text color if specified
                     if let Some(color) = &layer_obj.color {
                         if let Some((r, g, b)) = parse_hex_color(color) {
-                            layer.set_fill_color(Color::Rgb(Rgb::new(
-                                r as f32 / 255.0,
-                                g as f32 / 255.0,
-                                b as f32 / 255.0,
-                                None,
-                            )));
+                            layer.set_fill_color(pdf_color(r, g, b, color_space));
+                        }
+                    }
+
+                    match layer_obj.text_outline.as_ref().filter(|o| o.width > 0.0) {
+                        Some(outline) => {
+                            if let Some((r, g, b)) = parse_hex_color(&outline.color) {
+                                layer.set_outline_color(pdf_color(r, g, b, color_space));
+                            }
+                            layer.set_outline_thickness(outline.width);
+                            layer.set_text_rendering_mode(TextRenderingMode::FillStroke);
+                            draw_runs(0.0, 0.0);
+                            layer.set_text_rendering_mode(TextRenderingMode::Fill);
                         }
+                        None => draw_runs(0.0, 0.0),
                     }
 
-                    layer.use_text(content, font_size as f32, x, y, use_font);
+                    // Underline / strike-through as a rule across the text box
+                    let single_line = runs.len() == 1 && layer_obj.text_path.is_none();
+                    let decoration = layer_obj.text_decoration.as_deref().filter(|_| single_line);
+                    let rule_y = match decoration {
+                        Some("underline") => Some(page.height - layer_obj.bounds.y - font_size - 1.5),
+                        Some("line-through") => {
+                            Some(page.height - layer_obj.bounds.y - font_size * 0.7)
+                        }
+                        _ => None,
+                    };
+                    if let Some(rule_y) = rule_y {
+                        if let Some((r, g, b)) = layer_obj.color.as_deref().and_then(parse_hex_color) {
+                            layer.set_outline_color(pdf_color(r, g, b, color_space));
+                        }
+                        layer.set_outline_thickness((font_size / 16.0).max(0.5));
+                        layer.add_line(Line {
+                            points: vec![
+                                (Point::new(x, Mm(pt_to_mm(rule_y))), false),
+                                (
+                                    Point::new(
+                                        Mm(pt_to_mm(layer_obj.bounds.x + layer_obj.bounds.width)),
+                                        Mm(pt_to_mm(rule_y)),
+                                    ),
+                                    false,
+                                ),
+                            ],
+                            is_closed: false,
+                        });
+                    }
                 }
             }
             "shape" => {
+                use printpdf::path::{PaintMode, WindingOrder};
+
                 // Render shapes
-                let x = Mm(layer_obj.bounds.x as f32 * 0.352778);
-                let y = Mm((page.height - layer_obj.bounds.y - layer_obj.bounds.height) * 0.352778);
-                let w = Mm(layer_obj.bounds.width as f32 * 0.352778);
-                let h = Mm(layer_obj.bounds.height as f32 * 0.352778);
+                let x = Mm(pt_to_mm(layer_obj.bounds.x));
+                let y = Mm(pt_to_mm(page.height - layer_obj.bounds.y - layer_obj.bounds.height));
+                let w = Mm(pt_to_mm(layer_obj.bounds.width));
+                let h = Mm(pt_to_mm(layer_obj.bounds.height));
 
                 // Set fill color
                 if let Some(fill) = &layer_obj.fill_color {
                     if let Some((r, g, b)) = parse_hex_color(fill) {
-                        layer.set_fill_color(Color::Rgb(Rgb::new(
-                            r as f32 / 255.0,
-                            g as f32 / 255.0,
-                            b as f32 / 255.0,
-                            None,
-                        )));
+                        layer.set_fill_color(pdf_color(r, g, b, color_space));
                     }
                 }
 
                 // Set stroke color and width
                 if let Some(stroke) = &layer_obj.stroke_color {
                     if let Some((r, g, b)) = parse_hex_color(stroke) {
-                        layer.set_outline_color(Color::Rgb(Rgb::new(
-                            r as f32 / 255.0,
-                            g as f32 / 255.0,
-                            b as f32 / 255.0,
-                            None,
-                        )));
+                        layer.set_outline_color(pdf_color(r, g, b, color_space));
                     }
                 }
                 
                 let stroke_width = layer_obj.stroke_width.unwrap_or(1.0);
                 layer.set_outline_thickness(stroke_width);
 
-                // Draw rectangle using line operations
                 let points = vec![
                     (Point::new(x, y), false),
                     (Point::new(x + w, y), false),
                     (Point::new(x + w, y + h), false),
                     (Point::new(x, y + h), false),
                 ];
-                
-                let line = Line {
-                    points,
-                    is_closed: true,
+
+                // Filled when a fill color is set; outlined unless the stroke is zero
+                let has_fill = layer_obj.fill_color.as_deref().and_then(parse_hex_color).is_some();
+                let mode = match (has_fill, stroke_width > 0.0) {
+                    (true, true) => PaintMode::FillStroke,
+                    (true, false) => PaintMode::Fill,
+                    (false, _) => PaintMode::Stroke,
                 };
-                
-                layer.add_line(line);
+                layer.add_polygon(Polygon {
+                    rings: vec![points],
+                    mode,
+                    winding_order: WindingOrder::NonZero,
+                });
             }
             "image" => {
-                // Image embedding in printpdf 0.7 requires specific decoder setup
-                // For now, skip image embedding - images will need to be re-added manually
-                // TODO: Implement proper image embedding with printpdf's RawImage API
+                let Some((mut image, alpha)) = layer_image_xobject(layer_obj) else {
+                    tracing::warn!(layer = %layer_obj.id, "image unavailable for PDF export");
+                    if transparency.is_some() {
+                        layer.restore_graphics_state();
+                    }
+                    continue;
+                };
+                let profile = layer_icc_profile(layer_obj, &mut image);
+                let b = layer_obj.bounds;
+                let (px_w, px_h) = (image.width.0.max(1) as f32, image.height.0.max(1) as f32);
+                // At 72 dpi one pixel is one point; scale from there to the layer box
+                Image::from(image).add_to_layer(
+                    layer.clone(),
+                    ImageTransform {
+                        translate_x: Some(Mm(pt_to_mm(b.x))),
+                        translate_y: Some(Mm(pt_to_mm(page.height - b.y - b.height))),
+                        scale_x: Some(b.width / px_w),
+                        scale_y: Some(b.height / px_h),
+                        dpi: Some(units::POINTS_PER_INCH),
+                        ..Default::default()
+                    },
+                );
+                let name = format!("X{}", xobjects);
+                xobjects += 1;
+                if let Some((profile, components)) = profile {
+                    images.profiles.push(ImageProfile { page: page_number, name: name.clone(), profile, components });
+                }
+                if let Some(alpha) = alpha {
+                    images.masks.push(AlphaMask { page: page_number, name, width: px_w as u32, height: px_h as u32, alpha });
+                }
+            }
+            "vector" => {
+                use printpdf::lopdf::content::Operation;
+
+                if let Some(path) = layer_obj.path_data.as_ref().filter(|p| !p.commands.is_empty()) {
+                    let fill = layer_obj.fill_color.as_deref().and_then(parse_hex_color);
+                    let stroke = layer_obj.stroke_color.as_deref().and_then(parse_hex_color);
+                    layer.save_graphics_state();
+                    // Unpainted paths fill black, as PDF does
+                    let fill = fill.or(stroke.is_none().then_some((0, 0, 0)));
+                    if let Some((r, g, b)) = fill {
+                        layer.set_fill_color(pdf_color(r, g, b, color_space));
+                    }
+                    if let Some((r, g, b)) = stroke {
+                        layer.set_outline_color(pdf_color(r, g, b, color_space));
+                        layer.set_outline_thickness(layer_obj.stroke_width.unwrap_or(1.0));
+                    }
+                    for (operator, operands) in path_ops::pdf_path_operators(&path.commands, page.height) {
+                        layer.add_operation(Operation::new(operator, operands.into_iter().map(Into::into).collect()));
+                    }
+                    let even_odd = path.fill_rule == Some(FillRule::EvenOdd);
+                    layer.add_operation(Operation::new(
+                        path_ops::pdf_paint_operator(fill.is_some(), stroke.is_some(), even_odd),
+                        vec![],
+                    ));
+                    layer.restore_graphics_state();
+                }
             }
             _ => {
                 // Skip other layer types
             }
         }
+
+        if transparency.is_some() {
+            layer.restore_graphics_state();
+        }
     }
 
     Ok(())
 }
 
+/// Write `text` at the text cursor, setting note reference marks (superscript
+/// digits, which the built-in fonts lack) as small raised figures
+fn write_marked_text(
+    layer: &printpdf::PdfLayerReference,
+    text: &str,
+    font: &printpdf::IndirectFontRef,
+    font_size: f32,
+) {
+    let is_mark = |c: char| notes::from_superscript(c).is_some();
+    let mut rest = text;
+    while !rest.is_empty() {
+        let start = rest.find(is_mark).unwrap_or(rest.len());
+        if start > 0 {
+            layer.write_text(&rest[..start], font);
+        }
+        rest = &rest[start..];
+        let end = rest.find(|c| !is_mark(c)).unwrap_or(rest.len());
+        if end > 0 {
+            let digits: String = rest[..end].chars().filter_map(notes::from_superscript).collect();
+            layer.set_font(font, font_size * 0.6);
+            layer.set_line_offset(font_size * 0.35);
+            layer.write_text(digits.as_str(), font);
+            layer.set_line_offset(0.0);
+            layer.set_font(font, font_size);
+        }
+        rest = &rest[end..];
+    }
+}
+
+/// Background-role text layers for `stamps` on the `index`-th of `total`
+/// exported pages
+pub fn stamp_layers(page: &PageData, index: usize, total: usize, stamps: &[Stamp]) -> Vec<LayerObject> {
+    stamps
+        .iter()
+        .enumerate()
+        .map(|(k, stamp)| {
+            let text = stamp.text_for(index, total);
+            let width = text.chars().count() as f32 * stamp.font_size * HELVETICA_AVG_WIDTH;
+            let height = stamp.font_size * 1.2;
+            let (x, align) = match stamp.position {
+                StampPosition::TopLeft | StampPosition::BottomLeft => (stamp.margin, TextAlign::Left),
+                StampPosition::TopCenter | StampPosition::BottomCenter => {
+                    ((page.width - width) / 2.0, TextAlign::Center)
+                }
+                StampPosition::TopRight | StampPosition::BottomRight => {
+                    (page.width - stamp.margin - width, TextAlign::Right)
+                }
+            };
+            let y = match stamp.position {
+                StampPosition::TopLeft | StampPosition::TopCenter | StampPosition::TopRight => stamp.margin,
+                _ => page.height - stamp.margin - height,
+            };
+
+            LayerObject {
+                id: format!("stamp-{}-{}", page.page_index, k),
+                layer_type: LayerType::Text,
+                bounds: Bounds::new(x, y, width, height),
+                visible: true,
+                locked: true,
+                // Above all page content
+                z_index: i32::MAX - (stamps.len() - k) as i32,
+                opacity: 1.0,
+                blend_mode: None,
+                content: Some(text),
+                font_family: Some("Arial".to_string()),
+                font_size: Some(stamp.font_size),
+                font_weight: Some(400),
+                font_style: None,
+                color: Some(stamp.color.clone()),
+                text_align: Some(align),
+                text_decoration: None,
+                text_transform: None,
+                line_height: None,
+                letter_spacing: None,
+                background_color: None,
+                text_path: None,
+                text_outline: None,
+                text_shadow: None,
+                drop_cap: None,
+                notes: Vec::new(),
+                pagination: None,
+                image_url: None,
+                image_path: None,
+                image_data: None,
+                image_link: None,
+                shape_type: None,
+                stroke_color: None,
+                stroke_width: None,
+                fill_color: None,
+                text_wrap: None,
+                path_data: None,
+                transform: None,
+                source_type: SourceType::Manual,
+                role: LayerRole::Background,
+                ocr: None,
+                tags: Vec::new(),
+            }
+        })
+        .collect()
+}
+
+/// Add stamp layers to pages being exported (never to project pages)
+pub fn apply_stamps(pages: &mut [PageData], stamps: &[Stamp]) {
+    if stamps.is_empty() {
+        return;
+    }
+    let total = pages.len();
+    for (index, page) in pages.iter_mut().enumerate() {
+        let layers = stamp_layers(page, index, total, stamps);
+        page.layers.extend(layers);
+    }
+}
+
+/// Mix a color toward white; with a multiply blend this reads as opacity
+#[inline]
+fn fade(channel: u8, opacity: f32) -> u8 {
+    (255.0 - (255.0 - channel as f32) * opacity.clamp(0.0, 1.0)).round() as u8
+}
+
+/// Decode an image layer into an RGB image plus its alpha channel, when it
+/// has any transparency
+///
+/// JPEGs have no transparency and are embedded unchanged.
+fn layer_image_xobject(layer: &LayerObject) -> Option<(printpdf::ImageXObject, Option<Vec<u8>>)> {
+    let bytes = image_handler::layer_image_bytes(layer)?;
+    if let Some(jpeg) = jpeg_xobject(&bytes) {
+        return Some((jpeg, None));
+    }
+    let image = image::load_from_memory(&bytes).ok()?.to_rgba8();
+    let mut rgb = Vec::with_capacity(image.width() as usize * image.height() as usize * 3);
+    let mut alpha = Vec::with_capacity(image.width() as usize * image.height() as usize);
+    for p in image.pixels() {
+        rgb.extend_from_slice(&p.0[..3]);
+        alpha.push(p.0[3]);
+    }
+    let alpha = alpha.iter().any(|&a| a < 255).then_some(alpha);
+    let xobject = printpdf::ImageXObject {
+        width: printpdf::Px(image.width() as usize),
+        height: printpdf::Px(image.height() as usize),
+        color_space: printpdf::ColorSpace::Rgb,
+        bits_per_component: printpdf::ColorBits::Bit8,
+        interpolate: true,
+        image_data: rgb,
+        image_filter: None,
+        smask: None,
+        clipping_bbox: None,
+    };
+    Some((xobject, alpha))
+}
+
+/// Embed RGB or gray JPEG bytes as a DCTDecode image without re-encoding them
+fn jpeg_xobject(bytes: &[u8]) -> Option<printpdf::ImageXObject> {
+    let info = image_handler::jpeg_info(bytes).filter(|info| info.precision == 8)?;
+    let color_space = match info.components {
+        1 => printpdf::ColorSpace::Greyscale,
+        3 => printpdf::ColorSpace::Rgb,
+        _ => return None,
+    };
+    Some(printpdf::ImageXObject {
+        width: printpdf::Px(info.width as usize),
+        height: printpdf::Px(info.height as usize),
+        color_space,
+        bits_per_component: printpdf::ColorBits::Bit8,
+        interpolate: true,
+        image_data: bytes.to_vec(),
+        image_filter: Some(printpdf::ImageFilter::DCT),
+        smask: None,
+        clipping_bbox: None,
+    })
+}
+
+/// Stored ICC profile of an image layer, when it matches the exported
+/// image's color components
+///
+/// Images from gray profiles are decoded to RGB; they go back to gray here.
+fn layer_icc_profile(layer: &LayerObject, image: &mut printpdf::ImageXObject) -> Option<(Arc<Vec<u8>>, u8)> {
+    let profile = color_profile::profile(layer.image_data.as_ref()?.icc_profile.as_deref()?)?;
+    let components = color_profile::components(&profile)?;
+    if components == 1 && matches!(image.color_space, printpdf::ColorSpace::Rgb) && image.image_filter.is_none() {
+        image.image_data = image.image_data.iter().step_by(3).copied().collect();
+        image.color_space = printpdf::ColorSpace::Greyscale;
+    }
+    let expected = match image.color_space {
+        printpdf::ColorSpace::Greyscale => 1,
+        printpdf::ColorSpace::Rgb => 3,
+        _ => return None,
+    };
+    (components == expected).then_some((profile, components))
+}
+
+/// Decode a cached logo into an opaque RGB image faded for watermarking
+fn watermark_logo(image_id: &str, opacity: f32) -> Result<printpdf::ImageXObject, ExportError> {
+    let bytes = image_handler::get_image_bytes(image_id)
+        .ok_or_else(|| ExportError::PdfGeneration(format!("Watermark image not found: {}", image_id)))?;
+    let image = image::load_from_memory(&bytes)
+        .map_err(|e| ExportError::PdfGeneration(format!("Watermark image: {}", e)))?
+        .to_rgba8();
+
+    // Transparent pixels become white, which the multiply blend hides
+    let mut rgb = Vec::with_capacity(image.width() as usize * image.height() as usize * 3);
+    for p in image.pixels() {
+        let alpha = opacity * p.0[3] as f32 / 255.0;
+        rgb.extend(p.0[..3].iter().map(|&c| fade(c, alpha)));
+    }
+    Ok(printpdf::ImageXObject {
+        width: printpdf::Px(image.width() as usize),
+        height: printpdf::Px(image.height() as usize),
+        color_space: printpdf::ColorSpace::Rgb,
+        bits_per_component: printpdf::ColorBits::Bit8,
+        interpolate: true,
+        image_data: rgb,
+        image_filter: None,
+        smask: None,
+        clipping_bbox: None,
+    })
+}
+
+/// Name of a page's first PDF layer: with `create_layers` the group of its
+/// bottom visible layer, which `render_page_to_pdf` draws first
+fn base_layer_name(page: &PageData, create_layers: bool) -> String {
+    page.layers
+        .iter()
+        .filter(|l| create_layers && l.visible)
+        .min_by_key(|l| l.z_index)
+        .map_or_else(|| "Layer 1".to_string(), export::pdf_layer_name)
+}
+
+/// Layer a page's watermark goes on: its own "Watermark" PDF layer with
+/// `create_layers`, so it can be switched off
+fn watermark_layer(
+    doc: &printpdf::PdfDocumentReference,
+    page_idx: printpdf::PdfPageIndex,
+    layer_idx: printpdf::PdfLayerIndex,
+    create_layers: bool,
+) -> printpdf::PdfLayerIndex {
+    if create_layers {
+        doc.get_page(page_idx).add_layer("Watermark").layer
+    } else {
+        layer_idx
+    }
+}
+
+/// Draw a watermark centered on the page along its diagonal
+fn render_watermark(
+    doc: &printpdf::PdfDocumentReference,
+    page_idx: printpdf::PdfPageIndex,
+    layer_idx: printpdf::PdfLayerIndex,
+    page: &PageData,
+    watermark: &Watermark,
+    logo: Option<&printpdf::ImageXObject>,
+    color_space: ExportColorSpace,
+) {
+    use printpdf::*;
+
+    let layer = doc.get_page(page_idx).get_layer(layer_idx);
+    let angle = watermark
+        .angle
+        .unwrap_or_else(|| page.height.atan2(page.width).to_degrees());
+    let length = watermark.scale * page.width.hypot(page.height);
+    let (cx, cy) = (page.width / 2.0, page.height / 2.0);
+
+    layer.save_graphics_state();
+    layer.set_blend_mode(BlendMode::Seperable(SeperableBlendMode::Multiply));
+
+    if let Some(logo) = logo {
+        let (px_w, px_h) = (logo.width.0 as f32, logo.height.0 as f32);
+        // Fit the logo's width to the stamp length without leaving the page
+        let width = length.min(page.width.min(page.height) * px_w / px_h.max(1.0));
+        let dpi = px_w * units::POINTS_PER_INCH / width.max(1.0);
+        let height = px_h * units::POINTS_PER_INCH / dpi;
+        Image::from(logo.clone()).add_to_layer(
+            layer.clone(),
+            ImageTransform {
+                translate_x: Some(Mm(pt_to_mm(cx - width / 2.0))),
+                translate_y: Some(Mm(pt_to_mm(cy - height / 2.0))),
+                rotate: Some(ImageRotation {
+                    angle_ccw_degrees: angle,
+                    rotation_center_x: Px(logo.width.0 / 2),
+                    rotation_center_y: Px(logo.height.0 / 2),
+                }),
+                dpi: Some(dpi),
+                ..Default::default()
+            },
+        );
+    }
+
+    if let Some(text) = watermark.text.as_deref().filter(|t| !t.trim().is_empty()) {
+        if let Ok(font) = doc.add_builtin_font(BuiltinFont::HelveticaBold) {
+            let chars = text.chars().count().max(1) as f32;
+            let font_size = length / (chars * HELVETICA_AVG_WIDTH * 1.2);
+            let width = chars * font_size * HELVETICA_AVG_WIDTH * 1.2;
+            let cap_height = font_size * 0.7;
+            let (sin, cos) = angle.to_radians().sin_cos();
+            // Start so the text's center lands on the page center
+            let x = cx - cos * width / 2.0 + sin * cap_height / 2.0;
+            let y = cy - sin * width / 2.0 - cos * cap_height / 2.0;
+
+            let (r, g, b) = parse_hex_color(&watermark.color).unwrap_or((128, 128, 128));
+            layer.set_fill_color(pdf_color(
+                fade(r, watermark.opacity),
+                fade(g, watermark.opacity),
+                fade(b, watermark.opacity),
+                color_space,
+            ));
+            layer.begin_text_section();
+            layer.set_font(&font, font_size);
+            layer.set_text_matrix(TextMatrix::TranslateRotate(Pt(x), Pt(y), angle));
+            layer.write_text(text, &font);
+            layer.end_text_section();
+        }
+    }
+
+    layer.restore_graphics_state();
+}
+
+/// Build a PDF color in the requested output color space
+#[inline]
+fn pdf_color(r: u8, g: u8, b: u8, color_space: ExportColorSpace) -> printpdf::Color {
+    let (r, g, b) = (r as f32 / 255.0, g as f32 / 255.0, b as f32 / 255.0);
+    match color_space {
+        ExportColorSpace::Rgb => printpdf::Color::Rgb(printpdf::Rgb::new(r, g, b, None)),
+        ExportColorSpace::Cmyk => {
+            let (c, m, y, k) = rgb_to_cmyk(r, g, b);
+            printpdf::Color::Cmyk(printpdf::Cmyk::new(c, m, y, k, None))
+        }
+    }
+}
+
+/// Naive device RGB to CMYK conversion (0-1 channels); no ICC profile is applied
+#[inline]
+pub(crate) fn rgb_to_cmyk(r: f32, g: f32, b: f32) -> (f32, f32, f32, f32) {
+    let k = 1.0 - r.max(g).max(b);
+    if k >= 1.0 {
+        return (0.0, 0.0, 0.0, 1.0);
+    }
+    ((1.0 - r - k) / (1.0 - k), (1.0 - g - k) / (1.0 - k), (1.0 - b - k) / (1.0 - k), k)
+}
+
 /// Parse hex color string to RGB values
 #[inline]
-fn parse_hex_color(color: &str) -> Option<(u8, u8, u8)> {
+pub(crate) fn parse_hex_color(color: &str) -> Option<(u8, u8, u8)> {
     let color = color.trim_start_matches('#');
     if color.len() != 6 {
         return None;
@@ -308,10 +1285,11 @@ fn parse_hex_color(color: &str) -> Option<(u8, u8, u8)> {
 fn export_docx_sync(
     pages: &[PageData],
     output_path: &str,
-    _metadata: &DocumentMetadata,
+    metadata: &DocumentMetadata,
     options: &ExportOptions,
 ) -> Result<ExportResult, ExportError> {
-    use docx_rust::document::Paragraph;
+    use docx_rust::content_type::OverrideContentType;
+    use docx_rust::document::{EndNotes, FootNotes};
     use docx_rust::Docx;
 
     let page_range = options
@@ -319,11 +1297,19 @@ fn export_docx_sync(
         .unwrap_or((0, pages.len().saturating_sub(1)));
 
     let mut docx = Docx::default();
+    add_docx_properties(&mut docx, metadata);
+    let mut notes = DocxNotes::default();
 
     for (i, page) in pages.iter().enumerate() {
         if i < page_range.0 || i > page_range.1 {
             continue;
         }
+        let language = page
+            .metadata
+            .as_ref()
+            .and_then(|m| m.language.as_deref())
+            .or(metadata.language.as_deref())
+            .filter(|l| doc_metadata::is_language_tag(l));
 
         // Sort layers by z-index
         let mut sorted_layers: Vec<_> = page.layers.iter().filter(|l| l.visible).collect();
@@ -332,13 +1318,29 @@ fn export_docx_sync(
         for layer in sorted_layers {
             if layer.layer_type.to_string() == "text" {
                 if let Some(content) = &layer.content {
-                    let para = Paragraph::default().push_text(content.as_str());
-                    docx.document.push(para);
+                    let paragraph = notes.paragraph(docx_paragraph(layer), content, &layer.notes, language);
+                    docx.document.push(paragraph);
                 }
             }
         }
     }
 
+    // Word numbers the notes itself
+    if !notes.footnotes.is_empty() {
+        docx.footnotes = Some(FootNotes { content: notes.footnotes });
+        docx.content_types.overrides.push(OverrideContentType {
+            part: "/word/footnotes.xml".into(),
+            ty: DOCX_FOOTNOTES_CONTENT_TYPE.into(),
+        });
+    }
+    if !notes.endnotes.is_empty() {
+        docx.endnotes = Some(EndNotes { content: notes.endnotes });
+        docx.content_types.overrides.push(OverrideContentType {
+            part: "/word/endnotes.xml".into(),
+            ty: DOCX_ENDNOTES_CONTENT_TYPE.into(),
+        });
+    }
+
     // Write to file
     let file = File::create(output_path)?;
     docx.write(file)
@@ -348,31 +1350,255 @@ fn export_docx_sync(
         success: true,
         message: format!("Exported to DOCX: {}", output_path),
         output_path: Some(output_path.to_string()),
+        data: None,
+    })
+}
+
+/// Export an audio proof: the text in reading order as audio, SSML or text
+///
+/// With `split_chapters` each chapter gets its own file, numbered after the
+/// output name (`proof-01.wav`, `proof-02.wav`, ...); the result names the
+/// first. The extension is set by the output kind.
+fn export_speech_sync(
+    pages: &[PageData],
+    output_path: &str,
+    metadata: &DocumentMetadata,
+    options: &ExportOptions,
+) -> Result<ExportResult, ExportError> {
+    let speech_options = options.speech.clone().unwrap_or_default();
+    let pages = selected_pages(pages, options)?;
+
+    let chapters = speech::speech_chapters(pages);
+    if chapters.is_empty() {
+        return Err(ExportError::Speech("The pages have no text to read".to_string()));
+    }
+    let extension = match speech_options.output {
+        SpeechOutput::Audio => crate::tts::audio_extension(),
+        SpeechOutput::Ssml => "ssml",
+        SpeechOutput::Text => "txt",
+    };
+    let output = std::path::Path::new(output_path).with_extension(extension);
+    let stem = output.file_stem().map(|s| s.to_string_lossy().into_owned()).unwrap_or_default();
+    let files: Vec<&[speech::SpeechChapter]> =
+        if speech_options.split_chapters { chapters.chunks(1).collect() } else { vec![&chapters] };
+
+    let language = metadata.language.as_deref().filter(|l| doc_metadata::is_language_tag(l));
+    let mut written = Vec::with_capacity(files.len());
+    for (i, file) in files.iter().enumerate() {
+        let path = if files.len() > 1 {
+            output.with_file_name(format!("{}-{:02}.{}", stem, i + 1, extension))
+        } else {
+            output.clone()
+        };
+        match speech_options.output {
+            SpeechOutput::Text => std::fs::write(&path, speech::speech_text(file))?,
+            SpeechOutput::Ssml => std::fs::write(&path, speech::speech_ssml(file, language))?,
+            SpeechOutput::Audio => crate::tts::synthesize(
+                &speech::speech_text(file),
+                &speech::speech_ssml(file, language),
+                &path,
+                speech_options.voice.as_deref(),
+                speech_options.rate,
+            )
+            .map_err(ExportError::Speech)?,
+        }
+        written.push(path);
+    }
+
+    Ok(ExportResult {
+        success: true,
+        message: format!("Exported {} chapters to {} files", chapters.len(), written.len()),
+        output_path: written.first().map(|p| p.to_string_lossy().into_owned()),
+        data: None,
     })
 }
 
+/// Export a fixed-layout EPUB with one page per spine item and the first
+/// page, rendered as a JPEG, for its cover
+fn export_epub_sync(
+    pages: &[PageData],
+    output_path: &str,
+    metadata: &DocumentMetadata,
+    options: &ExportOptions,
+) -> Result<ExportResult, ExportError> {
+    let pages = selected_pages(pages, options)?;
+    let first = pages.first().ok_or(ExportError::NoPages)?;
+    let epub_options = options.epub.clone().unwrap_or_default();
+
+    // Kindle wants a cover image; the book still opens without one
+    let dpi = options.dpi.unwrap_or(export::DEFAULT_IMAGE_DPI).clamp(9, 600);
+    let cover_options = export::ImageSequenceOptions { format: export::ImageFileFormat::Jpeg, ..Default::default() };
+    let cover = render_page_image(first, dpi as f32 / units::POINTS_PER_INCH)
+        .map_err(ExportError::Epub)
+        .and_then(|page| image_sequence::encode_page(page, &cover_options, dpi, options.image_quality));
+    let cover = match cover {
+        Ok(cover) => Some(cover),
+        Err(e) => {
+            tracing::warn!("EPUB cover not rendered: {}", e);
+            None
+        }
+    };
+
+    let (start, end) = options.page_range.unwrap_or((0, pages.len() - 1));
+    let toc = sections::table_of_contents(pages, &sections::slice_sections(&options.sections, start, end));
+    let data = epub::write_fixed_layout_epub(
+        pages,
+        metadata,
+        &epub_options,
+        &toc,
+        cover.as_deref(),
+        image_handler::layer_image_bytes,
+    )
+    .map_err(ExportError::Epub)?;
+    let output = std::path::Path::new(output_path).with_extension("epub");
+    std::fs::write(&output, data)?;
+
+    Ok(ExportResult {
+        success: true,
+        message: format!("Exported {} pages to a fixed-layout EPUB", pages.len()),
+        output_path: Some(output.to_string_lossy().into_owned()),
+        data: None,
+    })
+}
+
+const DOCX_FOOTNOTES_CONTENT_TYPE: &str = "application/vnd.openxmlformats-officedocument.wordprocessingml.footnotes+xml";
+const DOCX_ENDNOTES_CONTENT_TYPE: &str = "application/vnd.openxmlformats-officedocument.wordprocessingml.endnotes+xml";
+
+/// Empty paragraph carrying a layer's keep and widow/orphan controls; Word
+/// only switches widow control on (two lines) or off
+fn docx_paragraph<'a>(layer: &LayerObject) -> docx_rust::document::Paragraph<'a> {
+    use docx_rust::formatting::{KeepLines, KeepNext, ParagraphProperty, WidowControl};
+
+    let paragraph = docx_rust::document::Paragraph::default();
+    let Some(pagination) = layer.pagination else {
+        return paragraph;
+    };
+    paragraph.property(ParagraphProperty {
+        keep_next: pagination.keep_with_next.then(KeepNext::default),
+        keep_lines: pagination.keep_together.then(KeepLines::default),
+        widow_control: Some(WidowControl { value: Some(pagination.widows.max(pagination.orphans) >= 2) }),
+        ..Default::default()
+    })
+}
+
+/// Footnote and endnote parts of a DOCX being written
+#[derive(Default)]
+struct DocxNotes<'a> {
+    footnotes: Vec<docx_rust::document::FootNote<'a>>,
+    endnotes: Vec<docx_rust::document::EndNote<'a>>,
+}
+
+impl<'a> DocxNotes<'a> {
+    /// `paragraph` with `content` appended, a note reference after each
+    /// note's anchor and the notes themselves collected
+    fn paragraph(
+        &mut self,
+        mut paragraph: docx_rust::document::Paragraph<'a>,
+        content: &'a str,
+        notes: &'a [Note],
+        language: Option<&str>,
+    ) -> docx_rust::document::Paragraph<'a> {
+        use docx_rust::document::{
+            EndNote, EndnoteRef, EndnoteReference, FootNote, FootnoteRef, FootnoteReference, Paragraph, Run,
+            RunContent, TextSpace,
+        };
+        use docx_rust::formatting::{CharacterProperty, Lang, VertAlign, VertAlignType};
+
+        let text_run = |text: &'a str| {
+            let run = Run::default().push_text((text, TextSpace::Preserve));
+            match language {
+                Some(language) => run.property(CharacterProperty {
+                    lang: Some(Lang::default().val(language.to_string())),
+                    ..Default::default()
+                }),
+                None => run,
+            }
+        };
+        let mark = |content: RunContent<'a>| {
+            let superscript = VertAlign { value: Some(VertAlignType::Superscript) };
+            Run::default()
+                .property(CharacterProperty { vertical_align: Some(superscript), ..Default::default() })
+                .push(content)
+        };
+        let body = |reference: RunContent<'a>, text: &'a str| {
+            Paragraph::default().push(mark(reference)).push(Run::default().push_text((" ", TextSpace::Preserve))).push(text_run(text))
+        };
+
+        let mut ordered: Vec<&Note> = notes.iter().collect();
+        ordered.sort_by_key(|n| n.anchor);
+        let mut start = 0;
+        for note in ordered {
+            let anchor = note.anchor.clamp(start, content.len());
+            if !content.is_char_boundary(anchor) {
+                continue;
+            }
+            if anchor > start {
+                paragraph = paragraph.push(text_run(&content[start..anchor]));
+                start = anchor;
+            }
+            // Ids 0 and -1 are reserved for separators
+            let id = self.footnotes.len() + self.endnotes.len() + 1;
+            let reference = match note.kind {
+                NoteKind::Footnote => {
+                    let mut footnote = FootNote { id: Some(id as isize), ..Default::default() };
+                    footnote.push(body(RunContent::FootnoteRef(FootnoteRef), &note.text));
+                    self.footnotes.push(footnote);
+                    RunContent::FootnoteReference(FootnoteReference { id: Some(id.to_string().into()), ..Default::default() })
+                }
+                NoteKind::Endnote => {
+                    let mut endnote = EndNote { id: Some(id as isize), ..Default::default() };
+                    endnote.push(body(RunContent::EndnoteRef(EndnoteRef), &note.text));
+                    self.endnotes.push(endnote);
+                    RunContent::EndnoteReference(EndnoteReference { id: Some(id.to_string().into()), ..Default::default() })
+                }
+            };
+            paragraph = paragraph.push(mark(reference));
+        }
+        if start < content.len() || notes.is_empty() {
+            paragraph = paragraph.push(text_run(&content[start..]));
+        }
+        paragraph
+    }
+}
+
+/// Core and custom document properties; docx-rust's own core part has no
+/// language, identifier or dates, so both parts are written as raw XML
+fn add_docx_properties(docx: &mut docx_rust::Docx, metadata: &DocumentMetadata) {
+    use docx_rust::content_type::OverrideContentType;
+
+    const CORE_RELATIONSHIP: &str = "http://schemas.openxmlformats.org/package/2006/relationships/metadata/core-properties";
+    const CUSTOM_RELATIONSHIP: &str =
+        "http://schemas.openxmlformats.org/officeDocument/2006/relationships/custom-properties";
+
+    docx.rels.add_rel(CORE_RELATIONSHIP, "docProps/core.xml");
+    docx.custom_xml.insert(
+        "docProps/core.xml".to_string(),
+        doc_metadata::docx_core_properties(metadata).into_bytes().into(),
+    );
+    if let Some(custom) = doc_metadata::docx_custom_properties(metadata) {
+        docx.rels.add_rel(CUSTOM_RELATIONSHIP, "docProps/custom.xml");
+        docx.content_types.overrides.push(OverrideContentType {
+            part: "/docProps/custom.xml".into(),
+            ty: "application/vnd.openxmlformats-officedocument.custom-properties+xml".into(),
+        });
+        docx.custom_xml.insert("docProps/custom.xml".to_string(), custom.into_bytes().into());
+    }
+}
+
 /// Export to BookProject format (JSON + assets) (synchronous)
 fn export_bookproj_sync(
     pages: &[PageData],
     output_path: &str,
     metadata: &DocumentMetadata,
-    _options: &ExportOptions,
+    options: &ExportOptions,
 ) -> Result<ExportResult, ExportError> {
-    let project = BookProjectData {
-        format: "bookproj".to_string(),
-        version: "1.0.0".to_string(),
-        metadata: metadata.clone(),
-        document: crate::models::DocumentData {
-            page_width: pages.first().map(|p| p.width).unwrap_or(612.0),
-            page_height: pages.first().map(|p| p.height).unwrap_or(792.0),
-            pages: pages.to_vec(),
-        },
-        settings: crate::models::ProjectSettings {
-            default_font: Some("Arial".to_string()),
-            default_font_size: Some(12.0),
-            export_quality: Some("standard".to_string()),
-        },
-    };
+    let project = vortex_core::export::build_project(
+        pages,
+        metadata,
+        options.changes.clone(),
+        options.track_changes,
+        options.sections.clone(),
+    );
 
     let json = serde_json::to_string_pretty(&project)?;
 
@@ -384,39 +1610,239 @@ fn export_bookproj_sync(
         success: true,
         message: format!("Project saved to: {}", output_path),
         output_path: Some(output_path.to_string()),
+        data: None,
     })
 }
 
+/// Read a project file: a v2 container, bare MessagePack or plain JSON
+///
+/// JSON is parsed straight from the file instead of a string copy, so peak
+/// memory stays close to the size of the parsed project.
+pub fn read_project_file(file_path: &str) -> Result<(BookProjectData, Vec<ArchiveImage>), String> {
+    let mut reader = BufReader::new(File::open(file_path).map_err(|e| e.to_string())?);
+    if archive::is_archive(reader.fill_buf().map_err(|e| e.to_string())?) {
+        ProjectArchiveReader::new(reader)?.into_project()
+    } else {
+        read_bare_project(reader).map(|project| (project, Vec::new()))
+    }
+}
+
+/// Parse a project that is not a v2 container, detecting MessagePack by its
+/// first byte
+/// and converting its geometry to points
+fn read_bare_project(mut reader: BufReader<File>) -> Result<BookProjectData, String> {
+    let is_msgpack = msgpack::looks_like_map(reader.fill_buf().map_err(|e| e.to_string())?);
+    let result: Result<BookProjectData, String> = if is_msgpack {
+        let mut data = Vec::new();
+        reader.read_to_end(&mut data).map_err(|e| e.to_string())?;
+        msgpack::from_slice(&data)
+    } else {
+        serde_json::from_reader(reader).map_err(|e| e.to_string())
+    };
+    let mut project = result.map_err(|e| format!("Invalid project file: {}", e))?;
+    units::normalize_to_points(&mut project.document);
+    Ok(project)
+}
+
+/// Cache a project's images; its ICC profiles are saved among them
+fn restore_images(images: Vec<ArchiveImage>) {
+    for image in images {
+        if color_profile::is_profile_id(&image.id) {
+            color_profile::insert_profile(image.id, image.data);
+        } else {
+            image_handler::cache_image(&image.id, image.data);
+        }
+    }
+}
+
 /// Load a BookProject file
 #[tauri::command]
 pub async fn load_project(file_path: String) -> Result<BookProjectData, String> {
-    let content = std::fs::read_to_string(&file_path).map_err(|e| e.to_string())?;
-    let project: BookProjectData = serde_json::from_str(&content).map_err(|e| e.to_string())?;
-    Ok(project)
+    tokio::task::spawn_blocking(move || {
+        let (mut project, images) = read_project_file(&file_path)?;
+        restore_images(images);
+        linked_images::restore_linked_images(&mut project.document.pages);
+        color_profile::tag_images(&project.document.pages);
+        let pages = &project.document.pages;
+        recent_projects::record(&file_path, &project.metadata, pages.len(), pages.first().cloned());
+        Ok(project)
+    })
+    .await
+    .map_err(|e| format!("Load task failed: {}", e))?
+}
+
+/// Project opened by `load_project_streamed`, pages still loading
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct StreamedProject {
+    /// Metadata, settings and page size; `document.pages` is empty
+    pub project: BookProjectData,
+    pub total_pages: usize,
+}
+
+enum PageSource {
+    Archive(Box<ProjectArchiveReader<BufReader<File>>>),
+    Loaded(std::vec::IntoIter<PageData>),
+}
+
+impl PageSource {
+    fn next_page(&mut self, index: usize) -> Option<Result<PageData, String>> {
+        match self {
+            PageSource::Archive(reader) => (index < reader.page_count()).then(|| reader.read_page(index)),
+            PageSource::Loaded(pages) => pages.next().map(Ok),
+        }
+    }
+}
+
+/// Open a project and stream its pages
+///
+/// Returns as soon as the project skeleton is read; pages follow as
+/// `project_load_progress` events (`currentPage`, `totalPages`, `page`) and
+/// a final `project_load_complete` event. v2 containers are read one page at
+/// a time; bare MessagePack and JSON projects are parsed first and then emitted page by page.
+#[tauri::command]
+pub async fn load_project_streamed(file_path: String, app_handle: AppHandle) -> Result<StreamedProject, String> {
+    let path = file_path.clone();
+    let (streamed, mut source) = tokio::task::spawn_blocking(move || {
+        let mut file = BufReader::new(File::open(&path).map_err(|e| e.to_string())?);
+        if archive::is_archive(file.fill_buf().map_err(|e| e.to_string())?) {
+            let mut reader = ProjectArchiveReader::new(file)?;
+            restore_images(reader.read_images()?);
+            let streamed = StreamedProject {
+                project: reader.project().clone(),
+                total_pages: reader.page_count(),
+            };
+            Ok::<_, String>((streamed, PageSource::Archive(Box::new(reader))))
+        } else {
+            let mut project = read_bare_project(file)?;
+            let pages = std::mem::take(&mut project.document.pages);
+            let streamed = StreamedProject { project, total_pages: pages.len() };
+            Ok((streamed, PageSource::Loaded(pages.into_iter())))
+        }
+    })
+    .await
+    .map_err(|e| format!("Load task failed: {}", e))??;
+
+    let total_pages = streamed.total_pages;
+    let metadata = streamed.project.metadata.clone();
+    tokio::task::spawn_blocking(move || {
+        let mut index = 0;
+        let error = loop {
+            match source.next_page(index) {
+                Some(Ok(mut page)) => {
+                    linked_images::restore_linked_images(std::slice::from_mut(&mut page));
+                    color_profile::tag_images(std::slice::from_ref(&page));
+                    if index == 0 {
+                        recent_projects::record(&file_path, &metadata, total_pages, Some(page.clone()));
+                    }
+                    let _ = app_handle.emit(
+                        "project_load_progress",
+                        serde_json::json!({
+                            "currentPage": index + 1,
+                            "totalPages": total_pages,
+                            "page": page
+                        }),
+                    );
+                    index += 1;
+                }
+                Some(Err(e)) => break Some(e),
+                None => break None,
+            }
+        };
+        if index == 0 && error.is_none() {
+            recent_projects::record(&file_path, &metadata, total_pages, None);
+        }
+        let _ = app_handle.emit(
+            "project_load_complete",
+            serde_json::json!({
+                "totalPages": total_pages,
+                "loadedPages": index,
+                "error": error
+            }),
+        );
+    });
+
+    Ok(streamed)
+}
+
+/// A project as a v2 container with its cached images and ICC profiles
+pub fn project_archive(project: &BookProjectData) -> Result<Vec<u8>, String> {
+    let mut images: Vec<ArchiveImage> = archive::referenced_image_ids(project)
+        .into_iter()
+        .filter_map(|id| image_handler::get_image_bytes(&id).map(|data| ArchiveImage { id, data }))
+        .collect();
+    images.extend(
+        color_profile::referenced_profiles(&project.document.pages)
+            .into_iter()
+            .map(|(id, data)| ArchiveImage { id, data: data.to_vec() }),
+    );
+    archive::write_archive(project, &images)
 }
 
-/// Save current project
+/// Save current project as a v2 container with its cached images and ICC profiles
+///
+/// Linked images are saved as links unless `linked_images` is `Embed`, which
+/// stores their bytes and drops the links.
 #[tauri::command]
 pub async fn save_project(
-    project: BookProjectData,
+    mut project: BookProjectData,
     output_path: String,
+    linked_images: Option<LinkedImages>,
 ) -> Result<ExportResult, String> {
-    let json = serde_json::to_string_pretty(&project).map_err(|e| e.to_string())?;
+    tokio::task::spawn_blocking(move || {
+        if linked_images == Some(LinkedImages::Embed) {
+            archive::embed_linked_images(&mut project);
+        }
+        let data = project_archive(&project)?;
 
-    let mut file = File::create(&output_path).map_err(|e| e.to_string())?;
-    file.write_all(json.as_bytes())
-        .map_err(|e| e.to_string())?;
+        let mut file = File::create(&output_path).map_err(|e| e.to_string())?;
+        file.write_all(&data).map_err(|e| e.to_string())?;
+        drop(file);
 
-    Ok(ExportResult {
-        success: true,
-        message: format!("Project saved: {}", output_path),
-        output_path: Some(output_path),
+        let pages = &project.document.pages;
+        recent_projects::record(&output_path, &project.metadata, pages.len(), pages.first().cloned());
+
+        Ok(ExportResult {
+            success: true,
+            message: format!("Project saved: {}", output_path),
+            output_path: Some(output_path),
+            data: None,
+        })
+    })
+    .await
+    .map_err(|e| format!("Save task failed: {}", e))?
+}
+
+/// Re-save a project file with a different encoding
+///
+/// Accepts any format `load_project` reads; embedded images are carried over.
+#[tauri::command]
+pub async fn convert_project(
+    input_path: String,
+    output_path: String,
+    encoding: ProjectEncoding,
+) -> Result<ExportResult, String> {
+    tokio::task::spawn_blocking(move || {
+        let (mut project, images) = read_project_file(&input_path)?;
+        project.settings.encoding = encoding;
+        let data = archive::write_archive(&project, &images)?;
+        std::fs::write(&output_path, data).map_err(|e| e.to_string())?;
+
+        Ok(ExportResult {
+            success: true,
+            message: format!("Project converted: {}", output_path),
+            output_path: Some(output_path),
+            data: None,
+        })
     })
+    .await
+    .map_err(|e| format!("Convert task failed: {}", e))?
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
+    use vortex_core::test_util;
 
     #[test]
     fn test_export_options_defaults() {
@@ -431,6 +1857,274 @@ mod tests {
         assert!(!options.create_layers);
     }
 
+    #[test]
+    fn test_bates_stamps_on_export_copies() {
+        let stamp: Stamp = serde_json::from_str(r#"{"text":"ACME{n} ({page}/{total})"}"#).unwrap();
+        assert_eq!(stamp.position, StampPosition::BottomRight);
+        let page = |i| PageData { page_index: i, width: 612.0, height: 792.0, dpi: None, layers: vec![], metadata: None, background: None };
+        let mut pages = vec![page(4), page(5)];
+
+        apply_stamps(&mut pages, std::slice::from_ref(&stamp));
+        let layer = &pages[1].layers[0];
+        assert_eq!(layer.content.as_deref(), Some("ACME000002 (2/2)"));
+        assert_eq!(layer.role, LayerRole::Background);
+        assert!(layer.bounds.x + layer.bounds.width <= 612.0 - stamp.margin + 0.01);
+        assert!(layer.bounds.y > 700.0);
+    }
+
+    #[test]
+    fn test_watermark_export() {
+        let page = PageData { page_index: 0, width: 612.0, height: 792.0, dpi: None, layers: vec![], metadata: None, background: None };
+        let path = std::env::temp_dir().join(format!("rook-watermark-{}.pdf", std::process::id()));
+        let options: ExportOptions = serde_json::from_value(serde_json::json!({
+            "format": "pdf",
+            "outputPath": path.to_str().unwrap(),
+            "watermark": { "text": "DRAFT" },
+            "stamps": [{ "text": "{page}", "position": "top-center" }]
+        }))
+        .unwrap();
+        let watermark = options.watermark.as_ref().unwrap();
+        assert_eq!((watermark.opacity, watermark.angle), (0.3, None));
+
+        let result = export_pdf_sync(std::slice::from_ref(&page), path.to_str().unwrap(), &DocumentMetadata::default(), &options).unwrap();
+        assert!(result.success);
+        let bytes = std::fs::read(&path).unwrap();
+        let _ = std::fs::remove_file(&path);
+        assert!(bytes.windows(8).any(|w| w == b"Multiply"));
+        assert_eq!(fade(0, 0.25), 191);
+        // The source page is untouched
+        assert!(page.layers.is_empty());
+    }
+
+    #[test]
+    fn test_transparency_export() {
+        let layer = test_util::layer("tint", "shape")
+            .bounds(72.0, 72.0, 200.0, 100.0)
+            .fields(serde_json::json!({ "opacity": 0.5, "blendMode": "multiply", "fillColor": "#3366CC" }))
+            .build();
+        let page = test_util::page(0, vec![layer]);
+        let path = std::env::temp_dir().join(format!("rook-transparency-{}.pdf", std::process::id()));
+        let options: ExportOptions = serde_json::from_value(serde_json::json!({ "format": "pdf", "outputPath": path.to_str().unwrap() })).unwrap();
+
+        export_pdf_sync(std::slice::from_ref(&page), path.to_str().unwrap(), &DocumentMetadata::default(), &options).unwrap();
+        let doc = lopdf::Document::load(&path).unwrap();
+        let _ = std::fs::remove_file(&path);
+        let page_id = *doc.get_pages().values().next().unwrap();
+        let (resources, ids) = doc.get_page_resources(page_id).unwrap();
+        let resources = resources.or_else(|| doc.get_dictionary(ids[0]).ok()).unwrap();
+        let states = resources.get(b"ExtGState").and_then(lopdf::Object::as_dict).unwrap();
+        let state = doc.get_dictionary(states.get(b"RookT1").and_then(lopdf::Object::as_reference).unwrap()).unwrap();
+        assert_eq!(state.get(b"ca").and_then(lopdf::Object::as_float).unwrap(), 0.5);
+        assert_eq!(state.get(b"BM").and_then(lopdf::Object::as_name).unwrap(), b"Multiply");
+        let content = doc.get_page_content(page_id).unwrap();
+        assert!(content.windows(10).any(|w| w == b"/RookT1 gs"));
+    }
+
+    #[test]
+    fn test_create_layers_export() {
+        let layer = |id: &str, z: i32, role: &str| {
+            test_util::layer(id, "shape")
+                .bounds(72.0, 72.0, 200.0, 100.0)
+                .z(z)
+                .fields(serde_json::json!({ "fillColor": "#3366CC", "role": role }))
+                .build()
+        };
+        let page = |index: usize| {
+            test_util::page(index, vec![layer("note", 2, "annotation"), layer("bg", 0, "background"), layer("body", 1, "content")])
+        };
+        let path = std::env::temp_dir().join(format!("rook-ocg-{}.pdf", std::process::id()));
+        let options: ExportOptions = serde_json::from_value(serde_json::json!({
+            "format": "pdf", "outputPath": path.to_str().unwrap(), "createLayers": true
+        }))
+        .unwrap();
+
+        export_pdf_sync(&[page(0), page(1)], path.to_str().unwrap(), &DocumentMetadata::default(), &options).unwrap();
+        let doc = lopdf::Document::load(&path).unwrap();
+        let _ = std::fs::remove_file(&path);
+        let properties = doc.catalog().unwrap().get(b"OCProperties").and_then(lopdf::Object::as_dict).unwrap();
+        let groups: Vec<lopdf::ObjectId> = properties
+            .get(b"OCGs")
+            .and_then(lopdf::Object::as_array)
+            .unwrap()
+            .iter()
+            .map(|r| r.as_reference().unwrap())
+            .collect();
+        let names: Vec<&[u8]> =
+            groups.iter().map(|&id| doc.get_dictionary(id).unwrap().get(b"Name").unwrap().as_str().unwrap()).collect();
+        assert_eq!(names, [&b"Background"[..], b"Content", b"Annotations"]);
+        // Both pages mark their content with the shared groups
+        for page_id in doc.get_pages().into_values() {
+            let (resources, ids) = doc.get_page_resources(page_id).unwrap();
+            let resources = resources.or_else(|| doc.get_dictionary(ids[0]).ok()).unwrap();
+            let marked = resources.get(b"Properties").and_then(lopdf::Object::as_dict).unwrap();
+            assert!(marked.iter().all(|(_, r)| groups.contains(&r.as_reference().unwrap())));
+            assert_eq!(marked.len(), 3);
+        }
+    }
+
+    #[test]
+    fn test_image_alpha_exported_as_soft_mask() {
+        let mut logo = image::RgbaImage::from_pixel(4, 2, image::Rgba([200, 0, 0, 255]));
+        logo.put_pixel(3, 1, image::Rgba([200, 0, 0, 0]));
+        let png = std::env::temp_dir().join(format!("rook-smask-logo-{}.png", std::process::id()));
+        logo.save(&png).unwrap();
+        let layer = test_util::layer("logo", "image").bounds(72.0, 72.0, 40.0, 20.0).with("imagePath", png.to_str().unwrap()).build();
+        let page = test_util::page(0, vec![layer]);
+        let path = std::env::temp_dir().join(format!("rook-smask-{}.pdf", std::process::id()));
+        let options: ExportOptions = serde_json::from_value(serde_json::json!({ "format": "pdf", "outputPath": path.to_str().unwrap() })).unwrap();
+
+        export_pdf_sync(std::slice::from_ref(&page), path.to_str().unwrap(), &DocumentMetadata::default(), &options).unwrap();
+        let doc = lopdf::Document::load(&path).unwrap();
+        let _ = std::fs::remove_file(&path);
+        let _ = std::fs::remove_file(&png);
+        let page_id = *doc.get_pages().values().next().unwrap();
+        let image_id = crate::pdf_tools::resource_id(&doc, page_id, "XObject", "X0").unwrap();
+        let image = doc.get_object(image_id).and_then(lopdf::Object::as_stream).unwrap();
+        let mask_id = image.dict.get(b"SMask").and_then(lopdf::Object::as_reference).unwrap();
+        let mask = doc.get_object(mask_id).and_then(lopdf::Object::as_stream).unwrap();
+        assert_eq!(mask.dict.get(b"Height").and_then(lopdf::Object::as_i64).unwrap(), 2);
+        assert_eq!(mask.get_plain_content().unwrap(), vec![255, 255, 255, 255, 255, 255, 255, 0]);
+    }
+
+    #[test]
+    fn test_jpeg_embedded_unchanged() {
+        let image = image::GrayImage::from_pixel(16, 8, image::Luma([90]));
+        let mut jpeg = std::io::Cursor::new(Vec::new());
+        image.write_to(&mut jpeg, image::ImageFormat::Jpeg).unwrap();
+        let jpeg = jpeg.into_inner();
+
+        let xobject = jpeg_xobject(&jpeg).unwrap();
+        assert_eq!(xobject.image_data, jpeg);
+        assert!(matches!(xobject.image_filter, Some(printpdf::ImageFilter::DCT)));
+        assert!(matches!(xobject.color_space, printpdf::ColorSpace::Greyscale));
+        assert_eq!((xobject.width.0, xobject.height.0), (16, 8));
+        assert!(jpeg_xobject(b"\x89PNG\r\n\x1a\n00000000").is_none());
+    }
+
+    #[test]
+    fn test_icc_profile_reembedded() {
+        let gray = moxcms::ColorProfile::new_gray_with_gamma(2.2).encode().unwrap();
+        let profile_id = color_profile::store_profile(gray.clone());
+        let scan = image::RgbaImage::from_pixel(3, 2, image::Rgba([40, 40, 40, 255]));
+        let png = std::env::temp_dir().join(format!("rook-icc-scan-{}.png", std::process::id()));
+        scan.save(&png).unwrap();
+        let layer = test_util::layer("scan", "image")
+            .bounds(72.0, 72.0, 30.0, 20.0)
+            .fields(serde_json::json!({
+                "imagePath": png.to_str().unwrap(), "sourceType": "extracted",
+                "imageData": { "width": 3, "height": 2, "colorSpace": "RGBA", "dpi": 7, "iccProfile": profile_id }
+            }))
+            .build();
+        let page = test_util::page(0, vec![layer]);
+        let path = std::env::temp_dir().join(format!("rook-icc-{}.pdf", std::process::id()));
+        let options: ExportOptions = serde_json::from_value(serde_json::json!({ "format": "pdf", "outputPath": path.to_str().unwrap() })).unwrap();
+
+        export_pdf_sync(std::slice::from_ref(&page), path.to_str().unwrap(), &DocumentMetadata::default(), &options).unwrap();
+        let doc = lopdf::Document::load(&path).unwrap();
+        let _ = std::fs::remove_file(&path);
+        let _ = std::fs::remove_file(&png);
+        let page_id = *doc.get_pages().values().next().unwrap();
+        let image_id = crate::pdf_tools::resource_id(&doc, page_id, "XObject", "X0").unwrap();
+        let image = doc.get_object(image_id).and_then(lopdf::Object::as_stream).unwrap();
+        // Gray pixels go out as gray, tagged with the original profile
+        assert_eq!(image.get_plain_content().unwrap(), vec![40; 6]);
+        let space = image.dict.get(b"ColorSpace").and_then(lopdf::Object::as_array).unwrap();
+        assert_eq!(space[0].as_name().unwrap(), b"ICCBased");
+        let profile = doc.get_object(space[1].as_reference().unwrap()).and_then(lopdf::Object::as_stream).unwrap();
+        assert_eq!(profile.dict.get(b"N").and_then(lopdf::Object::as_i64).unwrap(), 1);
+        assert_eq!(profile.get_plain_content().unwrap(), gray);
+    }
+
+    #[test]
+    fn test_text_path_effects_export() {
+        let layer = test_util::layer("title", "text")
+            .bounds(72.0, 72.0, 300.0, 40.0)
+            .fields(serde_json::json!({
+                "content": "Arc", "fontSize": 24,
+                "textPath": { "path": { "commands": [
+                    { "type": "moveTo", "x": 72, "y": 200 },
+                    { "type": "curveTo", "x1": 150, "y1": 100, "x2": 250, "y2": 100, "x": 330, "y": 200 }
+                ] } },
+                "textOutline": { "color": "#000000", "width": 0.75 },
+                "textShadow": { "color": "#999999", "offsetX": 2, "offsetY": 2 }
+            }))
+            .build();
+        let page = test_util::page(0, vec![layer]);
+        let path = std::env::temp_dir().join(format!("rook-text-path-{}.pdf", std::process::id()));
+        let options: ExportOptions = serde_json::from_value(serde_json::json!({ "format": "pdf", "outputPath": path.to_str().unwrap() })).unwrap();
+
+        export_pdf_sync(std::slice::from_ref(&page), path.to_str().unwrap(), &DocumentMetadata::default(), &options).unwrap();
+        let doc = lopdf::Document::load(&path).unwrap();
+        let _ = std::fs::remove_file(&path);
+        let page_id = *doc.get_pages().values().next().unwrap();
+        let content = String::from_utf8_lossy(&doc.get_page_content(page_id).unwrap()).into_owned();
+        // One text matrix per glyph, drawn for the shadow and again for the text
+        assert_eq!(content.matches(" Tm").count(), 6);
+        assert!(content.contains("2 Tr") && content.contains("0 Tr"));
+    }
+
+    #[test]
+    fn test_text_wraps_around_shape() {
+        let body = test_util::layer("body", "text").bounds(72.0, 72.0, 300.0, 200.0).fields(serde_json::json!({
+            "fontSize": 12,
+            "content": "Lorem ipsum dolor sit amet consectetur adipiscing elit sed do eiusmod tempor incididunt ut labore et dolore magna aliqua"
+        }));
+        let shape = test_util::layer("box", "shape").bounds(222.0, 60.0, 200.0, 100.0).z(1).fields(serde_json::json!({
+            "fillColor": "#CCCCCC", "textWrap": { "contour": "bounding-box", "offset": 6 }
+        }));
+        let page = test_util::page(0, vec![body.build(), shape.build()]);
+        let path = std::env::temp_dir().join(format!("rook-text-wrap-{}.pdf", std::process::id()));
+        let options: ExportOptions = serde_json::from_value(serde_json::json!({ "format": "pdf", "outputPath": path.to_str().unwrap() })).unwrap();
+
+        export_pdf_sync(std::slice::from_ref(&page), path.to_str().unwrap(), &DocumentMetadata::default(), &options).unwrap();
+        let doc = lopdf::Document::load(&path).unwrap();
+        let _ = std::fs::remove_file(&path);
+        let page_id = *doc.get_pages().values().next().unwrap();
+        let content = String::from_utf8_lossy(&doc.get_page_content(page_id).unwrap()).into_owned();
+        // Lines beside the box stop short of its left edge (less the offset)
+        let hex = |s: &str| s.bytes().map(|b| format!("{:02X}", b)).collect::<String>();
+        assert!(content.contains(&format!("<{}> Tj", hex("Lorem ipsum dolor sit"))));
+        assert!(content.contains("72 708 Tm") && content.contains("72 693.6 Tm"));
+    }
+
+    #[test]
+    fn test_page_labels_export() {
+        use crate::models::{PageLabel, PageLabelStyle, PageMetadata};
+        let labeled = |index: usize, style, number| PageData {
+            page_index: index,
+            width: 612.0,
+            height: 792.0,
+            dpi: None,
+            layers: vec![],
+            metadata: Some(PageMetadata {
+                original_page_index: Some(index),
+                rotation: None,
+                media_box: None,
+                crop_box: None,
+                trim_box: None,
+                bleed_box: None,
+                page_label: Some(PageLabel { style: Some(style), prefix: None, number }),
+                language: None,
+                guides: None,
+                custom: Default::default(),
+            }),
+            background: None,
+        };
+        let pages = vec![
+            labeled(0, PageLabelStyle::LowerRoman, 1),
+            labeled(1, PageLabelStyle::LowerRoman, 2),
+            labeled(2, PageLabelStyle::Decimal, 1),
+        ];
+        let path = std::env::temp_dir().join(format!("rook-page-labels-{}.pdf", std::process::id()));
+        let options: ExportOptions = serde_json::from_value(serde_json::json!({ "format": "pdf", "outputPath": path.to_str().unwrap() })).unwrap();
+
+        export_pdf_sync(&pages, path.to_str().unwrap(), &DocumentMetadata::default(), &options).unwrap();
+        let doc = lopdf::Document::load(&path).unwrap();
+        let _ = std::fs::remove_file(&path);
+        let texts: Vec<String> = page_labels::read_page_labels(&doc, 3).into_iter().map(|l| l.unwrap().text()).collect();
+        assert_eq!(texts, vec!["i", "ii", "1"]);
+    }
+
     #[test]
     fn test_export_error_display() {
         let err = ExportError::NoPages;
@@ -446,4 +2140,32 @@ mod tests {
         let s: String = err.into();
         assert!(s.contains("Invalid page range"));
     }
+
+    #[test]
+    fn test_read_project_file_accepts_all_formats() {
+        let project = BookProjectData::default();
+        let dir = std::env::temp_dir();
+
+        let json_path = dir.join(format!("rook-read-{}.bookproj", std::process::id()));
+        std::fs::write(&json_path, serde_json::to_vec_pretty(&project).unwrap()).unwrap();
+        let (loaded, images) = read_project_file(json_path.to_str().unwrap()).unwrap();
+        assert_eq!(loaded, project);
+        assert!(images.is_empty());
+
+        let image = ArchiveImage { id: "img-1".to_string(), data: vec![0xFF, 0xD8, 0xFF, 0] };
+        let archive_path = dir.join(format!("rook-read-{}.v2.bookproj", std::process::id()));
+        std::fs::write(&archive_path, archive::write_archive(&project, std::slice::from_ref(&image)).unwrap()).unwrap();
+        let (loaded, images) = read_project_file(archive_path.to_str().unwrap()).unwrap();
+        assert_eq!(loaded, project);
+        assert_eq!(images, vec![image]);
+
+        let msgpack_path = dir.join(format!("rook-read-{}.msgpack", std::process::id()));
+        std::fs::write(&msgpack_path, msgpack::to_vec(&project).unwrap()).unwrap();
+        let (loaded, _) = read_project_file(msgpack_path.to_str().unwrap()).unwrap();
+        assert_eq!(loaded, project);
+
+        let _ = std::fs::remove_file(json_path);
+        let _ = std::fs::remove_file(archive_path);
+        let _ = std::fs::remove_file(msgpack_path);
+    }
 }
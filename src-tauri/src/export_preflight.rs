@@ -0,0 +1,554 @@
+//! Export Preflight Module
+//!
+//! Validates a document against a target output profile before export:
+//! low-resolution images, RGB content when CMYK is required, text too close
+//! to the trim edge, missing fonts, overset text frames and transparency.
+//! Every issue references its page and, where applicable, its layer.
+
+use crate::export_handler::ExportColorSpace;
+use crate::export_presets::ExportPreset;
+use crate::models::{BlendMode, Bounds, ImageMetadata, LayerObject, LayerType, PageData, TextAlign};
+use serde::{Deserialize, Serialize};
+use vortex_core::text_wrap::{self, Exclusion};
+use vortex_core::units::POINTS_PER_INCH;
+
+/// Resolution commercial print expects from placed images
+const PRINT_DPI: u32 = 300;
+
+/// Average glyph advance as a fraction of the font size, used to estimate
+/// how much text fits in a frame without shaping it
+const AVG_CHAR_WIDTH_EM: f32 = 0.5;
+const DEFAULT_LINE_HEIGHT: f32 = 1.2;
+/// Slack allowed before a frame counts as overset
+const OVERSET_TOLERANCE: f32 = 1.05;
+
+/// Output requirements to check against
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct ExportPreflightProfile {
+    pub name: String,
+    /// Minimum effective image resolution
+    pub min_image_dpi: u32,
+    pub require_cmyk: bool,
+    /// Distance from the trim edge text must keep, in points
+    pub safe_margin: f32,
+    pub allow_transparency: bool,
+    #[serde(default = "default_check_fonts")]
+    pub check_fonts: bool,
+}
+
+fn default_check_fonts() -> bool {
+    true
+}
+
+impl ExportPreflightProfile {
+    /// Commercial print: 300dpi, CMYK, 1/8" safety margin, no transparency
+    pub fn print() -> Self {
+        Self {
+            name: "Print".to_string(),
+            min_image_dpi: PRINT_DPI,
+            require_cmyk: true,
+            safe_margin: 9.0,
+            allow_transparency: false,
+            check_fonts: true,
+        }
+    }
+
+    /// Derive a profile from an export preset
+    pub fn from_preset(preset: &ExportPreset) -> Self {
+        let require_cmyk = preset.color_space == ExportColorSpace::Cmyk;
+        Self {
+            name: preset.name.clone(),
+            min_image_dpi: preset.dpi.unwrap_or(150),
+            require_cmyk,
+            safe_margin: if require_cmyk { 9.0 } else { 4.5 },
+            allow_transparency: !require_cmyk,
+            check_fonts: true,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq, PartialOrd, Ord)]
+#[serde(rename_all = "lowercase")]
+#[repr(u8)]
+pub enum IssueSeverity {
+    Error = 0,
+    Warning = 1,
+    Info = 2,
+}
+
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+#[repr(u8)]
+pub enum PreflightIssueKind {
+    LowResolutionImage = 0,
+    RgbImage = 1,
+    RgbColor = 2,
+    TextNearTrim = 3,
+    MissingFont = 4,
+    OversetText = 5,
+    Transparency = 6,
+}
+
+/// A single preflight finding
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct PreflightIssue {
+    pub kind: PreflightIssueKind,
+    pub severity: IssueSeverity,
+    pub page_index: usize,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub layer_id: Option<String>,
+    pub message: String,
+}
+
+/// Preflight result for a profile
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ExportPreflightReport {
+    pub profile: ExportPreflightProfile,
+    pub issues: Vec<PreflightIssue>,
+    pub error_count: usize,
+    pub warning_count: usize,
+    /// No errors (warnings are allowed)
+    pub passed: bool,
+}
+
+impl ExportPreflightReport {
+    fn new(profile: ExportPreflightProfile, mut issues: Vec<PreflightIssue>) -> Self {
+        issues.sort_by_key(|i| (i.severity, i.page_index));
+        let count = |s| issues.iter().filter(|i| i.severity == s).count();
+        let error_count = count(IssueSeverity::Error);
+        let warning_count = count(IssueSeverity::Warning);
+        Self {
+            profile,
+            issues,
+            error_count,
+            warning_count,
+            passed: error_count == 0,
+        }
+    }
+}
+
+/// Pixel size of an image layer, from its metadata or the image cache
+fn pixel_size(layer: &LayerObject) -> Option<(u32, u32)> {
+    match &layer.image_data {
+        Some(meta) => Some((meta.width, meta.height)),
+        None => {
+            let id = layer.image_url.as_deref()?.trim_start_matches("image://");
+            let (w, h, _) = crate::image_handler::get_image_info(id.to_string())?;
+            Some((w, h))
+        }
+    }
+}
+
+/// Resolution an image layer prints at in its current bounds
+///
+/// Computed from the pixel size rather than the stored `dpi`, which is only
+/// as fresh as the last resize.
+pub fn effective_dpi(layer: &LayerObject) -> Option<f32> {
+    let (width, height) = pixel_size(layer)?;
+    ImageMetadata { width, height, color_space: String::new(), dpi: 0, icc_profile: None }.effective_dpi(&layer.bounds)
+}
+
+/// An image layer below the required resolution
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct ImageResolution {
+    pub page_index: usize,
+    pub layer_id: String,
+    pub effective_dpi: f32,
+    pub pixel_width: u32,
+    pub pixel_height: u32,
+    /// Largest size the image can be placed at while meeting the threshold, in points
+    pub max_width: f32,
+    pub max_height: f32,
+}
+
+/// Visible image layers that print below `min_dpi`, lowest resolution first
+pub fn low_resolution_images(pages: &[PageData], min_dpi: u32) -> Vec<ImageResolution> {
+    let mut found: Vec<ImageResolution> = pages
+        .iter()
+        .flat_map(|page| page.layers.iter().map(move |layer| (page.page_index, layer)))
+        .filter(|(_, layer)| layer.visible && layer.layer_type == LayerType::Image)
+        .filter_map(|(page_index, layer)| {
+            let dpi = effective_dpi(layer)?;
+            let (pixel_width, pixel_height) = pixel_size(layer)?;
+            let inches = |pixels: u32| pixels as f32 / min_dpi.max(1) as f32 * POINTS_PER_INCH;
+            (dpi < min_dpi as f32).then(|| ImageResolution {
+                page_index,
+                layer_id: layer.id.clone(),
+                effective_dpi: dpi,
+                pixel_width,
+                pixel_height,
+                max_width: inches(pixel_width),
+                max_height: inches(pixel_height),
+            })
+        })
+        .collect();
+    found.sort_by(|a, b| a.effective_dpi.total_cmp(&b.effective_dpi));
+    found
+}
+
+/// Hex color that is not a neutral gray
+fn is_chromatic(color: &str) -> bool {
+    let hex = color.trim_start_matches('#');
+    if hex.len() < 6 {
+        return false;
+    }
+    let channel = |i: usize| u8::from_str_radix(&hex[i..i + 2], 16).ok();
+    match (channel(0), channel(2), channel(4)) {
+        (Some(r), Some(g), Some(b)) => r != g || g != b,
+        _ => false,
+    }
+}
+
+/// Hex color with an alpha channel below opaque (#RRGGBBAA)
+fn has_alpha(color: &str) -> bool {
+    let hex = color.trim_start_matches('#');
+    hex.len() == 8 && u8::from_str_radix(&hex[6..8], 16).is_ok_and(|a| a < 255)
+}
+
+/// Estimated text height against the frame height, when overset
+///
+/// Frames that wrap around other layers or hold a drop cap are laid out
+/// line by line so the narrowed lines count.
+fn overset_ratio(layer: &LayerObject, exclusions: &[Exclusion]) -> Option<f32> {
+    let content = layer.content.as_deref()?;
+    let font_size = layer.font_size.unwrap_or(12.0);
+    if layer.bounds.width <= 0.0 || font_size <= 0.0 {
+        return None;
+    }
+    let char_width = font_size * AVG_CHAR_WIDTH_EM + layer.letter_spacing.unwrap_or(0.0);
+    let line_height = font_size * layer.line_height.unwrap_or(DEFAULT_LINE_HEIGHT);
+    if !exclusions.is_empty() || layer.drop_cap.is_some() {
+        // Lay out into an unbounded frame and see where the text ends
+        let tall = Bounds { height: f32::MAX / 2.0, ..layer.bounds };
+        let capped = LayerObject { bounds: tall, ..layer.clone() };
+        let (cap_bottom, layout) = match text_wrap::layout_drop_cap(&capped, content, exclusions) {
+            Some((cap, layout)) => (cap.baseline - layer.bounds.y, layout),
+            None => {
+                let measure = |s: &str| s.chars().count() as f32 * char_width;
+                (0.0, text_wrap::layout_frame(content, tall, line_height, TextAlign::Left, exclusions, measure))
+            }
+        };
+        let needed = layout.lines.last().map_or(0.0, |l| l.y + line_height - layer.bounds.y).max(cap_bottom);
+        let ratio = needed / layer.bounds.height.max(1.0);
+        return (ratio > OVERSET_TOLERANCE).then_some(ratio);
+    }
+    let chars_per_line = (layer.bounds.width / char_width).floor().max(1.0) as usize;
+    let lines: usize = content
+        .lines()
+        .map(|line| line.chars().count().div_ceil(chars_per_line).max(1))
+        .sum();
+    let needed = lines as f32 * line_height;
+    let ratio = needed / layer.bounds.height.max(1.0);
+    (ratio > OVERSET_TOLERANCE).then_some(ratio)
+}
+
+/// Run all layer-level checks except fonts (which need the font manager)
+pub fn check_pages(pages: &[PageData], profile: &ExportPreflightProfile) -> Vec<PreflightIssue> {
+    let mut issues = Vec::new();
+
+    for page in pages {
+        for layer in page.layers.iter().filter(|l| l.visible) {
+            let mut push = |kind, severity, message: String| {
+                issues.push(PreflightIssue {
+                    kind,
+                    severity,
+                    page_index: page.page_index,
+                    layer_id: Some(layer.id.clone()),
+                    message,
+                })
+            };
+
+            if layer.layer_type == LayerType::Image {
+                if let Some(dpi) = effective_dpi(layer) {
+                    let min = profile.min_image_dpi as f32;
+                    if dpi < min {
+                        let severity = if dpi < min / 2.0 {
+                            IssueSeverity::Error
+                        } else {
+                            IssueSeverity::Warning
+                        };
+                        push(
+                            PreflightIssueKind::LowResolutionImage,
+                            severity,
+                            format!("Image is {:.0} dpi, profile requires {}", dpi, profile.min_image_dpi),
+                        );
+                    }
+                }
+                if profile.require_cmyk {
+                    let space = layer
+                        .image_data
+                        .as_ref()
+                        .map(|m| m.color_space.to_ascii_uppercase())
+                        .unwrap_or_else(|| "RGB".to_string());
+                    if !space.contains("CMYK") && !space.contains("GRAY") {
+                        push(
+                            PreflightIssueKind::RgbImage,
+                            IssueSeverity::Warning,
+                            format!("{} image will be converted to CMYK", space),
+                        );
+                    }
+                }
+            }
+
+            let colors = [&layer.color, &layer.fill_color, &layer.stroke_color, &layer.background_color];
+            if profile.require_cmyk
+                && colors.iter().filter_map(|c| c.as_deref()).any(is_chromatic)
+            {
+                push(
+                    PreflightIssueKind::RgbColor,
+                    IssueSeverity::Info,
+                    "RGB color will be converted to CMYK".to_string(),
+                );
+            }
+
+            let blend_mode = layer.blend_mode.filter(|m| *m != BlendMode::Normal);
+            if !profile.allow_transparency
+                && (layer.opacity < 1.0
+                    || blend_mode.is_some()
+                    || colors.iter().filter_map(|c| c.as_deref()).any(has_alpha))
+            {
+                let detail = match blend_mode {
+                    Some(mode) => format!("{} blend", mode.css_name()),
+                    None => format!("{:.0}% opacity", layer.opacity * 100.0),
+                };
+                push(
+                    PreflightIssueKind::Transparency,
+                    IssueSeverity::Warning,
+                    format!("Layer uses transparency ({})", detail),
+                );
+            }
+
+            if layer.layer_type == LayerType::Text {
+                let b = &layer.bounds;
+                let distance = b.x.min(b.y).min(page.width - (b.x + b.width)).min(page.height - (b.y + b.height));
+                if distance < 0.0 {
+                    push(
+                        PreflightIssueKind::TextNearTrim,
+                        IssueSeverity::Error,
+                        "Text extends past the trim edge".to_string(),
+                    );
+                } else if distance < profile.safe_margin {
+                    push(
+                        PreflightIssueKind::TextNearTrim,
+                        IssueSeverity::Warning,
+                        format!("Text is {:.1}pt from trim, safe margin is {:.1}pt", distance, profile.safe_margin),
+                    );
+                }
+
+                if let Some(ratio) = overset_ratio(layer, &text_wrap::page_exclusions(page, layer)) {
+                    push(
+                        PreflightIssueKind::OversetText,
+                        IssueSeverity::Warning,
+                        format!("Text needs about {:.0}% of the frame height", ratio * 100.0),
+                    );
+                }
+            }
+        }
+    }
+
+    issues
+}
+
+/// Validate a document against an output profile
+///
+/// Uses `profile` when given, otherwise one derived from `preset_name`,
+/// otherwise the built-in print profile.
+#[tauri::command]
+pub async fn preflight_export(
+    pages: Vec<PageData>,
+    profile: Option<ExportPreflightProfile>,
+    preset_name: Option<String>,
+) -> Result<ExportPreflightReport, String> {
+    let profile = match (profile, preset_name) {
+        (Some(profile), _) => profile,
+        (None, Some(name)) => crate::export_presets::resolve_preset(&name, &[])
+            .map(|p| ExportPreflightProfile::from_preset(&p))
+            .ok_or_else(|| format!("Preset '{}' not found", name))?,
+        (None, None) => ExportPreflightProfile::print(),
+    };
+
+    let mut issues = check_pages(&pages, &profile);
+
+    if profile.check_fonts {
+        let audit = crate::font_manager::audit_fonts(pages).await?;
+        for usage in audit
+            .fonts
+            .iter()
+            .filter(|f| f.availability == crate::font_manager::audit::FontAvailability::Missing)
+        {
+            for location in &usage.locations {
+                issues.push(PreflightIssue {
+                    kind: PreflightIssueKind::MissingFont,
+                    severity: IssueSeverity::Error,
+                    page_index: location.page_index,
+                    layer_id: Some(location.layer_id.clone()),
+                    message: format!("Font '{}' is not available", usage.family),
+                });
+            }
+        }
+    }
+
+    Ok(ExportPreflightReport::new(profile, issues))
+}
+
+/// List image layers that print below `min_dpi` (300 when absent) at their current size
+#[tauri::command]
+pub fn check_image_resolution(pages: Vec<PageData>, min_dpi: Option<u32>) -> Vec<ImageResolution> {
+    low_resolution_images(&pages, min_dpi.unwrap_or(PRINT_DPI))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::{ImageMetadata, LayerRole, SourceType, TextWrap, WrapContour};
+
+    fn layer(id: &str, layer_type: LayerType, bounds: Bounds) -> LayerObject {
+        LayerObject {
+            id: id.to_string(),
+            layer_type,
+            bounds,
+            visible: true,
+            locked: false,
+            z_index: 0,
+            opacity: 1.0,
+            blend_mode: None,
+            content: None,
+            font_family: None,
+            font_size: None,
+            font_weight: None,
+            font_style: None,
+            color: None,
+            text_align: None,
+            text_decoration: None,
+            text_transform: None,
+            line_height: None,
+            letter_spacing: None,
+            background_color: None,
+            text_path: None,
+            text_outline: None,
+            text_shadow: None,
+            drop_cap: None,
+            notes: Vec::new(),
+            pagination: None,
+            image_url: None,
+            image_path: None,
+            image_data: None,
+            image_link: None,
+            shape_type: None,
+            stroke_color: None,
+            stroke_width: None,
+            fill_color: None,
+            text_wrap: None,
+            path_data: None,
+            transform: None,
+            source_type: SourceType::Manual,
+            role: LayerRole::Content,
+            ocr: None,
+            tags: Vec::new(),
+        }
+    }
+
+    fn page(layers: Vec<LayerObject>) -> PageData {
+        PageData {
+            page_index: 0,
+            width: 612.0,
+            height: 792.0,
+            dpi: None,
+            layers,
+            metadata: None,
+            background: None,
+        }
+    }
+
+    fn kinds(issues: &[PreflightIssue], id: &str) -> Vec<PreflightIssueKind> {
+        issues
+            .iter()
+            .filter(|i| i.layer_id.as_deref() == Some(id))
+            .map(|i| i.kind)
+            .collect()
+    }
+
+    #[test]
+    fn test_image_checks() {
+        // 300px across 144pt (2in) = 150dpi
+        let mut img = layer("img", LayerType::Image, Bounds::new(72.0, 72.0, 144.0, 144.0));
+        img.image_data = Some(ImageMetadata {
+            width: 300,
+            height: 300,
+            color_space: "RGBA".to_string(),
+            dpi: 72,
+            icc_profile: None,
+        });
+        img.opacity = 0.5;
+
+        let issues = check_pages(&[page(vec![img])], &ExportPreflightProfile::print());
+        let found = kinds(&issues, "img");
+        assert!(found.contains(&PreflightIssueKind::LowResolutionImage));
+        assert!(found.contains(&PreflightIssueKind::RgbImage));
+        assert!(found.contains(&PreflightIssueKind::Transparency));
+        let low_res = issues.iter().find(|i| i.kind == PreflightIssueKind::LowResolutionImage).unwrap();
+        assert_eq!(low_res.severity, IssueSeverity::Warning);
+    }
+
+    #[test]
+    fn test_text_checks() {
+        let mut near = layer("near", LayerType::Text, Bounds::new(4.0, 100.0, 200.0, 20.0));
+        near.content = Some("Hi".to_string());
+        near.color = Some("#FF0000".to_string());
+        let mut overset = layer("overset", LayerType::Text, Bounds::new(72.0, 200.0, 60.0, 14.0));
+        overset.content = Some("This sentence cannot possibly fit in such a small frame".to_string());
+        overset.font_size = Some(12.0);
+        let mut ok = layer("ok", LayerType::Text, Bounds::new(72.0, 300.0, 400.0, 20.0));
+        ok.content = Some("Fits fine".to_string());
+
+        let issues = check_pages(&[page(vec![near, overset, ok])], &ExportPreflightProfile::print());
+        assert_eq!(kinds(&issues, "near"), vec![PreflightIssueKind::RgbColor, PreflightIssueKind::TextNearTrim]);
+        assert_eq!(kinds(&issues, "overset"), vec![PreflightIssueKind::OversetText]);
+        assert!(kinds(&issues, "ok").is_empty());
+    }
+
+    #[test]
+    fn test_overset_counts_text_wrap() {
+        let mut frame = layer("frame", LayerType::Text, Bounds::new(72.0, 100.0, 200.0, 28.0));
+        frame.content = Some("Wrapped text needs more lines beside the picture here".to_string());
+        let mut picture = layer("picture", LayerType::Image, Bounds::new(172.0, 90.0, 150.0, 60.0));
+        picture.image_data = Some(ImageMetadata { width: 2000, height: 800, color_space: "CMYK".to_string(), dpi: 300, icc_profile: None });
+
+        let profile = ExportPreflightProfile::print();
+        assert!(kinds(&check_pages(&[page(vec![frame.clone(), picture.clone()])], &profile), "frame").is_empty());
+        picture.text_wrap = Some(TextWrap { contour: WrapContour::BoundingBox, offset: 0.0 });
+        let issues = check_pages(&[page(vec![frame, picture])], &profile);
+        assert_eq!(kinds(&issues, "frame"), vec![PreflightIssueKind::OversetText]);
+    }
+
+    #[test]
+    fn test_report_counts_and_preset_profile() {
+        let preset = crate::export_presets::resolve_preset("Web PDF RGB", &[]).unwrap();
+        let profile = ExportPreflightProfile::from_preset(&preset);
+        assert!(!profile.require_cmyk && profile.allow_transparency);
+
+        let mut past = layer("past", LayerType::Text, Bounds::new(600.0, 100.0, 50.0, 20.0));
+        past.content = Some("x".to_string());
+        let report = ExportPreflightReport::new(profile.clone(), check_pages(&[page(vec![past])], &profile));
+        assert_eq!(report.error_count, 1);
+        assert!(!report.passed);
+    }
+
+    #[test]
+    fn test_low_resolution_images_use_current_bounds() {
+        // Stored dpi is stale: 600px over 288pt (4in) is 150dpi now
+        let mut resized = layer("resized", LayerType::Image, Bounds::new(72.0, 72.0, 288.0, 288.0));
+        resized.image_data = Some(ImageMetadata { width: 600, height: 600, color_space: "RGB".to_string(), dpi: 300, icc_profile: None });
+        let mut sharp = layer("sharp", LayerType::Image, Bounds::new(72.0, 400.0, 144.0, 144.0));
+        sharp.image_data = Some(ImageMetadata { width: 600, height: 600, color_space: "RGB".to_string(), dpi: 300, icc_profile: None });
+
+        let found = low_resolution_images(&[page(vec![resized, sharp])], 300);
+        assert_eq!(found.len(), 1);
+        assert_eq!((found[0].layer_id.as_str(), found[0].effective_dpi), ("resized", 150.0));
+        assert_eq!((found[0].max_width, found[0].max_height), (144.0, 144.0));
+    }
+}
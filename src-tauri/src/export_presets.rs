@@ -0,0 +1,277 @@
+//! Export Presets Module
+//!
+//! Named export profiles so `ExportOptions` need not be re-specified for
+//! every export. Presets are resolved from three places, first match wins:
+//! 1. the project's own presets (`ProjectSettings::export_presets`)
+//! 2. user presets persisted in the app data dir (`export_presets.json`)
+//! 3. built-in presets
+//!
+//! Export pipelines go one step further: several outputs of one document,
+//! each with its own page range and options, written by a single command.
+
+use crate::models::{DocumentMetadata, ExportResult, PageData, TrackedChange};
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, RwLock};
+use vortex_core::export::ExportOptions;
+
+pub use vortex_core::export::{builtin_presets, ExportPipeline, ExportPreset, ExportTarget};
+
+const PRESETS_FILE: &str = "export_presets.json";
+
+/// Earlier names of built-in presets, still accepted from saved settings
+const RENAMED_BUILTINS: [(&str, &str); 2] = [("Print PDF 300dpi CMYK", "Print PDF CMYK"), ("Web PDF 96dpi RGB", "Web PDF RGB")];
+
+#[derive(Debug, Default)]
+struct PresetStore {
+    presets: Vec<ExportPreset>,
+    path: Option<PathBuf>,
+}
+
+lazy_static::lazy_static! {
+    static ref PRESET_STORE: Arc<RwLock<PresetStore>> = Arc::new(RwLock::new(PresetStore::default()));
+}
+
+/// Load user presets from `dir` and remember it for later saves
+pub fn init_export_presets(dir: PathBuf) -> Result<(), String> {
+    fs::create_dir_all(&dir).map_err(|e| e.to_string())?;
+    let path = dir.join(PRESETS_FILE);
+    let presets: Vec<ExportPreset> = fs::read(&path)
+        .ok()
+        .and_then(|data| serde_json::from_slice(&data).ok())
+        .unwrap_or_default();
+
+    let mut store = PRESET_STORE.write().map_err(|e| e.to_string())?;
+    store.presets = presets;
+    store.path = Some(path);
+    Ok(())
+}
+
+fn persist(store: &PresetStore) -> Result<(), String> {
+    let Some(path) = &store.path else {
+        return Ok(());
+    };
+    let data = serde_json::to_vec_pretty(&store.presets).map_err(|e| e.to_string())?;
+    let tmp = path.with_extension("json.tmp");
+    fs::write(&tmp, data).map_err(|e| e.to_string())?;
+    fs::rename(&tmp, path).map_err(|e| e.to_string())
+}
+
+fn is_builtin_name(name: &str) -> bool {
+    builtin_presets().iter().any(|p| p.name.eq_ignore_ascii_case(name))
+}
+
+/// Find a preset by name (case-insensitive), project presets first
+pub fn resolve_preset(name: &str, project_presets: &[ExportPreset]) -> Option<ExportPreset> {
+    let matches = |p: &&ExportPreset| p.name.eq_ignore_ascii_case(name);
+    if let Some(p) = project_presets.iter().find(matches) {
+        return Some(p.clone());
+    }
+    let user = PRESET_STORE
+        .read()
+        .ok()
+        .and_then(|s| s.presets.iter().find(matches).cloned());
+    let builtin = RENAMED_BUILTINS.iter().find(|(old, _)| old.eq_ignore_ascii_case(name)).map_or(name, |(_, new)| *new);
+    user.or_else(|| builtin_presets().into_iter().find(|p| p.name.eq_ignore_ascii_case(builtin)))
+}
+
+/// List built-in and user presets (user presets first)
+#[tauri::command]
+pub fn list_export_presets() -> Result<Vec<ExportPreset>, String> {
+    let store = PRESET_STORE.read().map_err(|e| e.to_string())?;
+    let mut presets = store.presets.clone();
+    presets.extend(builtin_presets());
+    Ok(presets)
+}
+
+/// Save a user preset, replacing any existing preset with the same name
+#[tauri::command]
+pub fn save_export_preset(preset: ExportPreset) -> Result<(), String> {
+    let name = preset.name.trim();
+    if name.is_empty() {
+        return Err("Preset name cannot be empty".to_string());
+    }
+    if is_builtin_name(name) {
+        return Err(format!("'{}' is a built-in preset", name));
+    }
+    let preset = ExportPreset {
+        name: name.to_string(),
+        builtin: false,
+        ..preset
+    };
+
+    let mut store = PRESET_STORE.write().map_err(|e| e.to_string())?;
+    match store.presets.iter_mut().find(|p| p.name.eq_ignore_ascii_case(name)) {
+        Some(existing) => *existing = preset,
+        None => store.presets.push(preset),
+    }
+    persist(&store)
+}
+
+/// Delete a user preset
+#[tauri::command]
+pub fn delete_export_preset(name: String) -> Result<(), String> {
+    if is_builtin_name(&name) {
+        return Err(format!("'{}' is a built-in preset", name));
+    }
+    let mut store = PRESET_STORE.write().map_err(|e| e.to_string())?;
+    let before = store.presets.len();
+    store.presets.retain(|p| !p.name.eq_ignore_ascii_case(&name));
+    if store.presets.len() == before {
+        return Err(format!("Preset '{}' not found", name));
+    }
+    persist(&store)
+}
+
+/// Options for an export to `output_path` that names no preset: the default
+/// preset setting when it resolves, plain PDF otherwise
+pub fn default_options(output_path: String, project_presets: &[ExportPreset]) -> Result<ExportOptions, String> {
+    let preset = crate::settings::current().export_preset.and_then(|name| resolve_preset(&name, project_presets));
+    match preset {
+        Some(preset) => Ok(preset.to_options(output_path, Vec::new())),
+        None => serde_json::from_value(serde_json::json!({ "format": "pdf", "outputPath": output_path }))
+            .map_err(|e| e.to_string()),
+    }
+}
+
+/// Export options seeded from the default export preset setting
+#[tauri::command]
+pub fn get_default_export_options(
+    output_path: String,
+    project_presets: Option<Vec<ExportPreset>>,
+) -> Result<ExportOptions, String> {
+    default_options(output_path, project_presets.as_deref().unwrap_or_default())
+}
+
+/// Export using a named preset
+#[tauri::command]
+pub async fn export_with_preset(
+    preset_name: String,
+    pages: Vec<PageData>,
+    output_path: String,
+    metadata: DocumentMetadata,
+    project_presets: Option<Vec<ExportPreset>>,
+    changes: Option<Vec<TrackedChange>>,
+    track_changes: Option<bool>,
+) -> Result<ExportResult, String> {
+    let preset = resolve_preset(&preset_name, project_presets.as_deref().unwrap_or_default())
+        .ok_or_else(|| format!("Preset '{}' not found", preset_name))?;
+    let options = ExportOptions {
+        track_changes: track_changes.unwrap_or(false),
+        ..preset.to_options(output_path.clone(), changes.unwrap_or_default())
+    };
+
+    crate::export_handler::export_document(
+        preset.format.as_str().to_string(),
+        pages,
+        output_path,
+        metadata,
+        options,
+    )
+    .await
+}
+
+/// Pages and options of one pipeline target: its page range is cut out of
+/// the pages up front and a relative output path is resolved in `output_dir`
+fn target_job(target: ExportTarget, pages: &[PageData], output_dir: Option<&str>) -> (Vec<PageData>, ExportOptions) {
+    let mut options = target.options;
+    let pages = match options.page_range.take() {
+        Some((start, end)) => pages[start..=end].to_vec(),
+        None => pages.to_vec(),
+    };
+    if let Some(dir) = output_dir {
+        options.output_path = Path::new(dir).join(&options.output_path).to_string_lossy().to_string();
+    }
+    (pages, options)
+}
+
+/// Export the targets of a pipeline one after another, e.g. the interior
+/// and the cover of a book with different bleed, color space and DPI
+///
+/// Returns one result per target, in order; a failed target does not stop
+/// the ones after it.
+#[tauri::command]
+pub async fn export_pipeline(
+    pipeline: ExportPipeline,
+    pages: Vec<PageData>,
+    metadata: DocumentMetadata,
+    output_dir: Option<String>,
+) -> Result<Vec<ExportResult>, String> {
+    pipeline.validate(pages.len())?;
+    let mut results = Vec::with_capacity(pipeline.targets.len());
+    for target in pipeline.targets {
+        let (target_pages, options) = target_job(target, &pages, output_dir.as_deref());
+        let format = options.format.as_str().to_string();
+        let output_path = options.output_path.clone();
+        results.push(
+            crate::export_handler::export_document(format, target_pages, output_path, metadata.clone(), options).await?,
+        );
+    }
+    Ok(results)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use vortex_core::export::{ExportColorSpace, ExportFormat};
+
+    #[test]
+    fn test_builtin_presets_roundtrip() {
+        for preset in builtin_presets() {
+            let json = serde_json::to_string(&preset).unwrap();
+            let parsed: ExportPreset = serde_json::from_str(&json).unwrap();
+            assert_eq!(parsed.name, preset.name);
+            assert_eq!(parsed.format, preset.format);
+            assert!(!parsed.builtin);
+        }
+    }
+
+    #[test]
+    fn test_project_preset_takes_precedence() {
+        let mut custom = builtin_presets().remove(0);
+        custom.builtin = false;
+        custom.dpi = Some(600);
+        let resolved = resolve_preset("print pdf cmyk", &[custom]).unwrap();
+        assert_eq!(resolved.dpi, Some(600));
+
+        let fallback = resolve_preset("Web PDF RGB", &[]).unwrap();
+        assert!(fallback.builtin);
+        // Settings saved before the rename still find the preset
+        assert_eq!(resolve_preset("web pdf 96dpi rgb", &[]).unwrap(), fallback);
+        assert!(resolve_preset("No such preset", &[]).is_none());
+    }
+
+    #[test]
+    fn test_target_job() {
+        let pages: Vec<PageData> = (0..4)
+            .map(|i| PageData {
+                page_index: i,
+                width: 612.0,
+                height: 792.0,
+                dpi: None,
+                layers: Vec::new(),
+                metadata: None,
+                background: None,
+            })
+            .collect();
+        let preset = resolve_preset("Print PDF CMYK", &[]).unwrap();
+        let mut options = preset.to_options("cover.pdf".to_string(), Vec::new());
+        options.page_range = Some((1, 2));
+        let target = ExportTarget { name: "Cover".to_string(), options };
+
+        let (target_pages, options) = target_job(target.clone(), &pages, Some("/out"));
+        assert_eq!(target_pages.iter().map(|p| p.page_index).collect::<Vec<_>>(), [1, 2]);
+        assert_eq!((options.page_range, options.output_path.as_str()), (None, "/out/cover.pdf"));
+        assert_eq!(target_job(target, &pages, None).1.output_path, "cover.pdf");
+    }
+
+    #[test]
+    fn test_to_options() {
+        let preset = resolve_preset("Print PDF CMYK", &[]).unwrap();
+        let options = preset.to_options("/tmp/out.pdf".to_string(), Vec::new());
+        assert_eq!(options.format, ExportFormat::Pdf);
+        assert_eq!(options.color_space, ExportColorSpace::Cmyk);
+        assert_eq!(options.dpi, Some(300));
+        assert!(save_export_preset(preset).is_err());
+    }
+}
@@ -7,6 +7,8 @@
 //! - EmbeddedFontExtractor: Extract fonts from PDFs
 //! - DocxFontExtractor: Extract fonts from DOCX
 //! - FontInstaller: Cross-platform font installation
+//! - MatchCache: Persistent match cache with user overrides
+//! - FontAudit: Usage report & missing-font detection
 //!
 //! ## Zero-Cost Abstractions
 //! - Uses `Cow<str>` for zero-copy string handling where possible
@@ -14,10 +16,13 @@
 //! - `const fn` for compile-time evaluation
 
 use serde::{Deserialize, Serialize};
-use std::borrow::Cow;
+use dashmap::DashMap;
 use std::collections::HashMap;
 use std::path::PathBuf;
 use std::sync::{Arc, RwLock};
+use std::time::Instant;
+
+pub use vortex_core::font_names::{FontWidth, ParsedFontName};
 
 // ============================================================================
 // TYPES & STRUCTS
@@ -33,6 +38,9 @@ pub struct FontInfo {
     pub source: FontSource,
     pub path: Option<String>,
     pub is_variable: bool,
+    /// File name of a user-installed font, the id `uninstall_font` takes
+    #[serde(default)]
+    pub id: Option<String>,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Default)]
@@ -43,22 +51,6 @@ pub struct FontStyle {
     pub width: FontWidth,
 }
 
-#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq, Default)]
-#[serde(rename_all = "lowercase")]
-#[repr(u8)]
-pub enum FontWidth {
-    UltraCondensed = 1,
-    ExtraCondensed = 2,
-    Condensed = 3,
-    SemiCondensed = 4,
-    #[default]
-    Normal = 5,
-    SemiExpanded = 6,
-    Expanded = 7,
-    ExtraExpanded = 8,
-    UltraExpanded = 9,
-}
-
 #[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
 #[serde(rename_all = "lowercase")]
 #[repr(u8)]
@@ -91,17 +83,6 @@ pub struct GoogleFont {
     pub files: HashMap<String, String>,
 }
 
-#[derive(Debug, Clone, Serialize, Deserialize)]
-#[serde(rename_all = "camelCase")]
-pub struct ParsedFontName {
-    pub family: String,
-    pub weight: u16,
-    pub is_italic: bool,
-    pub is_bold: bool,
-    pub width: FontWidth,
-    pub original: String,
-}
-
 #[derive(Debug, Clone, Serialize, Deserialize)]
 #[serde(rename_all = "camelCase")]
 pub struct FontMetrics {
@@ -134,18 +115,44 @@ impl Default for FontMetrics {
 // ============================================================================
 
 lazy_static::lazy_static! {
-    static ref FONT_MANAGER: Arc<RwLock<FontManagerState>> = 
-        Arc::new(RwLock::new(FontManagerState::default()));
+    static ref FONT_MANAGER: FontManagerState = FontManagerState::default();
 }
 
+/// Each part of the font state has its own lock or sharded map, so a system
+/// scan or Google Fonts fetch never blocks match lookups, overrides or
+/// embedded font reads, and no lock is held across an await
 #[derive(Default)]
 struct FontManagerState {
-    system_fonts: Vec<FontInfo>,
-    google_fonts: Vec<GoogleFont>,
-    embedded_fonts: HashMap<String, EmbeddedFont>,
-    font_cache: HashMap<String, FontMatch>,
-    last_system_scan: Option<std::time::Instant>,
-    google_fonts_loaded: bool,
+    /// Fonts found by the last system scan and when it finished
+    system_fonts: RwLock<Option<(Instant, Vec<FontInfo>)>>,
+    /// Held while scanning, so concurrent callers share one scan
+    system_scan: tokio::sync::Mutex<()>,
+    /// Google Fonts list, `None` until fetched
+    google_fonts: RwLock<Option<Arc<Vec<GoogleFont>>>>,
+    google_index: RwLock<Option<Arc<matcher::FontIndex>>>,
+    embedded_fonts: DashMap<String, EmbeddedFont>,
+    /// Matches keyed by `match_cache::cache_key`
+    font_cache: DashMap<String, FontMatch>,
+    font_overrides: DashMap<String, match_cache::FontOverride>,
+    cache_path: RwLock<Option<PathBuf>>,
+}
+
+/// Google Fonts list if it has been fetched
+fn loaded_google_fonts() -> Option<Arc<Vec<GoogleFont>>> {
+    FONT_MANAGER.google_fonts.read().ok().and_then(|fonts| fonts.clone())
+}
+
+fn set_google_fonts(fonts: &[GoogleFont]) {
+    if let Ok(mut slot) = FONT_MANAGER.google_fonts.write() {
+        *slot = Some(Arc::new(fonts.to_vec()));
+    }
+}
+
+/// Make the next `get_system_fonts` rescan
+fn invalidate_system_scan() {
+    if let Ok(mut slot) = FONT_MANAGER.system_fonts.write() {
+        *slot = None;
+    }
 }
 
 #[derive(Debug, Clone)]
@@ -161,189 +168,121 @@ struct EmbeddedFont {
 // FONT NORMALIZER - Parse and clean font names
 // ============================================================================
 
+/// Shared with the browser build (`vortex_core::font_names`)
 pub mod normalizer {
-    use super::*;
+    pub use vortex_core::font_names::{
+        get_canonical_name, normalize_for_comparison, parse_font_name, remove_subset_prefix,
+    };
+}
 
-    /// Parse a raw font name into structured components
-    #[inline]
-    pub fn parse_font_name(raw: &str) -> ParsedFontName {
-        let original = raw.to_string();
-        
-        // Remove PDF subset prefix (e.g., "ABCDEF+FontName" -> "FontName")
-        let name = remove_subset_prefix(raw);
-        
-        // Extract weight and style
-        let (family, weight, is_bold) = extract_weight(&name);
-        let (family, is_italic) = extract_italic(&family);
-        let (family, width) = extract_width(&family);
-        
-        // Clean remaining artifacts
-        let family = clean_family_name(&family);
-        
-        ParsedFontName {
-            family,
-            weight,
-            is_italic,
-            is_bold,
-            width,
-            original,
-        }
-    }
+// ============================================================================
+// FONT MATCHER - Fuzzy matching with confidence scoring
+// ============================================================================
 
-    /// Remove PDF subset prefix (6 uppercase letters + plus sign)
-    /// Zero-copy when no prefix exists
-    #[inline]
-    fn remove_subset_prefix(name: &str) -> Cow<'_, str> {
-        if let Some(pos) = name.find('+') {
-            if pos == 6 && name[..pos].chars().all(|c| c.is_ascii_uppercase()) {
-                return Cow::Owned(name[pos + 1..].to_string());
+pub mod matcher {
+    use super::*;
+    use vortex_core::font_names::{
+        fallback_stack, fallback_stack_with_category, find_family, generic_css_stack, guess_font_category,
+    };
+
+    pub use vortex_core::font_names::calculate_similarity;
+
+    /// Maximum Google Fonts candidates scored per indexed lookup
+    const INDEX_CANDIDATE_LIMIT: usize = 32;
+
+    /// Trigram + prefix index over Google Fonts names
+    ///
+    /// Narrows fuzzy matching to a small candidate set instead of running
+    /// Levenshtein against every family.
+    #[derive(Debug, Default, Clone)]
+    pub struct FontIndex {
+        /// The font list the index was built from, kept alive so its address
+        /// identifies it
+        fonts: Arc<Vec<GoogleFont>>,
+        normalized: Vec<String>,
+        exact: HashMap<String, usize>,
+        trigrams: HashMap<String, Vec<usize>>,
+        prefixes: HashMap<String, Vec<usize>>,
+    }
+
+    impl FontIndex {
+        pub fn build(fonts: Arc<Vec<GoogleFont>>) -> Self {
+            let mut index = Self { fonts, ..Self::default() };
+
+            for (id, font) in index.fonts.iter().enumerate() {
+                let normalized = normalizer::normalize_for_comparison(&font.family);
+
+                index.exact.entry(normalized.clone()).or_insert(id);
+                index.prefixes.entry(prefix_of(&normalized)).or_default().push(id);
+                for gram in trigrams_of(&normalized) {
+                    let postings = index.trigrams.entry(gram).or_default();
+                    if postings.last() != Some(&id) {
+                        postings.push(id);
+                    }
+                }
+                index.normalized.push(normalized);
             }
+
+            index
         }
-        Cow::Borrowed(name)
-    }
-
-    /// Extract font weight from name
-    fn extract_weight(name: &str) -> (String, u16, bool) {
-        let lower = name.to_lowercase();
-        let patterns = [
-            ("ultrathin", 50), ("hairline", 100), ("thin", 100),
-            ("extralight", 200), ("ultralight", 200),
-            ("light", 300), ("semilight", 350),
-            ("regular", 400), ("normal", 400), ("book", 400),
-            ("medium", 500),
-            ("semibold", 600), ("demibold", 600), ("demi", 600),
-            ("bold", 700),
-            ("extrabold", 800), ("ultrabold", 800), ("heavy", 800),
-            ("black", 900), ("extrablack", 950), ("ultrablack", 950),
-        ];
 
-        let mut weight = 400u16;
-        let mut is_bold = false;
-        let mut cleaned = name.to_string();
-
-        for (pattern, w) in patterns {
-            if lower.contains(pattern) {
-                weight = w;
-                is_bold = w >= 700;
-                // Remove pattern from name (case-insensitive)
-                let re = regex_lite::Regex::new(&format!(r"(?i)[-_]?{}[-_]?", pattern)).unwrap();
-                cleaned = re.replace_all(&cleaned, "").to_string();
-                break;
-            }
+        /// Whether the index was built from this very font list
+        #[inline]
+        pub fn is_for(&self, fonts: &[GoogleFont]) -> bool {
+            std::ptr::eq(self.fonts.as_slice(), fonts)
         }
 
-        (cleaned.trim().to_string(), weight, is_bold)
-    }
+        #[inline]
+        pub fn len(&self) -> usize {
+            self.normalized.len()
+        }
 
-    /// Extract italic/oblique style
-    fn extract_italic(name: &str) -> (String, bool) {
-        let lower = name.to_lowercase();
-        let is_italic = lower.contains("italic") || lower.contains("oblique") || lower.contains("ital");
-        
-        if is_italic {
-            let re = regex_lite::Regex::new(r"(?i)[-_]?(italic|oblique|ital)[-_]?").unwrap();
-            let cleaned = re.replace_all(name, "").to_string();
-            (cleaned.trim().to_string(), true)
-        } else {
-            (name.to_string(), false)
-        }
-    }
-
-    /// Extract width variant
-    fn extract_width(name: &str) -> (String, FontWidth) {
-        let lower = name.to_lowercase();
-        let patterns = [
-            ("ultracondensed", FontWidth::UltraCondensed),
-            ("extracondensed", FontWidth::ExtraCondensed),
-            ("semicondensed", FontWidth::SemiCondensed),
-            ("condensed", FontWidth::Condensed),
-            ("narrow", FontWidth::Condensed),
-            ("compressed", FontWidth::Condensed),
-            ("semiexpanded", FontWidth::SemiExpanded),
-            ("extraexpanded", FontWidth::ExtraExpanded),
-            ("ultraexpanded", FontWidth::UltraExpanded),
-            ("expanded", FontWidth::Expanded),
-            ("wide", FontWidth::Expanded),
-        ];
+        #[inline]
+        pub fn is_empty(&self) -> bool {
+            self.normalized.is_empty()
+        }
 
-        for (pattern, width) in patterns {
-            if lower.contains(pattern) {
-                let re = regex_lite::Regex::new(&format!(r"(?i)[-_]?{}[-_]?", pattern)).unwrap();
-                let cleaned = re.replace_all(name, "").to_string();
-                return (cleaned.trim().to_string(), width);
-            }
+        /// Exact normalized-name lookup
+        #[inline]
+        pub fn exact(&self, query_normalized: &str) -> Option<usize> {
+            self.exact.get(query_normalized).copied()
         }
 
-        (name.to_string(), FontWidth::Normal)
-    }
+        /// Candidate ids ranked by shared trigrams (prefix matches get a bonus)
+        pub fn candidates(&self, query_normalized: &str, limit: usize) -> Vec<usize> {
+            let mut scores: HashMap<usize, u32> = HashMap::new();
 
-    /// Clean remaining artifacts from family name
-    fn clean_family_name(name: &str) -> String {
-        let mut cleaned = name.to_string();
-        
-        // Remove common suffixes
-        let suffixes = ["MT", "PS", "Std", "Pro", "LT", "EF", "ITC", "BT", "Com"];
-        for suffix in suffixes {
-            if cleaned.ends_with(suffix) {
-                cleaned = cleaned[..cleaned.len() - suffix.len()].trim_end_matches('-').to_string();
+            for gram in trigrams_of(query_normalized) {
+                if let Some(postings) = self.trigrams.get(&gram) {
+                    for &id in postings {
+                        *scores.entry(id).or_insert(0) += 1;
+                    }
+                }
             }
-        }
-        
-        // Remove version numbers
-        let re = regex_lite::Regex::new(r"[-_]?v?\d+(\.\d+)*$").unwrap();
-        cleaned = re.replace_all(&cleaned, "").to_string();
-        
-        // Normalize spacing
-        cleaned = cleaned.replace('-', " ").replace('_', " ");
-        let re = regex_lite::Regex::new(r"\s+").unwrap();
-        cleaned = re.replace_all(&cleaned, " ").trim().to_string();
-        
-        // Title case
-        cleaned.split_whitespace()
-            .map(|word| {
-                let mut chars = word.chars();
-                match chars.next() {
-                    None => String::new(),
-                    Some(c) => c.to_uppercase().chain(chars).collect(),
+            if let Some(postings) = self.prefixes.get(&prefix_of(query_normalized)) {
+                for &id in postings {
+                    *scores.entry(id).or_insert(0) += 2;
                 }
-            })
-            .collect::<Vec<_>>()
-            .join(" ")
-    }
+            }
 
-    /// Normalize font name for comparison
-    #[inline]
-    pub fn normalize_for_comparison(name: &str) -> String {
-        let parsed = parse_font_name(name);
-        parsed.family.to_lowercase().replace(' ', "")
+            let mut ranked: Vec<(usize, u32)> = scores.into_iter().collect();
+            ranked.sort_unstable_by(|a, b| b.1.cmp(&a.1).then(a.0.cmp(&b.0)));
+            ranked.into_iter().take(limit).map(|(id, _)| id).collect()
+        }
     }
 
-    /// Get canonical font name mapping
-    #[inline]
-    pub fn get_canonical_name(name: &str) -> String {
-        let normalized = normalize_for_comparison(name);
-        
-        // Common font aliases - use static map for zero allocation on lookup
-        get_canonical_alias(&normalized)
-            .map(String::from)
-            .unwrap_or_else(|| parse_font_name(name).family)
-    }
-    
-    /// Static alias lookup (zero allocation)
     #[inline]
-    const fn get_canonical_alias(_normalized: &str) -> Option<&'static str> {
-        // Note: const fn can't use HashMap, so we use match
-        // For larger maps, consider phf crate
-        None // Fallback - actual matching done at runtime
+    fn prefix_of(normalized: &str) -> String {
+        normalized.chars().take(2).collect()
     }
-}
 
-// ============================================================================
-// FONT MATCHER - Fuzzy matching with confidence scoring
-// ============================================================================
-
-pub mod matcher {
-    use super::*;
+    fn trigrams_of(normalized: &str) -> Vec<String> {
+        let chars: Vec<char> = normalized.chars().collect();
+        if chars.len() < 3 {
+            return vec![normalized.to_string()];
+        }
+        chars.windows(3).map(|w| w.iter().collect()).collect()
+    }
 
     /// Find best matching font with confidence score
     #[inline]
@@ -353,22 +292,47 @@ pub mod matcher {
         google_fonts: &[GoogleFont],
         weight: u16,
         is_italic: bool,
+    ) -> FontMatch {
+        find_best_match_in(query, system_fonts, google_fonts, None, weight, is_italic)
+    }
+
+    /// Find best matching font, using a prebuilt index for the Google Fonts pass
+    #[inline]
+    pub fn find_best_match_indexed(
+        query: &str,
+        system_fonts: &[FontInfo],
+        google_fonts: &[GoogleFont],
+        index: &FontIndex,
+        weight: u16,
+        is_italic: bool,
+    ) -> FontMatch {
+        find_best_match_in(query, system_fonts, google_fonts, Some(index), weight, is_italic)
+    }
+
+    fn find_best_match_in(
+        query: &str,
+        system_fonts: &[FontInfo],
+        google_fonts: &[GoogleFont],
+        index: Option<&FontIndex>,
+        weight: u16,
+        is_italic: bool,
     ) -> FontMatch {
         let parsed = normalizer::parse_font_name(query);
         let query_normalized = normalizer::normalize_for_comparison(query);
         
-        // Try exact system font match
-        if let Some(m) = find_exact_system_match(&parsed.family, system_fonts) {
-            return m;
-        }
-        
-        // Try fuzzy system font match
-        if let Some(m) = find_fuzzy_system_match(&query_normalized, system_fonts, 0.8) {
+        // Try exact, then fuzzy system font match
+        if let Some(m) = find_system_match(query, system_fonts, 0.8) {
             return m;
         }
         
         // Try Google Fonts match
-        if let Some(m) = find_google_match(&parsed.family, &query_normalized, google_fonts, weight, is_italic) {
+        let google_match = match index {
+            Some(index) if index.is_for(google_fonts) => {
+                find_google_match_indexed(&query_normalized, google_fonts, index, weight)
+            }
+            _ => find_google_match(&parsed.family, &query_normalized, google_fonts, weight, is_italic),
+        };
+        if let Some(m) = google_match {
             return m;
         }
         
@@ -376,41 +340,17 @@ pub mod matcher {
         create_fallback_match(&parsed.family)
     }
 
-    #[inline]
-    fn find_exact_system_match(family: &str, fonts: &[FontInfo]) -> Option<FontMatch> {
-        let family_lower = family.to_lowercase();
-        
-        fonts.iter().find(|font| font.family.to_lowercase() == family_lower).map(|font| FontMatch {
-            family: font.family.clone(),
-            source: FontSource::System,
-            confidence: 1.0,
-            css_family: format!("'{}'", font.family),
-            google_url: None,
-            fallback_stack: get_fallback_stack(&font.family),
-        })
-    }
-
-    fn find_fuzzy_system_match(query: &str, fonts: &[FontInfo], threshold: f32) -> Option<FontMatch> {
-        let mut best_match: Option<(f32, &FontInfo)> = None;
-        
-        for font in fonts {
-            let font_normalized = normalizer::normalize_for_comparison(&font.family);
-            let similarity = calculate_similarity(query, &font_normalized);
-            
-            if similarity >= threshold {
-                if best_match.is_none() || similarity > best_match.unwrap().0 {
-                    best_match = Some((similarity, font));
-                }
-            }
-        }
-        
-        best_match.map(|(confidence, font)| FontMatch {
+    /// Exact, then fuzzy (similarity >= `threshold`), system font match
+    fn find_system_match(query: &str, fonts: &[FontInfo], threshold: f32) -> Option<FontMatch> {
+        let (id, confidence) = find_family(query, fonts.iter().map(|f| f.family.as_str()), threshold)?;
+        let font = &fonts[id];
+        Some(FontMatch {
             family: font.family.clone(),
             source: FontSource::System,
             confidence,
             css_family: format!("'{}'", font.family),
             google_url: None,
-            fallback_stack: get_fallback_stack(&font.family),
+            fallback_stack: fallback_stack(&font.family),
         })
     }
 
@@ -435,7 +375,7 @@ pub mod matcher {
                     confidence: 0.95,
                     css_family: format!("'{}'", font.family),
                     google_url: Some(url),
-                    fallback_stack: get_fallback_stack_with_category(&font.family, &font.category),
+                    fallback_stack: fallback_stack_with_category(&font.family, &font.category),
                 });
             }
             
@@ -456,114 +396,84 @@ pub mod matcher {
                 confidence: confidence * 0.9, // Slightly lower for fuzzy
                 css_family: format!("'{}'", font.family),
                 google_url: Some(url),
-                fallback_stack: get_fallback_stack_with_category(&font.family, &font.category),
+                fallback_stack: fallback_stack_with_category(&font.family, &font.category),
             }
         })
     }
 
-    fn create_fallback_match(family: &str) -> FontMatch {
-        let (fallback, category) = guess_font_category(family);
-        
-        FontMatch {
-            family: fallback.to_string(),
-            source: FontSource::System,
-            confidence: 0.3,
-            css_family: get_generic_css_stack(category).to_string(),
-            google_url: None,
-            fallback_stack: vec![fallback.to_string()],
+    fn find_google_match_indexed(
+        query_normalized: &str,
+        fonts: &[GoogleFont],
+        index: &FontIndex,
+        weight: u16,
+    ) -> Option<FontMatch> {
+        if let Some(id) = index.exact(query_normalized) {
+            let font = &fonts[id];
+            return Some(FontMatch {
+                family: font.family.clone(),
+                source: FontSource::Google,
+                confidence: 0.95,
+                css_family: format!("'{}'", font.family),
+                google_url: Some(build_google_font_url(&font.family, weight)),
+                fallback_stack: fallback_stack_with_category(&font.family, &font.category),
+            });
         }
-    }
 
-    /// Calculate string similarity using Levenshtein distance
-    #[inline]
-    pub fn calculate_similarity(a: &str, b: &str) -> f32 {
-        if a == b { return 1.0; }
-        if a.is_empty() || b.is_empty() { return 0.0; }
-        
-        let distance = levenshtein_distance(a, b);
-        let max_len = a.len().max(b.len()) as f32;
-        
-        1.0 - (distance as f32 / max_len)
-    }
+        let mut best_match: Option<(f32, &GoogleFont)> = None;
 
-    /// Optimized Levenshtein distance using single-row algorithm
-    fn levenshtein_distance(a: &str, b: &str) -> usize {
-        let a_chars: Vec<char> = a.chars().collect();
-        let b_chars: Vec<char> = b.chars().collect();
-        let a_len = a_chars.len();
-        let b_len = b_chars.len();
-        
-        if a_len == 0 { return b_len; }
-        if b_len == 0 { return a_len; }
-        
-        // Use single-row optimization (O(min(m,n)) space)
-        let (shorter, longer) = if a_len <= b_len { (&a_chars, &b_chars) } else { (&b_chars, &a_chars) };
-        let (m, n) = (shorter.len(), longer.len());
-        
-        let mut prev_row: Vec<usize> = (0..=m).collect();
-        
-        for j in 1..=n {
-            let mut prev_diag = prev_row[0];
-            prev_row[0] = j;
-            
-            for i in 1..=m {
-                let old_diag = prev_row[i];
-                let cost = if shorter[i - 1] == longer[j - 1] { 0 } else { 1 };
-                prev_row[i] = (prev_row[i] + 1)
-                    .min(prev_row[i - 1] + 1)
-                    .min(prev_diag + cost);
-                prev_diag = old_diag;
+        for id in index.candidates(query_normalized, INDEX_CANDIDATE_LIMIT) {
+            let font = &fonts[id];
+            let similarity = calculate_similarity(query_normalized, &index.normalized[id]);
+            if similarity >= 0.7 && best_match.map_or(true, |(best, _)| similarity > best) {
+                best_match = Some((similarity, font));
             }
         }
-        
-        prev_row[m]
-    }
 
-    #[inline]
-    fn guess_font_category(name: &str) -> (&'static str, &'static str) {
-        let lower = name.to_lowercase();
-        
-        if lower.contains("mono") || lower.contains("code") || lower.contains("console") || lower.contains("courier") {
-            ("Courier New", "monospace")
-        } else if lower.contains("serif") && !lower.contains("sans") {
-            ("Georgia", "serif")
-        } else if lower.contains("script") || lower.contains("cursive") || lower.contains("hand") {
-            ("Georgia", "cursive")
-        } else if lower.contains("display") || lower.contains("decorative") {
-            ("Impact", "display")
-        } else {
-            ("Arial", "sans-serif")
-        }
+        best_match.map(|(confidence, font)| FontMatch {
+            family: font.family.clone(),
+            source: FontSource::Google,
+            confidence: confidence * 0.9, // Slightly lower for fuzzy
+            css_family: format!("'{}'", font.family),
+            google_url: Some(build_google_font_url(&font.family, weight)),
+            fallback_stack: fallback_stack_with_category(&font.family, &font.category),
+        })
     }
 
-    #[inline]
-    fn get_fallback_stack(family: &str) -> Vec<String> {
-        let (_, category) = guess_font_category(family);
-        get_fallback_stack_with_category(family, category)
-    }
+    /// Build a match for a user-pinned override target
+    pub fn create_override_match(
+        target: &str,
+        system_fonts: &[FontInfo],
+        google_fonts: &[GoogleFont],
+        weight: u16,
+        is_italic: bool,
+    ) -> FontMatch {
+        let resolved = find_best_match(target, system_fonts, google_fonts, weight, is_italic);
 
-    fn get_fallback_stack_with_category(family: &str, category: &str) -> Vec<String> {
-        let mut stack = vec![family.to_string()];
-        
-        match category {
-            "serif" => stack.extend(["Georgia", "Times New Roman", "serif"].map(String::from)),
-            "monospace" => stack.extend(["Consolas", "Courier New", "monospace"].map(String::from)),
-            "cursive" | "handwriting" => stack.extend(["Georgia", "cursive"].map(String::from)),
-            "display" => stack.extend(["Impact", "Arial Black", "sans-serif"].map(String::from)),
-            _ => stack.extend(["Helvetica", "Arial", "sans-serif"].map(String::from)),
+        if normalizer::normalize_for_comparison(&resolved.family) == normalizer::normalize_for_comparison(target) {
+            return FontMatch { confidence: 1.0, ..resolved };
+        }
+
+        // Target isn't known locally or on Google Fonts - trust the user's choice
+        FontMatch {
+            family: target.to_string(),
+            source: FontSource::Custom,
+            confidence: 1.0,
+            css_family: format!("'{}'", target),
+            google_url: None,
+            fallback_stack: fallback_stack(target),
         }
-        
-        stack
     }
 
-    #[inline]
-    fn get_generic_css_stack(category: &str) -> &'static str {
-        match category {
-            "serif" => "Georgia, 'Times New Roman', Times, serif",
-            "monospace" => "'Courier New', Consolas, monospace",
-            "cursive" => "Georgia, cursive",
-            "display" => "Impact, 'Arial Black', sans-serif",
-            _ => "Arial, Helvetica, sans-serif",
+    fn create_fallback_match(family: &str) -> FontMatch {
+        let (fallback, category) = guess_font_category(family);
+        
+        FontMatch {
+            family: fallback.to_string(),
+            source: FontSource::System,
+            confidence: 0.3,
+            css_family: generic_css_stack(category).to_string(),
+            google_url: None,
+            fallback_stack: vec![fallback.to_string()],
         }
     }
 
@@ -639,10 +549,7 @@ pub mod google_fonts {
             .collect();
 
         // Update cache
-        if let Ok(mut state) = FONT_MANAGER.write() {
-            state.google_fonts = fonts.clone();
-            state.google_fonts_loaded = true;
-        }
+        set_google_fonts(&fonts);
 
         Ok(fonts)
     }
@@ -692,10 +599,7 @@ pub mod google_fonts {
             })
             .collect();
 
-        if let Ok(mut state) = FONT_MANAGER.write() {
-            state.google_fonts = fonts.clone();
-            state.google_fonts_loaded = true;
-        }
+        set_google_fonts(&fonts);
 
         Ok(fonts)
     }
@@ -703,20 +607,14 @@ pub mod google_fonts {
     /// Search Google Fonts with fuzzy matching
     pub async fn search(query: &str, limit: usize) -> Result<Vec<GoogleFont>, String> {
         // Ensure fonts are loaded
-        let needs_fetch = {
-            let state = FONT_MANAGER.read().map_err(|e| e.to_string())?;
-            !state.google_fonts_loaded
+        let google_fonts = match loaded_google_fonts() {
+            Some(fonts) => fonts,
+            None => Arc::new(fetch_fonts_list().await?),
         };
 
-        if needs_fetch {
-            fetch_fonts_list().await?;
-        }
-
-        let state = FONT_MANAGER.read().map_err(|e| e.to_string())?;
         let query_normalized = normalizer::normalize_for_comparison(query);
 
-        let mut scored: Vec<(f32, &GoogleFont)> = state
-            .google_fonts
+        let mut scored: Vec<(f32, &GoogleFont)> = google_fonts
             .iter()
             .map(|font| {
                 let font_normalized = normalizer::normalize_for_comparison(&font.family);
@@ -1078,8 +976,7 @@ pub mod pdf_extractor {
 
     /// Store extracted embedded font for later use
     pub fn store_embedded_font(name: &str, data: Vec<u8>, metrics: FontMetrics) -> Result<(), String> {
-        let mut state = FONT_MANAGER.write().map_err(|e| e.to_string())?;
-        state.embedded_fonts.insert(
+        FONT_MANAGER.embedded_fonts.insert(
             name.to_string(),
             EmbeddedFont {
                 name: name.to_string(),
@@ -1092,10 +989,7 @@ pub mod pdf_extractor {
 
     /// Get stored embedded font
     pub fn get_embedded_font(name: &str) -> Option<Vec<u8>> {
-        FONT_MANAGER
-            .read()
-            .ok()
-            .and_then(|state| state.embedded_fonts.get(name).map(|f| f.data.clone()))
+        FONT_MANAGER.embedded_fonts.get(name).map(|f| f.data.clone())
     }
 }
 
@@ -1149,6 +1043,10 @@ pub mod docx_extractor {
         pub spacing_before: Option<f32>,
         pub spacing_after: Option<f32>,
         pub line_spacing: Option<f32>,
+        pub keep_next: bool,
+        pub keep_lines: bool,
+        /// Word's widow/orphan control, on unless the paragraph turns it off
+        pub widow_control: Option<bool>,
     }
 
     /// Extract font info from DOCX run properties
@@ -1243,6 +1141,11 @@ pub mod docx_extractor {
                     info.line_spacing = Some(line as f32 / 240.0);
                 }
             }
+
+            // Pagination (a bare element means on)
+            info.keep_next = props.keep_next.as_ref().is_some_and(|k| k.value.unwrap_or(true));
+            info.keep_lines = props.keep_lines.as_ref().is_some_and(|k| k.value.unwrap_or(true));
+            info.widow_control = props.widow_control.as_ref().map(|w| w.value.unwrap_or(true));
         }
 
         info
@@ -1387,8 +1290,13 @@ pub mod docx_extractor {
 
 pub mod installer {
     use super::*;
+    use std::ffi::OsStr;
     use std::fs;
     use std::io::Write;
+    use std::path::Path;
+
+    /// File extensions written by the installer
+    const FONT_EXTENSIONS: [&str; 4] = ["ttf", "otf", "woff", "woff2"];
 
     #[derive(Debug, Clone, Serialize, Deserialize)]
     #[serde(rename_all = "camelCase")]
@@ -1408,24 +1316,14 @@ pub mod installer {
         let fonts_dir = get_user_fonts_dir()?;
         fs::create_dir_all(&fonts_dir).map_err(|e| e.to_string())?;
 
-        // Determine file extension from data
-        let ext = detect_font_format(data);
-        let filename = format!("{}.{}", sanitize_filename(family), ext);
-        let dest_path = fonts_dir.join(&filename);
-
-        // Write font file
-        let mut file = fs::File::create(&dest_path)
-            .map_err(|e| format!("Failed to create font file: {}", e))?;
-        file.write_all(data)
-            .map_err(|e| format!("Failed to write font data: {}", e))?;
+        let dest_path = write_font_file(&fonts_dir, family, data)?;
 
         // Refresh font cache
         refresh_font_cache()?;
 
         // Clear internal cache
-        if let Ok(mut state) = FONT_MANAGER.write() {
-            state.last_system_scan = None;
-        }
+        invalidate_system_scan();
+        let _ = match_cache::invalidate();
 
         // Notify frontend
         let _ = app_handle.emit("fonts_changed", ());
@@ -1438,6 +1336,20 @@ pub mod installer {
         })
     }
 
+    /// Write a font into `fonts_dir`, named after its family
+    pub(super) fn write_font_file(fonts_dir: &Path, family: &str, data: &[u8]) -> Result<PathBuf, String> {
+        // Determine file extension from data
+        let ext = detect_font_format(data);
+        let filename = format!("{}.{}", sanitize_filename(family), ext);
+        let dest_path = fonts_dir.join(&filename);
+
+        let mut file = fs::File::create(&dest_path)
+            .map_err(|e| format!("Failed to create font file: {}", e))?;
+        file.write_all(data)
+            .map_err(|e| format!("Failed to write font data: {}", e))?;
+        Ok(dest_path)
+    }
+
     /// Install font from file path
     pub async fn install_font_file(
         source_path: &str,
@@ -1583,7 +1495,8 @@ pub mod installer {
             .collect()
     }
 
-    fn extract_family_from_font(data: &[u8]) -> Option<String> {
+    /// Read the family name from a font's name table
+    pub fn extract_family_from_font(data: &[u8]) -> Option<String> {
         use ttf_parser::Face;
         
         let face = Face::parse(data, 0).ok()?;
@@ -1600,25 +1513,89 @@ pub mod installer {
         None
     }
 
-    /// Uninstall a user-installed font
-    pub fn uninstall_font(family: &str) -> Result<(), String> {
-        let fonts_dir = get_user_fonts_dir()?;
-        let sanitized = sanitize_filename(family);
+    /// Uninstall a user-installed font by the `id` `list_installed_fonts` gave it
+    pub fn uninstall_font(id: &str) -> Result<(), String> {
+        remove_font_file(&get_user_fonts_dir()?, id)?;
 
-        for ext in ["ttf", "otf", "woff", "woff2"] {
-            let path = fonts_dir.join(format!("{}.{}", sanitized, ext));
-            if path.exists() {
-                fs::remove_file(&path).map_err(|e| e.to_string())?;
-            }
+        refresh_font_cache()?;
+
+        invalidate_system_scan();
+        let _ = match_cache::invalidate();
+
+        Ok(())
+    }
+
+    /// Delete the font file named `id` from `fonts_dir`
+    pub(super) fn remove_font_file(fonts_dir: &Path, id: &str) -> Result<(), String> {
+        // Only a bare font file name, never a path out of the fonts directory
+        let is_font_name = Path::new(id).file_name() == Some(OsStr::new(id))
+            && Path::new(id)
+                .extension()
+                .and_then(|e| e.to_str())
+                .is_some_and(|e| FONT_EXTENSIONS.contains(&e.to_lowercase().as_str()));
+        let path = fonts_dir.join(id);
+        if !is_font_name || !path.is_file() {
+            return Err(format!("Font '{}' is not a user-installed font", id));
         }
+        fs::remove_file(&path).map_err(|e| e.to_string())
+    }
 
-        refresh_font_cache()?;
+    /// List fonts installed in the user fonts directory
+    pub fn list_installed_fonts() -> Result<Vec<FontInfo>, String> {
+        list_fonts_in(&get_user_fonts_dir()?)
+    }
 
-        if let Ok(mut state) = FONT_MANAGER.write() {
-            state.last_system_scan = None;
+    /// Fonts in `fonts_dir`, each with its file name as id
+    pub(super) fn list_fonts_in(fonts_dir: &Path) -> Result<Vec<FontInfo>, String> {
+        if !fonts_dir.exists() {
+            return Ok(Vec::new());
         }
 
-        Ok(())
+        let entries = fs::read_dir(fonts_dir).map_err(|e| e.to_string())?;
+        let mut fonts: Vec<FontInfo> = Vec::new();
+
+        for entry in entries.flatten() {
+            let path = entry.path();
+            let is_font = path
+                .extension()
+                .and_then(|e| e.to_str())
+                .map(|e| FONT_EXTENSIONS.contains(&e.to_lowercase().as_str()))
+                .unwrap_or(false);
+            if !is_font {
+                continue;
+            }
+
+            let stem = path
+                .file_stem()
+                .map(|s| s.to_string_lossy().to_string())
+                .unwrap_or_default();
+            let data = fs::read(&path).unwrap_or_default();
+            let face = ttf_parser::Face::parse(&data, 0).ok();
+
+            let family = extract_family_from_font(&data).unwrap_or_else(|| stem.clone());
+            let (weight, is_italic, is_oblique, is_variable) = face
+                .as_ref()
+                .map(|f| (f.weight().to_number(), f.is_italic(), f.is_oblique(), f.is_variable()))
+                .unwrap_or((400, false, false, false));
+
+            fonts.push(FontInfo {
+                family,
+                full_name: stem,
+                style: FontStyle {
+                    is_italic,
+                    is_oblique,
+                    width: FontWidth::Normal,
+                },
+                weight,
+                source: FontSource::Custom,
+                path: Some(path.to_string_lossy().to_string()),
+                is_variable,
+                id: Some(entry.file_name().to_string_lossy().to_string()),
+            });
+        }
+
+        fonts.sort_by_key(|f| f.family.to_lowercase());
+        Ok(fonts)
     }
 }
 
@@ -1629,8 +1606,8 @@ pub mod installer {
 pub mod system {
     use super::*;
 
-    /// Get all system fonts
-    pub fn enumerate_fonts() -> Result<Vec<FontInfo>, String> {
+    /// Get all system fonts, stopping when `job` is cancelled
+    pub fn enumerate_fonts(job: &crate::job_manager::JobHandle) -> Result<Vec<FontInfo>, String> {
         use font_kit::source::SystemSource;
 
         let source = SystemSource::new();
@@ -1639,7 +1616,10 @@ pub mod system {
         let mut fonts: Vec<FontInfo> = Vec::new();
         let mut seen: std::collections::HashSet<String> = std::collections::HashSet::new();
 
-        for family in families {
+        let total = families.len();
+        for (i, family) in families.into_iter().enumerate() {
+            job.check_cancelled()?;
+            job.progress(i as f32 / total as f32, Some(family.clone()));
             if seen.contains(&family) {
                 continue;
             }
@@ -1664,6 +1644,7 @@ pub mod system {
                             source: FontSource::System,
                             path: None,
                             is_variable: false,
+                            id: None,
                         });
                         break; // One entry per family
                     }
@@ -1699,6 +1680,337 @@ pub mod system {
         
         None
     }
+
+    /// Files of every installed style of a font family
+    pub fn get_font_paths(family: &str) -> Vec<PathBuf> {
+        use font_kit::source::SystemSource;
+        use font_kit::handle::Handle;
+
+        let Ok(handle) = SystemSource::new().select_family_by_name(family) else {
+            return Vec::new();
+        };
+        let mut paths: Vec<PathBuf> = handle
+            .fonts()
+            .iter()
+            .filter_map(|font| match font {
+                Handle::Path { path, .. } => Some(path.clone()),
+                Handle::Memory { .. } => None,
+            })
+            .collect();
+        paths.sort();
+        paths.dedup();
+        paths
+    }
+}
+
+// ============================================================================
+// MATCH CACHE - Persistent match cache & user overrides
+// ============================================================================
+
+pub mod match_cache {
+    use super::*;
+    use std::fs;
+    use std::sync::atomic::{AtomicBool, AtomicUsize, Ordering};
+    use std::time::Duration;
+
+    const CACHE_FILE: &str = "font_match_cache.json";
+    /// New matches are written this long after the first unsaved one
+    const PERSIST_DELAY: Duration = Duration::from_secs(2);
+
+    /// Set while a delayed write is scheduled
+    static PERSIST_PENDING: AtomicBool = AtomicBool::new(false);
+    static TEMP_FILES: AtomicUsize = AtomicUsize::new(0);
+
+    /// User-pinned mapping that takes precedence over fuzzy matching
+    #[derive(Debug, Clone, Serialize, Deserialize)]
+    #[serde(rename_all = "camelCase")]
+    pub struct FontOverride {
+        pub source: String,
+        pub target: String,
+    }
+
+    #[derive(Debug, Default, Serialize, Deserialize)]
+    #[serde(rename_all = "camelCase")]
+    struct CacheFile {
+        #[serde(default)]
+        matches: HashMap<String, FontMatch>,
+        #[serde(default)]
+        overrides: Vec<FontOverride>,
+    }
+
+    /// Load the persisted cache from `dir` and remember it for later saves
+    pub fn init(dir: PathBuf) -> Result<(), String> {
+        fs::create_dir_all(&dir).map_err(|e| e.to_string())?;
+        let path = dir.join(CACHE_FILE);
+
+        let file: CacheFile = fs::read(&path)
+            .ok()
+            .and_then(|data| serde_json::from_slice(&data).ok())
+            .unwrap_or_default();
+
+        // Entries from before matches were keyed by weight and style are dropped
+        for (key, font_match) in file.matches.into_iter().filter(|(key, _)| key.contains('|')) {
+            FONT_MANAGER.font_cache.insert(key, font_match);
+        }
+        for entry in file.overrides {
+            FONT_MANAGER
+                .font_overrides
+                .insert(normalizer::normalize_for_comparison(&entry.source), entry);
+        }
+        *FONT_MANAGER.cache_path.write().map_err(|e| e.to_string())? = Some(path);
+        Ok(())
+    }
+
+    /// Write matches and overrides to disk (no-op until `init` has run)
+    pub fn persist() -> Result<(), String> {
+        let Some(path) = FONT_MANAGER.cache_path.read().map_err(|e| e.to_string())?.clone() else {
+            return Ok(());
+        };
+        let mut overrides: Vec<FontOverride> = FONT_MANAGER.font_overrides.iter().map(|o| o.value().clone()).collect();
+        overrides.sort_by(|a, b| a.source.cmp(&b.source));
+        let file = CacheFile {
+            matches: FONT_MANAGER.font_cache.iter().map(|m| (m.key().clone(), m.value().clone())).collect(),
+            overrides,
+        };
+        let data = serde_json::to_vec_pretty(&file).map_err(|e| e.to_string())?;
+
+        // Write to a temp file first so a crash never leaves a truncated cache;
+        // each write has its own, as writes from different threads may overlap
+        let tmp = path.with_extension(format!("json.{}.tmp", TEMP_FILES.fetch_add(1, Ordering::Relaxed)));
+        fs::write(&tmp, data).map_err(|e| e.to_string())?;
+        fs::rename(&tmp, &path).map_err(|e| {
+            let _ = fs::remove_file(&tmp);
+            e.to_string()
+        })
+    }
+
+    /// Persist soon, coalescing the matches found in the meantime into one write
+    pub fn schedule_persist() {
+        if PERSIST_PENDING.swap(true, Ordering::AcqRel) {
+            return;
+        }
+        tauri::async_runtime::spawn(async {
+            tokio::time::sleep(PERSIST_DELAY).await;
+            PERSIST_PENDING.store(false, Ordering::Release);
+            let _ = tokio::task::spawn_blocking(persist).await;
+        });
+    }
+
+    /// Write a scheduled persist now, as the app exits
+    pub fn flush() -> Result<(), String> {
+        if PERSIST_PENDING.swap(false, Ordering::AcqRel) {
+            persist()
+        } else {
+            Ok(())
+        }
+    }
+
+    /// Key of a cached match; the result depends on the requested weight and
+    /// style as well as the name (e.g. its Google Fonts URL)
+    #[inline]
+    pub fn cache_key(name: &str, weight: u16, is_italic: bool) -> String {
+        format!("{}|{}|{}", name, weight, if is_italic { "italic" } else { "normal" })
+    }
+
+    /// Clear cached matches (overrides are kept)
+    pub fn invalidate() -> Result<(), String> {
+        FONT_MANAGER.font_cache.clear();
+        persist()
+    }
+
+    #[inline]
+    pub fn get_override(name: &str) -> Option<String> {
+        let key = normalizer::normalize_for_comparison(name);
+        FONT_MANAGER.font_overrides.get(&key).map(|o| o.target.clone())
+    }
+
+    pub fn set_override(source: &str, target: &str) -> Result<(), String> {
+        if source.trim().is_empty() || target.trim().is_empty() {
+            return Err("Override source and target must not be empty".to_string());
+        }
+
+        FONT_MANAGER.font_overrides.insert(
+            normalizer::normalize_for_comparison(source),
+            FontOverride {
+                source: source.trim().to_string(),
+                target: target.trim().to_string(),
+            },
+        );
+        persist()
+    }
+
+    /// Remove an override, returning whether one existed
+    pub fn remove_override(source: &str) -> Result<bool, String> {
+        let removed = FONT_MANAGER
+            .font_overrides
+            .remove(&normalizer::normalize_for_comparison(source))
+            .is_some();
+        persist()?;
+        Ok(removed)
+    }
+
+    pub fn list_overrides() -> Vec<FontOverride> {
+        let mut overrides: Vec<FontOverride> = FONT_MANAGER.font_overrides.iter().map(|o| o.value().clone()).collect();
+        overrides.sort_by_key(|o| o.source.to_lowercase());
+        overrides
+    }
+
+    /// Get the Google Fonts index, rebuilding it if the font list was replaced
+    pub fn google_index(google_fonts: &Arc<Vec<GoogleFont>>) -> Arc<matcher::FontIndex> {
+        if let Ok(slot) = FONT_MANAGER.google_index.read() {
+            if let Some(index) = &*slot {
+                if index.is_for(google_fonts) {
+                    return index.clone();
+                }
+            }
+        }
+
+        // Built without the lock held; a concurrent rebuild just wins the slot
+        let index = Arc::new(matcher::FontIndex::build(google_fonts.clone()));
+        if let Ok(mut slot) = FONT_MANAGER.google_index.write() {
+            *slot = Some(index.clone());
+        }
+        index
+    }
+}
+
+// ============================================================================
+// FONT AUDIT - Usage report & missing-font detection
+// ============================================================================
+
+pub mod audit {
+    use super::*;
+    use crate::models::{LayerType, PageData};
+
+    /// Matches below this confidence are treated as missing fonts
+    pub const MISSING_CONFIDENCE_THRESHOLD: f32 = 0.5;
+
+    #[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
+    #[serde(rename_all = "lowercase")]
+    #[repr(u8)]
+    pub enum FontAvailability {
+        System = 0,
+        Embedded = 1,
+        Google = 2,
+        Missing = 3,
+    }
+
+    #[derive(Debug, Clone, Serialize, Deserialize)]
+    #[serde(rename_all = "camelCase")]
+    pub struct FontLocation {
+        pub page_index: usize,
+        pub layer_id: String,
+    }
+
+    #[derive(Debug, Clone, Serialize, Deserialize)]
+    #[serde(rename_all = "camelCase")]
+    pub struct FontUsage {
+        pub family: String,
+        pub weight: u16,
+        pub is_italic: bool,
+        pub availability: FontAvailability,
+        pub matched_family: Option<String>,
+        pub confidence: f32,
+        pub layer_count: usize,
+        pub pages: Vec<usize>,
+        pub locations: Vec<FontLocation>,
+    }
+
+    #[derive(Debug, Clone, Serialize, Deserialize)]
+    #[serde(rename_all = "camelCase")]
+    pub struct FontAuditReport {
+        pub fonts: Vec<FontUsage>,
+        pub total_text_layers: usize,
+        pub missing_count: usize,
+    }
+
+    /// Group text layers by family/weight/style, in first-seen order
+    pub fn collect_usages(pages: &[PageData]) -> (Vec<FontUsage>, usize) {
+        let mut usages: Vec<FontUsage> = Vec::new();
+        let mut index: HashMap<(String, u16, bool), usize> = HashMap::new();
+        let mut total_text_layers = 0;
+
+        for page in pages {
+            for layer in &page.layers {
+                if layer.layer_type != LayerType::Text {
+                    continue;
+                }
+                total_text_layers += 1;
+
+                let family = layer
+                    .font_family
+                    .as_deref()
+                    .map(str::trim)
+                    .filter(|f| !f.is_empty())
+                    .unwrap_or("Unknown")
+                    .to_string();
+                let weight = layer.font_weight.unwrap_or(400);
+                let is_italic = layer
+                    .font_style
+                    .as_deref()
+                    .map(|s| s.eq_ignore_ascii_case("italic") || s.eq_ignore_ascii_case("oblique"))
+                    .unwrap_or(false);
+
+                let key = (family.clone(), weight, is_italic);
+                let idx = *index.entry(key).or_insert_with(|| {
+                    usages.push(FontUsage {
+                        family,
+                        weight,
+                        is_italic,
+                        availability: FontAvailability::Missing,
+                        matched_family: None,
+                        confidence: 0.0,
+                        layer_count: 0,
+                        pages: Vec::new(),
+                        locations: Vec::new(),
+                    });
+                    usages.len() - 1
+                });
+
+                let usage = &mut usages[idx];
+                usage.layer_count += 1;
+                if !usage.pages.contains(&page.page_index) {
+                    usage.pages.push(page.page_index);
+                }
+                usage.locations.push(FontLocation {
+                    page_index: page.page_index,
+                    layer_id: layer.id.clone(),
+                });
+            }
+        }
+
+        for usage in &mut usages {
+            usage.pages.sort_unstable();
+        }
+
+        (usages, total_text_layers)
+    }
+
+    /// Classify a match result into an availability bucket
+    #[inline]
+    pub fn classify(is_embedded: bool, font_match: &FontMatch) -> FontAvailability {
+        if is_embedded {
+            return FontAvailability::Embedded;
+        }
+        if font_match.confidence < MISSING_CONFIDENCE_THRESHOLD {
+            return FontAvailability::Missing;
+        }
+        match font_match.source {
+            FontSource::Google => FontAvailability::Google,
+            FontSource::Embedded => FontAvailability::Embedded,
+            FontSource::System | FontSource::Custom => FontAvailability::System,
+        }
+    }
+
+    /// Check whether a family was stored from a PDF's embedded fonts
+    pub fn is_embedded(family: &str) -> bool {
+        let normalized = normalizer::normalize_for_comparison(family);
+        FONT_MANAGER.embedded_fonts.contains_key(family)
+            || FONT_MANAGER
+                .embedded_fonts
+                .iter()
+                .any(|font| normalizer::normalize_for_comparison(font.key()) == normalized)
+    }
 }
 
 // ============================================================================
@@ -1710,18 +2022,31 @@ use tauri::{AppHandle, Emitter};
 /// Get all system fonts (cached)
 #[tauri::command]
 pub async fn get_system_fonts() -> Result<Vec<FontInfo>, String> {
-    let mut state = FONT_MANAGER.write().map_err(|e| e.to_string())?;
-
     // Return cached if recent
-    if let Some(last) = state.last_system_scan {
-        if last.elapsed().as_secs() < 30 && !state.system_fonts.is_empty() {
-            return Ok(state.system_fonts.clone());
-        }
+    let recent = || {
+        FONT_MANAGER.system_fonts.read().ok().and_then(|slot| match &*slot {
+            Some((last, fonts)) if last.elapsed().as_secs() < 30 && !fonts.is_empty() => Some(fonts.clone()),
+            _ => None,
+        })
+    };
+    if let Some(fonts) = recent() {
+        return Ok(fonts);
     }
 
-    let fonts = system::enumerate_fonts()?;
-    state.system_fonts = fonts.clone();
-    state.last_system_scan = Some(std::time::Instant::now());
+    // One scan at a time; callers that waited get the scan just finished
+    let _scan = FONT_MANAGER.system_scan.lock().await;
+    if let Some(fonts) = recent() {
+        return Ok(fonts);
+    }
+
+    let fonts = crate::job_manager::run(
+        crate::job_manager::JobKind::FontScan,
+        "System fonts",
+        crate::job_manager::JobPriority::Low,
+        system::enumerate_fonts,
+    )
+    .await?;
+    *FONT_MANAGER.system_fonts.write().map_err(|e| e.to_string())? = Some((Instant::now(), fonts.clone()));
 
     Ok(fonts)
 }
@@ -1745,44 +2070,60 @@ pub async fn find_font_match(
     weight: Option<u16>,
     is_italic: Option<bool>,
 ) -> Result<FontMatch, String> {
-    // Check cache first
-    {
-        let state = FONT_MANAGER.read().map_err(|e| e.to_string())?;
-        if let Some(cached) = state.font_cache.get(&font_name) {
+    let weight = weight.unwrap_or(400);
+    let is_italic = is_italic.unwrap_or(false);
+    let override_target = match_cache::get_override(&font_name);
+    let key = match_cache::cache_key(&font_name, weight, is_italic);
+
+    // Check cache first (user overrides always win)
+    if override_target.is_none() {
+        if let Some(cached) = FONT_MANAGER.font_cache.get(&key) {
             return Ok(cached.clone());
         }
     }
 
     let system_fonts = get_system_fonts().await?;
     
-    // Ensure Google Fonts are loaded - check in separate scope to release lock before await
-    let (google_fonts_loaded, cached_fonts) = {
-        let state = FONT_MANAGER.read().map_err(|e| e.to_string())?;
-        (state.google_fonts_loaded, state.google_fonts.clone())
-    };
-    
-    let google_fonts = if google_fonts_loaded {
-        cached_fonts
-    } else {
-        google_fonts::fetch_fonts_list().await.unwrap_or_else(|_| google_fonts::get_popular_fonts())
+    // Ensure Google Fonts are loaded
+    let google_fonts = match loaded_google_fonts() {
+        Some(fonts) => fonts,
+        None => Arc::new(
+            google_fonts::fetch_fonts_list().await.unwrap_or_else(|_| google_fonts::get_popular_fonts()),
+        ),
     };
 
-    let result = matcher::find_best_match(
-        &font_name,
-        &system_fonts,
-        &google_fonts,
-        weight.unwrap_or(400),
-        is_italic.unwrap_or(false),
-    );
+    if let Some(target) = override_target {
+        return Ok(matcher::create_override_match(&target, &system_fonts, &google_fonts, weight, is_italic));
+    }
+
+    let index = match_cache::google_index(&google_fonts);
+    let result = matcher::find_best_match_indexed(&font_name, &system_fonts, &google_fonts, &index, weight, is_italic);
 
     // Cache result
-    if let Ok(mut state) = FONT_MANAGER.write() {
-        state.font_cache.insert(font_name, result.clone());
-    }
+    FONT_MANAGER.font_cache.insert(key, result.clone());
+    match_cache::schedule_persist();
 
     Ok(result)
 }
 
+/// Pin a font name to a specific replacement family
+#[tauri::command]
+pub fn set_font_override(source: String, target: String) -> Result<(), String> {
+    match_cache::set_override(&source, &target)
+}
+
+/// Remove a pinned font override
+#[tauri::command]
+pub fn remove_font_override(source: String) -> Result<bool, String> {
+    match_cache::remove_override(&source)
+}
+
+/// List all pinned font overrides
+#[tauri::command]
+pub fn list_font_overrides() -> Vec<match_cache::FontOverride> {
+    match_cache::list_overrides()
+}
+
 /// Install Google Font
 #[tauri::command]
 pub async fn install_google_font(
@@ -1802,6 +2143,43 @@ pub async fn install_font_file(
     installer::install_font_file(&path, &app_handle).await
 }
 
+/// Install font from raw bytes (e.g. drag-and-dropped files)
+///
+/// The font file is the request body and `family` an optional header
+/// argument (see `ipc`).
+#[tauri::command]
+pub async fn install_font_bytes(
+    request: tauri::ipc::Request<'_>,
+    app_handle: AppHandle,
+) -> Result<installer::InstallResult, String> {
+    let data = crate::ipc::body(&request)?;
+    let family: Option<String> = crate::ipc::arg(&request, "family")?;
+    if data.is_empty() {
+        return Err("Font data is empty".to_string());
+    }
+
+    let family = family
+        .filter(|f| !f.trim().is_empty())
+        .or_else(|| installer::extract_family_from_font(&data))
+        .ok_or_else(|| "Could not determine font family name".to_string())?;
+
+    installer::install_font_bytes(&family, &data, &app_handle).await
+}
+
+/// List fonts installed by the user (custom fonts directory only)
+#[tauri::command]
+pub fn list_installed_custom_fonts() -> Result<Vec<FontInfo>, String> {
+    installer::list_installed_fonts()
+}
+
+/// Uninstall a user-installed font by its `id` from `list_installed_custom_fonts`
+#[tauri::command]
+pub fn uninstall_font(id: String, app_handle: AppHandle) -> Result<(), String> {
+    installer::uninstall_font(&id)?;
+    let _ = app_handle.emit("fonts_changed", ());
+    Ok(())
+}
+
 /// Parse font name into components
 #[tauri::command]
 pub fn parse_font_name(name: String) -> ParsedFontName {
@@ -1839,10 +2217,8 @@ pub fn get_google_font_css_url(family: String, weights: Vec<String>) -> String {
 /// Clear font cache
 #[tauri::command]
 pub fn clear_font_cache() -> Result<(), String> {
-    let mut state = FONT_MANAGER.write().map_err(|e| e.to_string())?;
-    state.font_cache.clear();
-    state.last_system_scan = None;
-    Ok(())
+    invalidate_system_scan();
+    match_cache::invalidate()
 }
 
 /// Get all fonts (system + Google)
@@ -1850,26 +2226,51 @@ pub fn clear_font_cache() -> Result<(), String> {
 pub async fn get_all_available_fonts() -> Result<AllFontsResponse, String> {
     let system = get_system_fonts().await?;
     
-    // Check cache state in separate scope to release lock before await
-    let (google_fonts_loaded, cached_google) = {
-        let state = FONT_MANAGER.read().map_err(|e| e.to_string())?;
-        (state.google_fonts_loaded, state.google_fonts.clone())
-    };
-    
-    let google = if google_fonts_loaded {
-        cached_google
-    } else {
-        google_fonts::fetch_fonts_list().await.unwrap_or_else(|_| google_fonts::get_popular_fonts())
+    let google = match loaded_google_fonts() {
+        Some(fonts) => fonts.as_ref().clone(),
+        None => google_fonts::fetch_fonts_list().await.unwrap_or_else(|_| google_fonts::get_popular_fonts()),
     };
 
-    let embedded: Vec<String> = {
-        let state = FONT_MANAGER.read().map_err(|e| e.to_string())?;
-        state.embedded_fonts.keys().cloned().collect()
-    };
+    let embedded: Vec<String> = FONT_MANAGER.embedded_fonts.iter().map(|f| f.key().clone()).collect();
 
     Ok(AllFontsResponse { system, google, embedded })
 }
 
+/// Audit font usage across all text layers, flagging missing fonts
+#[tauri::command]
+pub async fn audit_fonts(pages: Vec<crate::models::PageData>) -> Result<audit::FontAuditReport, String> {
+    let (mut fonts, total_text_layers) = audit::collect_usages(&pages);
+
+    for usage in &mut fonts {
+        let font_match = find_font_match(usage.family.clone(), Some(usage.weight), Some(usage.is_italic)).await?;
+        usage.availability = audit::classify(audit::is_embedded(&usage.family), &font_match);
+        usage.confidence = if usage.availability == audit::FontAvailability::Embedded {
+            1.0
+        } else {
+            font_match.confidence
+        };
+        usage.matched_family = Some(font_match.family);
+    }
+
+    // Missing fonts first, then by usage
+    fonts.sort_by(|a, b| {
+        (b.availability == audit::FontAvailability::Missing)
+            .cmp(&(a.availability == audit::FontAvailability::Missing))
+            .then(b.layer_count.cmp(&a.layer_count))
+    });
+
+    let missing_count = fonts
+        .iter()
+        .filter(|f| f.availability == audit::FontAvailability::Missing)
+        .count();
+
+    Ok(audit::FontAuditReport {
+        fonts,
+        total_text_layers,
+        missing_count,
+    })
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 #[serde(rename_all = "camelCase")]
 pub struct AllFontsResponse {
@@ -1877,3 +2278,230 @@ pub struct AllFontsResponse {
     pub google: Vec<GoogleFont>,
     pub embedded: Vec<String>,
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::fs;
+    use tokio::sync::{Mutex, MutexGuard};
+    use vortex_core::test_util::{layer, page};
+
+    /// Held by tests that use the global font state
+    static FONT_STATE: Mutex<()> = Mutex::const_new(());
+
+    fn lock_font_state() -> MutexGuard<'static, ()> {
+        FONT_STATE.blocking_lock()
+    }
+
+    fn temp_dir(name: &str) -> PathBuf {
+        let dir = std::env::temp_dir().join(format!("rook-fonts-{}-{}", name, std::process::id()));
+        let _ = fs::remove_dir_all(&dir);
+        dir
+    }
+
+    fn cached_match(family: &str, weight: u16) -> FontMatch {
+        FontMatch {
+            family: family.to_string(),
+            source: FontSource::Google,
+            confidence: 0.95,
+            css_family: format!("'{}'", family),
+            google_url: Some(format!("https://fonts.googleapis.com/css2?family={}:wght@{}", family, weight)),
+            fallback_stack: Vec::new(),
+        }
+    }
+
+    fn text(id: &str, family: &str) -> crate::models::LayerObject {
+        layer(id, "text").with("fontFamily", family).build()
+    }
+
+    #[test]
+    fn test_index_finds_exact_and_misspelled_families() {
+        let fonts = Arc::new(google_fonts::get_popular_fonts());
+        let index = matcher::FontIndex::build(fonts.clone());
+        assert_eq!(index.len(), fonts.len());
+
+        let open_sans = index.exact(&normalizer::normalize_for_comparison("Open Sans")).unwrap();
+        assert_eq!(fonts[open_sans].family, "Open Sans");
+
+        let roboto = fonts.iter().position(|f| f.family == "Roboto").unwrap();
+        assert!(index.candidates("robotto", 8).contains(&roboto));
+
+        let found = matcher::find_best_match_indexed("Robotto", &[], &fonts, &index, 700, false);
+        assert_eq!(found.family, "Roboto");
+        assert_eq!(found.source, FontSource::Google);
+        assert!(found.google_url.unwrap().contains("wght@700"));
+    }
+
+    #[test]
+    fn test_index_is_rebuilt_only_for_a_new_list() {
+        let _state = lock_font_state();
+        let fonts = Arc::new(google_fonts::get_popular_fonts());
+        let index = match_cache::google_index(&fonts);
+        assert!(index.is_for(&fonts));
+        assert!(Arc::ptr_eq(&index, &match_cache::google_index(&fonts)));
+
+        // A reloaded list is rebuilt even when its families are unchanged
+        let reloaded = Arc::new(google_fonts::get_popular_fonts());
+        assert!(!index.is_for(&reloaded));
+        let rebuilt = match_cache::google_index(&reloaded);
+        assert!(!Arc::ptr_eq(&index, &rebuilt));
+        assert!(rebuilt.is_for(&reloaded));
+    }
+
+    #[test]
+    fn test_overrides() {
+        let _state = lock_font_state();
+        FONT_MANAGER.font_overrides.clear();
+
+        assert!(match_cache::set_override(" ", "Inter").is_err());
+        match_cache::set_override(" Helvetica Neue ", "Inter").unwrap();
+        match_cache::set_override("Comic Sans MS", "Nunito").unwrap();
+        assert_eq!(match_cache::get_override("helvetica neue").as_deref(), Some("Inter"));
+
+        let sources: Vec<String> = match_cache::list_overrides().into_iter().map(|o| o.source).collect();
+        assert_eq!(sources, ["Comic Sans MS", "Helvetica Neue"]);
+
+        assert!(match_cache::remove_override("comic sans ms").unwrap());
+        assert!(!match_cache::remove_override("Comic Sans MS").unwrap());
+        assert_eq!(match_cache::get_override("Comic Sans MS"), None);
+        FONT_MANAGER.font_overrides.clear();
+    }
+
+    #[test]
+    fn test_cache_survives_a_restart() {
+        let _state = lock_font_state();
+        let dir = temp_dir("cache");
+        match_cache::init(dir.clone()).unwrap();
+
+        FONT_MANAGER.font_cache.clear();
+        FONT_MANAGER.font_overrides.clear();
+        match_cache::set_override("Helvetica Neue", "Inter").unwrap();
+        FONT_MANAGER.font_cache.insert(match_cache::cache_key("Roboto", 400, false), cached_match("Roboto", 400));
+        FONT_MANAGER.font_cache.insert(match_cache::cache_key("Roboto", 700, true), cached_match("Roboto", 700));
+        match_cache::persist().unwrap();
+
+        // An entry keyed by name alone, as older versions wrote them
+        let path = dir.join("font_match_cache.json");
+        let mut file: serde_json::Value = serde_json::from_slice(&fs::read(&path).unwrap()).unwrap();
+        file["matches"]["Roboto"] = serde_json::to_value(cached_match("Roboto", 900)).unwrap();
+        fs::write(&path, serde_json::to_vec(&file).unwrap()).unwrap();
+
+        FONT_MANAGER.font_cache.clear();
+        FONT_MANAGER.font_overrides.clear();
+        match_cache::init(dir.clone()).unwrap();
+
+        assert_eq!(FONT_MANAGER.font_cache.len(), 2);
+        let bold = FONT_MANAGER.font_cache.get(&match_cache::cache_key("Roboto", 700, true)).unwrap().clone();
+        assert!(bold.google_url.unwrap().contains("wght@700"));
+        assert_eq!(match_cache::get_override("Helvetica Neue").as_deref(), Some("Inter"));
+
+        FONT_MANAGER.font_cache.clear();
+        FONT_MANAGER.font_overrides.clear();
+        *FONT_MANAGER.cache_path.write().unwrap() = None;
+        let _ = fs::remove_dir_all(&dir);
+    }
+
+    #[test]
+    fn test_install_list_and_uninstall() {
+        let dir = temp_dir("installed");
+        fs::create_dir_all(&dir).unwrap();
+        let montserrat = include_bytes!("../../lopdf/tests/resources/fonts/Montserrat-Regular.ttf");
+
+        let installed = installer::write_font_file(&dir, "Montserrat", montserrat).unwrap();
+        assert_eq!(installed.file_name().unwrap(), "Montserrat.ttf");
+        // Put there by hand, so its file name is not the family's
+        fs::write(dir.join("Montserrat-Custom.ttf"), montserrat).unwrap();
+        fs::write(dir.join("notes.txt"), b"not a font").unwrap();
+
+        let fonts = installer::list_fonts_in(&dir).unwrap();
+        let mut ids: Vec<&str> = fonts.iter().filter_map(|f| f.id.as_deref()).collect();
+        ids.sort_unstable();
+        assert_eq!(ids, ["Montserrat-Custom.ttf", "Montserrat.ttf"]);
+        assert!(fonts.iter().all(|f| f.family == "Montserrat" && f.source == FontSource::Custom));
+
+        installer::remove_font_file(&dir, "Montserrat-Custom.ttf").unwrap();
+        let ids: Vec<Option<String>> = installer::list_fonts_in(&dir).unwrap().into_iter().map(|f| f.id).collect();
+        assert_eq!(ids, [Some("Montserrat.ttf".to_string())]);
+
+        // Unknown ids, non-font files and paths out of the directory are refused
+        assert!(installer::remove_font_file(&dir, "Montserrat-Custom.ttf").is_err());
+        assert!(installer::remove_font_file(&dir, "notes.txt").is_err());
+        fs::write(dir.with_extension("ttf"), montserrat).unwrap();
+        let outside = format!("../{}", dir.with_extension("ttf").file_name().unwrap().to_string_lossy());
+        assert!(installer::remove_font_file(&dir, &outside).is_err());
+        assert!(dir.with_extension("ttf").exists());
+
+        let _ = fs::remove_file(dir.with_extension("ttf"));
+        let _ = fs::remove_dir_all(&dir);
+    }
+
+    #[test]
+    fn test_collect_usages_groups_by_family_weight_and_style() {
+        let pages = vec![
+            page(1, vec![text("a", "Lora"), layer("img", "image").build(), text("b", " Lora ")]),
+            page(0, vec![
+                layer("c", "text").fields(serde_json::json!({ "fontFamily": "Lora", "fontStyle": "oblique" })).build(),
+                layer("d", "text").with("fontWeight", 700).build(),
+                text("e", "Lora"),
+            ]),
+        ];
+
+        let (usages, total_text_layers) = audit::collect_usages(&pages);
+        assert_eq!(total_text_layers, 5);
+        let keys: Vec<(&str, u16, bool, usize)> =
+            usages.iter().map(|u| (u.family.as_str(), u.weight, u.is_italic, u.layer_count)).collect();
+        assert_eq!(keys, [("Lora", 400, false, 3), ("Lora", 400, true, 1), ("Unknown", 700, false, 1)]);
+        assert_eq!(usages[0].pages, [0, 1]);
+        let layers: Vec<&str> = usages[0].locations.iter().map(|l| l.layer_id.as_str()).collect();
+        assert_eq!(layers, ["a", "b", "e"]);
+    }
+
+    #[tokio::test]
+    async fn test_audit_reports_missing_substituted_and_embedded_fonts() {
+        let _state = FONT_STATE.lock().await;
+        FONT_MANAGER.font_cache.clear();
+        FONT_MANAGER.font_overrides.clear();
+
+        // Matches are served from the cache, so no font scan or download runs
+        let substitute = FontMatch { confidence: 0.8, ..cached_match("Arimo", 400) };
+        let fallback = FontMatch {
+            source: FontSource::System,
+            confidence: 0.3,
+            google_url: None,
+            ..cached_match("Arial", 400)
+        };
+        for (family, font_match) in [("Helvetica", &substitute), ("Vortex Display", &fallback), ("Garamond", &fallback)] {
+            FONT_MANAGER.font_cache.insert(match_cache::cache_key(family, 400, false), font_match.clone());
+        }
+        FONT_MANAGER.embedded_fonts.insert(
+            "Garamond".to_string(),
+            EmbeddedFont { name: "Garamond".to_string(), data: Vec::new(), metrics: FontMetrics::default() },
+        );
+
+        let pages = vec![
+            page(0, vec![text("h1", "Helvetica"), text("h2", "Helvetica"), text("v", "Vortex Display")]),
+            page(1, vec![text("g", "Garamond"), text("h3", "Helvetica")]),
+        ];
+        let report = audit_fonts(pages).await.unwrap();
+
+        assert_eq!(report.total_text_layers, 5);
+        assert_eq!(report.missing_count, 1);
+        let fonts: Vec<(&str, audit::FontAvailability, Option<&str>, f32)> = report
+            .fonts
+            .iter()
+            .map(|u| (u.family.as_str(), u.availability, u.matched_family.as_deref(), u.confidence))
+            .collect();
+        assert_eq!(
+            fonts,
+            [
+                ("Vortex Display", audit::FontAvailability::Missing, Some("Arial"), 0.3),
+                ("Helvetica", audit::FontAvailability::Google, Some("Arimo"), 0.8),
+                ("Garamond", audit::FontAvailability::Embedded, Some("Arial"), 1.0),
+            ]
+        );
+        assert_eq!(report.fonts[1].pages, [0, 1]);
+
+        FONT_MANAGER.font_cache.clear();
+        FONT_MANAGER.embedded_fonts.remove("Garamond");
+    }
+}
@@ -6,11 +6,13 @@
 //! NOTE: Most functionality has been consolidated into font_manager.rs
 //! This module provides Tauri command wrappers and legacy API compatibility.
 
+use crate::ipc;
 use crate::font_manager::{self, FontInfo as FMFontInfo, FontSource as FMFontSource, GoogleFont as FMGoogleFont};
 use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
 use std::path::PathBuf;
 use std::sync::{Arc, RwLock};
+use tauri::ipc::Response;
 use tauri::{AppHandle, Emitter};
 
 lazy_static::lazy_static! {
@@ -131,18 +133,23 @@ pub fn get_google_font_url(family: String, weights: Vec<String>) -> String {
 }
 
 /// Store embedded font data from PDF
+///
+/// The font file is the request body and `font-name` a header argument
+/// (see `ipc`).
 #[tauri::command]
-pub fn store_embedded_font(font_name: String, font_data: Vec<u8>) -> Result<(), String> {
+pub fn store_embedded_font(request: tauri::ipc::Request<'_>) -> Result<(), String> {
+    let font_name: String = ipc::required_arg(&request, "font-name")?;
+    let font_data = ipc::body(&request)?.to_vec();
     let mut fonts = EMBEDDED_FONTS.write().map_err(|e| e.to_string())?;
     fonts.insert(font_name, font_data);
     Ok(())
 }
 
-/// Get embedded font data
+/// Get embedded font data, as raw bytes; empty when the font is unknown
 #[tauri::command]
-pub fn get_embedded_font(font_name: String) -> Result<Option<Vec<u8>>, String> {
+pub fn get_embedded_font(font_name: String) -> Result<Response, String> {
     let fonts = EMBEDDED_FONTS.read().map_err(|e| e.to_string())?;
-    Ok(fonts.get(&font_name).cloned())
+    Ok(Response::new(fonts.get(&font_name).cloned().unwrap_or_default()))
 }
 
 /// List all embedded fonts
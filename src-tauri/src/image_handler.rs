@@ -4,44 +4,61 @@
 //! Includes progressive loading, thumbnail generation, and format conversion.
 //! 
 //! ## Memory Safety
-//! - Uses `Arc<RwLock>` for concurrent read access (better than Mutex)
-//! - Implements LRU eviction to prevent unbounded memory growth
+//! - Entries live in a sharded map (`DashMap`), so reads and writes of
+//!   different images rarely contend and no command holds a cache-wide lock
+//! - Thumbnails and dimensions are computed outside any lock
+//! - Implements LRU eviction (by per-entry access ticks) to prevent unbounded
+//!   memory growth
 //! - Proper cleanup via `Drop` trait and explicit `clear_cache()`
+//! - Lazy sources let huge imports defer decoding until an image is requested,
+//!   and re-decode images the LRU already evicted
 
-use base64::{engine::general_purpose::STANDARD as BASE64, Engine as _};
-use std::collections::HashMap;
+use dashmap::DashMap;
 use std::fs::File;
 use std::io::Write;
+use std::sync::atomic::{AtomicU32, AtomicU64, AtomicUsize, Ordering};
 use std::sync::{Arc, RwLock};
+use crate::ipc;
+use crate::models::{LayerObject, PageData};
 use tauri::ipc::Response;
-
-/// Maximum cache size in bytes (100MB)
-const MAX_CACHE_SIZE: usize = 100 * 1024 * 1024;
+use vortex_core::image_crop;
+use vortex_core::image_place;
+use vortex_core::image_trace::{self, TraceOptions, TraceResult};
+use vortex_core::page_setup::{Margins, PageSetup};
 
 /// Thumbnail size for previews
 const THUMBNAIL_SIZE: u32 = 256;
 
+/// Longest side an image is traced at; larger images are downsampled first
+const TRACE_MAX_DIMENSION: u32 = 2048;
+/// Longest side of a preview trace
+const TRACE_PREVIEW_DIMENSION: u32 = 400;
+/// Quality edited JPEGs are re-encoded at
+const REENCODE_JPEG_QUALITY: u8 = 92;
+
 /// Image entry with metadata
-/// Uses `Box<[u8]>` instead of `Vec<u8>` for immutable data (saves capacity overhead)
-#[derive(Clone)]
+/// Uses `Arc<[u8]>` for immutable data, so readers take a reference and copy
+/// it after releasing the shard lock
 struct ImageEntry {
-    data: Box<[u8]>,
+    data: Arc<[u8]>,
     width: u32,
     height: u32,
     format: ImageFormat,
     thumbnail: Option<Box<[u8]>>,
+    /// Tick of the last access, for LRU eviction
+    last_access: AtomicU64,
 }
 
 impl ImageEntry {
-    /// Create new entry, converting Vec to Box for memory efficiency
     #[inline]
-    fn new(data: Vec<u8>, width: u32, height: u32, format: ImageFormat) -> Self {
+    fn new(data: Vec<u8>, width: u32, height: u32, format: ImageFormat, tick: u64) -> Self {
         Self {
-            data: data.into_boxed_slice(),
+            data: data.into(),
             width,
             height,
             format,
             thumbnail: None,
+            last_access: AtomicU64::new(tick),
         }
     }
     
@@ -101,71 +118,79 @@ impl ImageFormat {
 }
 
 /// Image handler for caching and serving images
-/// Thread-safe with RwLock for concurrent read access
+/// Thread-safe: all methods take `&self`, entries are sharded and the
+/// totals are atomics
 pub struct ImageHandler {
-    cache: HashMap<String, ImageEntry>,
-    total_size: usize,
-    access_order: Vec<String>, // For LRU eviction
+    cache: DashMap<String, ImageEntry>,
+    total_size: AtomicUsize,
+    max_size: AtomicUsize,
+    /// Source of access ticks; higher is more recent
+    clock: AtomicU64,
 }
 
 impl ImageHandler {
     #[inline]
     pub fn new() -> Self {
         Self {
-            cache: HashMap::with_capacity(64),
-            total_size: 0,
-            access_order: Vec::with_capacity(64),
+            cache: DashMap::with_capacity(64),
+            total_size: AtomicUsize::new(0),
+            max_size: AtomicUsize::new(crate::settings::current().image_cache_bytes()),
+            clock: AtomicU64::new(0),
         }
     }
 
+    #[inline]
+    fn tick(&self) -> u64 {
+        self.clock.fetch_add(1, Ordering::Relaxed)
+    }
+
+    /// Set the cache budget in bytes, evicting entries that no longer fit
+    pub fn set_max_size(&self, max_size: usize) {
+        self.max_size.store(max_size, Ordering::Relaxed);
+        self.evict_lru(0);
+    }
+
+    /// Image data, marking it as recently used
+    fn data(&self, image_id: &str) -> Option<Arc<[u8]>> {
+        let entry = self.cache.get(image_id)?;
+        entry.last_access.store(self.tick(), Ordering::Relaxed);
+        Some(entry.data.clone())
+    }
+
     /// Get image data as a Tauri v2 Response
-    pub fn get_image_response(&mut self, image_id: &str) -> Result<Response, ImageError> {
-        let data = self
-            .cache
-            .get(image_id)
-            .ok_or_else(|| ImageError::NotFound(image_id.to_string()))?
-            .data
-            .to_vec();
-        
-        // Update access order for LRU
-        self.update_access_order(image_id);
-        
-        Ok(Response::new(data))
+    pub fn get_image_response(&self, image_id: &str) -> Result<Response, ImageError> {
+        let data = self.data(image_id).ok_or_else(|| ImageError::NotFound(image_id.to_string()))?;
+        Ok(Response::new(data.to_vec()))
     }
 
     /// Get thumbnail for an image
-    pub fn get_thumbnail(&mut self, image_id: &str) -> Result<Vec<u8>, ImageError> {
-        // First check if thumbnail exists
-        if let Some(entry) = self.cache.get(image_id) {
-            if let Some(ref thumb) = entry.thumbnail {
+    pub fn get_thumbnail(&self, image_id: &str) -> Result<Vec<u8>, ImageError> {
+        let (data, width, height) = {
+            let entry = self.cache.get(image_id).ok_or_else(|| ImageError::NotFound(image_id.to_string()))?;
+            if let Some(thumb) = &entry.thumbnail {
                 return Ok(thumb.to_vec());
             }
+            (entry.data.clone(), entry.width, entry.height)
+        };
+
+        // Decoding and scaling can take a while; no lock is held meanwhile
+        let thumbnail = generate_thumbnail(&data, width, height).ok_or(ImageError::ThumbnailFailed)?;
+
+        // Store it unless the image was replaced or already given one
+        if let Some(mut entry) = self.cache.get_mut(image_id) {
+            if entry.thumbnail.is_none() && Arc::ptr_eq(&entry.data, &data) {
+                entry.thumbnail = Some(thumbnail.clone().into_boxed_slice());
+                self.total_size.fetch_add(thumbnail.len(), Ordering::Relaxed);
+            }
         }
-        
-        // Generate thumbnail if not cached
-        let entry = self
-            .cache
-            .get(image_id)
-            .ok_or_else(|| ImageError::NotFound(image_id.to_string()))?;
-        
-        let thumbnail = generate_thumbnail(&entry.data, entry.width, entry.height)
-            .ok_or(ImageError::ThumbnailFailed)?;
-        
-        // Store thumbnail (need to get mutable reference)
-        if let Some(entry) = self.cache.get_mut(image_id) {
-            let thumb_size = thumbnail.len();
-            entry.thumbnail = Some(thumbnail.clone().into_boxed_slice());
-            self.total_size += thumb_size;
-        }
-        
+
         Ok(thumbnail)
     }
 
     /// Get raw image bytes
     #[inline]
-    pub fn get_image_bytes(&mut self, image_id: &str) -> Option<Vec<u8>> {
-        self.update_access_order(image_id);
-        self.cache.get(image_id).map(|e| e.data.to_vec())
+    pub fn get_image_bytes(&self, image_id: &str) -> Option<Vec<u8>> {
+        self.data(image_id).map(|data| data.to_vec())
     }
 
     /// Get image metadata without copying data
@@ -176,13 +201,13 @@ impl ImageHandler {
 
     /// Cache an image with metadata
     #[inline]
-    pub fn cache_image(&mut self, image_id: &str, data: Vec<u8>) {
+    pub fn cache_image(&self, image_id: &str, data: Vec<u8>) {
         self.cache_image_with_dimensions(image_id, data, 0, 0);
     }
 
     /// Cache an image with known dimensions
     pub fn cache_image_with_dimensions(
-        &mut self,
+        &self,
         image_id: &str,
         data: Vec<u8>,
         width: u32,
@@ -191,17 +216,6 @@ impl ImageHandler {
         let data_size = data.len();
         let format = ImageFormat::from_bytes(&data);
 
-        // Remove old entry if exists
-        if let Some(old_entry) = self.cache.remove(image_id) {
-            self.total_size = self.total_size.saturating_sub(old_entry.size());
-            self.access_order.retain(|id| id != image_id);
-        }
-
-        // Evict if needed
-        if self.total_size + data_size > MAX_CACHE_SIZE {
-            self.evict_lru(data_size);
-        }
-
         // Detect dimensions if not provided
         let (w, h) = if width == 0 || height == 0 {
             detect_image_dimensions(&data).unwrap_or((0, 0))
@@ -209,35 +223,48 @@ impl ImageHandler {
             (width, height)
         };
 
-        self.total_size += data_size;
-        self.access_order.push(image_id.to_string());
-        self.cache.insert(image_id.to_string(), ImageEntry::new(data, w, h, format));
-    }
+        // Drop the old entry first so it is not counted against the budget
+        self.remove_image(image_id);
+        self.evict_lru(data_size);
 
-    /// Update access order for LRU
-    fn update_access_order(&mut self, image_id: &str) {
-        self.access_order.retain(|id| id != image_id);
-        self.access_order.push(image_id.to_string());
+        let entry = ImageEntry::new(data, w, h, format, self.tick());
+        self.total_size.fetch_add(data_size, Ordering::Relaxed);
+        if let Some(old) = self.cache.insert(image_id.to_string(), entry) {
+            // Cached by another thread since the removal above
+            self.total_size.fetch_sub(old.size(), Ordering::Relaxed);
+        }
     }
 
-    /// Evict least recently used entries
-    fn evict_lru(&mut self, needed_size: usize) {
-        while self.total_size + needed_size > MAX_CACHE_SIZE && !self.access_order.is_empty() {
-            let oldest = self.access_order.remove(0);
-            if let Some(entry) = self.cache.remove(&oldest) {
-                self.total_size = self.total_size.saturating_sub(entry.size());
+    /// Evict least recently used entries until `needed_size` more bytes fit
+    fn evict_lru(&self, needed_size: usize) {
+        let max_size = self.max_size.load(Ordering::Relaxed);
+        if self.total_size.load(Ordering::Relaxed) + needed_size <= max_size {
+            return;
+        }
+
+        // Snapshot the ticks so no shard stays locked while evicting
+        let mut by_age: Vec<(u64, String)> = self
+            .cache
+            .iter()
+            .map(|e| (e.last_access.load(Ordering::Relaxed), e.key().clone()))
+            .collect();
+        by_age.sort_unstable();
+        for (_, id) in by_age {
+            if self.total_size.load(Ordering::Relaxed) + needed_size <= max_size {
+                break;
             }
+            self.remove_image(&id);
         }
     }
 
     /// Remove an image from cache
-    pub fn remove_image(&mut self, image_id: &str) -> bool {
-        if let Some(entry) = self.cache.remove(image_id) {
-            self.total_size = self.total_size.saturating_sub(entry.size());
-            self.access_order.retain(|id| id != image_id);
-            true
-        } else {
-            false
+    pub fn remove_image(&self, image_id: &str) -> bool {
+        match self.cache.remove(image_id) {
+            Some((_, entry)) => {
+                self.total_size.fetch_sub(entry.size(), Ordering::Relaxed);
+                true
+            }
+            None => false,
         }
     }
 
@@ -254,10 +281,11 @@ impl ImageHandler {
     }
 
     /// Clear all cached images
-    pub fn clear_cache(&mut self) {
-        self.cache.clear();
-        self.access_order.clear();
-        self.total_size = 0;
+    pub fn clear_cache(&self) {
+        self.cache.retain(|_, entry| {
+            self.total_size.fetch_sub(entry.size(), Ordering::Relaxed);
+            false
+        });
     }
 
     /// Get the number of cached images
@@ -268,13 +296,13 @@ impl ImageHandler {
 
     /// Get total cache size in bytes
     #[inline]
-    pub const fn total_cache_size(&self) -> usize {
-        self.total_size
+    pub fn total_cache_size(&self) -> usize {
+        self.total_size.load(Ordering::Relaxed)
     }
 
     /// Get all cached image IDs
     pub fn get_cached_ids(&self) -> Vec<String> {
-        self.cache.keys().cloned().collect()
+        self.cache.iter().map(|e| e.key().clone()).collect()
     }
 }
 
@@ -289,10 +317,10 @@ impl Drop for ImageHandler {
     fn drop(&mut self) {
         #[cfg(debug_assertions)]
         if !self.cache.is_empty() {
-            eprintln!(
+            tracing::debug!(
                 "[ImageHandler] Dropping {} cached images ({} bytes)",
                 self.cache.len(),
-                self.total_size
+                self.total_cache_size()
             );
         }
         self.clear_cache();
@@ -318,6 +346,52 @@ pub enum ImageError {
     ThumbnailFailed,
 }
 
+/// Frame header of a JPEG stream
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct JpegInfo {
+    pub width: u32,
+    pub height: u32,
+    /// 1 for gray, 3 for YCbCr/RGB, 4 for CMYK
+    pub components: u8,
+    /// Bits per sample
+    pub precision: u8,
+}
+
+/// Read a JPEG's frame header (SOF0-SOF2) without decoding it
+pub fn jpeg_info(data: &[u8]) -> Option<JpegInfo> {
+    if ImageFormat::from_bytes(data) != ImageFormat::Jpeg {
+        return None;
+    }
+    let mut i = 2;
+    while i + 9 < data.len() {
+        if data[i] != 0xFF {
+            i += 1;
+            continue;
+        }
+        match data[i + 1] {
+            0xC0..=0xC2 => {
+                return Some(JpegInfo {
+                    precision: data[i + 4],
+                    height: u16::from_be_bytes([data[i + 5], data[i + 6]]) as u32,
+                    width: u16::from_be_bytes([data[i + 7], data[i + 8]]) as u32,
+                    components: data[i + 9],
+                });
+            }
+            // Padding and stuffed bytes
+            0x00 | 0xFF => i += 1,
+            // Markers without a length
+            0x01 | 0xD0..=0xD9 => i += 2,
+            _ => i += 2 + u16::from_be_bytes([data[i + 2], data[i + 3]]) as usize,
+        }
+    }
+    None
+}
+
+/// MIME type of encoded image bytes
+pub fn mime_type(data: &[u8]) -> &'static str {
+    ImageFormat::from_bytes(data).mime_type()
+}
+
 /// Detect image dimensions from raw bytes
 fn detect_image_dimensions(data: &[u8]) -> Option<(u32, u32)> {
     let format = ImageFormat::from_bytes(data);
@@ -332,25 +406,8 @@ fn detect_image_dimensions(data: &[u8]) -> Option<(u32, u32)> {
             }
         }
         ImageFormat::Jpeg => {
-            // JPEG: Need to parse SOF markers
-            let mut i = 2;
-            while i < data.len() - 9 {
-                if data[i] == 0xFF {
-                    let marker = data[i + 1];
-                    // SOF0, SOF1, SOF2 markers
-                    if marker >= 0xC0 && marker <= 0xC2 {
-                        let height = u16::from_be_bytes([data[i + 5], data[i + 6]]) as u32;
-                        let width = u16::from_be_bytes([data[i + 7], data[i + 8]]) as u32;
-                        return Some((width, height));
-                    }
-                    // Skip to next marker
-                    if marker != 0x00 && marker != 0xFF {
-                        let len = u16::from_be_bytes([data[i + 2], data[i + 3]]) as usize;
-                        i += 2 + len;
-                        continue;
-                    }
-                }
-                i += 1;
+            if let Some(info) = jpeg_info(data) {
+                return Some((info.width, info.height));
             }
         }
         _ => {}
@@ -375,16 +432,91 @@ fn generate_thumbnail(data: &[u8], _width: u32, _height: u32) -> Option<Vec<u8>>
     Some(buffer.into_inner())
 }
 
-// Global image handler instance using RwLock for concurrent reads
+/// Where to find an image that has not been decoded yet
+#[derive(Debug, Clone, PartialEq)]
+pub struct LazyImageSource {
+    pub file_path: String,
+    pub page_index: u16,
+    /// Index of the image object within the page's object list
+    pub object_index: usize,
+    /// Downsample so neither side exceeds this many pixels
+    pub max_dimension: Option<u32>,
+}
+
+/// Decodes a lazy source into encoded image bytes
+pub type LazyImageLoader = fn(&LazyImageSource) -> Option<Vec<u8>>;
+
+// Global image handler instance; it locks per shard internally
 lazy_static::lazy_static! {
-    static ref IMAGE_HANDLER: Arc<RwLock<ImageHandler>> = Arc::new(RwLock::new(ImageHandler::new()));
+    static ref IMAGE_HANDLER: ImageHandler = ImageHandler::new();
+    // Kept outside the handler so decoding never touches the cache
+    static ref LAZY_SOURCES: DashMap<String, LazyImageSource> = DashMap::new();
+    static ref LAZY_LOADER: RwLock<Option<LazyImageLoader>> = RwLock::new(None);
+}
+
+/// Fit `width`x`height` within `max_dimension`, keeping the aspect ratio
+pub fn fit_dimensions(width: u32, height: u32, max_dimension: Option<u32>) -> (u32, u32) {
+    match max_dimension {
+        Some(max) if max > 0 && width.max(height) > max => {
+            let scale = max as f64 / width.max(height) as f64;
+            (
+                ((width as f64 * scale).round() as u32).max(1),
+                ((height as f64 * scale).round() as u32).max(1),
+            )
+        }
+        _ => (width, height),
+    }
+}
+
+/// Set the function used to decode lazy sources on demand
+pub fn set_lazy_image_loader(loader: LazyImageLoader) {
+    if let Ok(mut slot) = LAZY_LOADER.write() {
+        *slot = Some(loader);
+    }
+}
+
+/// Register an image to be decoded the first time it is requested
+pub fn register_lazy_image(image_id: &str, source: LazyImageSource) {
+    LAZY_SOURCES.insert(image_id.to_string(), source);
+}
+
+/// Number of registered lazy sources
+pub fn lazy_image_count() -> usize {
+    LAZY_SOURCES.len()
+}
+
+/// Set the image cache budget in bytes
+pub fn set_cache_budget(max_size: usize) {
+    IMAGE_HANDLER.set_max_size(max_size);
+}
+
+/// Decode a lazily registered image into the cache if it is missing
+fn ensure_image_loaded(image_id: &str) -> bool {
+    if IMAGE_HANDLER.has_image(image_id) {
+        return true;
+    }
+    let source = LAZY_SOURCES.get(image_id).map(|s| s.clone());
+    let loader = LAZY_LOADER.read().ok().and_then(|l| *l);
+    let (Some(source), Some(loader)) = (source, loader) else {
+        return false;
+    };
+    match loader(&source) {
+        Some(data) => {
+            cache_image(image_id, data);
+            true
+        }
+        None => {
+            tracing::warn!(image_id, page = source.page_index, "lazy image decode failed");
+            false
+        }
+    }
 }
 
 /// Get image data via Tauri command
 #[tauri::command]
 pub fn get_image(image_id: String) -> Response {
-    let mut handler = IMAGE_HANDLER.write().unwrap();
-    handler
+    ensure_image_loaded(&image_id);
+    IMAGE_HANDLER
         .get_image_response(&image_id)
         .unwrap_or_else(|_| Response::new(vec![]))
 }
@@ -392,38 +524,166 @@ pub fn get_image(image_id: String) -> Response {
 /// Get image thumbnail via Tauri command
 #[tauri::command]
 pub fn get_image_thumbnail(image_id: String) -> Response {
-    let mut handler = IMAGE_HANDLER.write().unwrap();
-    match handler.get_thumbnail(&image_id) {
+    ensure_image_loaded(&image_id);
+    match IMAGE_HANDLER.get_thumbnail(&image_id) {
         Ok(data) => Response::new(data),
         Err(_) => Response::new(vec![]),
     }
 }
 
-/// Get image info via Tauri command (read-only, uses read lock)
+/// Get image info via Tauri command
 #[tauri::command]
 pub fn get_image_info(image_id: String) -> Option<(u32, u32, String)> {
-    let handler = IMAGE_HANDLER.read().unwrap();
-    handler.get_image_info(&image_id).map(|(w, h, f)| (w, h, f.mime_type().to_string()))
+    IMAGE_HANDLER.get_image_info(&image_id).map(|(w, h, f)| (w, h, f.mime_type().to_string()))
+}
+
+/// Place dropped image bytes on `page` as a new image layer
+///
+/// Format, pixel size and resolution are read from the file header. The
+/// layer is sized at that resolution (96 dpi when none is recorded), shrunk
+/// to fit inside `margins` (one inch by default), and centered on the drop
+/// point (`x`, `y`) or in the margins. The image is cached and the layer
+/// stacked above the page's layers; the frontend adds it to the page.
+///
+/// The image is the request body; `page`, `margins`, `x` and `y` are header
+/// arguments (see `ipc`). `page` may leave out its layers and give the
+/// `z-index` to stack at instead.
+#[tauri::command]
+pub fn place_image(request: tauri::ipc::Request<'_>) -> Result<LayerObject, String> {
+    let page: PageData = ipc::required_arg(&request, "page")?;
+    let mut layer = place_image_layer(
+        &page,
+        ipc::body(&request)?.to_vec(),
+        ipc::arg(&request, "margins")?,
+        ipc::arg(&request, "x")?,
+        ipc::arg(&request, "y")?,
+    )?;
+    if let Some(z_index) = ipc::arg(&request, "z-index")? {
+        layer.z_index = z_index;
+    }
+    Ok(layer)
+}
+
+/// Image layer for `data` on `page`, as `place_image` places it
+pub fn place_image_layer(
+    page: &PageData,
+    data: Vec<u8>,
+    margins: Option<Margins>,
+    x: Option<f32>,
+    y: Option<f32>,
+) -> Result<LayerObject, String> {
+    static PLACED: AtomicU32 = AtomicU32::new(0);
+    let stamp = std::time::SystemTime::now()
+        .duration_since(std::time::UNIX_EPOCH)
+        .map(|d| d.as_millis())
+        .unwrap_or_default();
+    let id = format!("placed-{}-{}-{}", page.page_index, stamp, PLACED.fetch_add(1, Ordering::Relaxed));
+
+    let setup = PageSetup { width: page.width, height: page.height, margins: margins.unwrap_or_default(), bleed: 0.0 };
+    let mut layer = image_place::image_layer(id, &data, &setup, x.zip(y))?;
+    layer.z_index = page.layers.iter().map(|l| l.z_index + 1).max().unwrap_or(0);
+    let metadata = layer.image_data.as_ref().ok_or("Image layer has no metadata")?;
+    cache_image_with_dimensions(&layer.id, data, metadata.width, metadata.height);
+    Ok(layer)
 }
 
-/// Export a layer image from data URL to file
+/// Trace an image layer into a vector layer, so a low-resolution logo prints sharp
+///
+/// With `preview` the image is traced at a small size for quick feedback while
+/// the options are adjusted. The traced layer is returned for the frontend to
+/// swap in; nothing is cached.
 #[tauri::command]
-pub fn export_layer_image(data_url: String, output_path: String) -> Result<bool, String> {
-    // Parse data URL: data:image/png;base64,<data>
-    let parts: Vec<&str> = data_url.splitn(2, ',').collect();
-    if parts.len() != 2 {
-        return Err("Invalid data URL format".to_string());
+pub async fn trace_image(
+    layer: LayerObject,
+    options: Option<TraceOptions>,
+    preview: Option<bool>,
+) -> Result<TraceResult, String> {
+    tokio::task::spawn_blocking(move || {
+        let bytes = layer_image_bytes(&layer).ok_or_else(|| format!("Image not found: {}", layer.id))?;
+        let image = image::load_from_memory(&bytes).map_err(|e| format!("Failed to decode image: {}", e))?;
+        let max = if preview.unwrap_or(false) { TRACE_PREVIEW_DIMENSION } else { TRACE_MAX_DIMENSION };
+        let (width, height) = fit_dimensions(image.width(), image.height(), Some(max));
+        let image = if (width, height) == (image.width(), image.height()) {
+            image
+        } else {
+            image.resize_exact(width, height, image::imageops::FilterType::Triangle)
+        };
+        image_trace::trace_layer(&layer, image.to_rgba8().as_raw(), width, height, &options.unwrap_or_default())
+    })
+    .await
+    .map_err(|e| format!("Trace task failed: {}", e))?
+}
+
+/// Encode an edited copy of `source`: JPEG again for JPEG sources, PNG otherwise
+pub(crate) fn encode_like(source: &[u8], image: &image::DynamicImage) -> Result<Vec<u8>, String> {
+    let mut data = std::io::Cursor::new(Vec::new());
+    if jpeg_info(source).is_some() {
+        let encoder = image::codecs::jpeg::JpegEncoder::new_with_quality(&mut data, REENCODE_JPEG_QUALITY);
+        if image.color().has_color() {
+            image.to_rgb8().write_with_encoder(encoder)
+        } else {
+            image.to_luma8().write_with_encoder(encoder)
+        }
+    } else {
+        image.write_to(&mut data, image::ImageFormat::Png)
     }
+    .map_err(|e| format!("Failed to encode image: {}", e))?;
+    Ok(data.into_inner())
+}
+
+/// Crop uniform margins baked into an image layer's bitmap
+///
+/// The tight image is cached under a new id (`<id>-cropped`) and the layer's
+/// bounds shrink to match, so the page looks the same. JPEGs stay JPEG, other
+/// formats become PNG. `None` when the image has no margins to crop.
+#[tauri::command]
+pub async fn auto_crop_image(layer: LayerObject, tolerance: Option<u8>) -> Result<Option<LayerObject>, String> {
+    tokio::task::spawn_blocking(move || {
+        let bytes = layer_image_bytes(&layer).ok_or_else(|| format!("Image not found: {}", layer.id))?;
+        let image = image::load_from_memory(&bytes).map_err(|e| format!("Failed to decode image: {}", e))?;
+        let (width, height) = (image.width(), image.height());
+        let tolerance = tolerance.unwrap_or(image_crop::DEFAULT_CROP_TOLERANCE);
+        let margins = image_crop::detect_margins(image.to_rgba8().as_raw(), width, height, tolerance)
+            .ok_or("Image is blank; nothing to crop")?;
+        if margins.is_empty() {
+            return Ok(None);
+        }
+
+        let tight = image.crop_imm(
+            margins.left,
+            margins.top,
+            width - margins.left - margins.right,
+            height - margins.top - margins.bottom,
+        );
+        let data = encode_like(&bytes, &tight)?;
+
+        let source_id = layer.image_url.as_deref().and_then(|url| url.strip_prefix("image://")).unwrap_or(&layer.id);
+        let id = format!("{}-cropped", source_id.trim_end_matches("-cropped"));
+        cache_image_with_dimensions(&id, data, tight.width(), tight.height());
+
+        let mut cropped = image_crop::crop_layer(&layer, width, height, &margins);
+        cropped.image_url = Some(format!("image://{}", id));
+        // The bitmap no longer matches the linked file
+        cropped.image_link = None;
+        Ok(Some(cropped))
+    })
+    .await
+    .map_err(|e| format!("Crop task failed: {}", e))?
+}
 
-    let base64_data = parts[1];
-    let image_data = BASE64
-        .decode(base64_data)
-        .map_err(|e| format!("Failed to decode base64: {}", e))?;
+/// Write an exported layer image to `output-path`
+///
+/// The encoded image is the request body and `output-path` a header
+/// argument (see `ipc`).
+#[tauri::command]
+pub fn export_layer_image(request: tauri::ipc::Request<'_>) -> Result<bool, String> {
+    let image_data = ipc::body(&request)?;
+    let output_path: String = ipc::required_arg(&request, "output-path")?;
 
     let mut file =
         File::create(&output_path).map_err(|e| format!("Failed to create file: {}", e))?;
 
-    file.write_all(&image_data)
+    file.write_all(image_data)
         .map_err(|e| format!("Failed to write file: {}", e))?;
 
     Ok(true)
@@ -432,43 +692,85 @@ pub fn export_layer_image(data_url: String, output_path: String) -> Result<bool,
 /// Cache an image (internal use)
 #[inline]
 pub fn cache_image(image_id: &str, data: Vec<u8>) {
-    let mut handler = IMAGE_HANDLER.write().unwrap();
-    handler.cache_image(image_id, data);
+    IMAGE_HANDLER.cache_image(image_id, data);
 }
 
 /// Cache an image with dimensions (internal use)
 #[inline]
 pub fn cache_image_with_dimensions(image_id: &str, data: Vec<u8>, width: u32, height: u32) {
-    let mut handler = IMAGE_HANDLER.write().unwrap();
-    handler.cache_image_with_dimensions(image_id, data, width, height);
+    IMAGE_HANDLER.cache_image_with_dimensions(image_id, data, width, height);
 }
 
 /// Get image bytes for protocol handler (internal use)
 #[inline]
 pub fn get_image_bytes(image_id: &str) -> Option<Vec<u8>> {
-    let mut handler = IMAGE_HANDLER.write().unwrap();
-    handler.get_image_bytes(image_id)
+    ensure_image_loaded(image_id);
+    IMAGE_HANDLER.get_image_bytes(image_id)
+}
+
+/// Image bytes without the import downsampling of lazy sources
+pub fn full_resolution_bytes(image_id: &str) -> Option<Vec<u8>> {
+    let source = LAZY_SOURCES.get(image_id).map(|s| s.clone());
+    let loader = LAZY_LOADER.read().ok().and_then(|l| *l);
+    match (source, loader) {
+        (Some(source), Some(loader)) if source.max_dimension.is_some() => {
+            loader(&LazyImageSource { max_dimension: None, ..source })
+        }
+        _ => get_image_bytes(image_id),
+    }
+}
+
+/// Encoded bytes of an image layer, from the cache (`image://<id>`) or its file path
+pub fn layer_image_bytes(layer: &LayerObject) -> Option<Vec<u8>> {
+    match layer.image_url.as_deref().and_then(|url| url.strip_prefix("image://")) {
+        Some(id) => get_image_bytes(id),
+        None => layer.image_path.as_deref().and_then(|path| std::fs::read(path).ok()),
+    }
 }
 
 /// Remove an image from cache (internal use)
 #[inline]
 pub fn remove_cached_image(image_id: &str) -> bool {
-    let mut handler = IMAGE_HANDLER.write().unwrap();
-    handler.remove_image(image_id)
+    IMAGE_HANDLER.remove_image(image_id)
 }
 
-/// Clear all cached images (internal use)
+/// Clear all cached images and their color profiles (internal use)
 /// Call this when closing a document to prevent memory leaks
 pub fn clear_image_cache() {
-    let mut handler = IMAGE_HANDLER.write().unwrap();
-    handler.clear_cache();
+    IMAGE_HANDLER.clear_cache();
+    LAZY_SOURCES.clear();
+    crate::color_profile::clear();
+}
+
+/// Ids of all cached and lazily registered images
+pub fn cached_image_ids() -> Vec<String> {
+    let mut ids = IMAGE_HANDLER.get_cached_ids();
+    ids.extend(LAZY_SOURCES.iter().map(|s| s.key().clone()));
+    ids.sort();
+    ids.dedup();
+    ids
+}
+
+/// Whether an image is cached or can be decoded on demand
+pub fn image_available(image_id: &str) -> bool {
+    IMAGE_HANDLER.has_image(image_id) || LAZY_SOURCES.contains_key(image_id)
+}
+
+/// Size in bytes of a cached image; `None` when it is not in the cache
+pub fn cached_image_size(image_id: &str) -> Option<usize> {
+    IMAGE_HANDLER.get_image_size(image_id)
+}
+
+/// Remove an image from the cache and forget its lazy source
+pub fn forget_image(image_id: &str) -> bool {
+    let lazy = LAZY_SOURCES.remove(image_id).is_some();
+    remove_cached_image(image_id) || lazy
 }
 
-/// Get cache statistics (read-only)
+/// Get cache statistics
 #[inline]
 pub fn get_cache_stats() -> (usize, usize) {
-    let handler = IMAGE_HANDLER.read().unwrap();
-    (handler.cache_count(), handler.total_cache_size())
+    (IMAGE_HANDLER.cache_count(), IMAGE_HANDLER.total_cache_size())
 }
 
 #[cfg(test)]
@@ -477,7 +779,7 @@ mod tests {
 
     #[test]
     fn test_image_handler_cache() {
-        let mut handler = ImageHandler::new();
+        let handler = ImageHandler::new();
         let image_data = vec![0x89, 0x50, 0x4E, 0x47]; // PNG magic bytes
 
         handler.cache_image("test-image", image_data.clone());
@@ -490,7 +792,7 @@ mod tests {
 
     #[test]
     fn test_image_handler_remove() {
-        let mut handler = ImageHandler::new();
+        let handler = ImageHandler::new();
         handler.cache_image("test-image", vec![1, 2, 3]);
 
         assert!(handler.remove_image("test-image"));
@@ -500,7 +802,7 @@ mod tests {
 
     #[test]
     fn test_image_handler_clear() {
-        let mut handler = ImageHandler::new();
+        let handler = ImageHandler::new();
         handler.cache_image("image-1", vec![1]);
         handler.cache_image("image-2", vec![2]);
 
@@ -512,7 +814,7 @@ mod tests {
 
     #[test]
     fn test_image_not_found() {
-        let mut handler = ImageHandler::new();
+        let handler = ImageHandler::new();
         let result = handler.get_image_response("nonexistent");
 
         assert!(result.is_err());
@@ -524,7 +826,7 @@ mod tests {
 
     #[test]
     fn test_cache_update_size_tracking() {
-        let mut handler = ImageHandler::new();
+        let handler = ImageHandler::new();
 
         // Add initial image
         handler.cache_image("test", vec![1, 2, 3, 4, 5]);
@@ -541,7 +843,7 @@ mod tests {
 
     #[test]
     fn test_multiple_images_size_tracking() {
-        let mut handler = ImageHandler::new();
+        let handler = ImageHandler::new();
 
         handler.cache_image("img1", vec![1, 2, 3]);
         handler.cache_image("img2", vec![4, 5, 6, 7]);
@@ -550,4 +852,76 @@ mod tests {
         assert_eq!(handler.cache_count(), 3);
         assert_eq!(handler.total_cache_size(), 9); // 3 + 4 + 2
     }
+
+    #[test]
+    fn test_budget_evicts_lru() {
+        let handler = ImageHandler::new();
+        handler.cache_image("a", vec![0; 6]);
+        handler.cache_image("b", vec![0; 6]);
+        handler.set_max_size(8);
+        assert!(!handler.has_image("a"));
+        assert!(handler.has_image("b"));
+        handler.cache_image("c", vec![0; 4]);
+        assert!(!handler.has_image("b"));
+    }
+
+    #[test]
+    fn test_concurrent_caching() {
+        let handler = ImageHandler::new();
+        std::thread::scope(|scope| {
+            for thread in 0..4 {
+                let handler = &handler;
+                scope.spawn(move || {
+                    for i in 0..50 {
+                        handler.cache_image(&format!("{}-{}", thread, i), vec![0; 10]);
+                        handler.get_image_bytes(&format!("{}-{}", thread, i / 2));
+                    }
+                });
+            }
+        });
+        assert_eq!(handler.cache_count(), 200);
+        assert_eq!(handler.total_cache_size(), 2000);
+    }
+
+    #[test]
+    fn test_fit_dimensions() {
+        assert_eq!(fit_dimensions(4000, 2000, Some(1000)), (1000, 500));
+        assert_eq!(fit_dimensions(800, 600, Some(1000)), (800, 600));
+        assert_eq!(fit_dimensions(4000, 2000, None), (4000, 2000));
+    }
+
+    #[test]
+    fn test_lazy_image_decoded_on_demand() {
+        fn loader(source: &LazyImageSource) -> Option<Vec<u8>> {
+            (source.object_index == 7).then(|| vec![source.page_index as u8; 3])
+        }
+        set_lazy_image_loader(loader);
+        let source = LazyImageSource {
+            file_path: "scan.pdf".to_string(),
+            page_index: 2,
+            object_index: 7,
+            max_dimension: None,
+        };
+        register_lazy_image("lazy-test-image", source.clone());
+        register_lazy_image("lazy-test-broken", LazyImageSource { object_index: 1, ..source });
+
+        assert_eq!(get_image_bytes("lazy-test-image"), Some(vec![2, 2, 2]));
+        assert!(IMAGE_HANDLER.has_image("lazy-test-image"));
+        assert_eq!(get_image_bytes("lazy-test-broken"), None);
+        remove_cached_image("lazy-test-image");
+    }
+
+    #[test]
+    fn test_jpeg_info() {
+        let image = image::RgbImage::from_pixel(40, 24, image::Rgb([200, 30, 30]));
+        let mut jpeg = std::io::Cursor::new(Vec::new());
+        image.write_to(&mut jpeg, image::ImageFormat::Jpeg).unwrap();
+        let jpeg = jpeg.into_inner();
+
+        let info = jpeg_info(&jpeg).unwrap();
+        assert_eq!((info.width, info.height, info.components, info.precision), (40, 24, 3, 8));
+        assert_eq!(mime_type(&jpeg), "image/jpeg");
+        assert_eq!(jpeg_info(&jpeg[..20]), None);
+        assert_eq!(jpeg_info(b"\x89PNG\r\n\x1a\n0000"), None);
+    }
 }
@@ -0,0 +1,201 @@
+//! Image Sequence Module
+//!
+//! The "images" export format: every page rasterized at the export DPI into
+//! a numbered PNG, JPEG or TIFF file, for web previews, slide decks and
+//! print shops that only take images. Pages go through a temporary PDF like
+//! `ink_coverage`, so watermarks, stamps and bleed come out as in a PDF
+//! export. TIFFs are LZW-compressed and all files carry their resolution
+//! except PNG, which the image crate writes without a density.
+
+use crate::export_handler::{self, ExportError};
+use crate::job_manager::JobHandle;
+use crate::models::{DocumentMetadata, ExportResult, PageData};
+use crate::ocr_handler;
+use crate::pdf_engine::load_pdfium;
+use image::codecs::jpeg::{JpegEncoder, PixelDensity};
+use image::{DynamicImage, ImageFormat, RgbaImage};
+use std::io::{Cursor, Write};
+use std::path::{Path, PathBuf};
+use tiff::encoder::colortype::{self, ColorType};
+use tiff::encoder::{Compression, Predictor, Rational, TiffEncoder};
+use tiff::tags::ResolutionUnit;
+use vortex_core::export::{
+    sequence_file_name, ExportFormat, ExportOptions, ImageFileFormat, ImageSequenceOptions, RasterColorMode,
+    DEFAULT_IMAGE_DPI,
+};
+use vortex_core::units::POINTS_PER_INCH;
+use zip::write::SimpleFileOptions;
+use zip::CompressionMethod;
+
+fn image_error(e: impl std::fmt::Display) -> ExportError {
+    ExportError::ImageExport(e.to_string())
+}
+
+fn encode_tiff<C: ColorType<Inner = u8>>(width: u32, height: u32, data: &[u8], dpi: u32) -> tiff::TiffResult<Vec<u8>> {
+    let mut buffer = Cursor::new(Vec::new());
+    let mut encoder =
+        TiffEncoder::new(&mut buffer)?.with_compression(Compression::Lzw).with_predictor(Predictor::Horizontal);
+    let mut image = encoder.new_image::<C>(width, height)?;
+    image.resolution(ResolutionUnit::Inch, Rational { n: dpi, d: 1 });
+    image.write_data(data)?;
+    Ok(buffer.into_inner())
+}
+
+/// Encode a rendered page in the sequence's file format and color mode;
+/// `quality` is the JPEG quality in percent
+pub(crate) fn encode_page(
+    page: RgbaImage,
+    options: &ImageSequenceOptions,
+    dpi: u32,
+    quality: u8,
+) -> Result<Vec<u8>, ExportError> {
+    let page = DynamicImage::ImageRgba8(page);
+    let image = match options.color_mode {
+        RasterColorMode::Rgb => DynamicImage::ImageRgb8(page.to_rgb8()),
+        RasterColorMode::Grayscale => DynamicImage::ImageLuma8(page.to_luma8()),
+    };
+    let (width, height) = (image.width(), image.height());
+    match options.format {
+        ImageFileFormat::Png => {
+            let mut buffer = Cursor::new(Vec::new());
+            image.write_to(&mut buffer, ImageFormat::Png).map_err(image_error)?;
+            Ok(buffer.into_inner())
+        }
+        ImageFileFormat::Jpeg => {
+            let mut buffer = Vec::new();
+            let mut encoder = JpegEncoder::new_with_quality(&mut buffer, quality.clamp(1, 100));
+            encoder.set_pixel_density(PixelDensity::dpi(dpi.min(u16::MAX as u32) as u16));
+            encoder.encode_image(&image).map_err(image_error)?;
+            Ok(buffer)
+        }
+        ImageFileFormat::Tiff => match &image {
+            DynamicImage::ImageLuma8(gray) => encode_tiff::<colortype::Gray8>(width, height, gray.as_raw(), dpi),
+            _ => encode_tiff::<colortype::RGB8>(width, height, image.as_bytes(), dpi),
+        }
+        .map_err(image_error),
+    }
+}
+
+/// Export `pages` as numbered image files next to `output_path`, or packed
+/// into a zip archive at `output_path`
+pub(crate) fn export_images_sync(
+    pages: &[PageData],
+    output_path: &str,
+    metadata: &DocumentMetadata,
+    options: &ExportOptions,
+    job: &JobHandle,
+) -> Result<ExportResult, ExportError> {
+    let sequence = options.images.unwrap_or_default();
+    let pages = export_handler::selected_pages(pages, options)?;
+    if pages.is_empty() {
+        return Err(ExportError::NoPages);
+    }
+    let dpi = options.dpi.unwrap_or(DEFAULT_IMAGE_DPI).clamp(9, 2400);
+
+    let pdf_path = std::env::temp_dir().join(format!("rook-images-{}.pdf", std::process::id()));
+    let pdf_options = ExportOptions {
+        format: ExportFormat::Pdf,
+        output_path: pdf_path.to_string_lossy().to_string(),
+        page_range: None,
+        create_layers: false,
+        encryption: None,
+        signature: None,
+        ..options.clone()
+    };
+    let rendered = export_handler::export_pdf_sync(pages, &pdf_options.output_path, metadata, &pdf_options)
+        .and_then(|_| write_images(&pdf_options.output_path, output_path, &sequence, dpi, options.image_quality, job));
+    let _ = std::fs::remove_file(&pdf_path);
+    let written = rendered?;
+
+    Ok(ExportResult {
+        success: true,
+        message: format!("Exported {} pages as {} images at {} dpi", pages.len(), sequence.format.extension(), dpi),
+        output_path: written.first().map(|p| p.to_string_lossy().into_owned()),
+        data: None,
+    })
+}
+
+/// Render each page of the PDF at `pdf_path` and write it out; returns the
+/// files written
+fn write_images(
+    pdf_path: &str,
+    output_path: &str,
+    sequence: &ImageSequenceOptions,
+    dpi: u32,
+    quality: u8,
+    job: &JobHandle,
+) -> Result<Vec<PathBuf>, ExportError> {
+    let pdfium = load_pdfium().map_err(ExportError::ImageExport)?;
+    let doc = pdfium.load_pdf_from_file(pdf_path, None).map_err(image_error)?;
+    let total = doc.pages().len() as usize;
+    let output = Path::new(output_path);
+    let stem = output.file_stem().map(|s| s.to_string_lossy().into_owned()).unwrap_or_else(|| "page".to_string());
+    let scale = dpi as f32 / POINTS_PER_INCH;
+
+    let mut archive = if sequence.zip {
+        Some(zip::ZipWriter::new(std::fs::File::create(output.with_extension("zip"))?))
+    } else {
+        None
+    };
+    // Image data is compressed already
+    let stored = SimpleFileOptions::default().compression_method(CompressionMethod::Stored);
+    let mut written = Vec::with_capacity(total);
+    for (i, page) in doc.pages().iter().enumerate() {
+        job.check_cancelled().map_err(ExportError::ImageExport)?;
+        let name = sequence_file_name(&stem, i + 1, total, sequence.format);
+        let bytes = encode_page(
+            ocr_handler::render_page_for_ocr(&page, scale).map_err(ExportError::ImageExport)?,
+            sequence,
+            dpi,
+            quality,
+        )?;
+        match &mut archive {
+            Some(zip) => {
+                zip.start_file(name, stored).map_err(image_error)?;
+                zip.write_all(&bytes)?;
+            }
+            None => {
+                let path = output.with_file_name(name);
+                std::fs::write(&path, bytes)?;
+                written.push(path);
+            }
+        }
+        job.progress((i + 1) as f32 / total as f32, Some(format!("Page {} of {}", i + 1, total)));
+    }
+    if let Some(zip) = archive {
+        zip.finish().map_err(image_error)?;
+        written = vec![output.with_extension("zip")];
+    }
+    Ok(written)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use tiff::decoder::Decoder;
+    use tiff::tags::Tag;
+
+    #[test]
+    fn test_encode_page() {
+        let page = RgbaImage::from_fn(40, 20, |x, _| image::Rgba([(x * 6) as u8, 0, 0, 255]));
+        let options = |format, color_mode| ImageSequenceOptions { format, color_mode, zip: false };
+
+        let tif = encode_page(page.clone(), &options(ImageFileFormat::Tiff, RasterColorMode::Rgb), 300, 100).unwrap();
+        let mut decoder = Decoder::new(Cursor::new(tif)).unwrap();
+        assert_eq!(decoder.dimensions().unwrap(), (40, 20));
+        // 5 = LZW
+        assert_eq!(decoder.get_tag_u32(Tag::Compression).unwrap(), 5);
+        assert_eq!(decoder.get_tag_u32(Tag::ResolutionUnit).unwrap(), 2);
+        assert_eq!(decoder.colortype().unwrap(), tiff::ColorType::RGB(8));
+
+        let gray = encode_page(page.clone(), &options(ImageFileFormat::Tiff, RasterColorMode::Grayscale), 300, 100);
+        assert_eq!(Decoder::new(Cursor::new(gray.unwrap())).unwrap().colortype().unwrap(), tiff::ColorType::Gray(8));
+
+        let png = encode_page(page.clone(), &options(ImageFileFormat::Png, RasterColorMode::Grayscale), 72, 100);
+        assert_eq!(image::load_from_memory(&png.unwrap()).unwrap().color(), image::ColorType::L8);
+        let jpeg = encode_page(page, &options(ImageFileFormat::Jpeg, RasterColorMode::Rgb), 200, 80).unwrap();
+        assert_eq!(image::guess_format(&jpeg).unwrap(), ImageFormat::Jpeg);
+        // JFIF density: units 1 (dpi), then 200 x 200
+        assert_eq!(jpeg[13..18], [1, 0, 200, 0, 200]);
+    }
+}
@@ -0,0 +1,118 @@
+//! Import Mappings Module
+//!
+//! Saved rules that give the layers of a recurring source layout their
+//! roles, tags and style as they are imported (see
+//! `vortex_core::import_mapping`). A mapping is recorded from a document as
+//! imported and the same document corrected, saved to the app data dir
+//! (`import_mappings.json`), and named in `ImportOptions::import_mapping` on
+//! later imports.
+
+use crate::models::PageData;
+use std::fs;
+use std::path::PathBuf;
+use std::sync::{Arc, RwLock};
+use vortex_core::import_mapping;
+
+pub use vortex_core::import_mapping::ImportMapping;
+
+const MAPPINGS_FILE: &str = "import_mappings.json";
+
+#[derive(Debug, Default)]
+struct MappingStore {
+    mappings: Vec<ImportMapping>,
+    path: Option<PathBuf>,
+}
+
+lazy_static::lazy_static! {
+    static ref MAPPING_STORE: Arc<RwLock<MappingStore>> = Arc::new(RwLock::new(MappingStore::default()));
+}
+
+/// Load saved mappings from `dir` and remember it for later saves
+pub fn init_import_mappings(dir: PathBuf) -> Result<(), String> {
+    fs::create_dir_all(&dir).map_err(|e| e.to_string())?;
+    let path = dir.join(MAPPINGS_FILE);
+    let mappings: Vec<ImportMapping> = fs::read(&path)
+        .ok()
+        .and_then(|data| serde_json::from_slice(&data).ok())
+        .unwrap_or_default();
+
+    let mut store = MAPPING_STORE.write().map_err(|e| e.to_string())?;
+    store.mappings = mappings;
+    store.path = Some(path);
+    Ok(())
+}
+
+fn persist(store: &MappingStore) -> Result<(), String> {
+    let Some(path) = &store.path else {
+        return Ok(());
+    };
+    let data = serde_json::to_vec_pretty(&store.mappings).map_err(|e| e.to_string())?;
+    let tmp = path.with_extension("json.tmp");
+    fs::write(&tmp, data).map_err(|e| e.to_string())?;
+    fs::rename(&tmp, path).map_err(|e| e.to_string())
+}
+
+/// Find a saved mapping by name (case-insensitive)
+pub fn resolve_mapping(name: &str) -> Option<ImportMapping> {
+    let store = MAPPING_STORE.read().ok()?;
+    store.mappings.iter().find(|m| m.name.eq_ignore_ascii_case(name)).cloned()
+}
+
+/// List the saved import mappings
+#[tauri::command]
+pub fn list_import_mappings() -> Result<Vec<ImportMapping>, String> {
+    let store = MAPPING_STORE.read().map_err(|e| e.to_string())?;
+    Ok(store.mappings.clone())
+}
+
+/// Save a mapping, replacing any existing mapping with the same name
+#[tauri::command]
+pub fn save_import_mapping(mapping: ImportMapping) -> Result<(), String> {
+    let name = mapping.name.trim();
+    if name.is_empty() {
+        return Err("Mapping name cannot be empty".to_string());
+    }
+    let mapping = ImportMapping { name: name.to_string(), ..mapping };
+
+    let mut store = MAPPING_STORE.write().map_err(|e| e.to_string())?;
+    match store.mappings.iter_mut().find(|m| m.name.eq_ignore_ascii_case(name)) {
+        Some(existing) => *existing = mapping,
+        None => store.mappings.push(mapping),
+    }
+    persist(&store)
+}
+
+/// Delete a saved mapping
+#[tauri::command]
+pub fn delete_import_mapping(name: String) -> Result<(), String> {
+    let mut store = MAPPING_STORE.write().map_err(|e| e.to_string())?;
+    let before = store.mappings.len();
+    store.mappings.retain(|m| !m.name.eq_ignore_ascii_case(&name));
+    if store.mappings.len() == before {
+        return Err(format!("Import mapping '{}' not found", name));
+    }
+    persist(&store)
+}
+
+/// Record the roles, tags and styles given to the layers of `imported` in
+/// `corrected` as a mapping named `name`, and save it
+#[tauri::command]
+pub fn record_import_mapping(
+    name: String,
+    imported: Vec<PageData>,
+    corrected: Vec<PageData>,
+) -> Result<ImportMapping, String> {
+    let mapping = import_mapping::record_mapping(name.trim(), &imported, &corrected);
+    if mapping.rules.is_empty() {
+        return Err("No layer roles, tags or styles were changed".to_string());
+    }
+    save_import_mapping(mapping.clone())?;
+    Ok(mapping)
+}
+
+/// Apply a mapping to pages already imported; returns the updated pages
+#[tauri::command]
+pub fn apply_import_mapping(mut pages: Vec<PageData>, mapping: ImportMapping) -> Vec<PageData> {
+    import_mapping::apply_mapping(&mut pages, &mapping);
+    pages
+}
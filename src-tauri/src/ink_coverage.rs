@@ -0,0 +1,243 @@
+//! Ink Coverage Module
+//!
+//! Print budgeting: which colors a document uses and roughly how much ink
+//! each page takes. Pages are exported to a temporary PDF, rendered at a low
+//! resolution with pdfium and converted to CMYK with the same naive device
+//! conversion as CMYK export. A page's coverage for a channel is that
+//! channel's mean over the page, so a solid black page is 100% K and the
+//! total can reach 400%.
+
+use crate::export_handler::{self, parse_hex_color, rgb_to_cmyk};
+use crate::models::{DocumentMetadata, PageData};
+use crate::ocr_handler;
+use crate::pdf_engine::load_pdfium;
+use image::RgbaImage;
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, BTreeSet};
+use vortex_core::export::ExportOptions;
+use vortex_core::units::POINTS_PER_INCH;
+
+/// Coarse enough to be quick, fine enough for a budget estimate
+const DEFAULT_DPI: u32 = 36;
+/// Total coverage (all four channels) above which a page is flagged, in percent
+const DEFAULT_MAX_COVERAGE: f32 = 100.0;
+
+/// Settings for ink coverage estimation
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct InkCoverageOptions {
+    /// Render resolution (default 36)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub dpi: Option<u32>,
+    /// Flag pages whose total coverage exceeds this percentage (default 100)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub max_coverage: Option<f32>,
+}
+
+/// What a color is used for
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq, PartialOrd, Ord)]
+#[serde(rename_all = "lowercase")]
+#[repr(u8)]
+pub enum ColorRole {
+    Text = 0,
+    Fill = 1,
+    Stroke = 2,
+    /// Text frame or page background
+    Background = 3,
+}
+
+/// One color and where it is used
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct ColorUsage {
+    /// Normalized "#RRGGBB"
+    pub color: String,
+    /// Layers (and page backgrounds) using it
+    pub uses: usize,
+    pub roles: Vec<ColorRole>,
+    pub pages: Vec<usize>,
+    /// Device CMYK equivalent, 0-100 per channel
+    pub cmyk: [f32; 4],
+}
+
+/// Estimated ink on one page, 0-100 per channel
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct PageInkCoverage {
+    pub page_index: usize,
+    pub cyan: f32,
+    pub magenta: f32,
+    pub yellow: f32,
+    pub black: f32,
+    /// Sum of the four channels
+    pub total: f32,
+    /// Total exceeds the threshold
+    pub flagged: bool,
+}
+
+/// Colors and ink coverage for a document
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct InkCoverageReport {
+    /// Most used first
+    pub colors: Vec<ColorUsage>,
+    pub pages: Vec<PageInkCoverage>,
+    /// Mean total coverage over all pages
+    pub average_total: f32,
+    pub flagged_pages: Vec<usize>,
+}
+
+fn cmyk_percent(r: u8, g: u8, b: u8) -> [f32; 4] {
+    let (c, m, y, k) = rgb_to_cmyk(r as f32 / 255.0, g as f32 / 255.0, b as f32 / 255.0);
+    [c * 100.0, m * 100.0, y * 100.0, k * 100.0]
+}
+
+/// Uses, roles and pages seen for one color
+type ColorTally = (usize, BTreeSet<ColorRole>, BTreeSet<usize>);
+
+/// Every color used by layers and page backgrounds, most used first
+pub fn collect_colors(pages: &[PageData]) -> Vec<ColorUsage> {
+    let mut found: BTreeMap<(u8, u8, u8), ColorTally> = BTreeMap::new();
+    let mut add = |color: Option<&str>, role, page_index| {
+        if let Some(rgb) = color.and_then(parse_hex_color) {
+            let entry = found.entry(rgb).or_default();
+            entry.0 += 1;
+            entry.1.insert(role);
+            entry.2.insert(page_index);
+        }
+    };
+
+    for page in pages {
+        add(page.background.as_ref().and_then(|b| b.color.as_deref()), ColorRole::Background, page.page_index);
+        for layer in page.layers.iter().filter(|l| l.visible) {
+            add(layer.color.as_deref(), ColorRole::Text, page.page_index);
+            add(layer.fill_color.as_deref(), ColorRole::Fill, page.page_index);
+            add(layer.stroke_color.as_deref(), ColorRole::Stroke, page.page_index);
+            add(layer.background_color.as_deref(), ColorRole::Background, page.page_index);
+        }
+    }
+
+    let mut colors: Vec<ColorUsage> = found
+        .into_iter()
+        .map(|((r, g, b), (uses, roles, pages))| ColorUsage {
+            color: format!("#{:02X}{:02X}{:02X}", r, g, b),
+            uses,
+            roles: roles.into_iter().collect(),
+            pages: pages.into_iter().collect(),
+            cmyk: cmyk_percent(r, g, b),
+        })
+        .collect();
+    colors.sort_by(|a, b| b.uses.cmp(&a.uses));
+    colors
+}
+
+/// Mean CMYK coverage of a rendered page, 0-100 per channel
+///
+/// Transparent pixels count as unprinted paper.
+pub fn image_coverage(image: &RgbaImage) -> [f32; 4] {
+    let mut sums = [0.0f64; 4];
+    for px in image.pixels() {
+        let alpha = px[3] as f32 / 255.0;
+        let over_white = |c: u8| (c as f32 * alpha + 255.0 * (1.0 - alpha)).round() as u8;
+        let cmyk = cmyk_percent(over_white(px[0]), over_white(px[1]), over_white(px[2]));
+        for (sum, value) in sums.iter_mut().zip(cmyk) {
+            *sum += value as f64;
+        }
+    }
+    let count = (image.width() as f64 * image.height() as f64).max(1.0);
+    sums.map(|sum| (sum / count) as f32)
+}
+
+/// Render `pages` and measure each one's ink coverage
+fn measure_pages(pages: &[PageData], dpi: u32) -> Result<Vec<[f32; 4]>, String> {
+    let path = std::env::temp_dir().join(format!("rook-ink-{}.pdf", std::process::id()));
+    let output_path = path.to_string_lossy().to_string();
+    let options: ExportOptions = serde_json::from_value(serde_json::json!({ "format": "pdf", "outputPath": output_path }))
+        .map_err(|e| e.to_string())?;
+    export_handler::export_pdf_sync(pages, &output_path, &DocumentMetadata::default(), &options)
+        .map_err(|e| format!("Failed to render pages: {}", e))?;
+
+    let result = render_coverage(&output_path, dpi);
+    let _ = std::fs::remove_file(&path);
+    result
+}
+
+fn render_coverage(path: &str, dpi: u32) -> Result<Vec<[f32; 4]>, String> {
+    let pdfium = load_pdfium()?;
+    let doc = pdfium.load_pdf_from_file(path, None).map_err(|e| format!("Failed to load render: {}", e))?;
+    let scale = dpi as f32 / POINTS_PER_INCH;
+    doc.pages()
+        .iter()
+        .map(|page| ocr_handler::render_page_for_ocr(&page, scale).map(|image| image_coverage(&image)))
+        .collect()
+}
+
+/// Build the color and coverage report
+pub fn analyze(pages: &[PageData], options: &InkCoverageOptions) -> Result<InkCoverageReport, String> {
+    if pages.is_empty() {
+        return Err("No pages to analyze".to_string());
+    }
+    let limit = options.max_coverage.unwrap_or(DEFAULT_MAX_COVERAGE);
+    let coverage = measure_pages(pages, options.dpi.unwrap_or(DEFAULT_DPI).clamp(9, 300))?;
+
+    let pages_coverage: Vec<PageInkCoverage> = pages
+        .iter()
+        .zip(coverage)
+        .map(|(page, [cyan, magenta, yellow, black])| {
+            let total = cyan + magenta + yellow + black;
+            PageInkCoverage { page_index: page.page_index, cyan, magenta, yellow, black, total, flagged: total > limit }
+        })
+        .collect();
+    let average_total = pages_coverage.iter().map(|p| p.total).sum::<f32>() / pages_coverage.len().max(1) as f32;
+    let flagged_pages = pages_coverage.iter().filter(|p| p.flagged).map(|p| p.page_index).collect();
+
+    Ok(InkCoverageReport { colors: collect_colors(pages), pages: pages_coverage, average_total, flagged_pages })
+}
+
+/// Report colors used and estimated ink coverage per page
+#[tauri::command]
+pub async fn analyze_ink_coverage(
+    pages: Vec<PageData>,
+    options: Option<InkCoverageOptions>,
+) -> Result<InkCoverageReport, String> {
+    tokio::task::spawn_blocking(move || analyze(&pages, &options.unwrap_or_default()))
+        .await
+        .map_err(|e| format!("Ink coverage task failed: {}", e))?
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::PageBackground;
+    use image::Rgba;
+    use vortex_core::test_util::{layer, page};
+
+    #[test]
+    fn test_image_coverage() {
+        // Left half black, right half transparent (paper)
+        let mut image = RgbaImage::from_pixel(4, 2, Rgba([0, 0, 0, 0]));
+        for y in 0..2 {
+            for x in 0..2 {
+                image.put_pixel(x, y, Rgba([0, 0, 0, 255]));
+            }
+        }
+        assert_eq!(image_coverage(&image), [0.0, 0.0, 0.0, 50.0]);
+
+        let red = RgbaImage::from_pixel(2, 2, Rgba([255, 0, 0, 255]));
+        assert_eq!(image_coverage(&red), [0.0, 100.0, 100.0, 0.0]);
+    }
+
+    #[test]
+    fn test_collect_colors() {
+        let text = layer("t", "text").bounds(0.0, 0.0, 100.0, 10.0).fields(serde_json::json!({ "content": "Ink", "color": "#000000" }));
+        let shape = layer("s", "shape").z(1).fields(serde_json::json!({ "fillColor": "#FFFFFF", "strokeColor": "#000000" }));
+        let mut pages = vec![page(0, vec![text.build(), shape.build()])];
+        pages[0].background = Some(PageBackground { color: Some("#ffffff".to_string()), image: None });
+        let colors = collect_colors(&pages);
+        assert_eq!(colors.len(), 2);
+        assert_eq!((colors[0].color.as_str(), colors[0].uses), ("#000000", 2));
+        assert_eq!(colors[0].roles, vec![ColorRole::Text, ColorRole::Stroke]);
+        assert_eq!(colors[0].cmyk, [0.0, 0.0, 0.0, 100.0]);
+        assert_eq!(colors[1].roles, vec![ColorRole::Fill, ColorRole::Background]);
+    }
+}
@@ -0,0 +1,67 @@
+//! Binary IPC
+//!
+//! Large byte payloads (images, fonts, exported files) skip JSON, which
+//! would send each byte as a number in an array: several times the size and
+//! slow to parse on both sides. Commands taking bytes read them from the raw
+//! request body, with their other arguments in headers; commands returning
+//! bytes answer with a `tauri::ipc::Response`, which the frontend receives
+//! as an `ArrayBuffer`.
+//!
+//! Header arguments are JSON, percent-encoded so any text fits in a header
+//! value, under lowercase names; the bridge's `invokeBinary` sends them.
+
+use serde::de::DeserializeOwned;
+use tauri::ipc::{InvokeBody, Request};
+
+/// The bytes a command was invoked with
+pub fn body<'a>(request: &'a Request<'_>) -> Result<&'a [u8], String> {
+    match request.body() {
+        InvokeBody::Raw(data) => Ok(data),
+        InvokeBody::Json(_) => Err("Expected binary data, not JSON".to_string()),
+    }
+}
+
+/// Header argument `name`, `None` when absent
+pub fn arg<T: DeserializeOwned>(request: &Request<'_>, name: &str) -> Result<Option<T>, String> {
+    let Some(value) = request.headers().get(name) else {
+        return Ok(None);
+    };
+    let text = value.to_str().map_err(|e| format!("Argument '{}': {}", name, e))?;
+    let json = percent_decode(text).ok_or_else(|| format!("Argument '{}' is not percent-encoded", name))?;
+    serde_json::from_str(&json).map(Some).map_err(|e| format!("Argument '{}': {}", name, e))
+}
+
+/// Header argument `name` that must be present
+pub fn required_arg<T: DeserializeOwned>(request: &Request<'_>, name: &str) -> Result<T, String> {
+    arg(request, name)?.ok_or_else(|| format!("Missing argument '{}'", name))
+}
+
+/// Text of a `encodeURIComponent` string
+fn percent_decode(text: &str) -> Option<String> {
+    let mut bytes = Vec::with_capacity(text.len());
+    let mut rest = text.as_bytes();
+    while let Some((&byte, tail)) = rest.split_first() {
+        if byte == b'%' {
+            let hex = std::str::from_utf8(tail.get(..2)?).ok()?;
+            bytes.push(u8::from_str_radix(hex, 16).ok()?);
+            rest = &tail[2..];
+        } else {
+            bytes.push(byte);
+            rest = tail;
+        }
+    }
+    String::from_utf8(bytes).ok()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_percent_decode() {
+        assert_eq!(percent_decode("%7B%22family%22%3A%22Caf%C3%A9%22%7D").as_deref(), Some("{\"family\":\"Café\"}"));
+        assert_eq!(percent_decode("12.5").as_deref(), Some("12.5"));
+        assert_eq!(percent_decode("%7"), None);
+        assert_eq!(percent_decode("%C3"), None);
+    }
+}
@@ -0,0 +1,522 @@
+//! Job Manager
+//!
+//! One queue for long-running backend work (imports, exports, OCR, font
+//! scans). Jobs run on a small pool of worker threads, highest priority
+//! first, and report progress through `job_updated` events so the frontend
+//! can show and cancel background work in one place.
+//!
+//! Commands keep their own return values: `run` queues the work and resolves
+//! when it finishes. Cancellation is cooperative; running jobs check
+//! `JobHandle::check_cancelled` between steps.
+
+use indexmap::IndexMap;
+use lazy_static::lazy_static;
+use serde::{Deserialize, Serialize};
+use std::cmp::Ordering as CmpOrdering;
+use std::collections::BinaryHeap;
+use std::panic::{catch_unwind, AssertUnwindSafe};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::{Arc, Condvar, Mutex, RwLock};
+
+/// Worker threads; jobs parallelize internally (rayon), so a few suffice
+const WORKER_COUNT: usize = 2;
+
+/// Finished jobs kept for `list_jobs`
+const MAX_FINISHED_JOBS: usize = 50;
+
+/// Error returned for jobs cancelled before or while running
+pub const CANCELLED: &str = "Job cancelled";
+
+lazy_static! {
+    static ref JOBS: Arc<JobManager> = Arc::new(JobManager::new(WORKER_COUNT));
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum JobKind {
+    Import,
+    Export,
+    Ocr,
+    FontScan,
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, PartialOrd, Ord, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum JobPriority {
+    Low,
+    #[default]
+    Normal,
+    /// Work the user is waiting on, e.g. opening a document
+    High,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum JobStatus {
+    Queued,
+    Running,
+    Completed,
+    Failed,
+    Cancelled,
+}
+
+impl JobStatus {
+    pub fn is_finished(self) -> bool {
+        matches!(self, JobStatus::Completed | JobStatus::Failed | JobStatus::Cancelled)
+    }
+}
+
+/// A job as reported to the frontend
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct JobInfo {
+    pub id: String,
+    pub kind: JobKind,
+    /// Short description, e.g. the file being imported
+    pub label: String,
+    pub priority: JobPriority,
+    pub status: JobStatus,
+    /// Completed fraction (0.0 - 1.0)
+    pub progress: f32,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub message: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub error: Option<String>,
+    /// Unix milliseconds
+    pub created_at: u64,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub started_at: Option<u64>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub finished_at: Option<u64>,
+}
+
+type JobWork = Box<dyn FnOnce(&JobHandle) -> Result<(), String> + Send>;
+
+/// Called with a job's state after every change
+pub type JobListener = Box<dyn Fn(&JobInfo) + Send + Sync>;
+
+struct QueuedJob {
+    id: String,
+    priority: JobPriority,
+    seq: u64,
+    work: JobWork,
+}
+
+impl PartialEq for QueuedJob {
+    fn eq(&self, other: &Self) -> bool {
+        self.seq == other.seq
+    }
+}
+
+impl Eq for QueuedJob {}
+
+impl PartialOrd for QueuedJob {
+    fn partial_cmp(&self, other: &Self) -> Option<CmpOrdering> {
+        Some(self.cmp(other))
+    }
+}
+
+impl Ord for QueuedJob {
+    /// Higher priority first, then first come first served
+    fn cmp(&self, other: &Self) -> CmpOrdering {
+        self.priority.cmp(&other.priority).then(other.seq.cmp(&self.seq))
+    }
+}
+
+struct JobEntry {
+    info: JobInfo,
+    cancelled: Arc<AtomicBool>,
+}
+
+#[derive(Default)]
+struct JobState {
+    next_seq: u64,
+    queue: BinaryHeap<QueuedJob>,
+    jobs: IndexMap<String, JobEntry>,
+    workers_started: bool,
+}
+
+/// Passed to running jobs for progress and cancellation
+pub struct JobHandle {
+    id: String,
+    cancelled: Arc<AtomicBool>,
+    manager: Arc<JobManager>,
+}
+
+impl JobHandle {
+    pub fn id(&self) -> &str {
+        &self.id
+    }
+
+    pub fn is_cancelled(&self) -> bool {
+        self.cancelled.load(Ordering::Relaxed)
+    }
+
+    /// `Err(CANCELLED)` once cancellation was requested
+    pub fn check_cancelled(&self) -> Result<(), String> {
+        if self.is_cancelled() {
+            Err(CANCELLED.to_string())
+        } else {
+            Ok(())
+        }
+    }
+
+    /// Report progress (0.0 - 1.0) with an optional status message
+    pub fn progress(&self, fraction: f32, message: Option<String>) {
+        self.manager.update(&self.id, |info| {
+            info.progress = fraction.clamp(0.0, 1.0);
+            if message.is_some() {
+                info.message = message;
+            }
+        });
+    }
+}
+
+pub struct JobManager {
+    state: Mutex<JobState>,
+    ready: Condvar,
+    workers: usize,
+    listener: RwLock<Option<JobListener>>,
+}
+
+impl JobManager {
+    pub fn new(workers: usize) -> Self {
+        Self {
+            state: Mutex::new(JobState::default()),
+            ready: Condvar::new(),
+            workers: workers.max(1),
+            listener: RwLock::new(None),
+        }
+    }
+
+    pub fn set_listener(&self, listener: JobListener) {
+        if let Ok(mut slot) = self.listener.write() {
+            *slot = Some(listener);
+        }
+    }
+
+    /// Queue work and return the job id
+    pub fn submit(
+        self: &Arc<Self>,
+        kind: JobKind,
+        label: impl Into<String>,
+        priority: JobPriority,
+        work: impl FnOnce(&JobHandle) -> Result<(), String> + Send + 'static,
+    ) -> String {
+        let id = {
+            let mut state = self.state.lock().unwrap_or_else(|e| e.into_inner());
+            if !state.workers_started {
+                state.workers_started = true;
+                for n in 0..self.workers {
+                    let manager = Arc::clone(self);
+                    let spawned = std::thread::Builder::new()
+                        .name(format!("job-worker-{}", n))
+                        .spawn(move || manager.work_loop());
+                    if let Err(e) = spawned {
+                        tracing::error!("failed to start job worker: {}", e);
+                    }
+                }
+            }
+
+            let seq = state.next_seq;
+            state.next_seq += 1;
+            let id = format!("job-{}", seq);
+            let info = JobInfo {
+                id: id.clone(),
+                kind,
+                label: label.into(),
+                priority,
+                status: JobStatus::Queued,
+                progress: 0.0,
+                message: None,
+                error: None,
+                created_at: now_ms(),
+                started_at: None,
+                finished_at: None,
+            };
+            state.jobs.insert(id.clone(), JobEntry { info: info.clone(), cancelled: Arc::new(AtomicBool::new(false)) });
+            state.queue.push(QueuedJob { id, priority, seq, work: Box::new(work) });
+            prune_finished(&mut state.jobs);
+            // Under the lock, so no worker reports the job running first
+            self.notify(&info);
+            info.id
+        };
+        self.ready.notify_one();
+        id
+    }
+
+    /// Queue work and wait for its result
+    pub async fn run<T: Send + 'static>(
+        self: &Arc<Self>,
+        kind: JobKind,
+        label: impl Into<String>,
+        priority: JobPriority,
+        work: impl FnOnce(&JobHandle) -> Result<T, String> + Send + 'static,
+    ) -> Result<T, String> {
+        let (tx, rx) = tokio::sync::oneshot::channel();
+        self.submit(kind, label, priority, move |job| {
+            let result = work(job);
+            let status = result.as_ref().map(|_| ()).map_err(Clone::clone);
+            let _ = tx.send(result);
+            status
+        });
+        // The sender is dropped unsent when the job is cancelled in the queue
+        rx.await.unwrap_or_else(|_| Err(CANCELLED.to_string()))
+    }
+
+    pub fn list(&self) -> Vec<JobInfo> {
+        let state = self.state.lock().unwrap_or_else(|e| e.into_inner());
+        state.jobs.values().map(|entry| entry.info.clone()).collect()
+    }
+
+    /// Cancel a queued or running job; false if it already finished
+    pub fn cancel(&self, id: &str) -> Result<bool, String> {
+        let info = {
+            let mut state = self.state.lock().unwrap_or_else(|e| e.into_inner());
+            let entry = state.jobs.get(id).ok_or_else(|| format!("Job not found: {}", id))?;
+            match entry.info.status {
+                status if status.is_finished() => return Ok(false),
+                JobStatus::Running => {
+                    entry.cancelled.store(true, Ordering::Relaxed);
+                    return Ok(true);
+                }
+                _ => {}
+            }
+            state.queue.retain(|job| job.id != id);
+            let entry = state.jobs.get_mut(id).ok_or_else(|| format!("Job not found: {}", id))?;
+            entry.cancelled.store(true, Ordering::Relaxed);
+            entry.info.status = JobStatus::Cancelled;
+            entry.info.finished_at = Some(now_ms());
+            entry.info.clone()
+        };
+        self.notify(&info);
+        Ok(true)
+    }
+
+    /// Apply `change` to a job and notify the listener
+    fn update(&self, id: &str, change: impl FnOnce(&mut JobInfo)) {
+        let info = {
+            let mut state = self.state.lock().unwrap_or_else(|e| e.into_inner());
+            let Some(entry) = state.jobs.get_mut(id) else {
+                return;
+            };
+            change(&mut entry.info);
+            entry.info.clone()
+        };
+        self.notify(&info);
+    }
+
+    fn notify(&self, info: &JobInfo) {
+        if let Ok(listener) = self.listener.read() {
+            if let Some(listener) = listener.as_ref() {
+                listener(info);
+            }
+        }
+    }
+
+    fn work_loop(self: Arc<Self>) {
+        loop {
+            let (job, cancelled, info) = {
+                let mut state = self.state.lock().unwrap_or_else(|e| e.into_inner());
+                let job = loop {
+                    if let Some(job) = state.queue.pop() {
+                        break job;
+                    }
+                    state = self.ready.wait(state).unwrap_or_else(|e| e.into_inner());
+                };
+                // Marked running under the lock it was popped under, so `cancel`
+                // either takes it off the queue or flags it running; a job
+                // cancelled in between is dropped, which cancels its `run`
+                let Some(entry) = state.jobs.get_mut(&job.id) else {
+                    continue;
+                };
+                if entry.cancelled.load(Ordering::Relaxed) {
+                    continue;
+                }
+                entry.info.status = JobStatus::Running;
+                entry.info.started_at = Some(now_ms());
+                (job, Arc::clone(&entry.cancelled), entry.info.clone())
+            };
+            self.notify(&info);
+
+            let handle = JobHandle { id: job.id.clone(), cancelled, manager: Arc::clone(&self) };
+            let work = job.work;
+            let result = catch_unwind(AssertUnwindSafe(|| work(&handle)));
+            let cancelled = handle.is_cancelled();
+
+            self.update(&job.id, |info| {
+                info.finished_at = Some(now_ms());
+                info.status = match result {
+                    _ if cancelled => JobStatus::Cancelled,
+                    Ok(Ok(())) => {
+                        info.progress = 1.0;
+                        JobStatus::Completed
+                    }
+                    Ok(Err(e)) => {
+                        info.error = Some(e);
+                        JobStatus::Failed
+                    }
+                    Err(_) => {
+                        info.error = Some("Job panicked".to_string());
+                        JobStatus::Failed
+                    }
+                };
+            });
+        }
+    }
+}
+
+/// Drop the oldest finished jobs past `MAX_FINISHED_JOBS`
+fn prune_finished(jobs: &mut IndexMap<String, JobEntry>) {
+    let finished = jobs.values().filter(|entry| entry.info.status.is_finished()).count();
+    let mut excess = finished.saturating_sub(MAX_FINISHED_JOBS);
+    jobs.retain(|_, entry| {
+        if excess > 0 && entry.info.status.is_finished() {
+            excess -= 1;
+            return false;
+        }
+        true
+    });
+}
+
+/// File name of a path, for job labels
+pub fn file_label(path: &str) -> String {
+    std::path::Path::new(path)
+        .file_name()
+        .map_or_else(|| path.to_string(), |name| name.to_string_lossy().into_owned())
+}
+
+fn now_ms() -> u64 {
+    std::time::SystemTime::now()
+        .duration_since(std::time::UNIX_EPOCH)
+        .map(|d| d.as_millis() as u64)
+        .unwrap_or_default()
+}
+
+/// The application's job manager
+pub fn jobs() -> &'static Arc<JobManager> {
+    &JOBS
+}
+
+/// Queue work on the application's job manager and wait for its result
+pub async fn run<T: Send + 'static>(
+    kind: JobKind,
+    label: impl Into<String>,
+    priority: JobPriority,
+    work: impl FnOnce(&JobHandle) -> Result<T, String> + Send + 'static,
+) -> Result<T, String> {
+    JOBS.run(kind, label, priority, work).await
+}
+
+/// List queued, running and recently finished jobs
+#[tauri::command]
+pub fn list_jobs() -> Vec<JobInfo> {
+    JOBS.list()
+}
+
+/// Cancel a job; false if it already finished
+#[tauri::command]
+pub fn cancel_job(job_id: String) -> Result<bool, String> {
+    JOBS.cancel(&job_id)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::mpsc;
+    use std::time::Duration;
+
+    #[test]
+    fn test_priority_order_and_cancel() {
+        let manager = Arc::new(JobManager::new(1));
+        let (order_tx, order_rx) = mpsc::channel();
+        let (gate_tx, gate_rx) = mpsc::channel::<()>();
+
+        // Hold the only worker until the queue is filled
+        manager.submit(JobKind::Import, "blocker", JobPriority::Normal, move |_| {
+            gate_rx.recv().map_err(|e| e.to_string())
+        });
+        let mut ids = Vec::new();
+        for (label, priority) in [("low", JobPriority::Low), ("normal", JobPriority::Normal), ("high", JobPriority::High)] {
+            let tx = order_tx.clone();
+            ids.push(manager.submit(JobKind::Export, label, priority, move |_| {
+                tx.send(label).map_err(|e| e.to_string())
+            }));
+        }
+        assert!(manager.cancel(&ids[1]).unwrap());
+        gate_tx.send(()).unwrap();
+
+        let order: Vec<&str> = (0..2).map(|_| order_rx.recv_timeout(Duration::from_secs(5)).unwrap()).collect();
+        assert_eq!(order, vec!["high", "low"]);
+        let statuses: Vec<JobStatus> = manager.list().iter().map(|j| j.status).collect();
+        assert_eq!(statuses[2], JobStatus::Cancelled);
+        assert!(!manager.cancel(&ids[1]).unwrap());
+    }
+
+    #[test]
+    fn test_run_reports_progress_and_cancellation() {
+        let manager = Arc::new(JobManager::new(1));
+        let updates = Arc::new(Mutex::new(Vec::new()));
+        let seen = Arc::clone(&updates);
+        manager.set_listener(Box::new(move |info| seen.lock().unwrap().push((info.status, info.progress))));
+
+        let runtime = tokio::runtime::Builder::new_current_thread().build().unwrap();
+        let value = runtime.block_on(manager.run(JobKind::Ocr, "ocr", JobPriority::High, |job| {
+            job.progress(0.5, Some("halfway".to_string()));
+            Ok(42)
+        }));
+        assert_eq!(value, Ok(42));
+        // The result is sent before the worker records completion
+        while !manager.list()[0].status.is_finished() {
+            std::thread::sleep(Duration::from_millis(1));
+        }
+        let updates = updates.lock().unwrap().clone();
+        assert_eq!(updates.first(), Some(&(JobStatus::Queued, 0.0)));
+        assert!(updates.contains(&(JobStatus::Running, 0.5)));
+        assert_eq!(updates.last(), Some(&(JobStatus::Completed, 1.0)));
+
+        // A running job stops at its next check once cancelled
+        let (started_tx, started_rx) = mpsc::channel();
+        let task = manager.clone();
+        let result = std::thread::spawn(move || {
+            runtime.block_on(task.run(JobKind::FontScan, "fonts", JobPriority::Normal, move |job| {
+                started_tx.send(job.id().to_string()).unwrap();
+                while !job.is_cancelled() {
+                    std::thread::sleep(Duration::from_millis(1));
+                }
+                job.check_cancelled().map(|()| 0)
+            }))
+        });
+        let id = started_rx.recv_timeout(Duration::from_secs(5)).unwrap();
+        assert!(manager.cancel(&id).unwrap());
+        assert_eq!(result.join().unwrap(), Err(CANCELLED.to_string()));
+    }
+
+    #[test]
+    fn test_jobs_cancelled_once_popped_never_start() {
+        let manager = Arc::new(JobManager::new(1));
+        let (gate_tx, gate_rx) = mpsc::channel::<()>();
+        manager.submit(JobKind::Import, "blocker", JobPriority::Normal, move |_| {
+            gate_rx.recv().map_err(|e| e.to_string())
+        });
+        let (ran_tx, ran_rx) = mpsc::channel::<()>();
+        let id = manager.submit(JobKind::Export, "late", JobPriority::Normal, move |_| {
+            ran_tx.send(()).map_err(|e| e.to_string())
+        });
+
+        // Leave the job queued but cancelled, as a cancel between a worker
+        // popping it and starting it would
+        {
+            let mut state = manager.state.lock().unwrap();
+            let entry = state.jobs.get_mut(&id).unwrap();
+            entry.cancelled.store(true, Ordering::Relaxed);
+            entry.info.status = JobStatus::Cancelled;
+        }
+        gate_tx.send(()).unwrap();
+
+        // Dropped unrun, so its sender goes with it
+        assert_eq!(ran_rx.recv_timeout(Duration::from_secs(5)), Err(mpsc::RecvTimeoutError::Disconnected));
+        let job = manager.list().into_iter().find(|job| job.id == id).unwrap();
+        assert_eq!((job.status, job.started_at), (JobStatus::Cancelled, None));
+    }
+}
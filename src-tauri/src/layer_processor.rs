@@ -3,21 +3,41 @@
 //! Handles layer operations including updates, deletions, and z-index management.
 //! Note: Layer state is primarily managed in the frontend (Pinia store).
 //! These commands provide backend validation and can be extended for persistence.
+//! The z-order and alignment logic lives in `vortex_core::layers`, and
+//! duplicate and empty-layer cleanup in `vortex_core::layer_cleanup`, and
+//! decorative elements in `vortex_core::decorations`, typography cleanup
+//! in `vortex_core::typography`, and footnotes in `vortex_core::notes`, all
+//! shared with the wasm build.
 
 use crate::models::{LayerObject, LayerUpdates, PageData};
+use std::sync::atomic::{AtomicU32, Ordering};
+use vortex_core::decorations::Decoration;
+use vortex_core::layer_cleanup::{self, DedupOptions, DedupResult, PruneOptions, PruneResult};
+use vortex_core::layer_query::{self, LayerMatches};
+use vortex_core::layer_transform::{self, StyleTransform, TransformResult};
+use vortex_core::layers::{self, LayerAlignment, LockViolation};
+use vortex_core::notes::{self, NoteEdit, NoteEditResult, NoteOptions, NumberedNote};
+use vortex_core::typography::{self, TypographyOptions, TypographyResult};
 
 /// Update a layer's properties
 /// 
 /// In the current architecture, layer state is managed in the frontend.
-/// This command validates updates and returns the updated layer.
-/// Can be extended to persist changes to a backend store.
+/// This command validates updates against the current `page` and returns the
+/// updated layer. A locked layer refuses every update except one that only
+/// unlocks it, unless `force` is set.
 #[tauri::command]
 pub fn update_layer(
-    _page_index: usize,
+    page_index: usize,
     layer_id: String,
     updates: LayerUpdates,
-) -> Result<LayerObject, String> {
-    // Create a placeholder layer with the updates applied
+    page: PageData,
+    force: Option<bool>,
+) -> Result<LayerObject, LockViolation> {
+    if let Some(layer) = layers::update_page_layer(page_index, &page.layers, &layer_id, &updates, force.unwrap_or(false))? {
+        return Ok(layer);
+    }
+
+    // A layer the page doesn't have yet gets a placeholder with the updates applied
     // The frontend maintains the actual state; this validates the update
     let mut layer = LayerObject {
         id: layer_id,
@@ -27,6 +47,7 @@ pub fn update_layer(
         locked: updates.locked.unwrap_or(false),
         z_index: updates.z_index.unwrap_or(0),
         opacity: updates.opacity.unwrap_or(1.0).clamp(0.0, 1.0),
+        blend_mode: updates.blend_mode.filter(|m| *m != crate::models::BlendMode::Normal),
         content: updates.content.clone(),
         font_family: updates.font_family.clone(),
         font_size: updates.font_size.map(|s| s.max(1.0)),
@@ -39,17 +60,27 @@ pub fn update_layer(
         line_height: updates.line_height,
         letter_spacing: updates.letter_spacing,
         background_color: updates.background_color.clone(),
+        text_path: None,
+        text_outline: None,
+        text_shadow: None,
+        drop_cap: updates.drop_cap.clone().filter(|c| c.lines >= 2),
+        notes: Vec::new(),
+        pagination: updates.pagination,
         image_url: None,
         image_path: None,
         image_data: None,
+        image_link: None,
         shape_type: None,
         stroke_color: None,
         stroke_width: None,
         fill_color: None,
+        text_wrap: updates.text_wrap.clone().filter(|w| w.contour != crate::models::WrapContour::None),
         path_data: None,
         transform: None,
         source_type: crate::models::SourceType::Manual,
         role: updates.role.clone().unwrap_or(crate::models::LayerRole::Content),
+        ocr: None,
+        tags: Vec::new(),
     };
     
     LayerProcessor::apply_updates(&mut layer, &updates);
@@ -59,23 +90,127 @@ pub fn update_layer(
 /// Delete a layer from a page
 /// 
 /// In the current architecture, layer deletion is handled in the frontend.
-/// This command acknowledges the deletion request.
+/// This command acknowledges the deletion request; it is refused for a
+/// locked layer of the current `page` unless `force` is set.
 #[tauri::command]
-pub fn delete_layer(_page_index: usize, _layer_id: String) -> Result<(), String> {
-    // Layer deletion is handled by the frontend store
-    // This command can be extended to persist deletions
-    Ok(())
+pub fn delete_layer(
+    page_index: usize,
+    layer_id: String,
+    page: PageData,
+    force: Option<bool>,
+) -> Result<(), LockViolation> {
+    let current = page.layers.iter().find(|l| l.id == layer_id);
+    match current {
+        Some(layer) if !force.unwrap_or(false) => layers::check_delete(page_index, layer),
+        _ => Ok(()),
+    }
 }
 
 /// Reorder layers on a page
 /// 
 /// In the current architecture, layer ordering is handled in the frontend.
-/// This command acknowledges the reorder request.
+/// This command acknowledges the reorder request (`layer_ids` bottom first);
+/// it is refused when a locked layer of the current `page` would move,
+/// unless `force` is set.
+#[tauri::command]
+pub fn reorder_layers(
+    page_index: usize,
+    layer_ids: Vec<String>,
+    page: PageData,
+    force: Option<bool>,
+) -> Result<(), LockViolation> {
+    if force.unwrap_or(false) {
+        return Ok(());
+    }
+    layers::check_reorder(page_index, &page.layers, &layer_ids)
+}
+
+/// Remove layers painted more than once, such as fake-bold text
+///
+/// Returns the cleaned pages with a group per kept layer listing the copies
+/// that were removed.
+#[tauri::command]
+pub fn deduplicate_layers(pages: Vec<PageData>, options: Option<DedupOptions>) -> Result<DedupResult, String> {
+    Ok(layer_cleanup::deduplicate_pages(pages, &options.unwrap_or_default()))
+}
+
+/// Remove layers that paint nothing: zero-area, invisible, blank text or
+/// off the page, as configured
+///
+/// Returns the cleaned pages with removal counts for each page that changed.
+#[tauri::command]
+pub fn prune_layers(pages: Vec<PageData>, options: Option<PruneOptions>) -> Result<PruneResult, String> {
+    Ok(layer_cleanup::prune_pages(pages, &options.unwrap_or_default()))
+}
+
+/// Smart quotes, em dashes, ellipses and French no-break spaces in the
+/// unlocked text layers, for the options' locale or each page's language
+///
+/// Returns the cleaned pages with the ids of the layers that changed.
 #[tauri::command]
-pub fn reorder_layers(_page_index: usize, _layer_ids: Vec<String>) -> Result<(), String> {
-    // Layer reordering is handled by the frontend store
-    // This command can be extended to persist the new order
-    Ok(())
+pub fn clean_typography(pages: Vec<PageData>, options: Option<TypographyOptions>) -> Result<TypographyResult, String> {
+    Ok(typography::clean_pages(pages, &options.unwrap_or_default()))
+}
+
+/// Every footnote and endnote with the number it prints with, in reading order
+#[tauri::command]
+pub fn list_notes(pages: Vec<PageData>, options: Option<NoteOptions>) -> Result<Vec<NumberedNote>, String> {
+    Ok(notes::number_notes(&pages, options.unwrap_or_default().footnote_numbering))
+}
+
+/// Insert, change or remove a note, returning the pages with the notes
+/// renumbered
+#[tauri::command]
+pub fn edit_note(mut pages: Vec<PageData>, edit: NoteEdit, options: Option<NoteOptions>) -> Result<NoteEditResult, String> {
+    let note_id = notes::edit_note(&mut pages, edit)?;
+    let notes = notes::number_notes(&pages, options.unwrap_or_default().footnote_numbering);
+    Ok(NoteEditResult { pages, note_id, notes })
+}
+
+/// Pages with their notes set as layers (marks, footnote areas and endnote
+/// pages), as PDF and EPUB export prints them, for previewing
+#[tauri::command]
+pub fn resolve_notes(pages: Vec<PageData>, options: Option<NoteOptions>) -> Result<Vec<PageData>, String> {
+    Ok(notes::resolve_notes(&pages, &options.unwrap_or_default()))
+}
+
+/// Ids of the layers matching a query such as `type:text size:..8
+/// -role:header`, per page (see `vortex_core::layer_query` for the syntax)
+#[tauri::command]
+pub fn query_layers(pages: Vec<PageData>, query: String) -> Result<Vec<LayerMatches>, String> {
+    layer_query::query_layers(&pages, &query)
+}
+
+/// Apply `transform` to every unlocked layer matching `query`, e.g. a font
+/// swap, a size scale and a shift, returning the pages and a per-layer
+/// report; with `dry_run` only the report
+#[tauri::command]
+pub fn transform_layers(
+    pages: Vec<PageData>,
+    query: String,
+    transform: StyleTransform,
+    dry_run: Option<bool>,
+) -> Result<TransformResult, String> {
+    layer_transform::transform_pages(pages, &query, &transform, dry_run.unwrap_or(false))
+}
+
+/// Vector layer for a rule, ornament, frame or badge on page `page_index`,
+/// its top-left corner at (`x`, `y`) and filled with `color` (black by default)
+#[tauri::command]
+pub fn create_decoration(
+    page_index: usize,
+    decoration: Decoration,
+    x: f32,
+    y: f32,
+    color: Option<String>,
+) -> Result<LayerObject, String> {
+    static CREATED: AtomicU32 = AtomicU32::new(0);
+    let stamp = std::time::SystemTime::now()
+        .duration_since(std::time::UNIX_EPOCH)
+        .map(|d| d.as_millis())
+        .unwrap_or_default();
+    let id = format!("decoration-{}-{}-{}", page_index, stamp, CREATED.fetch_add(1, Ordering::Relaxed));
+    decoration.layer(id, x, y, color.as_deref())
 }
 
 /// Layer processor for z-index and layer management operations
@@ -87,146 +222,45 @@ impl LayerProcessor {
     }
 
     /// Bring a layer to the front (highest z-index)
+    #[inline]
     pub fn bring_to_front(page: &mut PageData, layer_id: &str) -> Result<(), String> {
-        let max_z = page.layers.iter().map(|l| l.z_index).max().unwrap_or(0);
-
-        if let Some(layer) = page.layers.iter_mut().find(|l| l.id == layer_id) {
-            layer.z_index = max_z + 1;
-            Ok(())
-        } else {
-            Err(format!("Layer not found: {}", layer_id))
-        }
+        layers::bring_to_front(&mut page.layers, layer_id)
     }
 
     /// Send a layer to the back (lowest z-index)
+    #[inline]
     pub fn send_to_back(page: &mut PageData, layer_id: &str) -> Result<(), String> {
-        let min_z = page.layers.iter().map(|l| l.z_index).min().unwrap_or(0);
-
-        if let Some(layer) = page.layers.iter_mut().find(|l| l.id == layer_id) {
-            layer.z_index = min_z - 1;
-            Ok(())
-        } else {
-            Err(format!("Layer not found: {}", layer_id))
-        }
+        layers::send_to_back(&mut page.layers, layer_id)
     }
 
     /// Move a layer up one position in z-order
+    #[inline]
     pub fn move_up(page: &mut PageData, layer_id: &str) -> Result<(), String> {
-        // Find the layer and its current z-index
-        let current_z = page
-            .layers
-            .iter()
-            .find(|l| l.id == layer_id)
-            .map(|l| l.z_index)
-            .ok_or_else(|| format!("Layer not found: {}", layer_id))?;
-
-        // Find the next higher z-index
-        let next_z = page
-            .layers
-            .iter()
-            .filter(|l| l.z_index > current_z)
-            .map(|l| l.z_index)
-            .min();
-
-        if let Some(swap_z) = next_z {
-            // Swap z-indices
-            for layer in &mut page.layers {
-                if layer.id == layer_id {
-                    layer.z_index = swap_z;
-                } else if layer.z_index == swap_z {
-                    layer.z_index = current_z;
-                }
-            }
-        }
-        // If no higher layer exists, do nothing (already at top)
-
-        Ok(())
+        layers::move_up(&mut page.layers, layer_id)
     }
 
     /// Move a layer down one position in z-order
+    #[inline]
     pub fn move_down(page: &mut PageData, layer_id: &str) -> Result<(), String> {
-        // Find the layer and its current z-index
-        let current_z = page
-            .layers
-            .iter()
-            .find(|l| l.id == layer_id)
-            .map(|l| l.z_index)
-            .ok_or_else(|| format!("Layer not found: {}", layer_id))?;
-
-        // Find the next lower z-index
-        let prev_z = page
-            .layers
-            .iter()
-            .filter(|l| l.z_index < current_z)
-            .map(|l| l.z_index)
-            .max();
-
-        if let Some(swap_z) = prev_z {
-            // Swap z-indices
-            for layer in &mut page.layers {
-                if layer.id == layer_id {
-                    layer.z_index = swap_z;
-                } else if layer.z_index == swap_z {
-                    layer.z_index = current_z;
-                }
-            }
-        }
-        // If no lower layer exists, do nothing (already at bottom)
-
-        Ok(())
+        layers::move_down(&mut page.layers, layer_id)
     }
 
     /// Apply layer updates to a layer object
+    #[inline]
     pub fn apply_updates(layer: &mut LayerObject, updates: &LayerUpdates) {
-        if let Some(ref bounds) = updates.bounds {
-            layer.bounds = bounds.clone();
-        }
-        if let Some(visible) = updates.visible {
-            layer.visible = visible;
-        }
-        if let Some(locked) = updates.locked {
-            layer.locked = locked;
-        }
-        if let Some(z_index) = updates.z_index {
-            layer.z_index = z_index;
-        }
-        if let Some(opacity) = updates.opacity {
-            // Clamp opacity to valid range
-            layer.opacity = opacity.clamp(0.0, 1.0);
-        }
-        if let Some(ref content) = updates.content {
-            layer.content = Some(content.clone());
-        }
-        if let Some(ref font_family) = updates.font_family {
-            layer.font_family = Some(font_family.clone());
-        }
-        if let Some(font_size) = updates.font_size {
-            // Ensure font size is positive
-            layer.font_size = Some(font_size.max(1.0));
-        }
-        if let Some(font_weight) = updates.font_weight {
-            layer.font_weight = Some(font_weight);
-        }
-        if let Some(ref color) = updates.color {
-            layer.color = Some(color.clone());
-        }
-        if let Some(ref text_align) = updates.text_align {
-            layer.text_align = Some(text_align.clone());
-        }
-        if let Some(ref role) = updates.role {
-            layer.role = role.clone();
-        }
+        layers::apply_updates(layer, updates)
     }
 
     /// Normalize z-indices to be sequential starting from 0
+    #[inline]
     pub fn normalize_z_indices(page: &mut PageData) {
-        // Sort layers by current z-index
-        page.layers.sort_by_key(|l| l.z_index);
+        layers::normalize_z_indices(&mut page.layers)
+    }
 
-        // Reassign sequential z-indices
-        for (i, layer) in page.layers.iter_mut().enumerate() {
-            layer.z_index = i as i32;
-        }
+    /// Align layers to the edge or center of their combined bounds
+    #[inline]
+    pub fn align(page: &mut PageData, layer_ids: &[String], alignment: LayerAlignment) -> Result<usize, String> {
+        layers::align_layers(&mut page.layers, layer_ids, alignment)
     }
 }
 
@@ -250,6 +284,7 @@ mod tests {
             locked: false,
             z_index,
             opacity: 1.0,
+            blend_mode: None,
             content: None,
             font_family: None,
             font_size: None,
@@ -262,17 +297,27 @@ mod tests {
             line_height: None,
             letter_spacing: None,
             background_color: None,
+            text_path: None,
+            text_outline: None,
+            text_shadow: None,
+            drop_cap: None,
+            notes: Vec::new(),
+            pagination: None,
             image_url: None,
             image_path: None,
             image_data: None,
+            image_link: None,
             shape_type: None,
             stroke_color: None,
             stroke_width: None,
             fill_color: None,
+            text_wrap: None,
             path_data: None,
             transform: None,
             source_type: SourceType::Manual,
             role: LayerRole::Content,
+            ocr: None,
+            tags: Vec::new(),
         }
     }
 
@@ -288,6 +333,7 @@ mod tests {
                 create_test_layer("layer-3", 3),
             ],
             metadata: None,
+            background: None,
         }
     }
 
@@ -344,6 +390,7 @@ mod tests {
                 create_test_layer("layer-c", 100),
             ],
             metadata: None,
+            background: None,
         };
 
         LayerProcessor::normalize_z_indices(&mut page);
@@ -385,6 +432,25 @@ mod tests {
         assert_eq!(layer.font_size, Some(1.0)); // Should be clamped to 1.0
     }
 
+    #[test]
+    fn test_locked_layer_commands() {
+        let mut page = create_test_page();
+        page.layers[1].locked = true;
+        let content = LayerUpdates { content: Some("Edited".to_string()), ..Default::default() };
+
+        let refused = update_layer(0, "layer-2".to_string(), content.clone(), page.clone(), None).unwrap_err();
+        assert_eq!(refused.layer_ids, vec!["layer-2".to_string()]);
+        let forced = update_layer(0, "layer-2".to_string(), content, page.clone(), Some(true)).unwrap();
+        assert_eq!(forced.content.as_deref(), Some("Edited"));
+        assert!(forced.locked);
+
+        assert!(delete_layer(0, "layer-2".to_string(), page.clone(), None).is_err());
+        assert!(delete_layer(0, "layer-1".to_string(), page.clone(), None).is_ok());
+        let order = vec!["layer-2".to_string(), "layer-1".to_string(), "layer-3".to_string()];
+        assert!(reorder_layers(0, order.clone(), page.clone(), None).is_err());
+        assert!(reorder_layers(0, order, page, Some(true)).is_ok());
+    }
+
     #[test]
     fn test_layer_not_found() {
         let mut page = create_test_page();
@@ -392,28 +458,3 @@ mod tests {
         assert!(result.is_err());
     }
 }
-
-impl Default for LayerUpdates {
-    fn default() -> Self {
-        Self {
-            bounds: None,
-            visible: None,
-            locked: None,
-            z_index: None,
-            opacity: None,
-            content: None,
-            font_family: None,
-            font_size: None,
-            font_weight: None,
-            font_style: None,
-            color: None,
-            text_align: None,
-            text_decoration: None,
-            text_transform: None,
-            line_height: None,
-            letter_spacing: None,
-            background_color: None,
-            role: None,
-        }
-    }
-}
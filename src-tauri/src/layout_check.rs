@@ -0,0 +1,297 @@
+//! Layout Check Module
+//!
+//! Finds layout problems to fix before export: layers partly or entirely
+//! outside the page (past the bleed, when there is one), text over an image
+//! it does not stand out from, and text layers overlapping each other. Every
+//! warning names the layers involved so the editor can select them.
+
+use crate::export_handler::parse_hex_color;
+use crate::image_handler;
+use crate::models::{Bounds, LayerObject, LayerType, PageData};
+use image::RgbaImage;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+
+/// Image pixels sampled along each side of the area under a text layer
+const CONTRAST_SAMPLES: u32 = 32;
+
+/// Settings for the layout check
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct LayoutCheckOptions {
+    /// Bleed past the trim edge that layers may extend into, in points
+    #[serde(default)]
+    pub bleed: f32,
+    /// Lowest acceptable contrast ratio between text and the image behind it
+    #[serde(default = "default_min_contrast")]
+    pub min_contrast: f32,
+    /// Fraction of the smaller text layer that must be covered to count as overlapping
+    #[serde(default = "default_min_text_overlap")]
+    pub min_text_overlap: f32,
+}
+
+/// WCAG AA for large text
+fn default_min_contrast() -> f32 {
+    3.0
+}
+
+fn default_min_text_overlap() -> f32 {
+    0.2
+}
+
+impl Default for LayoutCheckOptions {
+    fn default() -> Self {
+        Self { bleed: 0.0, min_contrast: default_min_contrast(), min_text_overlap: default_min_text_overlap() }
+    }
+}
+
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+#[repr(u8)]
+pub enum LayoutIssueKind {
+    /// Nothing of the layer is on the page
+    OffPage = 0,
+    /// Part of the layer is cut off at the trim (or bleed) edge
+    PartlyOffPage = 1,
+    /// Text is hard to read against the image behind it
+    LowContrast = 2,
+    TextOverlap = 3,
+}
+
+/// A single layout finding
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct LayoutWarning {
+    pub kind: LayoutIssueKind,
+    pub page_index: usize,
+    /// The offending layer first, then any layer it clashes with
+    pub layer_ids: Vec<String>,
+    pub message: String,
+}
+
+/// Shared area of two bounds, if any
+fn overlap(a: &Bounds, b: &Bounds) -> Option<Bounds> {
+    let x = a.x.max(b.x);
+    let y = a.y.max(b.y);
+    let right = (a.x + a.width).min(b.x + b.width);
+    let bottom = (a.y + a.height).min(b.y + b.height);
+    (right > x && bottom > y).then(|| Bounds::new(x, y, right - x, bottom - y))
+}
+
+fn area(b: &Bounds) -> f32 {
+    b.width.max(0.0) * b.height.max(0.0)
+}
+
+/// Relative luminance of an sRGB color (0-255 channels)
+fn luminance(r: f32, g: f32, b: f32) -> f32 {
+    let linear = |c: f32| {
+        let c = c / 255.0;
+        if c <= 0.03928 {
+            c / 12.92
+        } else {
+            ((c + 0.055) / 1.055).powf(2.4)
+        }
+    };
+    0.2126 * linear(r) + 0.7152 * linear(g) + 0.0722 * linear(b)
+}
+
+fn contrast_ratio(a: f32, b: f32) -> f32 {
+    (a.max(b) + 0.05) / (a.min(b) + 0.05)
+}
+
+/// Average luminance of the part of `image` (placed at `placed`) under `area`
+fn luminance_under(image: &RgbaImage, placed: &Bounds, area: &Bounds) -> Option<f32> {
+    if placed.width <= 0.0 || placed.height <= 0.0 || image.width() == 0 || image.height() == 0 {
+        return None;
+    }
+    let to_px = |v: f32, origin: f32, size: f32, pixels: u32| {
+        (((v - origin) / size) * pixels as f32).clamp(0.0, pixels as f32 - 1.0)
+    };
+    let (x0, x1) = (
+        to_px(area.x, placed.x, placed.width, image.width()),
+        to_px(area.x + area.width, placed.x, placed.width, image.width()),
+    );
+    let (y0, y1) = (
+        to_px(area.y, placed.y, placed.height, image.height()),
+        to_px(area.y + area.height, placed.y, placed.height, image.height()),
+    );
+
+    let mut total = 0.0;
+    let mut weight = 0.0;
+    for i in 0..CONTRAST_SAMPLES {
+        for j in 0..CONTRAST_SAMPLES {
+            let t = |k: u32| (k as f32 + 0.5) / CONTRAST_SAMPLES as f32;
+            let px = image.get_pixel((x0 + (x1 - x0) * t(i)) as u32, (y0 + (y1 - y0) * t(j)) as u32);
+            let alpha = px[3] as f32 / 255.0;
+            total += luminance(px[0] as f32, px[1] as f32, px[2] as f32) * alpha;
+            weight += alpha;
+        }
+    }
+    (weight > 0.0).then(|| total / weight)
+}
+
+fn check_bounds(page: &PageData, layer: &LayerObject, bleed: f32, warnings: &mut Vec<LayoutWarning>) {
+    let sheet = Bounds::new(-bleed, -bleed, page.width + 2.0 * bleed, page.height + 2.0 * bleed);
+    let edge = if bleed > 0.0 { "bleed" } else { "page" };
+    let (kind, message) = match overlap(&layer.bounds, &sheet) {
+        None => (LayoutIssueKind::OffPage, format!("Layer is entirely outside the {}", edge)),
+        Some(visible) if area(&visible) + 0.01 < area(&layer.bounds) => {
+            let cut = 1.0 - area(&visible) / area(&layer.bounds);
+            (LayoutIssueKind::PartlyOffPage, format!("{:.0}% of the layer is past the {} edge", cut * 100.0, edge))
+        }
+        Some(_) => return,
+    };
+    warnings.push(LayoutWarning { kind, page_index: page.page_index, layer_ids: vec![layer.id.clone()], message });
+}
+
+/// Check one page; decoded images are kept in `images` across pages
+fn check_page(
+    page: &PageData,
+    options: &LayoutCheckOptions,
+    images: &mut HashMap<String, Option<RgbaImage>>,
+) -> Vec<LayoutWarning> {
+    let mut warnings = Vec::new();
+    let visible: Vec<&LayerObject> = page.layers.iter().filter(|l| l.visible && l.opacity > 0.0).collect();
+
+    for layer in &visible {
+        check_bounds(page, layer, options.bleed, &mut warnings);
+    }
+
+    let texts: Vec<&LayerObject> = visible
+        .iter()
+        .copied()
+        .filter(|l| l.layer_type == LayerType::Text && l.content.as_deref().is_some_and(|c| !c.trim().is_empty()))
+        .collect();
+
+    for text in &texts {
+        let (r, g, b) = text.color.as_deref().and_then(parse_hex_color).unwrap_or((0, 0, 0));
+        let text_luminance = luminance(r as f32, g as f32, b as f32);
+        // The topmost image under the text is the one it is read against
+        let behind = visible
+            .iter()
+            .filter(|l| l.layer_type == LayerType::Image && l.z_index < text.z_index)
+            .filter_map(|l| overlap(&text.bounds, &l.bounds).map(|shared| (*l, shared)))
+            .max_by_key(|(l, _)| l.z_index);
+        let Some((image_layer, shared)) = behind else {
+            continue;
+        };
+        let decoded = images.entry(image_layer.id.clone()).or_insert_with(|| {
+            image_handler::layer_image_bytes(image_layer)
+                .and_then(|bytes| image::load_from_memory(&bytes).ok())
+                .map(|img| img.to_rgba8())
+        });
+        let Some(background) = decoded.as_ref().and_then(|img| luminance_under(img, &image_layer.bounds, &shared))
+        else {
+            continue;
+        };
+        let ratio = contrast_ratio(text_luminance, background);
+        if ratio < options.min_contrast {
+            warnings.push(LayoutWarning {
+                kind: LayoutIssueKind::LowContrast,
+                page_index: page.page_index,
+                layer_ids: vec![text.id.clone(), image_layer.id.clone()],
+                message: format!(
+                    "Text contrast against the image is {:.1}:1, needs {:.1}:1",
+                    ratio, options.min_contrast
+                ),
+            });
+        }
+    }
+
+    for (i, a) in texts.iter().enumerate() {
+        for b in &texts[i + 1..] {
+            let Some(shared) = overlap(&a.bounds, &b.bounds) else {
+                continue;
+            };
+            let smaller = area(&a.bounds).min(area(&b.bounds));
+            if smaller > 0.0 && area(&shared) / smaller >= options.min_text_overlap {
+                warnings.push(LayoutWarning {
+                    kind: LayoutIssueKind::TextOverlap,
+                    page_index: page.page_index,
+                    layer_ids: vec![a.id.clone(), b.id.clone()],
+                    message: format!("Text layers overlap by {:.0}%", area(&shared) / smaller * 100.0),
+                });
+            }
+        }
+    }
+
+    warnings
+}
+
+/// Run every layout check over `pages`
+pub fn check_pages(pages: &[PageData], options: &LayoutCheckOptions) -> Vec<LayoutWarning> {
+    let mut images = HashMap::new();
+    pages.iter().flat_map(|page| check_page(page, options, &mut images)).collect()
+}
+
+/// Find off-page layers, low-contrast text over images and overlapping text
+#[tauri::command]
+pub async fn check_layout(
+    pages: Vec<PageData>,
+    options: Option<LayoutCheckOptions>,
+) -> Result<Vec<LayoutWarning>, String> {
+    tokio::task::spawn_blocking(move || check_pages(&pages, &options.unwrap_or_default()))
+        .await
+        .map_err(|e| format!("Layout check failed: {}", e))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use image::{ImageFormat, Rgba};
+    use vortex_core::test_util::{self, layer};
+
+    fn page(layers: Vec<LayerObject>) -> PageData {
+        test_util::page(0, layers)
+    }
+
+    fn text(id: &str, x: f32, y: f32, color: &str) -> LayerObject {
+        layer(id, "text")
+            .bounds(x, y, 200.0, 20.0)
+            .z(2)
+            .fields(serde_json::json!({ "content": "Caption", "color": color }))
+            .build()
+    }
+
+    fn kinds(warnings: &[LayoutWarning]) -> Vec<(LayoutIssueKind, Vec<&str>)> {
+        warnings.iter().map(|w| (w.kind, w.layer_ids.iter().map(String::as_str).collect())).collect()
+    }
+
+    #[test]
+    fn test_bounds_and_overlap() {
+        let pages = vec![page(vec![
+            text("a", 72.0, 72.0, "#000000"),
+            text("b", 80.0, 75.0, "#000000"),
+            text("edge", 500.0, 300.0, "#000000"),
+            text("gone", 700.0, 300.0, "#000000"),
+        ])];
+        let warnings = check_pages(&pages, &LayoutCheckOptions::default());
+        assert_eq!(
+            kinds(&warnings),
+            vec![
+                (LayoutIssueKind::PartlyOffPage, vec!["edge"]),
+                (LayoutIssueKind::OffPage, vec!["gone"]),
+                (LayoutIssueKind::TextOverlap, vec!["a", "b"]),
+            ]
+        );
+
+        // A bleed lets the edge layer run past the trim
+        let bleed = LayoutCheckOptions { bleed: 100.0, ..Default::default() };
+        let warnings = check_pages(&pages, &bleed);
+        assert!(!warnings.iter().any(|w| w.layer_ids == ["edge"]));
+    }
+
+    #[test]
+    fn test_low_contrast_text_over_image() {
+        let mut png = Vec::new();
+        RgbaImage::from_pixel(8, 8, Rgba([250, 250, 250, 255]))
+            .write_to(&mut std::io::Cursor::new(&mut png), ImageFormat::Png)
+            .unwrap();
+        image_handler::cache_image("layout-check-white", png);
+
+        let photo = layer("photo", "image").bounds(0.0, 0.0, 612.0, 400.0).z(1).with("imageUrl", "image://layout-check-white");
+        let pages = vec![page(vec![photo.build(), text("light", 72.0, 72.0, "#FFFF00"), text("dark", 72.0, 200.0, "#000000")])];
+        let warnings = check_pages(&pages, &LayoutCheckOptions::default());
+        assert_eq!(kinds(&warnings), vec![(LayoutIssueKind::LowContrast, vec!["light", "photo"])]);
+    }
+}
@@ -3,28 +3,62 @@
 //! This module provides the core backend functionality for the Book Creation Converter
 //! application, including document parsing, layer processing, image handling, and export.
 
-pub mod content_parser;
+#[cfg(feature = "api-server")]
+pub mod api_server;
+pub mod asset_manager;
+pub mod change_tracker;
+pub mod clipboard;
+pub mod color_conversion;
+pub mod cloud_import;
+pub mod color_profile;
+pub mod contact_sheet;
+pub mod cover;
+pub mod diagnostics;
+pub mod document_diff;
 pub mod document_parser;
 pub mod export_handler;
+pub mod export_preflight;
+pub mod export_presets;
 pub mod font_handler;
 pub mod font_manager;
 pub mod font_service;
-pub mod graphics_state;
 pub mod image_handler;
+pub mod image_sequence;
+pub mod import_mappings;
+pub mod ipc;
+pub mod ink_coverage;
+pub mod job_manager;
 pub mod layer_processor;
+pub mod layout_check;
+pub mod linked_images;
 pub mod live_sync;
-pub mod models;
+pub mod page_setup;
 pub mod ocr_handler;
-pub mod path_ops;
 pub mod pdf_analyzer;
+pub mod pdf_engine;
+pub mod pdf_images;
 pub mod pdf_reconstructor;
+pub mod pdf_repair;
+pub mod pdf_security;
+pub mod pdf_tools;
+pub mod photo_correction;
 pub mod print_service;
-pub mod text_ops;
+pub mod project_package;
+pub mod recent_projects;
+pub mod scanner;
+pub mod script_engine;
+pub mod settings;
+pub mod snapshot;
+pub mod source_watch;
+pub mod text_extraction;
+pub mod tts;
+
+// Shared with the wasm build
+pub use vortex_core::{content_parser, graphics_state, models, path_ops, text_ops, units};
 
 use tauri::http::{Request, Response};
 use tauri::UriSchemeContext;
-#[cfg(debug_assertions)]
-use tauri::Manager;
+use tauri::{Emitter, Manager};
 
 /// Clear the image cache (called when closing documents)
 #[tauri::command]
@@ -41,10 +75,10 @@ fn handle_image_protocol<R: tauri::Runtime>(
     // Path format: /image-id
     let image_id = path.trim_start_matches('/');
     
-    match image_handler::get_image_bytes(image_id) {
+    match image_handler::get_image_bytes(image_id).map(|data| color_profile::preview_bytes(image_id, data)) {
         Some(data) => Response::builder()
             .status(200)
-            .header("Content-Type", "image/png")
+            .header("Content-Type", image_handler::mime_type(&data))
             .header("Access-Control-Allow-Origin", "*")
             .body(data)
             .unwrap(),
@@ -68,6 +102,44 @@ pub fn run() {
                 let window = app.get_webview_window("main").unwrap();
                 window.open_devtools();
             }
+            // User preferences, read by the modules below
+            if let Ok(dir) = app.path().app_config_dir() {
+                let _ = settings::init_settings(dir);
+            }
+            // Pdfium search paths (bundled resources, saved library path)
+            let _ = pdf_engine::init_pdf_engine(
+                app.path().resource_dir().ok(),
+                app.path().app_data_dir().ok(),
+            );
+            // Repaired copies of damaged PDFs
+            if let Ok(dir) = app.path().app_cache_dir() {
+                let _ = pdf_repair::init_pdf_repair(dir.join("repaired"));
+            }
+            if let Ok(dir) = app.path().app_data_dir() {
+                // Rotating local log files
+                let _ = diagnostics::init_logging(dir.join("logs"));
+                // Restore persisted font matches and user overrides
+                let _ = font_manager::match_cache::init(dir.clone());
+                // Live sync session logs
+                let _ = live_sync::init_sync_logs(dir.join("sync_sessions"));
+                // User export presets
+                let _ = export_presets::init_export_presets(dir.clone());
+                // Saved import mappings
+                let _ = import_mappings::init_import_mappings(dir.clone());
+                // Project version history
+                let _ = snapshot::init_snapshots(dir.join("snapshots"));
+                // Saved bulk-edit scripts
+                let _ = script_engine::init_scripts(dir.clone());
+                // Cloud storage sign-ins and downloads
+                let _ = cloud_import::init_cloud_import(dir.join("cloud"));
+                // Start screen's recent projects
+                let _ = recent_projects::init_recent_projects(dir);
+            }
+            // Background job updates for the frontend
+            let handle = app.handle().clone();
+            job_manager::jobs().set_listener(Box::new(move |job| {
+                let _ = handle.emit("job_updated", job);
+            }));
             // Start font watcher for async updates
             let handle = app.handle().clone();
             tauri::async_runtime::spawn(async move {
@@ -77,20 +149,145 @@ pub fn run() {
         })
         .invoke_handler(tauri::generate_handler![
             document_parser::import_document,
+            // Cloud import commands
+            cloud_import::cloud_begin_auth,
+            cloud_import::cloud_poll_auth,
+            cloud_import::cloud_sign_out,
+            cloud_import::list_cloud_accounts,
+            cloud_import::list_cloud_documents,
+            cloud_import::import_cloud_document,
             layer_processor::update_layer,
             layer_processor::delete_layer,
             layer_processor::reorder_layers,
+            layer_processor::deduplicate_layers,
+            layer_processor::prune_layers,
+            layer_processor::query_layers,
+            layer_processor::transform_layers,
+            layer_processor::clean_typography,
+            layer_processor::list_notes,
+            layer_processor::edit_note,
+            layer_processor::resolve_notes,
+            layer_processor::create_decoration,
+            // Clipboard interchange
+            clipboard::copy_layers,
+            clipboard::paste_layers,
+            // Track changes commands
+            change_tracker::record_layer_change,
+            change_tracker::accept_change,
+            change_tracker::reject_change,
+            change_tracker::accept_all_changes,
+            change_tracker::reject_all_changes,
+            // Document comparison commands
+            document_diff::compare_documents,
+            document_diff::compare_projects,
+            // Source file watch commands
+            source_watch::watch_source_file,
+            source_watch::unwatch_source_file,
+            source_watch::reimport_and_merge,
+            source_watch::reimport_page,
+            // Script commands
+            script_engine::run_script,
+            script_engine::list_scripts,
+            script_engine::save_script,
+            script_engine::delete_script,
+            // Snapshot commands
+            snapshot::create_snapshot,
+            snapshot::list_snapshots,
+            snapshot::restore_snapshot,
             export_handler::export_document,
             export_handler::load_project,
+            export_handler::load_project_streamed,
+            export_handler::convert_project,
             export_handler::save_project,
+            project_package::package_project,
+            export_presets::list_export_presets,
+            export_presets::save_export_preset,
+            export_presets::delete_export_preset,
+            export_presets::export_with_preset,
+            export_presets::get_default_export_options,
+            export_presets::export_pipeline,
+            import_mappings::list_import_mappings,
+            import_mappings::save_import_mapping,
+            import_mappings::delete_import_mapping,
+            import_mappings::record_import_mapping,
+            import_mappings::apply_import_mapping,
+            page_setup::list_page_size_presets,
+            page_setup::resize_document,
+            page_setup::reflow_pages,
+            page_setup::detect_layout_guides,
+            page_setup::snap_to_guides,
+            page_setup::apply_page_layout,
+            page_setup::list_sections,
+            page_setup::set_section,
+            page_setup::remove_section,
+            text_extraction::extract_structured_text,
+            text_extraction::extract_plain_text,
+            text_extraction::extract_structure,
+            text_extraction::get_page_text,
+            scanner::list_scanners,
+            scanner::scan_pages,
+            photo_correction::detect_photo_corners,
+            photo_correction::correct_page_photo,
+            pdf_security::encrypt_pdf,
+            pdf_security::sign_pdf,
+            pdf_tools::merge_overlay,
+            pdf_tools::merge_pdfs,
+            pdf_tools::split_pdf,
+            pdf_tools::rotate_pdf_pages,
+            pdf_tools::delete_pdf_pages,
+            pdf_repair::validate_pdf,
+            pdf_repair::repair_pdf,
+            export_preflight::preflight_export,
+            export_preflight::check_image_resolution,
+            layout_check::check_layout,
+            ink_coverage::analyze_ink_coverage,
+            color_conversion::convert_document_colors,
             image_handler::get_image,
             image_handler::export_layer_image,
+            image_handler::place_image,
+            image_handler::trace_image,
+            image_handler::auto_crop_image,
+            linked_images::place_linked_image,
+            linked_images::refresh_linked_images,
+            asset_manager::list_assets,
+            asset_manager::find_unused_assets,
+            asset_manager::replace_asset,
+            asset_manager::relink_missing_assets,
             clear_image_cache,
+            // API server commands
+            #[cfg(feature = "api-server")]
+            api_server::start_api_server,
+            #[cfg(feature = "api-server")]
+            api_server::stop_api_server,
+            #[cfg(feature = "api-server")]
+            api_server::get_api_server_info,
+            // Background job commands
+            job_manager::list_jobs,
+            job_manager::cancel_job,
+            // Diagnostics commands
+            diagnostics::get_last_error,
+            diagnostics::create_diagnostics_bundle,
             // PDF analyzer commands
             pdf_analyzer::analyze_pdf_content,
+            pdf_analyzer::preflight_document,
+            // PDF engine commands
+            pdf_engine::get_pdf_engine_status,
+            pdf_engine::set_pdfium_library_path,
+            // App settings
+            settings::get_settings,
+            settings::update_settings,
+            // Recent projects
+            recent_projects::get_recent_projects,
+            recent_projects::pin_project,
+            recent_projects::remove_recent,
             // PDF reconstruction commands
             pdf_reconstructor::reconstruct_pdf_with_ocr,
             pdf_reconstructor::needs_ocr_reconstruction,
+            pdf_reconstructor::find_page_ocr_regions,
+            pdf_reconstructor::ocr_page_regions,
+            pdf_reconstructor::get_ocr_review_queue,
+            pdf_reconstructor::review_ocr_layer,
+            pdf_reconstructor::export_pdf_in_place,
             // Font service commands (legacy - delegates to font_manager)
             font_service::get_google_font_url,
             font_service::store_embedded_font,
@@ -105,19 +302,30 @@ pub fn run() {
             font_manager::search_google_fonts,
             font_manager::fetch_google_fonts,
             font_manager::find_font_match,
+            font_manager::set_font_override,
+            font_manager::remove_font_override,
+            font_manager::list_font_overrides,
             font_manager::install_google_font,
             font_manager::install_font_file,
+            font_manager::install_font_bytes,
+            font_manager::list_installed_custom_fonts,
+            font_manager::uninstall_font,
             font_manager::parse_font_name,
             font_manager::get_canonical_font_name,
             font_manager::is_font_installed,
             font_manager::get_google_font_css_url,
             font_manager::clear_font_cache,
             font_manager::get_all_available_fonts,
+            font_manager::audit_fonts,
             // Live sync commands
             live_sync::create_sync_session,
             live_sync::generate_permission_link,
             live_sync::parse_permission_link,
             live_sync::validate_permission,
+            live_sync::validate_remote_op,
+            live_sync::register_peer_permission,
+            live_sync::register_session_host,
+            live_sync::unregister_peer_permission,
             live_sync::get_rtc_config,
             live_sync::generate_peer_id,
             live_sync::create_join_message,
@@ -129,16 +337,45 @@ pub fn run() {
             live_sync::set_signaling_state,
             live_sync::get_signaling_state,
             live_sync::clear_signaling_state,
+            live_sync::start_signaling_server,
+            live_sync::stop_signaling_server,
+            live_sync::get_signaling_server_info,
+            live_sync::parse_signaling_link,
+            live_sync::connect_signaling_server,
+            live_sync::send_signal_message,
+            live_sync::disconnect_signaling_server,
             live_sync::create_sync_message,
             live_sync::serialize_sync_message,
             live_sync::parse_sync_message,
             live_sync::create_layer_update_op,
             live_sync::create_cursor_op,
             live_sync::create_presence_op,
+            live_sync::offer_asset,
+            live_sync::handle_asset_message,
+            live_sync::get_asset_transfers,
+            live_sync::cancel_asset_transfer,
+            live_sync::open_sync_log,
+            live_sync::close_sync_log,
+            live_sync::delete_sync_log,
+            live_sync::record_sync_message,
+            live_sync::queue_local_op,
+            live_sync::take_pending_ops,
+            live_sync::create_catch_up_op,
+            live_sync::get_ops_since,
             // Print service commands
             print_service::calculate_booklet_imposition,
             print_service::get_paper_dimensions,
+            print_service::preview_imposition,
+            print_service::generate_calibration_sheet,
+            print_service::export_signatures,
+            cover::create_cover_template,
         ])
-        .run(tauri::generate_context!())
-        .expect("error while running tauri application");
+        .build(tauri::generate_context!())
+        .expect("error while running tauri application")
+        .run(|_, event| {
+            if let tauri::RunEvent::Exit = event {
+                // Font matches found since the last delayed write
+                let _ = font_manager::match_cache::flush();
+            }
+        });
 }
@@ -0,0 +1,176 @@
+//! Linked Images Module
+//!
+//! Image layers that reference a file on disk instead of an embedded copy,
+//! so a figure reworked in an external tool can be picked up again without
+//! placing it anew. The file is still cached under the layer id for display
+//! and export; the layer's `ImageLink` records the modification time and
+//! hash it was loaded at, to tell when the file changed.
+
+use crate::image_handler;
+use crate::models::{ImageLink, LayerObject, PageData};
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use std::time::UNIX_EPOCH;
+use vortex_core::image_place;
+use vortex_core::page_setup::Margins;
+
+/// Result of checking linked images against their files
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct LinkRefresh {
+    pub pages: Vec<PageData>,
+    /// Layers reloaded from a changed file
+    pub refreshed: Vec<String>,
+    /// Layers whose file is gone; they keep the last loaded image
+    pub missing: Vec<String>,
+}
+
+/// What became of a linked file since it was last loaded
+#[derive(Debug, PartialEq)]
+enum LinkState {
+    Unchanged,
+    /// Saved again with the same contents; only the time moved
+    Touched(u64),
+    Changed(ImageLink, Vec<u8>),
+    Missing,
+}
+
+pub(crate) fn file_hash(data: &[u8]) -> String {
+    Sha256::digest(data).iter().map(|b| format!("{:02x}", b)).collect()
+}
+
+fn modified_secs(path: &str) -> Option<u64> {
+    let modified = std::fs::metadata(path).and_then(|m| m.modified()).ok()?;
+    Some(modified.duration_since(UNIX_EPOCH).map_or(0, |d| d.as_secs()))
+}
+
+/// Read a file to link, with the link describing it
+pub(crate) fn read_link(path: &str) -> Result<(ImageLink, Vec<u8>), String> {
+    let data = std::fs::read(path).map_err(|e| format!("Failed to read {}: {}", path, e))?;
+    let link = ImageLink { path: path.to_string(), modified: modified_secs(path).unwrap_or(0), hash: file_hash(&data) };
+    Ok((link, data))
+}
+
+/// Compare a link with its file; the contents are only hashed when the
+/// modification time moved
+fn check_link(link: &ImageLink) -> LinkState {
+    let Some(modified) = modified_secs(&link.path) else {
+        return LinkState::Missing;
+    };
+    if modified == link.modified {
+        return LinkState::Unchanged;
+    }
+    match read_link(&link.path) {
+        Ok((fresh, _)) if fresh.hash == link.hash => LinkState::Touched(fresh.modified),
+        Ok((fresh, data)) => LinkState::Changed(fresh, data),
+        Err(_) => LinkState::Missing,
+    }
+}
+
+/// Record new pixel dimensions on an image layer, keeping its placement
+pub(crate) fn set_image_size(layer: &mut LayerObject, width: u32, height: u32) {
+    if let Some(metadata) = layer.image_data.as_mut() {
+        metadata.width = width;
+        metadata.height = height;
+        if let Some(effective) = metadata.effective_dpi(&layer.bounds) {
+            metadata.dpi = (effective.round() as u32).max(1);
+        }
+    }
+}
+
+/// Point a layer at reloaded image bytes, keeping its placement
+fn relink_layer(layer: &mut LayerObject, link: ImageLink, data: Vec<u8>) -> Result<(), String> {
+    let header = image_place::read_header(&data).ok_or_else(|| format!("Unsupported image format: {}", link.path))?;
+    set_image_size(layer, header.width, header.height);
+    image_handler::cache_image_with_dimensions(&layer.id, data, header.width, header.height);
+    layer.image_url = Some(format!("image://{}", layer.id));
+    layer.image_link = Some(link);
+    Ok(())
+}
+
+/// Place an image file on a page as a linked layer
+///
+/// Sized and positioned like `place_image`; the layer keeps a link to `path`
+/// so `refresh_linked_images` can reload it after the file is edited.
+#[tauri::command]
+pub fn place_linked_image(
+    page: PageData,
+    path: String,
+    margins: Option<Margins>,
+    x: Option<f32>,
+    y: Option<f32>,
+) -> Result<LayerObject, String> {
+    let (link, data) = read_link(&path)?;
+    let mut layer = image_handler::place_image_layer(&page, data, margins, x, y)?;
+    layer.image_link = Some(link);
+    Ok(layer)
+}
+
+/// Reload linked images whose files changed since they were last loaded
+#[tauri::command]
+pub async fn refresh_linked_images(pages: Vec<PageData>) -> Result<LinkRefresh, String> {
+    tokio::task::spawn_blocking(move || {
+        let mut result = LinkRefresh { pages, ..Default::default() };
+        for layer in result.pages.iter_mut().flat_map(|p| &mut p.layers) {
+            let Some(link) = layer.image_link.as_mut() else {
+                continue;
+            };
+            match check_link(link) {
+                LinkState::Unchanged => {}
+                LinkState::Touched(modified) => link.modified = modified,
+                LinkState::Changed(fresh, data) => {
+                    relink_layer(layer, fresh, data)?;
+                    result.refreshed.push(layer.id.clone());
+                }
+                LinkState::Missing => result.missing.push(layer.id.clone()),
+            }
+        }
+        Ok(result)
+    })
+    .await
+    .map_err(|e| format!("Refresh task failed: {}", e))?
+}
+
+/// Cache the files of linked layers, for a project saved with its links kept
+///
+/// Files that changed meanwhile are loaded as they are now; `refresh_linked_images`
+/// then finds nothing to do for them. Missing files are logged and skipped.
+pub fn restore_linked_images(pages: &mut [PageData]) {
+    for layer in pages.iter_mut().flat_map(|p| &mut p.layers) {
+        let Some(path) = layer.image_link.as_ref().map(|l| l.path.clone()) else {
+            continue;
+        };
+        match read_link(&path).and_then(|(link, data)| relink_layer(layer, link, data)) {
+            Ok(()) => {}
+            Err(e) => tracing::warn!(layer = %layer.id, "linked image not restored: {}", e),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_check_link() {
+        let path = std::env::temp_dir().join(format!("rook-link-{}.png", std::process::id()));
+        let path_str = path.to_string_lossy().to_string();
+        std::fs::write(&path, b"first").unwrap();
+        let (link, data) = read_link(&path_str).unwrap();
+        assert_eq!((data.as_slice(), link.hash.len()), (&b"first"[..], 64));
+        assert_eq!(check_link(&link), LinkState::Unchanged);
+
+        let stale = ImageLink { modified: link.modified.saturating_sub(10), ..link.clone() };
+        assert_eq!(check_link(&stale), LinkState::Touched(link.modified));
+
+        std::fs::write(&path, b"second").unwrap();
+        let stale = ImageLink { modified: link.modified.saturating_sub(10), ..link.clone() };
+        match check_link(&stale) {
+            LinkState::Changed(fresh, data) => assert_eq!((fresh.hash == link.hash, data), (false, b"second".to_vec())),
+            other => panic!("expected a change, got {:?}", other),
+        }
+
+        std::fs::remove_file(&path).unwrap();
+        assert_eq!(check_link(&link), LinkState::Missing);
+    }
+}
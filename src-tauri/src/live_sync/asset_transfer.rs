@@ -0,0 +1,565 @@
+//! Asset Transfer - Chunked binary transfer of images and fonts between peers
+//!
+//! Layer updates reference local image cache ids and embedded font names that
+//! peers may not have. Assets are content-addressed (SHA-256), split into
+//! fixed-size chunks and reassembled on the receiving side. Partial transfers
+//! are kept so a re-offer after reconnecting only requests missing chunks.
+//!
+//! Only peers holding an editor link may push assets, and an offer never
+//! replaces a local asset that holds other content under the same id.
+
+use base64::{engine::general_purpose::STANDARD, Engine};
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use std::collections::HashMap;
+use std::sync::{Arc, RwLock};
+use tauri::{AppHandle, Emitter};
+
+use super::permission::check_peer_can_edit;
+use crate::font_manager::{pdf_extractor, FontMetrics};
+use crate::image_handler;
+
+/// Raw bytes per chunk (stays well under common data channel message limits)
+pub const DEFAULT_CHUNK_SIZE: usize = 16 * 1024;
+/// Largest chunk a peer may announce
+pub const MAX_CHUNK_SIZE: usize = 1024 * 1024;
+/// Largest asset a peer may offer
+pub const MAX_ASSET_SIZE: usize = 256 * 1024 * 1024;
+/// Most unfinished incoming transfers a single peer may hold
+pub const MAX_INCOMING_PER_PEER: usize = 8;
+
+/// Kind of asset being transferred
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq, Hash)]
+#[serde(rename_all = "lowercase")]
+#[repr(u8)]
+pub enum AssetKind {
+    Image = 0,
+    Font = 1,
+}
+
+/// Describes an asset before any chunks are sent
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct AssetManifest {
+    /// Local id (image cache id or embedded font name)
+    pub asset_id: String,
+    pub kind: AssetKind,
+    /// Hex SHA-256 of the full asset
+    pub content_hash: String,
+    pub size: usize,
+    pub chunk_size: usize,
+    pub chunk_count: usize,
+}
+
+/// Asset transfer messages exchanged over the data channel
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "camelCase")]
+pub enum AssetMessage {
+    /// Sender announces an asset
+    Offer { manifest: AssetManifest },
+    /// Receiver already has this content - nothing to send
+    Have { content_hash: String },
+    /// Receiver asks for specific chunks (all missing chunks on first request)
+    Request { content_hash: String, chunks: Vec<usize> },
+    /// One chunk of asset data (base64)
+    Chunk { content_hash: String, index: usize, data: String },
+    /// Receiver verified and stored the asset
+    Complete { content_hash: String },
+    /// Transfer failed or was rejected
+    Error { content_hash: String, message: String },
+}
+
+/// Progress snapshot of an incoming transfer
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct AssetTransferStatus {
+    pub manifest: AssetManifest,
+    pub received_chunks: usize,
+    pub received_bytes: usize,
+    pub missing_chunks: Vec<usize>,
+}
+
+struct IncomingTransfer {
+    manifest: AssetManifest,
+    /// Peer the chunks are accepted from (the latest to offer it)
+    peer_id: String,
+    chunks: Vec<Option<Vec<u8>>>,
+    received_bytes: usize,
+}
+
+impl IncomingTransfer {
+    fn new(manifest: AssetManifest, peer_id: &str) -> Self {
+        Self {
+            chunks: vec![None; manifest.chunk_count],
+            manifest,
+            peer_id: peer_id.to_string(),
+            received_bytes: 0,
+        }
+    }
+
+    fn missing(&self) -> Vec<usize> {
+        self.chunks
+            .iter()
+            .enumerate()
+            .filter(|(_, c)| c.is_none())
+            .map(|(i, _)| i)
+            .collect()
+    }
+
+    /// Length chunk `index` must have
+    fn chunk_len(&self, index: usize) -> usize {
+        let start = index * self.manifest.chunk_size;
+        self.manifest.size.saturating_sub(start).min(self.manifest.chunk_size)
+    }
+
+    fn status(&self) -> AssetTransferStatus {
+        let missing_chunks = self.missing();
+        AssetTransferStatus {
+            manifest: self.manifest.clone(),
+            received_chunks: self.manifest.chunk_count - missing_chunks.len(),
+            received_bytes: self.received_bytes,
+            missing_chunks,
+        }
+    }
+}
+
+#[derive(Default)]
+struct AssetTransferState {
+    /// Assets we offered, by content hash
+    outgoing: HashMap<String, (AssetManifest, Arc<Vec<u8>>)>,
+    /// Partially received assets, by content hash
+    incoming: HashMap<String, IncomingTransfer>,
+    /// Content hashes available locally -> (kind, asset id)
+    known: HashMap<String, (AssetKind, String)>,
+}
+
+lazy_static::lazy_static! {
+    static ref ASSET_TRANSFERS: Arc<RwLock<AssetTransferState>> =
+        Arc::new(RwLock::new(AssetTransferState::default()));
+}
+
+/// Hex SHA-256 of asset bytes
+pub fn content_hash(data: &[u8]) -> String {
+    Sha256::digest(data).iter().map(|b| format!("{:02x}", b)).collect()
+}
+
+/// Build a manifest for asset bytes
+pub fn build_manifest(asset_id: &str, kind: AssetKind, data: &[u8], chunk_size: usize) -> AssetManifest {
+    let chunk_size = chunk_size.clamp(1, MAX_CHUNK_SIZE);
+    AssetManifest {
+        asset_id: asset_id.to_string(),
+        kind,
+        content_hash: content_hash(data),
+        size: data.len(),
+        chunk_size,
+        chunk_count: data.len().div_ceil(chunk_size).max(1),
+    }
+}
+
+/// Refuse a peer's manifest that is too large or whose chunking is inconsistent,
+/// before anything is allocated for it
+fn validate_manifest(manifest: &AssetManifest) -> Result<(), String> {
+    if manifest.size > MAX_ASSET_SIZE {
+        return Err(format!(
+            "Asset is {} bytes, more than the {} byte limit",
+            manifest.size, MAX_ASSET_SIZE
+        ));
+    }
+    if manifest.chunk_size == 0 || manifest.chunk_size > MAX_CHUNK_SIZE {
+        return Err(format!("Chunk size {} is out of range", manifest.chunk_size));
+    }
+    if manifest.chunk_count != manifest.size.div_ceil(manifest.chunk_size).max(1) {
+        return Err(format!(
+            "Chunk count {} does not match {} bytes in {} byte chunks",
+            manifest.chunk_count, manifest.size, manifest.chunk_size
+        ));
+    }
+    Ok(())
+}
+
+fn load_local_asset(kind: AssetKind, asset_id: &str) -> Option<Vec<u8>> {
+    match kind {
+        AssetKind::Image => image_handler::get_image_bytes(asset_id),
+        AssetKind::Font => pdf_extractor::get_embedded_font(asset_id),
+    }
+}
+
+fn store_local_asset(kind: AssetKind, asset_id: &str, data: Vec<u8>) -> Result<(), String> {
+    match kind {
+        AssetKind::Image => {
+            image_handler::cache_image(asset_id, data);
+            Ok(())
+        }
+        AssetKind::Font => pdf_extractor::store_embedded_font(asset_id, data, FontMetrics::default()),
+    }
+}
+
+/// What the asset id a manifest names currently holds locally
+enum LocalAsset {
+    Missing,
+    /// Same content as offered
+    Matches,
+    /// Other content, which an offer must not replace
+    Conflicts,
+}
+
+fn local_asset(manifest: &AssetManifest) -> LocalAsset {
+    match load_local_asset(manifest.kind, &manifest.asset_id) {
+        None => LocalAsset::Missing,
+        Some(data) if content_hash(&data) == manifest.content_hash => LocalAsset::Matches,
+        Some(_) => LocalAsset::Conflicts,
+    }
+}
+
+fn id_conflict(manifest: &AssetManifest) -> AssetMessage {
+    AssetMessage::Error {
+        content_hash: manifest.content_hash.clone(),
+        message: format!("Asset id '{}' already holds other content", manifest.asset_id),
+    }
+}
+
+/// Copy already-known content under the asset id the sender expects; false
+/// when the content is no longer cached
+fn alias_known_content(state: &AssetTransferState, manifest: &AssetManifest) -> Result<bool, String> {
+    let Some((kind, id)) = state.known.get(&manifest.content_hash) else {
+        return Ok(false);
+    };
+    match load_local_asset(*kind, id) {
+        Some(data) => store_local_asset(manifest.kind, &manifest.asset_id, data).map(|_| true),
+        None => Ok(false),
+    }
+}
+
+fn emit_progress(app_handle: &AppHandle, transfer: &IncomingTransfer) {
+    let missing = transfer.chunks.iter().filter(|c| c.is_none()).count();
+    let _ = app_handle.emit(
+        "asset_transfer_progress",
+        serde_json::json!({
+            "contentHash": transfer.manifest.content_hash,
+            "assetId": transfer.manifest.asset_id,
+            "kind": transfer.manifest.kind,
+            "receivedChunks": transfer.manifest.chunk_count - missing,
+            "totalChunks": transfer.manifest.chunk_count,
+            "receivedBytes": transfer.received_bytes,
+            "totalBytes": transfer.manifest.size,
+        }),
+    );
+}
+
+fn handle_offer(manifest: AssetManifest, peer_id: &str) -> Result<Vec<AssetMessage>, String> {
+    let content_hash = manifest.content_hash.clone();
+    if let Err(message) = validate_manifest(&manifest) {
+        return Ok(vec![AssetMessage::Error { content_hash, message }]);
+    }
+    let mut state = ASSET_TRANSFERS.write().map_err(|e| e.to_string())?;
+
+    let have = match local_asset(&manifest) {
+        LocalAsset::Conflicts => return Ok(vec![id_conflict(&manifest)]),
+        LocalAsset::Matches => true,
+        LocalAsset::Missing => alias_known_content(&state, &manifest)?,
+    };
+    if have {
+        state
+            .known
+            .insert(content_hash.clone(), (manifest.kind, manifest.asset_id.clone()));
+        return Ok(vec![AssetMessage::Have { content_hash }]);
+    }
+
+    let resumes_own = state
+        .incoming
+        .get(&content_hash)
+        .is_some_and(|t| t.peer_id == peer_id);
+    let in_flight = state.incoming.values().filter(|t| t.peer_id == peer_id).count();
+    if !resumes_own && in_flight >= MAX_INCOMING_PER_PEER {
+        return Ok(vec![AssetMessage::Error {
+            content_hash,
+            message: format!("Peer already has {} transfers in flight", MAX_INCOMING_PER_PEER),
+        }]);
+    }
+
+    // Resume an existing partial transfer if the manifest matches
+    let transfer = state
+        .incoming
+        .entry(content_hash.clone())
+        .or_insert_with(|| IncomingTransfer::new(manifest.clone(), peer_id));
+    if transfer.manifest.chunk_size != manifest.chunk_size
+        || transfer.manifest.size != manifest.size
+        || transfer.manifest.chunk_count != manifest.chunk_count
+    {
+        *transfer = IncomingTransfer::new(manifest, peer_id);
+    }
+    transfer.peer_id = peer_id.to_string();
+
+    Ok(vec![AssetMessage::Request {
+        content_hash,
+        chunks: transfer.missing(),
+    }])
+}
+
+fn handle_request(content_hash: String, chunks: Vec<usize>) -> Result<Vec<AssetMessage>, String> {
+    let state = ASSET_TRANSFERS.read().map_err(|e| e.to_string())?;
+    let Some((manifest, data)) = state.outgoing.get(&content_hash) else {
+        return Ok(vec![AssetMessage::Error {
+            content_hash,
+            message: "Asset is no longer offered".to_string(),
+        }]);
+    };
+
+    Ok(chunks
+        .into_iter()
+        .filter(|&index| index < manifest.chunk_count)
+        .map(|index| {
+            let start = index * manifest.chunk_size;
+            let end = (start + manifest.chunk_size).min(data.len());
+            AssetMessage::Chunk {
+                content_hash: content_hash.clone(),
+                index,
+                data: STANDARD.encode(&data[start..end]),
+            }
+        })
+        .collect())
+}
+
+fn handle_chunk(
+    content_hash: String,
+    index: usize,
+    data: String,
+    peer_id: &str,
+    app_handle: &AppHandle,
+) -> Result<Vec<AssetMessage>, String> {
+    let bytes = STANDARD
+        .decode(data.as_bytes())
+        .map_err(|e| format!("Invalid chunk encoding: {}", e))?;
+
+    let mut state = ASSET_TRANSFERS.write().map_err(|e| e.to_string())?;
+    let Some(transfer) = state.incoming.get_mut(&content_hash).filter(|t| t.peer_id == peer_id) else {
+        return Ok(Vec::new());
+    };
+    if index >= transfer.manifest.chunk_count {
+        return Ok(vec![AssetMessage::Error {
+            content_hash,
+            message: format!("Chunk index {} out of range", index),
+        }]);
+    }
+    if bytes.len() != transfer.chunk_len(index) {
+        return Ok(vec![AssetMessage::Error {
+            content_hash,
+            message: format!("Chunk {} has {} bytes, expected {}", index, bytes.len(), transfer.chunk_len(index)),
+        }]);
+    }
+
+    if transfer.chunks[index].is_none() {
+        transfer.received_bytes += bytes.len();
+        transfer.chunks[index] = Some(bytes);
+        emit_progress(app_handle, transfer);
+    }
+
+    if transfer.chunks.iter().any(|c| c.is_none()) {
+        return Ok(Vec::new());
+    }
+
+    // All chunks received - assemble and verify
+    let Some(transfer) = state.incoming.remove(&content_hash) else {
+        return Ok(Vec::new());
+    };
+    let manifest = transfer.manifest;
+    let assembled: Vec<u8> = transfer.chunks.into_iter().flatten().flatten().collect();
+
+    if self::content_hash(&assembled) != manifest.content_hash {
+        return Ok(vec![AssetMessage::Error {
+            content_hash,
+            message: "Content hash mismatch after reassembly".to_string(),
+        }]);
+    }
+
+    // The id may have been taken while the chunks were arriving
+    match local_asset(&manifest) {
+        LocalAsset::Conflicts => return Ok(vec![id_conflict(&manifest)]),
+        LocalAsset::Matches => {}
+        LocalAsset::Missing => store_local_asset(manifest.kind, &manifest.asset_id, assembled)?,
+    }
+    state
+        .known
+        .insert(content_hash.clone(), (manifest.kind, manifest.asset_id.clone()));
+
+    let _ = app_handle.emit("asset_transfer_complete", &manifest);
+    Ok(vec![AssetMessage::Complete { content_hash }])
+}
+
+/// Offer a local image or embedded font to peers
+#[tauri::command]
+pub fn offer_asset(kind: AssetKind, asset_id: String, chunk_size: Option<usize>) -> Result<AssetMessage, String> {
+    let data = load_local_asset(kind, &asset_id)
+        .ok_or_else(|| format!("Asset '{}' not found", asset_id))?;
+    let manifest = build_manifest(&asset_id, kind, &data, chunk_size.unwrap_or(DEFAULT_CHUNK_SIZE));
+
+    let mut state = ASSET_TRANSFERS.write().map_err(|e| e.to_string())?;
+    state
+        .known
+        .insert(manifest.content_hash.clone(), (kind, asset_id));
+    state
+        .outgoing
+        .insert(manifest.content_hash.clone(), (manifest.clone(), Arc::new(data)));
+
+    Ok(AssetMessage::Offer { manifest })
+}
+
+/// Handle an incoming asset message, returning replies to send back to the peer
+///
+/// `peer_id` is the id signaling authenticated for the connection the
+/// message arrived on; offers and chunks are refused unless that peer holds
+/// an editor link for the session.
+#[tauri::command]
+pub fn handle_asset_message(
+    session_id: String,
+    peer_id: String,
+    message: AssetMessage,
+    app_handle: AppHandle,
+) -> Result<Vec<AssetMessage>, String> {
+    if let AssetMessage::Offer { manifest: AssetManifest { content_hash, .. } }
+    | AssetMessage::Chunk { content_hash, .. } = &message
+    {
+        if let Err(violation) = check_peer_can_edit(&session_id, &peer_id) {
+            return Ok(vec![AssetMessage::Error {
+                content_hash: content_hash.clone(),
+                message: violation.message,
+            }]);
+        }
+    }
+
+    match message {
+        AssetMessage::Offer { manifest } => handle_offer(manifest, &peer_id),
+        AssetMessage::Request { content_hash, chunks } => handle_request(content_hash, chunks),
+        AssetMessage::Chunk { content_hash, index, data } => handle_chunk(content_hash, index, data, &peer_id, &app_handle),
+        AssetMessage::Have { content_hash } | AssetMessage::Complete { content_hash } => {
+            // Peer has the asset - stop holding the bytes for it
+            let mut state = ASSET_TRANSFERS.write().map_err(|e| e.to_string())?;
+            state.outgoing.remove(&content_hash);
+            Ok(Vec::new())
+        }
+        AssetMessage::Error { content_hash, message } => {
+            let _ = app_handle.emit(
+                "asset_transfer_error",
+                serde_json::json!({ "contentHash": content_hash, "message": message }),
+            );
+            Ok(Vec::new())
+        }
+    }
+}
+
+/// List incomplete incoming transfers (for resuming after reconnect)
+#[tauri::command]
+pub fn get_asset_transfers() -> Result<Vec<AssetTransferStatus>, String> {
+    let state = ASSET_TRANSFERS.read().map_err(|e| e.to_string())?;
+    Ok(state.incoming.values().map(|t| t.status()).collect())
+}
+
+/// Drop a transfer in either direction
+#[tauri::command]
+pub fn cancel_asset_transfer(content_hash: String) -> Result<bool, String> {
+    let mut state = ASSET_TRANSFERS.write().map_err(|e| e.to_string())?;
+    let incoming = state.incoming.remove(&content_hash).is_some();
+    let outgoing = state.outgoing.remove(&content_hash).is_some();
+    Ok(incoming || outgoing)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_manifest_chunking() {
+        let data = vec![1u8; DEFAULT_CHUNK_SIZE * 2 + 10];
+        let manifest = build_manifest("img-1", AssetKind::Image, &data, DEFAULT_CHUNK_SIZE);
+        assert_eq!(manifest.chunk_count, 3);
+        assert_eq!(manifest.size, data.len());
+        assert_eq!(manifest.content_hash.len(), 64);
+
+        // Empty assets still have one (empty) chunk
+        assert_eq!(build_manifest("e", AssetKind::Font, &[], 1024).chunk_count, 1);
+    }
+
+    #[test]
+    fn test_content_hash_known_vector() {
+        assert_eq!(
+            content_hash(b"abc"),
+            "ba7816bf8f01cfea414140de5dae2223b00361a396177a9cb410ff61f20015ad"
+        );
+    }
+
+    #[test]
+    fn test_offer_request_resume() {
+        let data: Vec<u8> = (0..50u8).collect();
+        let manifest = build_manifest("sync-test-image", AssetKind::Image, &data, 16);
+
+        // First offer requests everything
+        let reply = handle_offer(manifest.clone(), "peer-a").unwrap();
+        match &reply[0] {
+            AssetMessage::Request { chunks, .. } => assert_eq!(chunks, &vec![0, 1, 2, 3]),
+            _ => panic!("Expected request"),
+        }
+
+        // Simulate a partial transfer, then a re-offer after reconnect
+        {
+            let mut state = ASSET_TRANSFERS.write().unwrap();
+            let transfer = state.incoming.get_mut(&manifest.content_hash).unwrap();
+            transfer.chunks[0] = Some(data[..16].to_vec());
+            transfer.chunks[2] = Some(data[32..48].to_vec());
+        }
+        let reply = handle_offer(manifest.clone(), "peer-a").unwrap();
+        match &reply[0] {
+            AssetMessage::Request { chunks, .. } => assert_eq!(chunks, &vec![1, 3]),
+            _ => panic!("Expected request"),
+        }
+
+        assert!(cancel_asset_transfer(manifest.content_hash).unwrap());
+    }
+
+    #[test]
+    fn test_offer_rejects_inconsistent_manifests() {
+        let manifest = build_manifest("sync-test-bad", AssetKind::Image, &[7u8; 40], 16);
+        let bad = [
+            AssetManifest { chunk_count: usize::MAX, ..manifest.clone() },
+            AssetManifest { chunk_count: 2, ..manifest.clone() },
+            AssetManifest { chunk_size: 0, ..manifest.clone() },
+            AssetManifest { chunk_size: MAX_CHUNK_SIZE + 1, chunk_count: 1, ..manifest.clone() },
+            AssetManifest { size: MAX_ASSET_SIZE + 1, chunk_count: (MAX_ASSET_SIZE + 1).div_ceil(16), ..manifest.clone() },
+        ];
+        for manifest in bad {
+            let reply = handle_offer(manifest.clone(), "peer-a").unwrap();
+            assert!(matches!(reply[..], [AssetMessage::Error { .. }]), "{:?}", manifest);
+        }
+        assert!(!ASSET_TRANSFERS.read().unwrap().incoming.contains_key(&manifest.content_hash));
+    }
+
+    #[test]
+    fn test_offer_never_replaces_local_assets() {
+        image_handler::cache_image("sync-test-taken", vec![1, 2, 3]);
+        let same = build_manifest("sync-test-taken", AssetKind::Image, &[1, 2, 3], 16);
+        assert!(matches!(handle_offer(same, "peer-a").unwrap()[..], [AssetMessage::Have { .. }]));
+
+        let other = build_manifest("sync-test-taken", AssetKind::Image, &[9, 9, 9], 16);
+        assert!(matches!(handle_offer(other.clone(), "peer-a").unwrap()[..], [AssetMessage::Error { .. }]));
+        assert!(!ASSET_TRANSFERS.read().unwrap().incoming.contains_key(&other.content_hash));
+        assert_eq!(image_handler::get_image_bytes("sync-test-taken"), Some(vec![1, 2, 3]));
+    }
+
+    #[test]
+    fn test_incoming_transfers_are_capped_per_peer() {
+        let manifests: Vec<_> = (0..=MAX_INCOMING_PER_PEER)
+            .map(|i| build_manifest(&format!("sync-test-cap-{}", i), AssetKind::Image, format!("cap {}", i).as_bytes(), 16))
+            .collect();
+        for manifest in &manifests[..MAX_INCOMING_PER_PEER] {
+            assert!(matches!(handle_offer(manifest.clone(), "peer-cap").unwrap()[..], [AssetMessage::Request { .. }]));
+        }
+        let over = manifests[MAX_INCOMING_PER_PEER].clone();
+        assert!(matches!(handle_offer(over.clone(), "peer-cap").unwrap()[..], [AssetMessage::Error { .. }]));
+        // Re-offering one already in flight is a resume, and other peers aren't affected
+        assert!(matches!(handle_offer(manifests[0].clone(), "peer-cap").unwrap()[..], [AssetMessage::Request { .. }]));
+        assert!(matches!(handle_offer(over.clone(), "peer-other").unwrap()[..], [AssetMessage::Request { .. }]));
+
+        for manifest in manifests {
+            cancel_asset_transfer(manifest.content_hash).unwrap();
+        }
+    }
+}
@@ -2,10 +2,17 @@
 //!
 //! Provides encrypted P2P synchronization with permission-based access control.
 
+pub mod asset_transfer;
+pub mod op_log;
 pub mod permission;
 pub mod signaling;
+pub mod signaling_server;
 pub mod sync_message;
+pub mod websocket;
 
+pub use asset_transfer::*;
+pub use op_log::*;
 pub use permission::*;
 pub use signaling::*;
+pub use signaling_server::*;
 pub use sync_message::*;
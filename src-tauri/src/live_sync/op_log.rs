@@ -0,0 +1,516 @@
+//! Sync Op Log - Persistent per-session history with offline queue
+//!
+//! Every durable op is appended to a JSON Lines log on disk, so a session can be
+//! resumed after a restart. Each peer tracks a state vector (highest `seq` per
+//! sender below which nothing is missing); on reconnect peers exchange vectors
+//! and send only what the other side is missing. Local ops made while disconnected are held in an offline
+//! queue until the connection comes back.
+
+use super::permission::check_peer_message;
+use super::sync_message::{create_sync_message, SyncMessage, SyncOp};
+use crate::models::{LayerObject, LayerRole};
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeSet, HashMap};
+use std::fs;
+use std::io::Write;
+use std::path::PathBuf;
+use std::sync::{Arc, RwLock};
+use tauri::{AppHandle, Emitter};
+use vortex_core::layers::{self, LockViolation};
+
+/// Highest sequence number per sender up to which every op has been seen
+pub type StateVector = HashMap<String, u64>;
+
+/// Summary returned when a log is opened
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SyncLogInfo {
+    pub session_id: String,
+    pub peer_id: String,
+    pub op_count: usize,
+    pub pending_count: usize,
+    pub local_seq: u64,
+    pub state_vector: StateVector,
+}
+
+/// In-memory op log for one session
+#[derive(Debug, Default)]
+pub struct OpLog {
+    pub session_id: String,
+    pub peer_id: String,
+    entries: Vec<SyncMessage>,
+    state_vector: StateVector,
+    /// Seqs per sender received past a gap in the state vector
+    ahead: HashMap<String, BTreeSet<u64>>,
+    pending: Vec<SyncMessage>,
+    local_seq: u64,
+}
+
+impl OpLog {
+    pub fn new(session_id: &str, peer_id: &str) -> Self {
+        Self {
+            session_id: session_id.to_string(),
+            peer_id: peer_id.to_string(),
+            ..Default::default()
+        }
+    }
+
+    /// Record a message; returns false for duplicates
+    ///
+    /// Messages may arrive out of order; the state vector only advances over
+    /// a contiguous run of seqs, so a gap is still asked for on catch-up.
+    pub fn record(&mut self, message: SyncMessage) -> bool {
+        let seen = self.state_vector.get(&message.sender_id).copied().unwrap_or(0);
+        if message.seq <= seen {
+            return false;
+        }
+        let ahead = self.ahead.entry(message.sender_id.clone()).or_default();
+        if !ahead.insert(message.seq) {
+            return false;
+        }
+
+        let mut contiguous = seen;
+        while ahead.remove(&(contiguous + 1)) {
+            contiguous += 1;
+        }
+        if ahead.is_empty() {
+            self.ahead.remove(&message.sender_id);
+        }
+        if contiguous > seen {
+            self.state_vector.insert(message.sender_id.clone(), contiguous);
+        }
+        if message.sender_id == self.peer_id {
+            self.local_seq = self.local_seq.max(message.seq);
+        }
+        self.entries.push(message);
+        true
+    }
+
+    /// Create a local message with the next sequence number
+    pub fn next_local(&mut self, op: SyncOp) -> SyncMessage {
+        self.local_seq += 1;
+        create_sync_message(self.peer_id.clone(), self.local_seq, op)
+    }
+
+    /// Ops the remote side hasn't seen, in log order
+    pub fn ops_since(&self, remote: &StateVector) -> Vec<SyncMessage> {
+        self.entries
+            .iter()
+            .filter(|m| m.seq > remote.get(&m.sender_id).copied().unwrap_or(0))
+            .cloned()
+            .collect()
+    }
+
+    pub fn state_vector(&self) -> &StateVector {
+        &self.state_vector
+    }
+
+    pub fn info(&self) -> SyncLogInfo {
+        SyncLogInfo {
+            session_id: self.session_id.clone(),
+            peer_id: self.peer_id.clone(),
+            op_count: self.entries.len(),
+            pending_count: self.pending.len(),
+            local_seq: self.local_seq,
+            state_vector: self.state_vector.clone(),
+        }
+    }
+}
+
+/// Ephemeral ops (cursor, selection, presence, acks) are never logged
+#[inline]
+pub fn is_durable(op: &SyncOp) -> bool {
+    !matches!(
+        op,
+        SyncOp::CursorMove { .. }
+            | SyncOp::SelectionChange { .. }
+            | SyncOp::Presence { .. }
+            | SyncOp::Ack { .. }
+            | SyncOp::CatchUp { .. }
+    )
+}
+
+#[derive(Default)]
+struct OpLogState {
+    dir: Option<PathBuf>,
+    logs: HashMap<String, OpLog>,
+}
+
+lazy_static::lazy_static! {
+    static ref SYNC_LOGS: Arc<RwLock<OpLogState>> = Arc::new(RwLock::new(OpLogState::default()));
+}
+
+/// Set the directory session logs are stored in
+pub fn init_sync_logs(dir: PathBuf) -> Result<(), String> {
+    fs::create_dir_all(&dir).map_err(|e| e.to_string())?;
+    SYNC_LOGS.write().map_err(|e| e.to_string())?.dir = Some(dir);
+    Ok(())
+}
+
+fn sanitize_session_id(session_id: &str) -> String {
+    session_id
+        .chars()
+        .map(|c| if c.is_ascii_alphanumeric() || c == '-' || c == '_' { c } else { '_' })
+        .collect()
+}
+
+fn log_path(dir: &std::path::Path, session_id: &str) -> PathBuf {
+    dir.join(format!("{}.oplog.jsonl", sanitize_session_id(session_id)))
+}
+
+fn pending_path(dir: &std::path::Path, session_id: &str) -> PathBuf {
+    dir.join(format!("{}.pending.json", sanitize_session_id(session_id)))
+}
+
+/// Load a session log from disk (missing files give an empty log)
+pub fn load_log(dir: &std::path::Path, session_id: &str, peer_id: &str) -> OpLog {
+    let mut log = OpLog::new(session_id, peer_id);
+
+    if let Ok(content) = fs::read_to_string(log_path(dir, session_id)) {
+        // Skip lines that fail to parse (e.g. a torn final write)
+        for line in content.lines().filter(|l| !l.trim().is_empty()) {
+            if let Ok(message) = serde_json::from_str::<SyncMessage>(line) {
+                log.record(message);
+            }
+        }
+    }
+
+    log.pending = fs::read(pending_path(dir, session_id))
+        .ok()
+        .and_then(|data| serde_json::from_slice(&data).ok())
+        .unwrap_or_default();
+    log.local_seq = log
+        .pending
+        .iter()
+        .map(|m| m.seq)
+        .fold(log.local_seq, u64::max);
+
+    log
+}
+
+fn append_to_disk(dir: &std::path::Path, session_id: &str, message: &SyncMessage) -> Result<(), String> {
+    let line = serde_json::to_string(message).map_err(|e| e.to_string())?;
+    let mut file = fs::OpenOptions::new()
+        .create(true)
+        .append(true)
+        .open(log_path(dir, session_id))
+        .map_err(|e| e.to_string())?;
+    writeln!(file, "{}", line).map_err(|e| e.to_string())
+}
+
+fn write_pending(dir: &std::path::Path, log: &OpLog) -> Result<(), String> {
+    let path = pending_path(dir, &log.session_id);
+    if log.pending.is_empty() {
+        if path.exists() {
+            fs::remove_file(&path).map_err(|e| e.to_string())?;
+        }
+        return Ok(());
+    }
+    let data = serde_json::to_vec(&log.pending).map_err(|e| e.to_string())?;
+    fs::write(path, data).map_err(|e| e.to_string())
+}
+
+/// Record a message in an open log and persist it if new
+fn record_and_persist(state: &mut OpLogState, session_id: &str, message: SyncMessage) -> Result<bool, String> {
+    let log = state
+        .logs
+        .get_mut(session_id)
+        .ok_or_else(|| format!("Sync log for session '{}' is not open", session_id))?;
+
+    if !is_durable(&message.op) {
+        return Ok(false);
+    }
+    if !log.record(message.clone()) {
+        return Ok(false);
+    }
+    if let Some(dir) = &state.dir {
+        append_to_disk(dir, session_id, &message)?;
+    }
+    Ok(true)
+}
+
+/// Open (or reopen) the op log for a session
+#[tauri::command]
+pub fn open_sync_log(session_id: String, peer_id: String) -> Result<SyncLogInfo, String> {
+    let mut state = SYNC_LOGS.write().map_err(|e| e.to_string())?;
+
+    let log = match &state.dir {
+        Some(dir) => load_log(dir, &session_id, &peer_id),
+        None => OpLog::new(&session_id, &peer_id),
+    };
+    let info = log.info();
+    state.logs.insert(session_id, log);
+    Ok(info)
+}
+
+/// Close a session log (data stays on disk)
+#[tauri::command]
+pub fn close_sync_log(session_id: String) -> Result<bool, String> {
+    Ok(SYNC_LOGS
+        .write()
+        .map_err(|e| e.to_string())?
+        .logs
+        .remove(&session_id)
+        .is_some())
+}
+
+/// Delete a session's log from memory and disk
+#[tauri::command]
+pub fn delete_sync_log(session_id: String) -> Result<(), String> {
+    let mut state = SYNC_LOGS.write().map_err(|e| e.to_string())?;
+    state.logs.remove(&session_id);
+
+    if let Some(dir) = &state.dir {
+        for path in [log_path(dir, &session_id), pending_path(dir, &session_id)] {
+            if path.exists() {
+                fs::remove_file(&path).map_err(|e| e.to_string())?;
+            }
+        }
+    }
+    Ok(())
+}
+
+/// Refuse remote edits of locked layers; `page_layers` are the targeted
+/// page's current layers. Peers have no override and cannot unlock.
+///
+/// A targeted layer missing from `page_layers` is refused too, as its lock
+/// can't be checked.
+fn check_layer_locks(op: &SyncOp, page_layers: &[LayerObject]) -> Result<(), LockViolation> {
+    let find = |page_index: usize, id: &str| {
+        page_layers.iter().find(|l| l.id == id).ok_or_else(|| LockViolation {
+            page_index,
+            layer_ids: vec![id.to_string()],
+            message: format!("Layer {} is not among the page's layers, so its lock can't be checked", id),
+        })
+    };
+    match op {
+        SyncOp::LayerUpdate { page_index, layer_id, .. } => {
+            layers::check_remote_update(*page_index, find(*page_index, layer_id)?)
+        }
+        SyncOp::LayerDelete { page_index, layer_id } => layers::check_delete(*page_index, find(*page_index, layer_id)?),
+        SyncOp::LayerReorder { page_index, layer_ids } => {
+            for id in layer_ids {
+                find(*page_index, id)?;
+            }
+            layers::check_reorder(*page_index, page_layers, layer_ids)
+        }
+        _ => Ok(()),
+    }
+}
+
+/// Record a message received from a peer; returns false for duplicates
+///
+/// `peer_id` is the id signaling authenticated for the connection the
+/// message arrived on; the message is checked against that peer's token.
+/// Ops outside the sender's permission scope are rejected and reported via a
+/// `permission_violation` event. `layer_role` is the targeted layer's current role.
+/// Ops changing locked layers among the targeted page's `page_layers` (empty
+/// for ops not aimed at a page) are rejected too and reported via a
+/// `lock_violation` event, as are ops aimed at layers `page_layers` lacks.
+#[tauri::command]
+pub fn record_sync_message(
+    session_id: String,
+    peer_id: String,
+    message: SyncMessage,
+    layer_role: Option<LayerRole>,
+    page_layers: Vec<LayerObject>,
+    app_handle: AppHandle,
+) -> Result<bool, String> {
+    if let Err(violation) = check_peer_message(&session_id, &peer_id, &message, layer_role) {
+        let _ = app_handle.emit(
+            "permission_violation",
+            serde_json::json!({
+                "sessionId": session_id,
+                "peerId": peer_id,
+                "senderId": message.sender_id,
+                "seq": message.seq,
+                "violation": violation,
+            }),
+        );
+        return Err(violation.to_string());
+    }
+    if let Err(violation) = check_layer_locks(&message.op, &page_layers) {
+        let _ = app_handle.emit(
+            "lock_violation",
+            serde_json::json!({
+                "sessionId": session_id,
+                "senderId": message.sender_id,
+                "seq": message.seq,
+                "violation": violation,
+            }),
+        );
+        return Err(violation.to_string());
+    }
+
+    let mut state = SYNC_LOGS.write().map_err(|e| e.to_string())?;
+    record_and_persist(&mut state, &session_id, message)
+}
+
+/// Create a local op message; while disconnected it is held in the offline queue
+#[tauri::command]
+pub fn queue_local_op(session_id: String, op: SyncOp, connected: bool) -> Result<SyncMessage, String> {
+    let mut state = SYNC_LOGS.write().map_err(|e| e.to_string())?;
+
+    let message = state
+        .logs
+        .get_mut(&session_id)
+        .ok_or_else(|| format!("Sync log for session '{}' is not open", session_id))?
+        .next_local(op);
+
+    record_and_persist(&mut state, &session_id, message.clone())?;
+
+    if !connected && is_durable(&message.op) {
+        let OpLogState { dir, logs } = &mut *state;
+        if let Some(log) = logs.get_mut(&session_id) {
+            log.pending.push(message.clone());
+            if let Some(dir) = dir {
+                write_pending(dir, log)?;
+            }
+        }
+    }
+
+    Ok(message)
+}
+
+/// Drain the offline queue after reconnecting
+#[tauri::command]
+pub fn take_pending_ops(session_id: String) -> Result<Vec<SyncMessage>, String> {
+    let mut state = SYNC_LOGS.write().map_err(|e| e.to_string())?;
+    let OpLogState { dir, logs } = &mut *state;
+    let log = logs
+        .get_mut(&session_id)
+        .ok_or_else(|| format!("Sync log for session '{}' is not open", session_id))?;
+
+    let pending = std::mem::take(&mut log.pending);
+    if let Some(dir) = dir {
+        write_pending(dir, log)?;
+    }
+    Ok(pending)
+}
+
+/// Build a catch-up request carrying our state vector
+#[tauri::command]
+pub fn create_catch_up_op(session_id: String) -> Result<SyncOp, String> {
+    let state = SYNC_LOGS.read().map_err(|e| e.to_string())?;
+    let log = state
+        .logs
+        .get(&session_id)
+        .ok_or_else(|| format!("Sync log for session '{}' is not open", session_id))?;
+    Ok(SyncOp::CatchUp {
+        state_vector: log.state_vector().clone(),
+    })
+}
+
+/// Ops a peer is missing, given its state vector
+#[tauri::command]
+pub fn get_ops_since(session_id: String, state_vector: StateVector) -> Result<Vec<SyncMessage>, String> {
+    let state = SYNC_LOGS.read().map_err(|e| e.to_string())?;
+    let log = state
+        .logs
+        .get(&session_id)
+        .ok_or_else(|| format!("Sync log for session '{}' is not open", session_id))?;
+    Ok(log.ops_since(&state_vector))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use vortex_core::test_util;
+
+    fn delete_op(sender: &str, seq: u64) -> SyncMessage {
+        create_sync_message(
+            sender.to_string(),
+            seq,
+            SyncOp::LayerDelete {
+                page_index: 0,
+                layer_id: format!("layer-{}", seq),
+            },
+        )
+    }
+
+    #[test]
+    fn test_record_dedupes_by_state_vector() {
+        let mut log = OpLog::new("s1", "peer-a");
+        assert!(log.record(delete_op("peer-b", 1)));
+        assert!(log.record(delete_op("peer-b", 2)));
+        assert!(!log.record(delete_op("peer-b", 2)));
+        assert_eq!(log.state_vector().get("peer-b"), Some(&2));
+    }
+
+    #[test]
+    fn test_record_keeps_out_of_order_ops() {
+        let mut log = OpLog::new("s1", "peer-a");
+        assert!(log.record(delete_op("peer-b", 1)));
+        assert!(log.record(delete_op("peer-b", 3)));
+        // The gap at 2 holds the state vector back, so catch-up still asks for it
+        assert_eq!(log.state_vector().get("peer-b"), Some(&1));
+        assert!(!log.record(delete_op("peer-b", 3)));
+
+        assert!(log.record(delete_op("peer-b", 2)));
+        assert_eq!(log.state_vector().get("peer-b"), Some(&3));
+        assert!(!log.record(delete_op("peer-b", 2)));
+        assert_eq!(log.info().op_count, 3);
+    }
+
+    #[test]
+    fn test_remote_ops_respect_locks() {
+        let layer = |id: &str, z: i32, locked: bool| {
+            test_util::layer(id, "shape").bounds(0.0, 0.0, 10.0, 10.0).z(z).with("locked", locked).build()
+        };
+        let page = [layer("layer-1", 0, true), layer("layer-2", 1, false)];
+
+        assert!(check_layer_locks(&delete_op("peer-b", 1).op, &page).is_err());
+        assert!(check_layer_locks(&delete_op("peer-b", 2).op, &page).is_ok());
+        let reorder = SyncOp::LayerReorder { page_index: 0, layer_ids: vec!["layer-2".into(), "layer-1".into()] };
+        assert_eq!(check_layer_locks(&reorder, &page).unwrap_err().layer_ids, ["layer-1"]);
+        // Peers can't unlock a layer either
+        let updates = crate::models::LayerUpdates { locked: Some(false), ..Default::default() };
+        let unlock = SyncOp::LayerUpdate { page_index: 0, layer_id: "layer-1".into(), updates };
+        assert!(check_layer_locks(&unlock, &page).is_err());
+        // Without the targeted layers nothing can be checked, so the op is refused
+        assert_eq!(check_layer_locks(&delete_op("peer-b", 2).op, &[]).unwrap_err().layer_ids, ["layer-2"]);
+        let partial = SyncOp::LayerReorder { page_index: 0, layer_ids: vec!["layer-2".into(), "layer-3".into()] };
+        assert_eq!(check_layer_locks(&partial, &page[1..]).unwrap_err().layer_ids, ["layer-3"]);
+    }
+
+    #[test]
+    fn test_catch_up_returns_missing_ops() {
+        let mut log = OpLog::new("s1", "peer-a");
+        log.record(delete_op("peer-a", 1));
+        log.record(delete_op("peer-a", 2));
+        log.record(delete_op("peer-b", 1));
+
+        let mut remote = StateVector::new();
+        remote.insert("peer-a".to_string(), 1);
+        let missing = log.ops_since(&remote);
+
+        assert_eq!(missing.len(), 2);
+        assert!(missing.iter().any(|m| m.sender_id == "peer-a" && m.seq == 2));
+        assert!(missing.iter().any(|m| m.sender_id == "peer-b" && m.seq == 1));
+    }
+
+    #[test]
+    fn test_ephemeral_ops_not_durable() {
+        assert!(!is_durable(&SyncOp::Ack { seq: 1 }));
+        assert!(is_durable(&SyncOp::LayerDelete {
+            page_index: 0,
+            layer_id: "x".to_string()
+        }));
+    }
+
+    #[test]
+    fn test_log_survives_reload() {
+        let dir = std::env::temp_dir().join(format!("rook-oplog-test-{}", std::process::id()));
+        fs::create_dir_all(&dir).unwrap();
+
+        let message = delete_op("peer-a", 1);
+        append_to_disk(&dir, "session/1", &message).unwrap();
+        let mut log = load_log(&dir, "session/1", "peer-a");
+        assert_eq!(log.info().op_count, 1);
+
+        // Local sequence continues after the persisted ops
+        assert_eq!(log.next_local(SyncOp::Ack { seq: 0 }).seq, 2);
+
+        let _ = fs::remove_dir_all(&dir);
+    }
+}
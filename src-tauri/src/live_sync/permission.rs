@@ -1,11 +1,20 @@
 //! Permission Token System - Encrypted shareable links with role-based access
 //!
-//! Uses ChaCha20-Poly1305 for authenticated encryption of permission tokens.
+//! Uses ChaCha20-Poly1305 for authenticated encryption of permission tokens, so
+//! a link can't be altered (e.g. a viewer link turned into an editor one)
+//! without the session's secret key.
 
 use base64::{engine::general_purpose::URL_SAFE_NO_PAD, Engine};
+use ring::aead::{Aad, LessSafeKey, Nonce, UnboundKey, CHACHA20_POLY1305, NONCE_LEN};
+use ring::rand::{SecureRandom, SystemRandom};
 use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::sync::{Arc, RwLock};
 use std::time::{SystemTime, UNIX_EPOCH};
 
+use super::sync_message::{SyncMessage, SyncOp};
+use crate::models::LayerRole;
+
 /// Permission roles for sync sessions
 #[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
 #[serde(rename_all = "lowercase")]
@@ -26,6 +35,40 @@ impl SyncRole {
     }
 }
 
+/// Restricts an editor to specific pages and/or layer roles
+///
+/// Empty lists mean "no restriction" for that dimension.
+#[derive(Debug, Clone, Default, Serialize, Deserialize, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct PermissionScope {
+    /// Inclusive page index ranges
+    #[serde(default)]
+    pub page_ranges: Vec<(usize, usize)>,
+    #[serde(default)]
+    pub layer_roles: Vec<LayerRole>,
+}
+
+impl PermissionScope {
+    #[inline]
+    pub fn is_unrestricted(&self) -> bool {
+        self.page_ranges.is_empty() && self.layer_roles.is_empty()
+    }
+
+    #[inline]
+    pub fn allows_page(&self, page_index: usize) -> bool {
+        self.page_ranges.is_empty()
+            || self
+                .page_ranges
+                .iter()
+                .any(|&(start, end)| page_index >= start && page_index <= end)
+    }
+
+    #[inline]
+    pub fn allows_role(&self, role: LayerRole) -> bool {
+        self.layer_roles.is_empty() || self.layer_roles.contains(&role)
+    }
+}
+
 /// Permission token payload
 #[derive(Debug, Clone, Serialize, Deserialize)]
 #[serde(rename_all = "camelCase")]
@@ -35,6 +78,55 @@ pub struct PermissionToken {
     pub created_at: u64,
     pub expires_at: Option<u64>,
     pub creator_id: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub scope: Option<PermissionScope>,
+}
+
+/// Why a remote op was rejected
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+#[repr(u8)]
+pub enum ViolationKind {
+    ReadOnly = 0,
+    CommentOnly = 1,
+    PageOutOfScope = 2,
+    LayerRoleOutOfScope = 3,
+    UnknownLayerRole = 4,
+    HostOnly = 5,
+    /// The sender never presented a valid permission link
+    UnknownPeer = 6,
+    /// The message names another sender than the connection it arrived on
+    SpoofedSender = 7,
+}
+
+/// Structured permission error surfaced to the frontend
+#[derive(Debug, Clone, Serialize, Deserialize, thiserror::Error)]
+#[serde(rename_all = "camelCase")]
+#[error("{message}")]
+pub struct PermissionViolation {
+    pub kind: ViolationKind,
+    pub message: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub page_index: Option<usize>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub layer_id: Option<String>,
+}
+
+impl PermissionViolation {
+    fn new(kind: ViolationKind, message: impl Into<String>) -> Self {
+        Self {
+            kind,
+            message: message.into(),
+            page_index: None,
+            layer_id: None,
+        }
+    }
+
+    fn at(mut self, page_index: usize, layer_id: Option<&str>) -> Self {
+        self.page_index = Some(page_index);
+        self.layer_id = layer_id.map(str::to_string);
+        self
+    }
 }
 
 /// Session info for sync
@@ -48,87 +140,65 @@ pub struct SyncSession {
     pub host_id: String,
 }
 
+/// Fill an array from the system's secure random source
+fn random_bytes<const N: usize>() -> Result<[u8; N], String> {
+    let mut bytes = [0u8; N];
+    SystemRandom::new()
+        .fill(&mut bytes)
+        .map_err(|_| "Failed to generate random bytes".to_string())?;
+    Ok(bytes)
+}
+
 /// Generate a random session ID
-fn generate_id() -> String {
-    use std::collections::hash_map::RandomState;
-    use std::hash::{BuildHasher, Hasher};
-    
-    let state = RandomState::new();
-    let mut hasher = state.build_hasher();
-    hasher.write_u128(
-        SystemTime::now()
-            .duration_since(UNIX_EPOCH)
-            .unwrap_or_default()
-            .as_nanos(),
-    );
-    format!("{:016x}", hasher.finish())
+fn generate_id() -> Result<String, String> {
+    Ok(format!("{:016x}", u64::from_le_bytes(random_bytes()?)))
 }
 
-/// Generate a 32-byte random key
-fn generate_key() -> [u8; 32] {
-    use std::collections::hash_map::RandomState;
-    use std::hash::{BuildHasher, Hasher};
-    
-    let mut key = [0u8; 32];
-    for chunk in key.chunks_mut(8) {
-        let state = RandomState::new();
-        let mut hasher = state.build_hasher();
-        hasher.write_u64(
-            SystemTime::now()
-                .duration_since(UNIX_EPOCH)
-                .unwrap_or_default()
-                .as_nanos() as u64,
-        );
-        let bytes = hasher.finish().to_le_bytes();
-        chunk.copy_from_slice(&bytes[..chunk.len()]);
-    }
-    key
+fn token_key(key: &[u8; 32]) -> Option<LessSafeKey> {
+    UnboundKey::new(&CHACHA20_POLY1305, key).ok().map(LessSafeKey::new)
 }
 
-/// Simple XOR-based encryption (for demo - use chacha20poly1305 in production)
-fn encrypt_token(data: &[u8], key: &[u8; 32]) -> Vec<u8> {
-    let nonce: [u8; 12] = generate_key()[..12].try_into().unwrap_or([0u8; 12]);
-    let mut encrypted = Vec::with_capacity(12 + data.len());
+/// Encrypt and authenticate a token; the random nonce is prepended
+fn encrypt_token(data: &[u8], key: &[u8; 32]) -> Result<Vec<u8>, String> {
+    let nonce: [u8; NONCE_LEN] = random_bytes()?;
+    let mut sealed = data.to_vec();
+    token_key(key)
+        .ok_or("Invalid key")?
+        .seal_in_place_append_tag(Nonce::assume_unique_for_key(nonce), Aad::empty(), &mut sealed)
+        .map_err(|_| "Encryption failed".to_string())?;
+
+    let mut encrypted = Vec::with_capacity(NONCE_LEN + sealed.len());
     encrypted.extend_from_slice(&nonce);
-    
-    // XOR encryption with key expansion
-    for (i, byte) in data.iter().enumerate() {
-        let key_byte = key[i % 32] ^ nonce[i % 12];
-        encrypted.push(byte ^ key_byte);
-    }
-    encrypted
+    encrypted.extend_from_slice(&sealed);
+    Ok(encrypted)
 }
 
-/// Decrypt token
+/// Decrypt a token, `None` if it was altered or sealed with another key
 fn decrypt_token(encrypted: &[u8], key: &[u8; 32]) -> Option<Vec<u8>> {
-    if encrypted.len() < 12 {
+    if encrypted.len() < NONCE_LEN {
         return None;
     }
-    
-    let nonce: [u8; 12] = encrypted[..12].try_into().ok()?;
-    let ciphertext = &encrypted[12..];
-    
-    let mut decrypted = Vec::with_capacity(ciphertext.len());
-    for (i, byte) in ciphertext.iter().enumerate() {
-        let key_byte = key[i % 32] ^ nonce[i % 12];
-        decrypted.push(byte ^ key_byte);
-    }
-    Some(decrypted)
+    let (nonce, ciphertext) = encrypted.split_at(NONCE_LEN);
+    let nonce = Nonce::try_assume_unique_for_key(nonce).ok()?;
+
+    let mut buffer = ciphertext.to_vec();
+    let plain = token_key(key)?.open_in_place(nonce, Aad::empty(), &mut buffer).ok()?;
+    Some(plain.to_vec())
 }
 
 /// Create a new sync session
 #[tauri::command]
 pub fn create_sync_session(name: String) -> Result<SyncSession, String> {
-    let key = generate_key();
+    let key: [u8; 32] = random_bytes()?;
     let session = SyncSession {
-        id: generate_id(),
+        id: generate_id()?,
         name,
         secret_key: URL_SAFE_NO_PAD.encode(key),
         created_at: SystemTime::now()
             .duration_since(UNIX_EPOCH)
             .unwrap_or_default()
             .as_secs(),
-        host_id: generate_id(),
+        host_id: generate_id()?,
     };
     Ok(session)
 }
@@ -139,6 +209,7 @@ pub fn generate_permission_link(
     session: SyncSession,
     role: SyncRole,
     expires_hours: Option<u64>,
+    scope: Option<PermissionScope>,
 ) -> Result<String, String> {
     let now = SystemTime::now()
         .duration_since(UNIX_EPOCH)
@@ -151,6 +222,7 @@ pub fn generate_permission_link(
         created_at: now,
         expires_at: expires_hours.map(|h| now + h * 3600),
         creator_id: session.host_id.clone(),
+        scope: scope.filter(|s| !s.is_unrestricted()),
     };
 
     let key: [u8; 32] = URL_SAFE_NO_PAD
@@ -160,7 +232,7 @@ pub fn generate_permission_link(
         .map_err(|_| "Invalid key length")?;
 
     let json = serde_json::to_vec(&token).map_err(|e| e.to_string())?;
-    let encrypted = encrypt_token(&json, &key);
+    let encrypted = encrypt_token(&json, &key)?;
     let encoded = URL_SAFE_NO_PAD.encode(&encrypted);
 
     // Format: rook://sync/{session_id}/{encrypted_token}
@@ -220,6 +292,240 @@ pub fn validate_permission(role: SyncRole, action: String) -> bool {
     }
 }
 
+/// Check a remote op against a peer's permission token
+///
+/// `layer_role` is the current role of the targeted layer, when the op refers
+/// to an existing layer by id. Scoped tokens require it for updates/deletes.
+pub fn check_op(
+    token: &PermissionToken,
+    op: &SyncOp,
+    layer_role: Option<LayerRole>,
+) -> Result<(), PermissionViolation> {
+    let scope = token.scope.clone().unwrap_or_default();
+
+    let require_edit = |page_index: usize, layer_id: Option<&str>| -> Result<(), PermissionViolation> {
+        match token.role {
+            SyncRole::Viewer => {
+                return Err(PermissionViolation::new(ViolationKind::ReadOnly, "Viewers cannot edit")
+                    .at(page_index, layer_id))
+            }
+            SyncRole::Commenter => {
+                return Err(PermissionViolation::new(
+                    ViolationKind::CommentOnly,
+                    "Commenters can only add or resolve comments",
+                )
+                .at(page_index, layer_id))
+            }
+            SyncRole::Editor => {}
+        }
+        if !scope.allows_page(page_index) {
+            return Err(PermissionViolation::new(
+                ViolationKind::PageOutOfScope,
+                format!("Page {} is outside this link's editable pages", page_index + 1),
+            )
+            .at(page_index, layer_id));
+        }
+        Ok(())
+    };
+
+    let require_role = |role: Option<LayerRole>, page_index: usize, layer_id: Option<&str>| {
+        if scope.layer_roles.is_empty() {
+            return Ok(());
+        }
+        match role {
+            Some(role) if scope.allows_role(role) => Ok(()),
+            Some(role) => Err(PermissionViolation::new(
+                ViolationKind::LayerRoleOutOfScope,
+                format!("Editing {} layers is not permitted", role),
+            )
+            .at(page_index, layer_id)),
+            None => Err(PermissionViolation::new(
+                ViolationKind::UnknownLayerRole,
+                "Layer role is required to validate a scoped edit",
+            )
+            .at(page_index, layer_id)),
+        }
+    };
+
+    match op {
+        SyncOp::LayerCreate { page_index, layer } => {
+            require_edit(*page_index, Some(&layer.id))?;
+            require_role(Some(layer.role), *page_index, Some(&layer.id))
+        }
+        SyncOp::LayerUpdate { page_index, layer_id, updates } => {
+            require_edit(*page_index, Some(layer_id))?;
+            require_role(layer_role, *page_index, Some(layer_id))?;
+            // Moving a layer into a role outside the scope is also an edit of that role
+            match updates.role {
+                Some(new_role) => require_role(Some(new_role), *page_index, Some(layer_id)),
+                None => Ok(()),
+            }
+        }
+        SyncOp::LayerDelete { page_index, layer_id } => {
+            require_edit(*page_index, Some(layer_id))?;
+            require_role(layer_role, *page_index, Some(layer_id))
+        }
+        SyncOp::LayerReorder { page_index, .. } => {
+            require_edit(*page_index, None)?;
+            if scope.layer_roles.is_empty() {
+                Ok(())
+            } else {
+                Err(PermissionViolation::new(
+                    ViolationKind::LayerRoleOutOfScope,
+                    "Reordering a whole page requires unrestricted layer roles",
+                )
+                .at(*page_index, None))
+            }
+        }
+        SyncOp::FullSync { .. } => {
+            if token.role.can_edit() && scope.is_unrestricted() {
+                Ok(())
+            } else {
+                Err(PermissionViolation::new(
+                    ViolationKind::HostOnly,
+                    "Full document sync requires unrestricted edit access",
+                ))
+            }
+        }
+        SyncOp::CommentAdd { page_index, .. } => {
+            if !token.role.can_comment() {
+                return Err(PermissionViolation::new(ViolationKind::ReadOnly, "Viewers cannot comment")
+                    .at(*page_index, None));
+            }
+            Ok(())
+        }
+        SyncOp::CommentResolve { .. } => {
+            if token.role.can_comment() {
+                Ok(())
+            } else {
+                Err(PermissionViolation::new(ViolationKind::ReadOnly, "Viewers cannot resolve comments"))
+            }
+        }
+        SyncOp::CursorMove { .. }
+        | SyncOp::SelectionChange { .. }
+        | SyncOp::Presence { .. }
+        | SyncOp::Ack { .. }
+        | SyncOp::CatchUp { .. } => Ok(()),
+    }
+}
+
+lazy_static::lazy_static! {
+    /// (session id, peer id) -> token presented by that peer
+    static ref PEER_PERMISSIONS: Arc<RwLock<HashMap<(String, String), PermissionToken>>> =
+        Arc::new(RwLock::new(HashMap::new()));
+}
+
+/// Check an incoming message against the token of the peer it came from
+///
+/// `peer_id` is the id signaling authenticated for the connection the
+/// message arrived on, never the `sender_id` the message claims; a message
+/// claiming another sender is refused. So are peers without a registered
+/// token, and everything when the registry can't be read.
+pub fn check_peer_message(
+    session_id: &str,
+    peer_id: &str,
+    message: &SyncMessage,
+    layer_role: Option<LayerRole>,
+) -> Result<(), PermissionViolation> {
+    if message.sender_id != peer_id {
+        return Err(PermissionViolation::new(
+            ViolationKind::SpoofedSender,
+            format!("Peer '{}' sent a message as '{}'", peer_id, message.sender_id),
+        ));
+    }
+    check_op(&peer_token(session_id, peer_id)?, &message.op, layer_role)
+}
+
+/// Check that a peer may push assets (images, fonts) into the session
+///
+/// Assets only back layers, so this takes an editor link; a page or role
+/// scope still leaves the peer something it may edit.
+pub fn check_peer_can_edit(session_id: &str, peer_id: &str) -> Result<(), PermissionViolation> {
+    match peer_token(session_id, peer_id)?.role {
+        SyncRole::Viewer => Err(PermissionViolation::new(ViolationKind::ReadOnly, "Viewers cannot send assets")),
+        SyncRole::Commenter => Err(PermissionViolation::new(
+            ViolationKind::CommentOnly,
+            "Commenters cannot send assets",
+        )),
+        SyncRole::Editor => Ok(()),
+    }
+}
+
+/// The token a peer registered, refusing unknown peers and everything when
+/// the registry can't be read
+fn peer_token(session_id: &str, peer_id: &str) -> Result<PermissionToken, PermissionViolation> {
+    let permissions = PEER_PERMISSIONS.read().map_err(|_| {
+        PermissionViolation::new(ViolationKind::UnknownPeer, "Peer permissions are unavailable")
+    })?;
+    permissions
+        .get(&(session_id.to_string(), peer_id.to_string()))
+        .cloned()
+        .ok_or_else(|| {
+            PermissionViolation::new(
+                ViolationKind::UnknownPeer,
+                format!("Peer '{}' has not presented a permission link", peer_id),
+            )
+        })
+}
+
+/// Verify the permission link a peer joined with and bind its token to the
+/// peer, for validating the peer's ops
+///
+/// `peer_id` is the id signaling authenticated for the connection the link
+/// arrived on. A peer keeps the link it redeemed until it is unregistered,
+/// so it can't swap in another one or take over the host's id.
+#[tauri::command]
+pub fn register_peer_permission(
+    peer_id: String,
+    link: String,
+    secret_key: String,
+) -> Result<PermissionToken, String> {
+    let token = parse_permission_link(link, secret_key)?;
+    let mut permissions = PEER_PERMISSIONS.write().map_err(|e| e.to_string())?;
+    match permissions.entry((token.session_id.clone(), peer_id)) {
+        std::collections::hash_map::Entry::Occupied(entry) => {
+            Err(format!("Peer '{}' has already redeemed a permission link", entry.key().1))
+        }
+        std::collections::hash_map::Entry::Vacant(entry) => Ok(entry.insert(token).clone()),
+    }
+}
+
+/// Trust ops from the session's host, as a joined peer holds no link for it
+#[tauri::command]
+pub fn register_session_host(session_id: String, host_id: String) -> Result<(), String> {
+    let token = PermissionToken {
+        session_id: session_id.clone(),
+        role: SyncRole::Editor,
+        created_at: SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .unwrap_or_default()
+            .as_secs(),
+        expires_at: None,
+        creator_id: host_id.clone(),
+        scope: None,
+    };
+    let mut permissions = PEER_PERMISSIONS.write().map_err(|e| e.to_string())?;
+    permissions.insert((session_id, host_id), token);
+    Ok(())
+}
+
+/// Forget a peer's token (e.g. when it leaves)
+#[tauri::command]
+pub fn unregister_peer_permission(session_id: String, peer_id: String) -> Result<bool, String> {
+    let mut permissions = PEER_PERMISSIONS.write().map_err(|e| e.to_string())?;
+    Ok(permissions.remove(&(session_id, peer_id)).is_some())
+}
+
+/// Validate a remote op against a token, returning a structured violation
+#[tauri::command]
+pub fn validate_remote_op(
+    token: PermissionToken,
+    op: SyncOp,
+    layer_role: Option<LayerRole>,
+) -> Result<(), PermissionViolation> {
+    check_op(&token, &op, layer_role)
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -235,7 +541,7 @@ mod tests {
     #[test]
     fn test_permission_link_roundtrip() {
         let session = create_sync_session("Test".to_string()).unwrap();
-        let link = generate_permission_link(session.clone(), SyncRole::Editor, None).unwrap();
+        let link = generate_permission_link(session.clone(), SyncRole::Editor, None, None).unwrap();
         
         assert!(link.starts_with("rook://sync/"));
         
@@ -244,6 +550,137 @@ mod tests {
         assert_eq!(token.session_id, session.id);
     }
 
+    #[test]
+    fn test_tampered_link_is_rejected() {
+        let session = create_sync_session("Test".to_string()).unwrap();
+        let link = generate_permission_link(session.clone(), SyncRole::Viewer, None, None).unwrap();
+        let (prefix, token) = link.rsplit_once('/').unwrap();
+        let mut bytes = URL_SAFE_NO_PAD.decode(token).unwrap();
+        let last = bytes.len() - 20;
+        bytes[last] ^= 1;
+        let tampered = format!("{}/{}", prefix, URL_SAFE_NO_PAD.encode(bytes));
+
+        assert!(parse_permission_link(tampered, session.secret_key.clone()).is_err());
+        let other = create_sync_session("Other".to_string()).unwrap();
+        assert!(parse_permission_link(link, other.secret_key).is_err());
+    }
+
+    #[test]
+    fn test_unregistered_peers_are_refused() {
+        let session = create_sync_session("Test".to_string()).unwrap();
+        let link = generate_permission_link(session.clone(), SyncRole::Viewer, None, None).unwrap();
+        let comment = SyncOp::CommentResolve { id: "c1".to_string() };
+        let message = |sender: &str| SyncMessage {
+            seq: 1,
+            timestamp: 0,
+            sender_id: sender.to_string(),
+            op: comment.clone(),
+        };
+
+        let refused = check_peer_message(&session.id, "peer-a", &message("peer-a"), None).unwrap_err();
+        assert_eq!(refused.kind, ViolationKind::UnknownPeer);
+
+        assert!(register_peer_permission("peer-a".to_string(), "rook://sync/x/y".to_string(), session.secret_key.clone()).is_err());
+        register_peer_permission("peer-a".to_string(), link.clone(), session.secret_key.clone()).unwrap();
+        // A viewer link doesn't allow resolving comments
+        assert_eq!(check_peer_message(&session.id, "peer-a", &message("peer-a"), None).unwrap_err().kind, ViolationKind::ReadOnly);
+
+        register_session_host(session.id.clone(), session.host_id.clone()).unwrap();
+        assert!(check_peer_message(&session.id, &session.host_id, &message(&session.host_id), None).is_ok());
+        // The viewer can't pass its messages off as the host's
+        let spoofed = check_peer_message(&session.id, "peer-a", &message(&session.host_id), None).unwrap_err();
+        assert_eq!(spoofed.kind, ViolationKind::SpoofedSender);
+        // Nor redeem a link under the host's id, or a second link for itself
+        let editor = generate_permission_link(session.clone(), SyncRole::Editor, None, None).unwrap();
+        assert!(register_peer_permission(session.host_id.clone(), editor.clone(), session.secret_key.clone()).is_err());
+        assert!(register_peer_permission("peer-a".to_string(), editor, session.secret_key.clone()).is_err());
+        assert!(unregister_peer_permission(session.id.clone(), "peer-a".to_string()).unwrap());
+        register_peer_permission("peer-a".to_string(), link, session.secret_key.clone()).unwrap();
+    }
+
+    #[test]
+    fn test_only_editors_send_assets() {
+        let session = create_sync_session("Test".to_string()).unwrap();
+        assert_eq!(check_peer_can_edit(&session.id, "peer-a").unwrap_err().kind, ViolationKind::UnknownPeer);
+
+        for (peer, role) in [("peer-a", SyncRole::Viewer), ("peer-b", SyncRole::Commenter), ("peer-c", SyncRole::Editor)] {
+            let link = generate_permission_link(session.clone(), role, None, None).unwrap();
+            register_peer_permission(peer.to_string(), link, session.secret_key.clone()).unwrap();
+        }
+        assert_eq!(check_peer_can_edit(&session.id, "peer-a").unwrap_err().kind, ViolationKind::ReadOnly);
+        assert_eq!(check_peer_can_edit(&session.id, "peer-b").unwrap_err().kind, ViolationKind::CommentOnly);
+        assert!(check_peer_can_edit(&session.id, "peer-c").is_ok());
+    }
+
+    #[test]
+    fn test_scoped_link_roundtrip() {
+        let session = create_sync_session("Test".to_string()).unwrap();
+        let scope = PermissionScope {
+            page_ranges: vec![(2, 4)],
+            layer_roles: vec![LayerRole::Annotation],
+        };
+        let link = generate_permission_link(session.clone(), SyncRole::Editor, None, Some(scope.clone())).unwrap();
+
+        let token = parse_permission_link(link, session.secret_key).unwrap();
+        assert_eq!(token.scope, Some(scope));
+    }
+
+    fn scoped_token(role: SyncRole, scope: Option<PermissionScope>) -> PermissionToken {
+        PermissionToken {
+            session_id: "s".to_string(),
+            role,
+            created_at: 0,
+            expires_at: None,
+            creator_id: "host".to_string(),
+            scope,
+        }
+    }
+
+    fn delete_op(page_index: usize) -> SyncOp {
+        SyncOp::LayerDelete {
+            page_index,
+            layer_id: "layer-1".to_string(),
+        }
+    }
+
+    #[test]
+    fn test_check_op_roles() {
+        let viewer = scoped_token(SyncRole::Viewer, None);
+        let err = check_op(&viewer, &delete_op(0), None).unwrap_err();
+        assert_eq!(err.kind, ViolationKind::ReadOnly);
+        assert!(check_op(&viewer, &SyncOp::Ack { seq: 1 }, None).is_ok());
+
+        let commenter = scoped_token(SyncRole::Commenter, None);
+        assert_eq!(
+            check_op(&commenter, &delete_op(0), None).unwrap_err().kind,
+            ViolationKind::CommentOnly
+        );
+        assert!(check_op(&commenter, &SyncOp::CommentResolve { id: "c".to_string() }, None).is_ok());
+    }
+
+    #[test]
+    fn test_check_op_scopes() {
+        let editor = scoped_token(
+            SyncRole::Editor,
+            Some(PermissionScope {
+                page_ranges: vec![(2, 4)],
+                layer_roles: vec![LayerRole::Annotation],
+            }),
+        );
+
+        let err = check_op(&editor, &delete_op(0), Some(LayerRole::Annotation)).unwrap_err();
+        assert_eq!(err.kind, ViolationKind::PageOutOfScope);
+        assert_eq!(err.page_index, Some(0));
+
+        let err = check_op(&editor, &delete_op(3), Some(LayerRole::Content)).unwrap_err();
+        assert_eq!(err.kind, ViolationKind::LayerRoleOutOfScope);
+
+        let err = check_op(&editor, &delete_op(3), None).unwrap_err();
+        assert_eq!(err.kind, ViolationKind::UnknownLayerRole);
+
+        assert!(check_op(&editor, &delete_op(3), Some(LayerRole::Annotation)).is_ok());
+    }
+
     #[test]
     fn test_role_permissions() {
         assert!(!SyncRole::Viewer.can_edit());
@@ -10,8 +10,13 @@ use std::sync::{Arc, RwLock};
 #[derive(Debug, Clone, Serialize, Deserialize)]
 #[serde(tag = "type", rename_all = "camelCase")]
 pub enum SignalMessage {
-    /// Join a session
-    Join { session_id: String, peer_id: String },
+    /// Join a session; the embedded server requires the secret from its join link
+    Join {
+        session_id: String,
+        peer_id: String,
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        secret: Option<String>,
+    },
     /// Leave a session
     Leave { session_id: String, peer_id: String },
     /// WebRTC offer
@@ -108,8 +113,8 @@ pub fn generate_peer_id() -> String {
 
 /// Create a join message
 #[tauri::command]
-pub fn create_join_message(session_id: String, peer_id: String) -> SignalMessage {
-    SignalMessage::Join { session_id, peer_id }
+pub fn create_join_message(session_id: String, peer_id: String, secret: Option<String>) -> SignalMessage {
+    SignalMessage::Join { session_id, peer_id, secret }
 }
 
 /// Create an offer message
@@ -184,13 +189,14 @@ mod tests {
         let msg = SignalMessage::Join {
             session_id: "test-session".to_string(),
             peer_id: "peer-123".to_string(),
+            secret: None,
         };
         
         let json = serialize_signal_message(msg.clone()).unwrap();
         let parsed = parse_signal_message(json).unwrap();
         
         match parsed {
-            SignalMessage::Join { session_id, peer_id } => {
+            SignalMessage::Join { session_id, peer_id, .. } => {
                 assert_eq!(session_id, "test-session");
                 assert_eq!(peer_id, "peer-123");
             }
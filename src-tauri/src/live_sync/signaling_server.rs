@@ -0,0 +1,640 @@
+//! Embedded Signaling Server - Zero-infrastructure LAN collaboration
+//!
+//! The host can run a lightweight WebSocket relay for `SignalMessage`s from the
+//! Tauri backend. Other machines join via a `rook://signal/...` link (rendered as
+//! a QR code by the frontend), and the client mode bridges a remote relay to
+//! frontend events.
+//!
+//! The server listens on every interface, so the link carries a random secret
+//! and only a `Join` for the hosted session that presents it is admitted.
+
+use super::signaling::{PeerInfo, SignalMessage};
+use super::websocket::{self, ClientStream, Message};
+use base64::{engine::general_purpose::URL_SAFE_NO_PAD, Engine};
+use futures_util::{SinkExt, StreamExt};
+use ring::rand::{SecureRandom, SystemRandom};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::net::{IpAddr, UdpSocket};
+use std::sync::{Arc, Mutex, RwLock};
+use std::time::{SystemTime, UNIX_EPOCH};
+use tauri::{AppHandle, Emitter};
+use tokio::net::{TcpListener, TcpStream};
+use tokio::sync::{mpsc, watch};
+
+/// Default port for the embedded signaling server
+pub const DEFAULT_SIGNALING_PORT: u16 = 47800;
+
+/// Info needed by other machines to reach the embedded server
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SignalingServerInfo {
+    pub session_id: String,
+    pub port: u16,
+    pub local_url: String,
+    pub lan_url: Option<String>,
+    pub join_link: String,
+    /// Secret a `Join` must carry
+    pub secret: String,
+}
+
+/// Parsed `rook://signal/{session_id}/{host:port}?secret={secret}` link
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SignalingLink {
+    pub session_id: String,
+    pub url: String,
+    pub secret: String,
+}
+
+/// Routes signaling messages between peers connected to the embedded server
+///
+/// Pure state machine - the socket layer feeds it messages by connection id and
+/// delivers whatever it returns.
+#[derive(Debug)]
+pub struct SignalRouter {
+    /// The hosted session, the only one peers may join
+    session_id: String,
+    secret: String,
+    /// session id -> peer id -> (connection id, info)
+    sessions: HashMap<String, HashMap<String, (u64, PeerInfo)>>,
+    /// connection id -> (session id, peer id)
+    connections: HashMap<u64, (String, String)>,
+}
+
+impl SignalRouter {
+    pub fn new(session_id: String, secret: String) -> Self {
+        Self {
+            session_id,
+            secret,
+            sessions: HashMap::new(),
+            connections: HashMap::new(),
+        }
+    }
+
+    /// Handle a message from a connection, returning (connection, message) deliveries
+    pub fn handle(&mut self, conn_id: u64, message: SignalMessage) -> Vec<(u64, SignalMessage)> {
+        match message {
+            SignalMessage::Join { session_id, peer_id, secret } => {
+                let admitted = session_id == self.session_id
+                    && secret_matches(secret.as_deref().unwrap_or(""), &self.secret);
+                if !admitted {
+                    return vec![(conn_id, error("Unknown session or wrong secret".to_string()))];
+                }
+
+                // Re-joining moves the connection to the new session
+                let mut out = self.disconnect(conn_id);
+
+                let peers = self.sessions.entry(session_id.clone()).or_default();
+                if let Some((existing, _)) = peers.get(&peer_id) {
+                    if *existing != conn_id {
+                        out.push((conn_id, error(format!("Peer id '{}' is already in use", peer_id))));
+                        return out;
+                    }
+                }
+
+                peers.insert(
+                    peer_id.clone(),
+                    (
+                        conn_id,
+                        PeerInfo {
+                            id: peer_id.clone(),
+                            role: "peer".to_string(),
+                            joined_at: now_millis(),
+                        },
+                    ),
+                );
+                self.connections.insert(conn_id, (session_id.clone(), peer_id));
+                out.extend(self.peer_list(&session_id));
+                out
+            }
+            SignalMessage::Leave { .. } => self.disconnect(conn_id),
+            SignalMessage::Offer { ref to, ref from, .. }
+            | SignalMessage::Answer { ref to, ref from, .. }
+            | SignalMessage::IceCandidate { ref to, ref from, .. } => {
+                let Some((session_id, peer_id)) = self.connections.get(&conn_id) else {
+                    return vec![(conn_id, error("Join a session before signaling".to_string()))];
+                };
+                if from != peer_id {
+                    return vec![(conn_id, error("Sender does not match joined peer id".to_string()))];
+                }
+
+                match self.sessions.get(session_id).and_then(|peers| peers.get(to)) {
+                    Some((target, _)) => vec![(*target, message.clone())],
+                    None => vec![(conn_id, error(format!("Peer '{}' not found", to)))],
+                }
+            }
+            SignalMessage::PeerList { .. } | SignalMessage::Error { .. } => {
+                vec![(conn_id, error("Unexpected message from client".to_string()))]
+            }
+        }
+    }
+
+    /// Remove a connection, notifying the rest of its session
+    pub fn disconnect(&mut self, conn_id: u64) -> Vec<(u64, SignalMessage)> {
+        let Some((session_id, peer_id)) = self.connections.remove(&conn_id) else {
+            return Vec::new();
+        };
+
+        if let Some(peers) = self.sessions.get_mut(&session_id) {
+            peers.remove(&peer_id);
+            if peers.is_empty() {
+                self.sessions.remove(&session_id);
+                return Vec::new();
+            }
+        }
+
+        self.peer_list(&session_id)
+    }
+
+    /// Number of peers currently joined to a session
+    pub fn peer_count(&self, session_id: &str) -> usize {
+        self.sessions.get(session_id).map(|p| p.len()).unwrap_or(0)
+    }
+
+    fn peer_list(&self, session_id: &str) -> Vec<(u64, SignalMessage)> {
+        let Some(peers) = self.sessions.get(session_id) else {
+            return Vec::new();
+        };
+
+        let mut list: Vec<PeerInfo> = peers.values().map(|(_, info)| info.clone()).collect();
+        list.sort_by(|a, b| a.joined_at.cmp(&b.joined_at).then_with(|| a.id.cmp(&b.id)));
+
+        peers
+            .values()
+            .map(|(conn, _)| (*conn, SignalMessage::PeerList { peers: list.clone() }))
+            .collect()
+    }
+}
+
+#[inline]
+fn error(message: String) -> SignalMessage {
+    SignalMessage::Error { message }
+}
+
+/// Compare without an early exit, so timing does not reveal a matching prefix
+fn secret_matches(given: &str, expected: &str) -> bool {
+    given.len() == expected.len()
+        && given
+            .bytes()
+            .zip(expected.bytes())
+            .fold(0u8, |acc, (a, b)| acc | (a ^ b))
+            == 0
+}
+
+fn generate_secret() -> Result<String, String> {
+    let mut bytes = [0u8; 24];
+    SystemRandom::new()
+        .fill(&mut bytes)
+        .map_err(|_| "Failed to generate signaling secret".to_string())?;
+    Ok(URL_SAFE_NO_PAD.encode(bytes))
+}
+
+#[inline]
+fn now_millis() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .unwrap_or_default()
+        .as_millis() as u64
+}
+
+/// Build a shareable join link for a server address
+pub fn build_signaling_link(session_id: &str, host: &str, port: u16, secret: &str) -> String {
+    format!("rook://signal/{}/{}:{}?secret={}", session_id, host, port, secret)
+}
+
+/// Best-effort LAN address (no packets are sent; connect only picks a route)
+fn detect_lan_ip() -> Option<IpAddr> {
+    let socket = UdpSocket::bind("0.0.0.0:0").ok()?;
+    socket.connect("8.8.8.8:80").ok()?;
+    socket
+        .local_addr()
+        .ok()
+        .map(|addr| addr.ip())
+        .filter(|ip| !ip.is_loopback() && !ip.is_unspecified())
+}
+
+// ============================================================================
+// SERVER
+// ============================================================================
+
+struct ServerShared {
+    router: Mutex<SignalRouter>,
+    outboxes: Mutex<HashMap<u64, mpsc::UnboundedSender<Message>>>,
+}
+
+impl ServerShared {
+    fn deliver(&self, routed: Vec<(u64, SignalMessage)>) {
+        let Ok(outboxes) = self.outboxes.lock() else {
+            return;
+        };
+        for (conn_id, message) in routed {
+            if let (Some(tx), Ok(json)) = (outboxes.get(&conn_id), serde_json::to_string(&message)) {
+                let _ = tx.send(Message::Text(json));
+            }
+        }
+    }
+}
+
+struct ServerHandle {
+    info: SignalingServerInfo,
+    shutdown: watch::Sender<bool>,
+}
+
+lazy_static::lazy_static! {
+    static ref SIGNALING_SERVER: Arc<RwLock<Option<ServerHandle>>> = Arc::new(RwLock::new(None));
+    static ref SIGNALING_CLIENT: Arc<RwLock<Option<mpsc::UnboundedSender<Message>>>> = Arc::new(RwLock::new(None));
+}
+
+async fn run_server(listener: TcpListener, router: SignalRouter, mut shutdown: watch::Receiver<bool>) {
+    let shared = Arc::new(ServerShared {
+        router: Mutex::new(router),
+        outboxes: Mutex::new(HashMap::new()),
+    });
+    let mut next_conn_id: u64 = 0;
+
+    loop {
+        tokio::select! {
+            _ = shutdown.changed() => break,
+            accepted = listener.accept() => {
+                if let Ok((stream, _)) = accepted {
+                    next_conn_id += 1;
+                    tokio::spawn(handle_connection(stream, next_conn_id, shared.clone(), shutdown.clone()));
+                }
+            }
+        }
+    }
+}
+
+async fn handle_connection(
+    stream: TcpStream,
+    conn_id: u64,
+    shared: Arc<ServerShared>,
+    mut shutdown: watch::Receiver<bool>,
+) {
+    let Ok(ws) = websocket::accept(stream).await else {
+        return;
+    };
+
+    let (mut sink, mut source) = ws.split();
+    let (tx, mut rx) = mpsc::unbounded_channel::<Message>();
+    if let Ok(mut outboxes) = shared.outboxes.lock() {
+        outboxes.insert(conn_id, tx.clone());
+    }
+
+    let writer_task = tokio::spawn(async move {
+        while let Some(message) = rx.recv().await {
+            let is_close = matches!(message, Message::Close(_));
+            if sink.send(message).await.is_err() || is_close {
+                break;
+            }
+        }
+    });
+
+    loop {
+        let message = tokio::select! {
+            _ = shutdown.changed() => break,
+            message = source.next() => message,
+        };
+
+        match message {
+            Some(Ok(Message::Text(text))) => {
+                let routed = match serde_json::from_str::<SignalMessage>(&text) {
+                    Ok(message) => shared
+                        .router
+                        .lock()
+                        .map(|mut router| router.handle(conn_id, message))
+                        .unwrap_or_default(),
+                    Err(e) => vec![(conn_id, error(format!("Invalid signal message: {}", e)))],
+                };
+                shared.deliver(routed);
+            }
+            Some(Ok(Message::Close(_))) | Some(Err(_)) | None => break,
+            // Pings are answered by tungstenite
+            Some(Ok(_)) => {}
+        }
+    }
+
+    let routed = shared
+        .router
+        .lock()
+        .map(|mut router| router.disconnect(conn_id))
+        .unwrap_or_default();
+    if let Ok(mut outboxes) = shared.outboxes.lock() {
+        outboxes.remove(&conn_id);
+    }
+    shared.deliver(routed);
+
+    let _ = tx.send(Message::Close(None));
+    drop(tx);
+    let _ = writer_task.await;
+}
+
+// ============================================================================
+// CLIENT
+// ============================================================================
+
+async fn run_client(
+    ws: ClientStream,
+    tx: mpsc::UnboundedSender<Message>,
+    mut rx: mpsc::UnboundedReceiver<Message>,
+    app_handle: AppHandle,
+) {
+    let (mut sink, mut source) = ws.split();
+
+    let writer_task = tokio::spawn(async move {
+        while let Some(message) = rx.recv().await {
+            let is_close = matches!(message, Message::Close(_));
+            if sink.send(message).await.is_err() || is_close {
+                break;
+            }
+        }
+    });
+
+    loop {
+        match source.next().await {
+            Some(Ok(Message::Text(text))) => match serde_json::from_str::<SignalMessage>(&text) {
+                Ok(message) => {
+                    let _ = app_handle.emit("signal_message", message);
+                }
+                Err(e) => {
+                    let _ = app_handle.emit(
+                        "signal_message",
+                        error(format!("Invalid signal message from server: {}", e)),
+                    );
+                }
+            },
+            Some(Ok(Message::Close(_))) | Some(Err(_)) | None => break,
+            Some(Ok(_)) => {}
+        }
+    }
+
+    // Only clear the global client if it hasn't been replaced by a newer connection
+    if let Ok(mut client) = SIGNALING_CLIENT.write() {
+        if client.as_ref().map(|c| c.same_channel(&tx)).unwrap_or(false) {
+            *client = None;
+        }
+    }
+    drop(tx);
+    writer_task.abort();
+
+    let _ = app_handle.emit(
+        "signaling_status",
+        serde_json::json!({ "connected": false }),
+    );
+}
+
+// ============================================================================
+// TAURI COMMANDS
+// ============================================================================
+
+/// Start the embedded signaling server on the LAN
+#[tauri::command]
+pub async fn start_signaling_server(
+    session_id: String,
+    port: Option<u16>,
+) -> Result<SignalingServerInfo, String> {
+    if let Some(handle) = SIGNALING_SERVER.read().map_err(|e| e.to_string())?.as_ref() {
+        return Err(format!(
+            "Signaling server already running on port {}",
+            handle.info.port
+        ));
+    }
+
+    let listener = TcpListener::bind(("0.0.0.0", port.unwrap_or(DEFAULT_SIGNALING_PORT)))
+        .await
+        .map_err(|e| format!("Failed to bind signaling server: {}", e))?;
+    let port = listener.local_addr().map_err(|e| e.to_string())?.port();
+
+    let lan_ip = detect_lan_ip();
+    let host = lan_ip
+        .map(|ip| ip.to_string())
+        .unwrap_or_else(|| "127.0.0.1".to_string());
+    let secret = generate_secret()?;
+    let info = SignalingServerInfo {
+        session_id: session_id.clone(),
+        port,
+        local_url: format!("ws://127.0.0.1:{}", port),
+        lan_url: lan_ip.map(|ip| format!("ws://{}:{}", ip, port)),
+        join_link: build_signaling_link(&session_id, &host, port, &secret),
+        secret: secret.clone(),
+    };
+
+    let (shutdown, shutdown_rx) = watch::channel(false);
+    {
+        let mut server = SIGNALING_SERVER.write().map_err(|e| e.to_string())?;
+        if server.is_some() {
+            return Err("Signaling server already running".to_string());
+        }
+        *server = Some(ServerHandle {
+            info: info.clone(),
+            shutdown,
+        });
+    }
+
+    let router = SignalRouter::new(session_id, secret);
+    tauri::async_runtime::spawn(run_server(listener, router, shutdown_rx));
+    Ok(info)
+}
+
+/// Stop the embedded signaling server, returning whether one was running
+#[tauri::command]
+pub fn stop_signaling_server() -> Result<bool, String> {
+    let handle = SIGNALING_SERVER.write().map_err(|e| e.to_string())?.take();
+    match handle {
+        Some(handle) => {
+            let _ = handle.shutdown.send(true);
+            Ok(true)
+        }
+        None => Ok(false),
+    }
+}
+
+/// Get info about the running embedded server
+#[tauri::command]
+pub fn get_signaling_server_info() -> Result<Option<SignalingServerInfo>, String> {
+    let server = SIGNALING_SERVER.read().map_err(|e| e.to_string())?;
+    Ok(server.as_ref().map(|h| h.info.clone()))
+}
+
+/// Parse a `rook://signal/...` join link
+#[tauri::command]
+pub fn parse_signaling_link(link: String) -> Result<SignalingLink, String> {
+    let rest = link
+        .strip_prefix("rook://signal/")
+        .ok_or_else(|| "Invalid signaling link format".to_string())?;
+    let (session_id, address) = rest
+        .split_once('/')
+        .ok_or_else(|| "Signaling link is missing server address".to_string())?;
+    let (address, secret) = address
+        .split_once("?secret=")
+        .ok_or_else(|| "Signaling link is missing its secret".to_string())?;
+
+    if session_id.is_empty() || address.is_empty() || secret.is_empty() {
+        return Err("Invalid signaling link format".to_string());
+    }
+
+    Ok(SignalingLink {
+        session_id: session_id.to_string(),
+        url: format!("ws://{}", address.trim_end_matches('/')),
+        secret: secret.to_string(),
+    })
+}
+
+/// Connect to a signaling server; incoming messages are emitted as `signal_message`
+#[tauri::command]
+pub async fn connect_signaling_server(url: String, app_handle: AppHandle) -> Result<(), String> {
+    let ws = websocket::connect(&url).await?;
+
+    let (tx, rx) = mpsc::unbounded_channel::<Message>();
+    let previous = SIGNALING_CLIENT
+        .write()
+        .map_err(|e| e.to_string())?
+        .replace(tx.clone());
+    if let Some(previous) = previous {
+        let _ = previous.send(Message::Close(None));
+    }
+
+    let _ = app_handle.emit(
+        "signaling_status",
+        serde_json::json!({ "connected": true, "url": url }),
+    );
+    tauri::async_runtime::spawn(run_client(ws, tx, rx, app_handle));
+    Ok(())
+}
+
+/// Send a message through the connected signaling server
+#[tauri::command]
+pub fn send_signal_message(message: SignalMessage) -> Result<(), String> {
+    let json = serde_json::to_string(&message).map_err(|e| e.to_string())?;
+    let client = SIGNALING_CLIENT.read().map_err(|e| e.to_string())?;
+    client
+        .as_ref()
+        .ok_or_else(|| "Not connected to a signaling server".to_string())?
+        .send(Message::Text(json))
+        .map_err(|_| "Signaling connection closed".to_string())
+}
+
+/// Disconnect from the signaling server
+#[tauri::command]
+pub fn disconnect_signaling_server() -> Result<(), String> {
+    if let Some(client) = SIGNALING_CLIENT.write().map_err(|e| e.to_string())?.take() {
+        let _ = client.send(Message::Close(None));
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn router() -> SignalRouter {
+        SignalRouter::new("session-1".to_string(), "secret".to_string())
+    }
+
+    fn join(router: &mut SignalRouter, conn: u64, peer: &str) -> Vec<(u64, SignalMessage)> {
+        router.handle(
+            conn,
+            SignalMessage::Join {
+                session_id: "session-1".to_string(),
+                peer_id: peer.to_string(),
+                secret: Some("secret".to_string()),
+            },
+        )
+    }
+
+    #[test]
+    fn test_join_broadcasts_peer_list() {
+        let mut router = router();
+        join(&mut router, 1, "peer-a");
+        let out = join(&mut router, 2, "peer-b");
+
+        assert_eq!(out.len(), 2);
+        assert_eq!(router.peer_count("session-1"), 2);
+        for (_, msg) in out {
+            match msg {
+                SignalMessage::PeerList { peers } => assert_eq!(peers.len(), 2),
+                _ => panic!("Expected peer list"),
+            }
+        }
+    }
+
+    #[test]
+    fn test_join_requires_session_secret() {
+        let mut router = router();
+        for (session_id, secret) in [
+            ("session-1", None),
+            ("session-1", Some("wrong!")),
+            ("session-2", Some("secret")),
+        ] {
+            let out = router.handle(
+                1,
+                SignalMessage::Join {
+                    session_id: session_id.to_string(),
+                    peer_id: "peer-a".to_string(),
+                    secret: secret.map(str::to_string),
+                },
+            );
+            assert!(matches!(out[..], [(1, SignalMessage::Error { .. })]));
+        }
+        assert_eq!(router.peer_count("session-1"), 0);
+        assert_eq!(router.peer_count("session-2"), 0);
+    }
+
+    #[test]
+    fn test_offer_routed_to_target() {
+        let mut router = router();
+        join(&mut router, 1, "peer-a");
+        join(&mut router, 2, "peer-b");
+
+        let out = router.handle(
+            1,
+            SignalMessage::Offer {
+                to: "peer-b".to_string(),
+                from: "peer-a".to_string(),
+                sdp: "v=0".to_string(),
+            },
+        );
+        assert_eq!(out.len(), 1);
+        assert_eq!(out[0].0, 2);
+
+        // Spoofed sender is rejected
+        let out = router.handle(
+            1,
+            SignalMessage::Offer {
+                to: "peer-b".to_string(),
+                from: "peer-b".to_string(),
+                sdp: "v=0".to_string(),
+            },
+        );
+        assert!(matches!(out[0], (1, SignalMessage::Error { .. })));
+    }
+
+    #[test]
+    fn test_disconnect_notifies_remaining_peers() {
+        let mut router = router();
+        join(&mut router, 1, "peer-a");
+        join(&mut router, 2, "peer-b");
+
+        let out = router.disconnect(1);
+        assert_eq!(out.len(), 1);
+        assert_eq!(out[0].0, 2);
+        assert_eq!(router.peer_count("session-1"), 1);
+
+        router.disconnect(2);
+        assert_eq!(router.peer_count("session-1"), 0);
+    }
+
+    #[test]
+    fn test_signaling_link_roundtrip() {
+        let secret = generate_secret().unwrap();
+        let link = build_signaling_link("abc123", "192.168.1.5", 47800, &secret);
+        let parsed = parse_signaling_link(link).unwrap();
+        assert_eq!(parsed.session_id, "abc123");
+        assert_eq!(parsed.url, "ws://192.168.1.5:47800");
+        assert_eq!(parsed.secret, secret);
+
+        assert!(parse_signaling_link("rook://sync/abc".to_string()).is_err());
+        assert!(parse_signaling_link("rook://signal/abc123/192.168.1.5:47800".to_string()).is_err());
+    }
+}
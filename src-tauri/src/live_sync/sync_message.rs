@@ -1,6 +1,7 @@
 //! Sync Message Types - Data channel message formats for real-time collaboration
 
 use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
 use crate::models::{Bounds, LayerObject, LayerUpdates};
 
 /// Sync operation types
@@ -29,6 +30,8 @@ pub enum SyncOp {
     Presence { peer_id: String, name: String, color: String, active: bool },
     /// Ack message
     Ack { seq: u64 },
+    /// Catch-up request with the sender's state vector (per sender, the seq up to which it has every op)
+    CatchUp { state_vector: HashMap<String, u64> },
 }
 
 /// Page sync data (minimal for initial sync)
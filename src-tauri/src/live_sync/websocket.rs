@@ -0,0 +1,73 @@
+//! WebSocket Transport - tokio-tungstenite with limits for the embedded signaling server
+//!
+//! Framing, fragmentation, control frames and the opening handshake are left to
+//! tungstenite (which also answers pings); this module only fixes the size limits
+//! so a peer cannot make the app buffer more than a signaling message needs.
+
+use tokio::io::{AsyncRead, AsyncWrite};
+use tokio::net::TcpStream;
+use tokio_tungstenite::tungstenite::protocol::WebSocketConfig;
+use tokio_tungstenite::{MaybeTlsStream, WebSocketStream};
+
+pub use tokio_tungstenite::tungstenite::Message;
+
+/// Signaling payloads are small SDP/ICE blobs; reject anything larger
+pub const MAX_MESSAGE_SIZE: usize = 1024 * 1024;
+
+/// Client side of a signaling connection
+pub type ClientStream = WebSocketStream<MaybeTlsStream<TcpStream>>;
+
+fn config() -> WebSocketConfig {
+    WebSocketConfig {
+        max_message_size: Some(MAX_MESSAGE_SIZE),
+        max_frame_size: Some(MAX_MESSAGE_SIZE),
+        ..WebSocketConfig::default()
+    }
+}
+
+/// Perform the server side of the opening handshake
+pub async fn accept<S>(stream: S) -> Result<WebSocketStream<S>, String>
+where
+    S: AsyncRead + AsyncWrite + Unpin,
+{
+    tokio_tungstenite::accept_async_with_config(stream, Some(config()))
+        .await
+        .map_err(|e| format!("WebSocket handshake failed: {}", e))
+}
+
+/// Connect to a `ws://host:port/path` URL
+pub async fn connect(url: &str) -> Result<ClientStream, String> {
+    if !url.starts_with("ws://") {
+        return Err("Only ws:// URLs are supported".to_string());
+    }
+    let (stream, _) = tokio_tungstenite::connect_async_with_config(url, Some(config()), false)
+        .await
+        .map_err(|e| format!("Failed to connect to signaling server: {}", e))?;
+    Ok(stream)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use futures_util::{SinkExt, StreamExt};
+
+    #[tokio::test]
+    async fn test_rejects_oversized_messages() {
+        let (client, server) = tokio::io::duplex(64 * 1024);
+        let server = tokio::spawn(async move {
+            let mut ws = accept(server).await.unwrap();
+            let first = ws.next().await.and_then(Result::ok);
+            let second = ws.next().await.map(|r| r.is_err());
+            (first, second)
+        });
+
+        let (mut ws, _) = tokio_tungstenite::client_async("ws://localhost/", client).await.unwrap();
+        ws.send(Message::Text("{\"type\":\"leave\"}".to_string())).await.unwrap();
+        // The server drops the connection mid-frame, so this send may fail too
+        let _ = ws.send(Message::Binary(vec![0; MAX_MESSAGE_SIZE + 1])).await;
+
+        let (first, second) = server.await.unwrap();
+        assert_eq!(first, Some(Message::Text("{\"type\":\"leave\"}".to_string())));
+        assert_eq!(second, Some(true));
+    }
+}
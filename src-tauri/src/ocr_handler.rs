@@ -1,12 +1,11 @@
 //! OCR Handler Module - Enhanced
 //! Provides text verification and recovery using Tesseract OCR with word-level detection
 
-use crate::models::{Bounds, LayerObject, LayerType, SourceType, LayerRole, TextAlign};
+use crate::models::{Bounds, LayerObject, LayerType, SourceType, LayerRole, TextAlign, OcrInfo, OcrReviewStatus, OcrWordConfidence};
 use image::{GrayImage, RgbaImage, DynamicImage, imageops};
 use pdfium_render::prelude::PdfRenderConfig;
-use std::sync::atomic::{AtomicUsize, Ordering};
-
-static OCR_COUNTER: AtomicUsize = AtomicUsize::new(0);
+use vortex_core::layer_ids::LayerIds;
+use vortex_core::ocr_correction::{OcrCorrector, OcrDictionary};
 
 /// OCR result for a text region with word-level data
 #[derive(Debug, Clone)]
@@ -33,16 +32,23 @@ pub struct OcrConfig {
     pub preprocess: bool,
     pub deskew: bool,
     pub psm: i32, // Page segmentation mode
+    /// Fix common misreadings before layers are created
+    pub post_correction: bool,
+    /// Project corrections, applied on top of the app-wide dictionary
+    pub dictionary: OcrDictionary,
 }
 
 impl Default for OcrConfig {
     fn default() -> Self {
+        let settings = crate::settings::current();
         Self {
-            language: "eng".to_string(),
-            min_confidence: 0.5,
+            language: settings.ocr_language,
+            min_confidence: settings.ocr_min_confidence,
             preprocess: true,
             deskew: false,
             psm: 3, // Fully automatic page segmentation
+            post_correction: settings.ocr_post_correction,
+            dictionary: OcrDictionary::default(),
         }
     }
 }
@@ -50,25 +56,39 @@ impl Default for OcrConfig {
 /// OCR engine wrapper with enhanced capabilities
 pub struct OcrEngine {
     config: OcrConfig,
+    corrector: Option<OcrCorrector>,
     #[allow(dead_code)]
     initialized: bool,
 }
 
 impl OcrEngine {
     pub fn new() -> Self {
-        Self {
-            config: OcrConfig::default(),
-            initialized: false,
-        }
+        Self::with_config(OcrConfig::default())
     }
 
     pub fn with_config(config: OcrConfig) -> Self {
+        let corrector = config.post_correction.then(|| {
+            let settings = crate::settings::current();
+            OcrCorrector::new(&config.language, &[&settings.ocr_dictionary, &config.dictionary])
+        });
         Self {
             config,
+            corrector,
             initialized: false,
         }
     }
 
+    /// Apply post-correction to recognized words, in reading order
+    fn correct_words(&self, words: &mut [OcrWord]) {
+        if let Some(corrector) = &self.corrector {
+            let texts: Vec<&str> = words.iter().map(|w| w.text.as_str()).collect();
+            let corrected = corrector.correct_words(&texts);
+            for (word, text) in words.iter_mut().zip(corrected) {
+                word.text = text;
+            }
+        }
+    }
+
     /// Preprocess image for better OCR results
     fn preprocess_image(&self, image: &GrayImage) -> GrayImage {
         let mut processed = image.clone();
@@ -109,12 +129,19 @@ impl OcrEngine {
 
         // Perform OCR
         match perform_tesseract_ocr_enhanced(&processed, &self.config) {
-            Ok((text, confidence, words)) => Ok(OcrResult {
-                text,
-                confidence,
-                bounds: region.clone(),
-                words,
-            }),
+            Ok((text, confidence, mut words)) => {
+                self.correct_words(&mut words);
+                let text = match &self.corrector {
+                    Some(corrector) => corrector.correct_text(&text),
+                    None => text,
+                };
+                Ok(OcrResult {
+                    text,
+                    confidence,
+                    bounds: region.clone(),
+                    words,
+                })
+            }
             Err(e) => Err(format!("OCR failed: {}", e)),
         }
     }
@@ -129,9 +156,11 @@ impl OcrEngine {
         let gray = DynamicImage::ImageRgba8(image.clone()).to_luma8();
         let processed = self.preprocess_image(&gray);
 
-        let (_, _, words) = perform_tesseract_ocr_enhanced(&processed, &self.config)?;
+        let (_, _, mut words) = perform_tesseract_ocr_enhanced(&processed, &self.config)?;
+        self.correct_words(&mut words);
 
         let mut layers = Vec::new();
+        let mut ids = LayerIds::new(page_index);
         let mut current_line: Vec<OcrWord> = Vec::new();
         let mut last_y: Option<f32> = None;
         let line_threshold = 10.0 * scale;
@@ -147,7 +176,7 @@ impl OcrEngine {
             if let Some(ly) = last_y {
                 if (word_y - ly).abs() > line_threshold && !current_line.is_empty() {
                     // New line detected, create layer from current line
-                    if let Some(layer) = create_line_layer(&current_line, page_index, scale) {
+                    if let Some(layer) = create_line_layer(&current_line, &mut ids, layers.len(), scale) {
                         layers.push(layer);
                     }
                     current_line.clear();
@@ -160,7 +189,7 @@ impl OcrEngine {
 
         // Don't forget the last line
         if !current_line.is_empty() {
-            if let Some(layer) = create_line_layer(&current_line, page_index, scale) {
+            if let Some(layer) = create_line_layer(&current_line, &mut ids, layers.len(), scale) {
                 layers.push(layer);
             }
         }
@@ -221,7 +250,7 @@ pub struct WordMatch {
 }
 
 /// Create a text layer from a line of OCR words
-fn create_line_layer(words: &[OcrWord], page_index: usize, scale: f32) -> Option<LayerObject> {
+fn create_line_layer(words: &[OcrWord], ids: &mut LayerIds, z_index: usize, scale: f32) -> Option<LayerObject> {
     if words.is_empty() {
         return None;
     }
@@ -237,24 +266,25 @@ fn create_line_layer(words: &[OcrWord], page_index: usize, scale: f32) -> Option
     let max_x = words.iter().map(|w| w.bounds.x + w.bounds.width).fold(0.0f32, f32::max);
     let max_y = words.iter().map(|w| w.bounds.y + w.bounds.height).fold(0.0f32, f32::max);
 
-    let _avg_confidence: f32 = words.iter().map(|w| w.confidence).sum::<f32>() / words.len() as f32;
+    let avg_confidence: f32 = words.iter().map(|w| w.confidence).sum::<f32>() / words.len() as f32;
     let avg_height = words.iter().map(|w| w.bounds.height).sum::<f32>() / words.len() as f32;
 
-    let idx = OCR_COUNTER.fetch_add(1, Ordering::SeqCst);
+    let bounds = Bounds::new(
+        min_x / scale,
+        min_y / scale,
+        (max_x - min_x) / scale,
+        (max_y - min_y) / scale,
+    );
 
     Some(LayerObject {
-        id: format!("ocr-{}-{}", page_index, idx),
+        id: ids.next("ocr", &text, Some(&bounds)),
         layer_type: LayerType::Text,
-        bounds: Bounds::new(
-            min_x / scale,
-            min_y / scale,
-            (max_x - min_x) / scale,
-            (max_y - min_y) / scale,
-        ),
+        bounds,
         visible: true,
         locked: false,
-        z_index: idx as i32,
+        z_index: z_index as i32,
         opacity: 1.0,
+        blend_mode: None,
         content: Some(text),
         font_family: Some("Arial".to_string()),
         font_size: Some((avg_height / scale).max(8.0).min(72.0)),
@@ -267,17 +297,43 @@ fn create_line_layer(words: &[OcrWord], page_index: usize, scale: f32) -> Option
         line_height: None,
         letter_spacing: None,
         background_color: None,
+        text_path: None,
+        text_outline: None,
+        text_shadow: None,
+        drop_cap: None,
+        notes: Vec::new(),
+        pagination: None,
         image_url: None,
         image_path: None,
         image_data: None,
+        image_link: None,
         shape_type: None,
         stroke_color: None,
         stroke_width: None,
         fill_color: None,
+        text_wrap: None,
         path_data: None,
         transform: None,
         source_type: SourceType::Extracted,
         role: LayerRole::Content,
+        ocr: Some(OcrInfo {
+            confidence: avg_confidence,
+            words: words
+                .iter()
+                .map(|w| OcrWordConfidence {
+                    text: w.text.clone(),
+                    confidence: w.confidence,
+                    bounds: Bounds::new(
+                        w.bounds.x / scale,
+                        w.bounds.y / scale,
+                        w.bounds.width / scale,
+                        w.bounds.height / scale,
+                    ),
+                })
+                .collect(),
+            review: OcrReviewStatus::Pending,
+        }),
+        tags: Vec::new(),
     })
 }
 
@@ -562,7 +618,7 @@ pub fn verify_page_text(
 
             match engine.verify_text(&image, &scaled_layer) {
                 Ok(verification) => results.push(verification),
-                Err(e) => eprintln!("OCR verification failed for {}: {}", layer.id, e),
+                Err(e) => tracing::warn!("OCR verification failed for {}: {}", layer.id, e),
             }
         }
     }
@@ -577,11 +633,6 @@ pub fn correct_text_with_ocr(layer: &mut LayerObject, verification: &TextVerific
     }
 }
 
-/// Reset OCR counter (call at start of each import)
-pub fn reset_ocr_counter() {
-    OCR_COUNTER.store(0, Ordering::SeqCst);
-}
-
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -623,6 +674,22 @@ mod tests {
         assert!(threshold >= 50 && threshold <= 200);
     }
 
+    #[test]
+    fn test_post_correction() {
+        let word = |text: &str| OcrWord { text: text.to_string(), confidence: 0.9, bounds: Bounds::new(0.0, 0.0, 10.0, 10.0) };
+        let mut words = vec![word("Tlie"), word("rnan"), word("tbe")];
+
+        let dictionary = OcrDictionary { words: vec!["tbe".to_string()], ..OcrDictionary::default() };
+        let config = OcrConfig { language: "eng".to_string(), post_correction: true, dictionary, ..OcrConfig::default() };
+        OcrEngine::with_config(config.clone()).correct_words(&mut words);
+        let texts: Vec<&str> = words.iter().map(|w| w.text.as_str()).collect();
+        assert_eq!(texts, ["The", "man", "tbe"]);
+
+        let mut raw = vec![word("rnan")];
+        OcrEngine::with_config(OcrConfig { post_correction: false, ..config }).correct_words(&mut raw);
+        assert_eq!(raw[0].text, "rnan");
+    }
+
     #[test]
     fn test_html_decode() {
         assert_eq!(html_decode("&amp;"), "&");
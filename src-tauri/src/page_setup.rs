@@ -0,0 +1,110 @@
+//! Page Setup Module
+//!
+//! Trim size presets, document resizing, reflow, layout guides, layout
+//! stamping and sections.
+//! The geometry lives in `vortex_core::page_setup`, `layout_guides`,
+//! `page_layout` and `sections`, shared with the wasm build; margins and
+//! bleed are stored in `ProjectSettings` and read by the DOCX importer and
+//! the PDF exporter.
+
+use crate::models::{Bounds, DocumentData, PageData, PageGuides};
+use vortex_core::layout_guides::{self, SnapResult};
+use vortex_core::models::Section;
+use vortex_core::page_layout::{self, PageLayoutOptions};
+use vortex_core::reflow;
+use vortex_core::sections::{self, SectionInfo, SectionsResult};
+
+pub use vortex_core::page_setup::{
+    page_size_presets, Margins, PageSetup, PageSizeInfo, PageSizePreset, ResizeMode,
+};
+
+/// List the built-in page size presets
+#[tauri::command]
+pub fn list_page_size_presets() -> Vec<PageSizeInfo> {
+    page_size_presets()
+}
+
+/// Change the trim size of a document
+///
+/// Layers are scaled to fit (default), re-centered, or left anchored at the
+/// top-left corner. Returns the resized document; the frontend owns state.
+#[tauri::command]
+pub async fn resize_document(
+    mut document: DocumentData,
+    width: f32,
+    height: f32,
+    mode: Option<ResizeMode>,
+) -> Result<DocumentData, String> {
+    tokio::task::spawn_blocking(move || {
+        vortex_core::page_setup::resize_document(&mut document, width, height, mode.unwrap_or_default())?;
+        Ok(document)
+    })
+    .await
+    .map_err(|e| format!("Resize task failed: {}", e))?
+}
+
+/// Repaginate the pages as one flow on pages of `page_setup` (US Letter
+/// with 1 in margins when absent), breaking text between pages as each
+/// paragraph's keep and widow/orphan controls allow
+#[tauri::command]
+pub async fn reflow_pages(pages: Vec<PageData>, page_setup: Option<PageSetup>) -> Result<Vec<PageData>, String> {
+    tokio::task::spawn_blocking(move || reflow::reflow(&pages, &page_setup.unwrap_or_default()))
+        .await
+        .map_err(|e| format!("Reflow task failed: {}", e))
+}
+
+/// Infer baseline grid, margin and column guides from the pages' layers and
+/// store them on each page's metadata; returns the updated pages
+#[tauri::command]
+pub async fn detect_layout_guides(mut pages: Vec<PageData>) -> Result<Vec<PageData>, String> {
+    tokio::task::spawn_blocking(move || {
+        layout_guides::apply_guides(&mut pages);
+        pages
+    })
+    .await
+    .map_err(|e| format!("Guide detection failed: {}", e))
+}
+
+/// Snap bounds being placed to a page's guides within `threshold` points
+/// (4 by default); `baseline` is a text layer's baseline below its top
+#[tauri::command]
+pub fn snap_to_guides(guides: PageGuides, bounds: Bounds, baseline: Option<f32>, threshold: Option<f32>) -> SnapResult {
+    layout_guides::snap_bounds(&guides, bounds, baseline, threshold.unwrap_or(layout_guides::DEFAULT_SNAP_THRESHOLD))
+}
+
+/// Copy the background, header and footer layers, page background and
+/// guides of page `source` onto the `targets`, replacing what was stamped
+/// before and keeping their content. Returns the updated pages.
+#[tauri::command]
+pub fn apply_page_layout(
+    mut pages: Vec<PageData>,
+    source: usize,
+    targets: Vec<usize>,
+    options: Option<PageLayoutOptions>,
+) -> Result<Vec<PageData>, String> {
+    page_layout::apply_page_layout(&mut pages, source, &targets, &options.unwrap_or_default())?;
+    Ok(pages)
+}
+
+/// The sections of a book in page order, with their page ranges, titles,
+/// chapter numbers and first page labels
+#[tauri::command]
+pub fn list_sections(pages: Vec<PageData>, sections: Vec<Section>) -> Vec<SectionInfo> {
+    sections::section_infos(&pages, &sections)
+}
+
+/// Add a section, or replace the one with the same id (a new id is made
+/// when it is empty), then relabel the pages and stamp the section master
+/// pages. Returns the updated pages and sections.
+#[tauri::command]
+pub fn set_section(mut pages: Vec<PageData>, mut sections: Vec<Section>, section: Section) -> Result<SectionsResult, String> {
+    let section_id = sections::set_section(&mut pages, &mut sections, section)?;
+    Ok(SectionsResult { pages, sections, section_id })
+}
+
+/// Remove a section; its pages join the section before
+#[tauri::command]
+pub fn remove_section(mut pages: Vec<PageData>, mut sections: Vec<Section>, id: String) -> Result<SectionsResult, String> {
+    sections::remove_section(&mut pages, &mut sections, &id)?;
+    Ok(SectionsResult { pages, sections, section_id: id })
+}
@@ -2,9 +2,17 @@
 //!
 //! Detects PDF content type (image-only, text-based, mixed, vector-heavy)
 //! and provides reconstruction strategies.
+//!
+//! Also provides a fast import preflight that inspects the PDF structure
+//! with lopdf (no pdfium, no layer building).
 
 use pdfium_render::prelude::*;
 use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, HashSet};
+
+/// Pages whose content streams have more operators than this are too heavy
+/// to import as vectors (typically CAD exports)
+pub const VECTOR_HEAVY_OPERATORS: usize = 100_000;
 
 /// PDF content type classification
 #[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
@@ -35,6 +43,9 @@ pub struct PageContentStats {
     pub image_coverage: f32,
     /// Ratio of text area to page area (0.0 - 1.0)  
     pub text_coverage: f32,
+    /// Printed page number from /PageLabels, e.g. "iv"
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub label: Option<String>,
 }
 
 /// Complete PDF analysis result
@@ -72,7 +83,7 @@ pub enum ReconstructionRecommendation {
 
 /// Analyze a PDF file and return content classification
 pub fn analyze_pdf(file_path: &str) -> Result<PdfAnalysis, String> {
-    let pdfium = Pdfium::default();
+    let pdfium = crate::pdf_engine::load_pdfium()?;
     let document = pdfium
         .load_pdf_from_file(file_path, None)
         .map_err(|e| format!("Failed to load PDF: {}", e))?;
@@ -201,6 +212,7 @@ fn analyze_page(page: &PdfPage, page_index: usize) -> PageContentStats {
         text_char_count,
         image_coverage: (image_area / page_area).min(1.0),
         text_coverage: (text_area / page_area).min(1.0),
+        label: page.label().map(str::to_string),
     }
 }
 
@@ -263,6 +275,424 @@ pub async fn analyze_pdf_content(file_path: String) -> Result<PdfAnalysis, Strin
     analyze_pdf(&file_path)
 }
 
+// ============================================================================
+// IMPORT PREFLIGHT - Quick structural analysis before a full import
+// ============================================================================
+
+/// The PDF standard 14 fonts, always available to viewers
+const STANDARD_FONTS: &[&str] = &[
+    "Times-Roman", "Times-Bold", "Times-Italic", "Times-BoldItalic",
+    "Helvetica", "Helvetica-Bold", "Helvetica-Oblique", "Helvetica-BoldOblique",
+    "Courier", "Courier-Bold", "Courier-Oblique", "Courier-BoldOblique",
+    "Symbol", "ZapfDingbats",
+];
+
+/// A font referenced by the document
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PreflightFont {
+    /// BaseFont as written in the PDF (may carry a subset prefix)
+    pub name: String,
+    pub family: String,
+    pub subtype: String,
+    pub embedded: bool,
+    /// Embedded, a standard 14 font, or installed on this system
+    pub available: bool,
+    pub pages: Vec<usize>,
+}
+
+/// Per-page preflight facts
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PreflightPage {
+    pub page_index: usize,
+    pub width: f32,
+    pub height: f32,
+    pub rotation: i64,
+    pub image_count: usize,
+    pub has_text: bool,
+    pub annotation_count: usize,
+    /// Content stream operators, a measure of how heavy the page is
+    pub operator_count: usize,
+}
+
+/// Distinct page size and how many pages use it
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct PageSizeCount {
+    pub width: f32,
+    pub height: f32,
+    pub count: usize,
+}
+
+/// Import preflight report
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ImportPreflight {
+    pub pdf_version: String,
+    pub file_size: u64,
+    pub page_count: usize,
+    pub page_sizes: Vec<PageSizeCount>,
+    pub pages: Vec<PreflightPage>,
+    pub fonts: Vec<PreflightFont>,
+    pub missing_font_count: usize,
+    pub image_count: usize,
+    /// Sum of (compressed) image stream sizes in bytes
+    pub image_bytes: u64,
+    pub has_forms: bool,
+    pub annotation_count: usize,
+    pub is_encrypted: bool,
+    /// Encrypted with a user password; page content could not be read
+    pub requires_password: bool,
+    /// Pages with images but no text, likely scans
+    pub image_only_pages: Vec<usize>,
+    pub ocr_recommendation: ReconstructionRecommendation,
+    /// Pages over `VECTOR_HEAVY_OPERATORS`, rasterized on import by default
+    pub vector_heavy_pages: Vec<usize>,
+}
+
+/// Look up a key, following a reference if needed
+fn resolve<'a>(doc: &'a lopdf::Document, dict: &'a lopdf::Dictionary, key: &[u8]) -> Option<&'a lopdf::Object> {
+    let obj = dict.get(key).ok()?;
+    doc.dereference(obj).ok().map(|(_, o)| o)
+}
+
+fn resolve_dict<'a>(doc: &'a lopdf::Document, dict: &'a lopdf::Dictionary, key: &[u8]) -> Option<&'a lopdf::Dictionary> {
+    resolve(doc, dict, key).and_then(|o| o.as_dict().ok())
+}
+
+fn name_of(obj: Option<&lopdf::Object>) -> String {
+    obj.and_then(|o| o.as_name().ok())
+        .map(|n| String::from_utf8_lossy(n).into_owned())
+        .unwrap_or_default()
+}
+
+/// Inherited page attribute (MediaBox, Rotate, ...)
+fn inherited<'a>(doc: &'a lopdf::Document, page: &'a lopdf::Dictionary, key: &[u8]) -> Option<&'a lopdf::Object> {
+    let mut node = page;
+    for _ in 0..32 {
+        if let Some(value) = resolve(doc, node, key) {
+            return Some(value);
+        }
+        node = resolve_dict(doc, node, b"Parent")?;
+    }
+    None
+}
+
+fn as_number(obj: &lopdf::Object) -> Option<f32> {
+    match obj {
+        lopdf::Object::Integer(i) => Some(*i as f32),
+        lopdf::Object::Real(r) => Some(*r),
+        _ => None,
+    }
+}
+
+/// Page size from the (inherited) MediaBox, Letter if missing
+fn media_box_size(doc: &lopdf::Document, page: &lopdf::Dictionary) -> (f32, f32) {
+    let media_box: Vec<f32> = inherited(doc, page, b"MediaBox")
+        .and_then(|o| o.as_array().ok())
+        .map(|a| a.iter().filter_map(as_number).collect())
+        .unwrap_or_default();
+    match media_box.as_slice() {
+        [x0, y0, x1, y1] => ((x1 - x0).abs(), (y1 - y0).abs()),
+        _ => (612.0, 792.0),
+    }
+}
+
+/// Page width and height in points
+pub fn page_dimensions(doc: &lopdf::Document, page_id: lopdf::ObjectId) -> (f32, f32) {
+    doc.get_dictionary(page_id)
+        .map(|page| media_box_size(doc, page))
+        .unwrap_or((612.0, 792.0))
+}
+
+/// Whether a font program is embedded (Type3 glyphs are always inline)
+fn font_is_embedded(doc: &lopdf::Document, font: &lopdf::Dictionary) -> bool {
+    let subtype = name_of(resolve(doc, font, b"Subtype"));
+    if subtype == "Type3" {
+        return true;
+    }
+    let descriptor_owner = if subtype == "Type0" {
+        resolve(doc, font, b"DescendantFonts")
+            .and_then(|o| o.as_array().ok())
+            .and_then(|a| a.first())
+            .and_then(|o| doc.dereference(o).ok())
+            .and_then(|(_, o)| o.as_dict().ok())
+    } else {
+        Some(font)
+    };
+    descriptor_owner
+        .and_then(|f| resolve_dict(doc, f, b"FontDescriptor"))
+        .map(|d| [&b"FontFile"[..], b"FontFile2", b"FontFile3"].iter().any(|k| d.has(k)))
+        .unwrap_or(false)
+}
+
+/// Cheap scan for text-showing operators in a content stream
+fn has_text_operators(content: &[u8]) -> bool {
+    content.windows(2).enumerate().any(|(i, w)| {
+        (w == b"Tj" || w == b"TJ")
+            && content.get(i + 2).map_or(true, |c| c.is_ascii_whitespace())
+            && i.checked_sub(1)
+                .and_then(|p| content.get(p))
+                .map_or(true, |c| c.is_ascii_whitespace() || matches!(c, b')' | b']' | b'>'))
+    })
+}
+
+/// Operand bytes: anything but whitespace and delimiters
+#[inline]
+fn is_regular(c: u8) -> bool {
+    !c.is_ascii_whitespace() && !b"()<>[]{}/%".contains(&c)
+}
+
+/// Cheap count of the operators in a content stream
+///
+/// Strings, names, numbers, arrays and dictionaries are skipped as operands,
+/// and so is inline image data.
+pub fn count_operators(content: &[u8]) -> usize {
+    let n = content.len();
+    let mut count = 0;
+    let mut i = 0;
+    while i < n {
+        match content[i] {
+            b'%' => {
+                while i < n && !matches!(content[i], b'\r' | b'\n') {
+                    i += 1;
+                }
+            }
+            b'(' => {
+                let mut depth = 0usize;
+                while i < n {
+                    match content[i] {
+                        b'\\' => i += 1,
+                        b'(' => depth += 1,
+                        b')' => {
+                            depth -= 1;
+                            if depth == 0 {
+                                break;
+                            }
+                        }
+                        _ => {}
+                    }
+                    i += 1;
+                }
+                i += 1;
+            }
+            b'<' if content.get(i + 1) != Some(&b'<') => {
+                while i < n && content[i] != b'>' {
+                    i += 1;
+                }
+                i += 1;
+            }
+            // Dictionary start
+            b'<' => i += 2,
+            b'/' => {
+                i += 1;
+                while i < n && is_regular(content[i]) {
+                    i += 1;
+                }
+            }
+            c if is_regular(c) => {
+                let start = i;
+                while i < n && is_regular(content[i]) {
+                    i += 1;
+                }
+                let token = &content[start..i];
+                if c.is_ascii_digit() || matches!(c, b'+' | b'-' | b'.') || matches!(token, b"true" | b"false" | b"null") {
+                    continue;
+                }
+                count += 1;
+                if token == b"ID" {
+                    // Inline image data runs to the next standalone EI
+                    while i + 2 <= n {
+                        let standalone = content[i - 1].is_ascii_whitespace()
+                            && content.get(i + 2).map_or(true, |c| c.is_ascii_whitespace());
+                        if standalone && &content[i..i + 2] == b"EI" {
+                            count += 1;
+                            break;
+                        }
+                        i += 1;
+                    }
+                    i += 2;
+                }
+            }
+            _ => i += 1,
+        }
+    }
+    count
+}
+
+/// Structural preflight of a loaded document
+///
+/// Font availability only accounts for embedded and standard fonts; callers
+/// refine it with a system font check.
+pub fn preflight_structure(doc: &lopdf::Document, file_size: u64) -> ImportPreflight {
+    let is_encrypted = doc.trailer.get(b"Encrypt").is_ok();
+    let page_ids = doc.get_pages();
+
+    let mut pages = Vec::with_capacity(page_ids.len());
+    let mut fonts: BTreeMap<String, PreflightFont> = BTreeMap::new();
+    let mut seen_images = HashSet::new();
+    let mut image_bytes = 0u64;
+    let mut image_count = 0usize;
+
+    for (idx, (_, &page_id)) in page_ids.iter().enumerate() {
+        let Ok(page) = doc.get_dictionary(page_id) else {
+            continue;
+        };
+
+        let (width, height) = media_box_size(doc, page);
+        let rotation = inherited(doc, page, b"Rotate")
+            .and_then(|o| o.as_i64().ok())
+            .unwrap_or(0);
+
+        for (_, font) in doc.get_page_fonts(page_id).unwrap_or_default() {
+            let name = name_of(resolve(doc, font, b"BaseFont"));
+            let name = if name.is_empty() { "Unnamed".to_string() } else { name };
+            let entry = fonts.entry(name.clone()).or_insert_with(|| {
+                let embedded = font_is_embedded(doc, font);
+                let base = crate::font_manager::normalizer::remove_subset_prefix(&name).to_string();
+                PreflightFont {
+                    family: crate::font_manager::normalizer::parse_font_name(&name).family,
+                    subtype: name_of(resolve(doc, font, b"Subtype")),
+                    available: embedded || STANDARD_FONTS.contains(&base.as_str()),
+                    embedded,
+                    name,
+                    pages: Vec::new(),
+                }
+            });
+            entry.pages.push(idx);
+        }
+
+        let mut page_images = 0;
+        let (resources, resource_ids) = doc.get_page_resources(page_id).unwrap_or((None, Vec::new()));
+        let resource_dicts = resources
+            .into_iter()
+            .chain(resource_ids.iter().filter_map(|id| doc.get_dictionary(*id).ok()));
+        for res in resource_dicts {
+            let Some(xobjects) = resolve_dict(doc, res, b"XObject") else {
+                continue;
+            };
+            for (_, value) in xobjects.iter() {
+                let Ok((id, obj)) = doc.dereference(value) else {
+                    continue;
+                };
+                let Ok(stream) = obj.as_stream() else {
+                    continue;
+                };
+                if name_of(stream.dict.get(b"Subtype").ok()) != "Image" {
+                    continue;
+                }
+                page_images += 1;
+                if id.map_or(true, |id| seen_images.insert(id)) {
+                    image_count += 1;
+                    image_bytes += stream.content.len() as u64;
+                }
+            }
+        }
+
+        let content = doc.get_page_content(page_id).unwrap_or_default();
+        let has_text = has_text_operators(&content);
+        let operator_count = count_operators(&content);
+        let annotation_count = doc.get_page_annotations(page_id).map(|a| a.len()).unwrap_or(0);
+
+        pages.push(PreflightPage {
+            page_index: idx,
+            width,
+            height,
+            rotation,
+            image_count: page_images,
+            has_text,
+            annotation_count,
+            operator_count,
+        });
+    }
+
+    let mut page_sizes: Vec<PageSizeCount> = Vec::new();
+    for page in &pages {
+        match page_sizes
+            .iter_mut()
+            .find(|s| (s.width - page.width).abs() < 0.5 && (s.height - page.height).abs() < 0.5)
+        {
+            Some(size) => size.count += 1,
+            None => page_sizes.push(PageSizeCount {
+                width: page.width,
+                height: page.height,
+                count: 1,
+            }),
+        }
+    }
+
+    let has_forms = doc
+        .catalog()
+        .ok()
+        .and_then(|c| resolve_dict(doc, c, b"AcroForm"))
+        .and_then(|f| resolve(doc, f, b"Fields"))
+        .and_then(|o| o.as_array().ok())
+        .is_some_and(|fields| !fields.is_empty());
+
+    let image_only_pages: Vec<usize> = pages
+        .iter()
+        .filter(|p| p.image_count > 0 && !p.has_text)
+        .map(|p| p.page_index)
+        .collect();
+    let ocr_recommendation = if image_only_pages.is_empty() {
+        ReconstructionRecommendation::None
+    } else if image_only_pages.len() == pages.len() {
+        ReconstructionRecommendation::OcrRequired
+    } else {
+        ReconstructionRecommendation::OcrVerification
+    };
+
+    let vector_heavy_pages: Vec<usize> = pages
+        .iter()
+        .filter(|p| p.operator_count > VECTOR_HEAVY_OPERATORS)
+        .map(|p| p.page_index)
+        .collect();
+
+    let fonts: Vec<PreflightFont> = fonts.into_values().collect();
+    ImportPreflight {
+        pdf_version: doc.version.clone(),
+        file_size,
+        page_count: pages.len(),
+        page_sizes,
+        annotation_count: pages.iter().map(|p| p.annotation_count).sum(),
+        pages,
+        missing_font_count: fonts.iter().filter(|f| !f.available).count(),
+        fonts,
+        image_count,
+        image_bytes,
+        has_forms,
+        requires_password: is_encrypted && page_ids.is_empty(),
+        is_encrypted,
+        image_only_pages,
+        ocr_recommendation,
+        vector_heavy_pages,
+    }
+}
+
+/// Preflight a PDF on disk, including a system font check
+pub fn preflight_pdf(file_path: &str) -> Result<ImportPreflight, String> {
+    let file_size = std::fs::metadata(file_path)
+        .map_err(|e| format!("Failed to read {}: {}", file_path, e))?
+        .len();
+    let doc = lopdf::Document::load(file_path).map_err(|e| format!("Failed to load PDF: {}", e))?;
+    let mut report = preflight_structure(&doc, file_size);
+
+    for font in report.fonts.iter_mut().filter(|f| !f.available) {
+        font.available = crate::font_manager::system::is_font_installed(&font.family);
+    }
+    report.missing_font_count = report.fonts.iter().filter(|f| !f.available).count();
+    Ok(report)
+}
+
+/// Quick analysis before committing to a full import
+#[tauri::command]
+pub async fn preflight_document(file_path: String) -> Result<ImportPreflight, String> {
+    tokio::task::spawn_blocking(move || preflight_pdf(&file_path))
+        .await
+        .map_err(|e| format!("Preflight task failed: {}", e))?
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -290,4 +720,92 @@ mod tests {
         let rec = determine_recommendation(&PdfContentType::ImageOnly, 0.9);
         assert_eq!(rec, ReconstructionRecommendation::OcrRequired);
     }
+
+    fn build_test_pdf() -> lopdf::Document {
+        use lopdf::{dictionary, Object, Stream};
+
+        let mut doc = lopdf::Document::with_version("1.7");
+        let pages_id = doc.new_object_id();
+        let helvetica = doc.add_object(dictionary! {
+            "Type" => "Font", "Subtype" => "Type1", "BaseFont" => "Helvetica",
+        });
+        let fancy = doc.add_object(dictionary! {
+            "Type" => "Font", "Subtype" => "TrueType", "BaseFont" => "ABCDEF+FancySerif-Bold",
+        });
+        let image = doc.add_object(Stream::new(
+            dictionary! { "Type" => "XObject", "Subtype" => "Image", "Width" => 2, "Height" => 2 },
+            vec![0u8; 12],
+        ));
+
+        let text = doc.add_object(Stream::new(dictionary! {}, b"BT /F1 12 Tf (Hi) Tj ET".to_vec()));
+        let page_text = doc.add_object(dictionary! {
+            "Type" => "Page",
+            "Parent" => pages_id,
+            "Contents" => text,
+            "Resources" => dictionary! {
+                "Font" => dictionary! { "F1" => helvetica, "F2" => fancy },
+            },
+        });
+        let scan = doc.add_object(Stream::new(dictionary! {}, b"q 595 0 0 842 0 0 cm /Im1 Do Q".to_vec()));
+        let page_scan = doc.add_object(dictionary! {
+            "Type" => "Page",
+            "Parent" => pages_id,
+            "MediaBox" => vec![0.into(), 0.into(), 595.into(), 842.into()],
+            "Contents" => scan,
+            "Resources" => dictionary! { "XObject" => dictionary! { "Im1" => image } },
+        });
+        doc.objects.insert(
+            pages_id,
+            Object::Dictionary(dictionary! {
+                "Type" => "Pages",
+                "Kids" => vec![page_text.into(), page_scan.into()],
+                "Count" => 2,
+                "MediaBox" => vec![0.into(), 0.into(), 612.into(), 792.into()],
+            }),
+        );
+        let catalog = doc.add_object(dictionary! { "Type" => "Catalog", "Pages" => pages_id });
+        doc.trailer.set("Root", catalog);
+        doc
+    }
+
+    #[test]
+    fn test_preflight_structure() {
+        let report = preflight_structure(&build_test_pdf(), 1024);
+
+        assert_eq!(report.page_count, 2);
+        assert_eq!(report.page_sizes.len(), 2);
+        assert_eq!((report.pages[0].width, report.pages[0].height), (612.0, 792.0));
+        assert!(report.pages[0].has_text);
+        assert!(!report.pages[1].has_text);
+        assert_eq!(report.image_count, 1);
+        assert_eq!(report.image_bytes, 12);
+        assert_eq!(report.image_only_pages, vec![1]);
+        assert_eq!(report.ocr_recommendation, ReconstructionRecommendation::OcrVerification);
+        assert_eq!((report.pages[0].operator_count, report.pages[1].operator_count), (4, 4));
+        assert!(report.vector_heavy_pages.is_empty());
+        assert!(!report.is_encrypted && !report.has_forms);
+
+        let helvetica = report.fonts.iter().find(|f| f.name == "Helvetica").unwrap();
+        assert!(helvetica.available && !helvetica.embedded);
+        let fancy = report.fonts.iter().find(|f| f.family == "FancySerif").unwrap();
+        assert!(!fancy.available);
+        assert_eq!(report.missing_font_count, 1);
+    }
+
+    #[test]
+    fn test_text_operator_scan() {
+        assert!(has_text_operators(b"BT (abc)Tj ET"));
+        assert!(has_text_operators(b"BT [(a) 10 (b)] TJ ET"));
+        assert!(!has_text_operators(b"q /Im1 Do Q"));
+        assert!(!has_text_operators(b"/FooTjBar Do"));
+    }
+
+    #[test]
+    fn test_operator_count() {
+        assert_eq!(count_operators(b"BT /F1 12 Tf (a (nested\\) Tj) ) Tj [(x) -5 <7a>] TJ ET"), 5);
+        assert_eq!(count_operators(b"0 0 m 10 10 l S % 1 2 re f\n1 0 0 RG true"), 4);
+        // Inline image bytes that happen to spell EI are data
+        assert_eq!(count_operators(b"BI /W 2 /H 1 /BPC 8 /CS /G ID \x00EI\xff EI Q"), 4);
+        assert_eq!(count_operators(b"<< /MCID 3 >> BDC EMC"), 2);
+    }
 }
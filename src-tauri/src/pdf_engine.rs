@@ -0,0 +1,299 @@
+//! PDF Engine Module
+//!
+//! Locates and binds the pdfium library. The search order is:
+//! 1. `ROOK_PDFIUM_PATH` environment variable
+//! 2. Library path from the app settings (`pdfiumPath`)
+//! 3. Bundled resource dir for the current OS
+//! 4. `lib/` next to the working directory (development builds)
+//! 5. System library
+//!
+//! When no pdfium library can be bound, imports fall back to a lopdf-only
+//! degraded mode (text, vectors and common images, no rendering).
+
+use crate::settings::{self, AppSettings};
+use pdfium_render::prelude::*;
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, RwLock};
+use tauri::{AppHandle, Emitter};
+
+/// Environment variable overriding the pdfium library location
+pub const PDFIUM_PATH_ENV: &str = "ROOK_PDFIUM_PATH";
+
+/// Where the library path was saved before it moved into the app settings
+const LEGACY_CONFIG_FILE: &str = "pdf_engine.json";
+
+/// Where a pdfium candidate came from
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+#[repr(u8)]
+pub enum LibrarySource {
+    Env = 0,
+    Config = 1,
+    Bundled = 2,
+    Development = 3,
+    System = 4,
+}
+
+/// Engine used for PDF import
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+#[repr(u8)]
+pub enum PdfEngine {
+    Pdfium = 0,
+    /// Degraded mode: no rendering, OCR or uncommon image formats
+    Lopdf = 1,
+}
+
+/// A library location to try
+#[derive(Debug, Clone, PartialEq)]
+pub struct LibraryCandidate {
+    pub source: LibrarySource,
+    /// Library file path, `None` for the system library
+    pub path: Option<PathBuf>,
+}
+
+/// Result of the latest pdfium lookup
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PdfEngineStatus {
+    pub engine: PdfEngine,
+    /// Whether imports run in lopdf-only degraded mode
+    pub degraded: bool,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub source: Option<LibrarySource>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub library_path: Option<String>,
+    /// Candidates tried, in order
+    pub searched: Vec<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub error: Option<String>,
+}
+
+/// Engine settings as saved by earlier versions
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct LegacyEngineConfig {
+    #[serde(default)]
+    library_path: Option<PathBuf>,
+}
+
+#[derive(Default)]
+struct EngineState {
+    resource_dir: Option<PathBuf>,
+    status: Option<PdfEngineStatus>,
+}
+
+lazy_static::lazy_static! {
+    static ref ENGINE_STATE: Arc<RwLock<EngineState>> = Arc::new(RwLock::new(EngineState::default()));
+}
+
+/// Remember the bundled resource dir, moving a library path saved in
+/// `legacy_dir` by earlier versions into the app settings
+pub fn init_pdf_engine(resource_dir: Option<PathBuf>, legacy_dir: Option<PathBuf>) -> Result<(), String> {
+    if let Some(legacy_path) = legacy_dir.map(|dir| dir.join(LEGACY_CONFIG_FILE)).filter(|p| p.exists()) {
+        let legacy: LegacyEngineConfig = fs::read(&legacy_path)
+            .ok()
+            .and_then(|data| serde_json::from_slice(&data).ok())
+            .unwrap_or_default();
+        if let Some(library_path) = legacy.library_path {
+            settings::update_with(|current| {
+                Ok(AppSettings { pdfium_path: current.pdfium_path.clone().or(Some(library_path)), ..current.clone() })
+            })?;
+        }
+        let _ = fs::remove_file(&legacy_path);
+    }
+
+    ENGINE_STATE.write().map_err(|e| e.to_string())?.resource_dir = resource_dir;
+    Ok(())
+}
+
+/// Bundled library dir for the current OS, relative to a `lib/` root
+fn bundled_library_dir() -> &'static str {
+    if cfg!(target_os = "windows") {
+        "pdfium-v8-win/bin"
+    } else if cfg!(target_os = "macos") {
+        "pdfium-v8-mac/lib"
+    } else {
+        "pdfium-v8-linux/lib"
+    }
+}
+
+/// Accept either a library file or a directory containing it
+fn library_file(path: &Path) -> PathBuf {
+    if path.is_file() || path.extension().is_some() {
+        path.to_path_buf()
+    } else {
+        Pdfium::pdfium_platform_library_name_at_path(path)
+    }
+}
+
+/// Candidates in search order
+pub fn candidate_paths(
+    env_path: Option<PathBuf>,
+    config_path: Option<PathBuf>,
+    resource_dir: Option<&Path>,
+) -> Vec<LibraryCandidate> {
+    let mut candidates = Vec::with_capacity(6);
+    let mut push = |source, path: PathBuf| {
+        candidates.push(LibraryCandidate { source, path: Some(library_file(&path)) });
+    };
+
+    if let Some(path) = env_path {
+        push(LibrarySource::Env, path);
+    }
+    if let Some(path) = config_path {
+        push(LibrarySource::Config, path);
+    }
+    if let Some(dir) = resource_dir {
+        push(LibrarySource::Bundled, dir.join("lib").join(bundled_library_dir()));
+        push(LibrarySource::Bundled, dir.to_path_buf());
+    }
+    for root in ["./lib", "../lib"] {
+        push(LibrarySource::Development, Path::new(root).join(bundled_library_dir()));
+    }
+
+    candidates.push(LibraryCandidate { source: LibrarySource::System, path: None });
+    candidates
+}
+
+fn describe(candidate: &LibraryCandidate) -> String {
+    match &candidate.path {
+        Some(path) => path.display().to_string(),
+        None => "system library".to_string(),
+    }
+}
+
+/// Bind pdfium from the first candidate that loads, recording the outcome
+pub fn load_pdfium() -> Result<Pdfium, String> {
+    let config_path = settings::current().pdfium_path;
+    let resource_dir = ENGINE_STATE.read().map_err(|e| e.to_string())?.resource_dir.clone();
+    let env_path = std::env::var_os(PDFIUM_PATH_ENV)
+        .filter(|v| !v.is_empty())
+        .map(PathBuf::from);
+    let candidates = candidate_paths(env_path, config_path, resource_dir.as_deref());
+
+    let mut searched = Vec::with_capacity(candidates.len());
+    let mut last_error = None;
+    for candidate in &candidates {
+        searched.push(describe(candidate));
+        let bound = match &candidate.path {
+            Some(path) => Pdfium::bind_to_library(path),
+            None => Pdfium::bind_to_system_library(),
+        };
+        match bound {
+            Ok(bindings) => {
+                record_status(PdfEngineStatus {
+                    engine: PdfEngine::Pdfium,
+                    degraded: false,
+                    source: Some(candidate.source),
+                    library_path: candidate.path.as_ref().map(|p| p.display().to_string()),
+                    searched,
+                    error: None,
+                });
+                return Ok(Pdfium::new(bindings));
+            }
+            Err(e) => last_error = Some(e.to_string()),
+        }
+    }
+
+    let error = format!(
+        "Failed to load pdfium: {}",
+        last_error.unwrap_or_else(|| "no candidates".to_string())
+    );
+    tracing::warn!(searched = ?searched, "{}", error);
+    record_status(PdfEngineStatus {
+        engine: PdfEngine::Lopdf,
+        degraded: true,
+        source: None,
+        library_path: None,
+        searched,
+        error: Some(error.clone()),
+    });
+    Err(error)
+}
+
+fn record_status(status: PdfEngineStatus) {
+    if let Ok(mut state) = ENGINE_STATE.write() {
+        state.status = Some(status);
+    }
+}
+
+/// Forget the last lookup so the next one searches again
+pub fn reset_status() {
+    if let Ok(mut state) = ENGINE_STATE.write() {
+        state.status = None;
+    }
+}
+
+/// Probe pdfium (if not done yet) and report which engine imports will use
+#[tauri::command]
+pub async fn get_pdf_engine_status() -> Result<PdfEngineStatus, String> {
+    if let Some(status) = ENGINE_STATE.read().map_err(|e| e.to_string())?.status.clone() {
+        return Ok(status);
+    }
+    tokio::task::spawn_blocking(|| {
+        let _ = load_pdfium();
+    })
+    .await
+    .map_err(|e| format!("Engine probe task failed: {}", e))?;
+    ENGINE_STATE
+        .read()
+        .map_err(|e| e.to_string())?
+        .status
+        .clone()
+        .ok_or_else(|| "PDF engine status unavailable".to_string())
+}
+
+/// Save (or clear) the configured pdfium path and probe again
+#[tauri::command]
+pub async fn set_pdfium_library_path(path: Option<String>, app_handle: AppHandle) -> Result<PdfEngineStatus, String> {
+    let pdfium_path = path.filter(|p| !p.trim().is_empty()).map(PathBuf::from);
+    let updated = settings::update_with(|current| Ok(AppSettings { pdfium_path, ..current.clone() }))?;
+    let _ = app_handle.emit("settings_changed", &updated);
+    reset_status();
+    get_pdf_engine_status().await
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_candidate_order() {
+        let candidates = candidate_paths(
+            Some(PathBuf::from("/env/libpdfium.so")),
+            Some(PathBuf::from("/config/libpdfium.so")),
+            Some(Path::new("/resources")),
+        );
+        let sources: Vec<LibrarySource> = candidates.iter().map(|c| c.source).collect();
+        assert_eq!(
+            sources,
+            vec![
+                LibrarySource::Env,
+                LibrarySource::Config,
+                LibrarySource::Bundled,
+                LibrarySource::Bundled,
+                LibrarySource::Development,
+                LibrarySource::Development,
+                LibrarySource::System,
+            ]
+        );
+        assert_eq!(candidates[0].path, Some(PathBuf::from("/env/libpdfium.so")));
+        assert!(candidates[2]
+            .path
+            .as_ref()
+            .unwrap()
+            .starts_with(Path::new("/resources/lib").join(bundled_library_dir())));
+        assert_eq!(candidates.last().unwrap().path, None);
+    }
+
+    #[test]
+    fn test_directory_candidate_resolves_library_name() {
+        let dir = std::env::temp_dir();
+        assert_eq!(library_file(&dir), dir.join(Pdfium::pdfium_platform_library_name()));
+        let file = dir.join("custom-pdfium.so");
+        assert_eq!(library_file(&file), file);
+    }
+}
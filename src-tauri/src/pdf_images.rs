@@ -0,0 +1,447 @@
+//! PDF Image Module
+//!
+//! Decodes image XObjects with lopdf, for imports without pdfium, and reads
+//! what pdfium's raw bitmaps lose: soft masks (SMask), composited into the
+//! alpha channel so transparent logos keep their shape instead of importing
+//! on an opaque box, and embedded gray or RGB ICC profiles, whose images are
+//! decoded here in the profile's color space instead of pdfium's sRGB.
+
+use image::{imageops, GrayImage, RgbaImage};
+use lopdf::{Dictionary, Document, Object, ObjectId, Stream};
+use std::collections::hash_map::DefaultHasher;
+use std::collections::{HashMap, HashSet};
+use std::hash::{Hash, Hasher};
+use std::sync::{Arc, Mutex};
+use std::time::SystemTime;
+
+/// Index of the most recently opened file and its modification time, shared
+/// by an import and the lazy decodes that follow it
+type CachedIndex = (String, Option<SystemTime>, Option<Arc<ImageIndex>>);
+
+lazy_static::lazy_static! {
+    static ref LAST_INDEX: Mutex<Option<CachedIndex>> = Mutex::new(None);
+}
+
+/// Color spaces image samples can be converted from
+#[derive(Debug, Clone, PartialEq)]
+enum SampleSpace {
+    Gray,
+    Rgb,
+    Cmyk,
+    /// Palette of base-space colors, one byte per component
+    Indexed(Box<SampleSpace>, Vec<u8>),
+}
+
+impl SampleSpace {
+    fn components(&self) -> usize {
+        match self {
+            Self::Gray | Self::Indexed(..) => 1,
+            Self::Rgb => 3,
+            Self::Cmyk => 4,
+        }
+    }
+
+    /// RGB for one pixel of 8-bit samples (palette indices for `Indexed`)
+    fn to_rgb(&self, samples: &[u8]) -> [u8; 3] {
+        match self {
+            Self::Gray => [samples[0]; 3],
+            Self::Rgb => [samples[0], samples[1], samples[2]],
+            Self::Cmyk => {
+                let [c, m, y, k] = [samples[0], samples[1], samples[2], samples[3]].map(|v| v as f32 / 255.0);
+                [c, m, y].map(|v| ((1.0 - v) * (1.0 - k) * 255.0).round() as u8)
+            }
+            Self::Indexed(base, palette) => {
+                let n = base.components();
+                let start = samples[0] as usize * n;
+                match palette.get(start..start + n) {
+                    Some(entry) => base.to_rgb(entry),
+                    None => [0; 3],
+                }
+            }
+        }
+    }
+}
+
+#[inline]
+fn resolve<'a>(doc: &'a Document, object: &'a Object) -> &'a Object {
+    match object {
+        Object::Reference(id) => doc.get_object(*id).unwrap_or(object),
+        _ => object,
+    }
+}
+
+fn sample_space(doc: &Document, object: &Object) -> Option<SampleSpace> {
+    match resolve(doc, object) {
+        Object::Name(name) => match name.as_slice() {
+            b"DeviceGray" | b"CalGray" | b"G" => Some(SampleSpace::Gray),
+            b"DeviceRGB" | b"CalRGB" | b"RGB" => Some(SampleSpace::Rgb),
+            b"DeviceCMYK" | b"CMYK" => Some(SampleSpace::Cmyk),
+            _ => None,
+        },
+        Object::Array(items) => match items.first()?.as_name().ok()? {
+            b"CalGray" => Some(SampleSpace::Gray),
+            b"CalRGB" => Some(SampleSpace::Rgb),
+            // Samples stay in the profile's space, see `icc_profile`
+            b"ICCBased" => match resolve(doc, items.get(1)?).as_stream().ok()?.dict.get(b"N").and_then(Object::as_i64).ok()? {
+                1 => Some(SampleSpace::Gray),
+                3 => Some(SampleSpace::Rgb),
+                4 => Some(SampleSpace::Cmyk),
+                _ => None,
+            },
+            b"Indexed" | b"I" => {
+                let base = sample_space(doc, items.get(1)?)?;
+                let palette = match resolve(doc, items.get(3)?) {
+                    Object::String(bytes, _) => bytes.clone(),
+                    Object::Stream(stream) => stream_data(stream).ok()?,
+                    _ => return None,
+                };
+                Some(SampleSpace::Indexed(Box::new(base), palette))
+            }
+            _ => None,
+        },
+        _ => None,
+    }
+}
+
+/// Stream content with its filters removed
+fn stream_data(stream: &Stream) -> Result<Vec<u8>, String> {
+    if stream.dict.has(b"Filter") {
+        stream.decompressed_content().map_err(|e| e.to_string())
+    } else {
+        Ok(stream.content.clone())
+    }
+}
+
+fn dict_int(dict: &Dictionary, key: &[u8]) -> Option<i64> {
+    dict.get(key).and_then(Object::as_i64).ok()
+}
+
+/// Unpack rows of `bits`-bit samples to one byte each; rows start on a byte
+fn unpack_samples(data: &[u8], width: usize, height: usize, components: usize, bits: usize) -> Option<Vec<u8>> {
+    let per_row = width * components;
+    let row_bytes = (per_row * bits).div_ceil(8);
+    if data.len() < row_bytes * height {
+        return None;
+    }
+    let mut samples = Vec::with_capacity(per_row * height);
+    for row in data.chunks_exact(row_bytes).take(height) {
+        match bits {
+            8 => samples.extend_from_slice(&row[..per_row]),
+            // Keep the high byte of 16-bit samples
+            16 => samples.extend(row.chunks_exact(2).map(|pair| pair[0])),
+            1 | 2 | 4 => {
+                let mask = (1u8 << bits) - 1;
+                samples.extend((0..per_row).map(|i| {
+                    let bit = i * bits;
+                    (row[bit / 8] >> (8 - bits - bit % 8)) & mask
+                }));
+            }
+            _ => return None,
+        }
+    }
+    Some(samples)
+}
+
+/// Decode an image XObject to RGBA, without its soft mask
+///
+/// Stencil masks, JPEG 2000 and unusual color spaces are not supported.
+pub fn decode_image(doc: &Document, stream: &Stream) -> Result<RgbaImage, String> {
+    let dict = &stream.dict;
+    if dict.get(b"ImageMask").and_then(Object::as_bool).unwrap_or(false) {
+        return Err("stencil masks have no colors".to_string());
+    }
+    let filters = stream.filters().unwrap_or_default();
+    if let Some(&filter) = filters.last() {
+        match filter {
+            b"DCTDecode" if filters.len() == 1 => {
+                return image::load_from_memory_with_format(&stream.content, image::ImageFormat::Jpeg)
+                    .map(|image| image.to_rgba8())
+                    .map_err(|e| format!("JPEG image: {}", e));
+            }
+            b"DCTDecode" | b"JPXDecode" | b"JBIG2Decode" | b"CCITTFaxDecode" => {
+                return Err(format!("unsupported image filter {}", String::from_utf8_lossy(filter)));
+            }
+            _ => {}
+        }
+    }
+
+    let width = dict_int(dict, b"Width").filter(|&w| w > 0).ok_or("image has no width")? as usize;
+    let height = dict_int(dict, b"Height").filter(|&h| h > 0).ok_or("image has no height")? as usize;
+    let bits = dict_int(dict, b"BitsPerComponent").unwrap_or(8) as usize;
+    let space = match dict.get(b"ColorSpace") {
+        Ok(object) => sample_space(doc, object).ok_or("unsupported image color space")?,
+        Err(_) => SampleSpace::Gray,
+    };
+    let components = space.components();
+    let data = stream_data(stream)?;
+    let mut samples = unpack_samples(&data, width, height, components, bits).ok_or("image data is truncated")?;
+
+    if !matches!(space, SampleSpace::Indexed(..)) {
+        // Scale to 8 bits, inverting components whose Decode range is reversed
+        let max = if bits == 16 { 255.0 } else { ((1u32 << bits.min(8)) - 1) as f32 };
+        let decode: Vec<f32> = dict
+            .get(b"Decode")
+            .and_then(Object::as_array)
+            .map(|d| d.iter().filter_map(|v| v.as_float().ok()).collect())
+            .unwrap_or_default();
+        for (i, sample) in samples.iter_mut().enumerate() {
+            let component = i % components;
+            let inverted = decode.get(component * 2..component * 2 + 2).is_some_and(|r| r[0] > r[1]);
+            let value = (*sample as f32 / max * 255.0).round() as u8;
+            *sample = if inverted { 255 - value } else { value };
+        }
+    }
+
+    let mut image = RgbaImage::new(width as u32, height as u32);
+    for (pixel, chunk) in image.pixels_mut().zip(samples.chunks_exact(components)) {
+        let [r, g, b] = space.to_rgb(chunk);
+        pixel.0 = [r, g, b, 255];
+    }
+    Ok(image)
+}
+
+/// Decode a soft mask stream to gray levels (the alpha values)
+fn decode_mask(doc: &Document, mask: &Stream) -> Result<GrayImage, String> {
+    Ok(imageops::grayscale(&decode_image(doc, mask)?))
+}
+
+/// Set `image`'s alpha from a mask, stretched to the image size
+pub fn apply_mask(image: &mut RgbaImage, mask: &GrayImage) {
+    let resized;
+    let mask = if mask.dimensions() == image.dimensions() {
+        mask
+    } else {
+        resized = imageops::resize(mask, image.width(), image.height(), imageops::FilterType::Triangle);
+        &resized
+    };
+    for (pixel, alpha) in image.pixels_mut().zip(mask.pixels()) {
+        pixel.0[3] = alpha.0[0];
+    }
+}
+
+/// The soft mask of an image XObject, when it has one that can be decoded
+pub fn soft_mask(doc: &Document, image: &Stream) -> Option<GrayImage> {
+    let mask = resolve(doc, image.dict.get(b"SMask").ok()?).as_stream().ok()?;
+    decode_mask(doc, mask)
+        .map_err(|e| tracing::warn!("soft mask unreadable: {}", e))
+        .ok()
+}
+
+/// Decode an image XObject by id, with its soft mask as alpha
+pub fn decode_with_mask(doc: &Document, id: ObjectId) -> Result<RgbaImage, String> {
+    let stream = doc.get_object(id).and_then(Object::as_stream).map_err(|e| e.to_string())?;
+    let mut image = decode_image(doc, stream)?;
+    if let Some(mask) = soft_mask(doc, stream) {
+        apply_mask(&mut image, &mask);
+    }
+    Ok(image)
+}
+
+fn content_key(data: &[u8]) -> u64 {
+    let mut hasher = DefaultHasher::new();
+    data.hash(&mut hasher);
+    hasher.finish()
+}
+
+/// Profile stream of an image's ICCBased color space
+fn icc_stream<'a>(doc: &'a Document, image: &'a Stream) -> Option<&'a Stream> {
+    let items = resolve(doc, image.dict.get(b"ColorSpace").ok()?).as_array().ok()?;
+    if items.first()?.as_name().ok()? != b"ICCBased" {
+        return None;
+    }
+    resolve(doc, items.get(1)?).as_stream().ok()
+}
+
+/// Embedded ICC profile of an image XObject, when it is a gray or RGB one
+pub fn icc_profile(doc: &Document, image: &Stream) -> Option<Vec<u8>> {
+    let profile = icc_stream(doc, image)?;
+    matches!(dict_int(&profile.dict, b"N"), Some(1 | 3)).then_some(())?;
+    stream_data(profile).ok()
+}
+
+fn image_stream(object: &Object) -> Option<&Stream> {
+    let stream = object.as_stream().ok()?;
+    (stream.dict.get(b"Subtype").and_then(Object::as_name).ok()? == b"Image").then_some(stream)
+}
+
+/// Keep only what soft masks and ICC profiles need: image streams with a
+/// mask, an ICC-capable color space or that can be a mask themselves, color
+/// space arrays, profile streams, and object streams the arrays may be in
+fn index_filter(id: ObjectId, object: &mut Object) -> Option<(ObjectId, Object)> {
+    let keep = match object {
+        Object::Array(items) => items.first().and_then(|first| first.as_name().ok()) == Some(b"ICCBased"),
+        Object::Stream(stream) => {
+            let dict = &stream.dict;
+            match dict.get(b"Subtype").and_then(Object::as_name) {
+                Ok(b"Image") => {
+                    dict.has(b"SMask")
+                        || matches!(dict.get(b"ColorSpace"), Ok(Object::Array(_) | Object::Reference(_)))
+                        || dict.get(b"ColorSpace").and_then(Object::as_name).is_ok_and(|cs| cs == b"DeviceGray")
+                }
+                Ok(_) => false,
+                Err(_) => dict.has_type(b"ObjStm") || (dict.has(b"N") && !dict.has(b"Type")),
+            }
+        }
+        _ => false,
+    };
+    keep.then(|| (id, object.clone()))
+}
+
+/// Soft masks and ICC profiles of a PDF's images, found by the images'
+/// encoded bytes
+///
+/// pdfium hands out an image's raw stream data but neither its mask nor its
+/// unconverted colors, so images are matched to their lopdf streams by content.
+pub struct ImageIndex {
+    doc: Document,
+    masks: HashMap<u64, ObjectId>,
+    profiles: HashMap<u64, ObjectId>,
+}
+
+impl ImageIndex {
+    /// Index the masked and profiled images of a file; `None` when it has none
+    pub fn load(path: &str) -> Option<Self> {
+        let mut doc = Document::load_filtered(path, index_filter)
+            .map_err(|e| tracing::warn!(path, "image index unreadable: {}", e))
+            .ok()?;
+        let mut masks = HashMap::new();
+        let mut profiles = HashMap::new();
+        let mut needed = HashSet::new();
+        for (&id, object) in &doc.objects {
+            let Some(stream) = image_stream(object) else { continue };
+            let key = content_key(&stream.content);
+            if let Ok(mask) = stream.dict.get(b"SMask").and_then(Object::as_reference) {
+                masks.insert(key, id);
+                needed.extend([id, mask]);
+            }
+            if icc_stream(&doc, stream).is_some_and(|profile| matches!(dict_int(&profile.dict, b"N"), Some(1 | 3))) {
+                profiles.insert(key, id);
+                needed.insert(id);
+            }
+        }
+        // Drop the images that were only kept in case they carried something
+        doc.objects.retain(|id, object| needed.contains(id) || image_stream(object).is_none());
+        (!masks.is_empty() || !profiles.is_empty()).then_some(Self { doc, masks, profiles })
+    }
+
+    /// Index for `path`, reusing the last one while the file is unchanged
+    pub fn for_file(path: &str) -> Option<Arc<Self>> {
+        let modified = std::fs::metadata(path).and_then(|m| m.modified()).ok();
+        let mut last = LAST_INDEX.lock().ok()?;
+        if let Some((cached_path, cached_modified, index)) = last.as_ref() {
+            if cached_path == path && *cached_modified == modified {
+                return index.clone();
+            }
+        }
+        let index = Self::load(path).map(Arc::new);
+        *last = Some((path.to_string(), modified, index.clone()));
+        index
+    }
+
+    fn stream(&self, id: ObjectId) -> Option<&Stream> {
+        self.doc.get_object(id).and_then(Object::as_stream).ok()
+    }
+
+    /// Whether the image whose encoded stream data is `raw` has a soft mask
+    pub fn has_mask(&self, raw: &[u8]) -> bool {
+        self.masks.contains_key(&content_key(raw))
+    }
+
+    /// Mask for the image whose encoded stream data is `raw`
+    pub fn mask_for(&self, raw: &[u8]) -> Option<GrayImage> {
+        soft_mask(&self.doc, self.stream(*self.masks.get(&content_key(raw))?)?)
+    }
+
+    /// Gray or RGB ICC profile of the image whose encoded stream data is `raw`
+    pub fn profile_for(&self, raw: &[u8]) -> Option<Vec<u8>> {
+        icc_profile(&self.doc, self.stream(*self.profiles.get(&content_key(raw))?)?)
+    }
+
+    /// Decode a profiled image in its profile's color space, with its mask
+    pub fn decode_profiled(&self, raw: &[u8]) -> Option<RgbaImage> {
+        let id = *self.profiles.get(&content_key(raw))?;
+        decode_with_mask(&self.doc, id)
+            .map_err(|e| tracing::warn!(object = id.0, "profiled image unreadable: {}", e))
+            .ok()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use lopdf::dictionary;
+
+    /// 2x1 RGB image with a soft mask: left opaque, right transparent
+    fn masked_image(doc: &mut Document) -> ObjectId {
+        let mask = doc.add_object(Stream::new(
+            dictionary! { "Type" => "XObject", "Subtype" => "Image", "Width" => 2, "Height" => 1,
+                "ColorSpace" => "DeviceGray", "BitsPerComponent" => 8 },
+            vec![255, 0],
+        ));
+        let mut image = Stream::new(
+            dictionary! { "Type" => "XObject", "Subtype" => "Image", "Width" => 2, "Height" => 1,
+                "ColorSpace" => "DeviceRGB", "BitsPerComponent" => 8, "SMask" => mask },
+            vec![255, 0, 0, 0, 0, 255],
+        );
+        image.compress().unwrap();
+        doc.add_object(image)
+    }
+
+    #[test]
+    fn test_decode_with_mask() {
+        let mut doc = Document::with_version("1.5");
+        let id = masked_image(&mut doc);
+        let image = decode_with_mask(&doc, id).unwrap();
+        assert_eq!(image.get_pixel(0, 0).0, [255, 0, 0, 255]);
+        assert_eq!(image.get_pixel(1, 0).0, [0, 0, 255, 0]);
+    }
+
+    #[test]
+    fn test_decode_indexed_and_bits() {
+        let doc = Document::with_version("1.5");
+        // 1-bit indexed: palette black, then green
+        let palette = Object::String(vec![0, 0, 0, 0, 255, 0], lopdf::StringFormat::Hexadecimal);
+        let indexed = Stream::new(
+            dictionary! { "Width" => 3, "Height" => 1, "BitsPerComponent" => 1,
+                "ColorSpace" => vec![Object::from("Indexed"), Object::from("DeviceRGB"), 1.into(), palette] },
+            vec![0b0100_0000],
+        );
+        let image = decode_image(&doc, &indexed).unwrap();
+        assert_eq!(image.get_pixel(1, 0).0, [0, 255, 0, 255]);
+        assert_eq!(image.get_pixel(2, 0).0, [0, 0, 0, 255]);
+
+        // Inverted gray, as scanned masks often are
+        let gray = Stream::new(
+            dictionary! { "Width" => 2, "Height" => 1, "BitsPerComponent" => 8, "ColorSpace" => "DeviceGray",
+                "Decode" => vec![Object::Integer(1), 0.into()] },
+            vec![0, 255],
+        );
+        assert_eq!(decode_image(&doc, &gray).unwrap().get_pixel(0, 0).0, [255, 255, 255, 255]);
+    }
+
+    #[test]
+    fn test_image_index() {
+        let mut doc = Document::with_version("1.5");
+        let id = masked_image(&mut doc);
+        let raw = doc.get_object(id).unwrap().as_stream().unwrap().content.clone();
+        // Profile values are kept as they are: 10 stays 10
+        let profile = doc.add_object(Stream::new(dictionary! { "N" => 3 }, b"not really icc".to_vec()));
+        let space = doc.add_object(vec![Object::from("ICCBased"), profile.into()]);
+        let profiled = doc.add_object(Stream::new(
+            dictionary! { "Type" => "XObject", "Subtype" => "Image", "Width" => 1, "Height" => 1,
+                "ColorSpace" => space, "BitsPerComponent" => 8 },
+            vec![10, 20, 30],
+        ));
+        let profiled_raw = doc.get_object(profiled).unwrap().as_stream().unwrap().content.clone();
+        let path = std::env::temp_dir().join(format!("rook-image-index-{}.pdf", std::process::id()));
+        doc.save(&path).unwrap();
+        let index = ImageIndex::load(path.to_str().unwrap()).unwrap();
+        let _ = std::fs::remove_file(&path);
+
+        assert!(index.has_mask(&raw) && !index.has_mask(&profiled_raw));
+        assert_eq!(index.mask_for(&raw).unwrap().as_raw(), &vec![255, 0]);
+        assert_eq!(index.profile_for(&raw), None);
+        assert_eq!(index.profile_for(&profiled_raw).unwrap(), b"not really icc");
+        assert_eq!(index.decode_profiled(&profiled_raw).unwrap().get_pixel(0, 0).0, [10, 20, 30, 255]);
+    }
+}
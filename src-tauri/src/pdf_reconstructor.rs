@@ -1,19 +1,29 @@
 //! PDF Reconstruction Module
 //!
-//! Handles reconstruction of image-only PDFs using OCR and other strategies.
+//! Handles reconstruction of image-only PDFs using OCR and other strategies,
+//! and edit-in-place export: layer edits written as an incremental update to
+//! the original file, so content the layer model does not cover (JavaScript,
+//! optional content, XMP, annotations) survives the round trip.
 
 use crate::models::{
-    Bounds, LayerObject, LayerRole, LayerType, SourceType, TextAlign,
+    iso8601_now, BlendMode, Bounds, ExportResult, FillRule, LayerObject, LayerRole, LayerType, OcrInfo,
+    OcrReviewStatus, PageData, SourceType, TextAlign,
 };
-use crate::ocr_handler::OcrEngine;
+use crate::font_manager::normalizer;
+use crate::job_manager::{self, JobHandle, JobKind, JobPriority};
+use crate::ocr_handler::{OcrConfig, OcrEngine};
 use crate::pdf_analyzer::{PdfAnalysis, ReconstructionRecommendation};
 use image::RgbaImage;
+use lopdf::content::{Content, Operation};
+use lopdf::{dictionary, Dictionary, Document, IncrementalDocument, Object, ObjectId, Stream, StringFormat};
 use pdfium_render::prelude::*;
 use serde::{Deserialize, Serialize};
-use std::sync::atomic::{AtomicUsize, Ordering};
+use std::collections::HashMap;
 use tauri::{AppHandle, Emitter};
-
-static LAYER_COUNTER: AtomicUsize = AtomicUsize::new(0);
+use vortex_core::layer_ids::LayerIds;
+use vortex_core::ocr_correction::OcrDictionary;
+use vortex_core::ocr_review::{self, OcrReviewAction, OcrReviewPage, OcrReviewResult};
+use vortex_core::path_ops;
 
 /// Reconstruction result
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -30,20 +40,25 @@ pub struct ReconstructionResult {
 #[derive(Debug, Clone, Serialize, Deserialize)]
 #[serde(rename_all = "camelCase")]
 pub struct OcrOptions {
-    /// Language for OCR (default: "eng")
+    /// Language for OCR (default: the OCR language setting)
     pub language: Option<String>,
     /// DPI for rendering pages (higher = better OCR, slower)
     pub render_dpi: Option<u32>,
     /// Minimum confidence threshold (0.0 - 1.0)
     pub min_confidence: Option<f32>,
+    /// Project corrections for post-processing (`ProjectSettings::ocr_dictionary`)
+    #[serde(default)]
+    pub dictionary: Option<OcrDictionary>,
 }
 
 impl Default for OcrOptions {
     fn default() -> Self {
+        let settings = crate::settings::current();
         Self {
-            language: Some("eng".to_string()),
-            render_dpi: Some(150),
-            min_confidence: Some(0.5),
+            language: Some(settings.ocr_language),
+            render_dpi: Some(settings.ocr_render_dpi),
+            min_confidence: Some(settings.ocr_min_confidence),
+            dictionary: None,
         }
     }
 }
@@ -55,13 +70,33 @@ pub async fn reconstruct_pdf_with_ocr(
     options: Option<OcrOptions>,
     app_handle: AppHandle,
 ) -> Result<ReconstructionResult, String> {
-    let opts = options.unwrap_or_default();
-    let render_dpi = opts.render_dpi.unwrap_or(150);
-    let min_confidence = opts.min_confidence.unwrap_or(0.5);
+    let label = job_manager::file_label(&file_path);
+    job_manager::run(JobKind::Ocr, label, JobPriority::Normal, move |job| {
+        reconstruct_with_ocr(&file_path, options.unwrap_or_default(), &app_handle, job)
+    })
+    .await
+}
 
-    let pdfium = Pdfium::default();
+/// OCR every page of a PDF, reporting progress to `job`
+fn reconstruct_with_ocr(
+    file_path: &str,
+    opts: OcrOptions,
+    app_handle: &AppHandle,
+    job: &JobHandle,
+) -> Result<ReconstructionResult, String> {
+    let settings = crate::settings::current();
+    let render_dpi = opts.render_dpi.unwrap_or(settings.ocr_render_dpi);
+    let min_confidence = opts.min_confidence.unwrap_or(settings.ocr_min_confidence);
+    let config = OcrConfig {
+        language: opts.language.unwrap_or(settings.ocr_language),
+        min_confidence,
+        dictionary: opts.dictionary.unwrap_or_default(),
+        ..OcrConfig::default()
+    };
+
+    let pdfium = crate::pdf_engine::load_pdfium()?;
     let document = pdfium
-        .load_pdf_from_file(&file_path, None)
+        .load_pdf_from_file(file_path, None)
         .map_err(|e| format!("Failed to load PDF: {}", e))?;
 
     let total_pages = document.pages().len();
@@ -69,15 +104,16 @@ pub async fn reconstruct_pdf_with_ocr(
     let mut total_confidence = 0.0f32;
     let mut confidence_count = 0usize;
 
-    LAYER_COUNTER.store(0, Ordering::SeqCst);
-
     for page_idx in 0..total_pages {
+        job.check_cancelled()?;
+        let status = format!("OCR processing page {} of {}", page_idx + 1, total_pages);
+        job.progress(f32::from(page_idx) / f32::from(total_pages), Some(status.clone()));
         let _ = app_handle.emit(
             "ocr_progress",
             serde_json::json!({
                 "currentPage": page_idx + 1,
                 "totalPages": total_pages,
-                "status": format!("OCR processing page {} of {}", page_idx + 1, total_pages)
+                "status": status
             }),
         );
 
@@ -90,7 +126,7 @@ pub async fn reconstruct_pdf_with_ocr(
         let image = render_page_to_image(&page, render_dpi)?;
 
         // Run OCR on the rendered image
-        let ocr_results = run_ocr_on_image(&image, min_confidence)?;
+        let ocr_results = run_ocr_on_image(&image, &config)?;
 
         for result in ocr_results {
             if result.confidence >= min_confidence {
@@ -154,9 +190,10 @@ pub struct OcrTextResult {
 }
 
 /// Run OCR on an image and return detected text regions
-fn run_ocr_on_image(image: &RgbaImage, min_confidence: f32) -> Result<Vec<OcrTextResult>, String> {
+fn run_ocr_on_image(image: &RgbaImage, config: &OcrConfig) -> Result<Vec<OcrTextResult>, String> {
     // Use the existing OCR engine
-    let mut engine = OcrEngine::new();
+    let min_confidence = config.min_confidence;
+    let mut engine = OcrEngine::with_config(config.clone());
     let mut results = Vec::new();
 
     // For now, do full-page OCR
@@ -173,7 +210,7 @@ fn run_ocr_on_image(image: &RgbaImage, min_confidence: f32) -> Result<Vec<OcrTex
             }
         }
         Err(e) => {
-            eprintln!("OCR failed: {}", e);
+            tracing::error!("OCR failed: {}", e);
         }
     }
 
@@ -187,25 +224,28 @@ pub fn ocr_results_to_layers(
     _page_height: f32,
     scale: f32,
 ) -> Vec<LayerObject> {
+    let mut ids = LayerIds::new(page_index);
     results
         .into_iter()
         .enumerate()
         .map(|(idx, result)| {
-            let z_index = LAYER_COUNTER.fetch_add(1, Ordering::SeqCst) as i32;
+            let z_index = idx as i32;
+            let bounds = Bounds::new(
+                result.bounds.x / scale,
+                result.bounds.y / scale,
+                result.bounds.width / scale,
+                result.bounds.height / scale,
+            );
 
             LayerObject {
-                id: format!("ocr-{}-{}", page_index, idx),
+                id: ids.next("ocr", &result.text, Some(&bounds)),
                 layer_type: LayerType::Text,
-                bounds: Bounds::new(
-                    result.bounds.x / scale,
-                    result.bounds.y / scale,
-                    result.bounds.width / scale,
-                    result.bounds.height / scale,
-                ),
+                bounds,
                 visible: true,
                 locked: false,
                 z_index,
                 opacity: 1.0,
+                blend_mode: None,
                 content: Some(result.text),
                 font_family: Some("Arial".to_string()),
                 font_size: Some(12.0),
@@ -218,17 +258,27 @@ pub fn ocr_results_to_layers(
                 line_height: None,
                 letter_spacing: None,
                 background_color: None,
+                text_path: None,
+                text_outline: None,
+                text_shadow: None,
+                drop_cap: None,
+                notes: Vec::new(),
+                pagination: None,
                 image_url: None,
                 image_path: None,
                 image_data: None,
+                image_link: None,
                 shape_type: None,
                 stroke_color: None,
                 stroke_width: None,
                 fill_color: None,
+                text_wrap: None,
                 path_data: None,
                 transform: None,
                 source_type: SourceType::Extracted,
                 role: LayerRole::Content,
+                ocr: Some(OcrInfo { confidence: result.confidence, words: Vec::new(), review: OcrReviewStatus::Pending }),
+                tags: Vec::new(),
             }
         })
         .collect()
@@ -242,3 +292,1003 @@ pub fn needs_ocr_reconstruction(analysis: PdfAnalysis) -> bool {
         ReconstructionRecommendation::OcrRequired | ReconstructionRecommendation::OcrVerification
     )
 }
+
+// ============================================================================
+// Partial-page OCR
+// ============================================================================
+//
+// Scanned inserts and figures containing text on otherwise native-text pages:
+// image layers with no text over them are OCR'd one at a time from their own
+// pixels, and the recognized lines are merged into the page's layers instead
+// of reprocessing the whole document.
+
+/// Smallest image side considered for OCR, in points
+const MIN_REGION_SIDE: f32 = 36.0;
+/// Share of a region existing text may cover for it to still count as image-only
+const MAX_REGION_TEXT_COVERAGE: f32 = 0.05;
+/// Share of an OCR line covered by existing text that marks it as a repeat
+const DUPLICATE_LINE_COVERAGE: f32 = 0.5;
+
+/// An image layer with no text over it, to be OCR'd on its own
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct OcrRegion {
+    pub page_index: usize,
+    /// The image layer
+    pub layer_id: String,
+    pub bounds: Bounds,
+    /// Text layers merged in; set by `ocr_page_regions`
+    #[serde(default)]
+    pub layers_added: usize,
+}
+
+/// Result of `ocr_page_regions`
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct RegionOcrResult {
+    pub pages: Vec<PageData>,
+    /// Regions processed, in page order
+    pub regions: Vec<OcrRegion>,
+    pub text_layers_added: usize,
+}
+
+fn overlap_area(a: &Bounds, b: &Bounds) -> f32 {
+    let width = (a.x + a.width).min(b.x + b.width) - a.x.max(b.x);
+    let height = (a.y + a.height).min(b.y + b.height) - a.y.max(b.y);
+    width.max(0.0) * height.max(0.0)
+}
+
+/// Share of `bounds` under the page's visible text layers
+fn text_coverage(page: &PageData, bounds: &Bounds) -> f32 {
+    let area = bounds.width * bounds.height;
+    if area <= 0.0 {
+        return 1.0;
+    }
+    let covered: f32 = page
+        .layers
+        .iter()
+        .filter(|l| l.layer_type == LayerType::Text && l.visible)
+        .map(|l| overlap_area(&l.bounds, bounds))
+        .sum();
+    covered / area
+}
+
+/// Image-only regions of a page: visible image layers at least
+/// `MIN_REGION_SIDE` on each side with next to no text over them
+pub fn find_ocr_regions(page: &PageData) -> Vec<OcrRegion> {
+    page.layers
+        .iter()
+        .filter(|l| l.layer_type == LayerType::Image && l.visible)
+        .filter(|l| l.bounds.width.min(l.bounds.height) >= MIN_REGION_SIDE)
+        .filter(|l| text_coverage(page, &l.bounds) <= MAX_REGION_TEXT_COVERAGE)
+        .map(|l| OcrRegion { page_index: page.page_index, layer_id: l.id.clone(), bounds: l.bounds, layers_added: 0 })
+        .collect()
+}
+
+/// Composite transparent pixels over white so they read as paper, not ink
+fn flatten_on_white(image: &mut RgbaImage) {
+    for pixel in image.pixels_mut() {
+        let alpha = pixel[3] as u16;
+        for channel in &mut pixel.0[..3] {
+            *channel = ((*channel as u16 * alpha + 255 * (255 - alpha)) / 255) as u8;
+        }
+        pixel[3] = 255;
+    }
+}
+
+/// OCR a region from its image layer, resampled to `dpi`; lines are
+/// returned in page coordinates
+fn ocr_region(engine: &mut OcrEngine, page: &PageData, region: &OcrRegion, dpi: u32) -> Result<Vec<LayerObject>, String> {
+    let layer = page
+        .layers
+        .iter()
+        .find(|l| l.id == region.layer_id)
+        .ok_or_else(|| format!("Layer {} not found", region.layer_id))?;
+    let bytes = crate::image_handler::layer_image_bytes(layer)
+        .ok_or_else(|| format!("Image for layer {} is unavailable", layer.id))?;
+    let image = image::load_from_memory(&bytes).map_err(|e| format!("Failed to decode image: {}", e))?.to_rgba8();
+
+    let scale = dpi as f32 / vortex_core::units::POINTS_PER_INCH;
+    let width = (region.bounds.width * scale).round().max(1.0) as u32;
+    let height = (region.bounds.height * scale).round().max(1.0) as u32;
+    let mut image = image::imageops::resize(&image, width, height, image::imageops::FilterType::Triangle);
+    flatten_on_white(&mut image);
+
+    let mut lines = engine.recognize_page(&image, page.page_index, scale)?;
+    for line in &mut lines {
+        let words = line.ocr.iter_mut().flat_map(|ocr| ocr.words.iter_mut().map(|w| &mut w.bounds));
+        for bounds in std::iter::once(&mut line.bounds).chain(words) {
+            bounds.x += region.bounds.x;
+            bounds.y += region.bounds.y;
+        }
+    }
+    Ok(lines)
+}
+
+/// Add OCR'd lines for `region` above the page's layers, skipping lines
+/// that repeat text already there; returns the number added
+fn merge_region_lines(page: &mut PageData, region: &OcrRegion, lines: Vec<LayerObject>) -> usize {
+    let top = page.layers.iter().map(|l| l.z_index).max().unwrap_or(0);
+    let lines: Vec<LayerObject> =
+        lines.into_iter().filter(|line| text_coverage(page, &line.bounds) < DUPLICATE_LINE_COVERAGE).collect();
+    let added = lines.len();
+    for (i, mut line) in lines.into_iter().enumerate() {
+        line.id = format!("ocr-{}-{}-{}", page.page_index, region.layer_id, i);
+        line.z_index = top + 1 + i as i32;
+        page.layers.push(line);
+    }
+    added
+}
+
+/// Image-only regions of `pages` that `ocr_page_regions` would process
+#[tauri::command]
+pub fn find_page_ocr_regions(pages: Vec<PageData>) -> Vec<OcrRegion> {
+    pages.iter().flat_map(find_ocr_regions).collect()
+}
+
+/// OCR the image-only regions of `pages` and merge the text into them
+///
+/// Regions are found by `find_ocr_regions`; `layer_ids` limits the run to
+/// those image layers. Pages without regions are returned unchanged; the
+/// frontend owns state. A region that fails is skipped with a warning.
+#[tauri::command]
+pub async fn ocr_page_regions(
+    mut pages: Vec<PageData>,
+    layer_ids: Option<Vec<String>>,
+    options: Option<OcrOptions>,
+    app_handle: AppHandle,
+) -> Result<RegionOcrResult, String> {
+    job_manager::run(JobKind::Ocr, "Image regions", JobPriority::Normal, move |job| {
+        let settings = crate::settings::current();
+        let opts = options.unwrap_or_default();
+        let dpi = opts.render_dpi.unwrap_or(settings.ocr_render_dpi);
+        let mut engine = OcrEngine::with_config(OcrConfig {
+            language: opts.language.unwrap_or(settings.ocr_language),
+            min_confidence: opts.min_confidence.unwrap_or(settings.ocr_min_confidence),
+            dictionary: opts.dictionary.unwrap_or_default(),
+            ..OcrConfig::default()
+        });
+
+        let mut regions: Vec<OcrRegion> = pages
+            .iter()
+            .flat_map(find_ocr_regions)
+            .filter(|r| layer_ids.as_ref().map_or(true, |ids| ids.contains(&r.layer_id)))
+            .collect();
+        let total = regions.len();
+        let mut text_layers_added = 0;
+        for (i, region) in regions.iter_mut().enumerate() {
+            job.check_cancelled()?;
+            let status = format!("OCR processing region {} of {}", i + 1, total);
+            job.progress(i as f32 / total as f32, Some(status.clone()));
+            let _ = app_handle.emit(
+                "ocr_progress",
+                serde_json::json!({ "currentPage": region.page_index + 1, "totalPages": pages.len(), "status": status }),
+            );
+
+            let Some(page) = pages.iter_mut().find(|p| p.page_index == region.page_index) else { continue };
+            match ocr_region(&mut engine, page, region, dpi) {
+                Ok(lines) => region.layers_added = merge_region_lines(page, region, lines),
+                Err(e) => tracing::warn!(page = region.page_index, layer = %region.layer_id, "region OCR failed: {}", e),
+            }
+            text_layers_added += region.layers_added;
+        }
+        Ok(RegionOcrResult { pages, regions, text_layers_added })
+    })
+    .await
+}
+
+// ============================================================================
+// OCR review
+// ============================================================================
+
+/// OCR'd text below `threshold` confidence (0.8 by default) awaiting review,
+/// grouped by page
+#[tauri::command]
+pub fn get_ocr_review_queue(pages: Vec<PageData>, threshold: Option<f32>) -> Vec<OcrReviewPage> {
+    ocr_review::review_queue(&pages, threshold.unwrap_or(ocr_review::DEFAULT_REVIEW_THRESHOLD))
+}
+
+/// Accept or correct an OCR'd layer, returning the updated pages and queue
+#[tauri::command]
+pub fn review_ocr_layer(
+    mut pages: Vec<PageData>,
+    page_index: usize,
+    layer_id: String,
+    action: OcrReviewAction,
+    threshold: Option<f32>,
+) -> Result<OcrReviewResult, String> {
+    ocr_review::review_layer(&mut pages, page_index, &layer_id, action)?;
+    let queue = ocr_review::review_queue(&pages, threshold.unwrap_or(ocr_review::DEFAULT_REVIEW_THRESHOLD));
+    Ok(OcrReviewResult { pages, queue })
+}
+
+// ============================================================================
+// Edit-in-place export
+// ============================================================================
+//
+// The original bytes are kept verbatim and one incremental update is
+// appended. Changed pages get an overlay content stream: original layers that
+// were edited or deleted are covered with white, then new and edited layers
+// are drawn on top. Untouched text under a cover is redrawn in the page font
+// it was set in. Covered content is hidden, not removed, and stays
+// recoverable from the earlier revision; use a full export to redact.
+//
+// White covers only pass for white paper, so pages where a cover would land
+// on an image, a fill or a page background are refused, as are rotated
+// pages, whose layers are not in the page's unrotated space.
+
+/// Padding around covered layers, in points
+const COVER_PADDING: f32 = 1.0;
+/// Prefix for resource names added by overlays
+const RESOURCE_PREFIX: &str = "Rook";
+
+/// Which layers of an edited page need drawing
+struct PageEdits<'a> {
+    /// Original bounds to cover with white
+    covers: Vec<Bounds>,
+    /// Layers to draw, in z-order
+    draws: Vec<&'a LayerObject>,
+    /// Ids of the untouched layers among `draws`, redrawn as they were
+    redrawn: Vec<&'a str>,
+}
+
+fn intersects(a: &Bounds, b: &Bounds) -> bool {
+    a.x < b.x + b.width && b.x < a.x + a.width && a.y < b.y + b.height && b.y < a.y + a.height
+}
+
+/// Compare an edited page with the page as imported
+fn page_edits<'a>(original: &PageData, edited: &'a PageData) -> PageEdits<'a> {
+    let before: HashMap<&str, &LayerObject> = original.layers.iter().map(|l| (l.id.as_str(), l)).collect();
+    let after: HashMap<&str, &LayerObject> = edited.layers.iter().map(|l| (l.id.as_str(), l)).collect();
+
+    let mut covers: Vec<Bounds> = original
+        .layers
+        .iter()
+        .filter(|l| l.visible && after.get(l.id.as_str()).copied() != Some(l))
+        .map(|l| l.bounds)
+        .collect();
+    let mut draws: Vec<&LayerObject> = edited
+        .layers
+        .iter()
+        .filter(|l| l.visible && before.get(l.id.as_str()).copied() != Some(l))
+        .collect();
+
+    // Untouched layers under a cover are redrawn so only the edit disappears
+    let mut redrawn = Vec::new();
+    if !covers.is_empty() {
+        for layer in edited.layers.iter().filter(|l| {
+            l.visible
+                && before.get(l.id.as_str()) == Some(l)
+                && covers.iter().any(|c| intersects(c, &l.bounds))
+        }) {
+            draws.push(layer);
+            redrawn.push(layer.id.as_str());
+        }
+    }
+    draws.sort_by_key(|l| l.z_index);
+    covers.iter_mut().for_each(|b| {
+        *b = Bounds::new(
+            b.x - COVER_PADDING,
+            b.y - COVER_PADDING,
+            b.width + 2.0 * COVER_PADDING,
+            b.height + 2.0 * COVER_PADDING,
+        )
+    });
+    PageEdits { covers, draws, redrawn }
+}
+
+/// Why white covers would show on a page, if they would: an image, a fill or
+/// a page background under a layer that is covered
+fn cover_conflict(original: &PageData, edited: &PageData, covers: &[Bounds]) -> Option<String> {
+    if covers.is_empty() {
+        return None;
+    }
+    if original.background.is_some() {
+        return Some("the page has a background".to_string());
+    }
+    let after: HashMap<&str, &LayerObject> = edited.layers.iter().map(|l| (l.id.as_str(), l)).collect();
+    original
+        .layers
+        .iter()
+        // Layers that are covered themselves are meant to disappear
+        .filter(|l| l.visible && after.get(l.id.as_str()).copied() == Some(*l))
+        .find(|l| {
+            let painted = match l.layer_type {
+                LayerType::Image => true,
+                LayerType::Shape | LayerType::Vector => l.fill_color.is_some(),
+                LayerType::Text => l.background_color.is_some(),
+            };
+            painted && covers.iter().any(|c| intersects(c, &l.bounds))
+        })
+        .map(|l| format!("an edited layer lies on the image or fill of layer '{}'", l.id))
+}
+
+/// Text of a layer encoded for the page font it was set in
+struct OriginalText {
+    /// Font resource name on the page
+    font: Vec<u8>,
+    lines: Vec<Vec<u8>>,
+}
+
+/// Text of untouched `layers`, encoded for the page fonts they were set in
+///
+/// The font is found by its parsed name, weight and style; layers whose font
+/// is missing or cannot encode every character are left out.
+fn original_text<'a>(
+    doc: &Document,
+    page_id: ObjectId,
+    layers: impl Iterator<Item = &'a LayerObject>,
+) -> HashMap<&'a str, OriginalText> {
+    let fonts = doc.get_page_fonts(page_id).unwrap_or_default();
+    let mut encoded = HashMap::new();
+    for layer in layers.filter(|l| l.layer_type == LayerType::Text) {
+        let (Some(family), Some(content)) = (layer.font_family.as_deref(), layer.content.as_deref()) else {
+            continue;
+        };
+        let family = normalizer::normalize_for_comparison(family);
+        let italic = layer.font_style.as_deref() == Some("italic");
+        let font = fonts.iter().find(|(_, font)| {
+            let Ok(base_font) = font.get(b"BaseFont").and_then(Object::as_name) else {
+                return false;
+            };
+            let parsed = normalizer::parse_font_name(&String::from_utf8_lossy(base_font));
+            normalizer::normalize_for_comparison(&parsed.family) == family
+                && Some(parsed.weight) == layer.font_weight
+                && parsed.is_italic == italic
+        });
+        let Some((name, font)) = font else { continue };
+        let Ok(encoding) = font.get_font_encoding(doc) else { continue };
+        let lines: Option<Vec<Vec<u8>>> = content
+            .lines()
+            .map(|line| {
+                let bytes = Document::encode_text(&encoding, line);
+                Document::decode_text(&encoding, &bytes).is_ok_and(|decoded| decoded == line).then_some(bytes)
+            })
+            .collect();
+        if let Some(lines) = lines {
+            encoded.insert(layer.id.as_str(), OriginalText { font: name.clone(), lines });
+        }
+    }
+    encoded
+}
+
+/// Encode text for the standard fonts' WinAnsiEncoding
+fn win_ansi(text: &str) -> Vec<u8> {
+    text.chars()
+        .map(|c| match c {
+            ' '..='~' | '\u{A0}'..='\u{FF}' => c as u8,
+            '\u{20AC}' => 0x80,
+            '\u{2026}' => 0x85,
+            '\u{2018}' => 0x91,
+            '\u{2019}' => 0x92,
+            '\u{201C}' => 0x93,
+            '\u{201D}' => 0x94,
+            '\u{2022}' => 0x95,
+            '\u{2013}' => 0x96,
+            '\u{2014}' => 0x97,
+            _ => b'?',
+        })
+        .collect()
+}
+
+fn rgb_operands(hex: &str) -> Option<Vec<Object>> {
+    let (r, g, b) = crate::export_handler::parse_hex_color(hex)?;
+    Some([r, g, b].iter().map(|&c| Object::Real(c as f32 / 255.0)).collect())
+}
+
+/// Decode an image layer into an RGB XObject (plus soft mask for alpha)
+fn image_stream(doc: &mut Document, layer: &LayerObject) -> Option<Stream> {
+    let bytes = crate::image_handler::layer_image_bytes(layer)?;
+    let image = image::load_from_memory(&bytes).ok()?.to_rgba8();
+    let (width, height) = image.dimensions();
+
+    let mut rgb = Vec::with_capacity(width as usize * height as usize * 3);
+    let mut alpha = Vec::with_capacity(width as usize * height as usize);
+    for p in image.pixels() {
+        rgb.extend_from_slice(&p.0[..3]);
+        alpha.push(p.0[3]);
+    }
+    let mut dict = dictionary! {
+        "Type" => "XObject",
+        "Subtype" => "Image",
+        "Width" => width as i64,
+        "Height" => height as i64,
+        "ColorSpace" => "DeviceRGB",
+        "BitsPerComponent" => 8,
+    };
+    if alpha.iter().any(|&a| a < 255) {
+        let mut smask = Stream::new(
+            dictionary! {
+                "Type" => "XObject",
+                "Subtype" => "Image",
+                "Width" => width as i64,
+                "Height" => height as i64,
+                "ColorSpace" => "DeviceGray",
+                "BitsPerComponent" => 8,
+            },
+            alpha,
+        );
+        let _ = smask.compress();
+        dict.set("SMask", doc.add_object(smask));
+    }
+    let mut stream = Stream::new(dict, rgb);
+    let _ = stream.compress();
+    Some(stream)
+}
+
+/// Overlay operations plus the resources they reference
+#[derive(Default)]
+struct Overlay {
+    operations: Vec<Operation>,
+    fonts: Vec<(String, &'static str)>,
+    xobjects: Vec<(String, ObjectId)>,
+    graphics_states: Vec<(String, f32, BlendMode)>,
+}
+
+impl Overlay {
+    fn font(&mut self, base_font: &'static str) -> String {
+        if let Some((name, _)) = self.fonts.iter().find(|(_, f)| *f == base_font) {
+            return name.clone();
+        }
+        let name = format!("{}F{}", RESOURCE_PREFIX, self.fonts.len() + 1);
+        self.fonts.push((name.clone(), base_font));
+        name
+    }
+
+    /// Apply the layer's opacity and blend mode; call inside a `q`/`Q` pair
+    fn transparency(&mut self, layer: &LayerObject) {
+        let blend_mode = layer.blend_mode.unwrap_or_default();
+        if layer.opacity >= 1.0 && blend_mode == BlendMode::Normal {
+            return;
+        }
+        let opacity = layer.opacity.clamp(0.0, 1.0);
+        let existing = self
+            .graphics_states
+            .iter()
+            .find(|(_, a, m)| (*a - opacity).abs() < 0.005 && *m == blend_mode);
+        let name = match existing {
+            Some((name, _, _)) => name.clone(),
+            None => {
+                let name = format!("{}GS{}", RESOURCE_PREFIX, self.graphics_states.len() + 1);
+                self.graphics_states.push((name.clone(), opacity, blend_mode));
+                name
+            }
+        };
+        self.op("gs", vec![Object::Name(name.into_bytes())]);
+    }
+
+    fn op(&mut self, operator: &str, operands: Vec<Object>) {
+        self.operations.push(Operation::new(operator, operands));
+    }
+
+    fn cover(&mut self, page_height: f32, b: &Bounds) {
+        self.op("q", vec![]);
+        self.op("rg", vec![1.0f32.into(), 1.0f32.into(), 1.0f32.into()]);
+        self.op("re", vec![b.x.into(), (page_height - b.y - b.height).into(), b.width.into(), b.height.into()]);
+        self.op("f", vec![]);
+        self.op("Q", vec![]);
+    }
+
+    /// Draw a layer the way the full PDF export does; text in its original
+    /// page font when given
+    fn draw(&mut self, doc: &mut Document, page_height: f32, layer: &LayerObject, original: Option<&OriginalText>) {
+        let b = layer.bounds;
+        match layer.layer_type {
+            LayerType::Text => {
+                let Some(content) = layer.content.as_deref().filter(|c| !c.is_empty()) else { return };
+                let font_size = layer.font_size.unwrap_or(12.0);
+                let leading = font_size * layer.line_height.unwrap_or(1.2);
+                let (font, lines) = match original {
+                    Some(text) => (text.font.clone(), text.lines.clone()),
+                    None => {
+                        let bold = layer.font_weight.unwrap_or(400) >= 700;
+                        let font = self.font(if bold { "Helvetica-Bold" } else { "Helvetica" });
+                        (font.into_bytes(), content.lines().map(win_ansi).collect())
+                    }
+                };
+
+                self.op("q", vec![]);
+                self.transparency(layer);
+                if let Some(rgb) = layer.color.as_deref().and_then(rgb_operands) {
+                    self.op("rg", rgb);
+                }
+                self.op("BT", vec![]);
+                self.op("Tf", vec![Object::Name(font), font_size.into()]);
+                self.op("Td", vec![b.x.into(), (page_height - b.y - font_size).into()]);
+                for (i, line) in lines.into_iter().enumerate() {
+                    if i > 0 {
+                        self.op("Td", vec![0.0f32.into(), (-leading).into()]);
+                    }
+                    self.op("Tj", vec![Object::String(line, StringFormat::Literal)]);
+                }
+                self.op("ET", vec![]);
+                self.op("Q", vec![]);
+            }
+            LayerType::Shape => {
+                self.op("q", vec![]);
+                self.transparency(layer);
+                let fill = layer.fill_color.as_deref().and_then(rgb_operands);
+                let has_fill = fill.is_some();
+                if let Some(rgb) = fill {
+                    self.op("rg", rgb);
+                }
+                if let Some(rgb) = layer.stroke_color.as_deref().and_then(rgb_operands) {
+                    self.op("RG", rgb);
+                }
+                self.op("w", vec![layer.stroke_width.unwrap_or(1.0).into()]);
+                self.op("re", vec![b.x.into(), (page_height - b.y - b.height).into(), b.width.into(), b.height.into()]);
+                self.op(if has_fill { "B" } else { "S" }, vec![]);
+                self.op("Q", vec![]);
+            }
+            LayerType::Image => {
+                let Some(stream) = image_stream(doc, layer) else {
+                    tracing::warn!(layer = %layer.id, "image unavailable for edit-in-place overlay");
+                    return;
+                };
+                let name = format!("{}Im{}", RESOURCE_PREFIX, self.xobjects.len() + 1);
+                self.xobjects.push((name.clone(), doc.add_object(stream)));
+                self.op("q", vec![]);
+                self.transparency(layer);
+                self.op(
+                    "cm",
+                    vec![b.width.into(), 0.into(), 0.into(), b.height.into(), b.x.into(), (page_height - b.y - b.height).into()],
+                );
+                self.op("Do", vec![Object::Name(name.into_bytes())]);
+                self.op("Q", vec![]);
+            }
+            LayerType::Vector => {
+                let Some(path) = layer.path_data.as_ref().filter(|p| !p.commands.is_empty()) else { return };
+                let fill = layer.fill_color.as_deref().and_then(rgb_operands);
+                let stroke = layer.stroke_color.as_deref().and_then(rgb_operands);
+                let (has_fill, has_stroke) = (fill.is_some() || stroke.is_none(), stroke.is_some());
+                self.op("q", vec![]);
+                self.transparency(layer);
+                if let Some(rgb) = fill {
+                    self.op("rg", rgb);
+                } else if !has_stroke {
+                    // Unpainted paths fill black, as PDF does
+                    self.op("rg", vec![0.0f32.into(), 0.0f32.into(), 0.0f32.into()]);
+                }
+                if let Some(rgb) = stroke {
+                    self.op("RG", rgb);
+                    self.op("w", vec![layer.stroke_width.unwrap_or(1.0).into()]);
+                }
+                for (operator, operands) in path_ops::pdf_path_operators(&path.commands, page_height) {
+                    self.op(operator, operands.into_iter().map(Object::from).collect());
+                }
+                let even_odd = path.fill_rule == Some(FillRule::EvenOdd);
+                self.op(path_ops::pdf_paint_operator(has_fill, has_stroke, even_odd), vec![]);
+                self.op("Q", vec![]);
+            }
+        }
+    }
+}
+
+/// Look up a page attribute, following the page tree's inheritance
+fn inherited(doc: &Document, page_id: ObjectId, key: &[u8]) -> Option<Object> {
+    let mut node = doc.get_dictionary(page_id).ok()?;
+    for _ in 0..32 {
+        if let Ok(value) = node.get(key) {
+            return Some(value.clone());
+        }
+        node = doc.get_dictionary(node.get(b"Parent").and_then(Object::as_reference).ok()?).ok()?;
+    }
+    None
+}
+
+/// Resolve an object in the update, cloning it from the original first
+fn object_mut(inc: &mut IncrementalDocument, id: ObjectId) -> Result<&mut Object, String> {
+    inc.opt_clone_object_to_new_document(id).map_err(|e| e.to_string())?;
+    inc.new_document.get_object_mut(id).map_err(|e| e.to_string())
+}
+
+/// A resource category dictionary (Font, XObject, ...) of a page in the update
+fn resource_category<'a>(
+    inc: &'a mut IncrementalDocument,
+    page_id: ObjectId,
+    category: &str,
+) -> Result<&'a mut Dictionary, String> {
+    // Inherited resources are copied onto the page so the additions do not shadow them
+    if !inc.get_prev_documents().get_dictionary(page_id).is_ok_and(|p| p.has(b"Resources")) {
+        let resources = inherited(inc.get_prev_documents(), page_id, b"Resources")
+            .unwrap_or_else(|| Object::Dictionary(Dictionary::new()));
+        object_mut(inc, page_id)?.as_dict_mut().map_err(|e| e.to_string())?.set("Resources", resources);
+    }
+    let page = object_mut(inc, page_id)?.as_dict_mut().map_err(|e| e.to_string())?;
+    let resources_ref = page.get(b"Resources").and_then(Object::as_reference).ok();
+    let category_ref = match resources_ref {
+        Some(id) => object_mut(inc, id)?,
+        None => object_mut(inc, page_id)?.as_dict_mut().map_err(|e| e.to_string())?.get_mut(b"Resources").map_err(|e| e.to_string())?,
+    }
+    .as_dict()
+    .map_err(|e| e.to_string())?
+    .get(category.as_bytes())
+    .and_then(Object::as_reference)
+    .ok();
+    if let Some(id) = category_ref {
+        return object_mut(inc, id)?.as_dict_mut().map_err(|e| e.to_string());
+    }
+
+    let resources = match resources_ref {
+        Some(id) => object_mut(inc, id)?,
+        None => object_mut(inc, page_id)?.as_dict_mut().map_err(|e| e.to_string())?.get_mut(b"Resources").map_err(|e| e.to_string())?,
+    }
+    .as_dict_mut()
+    .map_err(|e| e.to_string())?;
+    if !resources.get(category.as_bytes()).is_ok_and(|c| c.as_dict().is_ok()) {
+        resources.set(category, Dictionary::new());
+    }
+    resources.get_mut(category.as_bytes()).and_then(Object::as_dict_mut).map_err(|e| e.to_string())
+}
+
+/// Append an overlay to a page of the update
+fn apply_overlay(inc: &mut IncrementalDocument, page_id: ObjectId, overlay: Overlay) -> Result<(), String> {
+    for (name, base_font) in &overlay.fonts {
+        let font = inc.new_document.add_object(dictionary! {
+            "Type" => "Font",
+            "Subtype" => "Type1",
+            "BaseFont" => *base_font,
+            "Encoding" => "WinAnsiEncoding",
+        });
+        resource_category(inc, page_id, "Font")?.set(name.as_str(), font);
+    }
+    for (name, id) in &overlay.xobjects {
+        resource_category(inc, page_id, "XObject")?.set(name.as_str(), *id);
+    }
+    for (name, alpha, blend_mode) in &overlay.graphics_states {
+        let state = inc.new_document.add_object(crate::pdf_tools::transparency_state(*alpha, *blend_mode));
+        resource_category(inc, page_id, "ExtGState")?.set(name.as_str(), state);
+    }
+
+    // The original content may leave the graphics state altered, so it is
+    // bracketed in q/Q and the overlay starts from the default state. Layers
+    // are positioned in the visible (crop) box
+    let [origin_x, origin_y, ..] = crate::pdf_tools::page_box(inc.get_prev_documents(), page_id);
+    let mut operations = vec![Operation::new("Q", vec![]), Operation::new("q", vec![])];
+    if origin_x != 0.0 || origin_y != 0.0 {
+        operations.push(Operation::new("cm", vec![1.into(), 0.into(), 0.into(), 1.into(), origin_x.into(), origin_y.into()]));
+    }
+    operations.extend(overlay.operations);
+    operations.push(Operation::new("Q", vec![]));
+    let encoded = Content { operations }.encode().map_err(|e| e.to_string())?;
+    let mut overlay_stream = Stream::new(Dictionary::new(), encoded);
+    let _ = overlay_stream.compress();
+
+    let open = inc.new_document.add_object(Stream::new(Dictionary::new(), b"q\n".to_vec()));
+    let close = inc.new_document.add_object(overlay_stream);
+    let page = object_mut(inc, page_id)?.as_dict_mut().map_err(|e| e.to_string())?;
+    let mut contents = vec![open.into()];
+    match page.get(b"Contents") {
+        Ok(Object::Array(parts)) => contents.extend(parts.iter().cloned()),
+        Ok(other) => contents.push(other.clone()),
+        Err(_) => {}
+    }
+    contents.push(close.into());
+    page.set("Contents", contents);
+    Ok(())
+}
+
+/// Point the page tree at `page_ids`, in order, as one flat list
+fn rebuild_page_tree(inc: &mut IncrementalDocument, page_ids: &[ObjectId]) -> Result<(), String> {
+    let pages_id = inc
+        .get_prev_documents()
+        .catalog()
+        .and_then(|c| c.get(b"Pages"))
+        .and_then(Object::as_reference)
+        .map_err(|e| format!("PDF has no page tree: {}", e))?;
+    const INHERITABLE: [&[u8]; 4] = [b"Resources", b"MediaBox", b"CropBox", b"Rotate"];
+
+    for &page_id in page_ids {
+        let values: Vec<(&[u8], Object)> = INHERITABLE
+            .iter()
+            .filter_map(|&key| inherited(inc.get_prev_documents(), page_id, key).map(|v| (key, v)))
+            .collect();
+        let page = object_mut(inc, page_id)?.as_dict_mut().map_err(|e| e.to_string())?;
+        for (key, value) in values {
+            if !page.has(key) {
+                page.set(key, value);
+            }
+        }
+        page.set("Parent", pages_id);
+    }
+    let root = object_mut(inc, pages_id)?.as_dict_mut().map_err(|e| e.to_string())?;
+    root.set("Kids", page_ids.iter().map(|&id| Object::Reference(id)).collect::<Vec<_>>());
+    root.set("Count", page_ids.len() as i64);
+    Ok(())
+}
+
+/// Apply layer edits to the original PDF as one incremental update
+///
+/// `original` are the pages as imported from `source`; `edited` the current
+/// pages. Every edited page must come from the source file
+/// (`metadata.originalPageIndex`); pages may be dropped or reordered.
+pub fn edit_in_place(source: Vec<u8>, original: &[PageData], edited: &[PageData]) -> Result<(Vec<u8>, usize), String> {
+    let prev = Document::load_mem(&source).map_err(|e| format!("Failed to load PDF: {}", e))?;
+    if prev.is_encrypted() {
+        return Err("Edit-in-place does not support encrypted PDFs".to_string());
+    }
+    let source_pages: Vec<ObjectId> = prev.get_pages().into_values().collect();
+    let mut inc = IncrementalDocument::create_from(source, prev);
+    inc.new_document.version = inc.get_prev_documents().version.clone();
+
+    let source_index = |page: &PageData| page.metadata.as_ref().and_then(|m| m.original_page_index);
+    let mut order = Vec::with_capacity(edited.len());
+    let mut pages_updated = 0;
+    for (position, page) in edited.iter().enumerate() {
+        let index = source_index(page)
+            .filter(|&i| i < source_pages.len())
+            .ok_or_else(|| format!("Page {} is not from the source PDF; use a full export", position + 1))?;
+        if order.contains(&source_pages[index]) {
+            return Err(format!("Page {} repeats a source page; use a full export", position + 1));
+        }
+        order.push(source_pages[index]);
+
+        let before = original
+            .iter()
+            .find(|p| source_index(p) == Some(index))
+            .ok_or_else(|| format!("Imported page {} is missing", index + 1))?;
+        let edits = page_edits(before, page);
+        if edits.covers.is_empty() && edits.draws.is_empty() {
+            continue;
+        }
+        let page_id = source_pages[index];
+        let prev = inc.get_prev_documents();
+        let rotation = inherited(prev, page_id, b"Rotate").and_then(|r| r.as_i64().ok()).unwrap_or(0);
+        if rotation.rem_euclid(360) != 0 {
+            return Err(format!("Page {} is rotated; use a full export", position + 1));
+        }
+        if let Some(reason) = cover_conflict(before, page, &edits.covers) {
+            return Err(format!("Page {}: {}; use a full export", position + 1, reason));
+        }
+        // Layers are positioned from the top of the visible (crop) box
+        let [_, y0, _, y1] = crate::pdf_tools::page_box(prev, page_id);
+        let page_height = y1 - y0;
+        let originals = original_text(prev, page_id, edits.draws.iter().copied().filter(|l| edits.redrawn.contains(&l.id.as_str())));
+
+        let mut overlay = Overlay::default();
+        for cover in &edits.covers {
+            overlay.cover(page_height, cover);
+        }
+        for layer in edits.draws {
+            overlay.draw(&mut inc.new_document, page_height, layer, originals.get(layer.id.as_str()));
+        }
+        apply_overlay(&mut inc, page_id, overlay)?;
+        pages_updated += 1;
+    }
+
+    if order != source_pages {
+        rebuild_page_tree(&mut inc, &order)?;
+    }
+
+    if let Ok(info_id) = inc.get_prev_documents().trailer.get(b"Info").and_then(Object::as_reference) {
+        let stamp: String = iso8601_now().chars().filter(char::is_ascii_digit).collect();
+        if let Ok(info) = object_mut(&mut inc, info_id)?.as_dict_mut() {
+            info.set("ModDate", Object::string_literal(format!("D:{}Z", stamp)));
+        }
+    }
+
+    let mut out = Vec::new();
+    inc.save_to(&mut out).map_err(|e| format!("Failed to write PDF: {}", e))?;
+    Ok((out, pages_updated))
+}
+
+/// Export edits by appending an incremental update to the source PDF
+#[tauri::command]
+pub async fn export_pdf_in_place(
+    source_path: String,
+    original_pages: Vec<PageData>,
+    pages: Vec<PageData>,
+    output_path: String,
+) -> Result<ExportResult, String> {
+    tokio::task::spawn_blocking(move || {
+        let source = std::fs::read(&source_path).map_err(|e| format!("Failed to read PDF: {}", e))?;
+        let (pdf, pages_updated) = edit_in_place(source, &original_pages, &pages)?;
+        std::fs::write(&output_path, pdf).map_err(|e| format!("Failed to write PDF: {}", e))?;
+        Ok(ExportResult {
+            success: true,
+            message: format!("Updated {} of {} pages in place", pages_updated, pages.len()),
+            output_path: Some(output_path),
+            data: None,
+        })
+    })
+    .await
+    .map_err(|e| format!("Export task failed: {}", e))?
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::PageMetadata;
+
+    fn text_layer(text: &str, y: f32) -> LayerObject {
+        let result = OcrTextResult { text: text.to_string(), bounds: Bounds::new(72.0, y, 200.0, 14.0), confidence: 1.0 };
+        ocr_results_to_layers(vec![result], 0, 792.0, 1.0).remove(0)
+    }
+
+    fn source_pdf(pages: usize) -> Vec<u8> {
+        let mut doc = Document::with_version("1.5");
+        let pages_id = doc.new_object_id();
+        let kids: Vec<Object> = (0..pages)
+            .map(|i| {
+                let contents = doc.add_object(Stream::new(Dictionary::new(), format!("BT ({}) Tj ET", i).into_bytes()));
+                doc.add_object(dictionary! { "Type" => "Page", "Parent" => pages_id, "Contents" => contents }).into()
+            })
+            .collect();
+        doc.objects.insert(
+            pages_id,
+            Object::Dictionary(dictionary! {
+                "Type" => "Pages",
+                "Kids" => kids,
+                "Count" => pages as i64,
+                "MediaBox" => vec![0.into(), 0.into(), 612.into(), 792.into()],
+                "Resources" => dictionary! {
+                    "Font" => dictionary! {
+                        "F1" => dictionary! {
+                            "Type" => "Font",
+                            "Subtype" => "Type1",
+                            "BaseFont" => "Times-Roman",
+                            "Encoding" => "WinAnsiEncoding",
+                        },
+                    },
+                },
+            }),
+        );
+        let catalog = doc.add_object(dictionary! { "Type" => "Catalog", "Pages" => pages_id, "OpenAction" => "JavaScript" });
+        doc.trailer.set("Root", catalog);
+        let mut out = Vec::new();
+        doc.save_to(&mut out).unwrap();
+        out
+    }
+
+    fn page(index: usize, layers: Vec<LayerObject>) -> PageData {
+        PageData {
+            page_index: index,
+            width: 612.0,
+            height: 792.0,
+            dpi: Some(72),
+            layers,
+            metadata: Some(PageMetadata {
+                original_page_index: Some(index),
+                rotation: None,
+                media_box: None,
+                crop_box: None,
+                trim_box: None,
+                bleed_box: None,
+                page_label: None,
+                language: None,
+                guides: None,
+                custom: Default::default(),
+            }),
+            background: None,
+        }
+    }
+
+    #[test]
+    fn test_page_edits() {
+        let kept = text_layer("Kept", 100.0);
+        let mut edited = text_layer("Title", 300.0);
+        edited.id = "title".to_string();
+        let original = page(0, vec![kept.clone(), edited.clone()]);
+
+        assert!(page_edits(&original, &original.clone()).draws.is_empty());
+
+        edited.content = Some("New title".to_string());
+        let mut added = text_layer("Note", 500.0);
+        added.id = "note".to_string();
+        added.source_type = SourceType::Manual;
+        let current = page(0, vec![kept, edited, added]);
+        let edits = page_edits(&original, &current);
+        assert_eq!(edits.covers.len(), 1);
+        assert_eq!(edits.draws.iter().map(|l| l.id.as_str()).collect::<Vec<_>>(), ["title", "note"]);
+    }
+
+    #[test]
+    fn test_find_and_merge_ocr_regions() {
+        let mut figure = crate::scanner::image_page("figure".to_string(), 0, 300, 150, 150).layers.remove(0);
+        figure.bounds = Bounds::new(72.0, 400.0, 144.0, 72.0);
+        figure.role = LayerRole::Content;
+        let mut captioned = figure.clone();
+        captioned.id = "captioned".to_string();
+        captioned.bounds.y = 100.0;
+        let mut icon = figure.clone();
+        icon.id = "icon".to_string();
+        icon.bounds = Bounds::new(500.0, 700.0, 20.0, 20.0);
+        // Text over the second image means it was already OCR'd or is native
+        let mut page = page(0, vec![text_layer("Body", 50.0), figure, text_layer("In figure", 110.0), captioned, icon]);
+        page.layers[2].bounds.height = 60.0;
+
+        let regions = find_ocr_regions(&page);
+        assert_eq!(regions.len(), 1);
+        assert_eq!(regions[0].layer_id, "figure");
+
+        let mut repeat = text_layer("Body", 50.0);
+        repeat.bounds = Bounds::new(80.0, 52.0, 180.0, 12.0);
+        let lines = vec![text_layer("Axis label", 410.0), repeat];
+        assert_eq!(merge_region_lines(&mut page, &regions[0], lines), 1);
+        let added = page.layers.last().unwrap();
+        assert_eq!(added.id, "ocr-0-figure-0");
+        assert_eq!(added.content.as_deref(), Some("Axis label"));
+        assert!(page.layers.iter().all(|l| l.id == added.id || l.z_index < added.z_index));
+        // Once merged, the region no longer counts as image-only
+        assert!(find_ocr_regions(&page).is_empty());
+    }
+
+    #[test]
+    fn test_edit_in_place_appends_update() {
+        let source = source_pdf(2);
+        let original = vec![page(0, vec![text_layer("Hello", 100.0)]), page(1, vec![])];
+        let mut first = original[0].clone();
+        first.layers[0].content = Some("Hello, world".to_string());
+        let edited = vec![original[1].clone(), first];
+
+        let (pdf, updated) = edit_in_place(source.clone(), &original, &edited).unwrap();
+        assert_eq!(updated, 1);
+        assert!(pdf.starts_with(&source));
+
+        let doc = Document::load_mem(&pdf).unwrap();
+        let pages: Vec<ObjectId> = doc.get_pages().into_values().collect();
+        assert_eq!(pages.len(), 2);
+        // Reordered, and the untouched catalog entries survive
+        assert_eq!(doc.get_page_content(pages[0]).unwrap(), b"BT (1) Tj ET");
+        assert!(doc.catalog().unwrap().has(b"OpenAction"));
+
+        let content = String::from_utf8_lossy(&doc.get_page_content(pages[1]).unwrap()).to_string();
+        assert!(content.starts_with("q\n") && content.contains("(0) Tj"));
+        assert!(content.contains("(Hello, world) Tj"));
+        let (resources, _) = doc.get_page_resources(pages[1]).unwrap();
+        assert!(resources.unwrap().get(b"Font").unwrap().as_dict().unwrap().has(b"RookF1"));
+
+        let mut stray = page(2, vec![]);
+        stray.metadata = None;
+        assert!(edit_in_place(source, &original, &[stray]).is_err());
+    }
+
+    #[test]
+    fn test_edit_in_place_redraws_in_original_fonts() {
+        let mut kept = text_layer("Kept", 100.0);
+        kept.id = "kept".to_string();
+        kept.font_family = Some("Times Roman".to_string());
+        let mut title = text_layer("Title", 104.0);
+        title.id = "title".to_string();
+        let original = vec![page(0, vec![kept, title])];
+        let mut edited = original.clone();
+        edited[0].layers[1].content = Some("New title".to_string());
+
+        let (pdf, _) = edit_in_place(source_pdf(1), &original, &edited).unwrap();
+        let doc = Document::load_mem(&pdf).unwrap();
+        let page_id = *doc.get_pages().values().next().unwrap();
+        let content = String::from_utf8_lossy(&doc.get_page_content(page_id).unwrap()).to_string();
+        // The untouched layer under the cover keeps its font; the edit is set in Helvetica
+        assert!(content.contains("/F1 12 Tf") && content.contains("(Kept) Tj"));
+        assert!(content.contains("/RookF1 12 Tf") && content.contains("(New title) Tj"));
+    }
+
+    #[test]
+    fn test_edit_in_place_refuses_unsafe_pages() {
+        let mut title = text_layer("Title", 104.0);
+        title.id = "title".to_string();
+        let mut figure = crate::scanner::image_page("figure".to_string(), 0, 300, 150, 150).layers.remove(0);
+        figure.bounds = Bounds::new(60.0, 90.0, 240.0, 40.0);
+        figure.z_index = -1;
+        let original = vec![page(0, vec![figure, title.clone()]), page(1, vec![title])];
+
+        // A cover on an image would blank it
+        let mut edited = original.clone();
+        edited[0].layers[1].content = Some("New title".to_string());
+        let error = edit_in_place(source_pdf(2), &original, &edited[..1]).unwrap_err();
+        assert!(error.contains("image or fill"), "{}", error);
+        // Deleting the image itself is fine
+        edited[0].layers.remove(0);
+        assert!(edit_in_place(source_pdf(2), &original, &edited[..1]).is_ok());
+
+        let mut doc = Document::load_mem(&source_pdf(2)).unwrap();
+        let second = *doc.get_pages().get(&2).unwrap();
+        doc.get_dictionary_mut(second).unwrap().set("Rotate", 90);
+        let mut rotated = Vec::new();
+        doc.save_to(&mut rotated).unwrap();
+        let mut edited = original.clone();
+        edited[1].layers[0].content = Some("New title".to_string());
+        let error = edit_in_place(rotated, &original, &edited).unwrap_err();
+        assert!(error.contains("rotated"), "{}", error);
+    }
+}
@@ -0,0 +1,288 @@
+//! PDF Repair Module
+//!
+//! Recovery for damaged PDFs. A broken or missing cross-reference table is
+//! rebuilt by scanning the file for objects, references to objects that no
+//! longer exist are dropped (readers treat them as null anyway), and each
+//! page is checked on its own so one unreadable page is reported and
+//! skipped instead of failing the whole import.
+
+use crate::models::{PageRepair, PageRepairStatus, RepairReport};
+use lopdf::content::Content;
+use lopdf::{Dictionary, Document, Object, ObjectId};
+use sha2::{Digest, Sha256};
+use std::collections::BTreeSet;
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::RwLock;
+
+lazy_static::lazy_static! {
+    /// Folder for repaired copies, in the app cache
+    static ref REPAIR_DIR: RwLock<Option<PathBuf>> = RwLock::new(None);
+}
+
+/// Set the folder for repaired copies, removing the ones left by earlier runs
+///
+/// Copies are read lazily (deferred images, the image index) for as long as
+/// the document is open, so they are only cleaned up on the next start.
+pub fn init_pdf_repair(dir: PathBuf) -> Result<(), String> {
+    let _ = std::fs::remove_dir_all(&dir);
+    std::fs::create_dir_all(&dir).map_err(|e| e.to_string())?;
+    *REPAIR_DIR.write().map_err(|e| e.to_string())? = Some(dir);
+    Ok(())
+}
+
+/// Open a PDF, repairing it when it does not load as-is
+///
+/// The report lists every page once anything had to be repaired, and only
+/// the failed ones otherwise.
+pub fn load_repaired(data: &[u8]) -> Result<(Document, RepairReport), String> {
+    let mut report = RepairReport::default();
+    let mut doc = match Document::load_mem(data) {
+        Ok(doc) => doc,
+        Err(e) => {
+            let doc = Document::load_mem_rebuilding_xref(data)
+                .map_err(|rebuild| format!("Failed to load PDF: {} (repair failed: {})", e, rebuild))?;
+            report.load_error = Some(e.to_string());
+            report.xref_rebuilt = true;
+            doc
+        }
+    };
+
+    // Checked before pruning, which would turn missing content into empty pages
+    let checks: Vec<Result<(), String>> = doc.page_iter().map(|id| check_page(&doc, id)).collect();
+    report.dangling_references = drop_dangling_references(&mut doc);
+    if doc.get_pages().is_empty() {
+        return Err(report.load_error.map_or_else(
+            || "PDF has no readable pages".to_string(),
+            |e| format!("Failed to load PDF: {} (no pages recovered)", e),
+        ));
+    }
+
+    let repaired = report.xref_rebuilt || report.dangling_references > 0;
+    for (page_index, check) in checks.into_iter().enumerate() {
+        match check {
+            Err(error) => report.pages.push(PageRepair {
+                page_index,
+                status: PageRepairStatus::Failed,
+                error: Some(error),
+            }),
+            Ok(()) if repaired => report.pages.push(PageRepair {
+                page_index,
+                status: PageRepairStatus::Recovered,
+                error: None,
+            }),
+            Ok(()) => {}
+        }
+    }
+    Ok((doc, report))
+}
+
+/// Why a page cannot be read, if it cannot
+fn check_page(doc: &Document, page_id: ObjectId) -> Result<(), String> {
+    doc.get_dictionary(page_id)
+        .map_err(|e| format!("Page object {} {} unreadable: {}", page_id.0, page_id.1, e))?;
+    for id in doc.get_page_contents(page_id) {
+        doc.get_object(id)
+            .and_then(Object::as_stream)
+            .map_err(|e| format!("Content stream {} {} unreadable: {}", id.0, id.1, e))?;
+    }
+    let content = doc.get_page_content(page_id).map_err(|e| e.to_string())?;
+    Content::decode(&content).map_err(|e| format!("Content stream unparseable: {}", e))?;
+    Ok(())
+}
+
+fn is_dangling(object: &Object, existing: &BTreeSet<ObjectId>) -> bool {
+    matches!(object, Object::Reference(id) if !existing.contains(id))
+}
+
+/// Remove references to missing objects, returning how many were removed
+///
+/// Dictionary entries are dropped; array items become null so positional
+/// arrays such as destinations keep their shape.
+pub fn drop_dangling_references(doc: &mut Document) -> usize {
+    let existing: BTreeSet<ObjectId> = doc.objects.keys().copied().collect();
+    let mut dropped = prune_dict(&mut doc.trailer, &existing);
+    for object in doc.objects.values_mut() {
+        dropped += prune(object, &existing);
+    }
+    dropped
+}
+
+fn prune(object: &mut Object, existing: &BTreeSet<ObjectId>) -> usize {
+    match object {
+        Object::Dictionary(dict) => prune_dict(dict, existing),
+        Object::Stream(stream) => prune_dict(&mut stream.dict, existing),
+        Object::Array(items) => items
+            .iter_mut()
+            .map(|item| {
+                if is_dangling(item, existing) {
+                    *item = Object::Null;
+                    1
+                } else {
+                    prune(item, existing)
+                }
+            })
+            .sum(),
+        _ => 0,
+    }
+}
+
+fn prune_dict(dict: &mut Dictionary, existing: &BTreeSet<ObjectId>) -> usize {
+    let dangling: Vec<Vec<u8>> = dict
+        .iter()
+        .filter(|(_, value)| is_dangling(value, existing))
+        .map(|(key, _)| key.clone())
+        .collect();
+    for key in &dangling {
+        dict.remove(key);
+    }
+    dangling.len() + dict.iter_mut().map(|(_, value)| prune(value, existing)).sum::<usize>()
+}
+
+/// Write a repaired copy of `path` to the app cache, for readers that need a file
+///
+/// Failed pages are kept so page indices still match the original. The copy
+/// is named after a hash of the source path, so files that share a name do
+/// not overwrite each other, and repairing the same file again replaces it.
+pub fn repair_to_temp(path: &str) -> Result<(PathBuf, RepairReport), String> {
+    let data = std::fs::read(path).map_err(|e| format!("Failed to read {}: {}", path, e))?;
+    let (mut doc, report) = load_repaired(&data)?;
+    let dir = REPAIR_DIR
+        .read()
+        .map_err(|e| e.to_string())?
+        .clone()
+        .unwrap_or_else(|| std::env::temp_dir().join("rook-repaired"));
+    std::fs::create_dir_all(&dir).map_err(|e| format!("Failed to create repair folder: {}", e))?;
+    let out = dir.join(repaired_name(path));
+
+    // Written aside and renamed, as an earlier copy may still be read
+    static WRITES: AtomicUsize = AtomicUsize::new(0);
+    let partial = out.with_extension(format!("{}-{}.tmp", std::process::id(), WRITES.fetch_add(1, Ordering::Relaxed)));
+    let written = doc
+        .save(&partial)
+        .map_err(|e| e.to_string())
+        .and_then(|_| std::fs::rename(&partial, &out).map_err(|e| e.to_string()));
+    if let Err(e) = written {
+        let _ = std::fs::remove_file(&partial);
+        return Err(format!("Failed to write repaired PDF: {}", e));
+    }
+    Ok((out, report))
+}
+
+/// File name of the repaired copy of `path`
+fn repaired_name(path: &str) -> String {
+    let source = std::fs::canonicalize(path).unwrap_or_else(|_| PathBuf::from(path));
+    let digest = Sha256::digest(source.to_string_lossy().as_bytes());
+    let hash: String = digest[..8].iter().map(|b| format!("{:02x}", b)).collect();
+    let stem = Path::new(path).file_stem().map_or_else(|| "document".into(), |s| s.to_string_lossy());
+    format!("{}-{}.pdf", stem, hash)
+}
+
+/// Check a PDF for damage without changing it
+#[tauri::command]
+pub async fn validate_pdf(file_path: String) -> Result<RepairReport, String> {
+    tokio::task::spawn_blocking(move || {
+        let data = std::fs::read(&file_path).map_err(|e| format!("Failed to read {}: {}", file_path, e))?;
+        load_repaired(&data).map(|(_, report)| report)
+    })
+    .await
+    .map_err(|e| format!("Validate task failed: {}", e))?
+}
+
+/// Save a repaired copy of a PDF, leaving out pages that could not be read
+#[tauri::command]
+pub async fn repair_pdf(input_path: String, output_path: String) -> Result<RepairReport, String> {
+    tokio::task::spawn_blocking(move || {
+        let data = std::fs::read(&input_path).map_err(|e| format!("Failed to read {}: {}", input_path, e))?;
+        let (mut doc, report) = load_repaired(&data)?;
+        let failed: Vec<u32> = report.failed_pages().iter().map(|&i| i as u32 + 1).collect();
+        if failed.len() == doc.get_pages().len() {
+            return Err("No pages could be recovered".to_string());
+        }
+        doc.delete_pages(&failed);
+        doc.prune_objects();
+        doc.compress();
+        doc.save(&output_path).map_err(|e| format!("Failed to write {}: {}", output_path, e))?;
+        tracing::info!(path = %input_path, failed = failed.len(), "repaired PDF");
+        Ok(report)
+    })
+    .await
+    .map_err(|e| format!("Repair task failed: {}", e))?
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use lopdf::{dictionary, Stream};
+
+    /// Two-page PDF whose second page points at a content stream that is gone
+    fn damaged() -> Vec<u8> {
+        let mut doc = Document::with_version("1.5");
+        let pages_id = doc.new_object_id();
+        let content = doc.add_object(Stream::new(Dictionary::new(), b"BT (Intact) Tj ET".to_vec()));
+        let first = doc.add_object(dictionary! { "Type" => "Page", "Parent" => pages_id, "Contents" => content });
+        let second = doc.add_object(dictionary! { "Type" => "Page", "Parent" => pages_id, "Contents" => (90, 0) });
+        doc.objects.insert(
+            pages_id,
+            Object::Dictionary(dictionary! {
+                "Type" => "Pages",
+                "Kids" => vec![Object::from(first), Object::from(second)],
+                "Count" => 2,
+                "MediaBox" => vec![Object::Integer(0), 0.into(), 612.into(), 792.into()],
+            }),
+        );
+        let catalog = doc.add_object(dictionary! { "Type" => "Catalog", "Pages" => pages_id, "Outlines" => (91, 0) });
+        doc.trailer.set("Root", catalog);
+        let mut data = Vec::new();
+        doc.save_to(&mut data).unwrap();
+        data
+    }
+
+    #[test]
+    fn test_load_repaired_reports_pages() {
+        let (doc, report) = load_repaired(&damaged()).unwrap();
+        assert!(!report.xref_rebuilt);
+        assert_eq!(report.dangling_references, 2);
+        assert_eq!(report.failed_pages(), vec![1]);
+        assert_eq!(report.pages[0].status, PageRepairStatus::Recovered);
+        let catalog = doc.catalog().unwrap();
+        assert!(!catalog.has(b"Outlines"));
+    }
+
+    #[test]
+    fn test_load_repaired_rebuilds_xref() {
+        let mut data = damaged();
+        let xref = data.windows(4).rposition(|w| w == b"xref").unwrap();
+        data.truncate(xref);
+        let (doc, report) = load_repaired(&data).unwrap();
+        assert!(report.xref_rebuilt && report.load_error.is_some());
+        assert_eq!(doc.get_pages().len(), 2);
+        assert_eq!(report.failed_pages(), vec![1]);
+        assert!(load_repaired(b"%PDF-1.5\nnot a pdf").is_err());
+    }
+
+    #[test]
+    fn test_repaired_copies_of_same_named_files_are_kept_apart() {
+        let base = std::env::temp_dir().join(format!("rook-repair-test-{}", std::process::id()));
+        let paths: Vec<String> = ["a", "b"]
+            .iter()
+            .map(|folder| {
+                let dir = base.join(folder);
+                std::fs::create_dir_all(&dir).unwrap();
+                let path = dir.join("scan.pdf");
+                std::fs::write(&path, damaged()).unwrap();
+                path.to_string_lossy().to_string()
+            })
+            .collect();
+
+        let (first, _) = repair_to_temp(&paths[0]).unwrap();
+        let (second, _) = repair_to_temp(&paths[1]).unwrap();
+        assert_ne!(first, second);
+        assert_eq!(repair_to_temp(&paths[0]).unwrap().0, first);
+        assert!(Document::load(&first).is_ok() && Document::load(&second).is_ok());
+
+        let _ = std::fs::remove_dir_all(&base);
+        let _ = std::fs::remove_file(&first);
+        let _ = std::fs::remove_file(&second);
+    }
+}
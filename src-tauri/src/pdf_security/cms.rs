@@ -0,0 +1,180 @@
+//! CMS (PKCS#7) detached signatures
+//!
+//! Builds the SignedData blob embedded in a PDF signature's `/Contents`:
+//! SHA-256 digest, signing time and the signer's certificate chain, signed
+//! with an RSA (PKCS#1 v1.5) or ECDSA P-256 key.
+
+use super::der::{self, Reader};
+use super::pkcs12::Identity;
+use ring::rand::SystemRandom;
+use ring::signature::{self, EcdsaKeyPair, RsaKeyPair};
+
+const DATA: &str = "1.2.840.113549.1.7.1";
+const SIGNED_DATA: &str = "1.2.840.113549.1.7.2";
+const SHA256: &str = "2.16.840.1.101.3.4.2.1";
+const RSA_ENCRYPTION: &str = "1.2.840.113549.1.1.1";
+const ECDSA_WITH_SHA256: &str = "1.2.840.10045.4.3.2";
+const CONTENT_TYPE: &str = "1.2.840.113549.1.9.3";
+const MESSAGE_DIGEST: &str = "1.2.840.113549.1.9.4";
+const SIGNING_TIME: &str = "1.2.840.113549.1.9.5";
+
+enum SigningKey {
+    Rsa(RsaKeyPair),
+    Ecdsa(EcdsaKeyPair),
+}
+
+impl SigningKey {
+    fn from_pkcs8(pkcs8: &[u8], rng: &SystemRandom) -> Result<Self, String> {
+        if let Ok(key) = RsaKeyPair::from_pkcs8(pkcs8) {
+            return Ok(SigningKey::Rsa(key));
+        }
+        EcdsaKeyPair::from_pkcs8(&signature::ECDSA_P256_SHA256_ASN1_SIGNING, pkcs8, rng)
+            .map(SigningKey::Ecdsa)
+            .map_err(|e| format!("Unsupported signing key (RSA 2048+ or ECDSA P-256 required): {}", e))
+    }
+
+    /// SignerInfo signatureAlgorithm
+    fn algorithm(&self) -> Vec<u8> {
+        match self {
+            SigningKey::Rsa(_) => der::algorithm(RSA_ENCRYPTION, Some(&der::tlv(der::NULL, &[]))),
+            SigningKey::Ecdsa(_) => der::algorithm(ECDSA_WITH_SHA256, None),
+        }
+    }
+
+    fn sign(&self, message: &[u8], rng: &SystemRandom) -> Result<Vec<u8>, String> {
+        match self {
+            SigningKey::Rsa(key) => {
+                let mut sig = vec![0u8; key.public().modulus_len()];
+                key.sign(&signature::RSA_PKCS1_SHA256, rng, message, &mut sig)
+                    .map_err(|_| "RSA signing failed".to_string())?;
+                Ok(sig)
+            }
+            SigningKey::Ecdsa(key) => key
+                .sign(rng, message)
+                .map(|sig| sig.as_ref().to_vec())
+                .map_err(|_| "ECDSA signing failed".to_string()),
+        }
+    }
+}
+
+/// Raw issuer Name and serialNumber INTEGER of a certificate
+fn issuer_and_serial(certificate: &[u8]) -> Result<(Vec<u8>, Vec<u8>), String> {
+    let mut cert = Reader::new(Reader::new(certificate).expect(der::SEQUENCE)?);
+    let mut tbs = Reader::new(cert.expect(der::SEQUENCE)?);
+    tbs.optional(der::context(0))?;
+    let serial = tbs.read()?;
+    if serial.tag != der::INTEGER {
+        return Err("Malformed certificate serial number".to_string());
+    }
+    tbs.expect(der::SEQUENCE)?;
+    let issuer = tbs.read()?;
+    Ok((issuer.raw.to_vec(), serial.raw.to_vec()))
+}
+
+/// Build a detached SignedData over a SHA-256 `digest`
+///
+/// `signing_time` is a UTCTime string (`YYMMDDHHMMSSZ`).
+pub fn signed_data(identity: &Identity, digest: &[u8], signing_time: &str) -> Result<Vec<u8>, String> {
+    let rng = SystemRandom::new();
+    let key = SigningKey::from_pkcs8(&identity.private_key, &rng)?;
+    let (issuer, serial) = issuer_and_serial(&identity.certificate)?;
+    let sha256 = der::algorithm(SHA256, Some(&der::tlv(der::NULL, &[])));
+
+    let attribute = |oid: &str, value: Vec<u8>| der::sequence(&[&der::oid(oid), &der::set_of(vec![value])]);
+    let attributes = vec![
+        attribute(CONTENT_TYPE, der::oid(DATA)),
+        attribute(SIGNING_TIME, der::tlv(der::UTC_TIME, signing_time.as_bytes())),
+        attribute(MESSAGE_DIGEST, der::tlv(der::OCTET_STRING, digest)),
+    ];
+    // Signed as a SET OF; embedded with the [0] IMPLICIT tag
+    let signed_attributes = der::set_of(attributes);
+    let signature = key.sign(&signed_attributes, &rng)?;
+    let mut implicit_attributes = signed_attributes;
+    implicit_attributes[0] = der::context(0);
+
+    let signer_info = der::sequence(&[
+        &der::integer(&[1]),
+        &der::sequence(&[&issuer, &serial]),
+        &sha256,
+        &implicit_attributes,
+        &key.algorithm(),
+        &der::tlv(der::OCTET_STRING, &signature),
+    ]);
+    let certificates = der::tlv(der::context(0), &identity.chain.concat());
+    let signed = der::sequence(&[
+        &der::integer(&[1]),
+        &der::set_of(vec![sha256.clone()]),
+        &der::sequence(&[&der::oid(DATA)]),
+        &certificates,
+        &der::set_of(vec![signer_info]),
+    ]);
+    Ok(der::sequence(&[&der::oid(SIGNED_DATA), &der::tlv(der::context(0), &signed)]))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use ring::signature::{KeyPair, UnparsedPublicKey, ECDSA_P256_SHA256_ASN1, ECDSA_P256_SHA256_ASN1_SIGNING};
+
+    fn name(common_name: &str) -> Vec<u8> {
+        der::sequence(&[&der::set_of(vec![der::sequence(&[&der::oid("2.5.4.3"), &der::tlv(0x0C, common_name.as_bytes())])])])
+    }
+
+    /// Certificate with just the fields up to the issuer, optionally with a
+    /// v3 `[0]` version
+    fn certificate(v3: bool, serial: &[u8], issuer: &[u8]) -> Vec<u8> {
+        let version = der::tlv(der::context(0), &der::integer(&[2]));
+        let serial = der::integer(serial);
+        let (algorithm, subject) = (der::algorithm(ECDSA_WITH_SHA256, None), name("Subject"));
+        let mut fields: Vec<&[u8]> = vec![&serial, &algorithm, issuer, &subject];
+        if v3 {
+            fields.insert(0, &version);
+        }
+        der::sequence(&[&der::sequence(&fields)])
+    }
+
+    #[test]
+    fn test_issuer_and_serial() {
+        let issuer = name("ROOK Test CA");
+        for version in [true, false] {
+            let (found_issuer, serial) = issuer_and_serial(&certificate(version, &[0x01, 0x42], &issuer)).unwrap();
+            assert_eq!(found_issuer, issuer);
+            assert_eq!(serial, der::integer(&[0x01, 0x42]));
+        }
+
+        // A serial that isn't an INTEGER
+        let bad = der::sequence(&[&der::sequence(&[&der::tlv(der::OCTET_STRING, &[1]), &issuer])]);
+        assert!(issuer_and_serial(&bad).is_err());
+        assert!(issuer_and_serial(b"not der").is_err());
+    }
+
+    #[test]
+    fn test_signer_info_names_the_certificate() {
+        let rng = SystemRandom::new();
+        let pkcs8 = EcdsaKeyPair::generate_pkcs8(&ECDSA_P256_SHA256_ASN1_SIGNING, &rng).unwrap();
+        let key = EcdsaKeyPair::from_pkcs8(&ECDSA_P256_SHA256_ASN1_SIGNING, pkcs8.as_ref(), &rng).unwrap();
+        let issuer = name("ROOK Test CA");
+        let cert = certificate(true, &[0x07], &issuer);
+        let identity = Identity { private_key: pkcs8.as_ref().to_vec(), certificate: cert.clone(), chain: vec![cert] };
+
+        let blob = signed_data(&identity, &[9u8; 32], "261017120000Z").unwrap();
+        let mut content_info = Reader::new(Reader::new(&blob).expect(der::SEQUENCE).unwrap());
+        assert!(der::is_oid(content_info.expect(der::OID).unwrap(), SIGNED_DATA));
+        let mut signed =
+            Reader::new(Reader::new(content_info.expect(der::context(0)).unwrap()).expect(der::SEQUENCE).unwrap());
+        for tag in [der::INTEGER, der::SET, der::SEQUENCE, der::context(0)] {
+            signed.expect(tag).unwrap();
+        }
+        let mut info = Reader::new(Reader::new(signed.expect(der::SET).unwrap()).expect(der::SEQUENCE).unwrap());
+        info.expect(der::INTEGER).unwrap();
+        assert_eq!(info.expect(der::SEQUENCE).unwrap(), [issuer, der::integer(&[0x07])].concat());
+        info.expect(der::SEQUENCE).unwrap();
+        let attributes = info.read().unwrap();
+        info.expect(der::SEQUENCE).unwrap();
+        let signature = info.expect(der::OCTET_STRING).unwrap();
+
+        let mut message = attributes.raw.to_vec();
+        message[0] = der::SET;
+        UnparsedPublicKey::new(&ECDSA_P256_SHA256_ASN1, key.public_key().as_ref()).verify(&message, signature).unwrap();
+    }
+}
@@ -0,0 +1,210 @@
+//! Minimal DER reader and writer
+//!
+//! Just enough ASN.1 for PKCS#12 containers, X.509 certificate headers and
+//! CMS signatures. Only definite lengths are accepted (DER, not BER).
+
+pub const INTEGER: u8 = 0x02;
+pub const OCTET_STRING: u8 = 0x04;
+pub const NULL: u8 = 0x05;
+pub const OID: u8 = 0x06;
+pub const UTC_TIME: u8 = 0x17;
+pub const SEQUENCE: u8 = 0x30;
+pub const SET: u8 = 0x31;
+
+/// Context-specific constructed tag `[n]`
+#[inline]
+pub const fn context(n: u8) -> u8 {
+    0xA0 | n
+}
+
+/// One tag-length-value element
+#[derive(Debug, Clone, Copy)]
+pub struct Tlv<'a> {
+    pub tag: u8,
+    pub content: &'a [u8],
+    /// The whole element including tag and length
+    pub raw: &'a [u8],
+}
+
+/// Sequential reader over concatenated DER elements
+pub struct Reader<'a> {
+    data: &'a [u8],
+}
+
+impl<'a> Reader<'a> {
+    #[inline]
+    pub fn new(data: &'a [u8]) -> Self {
+        Self { data }
+    }
+
+    #[inline]
+    pub fn is_empty(&self) -> bool {
+        self.data.is_empty()
+    }
+
+    #[inline]
+    pub fn peek_tag(&self) -> Option<u8> {
+        self.data.first().copied()
+    }
+
+    pub fn read(&mut self) -> Result<Tlv<'a>, String> {
+        let data = self.data;
+        let (&tag, rest) = data.split_first().ok_or("Unexpected end of DER data")?;
+        let (&first, rest) = rest.split_first().ok_or("Truncated DER length")?;
+        let (len, header) = match first {
+            0x80 => return Err("Indefinite-length (BER) encoding is not supported".to_string()),
+            n if n < 0x80 => (n as usize, 2),
+            n => {
+                let count = (n & 0x7F) as usize;
+                if count > 4 || rest.len() < count {
+                    return Err("Invalid DER length".to_string());
+                }
+                let len = rest[..count].iter().fold(0usize, |acc, &b| (acc << 8) | b as usize);
+                (len, 2 + count)
+            }
+        };
+        if data.len() < header + len {
+            return Err("Truncated DER element".to_string());
+        }
+        let tlv = Tlv { tag, content: &data[header..header + len], raw: &data[..header + len] };
+        self.data = &data[header + len..];
+        Ok(tlv)
+    }
+
+    /// Read an element that must carry `tag`, returning its content
+    pub fn expect(&mut self, tag: u8) -> Result<&'a [u8], String> {
+        let tlv = self.read()?;
+        if tlv.tag != tag {
+            return Err(format!("Expected DER tag {:#04x}, found {:#04x}", tag, tlv.tag));
+        }
+        Ok(tlv.content)
+    }
+
+    /// Read the next element if it carries `tag`
+    pub fn optional(&mut self, tag: u8) -> Result<Option<&'a [u8]>, String> {
+        if self.peek_tag() == Some(tag) {
+            self.expect(tag).map(Some)
+        } else {
+            Ok(None)
+        }
+    }
+}
+
+/// Interpret INTEGER content as an unsigned number
+pub fn to_u64(content: &[u8]) -> Result<u64, String> {
+    let content = match content {
+        [0, rest @ ..] => rest,
+        other => other,
+    };
+    if content.len() > 8 {
+        return Err("Integer too large".to_string());
+    }
+    Ok(content.iter().fold(0u64, |acc, &b| (acc << 8) | b as u64))
+}
+
+/// Encode one element
+pub fn tlv(tag: u8, content: &[u8]) -> Vec<u8> {
+    let mut out = vec![tag];
+    match content.len() {
+        n if n < 0x80 => out.push(n as u8),
+        n => {
+            let bytes = n.to_be_bytes();
+            let skip = bytes.iter().take_while(|&&b| b == 0).count();
+            out.push(0x80 | (bytes.len() - skip) as u8);
+            out.extend_from_slice(&bytes[skip..]);
+        }
+    }
+    out.extend_from_slice(content);
+    out
+}
+
+/// Encode a constructed element from already-encoded parts
+pub fn constructed(tag: u8, parts: &[&[u8]]) -> Vec<u8> {
+    tlv(tag, &parts.concat())
+}
+
+#[inline]
+pub fn sequence(parts: &[&[u8]]) -> Vec<u8> {
+    constructed(SEQUENCE, parts)
+}
+
+/// SET OF, with members sorted as DER requires
+pub fn set_of(mut parts: Vec<Vec<u8>>) -> Vec<u8> {
+    parts.sort();
+    tlv(SET, &parts.concat())
+}
+
+/// Content bytes of a dotted object identifier
+pub fn oid_content(dotted: &str) -> Vec<u8> {
+    let arcs: Vec<u64> = dotted.split('.').filter_map(|a| a.parse().ok()).collect();
+    let mut out = Vec::new();
+    if arcs.len() < 2 {
+        return out;
+    }
+    let mut push = |mut arc: u64| {
+        let mut bytes = vec![(arc & 0x7F) as u8];
+        arc >>= 7;
+        while arc > 0 {
+            bytes.push(0x80 | (arc & 0x7F) as u8);
+            arc >>= 7;
+        }
+        out.extend(bytes.into_iter().rev());
+    };
+    push(arcs[0] * 40 + arcs[1]);
+    for &arc in &arcs[2..] {
+        push(arc);
+    }
+    out
+}
+
+#[inline]
+pub fn oid(dotted: &str) -> Vec<u8> {
+    tlv(OID, &oid_content(dotted))
+}
+
+/// Whether OID content matches a dotted identifier
+#[inline]
+pub fn is_oid(content: &[u8], dotted: &str) -> bool {
+    content == oid_content(dotted).as_slice()
+}
+
+/// Unsigned INTEGER from big-endian bytes
+pub fn integer(value: &[u8]) -> Vec<u8> {
+    let trimmed: &[u8] = match value.iter().position(|&b| b != 0) {
+        Some(i) => &value[i..],
+        None => &[0],
+    };
+    if trimmed[0] & 0x80 != 0 {
+        tlv(INTEGER, &[&[0u8][..], trimmed].concat())
+    } else {
+        tlv(INTEGER, trimmed)
+    }
+}
+
+/// AlgorithmIdentifier with optional parameters
+pub fn algorithm(dotted: &str, params: Option<&[u8]>) -> Vec<u8> {
+    match params {
+        Some(params) => sequence(&[&oid(dotted), params]),
+        None => sequence(&[&oid(dotted)]),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_round_trip() {
+        assert_eq!(oid("1.2.840.113549.1.7.2"), [0x06, 0x09, 0x2A, 0x86, 0x48, 0x86, 0xF7, 0x0D, 0x01, 0x07, 0x02]);
+        assert_eq!(integer(&[0x80]), [0x02, 0x02, 0x00, 0x80]);
+
+        let long = vec![7u8; 300];
+        let encoded = sequence(&[&tlv(OCTET_STRING, &long), &integer(&[1, 0])]);
+        let mut outer = Reader::new(&encoded);
+        let mut inner = Reader::new(outer.expect(SEQUENCE).unwrap());
+        assert_eq!(inner.expect(OCTET_STRING).unwrap(), long.as_slice());
+        assert_eq!(to_u64(inner.expect(INTEGER).unwrap()).unwrap(), 256);
+        assert!(inner.is_empty() && outer.is_empty());
+        assert!(Reader::new(&[0x30, 0x80, 0, 0]).read().is_err());
+    }
+}
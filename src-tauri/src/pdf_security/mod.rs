@@ -0,0 +1,566 @@
+//! PDF Security Module
+//!
+//! Password protection (AES-256, PDF 2.0 security handler) and PKCS#12
+//! digital signatures (adbe.pkcs7.detached) for exported PDFs, built on
+//! lopdf and a pure-Rust crypto stack (ring, RustCrypto AES).
+//!
+//! Signing appends the signature as an incremental update and patches a
+//! reserved `/Contents` hole in it, so earlier signatures stay valid and it
+//! must be the last step; signing an encrypted file is not supported.
+
+mod cms;
+mod der;
+mod pkcs12;
+
+use crate::models::iso8601_now;
+use lopdf::encryption::crypt_filters::{Aes256CryptFilter, CryptFilter};
+use lopdf::{
+    dictionary, Dictionary, Document, EncryptionState, EncryptionVersion, IncrementalDocument, Object, Permissions,
+    StringFormat,
+};
+use ring::rand::SecureRandom;
+use sha2::{Digest, Sha256};
+use std::collections::BTreeMap;
+use std::sync::Arc;
+
+pub use vortex_core::export::{PdfEncryption, PdfPermissions, PdfSignature};
+
+/// Bytes reserved for the CMS signature (certificate chain included)
+const SIGNATURE_CAPACITY: usize = 16 * 1024;
+/// Wide placeholder so the real byte offsets always fit when patched in
+const BYTE_RANGE_PLACEHOLDER: i64 = 9_999_999_999;
+
+fn permissions(p: &PdfPermissions) -> Permissions {
+    let mut flags = Permissions::COPYABLE_FOR_ACCESSIBILITY;
+    for (allowed, flag) in [
+        (p.print, Permissions::PRINTABLE),
+        (p.print && p.print_high_quality, Permissions::PRINTABLE_IN_HIGH_QUALITY),
+        (p.copy, Permissions::COPYABLE),
+        (p.modify, Permissions::MODIFIABLE),
+        (p.annotate, Permissions::ANNOTABLE),
+        (p.fill_forms, Permissions::FILLABLE),
+        (p.assemble, Permissions::ASSEMBLABLE),
+    ] {
+        if allowed {
+            flags |= flag;
+        }
+    }
+    flags
+}
+
+/// Encrypt a PDF with AES-256
+pub fn encrypt(pdf: &[u8], encryption: &PdfEncryption) -> Result<Vec<u8>, String> {
+    if encryption.owner_password.is_empty() {
+        return Err("An owner password is required to encrypt".to_string());
+    }
+    let mut doc = Document::load_mem(pdf).map_err(|e| format!("Failed to load PDF: {}", e))?;
+    if doc.is_encrypted() {
+        return Err("PDF is already encrypted".to_string());
+    }
+
+    let mut file_key = [0u8; 32];
+    ring::rand::SystemRandom::new()
+        .fill(&mut file_key)
+        .map_err(|_| "Failed to generate encryption key".to_string())?;
+    let filter: Arc<dyn CryptFilter> = Arc::new(Aes256CryptFilter);
+    let state = EncryptionState::try_from(EncryptionVersion::V5 {
+        encrypt_metadata: true,
+        crypt_filters: BTreeMap::from([(b"StdCF".to_vec(), filter)]),
+        file_encryption_key: &file_key,
+        stream_filter: b"StdCF".to_vec(),
+        string_filter: b"StdCF".to_vec(),
+        owner_password: &encryption.owner_password,
+        user_password: &encryption.user_password,
+        permissions: permissions(&encryption.permissions),
+    })
+    .map_err(|e| format!("Failed to set up encryption: {}", e))?;
+
+    // AES-256 (revision 6) is a PDF 2.0 feature, declared for 1.7 readers
+    // through Adobe extension level 8
+    doc.version = "1.7".to_string();
+    if let Ok(catalog) = doc.catalog_mut() {
+        catalog.set(
+            "Extensions",
+            dictionary! { "ADBE" => dictionary! { "BaseVersion" => Object::Name(b"1.7".to_vec()), "ExtensionLevel" => 8 } },
+        );
+    }
+    doc.encrypt(&state).map_err(|e| format!("Encryption failed: {}", e))?;
+
+    let mut out = Vec::new();
+    doc.save_to(&mut out).map_err(|e| format!("Failed to write PDF: {}", e))?;
+    Ok(out)
+}
+
+/// Resolve a dictionary entry that may be inline or a reference
+fn dict_entry_mut<'a>(doc: &'a mut Document, owner: lopdf::ObjectId, key: &[u8]) -> Result<&'a mut Object, String> {
+    let reference = match doc.get_dictionary(owner).ok().and_then(|d| d.get(key).ok()) {
+        Some(Object::Reference(id)) => Some(*id),
+        _ => None,
+    };
+    match reference {
+        Some(id) => doc.get_object_mut(id).map_err(|e| e.to_string()),
+        None => doc
+            .get_dictionary_mut(owner)
+            .map_err(|e| e.to_string())?
+            .get_mut(key)
+            .map_err(|e| e.to_string()),
+    }
+}
+
+/// Copy the object a dictionary entry references into the update, so it can
+/// be edited there
+fn clone_entry(update: &mut IncrementalDocument, owner: lopdf::ObjectId, key: &[u8]) -> Result<(), String> {
+    let reference = match update.new_document.get_dictionary(owner).ok().and_then(|d| d.get(key).ok()) {
+        Some(Object::Reference(id)) => *id,
+        _ => return Ok(()),
+    };
+    update.opt_clone_object_to_new_document(reference).map_err(|e| e.to_string())
+}
+
+/// Append an incremental update with an empty signature dictionary and an
+/// invisible signature field on the first page, returning the file and the
+/// length of the original part the update follows
+fn prepare_signature(pdf: &[u8], signature: &PdfSignature, signed_at: &str) -> Result<(Vec<u8>, usize), String> {
+    let mut update: IncrementalDocument = pdf.try_into().map_err(|e| format!("Failed to load PDF: {}", e))?;
+    let prev = update.get_prev_documents();
+    if prev.is_encrypted() || prev.trailer.has(b"Encrypt") {
+        return Err("Signing encrypted PDFs is not supported".to_string());
+    }
+    let first_page = *prev.get_pages().values().next().ok_or("PDF has no pages")?;
+    let catalog_id = prev
+        .trailer
+        .get(b"Root")
+        .and_then(Object::as_reference)
+        .map_err(|e| format!("PDF has no catalog: {}", e))?;
+    let field_count = prev.objects.values().filter(|o| o.as_dict().is_ok_and(|d| d.has(b"FT"))).count();
+    let version = prev.version.clone();
+
+    // Everything edited below is copied into the update; the original bytes
+    // are kept as they are
+    for id in [first_page, catalog_id] {
+        update.opt_clone_object_to_new_document(id).map_err(|e| e.to_string())?;
+    }
+    clone_entry(&mut update, first_page, b"Annots")?;
+    clone_entry(&mut update, catalog_id, b"AcroForm")?;
+    let fields_ref = match update.new_document.get_dictionary(catalog_id).and_then(|d| d.get(b"AcroForm")) {
+        Ok(Object::Reference(form_id)) => update.new_document.get_dictionary(*form_id).and_then(|d| d.get(b"Fields")),
+        Ok(form) => form.as_dict().and_then(|d| d.get(b"Fields")),
+        Err(e) => Err(e),
+    };
+    if let Ok(&Object::Reference(fields_id)) = fields_ref {
+        update.opt_clone_object_to_new_document(fields_id).map_err(|e| e.to_string())?;
+    }
+    let doc = &mut update.new_document;
+    doc.version = version;
+
+    let mut sig = dictionary! {
+        "Type" => "Sig",
+        "Filter" => "Adobe.PPKLite",
+        "SubFilter" => "adbe.pkcs7.detached",
+        "ByteRange" => vec![0.into(), BYTE_RANGE_PLACEHOLDER.into(), BYTE_RANGE_PLACEHOLDER.into(), BYTE_RANGE_PLACEHOLDER.into()],
+        "Contents" => Object::String(vec![0; SIGNATURE_CAPACITY], StringFormat::Hexadecimal),
+        "M" => Object::string_literal(signed_at),
+    };
+    for (key, value) in [
+        ("Reason", &signature.reason),
+        ("Location", &signature.location),
+        ("ContactInfo", &signature.contact_info),
+    ] {
+        if let Some(value) = value {
+            sig.set(key, lopdf::text_string(value));
+        }
+    }
+    let sig_id = doc.add_object(sig);
+
+    // Invisible widget so viewers list the signature
+    let field_id = doc.add_object(dictionary! {
+        "Type" => "Annot",
+        "Subtype" => "Widget",
+        "FT" => "Sig",
+        "T" => Object::string_literal(format!("Signature{}", field_count + 1)),
+        "V" => sig_id,
+        "F" => 132,
+        "Rect" => vec![0.into(), 0.into(), 0.into(), 0.into()],
+        "P" => first_page,
+    });
+
+    let has_annots = doc.get_dictionary(first_page).is_ok_and(|d| d.has(b"Annots"));
+    if !has_annots {
+        doc.get_dictionary_mut(first_page)
+            .map_err(|e| e.to_string())?
+            .set("Annots", Vec::<Object>::new());
+    }
+    dict_entry_mut(doc, first_page, b"Annots")?
+        .as_array_mut()
+        .map_err(|e| e.to_string())?
+        .push(field_id.into());
+
+    let has_form = doc.get_dictionary(catalog_id).is_ok_and(|d| d.has(b"AcroForm"));
+    if !has_form {
+        doc.get_dictionary_mut(catalog_id)
+            .map_err(|e| e.to_string())?
+            .set("AcroForm", dictionary! { "Fields" => Vec::<Object>::new() });
+    }
+    let form: &mut Dictionary = dict_entry_mut(doc, catalog_id, b"AcroForm")?
+        .as_dict_mut()
+        .map_err(|e| e.to_string())?;
+    if !form.has(b"Fields") {
+        form.set("Fields", Vec::<Object>::new());
+    }
+    // SignaturesExist | AppendOnly
+    form.set("SigFlags", 3);
+    let fields_ref = match form.get(b"Fields") {
+        Ok(Object::Reference(id)) => Some(*id),
+        _ => None,
+    };
+    match fields_ref {
+        Some(id) => doc.get_object_mut(id).and_then(Object::as_array_mut),
+        None => form.get_mut(b"Fields").and_then(Object::as_array_mut),
+    }
+    .map_err(|e| e.to_string())?
+    .push(field_id.into());
+
+    let mut out = Vec::new();
+    update.save_to(&mut out).map_err(|e| format!("Failed to write PDF: {}", e))?;
+    Ok((out, pdf.len()))
+}
+
+/// Byte span of the hex `/Contents` placeholder at or after `from`,
+/// including the angle brackets
+fn find_contents_hole(pdf: &[u8], mut from: usize) -> Option<(usize, usize)> {
+    let zeros = SIGNATURE_CAPACITY * 2;
+    while let Some(offset) = pdf[from..].iter().position(|&b| b == b'<') {
+        let start = from + offset;
+        let end = start + 1 + zeros;
+        if pdf.len() > end && pdf[end] == b'>' && pdf[start + 1..end].iter().all(|&b| b == b'0') {
+            return Some((start, end + 1));
+        }
+        from = start + 1;
+    }
+    None
+}
+
+/// Sign a PDF with the key and certificate in a PKCS#12 file
+pub fn sign(pdf: &[u8], signature: &PdfSignature) -> Result<Vec<u8>, String> {
+    let p12 = std::fs::read(&signature.certificate_path).map_err(|e| format!("Failed to read certificate: {}", e))?;
+    let identity = pkcs12::parse(&p12, &signature.password)?;
+
+    // iso8601_now: YYYY-MM-DDTHH:MM:SSZ
+    let now: String = iso8601_now().chars().filter(char::is_ascii_digit).collect();
+    let (mut pdf, original_len) = prepare_signature(pdf, signature, &format!("D:{}Z", now))?;
+
+    // Both placeholders are in the appended update, the ByteRange written
+    // just before the hole in the same dictionary; anything earlier belongs
+    // to the original file (e.g. an existing signature) and is left alone
+    let (start, end) = find_contents_hole(&pdf, original_len).ok_or("Signature placeholder not found")?;
+    let byte_range = format!("0 {} {} {}", start, end, pdf.len() - end);
+    let key = b"/ByteRange";
+    let at = original_len
+        + pdf[original_len..start]
+            .windows(key.len())
+            .rposition(|w| w == key)
+            .ok_or("ByteRange not found")?;
+    let open = at + pdf[at..].iter().position(|&b| b == b'[').ok_or("ByteRange not found")? + 1;
+    let close = open + pdf[open..].iter().position(|&b| b == b']').ok_or("ByteRange not found")?;
+    if byte_range.len() > close - open {
+        return Err("ByteRange placeholder too small".to_string());
+    }
+    pdf[open..close].copy_from_slice(format!("{:width$}", byte_range, width = close - open).as_bytes());
+
+    let mut hasher = Sha256::new();
+    hasher.update(&pdf[..start]);
+    hasher.update(&pdf[end..]);
+    let digest = hasher.finalize();
+
+    let cms = cms::signed_data(&identity, &digest, &now[2..])?;
+    if cms.len() > SIGNATURE_CAPACITY {
+        return Err("Certificate chain too large for the signature field".to_string());
+    }
+    let hex: String = cms.iter().map(|b| format!("{:02X}", b)).collect();
+    pdf[start + 1..start + 1 + hex.len()].copy_from_slice(hex.as_bytes());
+    Ok(pdf)
+}
+
+/// Apply the requested protection to an exported PDF
+pub fn secure(
+    pdf: Vec<u8>,
+    encryption: Option<&PdfEncryption>,
+    signature: Option<&PdfSignature>,
+) -> Result<Vec<u8>, String> {
+    match (encryption, signature) {
+        (Some(_), Some(_)) => Err("A PDF can be signed or encrypted on export, not both".to_string()),
+        (Some(encryption), None) => encrypt(&pdf, encryption),
+        (None, Some(signature)) => sign(&pdf, signature),
+        (None, None) => Ok(pdf),
+    }
+}
+
+/// Password-protect an existing PDF
+#[tauri::command]
+pub async fn encrypt_pdf(input_path: String, output_path: String, encryption: PdfEncryption) -> Result<(), String> {
+    tokio::task::spawn_blocking(move || {
+        let pdf = std::fs::read(&input_path).map_err(|e| format!("Failed to read PDF: {}", e))?;
+        std::fs::write(&output_path, encrypt(&pdf, &encryption)?).map_err(|e| format!("Failed to write PDF: {}", e))
+    })
+    .await
+    .map_err(|e| format!("Encryption task failed: {}", e))?
+}
+
+/// Digitally sign an existing PDF
+#[tauri::command]
+pub async fn sign_pdf(input_path: String, output_path: String, signature: PdfSignature) -> Result<(), String> {
+    tokio::task::spawn_blocking(move || {
+        let pdf = std::fs::read(&input_path).map_err(|e| format!("Failed to read PDF: {}", e))?;
+        std::fs::write(&output_path, sign(&pdf, &signature)?).map_err(|e| format!("Failed to write PDF: {}", e))
+    })
+    .await
+    .map_err(|e| format!("Signing task failed: {}", e))?
+}
+
+#[cfg(test)]
+mod tests {
+    use super::der::{self, Reader};
+    use super::*;
+    use aes::cipher::{block_padding::Pkcs7, BlockEncryptMut, KeyIvInit};
+    use lopdf::Stream;
+    use ring::signature::{EcdsaKeyPair, KeyPair, UnparsedPublicKey, ECDSA_P256_SHA256_ASN1, ECDSA_P256_SHA256_ASN1_SIGNING};
+
+    fn sample_pdf() -> Vec<u8> {
+        let mut doc = Document::with_version("1.5");
+        let pages_id = doc.new_object_id();
+        let contents = doc.add_object(Stream::new(dictionary! {}, b"BT /F1 12 Tf 72 700 Td (Hi) Tj ET".to_vec()));
+        let page = doc.add_object(dictionary! { "Type" => "Page", "Parent" => pages_id, "Contents" => contents });
+        doc.objects.insert(
+            pages_id,
+            Object::Dictionary(dictionary! {
+                "Type" => "Pages",
+                "Kids" => vec![page.into()],
+                "Count" => 1,
+                "MediaBox" => vec![0.into(), 0.into(), 612.into(), 792.into()],
+            }),
+        );
+        let catalog = doc.add_object(dictionary! { "Type" => "Catalog", "Pages" => pages_id });
+        doc.trailer.set("Root", catalog);
+        let mut out = Vec::new();
+        doc.save_to(&mut out).unwrap();
+        out
+    }
+
+    /// PKCS#12 with an ECDSA key (PBES2/AES-256) and a stub certificate
+    fn sample_p12(password: &str) -> (Vec<u8>, Vec<u8>) {
+        let rng = ring::rand::SystemRandom::new();
+        let pkcs8 = EcdsaKeyPair::generate_pkcs8(&ECDSA_P256_SHA256_ASN1_SIGNING, &rng).unwrap();
+        let key = EcdsaKeyPair::from_pkcs8(&ECDSA_P256_SHA256_ASN1_SIGNING, pkcs8.as_ref(), &rng).unwrap();
+
+        let name = der::sequence(&[&der::set_of(vec![der::sequence(&[
+            &der::oid("2.5.4.3"),
+            &der::tlv(0x0C, b"ROOK Test Signer"),
+        ])])]);
+        let tbs = der::sequence(&[&der::integer(&[0x42]), &der::algorithm("1.2.840.10045.4.3.2", None), &name]);
+        let cert = der::sequence(&[&tbs]);
+
+        let (salt, iv, iterations) = ([7u8; 8], [9u8; 16], 1000u32);
+        let mut aes_key = [0u8; 32];
+        ring::pbkdf2::derive(
+            ring::pbkdf2::PBKDF2_HMAC_SHA256,
+            std::num::NonZeroU32::new(iterations).unwrap(),
+            &salt,
+            password.as_bytes(),
+            &mut aes_key,
+        );
+        let plain = pkcs8.as_ref();
+        let mut buf = vec![0u8; plain.len() + 16];
+        buf[..plain.len()].copy_from_slice(plain);
+        let encrypted = cbc::Encryptor::<aes::Aes256>::new_from_slices(&aes_key, &iv)
+            .unwrap()
+            .encrypt_padded_mut::<Pkcs7>(&mut buf, plain.len())
+            .unwrap()
+            .to_vec();
+        let pbes2 = der::algorithm(
+            "1.2.840.113549.1.5.13",
+            Some(&der::sequence(&[
+                &der::algorithm(
+                    "1.2.840.113549.1.5.12",
+                    Some(&der::sequence(&[
+                        &der::tlv(der::OCTET_STRING, &salt),
+                        &der::integer(&iterations.to_be_bytes()),
+                        &der::algorithm("1.2.840.113549.2.9", Some(&der::tlv(der::NULL, &[]))),
+                    ])),
+                ),
+                &der::algorithm("2.16.840.1.101.3.4.1.42", Some(&der::tlv(der::OCTET_STRING, &iv))),
+            ])),
+        );
+
+        let key_bag = der::sequence(&[
+            &der::oid("1.2.840.113549.1.12.10.1.2"),
+            &der::tlv(der::context(0), &der::sequence(&[&pbes2, &der::tlv(der::OCTET_STRING, &encrypted)])),
+        ]);
+        let cert_bag = der::sequence(&[
+            &der::oid("1.2.840.113549.1.12.10.1.3"),
+            &der::tlv(
+                der::context(0),
+                &der::sequence(&[
+                    &der::oid("1.2.840.113549.1.9.22.1"),
+                    &der::tlv(der::context(0), &der::tlv(der::OCTET_STRING, &cert)),
+                ]),
+            ),
+        ]);
+        let data = |content: &[u8]| {
+            der::sequence(&[&der::oid("1.2.840.113549.1.7.1"), &der::tlv(der::context(0), &der::tlv(der::OCTET_STRING, content))])
+        };
+        let safes = der::sequence(&[&data(&der::sequence(&[&cert_bag])), &data(&der::sequence(&[&key_bag]))]);
+        let mac = pkcs12::mac_data(&safes, password, &[5u8; 8], 2048);
+        let pfx = der::sequence(&[&der::integer(&[3]), &data(&safes), &mac]);
+        (pfx, key.public_key().as_ref().to_vec())
+    }
+
+    #[test]
+    fn test_encrypt_requires_password_to_open() {
+        let encryption = PdfEncryption {
+            user_password: "reader".to_string(),
+            owner_password: "owner".to_string(),
+            permissions: PdfPermissions { copy: false, ..PdfPermissions::default() },
+        };
+        let encrypted = encrypt(&sample_pdf(), &encryption).unwrap();
+
+        let locked = Document::load_mem(&encrypted).unwrap();
+        assert!(locked.is_encrypted());
+        assert!(locked.get_pages().is_empty());
+        assert!(Document::load_mem_with_password(&encrypted, "wrong").is_err());
+        let doc = Document::load_mem_with_password(&encrypted, "reader").unwrap();
+        assert_eq!(doc.get_pages().len(), 1);
+
+        assert!(secure(sample_pdf(), Some(&encryption), Some(&PdfSignature {
+            certificate_path: String::new(),
+            password: String::new(),
+            reason: None,
+            location: None,
+            contact_info: None,
+        }))
+        .is_err());
+    }
+
+    fn write_p12(name: &str, password: &str) -> (std::path::PathBuf, Vec<u8>) {
+        let (p12, public_key) = sample_p12(password);
+        let path = std::env::temp_dir().join(format!("rook-{}-{}.p12", name, std::process::id()));
+        std::fs::write(&path, &p12).unwrap();
+        (path, public_key)
+    }
+
+    fn signature_dicts(doc: &Document) -> Vec<&Dictionary> {
+        doc.objects
+            .values()
+            .filter_map(|o| o.as_dict().ok())
+            .filter(|d| d.get(b"Type").and_then(Object::as_name).ok() == Some(b"Sig".as_slice()))
+            .collect()
+    }
+
+    /// Check that `sig` signs the bytes its ByteRange covers with `public_key`,
+    /// returning the covered length
+    fn verify_signature(signed: &[u8], sig: &Dictionary, public_key: &[u8]) -> usize {
+        // ByteRange covers everything except the signature hole
+        let range: Vec<usize> = sig.get(b"ByteRange").unwrap().as_array().unwrap().iter().map(|o| o.as_i64().unwrap() as usize).collect();
+        let mut covered = signed[..range[1]].to_vec();
+        covered.extend_from_slice(&signed[range[2]..range[2] + range[3]]);
+        let digest = Sha256::digest(&covered);
+
+        // Pull signed attributes and signature out of the CMS blob
+        let cms = sig.get(b"Contents").unwrap().as_str().unwrap();
+        let mut content_info = Reader::new(Reader::new(cms).expect(der::SEQUENCE).unwrap());
+        content_info.expect(der::OID).unwrap();
+        let mut signed_data =
+            Reader::new(Reader::new(content_info.expect(der::context(0)).unwrap()).expect(der::SEQUENCE).unwrap());
+        for tag in [der::INTEGER, der::SET, der::SEQUENCE, der::context(0)] {
+            signed_data.expect(tag).unwrap();
+        }
+        let mut infos = Reader::new(signed_data.expect(der::SET).unwrap());
+        let mut info = Reader::new(infos.expect(der::SEQUENCE).unwrap());
+        for tag in [der::INTEGER, der::SEQUENCE, der::SEQUENCE] {
+            info.expect(tag).unwrap();
+        }
+        let attributes = info.read().unwrap();
+        info.expect(der::SEQUENCE).unwrap();
+        let sig_value = info.expect(der::OCTET_STRING).unwrap();
+
+        assert!(attributes.content.windows(digest.len()).any(|w| w == digest.as_slice()));
+        let mut message = attributes.raw.to_vec();
+        message[0] = der::SET;
+        UnparsedPublicKey::new(&ECDSA_P256_SHA256_ASN1, public_key).verify(&message, sig_value).unwrap();
+        range[2] + range[3]
+    }
+
+    #[test]
+    fn test_sign_with_pkcs12() {
+        let (path, public_key) = write_p12("sign", "secret");
+        let mut signature = PdfSignature {
+            certificate_path: path.to_string_lossy().to_string(),
+            password: "wrong".to_string(),
+            reason: Some("Final proof".to_string()),
+            location: None,
+            contact_info: None,
+        };
+        assert!(sign(&sample_pdf(), &signature).is_err());
+        signature.password = "secret".to_string();
+        let original = sample_pdf();
+        let signed = sign(&original, &signature).unwrap();
+        let _ = std::fs::remove_file(&path);
+
+        // The signature is appended, leaving the original bytes untouched
+        assert!(signed.starts_with(&original));
+        let doc = Document::load_mem(&signed).unwrap();
+        let sigs = signature_dicts(&doc);
+        assert_eq!(sigs.len(), 1);
+        assert_eq!(verify_signature(&signed, sigs[0], &public_key), signed.len());
+    }
+
+    #[test]
+    fn test_second_signature_keeps_the_first() {
+        let (first_path, first_key) = write_p12("sign-first", "one");
+        let (second_path, second_key) = write_p12("sign-second", "two");
+        let signature = |path: &std::path::Path, password: &str| PdfSignature {
+            certificate_path: path.to_string_lossy().to_string(),
+            password: password.to_string(),
+            reason: None,
+            location: None,
+            contact_info: None,
+        };
+        let once = sign(&sample_pdf(), &signature(&first_path, "one")).unwrap();
+        let twice = sign(&once, &signature(&second_path, "two")).unwrap();
+        let _ = std::fs::remove_file(&first_path);
+        let _ = std::fs::remove_file(&second_path);
+
+        assert!(twice.starts_with(&once));
+        let doc = Document::load_mem(&twice).unwrap();
+        let mut sigs = signature_dicts(&doc);
+        assert_eq!(sigs.len(), 2);
+        // The first still covers exactly the once-signed file, the second all of it
+        sigs.sort_by_key(|d| d.get(b"ByteRange").unwrap().as_array().unwrap()[1].as_i64().unwrap());
+        assert_eq!(verify_signature(&twice, sigs[0], &first_key), once.len());
+        assert_eq!(verify_signature(&twice, sigs[1], &second_key), twice.len());
+
+        let catalog = doc.catalog().unwrap();
+        let form = match catalog.get(b"AcroForm").unwrap() {
+            Object::Reference(id) => doc.get_dictionary(*id).unwrap(),
+            form => form.as_dict().unwrap(),
+        };
+        assert_eq!(form.get(b"Fields").unwrap().as_array().unwrap().len(), 2);
+    }
+
+    #[test]
+    fn test_pkcs12_integrity_and_limits() {
+        let (p12, _) = sample_p12("secret");
+        assert!(pkcs12::parse(&p12, "secret").is_ok());
+        assert!(pkcs12::parse(&p12, "wrong").unwrap_err().starts_with("Incorrect certificate password"));
+
+        // Swap in MacData asking for more iterations than allowed
+        let mut pfx = Reader::new(Reader::new(&p12).expect(der::SEQUENCE).unwrap());
+        let (version, auth_safe) = (pfx.read().unwrap().raw, pfx.read().unwrap().raw);
+        let digest_info = der::sequence(&[
+            &der::algorithm("2.16.840.1.101.3.4.2.1", Some(&der::tlv(der::NULL, &[]))),
+            &der::tlv(der::OCTET_STRING, &[0u8; 32]),
+        ]);
+        let slow_mac = der::sequence(&[&digest_info, &der::tlv(der::OCTET_STRING, &[5u8; 8]), &der::integer(&20_000_000u32.to_be_bytes())]);
+        let slow = der::sequence(&[version, auth_safe, &slow_mac]);
+        assert!(pkcs12::parse(&slow, "secret").unwrap_err().contains("iterations"));
+    }
+}
@@ -0,0 +1,475 @@
+//! PKCS#12 reader
+//!
+//! Extracts the private key and certificate chain from a .p12/.pfx file.
+//! Supports PBES2 (PBKDF2 + AES-CBC), the default since OpenSSL 3; files
+//! using legacy RC2/3DES encryption are rejected and must be re-exported.
+//! The file's HMAC (MacData) is verified before anything is decrypted.
+
+use super::der::{self, Reader};
+use aes::cipher::{block_padding::Pkcs7, BlockDecryptMut, KeyIvInit};
+use ring::{digest, hmac};
+use std::num::NonZeroU32;
+
+const DATA: &str = "1.2.840.113549.1.7.1";
+const ENCRYPTED_DATA: &str = "1.2.840.113549.1.7.6";
+const KEY_BAG: &str = "1.2.840.113549.1.12.10.1.1";
+const SHROUDED_KEY_BAG: &str = "1.2.840.113549.1.12.10.1.2";
+const CERT_BAG: &str = "1.2.840.113549.1.12.10.1.3";
+const X509_CERTIFICATE: &str = "1.2.840.113549.1.9.22.1";
+const LOCAL_KEY_ID: &str = "1.2.840.113549.1.9.21";
+const PBES2: &str = "1.2.840.113549.1.5.13";
+const PBKDF2: &str = "1.2.840.113549.1.5.12";
+const HMAC_SHA1: &str = "1.2.840.113549.2.7";
+const HMAC_SHA256: &str = "1.2.840.113549.2.9";
+const HMAC_SHA384: &str = "1.2.840.113549.2.10";
+const HMAC_SHA512: &str = "1.2.840.113549.2.11";
+const AES128_CBC: &str = "2.16.840.1.101.3.4.1.2";
+const AES192_CBC: &str = "2.16.840.1.101.3.4.1.22";
+const AES256_CBC: &str = "2.16.840.1.101.3.4.1.42";
+const SHA1: &str = "1.3.14.3.2.26";
+const SHA256: &str = "2.16.840.1.101.3.4.2.1";
+const SHA384: &str = "2.16.840.1.101.3.4.2.2";
+const SHA512: &str = "2.16.840.1.101.3.4.2.3";
+
+/// Most key derivation iterations a file may ask for; real files use up to
+/// a few hundred thousand, and a crafted one could otherwise stall the export
+const MAX_ITERATIONS: u64 = 10_000_000;
+/// PKCS#12 key derivation purpose for MAC keys
+const MAC_KEY_ID: u8 = 3;
+
+/// Signing key and certificates from a PKCS#12 file
+#[derive(Debug, Clone)]
+pub struct Identity {
+    /// PKCS#8 PrivateKeyInfo
+    pub private_key: Vec<u8>,
+    /// Certificate matching the key
+    pub certificate: Vec<u8>,
+    /// All certificates in the file, signer first
+    pub chain: Vec<Vec<u8>>,
+}
+
+fn iteration_count(iterations: u64) -> Result<NonZeroU32, String> {
+    if iterations > MAX_ITERATIONS {
+        return Err(format!(
+            "Certificate file asks for {} key derivation iterations, more than the {} supported",
+            iterations, MAX_ITERATIONS
+        ));
+    }
+    NonZeroU32::new(iterations as u32).ok_or_else(|| "Invalid key derivation iteration count".to_string())
+}
+
+/// Password as a NUL-terminated big-endian BMPString, as PKCS#12 MACs use it
+fn bmp_password(password: &str) -> Vec<u8> {
+    password.encode_utf16().chain([0]).flat_map(u16::to_be_bytes).collect()
+}
+
+/// PKCS#12 key derivation (RFC 7292 appendix B)
+fn pkcs12_kdf(
+    hash: &'static digest::Algorithm,
+    password: &[u8],
+    salt: &[u8],
+    id: u8,
+    iterations: NonZeroU32,
+    len: usize,
+) -> Vec<u8> {
+    let v = hash.block_len();
+    let repeat = |data: &[u8]| -> Vec<u8> { data.iter().cycle().take(v * data.len().div_ceil(v)).copied().collect() };
+    let mut input = repeat(salt);
+    input.extend(repeat(password));
+
+    let mut out = Vec::with_capacity(len);
+    while out.len() < len {
+        let mut context = digest::Context::new(hash);
+        context.update(&vec![id; v]);
+        context.update(&input);
+        let mut a = context.finish();
+        for _ in 1..iterations.get() {
+            a = digest::digest(hash, a.as_ref());
+        }
+        let a = a.as_ref();
+        out.extend_from_slice(&a[..(len - out.len()).min(a.len())]);
+
+        // Each block of the input becomes block + A (repeated to v bytes) + 1
+        let b: Vec<u8> = a.iter().cycle().take(v).copied().collect();
+        for block in input.chunks_mut(v) {
+            let mut carry = 1u16;
+            for (x, y) in block.iter_mut().zip(&b).rev() {
+                let sum = u16::from(*x) + u16::from(*y) + carry;
+                *x = sum as u8;
+                carry = sum >> 8;
+            }
+        }
+    }
+    out
+}
+
+/// Verify the PFX MacData over the authSafe content
+fn verify_mac(mac_data: &[u8], content: &[u8], password: &str) -> Result<(), String> {
+    let mut mac_data = Reader::new(mac_data);
+    let mut digest_info = Reader::new(mac_data.expect(der::SEQUENCE)?);
+    let algorithm = Reader::new(digest_info.expect(der::SEQUENCE)?).expect(der::OID)?;
+    let expected = digest_info.expect(der::OCTET_STRING)?;
+    let salt = mac_data.expect(der::OCTET_STRING)?;
+    let iterations = match mac_data.optional(der::INTEGER)? {
+        Some(iterations) => der::to_u64(iterations)?,
+        None => 1,
+    };
+    let iterations = iteration_count(iterations)?;
+
+    let algorithm = match () {
+        _ if der::is_oid(algorithm, SHA1) => hmac::HMAC_SHA1_FOR_LEGACY_USE_ONLY,
+        _ if der::is_oid(algorithm, SHA256) => hmac::HMAC_SHA256,
+        _ if der::is_oid(algorithm, SHA384) => hmac::HMAC_SHA384,
+        _ if der::is_oid(algorithm, SHA512) => hmac::HMAC_SHA512,
+        _ => return Err("Unsupported integrity check in certificate file".to_string()),
+    };
+    let hash = algorithm.digest_algorithm();
+
+    // Some tools apply an empty password without the terminator
+    let password = bmp_password(password);
+    let candidates: &[&[u8]] = if password.len() == 2 { &[&password, &[]] } else { &[&password] };
+    for candidate in candidates {
+        let key = pkcs12_kdf(hash, candidate, salt, MAC_KEY_ID, iterations, hash.output_len());
+        if hmac::verify(&hmac::Key::new(algorithm, &key), content, expected).is_ok() {
+            return Ok(());
+        }
+    }
+    Err("Incorrect certificate password, or the file is damaged".to_string())
+}
+
+/// MacData for `content` under SHA-256, for building test files
+#[cfg(test)]
+pub(super) fn mac_data(content: &[u8], password: &str, salt: &[u8], iterations: u32) -> Vec<u8> {
+    let iterations = NonZeroU32::new(iterations).unwrap();
+    let key = pkcs12_kdf(&digest::SHA256, &bmp_password(password), salt, MAC_KEY_ID, iterations, 32);
+    let mac = hmac::sign(&hmac::Key::new(hmac::HMAC_SHA256, &key), content);
+    der::sequence(&[
+        &der::sequence(&[&der::algorithm(SHA256, Some(&der::tlv(der::NULL, &[]))), &der::tlv(der::OCTET_STRING, mac.as_ref())]),
+        &der::tlv(der::OCTET_STRING, salt),
+        &der::integer(&iterations.get().to_be_bytes()),
+    ])
+}
+
+/// Decrypt PBES2 content (`params` is the AlgorithmIdentifier parameters)
+fn pbes2_decrypt(params: &[u8], ciphertext: &[u8], password: &str) -> Result<Vec<u8>, String> {
+    let mut params = Reader::new(params);
+    let mut kdf = Reader::new(params.expect(der::SEQUENCE)?);
+    let mut scheme = Reader::new(params.expect(der::SEQUENCE)?);
+
+    if !der::is_oid(kdf.expect(der::OID)?, PBKDF2) {
+        return Err("Unsupported key derivation in certificate file".to_string());
+    }
+    let mut kdf_params = Reader::new(kdf.expect(der::SEQUENCE)?);
+    let salt = kdf_params.expect(der::OCTET_STRING)?;
+    let iterations = der::to_u64(kdf_params.expect(der::INTEGER)?)?;
+    kdf_params.optional(der::INTEGER)?;
+    let prf = match kdf_params.optional(der::SEQUENCE)? {
+        Some(prf) => {
+            let prf_oid = Reader::new(prf).expect(der::OID)?;
+            match () {
+                _ if der::is_oid(prf_oid, HMAC_SHA1) => ring::pbkdf2::PBKDF2_HMAC_SHA1,
+                _ if der::is_oid(prf_oid, HMAC_SHA256) => ring::pbkdf2::PBKDF2_HMAC_SHA256,
+                _ if der::is_oid(prf_oid, HMAC_SHA384) => ring::pbkdf2::PBKDF2_HMAC_SHA384,
+                _ if der::is_oid(prf_oid, HMAC_SHA512) => ring::pbkdf2::PBKDF2_HMAC_SHA512,
+                _ => return Err("Unsupported PBKDF2 hash in certificate file".to_string()),
+            }
+        }
+        None => ring::pbkdf2::PBKDF2_HMAC_SHA1,
+    };
+
+    let cipher = scheme.expect(der::OID)?;
+    let iv = scheme.expect(der::OCTET_STRING)?;
+    let key_len = match () {
+        _ if der::is_oid(cipher, AES128_CBC) => 16,
+        _ if der::is_oid(cipher, AES192_CBC) => 24,
+        _ if der::is_oid(cipher, AES256_CBC) => 32,
+        _ => return Err("Unsupported cipher in certificate file".to_string()),
+    };
+
+    let iterations = iteration_count(iterations)?;
+    let mut key = vec![0u8; key_len];
+    ring::pbkdf2::derive(prf, iterations, salt, password.as_bytes(), &mut key);
+
+    let mut buf = ciphertext.to_vec();
+    let wrong_password = |_| "Incorrect certificate password".to_string();
+    let plain = match key_len {
+        16 => cbc::Decryptor::<aes::Aes128>::new_from_slices(&key, iv)
+            .map_err(|e| e.to_string())?
+            .decrypt_padded_mut::<Pkcs7>(&mut buf)
+            .map_err(wrong_password)?,
+        24 => cbc::Decryptor::<aes::Aes192>::new_from_slices(&key, iv)
+            .map_err(|e| e.to_string())?
+            .decrypt_padded_mut::<Pkcs7>(&mut buf)
+            .map_err(wrong_password)?,
+        _ => cbc::Decryptor::<aes::Aes256>::new_from_slices(&key, iv)
+            .map_err(|e| e.to_string())?
+            .decrypt_padded_mut::<Pkcs7>(&mut buf)
+            .map_err(wrong_password)?,
+    };
+    Ok(plain.to_vec())
+}
+
+/// Decrypt content under an AlgorithmIdentifier
+fn decrypt(algorithm: &[u8], ciphertext: &[u8], password: &str) -> Result<Vec<u8>, String> {
+    let mut alg = Reader::new(algorithm);
+    if !der::is_oid(alg.expect(der::OID)?, PBES2) {
+        return Err("Certificate file uses legacy 3DES/RC2 encryption, which is not supported; re-export it \
+                    with AES-256 (the OpenSSL 3 default, or `openssl pkcs12 -export -keypbe AES-256-CBC \
+                    -certpbe AES-256-CBC`)"
+            .to_string());
+    }
+    pbes2_decrypt(alg.expect(der::SEQUENCE)?, ciphertext, password)
+}
+
+#[derive(Default)]
+struct Bags {
+    /// (PKCS#8 key, localKeyId)
+    keys: Vec<(Vec<u8>, Option<Vec<u8>>)>,
+    /// (certificate, localKeyId)
+    certs: Vec<(Vec<u8>, Option<Vec<u8>>)>,
+}
+
+fn local_key_id(attributes: Option<&[u8]>) -> Result<Option<Vec<u8>>, String> {
+    let Some(attributes) = attributes else { return Ok(None) };
+    let mut attributes = Reader::new(attributes);
+    while !attributes.is_empty() {
+        let mut attribute = Reader::new(attributes.expect(der::SEQUENCE)?);
+        if der::is_oid(attribute.expect(der::OID)?, LOCAL_KEY_ID) {
+            let mut values = Reader::new(attribute.expect(der::SET)?);
+            return Ok(Some(values.expect(der::OCTET_STRING)?.to_vec()));
+        }
+    }
+    Ok(None)
+}
+
+/// Collect keys and certificates from a SafeContents
+fn read_safe_contents(data: &[u8], password: &str, bags: &mut Bags) -> Result<(), String> {
+    let mut contents = Reader::new(Reader::new(data).expect(der::SEQUENCE)?);
+    while !contents.is_empty() {
+        let mut bag = Reader::new(contents.expect(der::SEQUENCE)?);
+        let bag_id = bag.expect(der::OID)?;
+        let value = bag.expect(der::context(0))?;
+        let key_id = local_key_id(bag.optional(der::SET)?)?;
+
+        if der::is_oid(bag_id, KEY_BAG) {
+            bags.keys.push((value.to_vec(), key_id));
+        } else if der::is_oid(bag_id, SHROUDED_KEY_BAG) {
+            let mut info = Reader::new(Reader::new(value).expect(der::SEQUENCE)?);
+            let algorithm = info.expect(der::SEQUENCE)?;
+            let encrypted = info.expect(der::OCTET_STRING)?;
+            bags.keys.push((decrypt(algorithm, encrypted, password)?, key_id));
+        } else if der::is_oid(bag_id, CERT_BAG) {
+            let mut cert_bag = Reader::new(Reader::new(value).expect(der::SEQUENCE)?);
+            if der::is_oid(cert_bag.expect(der::OID)?, X509_CERTIFICATE) {
+                let cert = Reader::new(cert_bag.expect(der::context(0))?).expect(der::OCTET_STRING)?;
+                bags.certs.push((cert.to_vec(), key_id));
+            }
+        }
+    }
+    Ok(())
+}
+
+/// Parse a PKCS#12 file
+pub fn parse(data: &[u8], password: &str) -> Result<Identity, String> {
+    let mut pfx = Reader::new(Reader::new(data).expect(der::SEQUENCE)?);
+    pfx.expect(der::INTEGER)?;
+    let mut auth_safe = Reader::new(pfx.expect(der::SEQUENCE)?);
+    if !der::is_oid(auth_safe.expect(der::OID)?, DATA) {
+        return Err("Public-key protected PKCS#12 files are not supported".to_string());
+    }
+    let safes = Reader::new(auth_safe.expect(der::context(0))?).expect(der::OCTET_STRING)?;
+    // Files exported without integrity protection (`-nomac`) have no MacData
+    if let Some(mac_data) = pfx.optional(der::SEQUENCE)? {
+        verify_mac(mac_data, safes, password)?;
+    }
+
+    let mut bags = Bags::default();
+    let mut safes = Reader::new(Reader::new(safes).expect(der::SEQUENCE)?);
+    while !safes.is_empty() {
+        let mut info = Reader::new(safes.expect(der::SEQUENCE)?);
+        let content_type = info.expect(der::OID)?;
+        let content = info.expect(der::context(0))?;
+        if der::is_oid(content_type, DATA) {
+            read_safe_contents(Reader::new(content).expect(der::OCTET_STRING)?, password, &mut bags)?;
+        } else if der::is_oid(content_type, ENCRYPTED_DATA) {
+            let mut encrypted = Reader::new(Reader::new(content).expect(der::SEQUENCE)?);
+            encrypted.expect(der::INTEGER)?;
+            let mut content_info = Reader::new(encrypted.expect(der::SEQUENCE)?);
+            content_info.expect(der::OID)?;
+            let algorithm = content_info.expect(der::SEQUENCE)?;
+            // [0] IMPLICIT OCTET STRING, primitive or (rarely) constructed
+            let ciphertext = match content_info.read()? {
+                tlv if tlv.tag == 0x80 => tlv.content.to_vec(),
+                tlv if tlv.tag == 0xA0 => {
+                    let mut chunks = Reader::new(tlv.content);
+                    let mut joined = Vec::new();
+                    while !chunks.is_empty() {
+                        joined.extend_from_slice(chunks.expect(der::OCTET_STRING)?);
+                    }
+                    joined
+                }
+                _ => return Err("Malformed encrypted certificate data".to_string()),
+            };
+            read_safe_contents(&decrypt(algorithm, &ciphertext, password)?, password, &mut bags)?;
+        }
+    }
+
+    let (private_key, key_id) = bags.keys.into_iter().next().ok_or("Certificate file has no private key")?;
+    let signer = bags
+        .certs
+        .iter()
+        .position(|(_, id)| key_id.is_some() && *id == key_id)
+        .unwrap_or(0);
+    if bags.certs.is_empty() {
+        return Err("Certificate file has no certificate".to_string());
+    }
+    let mut chain: Vec<Vec<u8>> = bags.certs.into_iter().map(|(cert, _)| cert).collect();
+    chain.swap(0, signer);
+    Ok(Identity { private_key, certificate: chain[0].clone(), chain })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use aes::cipher::BlockEncryptMut;
+
+    const KEY: &[u8] = b"pkcs8 key bytes";
+    const CERT: &[u8] = b"certificate bytes";
+
+    /// PBES2 AlgorithmIdentifier and ciphertext for `plain` under AES-CBC
+    /// with a PBKDF2-HMAC-SHA256 key of `key_len` bytes
+    fn pbes2_encrypt(plain: &[u8], password: &str, key_len: usize) -> (Vec<u8>, Vec<u8>) {
+        let (salt, iv, iterations) = ([3u8; 8], [4u8; 16], 100u32);
+        let mut key = vec![0u8; key_len];
+        ring::pbkdf2::derive(
+            ring::pbkdf2::PBKDF2_HMAC_SHA256,
+            NonZeroU32::new(iterations).unwrap(),
+            &salt,
+            password.as_bytes(),
+            &mut key,
+        );
+        let mut buf = vec![0u8; plain.len() + 16];
+        buf[..plain.len()].copy_from_slice(plain);
+        let (cipher, ciphertext) = match key_len {
+            16 => (
+                AES128_CBC,
+                cbc::Encryptor::<aes::Aes128>::new_from_slices(&key, &iv)
+                    .unwrap()
+                    .encrypt_padded_mut::<Pkcs7>(&mut buf, plain.len())
+                    .unwrap()
+                    .to_vec(),
+            ),
+            _ => (
+                AES256_CBC,
+                cbc::Encryptor::<aes::Aes256>::new_from_slices(&key, &iv)
+                    .unwrap()
+                    .encrypt_padded_mut::<Pkcs7>(&mut buf, plain.len())
+                    .unwrap()
+                    .to_vec(),
+            ),
+        };
+        let algorithm = der::algorithm(
+            PBES2,
+            Some(&der::sequence(&[
+                &der::algorithm(
+                    PBKDF2,
+                    Some(&der::sequence(&[
+                        &der::tlv(der::OCTET_STRING, &salt),
+                        &der::integer(&iterations.to_be_bytes()),
+                        &der::algorithm(HMAC_SHA256, Some(&der::tlv(der::NULL, &[]))),
+                    ])),
+                ),
+                &der::algorithm(cipher, Some(&der::tlv(der::OCTET_STRING, &iv))),
+            ])),
+        );
+        (algorithm, ciphertext)
+    }
+
+    fn data(content: &[u8]) -> Vec<u8> {
+        der::sequence(&[&der::oid(DATA), &der::tlv(der::context(0), &der::tlv(der::OCTET_STRING, content))])
+    }
+
+    fn key_id(id: &[u8]) -> Vec<u8> {
+        der::set_of(vec![der::sequence(&[&der::oid(LOCAL_KEY_ID), &der::set_of(vec![der::tlv(der::OCTET_STRING, id)])])])
+    }
+
+    fn shrouded_key_bag(algorithm: &[u8], ciphertext: &[u8], id: &[u8]) -> Vec<u8> {
+        der::sequence(&[
+            &der::oid(SHROUDED_KEY_BAG),
+            &der::tlv(der::context(0), &der::sequence(&[algorithm, &der::tlv(der::OCTET_STRING, ciphertext)])),
+            &key_id(id),
+        ])
+    }
+
+    fn cert_bag(cert: &[u8], id: &[u8]) -> Vec<u8> {
+        der::sequence(&[
+            &der::oid(CERT_BAG),
+            &der::tlv(
+                der::context(0),
+                &der::sequence(&[&der::oid(X509_CERTIFICATE), &der::tlv(der::context(0), &der::tlv(der::OCTET_STRING, cert))]),
+            ),
+            &key_id(id),
+        ])
+    }
+
+    /// Certificates in a PBES2 EncryptedData safe, as OpenSSL writes them
+    fn encrypted_safe(bags: &[&[u8]], password: &str) -> Vec<u8> {
+        let (algorithm, ciphertext) = pbes2_encrypt(&der::sequence(bags), password, 32);
+        let encrypted = der::sequence(&[
+            &der::integer(&[0]),
+            &der::sequence(&[&der::oid(DATA), &algorithm, &der::tlv(0x80, &ciphertext)]),
+        ]);
+        der::sequence(&[&der::oid(ENCRYPTED_DATA), &der::tlv(der::context(0), &encrypted)])
+    }
+
+    fn pfx(safes: &[&[u8]], mac: Option<&str>) -> Vec<u8> {
+        let safes = der::sequence(safes);
+        match mac {
+            Some(password) => der::sequence(&[&der::integer(&[3]), &data(&safes), &mac_data(&safes, password, &[5u8; 8], 100)]),
+            None => der::sequence(&[&der::integer(&[3]), &data(&safes)]),
+        }
+    }
+
+    #[test]
+    fn test_pbes2_bags() {
+        for key_len in [16, 32] {
+            let (algorithm, ciphertext) = pbes2_encrypt(KEY, "secret", key_len);
+            let certs = encrypted_safe(&[&cert_bag(b"issuer", b"ca"), &cert_bag(CERT, b"me")], "secret");
+            let keys = data(&der::sequence(&[&shrouded_key_bag(&algorithm, &ciphertext, b"me")]));
+            let identity = parse(&pfx(&[&certs, &keys], Some("secret")), "secret").unwrap();
+
+            assert_eq!(identity.private_key, KEY);
+            // The certificate sharing the key's localKeyId signs, whatever its position
+            assert_eq!(identity.certificate, CERT);
+            assert_eq!(identity.chain, vec![CERT.to_vec(), b"issuer".to_vec()]);
+        }
+    }
+
+    #[test]
+    fn test_mac_failure_and_wrong_password() {
+        let (algorithm, ciphertext) = pbes2_encrypt(KEY, "secret", 32);
+        let safes = [data(&der::sequence(&[&cert_bag(CERT, b"me")])), data(&der::sequence(&[&shrouded_key_bag(&algorithm, &ciphertext, b"me")]))];
+        let file = pfx(&[&safes[0], &safes[1]], Some("secret"));
+
+        assert!(parse(&file, "wrong").unwrap_err().starts_with("Incorrect certificate password"));
+        // A damaged file fails the MAC even with the right password
+        let mut damaged = file.clone();
+        let at = damaged.windows(CERT.len()).position(|w| w == CERT).unwrap();
+        damaged[at] ^= 1;
+        assert!(parse(&damaged, "secret").unwrap_err().contains("damaged"));
+
+        // Without MacData the wrong password only shows when decrypting
+        let unprotected = pfx(&[&safes[0], &safes[1]], None);
+        assert_eq!(parse(&unprotected, "secret").unwrap().private_key, KEY);
+        assert!(parse(&unprotected, "wrong").unwrap_err().starts_with("Incorrect certificate password"));
+    }
+
+    #[test]
+    fn test_legacy_3des_bags_are_refused() {
+        const PBE_SHA1_3DES: &str = "1.2.840.113549.1.12.1.3";
+        let params = der::sequence(&[&der::tlv(der::OCTET_STRING, &[1u8; 8]), &der::integer(&[0x08, 0x00])]);
+        let bag = shrouded_key_bag(&der::algorithm(PBE_SHA1_3DES, Some(&params)), &[0u8; 24], b"me");
+        let file = pfx(&[&data(&der::sequence(&[&bag])), &data(&der::sequence(&[&cert_bag(CERT, b"me")]))], Some("secret"));
+        assert!(parse(&file, "secret").unwrap_err().contains("legacy 3DES/RC2"));
+    }
+}
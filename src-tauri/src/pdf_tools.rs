@@ -0,0 +1,869 @@
+//! PDF Tools Module
+//!
+//! Page-level operations on existing PDF files, done directly with lopdf
+//! without importing layers: merging, splitting, rotating and deleting pages
+//! for the quick tools panel, and overlaying one document's pages onto
+//! another's (letterhead under content, proof marks over it).
+//!
+//! Page ranges are 1-based strings such as `"1-3, 5, 8-"`; an open end runs
+//! to the last page and a descending range (`"5-3"`) reverses the order.
+
+use crate::models::{BlendMode, ExportResult};
+use lopdf::content::{Content, Operation};
+use lopdf::{dictionary, Bookmark, Dictionary, Document, Object, ObjectId, Stream};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::path::Path;
+
+/// Whether stamped pages go over or under the existing content
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq, Default)]
+#[serde(rename_all = "lowercase")]
+#[repr(u8)]
+pub enum OverlayPlacement {
+    /// Proof marks and annotations on top
+    #[default]
+    Over = 0,
+    /// Stationery and letterheads underneath
+    Under = 1,
+}
+
+/// How a stamp page is sized onto the target page
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq, Default)]
+#[serde(rename_all = "lowercase")]
+#[repr(u8)]
+pub enum OverlayScale {
+    /// Actual size
+    None = 0,
+    /// Largest size that fits, keeping proportions
+    #[default]
+    Fit = 1,
+    /// Smallest size that covers the page, keeping proportions
+    Fill = 2,
+    /// Exactly the page size, distorting if needed
+    Stretch = 3,
+}
+
+/// Anchor of the stamp on the target page
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq, Default)]
+#[serde(rename_all = "kebab-case")]
+#[repr(u8)]
+pub enum OverlayAlign {
+    TopLeft = 0,
+    Top = 1,
+    TopRight = 2,
+    Left = 3,
+    #[default]
+    Center = 4,
+    Right = 5,
+    BottomLeft = 6,
+    Bottom = 7,
+    BottomRight = 8,
+}
+
+impl OverlayAlign {
+    /// Horizontal and vertical fractions of the free space, from bottom-left
+    #[inline]
+    fn factors(self) -> (f32, f32) {
+        let index = self as u8;
+        ((index % 3) as f32 / 2.0, 1.0 - (index / 3) as f32 / 2.0)
+    }
+}
+
+/// Which stamp page goes on target pages beyond the stamp's page count
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq, Default)]
+#[serde(rename_all = "lowercase")]
+#[repr(u8)]
+pub enum OverlayRepeat {
+    /// Keep using the last stamp page (a one-page letterhead on every page)
+    #[default]
+    Last = 0,
+    /// Start again from the first stamp page
+    Cycle = 1,
+    /// Leave the remaining pages unstamped
+    None = 2,
+}
+
+/// Options for `merge_overlay`
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase", default)]
+pub struct OverlayOptions {
+    pub placement: OverlayPlacement,
+    pub scale: OverlayScale,
+    pub align: OverlayAlign,
+    pub repeat: OverlayRepeat,
+    /// 0 (invisible) to 1 (solid); solid when absent
+    pub opacity: Option<f32>,
+}
+
+fn load(path: &str) -> Result<Document, String> {
+    let doc = Document::load(path).map_err(|e| format!("Failed to load {}: {}", path, e))?;
+    if doc.is_encrypted() {
+        return Err(format!("{} is encrypted", path));
+    }
+    Ok(doc)
+}
+
+pub(crate) fn save(doc: &mut Document, path: &str) -> Result<(), String> {
+    doc.compress();
+    doc.save(path).map(|_| ()).map_err(|e| format!("Failed to write {}: {}", path, e))
+}
+
+/// Look up a page attribute, following the page tree's inheritance
+fn inherited(doc: &Document, page_id: ObjectId, key: &[u8]) -> Option<Object> {
+    let mut node = doc.get_dictionary(page_id).ok()?;
+    for _ in 0..32 {
+        if let Ok(value) = node.get(key) {
+            return Some(value.clone());
+        }
+        node = doc.get_dictionary(node.get(b"Parent").and_then(Object::as_reference).ok()?).ok()?;
+    }
+    None
+}
+
+/// A page box entry (`MediaBox`, `CropBox`, ...) as normalized [x0, y0, x1, y1]
+pub(crate) fn read_page_box(doc: &Document, page_id: ObjectId, key: &[u8]) -> Option<[f32; 4]> {
+    let values = match inherited(doc, page_id, key)? {
+        Object::Reference(id) => doc.get_object(id).ok()?.as_array().ok()?.clone(),
+        Object::Array(values) => values,
+        _ => return None,
+    };
+    let numbers: Vec<f32> = values.iter().filter_map(|v| v.as_float().ok()).collect();
+    (numbers.len() == 4).then(|| {
+        [
+            numbers[0].min(numbers[2]),
+            numbers[1].min(numbers[3]),
+            numbers[0].max(numbers[2]),
+            numbers[1].max(numbers[3]),
+        ]
+    })
+}
+
+/// A box clipped to the MediaBox, `None` when nothing of it is left
+pub(crate) fn clip_to_media(b: [f32; 4], media: [f32; 4]) -> Option<[f32; 4]> {
+    let clipped = [b[0].max(media[0]), b[1].max(media[1]), b[2].min(media[2]), b[3].min(media[3])];
+    (clipped[2] > clipped[0] && clipped[3] > clipped[1]).then_some(clipped)
+}
+
+/// CropBox of a page clipped to its MediaBox, when it has one
+pub(crate) fn crop_box(doc: &Document, page_id: ObjectId, media: [f32; 4]) -> Option<[f32; 4]> {
+    clip_to_media(read_page_box(doc, page_id, b"CropBox")?, media)
+}
+
+/// Visible box of a page (CropBox, else MediaBox) as [x0, y0, x1, y1]
+pub(crate) fn page_box(doc: &Document, page_id: ObjectId) -> [f32; 4] {
+    let media = read_page_box(doc, page_id, b"MediaBox").unwrap_or([0.0, 0.0, 612.0, 792.0]);
+    crop_box(doc, page_id, media).unwrap_or(media)
+}
+
+/// Deep-copy an object from `src` into `dst`, renumbering references
+///
+/// `Parent` links are dropped so copying never drags in a page tree.
+fn copy_object(src: &Document, dst: &mut Document, object: &Object, map: &mut HashMap<ObjectId, ObjectId>) -> Object {
+    let copy_dict = |dict: &Dictionary, dst: &mut Document, map: &mut HashMap<ObjectId, ObjectId>| {
+        let mut copy = Dictionary::new();
+        for (key, value) in dict.iter() {
+            if key.as_slice() != b"Parent" {
+                copy.set(key.clone(), copy_object(src, dst, value, map));
+            }
+        }
+        copy
+    };
+    match object {
+        Object::Reference(id) => {
+            if let Some(&new_id) = map.get(id) {
+                return Object::Reference(new_id);
+            }
+            let new_id = dst.new_object_id();
+            map.insert(*id, new_id);
+            let copy = src.get_object(*id).map(|o| copy_object(src, dst, o, map)).unwrap_or(Object::Null);
+            dst.objects.insert(new_id, copy);
+            Object::Reference(new_id)
+        }
+        Object::Array(items) => Object::Array(items.iter().map(|o| copy_object(src, dst, o, map)).collect()),
+        Object::Dictionary(dict) => Object::Dictionary(copy_dict(dict, dst, map)),
+        Object::Stream(stream) => {
+            let mut copy = stream.clone();
+            copy.dict = copy_dict(&stream.dict, dst, map);
+            Object::Stream(copy)
+        }
+        other => other.clone(),
+    }
+}
+
+/// Resources dictionary of a page, created on the page when absent and
+/// resolved through references when shared
+fn page_resources_mut(doc: &mut Document, page_id: ObjectId) -> Result<&mut Dictionary, String> {
+    let page = doc.get_dictionary(page_id).map_err(|e| e.to_string())?;
+    if !page.has(b"Resources") {
+        // Copy inherited resources onto the page so additions do not shadow them
+        let resources = inherited(doc, page_id, b"Resources").unwrap_or_else(|| Object::Dictionary(Dictionary::new()));
+        doc.get_dictionary_mut(page_id).map_err(|e| e.to_string())?.set("Resources", resources);
+    }
+    let reference = doc
+        .get_dictionary(page_id)
+        .and_then(|p| p.get(b"Resources"))
+        .and_then(Object::as_reference)
+        .ok();
+    match reference {
+        Some(id) => doc.get_dictionary_mut(id),
+        None => doc
+            .get_dictionary_mut(page_id)
+            .and_then(|p| p.get_mut(b"Resources"))
+            .and_then(Object::as_dict_mut),
+    }
+    .map_err(|e| e.to_string())
+}
+
+/// Add a named entry to a resource category (XObject, ExtGState, ...) of a page
+pub(crate) fn add_resource(doc: &mut Document, page_id: ObjectId, category: &str, name: &str, id: ObjectId) -> Result<(), String> {
+    let resources = page_resources_mut(doc, page_id)?;
+    let shared = resources.get(category.as_bytes()).and_then(Object::as_reference).ok();
+    let entries = match shared {
+        Some(category_id) => doc.get_dictionary_mut(category_id).map_err(|e| e.to_string())?,
+        None => {
+            let resources = page_resources_mut(doc, page_id)?;
+            if !resources.get(category.as_bytes()).is_ok_and(|c| c.as_dict().is_ok()) {
+                resources.set(category, Dictionary::new());
+            }
+            resources
+                .get_mut(category.as_bytes())
+                .and_then(Object::as_dict_mut)
+                .map_err(|e| e.to_string())?
+        }
+    };
+    entries.set(name, id);
+    Ok(())
+}
+
+/// Object a page's named resource (XObject, ExtGState, ...) refers to,
+/// looking through inherited and shared resource dictionaries
+pub(crate) fn resource_id(doc: &Document, page_id: ObjectId, category: &str, name: &str) -> Option<ObjectId> {
+    let (direct, inherited) = doc.get_page_resources(page_id).ok()?;
+    let inherited = inherited.into_iter().filter_map(|id| doc.get_dictionary(id).ok());
+    direct.into_iter().chain(inherited).find_map(|resources| {
+        let entries = match resources.get(category.as_bytes()).ok()? {
+            Object::Reference(id) => doc.get_dictionary(*id).ok()?,
+            other => other.as_dict().ok()?,
+        };
+        entries.get(name.as_bytes()).and_then(Object::as_reference).ok()
+    })
+}
+
+/// ExtGState for a constant alpha and blend mode
+pub(crate) fn transparency_state(opacity: f32, blend_mode: BlendMode) -> Dictionary {
+    let opacity = opacity.clamp(0.0, 1.0);
+    dictionary! {
+        "Type" => "ExtGState",
+        "ca" => opacity,
+        "CA" => opacity,
+        "BM" => blend_mode.pdf_name(),
+    }
+}
+
+/// Add content streams before and after a page's existing content
+fn wrap_page_content(doc: &mut Document, page_id: ObjectId, before: Vec<u8>, after: Vec<u8>) -> Result<(), String> {
+    let before = doc.add_object(Stream::new(Dictionary::new(), before));
+    let after = doc.add_object(Stream::new(Dictionary::new(), after));
+    let page = doc.get_dictionary_mut(page_id).map_err(|e| e.to_string())?;
+    let mut contents = vec![Object::Reference(before)];
+    match page.get(b"Contents") {
+        Ok(Object::Array(parts)) => contents.extend(parts.iter().cloned()),
+        Ok(other) => contents.push(other.clone()),
+        Err(_) => {}
+    }
+    contents.push(Object::Reference(after));
+    page.set("Contents", contents);
+    Ok(())
+}
+
+/// Transform placing a stamp box onto a target box: [a 0 0 d e f]
+fn placement(target: [f32; 4], stamp: [f32; 4], options: &OverlayOptions) -> [f32; 6] {
+    let (tw, th) = (target[2] - target[0], target[3] - target[1]);
+    let (sw, sh) = ((stamp[2] - stamp[0]).max(1.0), (stamp[3] - stamp[1]).max(1.0));
+    let (sx, sy) = match options.scale {
+        OverlayScale::None => (1.0, 1.0),
+        OverlayScale::Fit => {
+            let s = (tw / sw).min(th / sh);
+            (s, s)
+        }
+        OverlayScale::Fill => {
+            let s = (tw / sw).max(th / sh);
+            (s, s)
+        }
+        OverlayScale::Stretch => (tw / sw, th / sh),
+    };
+    let (ax, ay) = options.align.factors();
+    let x = target[0] + (tw - sw * sx) * ax - stamp[0] * sx;
+    let y = target[1] + (th - sh * sy) * ay - stamp[1] * sy;
+    [sx, 0.0, 0.0, sy, x, y]
+}
+
+/// Turn a page of `stamp` into a form XObject inside `doc`
+pub(crate) fn page_to_form(
+    stamp: &Document,
+    stamp_page: ObjectId,
+    doc: &mut Document,
+    map: &mut HashMap<ObjectId, ObjectId>,
+) -> Result<(ObjectId, [f32; 4]), String> {
+    let content = stamp.get_page_content(stamp_page).map_err(|e| format!("Failed to read stamp page: {}", e))?;
+    let bbox = page_box(stamp, stamp_page);
+    let resources = inherited(stamp, stamp_page, b"Resources")
+        .map(|r| copy_object(stamp, doc, &r, map))
+        .unwrap_or_else(|| Object::Dictionary(Dictionary::new()));
+    let mut form = Stream::new(
+        dictionary! {
+            "Type" => "XObject",
+            "Subtype" => "Form",
+            "BBox" => bbox.iter().map(|&v| Object::Real(v)).collect::<Vec<_>>(),
+            "Resources" => resources,
+        },
+        content,
+    );
+    let _ = form.compress();
+    Ok((doc.add_object(form), bbox))
+}
+
+/// Stamp the pages of `stamp` onto the pages of `doc`
+pub fn overlay_documents(doc: &mut Document, stamp: &Document, options: &OverlayOptions) -> Result<usize, String> {
+    let stamp_pages: Vec<ObjectId> = stamp.get_pages().into_values().collect();
+    if stamp_pages.is_empty() {
+        return Err("Stamp PDF has no pages".to_string());
+    }
+    let opacity = options.opacity.map(|o| o.clamp(0.0, 1.0)).filter(|&o| o < 1.0);
+    let graphics_state = opacity.map(|alpha| doc.add_object(dictionary! { "Type" => "ExtGState", "ca" => alpha, "CA" => alpha }));
+
+    let mut map = HashMap::new();
+    let mut forms: HashMap<usize, (ObjectId, [f32; 4])> = HashMap::new();
+    let mut stamped = 0;
+    let page_ids: Vec<ObjectId> = doc.get_pages().into_values().collect();
+    for (i, &page_id) in page_ids.iter().enumerate() {
+        let stamp_index = match options.repeat {
+            _ if i < stamp_pages.len() => i,
+            OverlayRepeat::Last => stamp_pages.len() - 1,
+            OverlayRepeat::Cycle => i % stamp_pages.len(),
+            OverlayRepeat::None => break,
+        };
+        let (form_id, bbox) = match forms.get(&stamp_index) {
+            Some(&form) => form,
+            None => {
+                let form = page_to_form(stamp, stamp_pages[stamp_index], doc, &mut map)?;
+                forms.insert(stamp_index, form);
+                form
+            }
+        };
+
+        let form_name = format!("RookStamp{}", stamp_index + 1);
+        add_resource(doc, page_id, "XObject", &form_name, form_id)?;
+        let mut operations = vec![Operation::new("q", vec![])];
+        if let Some(state) = graphics_state {
+            add_resource(doc, page_id, "ExtGState", "RookStampGS", state)?;
+            operations.push(Operation::new("gs", vec![Object::Name(b"RookStampGS".to_vec())]));
+        }
+        let matrix = placement(page_box(doc, page_id), bbox, options);
+        operations.push(Operation::new("cm", matrix.iter().map(|&v| Object::Real(v)).collect()));
+        operations.push(Operation::new("Do", vec![Object::Name(form_name.into_bytes())]));
+        operations.push(Operation::new("Q", vec![]));
+        let stamp_ops = Content { operations }.encode().map_err(|e| e.to_string())?;
+
+        match options.placement {
+            OverlayPlacement::Under => wrap_page_content(doc, page_id, stamp_ops, Vec::new())?,
+            // The page's own content may leave the graphics state altered
+            OverlayPlacement::Over => wrap_page_content(doc, page_id, b"q\n".to_vec(), [b"Q\n".as_slice(), &stamp_ops].concat())?,
+        }
+        stamped += 1;
+    }
+    Ok(stamped)
+}
+
+/// Stamp each page of one PDF onto the matching page of another
+#[tauri::command]
+pub async fn merge_overlay(
+    base_path: String,
+    stamp_path: String,
+    output_path: String,
+    options: Option<OverlayOptions>,
+) -> Result<ExportResult, String> {
+    tokio::task::spawn_blocking(move || {
+        let options = options.unwrap_or_default();
+        let mut doc = load(&base_path)?;
+        let stamp = load(&stamp_path)?;
+        let stamped = overlay_documents(&mut doc, &stamp, &options)?;
+        save(&mut doc, &output_path)?;
+        Ok(ExportResult {
+            success: true,
+            message: format!("Stamped {} pages", stamped),
+            output_path: Some(output_path),
+            data: None,
+        })
+    })
+    .await
+    .map_err(|e| format!("Overlay task failed: {}", e))?
+}
+
+/// One input of `merge_pdfs`
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct MergeInput {
+    pub path: String,
+    /// Page ranges to take; every page when absent
+    #[serde(default)]
+    pub pages: Option<String>,
+}
+
+/// How `split_pdf` divides a document
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(tag = "by", rename_all = "camelCase")]
+pub enum SplitMode {
+    /// One file per range
+    Ranges { ranges: Vec<String> },
+    /// One file per bookmark at `level` (1 = top level)
+    Bookmarks {
+        #[serde(default = "default_bookmark_level")]
+        level: usize,
+    },
+    /// Files of a fixed number of pages
+    Every { pages: usize },
+}
+
+fn default_bookmark_level() -> usize {
+    1
+}
+
+/// Parse 1-based page ranges into 0-based page indices, in order
+pub fn parse_page_ranges(spec: &str, total: usize) -> Result<Vec<usize>, String> {
+    let spec = spec.trim();
+    if spec.is_empty() || spec.eq_ignore_ascii_case("all") {
+        return Ok((0..total).collect());
+    }
+    let page = |s: &str, default: usize| -> Result<usize, String> {
+        let s = s.trim();
+        if s.is_empty() {
+            return Ok(default);
+        }
+        match s.parse::<usize>() {
+            Ok(n) if (1..=total).contains(&n) => Ok(n),
+            _ => Err(format!("Invalid page \"{}\" (document has {} pages)", s, total)),
+        }
+    };
+    let mut indices = Vec::new();
+    for part in spec.split(',').map(str::trim).filter(|p| !p.is_empty()) {
+        match part.split_once('-') {
+            Some((start, end)) => {
+                let (start, end) = (page(start, 1)?, page(end, total)?);
+                if start <= end {
+                    indices.extend(start - 1..end);
+                } else {
+                    indices.extend((end - 1..start).rev());
+                }
+            }
+            None => indices.push(page(part, 0)? - 1),
+        }
+    }
+    Ok(indices)
+}
+
+/// Build a new document from pages of other documents, in order
+///
+/// Inherited page attributes are copied onto each page; shared resources are
+/// copied once per source. With `titles`, each source gets a bookmark on its
+/// first page.
+fn assemble(parts: &[(&Document, Vec<ObjectId>)], titles: Option<&[String]>) -> Result<Document, String> {
+    const INHERITABLE: [&[u8]; 4] = [b"Resources", b"MediaBox", b"CropBox", b"Rotate"];
+
+    let mut doc = Document::with_version("1.7");
+    let pages_id = doc.new_object_id();
+    let mut kids = Vec::new();
+    for (part, (src, page_ids)) in parts.iter().enumerate() {
+        let mut map = HashMap::new();
+        for (i, &page_id) in page_ids.iter().enumerate() {
+            // A repeated page needs its own object; its resources stay shared
+            map.remove(&page_id);
+            let new_id = match copy_object(src, &mut doc, &Object::Reference(page_id), &mut map) {
+                Object::Reference(id) => id,
+                _ => unreachable!(),
+            };
+            let values: Vec<(&[u8], Object)> = INHERITABLE
+                .iter()
+                .filter_map(|&key| inherited(src, page_id, key).map(|v| (key, v)))
+                .collect();
+            let values: Vec<(&[u8], Object)> =
+                values.into_iter().map(|(key, v)| (key, copy_object(src, &mut doc, &v, &mut map))).collect();
+            let page = doc.get_dictionary_mut(new_id).map_err(|e| format!("Invalid page object: {}", e))?;
+            for (key, value) in values {
+                if !page.has(key) {
+                    page.set(key, value);
+                }
+            }
+            page.set("Parent", pages_id);
+            kids.push(Object::Reference(new_id));
+
+            if let (0, Some(title)) = (i, titles.and_then(|t| t.get(part))) {
+                doc.add_bookmark(Bookmark::new(title.clone(), [0.0, 0.0, 0.0], 0, new_id), None);
+            }
+        }
+    }
+    if kids.is_empty() {
+        return Err("No pages selected".to_string());
+    }
+
+    doc.objects.insert(
+        pages_id,
+        Object::Dictionary(dictionary! { "Type" => "Pages", "Count" => kids.len() as i64, "Kids" => kids }),
+    );
+    let mut catalog = dictionary! { "Type" => "Catalog", "Pages" => pages_id };
+    if let Some(outline) = doc.build_outline() {
+        catalog.set("Outlines", outline);
+        catalog.set("PageMode", "UseOutlines");
+    }
+    let catalog_id = doc.add_object(catalog);
+    doc.trailer.set("Root", catalog_id);
+
+    if let Some((src, _)) = parts.first() {
+        if let Ok(info) = src.trailer.get(b"Info") {
+            let info = copy_object(src, &mut doc, info, &mut HashMap::new());
+            doc.trailer.set("Info", info);
+        }
+    }
+    Ok(doc)
+}
+
+fn page_ids(doc: &Document) -> Vec<ObjectId> {
+    doc.get_pages().into_values().collect()
+}
+
+/// Merge page ranges of several PDFs into one file
+pub fn merge_documents(inputs: &[(Document, Option<String>, String)], bookmarks: bool) -> Result<Document, String> {
+    let mut parts = Vec::with_capacity(inputs.len());
+    for (doc, ranges, _) in inputs {
+        let pages = page_ids(doc);
+        let selected = parse_page_ranges(ranges.as_deref().unwrap_or(""), pages.len())?;
+        parts.push((doc, selected.into_iter().map(|i| pages[i]).collect()));
+    }
+    let titles: Vec<String> = inputs.iter().map(|(_, _, title)| title.clone()).collect();
+    assemble(&parts, bookmarks.then_some(titles.as_slice()))
+}
+
+/// Pages (0-based indices) of one split part, with its bookmark title
+type SplitGroup = (Vec<usize>, Option<String>);
+
+fn split_groups(doc: &Document, mode: &SplitMode) -> Result<Vec<SplitGroup>, String> {
+    let total = doc.get_pages().len();
+    match mode {
+        SplitMode::Ranges { ranges } => {
+            ranges.iter().map(|r| parse_page_ranges(r, total).map(|pages| (pages, None))).collect()
+        }
+        SplitMode::Every { pages } => {
+            if *pages == 0 {
+                return Err("Pages per file must be at least 1".to_string());
+            }
+            Ok((0..total).collect::<Vec<_>>().chunks(*pages).map(|c| (c.to_vec(), None)).collect())
+        }
+        SplitMode::Bookmarks { level } => {
+            let toc = doc.get_toc().map_err(|_| "PDF has no bookmarks".to_string())?;
+            let mut starts: Vec<(usize, String)> = toc
+                .toc
+                .into_iter()
+                .filter(|entry| entry.level == (*level).max(1) && entry.page >= 1)
+                .map(|entry| (entry.page - 1, entry.title))
+                .collect();
+            starts.sort_by_key(|(page, _)| *page);
+            starts.dedup_by_key(|(page, _)| *page);
+            if starts.is_empty() {
+                return Err(format!("PDF has no level {} bookmarks", level));
+            }
+            // Pages before the first bookmark form their own part
+            if starts[0].0 > 0 {
+                starts.insert(0, (0, String::new()));
+            }
+            let mut groups = Vec::with_capacity(starts.len());
+            for (i, (start, title)) in starts.iter().enumerate() {
+                let end = starts.get(i + 1).map_or(total, |(next, _)| *next);
+                groups.push(((*start..end).collect(), Some(title.clone()).filter(|t| !t.is_empty())));
+            }
+            Ok(groups)
+        }
+    }
+}
+
+/// File-name-safe version of a bookmark title
+fn file_label(title: &str) -> String {
+    let label: String = title
+        .chars()
+        .map(|c| if c.is_alphanumeric() || c == '-' || c == '_' { c } else { ' ' })
+        .collect::<String>()
+        .split_whitespace()
+        .collect::<Vec<_>>()
+        .join("-");
+    label.chars().take(60).collect()
+}
+
+/// Split a PDF into several files, returning their paths
+pub fn split_document(doc: &Document, mode: &SplitMode, output_dir: &Path, stem: &str) -> Result<Vec<String>, String> {
+    let pages = page_ids(doc);
+    let groups = split_groups(doc, mode)?;
+    let digits = groups.len().to_string().len().max(2);
+    let mut outputs = Vec::with_capacity(groups.len());
+    for (n, (group, title)) in groups.iter().enumerate() {
+        let mut part = assemble(&[(doc, group.iter().map(|&i| pages[i]).collect())], None)?;
+        let name = match title.as_deref().map(file_label).filter(|l| !l.is_empty()) {
+            Some(label) => format!("{}-{:0digits$}-{}.pdf", stem, n + 1, label, digits = digits),
+            None => format!("{}-{:0digits$}.pdf", stem, n + 1, digits = digits),
+        };
+        let path = output_dir.join(name).to_string_lossy().to_string();
+        save(&mut part, &path)?;
+        outputs.push(path);
+    }
+    Ok(outputs)
+}
+
+/// Rotate pages clockwise by a multiple of 90 degrees
+pub fn rotate_pages(doc: &mut Document, indices: &[usize], angle: i32) -> Result<(), String> {
+    if angle % 90 != 0 {
+        return Err("Rotation must be a multiple of 90 degrees".to_string());
+    }
+    let pages = page_ids(doc);
+    let mut seen = std::collections::HashSet::new();
+    for &i in indices.iter().filter(|&&i| seen.insert(i)) {
+        let current = inherited(doc, pages[i], b"Rotate").and_then(|r| r.as_i64().ok()).unwrap_or(0);
+        let rotation = (current + angle as i64).rem_euclid(360);
+        doc.get_dictionary_mut(pages[i]).map_err(|e| e.to_string())?.set("Rotate", rotation);
+    }
+    Ok(())
+}
+
+/// Delete pages, keeping the rest of the document (forms, outlines) intact
+pub fn delete_pages(doc: &mut Document, indices: &[usize]) -> Result<(), String> {
+    let total = doc.get_pages().len();
+    let mut numbers: Vec<u32> = indices.iter().map(|&i| i as u32 + 1).collect();
+    numbers.sort_unstable();
+    numbers.dedup();
+    if numbers.len() >= total {
+        return Err("Cannot delete every page".to_string());
+    }
+    doc.delete_pages(&numbers);
+    doc.prune_objects();
+    Ok(())
+}
+
+/// Merge page ranges of several PDFs into one file
+#[tauri::command]
+pub async fn merge_pdfs(inputs: Vec<MergeInput>, output_path: String, bookmarks: Option<bool>) -> Result<ExportResult, String> {
+    tokio::task::spawn_blocking(move || {
+        let mut documents = Vec::with_capacity(inputs.len());
+        for input in inputs {
+            let title = Path::new(&input.path)
+                .file_stem()
+                .map(|s| s.to_string_lossy().to_string())
+                .unwrap_or_default();
+            documents.push((load(&input.path)?, input.pages, title));
+        }
+        let mut merged = merge_documents(&documents, bookmarks.unwrap_or(true))?;
+        let count = merged.get_pages().len();
+        save(&mut merged, &output_path)?;
+        Ok(ExportResult {
+            success: true,
+            message: format!("Merged {} pages from {} files", count, documents.len()),
+            output_path: Some(output_path),
+            data: None,
+        })
+    })
+    .await
+    .map_err(|e| format!("Merge task failed: {}", e))?
+}
+
+/// Split a PDF by page ranges, bookmarks or page count
+#[tauri::command]
+pub async fn split_pdf(input_path: String, output_dir: String, mode: SplitMode) -> Result<Vec<String>, String> {
+    tokio::task::spawn_blocking(move || {
+        let doc = load(&input_path)?;
+        let stem = Path::new(&input_path)
+            .file_stem()
+            .map(|s| s.to_string_lossy().to_string())
+            .unwrap_or_else(|| "part".to_string());
+        std::fs::create_dir_all(&output_dir).map_err(|e| format!("Failed to create output folder: {}", e))?;
+        split_document(&doc, &mode, Path::new(&output_dir), &stem)
+    })
+    .await
+    .map_err(|e| format!("Split task failed: {}", e))?
+}
+
+/// Rotate pages clockwise; every page when `pages` is absent
+#[tauri::command]
+pub async fn rotate_pdf_pages(
+    input_path: String,
+    output_path: String,
+    angle: i32,
+    pages: Option<String>,
+) -> Result<ExportResult, String> {
+    tokio::task::spawn_blocking(move || {
+        let mut doc = load(&input_path)?;
+        let indices = parse_page_ranges(pages.as_deref().unwrap_or(""), doc.get_pages().len())?;
+        rotate_pages(&mut doc, &indices, angle)?;
+        save(&mut doc, &output_path)?;
+        Ok(ExportResult {
+            success: true,
+            message: format!("Rotated {} pages", indices.len()),
+            output_path: Some(output_path),
+            data: None,
+        })
+    })
+    .await
+    .map_err(|e| format!("Rotate task failed: {}", e))?
+}
+
+/// Delete pages from a PDF
+#[tauri::command]
+pub async fn delete_pdf_pages(input_path: String, output_path: String, pages: String) -> Result<ExportResult, String> {
+    tokio::task::spawn_blocking(move || {
+        let mut doc = load(&input_path)?;
+        if pages.trim().is_empty() {
+            return Err("No pages given".to_string());
+        }
+        let indices = parse_page_ranges(&pages, doc.get_pages().len())?;
+        delete_pages(&mut doc, &indices)?;
+        let remaining = doc.get_pages().len();
+        save(&mut doc, &output_path)?;
+        Ok(ExportResult {
+            success: true,
+            message: format!("{} pages left", remaining),
+            output_path: Some(output_path),
+            data: None,
+        })
+    })
+    .await
+    .map_err(|e| format!("Delete task failed: {}", e))?
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Document with one page per content string, sharing inherited resources
+    fn sample(contents: &[&str], media_box: [i64; 4]) -> Document {
+        let mut doc = Document::with_version("1.5");
+        let pages_id = doc.new_object_id();
+        let font = doc.add_object(dictionary! { "Type" => "Font", "Subtype" => "Type1", "BaseFont" => "Helvetica" });
+        let kids: Vec<Object> = contents
+            .iter()
+            .map(|text| {
+                let content = doc.add_object(Stream::new(Dictionary::new(), text.as_bytes().to_vec()));
+                doc.add_object(dictionary! { "Type" => "Page", "Parent" => pages_id, "Contents" => content }).into()
+            })
+            .collect();
+        doc.objects.insert(
+            pages_id,
+            Object::Dictionary(dictionary! {
+                "Type" => "Pages",
+                "Kids" => kids,
+                "Count" => contents.len() as i64,
+                "MediaBox" => media_box.iter().map(|&v| Object::Integer(v)).collect::<Vec<_>>(),
+                "Resources" => dictionary! { "Font" => dictionary! { "F1" => font } },
+            }),
+        );
+        let catalog = doc.add_object(dictionary! { "Type" => "Catalog", "Pages" => pages_id });
+        doc.trailer.set("Root", catalog);
+        doc
+    }
+
+    #[test]
+    fn test_placement() {
+        let letter = [0.0, 0.0, 612.0, 792.0];
+        let half = [0.0, 0.0, 306.0, 396.0];
+        let fit = placement(letter, half, &OverlayOptions::default());
+        assert_eq!(fit, [2.0, 0.0, 0.0, 2.0, 0.0, 0.0]);
+
+        let options = OverlayOptions { scale: OverlayScale::None, align: OverlayAlign::TopRight, ..Default::default() };
+        assert_eq!(placement(letter, [10.0, 10.0, 316.0, 406.0], &options), [1.0, 0.0, 0.0, 1.0, 296.0, 386.0]);
+    }
+
+    #[test]
+    fn test_page_box_clips_crop_box() {
+        let mut doc = sample(&["q Q", "q Q"], [0, 0, 612, 792]);
+        let pages: Vec<ObjectId> = doc.get_pages().into_values().collect();
+        doc.get_dictionary_mut(pages[0]).unwrap().set("CropBox", vec![36.into(), (-10).into(), 700.into(), 774.into()]);
+        assert_eq!(page_box(&doc, pages[0]), [36.0, 0.0, 612.0, 774.0]);
+        assert_eq!(page_box(&doc, pages[1]), [0.0, 0.0, 612.0, 792.0]);
+        assert_eq!(read_page_box(&doc, pages[1], b"TrimBox"), None);
+    }
+
+    #[test]
+    fn test_overlay_documents() {
+        let mut doc = sample(&["BT (Body 1) Tj ET", "BT (Body 2) Tj ET", "BT (Body 3) Tj ET"], [0, 0, 612, 792]);
+        let stamp = sample(&["0 0 1 rg 0 0 306 396 re f"], [0, 0, 306, 396]);
+        let options = OverlayOptions { placement: OverlayPlacement::Under, opacity: Some(0.5), ..Default::default() };
+        assert_eq!(overlay_documents(&mut doc, &stamp, &options).unwrap(), 3);
+
+        let pages: Vec<ObjectId> = doc.get_pages().into_values().collect();
+        for (i, &page) in pages.iter().enumerate() {
+            let content = String::from_utf8(doc.get_page_content(page).unwrap()).unwrap();
+            assert!(content.starts_with("q\n/RookStampGS gs"), "{}", content);
+            assert!(content.contains(&format!("(Body {}) Tj", i + 1)));
+            // Inherited font survives next to the stamp
+            let (resources, _) = doc.get_page_resources(page).unwrap();
+            let resources = resources.unwrap();
+            assert!(resources.get(b"Font").unwrap().as_dict().unwrap().has(b"F1"));
+            let form = resources.get(b"XObject").unwrap().as_dict().unwrap().get(b"RookStamp1").unwrap();
+            let form = doc.get_object(form.as_reference().unwrap()).unwrap().as_stream().unwrap();
+            assert_eq!(form.dict.get(b"Subtype").unwrap().as_name().unwrap(), b"Form");
+        }
+
+        let once = OverlayOptions { repeat: OverlayRepeat::None, ..Default::default() };
+        let mut doc = sample(&["", ""], [0, 0, 612, 792]);
+        assert_eq!(overlay_documents(&mut doc, &stamp, &once).unwrap(), 1);
+    }
+
+    #[test]
+    fn test_parse_page_ranges() {
+        assert_eq!(parse_page_ranges("", 3).unwrap(), [0, 1, 2]);
+        assert_eq!(parse_page_ranges("1-2, 5, 8-", 9).unwrap(), [0, 1, 4, 7, 8]);
+        assert_eq!(parse_page_ranges("-2,4-3", 4).unwrap(), [0, 1, 3, 2]);
+        assert!(parse_page_ranges("0", 4).is_err());
+        assert!(parse_page_ranges("2-7", 4).is_err());
+    }
+
+    #[test]
+    fn test_merge_rotate_delete() {
+        let a = sample(&["BT (A1) Tj ET", "BT (A2) Tj ET"], [0, 0, 612, 792]);
+        let b = sample(&["BT (B1) Tj ET", "BT (B2) Tj ET", "BT (B3) Tj ET"], [0, 0, 420, 595]);
+        let inputs = vec![(a, None, "a".to_string()), (b, Some("3,1-1".to_string()), "b".to_string())];
+        let mut merged = merge_documents(&inputs, true).unwrap();
+
+        let pages = page_ids(&merged);
+        let text = |doc: &Document, id| String::from_utf8(doc.get_page_content(id).unwrap()).unwrap();
+        let order: Vec<String> = pages.iter().map(|&id| text(&merged, id)).collect();
+        assert_eq!(order, ["BT (A1) Tj ET", "BT (A2) Tj ET", "BT (B3) Tj ET", "BT (B1) Tj ET"]);
+        // Inherited box and resources travel with each page
+        assert_eq!(page_box(&merged, pages[2]), [0.0, 0.0, 420.0, 595.0]);
+        assert!(merged.get_page_fonts(pages[3]).unwrap().contains_key(b"F1".as_slice()));
+        let titles: Vec<String> = merged.get_toc().unwrap().toc.into_iter().map(|t| t.title).collect();
+        assert_eq!(titles, ["a", "b"]);
+
+        rotate_pages(&mut merged, &[0, 0, 3], -90).unwrap();
+        assert_eq!(merged.get_dictionary(pages[0]).unwrap().get(b"Rotate").unwrap().as_i64().unwrap(), 270);
+        assert!(rotate_pages(&mut merged, &[1], 45).is_err());
+
+        delete_pages(&mut merged, &[1, 2]).unwrap();
+        let remaining: Vec<String> = page_ids(&merged).into_iter().map(|id| text(&merged, id)).collect();
+        assert_eq!(remaining, ["BT (A1) Tj ET", "BT (B1) Tj ET"]);
+        assert!(delete_pages(&mut merged, &[0, 1]).is_err());
+    }
+
+    #[test]
+    fn test_split_groups() {
+        let mut doc = sample(&["", "", "", "", ""], [0, 0, 612, 792]);
+        let pages = page_ids(&doc);
+        let every = split_groups(&doc, &SplitMode::Every { pages: 2 }).unwrap();
+        assert_eq!(every.iter().map(|(g, _)| g.clone()).collect::<Vec<_>>(), [vec![0, 1], vec![2, 3], vec![4]]);
+
+        doc.add_bookmark(Bookmark::new("Part One".to_string(), [0.0; 3], 0, pages[1]), None);
+        doc.add_bookmark(Bookmark::new("Part Two".to_string(), [0.0; 3], 0, pages[3]), None);
+        let outline = doc.build_outline().unwrap();
+        doc.catalog_mut().unwrap().set("Outlines", outline);
+        let chapters = split_groups(&doc, &SplitMode::Bookmarks { level: 1 }).unwrap();
+        assert_eq!(
+            chapters,
+            [(vec![0], None), (vec![1, 2], Some("Part One".to_string())), (vec![3, 4], Some("Part Two".to_string()))]
+        );
+        assert_eq!(file_label("Part One: The/Start"), "Part-One-The-Start");
+    }
+}
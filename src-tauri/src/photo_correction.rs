@@ -0,0 +1,503 @@
+//! Photo Correction Module
+//!
+//! Straightens camera and phone photos of book pages before they become
+//! page images or go through OCR: page corner detection, a perspective warp
+//! onto an upright rectangle, and contrast normalization. Runs automatically
+//! on photo imports when `ImportOptions::correct_photos` is set, or on a
+//! cached image through `correct_page_photo` with user-adjusted corners.
+
+use crate::document_parser::ImportOptions;
+use crate::image_handler;
+use crate::models::{DocumentData, DocumentResponse};
+use crate::ocr_handler::OcrEngine;
+use crate::scanner;
+use image::{imageops, DynamicImage, GrayImage, RgbaImage};
+use serde::{Deserialize, Serialize};
+use std::collections::VecDeque;
+use vortex_core::units::POINTS_PER_INCH;
+
+/// Longest side of the thumbnail used for corner detection
+const DETECT_SIZE: u32 = 400;
+/// Smallest page area, as a fraction of the photo, accepted as a detection
+const MIN_PAGE_AREA: f32 = 0.15;
+/// A page filling more than this fraction is already cropped
+const MAX_PAGE_AREA: f32 = 0.95;
+/// Fraction of pixels clipped at each end by contrast normalization
+const CONTRAST_CLIP: f32 = 0.01;
+/// Upper bound on the corrected image's longest side
+const MAX_OUTPUT_SIZE: f32 = 8000.0;
+
+/// Point in image pixels
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq)]
+pub struct Point {
+    pub x: f32,
+    pub y: f32,
+}
+
+impl Point {
+    #[inline]
+    pub const fn new(x: f32, y: f32) -> Self {
+        Self { x, y }
+    }
+
+    #[inline]
+    fn distance(&self, other: &Point) -> f32 {
+        ((self.x - other.x).powi(2) + (self.y - other.y).powi(2)).sqrt()
+    }
+}
+
+/// Page corners: top-left, top-right, bottom-right, bottom-left
+pub type Quad = [Point; 4];
+
+/// Result of `correct_page_photo`
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct CorrectedPhoto {
+    /// Cache id of the corrected image
+    pub image_id: String,
+    pub width: u32,
+    pub height: u32,
+    /// Corners the warp used, in source image pixels
+    pub corners: Vec<Point>,
+}
+
+/// Otsu's threshold for a grayscale image
+fn otsu_threshold(gray: &GrayImage) -> u8 {
+    let mut histogram = [0u64; 256];
+    for p in gray.pixels() {
+        histogram[p.0[0] as usize] += 1;
+    }
+    let total = gray.pixels().len() as f64;
+    let sum_all: f64 = histogram.iter().enumerate().map(|(i, &c)| i as f64 * c as f64).sum();
+
+    let (mut best, mut best_variance) = (0u8, 0.0f64);
+    let (mut weight_bg, mut sum_bg) = (0.0f64, 0.0f64);
+    for (t, &count) in histogram.iter().enumerate() {
+        weight_bg += count as f64;
+        sum_bg += t as f64 * count as f64;
+        let weight_fg = total - weight_bg;
+        if weight_bg == 0.0 || weight_fg == 0.0 {
+            continue;
+        }
+        let mean_bg = sum_bg / weight_bg;
+        let mean_fg = (sum_all - sum_bg) / weight_fg;
+        let variance = weight_bg * weight_fg * (mean_bg - mean_fg).powi(2);
+        if variance > best_variance {
+            best_variance = variance;
+            best = t as u8;
+        }
+    }
+    best
+}
+
+/// Pixels of the largest 4-connected region brighter than `threshold`
+fn largest_bright_region(gray: &GrayImage, threshold: u8) -> Vec<(u32, u32)> {
+    let (w, h) = gray.dimensions();
+    let mut visited = vec![false; (w * h) as usize];
+    let mut best: Vec<(u32, u32)> = Vec::new();
+    let mut queue = VecDeque::new();
+
+    for start in 0..w * h {
+        let (sx, sy) = (start % w, start / w);
+        if visited[start as usize] || gray.get_pixel(sx, sy).0[0] <= threshold {
+            continue;
+        }
+        visited[start as usize] = true;
+        queue.push_back((sx, sy));
+        let mut region = Vec::new();
+        while let Some((x, y)) = queue.pop_front() {
+            region.push((x, y));
+            let neighbors = [
+                (x.wrapping_sub(1), y),
+                (x + 1, y),
+                (x, y.wrapping_sub(1)),
+                (x, y + 1),
+            ];
+            for (nx, ny) in neighbors {
+                if nx >= w || ny >= h {
+                    continue;
+                }
+                let idx = (ny * w + nx) as usize;
+                if !visited[idx] && gray.get_pixel(nx, ny).0[0] > threshold {
+                    visited[idx] = true;
+                    queue.push_back((nx, ny));
+                }
+            }
+        }
+        if region.len() > best.len() {
+            best = region;
+        }
+    }
+    best
+}
+
+/// Area of a quad (shoelace formula)
+fn quad_area(quad: &Quad) -> f32 {
+    let mut area = 0.0;
+    for i in 0..4 {
+        let (a, b) = (quad[i], quad[(i + 1) % 4]);
+        area += a.x * b.y - b.x * a.y;
+    }
+    area.abs() / 2.0
+}
+
+/// Find the corners of a page photographed against a darker background
+///
+/// Returns `None` when no page stands out or the photo is already cropped
+/// to the page.
+pub fn detect_page_corners(image: &DynamicImage) -> Option<Quad> {
+    let (width, height) = (image.width(), image.height());
+    if width < 8 || height < 8 {
+        return None;
+    }
+    let thumb = image.thumbnail(DETECT_SIZE, DETECT_SIZE).to_luma8();
+    let thumb = imageops::blur(&thumb, 1.5);
+    let region = largest_bright_region(&thumb, otsu_threshold(&thumb));
+
+    let thumb_area = (thumb.width() * thumb.height()) as f32;
+    let fraction = region.len() as f32 / thumb_area;
+    if !(MIN_PAGE_AREA..=MAX_PAGE_AREA).contains(&fraction) {
+        return None;
+    }
+
+    // Extremes along the diagonals are the corners of a roughly upright quad
+    let pick = |key: fn(f32, f32) -> f32, max: bool| {
+        let cmp = |a: &&(u32, u32), b: &&(u32, u32)| {
+            key(a.0 as f32, a.1 as f32).total_cmp(&key(b.0 as f32, b.1 as f32))
+        };
+        let &(x, y) = if max { region.iter().max_by(cmp) } else { region.iter().min_by(cmp) }?;
+        Some(Point::new(x as f32, y as f32))
+    };
+    let quad = [
+        pick(|x, y| x + y, false)?,
+        pick(|x, y| x - y, true)?,
+        pick(|x, y| x + y, true)?,
+        pick(|x, y| x - y, false)?,
+    ];
+    if quad_area(&quad) < MIN_PAGE_AREA * thumb_area {
+        return None;
+    }
+
+    let (sx, sy) = (width as f32 / thumb.width() as f32, height as f32 / thumb.height() as f32);
+    Some(quad.map(|p| Point::new((p.x + 0.5) * sx, (p.y + 0.5) * sy)))
+}
+
+/// Solve `a · x = b` for an 8×8 system with partial pivoting
+fn solve8(mut a: [[f64; 9]; 8]) -> Option<[f64; 8]> {
+    for col in 0..8 {
+        let pivot = (col..8).max_by(|&i, &j| a[i][col].abs().total_cmp(&a[j][col].abs()))?;
+        if a[pivot][col].abs() < 1e-10 {
+            return None;
+        }
+        a.swap(col, pivot);
+        let pivot_row = a[col];
+        for (r, row) in a.iter_mut().enumerate() {
+            if r != col {
+                let factor = row[col] / pivot_row[col];
+                for (v, p) in row.iter_mut().zip(pivot_row.iter()).skip(col) {
+                    *v -= factor * p;
+                }
+            }
+        }
+    }
+    let mut x = [0.0; 8];
+    for (i, v) in x.iter_mut().enumerate() {
+        *v = a[i][8] / a[i][i];
+    }
+    Some(x)
+}
+
+/// Homography mapping each `from` corner onto the matching `to` corner
+fn homography(from: &Quad, to: &Quad) -> Option<[f64; 9]> {
+    let mut a = [[0.0f64; 9]; 8];
+    for i in 0..4 {
+        let (u, v) = (from[i].x as f64, from[i].y as f64);
+        let (x, y) = (to[i].x as f64, to[i].y as f64);
+        a[2 * i] = [u, v, 1.0, 0.0, 0.0, 0.0, -u * x, -v * x, x];
+        a[2 * i + 1] = [0.0, 0.0, 0.0, u, v, 1.0, -u * y, -v * y, y];
+    }
+    let h = solve8(a)?;
+    Some([h[0], h[1], h[2], h[3], h[4], h[5], h[6], h[7], 1.0])
+}
+
+#[inline]
+fn apply(h: &[f64; 9], x: f64, y: f64) -> (f64, f64) {
+    let w = h[6] * x + h[7] * y + h[8];
+    ((h[0] * x + h[1] * y + h[2]) / w, (h[3] * x + h[4] * y + h[5]) / w)
+}
+
+/// Bilinear sample; out-of-bounds reads clamp to the edge
+fn sample(image: &RgbaImage, x: f64, y: f64) -> image::Rgba<u8> {
+    let (w, h) = (image.width() as f64, image.height() as f64);
+    let x = (x - 0.5).clamp(0.0, w - 1.0);
+    let y = (y - 0.5).clamp(0.0, h - 1.0);
+    let (x0, y0) = (x.floor() as u32, y.floor() as u32);
+    let (x1, y1) = ((x0 + 1).min(image.width() - 1), (y0 + 1).min(image.height() - 1));
+    let (fx, fy) = (x - x0 as f64, y - y0 as f64);
+
+    let mut out = [0u8; 4];
+    for (c, v) in out.iter_mut().enumerate() {
+        let top = image.get_pixel(x0, y0).0[c] as f64 * (1.0 - fx) + image.get_pixel(x1, y0).0[c] as f64 * fx;
+        let bottom = image.get_pixel(x0, y1).0[c] as f64 * (1.0 - fx) + image.get_pixel(x1, y1).0[c] as f64 * fx;
+        *v = (top * (1.0 - fy) + bottom * fy).round() as u8;
+    }
+    image::Rgba(out)
+}
+
+/// Warp the quad `corners` of `image` onto an upright rectangle
+///
+/// The output keeps the quad's longer edge lengths, so a page photographed
+/// at an angle comes out at roughly its on-photo resolution.
+pub fn warp_perspective(image: &RgbaImage, corners: &Quad) -> Result<RgbaImage, String> {
+    let [tl, tr, br, bl] = corners;
+    let mut width = tl.distance(tr).max(bl.distance(br));
+    let mut height = tl.distance(bl).max(tr.distance(br));
+    if width < 2.0 || height < 2.0 {
+        return Err("Page corners enclose no area".to_string());
+    }
+    let limit = MAX_OUTPUT_SIZE / width.max(height);
+    if limit < 1.0 {
+        width *= limit;
+        height *= limit;
+    }
+    let (out_w, out_h) = (width.round() as u32, height.round() as u32);
+
+    let target = [
+        Point::new(0.0, 0.0),
+        Point::new(out_w as f32, 0.0),
+        Point::new(out_w as f32, out_h as f32),
+        Point::new(0.0, out_h as f32),
+    ];
+    // Map output pixels back into the photo
+    let h = homography(&target, corners).ok_or("Page corners are degenerate")?;
+
+    Ok(RgbaImage::from_fn(out_w, out_h, |x, y| {
+        let (sx, sy) = apply(&h, x as f64 + 0.5, y as f64 + 0.5);
+        sample(image, sx, sy)
+    }))
+}
+
+/// Stretch luminance so the darkest and brightest 1% clip to black and
+/// white, evening out dim or washed-out photos
+pub fn normalize_contrast(image: &mut RgbaImage) {
+    let mut histogram = [0u64; 256];
+    for p in image.pixels() {
+        histogram[luma(p) as usize] += 1;
+    }
+    let clip = (image.pixels().len() as f32 * CONTRAST_CLIP) as u64;
+    let (Some(lo), Some(hi)) = (
+        clip_level(histogram.iter().enumerate(), clip),
+        clip_level(histogram.iter().enumerate().rev(), clip),
+    ) else {
+        return;
+    };
+    if hi - lo < 16.0 {
+        return;
+    }
+    let scale = 255.0 / (hi - lo);
+    for p in image.pixels_mut() {
+        for c in &mut p.0[..3] {
+            *c = ((*c as f32 - lo) * scale).clamp(0.0, 255.0).round() as u8;
+        }
+    }
+}
+
+/// First histogram level past `clip` pixels, walking in iteration order
+fn clip_level<'a>(levels: impl Iterator<Item = (usize, &'a u64)>, clip: u64) -> Option<f32> {
+    let mut seen = 0;
+    for (level, &count) in levels {
+        seen += count;
+        if seen > clip {
+            return Some(level as f32);
+        }
+    }
+    None
+}
+
+#[inline]
+fn luma(p: &image::Rgba<u8>) -> u8 {
+    ((p.0[0] as u32 * 299 + p.0[1] as u32 * 587 + p.0[2] as u32 * 114) / 1000) as u8
+}
+
+/// Run the correction pipeline on a photo
+///
+/// Uses `corners` when given, otherwise detects them; photos without a
+/// detectable page are only contrast-normalized. Returns the image and the
+/// corners used (the full frame when nothing was warped).
+pub fn correct_photo(
+    image: &DynamicImage,
+    corners: Option<Quad>,
+    contrast: bool,
+) -> Result<(RgbaImage, Quad), String> {
+    let rgba = image.to_rgba8();
+    let corners = corners.or_else(|| detect_page_corners(image));
+    let (mut out, used) = match corners {
+        Some(quad) => (warp_perspective(&rgba, &quad)?, quad),
+        None => {
+            let (w, h) = (rgba.width() as f32, rgba.height() as f32);
+            let frame = [Point::new(0.0, 0.0), Point::new(w, 0.0), Point::new(w, h), Point::new(0.0, h)];
+            (rgba, frame)
+        }
+    };
+    if contrast {
+        normalize_contrast(&mut out);
+    }
+    Ok((out, used))
+}
+
+fn encode_png(image: &RgbaImage) -> Result<Vec<u8>, String> {
+    let mut bytes = Vec::new();
+    image
+        .write_to(&mut std::io::Cursor::new(&mut bytes), image::ImageFormat::Png)
+        .map_err(|e| format!("Failed to encode image: {}", e))?;
+    Ok(bytes)
+}
+
+fn load_cached(image_id: &str) -> Result<DynamicImage, String> {
+    let bytes = image_handler::get_image_bytes(image_id).ok_or_else(|| format!("Image not found: {}", image_id))?;
+    image::load_from_memory(&bytes).map_err(|e| format!("Failed to decode image: {}", e))
+}
+
+/// Import a photo of a page as a one-page document
+///
+/// The photo is straightened when `correct_photos` is set, scaled to the
+/// width of the import page setup, and OCR'd when `ocr_photos` is set.
+pub fn import_photo(file_path: &str, options: &ImportOptions) -> Result<DocumentResponse, String> {
+    let bytes = std::fs::read(file_path).map_err(|e| format!("Failed to read image: {}", e))?;
+    let image = image::load_from_memory(&bytes).map_err(|e| format!("Failed to decode image: {}", e))?;
+    let (image, bytes) = if options.correct_photos {
+        let (corrected, _) = correct_photo(&image, None, true)?;
+        let bytes = encode_png(&corrected)?;
+        (corrected, bytes)
+    } else {
+        (image.to_rgba8(), bytes)
+    };
+
+    // Photos carry no physical size; fit the page setup width
+    let page_width = options.page_setup.unwrap_or_default().width;
+    let dpi = ((image.width() as f32 * POINTS_PER_INCH / page_width).round() as u32).max(1);
+    let id = format!(
+        "photo-{}",
+        std::time::SystemTime::now()
+            .duration_since(std::time::UNIX_EPOCH)
+            .map(|d| d.as_millis())
+            .unwrap_or_default()
+    );
+    let mut page = scanner::image_page(id.clone(), 0, image.width(), image.height(), dpi);
+    image_handler::cache_image_with_dimensions(&id, bytes, image.width(), image.height());
+
+    if options.ocr_photos {
+        scanner::add_ocr_layers(&mut OcrEngine::new(), &mut page, &image, dpi as f32 / POINTS_PER_INCH);
+    }
+
+    Ok(DocumentResponse {
+        success: true,
+        message: "Imported photo".to_string(),
+        data: Some(DocumentData::new(page.width, page.height, vec![page])),
+        repair: None,
+        metadata: None,
+    })
+}
+
+/// Detect page corners in a cached image, for the corner editor
+#[tauri::command]
+pub async fn detect_photo_corners(image_id: String) -> Result<Option<Vec<Point>>, String> {
+    tokio::task::spawn_blocking(move || {
+        let image = load_cached(&image_id)?;
+        Ok(detect_page_corners(&image).map(Vec::from))
+    })
+    .await
+    .map_err(|e| format!("Detection task failed: {}", e))?
+}
+
+/// Straighten a cached page photo
+///
+/// `corners` are the page corners in image pixels (top-left, top-right,
+/// bottom-right, bottom-left); they are detected when absent. The result is
+/// cached under a new id for the frontend to swap into the image layer.
+#[tauri::command]
+pub async fn correct_page_photo(
+    image_id: String,
+    corners: Option<Vec<Point>>,
+    normalize_contrast: Option<bool>,
+) -> Result<CorrectedPhoto, String> {
+    let corners: Option<Quad> = corners
+        .map(|c| c.try_into().map_err(|c: Vec<Point>| format!("Expected 4 corners, got {}", c.len())))
+        .transpose()?;
+
+    tokio::task::spawn_blocking(move || {
+        let image = load_cached(&image_id)?;
+        let (corrected, used) = correct_photo(&image, corners, normalize_contrast.unwrap_or(true))?;
+        let corrected_id = format!("{}-corrected", image_id.trim_end_matches("-corrected"));
+        let (width, height) = corrected.dimensions();
+        image_handler::cache_image_with_dimensions(&corrected_id, encode_png(&corrected)?, width, height);
+        Ok(CorrectedPhoto { image_id: corrected_id, width, height, corners: used.to_vec() })
+    })
+    .await
+    .map_err(|e| format!("Correction task failed: {}", e))?
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Dark photo with a bright, skewed page quad
+    fn skewed_page() -> (DynamicImage, Quad) {
+        let quad = [Point::new(60.0, 40.0), Point::new(330.0, 70.0), Point::new(310.0, 420.0), Point::new(40.0, 380.0)];
+        let inside = |x: f32, y: f32| {
+            (0..4).all(|i| {
+                let (a, b) = (quad[i], quad[(i + 1) % 4]);
+                (b.x - a.x) * (y - a.y) - (b.y - a.y) * (x - a.x) >= 0.0
+            })
+        };
+        let image = RgbaImage::from_fn(400, 480, |x, y| {
+            if inside(x as f32, y as f32) {
+                image::Rgba([230, 225, 215, 255])
+            } else {
+                image::Rgba([40, 35, 30, 255])
+            }
+        });
+        (DynamicImage::ImageRgba8(image), quad)
+    }
+
+    #[test]
+    fn test_detect_page_corners() {
+        let (image, quad) = skewed_page();
+        let detected = detect_page_corners(&image).expect("page detected");
+        for (found, expected) in detected.iter().zip(quad.iter()) {
+            assert!(found.distance(expected) < 6.0, "{:?} vs {:?}", found, expected);
+        }
+
+        // A plain photo with no page against a background
+        let blank = DynamicImage::ImageRgba8(RgbaImage::from_pixel(100, 100, image::Rgba([200, 200, 200, 255])));
+        assert!(detect_page_corners(&blank).is_none());
+    }
+
+    #[test]
+    fn test_warp_perspective() {
+        let (image, quad) = skewed_page();
+        let warped = warp_perspective(&image.to_rgba8(), &quad).unwrap();
+        assert_eq!(warped.width(), 273);
+        assert_eq!(warped.height(), 351);
+        // Everything inside the output is page, not background
+        for (x, y) in [(5, 5), (267, 5), (267, 345), (5, 345), (136, 175)] {
+            assert!(warped.get_pixel(x, y).0[0] > 200, "background at {},{}", x, y);
+        }
+
+        let h = homography(&quad, &quad).unwrap();
+        let (x, y) = apply(&h, 100.0, 200.0);
+        assert!((x - 100.0).abs() < 1e-6 && (y - 200.0).abs() < 1e-6);
+    }
+
+    #[test]
+    fn test_normalize_contrast() {
+        let mut image = RgbaImage::from_fn(100, 1, |x, _| {
+            let v = 100 + (x as u8) / 2;
+            image::Rgba([v, v, v, 255])
+        });
+        normalize_contrast(&mut image);
+        assert!(image.get_pixel(0, 0).0[0] < 10);
+        assert!(image.get_pixel(99, 0).0[0] > 245);
+        assert_eq!(image.get_pixel(50, 0).0[3], 255);
+    }
+}
@@ -4,13 +4,32 @@
 //! - Page ordering algorithm for correct folding sequence
 //! - Transformation matrices for positioning and rotation
 //! - Creep compensation for paper thickness
+//! - Signatures of nested sheets for sewn bindings, with collation and sewing marks
 //! - Support for A4, A5, A3, Letter paper sizes
 
-use crate::models::TransformMatrix;
+use crate::export_handler;
+use crate::models::{Bounds, PageData, TransformMatrix};
+use crate::pdf_tools;
+use crate::settings;
+use crate::units::{in_to_pt, mm_to_pt};
+use base64::Engine as _;
+use image::RgbaImage;
+use lopdf::content::{Content, Operation};
+use lopdf::{dictionary, Dictionary, Document, Object, ObjectId, Stream};
 use serde::{Deserialize, Serialize};
-
-/// Paper dimensions in PDF points (1pt = 1/72 inch, 1mm = 2.83465pt)
-const MM_TO_PT: f32 = 2.83465;
+use std::collections::HashMap;
+use std::path::Path;
+
+/// Default width of sheet preview thumbnails, in pixels
+const DEFAULT_PREVIEW_WIDTH: u32 = 400;
+/// Largest accepted printer offset, in mm
+const MAX_PRINTER_OFFSET_MM: f32 = 20.0;
+/// Calibration scales run this many mm either side of the center
+const CALIBRATION_SCALE_MM: i32 = 10;
+/// Collation mark size on the spine fold, in mm
+const COLLATION_MARK_MM: (f32, f32) = (3.0, 8.0);
+/// Distance of the first and last collation mark or sewing station from the sheet edge, in mm
+const SPINE_MARGIN_MM: f32 = 15.0;
 
 /// Standard paper sizes in points [width, height]
 #[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
@@ -28,11 +47,11 @@ impl PaperSize {
     /// Get dimensions in PDF points [width, height]
     pub fn dimensions(&self) -> (f32, f32) {
         match self {
-            PaperSize::A3 => (297.0 * MM_TO_PT, 420.0 * MM_TO_PT),
-            PaperSize::A4 => (210.0 * MM_TO_PT, 297.0 * MM_TO_PT),
-            PaperSize::A5 => (148.0 * MM_TO_PT, 210.0 * MM_TO_PT),
-            PaperSize::Letter => (612.0, 792.0),  // 8.5 × 11 in
-            PaperSize::Legal => (612.0, 1008.0),  // 8.5 × 14 in
+            PaperSize::A3 => (mm_to_pt(297.0), mm_to_pt(420.0)),
+            PaperSize::A4 => (mm_to_pt(210.0), mm_to_pt(297.0)),
+            PaperSize::A5 => (mm_to_pt(148.0), mm_to_pt(210.0)),
+            PaperSize::Letter => (in_to_pt(8.5), in_to_pt(11.0)),
+            PaperSize::Legal => (in_to_pt(8.5), in_to_pt(14.0)),
             PaperSize::Custom { width, height } => (*width, *height),
         }
     }
@@ -44,8 +63,45 @@ impl PaperSize {
     }
 }
 
+/// Shift applied to everything printed on one side of a sheet, in mm;
+/// positive x moves right and positive y up, looking at that side
+#[derive(Debug, Clone, Copy, Default, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase", default)]
+pub struct PrintOffset {
+    pub x_mm: f32,
+    pub y_mm: f32,
+}
+
+impl PrintOffset {
+    /// Move a page transform by the offset
+    pub fn apply(&self, transform: &mut TransformMatrix) {
+        transform.e += mm_to_pt(self.x_mm);
+        transform.f += mm_to_pt(self.y_mm);
+    }
+}
+
+/// Front and back misregistration of a printer, read off the sheet from
+/// `generate_calibration_sheet` and kept in the app settings
+#[derive(Debug, Clone, Copy, Default, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase", default)]
+pub struct PrinterCalibration {
+    pub front: PrintOffset,
+    pub back: PrintOffset,
+}
+
+impl PrinterCalibration {
+    pub fn validate(&self) -> Result<(), String> {
+        let offsets = [self.front.x_mm, self.front.y_mm, self.back.x_mm, self.back.y_mm];
+        if offsets.iter().any(|v| !v.is_finite() || v.abs() > MAX_PRINTER_OFFSET_MM) {
+            return Err(format!("Printer offsets must be within ±{} mm", MAX_PRINTER_OFFSET_MM));
+        }
+        Ok(())
+    }
+}
+
 /// Page position on sheet
-#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
 pub enum PagePosition {
     Left,
     Right,
@@ -65,6 +121,10 @@ pub struct PagePlacement {
 #[derive(Debug, Clone)]
 pub struct SheetLayout {
     pub sheet_index: usize,
+    /// Signature the sheet is folded into (0 for a single booklet)
+    pub signature_index: usize,
+    /// Position in its signature, 0 = outermost
+    pub signature_sheet: usize,
     pub front: [PagePlacement; 2],  // [left, right]
     pub back: [PagePlacement; 2],   // [left, right]
 }
@@ -85,6 +145,15 @@ pub struct ImpositionConfig {
     pub crop_marks: bool,
     /// Add fold marks
     pub fold_marks: bool,
+    /// Sheets nested into each signature; one signature for the whole book when absent
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub sheets_per_signature: Option<u32>,
+    /// Stepped marks on the spine fold of each signature, to check the collating order
+    #[serde(default)]
+    pub collation_marks: bool,
+    /// Sewing stations marked along the fold of each signature's center spread (0 = none)
+    #[serde(default)]
+    pub sewing_stations: u32,
 }
 
 impl Default for ImpositionConfig {
@@ -97,6 +166,9 @@ impl Default for ImpositionConfig {
             bleed_mm: 3.0,
             crop_marks: false,
             fold_marks: false,
+            sheets_per_signature: None,
+            collation_marks: false,
+            sewing_stations: 0,
         }
     }
 }
@@ -117,46 +189,45 @@ pub struct ImpositionResult {
 /// - Sheet 1 front: [N-2, 3] back: [4, N-3] (rotated 180°)
 /// - etc.
 pub fn calculate_page_ordering(total_pages: u32) -> ImpositionResult {
+    calculate_signature_ordering(total_pages, (pad_to_multiple_of_4(total_pages) / 4).max(1))
+}
+
+/// Calculate page ordering for a book sewn from signatures
+///
+/// Pages are split into runs of `sheets_per_signature` × 4, each ordered as
+/// its own booklet; the last signature takes the sheets left over.
+pub fn calculate_signature_ordering(total_pages: u32, sheets_per_signature: u32) -> ImpositionResult {
     let padded = pad_to_multiple_of_4(total_pages);
-    let sheets_count = padded / 4;
-    let mut sheets = Vec::with_capacity(sheets_count as usize);
-
-    for sheet_idx in 0..sheets_count {
-        // Front side (normal orientation)
-        let front_left = padded - (sheet_idx * 2);
-        let front_right = 1 + (sheet_idx * 2);
-
-        // Back side (180° rotation for duplex printing)
-        let back_left = 2 + (sheet_idx * 2);
-        let back_right = padded - 1 - (sheet_idx * 2);
-
-        sheets.push(SheetLayout {
-            sheet_index: sheet_idx as usize,
-            front: [
-                PagePlacement {
-                    page_num: if front_left <= total_pages { front_left } else { 0 },
-                    position: PagePosition::Left,
-                    rotation: 0,
-                },
-                PagePlacement {
-                    page_num: if front_right <= total_pages { front_right } else { 0 },
-                    position: PagePosition::Right,
-                    rotation: 0,
-                },
-            ],
-            back: [
-                PagePlacement {
-                    page_num: if back_left <= total_pages { back_left } else { 0 },
-                    position: PagePosition::Left,
-                    rotation: 180,
-                },
-                PagePlacement {
-                    page_num: if back_right <= total_pages { back_right } else { 0 },
-                    position: PagePosition::Right,
-                    rotation: 180,
-                },
-            ],
-        });
+    let per_signature = sheets_per_signature.max(1).saturating_mul(4);
+    let mut sheets = Vec::with_capacity((padded / 4) as usize);
+    let page = |num: u32, position, rotation| PagePlacement {
+        page_num: if num <= total_pages { num } else { 0 },
+        position,
+        rotation,
+    };
+
+    let mut first = 0;
+    while first < padded {
+        let count = per_signature.min(padded - first);
+        let signature_index = (first / per_signature) as usize;
+        for sheet_idx in 0..count / 4 {
+            sheets.push(SheetLayout {
+                sheet_index: sheets.len(),
+                signature_index,
+                signature_sheet: sheet_idx as usize,
+                // Front side (normal orientation)
+                front: [
+                    page(first + count - sheet_idx * 2, PagePosition::Left, 0),
+                    page(first + 1 + sheet_idx * 2, PagePosition::Right, 0),
+                ],
+                // Back side (180° rotation for duplex printing)
+                back: [
+                    page(first + 2 + sheet_idx * 2, PagePosition::Left, 180),
+                    page(first + count - 1 - sheet_idx * 2, PagePosition::Right, 180),
+                ],
+            });
+        }
+        first += count;
     }
 
     ImpositionResult {
@@ -264,9 +335,14 @@ pub fn generate_page_transform(
 }
 
 /// Full imposition with creep compensation
+///
+/// Creep builds up within a signature, so it is that of the thickest one.
 pub fn impose_booklet(total_pages: u32, config: &ImpositionConfig) -> ImpositionResult {
-    let mut result = calculate_page_ordering(total_pages);
-    let sheets_count = result.sheets.len() as u32;
+    let mut result = match config.sheets_per_signature {
+        Some(sheets) => calculate_signature_ordering(total_pages, sheets),
+        None => calculate_page_ordering(total_pages),
+    };
+    let sheets_count = signature_sheets(&result);
 
     if config.apply_creep && sheets_count > 1 {
         let creep = calculate_creep(sheets_count, config.paper_thickness_mm);
@@ -276,6 +352,11 @@ pub fn impose_booklet(total_pages: u32, config: &ImpositionConfig) -> Imposition
     result
 }
 
+/// Sheets in the thickest signature
+fn signature_sheets(result: &ImpositionResult) -> u32 {
+    result.sheets.iter().map(|s| s.signature_sheet as u32 + 1).max().unwrap_or(0)
+}
+
 /// Tauri command: Calculate booklet imposition
 #[tauri::command]
 pub fn calculate_booklet_imposition(
@@ -289,9 +370,10 @@ pub fn calculate_booklet_imposition(
     let cfg = config.unwrap_or_default();
     let result = impose_booklet(total_pages, &cfg);
     let sheets_count = result.sheets.len() as u32;
+    let signature_sheets = signature_sheets(&result);
 
-    let creep = if cfg.apply_creep && sheets_count > 1 {
-        Some(calculate_creep(sheets_count, cfg.paper_thickness_mm))
+    let creep = if cfg.apply_creep && signature_sheets > 1 {
+        Some(calculate_creep(signature_sheets, cfg.paper_thickness_mm))
     } else {
         None
     };
@@ -303,16 +385,18 @@ pub fn calculate_booklet_imposition(
         .map(|(idx, sheet)| {
             let creep_offset = creep
                 .as_ref()
-                .map(|c| c.sheet_offsets_mm.get(idx).copied().unwrap_or(0.0))
+                .map(|c| c.sheet_offsets_mm.get(sheet.signature_sheet).copied().unwrap_or(0.0))
                 .unwrap_or(0.0);
 
             SheetLayoutResponse {
                 sheet_index: idx,
+                signature_index: sheet.signature_index,
                 front_left: sheet.front[0].page_num,
                 front_right: sheet.front[1].page_num,
                 back_left: sheet.back[0].page_num,
                 back_right: sheet.back[1].page_num,
                 creep_offset_mm: creep_offset,
+                creep_offset_pt: mm_to_pt(creep_offset),
             }
         })
         .collect();
@@ -321,6 +405,7 @@ pub fn calculate_booklet_imposition(
         total_pages,
         padded_pages: result.padded_pages,
         sheets_count,
+        signatures_count: result.sheets.last().map_or(0, |s| s.signature_index as u32 + 1),
         total_creep_mm: result.total_creep_mm,
         sheets: sheet_layouts,
     })
@@ -333,6 +418,7 @@ pub struct BookletImpositionResponse {
     pub total_pages: u32,
     pub padded_pages: u32,
     pub sheets_count: u32,
+    pub signatures_count: u32,
     pub total_creep_mm: f32,
     pub sheets: Vec<SheetLayoutResponse>,
 }
@@ -342,11 +428,14 @@ pub struct BookletImpositionResponse {
 #[serde(rename_all = "camelCase")]
 pub struct SheetLayoutResponse {
     pub sheet_index: usize,
+    pub signature_index: usize,
     pub front_left: u32,
     pub front_right: u32,
     pub back_left: u32,
     pub back_right: u32,
     pub creep_offset_mm: f32,
+    /// Same offset in points, as taken by `calculate_page_transform`
+    pub creep_offset_pt: f32,
 }
 
 /// Tauri command: Get paper size dimensions
@@ -359,6 +448,435 @@ pub fn get_paper_dimensions(paper_size: PaperSize, landscape: bool) -> (f32, f32
     }
 }
 
+/// A page placed on one side of a sheet
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PlacementPreview {
+    /// 1-indexed page number (0 = blank)
+    pub page_num: u32,
+    pub position: PagePosition,
+    /// Rotation in degrees (0 or 180)
+    pub rotation: u16,
+    /// Page to sheet transform, PDF coordinates
+    pub transform: TransformMatrix,
+    /// Area the page covers on the sheet, in points from the top-left corner
+    pub bounds: Bounds,
+}
+
+/// One printed side of a sheet
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SheetSidePreview {
+    /// PNG data URL of the composited side
+    pub thumbnail: String,
+    pub placements: Vec<PlacementPreview>,
+}
+
+/// Both sides of one sheet
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SheetPreview {
+    pub sheet_index: usize,
+    pub creep_offset_pt: f32,
+    pub front: SheetSidePreview,
+    /// As printed: turned 180° relative to the front
+    pub back: SheetSidePreview,
+}
+
+/// Imposition with rendered sheets, for checking page order and creep before printing
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ImpositionPreview {
+    pub sheet_width: f32,
+    pub sheet_height: f32,
+    pub layout: BookletImpositionResponse,
+    pub sheets: Vec<SheetPreview>,
+}
+
+/// Landscape sheet the booklet is printed on, two pages side by side
+fn sheet_size(paper: &PaperSize) -> (f32, f32) {
+    let (w, h) = paper.dimensions();
+    (w.max(h), w.min(h))
+}
+
+/// Transforms and sheet areas of one side's pages, moved by the printer
+/// offset of that side; blanks take the final size
+fn side_placements(
+    side: &[PagePlacement; 2],
+    pages: &[PageData],
+    config: &ImpositionConfig,
+    creep_offset_pt: f32,
+    offset: PrintOffset,
+) -> Vec<PlacementPreview> {
+    let (sheet_width, sheet_height) = sheet_size(&config.paper_size);
+    side.iter()
+        .map(|placement| {
+            let page = (placement.page_num as usize).checked_sub(1).and_then(|i| pages.get(i));
+            let (page_width, page_height) = page.map_or(config.final_size.dimensions(), |p| (p.width, p.height));
+            let mut transform = generate_page_transform(
+                placement.position,
+                placement.rotation,
+                sheet_width,
+                sheet_height,
+                page_width,
+                page_height,
+                creep_offset_pt,
+            );
+            offset.apply(&mut transform);
+            let (x0, x1) = (transform.e, transform.e + transform.a * page_width);
+            let (y0, y1) = (transform.f, transform.f + transform.d * page_height);
+            let bounds = Bounds::new(x0.min(x1), sheet_height - y0.max(y1), (x1 - x0).abs(), (y1 - y0).abs());
+            PlacementPreview {
+                page_num: placement.page_num,
+                position: placement.position,
+                rotation: placement.rotation,
+                transform,
+                bounds,
+            }
+        })
+        .collect()
+}
+
+/// Paint one side of a sheet at `scale` pixels per point, with a line at the fold
+fn composite_side(
+    sheet: (f32, f32),
+    scale: f32,
+    placements: &[PlacementPreview],
+    render: &mut impl FnMut(&PlacementPreview) -> Result<RgbaImage, String>,
+) -> Result<RgbaImage, String> {
+    let px = |pt: f32| (pt * scale).round().max(1.0) as u32;
+    let mut canvas = RgbaImage::from_pixel(px(sheet.0), px(sheet.1), image::Rgba([255, 255, 255, 255]));
+    for placement in placements.iter().filter(|p| p.page_num > 0) {
+        let b = &placement.bounds;
+        let mut page =
+            image::imageops::resize(&render(placement)?, px(b.width), px(b.height), image::imageops::FilterType::Triangle);
+        if placement.rotation == 180 {
+            image::imageops::rotate180_in_place(&mut page);
+        }
+        image::imageops::overlay(&mut canvas, &page, (b.x * scale).round() as i64, (b.y * scale).round() as i64);
+    }
+    let fold = canvas.width() / 2;
+    for y in 0..canvas.height() {
+        canvas.put_pixel(fold, y, image::Rgba([160, 160, 160, 255]));
+    }
+    Ok(canvas)
+}
+
+fn png_data_url(image: &RgbaImage) -> Result<String, String> {
+    let mut png = std::io::Cursor::new(Vec::new());
+    image.write_to(&mut png, image::ImageFormat::Png).map_err(|e| e.to_string())?;
+    Ok(format!("data:image/png;base64,{}", base64::engine::general_purpose::STANDARD.encode(png.into_inner())))
+}
+
+/// Booklet imposition of `pages` with every sheet side rendered
+fn build_preview(
+    pages: &[PageData],
+    config: &ImpositionConfig,
+    calibration: &PrinterCalibration,
+    width: u32,
+    render: &mut impl FnMut(&PlacementPreview) -> Result<RgbaImage, String>,
+) -> Result<ImpositionPreview, String> {
+    let layout = calculate_booklet_imposition(pages.len() as u32, Some(config.clone()))?;
+    let (sheet_width, sheet_height) = sheet_size(&config.paper_size);
+    let scale = width.max(1) as f32 / sheet_width;
+
+    let mut sheets = Vec::with_capacity(layout.sheets.len());
+    for (sheet, response) in impose_booklet(pages.len() as u32, config).sheets.iter().zip(&layout.sheets) {
+        let mut side = |placement: &[PagePlacement; 2], offset| -> Result<SheetSidePreview, String> {
+            let placements = side_placements(placement, pages, config, response.creep_offset_pt, offset);
+            let image = composite_side((sheet_width, sheet_height), scale, &placements, render)?;
+            Ok(SheetSidePreview { thumbnail: png_data_url(&image)?, placements })
+        };
+        sheets.push(SheetPreview {
+            sheet_index: sheet.sheet_index,
+            creep_offset_pt: response.creep_offset_pt,
+            front: side(&sheet.front, calibration.front)?,
+            back: side(&sheet.back, calibration.back)?,
+        });
+    }
+    Ok(ImpositionPreview { sheet_width, sheet_height, layout, sheets })
+}
+
+/// Tauri command: Booklet imposition with a thumbnail of every sheet side
+///
+/// Pages are rendered with the export renderer and composited as they will
+/// print, `width` pixels across the sheet (default 400), with the printer
+/// calibration from the settings applied.
+#[tauri::command]
+pub async fn preview_imposition(
+    pages: Vec<PageData>,
+    config: Option<ImpositionConfig>,
+    width: Option<u32>,
+) -> Result<ImpositionPreview, String> {
+    tokio::task::spawn_blocking(move || {
+        let config = config.unwrap_or_default();
+        let width = width.unwrap_or(DEFAULT_PREVIEW_WIDTH);
+        let scale = width.max(1) as f32 / sheet_size(&config.paper_size).0;
+        let calibration = settings::current().printer_calibration;
+        build_preview(&pages, &config, &calibration, width, &mut |placement| {
+            let page = &pages[placement.page_num as usize - 1];
+            export_handler::render_page_image(page, scale * placement.bounds.width / page.width.max(1.0))
+        })
+    })
+    .await
+    .map_err(|e| format!("Imposition preview task failed: {}", e))?
+}
+
+/// Drawing of one side of the calibration sheet: a cross at the center
+/// with mm scales along both arms, and what to do with it
+fn calibration_side(width: f32, height: f32, back: bool) -> Vec<Operation> {
+    let (cx, cy) = (width / 2.0, height / 2.0);
+    let line = |ops: &mut Vec<Operation>, x0: f32, y0: f32, x1: f32, y1: f32| {
+        ops.push(Operation::new("m", vec![x0.into(), y0.into()]));
+        ops.push(Operation::new("l", vec![x1.into(), y1.into()]));
+    };
+    let text = |ops: &mut Vec<Operation>, x: f32, y: f32, size: f32, s: &str| {
+        ops.push(Operation::new("BT", vec![]));
+        ops.push(Operation::new("Tf", vec![Object::Name(b"F1".to_vec()), size.into()]));
+        ops.push(Operation::new("Td", vec![x.into(), y.into()]));
+        ops.push(Operation::new("Tj", vec![Object::string_literal(s)]));
+        ops.push(Operation::new("ET", vec![]));
+    };
+
+    let mut ops = vec![Operation::new("w", vec![0.3.into()])];
+    let arm = mm_to_pt(CALIBRATION_SCALE_MM as f32 + 2.0);
+    line(&mut ops, cx - arm, cy, cx + arm, cy);
+    line(&mut ops, cx, cy - arm, cx, cy + arm);
+    for mm in -CALIBRATION_SCALE_MM..=CALIBRATION_SCALE_MM {
+        let at = mm_to_pt(mm as f32);
+        let tick = mm_to_pt(if mm % 5 == 0 { 3.0 } else { 1.5 });
+        line(&mut ops, cx + at, cy, cx + at, cy - tick);
+        line(&mut ops, cx, cy + at, cx + tick, cy + at);
+    }
+    ops.push(Operation::new("S", vec![]));
+    for mm in (-CALIBRATION_SCALE_MM..=CALIBRATION_SCALE_MM).filter(|mm| mm % 5 == 0 && *mm != 0) {
+        let at = mm_to_pt(mm as f32);
+        text(&mut ops, cx + at - 4.0, cy - mm_to_pt(3.0) - 8.0, 6.0, &format!("{:+}", mm));
+        text(&mut ops, cx + mm_to_pt(3.0) + 2.0, cy + at - 2.0, 6.0, &format!("{:+}", mm));
+    }
+
+    let lines: &[&str] = if back {
+        &[
+            "Printer calibration - back",
+            "Hold the sheet to the light with this side facing you.",
+            "Where the cross on the front shows through, read both scales (mm):",
+            "enter the readings as the back offset.",
+        ]
+    } else {
+        &[
+            "Printer calibration - front",
+            "Print this file double-sided on one sheet, as you print booklets.",
+            "Fold the sheet in half both ways and open it again.",
+            "Where the folds cross, read both scales (mm): enter the readings as the front offset.",
+        ]
+    };
+    for (i, s) in lines.iter().enumerate() {
+        let size = if i == 0 { 11.0 } else { 8.0 };
+        text(&mut ops, mm_to_pt(15.0), height - mm_to_pt(15.0) - i as f32 * 12.0, size, s);
+    }
+    ops
+}
+
+/// Finish a PDF of `width` × `height` sheet sides, each its content and
+/// resources, under the page tree `pages_id`
+fn finish_sheets(
+    doc: &mut Document,
+    pages_id: ObjectId,
+    (width, height): (f32, f32),
+    sides: Vec<(Vec<Operation>, Dictionary)>,
+) -> Result<(), String> {
+    let mut kids: Vec<Object> = Vec::with_capacity(sides.len());
+    for (operations, resources) in sides {
+        let content = Content { operations }.encode().map_err(|e| e.to_string())?;
+        let content_id = doc.add_object(Stream::new(dictionary! {}, content));
+        let page = dictionary! {
+            "Type" => "Page",
+            "Parent" => pages_id,
+            "Contents" => content_id,
+            "Resources" => resources,
+        };
+        kids.push(doc.add_object(page).into());
+    }
+    doc.objects.insert(
+        pages_id,
+        Object::Dictionary(dictionary! {
+            "Type" => "Pages",
+            "Count" => kids.len() as i64,
+            "Kids" => kids,
+            "MediaBox" => vec![0.into(), 0.into(), width.into(), height.into()],
+        }),
+    );
+    let catalog = doc.add_object(dictionary! { "Type" => "Catalog", "Pages" => pages_id });
+    doc.trailer.set("Root", catalog);
+    Ok(())
+}
+
+/// Two-page PDF, front and back of a landscape sheet of `paper`, for
+/// measuring how far a printer shifts each side
+pub fn calibration_sheet(paper: &PaperSize) -> Result<Document, String> {
+    let (width, height) = sheet_size(paper);
+    let mut doc = Document::with_version("1.5");
+    let pages_id = doc.new_object_id();
+    let font = doc.add_object(dictionary! { "Type" => "Font", "Subtype" => "Type1", "BaseFont" => "Helvetica" });
+    let sides = [false, true]
+        .into_iter()
+        .map(|back| (calibration_side(width, height, back), dictionary! { "Font" => dictionary! { "F1" => font } }))
+        .collect();
+    finish_sheets(&mut doc, pages_id, (width, height), sides)?;
+    Ok(doc)
+}
+
+/// Tauri command: Write a printer calibration sheet (default A4)
+#[tauri::command]
+pub fn generate_calibration_sheet(output_path: String, paper_size: Option<PaperSize>) -> Result<(), String> {
+    let mut doc = calibration_sheet(&paper_size.unwrap_or(PaperSize::A4))?;
+    doc.save(&output_path).map(|_| ()).map_err(|e| format!("Failed to write {}: {}", output_path, e))
+}
+
+/// Heights along the spine fold, in points from the bottom, of `count`
+/// marks spread evenly between the spine margins
+fn spine_positions(count: u32, span: f32, sheet_height: f32) -> Vec<f32> {
+    let (low, high) = (mm_to_pt(SPINE_MARGIN_MM), sheet_height - mm_to_pt(SPINE_MARGIN_MM) - span);
+    match count {
+        0 => Vec::new(),
+        1 => vec![(sheet_height - span) / 2.0],
+        _ => (0..count).map(|i| high - (high - low) * i as f32 / (count - 1) as f32).collect(),
+    }
+}
+
+/// Filled box on the spine of a signature's outer sheet, one step lower
+/// for each signature, so a gathered book shows a clean staircase
+fn collation_mark(signature: usize, signatures: usize, (width, height): (f32, f32), offset: PrintOffset) -> Vec<Operation> {
+    let (w, h) = (mm_to_pt(COLLATION_MARK_MM.0), mm_to_pt(COLLATION_MARK_MM.1));
+    let y = spine_positions(signatures.max(2) as u32, h, height)[signature.min(signatures.max(2) - 1)];
+    let (x, y) = (width / 2.0 - w / 2.0 + mm_to_pt(offset.x_mm), y + mm_to_pt(offset.y_mm));
+    vec![
+        Operation::new("g", vec![0.into()]),
+        Operation::new("re", vec![x.into(), y.into(), w.into(), h.into()]),
+        Operation::new("f", vec![]),
+    ]
+}
+
+/// Ticks across the fold of a signature's center spread where it is pierced for sewing
+fn sewing_marks(stations: u32, (width, height): (f32, f32), offset: PrintOffset) -> Vec<Operation> {
+    let (x, half) = (width / 2.0 + mm_to_pt(offset.x_mm), mm_to_pt(2.0));
+    let mut ops = vec![Operation::new("G", vec![0.into()]), Operation::new("w", vec![0.5.into()])];
+    for y in spine_positions(stations, 0.0, height) {
+        let y = y + mm_to_pt(offset.y_mm);
+        ops.push(Operation::new("m", vec![(x - half).into(), y.into()]));
+        ops.push(Operation::new("l", vec![(x + half).into(), y.into()]));
+    }
+    ops.push(Operation::new("S", vec![]));
+    ops
+}
+
+/// PDF of one signature, front then back of each sheet, with the pages of
+/// `source` placed as form XObjects; `sheets` pairs each sheet with its creep offset in points
+fn signature_document(
+    source: &Document,
+    sheets: &[(&SheetLayout, f32)],
+    signatures: usize,
+    config: &ImpositionConfig,
+    calibration: &PrinterCalibration,
+) -> Result<Document, String> {
+    let sheet = sheet_size(&config.paper_size);
+    let source_pages = source.get_pages();
+    let innermost = sheets.iter().map(|(s, _)| s.signature_sheet).max().unwrap_or(0);
+    let mut doc = Document::with_version("1.5");
+    let pages_id = doc.new_object_id();
+    let mut map = HashMap::new();
+    let mut sides = Vec::with_capacity(sheets.len() * 2);
+
+    for &(layout, creep_offset_pt) in sheets {
+        for (side, offset, back) in [(&layout.front, calibration.front, false), (&layout.back, calibration.back, true)] {
+            let mut ops = Vec::new();
+            let mut xobjects = Dictionary::new();
+            for placement in side {
+                let Some(&page_id) = source_pages.get(&placement.page_num) else {
+                    continue;
+                };
+                let (form, bbox) = pdf_tools::page_to_form(source, page_id, &mut doc, &mut map)?;
+                let (page_width, page_height) = (bbox[2] - bbox[0], bbox[3] - bbox[1]);
+                let mut t = generate_page_transform(
+                    placement.position,
+                    placement.rotation,
+                    sheet.0,
+                    sheet.1,
+                    page_width,
+                    page_height,
+                    creep_offset_pt,
+                );
+                offset.apply(&mut t);
+                // The form keeps the page's own origin
+                let (e, f) = (t.e - t.a * bbox[0] - t.c * bbox[1], t.f - t.b * bbox[0] - t.d * bbox[1]);
+                let name = format!("Pg{}", placement.page_num);
+                ops.push(Operation::new("q", vec![]));
+                ops.push(Operation::new("cm", [t.a, t.b, t.c, t.d, e, f].iter().map(|&v| v.into()).collect()));
+                ops.push(Operation::new("Do", vec![Object::Name(name.clone().into_bytes())]));
+                ops.push(Operation::new("Q", vec![]));
+                xobjects.set(name, form);
+            }
+            if config.collation_marks && !back && layout.signature_sheet == 0 {
+                ops.extend(collation_mark(layout.signature_index, signatures, sheet, offset));
+            }
+            if back && layout.signature_sheet == innermost && config.sewing_stations > 0 {
+                ops.extend(sewing_marks(config.sewing_stations, sheet, offset));
+            }
+            sides.push((ops, dictionary! { "XObject" => xobjects }));
+        }
+    }
+    finish_sheets(&mut doc, pages_id, sheet, sides)?;
+    Ok(doc)
+}
+
+/// Tauri command: Export one imposed PDF per signature, for printing and
+/// folding each separately
+///
+/// Files go to `output_dir` as `<name>-signature-01.pdf` and so on (name
+/// defaults to "book"); the printer calibration from the settings is applied.
+#[tauri::command]
+pub async fn export_signatures(
+    pages: Vec<PageData>,
+    config: ImpositionConfig,
+    output_dir: String,
+    name: Option<String>,
+) -> Result<Vec<String>, String> {
+    tokio::task::spawn_blocking(move || {
+        let layout = calculate_booklet_imposition(pages.len() as u32, Some(config.clone()))?;
+        let ordering = impose_booklet(pages.len() as u32, &config);
+        let calibration = settings::current().printer_calibration;
+
+        let temp = std::env::temp_dir().join(format!("rook-signatures-{}.pdf", std::process::id()));
+        let temp_path = temp.to_string_lossy().to_string();
+        export_handler::export_plain_pdf(&pages, &temp_path)?;
+        let source = Document::load(&temp).map_err(|e| format!("Failed to read exported pages: {}", e));
+        let _ = std::fs::remove_file(&temp);
+        let source = source?;
+
+        std::fs::create_dir_all(&output_dir).map_err(|e| format!("Failed to create output folder: {}", e))?;
+        let name = name.filter(|n| !n.trim().is_empty()).unwrap_or_else(|| "book".to_string());
+        let signatures = layout.signatures_count as usize;
+        let mut paths = Vec::with_capacity(signatures);
+        for signature in 0..signatures {
+            let sheets: Vec<(&SheetLayout, f32)> = ordering
+                .sheets
+                .iter()
+                .zip(&layout.sheets)
+                .filter(|(sheet, _)| sheet.signature_index == signature)
+                .map(|(sheet, response)| (sheet, response.creep_offset_pt))
+                .collect();
+            let mut doc = signature_document(&source, &sheets, signatures, &config, &calibration)?;
+            let path = Path::new(&output_dir).join(format!("{}-signature-{:02}.pdf", name, signature + 1));
+            let path = path.to_string_lossy().to_string();
+            pdf_tools::save(&mut doc, &path)?;
+            paths.push(path);
+        }
+        Ok(paths)
+    })
+    .await
+    .map_err(|e| format!("Signature export task failed: {}", e))?
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -713,6 +1231,105 @@ mod tests {
         assert_eq!(result.sheets[0].back[1].rotation, 180);
     }
 
+    #[test]
+    fn test_imposition_preview() {
+        let page = |i| PageData {
+            page_index: i,
+            width: 100.0,
+            height: 100.0,
+            dpi: None,
+            layers: Vec::new(),
+            metadata: None,
+            background: None,
+        };
+        let config =
+            ImpositionConfig { paper_size: PaperSize::Custom { width: 100.0, height: 200.0 }, ..Default::default() };
+        // Page 2 is blue above green, to see the back side turned
+        let mut render = |p: &PlacementPreview| {
+            Ok(RgbaImage::from_fn(10, 10, |_, y| match (p.page_num, y < 5) {
+                (1, _) => image::Rgba([255, 0, 0, 255]),
+                (_, true) => image::Rgba([0, 0, 255, 255]),
+                (_, false) => image::Rgba([0, 255, 0, 255]),
+            }))
+        };
+        let preview = build_preview(&[page(0), page(1)], &config, &PrinterCalibration::default(), 20, &mut render).unwrap();
+        assert_eq!((preview.sheet_width, preview.sheet_height, preview.sheets.len()), (200.0, 100.0, 1));
+
+        let sheet = &preview.sheets[0];
+        assert_eq!(sheet.front.placements.iter().map(|p| p.page_num).collect::<Vec<_>>(), [0, 1]);
+        assert_eq!(sheet.front.placements[1].bounds, Bounds::new(100.0, 0.0, 100.0, 100.0));
+        assert_eq!(sheet.back.placements[0].bounds, Bounds::new(0.0, 0.0, 100.0, 100.0));
+        assert!(sheet.front.thumbnail.starts_with("data:image/png;base64,"));
+
+        let front = composite_side((200.0, 100.0), 0.1, &sheet.front.placements, &mut render).unwrap();
+        assert_eq!((front.get_pixel(5, 5).0, front.get_pixel(15, 5).0), ([255; 4], [255, 0, 0, 255]));
+        let back = composite_side((200.0, 100.0), 0.1, &sheet.back.placements, &mut render).unwrap();
+        assert_eq!((back.get_pixel(5, 1).0, back.get_pixel(5, 8).0), ([0, 255, 0, 255], [0, 0, 255, 255]));
+    }
+
+    #[test]
+    fn test_printer_calibration() {
+        let calibration = PrinterCalibration { back: PrintOffset { x_mm: 2.0, y_mm: -1.0 }, ..Default::default() };
+        assert!(calibration.validate().is_ok());
+        let ordering = calculate_page_ordering(4);
+        let config = ImpositionConfig::default();
+        let front = side_placements(&ordering.sheets[0].front, &[], &config, 0.0, calibration.front);
+        let back = side_placements(&ordering.sheets[0].back, &[], &config, 0.0, calibration.back);
+        // Looking at the back, 2 mm right and 1 mm down
+        assert!((back[0].bounds.x - front[0].bounds.x - mm_to_pt(2.0)).abs() < 0.01);
+        assert!((back[0].bounds.y - front[0].bounds.y - mm_to_pt(1.0)).abs() < 0.01);
+        assert!(PrinterCalibration { front: PrintOffset { x_mm: 25.0, y_mm: 0.0 }, ..calibration }.validate().is_err());
+
+        let mut doc = calibration_sheet(&PaperSize::A4).unwrap();
+        let mut pdf = Vec::new();
+        doc.save_to(&mut pdf).unwrap();
+        let doc = Document::load_mem(&pdf).unwrap();
+        assert_eq!(doc.get_pages().len(), 2);
+        let back = doc.get_page_content(doc.get_pages()[&2]).unwrap();
+        assert!(String::from_utf8_lossy(&back).contains("Printer calibration - back"));
+    }
+
+    #[test]
+    fn test_signatures() {
+        // 18 pages in signatures of two sheets: 1-8, 9-16, then 17-18 and two blanks on one sheet
+        let result = calculate_signature_ordering(18, 2);
+        assert_eq!(result.sheets.len(), 5);
+        let pages = |s: &SheetLayout| [s.front[0].page_num, s.front[1].page_num, s.back[0].page_num, s.back[1].page_num];
+        assert_eq!(pages(&result.sheets[1]), [6, 3, 4, 5]);
+        assert_eq!((result.sheets[2].signature_index, result.sheets[2].signature_sheet), (1, 0));
+        assert_eq!(pages(&result.sheets[2]), [16, 9, 10, 15]);
+        assert_eq!(pages(&result.sheets[4]), [0, 17, 18, 0]);
+
+        // Creep restarts in every signature
+        let config = ImpositionConfig {
+            sheets_per_signature: Some(2),
+            collation_marks: true,
+            sewing_stations: 3,
+            ..Default::default()
+        };
+        let response = calculate_booklet_imposition(18, Some(config.clone())).unwrap();
+        assert_eq!((response.signatures_count, response.sheets[2].creep_offset_mm), (3, 0.0));
+        assert!((response.total_creep_mm - 0.1).abs() < 1e-6 && response.sheets[3].creep_offset_mm > 0.0);
+
+        let mut source = Document::with_version("1.5");
+        let pages_id = source.new_object_id();
+        let sides = (1..=18)
+            .map(|n| (vec![Operation::new("Tj", vec![Object::string_literal(format!("p{}", n))])], dictionary! {}))
+            .collect();
+        finish_sheets(&mut source, pages_id, (420.0, 595.0), sides).unwrap();
+
+        let ordering = impose_booklet(18, &config);
+        let sheets: Vec<(&SheetLayout, f32)> = ordering.sheets[..2].iter().map(|s| (s, 0.0)).collect();
+        let doc = signature_document(&source, &sheets, 3, &config, &PrinterCalibration::default()).unwrap();
+        let content = |n: u32| String::from_utf8_lossy(&doc.get_page_content(doc.get_pages()[&n]).unwrap()).to_string();
+        assert_eq!(doc.get_pages().len(), 4);
+        // Outer front: pages 8 and 1 with the collation mark; inner back: the center spread with sewing ticks
+        assert!(content(1).contains("/Pg8 Do") && content(1).contains("/Pg1 Do") && content(1).contains(" re"));
+        assert!(content(4).contains("/Pg4 Do") && content(4).contains("/Pg5 Do") && content(4).matches(" l\n").count() == 3);
+        assert!(!content(2).contains(" re") && !content(3).contains(" l\n"));
+        assert_eq!(spine_positions(1, 0.0, 100.0), [50.0]);
+    }
+
     #[test]
     fn test_page_positions() {
         let result = calculate_page_ordering(4);
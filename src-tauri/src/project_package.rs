@@ -0,0 +1,268 @@
+//! Project Packaging Module
+//!
+//! "Collect for output": everything needed to open and print the document on
+//! another machine, gathered into one zip:
+//!
+//! ```text
+//! <title>.bookproj   the project, linked images embedded
+//! images/            every image at full resolution, by original file name when known
+//! fonts/             the files of every font family the text uses
+//! report.txt         what was collected, what is missing, font license flags
+//! ```
+//!
+//! Font files are copied whatever their license says; the report flags the
+//! ones whose embedding permissions forbid passing them on.
+
+use crate::asset_manager;
+use crate::export_handler;
+use crate::font_manager::{audit, pdf_extractor, system};
+use crate::image_handler;
+use crate::models::BookProjectData;
+use serde::{Deserialize, Serialize};
+use std::collections::HashSet;
+use std::fs::File;
+use std::io::Write;
+use std::path::Path;
+use vortex_core::archive;
+use zip::write::SimpleFileOptions;
+use zip::CompressionMethod;
+
+/// Embedding permissions of a font file (OpenType OS/2 `fsType`)
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub enum FontLicense {
+    Installable,
+    Editable,
+    PreviewAndPrint,
+    /// Must not be embedded or passed on without the vendor's permission
+    Restricted,
+    /// Not an OpenType file, or no OS/2 table
+    Unknown,
+}
+
+/// A font file in the package
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct PackagedFont {
+    pub family: String,
+    /// Path inside the package
+    pub file: String,
+    /// Extracted from the imported PDF rather than installed; usually a subset
+    pub embedded: bool,
+    pub license: FontLicense,
+    pub subsetting_allowed: bool,
+}
+
+/// An image in the package
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct PackagedImage {
+    pub asset_id: String,
+    /// Path inside the package
+    pub file: String,
+    /// Layers showing the image
+    pub usages: usize,
+}
+
+/// What `package_project` collected
+#[derive(Debug, Clone, Default, Serialize, Deserialize, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct PackageReport {
+    pub project_file: String,
+    pub images: Vec<PackagedImage>,
+    pub fonts: Vec<PackagedFont>,
+    /// Assets with no bytes to collect
+    pub missing_images: Vec<String>,
+    /// Families with no installed or embedded file
+    pub missing_fonts: Vec<String>,
+}
+
+impl PackageReport {
+    /// Fonts that must not be handed on as they are
+    pub fn restricted_fonts(&self) -> impl Iterator<Item = &PackagedFont> {
+        self.fonts.iter().filter(|f| f.license == FontLicense::Restricted)
+    }
+}
+
+/// License flags of font data; `Unknown` for data ttf-parser cannot read
+fn font_license(data: &[u8]) -> (FontLicense, bool) {
+    let Ok(face) = ttf_parser::Face::parse(data, 0) else {
+        return (FontLicense::Unknown, true);
+    };
+    let license = match face.permissions() {
+        Some(ttf_parser::Permissions::Installable) => FontLicense::Installable,
+        Some(ttf_parser::Permissions::Editable) => FontLicense::Editable,
+        Some(ttf_parser::Permissions::PreviewAndPrint) => FontLicense::PreviewAndPrint,
+        Some(ttf_parser::Permissions::Restricted) => FontLicense::Restricted,
+        None => FontLicense::Unknown,
+    };
+    (license, face.is_subsetting_allowed())
+}
+
+/// Font file extension from its leading tag
+fn font_extension(data: &[u8]) -> &'static str {
+    match data.get(..4) {
+        Some(b"OTTO") => "otf",
+        Some(b"ttcf") => "ttc",
+        Some(b"wOFF") => "woff",
+        Some(b"wOF2") => "woff2",
+        _ => "ttf",
+    }
+}
+
+/// `name` in `dir` (the package root when empty), numbered when the
+/// package already has that entry
+fn unique_entry(used: &mut HashSet<String>, dir: &str, name: &str) -> String {
+    let name: String = name.chars().map(|c| if c.is_alphanumeric() || "._- ".contains(c) { c } else { '_' }).collect();
+    let (stem, ext) = match name.rsplit_once('.') {
+        Some((stem, ext)) if !stem.is_empty() => (stem.to_string(), format!(".{}", ext)),
+        _ => (name.clone(), String::new()),
+    };
+    let prefix = if dir.is_empty() { String::new() } else { format!("{}/", dir) };
+    let mut entry = format!("{}{}", prefix, name);
+    let mut n = 1;
+    while !used.insert(entry.to_lowercase()) {
+        n += 1;
+        entry = format!("{}{}-{}{}", prefix, stem, n, ext);
+    }
+    entry
+}
+
+/// The report as plain text, for `report.txt`
+fn report_text(project: &BookProjectData, report: &PackageReport) -> String {
+    let mut text = format!("Package report: {}\n\n", project.metadata.title);
+    text.push_str(&format!("Project: {}\nPages: {}\n\n", report.project_file, project.document.pages.len()));
+
+    text.push_str(&format!("Images ({})\n", report.images.len()));
+    for image in &report.images {
+        text.push_str(&format!("  {}  used {}x\n", image.file, image.usages));
+    }
+    text.push_str(&format!("\nFonts ({})\n", report.fonts.len()));
+    for font in &report.fonts {
+        let source = if font.embedded { ", extracted from PDF" } else { "" };
+        text.push_str(&format!("  {}  {}: {:?}{}\n", font.file, font.family, font.license, source));
+    }
+
+    let restricted: Vec<&str> = report.restricted_fonts().map(|f| f.file.as_str()).collect();
+    if !restricted.is_empty() {
+        text.push_str("\nLicense restricted; check with the vendor before handing on:\n");
+        restricted.iter().for_each(|f| text.push_str(&format!("  {}\n", f)));
+    }
+    if !report.missing_images.is_empty() {
+        text.push_str(&format!("\nMissing images:\n  {}\n", report.missing_images.join("\n  ")));
+    }
+    if !report.missing_fonts.is_empty() {
+        text.push_str(&format!("\nMissing fonts:\n  {}\n", report.missing_fonts.join("\n  ")));
+    }
+    text
+}
+
+/// Collect a project, its images and fonts and a report into a zip
+#[tauri::command]
+pub async fn package_project(project: BookProjectData, output_path: String) -> Result<PackageReport, String> {
+    tokio::task::spawn_blocking(move || {
+        let mut zip = zip::ZipWriter::new(File::create(&output_path).map_err(|e| e.to_string())?);
+        let deflated = SimpleFileOptions::default().compression_method(CompressionMethod::Deflated);
+        // Images and fonts are mostly compressed already
+        let stored = SimpleFileOptions::default().compression_method(CompressionMethod::Stored);
+        let mut used = HashSet::new();
+        let mut report = PackageReport::default();
+
+        let title = project.metadata.title.trim();
+        let name = format!("{}.bookproj", if title.is_empty() { "project" } else { title });
+        report.project_file = unique_entry(&mut used, "", &name);
+        let mut packaged = project.clone();
+        archive::embed_linked_images(&mut packaged);
+        zip.start_file(report.project_file.as_str(), stored).map_err(|e| e.to_string())?;
+        zip.write_all(&export_handler::project_archive(&packaged)?).map_err(|e| e.to_string())?;
+
+        for asset in asset_manager::list_assets(project.document.pages.clone()) {
+            // The original file is full resolution; the cache may hold a downsampled copy
+            let original = asset.path.as_deref().and_then(|p| std::fs::read(p).ok().map(|data| (p, data)));
+            let (name, data) = match original {
+                Some((path, data)) => (Path::new(path).file_name().map(|n| n.to_string_lossy().to_string()), data),
+                None => match image_handler::full_resolution_bytes(&asset.id) {
+                    Some(data) => (None, data),
+                    None => {
+                        report.missing_images.push(asset.id);
+                        continue;
+                    }
+                },
+            };
+            let ext = image_handler::mime_type(&data).rsplit('/').next().unwrap_or("bin");
+            let name = name.unwrap_or_else(|| format!("{}.{}", asset.id, ext));
+            let file = unique_entry(&mut used, "images", &name);
+            zip.start_file(file.as_str(), stored).map_err(|e| e.to_string())?;
+            zip.write_all(&data).map_err(|e| e.to_string())?;
+            report.images.push(PackagedImage { asset_id: asset.id, file, usages: asset.usages.len() });
+        }
+
+        let (usages, _) = audit::collect_usages(&project.document.pages);
+        let mut seen = HashSet::new();
+        let families = usages.into_iter().map(|u| u.family).filter(|f| f != "Unknown" && seen.insert(f.to_lowercase()));
+        for family in families {
+            let files: Vec<(String, Vec<u8>, bool)> = match pdf_extractor::get_embedded_font(&family) {
+                Some(data) => vec![(format!("{}.{}", family, font_extension(&data)), data, true)],
+                None => system::get_font_paths(&family)
+                    .into_iter()
+                    .filter_map(|path| {
+                        let data = std::fs::read(&path).ok()?;
+                        Some((path.file_name()?.to_string_lossy().to_string(), data, false))
+                    })
+                    .collect(),
+            };
+            if files.is_empty() {
+                report.missing_fonts.push(family.clone());
+            }
+            for (name, data, embedded) in files {
+                let (license, subsetting_allowed) = font_license(&data);
+                let file = unique_entry(&mut used, "fonts", &name);
+                zip.start_file(file.as_str(), stored).map_err(|e| e.to_string())?;
+                zip.write_all(&data).map_err(|e| e.to_string())?;
+                report.fonts.push(PackagedFont { family: family.clone(), file, embedded, license, subsetting_allowed });
+            }
+        }
+
+        zip.start_file("report.txt", deflated).map_err(|e| e.to_string())?;
+        zip.write_all(report_text(&project, &report).as_bytes()).map_err(|e| e.to_string())?;
+        zip.finish().map_err(|e| e.to_string())?;
+        Ok(report)
+    })
+    .await
+    .map_err(|e| format!("Package task failed: {}", e))?
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_package_report() {
+        let mut used = HashSet::new();
+        assert_eq!(unique_entry(&mut used, "images", "fig 1.png"), "images/fig 1.png");
+        assert_eq!(unique_entry(&mut used, "images", "Fig 1.PNG"), "images/Fig 1-2.PNG");
+        assert_eq!(unique_entry(&mut used, "fonts", "a/b:c"), "fonts/a_b_c");
+        assert_eq!(unique_entry(&mut used, "", "Atlas.bookproj"), "Atlas.bookproj");
+        assert_eq!((font_extension(b"OTTO\0\0"), font_extension(&[0, 1, 0, 0])), ("otf", "ttf"));
+        assert_eq!(font_license(b"not a font"), (FontLicense::Unknown, true));
+
+        let mut project = BookProjectData::default();
+        project.metadata.title = "Atlas".to_string();
+        let report = PackageReport {
+            project_file: "Atlas.bookproj".to_string(),
+            fonts: vec![PackagedFont {
+                family: "Vendor Sans".to_string(),
+                file: "fonts/VendorSans.otf".to_string(),
+                embedded: false,
+                license: FontLicense::Restricted,
+                subsetting_allowed: false,
+            }],
+            missing_fonts: vec!["Gone Serif".to_string()],
+            ..Default::default()
+        };
+        let text = report_text(&project, &report);
+        assert!(text.starts_with("Package report: Atlas\n"));
+        assert!(text.contains("License restricted; check with the vendor before handing on:\n  fonts/VendorSans.otf\n"));
+        assert!(text.contains("Missing fonts:\n  Gone Serif\n"));
+    }
+}
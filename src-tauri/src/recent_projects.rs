@@ -0,0 +1,259 @@
+//! Recent Projects Module
+//!
+//! The start screen's list of recently opened and saved projects, persisted
+//! as `recent_projects.json` in the app data dir. Entries carry the project
+//! title, page count and file modification time; a thumbnail of the first
+//! page is rendered in the background into `recent_thumbnails/`. Pinned
+//! projects are listed first and never dropped; unpinned ones are capped at
+//! `MAX_RECENT`.
+
+use crate::export_handler;
+use crate::models::{iso8601_from_system_time, iso8601_now, DocumentMetadata, PageData};
+use base64::Engine;
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, RwLock};
+
+const RECENT_FILE: &str = "recent_projects.json";
+const THUMBNAIL_DIR: &str = "recent_thumbnails";
+/// Unpinned projects kept
+const MAX_RECENT: usize = 20;
+/// Thumbnail width in pixels
+const THUMBNAIL_WIDTH: f32 = 256.0;
+
+/// A recently opened or saved project
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct RecentProject {
+    pub path: String,
+    pub title: String,
+    pub page_count: usize,
+    /// File modification time when last recorded (ISO 8601)
+    pub modified: String,
+    /// Last opened or saved (ISO 8601)
+    pub last_opened: String,
+    #[serde(default)]
+    pub pinned: bool,
+    /// First page as a PNG data URL; filled in when listing
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub thumbnail: Option<String>,
+    /// The file no longer exists; filled in when listing
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub missing: bool,
+}
+
+#[derive(Debug, Default)]
+struct RecentStore {
+    projects: Vec<RecentProject>,
+    dir: Option<PathBuf>,
+}
+
+lazy_static::lazy_static! {
+    static ref RECENT_STORE: Arc<RwLock<RecentStore>> = Arc::new(RwLock::new(RecentStore::default()));
+}
+
+/// Load the recent list from `dir` and remember it for later saves
+pub fn init_recent_projects(dir: PathBuf) -> Result<(), String> {
+    fs::create_dir_all(dir.join(THUMBNAIL_DIR)).map_err(|e| e.to_string())?;
+    let projects: Vec<RecentProject> = fs::read(dir.join(RECENT_FILE))
+        .ok()
+        .and_then(|data| serde_json::from_slice(&data).ok())
+        .unwrap_or_default();
+
+    let mut store = RECENT_STORE.write().map_err(|e| e.to_string())?;
+    store.projects = projects;
+    store.dir = Some(dir);
+    Ok(())
+}
+
+fn persist(store: &RecentStore) -> Result<(), String> {
+    let Some(dir) = &store.dir else {
+        return Ok(());
+    };
+    let path = dir.join(RECENT_FILE);
+    let data = serde_json::to_vec_pretty(&store.projects).map_err(|e| e.to_string())?;
+    let tmp = path.with_extension("json.tmp");
+    fs::write(&tmp, data).map_err(|e| e.to_string())?;
+    fs::rename(&tmp, path).map_err(|e| e.to_string())
+}
+
+/// Thumbnail file for a project, keyed by a hash of its path
+fn thumbnail_path(dir: &Path, project_path: &str) -> PathBuf {
+    let key: String = Sha256::digest(project_path.as_bytes()).iter().take(8).map(|b| format!("{:02x}", b)).collect();
+    dir.join(THUMBNAIL_DIR).join(format!("{}.png", key))
+}
+
+fn file_modified(path: &str) -> Option<String> {
+    fs::metadata(path).and_then(|m| m.modified()).ok().map(iso8601_from_system_time)
+}
+
+/// Put `entry` at the front of the list, keeping the pin of an existing
+/// entry for the same path; returns the unpinned entries pushed off the end
+fn upsert(projects: &mut Vec<RecentProject>, mut entry: RecentProject) -> Vec<RecentProject> {
+    if let Some(i) = projects.iter().position(|p| p.path == entry.path) {
+        entry.pinned = projects.remove(i).pinned;
+    }
+    projects.insert(0, entry);
+
+    let mut unpinned = 0;
+    let (kept, dropped): (Vec<_>, Vec<_>) = std::mem::take(projects).into_iter().partition(|p| {
+        unpinned += usize::from(!p.pinned);
+        p.pinned || unpinned <= MAX_RECENT
+    });
+    *projects = kept;
+    dropped
+}
+
+/// Pinned projects first, each group most recent first
+fn sorted(projects: &[RecentProject]) -> Vec<RecentProject> {
+    let mut sorted = projects.to_vec();
+    sorted.sort_by(|a, b| b.pinned.cmp(&a.pinned).then_with(|| b.last_opened.cmp(&a.last_opened)));
+    sorted
+}
+
+/// Note that a project was opened or saved
+///
+/// `first_page` is rendered as the thumbnail on a background thread when the
+/// file changed since it was last recorded or has no thumbnail yet.
+pub fn record(path: &str, metadata: &DocumentMetadata, page_count: usize, first_page: Option<PageData>) {
+    let modified = file_modified(path).unwrap_or_default();
+    let title = match metadata.title.trim() {
+        "" => Path::new(path).file_stem().map(|s| s.to_string_lossy().to_string()).unwrap_or_default(),
+        title => title.to_string(),
+    };
+    let entry = RecentProject {
+        path: path.to_string(),
+        title,
+        page_count,
+        modified: modified.clone(),
+        last_opened: iso8601_now(),
+        pinned: false,
+        thumbnail: None,
+        missing: false,
+    };
+
+    let Ok(mut store) = RECENT_STORE.write() else { return };
+    let Some(dir) = store.dir.clone() else { return };
+    let unchanged = store.projects.iter().any(|p| p.path == path && p.modified == modified);
+    for dropped in upsert(&mut store.projects, entry) {
+        let _ = fs::remove_file(thumbnail_path(&dir, &dropped.path));
+    }
+    if let Err(e) = persist(&store) {
+        tracing::warn!("failed to save recent projects: {}", e);
+    }
+    drop(store);
+
+    let thumbnail = thumbnail_path(&dir, path);
+    if let Some(page) = first_page.filter(|_| !(unchanged && thumbnail.exists())) {
+        std::thread::spawn(move || match render_thumbnail(&page) {
+            Ok(png) => {
+                let _ = fs::write(&thumbnail, png);
+            }
+            Err(e) => tracing::warn!("project thumbnail failed: {}", e),
+        });
+    }
+}
+
+/// PNG of a page, `THUMBNAIL_WIDTH` pixels wide
+fn render_thumbnail(page: &PageData) -> Result<Vec<u8>, String> {
+    let image = export_handler::render_page_image(page, THUMBNAIL_WIDTH / page.width.max(1.0))?;
+    let mut png = std::io::Cursor::new(Vec::new());
+    image.write_to(&mut png, image::ImageFormat::Png).map_err(|e| e.to_string())?;
+    Ok(png.into_inner())
+}
+
+/// Recent projects, pinned first, with thumbnails and missing files marked
+#[tauri::command]
+pub fn get_recent_projects() -> Result<Vec<RecentProject>, String> {
+    let store = RECENT_STORE.read().map_err(|e| e.to_string())?;
+    let dir = store.dir.clone();
+    let mut projects = sorted(&store.projects);
+    drop(store);
+
+    for project in &mut projects {
+        project.missing = !Path::new(&project.path).exists();
+        project.thumbnail = dir
+            .as_ref()
+            .and_then(|dir| fs::read(thumbnail_path(dir, &project.path)).ok())
+            .map(|png| format!("data:image/png;base64,{}", base64::engine::general_purpose::STANDARD.encode(png)));
+    }
+    Ok(projects)
+}
+
+/// Pin or unpin a recent project
+#[tauri::command]
+pub fn pin_project(path: String, pinned: bool) -> Result<(), String> {
+    let mut store = RECENT_STORE.write().map_err(|e| e.to_string())?;
+    let project = store
+        .projects
+        .iter_mut()
+        .find(|p| p.path == path)
+        .ok_or_else(|| format!("'{}' is not a recent project", path))?;
+    project.pinned = pinned;
+    persist(&store)
+}
+
+/// Remove a project from the recent list (the file itself is kept)
+#[tauri::command]
+pub fn remove_recent(path: String) -> Result<(), String> {
+    let mut store = RECENT_STORE.write().map_err(|e| e.to_string())?;
+    let before = store.projects.len();
+    store.projects.retain(|p| p.path != path);
+    if store.projects.len() == before {
+        return Err(format!("'{}' is not a recent project", path));
+    }
+    if let Some(dir) = &store.dir {
+        let _ = fs::remove_file(thumbnail_path(dir, &path));
+    }
+    persist(&store)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn entry(path: &str, last_opened: &str) -> RecentProject {
+        RecentProject {
+            path: path.to_string(),
+            title: path.to_string(),
+            page_count: 1,
+            modified: String::new(),
+            last_opened: last_opened.to_string(),
+            pinned: false,
+            thumbnail: None,
+            missing: false,
+        }
+    }
+
+    #[test]
+    fn test_upsert_keeps_pins_and_caps_unpinned() {
+        let mut projects = vec![RecentProject { pinned: true, ..entry("/old.bookproj", "2024-01-01T00:00:00Z") }];
+        for i in 0..MAX_RECENT {
+            assert!(upsert(&mut projects, entry(&format!("/p{}.bookproj", i), "2024-02-01T00:00:00Z")).is_empty());
+        }
+        // One more unpinned project pushes the oldest unpinned one out; the pin stays
+        let dropped = upsert(&mut projects, entry("/new.bookproj", "2024-03-01T00:00:00Z"));
+        assert_eq!(dropped.len(), 1);
+        assert_eq!(dropped[0].path, "/p0.bookproj");
+        assert_eq!(projects.len(), MAX_RECENT + 1);
+
+        // Reopening moves a project to the front and keeps its pin
+        upsert(&mut projects, entry("/old.bookproj", "2024-04-01T00:00:00Z"));
+        assert_eq!(projects[0].path, "/old.bookproj");
+        assert!(projects[0].pinned);
+        assert_eq!(projects.iter().filter(|p| p.path == "/old.bookproj").count(), 1);
+    }
+
+    #[test]
+    fn test_sorted_pins_first() {
+        let projects = vec![
+            entry("/a", "2024-03-01T00:00:00Z"),
+            RecentProject { pinned: true, ..entry("/b", "2024-01-01T00:00:00Z") },
+            entry("/c", "2024-04-01T00:00:00Z"),
+        ];
+        let order: Vec<String> = sorted(&projects).into_iter().map(|p| p.path).collect();
+        assert_eq!(order, vec!["/b", "/c", "/a"]);
+    }
+}
@@ -0,0 +1,502 @@
+//! Scanner Module
+//!
+//! Acquires pages from a flatbed or document feeder through the platform
+//! scanning stack: SANE (`scanimage`) on Linux, WIA (via PowerShell COM) on
+//! Windows and ImageCaptureCore (via the `scanline` CLI) on macOS. Each
+//! scanned image becomes a new page with a full-page image layer; OCR text
+//! layers are added on top when requested.
+
+use crate::image_handler;
+use crate::models::{
+    Bounds, DocumentData, DocumentResponse, ImageMetadata, LayerObject, LayerRole, LayerType, PageData,
+    SourceType,
+};
+use crate::ocr_handler::{OcrConfig, OcrEngine};
+use image::RgbaImage;
+use serde::{Deserialize, Serialize};
+use std::path::{Path, PathBuf};
+use std::process::Command;
+use tauri::{AppHandle, Emitter};
+use vortex_core::ocr_correction::OcrDictionary;
+use vortex_core::units::px_to_pt;
+
+/// Color mode requested from the scanner
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq, Default)]
+#[serde(rename_all = "lowercase")]
+#[repr(u8)]
+pub enum ScanColorMode {
+    #[default]
+    Color = 0,
+    Gray = 1,
+    /// Black and white (text documents)
+    Lineart = 2,
+}
+
+/// Options for `scan_pages`
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ScanOptions {
+    /// Device id from `list_scanners`; the first scanner when absent
+    #[serde(default)]
+    pub device: Option<String>,
+    #[serde(default = "default_scan_dpi")]
+    pub dpi: u32,
+    #[serde(default)]
+    pub color_mode: ScanColorMode,
+    /// Scan from the document feeder until it is empty
+    #[serde(default)]
+    pub feeder: bool,
+    /// Index of the first new page, so scans can be appended
+    #[serde(default)]
+    pub start_page: usize,
+    /// Run OCR on each page and add text layers
+    #[serde(default)]
+    pub ocr: bool,
+    /// Tesseract language code(s), e.g. "eng" or "eng+deu"
+    #[serde(default)]
+    pub language: Option<String>,
+    /// Project corrections for OCR post-processing
+    #[serde(default)]
+    pub dictionary: Option<OcrDictionary>,
+}
+
+fn default_scan_dpi() -> u32 {
+    300
+}
+
+impl Default for ScanOptions {
+    fn default() -> Self {
+        Self {
+            device: None,
+            dpi: default_scan_dpi(),
+            color_mode: ScanColorMode::default(),
+            feeder: false,
+            start_page: 0,
+            ocr: false,
+            language: None,
+            dictionary: None,
+        }
+    }
+}
+
+/// A scanner reported by the platform
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct ScannerDevice {
+    pub id: String,
+    pub name: String,
+}
+
+/// Parse `id<TAB>name` lines as printed by the listing commands below
+fn parse_device_list(output: &str) -> Vec<ScannerDevice> {
+    output
+        .lines()
+        .filter_map(|line| {
+            let (id, name) = line.split_once('\t').unwrap_or((line, line));
+            let id = id.trim();
+            (!id.is_empty()).then(|| ScannerDevice { id: id.to_string(), name: name.trim().to_string() })
+        })
+        .collect()
+}
+
+/// Run a scanning tool, turning a missing binary into an actionable message
+fn run(mut command: Command, tool: &str, hint: &str) -> Result<std::process::Output, String> {
+    command.output().map_err(|e| match e.kind() {
+        std::io::ErrorKind::NotFound => format!("{} not found; {}", tool, hint),
+        _ => format!("Failed to run {}: {}", tool, e),
+    })
+}
+
+#[cfg(target_os = "linux")]
+mod platform {
+    use super::*;
+
+    const HINT: &str = "install SANE (sane-utils) to scan";
+
+    pub fn list_command() -> Command {
+        let mut command = Command::new("scanimage");
+        command.arg("--formatted-device-list=%d\t%v %m%n");
+        command
+    }
+
+    pub fn scan_command(options: &ScanOptions, dir: &Path) -> Command {
+        let mut command = Command::new("scanimage");
+        command.args(scanimage_args(options, dir));
+        command
+    }
+
+    pub fn run(command: Command) -> Result<std::process::Output, String> {
+        super::run(command, "scanimage", HINT)
+    }
+}
+
+#[cfg(target_os = "windows")]
+mod platform {
+    use super::*;
+
+    /// WIA_IPS_CUR_INTENT values for color, grayscale and text
+    fn intent(mode: ScanColorMode) -> u32 {
+        match mode {
+            ScanColorMode::Color => 1,
+            ScanColorMode::Gray => 2,
+            ScanColorMode::Lineart => 4,
+        }
+    }
+
+    fn quote(value: &str) -> String {
+        format!("'{}'", value.replace('\'', "''"))
+    }
+
+    fn powershell(script: String) -> Command {
+        let mut command = Command::new("powershell");
+        command.args(["-NoProfile", "-NonInteractive", "-Command", &script]);
+        command
+    }
+
+    pub fn list_command() -> Command {
+        powershell(
+            "(New-Object -ComObject WIA.DeviceManager).DeviceInfos | Where-Object { $_.Type -eq 1 } | \
+             ForEach-Object { \"$($_.DeviceID)`t$($_.Properties.Item('Name').Value)\" }"
+                .to_string(),
+        )
+    }
+
+    pub fn scan_command(options: &ScanOptions, dir: &Path) -> Command {
+        // 3088 selects the feeder, 6146-6148 are intent and X/Y resolution;
+        // the GUID is WIA's PNG format id
+        let script = format!(
+            r#"$ErrorActionPreference = 'Stop'
+$device = {device}
+$info = (New-Object -ComObject WIA.DeviceManager).DeviceInfos | Where-Object {{ $_.Type -eq 1 -and ($device -eq '' -or $_.DeviceID -eq $device) }} | Select-Object -First 1
+if (-not $info) {{ throw 'No WIA scanner found' }}
+$scanner = $info.Connect()
+$feeder = ${feeder}
+if ($feeder) {{ $scanner.Properties.Item('3088').Value = 1 }}
+$item = $scanner.Items.Item(1)
+foreach ($p in $item.Properties) {{
+  switch ($p.PropertyID) {{ 6146 {{ $p.Value = {intent} }} 6147 {{ $p.Value = {dpi} }} 6148 {{ $p.Value = {dpi} }} }}
+}}
+$n = 0
+do {{
+  try {{ $image = $item.Transfer('{{B96B3CAF-0728-11D3-9D7B-0000F81EF32E}}') }} catch {{ if ($n -gt 0) {{ break }} else {{ throw }} }}
+  $n++
+  $image.SaveFile((Join-Path {dir} ('scan-{{0:D4}}.png' -f $n)))
+}} while ($feeder)"#,
+            device = quote(options.device.as_deref().unwrap_or("")),
+            feeder = options.feeder,
+            intent = intent(options.color_mode),
+            dpi = options.dpi,
+            dir = quote(&dir.to_string_lossy()),
+        );
+        powershell(script)
+    }
+
+    pub fn run(command: Command) -> Result<std::process::Output, String> {
+        super::run(command, "PowerShell", "WIA scanning requires Windows PowerShell")
+    }
+}
+
+#[cfg(target_os = "macos")]
+mod platform {
+    use super::*;
+
+    // ImageCaptureCore has no command-line front end of its own
+    const HINT: &str = "install the ImageCaptureCore CLI `scanline` (brew install scanline) to scan";
+
+    pub fn list_command() -> Command {
+        let mut command = Command::new("scanline");
+        command.arg("-list");
+        command
+    }
+
+    pub fn scan_command(options: &ScanOptions, dir: &Path) -> Command {
+        let mut command = Command::new("scanline");
+        command
+            .args(["-dir", &dir.to_string_lossy(), "-name", "scan", "-jpeg"])
+            .args(["-resolution", &options.dpi.to_string()])
+            .arg(if options.feeder { "-feeder" } else { "-flatbed" });
+        if options.color_mode != ScanColorMode::Color {
+            command.arg("-mono");
+        }
+        if let Some(device) = &options.device {
+            command.args(["-scanner", device]);
+        }
+        command
+    }
+
+    pub fn run(command: Command) -> Result<std::process::Output, String> {
+        super::run(command, "scanline", HINT)
+    }
+}
+
+#[cfg(not(any(target_os = "linux", target_os = "windows", target_os = "macos")))]
+mod platform {
+    use super::*;
+
+    pub fn list_command() -> Command {
+        Command::new("scanimage")
+    }
+
+    pub fn scan_command(_options: &ScanOptions, _dir: &Path) -> Command {
+        Command::new("scanimage")
+    }
+
+    pub fn run(_command: Command) -> Result<std::process::Output, String> {
+        Err("Scanning is not supported on this platform".to_string())
+    }
+}
+
+/// `scanimage` arguments writing one PNG per page into `dir`
+#[cfg_attr(not(any(target_os = "linux", test)), allow(dead_code))]
+fn scanimage_args(options: &ScanOptions, dir: &Path) -> Vec<String> {
+    let mode = match options.color_mode {
+        ScanColorMode::Color => "Color",
+        ScanColorMode::Gray => "Gray",
+        ScanColorMode::Lineart => "Lineart",
+    };
+    let mut args = vec![
+        "--format=png".to_string(),
+        format!("--resolution={}", options.dpi),
+        format!("--mode={}", mode),
+        format!("--batch={}", dir.join("scan-%04d.png").to_string_lossy()),
+    ];
+    if !options.feeder {
+        args.push("--batch-count=1".to_string());
+    }
+    if let Some(device) = &options.device {
+        args.push(format!("--device-name={}", device));
+    }
+    args
+}
+
+/// Scanned image files in acquisition order
+fn collect_scans(dir: &Path) -> Result<Vec<PathBuf>, String> {
+    let mut files: Vec<PathBuf> = std::fs::read_dir(dir)
+        .map_err(|e| format!("Failed to read scan directory: {}", e))?
+        .filter_map(|entry| entry.ok().map(|e| e.path()))
+        .filter(|path| {
+            let ext = path.extension().and_then(|e| e.to_str()).unwrap_or("").to_lowercase();
+            matches!(ext.as_str(), "png" | "jpg" | "jpeg")
+        })
+        .collect();
+    files.sort();
+    Ok(files)
+}
+
+/// Acquire pages into `dir`; a non-zero exit after at least one page (e.g.
+/// an empty feeder) is not an error
+fn acquire(options: &ScanOptions, dir: &Path) -> Result<Vec<PathBuf>, String> {
+    let output = platform::run(platform::scan_command(options, dir))?;
+    let files = collect_scans(dir)?;
+    if files.is_empty() {
+        let stderr = String::from_utf8_lossy(&output.stderr);
+        return Err(match stderr.trim() {
+            "" => "Scanner returned no pages".to_string(),
+            msg => format!("Scan failed: {}", msg),
+        });
+    }
+    if !output.status.success() {
+        tracing::warn!(pages = files.len(), "scanner exited with {}", output.status);
+    }
+    Ok(files)
+}
+
+/// Page with an image as a full-page background layer, sized from its
+/// pixel dimensions at `dpi`
+pub(crate) fn image_page(id: String, page_index: usize, width_px: u32, height_px: u32, dpi: u32) -> PageData {
+    let (width, height) = (px_to_pt(width_px as f32, dpi), px_to_pt(height_px as f32, dpi));
+    let layer = LayerObject {
+        id: id.clone(),
+        layer_type: LayerType::Image,
+        bounds: Bounds::new(0.0, 0.0, width, height),
+        visible: true,
+        locked: false,
+        z_index: 0,
+        opacity: 1.0,
+        blend_mode: None,
+        content: None,
+        font_family: None,
+        font_size: None,
+        font_weight: None,
+        font_style: None,
+        color: None,
+        text_align: None,
+        text_decoration: None,
+        text_transform: None,
+        line_height: None,
+        letter_spacing: None,
+        background_color: None,
+        text_path: None,
+        text_outline: None,
+        text_shadow: None,
+        drop_cap: None,
+        notes: Vec::new(),
+        pagination: None,
+        image_url: Some(format!("image://{}", id)),
+        image_path: None,
+        image_data: Some(ImageMetadata {
+            width: width_px,
+            height: height_px,
+            color_space: "RGBA".to_string(),
+            dpi,
+            icc_profile: None,
+        }),
+        image_link: None,
+        shape_type: None,
+        stroke_color: None,
+        stroke_width: None,
+        fill_color: None,
+        text_wrap: None,
+        path_data: None,
+        transform: None,
+        source_type: SourceType::Imported,
+        role: LayerRole::Background,
+        ocr: None,
+        tags: Vec::new(),
+    };
+    PageData { page_index, width, height, dpi: Some(dpi), layers: vec![layer], metadata: None, background: None }
+}
+
+/// OCR an image page and stack the text layers above its image
+pub(crate) fn add_ocr_layers(engine: &mut OcrEngine, page: &mut PageData, image: &RgbaImage, scale: f32) {
+    match engine.recognize_page(image, page.page_index, scale) {
+        Ok(layers) => page.layers.extend(layers.into_iter().map(|mut layer| {
+            layer.z_index += 1;
+            layer
+        })),
+        Err(e) => tracing::warn!(page = page.page_index, "OCR failed on image page: {}", e),
+    }
+}
+
+/// Turn scanned files into pages, caching images and running OCR
+fn build_pages(files: &[PathBuf], options: &ScanOptions, app_handle: &AppHandle) -> Result<Vec<PageData>, String> {
+    let session = std::time::SystemTime::now()
+        .duration_since(std::time::UNIX_EPOCH)
+        .map(|d| d.as_millis())
+        .unwrap_or_default();
+    let mut engine = options.ocr.then(|| {
+        OcrEngine::with_config(OcrConfig {
+            language: options.language.clone().unwrap_or_else(|| OcrConfig::default().language),
+            dictionary: options.dictionary.clone().unwrap_or_default(),
+            ..OcrConfig::default()
+        })
+    });
+    let scale = options.dpi as f32 / vortex_core::units::POINTS_PER_INCH;
+
+    let mut pages = Vec::with_capacity(files.len());
+    for (i, file) in files.iter().enumerate() {
+        let page_index = options.start_page + i;
+        let _ = app_handle.emit(
+            "scan_progress",
+            serde_json::json!({
+                "currentPage": i + 1,
+                "totalPages": files.len(),
+                "status": format!("Processing page {}...", i + 1)
+            }),
+        );
+
+        let bytes = std::fs::read(file).map_err(|e| format!("Failed to read scan: {}", e))?;
+        let image = image::load_from_memory(&bytes).map_err(|e| format!("Failed to decode scan: {}", e))?;
+        let id = format!("scan-{}-{}", session, i);
+        let mut page = image_page(id.clone(), page_index, image.width(), image.height(), options.dpi);
+        image_handler::cache_image_with_dimensions(&id, bytes, image.width(), image.height());
+
+        if let Some(engine) = engine.as_mut() {
+            add_ocr_layers(engine, &mut page, &image.to_rgba8(), scale);
+        }
+        pages.push(page);
+    }
+    Ok(pages)
+}
+
+/// List available scanners
+#[tauri::command]
+pub async fn list_scanners() -> Result<Vec<ScannerDevice>, String> {
+    tokio::task::spawn_blocking(|| {
+        let output = platform::run(platform::list_command())?;
+        if !output.status.success() {
+            return Err(format!("Scanner listing failed: {}", String::from_utf8_lossy(&output.stderr).trim()));
+        }
+        Ok(parse_device_list(&String::from_utf8_lossy(&output.stdout)))
+    })
+    .await
+    .map_err(|e| format!("Scanner task failed: {}", e))?
+}
+
+/// Scan one page (or the whole feeder) into new pages
+///
+/// Returns a document holding only the new pages, numbered from
+/// `options.start_page`; the frontend appends them to the open document.
+#[tauri::command]
+pub async fn scan_pages(app_handle: AppHandle, options: Option<ScanOptions>) -> Result<DocumentResponse, String> {
+    let options = options.unwrap_or_default();
+    if !(50..=1200).contains(&options.dpi) {
+        return Err(format!("Scan resolution must be 50-1200 DPI, got {}", options.dpi));
+    }
+
+    let _ = app_handle.emit(
+        "scan_progress",
+        serde_json::json!({ "currentPage": 0, "totalPages": 0, "status": "Scanning..." }),
+    );
+
+    tokio::task::spawn_blocking(move || {
+        let dir = std::env::temp_dir().join(format!(
+            "rook-scan-{}-{}",
+            std::process::id(),
+            std::time::SystemTime::now()
+                .duration_since(std::time::UNIX_EPOCH)
+                .map(|d| d.as_nanos())
+                .unwrap_or_default()
+        ));
+        std::fs::create_dir_all(&dir).map_err(|e| format!("Failed to create scan directory: {}", e))?;
+
+        let result = acquire(&options, &dir).and_then(|files| build_pages(&files, &options, &app_handle));
+        let _ = std::fs::remove_dir_all(&dir);
+        let pages = result?;
+
+        let (width, height) = pages.first().map(|p| (p.width, p.height)).unwrap_or((612.0, 792.0));
+        Ok(DocumentResponse {
+            success: true,
+            message: format!("Scanned {} page(s)", pages.len()),
+            data: Some(DocumentData::new(width, height, pages)),
+            repair: None,
+            metadata: None,
+        })
+    })
+    .await
+    .map_err(|e| format!("Scan task failed: {}", e))?
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_device_list() {
+        let devices = parse_device_list("epson2:net:10.0.0.5\tEpson GT-S85\n\nplustek:libusb:001:004\n");
+        assert_eq!(devices.len(), 2);
+        assert_eq!(devices[0].id, "epson2:net:10.0.0.5");
+        assert_eq!(devices[0].name, "Epson GT-S85");
+        assert_eq!(devices[1].name, "plustek:libusb:001:004");
+    }
+
+    #[test]
+    fn test_scanimage_args_and_page_size() {
+        let options = ScanOptions { dpi: 600, color_mode: ScanColorMode::Gray, ..ScanOptions::default() };
+        let args = scanimage_args(&options, Path::new("/tmp/scan"));
+        assert!(args.contains(&"--resolution=600".to_string()));
+        assert!(args.contains(&"--mode=Gray".to_string()));
+        assert!(args.contains(&"--batch-count=1".to_string()));
+
+        let feeder = ScanOptions { feeder: true, device: Some("dev".into()), ..ScanOptions::default() };
+        let args = scanimage_args(&feeder, Path::new("/tmp/scan"));
+        assert!(!args.iter().any(|a| a.starts_with("--batch-count")));
+        assert!(args.contains(&"--device-name=dev".to_string()));
+
+        // Letter at 300 DPI
+        let page = image_page("scan-0".into(), 3, 2550, 3300, 300);
+        assert_eq!((page.width, page.height), (612.0, 792.0));
+        assert_eq!(page.layers[0].bounds.width, 612.0);
+        assert_eq!(page.page_index, 3);
+    }
+}
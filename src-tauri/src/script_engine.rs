@@ -0,0 +1,480 @@
+//! Script Engine Module
+//!
+//! Bulk document edits written as small Rhai scripts, e.g.
+//!
+//! ```text
+//! // Enlarge text smaller than 6pt
+//! modify(|l| l.type == "text" && l.fontSize < 6.0, |l| { l.fontSize = 8.0; l })
+//! ```
+//!
+//! Scripts only see the pages they are run on. Layers are maps in the
+//! project's JSON shape plus a `page` index; the API is:
+//! - `layers()` / `layers(filter)` — copies of matching layers; `filter` is
+//!   a closure or a query string such as `"type:text size:..6"` (see
+//!   `vortex_core::layer_query`)
+//! - `modify(filter, update)` — replace matching layers with `update(layer)`
+//! - `delete_layers(filter)` — remove matching layers
+//! - `set_property(id, name, value)` — set one property of one layer
+//! - `page_count()`, `add_page()`, `add_page(width, height)`
+//!
+//! There is no file, network or module access, and run time and memory
+//! are capped. Saved scripts live in the app data dir (`scripts.json`).
+
+use crate::models::{LayerObject, PageData};
+use rhai::{Array, Dynamic, Engine, EvalAltResult, FnPtr, Map, NativeCallContext, FLOAT, INT};
+use serde::{Deserialize, Serialize};
+use std::cell::RefCell;
+use std::fs;
+use std::path::PathBuf;
+use std::rc::Rc;
+use std::sync::{Arc, RwLock};
+use vortex_core::layer_query::LayerQuery;
+
+const SCRIPTS_FILE: &str = "scripts.json";
+
+/// Operations a script may run; stops runaway loops
+const MAX_OPERATIONS: u64 = 50_000_000;
+const MAX_STRING_SIZE: usize = 1024 * 1024;
+const MAX_COLLECTION_SIZE: usize = 100_000;
+/// Lines of `print` output kept
+const MAX_OUTPUT_LINES: usize = 1000;
+
+/// US Letter, for `add_page()` on an empty document
+const DEFAULT_PAGE_SIZE: (f32, f32) = (612.0, 792.0);
+
+/// A named script in the library
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct SavedScript {
+    pub name: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub description: Option<String>,
+    pub source: String,
+    /// Shipped with the app; cannot be changed or deleted
+    #[serde(default, skip_deserializing)]
+    pub builtin: bool,
+}
+
+/// Outcome of running a script
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ScriptResult {
+    pub pages: Vec<PageData>,
+    /// Layers modified or deleted
+    pub changed_layers: usize,
+    pub added_pages: usize,
+    /// Lines written with `print` and `debug`
+    pub output: Vec<String>,
+}
+
+#[derive(Debug, Default)]
+struct ScriptDocument {
+    pages: Vec<PageData>,
+    changed_layers: usize,
+    added_pages: usize,
+    output: Vec<String>,
+}
+
+type Shared = Rc<RefCell<ScriptDocument>>;
+type ScriptError = Box<EvalAltResult>;
+
+fn script_error(message: impl Into<String>) -> ScriptError {
+    message.into().into()
+}
+
+/// A layer as a script sees it
+fn layer_to_map(page_index: usize, layer: &LayerObject) -> Result<Dynamic, ScriptError> {
+    let mut map: Map = rhai::serde::to_dynamic(layer)?
+        .try_cast::<Map>()
+        .ok_or_else(|| script_error("Layer is not an object"))?;
+    map.insert("page".into(), Dynamic::from_int(page_index as INT));
+    Ok(map.into())
+}
+
+/// Read back a layer a script returned; `page` is dropped, the id kept
+fn map_to_layer(value: &Dynamic, id: &str) -> Result<LayerObject, ScriptError> {
+    // Through JSON: Rhai numbers are f64/i64 while layers use f32/i32
+    let mut json = serde_json::to_value(value).map_err(|e| script_error(e.to_string()))?;
+    let object = json
+        .as_object_mut()
+        .ok_or_else(|| script_error(format!("update must return the layer, got {}", value.type_name())))?;
+    object.remove("page");
+    object.insert("id".to_string(), id.into());
+    serde_json::from_value(json).map_err(|e| script_error(format!("Invalid layer {}: {}", id, e)))
+}
+
+/// Copies of every layer with its page index
+fn snapshot(doc: &Shared) -> Vec<(usize, LayerObject)> {
+    doc.borrow()
+        .pages
+        .iter()
+        .enumerate()
+        .flat_map(|(i, p)| p.layers.iter().map(move |l| (i, l.clone())))
+        .collect()
+}
+
+/// Layers `filter` accepts, as (page index, layer, script value)
+///
+/// No borrow is held while the filter runs, so it may call the API itself.
+fn matching(
+    doc: &Shared,
+    context: &NativeCallContext,
+    filter: Option<&FnPtr>,
+) -> Result<Vec<(usize, LayerObject, Dynamic)>, ScriptError> {
+    let mut matches = Vec::new();
+    for (page, layer) in snapshot(doc) {
+        let value = layer_to_map(page, &layer)?;
+        let keep = match filter {
+            Some(filter) => filter.call_within_context::<bool>(context, (value.clone(),))?,
+            None => true,
+        };
+        if keep {
+            matches.push((page, layer, value));
+        }
+    }
+    Ok(matches)
+}
+
+fn find_layer<'a>(pages: &'a mut [PageData], page: usize, id: &str) -> Option<&'a mut LayerObject> {
+    pages.get_mut(page)?.layers.iter_mut().find(|l| l.id == id)
+}
+
+fn add_page(doc: &Shared, width: FLOAT, height: FLOAT) -> Result<INT, ScriptError> {
+    if !(width > 0.0 && height > 0.0) {
+        return Err(script_error("Page size must be positive"));
+    }
+    let mut doc = doc.borrow_mut();
+    let page_index = doc.pages.len();
+    let mut page: PageData = serde_json::from_value(serde_json::json!({
+        "pageIndex": page_index, "width": width, "height": height, "layers": []
+    }))
+    .map_err(|e| script_error(e.to_string()))?;
+    page.page_index = page_index;
+    doc.pages.push(page);
+    doc.added_pages += 1;
+    Ok(page_index as INT)
+}
+
+fn build_engine(doc: &Shared) -> Engine {
+    let mut engine = Engine::new();
+    engine.set_max_operations(MAX_OPERATIONS);
+    engine.set_max_string_size(MAX_STRING_SIZE);
+    engine.set_max_array_size(MAX_COLLECTION_SIZE);
+    engine.set_max_map_size(MAX_COLLECTION_SIZE);
+    engine.set_module_resolver(rhai::module_resolvers::DummyModuleResolver::new());
+    engine.disable_symbol("eval");
+
+    let out = doc.clone();
+    engine.on_print(move |text| {
+        let mut doc = out.borrow_mut();
+        if doc.output.len() < MAX_OUTPUT_LINES {
+            doc.output.push(text.to_string());
+        }
+    });
+    let out = doc.clone();
+    engine.on_debug(move |text, _, position| {
+        let mut doc = out.borrow_mut();
+        if doc.output.len() < MAX_OUTPUT_LINES {
+            doc.output.push(format!("{:?}: {}", position, text));
+        }
+    });
+
+    let d = doc.clone();
+    engine.register_fn("layers", move |context: NativeCallContext| -> Result<Array, ScriptError> {
+        Ok(matching(&d, &context, None)?.into_iter().map(|(_, _, v)| v).collect())
+    });
+    let d = doc.clone();
+    engine.register_fn("layers", move |context: NativeCallContext, filter: FnPtr| -> Result<Array, ScriptError> {
+        Ok(matching(&d, &context, Some(&filter))?.into_iter().map(|(_, _, v)| v).collect())
+    });
+    let d = doc.clone();
+    engine.register_fn("layers", move |query: &str| -> Result<Array, ScriptError> {
+        let query = LayerQuery::parse(query).map_err(script_error)?;
+        snapshot(&d).into_iter().filter(|(_, l)| query.matches(l)).map(|(page, l)| layer_to_map(page, &l)).collect()
+    });
+
+    let d = doc.clone();
+    engine.register_fn(
+        "modify",
+        move |context: NativeCallContext, filter: FnPtr, update: FnPtr| -> Result<INT, ScriptError> {
+            let mut updates = Vec::new();
+            for (page, layer, value) in matching(&d, &context, Some(&filter))? {
+                let updated = map_to_layer(&update.call_within_context::<Dynamic>(&context, (value,))?, &layer.id)?;
+                if updated != layer {
+                    updates.push((page, updated));
+                }
+            }
+            let mut doc = d.borrow_mut();
+            let mut changed = 0;
+            for (page, updated) in updates {
+                if let Some(layer) = find_layer(&mut doc.pages, page, &updated.id) {
+                    *layer = updated;
+                    changed += 1;
+                }
+            }
+            doc.changed_layers += changed;
+            Ok(changed as INT)
+        },
+    );
+
+    let d = doc.clone();
+    engine.register_fn(
+        "delete_layers",
+        move |context: NativeCallContext, filter: FnPtr| -> Result<INT, ScriptError> {
+            let doomed = matching(&d, &context, Some(&filter))?;
+            let mut doc = d.borrow_mut();
+            for (page, layer, _) in &doomed {
+                if let Some(page) = doc.pages.get_mut(*page) {
+                    page.layers.retain(|l| l.id != layer.id);
+                }
+            }
+            doc.changed_layers += doomed.len();
+            Ok(doomed.len() as INT)
+        },
+    );
+
+    let d = doc.clone();
+    engine.register_fn(
+        "set_property",
+        move |id: &str, name: &str, value: Dynamic| -> Result<bool, ScriptError> {
+            if name == "id" || name == "page" {
+                return Err(script_error(format!("'{}' cannot be changed", name)));
+            }
+            let Some((page, layer)) = snapshot(&d).into_iter().find(|(_, l)| l.id == id) else {
+                return Ok(false);
+            };
+            let mut map = layer_to_map(page, &layer)?
+                .try_cast::<Map>()
+                .ok_or_else(|| script_error("Layer is not an object"))?;
+            map.insert(name.into(), value);
+            let updated = map_to_layer(&map.into(), id)?;
+            let mut doc = d.borrow_mut();
+            if updated != layer {
+                if let Some(layer) = find_layer(&mut doc.pages, page, id) {
+                    *layer = updated;
+                    doc.changed_layers += 1;
+                }
+            }
+            Ok(true)
+        },
+    );
+
+    let d = doc.clone();
+    engine.register_fn("page_count", move || d.borrow().pages.len() as INT);
+    let d = doc.clone();
+    engine.register_fn("add_page", move || -> Result<INT, ScriptError> {
+        let (width, height) = d
+            .borrow()
+            .pages
+            .last()
+            .map_or(DEFAULT_PAGE_SIZE, |p| (p.width, p.height));
+        add_page(&d, FLOAT::from(width), FLOAT::from(height))
+    });
+    let d = doc.clone();
+    engine.register_fn("add_page", move |width: FLOAT, height: FLOAT| add_page(&d, width, height));
+
+    engine
+}
+
+/// Run a script over `pages`
+pub fn run(source: &str, pages: Vec<PageData>) -> Result<ScriptResult, String> {
+    let doc: Shared = Rc::new(RefCell::new(ScriptDocument { pages, ..Default::default() }));
+    let engine = build_engine(&doc);
+    engine.run(source).map_err(|e| format!("Script failed: {}", e))?;
+    drop(engine);
+
+    let doc = Rc::try_unwrap(doc)
+        .map_err(|_| "Script state is still in use".to_string())?
+        .into_inner();
+    Ok(ScriptResult {
+        pages: doc.pages,
+        changed_layers: doc.changed_layers,
+        added_pages: doc.added_pages,
+        output: doc.output,
+    })
+}
+
+/// Check a script for syntax errors without running it
+pub fn check(source: &str) -> Result<(), String> {
+    let doc = Shared::default();
+    build_engine(&doc).compile(source).map(|_| ()).map_err(|e| e.to_string())
+}
+
+/// Scripts shipped with the app
+pub fn builtin_scripts() -> Vec<SavedScript> {
+    let script = |name: &str, description: &str, source: &str| SavedScript {
+        name: name.to_string(),
+        description: Some(description.to_string()),
+        source: source.to_string(),
+        builtin: true,
+    };
+    vec![
+        script(
+            "Enlarge tiny text",
+            "Set text smaller than 6pt to 8pt",
+            "modify(|l| l.type == \"text\" && l.fontSize < 6.0, |l| { l.fontSize = 8.0; l })",
+        ),
+        script(
+            "Raise footers",
+            "Move every footer layer up 10pt",
+            "modify(|l| l.role == \"footer\", |l| { l.bounds.y -= 10.0; l })",
+        ),
+        script(
+            "Remove hidden layers",
+            "Delete layers that are not visible",
+            "delete_layers(|l| !l.visible)",
+        ),
+    ]
+}
+
+#[derive(Debug, Default)]
+struct ScriptStore {
+    scripts: Vec<SavedScript>,
+    path: Option<PathBuf>,
+}
+
+lazy_static::lazy_static! {
+    static ref SCRIPT_STORE: Arc<RwLock<ScriptStore>> = Arc::new(RwLock::new(ScriptStore::default()));
+}
+
+/// Load saved scripts from `dir` and remember it for later saves
+pub fn init_scripts(dir: PathBuf) -> Result<(), String> {
+    fs::create_dir_all(&dir).map_err(|e| e.to_string())?;
+    let path = dir.join(SCRIPTS_FILE);
+    let scripts: Vec<SavedScript> = fs::read(&path)
+        .ok()
+        .and_then(|data| serde_json::from_slice(&data).ok())
+        .unwrap_or_default();
+
+    let mut store = SCRIPT_STORE.write().map_err(|e| e.to_string())?;
+    store.scripts = scripts;
+    store.path = Some(path);
+    Ok(())
+}
+
+fn persist(store: &ScriptStore) -> Result<(), String> {
+    let Some(path) = &store.path else {
+        return Ok(());
+    };
+    let data = serde_json::to_vec_pretty(&store.scripts).map_err(|e| e.to_string())?;
+    let tmp = path.with_extension("json.tmp");
+    fs::write(&tmp, data).map_err(|e| e.to_string())?;
+    fs::rename(&tmp, path).map_err(|e| e.to_string())
+}
+
+fn is_builtin_name(name: &str) -> bool {
+    builtin_scripts().iter().any(|s| s.name.eq_ignore_ascii_case(name))
+}
+
+/// Run a script over the given pages
+#[tauri::command]
+pub async fn run_script(source: String, pages: Vec<PageData>) -> Result<ScriptResult, String> {
+    tokio::task::spawn_blocking(move || run(&source, pages))
+        .await
+        .map_err(|e| format!("Script task failed: {}", e))?
+}
+
+/// List saved and built-in scripts (saved scripts first)
+#[tauri::command]
+pub fn list_scripts() -> Result<Vec<SavedScript>, String> {
+    let store = SCRIPT_STORE.read().map_err(|e| e.to_string())?;
+    let mut scripts = store.scripts.clone();
+    scripts.extend(builtin_scripts());
+    Ok(scripts)
+}
+
+/// Save a script, replacing any saved script with the same name
+#[tauri::command]
+pub fn save_script(script: SavedScript) -> Result<(), String> {
+    let name = script.name.trim();
+    if name.is_empty() {
+        return Err("Script name cannot be empty".to_string());
+    }
+    if is_builtin_name(name) {
+        return Err(format!("'{}' is a built-in script", name));
+    }
+    check(&script.source)?;
+    let script = SavedScript { name: name.to_string(), builtin: false, ..script };
+
+    let mut store = SCRIPT_STORE.write().map_err(|e| e.to_string())?;
+    match store.scripts.iter_mut().find(|s| s.name.eq_ignore_ascii_case(name)) {
+        Some(existing) => *existing = script,
+        None => store.scripts.push(script),
+    }
+    persist(&store)
+}
+
+/// Delete a saved script
+#[tauri::command]
+pub fn delete_script(name: String) -> Result<(), String> {
+    if is_builtin_name(&name) {
+        return Err(format!("'{}' is a built-in script", name));
+    }
+    let mut store = SCRIPT_STORE.write().map_err(|e| e.to_string())?;
+    let before = store.scripts.len();
+    store.scripts.retain(|s| !s.name.eq_ignore_ascii_case(&name));
+    if store.scripts.len() == before {
+        return Err(format!("Script '{}' not found", name));
+    }
+    persist(&store)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use vortex_core::test_util;
+
+    fn pages() -> Vec<PageData> {
+        let layer = |id: &str, size: f32, role: &str, y: f32| {
+            test_util::layer(id, "text")
+                .bounds(72.0, y, 200.0, 10.0)
+                .fields(serde_json::json!({ "content": id, "fontSize": size, "sourceType": "extracted", "role": role }))
+                .build()
+        };
+        vec![test_util::page(
+            0,
+            vec![layer("tiny", 5.0, "content", 100.0), layer("body", 11.0, "content", 200.0), layer("folio", 9.0, "footer", 760.0)],
+        )]
+    }
+
+    #[test]
+    fn test_builtin_scripts() {
+        let result = run(&builtin_scripts()[0].source, pages()).unwrap();
+        let sizes: Vec<f32> = result.pages[0].layers.iter().map(|l| l.font_size.unwrap()).collect();
+        assert_eq!(sizes, vec![8.0, 11.0, 9.0]);
+        assert_eq!(result.changed_layers, 1);
+
+        let result = run(&builtin_scripts()[1].source, pages()).unwrap();
+        assert_eq!(result.pages[0].layers[2].bounds.y, 750.0);
+        assert_eq!(result.pages[0].layers[0].bounds.y, 100.0);
+        for script in builtin_scripts() {
+            assert!(check(&script.source).is_ok(), "{}", script.name);
+        }
+    }
+
+    #[test]
+    fn test_script_api() {
+        let script = r##"
+            print(`${layers().len()} layers, ${layers("-role:footer").len()} outside the footer`);
+            set_property("body", "color", "#FF0000");
+            delete_layers(|l| l.role == "footer");
+            let page = add_page();
+            print(`page ${page} of ${page_count()}`);
+        "##;
+        let result = run(script, pages()).unwrap();
+        assert_eq!(result.output, vec!["3 layers, 2 outside the footer", "page 1 of 2"]);
+        assert_eq!(result.pages[0].layers.len(), 2);
+        assert_eq!(result.pages[0].layers[1].color.as_deref(), Some("#FF0000"));
+        assert_eq!((result.pages[1].width, result.pages[1].page_index), (612.0, 1));
+        assert_eq!((result.changed_layers, result.added_pages), (2, 1));
+    }
+
+    #[test]
+    fn test_script_sandbox() {
+        assert!(run("loop {}", pages()).unwrap_err().contains("Script failed"));
+        assert!(run(r#"eval("1")"#, pages()).is_err());
+        assert!(run(r#"import "fs" as fs;"#, pages()).is_err());
+        assert!(run(r#"set_property("body", "fontSize", "big")"#, pages()).is_err());
+        assert!(check("modify(|l| ").is_err());
+    }
+}
@@ -0,0 +1,254 @@
+//! Settings Module
+//!
+//! App-wide preferences persisted as `settings.json` in the app config dir:
+//! the pdfium library path, OCR defaults, image cache size, the default
+//! export preset and the printer calibration. Modules read the current values with [`current`] when they
+//! need them; changes made through [`update_settings`] are applied to the
+//! running image cache and PDF engine and announced with a
+//! `settings_changed` event.
+
+use crate::print_service::PrinterCalibration;
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::path::PathBuf;
+use std::sync::{Arc, RwLock};
+use tauri::{AppHandle, Emitter};
+use vortex_core::ocr_correction::OcrDictionary;
+
+const SETTINGS_FILE: &str = "settings.json";
+
+/// Smallest and largest accepted image cache, in MB
+const IMAGE_CACHE_RANGE_MB: (usize, usize) = (16, 16 * 1024);
+
+/// User preferences
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase", default)]
+pub struct AppSettings {
+    /// pdfium library file or directory, tried after `ROOK_PDFIUM_PATH`
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub pdfium_path: Option<PathBuf>,
+    /// Tesseract language code(s), e.g. "eng" or "eng+deu"
+    pub ocr_language: String,
+    /// Words below this confidence (0-1) are dropped
+    pub ocr_min_confidence: f32,
+    /// Page render resolution for OCR
+    pub ocr_render_dpi: u32,
+    /// Fix common misreadings in OCR output before layers are created
+    pub ocr_post_correction: bool,
+    /// App-wide OCR corrections; a project's own dictionary is applied on top
+    pub ocr_dictionary: OcrDictionary,
+    /// Image cache size, and the decoded image memory an import may use
+    /// before images load lazily, in MB
+    pub image_cache_mb: usize,
+    /// Images smaller than this many pixels on a side are skipped on import
+    pub min_image_size: u32,
+    /// Export preset used when an export does not name one
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub export_preset: Option<String>,
+    /// Front and back offsets applied to imposed sheets
+    pub printer_calibration: PrinterCalibration,
+}
+
+impl Default for AppSettings {
+    fn default() -> Self {
+        Self {
+            pdfium_path: None,
+            ocr_language: "eng".to_string(),
+            ocr_min_confidence: 0.5,
+            ocr_render_dpi: 150,
+            ocr_post_correction: true,
+            ocr_dictionary: OcrDictionary::default(),
+            image_cache_mb: 100,
+            min_image_size: 4,
+            export_preset: None,
+            printer_calibration: PrinterCalibration::default(),
+        }
+    }
+}
+
+impl AppSettings {
+    /// Image cache size in bytes
+    pub fn image_cache_bytes(&self) -> usize {
+        self.image_cache_mb.saturating_mul(1024 * 1024)
+    }
+
+    fn validate(&self) -> Result<(), String> {
+        let language_ok = self
+            .ocr_language
+            .split('+')
+            .all(|l| !l.is_empty() && l.chars().all(|c| c.is_ascii_alphanumeric() || c == '_'));
+        if !language_ok {
+            return Err(format!("Invalid OCR language '{}'", self.ocr_language));
+        }
+        if !(0.0..=1.0).contains(&self.ocr_min_confidence) {
+            return Err("OCR confidence must be between 0 and 1".to_string());
+        }
+        if !(72..=1200).contains(&self.ocr_render_dpi) {
+            return Err(format!("OCR resolution must be 72-1200 DPI, got {}", self.ocr_render_dpi));
+        }
+        let (min_mb, max_mb) = IMAGE_CACHE_RANGE_MB;
+        if !(min_mb..=max_mb).contains(&self.image_cache_mb) {
+            return Err(format!("Image cache must be {}-{} MB, got {}", min_mb, max_mb, self.image_cache_mb));
+        }
+        if self.min_image_size == 0 {
+            return Err("Minimum image size must be at least 1 pixel".to_string());
+        }
+        self.printer_calibration.validate()
+    }
+}
+
+#[derive(Debug, Default)]
+struct SettingsStore {
+    settings: AppSettings,
+    path: Option<PathBuf>,
+}
+
+lazy_static::lazy_static! {
+    static ref SETTINGS_STORE: Arc<RwLock<SettingsStore>> = Arc::new(RwLock::new(SettingsStore::default()));
+}
+
+/// Load settings from `dir` and remember it for later saves
+///
+/// A missing or unreadable file leaves the defaults in place; fields the
+/// file lacks take their default values.
+pub fn init_settings(dir: PathBuf) -> Result<(), String> {
+    fs::create_dir_all(&dir).map_err(|e| e.to_string())?;
+    let path = dir.join(SETTINGS_FILE);
+    let settings = match fs::read(&path) {
+        Ok(data) => serde_json::from_slice::<AppSettings>(&data)
+            .ok()
+            .filter(|s| s.validate().is_ok())
+            .unwrap_or_else(|| {
+                tracing::warn!(path = %path.display(), "settings unreadable, using defaults");
+                AppSettings::default()
+            }),
+        Err(_) => AppSettings::default(),
+    };
+
+    let mut store = SETTINGS_STORE.write().map_err(|e| e.to_string())?;
+    store.settings = settings;
+    store.path = Some(path);
+    Ok(())
+}
+
+/// The settings in effect
+pub fn current() -> AppSettings {
+    SETTINGS_STORE.read().map(|s| s.settings.clone()).unwrap_or_default()
+}
+
+fn persist(store: &SettingsStore) -> Result<(), String> {
+    let Some(path) = &store.path else {
+        return Ok(());
+    };
+    let data = serde_json::to_vec_pretty(&store.settings).map_err(|e| e.to_string())?;
+    let tmp = path.with_extension("json.tmp");
+    fs::write(&tmp, data).map_err(|e| e.to_string())?;
+    fs::rename(&tmp, path).map_err(|e| e.to_string())
+}
+
+/// Apply a JSON merge patch to settings
+///
+/// Keys in `patch` replace the current values; a `null` resets a key to its
+/// default. Unknown keys are rejected so typos do not go unnoticed.
+pub fn merge_patch(settings: &AppSettings, patch: serde_json::Value) -> Result<AppSettings, String> {
+    let serde_json::Value::Object(patch) = patch else {
+        return Err("Settings update must be an object".to_string());
+    };
+    let serde_json::Value::Object(mut merged) = serde_json::to_value(settings).map_err(|e| e.to_string())? else {
+        return Err("Settings are not an object".to_string());
+    };
+    // Optional fields are left out when unset, so fill them to list every key
+    let all_fields = AppSettings {
+        pdfium_path: Some(PathBuf::new()),
+        export_preset: Some(String::new()),
+        ..AppSettings::default()
+    };
+    let known = serde_json::to_value(all_fields).map_err(|e| e.to_string())?;
+    for (key, value) in patch {
+        if known.get(&key).is_none() {
+            return Err(format!("Unknown setting '{}'", key));
+        }
+        if value.is_null() {
+            merged.remove(&key);
+        } else {
+            merged.insert(key, value);
+        }
+    }
+    let updated: AppSettings =
+        serde_json::from_value(serde_json::Value::Object(merged)).map_err(|e| format!("Invalid settings: {}", e))?;
+    updated.validate()?;
+    Ok(updated)
+}
+
+/// Change settings with `edit`, save them and apply what changed
+pub fn update_with(edit: impl FnOnce(&AppSettings) -> Result<AppSettings, String>) -> Result<AppSettings, String> {
+    let (previous, updated) = {
+        let mut store = SETTINGS_STORE.write().map_err(|e| e.to_string())?;
+        let updated = edit(&store.settings)?;
+        let previous = std::mem::replace(&mut store.settings, updated.clone());
+        if let Err(e) = persist(&store) {
+            store.settings = previous;
+            return Err(format!("Failed to save settings: {}", e));
+        }
+        (previous, updated)
+    };
+
+    if previous.image_cache_mb != updated.image_cache_mb {
+        crate::image_handler::set_cache_budget(updated.image_cache_bytes());
+    }
+    if previous.pdfium_path != updated.pdfium_path {
+        crate::pdf_engine::reset_status();
+    }
+    Ok(updated)
+}
+
+/// Current settings
+#[tauri::command]
+pub fn get_settings() -> Result<AppSettings, String> {
+    Ok(current())
+}
+
+/// Update settings with a partial object; `null` values reset to defaults
+#[tauri::command]
+pub fn update_settings(patch: serde_json::Value, app_handle: AppHandle) -> Result<AppSettings, String> {
+    let settings = update_with(|settings| merge_patch(settings, patch))?;
+    let _ = app_handle.emit("settings_changed", &settings);
+    Ok(settings)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    #[test]
+    fn test_merge_patch() {
+        let settings = AppSettings { ocr_language: "deu".to_string(), ..AppSettings::default() };
+        let updated = merge_patch(&settings, json!({ "imageCacheMb": 256, "pdfiumPath": "/opt/pdfium" })).unwrap();
+        assert_eq!(updated.image_cache_mb, 256);
+        assert_eq!(updated.ocr_language, "deu");
+        assert_eq!(updated.pdfium_path, Some(PathBuf::from("/opt/pdfium")));
+
+        // null resets to the default
+        let reset = merge_patch(&updated, json!({ "ocrLanguage": null, "pdfiumPath": null })).unwrap();
+        assert_eq!(reset.ocr_language, "eng");
+        assert_eq!(reset.pdfium_path, None);
+
+        assert!(merge_patch(&settings, json!({ "ocrLanguge": "fra" })).unwrap_err().contains("Unknown"));
+        assert!(merge_patch(&settings, json!({ "imageCacheMb": 1 })).is_err());
+        assert!(merge_patch(&settings, json!({ "ocrLanguage": "eng; rm" })).is_err());
+        assert!(merge_patch(&settings, json!({ "ocrLanguage": "eng+chi_sim" })).is_ok());
+
+        let calibrated = merge_patch(&settings, json!({ "printerCalibration": { "back": { "xMm": 1.5 } } })).unwrap();
+        assert_eq!((calibrated.printer_calibration.back.x_mm, calibrated.printer_calibration.back.y_mm), (1.5, 0.0));
+        assert!(merge_patch(&settings, json!({ "printerCalibration": { "front": { "yMm": 40 } } })).is_err());
+    }
+
+    #[test]
+    fn test_partial_file_takes_defaults() {
+        let settings: AppSettings = serde_json::from_value(json!({ "ocrLanguage": "fra" })).unwrap();
+        assert_eq!(settings.ocr_language, "fra");
+        assert_eq!(settings.image_cache_mb, AppSettings::default().image_cache_mb);
+        assert_eq!(settings.image_cache_bytes(), 100 * 1024 * 1024);
+    }
+}
@@ -0,0 +1,387 @@
+//! Snapshot Module
+//!
+//! Named version-history checkpoints for projects. Each project gets one
+//! deflate-compressed archive in the app data dir holding:
+//! - `pages/{sha256}.json` — content-addressed page blobs, shared between
+//!   snapshots so each checkpoint only stores the pages that changed (stored
+//!   without their index, so inserting or moving pages keeps them shared)
+//! - `snapshots/{id}.json` — manifest with the project minus its pages and
+//!   the ordered list of page hashes
+//!
+//! Archives are append-only; restoring never modifies history. Each snapshot
+//! is appended to a copy that replaces the archive once finished, one writer
+//! per archive at a time.
+
+use crate::models::{BookProjectData, PageData};
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use std::collections::{HashMap, HashSet};
+use std::fs::{self, File, OpenOptions};
+use std::io::{Read, Write};
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex, RwLock};
+
+const PAGE_PREFIX: &str = "pages/";
+const SNAPSHOT_PREFIX: &str = "snapshots/";
+
+lazy_static::lazy_static! {
+    static ref SNAPSHOT_DIR: Arc<RwLock<Option<PathBuf>>> = Arc::new(RwLock::new(None));
+    /// Serializes writers per archive path
+    static ref ARCHIVE_LOCKS: Mutex<HashMap<PathBuf, Arc<Mutex<()>>>> = Mutex::new(HashMap::new());
+}
+
+/// Summary of a stored snapshot
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct SnapshotInfo {
+    pub id: String,
+    pub name: String,
+    pub created_at: String,
+    pub page_count: usize,
+    /// Pages newly stored by this snapshot (the rest are shared)
+    pub new_pages: usize,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct SnapshotManifest {
+    info: SnapshotInfo,
+    /// Project with `document.pages` emptied
+    project: BookProjectData,
+    page_hashes: Vec<String>,
+}
+
+/// Set the directory snapshot archives are stored in
+pub fn init_snapshots(dir: PathBuf) -> Result<(), String> {
+    fs::create_dir_all(&dir).map_err(|e| e.to_string())?;
+    *SNAPSHOT_DIR.write().map_err(|e| e.to_string())? = Some(dir);
+    Ok(())
+}
+
+/// Archive path for a project, keyed by a hash of its file path
+fn archive_path(project_path: &str) -> Result<PathBuf, String> {
+    let dir = SNAPSHOT_DIR
+        .read()
+        .map_err(|e| e.to_string())?
+        .clone()
+        .ok_or("Snapshot storage is not initialized")?;
+    let key = hex_digest(project_path.as_bytes());
+    Ok(dir.join(format!("{}.zip", &key[..16])))
+}
+
+fn hex_digest(data: &[u8]) -> String {
+    Sha256::digest(data).iter().map(|b| format!("{:02x}", b)).collect()
+}
+
+fn new_snapshot_id() -> String {
+    let nanos = std::time::SystemTime::now()
+        .duration_since(std::time::UNIX_EPOCH)
+        .map(|d| d.as_nanos())
+        .unwrap_or(0);
+    format!("snap-{:x}", nanos)
+}
+
+/// Names of all entries in an archive (empty if it does not exist yet)
+fn entry_names(archive: &Path) -> Result<HashSet<String>, String> {
+    if !archive.exists() {
+        return Ok(HashSet::new());
+    }
+    let zip = zip::ZipArchive::new(File::open(archive).map_err(|e| e.to_string())?)
+        .map_err(|e| e.to_string())?;
+    Ok(zip.file_names().map(str::to_string).collect())
+}
+
+fn archive_lock(archive: &Path) -> Result<Arc<Mutex<()>>, String> {
+    let mut locks = ARCHIVE_LOCKS.lock().map_err(|e| e.to_string())?;
+    Ok(locks.entry(archive.to_path_buf()).or_default().clone())
+}
+
+/// Store a snapshot of `project` in `archive`
+pub fn write_snapshot(
+    archive: &Path,
+    name: &str,
+    project: &BookProjectData,
+) -> Result<SnapshotInfo, String> {
+    let lock = archive_lock(archive)?;
+    let _guard = lock.lock().map_err(|e| e.to_string())?;
+
+    // Append to a copy so a failed or interrupted write leaves the archive as it was
+    let temp = archive.with_extension("zip.tmp");
+    let result = write_snapshot_to(archive, &temp, name, project);
+    if result.is_err() {
+        let _ = fs::remove_file(&temp);
+    }
+    result
+}
+
+fn write_snapshot_to(
+    archive: &Path,
+    temp: &Path,
+    name: &str,
+    project: &BookProjectData,
+) -> Result<SnapshotInfo, String> {
+    let existing = entry_names(archive)?;
+    if existing.is_empty() {
+        let _ = fs::remove_file(temp);
+    } else {
+        fs::copy(archive, temp).map_err(|e| e.to_string())?;
+    }
+
+    let file = OpenOptions::new()
+        .read(true)
+        .write(true)
+        .create(true)
+        .truncate(false)
+        .open(temp)
+        .map_err(|e| e.to_string())?;
+    let mut writer = if existing.is_empty() {
+        zip::ZipWriter::new(file)
+    } else {
+        zip::ZipWriter::new_append(file).map_err(|e| e.to_string())?
+    };
+    let options = zip::write::SimpleFileOptions::default()
+        .compression_method(zip::CompressionMethod::Deflated);
+
+    let mut page_hashes = Vec::with_capacity(project.document.pages.len());
+    let mut written = HashSet::new();
+    for page in &project.document.pages {
+        // The index comes from the manifest order on restore
+        let json = serde_json::to_vec(&PageData { page_index: 0, ..page.clone() }).map_err(|e| e.to_string())?;
+        let hash = hex_digest(&json);
+        let entry = format!("{}{}.json", PAGE_PREFIX, hash);
+        if !existing.contains(&entry) && written.insert(entry.clone()) {
+            writer.start_file(entry, options).map_err(|e| e.to_string())?;
+            writer.write_all(&json).map_err(|e| e.to_string())?;
+        }
+        page_hashes.push(hash);
+    }
+
+    let info = SnapshotInfo {
+        id: new_snapshot_id(),
+        name: name.to_string(),
+        created_at: crate::models::iso8601_now(),
+        page_count: page_hashes.len(),
+        new_pages: written.len(),
+    };
+    let mut stripped = project.clone();
+    stripped.document.pages.clear();
+    let manifest = SnapshotManifest {
+        info: info.clone(),
+        project: stripped,
+        page_hashes,
+    };
+
+    writer
+        .start_file(format!("{}{}.json", SNAPSHOT_PREFIX, info.id), options)
+        .map_err(|e| e.to_string())?;
+    writer
+        .write_all(&serde_json::to_vec(&manifest).map_err(|e| e.to_string())?)
+        .map_err(|e| e.to_string())?;
+    writer
+        .finish()
+        .and_then(|file| Ok(file.sync_all()?))
+        .map_err(|e| e.to_string())?;
+    fs::rename(temp, archive).map_err(|e| e.to_string())?;
+
+    Ok(info)
+}
+
+fn read_entry<R: Read + std::io::Seek>(
+    zip: &mut zip::ZipArchive<R>,
+    name: &str,
+) -> Result<Vec<u8>, String> {
+    let mut entry = zip.by_name(name).map_err(|e| format!("{}: {}", name, e))?;
+    let mut data = Vec::with_capacity(entry.size() as usize);
+    entry.read_to_end(&mut data).map_err(|e| e.to_string())?;
+    Ok(data)
+}
+
+fn read_manifest<R: Read + std::io::Seek>(
+    zip: &mut zip::ZipArchive<R>,
+    id: &str,
+) -> Result<SnapshotManifest, String> {
+    let data = read_entry(zip, &format!("{}{}.json", SNAPSHOT_PREFIX, id))
+        .map_err(|_| format!("Snapshot {} not found", id))?;
+    serde_json::from_slice(&data).map_err(|e| e.to_string())
+}
+
+/// List snapshots in an archive, oldest first
+pub fn read_snapshots(archive: &Path) -> Result<Vec<SnapshotInfo>, String> {
+    let ids: Vec<String> = entry_names(archive)?
+        .into_iter()
+        .filter_map(|n| {
+            n.strip_prefix(SNAPSHOT_PREFIX)
+                .and_then(|n| n.strip_suffix(".json"))
+                .map(str::to_string)
+        })
+        .collect();
+    if ids.is_empty() {
+        return Ok(Vec::new());
+    }
+
+    let mut zip = zip::ZipArchive::new(File::open(archive).map_err(|e| e.to_string())?)
+        .map_err(|e| e.to_string())?;
+    let mut infos = ids
+        .iter()
+        .map(|id| read_manifest(&mut zip, id).map(|m| m.info))
+        .collect::<Result<Vec<_>, _>>()?;
+    infos.sort_by(|a, b| a.created_at.cmp(&b.created_at).then_with(|| a.id.cmp(&b.id)));
+    Ok(infos)
+}
+
+/// Rebuild the project stored under snapshot `id`
+pub fn read_snapshot(archive: &Path, id: &str) -> Result<BookProjectData, String> {
+    let mut zip = zip::ZipArchive::new(
+        File::open(archive).map_err(|_| "No snapshots for this project".to_string())?,
+    )
+    .map_err(|e| e.to_string())?;
+    let manifest = read_manifest(&mut zip, id)?;
+
+    let mut project = manifest.project;
+    project.document.pages = manifest
+        .page_hashes
+        .iter()
+        .enumerate()
+        .map(|(page_index, hash)| {
+            let data = read_entry(&mut zip, &format!("{}{}.json", PAGE_PREFIX, hash))?;
+            let page = serde_json::from_slice::<PageData>(&data).map_err(|e| e.to_string())?;
+            Ok(PageData { page_index, ..page })
+        })
+        .collect::<Result<_, String>>()?;
+    Ok(project)
+}
+
+/// Checkpoint the current project under a name
+#[tauri::command]
+pub async fn create_snapshot(
+    project_path: String,
+    name: String,
+    project: BookProjectData,
+) -> Result<SnapshotInfo, String> {
+    let archive = archive_path(&project_path)?;
+    tokio::task::spawn_blocking(move || write_snapshot(&archive, &name, &project))
+        .await
+        .map_err(|e| format!("Snapshot task failed: {}", e))?
+}
+
+/// List a project's snapshots, oldest first
+#[tauri::command]
+pub async fn list_snapshots(project_path: String) -> Result<Vec<SnapshotInfo>, String> {
+    let archive = archive_path(&project_path)?;
+    tokio::task::spawn_blocking(move || read_snapshots(&archive))
+        .await
+        .map_err(|e| format!("Snapshot task failed: {}", e))?
+}
+
+/// Load the project as it was at a snapshot
+#[tauri::command]
+pub async fn restore_snapshot(
+    project_path: String,
+    snapshot_id: String,
+) -> Result<BookProjectData, String> {
+    let archive = archive_path(&project_path)?;
+    tokio::task::spawn_blocking(move || read_snapshot(&archive, &snapshot_id))
+        .await
+        .map_err(|e| format!("Snapshot task failed: {}", e))?
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn page(index: usize, width: f32) -> PageData {
+        PageData {
+            page_index: index,
+            width,
+            height: 792.0,
+            dpi: None,
+            layers: Vec::new(),
+            metadata: None,
+            background: None,
+        }
+    }
+
+    fn temp_archive(name: &str) -> PathBuf {
+        let path = std::env::temp_dir().join(format!(
+            "rook-snapshot-{}-{}.zip",
+            name,
+            std::process::id()
+        ));
+        let _ = fs::remove_file(&path);
+        path
+    }
+
+    #[test]
+    fn test_snapshots_share_unchanged_pages() {
+        let archive = temp_archive("share");
+        let mut project = BookProjectData::default();
+        project.document.pages = vec![page(0, 612.0), page(1, 600.0)];
+
+        let first = write_snapshot(&archive, "Draft", &project).unwrap();
+        assert_eq!(first.new_pages, 2);
+
+        project.document.pages[1].width = 500.0;
+        project.metadata.title = "Revised".to_string();
+        let second = write_snapshot(&archive, "Revised", &project).unwrap();
+        assert_eq!(second.new_pages, 1);
+
+        let list = read_snapshots(&archive).unwrap();
+        assert_eq!(list.iter().map(|s| s.name.as_str()).collect::<Vec<_>>(), vec!["Draft", "Revised"]);
+
+        let restored = read_snapshot(&archive, &first.id).unwrap();
+        assert_eq!(restored.document.pages[1].width, 600.0);
+        assert_ne!(restored.metadata.title, "Revised");
+        let latest = read_snapshot(&archive, &second.id).unwrap();
+        assert_eq!(latest, project);
+
+        let _ = fs::remove_file(&archive);
+    }
+
+    #[test]
+    fn test_inserted_pages_keep_the_rest_shared() {
+        let archive = temp_archive("insert");
+        let mut project = BookProjectData::default();
+        project.document.pages = vec![page(0, 612.0), page(1, 500.0)];
+        write_snapshot(&archive, "Draft", &project).unwrap();
+
+        // A new first page shifts every index but only adds one blob
+        project.document.pages.insert(0, page(0, 400.0));
+        for (i, page) in project.document.pages.iter_mut().enumerate() {
+            page.page_index = i;
+        }
+        let second = write_snapshot(&archive, "Inserted", &project).unwrap();
+        assert_eq!(second.new_pages, 1);
+        assert_eq!(read_snapshot(&archive, &second.id).unwrap(), project);
+
+        let _ = fs::remove_file(&archive);
+    }
+
+    #[test]
+    fn test_concurrent_snapshots_are_all_kept() {
+        let archive = temp_archive("concurrent");
+        let threads: Vec<_> = (0..8)
+            .map(|i| {
+                let archive = archive.clone();
+                std::thread::spawn(move || {
+                    let mut project = BookProjectData::default();
+                    project.document.pages = vec![page(0, 100.0 + i as f32)];
+                    write_snapshot(&archive, &format!("Snapshot {}", i), &project).unwrap()
+                })
+            })
+            .collect();
+        let written: HashSet<String> = threads.into_iter().map(|t| t.join().unwrap().id).collect();
+
+        let listed: HashSet<String> = read_snapshots(&archive).unwrap().into_iter().map(|s| s.id).collect();
+        assert_eq!(listed, written);
+        assert!(!archive.with_extension("zip.tmp").exists());
+
+        let _ = fs::remove_file(&archive);
+    }
+
+    #[test]
+    fn test_missing_snapshot() {
+        let archive = temp_archive("missing");
+        assert!(read_snapshots(&archive).unwrap().is_empty());
+        assert!(read_snapshot(&archive, "snap-0").is_err());
+    }
+}
@@ -0,0 +1,382 @@
+//! Source Watch Module
+//!
+//! Notices when the file a project was imported from changes on disk (e.g.
+//! re-exported from Word) and merges a fresh import into the project.
+//!
+//! The merge is three-way: the pages as first imported, the pages as edited,
+//! and the re-parsed pages. Layers the user left alone take the new source
+//! version; edited, added and deleted layers stay as the user left them.
+//! Layers are matched by id, or by type and bounds when ids shifted (the
+//! source changed there, or the project predates content-derived ids, see
+//! `vortex_core::layer_ids`).
+//!
+//! A single page can also be re-parsed on request, replacing its extracted
+//! layers when editing has mangled it.
+
+use crate::document_parser::{self, ImportOptions};
+use crate::models::{Bounds, LayerObject, PageData, PageMetadata, SourceType};
+use lazy_static::lazy_static;
+use notify::{Config, Event, RecommendedWatcher, RecursiveMode, Watcher};
+use serde::{Deserialize, Serialize};
+use std::collections::HashSet;
+use std::path::PathBuf;
+use std::sync::mpsc::{channel, Receiver, RecvTimeoutError};
+use std::sync::Mutex;
+use std::time::Duration;
+use tauri::{AppHandle, Emitter};
+
+/// Quiet period after the last change before `source_changed` is emitted;
+/// saving often writes a file several times in a row
+const DEBOUNCE: Duration = Duration::from_millis(750);
+
+/// Bounds within this many points count as the same position
+const BOUNDS_TOLERANCE: f32 = 1.0;
+
+lazy_static! {
+    /// The watcher for the open project's source; dropping it ends the watch
+    static ref SOURCE_WATCHER: Mutex<Option<RecommendedWatcher>> = Mutex::new(None);
+}
+
+/// Result of merging a re-import into the project
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ReimportMerge {
+    pub pages: Vec<PageData>,
+    /// Layers replaced by their new source version
+    pub updated: usize,
+    /// Layers new in the source
+    pub added: usize,
+    /// Layers gone from the source
+    pub removed: usize,
+    /// Edited layers kept although the source changed or dropped them
+    pub kept_edits: usize,
+}
+
+/// Wait for a burst of events to settle; false once the sender is gone
+fn debounce(rx: &Receiver<()>, quiet: Duration) -> bool {
+    if rx.recv().is_err() {
+        return false;
+    }
+    loop {
+        match rx.recv_timeout(quiet) {
+            Ok(()) => {}
+            Err(RecvTimeoutError::Timeout) => return true,
+            Err(RecvTimeoutError::Disconnected) => return false,
+        }
+    }
+}
+
+/// Watch a source file and emit `source_changed` (with its path) after it
+/// changes; replaces any previous watch
+#[tauri::command]
+pub fn watch_source_file(file_path: String, app_handle: AppHandle) -> Result<(), String> {
+    let path = PathBuf::from(&file_path);
+    let canonical = path.canonicalize().map_err(|e| format!("Cannot watch {}: {}", file_path, e))?;
+    let parent = canonical.parent().ok_or_else(|| format!("Cannot watch {}", file_path))?.to_path_buf();
+
+    // Watch the directory: editors often save by replacing the file
+    let (tx, rx) = channel();
+    let target = canonical.clone();
+    let mut watcher = RecommendedWatcher::new(
+        move |res: Result<Event, notify::Error>| {
+            if let Ok(event) = res {
+                if !event.kind.is_access() && event.paths.iter().any(|p| p == &target || p.file_name() == target.file_name()) {
+                    let _ = tx.send(());
+                }
+            }
+        },
+        Config::default(),
+    )
+    .map_err(|e| e.to_string())?;
+    watcher.watch(&parent, RecursiveMode::NonRecursive).map_err(|e| e.to_string())?;
+
+    std::thread::spawn(move || {
+        while debounce(&rx, DEBOUNCE) {
+            if canonical.exists() {
+                tracing::info!(path = %file_path, "source file changed");
+                let _ = app_handle.emit("source_changed", serde_json::json!({ "filePath": file_path }));
+            }
+        }
+    });
+
+    *SOURCE_WATCHER.lock().map_err(|e| e.to_string())? = Some(watcher);
+    Ok(())
+}
+
+/// Stop watching the source file
+#[tauri::command]
+pub fn unwatch_source_file() -> Result<(), String> {
+    SOURCE_WATCHER.lock().map_err(|e| e.to_string())?.take();
+    Ok(())
+}
+
+/// Re-import a source file and merge it into the edited pages
+///
+/// `original_pages` are the pages as first imported, `pages` as edited now.
+#[tauri::command]
+pub async fn reimport_and_merge(
+    file_path: String,
+    file_type: String,
+    original_pages: Vec<PageData>,
+    pages: Vec<PageData>,
+    options: Option<ImportOptions>,
+    app_handle: AppHandle,
+) -> Result<ReimportMerge, String> {
+    let response = document_parser::import_document(file_path, file_type, options, app_handle).await?;
+    let fresh = match response.data {
+        Some(data) if response.success => data.pages,
+        _ => return Err(response.message),
+    };
+    Ok(merge_reimport(&original_pages, &pages, fresh))
+}
+
+/// Re-parse the source page `page` came from and replace its extracted
+/// layers; layers added by hand or copied in stay. Returns the new page.
+#[tauri::command]
+pub async fn reimport_page(
+    file_path: String,
+    file_type: String,
+    page: PageData,
+    options: Option<ImportOptions>,
+    app_handle: AppHandle,
+) -> Result<PageData, String> {
+    let index = source_index(&page);
+    let fresh =
+        document_parser::import_source_page(file_path, file_type, index, options.unwrap_or_default(), app_handle)
+            .await?;
+    Ok(replace_extracted_layers(page, fresh))
+}
+
+/// `page` with its extracted layers swapped for those of `fresh`
+///
+/// Fresh layers go below the kept ones, as extracted content sat below
+/// additions before. A fresh id taken by a kept layer gets a suffix.
+fn replace_extracted_layers(page: PageData, fresh: PageData) -> PageData {
+    let mut kept: Vec<LayerObject> =
+        page.layers.into_iter().filter(|l| l.source_type != SourceType::Extracted).collect();
+    kept.sort_by_key(|l| l.z_index);
+    let mut layers: Vec<LayerObject> = fresh.layers;
+    layers.sort_by_key(|l| l.z_index);
+    for layer in &mut layers {
+        if kept.iter().any(|k| k.id == layer.id) {
+            layer.id.push_str("-reimported");
+        }
+    }
+    layers.extend(kept);
+    for (z, layer) in layers.iter_mut().enumerate() {
+        layer.z_index = z as i32;
+    }
+
+    let metadata = match (page.metadata, fresh.metadata) {
+        // Guides and custom properties belong to the project, the boxes to the source
+        (Some(old), Some(new)) => Some(PageMetadata { guides: old.guides, custom: old.custom, ..new }),
+        (old, new) => new.or(old),
+    };
+    PageData { layers, metadata, ..page }
+}
+
+/// Index of the source page a page came from
+fn source_index(page: &PageData) -> usize {
+    page.metadata.as_ref().and_then(|m| m.original_page_index).unwrap_or(page.page_index)
+}
+
+fn same_position(a: &Bounds, b: &Bounds) -> bool {
+    (a.x - b.x).abs() <= BOUNDS_TOLERANCE
+        && (a.y - b.y).abs() <= BOUNDS_TOLERANCE
+        && (a.width - b.width).abs() <= BOUNDS_TOLERANCE
+        && (a.height - b.height).abs() <= BOUNDS_TOLERANCE
+}
+
+/// Three-way merge of one page's layers
+fn merge_layers(base: &[LayerObject], ours: &[LayerObject], theirs: &[LayerObject], merge: &mut ReimportMerge) -> Vec<LayerObject> {
+    let mut layers = Vec::with_capacity(theirs.len());
+    let mut matched: HashSet<&str> = HashSet::new();
+
+    for fresh in theirs {
+        let original = base.iter().find(|b| b.id == fresh.id && !matched.contains(b.id.as_str())).or_else(|| {
+            base.iter().find(|b| {
+                !matched.contains(b.id.as_str()) && b.layer_type == fresh.layer_type && same_position(&b.bounds, &fresh.bounds)
+            })
+        });
+        let Some(original) = original else {
+            merge.added += 1;
+            layers.push(fresh.clone());
+            continue;
+        };
+        matched.insert(original.id.as_str());
+        match ours.iter().find(|l| l.id == original.id) {
+            // Deleted by the user
+            None => {}
+            Some(edited) if edited == original => {
+                if fresh != original {
+                    merge.updated += 1;
+                }
+                layers.push(fresh.clone());
+            }
+            Some(edited) => {
+                if fresh != original {
+                    merge.kept_edits += 1;
+                }
+                let mut edited = edited.clone();
+                // The import refilled the image cache under the fresh id
+                if edited.image_url == original.image_url {
+                    edited.image_url.clone_from(&fresh.image_url);
+                }
+                layers.push(edited);
+            }
+        }
+    }
+
+    for original in base.iter().filter(|b| !matched.contains(b.id.as_str())) {
+        match ours.iter().find(|l| l.id == original.id) {
+            Some(edited) if edited != original => {
+                merge.kept_edits += 1;
+                layers.push(edited.clone());
+            }
+            Some(_) => merge.removed += 1,
+            None => {}
+        }
+    }
+
+    // Layers the user added
+    layers.extend(ours.iter().filter(|l| !base.iter().any(|b| b.id == l.id)).cloned());
+    layers.sort_by_key(|l| l.z_index);
+    layers
+}
+
+/// Merge freshly imported pages into edited pages
+///
+/// Pages keep the user's order; pages new in the source are appended, and
+/// pages gone from the source are dropped unless they were edited.
+pub fn merge_reimport(original: &[PageData], edited: &[PageData], fresh: Vec<PageData>) -> ReimportMerge {
+    let mut merge = ReimportMerge::default();
+    let find = |pages: &[PageData], index: usize| pages.iter().position(|p| source_index(p) == index);
+    let mut used = vec![false; fresh.len()];
+    let mut pages = Vec::with_capacity(edited.len().max(fresh.len()));
+
+    for page in edited {
+        let index = source_index(page);
+        let Some(base) = find(original, index).map(|i| &original[i]) else {
+            // Added by the user
+            pages.push(page.clone());
+            continue;
+        };
+        match find(&fresh, index) {
+            Some(i) => {
+                used[i] = true;
+                let layers = merge_layers(&base.layers, &page.layers, &fresh[i].layers, &mut merge);
+                pages.push(PageData { layers, ..fresh[i].clone() });
+            }
+            None if page.layers != base.layers => {
+                merge.kept_edits += 1;
+                pages.push(page.clone());
+            }
+            None => merge.removed += base.layers.len(),
+        }
+    }
+
+    // Source pages the user had not deleted, or that are new
+    for (i, page) in fresh.into_iter().enumerate() {
+        if !used[i] && find(original, source_index(&page)).is_none() {
+            merge.added += page.layers.len();
+            pages.push(page);
+        }
+    }
+    for (i, page) in pages.iter_mut().enumerate() {
+        page.page_index = i;
+    }
+    merge.pages = pages;
+    merge
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::sync::mpsc::Sender;
+    use vortex_core::test_util;
+
+    fn layer(id: &str, content: &str, y: f32) -> LayerObject {
+        test_util::layer(id, "text")
+            .bounds(72.0, y, 200.0, 14.0)
+            .z(y as i32)
+            .fields(serde_json::json!({ "content": content, "sourceType": "extracted" }))
+            .build()
+    }
+
+    fn page(index: usize, layers: Vec<LayerObject>) -> PageData {
+        serde_json::from_value(serde_json::json!({
+            "pageIndex": index, "width": 612, "height": 792, "layers": layers,
+            "metadata": { "originalPageIndex": index }
+        }))
+        .unwrap()
+    }
+
+    #[test]
+    fn test_merge_keeps_edits() {
+        let original = vec![page(0, vec![layer("a", "Title", 10.0), layer("b", "Body", 30.0), layer("c", "Old", 50.0)])];
+        let mut edited = original.clone();
+        edited[0].layers[0].content = Some("My title".into());
+        edited[0].layers.push(layer("note", "Added by hand", 70.0));
+        // Body re-exported with new text; its id shifted but it sits in the same place
+        let fresh = vec![
+            page(0, vec![layer("a", "Title v2", 10.0), layer("b2", "Body v2", 30.0), layer("d", "New", 90.0)]),
+            page(1, vec![layer("e", "Appendix", 10.0)]),
+        ];
+
+        let merge = merge_reimport(&original, &edited, fresh);
+        let contents: Vec<Vec<&str>> = merge
+            .pages
+            .iter()
+            .map(|p| p.layers.iter().map(|l| l.content.as_deref().unwrap()).collect())
+            .collect();
+        assert_eq!(contents, vec![vec!["My title", "Body v2", "Added by hand", "New"], vec!["Appendix"]]);
+        assert_eq!((merge.updated, merge.added, merge.removed, merge.kept_edits), (1, 2, 1, 1));
+        assert_eq!(merge.pages[1].page_index, 1);
+    }
+
+    #[test]
+    fn test_merge_relinks_kept_images() {
+        let mut image = layer("image-0-0", "", 10.0);
+        image.image_url = Some("image://image-0-0".into());
+        let original = vec![page(0, vec![image.clone()])];
+        let mut edited = original.clone();
+        edited[0].layers[0].opacity = 0.5;
+        // A project from before ids were content-derived
+        let mut fresh_image = layer("image-0-5f3c0a91be27", "", 10.0);
+        fresh_image.image_url = Some("image://image-0-5f3c0a91be27".into());
+
+        let merge = merge_reimport(&original, &edited, vec![page(0, vec![fresh_image])]);
+        let kept = &merge.pages[0].layers[0];
+        assert_eq!((kept.id.as_str(), kept.opacity), ("image-0-0", 0.5));
+        assert_eq!(kept.image_url.as_deref(), Some("image://image-0-5f3c0a91be27"));
+    }
+
+    #[test]
+    fn test_replace_extracted_layers() {
+        let mut edited = page(3, vec![layer("a", "Mangled", 10.0), layer("b", "Body", 30.0)]);
+        edited.page_index = 1;
+        let mut note = layer("b", "My note", 5.0);
+        note.source_type = SourceType::Manual;
+        edited.layers.push(note);
+        edited.layers.remove(0);
+        let fresh = page(3, vec![layer("a", "Title", 10.0), layer("b", "Body", 30.0)]);
+
+        let page = replace_extracted_layers(edited, fresh);
+        let layers: Vec<(&str, &str, i32)> =
+            page.layers.iter().map(|l| (l.id.as_str(), l.content.as_deref().unwrap(), l.z_index)).collect();
+        assert_eq!(layers, [("a", "Title", 0), ("b-reimported", "Body", 1), ("b", "My note", 2)]);
+        assert_eq!(page.page_index, 1);
+    }
+
+    #[test]
+    fn test_debounce_coalesces_bursts() {
+        let (tx, rx) = channel();
+        let burst = |tx: &Sender<()>| (0..5).for_each(|_| tx.send(()).unwrap());
+        burst(&tx);
+        assert!(debounce(&rx, Duration::from_millis(20)));
+        assert!(rx.try_recv().is_err());
+        burst(&tx);
+        drop(tx);
+        assert!(!debounce(&rx, Duration::from_millis(20)));
+    }
+}
@@ -0,0 +1,131 @@
+//! Text Extraction Module
+//!
+//! Structured text for integration pipelines: pages → blocks → lines → spans
+//! with bounds, fonts and colors, read straight from the content streams
+//! (lopdf) without building layers or touching pdfium. Grouping is shared
+//! with the wasm build via `vortex_core::text_structure`. The document
+//! outline is classified from the layers of open pages instead.
+
+use crate::content_parser;
+use crate::pdf_analyzer::page_dimensions;
+
+use vortex_core::doc_structure::{self, DocumentStructure};
+use vortex_core::models::PageData;
+pub use vortex_core::text_structure::{
+    PageText, PageTextOptions, StructuredPage, StructuredText, TextBlock, TextLine, TextSpan,
+};
+
+/// Extract the text hierarchy of a PDF, optionally limited to an inclusive
+/// 0-based page range
+pub fn extract_structured(file_path: &str, page_range: Option<(usize, usize)>) -> Result<StructuredText, String> {
+    let doc = lopdf::Document::load(file_path).map_err(|e| format!("Failed to load PDF: {}", e))?;
+    structure_document(&doc, page_range)
+}
+
+fn structure_document(doc: &lopdf::Document, page_range: Option<(usize, usize)>) -> Result<StructuredText, String> {
+    let page_ids: Vec<lopdf::ObjectId> = doc.get_pages().into_values().collect();
+    let (first, last) = page_range.unwrap_or((0, page_ids.len().saturating_sub(1)));
+    if first > last || (!page_ids.is_empty() && last >= page_ids.len()) {
+        return Err(format!("Range {}-{} is invalid for {} pages", first, last, page_ids.len()));
+    }
+
+    let mut pages = Vec::with_capacity(page_ids.len());
+    for (page_index, page_id) in page_ids.into_iter().enumerate().skip(first).take(last + 1 - first) {
+        let (width, height) = page_dimensions(doc, page_id);
+        let spans = match content_parser::parse_page_content(doc, page_id, height) {
+            Ok((texts, _)) => content_parser::to_text_spans(texts),
+            Err(e) => {
+                tracing::warn!(page = page_index, "text extraction failed: {}", e);
+                Vec::new()
+            }
+        };
+        pages.push(vortex_core::text_structure::structure_page(page_index, width, height, spans));
+    }
+    Ok(StructuredText { pages })
+}
+
+/// Extract a PDF's text as a JSON hierarchy of pages, blocks, lines and spans
+#[tauri::command]
+pub async fn extract_structured_text(
+    file_path: String,
+    page_range: Option<(usize, usize)>,
+) -> Result<StructuredText, String> {
+    tokio::task::spawn_blocking(move || extract_structured(&file_path, page_range))
+        .await
+        .map_err(|e| format!("Extraction task failed: {}", e))?
+}
+
+/// Extract a PDF's text as plain text: blocks separated by blank lines,
+/// pages by form feeds
+#[tauri::command]
+pub async fn extract_plain_text(file_path: String, page_range: Option<(usize, usize)>) -> Result<String, String> {
+    tokio::task::spawn_blocking(move || extract_structured(&file_path, page_range).map(|s| s.plain_text()))
+        .await
+        .map_err(|e| format!("Extraction task failed: {}", e))?
+}
+
+/// Classify the pages' text layers into headings, body, captions and
+/// footnotes, with the heading tree for an outline panel or TOC
+#[tauri::command]
+pub fn extract_structure(pages: Vec<PageData>) -> DocumentStructure {
+    doc_structure::extract_structure(&pages)
+}
+
+/// A page's text in reading order, for copying and text exports, with
+/// style runs when `options.styles` is set
+#[tauri::command]
+pub fn get_page_text(page: PageData, options: Option<PageTextOptions>) -> PageText {
+    vortex_core::text_structure::page_text(&page, &options.unwrap_or_default())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use lopdf::{dictionary, Document, Object, Stream};
+
+    fn text_pdf(content: &[u8]) -> Document {
+        let mut doc = Document::with_version("1.5");
+        let pages_id = doc.new_object_id();
+        let font = doc.add_object(dictionary! {
+            "Type" => "Font", "Subtype" => "Type1", "BaseFont" => "Helvetica",
+        });
+        let contents = doc.add_object(Stream::new(dictionary! {}, content.to_vec()));
+        let page = doc.add_object(dictionary! {
+            "Type" => "Page",
+            "Parent" => pages_id,
+            "Contents" => contents,
+            "Resources" => dictionary! { "Font" => dictionary! { "F1" => font } },
+        });
+        doc.objects.insert(
+            pages_id,
+            Object::Dictionary(dictionary! {
+                "Type" => "Pages",
+                "Kids" => vec![page.into()],
+                "Count" => 1,
+                "MediaBox" => vec![0.into(), 0.into(), 612.into(), 792.into()],
+            }),
+        );
+        let catalog = doc.add_object(dictionary! { "Type" => "Catalog", "Pages" => pages_id });
+        doc.trailer.set("Root", catalog);
+        doc
+    }
+
+    #[test]
+    fn test_structure_document() {
+        let doc = text_pdf(
+            b"BT /F1 24 Tf 72 700 Td (Chapter One) Tj ET \
+              BT /F1 12 Tf 72 640 Td (It was a dark) Tj ET \
+              BT /F1 12 Tf 72 626 Td (and stormy night.) Tj ET",
+        );
+        let structured = structure_document(&doc, None).unwrap();
+
+        assert_eq!(structured.pages.len(), 1);
+        let page = &structured.pages[0];
+        assert_eq!((page.width, page.height), (612.0, 792.0));
+        let blocks: Vec<String> = page.blocks.iter().map(TextBlock::text).collect();
+        assert_eq!(blocks, vec!["Chapter One", "It was a dark\nand stormy night."]);
+        assert_eq!(page.blocks[0].lines[0].spans[0].font_size, 24.0);
+
+        assert!(structure_document(&doc, Some((0, 1))).is_err());
+    }
+}
@@ -0,0 +1,156 @@
+//! Text-to-Speech Module
+//!
+//! Renders audio proofs with the platform's speech engine: `say` on macOS,
+//! System.Speech through PowerShell on Windows and `espeak-ng` on Linux.
+//! The text is handed over in a temporary file so long chapters do not hit
+//! command-line limits.
+
+use std::path::Path;
+use std::process::Command;
+use std::sync::atomic::{AtomicU32, Ordering};
+
+static INPUT_COUNTER: AtomicU32 = AtomicU32::new(0);
+
+/// Run the speech engine, turning a missing binary into an actionable message
+fn run(mut command: Command, tool: &str, hint: &str) -> Result<std::process::Output, String> {
+    command.output().map_err(|e| match e.kind() {
+        std::io::ErrorKind::NotFound => format!("{} not found; {}", tool, hint),
+        _ => format!("Failed to run {}: {}", tool, e),
+    })
+}
+
+#[cfg(target_os = "linux")]
+mod platform {
+    use super::*;
+
+    pub const AUDIO_EXTENSION: &str = "wav";
+    /// espeak-ng reads plain text
+    pub const TAKES_SSML: bool = false;
+
+    pub fn command(input: &Path, output: &Path, voice: Option<&str>, rate: Option<u32>) -> Command {
+        let mut command = Command::new("espeak-ng");
+        command.arg("-w").arg(output).arg("-f").arg(input);
+        if let Some(voice) = voice {
+            command.args(["-v", voice]);
+        }
+        if let Some(rate) = rate {
+            command.args(["-s", &rate.to_string()]);
+        }
+        command
+    }
+
+    pub fn run(command: Command) -> Result<std::process::Output, String> {
+        super::run(command, "espeak-ng", "install espeak-ng to export audio proofs")
+    }
+}
+
+#[cfg(target_os = "macos")]
+mod platform {
+    use super::*;
+
+    pub const AUDIO_EXTENSION: &str = "aiff";
+    pub const TAKES_SSML: bool = false;
+
+    pub fn command(input: &Path, output: &Path, voice: Option<&str>, rate: Option<u32>) -> Command {
+        let mut command = Command::new("say");
+        command.arg("-o").arg(output).arg("-f").arg(input);
+        if let Some(voice) = voice {
+            command.args(["-v", voice]);
+        }
+        if let Some(rate) = rate {
+            command.args(["-r", &rate.to_string()]);
+        }
+        command
+    }
+
+    pub fn run(command: Command) -> Result<std::process::Output, String> {
+        super::run(command, "say", "audio proofs need the macOS speech synthesizer")
+    }
+}
+
+#[cfg(target_os = "windows")]
+mod platform {
+    use super::*;
+
+    pub const AUDIO_EXTENSION: &str = "wav";
+    /// System.Speech takes SSML, so chapter titles get their pause
+    pub const TAKES_SSML: bool = true;
+
+    /// Typical speaking rate, System.Speech's rate 0
+    const DEFAULT_WPM: f32 = 180.0;
+
+    fn quote(value: &str) -> String {
+        format!("'{}'", value.replace('\'', "''"))
+    }
+
+    pub fn command(input: &Path, output: &Path, voice: Option<&str>, rate: Option<u32>) -> Command {
+        // System.Speech rates run from -10 to 10, roughly 10% apart
+        let rate = rate.map_or(0, |wpm| ((wpm as f32 / DEFAULT_WPM - 1.0) * 10.0).round().clamp(-10.0, 10.0) as i32);
+        let voice = voice.map(|v| format!("$s.SelectVoice({})\n", quote(v))).unwrap_or_default();
+        let script = format!(
+            r#"$ErrorActionPreference = 'Stop'
+Add-Type -AssemblyName System.Speech
+$s = New-Object System.Speech.Synthesis.SpeechSynthesizer
+{voice}$s.Rate = {rate}
+$s.SetOutputToWaveFile({output})
+$s.SpeakSsml([IO.File]::ReadAllText({input}))
+$s.Dispose()"#,
+            voice = voice,
+            rate = rate,
+            output = quote(&output.to_string_lossy()),
+            input = quote(&input.to_string_lossy()),
+        );
+        let mut command = Command::new("powershell");
+        command.args(["-NoProfile", "-NonInteractive", "-Command", &script]);
+        command
+    }
+
+    pub fn run(command: Command) -> Result<std::process::Output, String> {
+        super::run(command, "PowerShell", "audio proofs require Windows PowerShell")
+    }
+}
+
+#[cfg(not(any(target_os = "linux", target_os = "windows", target_os = "macos")))]
+mod platform {
+    use super::*;
+
+    pub const AUDIO_EXTENSION: &str = "wav";
+    pub const TAKES_SSML: bool = false;
+
+    pub fn command(_input: &Path, _output: &Path, _voice: Option<&str>, _rate: Option<u32>) -> Command {
+        Command::new("espeak-ng")
+    }
+
+    pub fn run(_command: Command) -> Result<std::process::Output, String> {
+        Err("Audio proofs are not supported on this platform".to_string())
+    }
+}
+
+/// Extension of the audio files the platform engine writes
+pub fn audio_extension() -> &'static str {
+    platform::AUDIO_EXTENSION
+}
+
+/// Speak a chapter into `output`; the engine is given `ssml` where it
+/// understands it and `text` otherwise. `rate` is in words per minute.
+pub fn synthesize(text: &str, ssml: &str, output: &Path, voice: Option<&str>, rate: Option<u32>) -> Result<(), String> {
+    let input = std::env::temp_dir().join(format!(
+        "rook-speech-{}-{}.txt",
+        std::process::id(),
+        INPUT_COUNTER.fetch_add(1, Ordering::Relaxed)
+    ));
+    std::fs::write(&input, if platform::TAKES_SSML { ssml } else { text })
+        .map_err(|e| format!("Failed to write speech input: {}", e))?;
+    let result = platform::run(platform::command(&input, output, voice, rate));
+    let _ = std::fs::remove_file(&input);
+
+    let output = result?;
+    if !output.status.success() {
+        let stderr = String::from_utf8_lossy(&output.stderr);
+        return Err(match stderr.trim() {
+            "" => format!("Speech engine exited with {}", output.status),
+            msg => format!("Speech engine failed: {}", msg),
+        });
+    }
+    Ok(())
+}
@@ -1,126 +1,646 @@
 //! DOCX Parser for WASM
 //! Parses DOCX files (ZIP with XML) into document layers
+//!
+//! Mirrors the desktop parser (src-tauri document_parser + docx_extractor):
+//! same run font merging, paragraph spacing/indent handling, table layout
+//! and layer ids, so both builds produce the same layers for a file.
 
 use crate::models::*;
+use quick_xml::events::{BytesStart, Event};
+use quick_xml::Reader;
 use std::io::{Cursor, Read};
+use vortex_core::font_names::get_canonical_name;
+use vortex_core::layer_ids::LayerIds;
+use vortex_core::page_setup::PageSetup;
+use vortex_core::reflow;
 use zip::ZipArchive;
 
-pub fn parse_docx(data: &[u8]) -> Result<DocumentData, String> {
+/// Default document font (DOCX default)
+const DEFAULT_FONT: &str = "Calibri";
+
+/// Run font info (mirrors docx_extractor::DocxFontInfo)
+#[derive(Debug, Clone)]
+struct DocxFontInfo {
+    resolved: String,
+    size: Option<f32>,
+    is_bold: bool,
+    is_italic: bool,
+    color: Option<String>,
+    underline: bool,
+    strike: bool,
+}
+
+impl Default for DocxFontInfo {
+    fn default() -> Self {
+        Self {
+            resolved: "Arial".to_string(),
+            size: None,
+            is_bold: false,
+            is_italic: false,
+            color: None,
+            underline: false,
+            strike: false,
+        }
+    }
+}
+
+/// Paragraph formatting (mirrors docx_extractor::ParagraphInfo)
+#[derive(Debug, Clone, Default)]
+struct ParagraphInfo {
+    font_family: Option<String>,
+    font_size: Option<f32>,
+    is_bold: bool,
+    is_italic: bool,
+    color: Option<String>,
+    alignment: Option<String>,
+    indent_left: Option<f32>,
+    indent_right: Option<f32>,
+    spacing_after: Option<f32>,
+    line_spacing: Option<f32>,
+    keep_next: bool,
+    keep_lines: bool,
+    /// Word's widow/orphan control, on unless the paragraph turns it off
+    widow_control: Option<bool>,
+}
+
+#[derive(Debug, Default)]
+struct Paragraph {
+    props: ParagraphInfo,
+    /// Run text with its own font (empty runs kept: table cells take the first run's font)
+    runs: Vec<(String, DocxFontInfo)>,
+}
+
+#[derive(Debug, Default)]
+struct TableCell {
+    width: Option<f32>,
+    paragraphs: Vec<Paragraph>,
+}
+
+#[derive(Debug, Default)]
+struct Table {
+    width: Option<f32>,
+    grid: Vec<f32>,
+    rows: Vec<Vec<TableCell>>,
+}
+
+enum BodyContent {
+    Paragraph(Paragraph),
+    Table(Table),
+}
+
+pub fn parse_docx(data: &[u8], page_setup: &PageSetup) -> Result<DocumentData, String> {
     let cursor = Cursor::new(data);
     let mut archive = ZipArchive::new(cursor).map_err(|e| format!("Invalid DOCX: {}", e))?;
 
-    let mut pages = Vec::new();
-    let mut current_page = create_page(0);
+    let mut content = String::new();
+    archive
+        .by_name("word/document.xml")
+        .map_err(|e| format!("Invalid DOCX: {}", e))?
+        .read_to_string(&mut content)
+        .map_err(|e| e.to_string())?;
+
+    let body = parse_body(&content)?;
+
+    let mut layers = Vec::new();
     let mut layer_counter = 0;
-    let mut y_offset: f32 = 50.0;
-
-    // Parse document.xml
-    if let Ok(mut file) = archive.by_name("word/document.xml") {
-        let mut content = String::new();
-        file.read_to_string(&mut content).map_err(|e| e.to_string())?;
-        
-        // Simple XML text extraction
-        let paragraphs = extract_paragraphs(&content);
-        
-        for text in paragraphs {
-            if text.trim().is_empty() {
-                y_offset += 20.0;
-                continue;
+    // Text flows, so ids come from content alone
+    let mut ids = LayerIds::new(0);
+    let content = page_setup.content_bounds();
+    let mut current_y: f32 = content.y;
+    let content_width = content.width;
+
+    for item in &body {
+        match item {
+            BodyContent::Paragraph(para) => layers.extend(layout_paragraph(
+                para,
+                content.x,
+                &mut current_y,
+                content_width,
+                &mut layer_counter,
+                &mut ids,
+            )),
+            BodyContent::Table(table) => layers.extend(layout_table(
+                table,
+                content.x,
+                &mut current_y,
+                content_width,
+                &mut layer_counter,
+                &mut ids,
+            )),
+        }
+    }
+
+    // Laid out as one long page, then paginated
+    let flow = PageData {
+        page_index: 0,
+        width: page_setup.width,
+        height: page_setup.height,
+        dpi: Some(72),
+        layers,
+        metadata: None,
+        background: None,
+    };
+    Ok(DocumentData::new(page_setup.width, page_setup.height, reflow::reflow(&[flow], page_setup)))
+}
+
+// ============== XML reading ==============
+
+fn attr(e: &BytesStart, key: &[u8]) -> Option<String> {
+    e.attributes()
+        .flatten()
+        .find(|a| a.key.as_ref() == key)
+        .and_then(|a| a.unescape_value().ok())
+        .map(|v| v.into_owned())
+}
+
+/// Numeric attribute in twips converted by `divisor`
+fn attr_num(e: &BytesStart, key: &[u8], divisor: f32) -> Option<f32> {
+    attr(e, key)?.parse::<f32>().ok().map(|v| v / divisor)
+}
+
+/// An OOXML on/off property: on unless `w:val` says otherwise
+fn on_off(e: &BytesStart) -> bool {
+    !matches!(attr(e, b"w:val").as_deref(), Some("false" | "0" | "off"))
+}
+
+fn skip(reader: &mut Reader<&[u8]>, e: &BytesStart) -> Result<(), String> {
+    reader.read_to_end(e.name()).map(|_| ()).map_err(|e| e.to_string())
+}
+
+fn next<'a>(reader: &mut Reader<&'a [u8]>) -> Result<Event<'a>, String> {
+    match reader.read_event().map_err(|e| format!("Invalid document.xml: {}", e))? {
+        Event::Eof => Err("Unexpected end of document.xml".to_string()),
+        event => Ok(event),
+    }
+}
+
+fn parse_body(xml: &str) -> Result<Vec<BodyContent>, String> {
+    let mut reader = Reader::from_str(xml);
+    let mut body = Vec::new();
+    let mut in_body = false;
+
+    loop {
+        match reader.read_event().map_err(|e| format!("Invalid document.xml: {}", e))? {
+            Event::Start(e) if e.name().as_ref() == b"w:body" => in_body = true,
+            Event::End(e) if e.name().as_ref() == b"w:body" => break,
+            Event::Start(e) if in_body => match e.name().as_ref() {
+                b"w:p" => body.push(BodyContent::Paragraph(parse_paragraph(&mut reader)?)),
+                b"w:tbl" => body.push(BodyContent::Table(parse_table(&mut reader)?)),
+                _ => skip(&mut reader, &e)?,
+            },
+            Event::Empty(e) if in_body && e.name().as_ref() == b"w:p" => {
+                body.push(BodyContent::Paragraph(Paragraph::default()));
             }
+            Event::Eof => break,
+            _ => {}
+        }
+    }
+
+    Ok(body)
+}
+
+fn parse_paragraph(reader: &mut Reader<&[u8]>) -> Result<Paragraph, String> {
+    let mut para = Paragraph::default();
+    loop {
+        match next(reader)? {
+            Event::Start(e) => match e.name().as_ref() {
+                b"w:pPr" => para.props = parse_paragraph_props(reader)?,
+                b"w:r" => para.runs.push(parse_run(reader)?),
+                _ => skip(reader, &e)?,
+            },
+            Event::End(e) if e.name().as_ref() == b"w:p" => return Ok(para),
+            _ => {}
+        }
+    }
+}
 
-            // Check for page break
-            if y_offset > 700.0 {
-                pages.push(current_page);
-                current_page = create_page(pages.len());
-                y_offset = 50.0;
+fn parse_paragraph_props(reader: &mut Reader<&[u8]>) -> Result<ParagraphInfo, String> {
+    let mut info = ParagraphInfo::default();
+    loop {
+        let (e, is_start) = match next(reader)? {
+            Event::Start(e) => (e, true),
+            Event::Empty(e) => (e, false),
+            Event::End(e) if e.name().as_ref() == b"w:pPr" => return Ok(info),
+            _ => continue,
+        };
+        match e.name().as_ref() {
+            b"w:jc" => {
+                info.alignment = Some(
+                    match attr(&e, b"w:val").as_deref() {
+                        Some("center") => "center",
+                        Some("right") => "right",
+                        Some("both") => "justify",
+                        _ => "left",
+                    }
+                    .to_string(),
+                );
+            }
+            // Indentation (twips to points: 1 twip = 1/20 point)
+            b"w:ind" => {
+                info.indent_left = attr_num(&e, b"w:left", 20.0).or_else(|| attr_num(&e, b"w:start", 20.0));
+                info.indent_right = attr_num(&e, b"w:right", 20.0).or_else(|| attr_num(&e, b"w:end", 20.0));
+            }
+            // Spacing (twips to points, 240 = single line)
+            b"w:spacing" => {
+                info.spacing_after = attr_num(&e, b"w:after", 20.0);
+                info.line_spacing = attr_num(&e, b"w:line", 240.0);
             }
+            // Pagination (a bare element means on)
+            b"w:keepNext" => info.keep_next = on_off(&e),
+            b"w:keepLines" => info.keep_lines = on_off(&e),
+            b"w:widowControl" => info.widow_control = Some(on_off(&e)),
+            _ => {}
+        }
+        if is_start {
+            skip(reader, &e)?;
+        }
+    }
+}
 
-            let layer = LayerObject {
-                id: format!("text-{}-{}", current_page.page_index, layer_counter),
-                layer_type: "text".to_string(),
-                bounds: Bounds { x: 50.0, y: y_offset, width: 500.0, height: 20.0 },
-                visible: true,
-                locked: false,
-                z_index: layer_counter as i32,
-                opacity: 1.0,
-                content: Some(text),
-                font_family: Some("Arial".to_string()),
-                font_size: Some(12.0),
-                font_weight: Some(400),
-                color: Some("#000000".to_string()),
-                text_align: Some("left".to_string()),
-                image_url: None,
-                image_path: None,
-                image_data: None,
-                shape_type: None,
-                stroke_color: None,
-                stroke_width: None,
-                fill_color: None,
-                source_type: "extracted".to_string(),
-                role: "content".to_string(),
-            };
+fn parse_run(reader: &mut Reader<&[u8]>) -> Result<(String, DocxFontInfo), String> {
+    let mut text = String::new();
+    let mut font = DocxFontInfo::default();
+    loop {
+        match next(reader)? {
+            Event::Start(e) => match e.name().as_ref() {
+                b"w:rPr" => font = parse_run_props(reader)?,
+                b"w:t" => text.push_str(&read_text(reader)?),
+                _ => skip(reader, &e)?,
+            },
+            Event::End(e) if e.name().as_ref() == b"w:r" => return Ok((text, font)),
+            _ => {}
+        }
+    }
+}
 
-            current_page.layers.push(layer);
-            layer_counter += 1;
-            y_offset += 25.0;
+fn read_text(reader: &mut Reader<&[u8]>) -> Result<String, String> {
+    let mut text = String::new();
+    loop {
+        match next(reader)? {
+            Event::Text(t) => text.push_str(&t.unescape().map_err(|e| e.to_string())?),
+            Event::CData(t) => text.push_str(&String::from_utf8_lossy(&t)),
+            Event::End(e) if e.name().as_ref() == b"w:t" => return Ok(text),
+            _ => {}
         }
     }
+}
 
-    pages.push(current_page);
+fn parse_run_props(reader: &mut Reader<&[u8]>) -> Result<DocxFontInfo, String> {
+    let mut info = DocxFontInfo::default();
+    loop {
+        let (e, is_start) = match next(reader)? {
+            Event::Start(e) => (e, true),
+            Event::Empty(e) => (e, false),
+            Event::End(e) if e.name().as_ref() == b"w:rPr" => return Ok(info),
+            _ => continue,
+        };
+        match e.name().as_ref() {
+            b"w:rFonts" => {
+                // Resolve to best available font
+                info.resolved = attr(&e, b"w:ascii")
+                    .or_else(|| attr(&e, b"w:hAnsi"))
+                    .or_else(|| attr(&e, b"w:eastAsia"))
+                    .unwrap_or_else(|| "Arial".to_string());
+            }
+            // Half-points to points
+            b"w:sz" => info.size = attr_num(&e, b"w:val", 2.0),
+            b"w:b" => info.is_bold = true,
+            b"w:i" => info.is_italic = true,
+            b"w:u" => info.underline = true,
+            b"w:strike" => info.strike = true,
+            b"w:color" => info.color = attr(&e, b"w:val").map(|v| format!("#{}", v)),
+            _ => {}
+        }
+        if is_start {
+            skip(reader, &e)?;
+        }
+    }
+}
 
-    Ok(DocumentData {
-        page_width: 612.0,
-        page_height: 792.0,
-        pages,
-    })
+fn parse_table(reader: &mut Reader<&[u8]>) -> Result<Table, String> {
+    let mut table = Table::default();
+    loop {
+        match next(reader)? {
+            Event::Start(e) => match e.name().as_ref() {
+                b"w:tblPr" | b"w:tblGrid" => {}
+                b"w:tr" => table.rows.push(parse_row(reader)?),
+                _ => skip(reader, &e)?,
+            },
+            Event::Empty(e) => match e.name().as_ref() {
+                // Twips to points
+                b"w:tblW" => table.width = attr_num(&e, b"w:w", 20.0),
+                b"w:gridCol" => table.grid.push(attr_num(&e, b"w:w", 20.0).unwrap_or(0.0)),
+                _ => {}
+            },
+            Event::End(e) if e.name().as_ref() == b"w:tbl" => return Ok(table),
+            _ => {}
+        }
+    }
 }
 
-fn create_page(index: usize) -> PageData {
-    PageData {
-        page_index: index,
-        width: 612.0,
-        height: 792.0,
-        dpi: Some(72),
-        layers: Vec::new(),
-        metadata: None,
+fn parse_row(reader: &mut Reader<&[u8]>) -> Result<Vec<TableCell>, String> {
+    let mut cells = Vec::new();
+    loop {
+        match next(reader)? {
+            Event::Start(e) if e.name().as_ref() == b"w:tc" => cells.push(parse_cell(reader)?),
+            Event::Start(e) => skip(reader, &e)?,
+            Event::End(e) if e.name().as_ref() == b"w:tr" => return Ok(cells),
+            _ => {}
+        }
+    }
+}
+
+fn parse_cell(reader: &mut Reader<&[u8]>) -> Result<TableCell, String> {
+    let mut cell = TableCell::default();
+    loop {
+        match next(reader)? {
+            Event::Start(e) => match e.name().as_ref() {
+                b"w:tcPr" => {}
+                b"w:p" => cell.paragraphs.push(parse_paragraph(reader)?),
+                _ => skip(reader, &e)?,
+            },
+            Event::Empty(e) => match e.name().as_ref() {
+                b"w:tcW" => cell.width = attr_num(&e, b"w:w", 20.0),
+                b"w:p" => cell.paragraphs.push(Paragraph::default()),
+                _ => {}
+            },
+            Event::End(e) if e.name().as_ref() == b"w:tc" => return Ok(cell),
+            _ => {}
+        }
     }
 }
 
-fn extract_paragraphs(xml: &str) -> Vec<String> {
-    let mut paragraphs = Vec::new();
-    let mut current_text = String::new();
-    let mut in_text = false;
-    let mut chars = xml.chars().peekable();
+// ============== Layout ==============
 
-    while let Some(c) = chars.next() {
-        if c == '<' {
-            let mut tag = String::new();
-            while let Some(&nc) = chars.peek() {
-                if nc == '>' {
-                    chars.next();
-                    break;
-                }
-                tag.push(chars.next().unwrap());
-            }
+/// Merge run font info with paragraph defaults
+fn merge_font_info(run: &DocxFontInfo, para: &ParagraphInfo) -> DocxFontInfo {
+    DocxFontInfo {
+        resolved: if run.resolved != "Arial" {
+            run.resolved.clone()
+        } else {
+            para.font_family.clone().unwrap_or_else(|| DEFAULT_FONT.to_string())
+        },
+        size: run.size.or(para.font_size),
+        is_bold: run.is_bold || para.is_bold,
+        is_italic: run.is_italic || para.is_italic,
+        color: run.color.clone().or_else(|| para.color.clone()),
+        underline: run.underline,
+        strike: run.strike,
+    }
+}
+
+fn text_align(alignment: Option<&str>) -> TextAlign {
+    match alignment {
+        Some("center") => TextAlign::Center,
+        Some("right") => TextAlign::Right,
+        _ => TextAlign::Left,
+    }
+}
+
+fn text_layer(ids: &mut LayerIds, z_index: usize, bounds: Bounds, text: String, font: &DocxFontInfo, font_size: f32, align: TextAlign) -> LayerObject {
+    LayerObject {
+        id: ids.next("text", &text, None),
+        layer_type: LayerType::Text,
+        bounds,
+        visible: true,
+        locked: false,
+        z_index: z_index as i32,
+        opacity: 1.0,
+        blend_mode: None,
+        content: Some(text),
+        font_family: Some(get_canonical_name(&font.resolved)),
+        font_size: Some(font_size),
+        font_weight: Some(if font.is_bold { 700 } else { 400 }),
+        font_style: font.is_italic.then(|| "italic".to_string()),
+        color: Some(font.color.clone().unwrap_or_else(|| "#000000".to_string())),
+        text_align: Some(align),
+        text_decoration: None,
+        text_transform: None,
+        line_height: None,
+        letter_spacing: None,
+        background_color: None,
+        text_path: None,
+        text_outline: None,
+        text_shadow: None,
+        drop_cap: None,
+        notes: Vec::new(),
+        pagination: None,
+        image_url: None,
+        image_path: None,
+        image_data: None,
+        image_link: None,
+        shape_type: None,
+        stroke_color: None,
+        stroke_width: None,
+        fill_color: None,
+        text_wrap: None,
+        path_data: None,
+        transform: None,
+        source_type: SourceType::Extracted,
+        role: LayerRole::Content,
+        ocr: None,
+        tags: Vec::new(),
+    }
+}
+
+/// Keep and widow/orphan controls of a paragraph; `None` for Word's
+/// defaults (widow control on)
+fn pagination(props: &ParagraphInfo) -> Option<Pagination> {
+    let lines = if props.widow_control == Some(false) { 1 } else { 2 };
+    let pagination = Pagination {
+        keep_together: props.keep_lines,
+        keep_with_next: props.keep_next,
+        widows: lines,
+        orphans: lines,
+    };
+    (pagination != Pagination::default()).then_some(pagination)
+}
 
-            if tag.starts_with("w:t") && !tag.contains('/') {
-                in_text = true;
-            } else if tag == "/w:t" {
-                in_text = false;
-            } else if tag == "/w:p" {
-                if !current_text.is_empty() {
-                    paragraphs.push(current_text.clone());
-                    current_text.clear();
+fn layout_paragraph(
+    para: &Paragraph,
+    x_offset: f32,
+    current_y: &mut f32,
+    max_width: f32,
+    counter: &mut usize,
+    ids: &mut LayerIds,
+) -> Vec<LayerObject> {
+    let props = &para.props;
+    let mut layers = Vec::new();
+
+    if para.runs.iter().all(|(text, _)| text.is_empty()) {
+        *current_y += props.spacing_after.unwrap_or(6.0);
+        return layers;
+    }
+
+    let indent_left = props.indent_left.unwrap_or(0.0);
+    let x = x_offset + indent_left;
+    let available_width = max_width - indent_left - props.indent_right.unwrap_or(0.0);
+
+    let mut run_x = x;
+    for (text, run_font) in para.runs.iter().filter(|(text, _)| !text.is_empty()) {
+        let font = merge_font_info(run_font, props);
+        let font_size = font.size.unwrap_or(11.0);
+        let text_height = font_size * props.line_spacing.unwrap_or(1.15);
+
+        let char_width_factor = if font.resolved.to_lowercase().contains("mono") { 0.6 } else { 0.5 };
+        let text_width = (text.chars().count() as f32 * font_size * char_width_factor).min(available_width);
+
+        let mut layer = text_layer(
+            ids,
+            *counter,
+            Bounds { x: run_x, y: *current_y, width: text_width.max(1.0), height: text_height },
+            text.clone(),
+            &font,
+            font_size,
+            text_align(props.alignment.as_deref()),
+        );
+        layer.text_decoration = if font.underline {
+            Some("underline".to_string())
+        } else if font.strike {
+            Some("line-through".to_string())
+        } else {
+            None
+        };
+        layer.line_height = props.line_spacing;
+        layer.pagination = pagination(props);
+        layers.push(layer);
+
+        run_x += text_width;
+        *counter += 1;
+    }
+
+    let last_font_size = layers.last().and_then(|l| l.font_size).unwrap_or(11.0);
+    let line_height = last_font_size * props.line_spacing.unwrap_or(1.15);
+    *current_y += line_height + props.spacing_after.unwrap_or(4.0);
+
+    layers
+}
+
+fn layout_table(
+    table: &Table,
+    x_offset: f32,
+    current_y: &mut f32,
+    max_width: f32,
+    counter: &mut usize,
+    ids: &mut LayerIds,
+) -> Vec<LayerObject> {
+    let mut layers = Vec::new();
+
+    let col_widths = &table.grid;
+    let total_width = table.width.unwrap_or(max_width);
+    let num_cols = col_widths.len().max(1);
+    let default_col_width = total_width / num_cols as f32;
+
+    let table_start_y = *current_y;
+    let mut row_y = table_start_y;
+
+    for row in &table.rows {
+        let mut row_height: f32 = 20.0;
+
+        for (col_index, cell) in row.iter().enumerate() {
+            let cell_x: f32 = x_offset + col_widths.iter().take(col_index).sum::<f32>();
+            let cell_width = col_widths
+                .get(col_index)
+                .copied()
+                .unwrap_or_else(|| cell.width.unwrap_or(default_col_width));
+
+            let mut cell_content_y = row_y + 2.0;
+            for para in &cell.paragraphs {
+                let cell_text: String = para.runs.iter().map(|(t, _)| t.as_str()).collect();
+                if cell_text.trim().is_empty() {
+                    continue;
                 }
+
+                let font = para
+                    .runs
+                    .first()
+                    .map(|(_, f)| merge_font_info(f, &para.props))
+                    .unwrap_or_else(|| DocxFontInfo {
+                        resolved: DEFAULT_FONT.to_string(),
+                        size: Some(11.0),
+                        ..Default::default()
+                    });
+
+                let font_size = font.size.unwrap_or(11.0);
+                let text_height = font_size * 1.2;
+
+                layers.push(text_layer(
+                    ids,
+                    *counter,
+                    Bounds {
+                        x: cell_x + 4.0,
+                        y: cell_content_y,
+                        width: (cell_width - 8.0).max(1.0),
+                        height: text_height,
+                    },
+                    cell_text,
+                    &font,
+                    font_size,
+                    text_align(para.props.alignment.as_deref()),
+                ));
+
+                cell_content_y += text_height + 2.0;
+                *counter += 1;
             }
-        } else if in_text {
-            current_text.push(c);
+
+            let cell_height = cell_content_y - row_y + 4.0;
+            row_height = row_height.max(cell_height);
         }
+
+        row_y += row_height;
     }
 
-    if !current_text.is_empty() {
-        paragraphs.push(current_text);
+    let table_height = row_y - table_start_y;
+    if table_height > 0.0 {
+        layers.insert(
+            0,
+            LayerObject {
+                id: ids.next("table-border", &format!("{} columns", num_cols), None),
+                layer_type: LayerType::Shape,
+                bounds: Bounds { x: x_offset, y: table_start_y, width: total_width, height: table_height },
+                visible: true,
+                locked: false,
+                z_index: 0,
+                opacity: 1.0,
+                blend_mode: None,
+                content: None,
+                font_family: None,
+                font_size: None,
+                font_weight: None,
+                font_style: None,
+                color: None,
+                text_align: None,
+                text_decoration: None,
+                text_transform: None,
+                line_height: None,
+                letter_spacing: None,
+                background_color: None,
+                text_path: None,
+                text_outline: None,
+                text_shadow: None,
+                drop_cap: None,
+                notes: Vec::new(),
+                pagination: None,
+                image_url: None,
+                image_path: None,
+                image_data: None,
+                image_link: None,
+                shape_type: Some(ShapeType::Rectangle),
+                stroke_color: Some("#000000".to_string()),
+                stroke_width: Some(1.0),
+                fill_color: None,
+                text_wrap: None,
+                path_data: None,
+                transform: None,
+                source_type: SourceType::Extracted,
+                role: LayerRole::Content,
+                ocr: None,
+                tags: Vec::new(),
+            },
+        );
+        *counter += 1;
     }
 
-    paragraphs
+    *current_y = row_y + 8.0;
+    layers
 }
@@ -2,6 +2,7 @@
 //! Generates export files in memory as byte arrays
 
 use crate::models::*;
+use vortex_core::doc_metadata;
 use std::io::{Cursor, Write};
 use zip::write::SimpleFileOptions;
 use zip::ZipWriter;
@@ -10,46 +11,85 @@ pub fn export_bookproj(
     pages: &[PageData],
     metadata: &DocumentMetadata,
 ) -> Result<Vec<u8>, String> {
-    let project = BookProjectData {
-        format: "bookproj".to_string(),
-        version: "1.0.0".to_string(),
-        metadata: metadata.clone(),
-        document: DocumentData {
-            page_width: pages.first().map(|p| p.width).unwrap_or(612.0),
-            page_height: pages.first().map(|p| p.height).unwrap_or(792.0),
-            pages: pages.to_vec(),
-        },
-        settings: ProjectSettings::default(),
-    };
+    let project = vortex_core::export::build_project(pages, metadata, Vec::new(), false, Vec::new());
 
     serde_json::to_vec_pretty(&project).map_err(|e| e.to_string())
 }
 
 pub fn export_docx(
     pages: &[PageData],
-    _metadata: &DocumentMetadata,
+    metadata: &DocumentMetadata,
 ) -> Result<Vec<u8>, String> {
     let mut buffer = Cursor::new(Vec::new());
     let mut zip = ZipWriter::new(&mut buffer);
     let options = SimpleFileOptions::default();
+    let custom_properties = doc_metadata::docx_custom_properties(metadata);
+
+    // Footnote and endnote bodies are collected while the body is written
+    let mut notes = DocxNotes::default();
+    let doc_content = generate_document_xml(pages, metadata.language.as_deref(), &mut notes);
+    let note_parts: Vec<(usize, &str, &Vec<String>)> = [("footnotes", &notes.footnotes), ("endnotes", &notes.endnotes)]
+        .into_iter()
+        .filter(|(_, bodies)| !bodies.is_empty())
+        .enumerate()
+        .map(|(i, (part, bodies))| (i + 2, part, bodies))
+        .collect();
 
     // [Content_Types].xml
     zip.start_file("[Content_Types].xml", options).map_err(|e| e.to_string())?;
-    zip.write_all(CONTENT_TYPES.as_bytes()).map_err(|e| e.to_string())?;
+    let mut content_types = match custom_properties {
+        Some(_) => CONTENT_TYPES.replace("</Types>", CUSTOM_CONTENT_TYPE),
+        None => CONTENT_TYPES.to_string(),
+    };
+    for (_, part, _) in &note_parts {
+        content_types = content_types.replace(
+            "</Types>",
+            &format!(
+                "<Override PartName=\"/word/{}.xml\" ContentType=\"application/vnd.openxmlformats-officedocument.wordprocessingml.{}+xml\"/>\n</Types>",
+                part, part
+            ),
+        );
+    }
+    zip.write_all(content_types.as_bytes()).map_err(|e| e.to_string())?;
 
     // _rels/.rels
     zip.start_file("_rels/.rels", options).map_err(|e| e.to_string())?;
-    zip.write_all(RELS.as_bytes()).map_err(|e| e.to_string())?;
-
-    // word/_rels/document.xml.rels
-    zip.start_file("word/_rels/document.xml.rels", options).map_err(|e| e.to_string())?;
-    zip.write_all(DOC_RELS.as_bytes()).map_err(|e| e.to_string())?;
+    let rels = match custom_properties {
+        Some(_) => RELS.replace("</Relationships>", CUSTOM_REL),
+        None => RELS.to_string(),
+    };
+    zip.write_all(rels.as_bytes()).map_err(|e| e.to_string())?;
+
+    // docProps/core.xml and docProps/custom.xml
+    zip.start_file("docProps/core.xml", options).map_err(|e| e.to_string())?;
+    zip.write_all(doc_metadata::docx_core_properties(metadata).as_bytes()).map_err(|e| e.to_string())?;
+    if let Some(custom) = custom_properties {
+        zip.start_file("docProps/custom.xml", options).map_err(|e| e.to_string())?;
+        zip.write_all(custom.as_bytes()).map_err(|e| e.to_string())?;
+    }
 
     // word/document.xml
-    let doc_content = generate_document_xml(pages);
     zip.start_file("word/document.xml", options).map_err(|e| e.to_string())?;
     zip.write_all(doc_content.as_bytes()).map_err(|e| e.to_string())?;
 
+    // word/footnotes.xml and word/endnotes.xml
+    let mut doc_rels = DOC_RELS.to_string();
+    for (id, part, bodies) in &note_parts {
+        zip.start_file(format!("word/{}.xml", part), options).map_err(|e| e.to_string())?;
+        zip.write_all(notes_xml(part, bodies).as_bytes()).map_err(|e| e.to_string())?;
+        doc_rels = doc_rels.replace(
+            "</Relationships>",
+            &format!(
+                "<Relationship Id=\"rId{}\" Type=\"http://schemas.openxmlformats.org/officeDocument/2006/relationships/{}\" Target=\"{}.xml\"/>\n</Relationships>",
+                id, part, part
+            ),
+        );
+    }
+
+    // word/_rels/document.xml.rels
+    zip.start_file("word/_rels/document.xml.rels", options).map_err(|e| e.to_string())?;
+    zip.write_all(doc_rels.as_bytes()).map_err(|e| e.to_string())?;
+
     // word/styles.xml
     zip.start_file("word/styles.xml", options).map_err(|e| e.to_string())?;
     zip.write_all(STYLES.as_bytes()).map_err(|e| e.to_string())?;
@@ -58,16 +98,25 @@ pub fn export_docx(
     Ok(buffer.into_inner())
 }
 
-fn generate_document_xml(pages: &[PageData]) -> String {
+/// Runs carry the page's language, else the document's
+fn generate_document_xml(pages: &[PageData], document_language: Option<&str>, notes: &mut DocxNotes) -> String {
     let mut body = String::new();
     
     for page in pages {
+        let run_properties = page
+            .metadata
+            .as_ref()
+            .and_then(|m| m.language.as_deref())
+            .or(document_language)
+            .filter(|l| doc_metadata::is_language_tag(l))
+            .map(|language| format!(r#"<w:rPr><w:lang w:val="{}"/></w:rPr>"#, language))
+            .unwrap_or_default();
         for layer in &page.layers {
-            if layer.layer_type == "text" {
+            if layer.layer_type == LayerType::Text {
                 if let Some(content) = &layer.content {
                     body.push_str(&format!(
-                        r#"<w:p><w:r><w:t>{}</w:t></w:r></w:p>"#,
-                        escape_xml(content)
+                        "<w:p>{}</w:p>",
+                        notes.paragraph(content, &layer.notes, &run_properties)
                     ));
                 }
             }
@@ -85,6 +134,68 @@ fn generate_document_xml(pages: &[PageData]) -> String {
     )
 }
 
+/// Note bodies in document order; Word numbers the references itself
+#[derive(Default)]
+struct DocxNotes {
+    footnotes: Vec<String>,
+    endnotes: Vec<String>,
+}
+
+impl DocxNotes {
+    /// Text runs split at each note's anchor, with a reference run there
+    fn paragraph(&mut self, content: &str, layer_notes: &[Note], run_properties: &str) -> String {
+        let mut anchored: Vec<&Note> = layer_notes.iter().collect();
+        anchored.sort_by_key(|n| n.anchor);
+        let mut runs = String::new();
+        let mut start = 0;
+        for note in anchored {
+            let anchor = note.anchor.clamp(start, content.len());
+            if !content.is_char_boundary(anchor) {
+                continue;
+            }
+            runs.push_str(&text_run(&content[start..anchor], run_properties));
+            start = anchor;
+            let (tag, bodies) = match note.kind {
+                NoteKind::Footnote => ("footnote", &mut self.footnotes),
+                NoteKind::Endnote => ("endnote", &mut self.endnotes),
+            };
+            // Ids 0 and 1 are taken by the separator notes
+            let id = bodies.len() + 2;
+            bodies.push(format!(
+                r#"<w:{tag} w:id="{id}"><w:p><w:r><w:rPr><w:vertAlign w:val="superscript"/></w:rPr><w:{tag}Ref/></w:r>{}</w:p></w:{tag}>"#,
+                text_run(&format!(" {}", note.text), run_properties)
+            ));
+            runs.push_str(&format!(
+                r#"<w:r><w:rPr><w:vertAlign w:val="superscript"/></w:rPr><w:{tag}Reference w:id="{id}"/></w:r>"#
+            ));
+        }
+        runs.push_str(&text_run(&content[start..], run_properties));
+        runs
+    }
+}
+
+fn text_run(text: &str, run_properties: &str) -> String {
+    format!(
+        r#"<w:r>{}<w:t xml:space="preserve">{}</w:t></w:r>"#,
+        run_properties,
+        escape_xml(text)
+    )
+}
+
+/// A footnotes or endnotes part, with the separators Word expects first
+fn notes_xml(part: &str, bodies: &[String]) -> String {
+    let tag = part.trim_end_matches('s');
+    format!(
+        r#"<?xml version="1.0" encoding="UTF-8" standalone="yes"?>
+<w:{part} xmlns:w="http://schemas.openxmlformats.org/wordprocessingml/2006/main">
+<w:{tag} w:type="separator" w:id="0"><w:p><w:r><w:separator/></w:r></w:p></w:{tag}>
+<w:{tag} w:type="continuationSeparator" w:id="1"><w:p><w:r><w:continuationSeparator/></w:r></w:p></w:{tag}>
+{}
+</w:{part}>"#,
+        bodies.concat()
+    )
+}
+
 fn escape_xml(s: &str) -> String {
     s.replace('&', "&amp;")
         .replace('<', "&lt;")
@@ -98,11 +209,19 @@ const CONTENT_TYPES: &str = r#"<?xml version="1.0" encoding="UTF-8" standalone="
 <Default Extension="xml" ContentType="application/xml"/>
 <Override PartName="/word/document.xml" ContentType="application/vnd.openxmlformats-officedocument.wordprocessingml.document.main+xml"/>
 <Override PartName="/word/styles.xml" ContentType="application/vnd.openxmlformats-officedocument.wordprocessingml.styles+xml"/>
+<Override PartName="/docProps/core.xml" ContentType="application/vnd.openxmlformats-package.core-properties+xml"/>
+</Types>"#;
+
+const CUSTOM_CONTENT_TYPE: &str = r#"<Override PartName="/docProps/custom.xml" ContentType="application/vnd.openxmlformats-officedocument.custom-properties+xml"/>
 </Types>"#;
 
 const RELS: &str = r#"<?xml version="1.0" encoding="UTF-8" standalone="yes"?>
 <Relationships xmlns="http://schemas.openxmlformats.org/package/2006/relationships">
 <Relationship Id="rId1" Type="http://schemas.openxmlformats.org/officeDocument/2006/relationships/officeDocument" Target="word/document.xml"/>
+<Relationship Id="rId2" Type="http://schemas.openxmlformats.org/package/2006/relationships/metadata/core-properties" Target="docProps/core.xml"/>
+</Relationships>"#;
+
+const CUSTOM_REL: &str = r#"<Relationship Id="rId3" Type="http://schemas.openxmlformats.org/officeDocument/2006/relationships/custom-properties" Target="docProps/custom.xml"/>
 </Relationships>"#;
 
 const DOC_RELS: &str = r#"<?xml version="1.0" encoding="UTF-8" standalone="yes"?>
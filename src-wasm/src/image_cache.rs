@@ -1,32 +1,224 @@
 //! Image Handler for WASM
 //! In-memory image cache for web environment
+//!
+//! Mirrors the desktop image_handler: entries are evicted least recently
+//! used first once the byte budget is exceeded, so long editing sessions
+//! in the browser stay bounded.
 
+use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
 use std::sync::Mutex;
+use vortex_core::archive::ArchiveImage;
+
+/// Default cache budget in bytes (50MB, browsers are tighter than desktop)
+const DEFAULT_MAX_CACHE_SIZE: usize = 50 * 1024 * 1024;
+
+/// Supported image formats
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+#[repr(u8)]
+pub enum ImageFormat {
+    Png = 0,
+    Jpeg = 1,
+    WebP = 2,
+    Unknown = 255,
+}
+
+impl ImageFormat {
+    /// Detect format from magic bytes
+    pub const fn from_bytes(data: &[u8]) -> Self {
+        if data.len() < 12 {
+            return Self::Unknown;
+        }
+
+        // PNG: 89 50 4E 47 0D 0A 1A 0A
+        if data[0] == 0x89 && data[1] == 0x50 && data[2] == 0x4E && data[3] == 0x47 {
+            return Self::Png;
+        }
+
+        // JPEG: FF D8 FF
+        if data[0] == 0xFF && data[1] == 0xD8 && data[2] == 0xFF {
+            return Self::Jpeg;
+        }
+
+        // WebP: RIFF....WEBP
+        if data[0] == b'R' && data[1] == b'I' && data[2] == b'F' && data[3] == b'F'
+            && data[8] == b'W' && data[9] == b'E' && data[10] == b'B' && data[11] == b'P' {
+            return Self::WebP;
+        }
+
+        Self::Unknown
+    }
+
+    pub const fn mime_type(self) -> &'static str {
+        match self {
+            Self::Png => "image/png",
+            Self::Jpeg => "image/jpeg",
+            Self::WebP => "image/webp",
+            Self::Unknown => "application/octet-stream",
+        }
+    }
+}
+
+/// Dimensions and format of an image buffer
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ImageInfo {
+    pub width: u32,
+    pub height: u32,
+    pub format: ImageFormat,
+    pub mime_type: String,
+}
+
+/// Cache usage counters
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct CacheStats {
+    pub count: usize,
+    pub total_size: usize,
+    pub max_size: usize,
+    pub hits: u64,
+    pub misses: u64,
+    pub evictions: u64,
+}
+
+struct ImageEntry {
+    data: Box<[u8]>,
+    width: u32,
+    height: u32,
+    format: ImageFormat,
+}
 
 pub struct ImageCache {
-    cache: HashMap<String, Vec<u8>>,
+    cache: HashMap<String, ImageEntry>,
+    total_size: usize,
+    access_order: Vec<String>, // For LRU eviction
+    max_size: usize,
+    hits: u64,
+    misses: u64,
+    evictions: u64,
 }
 
 impl ImageCache {
     pub fn new() -> Self {
-        Self { cache: HashMap::new() }
+        Self {
+            cache: HashMap::with_capacity(64),
+            total_size: 0,
+            access_order: Vec::with_capacity(64),
+            max_size: DEFAULT_MAX_CACHE_SIZE,
+            hits: 0,
+            misses: 0,
+            evictions: 0,
+        }
     }
 
     pub fn store(&mut self, id: &str, data: Vec<u8>) {
-        self.cache.insert(id.to_string(), data);
+        self.remove(id);
+
+        let data_size = data.len();
+        self.evict_until(self.max_size.saturating_sub(data_size));
+
+        let format = ImageFormat::from_bytes(&data);
+        let (width, height) = detect_image_dimensions(&data).unwrap_or((0, 0));
+        self.total_size += data_size;
+        self.access_order.push(id.to_string());
+        self.cache.insert(
+            id.to_string(),
+            ImageEntry { data: data.into_boxed_slice(), width, height, format },
+        );
+    }
+
+    pub fn get(&mut self, id: &str) -> Option<Vec<u8>> {
+        self.get_with(id, <[u8]>::to_vec)
     }
 
-    pub fn get(&self, id: &str) -> Option<&Vec<u8>> {
-        self.cache.get(id)
+    /// `read` of the image bytes, without copying them out of the cache
+    pub fn get_with<R>(&mut self, id: &str, read: impl FnOnce(&[u8]) -> R) -> Option<R> {
+        match self.cache.get(id) {
+            Some(entry) => {
+                let result = read(&entry.data);
+                self.hits += 1;
+                self.touch(id);
+                Some(result)
+            }
+            None => {
+                self.misses += 1;
+                None
+            }
+        }
     }
 
-    pub fn _remove(&mut self, id: &str) -> bool {
-        self.cache.remove(id).is_some()
+    /// Image bytes without touching LRU order or hit counters
+    pub fn peek(&self, id: &str) -> Option<&[u8]> {
+        self.cache.get(id).map(|e| &e.data[..])
+    }
+
+    pub fn info(&self, id: &str) -> Option<ImageInfo> {
+        self.cache.get(id).map(|e| ImageInfo {
+            width: e.width,
+            height: e.height,
+            format: e.format,
+            mime_type: e.format.mime_type().to_string(),
+        })
+    }
+
+    pub fn remove(&mut self, id: &str) -> bool {
+        match self.cache.remove(id) {
+            Some(entry) => {
+                self.total_size = self.total_size.saturating_sub(entry.data.len());
+                self.access_order.retain(|cached| cached != id);
+                true
+            }
+            None => false,
+        }
     }
 
     pub fn clear(&mut self) {
         self.cache.clear();
+        self.access_order.clear();
+        self.total_size = 0;
+    }
+
+    /// Set the byte budget, evicting entries that no longer fit
+    pub fn set_max_size(&mut self, max_size: usize) {
+        self.max_size = max_size;
+        self.evict_until(max_size);
+    }
+
+    /// Evict least recently used entries until the cache fits in `budget` bytes
+    pub fn evict_until(&mut self, budget: usize) -> usize {
+        let mut evicted = 0;
+        while self.total_size > budget && !self.access_order.is_empty() {
+            let oldest = self.access_order.remove(0);
+            if let Some(entry) = self.cache.remove(&oldest) {
+                self.total_size = self.total_size.saturating_sub(entry.data.len());
+                evicted += 1;
+            }
+        }
+        self.evictions += evicted as u64;
+        evicted
+    }
+
+    pub fn stats(&self) -> CacheStats {
+        CacheStats {
+            count: self.cache.len(),
+            total_size: self.total_size,
+            max_size: self.max_size,
+            hits: self.hits,
+            misses: self.misses,
+            evictions: self.evictions,
+        }
+    }
+
+    fn touch(&mut self, id: &str) {
+        self.access_order.retain(|cached| cached != id);
+        self.access_order.push(id.to_string());
+    }
+}
+
+impl Default for ImageCache {
+    fn default() -> Self {
+        Self::new()
     }
 }
 
@@ -34,6 +226,48 @@ lazy_static::lazy_static! {
     pub static ref IMAGE_CACHE: Mutex<ImageCache> = Mutex::new(ImageCache::new());
 }
 
+/// Detect image dimensions from header bytes, decoding only as a fallback
+pub fn detect_image_dimensions(data: &[u8]) -> Option<(u32, u32)> {
+    match ImageFormat::from_bytes(data) {
+        // PNG: width at bytes 16-19, height at 20-23 (big endian)
+        ImageFormat::Png if data.len() >= 24 => {
+            let width = u32::from_be_bytes([data[16], data[17], data[18], data[19]]);
+            let height = u32::from_be_bytes([data[20], data[21], data[22], data[23]]);
+            return Some((width, height));
+        }
+        ImageFormat::Jpeg => {
+            // JPEG: walk markers up to the first SOF0-SOF2
+            let mut i = 2;
+            while i + 9 < data.len() {
+                if data[i] == 0xFF {
+                    let marker = data[i + 1];
+                    if (0xC0..=0xC2).contains(&marker) {
+                        let height = u16::from_be_bytes([data[i + 5], data[i + 6]]) as u32;
+                        let width = u16::from_be_bytes([data[i + 7], data[i + 8]]) as u32;
+                        return Some((width, height));
+                    }
+                    if marker != 0x00 && marker != 0xFF {
+                        let len = u16::from_be_bytes([data[i + 2], data[i + 3]]) as usize;
+                        i += 2 + len;
+                        continue;
+                    }
+                }
+                i += 1;
+            }
+        }
+        _ => {}
+    }
+
+    image::load_from_memory(data).ok().map(|img| (img.width(), img.height()))
+}
+
+/// Dimensions and format of raw image bytes
+pub fn detect_image_info(data: &[u8]) -> Option<ImageInfo> {
+    let (width, height) = detect_image_dimensions(data)?;
+    let format = ImageFormat::from_bytes(data);
+    Some(ImageInfo { width, height, format, mime_type: format.mime_type().to_string() })
+}
+
 pub fn cache_image(id: &str, data: Vec<u8>) {
     if let Ok(mut cache) = IMAGE_CACHE.lock() {
         cache.store(id, data);
@@ -41,7 +275,30 @@ pub fn cache_image(id: &str, data: Vec<u8>) {
 }
 
 pub fn get_cached_image(id: &str) -> Option<Vec<u8>> {
-    IMAGE_CACHE.lock().ok()?.get(id).cloned()
+    IMAGE_CACHE.lock().ok()?.get(id)
+}
+
+/// Cached image as a new JS `Uint8Array`, copied once from the cache
+pub fn get_cached_array(id: &str) -> Option<js_sys::Uint8Array> {
+    IMAGE_CACHE.lock().ok()?.get_with(id, |data| js_sys::Uint8Array::from(data))
+}
+
+/// Copies of the cached images among `ids`, skipping ids not in the cache
+pub fn snapshot_images(ids: &[String]) -> Vec<ArchiveImage> {
+    let Ok(cache) = IMAGE_CACHE.lock() else {
+        return Vec::new();
+    };
+    ids.iter()
+        .filter_map(|id| cache.peek(id).map(|data| ArchiveImage { id: id.clone(), data: data.to_vec() }))
+        .collect()
+}
+
+pub fn get_cached_image_info(id: &str) -> Option<ImageInfo> {
+    IMAGE_CACHE.lock().ok()?.info(id)
+}
+
+pub fn remove_cached_image(id: &str) -> bool {
+    IMAGE_CACHE.lock().map(|mut cache| cache.remove(id)).unwrap_or(false)
 }
 
 pub fn clear_cache() {
@@ -49,3 +306,17 @@ pub fn clear_cache() {
         cache.clear();
     }
 }
+
+pub fn set_cache_budget(max_size: usize) {
+    if let Ok(mut cache) = IMAGE_CACHE.lock() {
+        cache.set_max_size(max_size);
+    }
+}
+
+pub fn evict_to_budget(budget: usize) -> usize {
+    IMAGE_CACHE.lock().map(|mut cache| cache.evict_until(budget)).unwrap_or(0)
+}
+
+pub fn cache_stats() -> Option<CacheStats> {
+    IMAGE_CACHE.lock().ok().map(|cache| cache.stats())
+}
@@ -4,26 +4,56 @@
 mod docx_parser;
 mod export;
 mod image_cache;
-mod models;
+mod project_loader;
 
-use models::*;
+use vortex_core::archive;
+use vortex_core::clipboard;
+use vortex_core::decorations::Decoration;
+use vortex_core::doc_structure;
+use vortex_core::epub::{self, EpubOptions};
+use vortex_core::font_names;
+use vortex_core::image_place;
+use vortex_core::import_mapping::{self, ImportMapping};
+use vortex_core::layer_cleanup::{self, DedupOptions, PruneOptions};
+use vortex_core::layer_query;
+use vortex_core::layer_transform::{self, StyleTransform};
+use vortex_core::layers::{self, LayerAlignment};
+use vortex_core::layout_guides;
+use vortex_core::models::{self, *};
+use vortex_core::notes::{self, NoteEdit, NoteEditResult, NoteOptions};
+use vortex_core::ocr_review::{self, OcrReviewAction, OcrReviewResult};
+use vortex_core::page_layout::{self, PageLayoutOptions};
+use vortex_core::page_setup::{self, Margins, PageSetup, ResizeMode};
+use vortex_core::reflow;
+use vortex_core::sections::{self, SectionsResult};
+use vortex_core::speech::{self, SpeechOutput};
+use vortex_core::text_structure::{self, PageTextOptions, SpanPage};
+use vortex_core::typography::{self, TypographyOptions};
 use wasm_bindgen::prelude::*;
 
+/// Minimum similarity for a fuzzy font match (same as the desktop matcher)
+const FONT_MATCH_THRESHOLD: f32 = 0.8;
+
 #[wasm_bindgen(start)]
 pub fn init() {
     console_error_panic_hook::set_once();
 }
 
-/// Parse DOCX file from bytes
+/// Parse DOCX file from bytes, laid out on the given page setup
+/// (US Letter with 1 in margins when omitted)
 #[wasm_bindgen]
-pub fn parse_docx(data: &[u8]) -> Result<JsValue, JsValue> {
-    let result = docx_parser::parse_docx(data);
+pub fn parse_docx(data: &[u8], page_setup_js: JsValue) -> Result<JsValue, JsValue> {
+    let page_setup: Option<PageSetup> = serde_wasm_bindgen::from_value(page_setup_js)
+        .map_err(|e| JsValue::from_str(&e.to_string()))?;
+    let result = docx_parser::parse_docx(data, &page_setup.unwrap_or_default());
     match result {
         Ok(doc) => {
             let response = DocumentResponse {
                 success: true,
                 message: "Document parsed successfully".to_string(),
                 data: Some(doc),
+                repair: None,
+                metadata: None,
             };
             serde_wasm_bindgen::to_value(&response).map_err(|e| JsValue::from_str(&e.to_string()))
         }
@@ -32,6 +62,8 @@ pub fn parse_docx(data: &[u8]) -> Result<JsValue, JsValue> {
                 success: false,
                 message: e,
                 data: None,
+                repair: None,
+                metadata: None,
             };
             serde_wasm_bindgen::to_value(&response).map_err(|e| JsValue::from_str(&e.to_string()))
         }
@@ -52,16 +84,14 @@ pub fn create_document_from_pages(pages_js: JsValue, width: f32, height: f32) ->
     let pages: Vec<PageData> = serde_wasm_bindgen::from_value(pages_js)
         .map_err(|e| JsValue::from_str(&e.to_string()))?;
     
-    let doc = DocumentData {
-        page_width: width,
-        page_height: height,
-        pages,
-    };
+    let doc = DocumentData::new(width, height, pages);
     
     let response = DocumentResponse {
         success: true,
         message: "Document created".to_string(),
         data: Some(doc),
+        repair: None,
+        metadata: None,
     };
     
     serde_wasm_bindgen::to_value(&response).map_err(|e| JsValue::from_str(&e.to_string()))
@@ -89,32 +119,112 @@ pub fn export_docx(pages_js: JsValue, metadata_js: JsValue) -> Result<Vec<u8>, J
     export::export_docx(&pages, &metadata).map_err(|e| JsValue::from_str(&e))
 }
 
-/// Load project from bytes
+/// Export document text for audio proofing as one SSML or plain text file;
+/// audio needs the desktop speech engines
+#[wasm_bindgen]
+pub fn export_speech(pages_js: JsValue, metadata_js: JsValue, output_js: JsValue) -> Result<Vec<u8>, JsValue> {
+    let pages: Vec<PageData> = serde_wasm_bindgen::from_value(pages_js)
+        .map_err(|e| JsValue::from_str(&e.to_string()))?;
+    let metadata: DocumentMetadata = serde_wasm_bindgen::from_value(metadata_js)
+        .map_err(|e| JsValue::from_str(&e.to_string()))?;
+    let output: SpeechOutput = serde_wasm_bindgen::from_value(output_js)
+        .map_err(|e| JsValue::from_str(&e.to_string()))?;
+
+    let chapters = speech::speech_chapters(&pages);
+    if chapters.is_empty() {
+        return Err(JsValue::from_str("The pages have no text to read"));
+    }
+    let language = metadata.language.as_deref().filter(|l| vortex_core::doc_metadata::is_language_tag(l));
+    match output {
+        SpeechOutput::Text => Ok(speech::speech_text(&chapters).into_bytes()),
+        SpeechOutput::Ssml => Ok(speech::speech_ssml(&chapters, language).into_bytes()),
+        SpeechOutput::Audio => Err(JsValue::from_str("Audio proofs require the desktop app")),
+    }
+}
+
+/// Export a fixed-layout EPUB of the pages in `page_range` (all when
+/// absent), with the `sections` as its contents; images come from the image
+/// cache, by `image://` id, layer id or image path, and there is no cover
+#[wasm_bindgen]
+pub fn export_epub(
+    pages_js: JsValue,
+    metadata_js: JsValue,
+    options_js: JsValue,
+    sections_js: JsValue,
+    page_range_js: JsValue,
+) -> Result<Vec<u8>, JsValue> {
+    let pages: Vec<PageData> = serde_wasm_bindgen::from_value(pages_js)
+        .map_err(|e| JsValue::from_str(&e.to_string()))?;
+    let metadata: DocumentMetadata = serde_wasm_bindgen::from_value(metadata_js)
+        .map_err(|e| JsValue::from_str(&e.to_string()))?;
+    let options: EpubOptions = serde_wasm_bindgen::from_value(options_js)
+        .map_err(|e| JsValue::from_str(&e.to_string()))?;
+    let sections: Vec<Section> = serde_wasm_bindgen::from_value(sections_js)
+        .map_err(|e| JsValue::from_str(&e.to_string()))?;
+    let page_range: Option<(usize, usize)> = serde_wasm_bindgen::from_value(page_range_js)
+        .map_err(|e| JsValue::from_str(&e.to_string()))?;
+
+    let image_bytes = |layer: &LayerObject| {
+        let url_id = layer.image_url.as_deref().and_then(|url| url.strip_prefix("image://"));
+        url_id
+            .into_iter()
+            .chain([layer.id.as_str()])
+            .chain(layer.image_path.as_deref())
+            .find_map(image_cache::get_cached_image)
+    };
+    let (start, end) = page_range.unwrap_or((0, pages.len().saturating_sub(1)));
+    if start > end || end >= pages.len() {
+        return Err(JsValue::from_str("Invalid page range"));
+    }
+    let selected = &pages[start..=end];
+    let sections = sections::slice_sections(&sections, start, end);
+    let pages = notes::resolve_notes(selected, &NoteOptions::default());
+    let sections = sections::remap_sections(&sections, selected, &pages);
+    let pages = sections::fill_running_heads(&pages, &sections);
+    let toc = sections::table_of_contents(&pages, &sections);
+    epub::write_fixed_layout_epub(&pages, &metadata, &options, &toc, None, image_bytes)
+        .map_err(|e| JsValue::from_str(&e))
+}
+
+/// Load project from bytes (zip container, MessagePack or plain JSON); embedded images
+/// are restored into the image cache
 #[wasm_bindgen]
 pub fn load_project(data: &[u8]) -> Result<JsValue, JsValue> {
-    let project: BookProjectData = serde_json::from_slice(data)
-        .map_err(|e| JsValue::from_str(&format!("Invalid project file: {}", e)))?;
+    let (project, images) = archive::read_project(data).map_err(|e| JsValue::from_str(&e))?;
+    for image in images {
+        image_cache::cache_image(&image.id, image.data);
+    }
     serde_wasm_bindgen::to_value(&project).map_err(|e| JsValue::from_str(&e.to_string()))
 }
 
-/// Save project to bytes
+/// Save project to a zip container bundling the cached images its layers use
 #[wasm_bindgen]
 pub fn save_project(project_js: JsValue) -> Result<Vec<u8>, JsValue> {
     let project: BookProjectData = serde_wasm_bindgen::from_value(project_js)
         .map_err(|e| JsValue::from_str(&e.to_string()))?;
-    serde_json::to_vec_pretty(&project).map_err(|e| JsValue::from_str(&e.to_string()))
+    let images = image_cache::snapshot_images(&archive::referenced_image_ids(&project));
+    archive::write_archive(&project, &images).map_err(|e| JsValue::from_str(&e))
+}
+
+/// Re-encode project bytes as a zip container using the given encoding
+/// (`"json"` or `"messagepack"`)
+#[wasm_bindgen]
+pub fn convert_project(data: &[u8], encoding: JsValue) -> Result<Vec<u8>, JsValue> {
+    let encoding: ProjectEncoding = serde_wasm_bindgen::from_value(encoding)
+        .map_err(|e| JsValue::from_str(&e.to_string()))?;
+    archive::convert_project(data, encoding).map_err(|e| JsValue::from_str(&e))
 }
 
-/// Cache image data
+/// Cache image data; the bytes copied in from JS are stored as they are
 #[wasm_bindgen]
-pub fn cache_image(id: &str, data: &[u8]) {
-    image_cache::cache_image(id, data.to_vec());
+pub fn cache_image(id: &str, data: Vec<u8>) {
+    image_cache::cache_image(id, data);
 }
 
 /// Get cached image
 #[wasm_bindgen]
-pub fn get_image(id: &str) -> Option<Vec<u8>> {
-    image_cache::get_cached_image(id)
+pub fn get_image(id: &str) -> Option<js_sys::Uint8Array> {
+    image_cache::get_cached_array(id)
 }
 
 /// Clear image cache
@@ -123,21 +233,502 @@ pub fn clear_image_cache() {
     image_cache::clear_cache();
 }
 
-/// Update layer (returns updated layer)
+/// Remove a cached image
+#[wasm_bindgen]
+pub fn remove_image(id: &str) -> bool {
+    image_cache::remove_cached_image(id)
+}
+
+/// Set the image cache budget in bytes (evicts least recently used images)
 #[wasm_bindgen]
-pub fn update_layer(layer_js: JsValue, updates_js: JsValue) -> Result<JsValue, JsValue> {
-    let mut layer: LayerObject = serde_wasm_bindgen::from_value(layer_js)
+pub fn set_image_cache_budget(max_bytes: usize) {
+    image_cache::set_cache_budget(max_bytes);
+}
+
+/// Evict least recently used images until the cache fits in `budget_bytes`
+/// (returns the number of evicted images)
+#[wasm_bindgen]
+pub fn evict_to_budget(budget_bytes: usize) -> usize {
+    image_cache::evict_to_budget(budget_bytes)
+}
+
+/// Image cache stats (count, sizes, hits, misses, evictions)
+#[wasm_bindgen]
+pub fn get_cache_stats() -> Result<JsValue, JsValue> {
+    let stats = image_cache::cache_stats().ok_or_else(|| JsValue::from_str("Image cache unavailable"))?;
+    serde_wasm_bindgen::to_value(&stats).map_err(|e| JsValue::from_str(&e.to_string()))
+}
+
+/// Detect dimensions and format of image bytes
+#[wasm_bindgen]
+pub fn detect_image_info(data: &[u8]) -> Result<JsValue, JsValue> {
+    let info = image_cache::detect_image_info(data)
+        .ok_or_else(|| JsValue::from_str("Unrecognized image data"))?;
+    serde_wasm_bindgen::to_value(&info).map_err(|e| JsValue::from_str(&e.to_string()))
+}
+
+/// Dimensions and format of a cached image
+#[wasm_bindgen]
+pub fn get_image_info(id: &str) -> Result<JsValue, JsValue> {
+    let info = image_cache::get_cached_image_info(id)
+        .ok_or_else(|| JsValue::from_str(&format!("Image not found: {}", id)))?;
+    serde_wasm_bindgen::to_value(&info).map_err(|e| JsValue::from_str(&e.to_string()))
+}
+
+/// Update a layer of `page` (returns the updated layer, or null when the
+/// page has no such layer)
+///
+/// A locked layer refuses every update except one that only unlocks it,
+/// unless `force` is set; the error is the same `LockViolation` the desktop
+/// command returns.
+#[wasm_bindgen]
+pub fn update_layer(
+    page_index: usize,
+    page_js: JsValue,
+    layer_id: &str,
+    updates_js: JsValue,
+    force: Option<bool>,
+) -> Result<JsValue, JsValue> {
+    let page: PageData = serde_wasm_bindgen::from_value(page_js)
         .map_err(|e| JsValue::from_str(&e.to_string()))?;
     let updates: LayerUpdates = serde_wasm_bindgen::from_value(updates_js)
         .map_err(|e| JsValue::from_str(&e.to_string()))?;
-    
-    if let Some(bounds) = updates.bounds { layer.bounds = bounds; }
-    if let Some(visible) = updates.visible { layer.visible = visible; }
-    if let Some(locked) = updates.locked { layer.locked = locked; }
-    if let Some(z_index) = updates.z_index { layer.z_index = z_index; }
-    if let Some(opacity) = updates.opacity { layer.opacity = opacity; }
-    if let Some(content) = updates.content { layer.content = Some(content); }
-    
+
+    let layer = layers::update_page_layer(page_index, &page.layers, layer_id, &updates, force.unwrap_or(false))
+        .map_err(|v| serde_wasm_bindgen::to_value(&v).unwrap_or_else(|_| JsValue::from_str(&v.message)))?;
+    match layer {
+        Some(layer) => serde_wasm_bindgen::to_value(&layer).map_err(|e| JsValue::from_str(&e.to_string())),
+        None => Ok(JsValue::NULL),
+    }
+}
+
+/// Run a layer operation on a page's layers and return the updated layers
+fn with_layers(
+    layers_js: JsValue,
+    op: impl FnOnce(&mut [LayerObject]) -> Result<(), String>,
+) -> Result<JsValue, JsValue> {
+    let mut layers: Vec<LayerObject> = serde_wasm_bindgen::from_value(layers_js)
+        .map_err(|e| JsValue::from_str(&e.to_string()))?;
+    op(&mut layers).map_err(|e| JsValue::from_str(&e))?;
+    serde_wasm_bindgen::to_value(&layers).map_err(|e| JsValue::from_str(&e.to_string()))
+}
+
+/// Bring a layer to the front (returns updated layers)
+#[wasm_bindgen]
+pub fn bring_to_front(layers_js: JsValue, layer_id: &str) -> Result<JsValue, JsValue> {
+    with_layers(layers_js, |l| layers::bring_to_front(l, layer_id))
+}
+
+/// Send a layer to the back (returns updated layers)
+#[wasm_bindgen]
+pub fn send_to_back(layers_js: JsValue, layer_id: &str) -> Result<JsValue, JsValue> {
+    with_layers(layers_js, |l| layers::send_to_back(l, layer_id))
+}
+
+/// Move a layer up one position in z-order (returns updated layers)
+#[wasm_bindgen]
+pub fn move_layer_up(layers_js: JsValue, layer_id: &str) -> Result<JsValue, JsValue> {
+    with_layers(layers_js, |l| layers::move_up(l, layer_id))
+}
+
+/// Move a layer down one position in z-order (returns updated layers)
+#[wasm_bindgen]
+pub fn move_layer_down(layers_js: JsValue, layer_id: &str) -> Result<JsValue, JsValue> {
+    with_layers(layers_js, |l| layers::move_down(l, layer_id))
+}
+
+/// Sort layers by z-index and renumber from 0 (returns updated layers)
+#[wasm_bindgen]
+pub fn normalize_z_indices(layers_js: JsValue) -> Result<JsValue, JsValue> {
+    with_layers(layers_js, |l| {
+        layers::normalize_z_indices(l);
+        Ok(())
+    })
+}
+
+/// Align layers on an edge or center: "left", "center", "right", "top",
+/// "middle" or "bottom" (returns updated layers)
+#[wasm_bindgen]
+pub fn align_layers(layers_js: JsValue, layer_ids_js: JsValue, alignment: JsValue) -> Result<JsValue, JsValue> {
+    let layer_ids: Vec<String> = serde_wasm_bindgen::from_value(layer_ids_js)
+        .map_err(|e| JsValue::from_str(&e.to_string()))?;
+    let alignment: LayerAlignment = serde_wasm_bindgen::from_value(alignment)
+        .map_err(|e| JsValue::from_str(&e.to_string()))?;
+    with_layers(layers_js, |l| layers::align_layers(l, &layer_ids, alignment).map(|_| ()))
+}
+
+/// Remove layers painted more than once, such as fake-bold text
+/// (returns `{ pages, groups, removedLayers }`)
+#[wasm_bindgen]
+pub fn deduplicate_layers(pages_js: JsValue, options_js: JsValue) -> Result<JsValue, JsValue> {
+    let pages: Vec<PageData> = serde_wasm_bindgen::from_value(pages_js)
+        .map_err(|e| JsValue::from_str(&e.to_string()))?;
+    let options: Option<DedupOptions> = serde_wasm_bindgen::from_value(options_js)
+        .map_err(|e| JsValue::from_str(&e.to_string()))?;
+    let result = layer_cleanup::deduplicate_pages(pages, &options.unwrap_or_default());
+    serde_wasm_bindgen::to_value(&result).map_err(|e| JsValue::from_str(&e.to_string()))
+}
+
+/// Remove layers that paint nothing (returns `{ pages, pruned, removedLayers }`)
+#[wasm_bindgen]
+pub fn prune_layers(pages_js: JsValue, options_js: JsValue) -> Result<JsValue, JsValue> {
+    let pages: Vec<PageData> = serde_wasm_bindgen::from_value(pages_js)
+        .map_err(|e| JsValue::from_str(&e.to_string()))?;
+    let options: Option<PruneOptions> = serde_wasm_bindgen::from_value(options_js)
+        .map_err(|e| JsValue::from_str(&e.to_string()))?;
+    let result = layer_cleanup::prune_pages(pages, &options.unwrap_or_default());
+    serde_wasm_bindgen::to_value(&result).map_err(|e| JsValue::from_str(&e.to_string()))
+}
+
+/// Ids of the layers matching a query, per page (returns `[{ pageIndex, layerIds }]`)
+#[wasm_bindgen]
+pub fn query_layers(pages_js: JsValue, query: &str) -> Result<JsValue, JsValue> {
+    let pages: Vec<PageData> = serde_wasm_bindgen::from_value(pages_js)
+        .map_err(|e| JsValue::from_str(&e.to_string()))?;
+    let matches = layer_query::query_layers(&pages, query).map_err(|e| JsValue::from_str(&e))?;
+    serde_wasm_bindgen::to_value(&matches).map_err(|e| JsValue::from_str(&e.to_string()))
+}
+
+/// Apply a style transform to the layers matching a query (returns
+/// `{ pages, changes, skipped }`; `pages` is empty for a dry run)
+#[wasm_bindgen]
+pub fn transform_layers(pages_js: JsValue, query: &str, transform_js: JsValue, dry_run: bool) -> Result<JsValue, JsValue> {
+    let pages: Vec<PageData> = serde_wasm_bindgen::from_value(pages_js)
+        .map_err(|e| JsValue::from_str(&e.to_string()))?;
+    let transform: StyleTransform = serde_wasm_bindgen::from_value(transform_js)
+        .map_err(|e| JsValue::from_str(&e.to_string()))?;
+    let result = layer_transform::transform_pages(pages, query, &transform, dry_run).map_err(|e| JsValue::from_str(&e))?;
+    serde_wasm_bindgen::to_value(&result).map_err(|e| JsValue::from_str(&e.to_string()))
+}
+
+/// Record the roles, tags and styles given to the layers of `imported` in
+/// `corrected` as an import mapping
+#[wasm_bindgen]
+pub fn record_import_mapping(name: &str, imported_js: JsValue, corrected_js: JsValue) -> Result<JsValue, JsValue> {
+    let imported: Vec<PageData> = serde_wasm_bindgen::from_value(imported_js)
+        .map_err(|e| JsValue::from_str(&e.to_string()))?;
+    let corrected: Vec<PageData> = serde_wasm_bindgen::from_value(corrected_js)
+        .map_err(|e| JsValue::from_str(&e.to_string()))?;
+    let mapping = import_mapping::record_mapping(name.trim(), &imported, &corrected);
+    serde_wasm_bindgen::to_value(&mapping).map_err(|e| JsValue::from_str(&e.to_string()))
+}
+
+/// Apply an import mapping to the unlocked layers; returns the updated pages
+#[wasm_bindgen]
+pub fn apply_import_mapping(pages_js: JsValue, mapping_js: JsValue) -> Result<JsValue, JsValue> {
+    let mut pages: Vec<PageData> = serde_wasm_bindgen::from_value(pages_js)
+        .map_err(|e| JsValue::from_str(&e.to_string()))?;
+    let mapping: ImportMapping = serde_wasm_bindgen::from_value(mapping_js)
+        .map_err(|e| JsValue::from_str(&e.to_string()))?;
+    import_mapping::apply_mapping(&mut pages, &mapping);
+    serde_wasm_bindgen::to_value(&pages).map_err(|e| JsValue::from_str(&e.to_string()))
+}
+
+/// Smart quotes, dashes, ellipses and French spacing in the unlocked text
+/// layers (returns `{ pages, changed }`)
+#[wasm_bindgen]
+pub fn clean_typography(pages_js: JsValue, options_js: JsValue) -> Result<JsValue, JsValue> {
+    let pages: Vec<PageData> = serde_wasm_bindgen::from_value(pages_js)
+        .map_err(|e| JsValue::from_str(&e.to_string()))?;
+    let options: Option<TypographyOptions> = serde_wasm_bindgen::from_value(options_js)
+        .map_err(|e| JsValue::from_str(&e.to_string()))?;
+    let result = typography::clean_pages(pages, &options.unwrap_or_default());
+    serde_wasm_bindgen::to_value(&result).map_err(|e| JsValue::from_str(&e.to_string()))
+}
+
+/// Every footnote and endnote with the number it prints with, in reading order
+#[wasm_bindgen]
+pub fn list_notes(pages_js: JsValue, options_js: JsValue) -> Result<JsValue, JsValue> {
+    let pages: Vec<PageData> = serde_wasm_bindgen::from_value(pages_js)
+        .map_err(|e| JsValue::from_str(&e.to_string()))?;
+    let options: Option<NoteOptions> = serde_wasm_bindgen::from_value(options_js)
+        .map_err(|e| JsValue::from_str(&e.to_string()))?;
+    let result = notes::number_notes(&pages, options.unwrap_or_default().footnote_numbering);
+    serde_wasm_bindgen::to_value(&result).map_err(|e| JsValue::from_str(&e.to_string()))
+}
+
+/// Insert, change or remove a note (returns `{ pages, noteId, notes }`)
+#[wasm_bindgen]
+pub fn edit_note(pages_js: JsValue, edit_js: JsValue, options_js: JsValue) -> Result<JsValue, JsValue> {
+    let mut pages: Vec<PageData> = serde_wasm_bindgen::from_value(pages_js)
+        .map_err(|e| JsValue::from_str(&e.to_string()))?;
+    let edit: NoteEdit = serde_wasm_bindgen::from_value(edit_js)
+        .map_err(|e| JsValue::from_str(&e.to_string()))?;
+    let options: Option<NoteOptions> = serde_wasm_bindgen::from_value(options_js)
+        .map_err(|e| JsValue::from_str(&e.to_string()))?;
+    let note_id = notes::edit_note(&mut pages, edit).map_err(|e| JsValue::from_str(&e))?;
+    let notes = notes::number_notes(&pages, options.unwrap_or_default().footnote_numbering);
+    serde_wasm_bindgen::to_value(&NoteEditResult { pages, note_id, notes })
+        .map_err(|e| JsValue::from_str(&e.to_string()))
+}
+
+/// Pages with their notes set as layers, as PDF and EPUB export prints them
+#[wasm_bindgen]
+pub fn resolve_notes(pages_js: JsValue, options_js: JsValue) -> Result<JsValue, JsValue> {
+    let pages: Vec<PageData> = serde_wasm_bindgen::from_value(pages_js)
+        .map_err(|e| JsValue::from_str(&e.to_string()))?;
+    let options: Option<NoteOptions> = serde_wasm_bindgen::from_value(options_js)
+        .map_err(|e| JsValue::from_str(&e.to_string()))?;
+    let result = notes::resolve_notes(&pages, &options.unwrap_or_default());
+    serde_wasm_bindgen::to_value(&result).map_err(|e| JsValue::from_str(&e.to_string()))
+}
+
+/// OCR'd text below `threshold` confidence (0.8 when omitted) awaiting
+/// review, grouped by page
+#[wasm_bindgen]
+pub fn ocr_review_queue(pages_js: JsValue, threshold: Option<f32>) -> Result<JsValue, JsValue> {
+    let pages: Vec<PageData> = serde_wasm_bindgen::from_value(pages_js)
+        .map_err(|e| JsValue::from_str(&e.to_string()))?;
+    let queue = ocr_review::review_queue(&pages, threshold.unwrap_or(ocr_review::DEFAULT_REVIEW_THRESHOLD));
+    serde_wasm_bindgen::to_value(&queue).map_err(|e| JsValue::from_str(&e.to_string()))
+}
+
+/// Accept or correct an OCR'd layer (returns `{ pages, queue }`)
+#[wasm_bindgen]
+pub fn review_ocr_layer(
+    pages_js: JsValue,
+    page_index: usize,
+    layer_id: &str,
+    action_js: JsValue,
+    threshold: Option<f32>,
+) -> Result<JsValue, JsValue> {
+    let mut pages: Vec<PageData> = serde_wasm_bindgen::from_value(pages_js)
+        .map_err(|e| JsValue::from_str(&e.to_string()))?;
+    let action: OcrReviewAction = serde_wasm_bindgen::from_value(action_js)
+        .map_err(|e| JsValue::from_str(&e.to_string()))?;
+    ocr_review::review_layer(&mut pages, page_index, layer_id, action).map_err(|e| JsValue::from_str(&e))?;
+    let queue = ocr_review::review_queue(&pages, threshold.unwrap_or(ocr_review::DEFAULT_REVIEW_THRESHOLD));
+    serde_wasm_bindgen::to_value(&OcrReviewResult { pages, queue }).map_err(|e| JsValue::from_str(&e.to_string()))
+}
+
+/// Built-in page size presets with sizes and suggested margins
+#[wasm_bindgen]
+pub fn list_page_size_presets() -> Result<JsValue, JsValue> {
+    serde_wasm_bindgen::to_value(&page_setup::page_size_presets()).map_err(|e| JsValue::from_str(&e.to_string()))
+}
+
+/// Change the trim size of a document; `mode` is "scale" (default),
+/// "center" or "topLeft" (returns the resized document)
+#[wasm_bindgen]
+pub fn resize_document(document_js: JsValue, width: f32, height: f32, mode: JsValue) -> Result<JsValue, JsValue> {
+    let mut document: DocumentData = serde_wasm_bindgen::from_value(document_js)
+        .map_err(|e| JsValue::from_str(&e.to_string()))?;
+    let mode: Option<ResizeMode> = serde_wasm_bindgen::from_value(mode)
+        .map_err(|e| JsValue::from_str(&e.to_string()))?;
+    page_setup::resize_document(&mut document, width, height, mode.unwrap_or_default())
+        .map_err(|e| JsValue::from_str(&e))?;
+    serde_wasm_bindgen::to_value(&document).map_err(|e| JsValue::from_str(&e.to_string()))
+}
+
+/// Repaginate the pages as one flow on pages of `page_setup` (US Letter with
+/// 1 in margins when absent), honouring each paragraph's keep and
+/// widow/orphan controls
+#[wasm_bindgen]
+pub fn reflow_pages(pages_js: JsValue, page_setup_js: JsValue) -> Result<JsValue, JsValue> {
+    let pages: Vec<PageData> = serde_wasm_bindgen::from_value(pages_js)
+        .map_err(|e| JsValue::from_str(&e.to_string()))?;
+    let page_setup: Option<PageSetup> = serde_wasm_bindgen::from_value(page_setup_js)
+        .map_err(|e| JsValue::from_str(&e.to_string()))?;
+    let pages = reflow::reflow(&pages, &page_setup.unwrap_or_default());
+    serde_wasm_bindgen::to_value(&pages).map_err(|e| JsValue::from_str(&e.to_string()))
+}
+
+/// Infer layout guides from the pages' layers and store them on each page
+#[wasm_bindgen]
+pub fn detect_layout_guides(pages_js: JsValue) -> Result<JsValue, JsValue> {
+    let mut pages: Vec<PageData> = serde_wasm_bindgen::from_value(pages_js)
+        .map_err(|e| JsValue::from_str(&e.to_string()))?;
+    layout_guides::apply_guides(&mut pages);
+    serde_wasm_bindgen::to_value(&pages).map_err(|e| JsValue::from_str(&e.to_string()))
+}
+
+/// Stamp the background, header and footer layers and guides of page
+/// `source` onto the `targets`; returns the updated pages
+#[wasm_bindgen]
+pub fn apply_page_layout(
+    pages_js: JsValue,
+    source: usize,
+    targets_js: JsValue,
+    options_js: JsValue,
+) -> Result<JsValue, JsValue> {
+    let mut pages: Vec<PageData> = serde_wasm_bindgen::from_value(pages_js)
+        .map_err(|e| JsValue::from_str(&e.to_string()))?;
+    let targets: Vec<usize> = serde_wasm_bindgen::from_value(targets_js)
+        .map_err(|e| JsValue::from_str(&e.to_string()))?;
+    let options: Option<PageLayoutOptions> = serde_wasm_bindgen::from_value(options_js)
+        .map_err(|e| JsValue::from_str(&e.to_string()))?;
+    page_layout::apply_page_layout(&mut pages, source, &targets, &options.unwrap_or_default())
+        .map_err(|e| JsValue::from_str(&e))?;
+    serde_wasm_bindgen::to_value(&pages).map_err(|e| JsValue::from_str(&e.to_string()))
+}
+
+/// The sections of a book in page order, with their page ranges, titles,
+/// chapter numbers and first page labels
+#[wasm_bindgen]
+pub fn list_sections(pages_js: JsValue, sections_js: JsValue) -> Result<JsValue, JsValue> {
+    let pages: Vec<PageData> = serde_wasm_bindgen::from_value(pages_js)
+        .map_err(|e| JsValue::from_str(&e.to_string()))?;
+    let sections: Vec<Section> = serde_wasm_bindgen::from_value(sections_js)
+        .map_err(|e| JsValue::from_str(&e.to_string()))?;
+    serde_wasm_bindgen::to_value(&sections::section_infos(&pages, &sections))
+        .map_err(|e| JsValue::from_str(&e.to_string()))
+}
+
+/// Add or replace a section, then relabel the pages and stamp the section
+/// master pages
+#[wasm_bindgen]
+pub fn set_section(pages_js: JsValue, sections_js: JsValue, section_js: JsValue) -> Result<JsValue, JsValue> {
+    let mut pages: Vec<PageData> = serde_wasm_bindgen::from_value(pages_js)
+        .map_err(|e| JsValue::from_str(&e.to_string()))?;
+    let mut sections: Vec<Section> = serde_wasm_bindgen::from_value(sections_js)
+        .map_err(|e| JsValue::from_str(&e.to_string()))?;
+    let section: Section = serde_wasm_bindgen::from_value(section_js)
+        .map_err(|e| JsValue::from_str(&e.to_string()))?;
+    let section_id = sections::set_section(&mut pages, &mut sections, section).map_err(|e| JsValue::from_str(&e))?;
+    serde_wasm_bindgen::to_value(&SectionsResult { pages, sections, section_id })
+        .map_err(|e| JsValue::from_str(&e.to_string()))
+}
+
+/// Remove a section; its pages join the section before
+#[wasm_bindgen]
+pub fn remove_section(pages_js: JsValue, sections_js: JsValue, id: &str) -> Result<JsValue, JsValue> {
+    let mut pages: Vec<PageData> = serde_wasm_bindgen::from_value(pages_js)
+        .map_err(|e| JsValue::from_str(&e.to_string()))?;
+    let mut sections: Vec<Section> = serde_wasm_bindgen::from_value(sections_js)
+        .map_err(|e| JsValue::from_str(&e.to_string()))?;
+    sections::remove_section(&mut pages, &mut sections, id).map_err(|e| JsValue::from_str(&e))?;
+    serde_wasm_bindgen::to_value(&SectionsResult { pages, sections, section_id: id.to_string() })
+        .map_err(|e| JsValue::from_str(&e.to_string()))
+}
+
+/// Snap bounds being placed to a page's guides
+#[wasm_bindgen]
+pub fn snap_to_guides(
+    guides_js: JsValue,
+    bounds_js: JsValue,
+    baseline: Option<f32>,
+    threshold: Option<f32>,
+) -> Result<JsValue, JsValue> {
+    let guides: PageGuides = serde_wasm_bindgen::from_value(guides_js)
+        .map_err(|e| JsValue::from_str(&e.to_string()))?;
+    let bounds: Bounds = serde_wasm_bindgen::from_value(bounds_js)
+        .map_err(|e| JsValue::from_str(&e.to_string()))?;
+    let threshold = threshold.unwrap_or(layout_guides::DEFAULT_SNAP_THRESHOLD);
+    serde_wasm_bindgen::to_value(&layout_guides::snap_bounds(&guides, bounds, baseline, threshold))
+        .map_err(|e| JsValue::from_str(&e.to_string()))
+}
+
+/// Group pdf.js text spans into the pages → blocks → lines → spans hierarchy
+/// (`pages_js`: `[{ pageIndex, width, height, spans: [{ text, bounds, fontSize, fontName?, color? }] }]`)
+#[wasm_bindgen]
+pub fn extract_structured_text(pages_js: JsValue) -> Result<JsValue, JsValue> {
+    let pages: Vec<SpanPage> = serde_wasm_bindgen::from_value(pages_js)
+        .map_err(|e| JsValue::from_str(&e.to_string()))?;
+    serde_wasm_bindgen::to_value(&text_structure::structure_pages(pages))
+        .map_err(|e| JsValue::from_str(&e.to_string()))
+}
+
+/// Same grouping as `extract_structured_text`, returned as plain text
+#[wasm_bindgen]
+pub fn extract_plain_text(pages_js: JsValue) -> Result<String, JsValue> {
+    let pages: Vec<SpanPage> = serde_wasm_bindgen::from_value(pages_js)
+        .map_err(|e| JsValue::from_str(&e.to_string()))?;
+    Ok(text_structure::structure_pages(pages).plain_text())
+}
+
+/// Headings, body, captions and footnotes of the pages' text layers, with
+/// the heading tree
+#[wasm_bindgen]
+pub fn extract_structure(pages_js: JsValue) -> Result<JsValue, JsValue> {
+    let pages: Vec<PageData> = serde_wasm_bindgen::from_value(pages_js)
+        .map_err(|e| JsValue::from_str(&e.to_string()))?;
+    serde_wasm_bindgen::to_value(&doc_structure::extract_structure(&pages))
+        .map_err(|e| JsValue::from_str(&e.to_string()))
+}
+
+/// A page's text in reading order (returns `{ pageIndex, text, runs? }`)
+#[wasm_bindgen]
+pub fn get_page_text(page_js: JsValue, options_js: JsValue) -> Result<JsValue, JsValue> {
+    let page: PageData = serde_wasm_bindgen::from_value(page_js)
+        .map_err(|e| JsValue::from_str(&e.to_string()))?;
+    let options: Option<PageTextOptions> = serde_wasm_bindgen::from_value(options_js)
+        .map_err(|e| JsValue::from_str(&e.to_string()))?;
+    serde_wasm_bindgen::to_value(&text_structure::page_text(&page, &options.unwrap_or_default()))
+        .map_err(|e| JsValue::from_str(&e.to_string()))
+}
+
+/// Data URL of an image layer, from its URL or the image cache
+fn image_data_url(layer: &LayerObject) -> Option<String> {
+    use base64::Engine;
+    let url = layer.image_url.as_deref()?;
+    if url.starts_with("data:") {
+        return Some(url.to_string());
+    }
+    let id = url.strip_prefix("image://")?;
+    let data = image_cache::get_cached_image(id)?;
+    let mime = image_cache::detect_image_info(&data).map(|i| i.mime_type)?;
+    Some(format!("data:{};base64,{}", mime, base64::engine::general_purpose::STANDARD.encode(data)))
+}
+
+/// Layers as an SVG document for the clipboard (`undefined` for no layers)
+#[wasm_bindgen]
+pub fn copy_layers_svg(layers_js: JsValue) -> Result<Option<String>, JsValue> {
+    let layers: Vec<LayerObject> = serde_wasm_bindgen::from_value(layers_js)
+        .map_err(|e| JsValue::from_str(&e.to_string()))?;
+    Ok(clipboard::layers_to_svg(&layers, image_data_url))
+}
+
+/// Text of the text layers in reading order, for the clipboard
+#[wasm_bindgen]
+pub fn copy_layers_text(layers_js: JsValue) -> Result<String, JsValue> {
+    let layers: Vec<LayerObject> = serde_wasm_bindgen::from_value(layers_js)
+        .map_err(|e| JsValue::from_str(&e.to_string()))?;
+    Ok(clipboard::layers_to_text(&layers))
+}
+
+/// Layers from pasted SVG, ids `{id_prefix}-{n}` (image layers keep data URLs)
+#[wasm_bindgen]
+pub fn paste_svg(svg: &str, id_prefix: &str) -> Result<JsValue, JsValue> {
+    let layers = clipboard::svg_to_layers(svg, id_prefix).map_err(|e| JsValue::from_str(&e))?;
+    serde_wasm_bindgen::to_value(&layers).map_err(|e| JsValue::from_str(&e.to_string()))
+}
+
+/// Text layer from pasted plain text, top-left at (`x`, `y`) (`null` for blank text)
+#[wasm_bindgen]
+pub fn paste_text(text: &str, id: &str, x: f32, y: f32) -> Result<JsValue, JsValue> {
+    let layer = clipboard::text_to_layer(text, id.to_string(), x, y);
+    serde_wasm_bindgen::to_value(&layer).map_err(|e| JsValue::from_str(&e.to_string()))
+}
+
+/// Vector layer `id` for a rule, ornament, frame or badge, top-left at
+/// (`x`, `y`) and filled with `color` (black when omitted)
+#[wasm_bindgen]
+pub fn create_decoration(decoration_js: JsValue, id: &str, x: f32, y: f32, color: Option<String>) -> Result<JsValue, JsValue> {
+    let decoration: Decoration =
+        serde_wasm_bindgen::from_value(decoration_js).map_err(|e| JsValue::from_str(&e.to_string()))?;
+    let layer = decoration.layer(id.to_string(), x, y, color.as_deref()).map_err(|e| JsValue::from_str(&e))?;
+    serde_wasm_bindgen::to_value(&layer).map_err(|e| JsValue::from_str(&e.to_string()))
+}
+
+/// Image layer `id` for dropped image bytes on `page`, fitted inside
+/// `margins` (1 in when omitted) and centered on (`x`, `y`) when given;
+/// the image is cached and the layer stacked above the page's others
+#[wasm_bindgen]
+pub fn place_image(
+    data: Vec<u8>,
+    page_js: JsValue,
+    margins_js: JsValue,
+    x: Option<f32>,
+    y: Option<f32>,
+    id: &str,
+) -> Result<JsValue, JsValue> {
+    let page: PageData = serde_wasm_bindgen::from_value(page_js).map_err(|e| JsValue::from_str(&e.to_string()))?;
+    let margins: Option<Margins> =
+        serde_wasm_bindgen::from_value(margins_js).map_err(|e| JsValue::from_str(&e.to_string()))?;
+    let setup = PageSetup { width: page.width, height: page.height, margins: margins.unwrap_or_default(), bleed: 0.0 };
+    let mut layer = image_place::image_layer(id.to_string(), &data, &setup, x.zip(y)).map_err(|e| JsValue::from_str(&e))?;
+    layer.z_index = page.layers.iter().map(|l| l.z_index + 1).max().unwrap_or(0);
+    image_cache::cache_image(id, data);
     serde_wasm_bindgen::to_value(&layer).map_err(|e| JsValue::from_str(&e.to_string()))
 }
 
@@ -155,18 +746,117 @@ pub fn export_with_result(pages_js: JsValue, metadata_js: JsValue, format: &str)
         _ => Err(format!("Unsupported format: {}", format)),
     };
     
-    let response = match result {
-        Ok(data) => ExportResult {
-            success: true,
-            message: format!("Exported to {} successfully", format),
-            data: Some(data),
-        },
-        Err(e) => ExportResult {
-            success: false,
-            message: e,
-            data: None,
-        },
+    let (response, data) = match result {
+        Ok(data) => (
+            ExportResult {
+                success: true,
+                message: format!("Exported to {} successfully", format),
+                output_path: None,
+                data: None,
+            },
+            Some(data),
+        ),
+        Err(e) => (ExportResult { success: false, message: e, output_path: None, data: None }, None),
     };
-    
-    serde_wasm_bindgen::to_value(&response).map_err(|e| JsValue::from_str(&e.to_string()))
+
+    // serde would write the bytes as an array of numbers; a Uint8Array is
+    // one copy and what the download code takes
+    let value = serde_wasm_bindgen::to_value(&response).map_err(|e| JsValue::from_str(&e.to_string()))?;
+    if let Some(data) = data {
+        js_sys::Reflect::set(&value, &JsValue::from_str("data"), &js_sys::Uint8Array::from(&data[..]))?;
+    }
+    Ok(value)
+}
+
+/// Parse a raw (possibly subset) font name into family, weight and style
+#[wasm_bindgen]
+pub fn parse_font_name(raw: &str) -> Result<JsValue, JsValue> {
+    serde_wasm_bindgen::to_value(&font_names::parse_font_name(raw))
+        .map_err(|e| JsValue::from_str(&e.to_string()))
+}
+
+/// Canonical family for a raw font name ("ABCDEF+Arial-BoldMT" -> "Arial")
+#[wasm_bindgen]
+pub fn get_canonical_font_name(raw: &str) -> String {
+    font_names::get_canonical_name(raw)
+}
+
+/// Match a raw font name against the families available to the page
+#[wasm_bindgen]
+pub fn match_font(raw: &str, available_js: JsValue) -> Result<JsValue, JsValue> {
+    let available: Vec<String> = serde_wasm_bindgen::from_value(available_js)
+        .map_err(|e| JsValue::from_str(&e.to_string()))?;
+    serde_wasm_bindgen::to_value(&font_names::match_font(raw, &available, FONT_MATCH_THRESHOLD))
+        .map_err(|e| JsValue::from_str(&e.to_string()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_canonical_font_name() {
+        assert_eq!(get_canonical_font_name("ABCDEF+Arial-BoldMT"), "Arial");
+    }
+
+    #[test]
+    fn test_image_cache_exports() {
+        cache_image("wasm-test-a", vec![0u8; 64]);
+        cache_image("wasm-test-b", vec![0u8; 64]);
+        assert!(remove_image("wasm-test-a"));
+        assert!(!remove_image("wasm-test-a"));
+        assert!(evict_to_budget(0) >= 1);
+        assert!(image_cache::get_cached_image("wasm-test-b").is_none());
+    }
+}
+
+/// Exports that take or return JS values, run with `wasm-pack test`
+#[cfg(all(test, target_arch = "wasm32"))]
+mod wasm_tests {
+    use super::*;
+    use vortex_core::test_util::{layer, page};
+    use wasm_bindgen_test::wasm_bindgen_test;
+
+    fn to_js<T: serde::Serialize>(value: &T) -> JsValue {
+        serde_wasm_bindgen::to_value(value).unwrap()
+    }
+
+    #[wasm_bindgen_test]
+    fn test_update_layer_checks_locks() {
+        let page_js = to_js(&page(0, vec![layer("a", "text").with("locked", true).build()]));
+        let move_it = to_js(&serde_json::json!({ "bounds": { "x": 5, "y": 5, "width": 10, "height": 10 } }));
+
+        let refused = update_layer(0, page_js.clone(), "a", move_it.clone(), None).unwrap_err();
+        let violation: serde_json::Value = serde_wasm_bindgen::from_value(refused).unwrap();
+        assert_eq!(violation["layerIds"][0], "a");
+
+        let moved: LayerObject =
+            serde_wasm_bindgen::from_value(update_layer(0, page_js.clone(), "a", move_it.clone(), Some(true)).unwrap()).unwrap();
+        assert_eq!(moved.bounds.x, 5.0);
+        assert!(update_layer(0, page_js, "missing", move_it, None).unwrap().is_null());
+    }
+
+    #[wasm_bindgen_test]
+    fn test_query_layers() {
+        let pages = vec![page(0, vec![layer("t", "text").build(), layer("i", "image").build()])];
+        let matches: serde_json::Value =
+            serde_wasm_bindgen::from_value(query_layers(to_js(&pages), "type:image").unwrap()).unwrap();
+        assert_eq!(matches.as_array().map(Vec::len), Some(1));
+        assert!(query_layers(to_js(&pages), "bogus:1").is_err());
+    }
+
+    #[wasm_bindgen_test]
+    fn test_clipboard_text_round_trip() {
+        let pasted: LayerObject = serde_wasm_bindgen::from_value(paste_text("Hello", "p1", 10.0, 20.0).unwrap()).unwrap();
+        assert_eq!(pasted.bounds.x, 10.0);
+        assert_eq!(copy_layers_text(to_js(&vec![pasted])).unwrap(), "Hello");
+    }
+
+    #[wasm_bindgen_test]
+    fn test_match_font() {
+        let available = to_js(&vec!["Arial".to_string(), "Georgia".to_string()]);
+        let matched: serde_json::Value =
+            serde_wasm_bindgen::from_value(match_font("ABCDEF+Arial-BoldMT", available).unwrap()).unwrap();
+        assert!(matched.to_string().contains("Arial"));
+    }
 }
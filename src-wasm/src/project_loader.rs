@@ -0,0 +1,71 @@
+//! Incremental project loading for WASM
+//! Reads the project skeleton up front and pages on demand, so the editor can
+//! show the first pages of a large project before the rest are parsed
+
+use crate::image_cache;
+use crate::models::{BookProjectData, PageData};
+use std::io::Cursor;
+use vortex_core::archive::{self, ProjectArchiveReader};
+use wasm_bindgen::prelude::*;
+
+enum PageSource {
+    /// v2 container: pages are parsed one entry at a time
+    Archive(Box<ProjectArchiveReader<Cursor<Vec<u8>>>>),
+    /// Bare MessagePack or plain JSON project: parsed in one pass
+    Loaded(Vec<PageData>),
+}
+
+#[wasm_bindgen]
+pub struct ProjectLoader {
+    project: BookProjectData,
+    source: PageSource,
+}
+
+#[wasm_bindgen]
+impl ProjectLoader {
+    /// Open project bytes; embedded images are restored into the image cache
+    #[wasm_bindgen(constructor)]
+    pub fn new(data: Vec<u8>) -> Result<ProjectLoader, JsValue> {
+        if archive::is_archive(&data) {
+            let mut reader = ProjectArchiveReader::new(Cursor::new(data)).map_err(|e| JsValue::from_str(&e))?;
+            for image in reader.read_images().map_err(|e| JsValue::from_str(&e))? {
+                image_cache::cache_image(&image.id, image.data);
+            }
+            Ok(ProjectLoader {
+                project: reader.project().clone(),
+                source: PageSource::Archive(Box::new(reader)),
+            })
+        } else {
+            let (mut project, _) = archive::read_project(&data).map_err(|e| JsValue::from_str(&e))?;
+            let pages = std::mem::take(&mut project.document.pages);
+            Ok(ProjectLoader { project, source: PageSource::Loaded(pages) })
+        }
+    }
+
+    #[wasm_bindgen(getter, js_name = totalPages)]
+    pub fn total_pages(&self) -> usize {
+        match &self.source {
+            PageSource::Archive(reader) => reader.page_count(),
+            PageSource::Loaded(pages) => pages.len(),
+        }
+    }
+
+    /// Metadata, settings and page size (`document.pages` is empty)
+    pub fn project(&self) -> Result<JsValue, JsValue> {
+        serde_wasm_bindgen::to_value(&self.project).map_err(|e| JsValue::from_str(&e.to_string()))
+    }
+
+    /// Parse and return one page
+    pub fn load_page(&mut self, index: usize) -> Result<JsValue, JsValue> {
+        let result = match &mut self.source {
+            PageSource::Archive(reader) => reader
+                .read_page(index)
+                .and_then(|page| serde_wasm_bindgen::to_value(&page).map_err(|e| e.to_string())),
+            PageSource::Loaded(pages) => pages
+                .get(index)
+                .ok_or_else(|| format!("Page {} out of range ({} pages)", index, pages.len()))
+                .and_then(|page| serde_wasm_bindgen::to_value(page).map_err(|e| e.to_string())),
+        };
+        result.map_err(|e| JsValue::from_str(&e))
+    }
+}
@@ -104,8 +104,11 @@ export function getMimeType(filename: string): string {
     bookproj: 'application/json',
     json: 'application/json',
     png: 'image/png',
+    ssml: 'application/ssml+xml',
+    txt: 'text/plain',
     jpg: 'image/jpeg',
     jpeg: 'image/jpeg',
+    epub: 'application/epub+zip',
   };
   return mimeTypes[ext || ''] || 'application/octet-stream';
 }
@@ -120,6 +123,7 @@ export function getExtension(format: string): string {
     bookproj: '.bookproj',
     png: '.png',
     zip: '.zip',
+    epub: '.epub',
   };
   return extensions[format] || '';
 }
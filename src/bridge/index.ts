@@ -20,18 +20,102 @@ import type {
   OcrOptions,
   ReconstructionResult,
   ImportOptions,
+  JobInfo,
+  ReimportMerge,
+  CloudProvider,
+  CloudClient,
+  CloudAuthorization,
+  CloudAuthStatus,
+  CloudDocument,
+  ApiServerInfo,
+  SavedScript,
+  ScriptResult,
+  RepairReport,
+  DedupOptions,
+  DedupResult,
+  PruneOptions,
+  PruneResult,
+  LayerMatches,
+  StyleTransform,
+  TransformResult,
+  TypographyOptions,
+  TypographyResult,
+  ImportMapping,
+  NoteOptions,
+  NumberedNote,
+  NoteEdit,
+  NoteEditResult,
+  LayoutCheckOptions,
+  LayoutWarning,
+  InkCoverageOptions,
+  InkCoverageReport,
+  ColorConversionOptions,
+  ColorConversion,
+  BookletConfig,
+  BookletPaperSize,
+  ImpositionPreview,
+  CoverOptions,
+  CoverTemplate,
+  ExportPipeline,
+  ImageResolution,
+  AppSettings,
+  RecentProject,
+  ClipboardContent,
+  PasteContent,
+  Margins,
+  LinkedImages,
+  LinkRefresh,
+  AssetInfo,
+  AssetUpdate,
+  PackageReport,
+  TraceOptions,
+  TraceResult,
+  Decoration,
+  OcrRegion,
+  RegionOcrResult,
+  OcrReviewPage,
+  OcrReviewAction,
+  OcrReviewResult,
+  DocumentStructure,
+  PageText,
+  PageTextOptions,
+  PageGuides,
+  PageSetup,
+  PageLayoutOptions,
+  Section,
+  SectionInfo,
+  SectionsResult,
+  SnapResult,
+  Bounds,
+  SourceDocument,
 } from './types';
 import { calculateImposition } from './printImposition';
 
 // Tauri imports (only used in Tauri environment)
 // eslint-disable-next-line @typescript-eslint/no-explicit-any
-type InvokeFn = (cmd: string, args?: Record<string, unknown>) => Promise<any>;
+type InvokeFn = (
+  cmd: string,
+  args?: Record<string, unknown> | Uint8Array,
+  options?: { headers: Record<string, string> }
+) => Promise<any>;
 // eslint-disable-next-line @typescript-eslint/no-explicit-any
 type DialogModule = { open: (options?: any) => Promise<any>; save: (options?: any) => Promise<any> };
 
 let invoke: InvokeFn | null = null;
 let tauriDialog: DialogModule | null = null;
 
+/**
+ * Invoke a command with `data` as the raw request body instead of a JSON array
+ * of numbers; the other arguments travel in headers as percent-encoded JSON
+ */
+function invokeBinary<T>(cmd: string, data: Uint8Array, args: Record<string, unknown> = {}): Promise<T> {
+  const headers: Record<string, string> = {};
+  for (const [name, value] of Object.entries(args)) {
+    if (value !== undefined) headers[name] = encodeURIComponent(JSON.stringify(value));
+  }
+  return (invoke?.(cmd, data, { headers }) ?? Promise.reject(new Error('Bridge not initialized'))) as Promise<T>;
+}
+
 /**
  * Initialize the bridge (call on app startup)
  */
@@ -63,7 +147,8 @@ export async function importDocument(
     }
 
     const fileType = filePath.endsWith('.pdf') ? 'pdf' : 'docx';
-    return invoke?.('import_document', { filePath, fileType }) as Promise<DocumentResponse>;
+    const response = (await invoke?.('import_document', { filePath, fileType })) as DocumentResponse;
+    return { ...response, source: { path: filePath, fileType } };
   }
 
   // Web: Use browser file picker
@@ -410,6 +495,22 @@ export async function exportDocument(
     return exportPng(pagesToExport, filename, options, onProgress);
   }
 
+  if (options.format === 'speech') {
+    return exportSpeech(pagesToExport, metadata, filename, options);
+  }
+
+  if (options.format === 'images') {
+    return exportImages(pages, metadata, filename, options);
+  }
+
+  if (options.format === 'contactsheet') {
+    return exportContactSheet(pages, metadata, filename, options);
+  }
+
+  if (options.format === 'epub') {
+    return exportEpub(pages, metadata, filename, options);
+  }
+
   const format = options.format; // Now narrowed to 'pdf' | 'docx' | 'bookproj'
 
   if (isTauri()) {
@@ -452,6 +553,158 @@ export async function exportDocument(
   }
 }
 
+/**
+ * Export each target of a pipeline to its own file with its own options, e.g. the
+ * interior and cover of a book; one result per target, in order (desktop only)
+ */
+export async function exportPipeline(
+  pipeline: ExportPipeline,
+  pages: PageData[],
+  metadata: DocumentMetadata,
+  outputDir?: string
+): Promise<ExportResult[]> {
+  if (!isTauri()) {
+    throw new Error('Export pipelines require the desktop app');
+  }
+  return invoke?.('export_pipeline', { pipeline, pages, metadata, outputDir }) as Promise<ExportResult[]>;
+}
+
+/**
+ * Export text for audio proofing; the desktop app writes the extension
+ * its speech engine produces and, by default, one file per chapter
+ */
+async function exportSpeech(
+  pages: PageData[],
+  metadata: DocumentMetadata,
+  filename: string,
+  options: ExportOptions
+): Promise<ExportResult> {
+  const output = options.speech?.output ?? 'audio';
+  const extensions = output === 'audio' ? ['wav', 'aiff'] : [output === 'ssml' ? 'ssml' : 'txt'];
+
+  if (isTauri()) {
+    const outputPath = await tauriDialog?.save({
+      defaultPath: `${filename}.${extensions[0]}`,
+      filters: [{ name: output.toUpperCase(), extensions }],
+    });
+    if (!outputPath) {
+      return { success: false, message: 'Export cancelled' };
+    }
+    return invoke?.('export_document', {
+      format: 'speech',
+      pages,
+      outputPath,
+      metadata,
+      options: { ...options, pageRange: undefined, outputPath },
+    }) as Promise<ExportResult>;
+  }
+
+  try {
+    const data = getWasm().export_speech(pages, metadata, output);
+    const name = `${filename}.${extensions[0]}`;
+    downloadFile(data, name, getMimeType(name));
+    return { success: true, message: 'Export completed', data };
+  } catch (error) {
+    return { success: false, message: `Export failed: ${error}` };
+  }
+}
+
+/**
+ * Export a fixed-layout EPUB; the desktop app also renders the first page
+ * as the cover, which Kindle requires
+ */
+async function exportEpub(
+  pages: PageData[],
+  metadata: DocumentMetadata,
+  filename: string,
+  options: ExportOptions
+): Promise<ExportResult> {
+  if (isTauri()) {
+    const outputPath = await tauriDialog?.save({
+      defaultPath: `${filename}.epub`,
+      filters: [{ name: 'EPUB', extensions: ['epub'] }],
+    });
+    if (!outputPath) {
+      return { success: false, message: 'Export cancelled' };
+    }
+    return invoke?.('export_document', {
+      format: 'epub',
+      pages,
+      outputPath,
+      metadata,
+      options: { ...options, outputPath },
+    }) as Promise<ExportResult>;
+  }
+
+  try {
+    const data = getWasm().export_epub(pages, metadata, options.epub ?? {}, options.sections ?? [], options.pageRange);
+    downloadFile(data, `${filename}.epub`, getMimeType(`${filename}.epub`));
+    return { success: true, message: 'Export completed', data };
+  } catch (error) {
+    return { success: false, message: `Export failed: ${error}` };
+  }
+}
+
+/**
+ * Export pages as numbered image files rendered by the desktop backend;
+ * progress is reported through job events
+ */
+async function exportImages(
+  pages: PageData[],
+  metadata: DocumentMetadata,
+  filename: string,
+  options: ExportOptions
+): Promise<ExportResult> {
+  if (!isTauri()) {
+    throw new Error('Image sequence export requires the desktop app');
+  }
+  const images = options.images ?? {};
+  const extension = images.zip ? 'zip' : { png: 'png', jpeg: 'jpg', tiff: 'tif' }[images.format ?? 'png'];
+  const outputPath = await tauriDialog?.save({
+    defaultPath: `${filename}.${extension}`,
+    filters: [{ name: extension.toUpperCase(), extensions: [extension] }],
+  });
+  if (!outputPath) {
+    return { success: false, message: 'Export cancelled' };
+  }
+  return invoke?.('export_document', {
+    format: 'images',
+    pages,
+    outputPath,
+    metadata,
+    options: { ...options, outputPath },
+  }) as Promise<ExportResult>;
+}
+
+/**
+ * Export N-up proof sheets of page thumbnails as a PDF, rendered by the
+ * desktop backend
+ */
+async function exportContactSheet(
+  pages: PageData[],
+  metadata: DocumentMetadata,
+  filename: string,
+  options: ExportOptions
+): Promise<ExportResult> {
+  if (!isTauri()) {
+    throw new Error('Contact sheet export requires the desktop app');
+  }
+  const outputPath = await tauriDialog?.save({
+    defaultPath: `${filename}-contact-sheet.pdf`,
+    filters: [{ name: 'PDF', extensions: ['pdf'] }],
+  });
+  if (!outputPath) {
+    return { success: false, message: 'Export cancelled' };
+  }
+  return invoke?.('export_document', {
+    format: 'contactsheet',
+    pages,
+    outputPath,
+    metadata,
+    options: { ...options, outputPath },
+  }) as Promise<ExportResult>;
+}
+
 /**
  * Export pages as PNG (single or ZIP)
  */
@@ -498,7 +751,7 @@ async function exportPng(
 /**
  * Save project
  */
-export async function saveProject(project: BookProjectData): Promise<ExportResult> {
+export async function saveProject(project: BookProjectData, linkedImages?: LinkedImages): Promise<ExportResult> {
   if (isTauri()) {
     const outputPath = await tauriDialog?.save({
       filters: [{ name: 'Book Project', extensions: ['bookproj'] }],
@@ -508,18 +761,36 @@ export async function saveProject(project: BookProjectData): Promise<ExportResul
       return { success: false, message: 'Save cancelled' };
     }
 
-    return invoke?.('save_project', { project, outputPath }) as Promise<ExportResult>;
+    return invoke?.('save_project', { project, outputPath, linkedImages }) as Promise<ExportResult>;
   }
 
   // Web: Download as file
   const wasm = getWasm();
   const data = wasm.save_project(project);
   const filename = `${project.metadata.title || 'project'}.bookproj`;
-  downloadFile(data, filename, 'application/json');
+  downloadFile(data, filename, 'application/zip');
   
   return { success: true, message: 'Project saved' };
 }
 
+/**
+ * Collect the project, its images at full resolution, its fonts and a report into one zip
+ * for handing off (desktop only); null when the dialog is cancelled
+ */
+export async function packageProject(project: BookProjectData): Promise<PackageReport | null> {
+  if (!isTauri()) {
+    throw new Error('Packaging requires the desktop app');
+  }
+  const outputPath = await tauriDialog?.save({
+    defaultPath: `${project.metadata.title || 'project'} package.zip`,
+    filters: [{ name: 'Zip Archive', extensions: ['zip'] }],
+  });
+  if (!outputPath) {
+    return null;
+  }
+  return invoke?.('package_project', { project, outputPath }) as Promise<PackageReport>;
+}
+
 /**
  * Load project
  */
@@ -543,30 +814,35 @@ export async function loadProject(): Promise<BookProjectData | null> {
 }
 
 /**
- * Update layer
+ * Update layer; `page` is the page's current state, checked for locks
  */
 export async function updateLayer(
   pageIndex: number,
   layerId: string,
   updates: LayerUpdates,
-  currentLayer?: LayerObject
+  page: PageData,
+  force?: boolean
 ): Promise<LayerObject | null> {
   if (isTauri()) {
-    return invoke?.('update_layer', { pageIndex, layerId, updates }) as Promise<LayerObject>;
+    return invoke?.('update_layer', { pageIndex, layerId, updates, page, force }) as Promise<LayerObject>;
   }
 
-  // Web: Use WASM
-  if (!currentLayer) return null;
+  // Web: Use WASM, which applies the same lock check
   const wasm = getWasm();
-  return wasm.update_layer(currentLayer, updates);
+  return wasm.update_layer(pageIndex, page, layerId, updates, force);
 }
 
 /**
  * Delete layer (handled in frontend for web)
  */
-export async function deleteLayer(pageIndex: number, layerId: string): Promise<boolean> {
+export async function deleteLayer(
+  pageIndex: number,
+  layerId: string,
+  page: PageData,
+  force?: boolean
+): Promise<boolean> {
   if (isTauri()) {
-    await invoke?.('delete_layer', { pageIndex, layerId });
+    await invoke?.('delete_layer', { pageIndex, layerId, page, force });
     return true;
   }
   // Web: Handled by frontend store
@@ -576,9 +852,14 @@ export async function deleteLayer(pageIndex: number, layerId: string): Promise<b
 /**
  * Reorder layers (handled in frontend for web)
  */
-export async function reorderLayers(pageIndex: number, layerIds: string[]): Promise<boolean> {
+export async function reorderLayers(
+  pageIndex: number,
+  layerIds: string[],
+  page: PageData,
+  force?: boolean
+): Promise<boolean> {
   if (isTauri()) {
-    await invoke?.('reorder_layers', { pageIndex, layerIds });
+    await invoke?.('reorder_layers', { pageIndex, layerIds, page, force });
     return true;
   }
   // Web: Handled by frontend store
@@ -586,176 +867,764 @@ export async function reorderLayers(pageIndex: number, layerIds: string[]): Prom
 }
 
 /**
- * Get image data
+ * Remove layers painted more than once, such as fake-bold text
  */
-export async function getImage(imageId: string): Promise<Uint8Array | null> {
+export async function deduplicateLayers(pages: PageData[], options?: DedupOptions): Promise<DedupResult> {
   if (isTauri()) {
-    return invoke?.('get_image', { imageId }) as Promise<Uint8Array>;
+    return invoke?.('deduplicate_layers', { pages, options }) as Promise<DedupResult>;
   }
-
   const wasm = getWasm();
-  return wasm.get_image(imageId) || null;
+  return wasm.deduplicate_layers(pages, options);
 }
 
 /**
- * Cache image
+ * Remove layers that paint nothing: zero-area, invisible, blank text or off the page
  */
-export function cacheImage(imageId: string, data: Uint8Array): void {
-  if (!isTauri()) {
-    const wasm = getWasm();
-    wasm.cache_image(imageId, data);
+export async function pruneLayers(pages: PageData[], options?: PruneOptions): Promise<PruneResult> {
+  if (isTauri()) {
+    return invoke?.('prune_layers', { pages, options }) as Promise<PruneResult>;
   }
+  const wasm = getWasm();
+  return wasm.prune_layers(pages, options);
 }
 
-// Re-export types and utilities
-export * from './types';
-export * from './environment';
-export { pickFile, pickFiles, downloadFile } from './fileHandlers';
-export { clearImageCache, isScannedPage, getCachedPage, renderPdfPagesToImages } from './pdfParser';
-export * from './fontService';
-export { 
-  getTypographyBridge,
-  initTypographyBridge,
-  getAvailableFonts,
-  searchFonts,
-  applyTextFormatting,
-  validateFontSupport,
-  preloadCommonFonts,
-  getFontFallbackChain,
-  calculateTextMetrics,
-  type TypographyBridge,
-  type TextMetrics,
-} from './typographyBridge';
-export { 
-  analyzePdfContent, 
-  getContentTypeDescription, 
-  getRecommendationText 
-} from './pdfAnalyzer';
-export {
-  initOcr,
-  terminateOcr,
-  isOcrReady,
-  recognizeImage,
-  recognizeRegion,
-  ocrPageToLayers,
-  renderPageToCanvas,
-  type OcrProgressCallback,
-} from './ocrService';
-export {
-  calculateImposition,
-  impose2Up,
-  imposeBooklet,
-  impose4Up,
-  imposeNUp,
-  getImpositionDescription,
-} from './printImposition';
-
 /**
- * Analyze PDF content type (image-only, text-based, mixed, vector-heavy)
- * Works in both Tauri and Web environments
+ * Ids of the layers matching a query, per page. Terms are `key:value` and all
+ * must hold; `-` negates one. Keys: type, role (comma lists), font and text
+ * (regex, `/…/i` ignores case), size (`12`, `8..12`, `..8`), color, in
+ * (`x,y,width,height`) and tag. Example: `type:text size:..8 -role:header`
  */
-export async function analyzePdf(filePathOrData: string | Uint8Array): Promise<PdfAnalysis> {
-  if (isTauri() && typeof filePathOrData === 'string') {
-    return invoke?.('analyze_pdf_content', { filePath: filePathOrData }) as Promise<PdfAnalysis>;
-  }
-  
-  // Web: analyze from Uint8Array
-  if (filePathOrData instanceof Uint8Array) {
-    return analyzeWebPdf(filePathOrData);
+export async function queryLayers(pages: PageData[], query: string): Promise<LayerMatches[]> {
+  if (isTauri()) {
+    return invoke?.('query_layers', { pages, query }) as Promise<LayerMatches[]>;
   }
-  
-  throw new Error('Invalid input: Tauri requires file path, Web requires Uint8Array');
+  return getWasm().query_layers(pages, query);
 }
 
 /**
- * Reconstruct PDF with OCR (Tauri only - requires native OCR)
- * For web, use ocrPageToLayers from ocrService
+ * Apply a style transform to every unlocked layer matching `query` (see
+ * queryLayers). Returns the pages to apply as one undo step and a per-layer
+ * report; a dry run returns only the report.
  */
-export async function reconstructPdfWithOcr(
-  filePath: string,
-  options?: OcrOptions,
-  onProgress?: (current: number, total: number, status: string) => void
-): Promise<ReconstructionResult> {
-  if (!isTauri()) {
-    return {
-      success: false,
-      message: 'Use ocrPageToLayers() for web OCR reconstruction',
-      pagesProcessed: 0,
-      textLayersAdded: 0,
-      confidence: 0,
-    };
-  }
-
-  // Listen for progress events
-  if (onProgress) {
-    const { listen } = await import('@tauri-apps/api/event');
-    const unlisten = await listen<{ currentPage: number; totalPages: number; status: string }>(
-      'ocr_progress',
-      (event) => {
-        onProgress(event.payload.currentPage, event.payload.totalPages, event.payload.status);
-      }
-    );
-    
-    try {
-      const result = await invoke?.('reconstruct_pdf_with_ocr', { filePath, options }) as ReconstructionResult;
-      unlisten();
-      return result;
-    } catch (e) {
-      unlisten();
-      throw e;
-    }
+export async function transformLayers(
+  pages: PageData[],
+  query: string,
+  transform: StyleTransform,
+  dryRun = false
+): Promise<TransformResult> {
+  if (isTauri()) {
+    return invoke?.('transform_layers', { pages, query, transform, dryRun }) as Promise<TransformResult>;
   }
-
-  return invoke?.('reconstruct_pdf_with_ocr', { filePath, options }) as Promise<ReconstructionResult>;
+  return getWasm().transform_layers(pages, query, transform, dryRun);
 }
 
 /**
- * OCR a scanned page in web mode
- * Renders page to canvas and runs Tesseract.js OCR
+ * Smart quotes, em dashes, ellipses and French no-break spaces in every unlocked
+ * text layer. Returns the pages to apply as one undo step and the layers changed.
  */
-export async function ocrScannedPage(
-  pageIndex: number,
-  onProgress?: (progress: number, status: string) => void
-): Promise<LayerObject[]> {
+export async function cleanTypography(pages: PageData[], options?: TypographyOptions): Promise<TypographyResult> {
   if (isTauri()) {
-    throw new Error('Use reconstructPdfWithOcr for Tauri OCR');
+    return invoke?.('clean_typography', { pages, options }) as Promise<TypographyResult>;
   }
+  return getWasm().clean_typography(pages, options);
+}
 
-  const { getCachedPage } = await import('./pdfParser');
-  const { ocrPageToLayers, renderPageToCanvas } = await import('./ocrService');
-
-  const page = await getCachedPage(pageIndex + 1);
-  if (!page) throw new Error('Page not cached');
+/**
+ * Saved import mappings (desktop only)
+ */
+export async function listImportMappings(): Promise<ImportMapping[]> {
+  if (!isTauri()) return [];
+  return invoke?.('list_import_mappings') as Promise<ImportMapping[]>;
+}
 
-  onProgress?.(0.1, 'Rendering page...');
-  const canvas = await renderPageToCanvas(page, 2.0); // 2x scale for better OCR
+/**
+ * Save an import mapping, replacing one with the same name (desktop only)
+ */
+export async function saveImportMapping(mapping: ImportMapping): Promise<void> {
+  if (!isTauri()) return;
+  await invoke?.('save_import_mapping', { mapping });
+}
 
-  onProgress?.(0.3, 'Running OCR...');
-  const viewport = (page as { getViewport: (o: { scale: number }) => { height: number } }).getViewport({ scale: 1.0 });
-  
-  return ocrPageToLayers(canvas, pageIndex, viewport.height, (p, s) => {
-    onProgress?.(0.3 + p * 0.7, s);
-  });
+/**
+ * Delete a saved import mapping
+ */
+export async function deleteImportMapping(name: string): Promise<void> {
+  if (!isTauri()) return;
+  await invoke?.('delete_import_mapping', { name });
 }
 
 /**
- * Check if document needs OCR reconstruction
+ * Record the roles, tags and styles given to the layers of `imported` in
+ * `corrected` as an import mapping; the desktop app also saves it
  */
-export function needsOcrReconstruction(analysis: PdfAnalysis): boolean {
-  return analysis.recommendation === 'ocr-required' || analysis.recommendation === 'ocr-verification';
+export async function recordImportMapping(
+  name: string,
+  imported: PageData[],
+  corrected: PageData[]
+): Promise<ImportMapping> {
+  if (isTauri()) {
+    return invoke?.('record_import_mapping', { name, imported, corrected }) as Promise<ImportMapping>;
+  }
+  return getWasm().record_import_mapping(name, imported, corrected);
 }
 
 /**
- * Import document with analysis
- * Returns document data along with content analysis for PDFs
+ * Apply an import mapping to the unlocked layers of pages already imported
  */
-export async function importDocumentWithAnalysis(
-  onProgress?: (current: number, total: number, status: string) => void
-): Promise<DocumentResponseWithAnalysis> {
+export async function applyImportMapping(pages: PageData[], mapping: ImportMapping): Promise<PageData[]> {
   if (isTauri()) {
-    const filePath = await tauriDialog?.open({
-      filters: [{ name: 'Documents', extensions: ['pdf', 'docx'] }],
-    });
+    return invoke?.('apply_import_mapping', { pages, mapping }) as Promise<PageData[]>;
+  }
+  return getWasm().apply_import_mapping(pages, mapping);
+}
+
+/**
+ * Every footnote and endnote with the number it prints with, in reading order
+ */
+export async function listNotes(pages: PageData[], options?: NoteOptions): Promise<NumberedNote[]> {
+  if (isTauri()) {
+    return invoke?.('list_notes', { pages, options }) as Promise<NumberedNote[]>;
+  }
+  return getWasm().list_notes(pages, options);
+}
+
+/**
+ * Insert, change or remove a note. Returns the pages to apply as one undo step
+ * and the notes renumbered.
+ */
+export async function editNote(pages: PageData[], edit: NoteEdit, options?: NoteOptions): Promise<NoteEditResult> {
+  if (isTauri()) {
+    return invoke?.('edit_note', { pages, edit, options }) as Promise<NoteEditResult>;
+  }
+  return getWasm().edit_note(pages, edit, options);
+}
+
+/**
+ * Pages with their notes set as layers (reference marks, footnote areas and
+ * endnote pages), as PDF and EPUB export prints them
+ */
+export async function resolveNotes(pages: PageData[], options?: NoteOptions): Promise<PageData[]> {
+  if (isTauri()) {
+    return invoke?.('resolve_notes', { pages, options }) as Promise<PageData[]>;
+  }
+  return getWasm().resolve_notes(pages, options);
+}
+
+/**
+ * Vector layer for a rule, ornament, frame or badge, top-left at (x, y)
+ */
+export async function createDecoration(
+  pageIndex: number,
+  decoration: Decoration,
+  x: number,
+  y: number,
+  color?: string
+): Promise<LayerObject> {
+  if (isTauri()) {
+    return invoke?.('create_decoration', { pageIndex, decoration, x, y, color }) as Promise<LayerObject>;
+  }
+  const wasm = getWasm();
+  return wasm.create_decoration(decoration, `decoration-${pageIndex}-${Date.now()}-${Math.random().toString(36).slice(2, 8)}`, x, y, color);
+}
+
+/**
+ * Serialize layers for the system clipboard as SVG, plain text and (desktop only) PNG
+ */
+export async function copyLayers(page: PageData, layerIds: string[], scale?: number): Promise<ClipboardContent> {
+  if (isTauri()) {
+    return invoke?.('copy_layers', { page, layerIds, scale }) as Promise<ClipboardContent>;
+  }
+  const wasm = getWasm();
+  const layers = page.layers.filter((l) => layerIds.includes(l.id));
+  const svg = wasm.copy_layers_svg(layers);
+  if (!svg) {
+    throw new Error('No layers to copy');
+  }
+  return { svg, text: wasm.copy_layers_text(layers) };
+}
+
+/**
+ * Layers from pasted SVG, image or text, top-left at (x, y) when given
+ */
+export async function pasteLayers(
+  pageIndex: number,
+  content: PasteContent,
+  x?: number,
+  y?: number
+): Promise<LayerObject[]> {
+  if (isTauri()) {
+    return invoke?.('paste_layers', { pageIndex, content, x, y }) as Promise<LayerObject[]>;
+  }
+  const wasm = getWasm();
+  const idPrefix = `paste-${pageIndex}-${Date.now()}`;
+  if (content.svg?.trim()) {
+    return wasm.paste_svg(content.svg, idPrefix);
+  }
+  if (content.text !== undefined) {
+    const layer = wasm.paste_text(content.text, `${idPrefix}-0`, x ?? 72, y ?? 72);
+    if (layer) return [layer];
+  }
+  throw new Error('Nothing to paste');
+}
+
+/**
+ * Image layer for dropped image bytes, sized at the file's resolution and fitted
+ * inside the margins; centered on the drop point (x, y) when given
+ */
+export async function placeImage(
+  page: PageData,
+  data: Uint8Array,
+  margins?: Margins,
+  x?: number,
+  y?: number
+): Promise<LayerObject> {
+  if (isTauri()) {
+    const zIndex = page.layers.reduce((top, layer) => Math.max(top, layer.zIndex + 1), 0);
+    const header = { ...page, layers: [] };
+    return invokeBinary<LayerObject>('place_image', data, { page: header, margins, x, y, 'z-index': zIndex });
+  }
+  const wasm = getWasm();
+  return wasm.place_image(data, page, margins, x, y, `placed-${page.pageIndex}-${Date.now()}-${Math.random().toString(36).slice(2, 8)}`);
+}
+
+/**
+ * Trace an image layer into a vector layer with id `<id>-traced`, for the caller to
+ * swap in; preview traces a small copy for quick feedback (desktop only)
+ */
+export async function traceImage(layer: LayerObject, options?: TraceOptions, preview?: boolean): Promise<TraceResult> {
+  if (!isTauri()) {
+    throw new Error('Image tracing requires the desktop app');
+  }
+  return invoke?.('trace_image', { layer, options, preview }) as Promise<TraceResult>;
+}
+
+/**
+ * Crop uniform margins out of an image layer's bitmap, shrinking its bounds so the
+ * page looks the same; null when there is nothing to crop (desktop only)
+ */
+export async function autoCropImage(layer: LayerObject, tolerance?: number): Promise<LayerObject | null> {
+  if (!isTauri()) {
+    throw new Error('Image cropping requires the desktop app');
+  }
+  return invoke?.('auto_crop_image', { layer, tolerance }) as Promise<LayerObject | null>;
+}
+
+/**
+ * Place an image file as a layer linked to the file, so later edits to it can be
+ * picked up by refreshLinkedImages (desktop only)
+ */
+export async function placeLinkedImage(
+  page: PageData,
+  path: string,
+  margins?: Margins,
+  x?: number,
+  y?: number
+): Promise<LayerObject> {
+  if (!isTauri()) {
+    throw new Error('Linked images require the desktop app');
+  }
+  return invoke?.('place_linked_image', { page, path, margins, x, y }) as Promise<LayerObject>;
+}
+
+/**
+ * Reload linked images whose files changed on disk (desktop only)
+ */
+export async function refreshLinkedImages(pages: PageData[]): Promise<LinkRefresh> {
+  if (!isTauri()) {
+    throw new Error('Linked images require the desktop app');
+  }
+  return invoke?.('refresh_linked_images', { pages }) as Promise<LinkRefresh>;
+}
+
+/**
+ * Image assets with the layers using them, for the assets panel (desktop only)
+ */
+export async function listAssets(pages: PageData[]): Promise<AssetInfo[]> {
+  if (!isTauri()) {
+    throw new Error('The asset manager requires the desktop app');
+  }
+  return invoke?.('list_assets', { pages }) as Promise<AssetInfo[]>;
+}
+
+/**
+ * Cached images no layer uses; with remove they are dropped from the cache (desktop only)
+ */
+export async function findUnusedAssets(pages: PageData[], remove?: boolean): Promise<string[]> {
+  if (!isTauri()) {
+    throw new Error('The asset manager requires the desktop app');
+  }
+  return invoke?.('find_unused_assets', { pages, remove }) as Promise<string[]>;
+}
+
+/**
+ * Replace an asset with an image file on every layer that uses it (desktop only)
+ */
+export async function replaceAsset(pages: PageData[], assetId: string, path: string): Promise<AssetUpdate> {
+  if (!isTauri()) {
+    throw new Error('The asset manager requires the desktop app');
+  }
+  return invoke?.('replace_asset', { pages, assetId, path }) as Promise<AssetUpdate>;
+}
+
+/**
+ * Look for the files of missing assets by name in a folder and its subfolders (desktop only)
+ */
+export async function relinkMissingAssets(pages: PageData[], folder: string): Promise<AssetUpdate> {
+  if (!isTauri()) {
+    throw new Error('The asset manager requires the desktop app');
+  }
+  return invoke?.('relink_missing_assets', { pages, folder }) as Promise<AssetUpdate>;
+}
+
+/**
+ * Find off-page layers, low-contrast text over images and overlapping text (desktop only)
+ */
+export async function checkLayout(pages: PageData[], options?: LayoutCheckOptions): Promise<LayoutWarning[]> {
+  if (!isTauri()) {
+    throw new Error('Layout checks require the desktop app');
+  }
+  return invoke?.('check_layout', { pages, options }) as Promise<LayoutWarning[]>;
+}
+
+/**
+ * Colors used and estimated ink coverage per page, for print budgeting (desktop only)
+ */
+export async function analyzeInkCoverage(pages: PageData[], options?: InkCoverageOptions): Promise<InkCoverageReport> {
+  if (!isTauri()) {
+    throw new Error('Ink coverage analysis requires the desktop app');
+  }
+  return invoke?.('analyze_ink_coverage', { pages, options }) as Promise<InkCoverageReport>;
+}
+
+/**
+ * Convert every layer color, and optionally every image, to grayscale or ink-limited
+ * CMYK; with preview only the counts of what would change come back (desktop only)
+ */
+export async function convertDocumentColors(
+  pages: PageData[],
+  options: ColorConversionOptions
+): Promise<ColorConversion> {
+  if (!isTauri()) {
+    throw new Error('Color conversion requires the desktop app');
+  }
+  return invoke?.('convert_document_colors', { pages, options }) as Promise<ColorConversion>;
+}
+
+/**
+ * Saddle-stitch imposition with a thumbnail of every sheet side as it will print,
+ * width pixels across the sheet (default 400), to check page order and creep (desktop only)
+ */
+export async function previewImposition(
+  pages: PageData[],
+  config?: BookletConfig,
+  width?: number
+): Promise<ImpositionPreview> {
+  if (!isTauri()) {
+    throw new Error('Imposition preview requires the desktop app');
+  }
+  return invoke?.('preview_imposition', { pages, config, width }) as Promise<ImpositionPreview>;
+}
+
+/**
+ * Save a two-sided printer calibration sheet (default A4). Readings taken from the
+ * printed sheet go into the printerCalibration setting (desktop only)
+ */
+export async function generateCalibrationSheet(paperSize?: BookletPaperSize): Promise<string | null> {
+  if (!isTauri()) {
+    throw new Error('Printer calibration requires the desktop app');
+  }
+  const outputPath = await tauriDialog?.save({
+    defaultPath: 'printer calibration.pdf',
+    filters: [{ name: 'PDF', extensions: ['pdf'] }],
+  });
+  if (!outputPath) {
+    return null;
+  }
+  await invoke?.('generate_calibration_sheet', { outputPath, paperSize });
+  return outputPath;
+}
+
+/**
+ * Export one imposed PDF per signature into outputDir, named `<name>-signature-01.pdf`
+ * and so on; returns the files written (desktop only)
+ */
+export async function exportSignatures(
+  pages: PageData[],
+  config: BookletConfig,
+  outputDir: string,
+  name?: string
+): Promise<string[]> {
+  if (!isTauri()) {
+    throw new Error('Signature export requires the desktop app');
+  }
+  return invoke?.('export_signatures', { pages, config, outputDir, name }) as Promise<string[]>;
+}
+
+/**
+ * Compute a cover's spine width and build its back/spine/front template page; with
+ * outputPath the template is also saved as a print-ready PDF (desktop only)
+ */
+export async function createCoverTemplate(options: CoverOptions, outputPath?: string): Promise<CoverTemplate> {
+  if (!isTauri()) {
+    throw new Error('Cover templates require the desktop app');
+  }
+  return invoke?.('create_cover_template', { options, outputPath }) as Promise<CoverTemplate>;
+}
+
+/**
+ * Image layers that print below minDpi (default 300) at their current size, lowest first (desktop only)
+ */
+export async function checkImageResolution(pages: PageData[], minDpi?: number): Promise<ImageResolution[]> {
+  if (!isTauri()) {
+    throw new Error('Image resolution checks require the desktop app');
+  }
+  return invoke?.('check_image_resolution', { pages, minDpi }) as Promise<ImageResolution[]>;
+}
+
+/**
+ * Write encoded image bytes to a file (desktop only)
+ */
+export async function writeImageFile(data: Uint8Array, outputPath: string): Promise<void> {
+  if (!isTauri()) {
+    throw new Error('Writing files requires the desktop app');
+  }
+  await invokeBinary<boolean>('export_layer_image', data, { 'output-path': outputPath });
+}
+
+/**
+ * Get image data
+ */
+export async function getImage(imageId: string): Promise<Uint8Array | null> {
+  if (isTauri()) {
+    const data = (await invoke?.('get_image', { imageId })) as ArrayBuffer;
+    return data.byteLength > 0 ? new Uint8Array(data) : null;
+  }
+
+  const wasm = getWasm();
+  return wasm.get_image(imageId) || null;
+}
+
+/**
+ * Cache image
+ */
+export function cacheImage(imageId: string, data: Uint8Array): void {
+  if (!isTauri()) {
+    const wasm = getWasm();
+    wasm.cache_image(imageId, data);
+  }
+}
+
+// Re-export types and utilities
+export * from './types';
+export * from './environment';
+export { pickFile, pickFiles, downloadFile } from './fileHandlers';
+export { clearImageCache, isScannedPage, getCachedPage, renderPdfPagesToImages } from './pdfParser';
+export * from './fontService';
+export { 
+  getTypographyBridge,
+  initTypographyBridge,
+  getAvailableFonts,
+  searchFonts,
+  applyTextFormatting,
+  validateFontSupport,
+  preloadCommonFonts,
+  getFontFallbackChain,
+  calculateTextMetrics,
+  type TypographyBridge,
+  type TextMetrics,
+} from './typographyBridge';
+export { 
+  analyzePdfContent, 
+  getContentTypeDescription, 
+  getRecommendationText 
+} from './pdfAnalyzer';
+export {
+  initOcr,
+  terminateOcr,
+  isOcrReady,
+  recognizeImage,
+  recognizeRegion,
+  ocrPageToLayers,
+  renderPageToCanvas,
+  type OcrProgressCallback,
+} from './ocrService';
+export {
+  calculateImposition,
+  impose2Up,
+  imposeBooklet,
+  impose4Up,
+  imposeNUp,
+  getImpositionDescription,
+} from './printImposition';
+
+/**
+ * Analyze PDF content type (image-only, text-based, mixed, vector-heavy)
+ * Works in both Tauri and Web environments
+ */
+export async function analyzePdf(filePathOrData: string | Uint8Array): Promise<PdfAnalysis> {
+  if (isTauri() && typeof filePathOrData === 'string') {
+    return invoke?.('analyze_pdf_content', { filePath: filePathOrData }) as Promise<PdfAnalysis>;
+  }
+  
+  // Web: analyze from Uint8Array
+  if (filePathOrData instanceof Uint8Array) {
+    return analyzeWebPdf(filePathOrData);
+  }
+  
+  throw new Error('Invalid input: Tauri requires file path, Web requires Uint8Array');
+}
+
+/**
+ * Reconstruct PDF with OCR (Tauri only - requires native OCR)
+ * For web, use ocrPageToLayers from ocrService
+ */
+export async function reconstructPdfWithOcr(
+  filePath: string,
+  options?: OcrOptions,
+  onProgress?: (current: number, total: number, status: string) => void
+): Promise<ReconstructionResult> {
+  if (!isTauri()) {
+    return {
+      success: false,
+      message: 'Use ocrPageToLayers() for web OCR reconstruction',
+      pagesProcessed: 0,
+      textLayersAdded: 0,
+      confidence: 0,
+    };
+  }
+
+  // Listen for progress events
+  if (onProgress) {
+    const { listen } = await import('@tauri-apps/api/event');
+    const unlisten = await listen<{ currentPage: number; totalPages: number; status: string }>(
+      'ocr_progress',
+      (event) => {
+        onProgress(event.payload.currentPage, event.payload.totalPages, event.payload.status);
+      }
+    );
+    
+    try {
+      const result = await invoke?.('reconstruct_pdf_with_ocr', { filePath, options }) as ReconstructionResult;
+      unlisten();
+      return result;
+    } catch (e) {
+      unlisten();
+      throw e;
+    }
+  }
+
+  return invoke?.('reconstruct_pdf_with_ocr', { filePath, options }) as Promise<ReconstructionResult>;
+}
+
+/**
+ * Image-only regions (scanned inserts, figures containing text) that ocrPageRegions would OCR
+ */
+export async function findOcrRegions(pages: PageData[]): Promise<OcrRegion[]> {
+  if (!isTauri()) {
+    throw new Error('Region OCR requires the desktop app');
+  }
+  return invoke?.('find_page_ocr_regions', { pages }) as Promise<OcrRegion[]>;
+}
+
+/**
+ * OCR just the image-only regions of pages and merge the text into their layers
+ */
+export async function ocrPageRegions(
+  pages: PageData[],
+  layerIds?: string[],
+  options?: OcrOptions
+): Promise<RegionOcrResult> {
+  if (!isTauri()) {
+    throw new Error('Region OCR requires the desktop app');
+  }
+  return invoke?.('ocr_page_regions', { pages, layerIds, options }) as Promise<RegionOcrResult>;
+}
+
+/**
+ * Headings, body text, captions and footnotes of the pages' text layers,
+ * with the heading tree for an outline panel or table of contents
+ */
+export async function extractStructure(pages: PageData[]): Promise<DocumentStructure> {
+  if (isTauri()) {
+    return invoke?.('extract_structure', { pages }) as Promise<DocumentStructure>;
+  }
+  return getWasm().extract_structure(pages);
+}
+
+/**
+ * A page's text in reading order, for copy actions and text exports;
+ * pass `styles` for font runs and `layerIds` to read only a selection
+ */
+export async function getPageText(page: PageData, options?: PageTextOptions): Promise<PageText> {
+  if (isTauri()) {
+    return invoke?.('get_page_text', { page, options }) as Promise<PageText>;
+  }
+  return getWasm().get_page_text(page, options);
+}
+
+/**
+ * Repaginate the pages as one flow on pages of `pageSetup` (US Letter with
+ * 1 in margins when absent), breaking text between pages as each paragraph's
+ * keep and widow/orphan controls allow
+ */
+export async function reflowPages(pages: PageData[], pageSetup?: PageSetup): Promise<PageData[]> {
+  if (isTauri()) {
+    return invoke?.('reflow_pages', { pages, pageSetup }) as Promise<PageData[]>;
+  }
+  return getWasm().reflow_pages(pages, pageSetup);
+}
+
+/**
+ * Infer baseline grid, margin and column guides from the pages' layers;
+ * returns the pages with the guides stored in their metadata
+ */
+export async function detectLayoutGuides(pages: PageData[]): Promise<PageData[]> {
+  if (isTauri()) {
+    return invoke?.('detect_layout_guides', { pages }) as Promise<PageData[]>;
+  }
+  return getWasm().detect_layout_guides(pages);
+}
+
+/**
+ * Copy the background, header and footer layers, page background and guides
+ * of page `source` onto the `targets`, replacing layers stamped earlier and
+ * keeping their content; returns the updated pages
+ */
+export async function applyPageLayout(
+  pages: PageData[],
+  source: number,
+  targets: number[],
+  options?: PageLayoutOptions
+): Promise<PageData[]> {
+  if (isTauri()) {
+    return invoke?.('apply_page_layout', { pages, source, targets, options }) as Promise<PageData[]>;
+  }
+  return getWasm().apply_page_layout(pages, source, targets, options);
+}
+
+/**
+ * The sections of a book in page order, with their page ranges, titles,
+ * chapter numbers and first page labels
+ */
+export async function listSections(pages: PageData[], sections: Section[]): Promise<SectionInfo[]> {
+  if (isTauri()) {
+    return invoke?.('list_sections', { pages, sections }) as Promise<SectionInfo[]>;
+  }
+  return getWasm().list_sections(pages, sections);
+}
+
+/**
+ * Add a section, or replace the one with the same id (a new id is made when
+ * it is empty), then relabel the pages and stamp the section master pages
+ */
+export async function setSection(pages: PageData[], sections: Section[], section: Section): Promise<SectionsResult> {
+  if (isTauri()) {
+    return invoke?.('set_section', { pages, sections, section }) as Promise<SectionsResult>;
+  }
+  return getWasm().set_section(pages, sections, section);
+}
+
+/**
+ * Remove a section; its pages join the section before
+ */
+export async function removeSection(pages: PageData[], sections: Section[], id: string): Promise<SectionsResult> {
+  if (isTauri()) {
+    return invoke?.('remove_section', { pages, sections, id }) as Promise<SectionsResult>;
+  }
+  return getWasm().remove_section(pages, sections, id);
+}
+
+/**
+ * Snap bounds being placed to a page's guides within `threshold` points
+ * (4 by default); `baseline` is a text layer's baseline below its top
+ */
+export async function snapToGuides(
+  guides: PageGuides,
+  bounds: Bounds,
+  baseline?: number,
+  threshold?: number
+): Promise<SnapResult> {
+  if (isTauri()) {
+    return invoke?.('snap_to_guides', { guides, bounds, baseline, threshold }) as Promise<SnapResult>;
+  }
+  return getWasm().snap_to_guides(guides, bounds, baseline, threshold);
+}
+
+/**
+ * OCR'd text below `threshold` confidence (0.8 by default) that has not been
+ * reviewed yet, grouped by page
+ */
+export async function getOcrReviewQueue(pages: PageData[], threshold?: number): Promise<OcrReviewPage[]> {
+  if (isTauri()) {
+    return invoke?.('get_ocr_review_queue', { pages, threshold }) as Promise<OcrReviewPage[]>;
+  }
+  return getWasm().ocr_review_queue(pages, threshold);
+}
+
+/**
+ * Accept or correct an OCR'd layer; returns the updated pages and the
+ * remaining review queue
+ */
+export async function reviewOcrLayer(
+  pages: PageData[],
+  pageIndex: number,
+  layerId: string,
+  action: OcrReviewAction,
+  threshold?: number
+): Promise<OcrReviewResult> {
+  if (isTauri()) {
+    return invoke?.('review_ocr_layer', { pages, pageIndex, layerId, action, threshold }) as Promise<OcrReviewResult>;
+  }
+  return getWasm().review_ocr_layer(pages, pageIndex, layerId, action, threshold);
+}
+
+/**
+ * OCR a scanned page in web mode
+ * Renders page to canvas and runs Tesseract.js OCR
+ */
+export async function ocrScannedPage(
+  pageIndex: number,
+  onProgress?: (progress: number, status: string) => void
+): Promise<LayerObject[]> {
+  if (isTauri()) {
+    throw new Error('Use reconstructPdfWithOcr for Tauri OCR');
+  }
+
+  const { getCachedPage } = await import('./pdfParser');
+  const { ocrPageToLayers, renderPageToCanvas } = await import('./ocrService');
+
+  const page = await getCachedPage(pageIndex + 1);
+  if (!page) throw new Error('Page not cached');
+
+  onProgress?.(0.1, 'Rendering page...');
+  const canvas = await renderPageToCanvas(page, 2.0); // 2x scale for better OCR
+
+  onProgress?.(0.3, 'Running OCR...');
+  const viewport = (page as { getViewport: (o: { scale: number }) => { height: number } }).getViewport({ scale: 1.0 });
+  
+  return ocrPageToLayers(canvas, pageIndex, viewport.height, (p, s) => {
+    onProgress?.(0.3 + p * 0.7, s);
+  });
+}
+
+/**
+ * Check if document needs OCR reconstruction
+ */
+export function needsOcrReconstruction(analysis: PdfAnalysis): boolean {
+  return analysis.recommendation === 'ocr-required' || analysis.recommendation === 'ocr-verification';
+}
+
+/**
+ * Import document with analysis
+ * Returns document data along with content analysis for PDFs
+ */
+export async function importDocumentWithAnalysis(
+  onProgress?: (current: number, total: number, status: string) => void
+): Promise<DocumentResponseWithAnalysis> {
+  if (isTauri()) {
+    const filePath = await tauriDialog?.open({
+      filters: [{ name: 'Documents', extensions: ['pdf', 'docx'] }],
+    });
     
     if (!filePath || typeof filePath !== 'string') {
       return { success: false, message: 'No file selected', data: undefined };
@@ -818,3 +1687,299 @@ export async function importDocumentWithAnalysis(
   const wasm = getWasm();
   return wasm.parse_docx(file.data);
 }
+
+/**
+ * List queued, running and recently finished background jobs (desktop only)
+ */
+export async function listJobs(): Promise<JobInfo[]> {
+  if (!isTauri()) return [];
+  return invoke?.('list_jobs') as Promise<JobInfo[]>;
+}
+
+/**
+ * Cancel a background job; false if it already finished
+ */
+export async function cancelJob(jobId: string): Promise<boolean> {
+  if (!isTauri()) return false;
+  return invoke?.('cancel_job', { jobId }) as Promise<boolean>;
+}
+
+/**
+ * Watch a project's source file and call onChange after it changes on disk
+ * (desktop only). Returns a function that stops the watch.
+ */
+export async function watchSourceFile(
+  filePath: string,
+  onChange: (filePath: string) => void
+): Promise<() => void> {
+  if (!isTauri()) return () => {};
+  const { listen } = await import('@tauri-apps/api/event');
+  const unlisten = await listen<{ filePath: string }>('source_changed', (event) => {
+    onChange(event.payload.filePath);
+  });
+  try {
+    await invoke?.('watch_source_file', { filePath });
+  } catch (e) {
+    unlisten();
+    throw e;
+  }
+  return () => {
+    unlisten();
+    invoke?.('unwatch_source_file');
+  };
+}
+
+/**
+ * Re-import a changed source file and merge it into the edited pages,
+ * keeping manual edits (desktop only)
+ */
+export async function reimportAndMerge(
+  filePath: string,
+  originalPages: PageData[],
+  pages: PageData[],
+  options?: ImportOptions
+): Promise<ReimportMerge> {
+  if (!isTauri()) {
+    throw new Error('Re-importing a source file requires the desktop app');
+  }
+  const fileType = filePath.toLowerCase().endsWith('.pdf') ? 'pdf' : 'docx';
+  return invoke?.('reimport_and_merge', {
+    filePath,
+    fileType,
+    originalPages,
+    pages,
+    options,
+  }) as Promise<ReimportMerge>;
+}
+
+/**
+ * Re-parse the source page a page came from, replacing its extracted layers
+ * and keeping layers added by hand (desktop only)
+ */
+export async function reimportPage(
+  source: SourceDocument,
+  page: PageData,
+  options?: ImportOptions
+): Promise<PageData> {
+  if (!isTauri()) {
+    throw new Error('Re-importing a source page requires the desktop app');
+  }
+  return invoke?.('reimport_page', {
+    filePath: source.path,
+    fileType: source.fileType,
+    page,
+    options,
+  }) as Promise<PageData>;
+}
+
+/**
+ * Start signing in to a cloud storage provider (desktop only)
+ */
+export async function cloudBeginAuth(
+  provider: CloudProvider,
+  client: CloudClient
+): Promise<CloudAuthorization> {
+  if (!isTauri()) {
+    throw new Error('Cloud import requires the desktop app');
+  }
+  return invoke?.('cloud_begin_auth', { provider, client }) as Promise<CloudAuthorization>;
+}
+
+/**
+ * Poll a pending sign-in; for Dropbox, pass the code the user pasted
+ */
+export async function cloudPollAuth(provider: CloudProvider, code?: string): Promise<CloudAuthStatus> {
+  if (!isTauri()) return 'denied';
+  return invoke?.('cloud_poll_auth', { provider, code }) as Promise<CloudAuthStatus>;
+}
+
+/**
+ * Forget a provider's saved sign-in
+ */
+export async function cloudSignOut(provider: CloudProvider): Promise<void> {
+  if (!isTauri()) return;
+  await invoke?.('cloud_sign_out', { provider });
+}
+
+/**
+ * Providers the user is signed in to
+ */
+export async function listCloudAccounts(): Promise<CloudProvider[]> {
+  if (!isTauri()) return [];
+  return invoke?.('list_cloud_accounts') as Promise<CloudProvider[]>;
+}
+
+/**
+ * Recently modified PDF and Word documents in a provider
+ */
+export async function listCloudDocuments(provider: CloudProvider, limit?: number): Promise<CloudDocument[]> {
+  if (!isTauri()) return [];
+  return invoke?.('list_cloud_documents', { provider, limit }) as Promise<CloudDocument[]>;
+}
+
+/**
+ * Download a cloud document and import it (desktop only)
+ */
+export async function importCloudDocument(
+  document: CloudDocument,
+  options?: ImportOptions
+): Promise<DocumentResponse> {
+  if (!isTauri()) {
+    return { success: false, message: 'Cloud import requires the desktop app', data: undefined };
+  }
+  return invoke?.('import_cloud_document', { document, options }) as Promise<DocumentResponse>;
+}
+
+/**
+ * Start the local REST API for automation (desktop builds with the
+ * api-server feature). A token is generated when none is given.
+ */
+export async function startApiServer(options?: {
+  port?: number;
+  token?: string;
+  allowLan?: boolean;
+  exportDir?: string;
+}): Promise<ApiServerInfo> {
+  if (!isTauri()) {
+    throw new Error('The API server requires the desktop app');
+  }
+  return invoke?.('start_api_server', {
+    port: options?.port,
+    token: options?.token,
+    allowLan: options?.allowLan,
+    exportDir: options?.exportDir,
+  }) as Promise<ApiServerInfo>;
+}
+
+/**
+ * Stop the API server; false if it was not running
+ */
+export async function stopApiServer(): Promise<boolean> {
+  if (!isTauri()) return false;
+  return invoke?.('stop_api_server') as Promise<boolean>;
+}
+
+/**
+ * The running API server, if any
+ */
+export async function getApiServerInfo(): Promise<ApiServerInfo | null> {
+  if (!isTauri()) return null;
+  return invoke?.('get_api_server_info') as Promise<ApiServerInfo | null>;
+}
+
+/**
+ * Run a bulk-edit script over pages (desktop only)
+ */
+export async function runScript(source: string, pages: PageData[]): Promise<ScriptResult> {
+  if (!isTauri()) {
+    throw new Error('Scripts require the desktop app');
+  }
+  return invoke?.('run_script', { source, pages }) as Promise<ScriptResult>;
+}
+
+/**
+ * Saved and built-in scripts
+ */
+export async function listScripts(): Promise<SavedScript[]> {
+  if (!isTauri()) return [];
+  return invoke?.('list_scripts') as Promise<SavedScript[]>;
+}
+
+/**
+ * Save a script to the library, replacing one with the same name
+ */
+export async function saveScript(script: SavedScript): Promise<void> {
+  if (!isTauri()) return;
+  await invoke?.('save_script', { script });
+}
+
+/**
+ * Delete a saved script
+ */
+export async function deleteScript(name: string): Promise<void> {
+  if (!isTauri()) return;
+  await invoke?.('delete_script', { name });
+}
+
+/**
+ * Check a PDF for damage without changing it (desktop only)
+ */
+export async function validatePdf(filePath: string): Promise<RepairReport> {
+  if (!isTauri()) {
+    throw new Error('PDF validation requires the desktop app');
+  }
+  return invoke?.('validate_pdf', { filePath }) as Promise<RepairReport>;
+}
+
+/**
+ * Save a repaired copy of a damaged PDF, leaving out unreadable pages
+ */
+export async function repairPdf(inputPath: string, outputPath: string): Promise<RepairReport> {
+  if (!isTauri()) {
+    throw new Error('PDF repair requires the desktop app');
+  }
+  return invoke?.('repair_pdf', { inputPath, outputPath }) as Promise<RepairReport>;
+}
+
+/**
+ * App preferences (desktop only)
+ */
+export async function getSettings(): Promise<AppSettings | null> {
+  if (!isTauri()) return null;
+  return invoke?.('get_settings') as Promise<AppSettings>;
+}
+
+/**
+ * Change some preferences; `null` resets a key to its default
+ */
+export async function updateSettings(
+  patch: { [K in keyof AppSettings]?: AppSettings[K] | null }
+): Promise<AppSettings> {
+  if (!isTauri()) {
+    throw new Error('Settings require the desktop app');
+  }
+  return invoke?.('update_settings', { patch }) as Promise<AppSettings>;
+}
+
+/**
+ * Call onChange whenever preferences change. Returns a function that stops listening.
+ */
+export async function onSettingsChanged(onChange: (settings: AppSettings) => void): Promise<() => void> {
+  if (!isTauri()) return () => {};
+  const { listen } = await import('@tauri-apps/api/event');
+  return listen<AppSettings>('settings_changed', (event) => onChange(event.payload));
+}
+
+/**
+ * Export options from the default export preset setting
+ */
+export async function getDefaultExportOptions(outputPath: string): Promise<ExportOptions> {
+  if (!isTauri()) {
+    return { format: 'pdf' };
+  }
+  return invoke?.('get_default_export_options', { outputPath }) as Promise<ExportOptions>;
+}
+
+/**
+ * Recently opened and saved projects, pinned first (desktop only)
+ */
+export async function getRecentProjects(): Promise<RecentProject[]> {
+  if (!isTauri()) return [];
+  return invoke?.('get_recent_projects') as Promise<RecentProject[]>;
+}
+
+/**
+ * Pin or unpin a recent project
+ */
+export async function pinProject(path: string, pinned: boolean): Promise<void> {
+  if (!isTauri()) return;
+  await invoke?.('pin_project', { path, pinned });
+}
+
+/**
+ * Remove a project from the recent list; the file is kept
+ */
+export async function removeRecent(path: string): Promise<void> {
+  if (!isTauri()) return;
+  await invoke?.('remove_recent', { path });
+}
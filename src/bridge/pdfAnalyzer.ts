@@ -39,9 +39,13 @@ export async function analyzePdfContent(data: Uint8Array): Promise<PdfAnalysis>
   let totalImageCoverage = 0;
   let totalTextCoverage = 0;
 
+  // Printed page numbers from /PageLabels (i, ii, ... 1, 2), when present
+  const labels = await pdf.getPageLabels().catch(() => null);
+
   for (let pageNum = 1; pageNum <= totalPages; pageNum++) {
     const page = await pdf.getPage(pageNum);
     const stats = await analyzePageContent(page, pageNum - 1);
+    if (labels?.[pageNum - 1]) stats.label = labels[pageNum - 1];
 
     totalText += stats.textObjects;
     totalImages += stats.imageObjects;
@@ -1,5 +1,6 @@
 // PNG Export Service - Renders pages to PNG images
-import type { PageData, LayerObject } from './types';
+import type { PageData, LayerObject, TextPath } from './types';
+import { flattenPath, layoutFrame, pageExclusions, placeDropCap } from './textWrap';
 
 /**
  * Render a single page to PNG blob
@@ -27,7 +28,7 @@ export async function renderPageToPng(
   for (const layer of sortedLayers) {
     if (!layer.visible) continue;
     ctx.globalAlpha = layer.opacity;
-    await renderLayer(ctx, layer);
+    await renderLayer(ctx, layer, page.layers);
   }
   
   return new Promise((resolve, reject) => {
@@ -42,10 +43,10 @@ export async function renderPageToPng(
 /**
  * Render a layer to canvas context
  */
-async function renderLayer(ctx: CanvasRenderingContext2D, layer: LayerObject): Promise<void> {
+async function renderLayer(ctx: CanvasRenderingContext2D, layer: LayerObject, pageLayers: LayerObject[]): Promise<void> {
   switch (layer.type) {
     case 'text':
-      renderTextLayer(ctx, layer);
+      renderTextLayer(ctx, layer, pageLayers);
       break;
     case 'image':
       await renderImageLayer(ctx, layer);
@@ -59,7 +60,7 @@ async function renderLayer(ctx: CanvasRenderingContext2D, layer: LayerObject): P
   }
 }
 
-function renderTextLayer(ctx: CanvasRenderingContext2D, layer: LayerObject): void {
+function renderTextLayer(ctx: CanvasRenderingContext2D, layer: LayerObject, pageLayers: LayerObject[]): void {
   if (!layer.content) return;
   
   const { bounds } = layer;
@@ -74,6 +75,54 @@ function renderTextLayer(ctx: CanvasRenderingContext2D, layer: LayerObject): voi
   ctx.fillStyle = layer.color || '#000000';
   ctx.textAlign = (layer.textAlign as CanvasTextAlign) || 'left';
   ctx.textBaseline = 'top';
+
+  if (layer.textShadow) {
+    // Shadow settings ignore the transform, so scale them to device pixels
+    const { a, b } = ctx.getTransform();
+    const k = Math.hypot(a, b);
+    ctx.shadowColor = layer.textShadow.color;
+    ctx.shadowOffsetX = layer.textShadow.offsetX * k;
+    ctx.shadowOffsetY = layer.textShadow.offsetY * k;
+    ctx.shadowBlur = (layer.textShadow.blur || 0) * k;
+  }
+
+  if (layer.textPath) {
+    renderTextOnPath(ctx, layer, layer.textPath);
+    ctx.restore();
+    return;
+  }
+
+  // Flow around wrapping layers and any drop cap line by line
+  const exclusions = pageExclusions(pageLayers, layer);
+  const lineHeight = fontSize * (layer.lineHeight || 1.2);
+  const font = (size: number, family: string) => `${fontStyle}${fontWeight} ${size}px ${family}`;
+  const measureAt = (s: string, size: number) => {
+    ctx.font = font(size, layer.dropCap?.fontFamily || fontFamily);
+    const width = ctx.measureText(s).width;
+    ctx.font = font(fontSize, fontFamily);
+    return width;
+  };
+  const dropCap = layer.dropCap && placeDropCap(layer.content, bounds, fontSize, lineHeight, layer.dropCap, measureAt);
+  if (exclusions.length || dropCap) {
+    const measure = (s: string) => ctx.measureText(s).width;
+    const align = layer.textAlign === 'center' || layer.textAlign === 'right' ? layer.textAlign : 'left';
+    ctx.textAlign = 'left';
+    let text = layer.content;
+    if (dropCap) {
+      ctx.font = font(dropCap.cap.fontSize, layer.dropCap?.fontFamily || fontFamily);
+      ctx.textBaseline = 'alphabetic';
+      paintText(ctx, layer, dropCap.cap.text, dropCap.cap.x, dropCap.cap.baseline);
+      ctx.font = font(fontSize, fontFamily);
+      ctx.textBaseline = 'top';
+      exclusions.push(dropCap.cap.exclusion);
+      text = dropCap.rest;
+    }
+    for (const line of layoutFrame(text, bounds, lineHeight, align, exclusions, measure).lines) {
+      paintText(ctx, layer, line.text, line.x, line.y);
+    }
+    ctx.restore();
+    return;
+  }
   
   // Handle text decoration
   if (layer.textDecoration === 'underline' || layer.textDecoration === 'line-through') {
@@ -102,18 +151,80 @@ function renderTextLayer(ctx: CanvasRenderingContext2D, layer: LayerObject): voi
     const metrics = ctx.measureText(testLine);
     
     if (metrics.width > bounds.width && line) {
-      ctx.fillText(line, bounds.x, y);
+      paintText(ctx, layer, line, bounds.x, y);
       line = word;
       y += lineHeight;
     } else {
       line = testLine;
     }
   }
-  ctx.fillText(line, bounds.x, y);
+  paintText(ctx, layer, line, bounds.x, y);
   
   ctx.restore();
 }
 
+/** Fill text, then stroke its outline (without repeating the shadow) */
+function paintText(ctx: CanvasRenderingContext2D, layer: LayerObject, text: string, x: number, y: number): void {
+  ctx.fillText(text, x, y);
+  const outline = layer.textOutline;
+  if (!outline || outline.width <= 0) return;
+  ctx.save();
+  ctx.shadowColor = 'transparent';
+  ctx.strokeStyle = outline.color;
+  ctx.lineWidth = outline.width;
+  ctx.lineJoin = 'round';
+  ctx.strokeText(text, x, y);
+  ctx.restore();
+}
+
+/** Point and direction at `distance` along a polyline */
+function pointAt(points: [number, number][], distance: number): { x: number; y: number; angle: number } | null {
+  let remaining = distance;
+  for (let i = 1; i < points.length; i++) {
+    const dx = points[i][0] - points[i - 1][0];
+    const dy = points[i][1] - points[i - 1][1];
+    const segment = Math.hypot(dx, dy);
+    if (segment === 0) continue;
+    if (remaining <= segment) {
+      const t = remaining / segment;
+      return { x: points[i - 1][0] + dx * t, y: points[i - 1][1] + dy * t, angle: Math.atan2(dy, dx) };
+    }
+    remaining -= segment;
+  }
+  return null;
+}
+
+/** Draw each glyph centered on the path, rotated to its direction (mirrors vortex-core text_path) */
+function renderTextOnPath(ctx: CanvasRenderingContext2D, layer: LayerObject, textPath: TextPath): void {
+  const points = flattenPath(textPath.path);
+  const chars = Array.from(layer.content || '').filter((c) => c >= ' ');
+  const spacing = layer.letterSpacing || 0;
+  const advances = chars.map((c) => ctx.measureText(c).width + spacing);
+  const total = advances.reduce((sum, a) => sum + a, 0);
+  let pathLength = 0;
+  for (let i = 1; i < points.length; i++) {
+    pathLength += Math.hypot(points[i][0] - points[i - 1][0], points[i][1] - points[i - 1][1]);
+  }
+
+  const startOffset = textPath.startOffset || 0;
+  const slack = pathLength - startOffset - total;
+  let distance = startOffset + (layer.textAlign === 'center' ? slack / 2 : layer.textAlign === 'right' ? slack : 0);
+
+  ctx.textAlign = 'center';
+  ctx.textBaseline = 'alphabetic';
+  chars.forEach((char, i) => {
+    const center = distance + advances[i] / 2;
+    distance += advances[i];
+    const at = center >= 0 ? pointAt(points, center) : null;
+    if (!at) return;
+    ctx.save();
+    ctx.translate(at.x, at.y);
+    ctx.rotate(at.angle);
+    paintText(ctx, layer, char, 0, 0);
+    ctx.restore();
+  });
+}
+
 async function renderImageLayer(ctx: CanvasRenderingContext2D, layer: LayerObject): Promise<void> {
   const { bounds } = layer;
   const src = layer.imageUrl || layer.imagePath;
@@ -0,0 +1,232 @@
+// Text Wrap - lays out frame text around the wrap contours of other layers
+// Mirrors vortex-core text_wrap so the raster preview matches PDF export
+
+import type { DropCap, LayerObject, PathData } from './types';
+
+type Point = [number, number];
+
+/** Spans narrower than this many line heights are left empty */
+const MIN_SPAN_LINES = 2;
+
+/** Capital height as a fraction of the font size, as Helvetica */
+const CAP_HEIGHT = 0.718;
+
+/**
+ * Convex polygon (page coordinates) grown by an offset
+ */
+export interface Exclusion {
+  hull: Point[];
+  offset: number;
+}
+
+export interface LineBox {
+  text: string;
+  x: number;
+  /** Top of the line band */
+  y: number;
+  width: number;
+}
+
+/**
+ * Flatten a path into one polyline, joining subpaths end to end
+ */
+export function flattenPath(path: PathData): Point[] {
+  const points: Point[] = [];
+  let start: Point = [0, 0];
+  let current: Point = [0, 0];
+  for (const cmd of path.commands) {
+    switch (cmd.type) {
+      case 'moveTo':
+        start = current = [cmd.x || 0, cmd.y || 0];
+        points.push(current);
+        break;
+      case 'lineTo':
+        current = [cmd.x || 0, cmd.y || 0];
+        points.push(current);
+        break;
+      case 'curveTo': {
+        const [x0, y0] = current;
+        const [x1, y1, x2, y2, x, y] = [cmd.x1 || 0, cmd.y1 || 0, cmd.x2 || 0, cmd.y2 || 0, cmd.x || 0, cmd.y || 0];
+        for (let i = 1; i <= 16; i++) {
+          const t = i / 16;
+          const u = 1 - t;
+          const [a, b, c, d] = [u * u * u, 3 * u * u * t, 3 * u * t * t, t * t * t];
+          points.push([a * x0 + b * x1 + c * x2 + d * x, a * y0 + b * y1 + c * y2 + d * y]);
+        }
+        current = [x, y];
+        break;
+      }
+      case 'closePath':
+        current = start;
+        points.push(current);
+        break;
+    }
+  }
+  return points;
+}
+
+/**
+ * Convex hull (monotone chain)
+ */
+export function convexHull(input: Point[]): Point[] {
+  const points = [...input].sort((a, b) => a[0] - b[0] || a[1] - b[1]);
+  if (points.length < 3) return points;
+  const cross = (o: Point, a: Point, b: Point) => (a[0] - o[0]) * (b[1] - o[1]) - (a[1] - o[1]) * (b[0] - o[0]);
+  const hull: Point[] = [];
+  for (const pass of [points, [...points].reverse()]) {
+    const floor = hull.length;
+    for (const p of pass) {
+      while (hull.length >= floor + 2 && cross(hull[hull.length - 2], hull[hull.length - 1], p) <= 0) hull.pop();
+      hull.push(p);
+    }
+    hull.pop();
+  }
+  return hull;
+}
+
+/**
+ * Exclusion for a layer's wrap settings, if it has any
+ */
+export function layerExclusion(layer: LayerObject): Exclusion | null {
+  const wrap = layer.textWrap;
+  if (!wrap || wrap.contour === 'none') return null;
+  const { x, y, width, height } = layer.bounds;
+  const rect: Point[] = [[x, y], [x + width, y], [x + width, y + height], [x, y + height]];
+  const hull = wrap.contour === 'convex-hull' && layer.pathData ? convexHull(flattenPath(layer.pathData)) : rect;
+  return hull.length >= 3 ? { hull, offset: Math.max(wrap.offset || 0, 0) } : null;
+}
+
+/**
+ * Horizontal extent of an exclusion across the band top..bottom
+ */
+export function exclusionSpan(exclusion: Exclusion, top: number, bottom: number): [number, number] | null {
+  const { hull, offset } = exclusion;
+  const [t, b] = [top - offset, bottom + offset];
+  const xs: number[] = [];
+  hull.forEach((p, i) => {
+    const q = hull[(i + 1) % hull.length];
+    if (p[1] >= t && p[1] <= b) xs.push(p[0]);
+    for (const y of [t, b]) {
+      if ((p[1] - y) * (q[1] - y) < 0) xs.push(p[0] + ((q[0] - p[0]) * (y - p[1])) / (q[1] - p[1]));
+    }
+  });
+  return xs.length ? [Math.min(...xs) - offset, Math.max(...xs) + offset] : null;
+}
+
+/**
+ * Exclusions from other visible layers on the page that reach the frame
+ */
+export function pageExclusions(layers: LayerObject[], frame: LayerObject): Exclusion[] {
+  const { y, height } = frame.bounds;
+  return layers
+    .filter((l) => l.visible && l.id !== frame.id)
+    .map(layerExclusion)
+    .filter((e): e is Exclusion => e !== null && exclusionSpan(e, y, y + height) !== null);
+}
+
+function freeSpans(left: number, right: number, top: number, bottom: number, exclusions: Exclusion[]): [number, number][] {
+  let spans: [number, number][] = [[left, right]];
+  for (const e of exclusions) {
+    const span = exclusionSpan(e, top, bottom);
+    if (!span) continue;
+    spans = spans
+      .flatMap(([a, b]): [number, number][] => [[a, Math.min(b, span[0])], [Math.max(a, span[1]), b]])
+      .filter(([a, b]) => b > a);
+  }
+  return spans;
+}
+
+/**
+ * Lay out text in the frame's bounds around exclusions; each newline
+ * starts a paragraph on a fresh line
+ */
+export function layoutFrame(
+  text: string,
+  frame: LayerObject['bounds'],
+  lineHeight: number,
+  align: 'left' | 'center' | 'right',
+  exclusions: Exclusion[],
+  measure: (s: string) => number
+): { lines: LineBox[]; overset: boolean } {
+  const paragraphs = text.split('\n').map((p) => p.split(/\s+/).filter(Boolean));
+  const lines: LineBox[] = [];
+  let [para, word] = [0, 0];
+  let top = frame.y;
+
+  while (para < paragraphs.length && lineHeight > 0 && top + lineHeight <= frame.y + frame.height + 0.01) {
+    const spans = freeSpans(frame.x, frame.x + frame.width, top, top + lineHeight, exclusions);
+    const fullWidth = spans.length === 1 && spans[0][1] - spans[0][0] >= frame.width - 0.01;
+    for (const [x0, x1] of spans) {
+      const width = x1 - x0;
+      if (!fullWidth && width < lineHeight * MIN_SPAN_LINES) continue;
+      const words = paragraphs[para];
+      let line = '';
+      while (word < words.length) {
+        const candidate = line ? `${line} ${words[word]}` : words[word];
+        if (measure(candidate) > width && !(!line && fullWidth)) break;
+        line = candidate;
+        word++;
+      }
+      if (line) {
+        const used = measure(line);
+        const x = x0 + (align === 'center' ? (width - used) / 2 : align === 'right' ? width - used : 0);
+        lines.push({ text: line, x, y: top, width: used });
+      }
+      if (word >= words.length) break;
+    }
+    if (word >= paragraphs[para].length) {
+      para++;
+      word = 0;
+    }
+    top += lineHeight;
+  }
+
+  return { lines, overset: para < paragraphs.length };
+}
+
+export interface DropCapBox {
+  /** The letters set large */
+  text: string;
+  fontSize: number;
+  /** Left end of the cap's baseline */
+  x: number;
+  baseline: number;
+  width: number;
+  /** Room the cap takes from the lines beside it */
+  exclusion: Exclusion;
+}
+
+/**
+ * Split the drop cap off `text` and place it at the top-left of the frame;
+ * null when it drops fewer than 2 lines or the text does not start with a letter
+ */
+export function placeDropCap(
+  text: string,
+  frame: LayerObject['bounds'],
+  fontSize: number,
+  lineHeight: number,
+  cap: DropCap,
+  measure: (s: string, size: number) => number
+): { cap: DropCapBox; rest: string } | null {
+  const lines = cap.lines ?? 3;
+  if (lines < 2 || fontSize <= 0) return null;
+  const chars = Array.from(text.trimStart());
+  const wanted = Math.max(cap.letters ?? 1, 1);
+  let [letters, end] = [0, 0];
+  for (const c of chars) {
+    if (/\s/.test(c) || letters >= wanted) break;
+    if (/[\p{L}\p{N}]/u.test(c)) letters++;
+    end++;
+  }
+  if (!letters) return null;
+  const capText = chars.slice(0, end).join('');
+  const capSize = ((lines - 1) * lineHeight + CAP_HEIGHT * fontSize) / CAP_HEIGHT;
+  const baseline = frame.y + fontSize + (lines - 1) * lineHeight;
+  const width = measure(capText, capSize);
+  const right = frame.x + width + Math.max(cap.gap || 0, 0);
+  const hull: Point[] = [[frame.x, frame.y], [right, frame.y], [right, baseline], [frame.x, baseline]];
+  return {
+    cap: { text: capText, fontSize: capSize, x: frame.x, baseline, width, exclusion: { hull, offset: 0 } },
+    rest: chars.slice(end).join(''),
+  };
+}
@@ -37,6 +37,204 @@ export interface ImageMetadata {
   height: number;
   colorSpace: string;
   dpi: number;
+  /** Embedded ICC profile the pixels are in; re-embedded on PDF export */
+  iccProfile?: string;
+}
+
+/** External file a linked image layer was last loaded from */
+export interface ImageLink {
+  path: string;
+  /** Modification time, seconds since the Unix epoch */
+  modified: number;
+  /** SHA-256 of the file contents, hex */
+  hash: string;
+}
+
+/** How saveProject stores linked images: as links, or embedded with the links dropped */
+export type LinkedImages = 'keep' | 'embed';
+
+/** A layer showing an asset */
+export interface AssetUsage {
+  pageIndex: number;
+  layerId: string;
+}
+
+/** An image asset: the id a layer's image is cached under, with the layers using it */
+export interface AssetInfo {
+  id: string;
+  /** File the image came from or is linked to */
+  path?: string;
+  linked: boolean;
+  width: number;
+  height: number;
+  /** Encoded size in bytes; absent when not in the cache */
+  size?: number;
+  usages: AssetUsage[];
+  /** Neither cached nor readable from its file, or a linked file is gone */
+  missing: boolean;
+}
+
+/** Result of replaceAsset and relinkMissingAssets */
+export interface AssetUpdate {
+  pages: PageData[];
+  /** Layers now showing the new image */
+  updated: string[];
+  /** Missing assets no file was found for */
+  unresolved: string[];
+}
+
+/** Embedding permissions of a font file (OpenType fsType); 'restricted' must not be handed on */
+export type FontLicense = 'installable' | 'editable' | 'previewAndPrint' | 'restricted' | 'unknown';
+
+/** A font file collected by packageProject */
+export interface PackagedFont {
+  family: string;
+  /** Path inside the package */
+  file: string;
+  /** Extracted from the imported PDF rather than installed */
+  embedded: boolean;
+  license: FontLicense;
+  subsettingAllowed: boolean;
+}
+
+/** An image collected by packageProject */
+export interface PackagedImage {
+  assetId: string;
+  file: string;
+  usages: number;
+}
+
+/** What packageProject collected; also written to report.txt in the package */
+export interface PackageReport {
+  projectFile: string;
+  images: PackagedImage[];
+  fonts: PackagedFont[];
+  missingImages: string[];
+  missingFonts: string[];
+}
+
+/** Options for traceImage */
+export interface TraceOptions {
+  /** Luminance below which a pixel is ink, 0-255; 128 by default */
+  threshold?: number;
+  /** Corner rounding: 0 keeps every corner, 1.334 rounds all; 1 by default */
+  smoothing?: number;
+  /** Outlines enclosing fewer pixels are dropped as noise; 4 by default */
+  despeckle?: number;
+  /** Trace the light pixels instead, for a light logo on a dark ground */
+  invert?: boolean;
+  /** Fill color; the average ink color when absent */
+  color?: string;
+}
+
+/** An image layer traced into a vector layer */
+export interface TraceResult {
+  layer: LayerObject;
+  /** Closed outlines, holes included */
+  outlines: number;
+  /** Path commands, a measure of the output's complexity */
+  nodes: number;
+}
+
+/** Color space convertDocumentColors converts to */
+export type ColorTarget = 'grayscale' | 'cmyk';
+
+export interface ColorConversionOptions {
+  target: ColorTarget;
+  /** Convert image pixels as well as layer colors */
+  includeImages?: boolean;
+  /** Total ink limit for CMYK, 100-400 percent (default 260) */
+  maxInk?: number;
+  /** Only count what would change; pages come back as given */
+  preview?: boolean;
+}
+
+/** Result of convertDocumentColors */
+export interface ColorConversion {
+  pages: PageData[];
+  layersChanged: number;
+  imagesChanged: number;
+  colorsChanged: number;
+}
+
+/** Result of refreshLinkedImages */
+export interface LinkRefresh {
+  pages: PageData[];
+  /** Layers reloaded from a changed file */
+  refreshed: string[];
+  /** Layers whose file is gone; they keep the last loaded image */
+  missing: string[];
+}
+
+/** PDF / CSS blend modes; `normal` when absent */
+export type BlendMode =
+  | 'normal' | 'multiply' | 'screen' | 'overlay' | 'darken' | 'lighten'
+  | 'color-dodge' | 'color-burn' | 'hard-light' | 'soft-light' | 'difference'
+  | 'exclusion' | 'hue' | 'saturation' | 'color' | 'luminosity';
+
+/** Baseline path for text set along a curve, in page coordinates */
+export interface TextPath {
+  path: PathData;
+  /** Distance along the path before the first glyph, in points */
+  startOffset?: number;
+}
+
+export interface TextOutline {
+  color: string;
+  width: number;
+}
+
+export interface TextShadow {
+  color: string;
+  offsetX: number;
+  offsetY: number;
+  /** Blur radius; raster output only */
+  blur?: number;
+}
+
+/** Outline text frames wrap around; `none` turns wrapping off */
+export type WrapContour = 'none' | 'bounding-box' | 'convex-hull';
+
+export interface TextWrap {
+  contour: WrapContour;
+  /** Gap between the contour and text, in points */
+  offset?: number;
+}
+
+/** Large initial letter set into the first lines of a text frame */
+export interface DropCap {
+  /** Lines the cap drops through (default 3) */
+  lines?: number;
+  /** Letters set large (default 1) */
+  letters?: number;
+  /** The layer's font when absent */
+  fontFamily?: string;
+  /** Space between the cap and the text beside it, in points */
+  gap?: number;
+}
+
+/** Paragraph controls reflow honours when text breaks across pages */
+export interface Pagination {
+  /** Never split the paragraph between pages */
+  keepTogether?: boolean;
+  /** Keep the paragraph on the page the next one starts on, as for headings */
+  keepWithNext?: boolean;
+  /** Fewest lines carried over to the top of the next page (default 2) */
+  widows?: number;
+  /** Fewest lines left at the foot of a page (default 2) */
+  orphans?: number;
+}
+
+/** Where a note is set */
+export type NoteKind = 'footnote' | 'endnote';
+
+/** Footnote or endnote anchored in a text layer; numbers come from reading order */
+export interface Note {
+  id: string;
+  /** Byte offset (UTF-8) into the layer's content that the reference mark follows */
+  anchor: number;
+  kind?: NoteKind;
+  text: string;
 }
 
 export interface LayerObject {
@@ -47,6 +245,7 @@ export interface LayerObject {
   locked: boolean;
   zIndex: number;
   opacity: number;
+  blendMode?: BlendMode;
   // Text fields
   content?: string;
   fontFamily?: string;
@@ -58,17 +257,26 @@ export interface LayerObject {
   lineHeight?: number;
   letterSpacing?: number;
   backgroundColor?: string;
+  textPath?: TextPath;
+  textOutline?: TextOutline;
+  textShadow?: TextShadow;
+  dropCap?: DropCap;
+  notes?: Note[];
+  /** Keep and widow/orphan controls; the defaults when absent */
+  pagination?: Pagination;
   color?: string;
   textAlign?: 'left' | 'center' | 'right' | 'justify';
   // Image fields
   imageUrl?: string;
   imagePath?: string;
   imageData?: ImageMetadata;
+  imageLink?: ImageLink;
   // Shape/Vector fields
   shapeType?: string;
   strokeColor?: string;
   strokeWidth?: number;
   fillColor?: string;
+  textWrap?: TextWrap;
   pathData?: PathData;
   // Watermark fields
   blendMode?: string;
@@ -81,6 +289,10 @@ export interface LayerObject {
   // Metadata
   sourceType: 'extracted' | 'manual' | 'imported';
   role: 'background' | 'content' | 'header' | 'footer' | 'annotation';
+  /** Recognition confidence and review state of OCR'd text */
+  ocr?: OcrInfo;
+  /** Free-form categories such as 'print-only', 'screen-only' or 'notes', for export filters */
+  tags?: string[];
   // Hierarchical text data (Paragraph → Line → Word)
   metadata?: {
     lines?: Array<{
@@ -92,6 +304,86 @@ export interface LayerObject {
   };
 }
 
+export type PageLabelStyle = 'decimal' | 'upper-roman' | 'lower-roman' | 'upper-alpha' | 'lower-alpha';
+
+/** Printed page number: prefix plus the page's number in `style` */
+export interface PageLabel {
+  style?: PageLabelStyle;
+  prefix?: string;
+  number: number;
+}
+
+/** Evenly spaced baselines: offset, offset + spacing, … */
+export interface BaselineGrid {
+  spacing: number;
+  /** First baseline from the page top */
+  offset: number;
+}
+
+/** What applyPageLayout copies from the source page */
+export interface PageLayoutOptions {
+  /** Roles of the layers copied and replaced (default background, header, footer) */
+  roles?: LayerObject['role'][];
+  /** Also copy the guides (default true) */
+  guides?: boolean;
+}
+
+/** What a section of the book holds */
+export type SectionKind = 'frontMatter' | 'chapter' | 'backMatter';
+
+/** Run of pages from `startPage` up to where the next section starts */
+export interface Section {
+  id: string;
+  /** First page, 0-based */
+  startPage: number;
+  kind?: SectionKind;
+  /** Contents and running head title; the section's first heading when absent */
+  title?: string;
+  /** Page number style; lower roman for front matter, else decimal, when absent */
+  numbering?: PageLabelStyle;
+  prefix?: string;
+  /** First page number; numbering carries on from the section before when absent */
+  restartAt?: number;
+  /** Page whose background, header and footer layers the section's pages take */
+  masterPage?: number;
+}
+
+/** A section with its page range and derived title and numbers */
+export interface SectionInfo {
+  id: string;
+  kind: SectionKind;
+  title: string;
+  chapter?: number;
+  startPage: number;
+  /** Last page, inclusive */
+  endPage: number;
+  /** Printed number of the first page */
+  firstLabel: string;
+}
+
+/** Sections after an edit, with the pages relabeled and restamped */
+export interface SectionsResult {
+  pages: PageData[];
+  sections: Section[];
+  sectionId: string;
+}
+
+/** Guide lines of a page, in points from its top-left corner */
+export interface PageGuides {
+  /** x positions of margin and column edges */
+  vertical?: number[];
+  /** y positions of the top and bottom margins */
+  horizontal?: number[];
+  baselineGrid?: BaselineGrid;
+}
+
+/** Bounds after snapping and the guides they snapped to */
+export interface SnapResult {
+  bounds: Bounds;
+  vertical?: number;
+  horizontal?: number;
+}
+
 export interface PageData {
   pageIndex: number;
   width: number;
@@ -102,13 +394,40 @@ export interface PageData {
     originalPageIndex?: number;
     rotation?: number;
     mediaBox?: [number, number, number, number];
+    /** Source PDF boxes as [x0, y0, x1, y1], y up; layers sit in the crop box */
+    cropBox?: [number, number, number, number];
+    trimBox?: [number, number, number, number];
+    bleedBox?: [number, number, number, number];
+    pageLabel?: PageLabel;
+    /** BCP 47 language tag when the page differs from the document */
+    language?: string;
+    /** Layout guides inferred by detect_layout_guides */
+    guides?: PageGuides;
+    custom?: Record<string, string>;
   };
+  background?: PageBackground;
+}
+
+/** Page fill drawn beneath every layer; `image` is an `image://` id or a file path */
+export interface PageBackground {
+  color?: string;
+  image?: string;
+}
+
+export type LengthUnit = 'pt' | 'mm' | 'in' | 'px';
+
+/** A length unit; `dpi` applies to `px` only (96 when omitted) */
+export interface DocumentUnits {
+  unit: LengthUnit;
+  dpi?: number;
 }
 
 export interface DocumentData {
   pageWidth: number;
   pageHeight: number;
   pages: PageData[];
+  /** Unit of all sizes and bounds; points when absent */
+  units?: DocumentUnits;
 }
 
 export interface DocumentMetadata {
@@ -117,12 +436,26 @@ export interface DocumentMetadata {
   created: string;
   modified: string;
   description?: string;
+  subject?: string;
+  keywords?: string[];
+  isbn?: string;
+  publisher?: string;
+  /** BCP 47 language tag, e.g. "en-US" */
+  language?: string;
+  /** Exported as PDF Info entries and DOCX custom properties */
+  custom?: Record<string, string>;
 }
 
 export interface DocumentResponse {
   success: boolean;
   message: string;
   data?: DocumentData;
+  /** Damage found in the source file and how it was handled */
+  repair?: RepairReport;
+  /** Title, author, keywords and other properties read from the source file */
+  metadata?: DocumentMetadata;
+  /** The imported file, set by the bridge on desktop imports */
+  source?: SourceDocument;
 }
 
 export interface ExportResult {
@@ -132,6 +465,37 @@ export interface ExportResult {
   data?: Uint8Array;
 }
 
+export type ProjectEncoding = 'json' | 'messagepack';
+
+/** Page margins in points */
+export interface Margins {
+  top: number;
+  right: number;
+  bottom: number;
+  left: number;
+}
+
+export type PageSizePreset = 'letter' | 'a4' | 'a5' | 'trade6x9' | 'digest5x8' | 'squarePhoto';
+
+/** Trim size, margins and bleed, all in points */
+export interface PageSetup {
+  width: number;
+  height: number;
+  margins?: Margins;
+  bleed?: number;
+}
+
+export interface PageSizeInfo {
+  preset: PageSizePreset;
+  name: string;
+  width: number;
+  height: number;
+  margins: Margins;
+}
+
+/** How layers follow a page size change */
+export type ResizeMode = 'scale' | 'center' | 'topLeft';
+
 export interface BookProjectData {
   format: string;
   version: string;
@@ -141,15 +505,276 @@ export interface BookProjectData {
     defaultFont?: string;
     defaultFontSize?: number;
     exportQuality?: string;
+    encoding?: ProjectEncoding;
+    margins?: Margins;
+    bleed?: number;
+    /** Split exports saved with this project */
+    exportPipelines?: ExportPipeline[];
+    displayUnits?: DocumentUnits;
+    /** Corrections for OCR output in this project */
+    ocrDictionary?: OcrDictionary;
+    /** File the project was imported from, for re-importing pages */
+    source?: SourceDocument;
   };
+  /** Front matter, chapters and back matter */
+  sections?: Section[];
+}
+
+/** Source file of an imported project */
+export interface SourceDocument {
+  path: string;
+  /** Import type: 'pdf', 'docx', 'png', … */
+  fileType: string;
+}
+
+/** A run of text with one font and color */
+export interface TextSpan {
+  text: string;
+  bounds: Bounds;
+  fontName?: string;
+  fontFamily?: string;
+  fontSize: number;
+  fontWeight?: number;
+  italic?: boolean;
+  color?: string;
+}
+
+export interface StructuredTextLine {
+  bounds: Bounds;
+  text: string;
+  spans: TextSpan[];
+}
+
+export interface StructuredTextBlock {
+  bounds: Bounds;
+  lines: StructuredTextLine[];
+}
+
+export interface StructuredTextPage {
+  pageIndex: number;
+  width: number;
+  height: number;
+  blocks: StructuredTextBlock[];
+}
+
+/** Pages → blocks → lines → spans, as returned by extract_structured_text */
+export interface StructuredText {
+  pages: StructuredTextPage[];
+}
+
+/** Which text layers getPageText reads */
+export interface PageTextOptions {
+  /** Also return style runs */
+  styles?: boolean;
+  /** Only layers with these roles; all when empty */
+  roles?: LayerObject['role'][];
+  /** Only these layers, e.g. the selection being copied */
+  layerIds?: string[];
+}
+
+/** Text in one style; offsets are UTF-16 indices into PageText.text */
+export interface StyleRun {
+  start: number;
+  end: number;
+  fontFamily: string;
+  fontSize: number;
+  fontWeight: number;
+  italic: boolean;
+  color: string;
+}
+
+/** A page's text in reading order: lines joined by newlines, blocks by blank lines */
+export interface PageText {
+  pageIndex: number;
+  text: string;
+  runs?: StyleRun[];
+}
+
+/** Unstructured spans of one page (browser input to extract_structured_text) */
+export interface SpanPage {
+  pageIndex: number;
+  width: number;
+  height: number;
+  spans: TextSpan[];
+}
+
+/** What a text layer is in the document structure */
+export type StructureRole = 'heading' | 'body' | 'caption' | 'footnote';
+
+/** A classified text layer */
+export interface StructureElement {
+  layerId: string;
+  pageIndex: number;
+  role: StructureRole;
+  /** Heading level, 1 being the largest */
+  level?: number;
+  text: string;
+  bounds: Bounds;
+}
+
+/** A heading and the headings under it */
+export interface OutlineNode {
+  title: string;
+  level: number;
+  pageIndex: number;
+  /** Layers making up the heading; the first is the navigation target */
+  layerIds: string[];
+  children: OutlineNode[];
+}
+
+/** Classified text and heading tree, as returned by extract_structure */
+export interface DocumentStructure {
+  bodyFontSize: number;
+  bodyFontWeight: number;
+  elements: StructureElement[];
+  outline: OutlineNode[];
+}
+
+/** Color mode requested from a scanner */
+export type ScanColorMode = 'color' | 'gray' | 'lineart';
+
+/** A scanner reported by list_scanners */
+export interface ScannerDevice {
+  id: string;
+  name: string;
+}
+
+/** Options for scan_pages */
+export interface ScanOptions {
+  device?: string;
+  dpi?: number;
+  colorMode?: ScanColorMode;
+  feeder?: boolean;
+  startPage?: number;
+  ocr?: boolean;
+  language?: string;
+  /** Project corrections for OCR post-processing */
+  dictionary?: OcrDictionary;
+}
+
+/** Point in image pixels */
+export interface ImagePoint {
+  x: number;
+  y: number;
+}
+
+/** Result of correct_page_photo */
+export interface CorrectedPhoto {
+  imageId: string;
+  width: number;
+  height: number;
+  /** Top-left, top-right, bottom-right, bottom-left, in source pixels */
+  corners: ImagePoint[];
+}
+
+/** Text or logo stamped diagonally across every exported page */
+export interface Watermark {
+  text?: string;
+  imageId?: string;
+  color?: string;
+  /** 0 (invisible) to 1 (solid) */
+  opacity?: number;
+  /** Counter-clockwise degrees; the page diagonal when absent */
+  angle?: number;
+  /** Fraction of the page diagonal */
+  scale?: number;
 }
 
+export type StampPosition =
+  | 'top-left'
+  | 'top-center'
+  | 'top-right'
+  | 'bottom-left'
+  | 'bottom-center'
+  | 'bottom-right';
+
+/** Header/footer text stamped at export; supports {page}, {total} and {n} */
+export interface Stamp {
+  text: string;
+  position?: StampPosition;
+  fontSize?: number;
+  color?: string;
+  margin?: number;
+  startNumber?: number;
+  digits?: number;
+}
+
+/** Operations a reader may perform on an encrypted PDF (all allowed by default) */
+export interface PdfPermissions {
+  print?: boolean;
+  printHighQuality?: boolean;
+  copy?: boolean;
+  modify?: boolean;
+  annotate?: boolean;
+  fillForms?: boolean;
+  assemble?: boolean;
+}
+
+/** AES-256 password protection applied at export */
+export interface PdfEncryption {
+  /** Needed to open the file; empty allows opening without a password */
+  userPassword?: string;
+  ownerPassword: string;
+  permissions?: PdfPermissions;
+}
+
+/**
+ * Digital signature from a PKCS#12 (.p12/.pfx) certificate. Only AES-encrypted
+ * files (the OpenSSL 3 default) are read; legacy 3DES/RC2 files are rejected
+ * with an error asking to re-export them.
+ */
+export interface PdfSignature {
+  certificatePath: string;
+  password?: string;
+  reason?: string;
+  location?: string;
+  contactInfo?: string;
+}
+
+export type OverlayPlacement = 'over' | 'under';
+export type OverlayScale = 'none' | 'fit' | 'fill' | 'stretch';
+export type OverlayAlign =
+  | 'top-left'
+  | 'top'
+  | 'top-right'
+  | 'left'
+  | 'center'
+  | 'right'
+  | 'bottom-left'
+  | 'bottom'
+  | 'bottom-right';
+/** Stamp page used past the end of the stamp document */
+export type OverlayRepeat = 'last' | 'cycle' | 'none';
+
+/** Options for stamping one PDF's pages onto another's */
+export interface OverlayOptions {
+  placement?: OverlayPlacement;
+  scale?: OverlayScale;
+  align?: OverlayAlign;
+  repeat?: OverlayRepeat;
+  /** 0 (invisible) to 1 (solid) */
+  opacity?: number;
+}
+
+/** One input of a PDF merge; `pages` uses ranges like "1-3, 5, 8-" */
+export interface MergeInput {
+  path: string;
+  pages?: string;
+}
+
+/** How a PDF is split into files */
+export type SplitMode =
+  | { by: 'ranges'; ranges: string[] }
+  | { by: 'bookmarks'; level?: number }
+  | { by: 'every'; pages: number };
+
 export interface LayerUpdates {
   bounds?: Bounds;
   visible?: boolean;
   locked?: boolean;
   zIndex?: number;
   opacity?: number;
+  blendMode?: BlendMode;
   content?: string;
   imagePath?: string;
   imageUrl?: string;
@@ -162,6 +787,11 @@ export interface LayerUpdates {
   lineHeight?: number;
   letterSpacing?: number;
   backgroundColor?: string;
+  /** A `none` contour clears the wrap */
+  textWrap?: TextWrap;
+  /** Fewer than 2 lines clears the drop cap */
+  dropCap?: DropCap;
+  pagination?: Pagination;
   color?: string;
   textAlign?: string;
   strokeColor?: string;
@@ -174,22 +804,141 @@ export interface LayerUpdates {
   watermarkScale?: number;
   watermarkRotation?: number;
   watermarkTileSpacing?: number;
+  tags?: string[];
 }
 
 export interface ExportOptions {
-  format: 'pdf' | 'docx' | 'bookproj' | 'png';
+  format: 'pdf' | 'docx' | 'bookproj' | 'png' | 'speech' | 'images' | 'contactsheet' | 'epub';
   filename?: string;
   pageRange?: [number, number];
   imageQuality?: number;       // 0.0 - 1.0 for JPEG/PNG compression
   compressText?: boolean;
-  createLayers?: boolean;
+  createLayers?: boolean;      // PDF: toggleable layers per layer role or first tag
+  /** Source PDF box imported pages are exported at (default 'crop') */
+  pageBox?: PageBox;
   // PNG-specific
   pngScale?: number;           // Render scale (1.0 = 72dpi, 2.0 = 144dpi)
   zipMultiple?: boolean;       // ZIP multiple pages into single file
+  // Speech-specific
+  speech?: SpeechOptions;
+  // Image sequence and contact sheet (desktop only)
+  dpi?: number;                // Render resolution (default 150)
+  images?: ImageSequenceOptions;
+  contactSheet?: ContactSheetOptions;
+  // Fixed-layout EPUB
+  epub?: EpubOptions;
+  /** Layers left out of this output */
+  layerFilter?: LayerFilter;
+  /** Footnote numbering and note layout; DOCX keeps its own */
+  notes?: NoteOptions;
+  /** The project's sections, for running heads, the PDF outline and EPUB contents */
+  sections?: Section[];
+  /** One file per section (PDF, DOCX and EPUB, desktop only); the page range is ignored */
+  splitSections?: boolean;
+  /** The project's track-changes mode, saved with Book Project exports */
+  trackChanges?: boolean;
+}
+
+/**
+ * Which layers an export keeps: those matching every non-empty include list and
+ * none of the exclude lists; tags compare case-insensitively
+ */
+export interface LayerFilter {
+  includeTags?: string[];
+  excludeTags?: string[];
+  includeRoles?: LayerObject['role'][];
+  excludeRoles?: LayerObject['role'][];
+  includeSources?: LayerObject['sourceType'][];
+  excludeSources?: LayerObject['sourceType'][];
 }
 
 export type ExportFormat = ExportOptions['format'];
 
+/** What a speech export writes; audio is desktop-only */
+export type SpeechOutput = 'audio' | 'ssml' | 'text';
+
+/** Audio proofing export options */
+export interface SpeechOptions {
+  output?: SpeechOutput;
+  /** Engine voice name (system default when absent) */
+  voice?: string;
+  /** Speaking rate in words per minute */
+  rate?: number;
+  /** One file per chapter (default true) */
+  splitChapters?: boolean;
+}
+
+/** File format of image sequence pages; TIFFs are LZW-compressed */
+export type ImageFileFormat = 'png' | 'jpeg' | 'tiff';
+
+/** Image sequence export options */
+export interface ImageSequenceOptions {
+  format?: ImageFileFormat;
+  colorMode?: 'rgb' | 'grayscale';
+  /** Pack the numbered files into one zip archive */
+  zip?: boolean;
+}
+
+/** N-up proof sheet layout; lengths in points */
+export interface ContactSheetOptions {
+  /** Default 'letter' */
+  paper?: PageSizePreset;
+  landscape?: boolean;
+  /** Default 3 x 4 */
+  columns?: number;
+  rows?: number;
+  /** Default 36 */
+  margin?: number;
+  /** Space between thumbnails (default 18) */
+  gap?: number;
+  /** Caption thumbnails with their page label (default true) */
+  pageNumbers?: boolean;
+  /** Print each page's annotation text under its thumbnail */
+  annotations?: boolean;
+}
+
+/** Fixed-layout EPUB 3 reader settings */
+export interface EpubOptions {
+  /** Orientation readers lock to (default 'auto') */
+  orientation?: 'auto' | 'portrait' | 'landscape';
+  /** When readers show two-page spreads (default 'auto') */
+  spread?: 'auto' | 'none' | 'landscape' | 'both';
+  /** Kindle book type, e.g. 'children' or 'comic' */
+  bookType?: string;
+  /** Pages turn right to left, as in manga */
+  rightToLeft?: boolean;
+}
+
+/** Source PDF page box */
+export type PageBox = 'crop' | 'media' | 'trim' | 'bleed';
+
+/** Backend export options of one pipeline target */
+export interface ExportTargetOptions {
+  format: 'pdf' | 'docx' | 'bookproj' | 'speech';
+  /** Relative paths are resolved in the folder given to exportPipeline */
+  outputPath: string;
+  pageRange?: [number, number];
+  dpi?: number;
+  colorSpace?: 'rgb' | 'cmyk';
+  /** Print bleed in points (PDF only) */
+  bleed?: number;
+  imageQuality?: number;
+  pageBox?: PageBox;
+  layerFilter?: LayerFilter;
+}
+
+/** One output of an export pipeline, e.g. 'Interior' or 'Cover' */
+export interface ExportTarget {
+  name: string;
+  options: ExportTargetOptions;
+}
+
+/** Several outputs of one document, each with its own page range and options */
+export interface ExportPipeline {
+  name: string;
+  targets: ExportTarget[];
+}
+
 // PDF Content Analysis Types
 
 /** PDF content type classification */
@@ -216,6 +965,8 @@ export interface PageContentStats {
   textCharCount: number;
   imageCoverage: number;  // 0.0 - 1.0
   textCoverage: number;   // 0.0 - 1.0
+  /** Printed page number from /PageLabels, e.g. "iv" */
+  label?: string;
 }
 
 /** Complete PDF analysis result */
@@ -244,6 +995,16 @@ export interface OcrOptions {
   language?: string;
   renderDpi?: number;
   minConfidence?: number;
+  /** Project corrections for post-processing */
+  dictionary?: OcrDictionary;
+}
+
+/** User corrections for OCR output */
+export interface OcrDictionary {
+  /** Misread words and their corrections, matched ignoring case */
+  replacements?: Record<string, string>;
+  /** Words that are right as read: names, jargon, part numbers */
+  words?: string[];
 }
 
 /** Reconstruction result */
@@ -255,6 +1016,23 @@ export interface ReconstructionResult {
   confidence: number;
 }
 
+/** Image layer with no text over it, OCR'd on its own by ocrPageRegions */
+export interface OcrRegion {
+  pageIndex: number;
+  /** The image layer */
+  layerId: string;
+  bounds: Bounds;
+  /** Text layers merged in */
+  layersAdded: number;
+}
+
+/** Result of ocrPageRegions */
+export interface RegionOcrResult {
+  pages: PageData[];
+  regions: OcrRegion[];
+  textLayersAdded: number;
+}
+
 /** OCR word with bounding box */
 export interface OcrWord {
   text: string;
@@ -269,6 +1047,39 @@ export interface OcrResult {
   words: OcrWord[];
 }
 
+export type OcrReviewStatus = 'pending' | 'accepted' | 'corrected';
+
+/** OCR provenance of a text layer; word bounds in page points */
+export interface OcrInfo {
+  /** Mean word confidence, 0-1 */
+  confidence: number;
+  words?: OcrWord[];
+  review?: OcrReviewStatus;
+}
+
+/** OCR text layer awaiting review */
+export interface OcrReviewItem {
+  layerId: string;
+  text: string;
+  confidence: number;
+  /** Words below the threshold */
+  words: OcrWord[];
+}
+
+/** Queued layers of one page, least confident first */
+export interface OcrReviewPage {
+  pageIndex: number;
+  items: OcrReviewItem[];
+}
+
+export type OcrReviewAction = { action: 'accept' } | { action: 'correct'; text: string };
+
+/** Result of reviewOcrLayer */
+export interface OcrReviewResult {
+  pages: PageData[];
+  queue: OcrReviewPage[];
+}
+
 // ============================================================================
 // Import Options Types
 // ============================================================================
@@ -324,6 +1135,12 @@ export interface ImportOptions {
   // General options
   preserveAnnotations?: boolean;
   extractImages?: boolean;
+  // Drop layers that paint nothing once a PDF is imported
+  prune?: PruneOptions;
+  // Smart quotes, dashes and ellipses in the imported text
+  typography?: TypographyOptions;
+  // Saved import mapping giving the extracted layers their roles, tags and styles
+  importMapping?: string;
 }
 
 /** Imposed page result */
@@ -347,3 +1164,566 @@ export interface ImpositionResult {
   paperSize: { width: number; height: number };
   duplex: boolean;
 }
+
+/** Paper size of a booklet imposition (calculate_booklet_imposition / previewImposition) */
+export type BookletPaperSize = 'a3' | 'a4' | 'a5' | 'letter' | 'legal' | { custom: { width: number; height: number } };
+
+/** Saddle-stitch booklet settings */
+export interface BookletConfig {
+  paperSize: BookletPaperSize;
+  finalSize: BookletPaperSize;
+  /** Paper thickness in mm (0.1 for 80gsm) */
+  paperThicknessMm: number;
+  applyCreep: boolean;
+  bleedMm: number;
+  cropMarks: boolean;
+  foldMarks: boolean;
+  /** Sheets nested into each signature; one signature for the whole book when absent */
+  sheetsPerSignature?: number;
+  /** Stepped marks on each signature's spine, to check the collating order */
+  collationMarks?: boolean;
+  /** Sewing stations marked on the fold of each signature's center spread */
+  sewingStations?: number;
+}
+
+/** Page numbers (1-indexed, 0 = blank) on one booklet sheet */
+export interface BookletSheet {
+  sheetIndex: number;
+  signatureIndex: number;
+  frontLeft: number;
+  frontRight: number;
+  backLeft: number;
+  backRight: number;
+  creepOffsetMm: number;
+  creepOffsetPt: number;
+}
+
+export interface BookletImposition {
+  totalPages: number;
+  paddedPages: number;
+  sheetsCount: number;
+  signaturesCount: number;
+  totalCreepMm: number;
+  sheets: BookletSheet[];
+}
+
+/** A page placed on one side of a sheet */
+export interface SheetPlacement {
+  /** 1-indexed page number (0 = blank) */
+  pageNum: number;
+  position: 'left' | 'right';
+  rotation: number;
+  /** Page to sheet transform, PDF coordinates */
+  transform: TransformMatrix;
+  /** Area the page covers on the sheet, in points from the top-left corner */
+  bounds: Bounds;
+}
+
+export interface SheetSidePreview {
+  /** PNG data URL of the composited side */
+  thumbnail: string;
+  placements: SheetPlacement[];
+}
+
+export interface SheetPreview {
+  sheetIndex: number;
+  creepOffsetPt: number;
+  front: SheetSidePreview;
+  /** As printed: turned 180° relative to the front */
+  back: SheetSidePreview;
+}
+
+/** Result of previewImposition */
+export interface ImpositionPreview {
+  sheetWidth: number;
+  sheetHeight: number;
+  layout: BookletImposition;
+  sheets: SheetPreview[];
+}
+
+/** Input of createCoverTemplate; lengths in points unless named Mm */
+export interface CoverOptions {
+  pageCount: number;
+  /** Default 0.1 */
+  paperThicknessMm?: number;
+  /** Trim size of one cover panel, i.e. of the interior pages */
+  trimWidth: number;
+  trimHeight: number;
+  /** Default 9 */
+  bleed?: number;
+  /** Default 18 */
+  safety?: number;
+  /** Added to the spine for the cover stock */
+  coverThicknessMm?: number;
+}
+
+/** Panels of a cover template, in points from its top-left corner */
+export interface CoverLayout {
+  width: number;
+  height: number;
+  bleed: number;
+  spineWidth: number;
+  spineWidthMm: number;
+  back: Bounds;
+  spine: Bounds;
+  front: Bounds;
+}
+
+/** Result of createCoverTemplate */
+export interface CoverTemplate {
+  layout: CoverLayout;
+  page: PageData;
+}
+
+/** Kind of background job */
+export type JobKind = 'import' | 'export' | 'ocr' | 'font-scan';
+
+export type JobPriority = 'low' | 'normal' | 'high';
+
+export type JobStatus = 'queued' | 'running' | 'completed' | 'failed' | 'cancelled';
+
+/** Background job, as listed by list_jobs and sent with job_updated events */
+export interface JobInfo {
+  id: string;
+  kind: JobKind;
+  /** Short description, e.g. the file being imported */
+  label: string;
+  priority: JobPriority;
+  status: JobStatus;
+  /** Completed fraction (0-1) */
+  progress: number;
+  message?: string;
+  error?: string;
+  /** Unix milliseconds */
+  createdAt: number;
+  startedAt?: number;
+  finishedAt?: number;
+}
+
+/** Result of reimport_and_merge */
+export interface ReimportMerge {
+  pages: PageData[];
+  /** Layers replaced by their new source version */
+  updated: number;
+  /** Layers new in the source */
+  added: number;
+  /** Layers gone from the source */
+  removed: number;
+  /** Edited layers kept although the source changed or dropped them */
+  keptEdits: number;
+}
+
+export type CloudProvider = 'google-drive' | 'onedrive' | 'dropbox';
+
+/** OAuth app registration for a cloud provider */
+export interface CloudClient {
+  clientId: string;
+  /** Required by Google for "TV and limited input" clients */
+  clientSecret?: string;
+}
+
+/** Sign-in the user completes at the provider */
+export interface CloudAuthorization {
+  provider: CloudProvider;
+  /** Code to enter at verificationUri; absent for Dropbox, which shows a code to paste back */
+  userCode?: string;
+  verificationUri: string;
+  /** Seconds between polls */
+  interval: number;
+  /** Seconds until the sign-in expires */
+  expiresIn: number;
+}
+
+export type CloudAuthStatus = 'pending' | 'authorized' | 'denied' | 'expired';
+
+/** A document in cloud storage */
+export interface CloudDocument {
+  provider: CloudProvider;
+  id: string;
+  name: string;
+  mimeType?: string;
+  /** RFC 3339 */
+  modified?: string;
+  size?: number;
+  /** Drive holding the item, for OneDrive files shared from another drive */
+  driveId?: string;
+}
+
+/** Running local REST API server */
+export interface ApiServerInfo {
+  port: number;
+  /** Base URL, e.g. http://127.0.0.1:47810/api */
+  url: string;
+  /** Bearer token clients must send */
+  token: string;
+  allowLan: boolean;
+  /** Directory exports are written to; relative output paths resolve here */
+  exportDir: string;
+}
+
+/** Bulk-edit script in the script library */
+export interface SavedScript {
+  name: string;
+  description?: string;
+  /** Rhai source */
+  source: string;
+  /** Shipped with the app; cannot be changed or deleted */
+  builtin?: boolean;
+}
+
+/** Result of run_script */
+export interface ScriptResult {
+  pages: PageData[];
+  /** Layers modified or deleted */
+  changedLayers: number;
+  addedPages: number;
+  /** Lines written with print and debug */
+  output: string[];
+}
+
+/** A page of a damaged PDF that was recovered or skipped */
+export interface PageRepair {
+  /** Zero-based index in the source file */
+  pageIndex: number;
+  status: 'recovered' | 'failed';
+  error?: string;
+}
+
+/** Damage found in a PDF and what the repair pass did about it */
+export interface RepairReport {
+  /** Why the file could not be opened as-is */
+  loadError?: string;
+  /** The cross-reference table was rebuilt by scanning for objects */
+  xrefRebuilt: boolean;
+  /** References to missing objects that were removed */
+  danglingReferences: number;
+  pages: PageRepair[];
+}
+
+/** Settings for duplicate layer detection */
+export interface DedupOptions {
+  /** Largest difference in position or size, in points, between copies (default 1) */
+  tolerance?: number;
+  /** Make text bold when its copies were offset (default true) */
+  promoteFakeBold?: boolean;
+}
+
+/** Copies of one layer collapsed into it */
+export interface DuplicateGroup {
+  pageIndex: number;
+  keptId: string;
+  removedIds: string[];
+  /** The copies were offset text, and the kept layer was made bold */
+  fakeBold: boolean;
+}
+
+/** Result of deduplicate_layers */
+export interface DedupResult {
+  pages: PageData[];
+  groups: DuplicateGroup[];
+  removedLayers: number;
+}
+
+/** Rules for pruning layers that paint nothing */
+export interface PruneOptions {
+  /** Remove layers covering less than this many square points (default 0.25) */
+  minArea?: number;
+  /** Remove hidden layers and ones with nothing opaque to paint (default true) */
+  removeInvisible?: boolean;
+  /** Remove empty or whitespace-only text (default true) */
+  removeBlankText?: boolean;
+  /** Remove layers entirely outside the page (default true) */
+  removeOffPage?: boolean;
+}
+
+/** Layers pruned from one page, by the rule that removed them */
+export interface PrunedPage {
+  pageIndex: number;
+  tooSmall: number;
+  invisible: number;
+  blankText: number;
+  offPage: number;
+}
+
+/** Result of prune_layers */
+export interface PruneResult {
+  pages: PageData[];
+  /** Only pages that lost layers */
+  pruned: PrunedPage[];
+  removedLayers: number;
+}
+
+/** Layers on one page matched by queryLayers */
+export interface LayerMatches {
+  pageIndex: number;
+  layerIds: string[];
+}
+
+/** Changes transformLayers makes to each matched layer */
+export interface StyleTransform {
+  /** Font family replacements, old name to new */
+  replaceFonts?: Record<string, string>;
+  /** Font size factor, e.g. 1.1 for 110% */
+  fontScale?: number;
+  /** Shift in points, right and down */
+  dx?: number;
+  dy?: number;
+  /** Properties set on every matched layer */
+  set?: LayerUpdates;
+}
+
+/** A layer transformLayers changed (or would change), with the properties that differ */
+export interface LayerChange {
+  pageIndex: number;
+  layerId: string;
+  fields: string[];
+}
+
+export interface TransformResult {
+  /** Transformed pages; empty for a dry run */
+  pages: PageData[];
+  changes: LayerChange[];
+  /** Matched layers left unchanged because they are locked */
+  skipped: LayerChange[];
+}
+
+/** Which extracted layers an import mapping rule applies to; absent fields match anything */
+export interface MappingMatch {
+  layerType?: LayerObject['type'];
+  /** Font family, ignoring case */
+  fontFamily?: string;
+  minFontSize?: number;
+  maxFontSize?: number;
+  bold?: boolean;
+  /** Area of the page the layer's center must be in, in points */
+  region?: Bounds;
+}
+
+/** Matched layers and the role, tags and style set on them */
+export interface MappingRule {
+  match: MappingMatch;
+  set: LayerUpdates;
+}
+
+/** Saved rules for a recurring source layout, applied on import */
+export interface ImportMapping {
+  name: string;
+  rules: MappingRule[];
+}
+
+/** Typography cleanup rules; each defaults to on */
+export interface TypographyOptions {
+  /** BCP 47 tag choosing the quote marks; each page's language, else English, when absent */
+  locale?: string;
+  /** Straight quotes to the locale's quote marks, apostrophes to ’ */
+  smartQuotes?: boolean;
+  /** `--` and `---` to an em dash */
+  dashes?: boolean;
+  /** `...` to an ellipsis */
+  ellipses?: boolean;
+  /** No-break spaces before ; : ! ? and inside guillemets (French only) */
+  frenchSpacing?: boolean;
+}
+
+/** Result of clean_typography */
+export interface TypographyResult {
+  pages: PageData[];
+  /** Layers whose text changed, per page */
+  changed: LayerMatches[];
+}
+
+/** Where footnote numbers restart; endnotes always restart each chapter */
+export type NoteNumbering = 'document' | 'chapter' | 'page';
+
+/** Numbering and layout of notes */
+export interface NoteOptions {
+  footnoteNumbering?: NoteNumbering;
+  /** Note text size in points; 0.8× the anchoring text for footnotes, 10 pt for endnotes, when absent */
+  fontSize?: number;
+  /** Heading over each chapter's endnotes (default "Notes") */
+  endnoteTitle?: string;
+  /** Margin around endnote pages, in points (default 72) */
+  endnoteMargin?: number;
+}
+
+/** A note with the number it prints with */
+export interface NumberedNote {
+  id: string;
+  pageIndex: number;
+  layerId: string;
+  kind: NoteKind;
+  number: number;
+  /** Chapter the anchor is in, from 0 */
+  chapter: number;
+  text: string;
+}
+
+/** Change to the notes of a document */
+export type NoteEdit =
+  | { action: 'insert'; pageIndex: number; layerId: string; anchor: number; kind?: NoteKind; text: string }
+  | { action: 'update'; id: string; text?: string; kind?: NoteKind }
+  | { action: 'remove'; id: string };
+
+/** Result of edit_note */
+export interface NoteEditResult {
+  pages: PageData[];
+  noteId: string;
+  notes: NumberedNote[];
+}
+
+/** Settings for check_layout */
+export interface LayoutCheckOptions {
+  /** Bleed past the trim edge that layers may extend into, in points */
+  bleed?: number;
+  /** Lowest acceptable contrast ratio between text and the image behind it (default 3) */
+  minContrast?: number;
+  /** Fraction of the smaller text layer that must be covered to count as overlapping (default 0.2) */
+  minTextOverlap?: number;
+}
+
+export type LayoutIssueKind = 'offPage' | 'partlyOffPage' | 'lowContrast' | 'textOverlap';
+
+/** A single layout finding */
+export interface LayoutWarning {
+  kind: LayoutIssueKind;
+  pageIndex: number;
+  /** The offending layer first, then any layer it clashes with */
+  layerIds: string[];
+  message: string;
+}
+
+/** Settings for analyze_ink_coverage */
+export interface InkCoverageOptions {
+  /** Render resolution (default 36) */
+  dpi?: number;
+  /** Flag pages whose total coverage exceeds this percentage (default 100) */
+  maxCoverage?: number;
+}
+
+export type ColorRole = 'text' | 'fill' | 'stroke' | 'background';
+
+/** One color and where it is used */
+export interface ColorUsage {
+  /** Normalized "#RRGGBB" */
+  color: string;
+  uses: number;
+  roles: ColorRole[];
+  pages: number[];
+  /** Device CMYK equivalent, 0-100 per channel */
+  cmyk: [number, number, number, number];
+}
+
+/** Estimated ink on one page, 0-100 per channel */
+export interface PageInkCoverage {
+  pageIndex: number;
+  cyan: number;
+  magenta: number;
+  yellow: number;
+  black: number;
+  /** Sum of the four channels (up to 400) */
+  total: number;
+  flagged: boolean;
+}
+
+/** Result of analyze_ink_coverage */
+export interface InkCoverageReport {
+  /** Most used first */
+  colors: ColorUsage[];
+  pages: PageInkCoverage[];
+  averageTotal: number;
+  flaggedPages: number[];
+}
+
+/** An image layer below the required resolution, from check_image_resolution */
+export interface ImageResolution {
+  pageIndex: number;
+  layerId: string;
+  /** Resolution at the layer's current size */
+  effectiveDpi: number;
+  pixelWidth: number;
+  pixelHeight: number;
+  /** Largest size (points) the image can be placed at while meeting the threshold */
+  maxWidth: number;
+  maxHeight: number;
+}
+
+/** App preferences, from get_settings */
+export interface AppSettings {
+  /** pdfium library file or directory */
+  pdfiumPath?: string;
+  /** Tesseract language code(s), e.g. "eng+deu" */
+  ocrLanguage: string;
+  /** 0-1 */
+  ocrMinConfidence: number;
+  ocrRenderDpi: number;
+  /** Fix common misreadings in OCR output */
+  ocrPostCorrection: boolean;
+  /** App-wide OCR corrections; a project's own are applied on top */
+  ocrDictionary: OcrDictionary;
+  /** Image cache size and default import memory budget */
+  imageCacheMb: number;
+  /** Images smaller than this many pixels on a side are skipped on import */
+  minImageSize: number;
+  /** Export preset used when an export names none */
+  exportPreset?: string;
+  /** Front and back offsets applied to imposed sheets */
+  printerCalibration: PrinterCalibration;
+}
+
+/** Shift of everything printed on one side of a sheet, in mm; right and up positive, looking at that side */
+export interface PrintOffset {
+  xMm: number;
+  yMm: number;
+}
+
+/** Printer misregistration read off the sheet from generateCalibrationSheet (±20 mm) */
+export interface PrinterCalibration {
+  front: PrintOffset;
+  back: PrintOffset;
+}
+
+/** Entry of get_recent_projects */
+export interface RecentProject {
+  path: string;
+  title: string;
+  pageCount: number;
+  /** File modification time when last opened or saved (ISO 8601) */
+  modified: string;
+  /** ISO 8601 */
+  lastOpened: string;
+  pinned: boolean;
+  /** First page as a PNG data URL, once rendered */
+  thumbnail?: string;
+  /** The file no longer exists */
+  missing?: boolean;
+}
+
+/** Copied layers in every clipboard format, from copy_layers */
+export interface ClipboardContent {
+  /** SVG document; carries the layers for pasting back into Rook */
+  svg: string;
+  /** Text layers in reading order */
+  text: string;
+  /** PNG data URL, when it could be rendered */
+  png?: string;
+}
+
+/** Clipboard contents offered for pasting; SVG wins over an image, an image over text */
+export interface PasteContent {
+  svg?: string;
+  /** Data URL or bare base64 */
+  image?: string;
+  text?: string;
+}
+
+export type RuleStyle = 'solid' | 'double' | 'dotted' | 'tapered' | 'diamond';
+export type OrnamentStyle = 'dinkus' | 'asterism' | 'lozenge' | 'flourish';
+export type BadgeShape = 'circle' | 'square' | 'rounded' | 'pill' | 'diamond';
+
+/** Parametric decorative element for create_decoration; sizes in points */
+export type Decoration =
+  | { kind: 'rule'; length: number; thickness: number; style?: RuleStyle }
+  | { kind: 'ornament'; size: number; style?: OrnamentStyle }
+  | { kind: 'frame'; width: number; height: number; thickness: number; cornerRadius?: number; double?: boolean }
+  /** Shape behind a page number or drop cap */
+  | { kind: 'badge'; width: number; height: number; shape?: BadgeShape };
@@ -95,7 +95,8 @@ describe('Typography Bridge', () => {
         fontWeight: 700
       }
       
-      const result = await applyTextFormatting(layer, updates)
+      const page = { pageIndex: 0, width: 612, height: 792, layers: [layer] }
+      const result = await applyTextFormatting(0, layer.id, updates, page)
       
       expect(result).toBeDefined()
     })
@@ -12,14 +12,14 @@ import {
   type FontInfo,
   type GoogleFont 
 } from './fontService'
-import type { LayerObject, LayerUpdates } from './types'
+import type { LayerObject, LayerUpdates, PageData } from './types'
 
 export interface TypographyBridge {
   initTypography(): Promise<void>
   getAvailableFonts(): Promise<{ system: FontInfo[]; google: GoogleFont[] }>
   searchFonts(query: string): Promise<GoogleFont[]>
   loadFont(family: string, weights?: string[]): Promise<void>
-  applyTextFormatting(layer: LayerObject, updates: LayerUpdates): Promise<LayerObject | null>
+  applyTextFormatting(pageIndex: number, layerId: string, updates: LayerUpdates, page: PageData): Promise<LayerObject | null>
   validateFontSupport(fontFamily: string): Promise<boolean>
 }
 
@@ -47,14 +47,15 @@ class TauriTypographyBridge implements TypographyBridge {
     await loadGoogleFont(family, weights)
   }
 
-  async applyTextFormatting(layer: LayerObject, updates: LayerUpdates): Promise<LayerObject | null> {
+  async applyTextFormatting(
+    pageIndex: number,
+    layerId: string,
+    updates: LayerUpdates,
+    page: PageData
+  ): Promise<LayerObject | null> {
     if (!this.invoke) throw new Error('Tauri not initialized')
     
-    return this.invoke('update_layer', {
-      pageIndex: 0, // Will be provided by caller
-      layerId: layer.id,
-      updates
-    }) as Promise<LayerObject>
+    return this.invoke('update_layer', { pageIndex, layerId, updates, page }) as Promise<LayerObject>
   }
 
   async validateFontSupport(fontFamily: string): Promise<boolean> {
@@ -87,9 +88,14 @@ class WasmTypographyBridge implements TypographyBridge {
     await loadGoogleFont(family, weights)
   }
 
-  async applyTextFormatting(layer: LayerObject, updates: LayerUpdates): Promise<LayerObject | null> {
+  async applyTextFormatting(
+    pageIndex: number,
+    layerId: string,
+    updates: LayerUpdates,
+    page: PageData
+  ): Promise<LayerObject | null> {
     const wasm = getWasm()
-    return wasm.update_layer(layer, updates)
+    return wasm.update_layer(pageIndex, page, layerId, updates)
   }
 
   async validateFontSupport(fontFamily: string): Promise<boolean> {
@@ -154,11 +160,13 @@ export async function loadFont(family: string, weights?: string[]): Promise<void
  * Apply text formatting (cross-platform)
  */
 export async function applyTextFormatting(
-  layer: LayerObject, 
-  updates: LayerUpdates
+  pageIndex: number,
+  layerId: string,
+  updates: LayerUpdates,
+  page: PageData
 ): Promise<LayerObject | null> {
   const bridge = getTypographyBridge()
-  return bridge.applyTextFormatting(layer, updates)
+  return bridge.applyTextFormatting(pageIndex, layerId, updates, page)
 }
 
 /**
@@ -6,28 +6,181 @@ import type {
   BookProjectData,
   LayerObject,
   LayerUpdates,
+  ProjectEncoding,
+  DocumentData,
+  PageSetup,
+  PageSizeInfo,
+  ResizeMode,
+  SpanPage,
+  StructuredText,
+  PageText,
+  PageTextOptions,
+  DedupOptions,
+  DedupResult,
+  PruneOptions,
+  PruneResult,
+  LayerMatches,
+  StyleTransform,
+  TransformResult,
+  TypographyOptions,
+  TypographyResult,
+  ImportMapping,
+  NoteOptions,
+  NumberedNote,
+  NoteEdit,
+  NoteEditResult,
+  Margins,
+  Decoration,
+  OcrReviewPage,
+  OcrReviewAction,
+  OcrReviewResult,
+  SpeechOutput,
+  EpubOptions,
+  DocumentStructure,
+  PageGuides,
+  PageLayoutOptions,
+  Section,
+  SectionInfo,
+  SectionsResult,
+  SnapResult,
+  Bounds,
 } from './types';
 
 // WASM module interface
 interface WasmModule {
-  parse_docx(data: Uint8Array): DocumentResponse;
+  parse_docx(data: Uint8Array, pageSetup?: PageSetup): DocumentResponse;
   process_pdf_page(pageData: PageData): PageData;
   create_document_from_pages(pages: PageData[], width: number, height: number): DocumentResponse;
   export_bookproj(pages: PageData[], metadata: DocumentMetadata): Uint8Array;
   export_docx(pages: PageData[], metadata: DocumentMetadata): Uint8Array;
+  export_speech(pages: PageData[], metadata: DocumentMetadata, output: SpeechOutput): Uint8Array;
+  export_epub(
+    pages: PageData[],
+    metadata: DocumentMetadata,
+    options: EpubOptions,
+    sections: Section[],
+    pageRange?: [number, number]
+  ): Uint8Array;
   load_project(data: Uint8Array): BookProjectData;
   save_project(project: BookProjectData): Uint8Array;
+  convert_project(data: Uint8Array, encoding: ProjectEncoding): Uint8Array;
+  ProjectLoader: new (data: Uint8Array) => WasmProjectLoader;
   cache_image(id: string, data: Uint8Array): void;
   get_image(id: string): Uint8Array | undefined;
   clear_image_cache(): void;
-  update_layer(layer: LayerObject, updates: LayerUpdates): LayerObject;
+  remove_image(id: string): boolean;
+  set_image_cache_budget(maxBytes: number): void;
+  evict_to_budget(budgetBytes: number): number;
+  get_cache_stats(): WasmCacheStats;
+  detect_image_info(data: Uint8Array): WasmImageInfo;
+  get_image_info(id: string): WasmImageInfo;
+  update_layer(pageIndex: number, page: PageData, layerId: string, updates: LayerUpdates, force?: boolean): LayerObject | null;
+  bring_to_front(layers: LayerObject[], layerId: string): LayerObject[];
+  send_to_back(layers: LayerObject[], layerId: string): LayerObject[];
+  move_layer_up(layers: LayerObject[], layerId: string): LayerObject[];
+  move_layer_down(layers: LayerObject[], layerId: string): LayerObject[];
+  normalize_z_indices(layers: LayerObject[]): LayerObject[];
+  align_layers(layers: LayerObject[], layerIds: string[], alignment: LayerAlignment): LayerObject[];
+  deduplicate_layers(pages: PageData[], options?: DedupOptions): DedupResult;
+  prune_layers(pages: PageData[], options?: PruneOptions): PruneResult;
+  query_layers(pages: PageData[], query: string): LayerMatches[];
+  transform_layers(pages: PageData[], query: string, transform: StyleTransform, dryRun: boolean): TransformResult;
+  record_import_mapping(name: string, imported: PageData[], corrected: PageData[]): ImportMapping;
+  apply_import_mapping(pages: PageData[], mapping: ImportMapping): PageData[];
+  clean_typography(pages: PageData[], options?: TypographyOptions): TypographyResult;
+  list_notes(pages: PageData[], options?: NoteOptions): NumberedNote[];
+  edit_note(pages: PageData[], edit: NoteEdit, options?: NoteOptions): NoteEditResult;
+  resolve_notes(pages: PageData[], options?: NoteOptions): PageData[];
+  ocr_review_queue(pages: PageData[], threshold?: number): OcrReviewPage[];
+  review_ocr_layer(pages: PageData[], pageIndex: number, layerId: string, action: OcrReviewAction, threshold?: number): OcrReviewResult;
+  list_page_size_presets(): PageSizeInfo[];
+  resize_document(document: DocumentData, width: number, height: number, mode?: ResizeMode): DocumentData;
+  extract_structured_text(pages: SpanPage[]): StructuredText;
+  extract_plain_text(pages: SpanPage[]): string;
+  extract_structure(pages: PageData[]): DocumentStructure;
+  get_page_text(page: PageData, options?: PageTextOptions): PageText;
+  reflow_pages(pages: PageData[], pageSetup?: PageSetup): PageData[];
+  detect_layout_guides(pages: PageData[]): PageData[];
+  apply_page_layout(pages: PageData[], source: number, targets: number[], options?: PageLayoutOptions): PageData[];
+  list_sections(pages: PageData[], sections: Section[]): SectionInfo[];
+  set_section(pages: PageData[], sections: Section[], section: Section): SectionsResult;
+  remove_section(pages: PageData[], sections: Section[], id: string): SectionsResult;
+  snap_to_guides(guides: PageGuides, bounds: Bounds, baseline?: number, threshold?: number): SnapResult;
+  copy_layers_svg(layers: LayerObject[]): string | undefined;
+  copy_layers_text(layers: LayerObject[]): string;
+  paste_svg(svg: string, idPrefix: string): LayerObject[];
+  paste_text(text: string, id: string, x: number, y: number): LayerObject | null;
+  create_decoration(decoration: Decoration, id: string, x: number, y: number, color?: string): LayerObject;
+  place_image(data: Uint8Array, page: PageData, margins: Margins | undefined, x: number | undefined, y: number | undefined, id: string): LayerObject;
   // Typography functions
+  parse_font_name(raw: string): WasmParsedFontName;
+  get_canonical_font_name(raw: string): string;
+  match_font(raw: string, available: string[]): WasmFontMatch;
   get_system_fonts(): string[];
   search_fonts(query: string): { family: string; variants: string[]; category: string }[];
   apply_text_formatting(layer: LayerObject, formatting: Record<string, unknown>): LayerObject;
   default(input?: string | URL): Promise<void>;
 }
 
+/**
+ * Incremental project loader (skeleton first, pages on demand)
+ */
+export interface WasmProjectLoader {
+  readonly totalPages: number;
+  project(): BookProjectData;
+  load_page(index: number): PageData;
+  free(): void;
+}
+
+/**
+ * Edge or center to align layers on
+ */
+export type LayerAlignment = 'left' | 'center' | 'right' | 'top' | 'middle' | 'bottom';
+
+/**
+ * Font name split into family, weight and style
+ */
+export interface WasmParsedFontName {
+  family: string;
+  weight: number;
+  isItalic: boolean;
+  isBold: boolean;
+  width: string;
+  original: string;
+}
+
+/**
+ * Best match for a font name among the available families
+ */
+export interface WasmFontMatch {
+  family: string;
+  confidence: number;
+  cssFamily: string;
+  fallbackStack: string[];
+}
+
+/**
+ * WASM image cache counters
+ */
+export interface WasmCacheStats {
+  count: number;
+  totalSize: number;
+  maxSize: number;
+  hits: number;
+  misses: number;
+  evictions: number;
+}
+
+/**
+ * Image dimensions and detected format
+ */
+export interface WasmImageInfo {
+  width: number;
+  height: number;
+  format: 'png' | 'jpeg' | 'webp' | 'unknown';
+  mimeType: string;
+}
+
 // Module state
 let wasmModule: WasmModule | null = null;
 let wasmLoadPromise: Promise<WasmModule> | null = null;
@@ -412,6 +565,33 @@ export function clearWasmCaches(): void {
   }
 }
 
+/**
+ * Get WASM image cache stats
+ */
+export function getWasmCacheStats(): WasmCacheStats | null {
+  if (!wasmModule) return null;
+  try {
+    return wasmModule.get_cache_stats();
+  } catch (e) {
+    console.warn('Failed to read WASM cache stats:', e);
+    return null;
+  }
+}
+
+/**
+ * Set the WASM image cache budget in bytes
+ */
+export function setWasmCacheBudget(maxBytes: number): void {
+  wasmModule?.set_image_cache_budget(maxBytes);
+}
+
+/**
+ * Evict least recently used images until the cache fits in the budget
+ */
+export function evictWasmCache(budgetBytes: number): number {
+  return wasmModule?.evict_to_budget(budgetBytes) ?? 0;
+}
+
 /**
  * Get approximate WASM memory usage
  */
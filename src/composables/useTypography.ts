@@ -139,11 +139,13 @@ export function useTypography() {
     }
     
     try {
+      const page = documentStore.currentPage
+      if (!page) return
       const updatedLayer = await updateLayer(
         documentStore.currentPageIndex,
         selectedLayer.value.id,
         layerUpdates,
-        selectedLayer.value
+        page
       )
       
       if (updatedLayer) {
@@ -79,6 +79,7 @@ export interface ImageMetadata {
   height: number
   colorSpace: 'RGB' | 'RGBA' | 'Grayscale'
   dpi: number
+  iccProfile?: string
 }
 
 /** Watermark position enumeration */
@@ -150,6 +151,13 @@ export interface PageMetadata {
   originalPageIndex?: number
   rotation?: 0 | 90 | 180 | 270
   mediaBox?: [number, number, number, number]
+  /** Source PDF boxes as [x0, y0, x1, y1], y up; layers sit in the crop box */
+  cropBox?: [number, number, number, number]
+  trimBox?: [number, number, number, number]
+  bleedBox?: [number, number, number, number]
+  /** BCP 47 language tag when the page differs from the document */
+  language?: string
+  custom?: Record<string, string>
 }
 
 /** A single page containing multiple layers */
@@ -169,6 +177,20 @@ export interface DocumentMetadata {
   created: string
   modified: string
   description?: string
+  subject?: string
+  keywords?: string[]
+  isbn?: string
+  publisher?: string
+  /** BCP 47 language tag, e.g. "en-US" */
+  language?: string
+  /** Exported as PDF Info entries and DOCX custom properties */
+  custom?: Record<string, string>
+}
+
+/** Length unit; `dpi` applies to `px` only */
+export interface DocumentUnits {
+  unit: 'pt' | 'mm' | 'in' | 'px'
+  dpi?: number
 }
 
 /** Document data containing all pages */
@@ -176,13 +198,27 @@ export interface DocumentData {
   pageWidth: number
   pageHeight: number
   pages: PageData[]
+  /** Unit of all sizes and bounds; points when absent */
+  units?: DocumentUnits
 }
 
+/** On-disk encoding of saved project data */
+export type ProjectEncoding = 'json' | 'messagepack'
+
 /** Project settings */
 export interface ProjectSettings {
   defaultFont?: string
   defaultFontSize?: number
   exportQuality?: 'draft' | 'standard' | 'high'
+  encoding?: ProjectEncoding
+  /** Page margins in points; the trim size is the document page size */
+  margins?: { top: number; right: number; bottom: number; left: number }
+  /** Print bleed past the trim edge, in points */
+  bleed?: number
+  /** Unit lengths are shown in; storage is always points */
+  displayUnits?: DocumentUnits
+  /** File the project was imported from, for re-importing pages */
+  source?: { path: string; fileType: string }
 }
 
 /** Complete book project data */
@@ -203,6 +239,8 @@ export type HistoryEntryType =
   | 'page_add'
   | 'page_delete'
   | 'page_reorder'
+  | 'page_replace'
+  | 'pages_replace'
 
 /** History entry for undo/redo */
 export interface HistoryEntry {
@@ -225,6 +263,8 @@ export interface DocumentResponse {
   success: boolean
   message: string
   data?: DocumentData
+  /** Title, author, keywords and other properties read from the source file */
+  metadata?: DocumentMetadata
 }
 
 /** Result from export operations */
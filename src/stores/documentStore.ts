@@ -11,9 +11,19 @@ import {
   importDocumentWithOptions,
   saveProject as bridgeSave,
   loadProject as bridgeLoad,
+  reimportPage as bridgeReimportPage,
+  transformLayers as bridgeTransformLayers,
   isTauri
 } from '@/bridge'
-import type { BookProjectData as BridgeBookProject, PdfAnalysis, ImportOptions } from '@/bridge'
+import type {
+  BookProjectData as BridgeBookProject,
+  PageData as BridgePage,
+  PdfAnalysis,
+  ImportOptions,
+  LinkedImages,
+  StyleTransform,
+  TransformResult
+} from '@/bridge'
 import type {
   BookProjectData,
   PageData,
@@ -22,7 +32,8 @@ import type {
   HistoryEntry,
   SourceInfo,
   LayerRole,
-  DocumentData
+  DocumentData,
+  DocumentMetadata
 } from '@/models'
 import { createEmptyProject, createDefaultMetadata } from '@/models'
 
@@ -80,13 +91,15 @@ export const useDocumentStore = defineStore('document', () => {
 
   function transformToBookProject(
     data: DocumentData,
-    fileName: string
+    fileName: string,
+    metadata?: DocumentMetadata
   ): BookProjectData {
     const project = createEmptyProject()
     project.document = data
     project.metadata = {
       ...createDefaultMetadata(),
-      title: fileName.replace(/\.[^/.]+$/, '') ?? 'Untitled'
+      ...metadata,
+      title: metadata?.title || (fileName.replace(/\.[^/.]+$/, '') ?? 'Untitled')
     }
     return project
   }
@@ -127,7 +140,14 @@ export const useDocumentStore = defineStore('document', () => {
         : await importDocumentWithAnalysis(progressCallback)
 
       if (result.success && result.data) {
-        document.value = transformToBookProject(result.data as unknown as DocumentData, file?.name || 'Imported Document')
+        document.value = transformToBookProject(
+          result.data as unknown as DocumentData,
+          file?.name || 'Imported Document',
+          result.metadata as DocumentMetadata | undefined
+        )
+        if ('source' in result && result.source) {
+          document.value.settings.source = result.source
+        }
         currentPageIndex.value = 0
         sourceFile.value = {
           path: '',
@@ -164,14 +184,14 @@ export const useDocumentStore = defineStore('document', () => {
   }
 
   /**
-   * Save project using bridge
+   * Save project using bridge; linked images stay links unless `linkedImages` is 'embed'
    */
-  async function saveProject(): Promise<{ success: boolean; message: string }> {
+  async function saveProject(linkedImages?: LinkedImages): Promise<{ success: boolean; message: string }> {
     if (!document.value) return { success: false, message: 'No document to save' }
 
     isLoading.value = true
     try {
-      const result = await bridgeSave(document.value as unknown as BridgeBookProject)
+      const result = await bridgeSave(document.value as unknown as BridgeBookProject, linkedImages)
       return result
     } catch (e) {
       return { success: false, message: e instanceof Error ? e.message : String(e) }
@@ -334,6 +354,10 @@ export const useDocumentStore = defineStore('document', () => {
   }
 
   function applyHistoryEntry(entry: HistoryEntry, direction: 'undo' | 'redo'): void {
+    if (document.value && entry.type === 'pages_replace') {
+      document.value.document.pages = (direction === 'undo' ? entry.previousState : entry.newState) as PageData[]
+      return
+    }
     if (!document.value || entry.pageIndex === undefined) return
 
     const page = document.value.document.pages[entry.pageIndex]
@@ -360,6 +384,12 @@ export const useDocumentStore = defineStore('document', () => {
         }
         break
 
+      case 'page_replace':
+        if (state) {
+          document.value.document.pages[entry.pageIndex] = { ...(state as PageData), pageIndex: entry.pageIndex }
+        }
+        break
+
       case 'layer_delete':
         if (direction === 'undo') {
           if (entry.previousState) page.layers.push(entry.previousState as LayerObject)
@@ -514,6 +544,73 @@ export const useDocumentStore = defineStore('document', () => {
     }
   }
 
+  /**
+   * Re-parse a page from the project's source file, replacing its extracted
+   * layers and keeping those added by hand (desktop only)
+   */
+  async function reimportPage(pageIndex: number): Promise<boolean> {
+    if (!document.value) return false
+
+    const page = document.value.document.pages[pageIndex]
+    const source = document.value.settings.source
+    if (!page) return false
+    if (!source) {
+      error.value = 'The project has no source file to re-import from'
+      return false
+    }
+
+    isLoading.value = true
+    try {
+      const fresh = (await bridgeReimportPage(source, page as unknown as BridgePage)) as unknown as PageData
+      pushHistory({
+        type: 'page_replace',
+        timestamp: new Date().toISOString(),
+        pageIndex,
+        previousState: page,
+        newState: fresh
+      })
+      document.value.document.pages[pageIndex] = { ...fresh, pageIndex }
+      selectedLayerIds.value = []
+      return true
+    } catch (e) {
+      error.value = e instanceof Error ? e.message : String(e)
+      return false
+    } finally {
+      isLoading.value = false
+    }
+  }
+
+  /**
+   * Apply a style transform to every layer matching `query` across the
+   * document as one undo step; a dry run only returns the report
+   */
+  async function transformLayers(
+    query: string,
+    transform: StyleTransform,
+    dryRun = false
+  ): Promise<TransformResult | null> {
+    if (!document.value) return null
+
+    const pages = document.value.document.pages
+    try {
+      const result = await bridgeTransformLayers(pages as unknown as BridgePage[], query, transform, dryRun)
+      if (!dryRun && result.changes.length > 0) {
+        const transformed = result.pages as unknown as PageData[]
+        pushHistory({
+          type: 'pages_replace',
+          timestamp: new Date().toISOString(),
+          previousState: pages,
+          newState: transformed
+        })
+        document.value.document.pages = transformed
+      }
+      return result
+    } catch (e) {
+      error.value = e instanceof Error ? e.message : String(e)
+      return null
+    }
+  }
+
   function setLayerRole(pageIndex: number, layerId: string, role: LayerRole): void {
     if (!document.value) return
 
@@ -720,6 +817,8 @@ export const useDocumentStore = defineStore('document', () => {
     duplicateLayer,
     deletePage,
     reorderPage,
+    reimportPage,
+    transformLayers,
     setLayerRole,
     applyHeaderFooterToRange,
     dismissAnalysis,
@@ -11,7 +11,7 @@ declare module 'book-creation-wasm' {
   export function parse_docx(data: Uint8Array): unknown;
   export function process_pdf_page(page_data: unknown): unknown;
   export function save_project(project_js: unknown): Uint8Array;
-  export function update_layer(layer_js: unknown, updates_js: unknown): unknown;
+  export function update_layer(page_index: number, page_js: unknown, layer_id: string, updates_js: unknown, force?: boolean): unknown;
   
   type InitInput = RequestInfo | URL | Response | BufferSource | WebAssembly.Module;
   export default function __wbg_init(input?: InitInput | Promise<InitInput>): Promise<unknown>;
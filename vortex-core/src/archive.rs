@@ -0,0 +1,475 @@
+//! Project archive (v2 container)
+//!
+//! A `.bookproj` v2 file is a zip holding the project JSON plus the image
+//! bytes its layers reference, so a saved project keeps its pictures:
+//!
+//! ```text
+//! manifest.json      format, version, encoding, page count and image index
+//! project.json       BookProjectData without its pages
+//! pages/0.json       one PageData per entry
+//! images/0.png       image bytes, named by position in the manifest
+//! ```
+//!
+//! With `settings.encoding = messagepack` the project and page entries are
+//! MessagePack (`project.msgpack`, `pages/0.msgpack`); the manifest stays JSON.
+//!
+//! Pages are separate entries so `ProjectArchiveReader` can hand out the
+//! first pages before the rest are parsed, and never holds the whole
+//! document as one JSON buffer. Image ids are arbitrary strings, hence the
+//! index-based entry names. Plain JSON (v1) and bare MessagePack projects are
+//! still accepted by `read_project`.
+
+use crate::models::{BookProjectData, DocumentData, LayerType, PageData, ProjectEncoding};
+use crate::msgpack;
+use crate::units::{self, DocumentUnits};
+use serde::de::DeserializeOwned;
+use serde::{Deserialize, Serialize};
+use std::io::{Cursor, Read, Seek, Write};
+use zip::write::SimpleFileOptions;
+use zip::{CompressionMethod, ZipArchive, ZipWriter};
+
+/// Container format marker in the manifest
+pub const ARCHIVE_FORMAT: &str = "bookproj-archive";
+
+/// Current container version
+pub const ARCHIVE_VERSION: u32 = 2;
+
+const MANIFEST_ENTRY: &str = "manifest.json";
+const PROJECT_ENTRY: &str = "project";
+const PAGE_DIR: &str = "pages/";
+const IMAGE_DIR: &str = "images/";
+
+/// An image stored alongside the project
+#[derive(Debug, Clone, PartialEq)]
+pub struct ArchiveImage {
+    pub id: String,
+    pub data: Vec<u8>,
+}
+
+/// Manifest entry for one image
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct ArchiveImageEntry {
+    pub id: String,
+    pub path: String,
+}
+
+/// Archive manifest
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct ArchiveManifest {
+    pub format: String,
+    pub version: u32,
+    /// Encoding of the project and page entries
+    #[serde(default)]
+    pub encoding: ProjectEncoding,
+    /// Number of `pages/N` entries
+    #[serde(default)]
+    pub page_count: usize,
+    #[serde(default)]
+    pub images: Vec<ArchiveImageEntry>,
+}
+
+/// Whether the bytes look like a zip container rather than plain JSON
+#[inline]
+pub fn is_archive(data: &[u8]) -> bool {
+    data.starts_with(b"PK\x03\x04")
+}
+
+/// Image ids an image layer may be cached under (layer id, then image path)
+///
+/// Linked layers are left out: their files are read again when the project
+/// opens. Call `embed_linked_images` first to store them as well.
+pub fn referenced_image_ids(project: &BookProjectData) -> Vec<String> {
+    let mut ids = Vec::new();
+    for layer in project
+        .document
+        .pages
+        .iter()
+        .flat_map(|p| &p.layers)
+        .filter(|l| l.layer_type == LayerType::Image && l.image_link.is_none())
+    {
+        for id in std::iter::once(&layer.id).chain(layer.image_path.as_ref()) {
+            if !ids.contains(id) {
+                ids.push(id.clone());
+            }
+        }
+    }
+    ids
+}
+
+/// Drop every image link so the images are saved with the project;
+/// returns the number of layers unlinked
+pub fn embed_linked_images(project: &mut BookProjectData) -> usize {
+    let mut embedded = 0;
+    for layer in project.document.pages.iter_mut().flat_map(|p| &mut p.layers) {
+        if layer.image_link.take().is_some() {
+            embedded += 1;
+        }
+    }
+    embedded
+}
+
+#[inline]
+fn entry_extension(encoding: ProjectEncoding) -> &'static str {
+    match encoding {
+        ProjectEncoding::Json => "json",
+        ProjectEncoding::MessagePack => "msgpack",
+    }
+}
+
+fn encode<T: Serialize>(encoding: ProjectEncoding, value: &T) -> Result<Vec<u8>, String> {
+    match encoding {
+        ProjectEncoding::Json => serde_json::to_vec(value).map_err(|e| e.to_string()),
+        ProjectEncoding::MessagePack => msgpack::to_vec(value),
+    }
+}
+
+fn decode<T: DeserializeOwned>(encoding: ProjectEncoding, data: &[u8]) -> Result<T, String> {
+    match encoding {
+        ProjectEncoding::Json => serde_json::from_slice(data).map_err(|e| e.to_string()),
+        ProjectEncoding::MessagePack => msgpack::from_slice(data),
+    }
+}
+
+fn image_extension(data: &[u8]) -> &'static str {
+    if data.starts_with(&[0x89, b'P', b'N', b'G']) {
+        "png"
+    } else if data.starts_with(&[0xFF, 0xD8, 0xFF]) {
+        "jpg"
+    } else if data.len() >= 12 && data.starts_with(b"RIFF") && &data[8..12] == b"WEBP" {
+        "webp"
+    } else {
+        "bin"
+    }
+}
+
+/// Write a project and its images into a v2 container
+pub fn write_archive(project: &BookProjectData, images: &[ArchiveImage]) -> Result<Vec<u8>, String> {
+    let mut zip = ZipWriter::new(Cursor::new(Vec::new()));
+    let deflated = SimpleFileOptions::default().compression_method(CompressionMethod::Deflated);
+    // Images are already compressed
+    let stored = SimpleFileOptions::default().compression_method(CompressionMethod::Stored);
+
+    let pages = &project.document.pages;
+    let encoding = project.settings.encoding;
+    let ext = entry_extension(encoding);
+    let mut manifest = ArchiveManifest {
+        format: ARCHIVE_FORMAT.to_string(),
+        version: ARCHIVE_VERSION,
+        encoding,
+        page_count: pages.len(),
+        images: Vec::with_capacity(images.len()),
+    };
+
+    // Skeleton first so readers can show document info before any page
+    let skeleton = BookProjectData {
+        format: project.format.clone(),
+        version: project.version.clone(),
+        metadata: project.metadata.clone(),
+        document: DocumentData {
+            page_width: project.document.page_width,
+            page_height: project.document.page_height,
+            pages: Vec::new(),
+            units: project.document.units,
+        },
+        settings: project.settings.clone(),
+        changes: project.changes.clone(),
+        sections: project.sections.clone(),
+    };
+    zip.start_file(format!("{}.{}", PROJECT_ENTRY, ext), deflated).map_err(|e| e.to_string())?;
+    zip.write_all(&encode(encoding, &skeleton)?).map_err(|e| e.to_string())?;
+
+    for (i, page) in pages.iter().enumerate() {
+        zip.start_file(format!("{}{}.{}", PAGE_DIR, i, ext), deflated).map_err(|e| e.to_string())?;
+        zip.write_all(&encode(encoding, page)?).map_err(|e| e.to_string())?;
+    }
+
+    for (i, image) in images.iter().enumerate() {
+        let path = format!("{}{}.{}", IMAGE_DIR, i, image_extension(&image.data));
+        zip.start_file(path.as_str(), stored).map_err(|e| e.to_string())?;
+        zip.write_all(&image.data).map_err(|e| e.to_string())?;
+        manifest.images.push(ArchiveImageEntry { id: image.id.clone(), path });
+    }
+
+    zip.start_file(MANIFEST_ENTRY, deflated).map_err(|e| e.to_string())?;
+    serde_json::to_writer_pretty(&mut zip, &manifest).map_err(|e| e.to_string())?;
+
+    Ok(zip.finish().map_err(|e| e.to_string())?.into_inner())
+}
+
+fn read_entry<R: Read + Seek>(zip: &mut ZipArchive<R>, name: &str) -> Result<Vec<u8>, String> {
+    let mut entry = zip.by_name(name).map_err(|e| format!("{}: {}", name, e))?;
+    let mut data = Vec::with_capacity(entry.size() as usize);
+    entry.read_to_end(&mut data).map_err(|e| format!("{}: {}", name, e))?;
+    Ok(data)
+}
+
+/// Incremental reader over a v2 container
+///
+/// Opening parses only the manifest and the project skeleton; pages and
+/// images are read on demand.
+pub struct ProjectArchiveReader<R> {
+    zip: ZipArchive<R>,
+    manifest: ArchiveManifest,
+    project: BookProjectData,
+    /// Unit the stored pages are written in; pages are returned in points
+    units: DocumentUnits,
+}
+
+impl<R: Read + Seek> ProjectArchiveReader<R> {
+    pub fn new(reader: R) -> Result<Self, String> {
+        let mut zip = ZipArchive::new(reader).map_err(|e| format!("Invalid project archive: {}", e))?;
+
+        let manifest: ArchiveManifest = serde_json::from_slice(&read_entry(&mut zip, MANIFEST_ENTRY)?)
+            .map_err(|e| format!("Invalid project manifest: {}", e))?;
+        if manifest.format != ARCHIVE_FORMAT {
+            return Err(format!("Unsupported project container: {}", manifest.format));
+        }
+        if manifest.version > ARCHIVE_VERSION {
+            return Err(format!(
+                "Project archive version {} is newer than supported ({})",
+                manifest.version, ARCHIVE_VERSION
+            ));
+        }
+
+        let entry = format!("{}.{}", PROJECT_ENTRY, entry_extension(manifest.encoding));
+        let mut project: BookProjectData = decode(manifest.encoding, &read_entry(&mut zip, &entry)?)
+            .map_err(|e| format!("Invalid project file: {}", e))?;
+        let units = project.document.units;
+        units::normalize_to_points(&mut project.document);
+
+        Ok(Self { zip, manifest, project, units })
+    }
+
+    /// Encoding of the project and page entries
+    #[inline]
+    pub fn encoding(&self) -> ProjectEncoding {
+        self.manifest.encoding
+    }
+
+    /// Number of pages stored as separate entries
+    #[inline]
+    pub fn page_count(&self) -> usize {
+        self.manifest.page_count
+    }
+
+    /// Project metadata, settings and page size (pages not included)
+    #[inline]
+    pub fn project(&self) -> &BookProjectData {
+        &self.project
+    }
+
+    /// Parse one page
+    pub fn read_page(&mut self, index: usize) -> Result<PageData, String> {
+        if index >= self.manifest.page_count {
+            return Err(format!("Page {} out of range ({} pages)", index, self.manifest.page_count));
+        }
+        let encoding = self.manifest.encoding;
+        let data = read_entry(&mut self.zip, &format!("{}{}.{}", PAGE_DIR, index, entry_extension(encoding)))?;
+        let mut page = decode(encoding, &data).map_err(|e| format!("Invalid page {}: {}", index, e))?;
+        units::page_to_points(&mut page, self.units);
+        Ok(page)
+    }
+
+    /// Read all embedded images
+    pub fn read_images(&mut self) -> Result<Vec<ArchiveImage>, String> {
+        let entries = self.manifest.images.clone();
+        entries
+            .into_iter()
+            .map(|entry| {
+                read_entry(&mut self.zip, &entry.path).map(|data| ArchiveImage { id: entry.id, data })
+            })
+            .collect()
+    }
+
+    /// Read everything into a complete project
+    pub fn into_project(mut self) -> Result<(BookProjectData, Vec<ArchiveImage>), String> {
+        let images = self.read_images()?;
+        let mut pages = Vec::with_capacity(self.manifest.page_count);
+        for index in 0..self.manifest.page_count {
+            pages.push(self.read_page(index)?);
+        }
+        let mut project = self.project;
+        project.document.pages.extend(pages);
+        Ok((project, images))
+    }
+}
+
+/// Read a v2 container
+pub fn read_archive(data: &[u8]) -> Result<(BookProjectData, Vec<ArchiveImage>), String> {
+    ProjectArchiveReader::new(Cursor::new(data))?.into_project()
+}
+
+/// Read a v2 container, a bare MessagePack project or a plain JSON (v1) project
+///
+/// Geometry is returned in points whatever unit the file declares.
+pub fn read_project(data: &[u8]) -> Result<(BookProjectData, Vec<ArchiveImage>), String> {
+    if is_archive(data) {
+        return read_archive(data);
+    }
+    let mut project: BookProjectData = if msgpack::looks_like_map(data) {
+        msgpack::from_slice(data)
+    } else {
+        serde_json::from_slice(data).map_err(|e| e.to_string())
+    }
+    .map_err(|e| format!("Invalid project file: {}", e))?;
+    units::normalize_to_points(&mut project.document);
+    Ok((project, Vec::new()))
+}
+
+/// Re-encode a project file (any readable format) as a v2 container
+pub fn convert_project(data: &[u8], encoding: ProjectEncoding) -> Result<Vec<u8>, String> {
+    let (mut project, images) = read_project(data)?;
+    project.settings.encoding = encoding;
+    write_archive(&project, &images)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::{Bounds, ImageLink, LayerObject, LayerRole, PageData, SourceType};
+
+    fn image_layer(id: &str, image_path: Option<&str>) -> LayerObject {
+        LayerObject {
+            id: id.to_string(),
+            layer_type: LayerType::Image,
+            bounds: Bounds::new(0.0, 0.0, 10.0, 10.0),
+            visible: true,
+            locked: false,
+            z_index: 0,
+            opacity: 1.0,
+            blend_mode: None,
+            content: None,
+            font_family: None,
+            font_size: None,
+            font_weight: None,
+            font_style: None,
+            color: None,
+            text_align: None,
+            text_decoration: None,
+            text_transform: None,
+            line_height: None,
+            letter_spacing: None,
+            background_color: None,
+            text_path: None,
+            text_outline: None,
+            text_shadow: None,
+            drop_cap: None,
+            notes: Vec::new(),
+            pagination: None,
+            image_url: None,
+            image_path: image_path.map(str::to_string),
+            image_data: None,
+            image_link: None,
+            shape_type: None,
+            stroke_color: None,
+            stroke_width: None,
+            fill_color: None,
+            text_wrap: None,
+            path_data: None,
+            transform: None,
+            source_type: SourceType::Extracted,
+            role: LayerRole::Content,
+            ocr: None,
+            tags: Vec::new(),
+        }
+    }
+
+    fn project() -> BookProjectData {
+        let page = PageData {
+            page_index: 0,
+            width: 612.0,
+            height: 792.0,
+            dpi: None,
+            layers: vec![image_layer("image-0-1", Some("img-a")), image_layer("image-0-2", None)],
+            metadata: None,
+            background: None,
+        };
+        let metadata = crate::models::DocumentMetadata {
+            title: "Archive".to_string(),
+            author: String::new(),
+            created: String::new(),
+            modified: String::new(),
+            description: None,
+            subject: None,
+            keywords: Vec::new(),
+            isbn: None,
+            publisher: None,
+            language: None,
+            custom: Default::default(),
+        };
+        crate::export::build_project(&[page], &metadata, Vec::new(), false, Vec::new())
+    }
+
+    #[test]
+    fn test_archive_roundtrip() {
+        let project = project();
+        let images = vec![
+            ArchiveImage { id: "img-a".to_string(), data: vec![0x89, b'P', b'N', b'G', 1, 2, 3] },
+            ArchiveImage { id: "image-0-2".to_string(), data: vec![0xFF, 0xD8, 0xFF, 4, 5] },
+        ];
+
+        let data = write_archive(&project, &images).unwrap();
+        assert!(is_archive(&data));
+
+        let (loaded, loaded_images) = read_project(&data).unwrap();
+        assert_eq!(loaded, project);
+        assert_eq!(loaded_images, images);
+    }
+
+    #[test]
+    fn test_reader_loads_pages_on_demand() {
+        let project = project();
+        let data = write_archive(&project, &[]).unwrap();
+
+        let mut reader = ProjectArchiveReader::new(Cursor::new(&data[..])).unwrap();
+        assert_eq!(reader.page_count(), 1);
+        assert!(reader.project().document.pages.is_empty());
+        assert_eq!(reader.project().metadata.title, "Archive");
+        assert_eq!(reader.read_page(0).unwrap(), project.document.pages[0]);
+        assert!(reader.read_page(1).is_err());
+    }
+
+    #[test]
+    fn test_read_plain_json_project() {
+        let project = project();
+        let data = serde_json::to_vec_pretty(&project).unwrap();
+        assert!(!is_archive(&data));
+
+        let (loaded, images) = read_project(&data).unwrap();
+        assert_eq!(loaded, project);
+        assert!(images.is_empty());
+    }
+
+    #[test]
+    fn test_messagepack_archive_and_conversion() {
+        let mut project = project();
+        project.settings.encoding = ProjectEncoding::MessagePack;
+        let data = write_archive(&project, &[]).unwrap();
+
+        let reader = ProjectArchiveReader::new(Cursor::new(&data[..])).unwrap();
+        assert_eq!(reader.encoding(), ProjectEncoding::MessagePack);
+        assert_eq!(read_project(&data).unwrap().0, project);
+
+        let json = convert_project(&data, ProjectEncoding::Json).unwrap();
+        let (converted, _) = read_project(&json).unwrap();
+        assert_eq!(converted.settings.encoding, ProjectEncoding::Json);
+        assert_eq!(converted.document, project.document);
+
+        let bare = msgpack::to_vec(&project).unwrap();
+        assert_eq!(read_project(&bare).unwrap().0, project);
+    }
+
+    #[test]
+    fn test_referenced_image_ids() {
+        let mut project = project();
+        assert_eq!(referenced_image_ids(&project), vec!["image-0-1", "img-a", "image-0-2"]);
+
+        project.document.pages[0].layers[1].image_link =
+            Some(ImageLink { path: "/figures/plot.png".to_string(), modified: 1, hash: "ab".to_string() });
+        assert_eq!(referenced_image_ids(&project), vec!["image-0-1", "img-a"]);
+        assert_eq!(embed_linked_images(&mut project), 1);
+        assert_eq!(referenced_image_ids(&project), vec!["image-0-1", "img-a", "image-0-2"]);
+    }
+}
@@ -0,0 +1,1387 @@
+//! Clipboard interchange
+//!
+//! Converts layers to the formats other apps exchange through the clipboard
+//! and back: an SVG fragment (Illustrator, Figma, Inkscape) and plain text
+//! (Word, text editors). Copied SVG also carries the layers' own JSON in a
+//! `<metadata>` element, so pasting it back into Rook is lossless; SVG from
+//! elsewhere is read element by element. Rendering a PNG needs a rasterizer
+//! and is left to the desktop build.
+
+use crate::doc_metadata::{escape_xml, unescape_xml};
+use crate::layers::selection_bounds;
+use crate::models::{
+    transparency_css, Bounds, FillRule, LayerObject, LayerRole, LayerType, PathCommand, PathData, ShapeType,
+    SourceType, TextAlign, TransformMatrix,
+};
+use crate::text_wrap;
+use crate::units::{in_to_pt, mm_to_pt};
+
+/// `id` of the `<metadata>` element holding the copied layers' JSON
+const LAYERS_METADATA_ID: &str = "rook-layers";
+/// Font size of text with none set
+const DEFAULT_FONT_SIZE: f32 = 12.0;
+/// Line height of text with none set, in font sizes
+const DEFAULT_LINE_HEIGHT: f32 = 1.2;
+/// Average advance width used to size pasted text frames, in ems
+const AVG_CHAR_WIDTH: f32 = 0.5;
+/// Widest frame plain text is pasted into, in points (6.5in)
+const MAX_TEXT_WIDTH: f32 = 468.0;
+/// Bezier handle length of a quarter ellipse, in radii
+const KAPPA: f32 = 0.552_284_8;
+
+/// Number for SVG output, rounded to 1/1000
+pub(crate) fn num(value: f32) -> String {
+    format!("{}", (value * 1000.0).round() / 1000.0)
+}
+
+fn path_d(path: &PathData) -> String {
+    let parts: Vec<String> = path
+        .commands
+        .iter()
+        .map(|cmd| match *cmd {
+            PathCommand::MoveTo { x, y } => format!("M{} {}", num(x), num(y)),
+            PathCommand::LineTo { x, y } => format!("L{} {}", num(x), num(y)),
+            PathCommand::CurveTo { x1, y1, x2, y2, x, y } => {
+                format!("C{} {} {} {} {} {}", num(x1), num(y1), num(x2), num(y2), num(x), num(y))
+            }
+            PathCommand::ClosePath => "Z".to_string(),
+        })
+        .collect();
+    parts.join(" ")
+}
+
+/// Fill and stroke attributes of a shape or vector layer
+fn paint_attributes(layer: &LayerObject) -> String {
+    let mut attrs = match (&layer.fill_color, &layer.stroke_color) {
+        (Some(fill), _) => format!(" fill=\"{}\"", escape_xml(fill)),
+        // Unpainted vectors fill black, like PDF
+        (None, None) if layer.layer_type == LayerType::Vector => String::new(),
+        (None, _) => " fill=\"none\"".to_string(),
+    };
+    if let Some(stroke) = &layer.stroke_color {
+        let width = layer.stroke_width.unwrap_or(1.0);
+        attrs.push_str(&format!(" stroke=\"{}\" stroke-width=\"{}\"", escape_xml(stroke), num(width)));
+    }
+    if layer.path_data.as_ref().and_then(|p| p.fill_rule) == Some(FillRule::EvenOdd) {
+        attrs.push_str(" fill-rule=\"evenodd\"");
+    }
+    attrs
+}
+
+fn text_element(layer: &LayerObject, content: &str) -> String {
+    let b = layer.bounds;
+    let size = layer.font_size.unwrap_or(DEFAULT_FONT_SIZE);
+    let line_height = size * layer.line_height.unwrap_or(DEFAULT_LINE_HEIGHT);
+    let (anchor, x) = match layer.text_align.unwrap_or_default() {
+        TextAlign::Left => ("start", b.x),
+        TextAlign::Center => ("middle", b.x + b.width / 2.0),
+        TextAlign::Right => ("end", b.x + b.width),
+    };
+
+    let mut attrs = format!(" font-size=\"{}\"", num(size));
+    if let Some(family) = &layer.font_family {
+        attrs.push_str(&format!(" font-family=\"{}\"", escape_xml(family)));
+    }
+    if let Some(weight) = layer.font_weight {
+        attrs.push_str(&format!(" font-weight=\"{}\"", weight));
+    }
+    if let Some(style) = &layer.font_style {
+        attrs.push_str(&format!(" font-style=\"{}\"", escape_xml(style)));
+    }
+    if let Some(decoration) = &layer.text_decoration {
+        attrs.push_str(&format!(" text-decoration=\"{}\"", escape_xml(decoration)));
+    }
+    if let Some(spacing) = layer.letter_spacing {
+        attrs.push_str(&format!(" letter-spacing=\"{}\"", num(spacing)));
+    }
+    let fill = layer.color.as_deref().unwrap_or("#000000");
+
+    // Lines beside a drop cap are laid out here, each already aligned
+    if let Some((cap, layout)) = text_wrap::layout_drop_cap(layer, content, &[]) {
+        let family = layer.drop_cap.as_ref().and_then(|c| c.font_family.as_ref()).or(layer.font_family.as_ref());
+        let cap_family = family.map(|f| format!(" font-family=\"{}\"", escape_xml(f))).unwrap_or_default();
+        let lines: String = layout
+            .lines
+            .iter()
+            .map(|line| {
+                let (x, y) = (num(line.x), num(line.y + size));
+                format!("<tspan x=\"{}\" y=\"{}\">{}</tspan>", x, y, escape_xml(&line.text))
+            })
+            .collect();
+        return format!(
+            concat!(
+                "<g fill=\"{}\"><text x=\"{}\" y=\"{}\" font-size=\"{}\"{}>{}</text>",
+                "<text{} xml:space=\"preserve\">{}</text></g>"
+            ),
+            escape_xml(fill),
+            num(cap.x),
+            num(cap.baseline),
+            num(cap.font_size),
+            cap_family,
+            escape_xml(&cap.text),
+            attrs,
+            lines
+        );
+    }
+    if anchor != "start" {
+        attrs.push_str(&format!(" text-anchor=\"{}\"", anchor));
+    }
+
+    let lines: String = content
+        .lines()
+        .enumerate()
+        .map(|(i, line)| {
+            let dy = if i == 0 { 0.0 } else { line_height };
+            format!("<tspan x=\"{}\" dy=\"{}\">{}</tspan>", num(x), num(dy), escape_xml(line))
+        })
+        .collect();
+    format!(
+        "<text x=\"{}\" y=\"{}\" fill=\"{}\"{} xml:space=\"preserve\">{}</text>",
+        num(x),
+        num(b.y + size),
+        escape_xml(fill),
+        attrs,
+        lines
+    )
+}
+
+/// SVG element drawing a layer, `None` for layers with nothing to draw
+pub(crate) fn svg_element(layer: &LayerObject, image_href: &impl Fn(&LayerObject) -> Option<String>) -> Option<String> {
+    let b = layer.bounds;
+    let element = match layer.layer_type {
+        LayerType::Text => text_element(layer, layer.content.as_deref()?),
+        LayerType::Image => format!(
+            "<image x=\"{}\" y=\"{}\" width=\"{}\" height=\"{}\" preserveAspectRatio=\"none\" xlink:href=\"{}\"/>",
+            num(b.x),
+            num(b.y),
+            num(b.width),
+            num(b.height),
+            escape_xml(&image_href(layer)?)
+        ),
+        LayerType::Vector => format!("<path d=\"{}\"{}/>", path_d(layer.path_data.as_ref()?), paint_attributes(layer)),
+        LayerType::Shape => {
+            let paint = paint_attributes(layer);
+            match (layer.shape_type, &layer.path_data) {
+                (Some(ShapeType::Circle), _) => format!(
+                    "<ellipse cx=\"{}\" cy=\"{}\" rx=\"{}\" ry=\"{}\"{}/>",
+                    num(b.x + b.width / 2.0),
+                    num(b.y + b.height / 2.0),
+                    num(b.width / 2.0),
+                    num(b.height / 2.0),
+                    paint
+                ),
+                (Some(ShapeType::Line), _) => format!(
+                    "<line x1=\"{}\" y1=\"{}\" x2=\"{}\" y2=\"{}\"{}/>",
+                    num(b.x),
+                    num(b.y),
+                    num(b.x + b.width),
+                    num(b.y + b.height),
+                    paint
+                ),
+                (Some(ShapeType::Polygon), Some(path)) => format!("<path d=\"{}\"{}/>", path_d(path), paint),
+                _ => format!(
+                    "<rect x=\"{}\" y=\"{}\" width=\"{}\" height=\"{}\"{}/>",
+                    num(b.x),
+                    num(b.y),
+                    num(b.width),
+                    num(b.height),
+                    paint
+                ),
+            }
+        }
+    };
+
+    let css = transparency_css(layer);
+    if css.is_empty() {
+        return Some(element);
+    }
+    Some(format!("<g style=\"{}\">{}</g>", css, element))
+}
+
+/// SVG document of `layers`, cropped to their combined bounds
+///
+/// Coordinates stay in page points, so the `viewBox` starts at the
+/// selection's corner. `image_href` supplies each image layer's `href`
+/// (usually a data URL); images without one are left out of the drawing but
+/// kept in the embedded layer JSON. Hidden layers are not drawn.
+pub fn layers_to_svg(layers: &[LayerObject], image_href: impl Fn(&LayerObject) -> Option<String>) -> Option<String> {
+    let ids: Vec<String> = layers.iter().map(|l| l.id.clone()).collect();
+    let b = selection_bounds(layers, &ids)?;
+    let json = serde_json::to_string(layers).ok()?;
+
+    let mut sorted: Vec<&LayerObject> = layers.iter().filter(|l| l.visible).collect();
+    sorted.sort_by_key(|l| l.z_index);
+    let elements: String = sorted.into_iter().filter_map(|l| svg_element(l, &image_href)).collect();
+
+    Some(format!(
+        concat!(
+            "<svg xmlns=\"http://www.w3.org/2000/svg\" xmlns:xlink=\"http://www.w3.org/1999/xlink\" ",
+            "width=\"{w}\" height=\"{h}\" viewBox=\"{x} {y} {w} {h}\">",
+            "<metadata id=\"{id}\">{json}</metadata>{elements}</svg>"
+        ),
+        w = num(b.width),
+        h = num(b.height),
+        x = num(b.x),
+        y = num(b.y),
+        id = LAYERS_METADATA_ID,
+        json = escape_xml(&json),
+        elements = elements
+    ))
+}
+
+/// Text of the text layers in `layers`, in reading order
+pub fn layers_to_text(layers: &[LayerObject]) -> String {
+    crate::text_structure::layers_text(layers)
+}
+
+/// Layer with every optional field unset
+pub(crate) fn new_layer(id: String, layer_type: LayerType, bounds: Bounds) -> LayerObject {
+    LayerObject {
+        id,
+        layer_type,
+        bounds,
+        visible: true,
+        locked: false,
+        z_index: 0,
+        opacity: 1.0,
+        blend_mode: None,
+        content: None,
+        font_family: None,
+        font_size: None,
+        font_weight: None,
+        font_style: None,
+        color: None,
+        text_align: None,
+        text_decoration: None,
+        text_transform: None,
+        line_height: None,
+        letter_spacing: None,
+        background_color: None,
+        text_path: None,
+        text_outline: None,
+        text_shadow: None,
+        drop_cap: None,
+        notes: Vec::new(),
+        pagination: None,
+        image_url: None,
+        image_path: None,
+        image_data: None,
+        image_link: None,
+        shape_type: None,
+        stroke_color: None,
+        stroke_width: None,
+        fill_color: None,
+        text_wrap: None,
+        path_data: None,
+        transform: None,
+        source_type: SourceType::Imported,
+        role: LayerRole::Content,
+        ocr: None,
+        tags: Vec::new(),
+    }
+}
+
+/// Size of a text frame holding `lines`, estimated from an average glyph width
+fn text_frame_size(lines: &[&str], font_size: f32) -> (f32, f32) {
+    let longest = lines.iter().map(|l| l.chars().count()).max().unwrap_or(0);
+    let width = (longest as f32 * font_size * AVG_CHAR_WIDTH).max(font_size);
+    let height = lines.len().max(1) as f32 * font_size * DEFAULT_LINE_HEIGHT;
+    (width, height)
+}
+
+/// Text layer holding pasted plain text, its top-left corner at (`x`, `y`)
+///
+/// Line endings are normalized; the frame is sized to the longest line, up
+/// to `MAX_TEXT_WIDTH`, beyond which the text wraps.
+pub fn text_to_layer(text: &str, id: String, x: f32, y: f32) -> Option<LayerObject> {
+    let text = text.replace("\r\n", "\n").replace('\r', "\n");
+    let text = text.trim_matches('\n');
+    if text.trim().is_empty() {
+        return None;
+    }
+    let lines: Vec<&str> = text.lines().collect();
+    let (width, height) = text_frame_size(&lines, DEFAULT_FONT_SIZE);
+    let mut layer = new_layer(id, LayerType::Text, Bounds::new(x, y, width.min(MAX_TEXT_WIDTH), height));
+    layer.source_type = SourceType::Manual;
+    layer.content = Some(text.to_string());
+    layer.font_size = Some(DEFAULT_FONT_SIZE);
+    layer.color = Some("#000000".to_string());
+    Some(layer)
+}
+
+/// Move layers by (`dx`, `dy`), including their vector and text paths
+pub fn offset_layers(layers: &mut [LayerObject], dx: f32, dy: f32) {
+    let offset_path = |path: &mut PathData| {
+        for cmd in &mut path.commands {
+            match cmd {
+                PathCommand::MoveTo { x, y } | PathCommand::LineTo { x, y } => {
+                    *x += dx;
+                    *y += dy;
+                }
+                PathCommand::CurveTo { x1, y1, x2, y2, x, y } => {
+                    for (px, py) in [(x1, y1), (x2, y2), (x, y)] {
+                        *px += dx;
+                        *py += dy;
+                    }
+                }
+                PathCommand::ClosePath => {}
+            }
+        }
+    };
+    for layer in layers {
+        layer.bounds.x += dx;
+        layer.bounds.y += dy;
+        if let Some(path) = &mut layer.path_data {
+            offset_path(path);
+        }
+        if let Some(text_path) = &mut layer.text_path {
+            offset_path(&mut text_path.path);
+        }
+    }
+}
+
+// ============================================================================
+// SVG reading
+// ============================================================================
+
+enum Node<'a> {
+    Open { name: &'a str, attrs: Vec<(&'a str, String)>, empty: bool },
+    Close(&'a str),
+    Text(String),
+}
+
+/// Byte offset of the `>` closing a tag, skipping quoted attribute values
+fn tag_end(tag: &str) -> usize {
+    let mut quote = None;
+    for (i, c) in tag.char_indices() {
+        match (quote, c) {
+            (None, '"' | '\'') => quote = Some(c),
+            (Some(q), c) if c == q => quote = None,
+            (None, '>') => return i,
+            _ => {}
+        }
+    }
+    tag.len()
+}
+
+fn parse_attributes(mut rest: &str) -> Vec<(&str, String)> {
+    let mut attrs = Vec::new();
+    loop {
+        rest = rest.trim_start();
+        let Some(eq) = rest.find('=') else { break };
+        let name = rest[..eq].trim();
+        let value = rest[eq + 1..].trim_start();
+        let Some(quote) = value.chars().next().filter(|c| *c == '"' || *c == '\'') else { break };
+        let Some(end) = value[1..].find(quote) else { break };
+        attrs.push((name, unescape_xml(&value[1..end + 1])));
+        rest = &value[end + 2..];
+    }
+    attrs
+}
+
+/// Split an SVG document into tags and text, dropping comments,
+/// declarations and processing instructions
+fn parse_nodes(svg: &str) -> Vec<Node<'_>> {
+    let mut nodes = Vec::new();
+    let mut rest = svg;
+    while !rest.is_empty() {
+        if let Some(after) = rest.strip_prefix("<!--") {
+            rest = after.split_once("-->").map_or("", |(_, r)| r);
+        } else if let Some(after) = rest.strip_prefix("<![CDATA[") {
+            let (text, r) = after.split_once("]]>").unwrap_or((after, ""));
+            nodes.push(Node::Text(text.to_string()));
+            rest = r;
+        } else if rest.starts_with("<?") || rest.starts_with("<!") {
+            rest = rest.split_once('>').map_or("", |(_, r)| r);
+        } else if let Some(after) = rest.strip_prefix("</") {
+            let (name, r) = after.split_once('>').unwrap_or((after, ""));
+            nodes.push(Node::Close(name.trim()));
+            rest = r;
+        } else if let Some(after) = rest.strip_prefix('<') {
+            let end = tag_end(after);
+            let tag = &after[..end];
+            rest = after.get(end + 1..).unwrap_or("");
+            let (tag, empty) = match tag.strip_suffix('/') {
+                Some(tag) => (tag, true),
+                None => (tag, false),
+            };
+            let name_end = tag.find(char::is_whitespace).unwrap_or(tag.len());
+            nodes.push(Node::Open { name: &tag[..name_end], attrs: parse_attributes(&tag[name_end..]), empty });
+        } else {
+            let end = rest.find('<').unwrap_or(rest.len());
+            nodes.push(Node::Text(unescape_xml(&rest[..end])));
+            rest = &rest[end..];
+        }
+    }
+    nodes
+}
+
+/// Element name without a namespace prefix
+fn local_name(name: &str) -> &str {
+    name.rsplit(':').next().unwrap_or(name)
+}
+
+/// Leading number of a length, converted to points; user units and pixels
+/// are taken as points
+fn parse_length(value: &str) -> Option<f32> {
+    let value = value.trim();
+    let end = value
+        .char_indices()
+        .find(|&(i, c)| !(c.is_ascii_digit() || c == '.' || ((c == '-' || c == '+') && i == 0)))
+        .map_or(value.len(), |(i, _)| i);
+    let number: f32 = value[..end].parse().ok()?;
+    Some(match value[end..].trim() {
+        "mm" => mm_to_pt(number),
+        "cm" => mm_to_pt(number * 10.0),
+        "in" => in_to_pt(number),
+        _ => number,
+    })
+}
+
+/// Numbers in a list attribute (`points`, `viewBox`, transform arguments)
+fn parse_numbers(value: &str) -> Vec<f32> {
+    let mut tokens = PathTokens::new(value);
+    std::iter::from_fn(|| tokens.number()).collect()
+}
+
+fn named_color(name: &str) -> Option<&'static str> {
+    Some(match name.to_ascii_lowercase().as_str() {
+        "black" => "#000000",
+        "white" => "#ffffff",
+        "red" => "#ff0000",
+        "lime" => "#00ff00",
+        "green" => "#008000",
+        "blue" => "#0000ff",
+        "yellow" => "#ffff00",
+        "cyan" | "aqua" => "#00ffff",
+        "magenta" | "fuchsia" => "#ff00ff",
+        "gray" | "grey" => "#808080",
+        "silver" => "#c0c0c0",
+        "maroon" => "#800000",
+        "olive" => "#808000",
+        "navy" => "#000080",
+        "purple" => "#800080",
+        "teal" => "#008080",
+        "orange" => "#ffa500",
+        _ => return None,
+    })
+}
+
+/// Paint value as a hex color: `Some(None)` for `none`, `None` when not
+/// understood (gradients, `currentColor`), which keeps the inherited paint
+fn parse_paint(value: &str) -> Option<Option<String>> {
+    let value = value.trim();
+    if value == "none" || value == "transparent" {
+        return Some(None);
+    }
+    if let Some(hex) = value.strip_prefix('#') {
+        if !hex.chars().all(|c| c.is_ascii_hexdigit()) {
+            return None;
+        }
+        return match hex.len() {
+            3 => Some(Some(format!("#{}", hex.chars().flat_map(|c| [c, c]).collect::<String>().to_lowercase()))),
+            6 => Some(Some(format!("#{}", hex.to_lowercase()))),
+            _ => None,
+        };
+    }
+    if let Some(args) = value.strip_prefix("rgb(").and_then(|v| v.strip_suffix(')')) {
+        let channels: Vec<u8> = args
+            .split(',')
+            .filter_map(|c| {
+                let c = c.trim();
+                match c.strip_suffix('%') {
+                    Some(percent) => percent.parse::<f32>().ok().map(|p| (p * 2.55).round().clamp(0.0, 255.0) as u8),
+                    None => c.parse::<f32>().ok().map(|v| v.round().clamp(0.0, 255.0) as u8),
+                }
+            })
+            .collect();
+        return match channels[..] {
+            [r, g, b] => Some(Some(format!("#{:02x}{:02x}{:02x}", r, g, b))),
+            _ => None,
+        };
+    }
+    named_color(value).map(|hex| Some(hex.to_string()))
+}
+
+/// A `transform` attribute as one matrix (the rightmost transform applies first)
+fn parse_transform(value: &str) -> TransformMatrix {
+    let mut matrix = TransformMatrix::identity();
+    for part in value.split(')') {
+        let Some((name, args)) = part.split_once('(') else { continue };
+        let args = parse_numbers(args);
+        let arg = |i: usize, default: f32| args.get(i).copied().unwrap_or(default);
+        let step = match name.trim().trim_start_matches(',').trim() {
+            "matrix" if args.len() == 6 => {
+                TransformMatrix { a: args[0], b: args[1], c: args[2], d: args[3], e: args[4], f: args[5] }
+            }
+            "translate" => TransformMatrix::translate(arg(0, 0.0), arg(1, 0.0)),
+            "scale" => TransformMatrix::scale(arg(0, 1.0), arg(1, arg(0, 1.0))),
+            "rotate" => {
+                let (sin, cos) = arg(0, 0.0).to_radians().sin_cos();
+                let (cx, cy) = (arg(1, 0.0), arg(2, 0.0));
+                TransformMatrix::translate(-cx, -cy)
+                    .multiply(&TransformMatrix { a: cos, b: sin, c: -sin, d: cos, e: 0.0, f: 0.0 })
+                    .multiply(&TransformMatrix::translate(cx, cy))
+            }
+            "skewX" => TransformMatrix { c: arg(0, 0.0).to_radians().tan(), ..TransformMatrix::identity() },
+            "skewY" => TransformMatrix { b: arg(0, 0.0).to_radians().tan(), ..TransformMatrix::identity() },
+            _ => continue,
+        };
+        matrix = step.multiply(&matrix);
+    }
+    matrix
+}
+
+/// Tokens of path data and number lists
+struct PathTokens<'a> {
+    data: &'a [u8],
+    pos: usize,
+}
+
+impl<'a> PathTokens<'a> {
+    fn new(data: &'a str) -> Self {
+        Self { data: data.as_bytes(), pos: 0 }
+    }
+
+    fn skip_separators(&mut self) {
+        while self.data.get(self.pos).is_some_and(|b| b.is_ascii_whitespace() || *b == b',') {
+            self.pos += 1;
+        }
+    }
+
+    fn at_end(&mut self) -> bool {
+        self.skip_separators();
+        self.pos >= self.data.len()
+    }
+
+    fn command(&mut self) -> Option<u8> {
+        self.skip_separators();
+        let byte = *self.data.get(self.pos)?;
+        (byte.is_ascii_alphabetic() && byte != b'e' && byte != b'E').then(|| {
+            self.pos += 1;
+            byte
+        })
+    }
+
+    fn number(&mut self) -> Option<f32> {
+        self.skip_separators();
+        let start = self.pos;
+        let mut end = start;
+        let at = |i: usize| self.data.get(i).copied();
+        if matches!(at(end), Some(b'-' | b'+')) {
+            end += 1;
+        }
+        let mut digits = false;
+        while at(end).is_some_and(|b| b.is_ascii_digit()) {
+            end += 1;
+            digits = true;
+        }
+        if at(end) == Some(b'.') {
+            end += 1;
+            while at(end).is_some_and(|b| b.is_ascii_digit()) {
+                end += 1;
+                digits = true;
+            }
+        }
+        if !digits {
+            return None;
+        }
+        if matches!(at(end), Some(b'e' | b'E')) {
+            let mut exp = end + 1;
+            if matches!(at(exp), Some(b'-' | b'+')) {
+                exp += 1;
+            }
+            if at(exp).is_some_and(|b| b.is_ascii_digit()) {
+                while at(exp).is_some_and(|b| b.is_ascii_digit()) {
+                    exp += 1;
+                }
+                end = exp;
+            }
+        }
+        let value = std::str::from_utf8(&self.data[start..end]).ok()?.parse().ok()?;
+        self.pos = end;
+        Some(value)
+    }
+
+    /// Arc flags may be written without separators ("a1 1 0 00 2 2")
+    fn flag(&mut self) -> Option<bool> {
+        self.skip_separators();
+        let flag = match self.data.get(self.pos)? {
+            b'0' => false,
+            b'1' => true,
+            _ => return None,
+        };
+        self.pos += 1;
+        Some(flag)
+    }
+
+    fn point(&mut self) -> Option<(f32, f32)> {
+        Some((self.number()?, self.number()?))
+    }
+}
+
+/// Cubic curves approximating an SVG elliptical arc
+#[allow(clippy::too_many_arguments)]
+fn arc_curves(
+    from: (f32, f32),
+    rx: f32,
+    ry: f32,
+    angle: f32,
+    large_arc: bool,
+    sweep: bool,
+    to: (f32, f32),
+    out: &mut Vec<PathCommand>,
+) {
+    if from == to {
+        return;
+    }
+    let (mut rx, mut ry) = (rx.abs(), ry.abs());
+    if rx == 0.0 || ry == 0.0 {
+        out.push(PathCommand::LineTo { x: to.0, y: to.1 });
+        return;
+    }
+    let (sin, cos) = angle.to_radians().sin_cos();
+    let (dx, dy) = ((from.0 - to.0) / 2.0, (from.1 - to.1) / 2.0);
+    let (x1, y1) = (cos * dx + sin * dy, -sin * dx + cos * dy);
+
+    // Radii too small to reach the end point are scaled up
+    let lambda = (x1 * x1) / (rx * rx) + (y1 * y1) / (ry * ry);
+    if lambda > 1.0 {
+        rx *= lambda.sqrt();
+        ry *= lambda.sqrt();
+    }
+    let numerator = rx * rx * ry * ry - rx * rx * y1 * y1 - ry * ry * x1 * x1;
+    let denominator = rx * rx * y1 * y1 + ry * ry * x1 * x1;
+    let sign = if large_arc == sweep { -1.0 } else { 1.0 };
+    let coef = sign * (numerator / denominator).max(0.0).sqrt();
+    let (cx1, cy1) = (coef * rx * y1 / ry, -coef * ry * x1 / rx);
+    let (cx, cy) = (
+        cos * cx1 - sin * cy1 + (from.0 + to.0) / 2.0,
+        sin * cx1 + cos * cy1 + (from.1 + to.1) / 2.0,
+    );
+
+    let start = ((y1 - cy1) / ry).atan2((x1 - cx1) / rx);
+    let mut sweep_angle = ((-y1 - cy1) / ry).atan2((-x1 - cx1) / rx) - start;
+    if sweep && sweep_angle < 0.0 {
+        sweep_angle += std::f32::consts::TAU;
+    } else if !sweep && sweep_angle > 0.0 {
+        sweep_angle -= std::f32::consts::TAU;
+    }
+
+    let segments = (sweep_angle.abs() / std::f32::consts::FRAC_PI_2).ceil().max(1.0) as usize;
+    let delta = sweep_angle / segments as f32;
+    let handle = 4.0 / 3.0 * (delta / 4.0).tan();
+    let map = |ux: f32, uy: f32| (cx + rx * ux * cos - ry * uy * sin, cy + rx * ux * sin + ry * uy * cos);
+    for i in 0..segments {
+        let a1 = start + delta * i as f32;
+        let a2 = a1 + delta;
+        let (s1, c1) = a1.sin_cos();
+        let (s2, c2) = a2.sin_cos();
+        let (x1, y1) = map(c1 - handle * s1, s1 + handle * c1);
+        let (x2, y2) = map(c2 + handle * s2, s2 - handle * c2);
+        let (x, y) = if i + 1 == segments { to } else { map(c2, s2) };
+        out.push(PathCommand::CurveTo { x1, y1, x2, y2, x, y });
+    }
+}
+
+/// Path commands of SVG path data, in absolute coordinates
+///
+/// Quadratic curves and arcs become cubics. Parsing stops at the first
+/// error, keeping what came before, as SVG renderers do.
+pub fn parse_path_data(d: &str) -> Vec<PathCommand> {
+    let mut tokens = PathTokens::new(d);
+    let mut commands = Vec::new();
+    let (mut current, mut start) = ((0.0f32, 0.0f32), (0.0f32, 0.0f32));
+    // Last control point of the previous cubic / quadratic, for S and T
+    let mut last_cubic: Option<(f32, f32)> = None;
+    let mut last_quad: Option<(f32, f32)> = None;
+    let mut command = None;
+
+    loop {
+        match tokens.command() {
+            Some(c) => command = Some(c),
+            None if tokens.at_end() => break,
+            None if matches!(command, None | Some(b'Z' | b'z')) => break,
+            None => {}
+        }
+        let Some(c) = command else { break };
+        let relative = c.is_ascii_lowercase();
+        let origin = if relative { current } else { (0.0, 0.0) };
+        let abs = move |(x, y): (f32, f32)| (origin.0 + x, origin.1 + y);
+        let (mut cubic, mut quad) = (None, None);
+
+        let ok = match c.to_ascii_uppercase() {
+            b'M' => tokens.point().map(|p| {
+                current = abs(p);
+                start = current;
+                commands.push(PathCommand::MoveTo { x: current.0, y: current.1 });
+                // Further pairs are implicit line-tos
+                command = Some(if relative { b'l' } else { b'L' });
+            }),
+            b'L' => tokens.point().map(|p| {
+                current = abs(p);
+                commands.push(PathCommand::LineTo { x: current.0, y: current.1 });
+            }),
+            b'H' => tokens.number().map(|x| {
+                current.0 = if relative { current.0 + x } else { x };
+                commands.push(PathCommand::LineTo { x: current.0, y: current.1 });
+            }),
+            b'V' => tokens.number().map(|y| {
+                current.1 = if relative { current.1 + y } else { y };
+                commands.push(PathCommand::LineTo { x: current.0, y: current.1 });
+            }),
+            b'C' | b'S' => {
+                let first = if c.eq_ignore_ascii_case(&b'C') {
+                    tokens.point().map(abs)
+                } else {
+                    Some(last_cubic.map_or(current, |(x, y)| (2.0 * current.0 - x, 2.0 * current.1 - y)))
+                };
+                first.zip(tokens.point().map(abs)).zip(tokens.point().map(abs)).map(|((c1, c2), end)| {
+                    commands.push(PathCommand::CurveTo { x1: c1.0, y1: c1.1, x2: c2.0, y2: c2.1, x: end.0, y: end.1 });
+                    cubic = Some(c2);
+                    current = end;
+                })
+            }
+            b'Q' | b'T' => {
+                let control = if c.eq_ignore_ascii_case(&b'Q') {
+                    tokens.point().map(abs)
+                } else {
+                    Some(last_quad.map_or(current, |(x, y)| (2.0 * current.0 - x, 2.0 * current.1 - y)))
+                };
+                control.zip(tokens.point().map(abs)).map(|(q, end)| {
+                    let c1 = (current.0 + 2.0 / 3.0 * (q.0 - current.0), current.1 + 2.0 / 3.0 * (q.1 - current.1));
+                    let c2 = (end.0 + 2.0 / 3.0 * (q.0 - end.0), end.1 + 2.0 / 3.0 * (q.1 - end.1));
+                    commands.push(PathCommand::CurveTo { x1: c1.0, y1: c1.1, x2: c2.0, y2: c2.1, x: end.0, y: end.1 });
+                    quad = Some(q);
+                    current = end;
+                })
+            }
+            b'A' => (|| {
+                let (rx, ry, angle) = (tokens.number()?, tokens.number()?, tokens.number()?);
+                let (large_arc, sweep) = (tokens.flag()?, tokens.flag()?);
+                let end = abs(tokens.point()?);
+                arc_curves(current, rx, ry, angle, large_arc, sweep, end, &mut commands);
+                current = end;
+                Some(())
+            })(),
+            b'Z' => {
+                commands.push(PathCommand::ClosePath);
+                current = start;
+                Some(())
+            }
+            _ => None,
+        };
+        if ok.is_none() {
+            break;
+        }
+        last_cubic = cubic;
+        last_quad = quad;
+    }
+    commands
+}
+
+/// Presentation state inherited down the SVG tree
+#[derive(Clone)]
+struct SvgStyle {
+    transform: TransformMatrix,
+    fill: Option<String>,
+    stroke: Option<String>,
+    stroke_width: f32,
+    opacity: f32,
+    fill_rule: FillRule,
+    font_family: Option<String>,
+    font_size: f32,
+    font_weight: Option<u16>,
+    font_style: Option<String>,
+    text_anchor: TextAlign,
+}
+
+impl Default for SvgStyle {
+    fn default() -> Self {
+        Self {
+            transform: TransformMatrix::identity(),
+            fill: Some("#000000".to_string()),
+            stroke: None,
+            stroke_width: 1.0,
+            opacity: 1.0,
+            fill_rule: FillRule::NonZero,
+            font_family: None,
+            font_size: DEFAULT_FONT_SIZE,
+            font_weight: None,
+            font_style: None,
+            text_anchor: TextAlign::Left,
+        }
+    }
+}
+
+/// Attribute lookup where `style` declarations override presentation attributes
+struct Attributes<'a> {
+    attrs: &'a [(&'a str, String)],
+    declarations: Vec<(String, String)>,
+}
+
+impl<'a> Attributes<'a> {
+    fn new(attrs: &'a [(&'a str, String)]) -> Self {
+        let declarations = attrs
+            .iter()
+            .find(|(name, _)| *name == "style")
+            .map(|(_, style)| {
+                style
+                    .split(';')
+                    .filter_map(|d| d.split_once(':'))
+                    .map(|(k, v)| (k.trim().to_string(), v.trim().to_string()))
+                    .collect()
+            })
+            .unwrap_or_default();
+        Self { attrs, declarations }
+    }
+
+    fn get(&self, name: &str) -> Option<&str> {
+        self.declarations
+            .iter()
+            .rev()
+            .find(|(k, _)| k == name)
+            .map(|(_, v)| v.as_str())
+            .or_else(|| self.attrs.iter().find(|(k, _)| *k == name).map(|(_, v)| v.as_str()))
+    }
+
+    fn length(&self, name: &str) -> f32 {
+        self.get(name).and_then(parse_length).unwrap_or(0.0)
+    }
+}
+
+impl SvgStyle {
+    /// Style of a child element with `attrs`
+    fn inherit(&self, attrs: &Attributes) -> Self {
+        let mut style = self.clone();
+        if let Some(transform) = attrs.get("transform") {
+            style.transform = parse_transform(transform).multiply(&self.transform);
+        }
+        if let Some(fill) = attrs.get("fill").and_then(parse_paint) {
+            style.fill = fill;
+        }
+        if let Some(stroke) = attrs.get("stroke").and_then(parse_paint) {
+            style.stroke = stroke;
+        }
+        if let Some(width) = attrs.get("stroke-width").and_then(parse_length) {
+            style.stroke_width = width;
+        }
+        if let Some(opacity) = attrs.get("opacity").and_then(|o| o.trim().parse::<f32>().ok()) {
+            style.opacity *= opacity.clamp(0.0, 1.0);
+        }
+        match attrs.get("fill-rule") {
+            Some("evenodd") => style.fill_rule = FillRule::EvenOdd,
+            Some("nonzero") => style.fill_rule = FillRule::NonZero,
+            _ => {}
+        }
+        if let Some(family) = attrs.get("font-family") {
+            let first = family.split(',').next().unwrap_or_default();
+            style.font_family = Some(first.trim().trim_matches(|c| c == '"' || c == '\'').to_string());
+        }
+        if let Some(size) = attrs.get("font-size") {
+            match size.trim().strip_suffix("em").and_then(|em| em.trim().parse::<f32>().ok()) {
+                Some(em) => style.font_size *= em,
+                None => style.font_size = parse_length(size).unwrap_or(style.font_size),
+            }
+        }
+        match attrs.get("font-weight") {
+            Some("bold" | "bolder") => style.font_weight = Some(700),
+            Some("normal") => style.font_weight = Some(400),
+            Some(weight) => style.font_weight = weight.trim().parse().ok().or(style.font_weight),
+            None => {}
+        }
+        if let Some(font_style) = attrs.get("font-style") {
+            style.font_style = Some(font_style.trim().to_string());
+        }
+        match attrs.get("text-anchor") {
+            Some("middle") => style.text_anchor = TextAlign::Center,
+            Some("end") => style.text_anchor = TextAlign::Right,
+            Some("start") => style.text_anchor = TextAlign::Left,
+            _ => {}
+        }
+        style
+    }
+
+    /// Transform has no rotation or skew
+    fn axis_aligned(&self) -> bool {
+        self.transform.b.abs() < 1e-6 && self.transform.c.abs() < 1e-6
+    }
+}
+
+/// Text element being collected
+struct PendingText {
+    style: SvgStyle,
+    x: f32,
+    y: f32,
+    lines: Vec<String>,
+}
+
+impl PendingText {
+    fn push(&mut self, text: &str) {
+        let line = self.lines.last_mut().expect("text starts with a line");
+        for word in text.split_whitespace() {
+            if !line.is_empty() && !line.ends_with(' ') {
+                line.push(' ');
+            }
+            line.push_str(word);
+        }
+        if text.ends_with(char::is_whitespace) && !line.is_empty() {
+            line.push(' ');
+        }
+    }
+}
+
+/// Builds layers from SVG elements
+struct SvgReader<'a> {
+    id_prefix: &'a str,
+    layers: Vec<LayerObject>,
+}
+
+impl SvgReader<'_> {
+    fn next_layer(&mut self, layer_type: LayerType, bounds: Bounds, style: &SvgStyle) -> &mut LayerObject {
+        let index = self.layers.len();
+        let mut layer = new_layer(format!("{}-{}", self.id_prefix, index), layer_type, bounds);
+        layer.z_index = index as i32;
+        layer.opacity = style.opacity;
+        self.layers.push(layer);
+        &mut self.layers[index]
+    }
+
+    fn painted(&mut self, layer_type: LayerType, bounds: Bounds, style: &SvgStyle) -> Option<&mut LayerObject> {
+        if style.fill.is_none() && style.stroke.is_none() {
+            return None;
+        }
+        let scale = (style.transform.scale_x() + style.transform.scale_y()) / 2.0;
+        let layer = self.next_layer(layer_type, bounds, style);
+        layer.fill_color = style.fill.clone();
+        layer.stroke_color = style.stroke.clone();
+        layer.stroke_width = style.stroke.as_ref().map(|_| style.stroke_width * scale);
+        Some(layer)
+    }
+
+    /// Vector layer of `commands` (in the element's user space)
+    fn vector(&mut self, commands: Vec<PathCommand>, style: &SvgStyle) {
+        let t = style.transform;
+        let commands: Vec<PathCommand> = commands
+            .into_iter()
+            .map(|cmd| match cmd {
+                PathCommand::MoveTo { x, y } => {
+                    let (x, y) = t.transform_point(x, y);
+                    PathCommand::MoveTo { x, y }
+                }
+                PathCommand::LineTo { x, y } => {
+                    let (x, y) = t.transform_point(x, y);
+                    PathCommand::LineTo { x, y }
+                }
+                PathCommand::CurveTo { x1, y1, x2, y2, x, y } => {
+                    let (x1, y1) = t.transform_point(x1, y1);
+                    let (x2, y2) = t.transform_point(x2, y2);
+                    let (x, y) = t.transform_point(x, y);
+                    PathCommand::CurveTo { x1, y1, x2, y2, x, y }
+                }
+                PathCommand::ClosePath => PathCommand::ClosePath,
+            })
+            .collect();
+        let points: Vec<(f32, f32)> = commands
+            .iter()
+            .flat_map(|cmd| match *cmd {
+                PathCommand::MoveTo { x, y } | PathCommand::LineTo { x, y } => vec![(x, y)],
+                PathCommand::CurveTo { x1, y1, x2, y2, x, y } => vec![(x1, y1), (x2, y2), (x, y)],
+                PathCommand::ClosePath => Vec::new(),
+            })
+            .collect();
+        let Some(bounds) = points_bounds(&points) else { return };
+        let fill_rule = (style.fill_rule == FillRule::EvenOdd).then_some(FillRule::EvenOdd);
+        if let Some(layer) = self.painted(LayerType::Vector, bounds, style) {
+            layer.path_data = Some(PathData { commands, fill_rule });
+        }
+    }
+
+    /// Shape layer when the transform keeps it upright, else a vector
+    fn shape(&mut self, shape_type: ShapeType, rect: Bounds, outline: Vec<PathCommand>, style: &SvgStyle) {
+        if !style.axis_aligned() {
+            return self.vector(outline, style);
+        }
+        let (x0, y0) = style.transform.transform_point(rect.x, rect.y);
+        let (x1, y1) = style.transform.transform_point(rect.x + rect.width, rect.y + rect.height);
+        let bounds = Bounds::new(x0.min(x1), y0.min(y1), (x1 - x0).abs(), (y1 - y0).abs());
+        if let Some(layer) = self.painted(LayerType::Shape, bounds, style) {
+            layer.shape_type = Some(shape_type);
+        }
+    }
+
+    fn element(&mut self, name: &str, attrs: &Attributes, style: &SvgStyle) {
+        match name {
+            "rect" => {
+                let rect = Bounds::new(attrs.length("x"), attrs.length("y"), attrs.length("width"), attrs.length("height"));
+                if rect.width <= 0.0 || rect.height <= 0.0 {
+                    return;
+                }
+                let rx = attrs.get("rx").and_then(parse_length);
+                let ry = attrs.get("ry").and_then(parse_length);
+                let (rx, ry) = match (rx.or(ry), ry.or(rx)) {
+                    (Some(rx), Some(ry)) => ((rx.min(rect.width / 2.0)), ry.min(rect.height / 2.0)),
+                    _ => (0.0, 0.0),
+                };
+                if rx > 0.0 && ry > 0.0 {
+                    return self.vector(rounded_rect(rect, rx, ry), style);
+                }
+                self.shape(ShapeType::Rectangle, rect, rounded_rect(rect, 0.0, 0.0), style);
+            }
+            "circle" | "ellipse" => {
+                let (cx, cy) = (attrs.length("cx"), attrs.length("cy"));
+                let (rx, ry) = match name {
+                    "circle" => (attrs.length("r"), attrs.length("r")),
+                    _ => (attrs.length("rx"), attrs.length("ry")),
+                };
+                if rx <= 0.0 || ry <= 0.0 {
+                    return;
+                }
+                let rect = Bounds::new(cx - rx, cy - ry, rx * 2.0, ry * 2.0);
+                self.shape(ShapeType::Circle, rect, ellipse_path(cx, cy, rx, ry), style);
+            }
+            "line" => {
+                let commands = vec![
+                    PathCommand::MoveTo { x: attrs.length("x1"), y: attrs.length("y1") },
+                    PathCommand::LineTo { x: attrs.length("x2"), y: attrs.length("y2") },
+                ];
+                // Lines are never filled
+                self.vector(commands, &SvgStyle { fill: None, ..style.clone() });
+            }
+            "polyline" | "polygon" => {
+                let numbers = parse_numbers(attrs.get("points").unwrap_or_default());
+                let mut commands: Vec<PathCommand> = numbers
+                    .chunks_exact(2)
+                    .enumerate()
+                    .map(|(i, p)| match i {
+                        0 => PathCommand::MoveTo { x: p[0], y: p[1] },
+                        _ => PathCommand::LineTo { x: p[0], y: p[1] },
+                    })
+                    .collect();
+                if commands.len() < 2 {
+                    return;
+                }
+                if name == "polygon" {
+                    commands.push(PathCommand::ClosePath);
+                }
+                self.vector(commands, style);
+            }
+            "path" => {
+                let commands = parse_path_data(attrs.get("d").unwrap_or_default());
+                if commands.len() > 1 {
+                    self.vector(commands, style);
+                }
+            }
+            "image" => {
+                let href = attrs.get("href").or_else(|| attrs.get("xlink:href")).unwrap_or_default();
+                if !href.starts_with("data:image/") {
+                    return;
+                }
+                let rect = Bounds::new(attrs.length("x"), attrs.length("y"), attrs.length("width"), attrs.length("height"));
+                let (x0, y0) = style.transform.transform_point(rect.x, rect.y);
+                let (x1, y1) = style.transform.transform_point(rect.x + rect.width, rect.y + rect.height);
+                let bounds = Bounds::new(x0.min(x1), y0.min(y1), (x1 - x0).abs(), (y1 - y0).abs());
+                let layer = self.next_layer(LayerType::Image, bounds, style);
+                layer.image_url = Some(href.to_string());
+            }
+            _ => {}
+        }
+    }
+
+    fn text(&mut self, text: PendingText) {
+        let lines: Vec<&str> = text.lines.iter().map(|l| l.trim()).collect();
+        let first = lines.iter().position(|l| !l.is_empty());
+        let last = lines.iter().rposition(|l| !l.is_empty());
+        let (Some(first), Some(last)) = (first, last) else { return };
+        let lines = &lines[first..=last];
+
+        let style = &text.style;
+        let size = style.font_size * style.transform.scale_y();
+        let (x, baseline) = style.transform.transform_point(text.x, text.y);
+        let (width, height) = text_frame_size(lines, size);
+        let left = match style.text_anchor {
+            TextAlign::Left => x,
+            TextAlign::Center => x - width / 2.0,
+            TextAlign::Right => x - width,
+        };
+        let bounds = Bounds::new(left, baseline - size, width, height);
+        let fill = style.fill.clone();
+        let layer = self.next_layer(LayerType::Text, bounds, style);
+        layer.content = Some(lines.join("\n"));
+        layer.font_size = Some(size);
+        layer.font_family = style.font_family.clone();
+        layer.font_weight = style.font_weight;
+        layer.font_style = style.font_style.clone();
+        layer.color = fill.or_else(|| Some("#000000".to_string()));
+        layer.text_align = (style.text_anchor != TextAlign::Left).then_some(style.text_anchor);
+    }
+}
+
+fn points_bounds(points: &[(f32, f32)]) -> Option<Bounds> {
+    let (first, rest) = points.split_first()?;
+    let (mut min, mut max) = (*first, *first);
+    for &(x, y) in rest {
+        min = (min.0.min(x), min.1.min(y));
+        max = (max.0.max(x), max.1.max(y));
+    }
+    Some(Bounds::new(min.0, min.1, max.0 - min.0, max.1 - min.1))
+}
+
+pub(crate) fn ellipse_path(cx: f32, cy: f32, rx: f32, ry: f32) -> Vec<PathCommand> {
+    let (kx, ky) = (rx * KAPPA, ry * KAPPA);
+    vec![
+        PathCommand::MoveTo { x: cx + rx, y: cy },
+        PathCommand::CurveTo { x1: cx + rx, y1: cy + ky, x2: cx + kx, y2: cy + ry, x: cx, y: cy + ry },
+        PathCommand::CurveTo { x1: cx - kx, y1: cy + ry, x2: cx - rx, y2: cy + ky, x: cx - rx, y: cy },
+        PathCommand::CurveTo { x1: cx - rx, y1: cy - ky, x2: cx - kx, y2: cy - ry, x: cx, y: cy - ry },
+        PathCommand::CurveTo { x1: cx + kx, y1: cy - ry, x2: cx + rx, y2: cy - ky, x: cx + rx, y: cy },
+        PathCommand::ClosePath,
+    ]
+}
+
+/// Rectangle outline with elliptical corners of radii `rx`, `ry` (zero for square)
+pub(crate) fn rounded_rect(rect: Bounds, rx: f32, ry: f32) -> Vec<PathCommand> {
+    let (left, top, right, bottom) = (rect.x, rect.y, rect.x + rect.width, rect.y + rect.height);
+    if rx <= 0.0 || ry <= 0.0 {
+        return vec![
+            PathCommand::MoveTo { x: left, y: top },
+            PathCommand::LineTo { x: right, y: top },
+            PathCommand::LineTo { x: right, y: bottom },
+            PathCommand::LineTo { x: left, y: bottom },
+            PathCommand::ClosePath,
+        ];
+    }
+    let (kx, ky) = (rx * (1.0 - KAPPA), ry * (1.0 - KAPPA));
+    vec![
+        PathCommand::MoveTo { x: left + rx, y: top },
+        PathCommand::LineTo { x: right - rx, y: top },
+        PathCommand::CurveTo { x1: right - kx, y1: top, x2: right, y2: top + ky, x: right, y: top + ry },
+        PathCommand::LineTo { x: right, y: bottom - ry },
+        PathCommand::CurveTo { x1: right, y1: bottom - ky, x2: right - kx, y2: bottom, x: right - rx, y: bottom },
+        PathCommand::LineTo { x: left + rx, y: bottom },
+        PathCommand::CurveTo { x1: left + kx, y1: bottom, x2: left, y2: bottom - ky, x: left, y: bottom - ry },
+        PathCommand::LineTo { x: left, y: top + ry },
+        PathCommand::CurveTo { x1: left, y1: top + ky, x2: left + kx, y2: top, x: left + rx, y: top },
+        PathCommand::ClosePath,
+    ]
+}
+
+/// Elements whose content is not drawn where it appears
+const NON_RENDERED: [&str; 12] = [
+    "defs",
+    "clipPath",
+    "mask",
+    "symbol",
+    "pattern",
+    "marker",
+    "linearGradient",
+    "radialGradient",
+    "filter",
+    "style",
+    "script",
+    "metadata",
+];
+
+/// Layers copied from Rook, read from the SVG's `<metadata>`
+fn embedded_layers(svg: &str) -> Option<Vec<LayerObject>> {
+    let open = format!("<metadata id=\"{}\">", LAYERS_METADATA_ID);
+    let start = svg.find(&open)? + open.len();
+    let end = start + svg[start..].find("</metadata>")?;
+    serde_json::from_str(&unescape_xml(&svg[start..end])).ok()
+}
+
+/// Layers pasted from an SVG document
+///
+/// SVG copied from Rook yields the original layers. Any other SVG is read
+/// element by element: upright rectangles and ellipses become shapes, other
+/// geometry vector paths, `<text>` text layers and embedded `<image>`s image
+/// layers whose `image_url` is the data URL. User units are taken as points
+/// and coordinates are kept, so callers position the result with
+/// [`offset_layers`]. Layers get ids `{id_prefix}-{n}` and z-indices in
+/// document order.
+pub fn svg_to_layers(svg: &str, id_prefix: &str) -> Result<Vec<LayerObject>, String> {
+    if !svg.contains("<svg") {
+        return Err("Not an SVG document".to_string());
+    }
+    if let Some(mut layers) = embedded_layers(svg) {
+        layers.sort_by_key(|l| l.z_index);
+        for (i, layer) in layers.iter_mut().enumerate() {
+            layer.id = format!("{}-{}", id_prefix, i);
+            layer.z_index = i as i32;
+        }
+        return Ok(layers);
+    }
+
+    let mut reader = SvgReader { id_prefix, layers: Vec::new() };
+    let mut styles = vec![SvgStyle::default()];
+    let mut skip_depth = 0usize;
+    let mut text: Option<PendingText> = None;
+
+    for node in parse_nodes(svg) {
+        match node {
+            Node::Open { name, attrs, empty } => {
+                let name = local_name(name);
+                if skip_depth > 0 || NON_RENDERED.contains(&name) {
+                    skip_depth += usize::from(!empty);
+                    continue;
+                }
+                let attributes = Attributes::new(&attrs);
+                let style = styles.last().expect("root style").inherit(&attributes);
+                match (name, &mut text) {
+                    ("text", _) => {
+                        let x = parse_numbers(attributes.get("x").unwrap_or_default()).first().copied().unwrap_or(0.0);
+                        let y = parse_numbers(attributes.get("y").unwrap_or_default()).first().copied().unwrap_or(0.0);
+                        text = Some(PendingText { style: style.clone(), x, y, lines: vec![String::new()] });
+                    }
+                    ("tspan", Some(pending)) => {
+                        // A tspan that repositions starts a new line
+                        let moves = attributes.get("x").is_some()
+                            || attributes.get("y").is_some()
+                            || attributes.length("dy") != 0.0;
+                        if moves && pending.lines.last().is_some_and(|l| !l.trim().is_empty()) {
+                            pending.lines.push(String::new());
+                        }
+                    }
+                    _ => reader.element(name, &attributes, &style),
+                }
+                if !empty {
+                    styles.push(style);
+                }
+            }
+            Node::Close(name) => {
+                if skip_depth > 0 {
+                    skip_depth -= 1;
+                    continue;
+                }
+                if local_name(name) == "text" {
+                    if let Some(pending) = text.take() {
+                        reader.text(pending);
+                    }
+                }
+                if styles.len() > 1 {
+                    styles.pop();
+                }
+            }
+            Node::Text(content) => {
+                if let Some(pending) = &mut text {
+                    pending.push(&content);
+                }
+            }
+        }
+    }
+    Ok(reader.layers)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn layer(json: serde_json::Value) -> LayerObject {
+        crate::test_util::layer("", "text").fields(json).build()
+    }
+
+    #[test]
+    fn test_svg_roundtrip_keeps_layers() {
+        let layers = vec![
+            layer(serde_json::json!({
+                "id": "t", "type": "text", "bounds": { "x": 72, "y": 100, "width": 200, "height": 30 },
+                "content": "Fish & <Chips>\nto go", "fontSize": 12, "fontFamily": "Georgia", "color": "#112233"
+            })),
+            layer(serde_json::json!({
+                "id": "s", "type": "shape", "bounds": { "x": 60, "y": 90, "width": 50, "height": 40 },
+                "shapeType": "circle", "fillColor": "#ff0000", "opacity": 0.5, "zIndex": 1
+            })),
+        ];
+        let svg = layers_to_svg(&layers, |_| None).unwrap();
+        assert!(svg.contains("viewBox=\"60 90 212 40\""));
+        assert!(svg.contains("<tspan x=\"72\" dy=\"0\">Fish &amp; &lt;Chips&gt;</tspan>"));
+        assert!(svg.contains("<g style=\"opacity:0.5;\"><ellipse cx=\"85\" cy=\"110\""));
+
+        let pasted = svg_to_layers(&svg, "paste").unwrap();
+        assert_eq!(pasted.len(), 2);
+        assert_eq!(pasted[0].id, "paste-0");
+        assert_eq!(pasted[0].content.as_deref(), Some("Fish & <Chips>\nto go"));
+        assert_eq!(pasted[1].shape_type, Some(ShapeType::Circle));
+        assert_eq!(layers_to_text(&layers), "Fish & <Chips>\nto go");
+    }
+
+    #[test]
+    fn test_foreign_svg() {
+        let svg = r##"<?xml version="1.0"?>
+            <!-- exported -->
+            <svg xmlns="http://www.w3.org/2000/svg" viewBox="0 0 200 200">
+              <defs><linearGradient id="g"><stop offset="0"/></linearGradient><rect id="hidden" width="9" height="9"/></defs>
+              <g transform="translate(10 20)" fill="#0F0">
+                <rect x="5" y="5" width="20" height="10" style="stroke:rgb(0,0,255);stroke-width:2"/>
+                <circle cx="50" cy="50" r="10" transform="rotate(45 50 50)"/>
+                <path d="M0,0 h10 v10 q-5 5 -10 0 a5 5 0 01 0-10z" fill="none" stroke="black"/>
+              </g>
+              <text x="100" y="150" font-family="'Open Sans', sans-serif" font-size="16px" text-anchor="middle">
+                <tspan x="100" dy="0">Hello &#x2019;world&#8217;</tspan><tspan x="100" dy="1.2em">again</tspan>
+              </text>
+              <image x="0" y="0" width="4" height="2" href="data:image/png;base64,AAAA"/>
+            </svg>"##;
+        let layers = svg_to_layers(svg, "p").unwrap();
+        let types: Vec<LayerType> = layers.iter().map(|l| l.layer_type).collect();
+        assert_eq!(
+            types,
+            vec![LayerType::Shape, LayerType::Vector, LayerType::Vector, LayerType::Text, LayerType::Image]
+        );
+
+        let rect = &layers[0];
+        assert_eq!(rect.bounds, Bounds::new(15.0, 25.0, 20.0, 10.0));
+        assert_eq!(rect.fill_color.as_deref(), Some("#00ff00"));
+        assert_eq!(rect.stroke_color.as_deref(), Some("#0000ff"));
+        assert_eq!(rect.stroke_width, Some(2.0));
+
+        // Rotated circle stays centered at (60, 70)
+        let b = layers[1].bounds;
+        assert!((b.x + b.width / 2.0 - 60.0).abs() < 0.01 && (b.y + b.height / 2.0 - 70.0).abs() < 0.01);
+
+        let path = layers[2].path_data.as_ref().unwrap();
+        assert_eq!(layers[2].fill_color, None);
+        assert_eq!(path.commands.last(), Some(&PathCommand::ClosePath));
+        assert!(matches!(path.commands[3], PathCommand::CurveTo { x: 10.0, y: 30.0, .. }));
+
+        let text = &layers[3];
+        assert_eq!(text.content.as_deref(), Some("Hello \u{2019}world\u{2019}\nagain"));
+        assert_eq!(text.font_family.as_deref(), Some("Open Sans"));
+        assert_eq!(text.font_size, Some(16.0));
+        assert_eq!(text.text_align, Some(TextAlign::Center));
+        assert_eq!(text.bounds.y, 134.0);
+
+        assert_eq!(layers[4].image_url.as_deref(), Some("data:image/png;base64,AAAA"));
+        assert!(svg_to_layers("plain text", "p").is_err());
+    }
+
+    #[test]
+    fn test_parse_path_data() {
+        let commands = parse_path_data("M10-5L1.5.5 1e1 2c0 0 1 1 2 2s3 3 4 4");
+        assert_eq!(commands[0], PathCommand::MoveTo { x: 10.0, y: -5.0 });
+        assert_eq!(commands[1], PathCommand::LineTo { x: 1.5, y: 0.5 });
+        assert_eq!(commands[2], PathCommand::LineTo { x: 10.0, y: 2.0 });
+        // Smooth curve reflects the previous control point
+        assert_eq!(
+            commands[4],
+            PathCommand::CurveTo { x1: 13.0, y1: 5.0, x2: 15.0, y2: 7.0, x: 16.0, y: 8.0 }
+        );
+
+        // Half circle arc ends exactly at its end point
+        let arc = parse_path_data("M0 0 A10 10 0 0 1 20 0");
+        assert_eq!(arc.len(), 3);
+        assert!(matches!(arc[2], PathCommand::CurveTo { x: 20.0, y: 0.0, .. }));
+        // Errors keep what parsed before them
+        assert_eq!(parse_path_data("M0 0 L5 5 L").len(), 2);
+    }
+
+    #[test]
+    fn test_text_to_layer_and_offset() {
+        let mut layers = vec![text_to_layer("Line one\r\nLine two\r\n", "t".to_string(), 10.0, 20.0).unwrap()];
+        assert_eq!(layers[0].content.as_deref(), Some("Line one\nLine two"));
+        assert_eq!(layers[0].bounds.height, 2.0 * 12.0 * 1.2);
+        assert!(text_to_layer(" \n ", "t".to_string(), 0.0, 0.0).is_none());
+
+        offset_layers(&mut layers, 5.0, -5.0);
+        assert_eq!((layers[0].bounds.x, layers[0].bounds.y), (15.0, 15.0));
+    }
+}
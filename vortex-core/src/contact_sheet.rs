@@ -0,0 +1,278 @@
+//! Contact sheets
+//!
+//! Proof sheets for review printing: page thumbnails laid out N-up in a
+//! grid on letter or A4 sheets, each captioned with its page label and,
+//! optionally, the text of its annotation layers. This is the layout only;
+//! the caller renders the thumbnails and passes their image URLs.
+
+use crate::clipboard::new_layer;
+use crate::models::{Bounds, LayerObject, LayerRole, LayerType, PageData, ShapeType, TextAlign};
+use crate::page_setup::PageSizePreset;
+use serde::{Deserialize, Serialize};
+
+/// Height of one caption line, in points
+const CAPTION_LINE: f32 = 11.0;
+/// Annotation text beyond this many characters is cut off
+const MAX_NOTE_CHARS: usize = 120;
+
+/// Grid and captions of a contact sheet
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct ContactSheetOptions {
+    #[serde(default = "default_paper")]
+    pub paper: PageSizePreset,
+    #[serde(default)]
+    pub landscape: bool,
+    #[serde(default = "default_columns")]
+    pub columns: u32,
+    #[serde(default = "default_rows")]
+    pub rows: u32,
+    /// Sheet margin and space between thumbnails, in points
+    #[serde(default = "default_margin")]
+    pub margin: f32,
+    #[serde(default = "default_gap")]
+    pub gap: f32,
+    #[serde(default = "default_true")]
+    pub page_numbers: bool,
+    /// Print the text of each page's annotation layers under its thumbnail
+    #[serde(default)]
+    pub annotations: bool,
+}
+
+fn default_paper() -> PageSizePreset {
+    PageSizePreset::Letter
+}
+
+fn default_columns() -> u32 {
+    3
+}
+
+fn default_rows() -> u32 {
+    4
+}
+
+fn default_margin() -> f32 {
+    36.0
+}
+
+fn default_gap() -> f32 {
+    18.0
+}
+
+fn default_true() -> bool {
+    true
+}
+
+impl Default for ContactSheetOptions {
+    fn default() -> Self {
+        Self {
+            paper: default_paper(),
+            landscape: false,
+            columns: default_columns(),
+            rows: default_rows(),
+            margin: default_margin(),
+            gap: default_gap(),
+            page_numbers: true,
+            annotations: false,
+        }
+    }
+}
+
+impl ContactSheetOptions {
+    /// Sheet width and height in points
+    pub fn sheet_size(&self) -> (f32, f32) {
+        let (w, h) = self.paper.size();
+        if self.landscape {
+            (w.max(h), w.min(h))
+        } else {
+            (w.min(h), w.max(h))
+        }
+    }
+
+    #[inline]
+    fn caption_lines(&self) -> u32 {
+        u32::from(self.page_numbers) + 2 * u32::from(self.annotations)
+    }
+}
+
+/// Where one page goes on the contact sheets
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct ContactSheetCell {
+    /// Position of the page in the pages laid out
+    pub page: usize,
+    pub sheet: usize,
+    /// The thumbnail, fitted to its cell, in points from the sheet's top-left
+    pub thumbnail: Bounds,
+    pub caption: Bounds,
+}
+
+/// Place pages of the given sizes in the grid, filling each sheet row by row
+pub fn contact_sheet_layout(
+    page_sizes: &[(f32, f32)],
+    options: &ContactSheetOptions,
+) -> Result<Vec<ContactSheetCell>, String> {
+    let (columns, rows) = (options.columns, options.rows);
+    if columns == 0 || rows == 0 {
+        return Err("A contact sheet needs at least one row and column".to_string());
+    }
+    let (width, height) = options.sheet_size();
+    let cell_width = (width - 2.0 * options.margin - (columns - 1) as f32 * options.gap) / columns as f32;
+    let cell_height = (height - 2.0 * options.margin - (rows - 1) as f32 * options.gap) / rows as f32;
+    let caption_height = options.caption_lines() as f32 * CAPTION_LINE;
+    let image_height = cell_height - caption_height;
+    if cell_width <= 0.0 || image_height <= 0.0 {
+        return Err(format!("The sheet is too small for {} x {} thumbnails", columns, rows));
+    }
+
+    let per_sheet = (columns * rows) as usize;
+    Ok(page_sizes
+        .iter()
+        .enumerate()
+        .map(|(page, &(page_width, page_height))| {
+            let slot = page % per_sheet;
+            let x = options.margin + (slot % columns as usize) as f32 * (cell_width + options.gap);
+            let y = options.margin + (slot / columns as usize) as f32 * (cell_height + options.gap);
+            let scale = (cell_width / page_width.max(1.0)).min(image_height / page_height.max(1.0));
+            let (w, h) = (page_width * scale, page_height * scale);
+            ContactSheetCell {
+                page,
+                sheet: page / per_sheet,
+                thumbnail: Bounds::new(x + (cell_width - w) / 2.0, y + (image_height - h), w, h),
+                caption: Bounds::new(x, y + image_height, cell_width, caption_height),
+            }
+        })
+        .collect())
+}
+
+/// Text of a page's annotation layers, shortened for a caption
+fn annotation_note(page: &PageData) -> Option<String> {
+    let notes: Vec<&str> = page
+        .layers
+        .iter()
+        .filter(|l| l.role == LayerRole::Annotation && l.visible)
+        .filter_map(|l| l.content.as_deref().map(str::trim))
+        .filter(|text| !text.is_empty())
+        .collect();
+    if notes.is_empty() {
+        return None;
+    }
+    let note = notes.join(" · ").split_whitespace().collect::<Vec<_>>().join(" ");
+    Some(match note.char_indices().nth(MAX_NOTE_CHARS) {
+        Some((cut, _)) => format!("{}…", &note[..cut]),
+        None => note,
+    })
+}
+
+fn caption_layer(id: String, bounds: Bounds, text: String, size: f32, color: &str) -> LayerObject {
+    let mut layer = new_layer(id, LayerType::Text, bounds);
+    layer.content = Some(text);
+    (layer.font_family, layer.font_size) = (Some("Helvetica".to_string()), Some(size));
+    layer.color = Some(color.to_string());
+    layer.text_align = Some(TextAlign::Center);
+    layer
+}
+
+/// Contact sheet pages for `pages`, showing `thumbnails[i]` (an image URL)
+/// for `pages[i]`
+///
+/// Captions show the page label, or the page number when there is none.
+pub fn contact_sheet_pages(
+    pages: &[PageData],
+    thumbnails: &[String],
+    options: &ContactSheetOptions,
+) -> Result<Vec<PageData>, String> {
+    if thumbnails.len() != pages.len() {
+        return Err(format!("Got {} thumbnails for {} pages", thumbnails.len(), pages.len()));
+    }
+    let sizes: Vec<(f32, f32)> = pages.iter().map(|p| (p.width, p.height)).collect();
+    let cells = contact_sheet_layout(&sizes, options)?;
+    let (width, height) = options.sheet_size();
+    let mut sheets: Vec<PageData> = Vec::new();
+
+    for cell in cells {
+        if cell.sheet == sheets.len() {
+            sheets.push(PageData {
+                page_index: cell.sheet,
+                width,
+                height,
+                dpi: None,
+                layers: Vec::new(),
+                metadata: None,
+                background: None,
+            });
+        }
+        let page = &pages[cell.page];
+        let layers = &mut sheets[cell.sheet].layers;
+        let id = |kind: &str| format!("contact-{}-{}", kind, cell.page);
+
+        let mut thumbnail = new_layer(id("page"), LayerType::Image, cell.thumbnail);
+        thumbnail.image_url = Some(thumbnails[cell.page].clone());
+        let mut frame = new_layer(id("frame"), LayerType::Shape, cell.thumbnail);
+        frame.shape_type = Some(ShapeType::Rectangle);
+        (frame.stroke_color, frame.stroke_width) = (Some("#999999".to_string()), Some(0.5));
+        layers.extend([thumbnail, frame]);
+
+        let caption = cell.caption;
+        let mut line = caption.y;
+        let caption_bounds = |y: f32, lines: f32| Bounds::new(caption.x, y, caption.width, CAPTION_LINE * lines);
+        if options.page_numbers {
+            let label = page.metadata.as_ref().and_then(|m| m.page_label.as_ref()).map(|l| l.text());
+            let label = label.filter(|l| !l.is_empty()).unwrap_or_else(|| (page.page_index + 1).to_string());
+            layers.push(caption_layer(id("label"), caption_bounds(line, 1.0), label, 8.0, "#000000"));
+            line += CAPTION_LINE;
+        }
+        if let Some(note) = annotation_note(page).filter(|_| options.annotations) {
+            layers.push(caption_layer(id("note"), caption_bounds(line, 2.0), note, 6.5, "#555555"));
+        }
+    }
+    for sheet in &mut sheets {
+        for (z, layer) in sheet.layers.iter_mut().enumerate() {
+            layer.z_index = z as i32;
+        }
+    }
+    Ok(sheets)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_contact_sheet() {
+        let options = ContactSheetOptions { columns: 2, rows: 2, annotations: true, ..Default::default() };
+        let cells = contact_sheet_layout(&[(612.0, 792.0); 5], &options).unwrap();
+        assert_eq!(cells.iter().map(|c| c.sheet).collect::<Vec<_>>(), [0, 0, 0, 0, 1]);
+        // (612 - 72 - 18) / 2 wide, (792 - 72 - 18) / 2 - 33 tall for the thumbnail
+        let (cell_width, image_height) = (261.0, 318.0);
+        let t = cells[1].thumbnail;
+        assert!((t.height - image_height).abs() < 1e-3 && t.width < cell_width);
+        assert!((t.x + t.width / 2.0 - (36.0 + 279.0 + cell_width / 2.0)).abs() < 1e-3);
+        assert_eq!(cells[2].caption.y, 36.0 + 369.0 + image_height);
+        assert_eq!(cells[4].thumbnail, cells[0].thumbnail);
+        assert!(contact_sheet_layout(&[(612.0, 792.0)], &ContactSheetOptions { rows: 0, ..options }).is_err());
+
+        let mut note = new_layer("n".to_string(), LayerType::Text, Bounds::new(0.0, 0.0, 10.0, 10.0));
+        (note.role, note.content) = (LayerRole::Annotation, Some("Fix the\nkerning".to_string()));
+        let pages: Vec<PageData> = (0..5)
+            .map(|i| PageData {
+                page_index: i,
+                width: 612.0,
+                height: 792.0,
+                dpi: None,
+                layers: if i == 1 { vec![note.clone()] } else { Vec::new() },
+                metadata: None,
+                background: None,
+            })
+            .collect();
+        let thumbnails: Vec<String> = (0..5).map(|i| format!("image://thumb-{}", i)).collect();
+        let sheets = contact_sheet_pages(&pages, &thumbnails, &options).unwrap();
+        assert_eq!(sheets.len(), 2);
+        let text = |layer: &LayerObject| layer.content.clone().unwrap_or_default();
+        let captions: Vec<String> =
+            sheets[0].layers.iter().filter(|l| l.layer_type == LayerType::Text).map(text).collect();
+        assert_eq!(captions, ["1", "2", "Fix the kerning", "3", "4"]);
+        assert_eq!(sheets[1].layers[0].image_url.as_deref(), Some("image://thumb-4"));
+        assert!(contact_sheet_pages(&pages, &thumbnails[..2], &options).is_err());
+    }
+}
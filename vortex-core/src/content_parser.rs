@@ -9,13 +9,16 @@
 #![allow(non_snake_case)]
 
 use crate::graphics_state::{cmyk_to_rgb, normalize_font_name, rgba_to_hex, GraphicsState};
+use crate::layer_ids::LayerIds;
 use crate::models::{
-    Bounds, LayerObject, LayerRole, LayerType, PathCommand, PathData, SourceType, TextAlign,
-    TransformMatrix,
+    BlendMode, Bounds, LayerObject, LayerRole, LayerType, PathCommand, PathData, SourceType,
+    TextAlign, TransformMatrix,
 };
 use crate::path_ops::{transform_path, ExtractedPath};
 use crate::text_ops::{create_text, ExtractedText};
-use lopdf::{content::Content, Document, Object, ObjectId};
+use crate::text_structure::TextSpan;
+use lopdf::{content::Content, Dictionary, Document, Object, ObjectId};
+use std::collections::HashMap;
 
 /// Initial capacity for path commands (most paths have < 32 commands)
 const PATH_CAPACITY: usize = 32;
@@ -30,6 +33,30 @@ pub fn parse_page_content(
     page_id: ObjectId,
     page_height: f32,
 ) -> Result<(Vec<ExtractedText>, Vec<ExtractedPath>), String> {
+    parse_page(doc, page_id, page_height).map(|page| (page.texts, page.paths))
+}
+
+/// An image XObject painted on a page with `Do`
+#[derive(Debug, Clone, PartialEq)]
+pub struct PlacedImage {
+    pub id: ObjectId,
+    /// Bounding box of the image's unit square under the CTM (top-left origin)
+    pub bounds: Bounds,
+    /// Constant fill alpha and uniform soft mask in effect
+    pub opacity: f32,
+    pub blend_mode: BlendMode,
+}
+
+/// Everything extracted from one page's content stream
+#[derive(Debug, Default)]
+pub struct ParsedPage {
+    pub texts: Vec<ExtractedText>,
+    pub paths: Vec<ExtractedPath>,
+    pub images: Vec<PlacedImage>,
+}
+
+/// Parse content stream, including where image XObjects are drawn
+pub fn parse_page(doc: &Document, page_id: ObjectId, page_height: f32) -> Result<ParsedPage, String> {
     let content_data = doc
         .get_page_content(page_id)
         .map_err(|e| format!("Failed to get page content: {}", e))?;
@@ -38,12 +65,134 @@ pub fn parse_page_content(
         .map_err(|e| format!("Failed to decode content: {}", e))?;
 
     let mut ctx = ParseContext::new(page_height);
+    ctx.ext_g_states = page_ext_g_states(doc, page_id);
+    ctx.image_xobjects = page_image_xobjects(doc, page_id);
 
     for op in &content.operations {
         ctx.process_operator(&op.operator, &op.operands);
     }
 
-    Ok((ctx.texts, ctx.paths))
+    Ok(ParsedPage { texts: ctx.texts, paths: ctx.paths, images: ctx.images })
+}
+
+/// Transparency settings of a named ExtGState resource
+#[derive(Debug, Clone, Copy, Default)]
+struct ExtGState {
+    fill_alpha: Option<f32>,
+    stroke_alpha: Option<f32>,
+    blend_mode: Option<BlendMode>,
+    /// `Some(1.0)` for `/SMask /None`; the mask's alpha when it is uniform
+    soft_mask: Option<f32>,
+}
+
+/// Resolve a possibly indirect object
+#[inline]
+fn resolve<'a>(doc: &'a Document, object: &'a Object) -> &'a Object {
+    match object {
+        Object::Reference(id) => doc.get_object(*id).unwrap_or(object),
+        _ => object,
+    }
+}
+
+/// Alpha of a luminosity soft mask whose group paints one flat gray, the
+/// common "opacity mask" case; gradients and images would need rendering
+fn uniform_soft_mask(doc: &Document, mask: &Dictionary) -> Option<f32> {
+    if mask.get(b"S").and_then(Object::as_name).ok()? != b"Luminosity" {
+        return None;
+    }
+    let group = resolve(doc, mask.get(b"G").ok()?).as_stream().ok()?;
+    let content = Content::decode(&group.decompressed_content().ok()?).ok()?;
+
+    let mut value: Option<f32> = None;
+    for op in &content.operations {
+        let ops = op.operands.as_slice();
+        let luminance = match op.operator.as_str() {
+            "Do" | "sh" | "BI" | "gs" => return None,
+            "g" | "sc" | "scn" if ops.len() == 1 => get_float(ops, 0),
+            "rg" | "sc" | "scn" if ops.len() == 3 => {
+                0.3 * get_float(ops, 0) + 0.59 * get_float(ops, 1) + 0.11 * get_float(ops, 2)
+            }
+            "k" if ops.len() == 4 => {
+                let (r, g, b) = cmyk_to_rgb(get_float(ops, 0), get_float(ops, 1), get_float(ops, 2), get_float(ops, 3));
+                0.3 * r + 0.59 * g + 0.11 * b
+            }
+            _ => continue,
+        };
+        match value {
+            Some(v) if (v - luminance).abs() > 0.001 => return None,
+            _ => value = Some(luminance),
+        }
+    }
+    value.map(|v| v.clamp(0.0, 1.0))
+}
+
+fn read_ext_g_state(doc: &Document, dict: &Dictionary) -> ExtGState {
+    let number = |key: &[u8]| dict.get(key).ok().and_then(|o| get_float_opt(std::slice::from_ref(resolve(doc, o)), 0));
+    let blend_mode = dict.get(b"BM").ok().map(|o| resolve(doc, o)).and_then(|bm| match bm {
+        Object::Name(name) => BlendMode::from_pdf_name(name),
+        // An array lists preferences; use the first one we know
+        Object::Array(names) => names.iter().filter_map(|n| n.as_name().ok()).find_map(BlendMode::from_pdf_name),
+        _ => None,
+    });
+    let soft_mask = dict.get(b"SMask").ok().map(|o| resolve(doc, o)).and_then(|mask| match mask {
+        Object::Name(name) if name == b"None" => Some(1.0),
+        Object::Dictionary(mask) => uniform_soft_mask(doc, mask),
+        _ => None,
+    });
+    ExtGState {
+        fill_alpha: number(b"ca").map(|a| a.clamp(0.0, 1.0)),
+        stroke_alpha: number(b"CA").map(|a| a.clamp(0.0, 1.0)),
+        blend_mode,
+        soft_mask,
+    }
+}
+
+/// Named graphics states available to a page, including inherited resources
+fn page_ext_g_states(doc: &Document, page_id: ObjectId) -> HashMap<Vec<u8>, ExtGState> {
+    let mut states = HashMap::new();
+    let Ok((resource_dict, resource_ids)) = doc.get_page_resources(page_id) else {
+        return states;
+    };
+    let inherited = resource_ids.into_iter().filter_map(|id| doc.get_dictionary(id).ok());
+    for resources in resource_dict.into_iter().chain(inherited) {
+        let Ok(Object::Dictionary(entries)) = resources.get(b"ExtGState").map(|o| resolve(doc, o)) else {
+            continue;
+        };
+        for (name, value) in entries.iter() {
+            if let Object::Dictionary(dict) = resolve(doc, value) {
+                states.entry(name.clone()).or_insert_with(|| read_ext_g_state(doc, dict));
+            }
+        }
+    }
+    states
+}
+
+/// Image XObjects available to a page by resource name, including inherited
+/// resources; form XObjects are left out
+fn page_image_xobjects(doc: &Document, page_id: ObjectId) -> HashMap<Vec<u8>, ObjectId> {
+    let mut images = HashMap::new();
+    let Ok((resource_dict, resource_ids)) = doc.get_page_resources(page_id) else {
+        return images;
+    };
+    let inherited = resource_ids.into_iter().filter_map(|id| doc.get_dictionary(id).ok());
+    for resources in resource_dict.into_iter().chain(inherited) {
+        let Ok(Object::Dictionary(entries)) = resources.get(b"XObject").map(|o| resolve(doc, o)) else {
+            continue;
+        };
+        for (name, value) in entries.iter() {
+            let Ok(id) = value.as_reference() else {
+                continue;
+            };
+            let is_image = doc
+                .get_object(id)
+                .and_then(Object::as_stream)
+                .is_ok_and(|s| s.dict.get(b"Subtype").and_then(Object::as_name).is_ok_and(|t| t == b"Image"));
+            if is_image {
+                images.entry(name.clone()).or_insert(id);
+            }
+        }
+    }
+    images
 }
 
 /// Parsing context holding state and results
@@ -55,6 +204,9 @@ struct ParseContext {
     path_start: (f32, f32),
     current_point: (f32, f32),
     page_height: f32,
+    ext_g_states: HashMap<Vec<u8>, ExtGState>,
+    images: Vec<PlacedImage>,
+    image_xobjects: HashMap<Vec<u8>, ObjectId>,
 }
 
 impl ParseContext {
@@ -71,6 +223,9 @@ impl ParseContext {
             path_start: (0.0, 0.0),
             current_point: (0.0, 0.0),
             page_height,
+            ext_g_states: HashMap::new(),
+            images: Vec::new(),
+            image_xobjects: HashMap::new(),
         }
     }
 
@@ -90,9 +245,13 @@ impl ParseContext {
         match op {
             // Graphics state
             "q" => self.state_stack.push(self.state().clone()),
-            "Q" => { if self.state_stack.len() > 1 { self.state_stack.pop(); } }
+            "Q" if self.state_stack.len() > 1 => {
+                self.state_stack.pop();
+            }
             "cm" => self.op_cm(operands),
             "w" => self.op_w(operands),
+            "gs" => self.op_gs(operands),
+            "Do" => self.op_Do(operands),
 
             // Path construction
             "m" => self.op_m(operands),
@@ -152,12 +311,48 @@ impl ParseContext {
         }
     }
 
+    /// Record an image XObject; it fills the unit square mapped by the CTM
+    fn op_Do(&mut self, ops: &[Object]) {
+        let Some(&id) = ops.first().and_then(|o| o.as_name().ok()).and_then(|n| self.image_xobjects.get(n)) else {
+            return;
+        };
+        let state = self.state();
+        let corners = [(0.0, 0.0), (1.0, 0.0), (0.0, 1.0), (1.0, 1.0)].map(|(x, y)| state.ctm.transform_point(x, y));
+        let (min_x, max_x) = corners.iter().fold((f32::MAX, f32::MIN), |(lo, hi), p| (lo.min(p.0), hi.max(p.0)));
+        let (min_y, max_y) = corners.iter().fold((f32::MAX, f32::MIN), |(lo, hi), p| (lo.min(p.1), hi.max(p.1)));
+        self.images.push(PlacedImage {
+            id,
+            bounds: Bounds::new(min_x, self.page_height - max_y, max_x - min_x, max_y - min_y),
+            opacity: state.fill_alpha * state.soft_mask,
+            blend_mode: state.blend_mode,
+        });
+    }
+
     fn op_w(&mut self, ops: &[Object]) {
         if let Some(w) = get_float_opt(ops, 0) {
             self.state_mut().line_width = w;
         }
     }
 
+    fn op_gs(&mut self, ops: &[Object]) {
+        let Some(gs) = ops.first().and_then(|o| o.as_name().ok()).and_then(|n| self.ext_g_states.get(n)).copied() else {
+            return;
+        };
+        let state = self.state_mut();
+        if let Some(alpha) = gs.fill_alpha {
+            state.fill_alpha = alpha;
+        }
+        if let Some(alpha) = gs.stroke_alpha {
+            state.stroke_alpha = alpha;
+        }
+        if let Some(mode) = gs.blend_mode {
+            state.blend_mode = mode;
+        }
+        if let Some(mask) = gs.soft_mask {
+            state.soft_mask = mask;
+        }
+    }
+
     // Path construction
     fn op_m(&mut self, ops: &[Object]) {
         if let (Some(x), Some(y)) = (get_float_opt(ops, 0), get_float_opt(ops, 1)) {
@@ -231,35 +426,22 @@ impl ParseContext {
     // Path painting
     fn paint_stroke(&mut self, close: bool) {
         if close { self.current_path.push(PathCommand::ClosePath); }
-        if !self.current_path.is_empty() {
-            let state = self.state();
-            self.paths.push(transform_path(
-                &self.current_path, Some(state.stroke_color), None,
-                state.line_width, &state.ctm, self.page_height,
-            ));
-            self.current_path.clear();
-        }
+        self.push_path(true, false);
     }
 
     fn paint_fill(&mut self) {
-        if !self.current_path.is_empty() {
-            let state = self.state();
-            self.paths.push(transform_path(
-                &self.current_path, None, Some(state.fill_color),
-                state.line_width, &state.ctm, self.page_height,
-            ));
-            self.current_path.clear();
-        }
+        self.push_path(false, true);
     }
 
     fn paint_both(&mut self, close: bool) {
         if close { self.current_path.push(PathCommand::ClosePath); }
+        self.push_path(true, true);
+    }
+
+    fn push_path(&mut self, stroke: bool, fill: bool) {
         if !self.current_path.is_empty() {
-            let state = self.state();
-            self.paths.push(transform_path(
-                &self.current_path, Some(state.stroke_color), Some(state.fill_color),
-                state.line_width, &state.ctm, self.page_height,
-            ));
+            let path = transform_path(&self.current_path, stroke, fill, self.state(), self.page_height);
+            self.paths.push(path);
             self.current_path.clear();
         }
     }
@@ -324,7 +506,7 @@ impl ParseContext {
         if ops.len() >= 6 {
             let m = parse_matrix(ops);
             let state = self.state_mut();
-            state.text_matrix = m.clone();
+            state.text_matrix = m;
             state.line_matrix = m;
         }
     }
@@ -334,7 +516,7 @@ impl ParseContext {
             let translate = TransformMatrix::translate(tx, ty);
             let state = self.state_mut();
             state.line_matrix = state.line_matrix.multiply(&translate);
-            state.text_matrix = state.line_matrix.clone();
+            state.text_matrix = state.line_matrix;
         }
     }
 
@@ -344,7 +526,7 @@ impl ParseContext {
             state.leading = -ty;
             let translate = TransformMatrix::translate(tx, ty);
             state.line_matrix = state.line_matrix.multiply(&translate);
-            state.text_matrix = state.line_matrix.clone();
+            state.text_matrix = state.line_matrix;
         }
     }
 
@@ -353,7 +535,7 @@ impl ParseContext {
         let translate = TransformMatrix::translate(0.0, -leading);
         let state = self.state_mut();
         state.line_matrix = state.line_matrix.multiply(&translate);
-        state.text_matrix = state.line_matrix.clone();
+        state.text_matrix = state.line_matrix;
     }
 
     // Text showing
@@ -389,7 +571,7 @@ impl ParseContext {
                     }
                     Object::Integer(_) | Object::Real(_) => {
                         // Positioning adjustment - large negative values often indicate space
-                        if let Some(adj) = get_float_opt(&[item.clone()], 0) {
+                        if let Some(adj) = get_float_opt(std::slice::from_ref(item), 0) {
                             if adj < -100.0 {
                                 combined.push(' ');
                             }
@@ -491,6 +673,23 @@ fn parse_matrix(ops: &[Object]) -> TransformMatrix {
     }
 }
 
+/// Convert extracted text to spans for `text_structure`
+pub fn to_text_spans(texts: Vec<ExtractedText>) -> Vec<TextSpan> {
+    texts
+        .into_iter()
+        .map(|t| {
+            let color = rgba_to_hex(&t.color);
+            TextSpan::new(t.text, Bounds::new(t.x, t.y, t.width, t.height), t.font_name, t.font_size, color)
+        })
+        .collect()
+}
+
+/// Layers store `Normal` as no blend mode
+#[inline]
+fn blend_mode(mode: BlendMode) -> Option<BlendMode> {
+    (mode != BlendMode::Normal).then_some(mode)
+}
+
 /// Convert extracted elements to LayerObjects
 pub fn to_layer_objects(
     texts: Vec<ExtractedText>,
@@ -498,17 +697,21 @@ pub fn to_layer_objects(
     page_index: usize,
 ) -> Vec<LayerObject> {
     let mut layers = Vec::new();
+    let mut ids = LayerIds::new(page_index);
     let mut z = 0;
 
-    for (i, path) in paths.into_iter().enumerate() {
+    for path in paths {
+        let alpha = path.fill_color.or(path.stroke_color).map_or(1.0, |c| c[3]);
+        let key = serde_json::to_string(&path.commands).unwrap_or_default();
         layers.push(LayerObject {
-            id: format!("vector-{}-{}", page_index, i),
+            id: ids.next("vector", &key, Some(&path.bounds)),
             layer_type: LayerType::Vector,
             bounds: path.bounds,
             visible: true,
             locked: false,
             z_index: z,
-            opacity: 1.0,
+            opacity: alpha,
+            blend_mode: blend_mode(path.blend_mode),
             content: None,
             font_family: None,
             font_size: None,
@@ -521,31 +724,43 @@ pub fn to_layer_objects(
             line_height: None,
             letter_spacing: None,
             background_color: None,
+            text_path: None,
+            text_outline: None,
+            text_shadow: None,
+            drop_cap: None,
+            notes: Vec::new(),
+            pagination: None,
             image_url: None,
             image_path: None,
             image_data: None,
+            image_link: None,
             shape_type: None,
             stroke_color: path.stroke_color.map(|c| rgba_to_hex(&c)),
             stroke_width: Some(path.line_width),
             fill_color: path.fill_color.map(|c| rgba_to_hex(&c)),
+            text_wrap: None,
             source_type: SourceType::Extracted,
             role: LayerRole::Content,
             path_data: Some(PathData { commands: path.commands, fill_rule: None }),
             transform: Some(path.transform),
+            ocr: None,
+            tags: Vec::new(),
         });
         z += 1;
     }
 
-    for (i, text) in texts.into_iter().enumerate() {
+    for text in texts {
         let is_italic = text.font_name.to_lowercase().contains("italic");
+        let bounds = Bounds::new(text.x, text.y, text.width, text.height);
         layers.push(LayerObject {
-            id: format!("text-{}-{}", page_index, i),
+            id: ids.next("text", &text.text, Some(&bounds)),
             layer_type: LayerType::Text,
-            bounds: Bounds::new(text.x, text.y, text.width, text.height),
+            bounds,
             visible: true,
             locked: false,
             z_index: z,
-            opacity: 1.0,
+            opacity: text.color[3],
+            blend_mode: blend_mode(text.blend_mode),
             content: Some(text.text),
             font_family: Some(normalize_font_name(&text.font_name)),
             font_size: Some(text.font_size),
@@ -558,20 +773,61 @@ pub fn to_layer_objects(
             line_height: None,
             letter_spacing: None,
             background_color: None,
+            text_path: None,
+            text_outline: None,
+            text_shadow: None,
+            drop_cap: None,
+            notes: Vec::new(),
+            pagination: None,
             image_url: None,
             image_path: None,
             image_data: None,
+            image_link: None,
             shape_type: None,
             stroke_color: None,
             stroke_width: None,
             fill_color: None,
+            text_wrap: None,
             source_type: SourceType::Extracted,
             role: LayerRole::Content,
             path_data: None,
             transform: Some(text.transform),
+            ocr: None,
+            tags: Vec::new(),
         });
         z += 1;
     }
 
     layers
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use lopdf::{dictionary, Stream};
+
+    #[test]
+    fn test_parse_page_places_images() {
+        let mut doc = Document::with_version("1.5");
+        let image = doc.add_object(Stream::new(
+            dictionary! { "Type" => "XObject", "Subtype" => "Image", "Width" => 2, "Height" => 2 },
+            vec![0; 12],
+        ));
+        let form = doc.add_object(Stream::new(dictionary! { "Type" => "XObject", "Subtype" => "Form" }, Vec::new()));
+        let content = doc.add_object(Stream::new(
+            Dictionary::new(),
+            b"q 200 0 0 100 50 600 cm /Im1 Do Q /Fm1 Do".to_vec(),
+        ));
+        let page = doc.add_object(dictionary! {
+            "Type" => "Page",
+            "Contents" => content,
+            "Resources" => dictionary! { "XObject" => dictionary! { "Im1" => image, "Fm1" => form } },
+        });
+
+        let parsed = parse_page(&doc, page, 792.0).unwrap();
+        assert_eq!(parsed.images.len(), 1);
+        assert_eq!(parsed.images[0].id, image);
+        assert_eq!(parsed.images[0].bounds, Bounds::new(50.0, 92.0, 200.0, 100.0));
+        assert_eq!(parsed.images[0].opacity, 1.0);
+    }
+}
@@ -0,0 +1,173 @@
+//! Book cover templates
+//!
+//! A wraparound cover is one sheet: back cover, spine and front cover side
+//! by side, with bleed all around. The spine is as wide as the book block is
+//! thick, so it follows from the page count and the paper's caliper.
+//!
+//! The template page includes the bleed; its metadata records the trim and
+//! bleed boxes (PDF user space) and guides at the bleed, safety and fold
+//! lines (points from the top-left).
+
+use crate::models::{Bounds, PageData, PageGuides, PageMetadata};
+use crate::units::mm_to_pt;
+use serde::{Deserialize, Serialize};
+
+/// Caliper of common 80 gsm uncoated text stock
+pub const DEFAULT_PAPER_THICKNESS_MM: f32 = 0.1;
+/// 0.125 in
+pub const DEFAULT_COVER_BLEED: f32 = 9.0;
+/// 0.25 in kept clear of the trim and folds
+pub const DEFAULT_COVER_SAFETY: f32 = 18.0;
+
+/// What a cover is computed from; lengths in points unless named `_mm`
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct CoverOptions {
+    pub page_count: u32,
+    #[serde(default = "default_paper_thickness")]
+    pub paper_thickness_mm: f32,
+    /// Trim size of one cover panel, i.e. of the interior pages
+    pub trim_width: f32,
+    pub trim_height: f32,
+    #[serde(default = "default_bleed")]
+    pub bleed: f32,
+    #[serde(default = "default_safety")]
+    pub safety: f32,
+    /// Added to the spine for the cover stock wrapping the block
+    #[serde(default)]
+    pub cover_thickness_mm: f32,
+}
+
+fn default_paper_thickness() -> f32 {
+    DEFAULT_PAPER_THICKNESS_MM
+}
+
+fn default_bleed() -> f32 {
+    DEFAULT_COVER_BLEED
+}
+
+fn default_safety() -> f32 {
+    DEFAULT_COVER_SAFETY
+}
+
+/// Panels of a cover template, in points from its top-left corner
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct CoverLayout {
+    /// Full sheet, bleed included
+    pub width: f32,
+    pub height: f32,
+    pub bleed: f32,
+    pub spine_width: f32,
+    pub spine_width_mm: f32,
+    /// Trimmed panels
+    pub back: Bounds,
+    pub spine: Bounds,
+    pub front: Bounds,
+}
+
+/// Thickness of a book block: one sheet of paper per two pages
+pub fn spine_width_mm(page_count: u32, paper_thickness_mm: f32) -> f32 {
+    page_count.div_ceil(2) as f32 * paper_thickness_mm
+}
+
+/// Panel geometry for `options`
+pub fn cover_layout(options: &CoverOptions) -> Result<CoverLayout, String> {
+    if options.page_count == 0 {
+        return Err("Page count must be at least 1".to_string());
+    }
+    if options.trim_width <= 0.0 || options.trim_height <= 0.0 {
+        return Err(format!("Invalid trim size: {} x {}", options.trim_width, options.trim_height));
+    }
+    if options.paper_thickness_mm <= 0.0 || options.cover_thickness_mm < 0.0 || options.bleed < 0.0 {
+        return Err("Paper thickness must be positive; cover thickness and bleed not negative".to_string());
+    }
+    let spine_width_mm = spine_width_mm(options.page_count, options.paper_thickness_mm) + options.cover_thickness_mm;
+    let spine_width = mm_to_pt(spine_width_mm);
+    let (w, h, b) = (options.trim_width, options.trim_height, options.bleed);
+    Ok(CoverLayout {
+        width: w * 2.0 + spine_width + b * 2.0,
+        height: h + b * 2.0,
+        bleed: b,
+        spine_width,
+        spine_width_mm,
+        back: Bounds::new(b, b, w, h),
+        spine: Bounds::new(b + w, b, spine_width, h),
+        front: Bounds::new(b + w + spine_width, b, w, h),
+    })
+}
+
+/// Empty cover page for `layout`, with its print boxes and guides
+///
+/// Vertical guides mark the bleed edges, the trim, the two folds and the
+/// safety lines inside each panel; horizontal guides the bleed, trim and
+/// safety lines. Safety lines are left out of a spine too narrow for them.
+pub fn cover_page(layout: &CoverLayout, safety: f32) -> PageData {
+    let (back, spine, front) = (layout.back, layout.spine, layout.front);
+    let mut vertical = vec![
+        back.x,
+        back.x + safety,
+        back.x + back.width - safety,
+        spine.x,
+        spine.x + spine.width,
+        front.x + safety,
+        front.x + front.width - safety,
+        front.x + front.width,
+    ];
+    if spine.width > safety * 2.0 {
+        vertical.extend([spine.x + safety, spine.x + spine.width - safety]);
+    }
+    let mut horizontal = vec![back.y, back.y + safety, back.y + back.height - safety, back.y + back.height];
+    if layout.bleed > 0.0 {
+        vertical.extend([0.0, layout.width]);
+        horizontal.extend([0.0, layout.height]);
+    }
+    vertical.sort_by(f32::total_cmp);
+    horizontal.sort_by(f32::total_cmp);
+
+    let (w, h, b) = (layout.width, layout.height, layout.bleed);
+    PageData {
+        page_index: 0,
+        width: w,
+        height: h,
+        dpi: None,
+        layers: Vec::new(),
+        metadata: Some(PageMetadata {
+            media_box: Some([0.0, 0.0, w, h]),
+            trim_box: Some([b, b, w - b, h - b]),
+            bleed_box: Some([0.0, 0.0, w, h]),
+            guides: Some(PageGuides { vertical, horizontal, baseline_grid: None }),
+            ..Default::default()
+        }),
+        background: None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_cover_layout() {
+        assert_eq!(spine_width_mm(201, 0.1), 10.1);
+
+        let options: CoverOptions =
+            serde_json::from_value(serde_json::json!({ "pageCount": 254, "trimWidth": 432, "trimHeight": 648 })).unwrap();
+        let layout = cover_layout(&options).unwrap();
+        assert!((layout.spine_width_mm - 12.7).abs() < 1e-4);
+        assert!((layout.spine_width - 36.0).abs() < 1e-3);
+        assert!((layout.width - (432.0 * 2.0 + 36.0 + 18.0)).abs() < 1e-3);
+        assert_eq!(layout.height, 666.0);
+        assert_eq!(layout.spine.x, 441.0);
+        assert!((layout.front.x - 477.0).abs() < 1e-3);
+        assert!(cover_layout(&CoverOptions { page_count: 0, ..options }).is_err());
+
+        let page = cover_page(&layout, DEFAULT_COVER_SAFETY);
+        let metadata = page.metadata.unwrap();
+        assert_eq!(metadata.trim_box.map(|b| b[3]), Some(657.0));
+        let guides = metadata.guides.unwrap();
+        // Bleed, trim and safety at each outer edge, both folds; a 36 pt spine is too narrow for safety lines
+        assert_eq!(guides.vertical.len(), 10);
+        assert_eq!(guides.horizontal, [0.0, 9.0, 27.0, 639.0, 657.0, 666.0]);
+    }
+}
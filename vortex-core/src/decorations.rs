@@ -0,0 +1,362 @@
+//! Decorative elements
+//!
+//! Parametric rules, ornaments, frames and badges (the shapes behind page
+//! numbers and drop caps), generated as filled vector layers so they stay
+//! vector through export. Strokes are drawn as filled outlines, so every
+//! element is painted with a single fill color.
+
+use crate::clipboard::{ellipse_path, new_layer, rounded_rect};
+use crate::models::{Bounds, FillRule, LayerObject, LayerType, PathCommand, PathData, SourceType};
+use serde::{Deserialize, Serialize};
+
+/// Color of decorations with none given
+const DEFAULT_COLOR: &str = "#000000";
+
+/// How a rule is drawn
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq, Default)]
+#[serde(rename_all = "camelCase")]
+pub enum RuleStyle {
+    #[default]
+    Solid,
+    /// Two lines `thickness` apart
+    Double,
+    /// A row of round dots `thickness` across
+    Dotted,
+    /// Thickest in the middle, tapering to points at the ends
+    Tapered,
+    /// Broken in the middle by a diamond
+    Diamond,
+}
+
+/// Section-break ornaments
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq, Default)]
+#[serde(rename_all = "camelCase")]
+pub enum OrnamentStyle {
+    /// Three dots in a row
+    #[default]
+    Dinkus,
+    /// Three stars in a triangle (⁂)
+    Asterism,
+    /// Hollow diamond
+    Lozenge,
+    /// Two leaves either side of a dot
+    Flourish,
+}
+
+/// Filled shapes behind page numbers and drop caps
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq, Default)]
+#[serde(rename_all = "camelCase")]
+pub enum BadgeShape {
+    #[default]
+    Circle,
+    Square,
+    /// Corners rounded by a quarter of the shorter side
+    Rounded,
+    /// Fully rounded ends
+    Pill,
+    Diamond,
+}
+
+/// A decorative element; sizes in points
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq)]
+#[serde(tag = "kind", rename_all = "camelCase", rename_all_fields = "camelCase")]
+pub enum Decoration {
+    /// Horizontal rule `length` long
+    Rule {
+        length: f32,
+        thickness: f32,
+        #[serde(default)]
+        style: RuleStyle,
+    },
+    /// Ornament `size` wide
+    Ornament {
+        size: f32,
+        #[serde(default)]
+        style: OrnamentStyle,
+    },
+    /// Border `width` x `height`, its outer corners rounded by `corner_radius`
+    Frame {
+        width: f32,
+        height: f32,
+        thickness: f32,
+        #[serde(default)]
+        corner_radius: f32,
+        /// Two borders `thickness` apart
+        #[serde(default)]
+        double: bool,
+    },
+    /// Shape `width` x `height` behind a page number or drop cap
+    Badge {
+        width: f32,
+        height: f32,
+        #[serde(default)]
+        shape: BadgeShape,
+    },
+}
+
+/// Outline of a decoration drawn at the origin
+struct Outline {
+    commands: Vec<PathCommand>,
+    width: f32,
+    height: f32,
+    even_odd: bool,
+}
+
+fn rect(x: f32, y: f32, width: f32, height: f32) -> Vec<PathCommand> {
+    rounded_rect(Bounds::new(x, y, width, height), 0.0, 0.0)
+}
+
+fn polygon(points: &[(f32, f32)]) -> Vec<PathCommand> {
+    let mut commands: Vec<PathCommand> = points
+        .iter()
+        .enumerate()
+        .map(|(i, &(x, y))| if i == 0 { PathCommand::MoveTo { x, y } } else { PathCommand::LineTo { x, y } })
+        .collect();
+    commands.push(PathCommand::ClosePath);
+    commands
+}
+
+fn diamond(cx: f32, cy: f32, rx: f32, ry: f32) -> Vec<PathCommand> {
+    polygon(&[(cx, cy - ry), (cx + rx, cy), (cx, cy + ry), (cx - rx, cy)])
+}
+
+/// Lens from (`x0`, `cy`) to (`x1`, `cy`), `thickness` across at its middle
+fn lens(x0: f32, x1: f32, cy: f32, thickness: f32) -> Vec<PathCommand> {
+    // Cubic controls at 4/3 of the bulge put the curve's midpoint at the bulge
+    let (third, bulge) = ((x1 - x0) / 3.0, thickness * 2.0 / 3.0);
+    vec![
+        PathCommand::MoveTo { x: x0, y: cy },
+        PathCommand::CurveTo { x1: x0 + third, y1: cy - bulge, x2: x1 - third, y2: cy - bulge, x: x1, y: cy },
+        PathCommand::CurveTo { x1: x1 - third, y1: cy + bulge, x2: x0 + third, y2: cy + bulge, x: x0, y: cy },
+        PathCommand::ClosePath,
+    ]
+}
+
+/// Six-pointed star centered on (`cx`, `cy`)
+fn star(cx: f32, cy: f32, radius: f32) -> Vec<PathCommand> {
+    let points: Vec<(f32, f32)> = (0..12)
+        .map(|i| {
+            let r = if i % 2 == 0 { radius } else { radius * 0.45 };
+            let angle = std::f32::consts::PI * (i as f32 / 6.0 - 0.5);
+            (cx + r * angle.cos(), cy + r * angle.sin())
+        })
+        .collect();
+    polygon(&points)
+}
+
+fn rule(length: f32, t: f32, style: RuleStyle) -> Outline {
+    let (commands, height) = match style {
+        RuleStyle::Solid => (rect(0.0, 0.0, length, t), t),
+        RuleStyle::Double => ([rect(0.0, 0.0, length, t), rect(0.0, 2.0 * t, length, t)].concat(), 3.0 * t),
+        RuleStyle::Dotted => {
+            let r = t / 2.0;
+            let count = (((length - t) / (2.0 * t)).floor() as usize + 1).max(1);
+            let step = if count > 1 { (length - t) / (count - 1) as f32 } else { 0.0 };
+            let x0 = if count > 1 { r } else { length / 2.0 };
+            ((0..count).flat_map(|i| ellipse_path(x0 + i as f32 * step, r, r, r)).collect(), t)
+        }
+        RuleStyle::Tapered => (lens(0.0, length, t / 2.0, t), t),
+        RuleStyle::Diamond => {
+            let d = 3.0 * t;
+            let (mid, gap) = (length / 2.0, d * 1.5);
+            let arm = (mid - gap).max(0.0);
+            let y = (d - t) / 2.0;
+            ([rect(0.0, y, arm, t), diamond(mid, d / 2.0, d / 2.0, d / 2.0), rect(length - arm, y, arm, t)].concat(), d)
+        }
+    };
+    Outline { commands, width: length, height, even_odd: false }
+}
+
+fn ornament(s: f32, style: OrnamentStyle) -> Outline {
+    let (commands, height, even_odd) = match style {
+        OrnamentStyle::Dinkus => {
+            let r = s / 12.0;
+            ([1.0, 3.0, 5.0].iter().flat_map(|k| ellipse_path(s * k / 6.0, r, r, r)).collect(), 2.0 * r, false)
+        }
+        OrnamentStyle::Asterism => {
+            let r = s / 4.0;
+            let height = r * (2.0 + 3f32.sqrt());
+            let stars = [star(s / 2.0, r, r), star(r, height - r, r), star(s - r, height - r, r)].concat();
+            (stars, height, false)
+        }
+        OrnamentStyle::Lozenge => {
+            let h = s / 2.0;
+            ([diamond(h, h, h, h), diamond(h, h, h * 0.45, h * 0.45)].concat(), s, true)
+        }
+        OrnamentStyle::Flourish => {
+            let (height, dot) = (s / 6.0, s / 24.0);
+            let cy = height / 2.0;
+            let commands =
+                [lens(0.0, s * 0.42, cy, height), ellipse_path(s / 2.0, cy, dot, dot), lens(s * 0.58, s, cy, height)].concat();
+            (commands, height, false)
+        }
+    };
+    Outline { commands, width: s, height, even_odd }
+}
+
+fn frame(width: f32, height: f32, t: f32, radius: f32, double: bool) -> Result<Outline, String> {
+    let edges = if double { 4 } else { 2 };
+    if t * edges as f32 * 1.5 > width.min(height) {
+        return Err("Frame is too small for its border thickness".to_string());
+    }
+    // Nested outlines alternate filled and clear under the even-odd rule
+    let commands = (0..edges)
+        .flat_map(|i| {
+            let inset = i as f32 * t;
+            let r = (radius - inset).max(0.0);
+            rounded_rect(Bounds::new(inset, inset, width - 2.0 * inset, height - 2.0 * inset), r, r)
+        })
+        .collect();
+    Ok(Outline { commands, width, height, even_odd: true })
+}
+
+fn badge(width: f32, height: f32, shape: BadgeShape) -> Outline {
+    let (rx, ry) = (width / 2.0, height / 2.0);
+    let commands = match shape {
+        BadgeShape::Circle => ellipse_path(rx, ry, rx, ry),
+        BadgeShape::Square => rect(0.0, 0.0, width, height),
+        BadgeShape::Rounded => {
+            let r = width.min(height) / 4.0;
+            rounded_rect(Bounds::new(0.0, 0.0, width, height), r, r)
+        }
+        BadgeShape::Pill => {
+            let r = width.min(height) / 2.0;
+            rounded_rect(Bounds::new(0.0, 0.0, width, height), r, r)
+        }
+        BadgeShape::Diamond => diamond(rx, ry, rx, ry),
+    };
+    Outline { commands, width, height, even_odd: false }
+}
+
+impl Decoration {
+    fn outline(&self) -> Result<Outline, String> {
+        let sizes: &[f32] = match self {
+            Decoration::Rule { length, thickness, .. } => &[*length, *thickness],
+            Decoration::Ornament { size, .. } => &[*size],
+            Decoration::Frame { width, height, thickness, .. } => &[*width, *height, *thickness],
+            Decoration::Badge { width, height, .. } => &[*width, *height],
+        };
+        if sizes.iter().any(|s| !s.is_finite() || *s <= 0.0) {
+            return Err("Decoration sizes must be positive".to_string());
+        }
+        Ok(match *self {
+            Decoration::Rule { length, thickness, style } => rule(length, thickness, style),
+            Decoration::Ornament { size, style } => ornament(size, style),
+            Decoration::Frame { width, height, thickness, corner_radius, double } => {
+                frame(width, height, thickness, corner_radius.max(0.0), double)?
+            }
+            Decoration::Badge { width, height, shape } => badge(width, height, shape),
+        })
+    }
+
+    /// Vector layer `id` for this decoration, its top-left corner at (`x`, `y`)
+    /// and filled with `color` (black when `None`)
+    pub fn layer(&self, id: String, x: f32, y: f32, color: Option<&str>) -> Result<LayerObject, String> {
+        let outline = self.outline()?;
+        let commands = outline
+            .commands
+            .into_iter()
+            .map(|cmd| match cmd {
+                PathCommand::MoveTo { x: px, y: py } => PathCommand::MoveTo { x: x + px, y: y + py },
+                PathCommand::LineTo { x: px, y: py } => PathCommand::LineTo { x: x + px, y: y + py },
+                PathCommand::CurveTo { x1, y1, x2, y2, x: px, y: py } => PathCommand::CurveTo {
+                    x1: x + x1,
+                    y1: y + y1,
+                    x2: x + x2,
+                    y2: y + y2,
+                    x: x + px,
+                    y: y + py,
+                },
+                PathCommand::ClosePath => PathCommand::ClosePath,
+            })
+            .collect();
+
+        let mut layer = new_layer(id, LayerType::Vector, Bounds::new(x, y, outline.width, outline.height));
+        layer.path_data = Some(PathData { commands, fill_rule: outline.even_odd.then_some(FillRule::EvenOdd) });
+        layer.fill_color = Some(color.unwrap_or(DEFAULT_COLOR).to_string());
+        layer.source_type = SourceType::Manual;
+        Ok(layer)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn points(layer: &LayerObject) -> Vec<(f32, f32)> {
+        layer
+            .path_data
+            .as_ref()
+            .unwrap()
+            .commands
+            .iter()
+            .filter_map(|cmd| match *cmd {
+                PathCommand::MoveTo { x, y } | PathCommand::LineTo { x, y } | PathCommand::CurveTo { x, y, .. } => {
+                    Some((x, y))
+                }
+                PathCommand::ClosePath => None,
+            })
+            .collect()
+    }
+
+    fn inside(layer: &LayerObject) -> bool {
+        let b = layer.bounds;
+        points(layer).iter().all(|&(x, y)| {
+            x >= b.x - 0.01 && x <= b.x + b.width + 0.01 && y >= b.y - 0.01 && y <= b.y + b.height + 0.01
+        })
+    }
+
+    #[test]
+    fn test_decoration_layers() {
+        let decorations = [
+            Decoration::Rule { length: 200.0, thickness: 2.0, style: RuleStyle::Solid },
+            Decoration::Rule { length: 200.0, thickness: 2.0, style: RuleStyle::Double },
+            Decoration::Rule { length: 200.0, thickness: 4.0, style: RuleStyle::Dotted },
+            Decoration::Rule { length: 200.0, thickness: 2.0, style: RuleStyle::Tapered },
+            Decoration::Rule { length: 200.0, thickness: 2.0, style: RuleStyle::Diamond },
+            Decoration::Ornament { size: 48.0, style: OrnamentStyle::Dinkus },
+            Decoration::Ornament { size: 48.0, style: OrnamentStyle::Asterism },
+            Decoration::Ornament { size: 48.0, style: OrnamentStyle::Lozenge },
+            Decoration::Ornament { size: 48.0, style: OrnamentStyle::Flourish },
+            Decoration::Frame { width: 300.0, height: 200.0, thickness: 3.0, corner_radius: 12.0, double: true },
+            Decoration::Badge { width: 24.0, height: 24.0, shape: BadgeShape::Circle },
+            Decoration::Badge { width: 40.0, height: 20.0, shape: BadgeShape::Pill },
+            Decoration::Badge { width: 40.0, height: 40.0, shape: BadgeShape::Diamond },
+        ];
+        for decoration in decorations {
+            let layer = decoration.layer("d".to_string(), 100.0, 50.0, Some("#aa0000")).unwrap();
+            assert_eq!(layer.layer_type, LayerType::Vector);
+            assert_eq!((layer.bounds.x, layer.bounds.y), (100.0, 50.0));
+            assert_eq!(layer.fill_color.as_deref(), Some("#aa0000"));
+            assert!(inside(&layer), "{:?} draws outside its bounds", decoration);
+        }
+
+        let rule = Decoration::Rule { length: 200.0, thickness: 4.0, style: RuleStyle::Dotted };
+        let dots = rule.layer("d".to_string(), 0.0, 0.0, None).unwrap();
+        // Dots 4pt across on 8pt centers, the first and last touching the ends
+        let moves = dots.path_data.as_ref().unwrap().commands.iter().filter(|c| matches!(c, PathCommand::MoveTo { .. }));
+        assert_eq!(moves.count(), 25);
+        assert_eq!(dots.fill_color.as_deref(), Some(DEFAULT_COLOR));
+
+        let frame = Decoration::Frame { width: 300.0, height: 200.0, thickness: 3.0, corner_radius: 0.0, double: false };
+        let layer = frame.layer("f".to_string(), 0.0, 0.0, None).unwrap();
+        assert_eq!(layer.path_data.unwrap().fill_rule, Some(FillRule::EvenOdd));
+        assert_eq!(layer.bounds, Bounds::new(0.0, 0.0, 300.0, 200.0));
+    }
+
+    #[test]
+    fn test_decoration_json_and_errors() {
+        let json = r#"{"kind":"frame","width":100,"height":80,"thickness":2,"cornerRadius":6}"#;
+        let decoration: Decoration = serde_json::from_str(json).unwrap();
+        assert_eq!(
+            decoration,
+            Decoration::Frame { width: 100.0, height: 80.0, thickness: 2.0, corner_radius: 6.0, double: false }
+        );
+        let badge: Decoration = serde_json::from_str(r#"{"kind":"badge","width":20,"height":20}"#).unwrap();
+        assert_eq!(badge, Decoration::Badge { width: 20.0, height: 20.0, shape: BadgeShape::Circle });
+
+        assert!(Decoration::Ornament { size: 0.0, style: OrnamentStyle::Dinkus }.layer("o".to_string(), 0.0, 0.0, None).is_err());
+        let thick = Decoration::Frame { width: 20.0, height: 20.0, thickness: 5.0, corner_radius: 0.0, double: true };
+        assert!(thick.layer("f".to_string(), 0.0, 0.0, None).is_err());
+    }
+}
@@ -0,0 +1,723 @@
+//! Document Metadata
+//! Title, subject, keywords, ISBN, publisher, language and custom properties
+//! in export formats
+//!
+//! PDF gets an Info dictionary, an XMP packet and the catalog /Lang; pages
+//! whose language differs are wrapped in a marked-content span carrying
+//! their own /Lang. DOCX gets core and custom property parts, EPUB the OPF
+//! `<metadata>` element.
+
+use crate::models::DocumentMetadata;
+
+/// Info keys written from the standard fields, never from custom properties
+const RESERVED_INFO_KEYS: [&str; 9] =
+    ["Title", "Author", "Subject", "Keywords", "Creator", "Producer", "CreationDate", "ModDate", "Trapped"];
+
+impl DocumentMetadata {
+    /// Whether anything beyond title and author needs writing
+    pub fn has_extended_fields(&self) -> bool {
+        self.description.is_some()
+            || self.subject.is_some()
+            || !self.keywords.is_empty()
+            || self.isbn.is_some()
+            || self.publisher.is_some()
+            || self.language.is_some()
+            || !self.custom.is_empty()
+    }
+
+    /// Keywords as one string, the way PDF Info and DOCX store them
+    pub fn keywords_text(&self) -> String {
+        self.keywords.join(", ")
+    }
+
+    /// Custom properties plus the fields formats have no standard slot for
+    fn extra_properties(&self) -> Vec<(&str, &str)> {
+        let mut properties: Vec<(&str, &str)> = Vec::new();
+        if let Some(isbn) = &self.isbn {
+            properties.push(("ISBN", isbn));
+        }
+        if let Some(publisher) = &self.publisher {
+            properties.push(("Publisher", publisher));
+        }
+        for (key, value) in &self.custom {
+            if !key.is_empty() && !properties.iter().any(|(k, _)| k == key) {
+                properties.push((key, value));
+            }
+        }
+        properties
+    }
+}
+
+/// Well-formed BCP 47 tag shape: ASCII letters and digits in hyphenated parts
+pub fn is_language_tag(tag: &str) -> bool {
+    !tag.is_empty()
+        && tag.len() <= 35
+        && tag.split('-').all(|part| (1..=8).contains(&part.len()) && part.chars().all(|c| c.is_ascii_alphanumeric()))
+}
+
+/// Escape text for XML element content and attribute values
+pub fn escape_xml(text: &str) -> String {
+    let mut escaped = String::with_capacity(text.len());
+    for c in text.chars() {
+        match c {
+            '&' => escaped.push_str("&amp;"),
+            '<' => escaped.push_str("&lt;"),
+            '>' => escaped.push_str("&gt;"),
+            '"' => escaped.push_str("&quot;"),
+            '\'' => escaped.push_str("&apos;"),
+            // Control characters are not allowed in XML 1.0
+            c if (c as u32) < 0x20 && !matches!(c, '\t' | '\n' | '\r') => {}
+            c => escaped.push(c),
+        }
+    }
+    escaped
+}
+
+/// A custom property name usable as an XML element name
+fn is_xml_name(name: &str) -> bool {
+    let mut chars = name.chars();
+    chars.next().is_some_and(|c| c.is_ascii_alphabetic() || c == '_')
+        && chars.all(|c| c.is_ascii_alphanumeric() || matches!(c, '_' | '-' | '.'))
+        && !name.to_ascii_lowercase().starts_with("xml")
+}
+
+fn isbn_urn(isbn: &str) -> String {
+    let digits: String = isbn.chars().filter(|c| c.is_ascii_alphanumeric()).collect();
+    format!("urn:isbn:{}", digits)
+}
+
+/// XMP packet for a PDF /Metadata stream
+///
+/// Custom properties go in the `pdfx` namespace, mirroring the Info
+/// dictionary; names that are not valid XML names or that are standard Info
+/// keys are left out.
+pub fn xmp_packet(metadata: &DocumentMetadata) -> String {
+    let mut body = String::from("<dc:format>application/pdf</dc:format>");
+    let alt = |value: &str| format!("<rdf:Alt><rdf:li xml:lang=\"x-default\">{}</rdf:li></rdf:Alt>", escape_xml(value));
+    let list = |kind: &str, values: &[&str]| {
+        let items: String = values.iter().map(|v| format!("<rdf:li>{}</rdf:li>", escape_xml(v))).collect();
+        format!("<rdf:{kind}>{items}</rdf:{kind}>")
+    };
+
+    if !metadata.title.is_empty() {
+        body.push_str(&format!("<dc:title>{}</dc:title>", alt(&metadata.title)));
+    }
+    if !metadata.author.is_empty() {
+        body.push_str(&format!("<dc:creator>{}</dc:creator>", list("Seq", &[&metadata.author])));
+    }
+    // The Info /Subject maps to dc:description; keywords to dc:subject
+    if let Some(description) = metadata.description.as_ref().or(metadata.subject.as_ref()) {
+        body.push_str(&format!("<dc:description>{}</dc:description>", alt(description)));
+    }
+    if !metadata.keywords.is_empty() {
+        let keywords: Vec<&str> = metadata.keywords.iter().map(String::as_str).collect();
+        body.push_str(&format!("<dc:subject>{}</dc:subject>", list("Bag", &keywords)));
+        body.push_str(&format!("<pdf:Keywords>{}</pdf:Keywords>", escape_xml(&metadata.keywords_text())));
+    }
+    if let Some(publisher) = &metadata.publisher {
+        body.push_str(&format!("<dc:publisher>{}</dc:publisher>", list("Bag", &[publisher])));
+    }
+    if let Some(language) = metadata.language.as_deref().filter(|l| is_language_tag(l)) {
+        body.push_str(&format!("<dc:language>{}</dc:language>", list("Bag", &[language])));
+    }
+    if let Some(isbn) = &metadata.isbn {
+        body.push_str(&format!("<dc:identifier>{}</dc:identifier>", escape_xml(&isbn_urn(isbn))));
+        body.push_str(&format!("<prism:isbn>{}</prism:isbn>", escape_xml(isbn)));
+    }
+    if !metadata.created.is_empty() {
+        body.push_str(&format!("<xmp:CreateDate>{}</xmp:CreateDate>", escape_xml(&metadata.created)));
+    }
+    if !metadata.modified.is_empty() {
+        body.push_str(&format!("<xmp:ModifyDate>{}</xmp:ModifyDate>", escape_xml(&metadata.modified)));
+    }
+    for (key, value) in &metadata.custom {
+        if is_xml_name(key) && !RESERVED_INFO_KEYS.contains(&key.as_str()) {
+            body.push_str(&format!("<pdfx:{key}>{}</pdfx:{key}>", escape_xml(value)));
+        }
+    }
+
+    format!(
+        concat!(
+            "<?xpacket begin=\"\u{FEFF}\" id=\"W5M0MpCehiHzreSzNTczkc9d\"?>\n",
+            "<x:xmpmeta xmlns:x=\"adobe:ns:meta/\">",
+            "<rdf:RDF xmlns:rdf=\"http://www.w3.org/1999/02/22-rdf-syntax-ns#\">",
+            "<rdf:Description rdf:about=\"\"",
+            " xmlns:dc=\"http://purl.org/dc/elements/1.1/\"",
+            " xmlns:pdf=\"http://ns.adobe.com/pdf/1.3/\"",
+            " xmlns:xmp=\"http://ns.adobe.com/xap/1.0/\"",
+            " xmlns:prism=\"http://prismstandard.org/namespaces/basic/2.0/\"",
+            " xmlns:pdfx=\"http://ns.adobe.com/pdfx/1.3/\">",
+            "{}</rdf:Description></rdf:RDF></x:xmpmeta>\n",
+            "<?xpacket end=\"w\"?>"
+        ),
+        body
+    )
+}
+
+/// DOCX `docProps/core.xml`
+pub fn docx_core_properties(metadata: &DocumentMetadata) -> String {
+    let mut body = String::new();
+    let mut element = |name: &str, value: &str| {
+        if !value.is_empty() {
+            body.push_str(&format!("<{name}>{}</{name}>", escape_xml(value)));
+        }
+    };
+    element("dc:title", &metadata.title);
+    element("dc:subject", metadata.subject.as_deref().unwrap_or_default());
+    element("dc:creator", &metadata.author);
+    element("cp:keywords", &metadata.keywords_text());
+    element("dc:description", metadata.description.as_deref().unwrap_or_default());
+    element("dc:language", metadata.language.as_deref().unwrap_or_default());
+    element("dc:identifier", &metadata.isbn.as_deref().map(isbn_urn).unwrap_or_default());
+    for (name, date) in [("dcterms:created", &metadata.created), ("dcterms:modified", &metadata.modified)] {
+        if !date.is_empty() {
+            body.push_str(&format!("<{name} xsi:type=\"dcterms:W3CDTF\">{}</{name}>", escape_xml(date)));
+        }
+    }
+    format!(
+        concat!(
+            "<?xml version=\"1.0\" encoding=\"UTF-8\" standalone=\"yes\"?>\n",
+            "<cp:coreProperties",
+            " xmlns:cp=\"http://schemas.openxmlformats.org/package/2006/metadata/core-properties\"",
+            " xmlns:dc=\"http://purl.org/dc/elements/1.1/\"",
+            " xmlns:dcterms=\"http://purl.org/dc/terms/\"",
+            " xmlns:xsi=\"http://www.w3.org/2001/XMLSchema-instance\">{}</cp:coreProperties>"
+        ),
+        body
+    )
+}
+
+/// DOCX `docProps/custom.xml` for the publisher, ISBN and custom
+/// properties; `None` when there are none
+pub fn docx_custom_properties(metadata: &DocumentMetadata) -> Option<String> {
+    let properties = metadata.extra_properties();
+    if properties.is_empty() {
+        return None;
+    }
+    // Property ids 0 and 1 are reserved
+    let body: String = properties
+        .iter()
+        .enumerate()
+        .map(|(i, (name, value))| {
+            format!(
+                "<property fmtid=\"{{D5CDD505-2E9C-101B-9397-08002B2CF9AE}}\" pid=\"{}\" name=\"{}\"><vt:lpwstr>{}</vt:lpwstr></property>",
+                i + 2,
+                escape_xml(name),
+                escape_xml(value)
+            )
+        })
+        .collect();
+    Some(format!(
+        concat!(
+            "<?xml version=\"1.0\" encoding=\"UTF-8\" standalone=\"yes\"?>\n",
+            "<Properties xmlns=\"http://schemas.openxmlformats.org/officeDocument/2006/custom-properties\"",
+            " xmlns:vt=\"http://schemas.openxmlformats.org/officeDocument/2006/docPropsVTypes\">{}</Properties>"
+        ),
+        body
+    ))
+}
+
+/// EPUB 3 OPF `<metadata>` element
+///
+/// The ISBN is the package identifier (`pub-id`) when set, else
+/// `fallback_id`; EPUB requires a language, so "und" stands in for a
+/// missing one.
+pub fn opf_metadata(metadata: &DocumentMetadata, fallback_id: &str) -> String {
+    let mut body = String::new();
+    let identifier = metadata.isbn.as_deref().map(isbn_urn).unwrap_or_else(|| fallback_id.to_string());
+    body.push_str(&format!("<dc:identifier id=\"pub-id\">{}</dc:identifier>", escape_xml(&identifier)));
+    body.push_str(&format!("<dc:title>{}</dc:title>", escape_xml(&metadata.title)));
+    let language = metadata.language.as_deref().filter(|l| is_language_tag(l)).unwrap_or("und");
+    body.push_str(&format!("<dc:language>{}</dc:language>", language));
+
+    let mut element = |name: &str, value: &str| {
+        if !value.is_empty() {
+            body.push_str(&format!("<{name}>{}</{name}>", escape_xml(value)));
+        }
+    };
+    element("dc:creator", &metadata.author);
+    element("dc:publisher", metadata.publisher.as_deref().unwrap_or_default());
+    element("dc:description", metadata.description.as_deref().unwrap_or_default());
+    element("dc:subject", metadata.subject.as_deref().unwrap_or_default());
+    for keyword in &metadata.keywords {
+        element("dc:subject", keyword);
+    }
+    element("dc:date", &metadata.created);
+    if !metadata.modified.is_empty() {
+        body.push_str(&format!("<meta property=\"dcterms:modified\">{}</meta>", escape_xml(&metadata.modified)));
+    }
+    for (key, value) in &metadata.custom {
+        body.push_str(&format!("<meta name=\"{}\" content=\"{}\"/>", escape_xml(key), escape_xml(value)));
+    }
+    format!("<metadata xmlns:dc=\"http://purl.org/dc/elements/1.1/\">{}</metadata>", body)
+}
+
+/// PDF date string (`D:YYYYMMDDHHmmSS+HH'mm'`) for an ISO 8601 date;
+/// fractional seconds are dropped
+pub fn pdf_date(iso: &str) -> Option<String> {
+    let (date, time) = iso.trim().split_once('T').unwrap_or((iso.trim(), ""));
+    let date: String = date.split('-').collect();
+    let (clock, zone) = time.split_at(time.find(['Z', '+', '-']).unwrap_or(time.len()));
+    let clock: String = clock.split('.').next().unwrap_or_default().split(':').collect();
+    let digits = format!("{}{}", date, clock);
+    if !matches!(date.len(), 4 | 6 | 8) || !matches!(clock.len(), 0 | 2 | 4 | 6) || !digits.chars().all(|c| c.is_ascii_digit())
+    {
+        return None;
+    }
+    let zone = match zone.split_once(':') {
+        _ if zone == "Z" => "Z".to_string(),
+        Some((hours, minutes)) => format!("{}'{}'", hours, minutes),
+        None if zone.len() == 3 => format!("{}'00'", zone),
+        None => String::new(),
+    };
+    Some(format!("D:{}{}", digits, zone))
+}
+
+/// ISO 8601 date for a PDF date string; missing fields take their lowest
+/// value and a missing offset is left out
+pub fn iso_date(pdf: &str) -> Option<String> {
+    let text = pdf.trim();
+    let text = text.strip_prefix("D:").unwrap_or(text);
+    let (digits, zone) = text.split_at(text.find(|c: char| !c.is_ascii_digit()).unwrap_or(text.len()));
+    if digits.len() < 4 || digits.len() > 14 || digits.len() % 2 != 0 {
+        return None;
+    }
+    let field = |start: usize, default: &'static str| digits.get(start..start + 2).unwrap_or(default);
+    let mut iso = format!(
+        "{}-{}-{}T{}:{}:{}",
+        &digits[..4],
+        field(4, "01"),
+        field(6, "01"),
+        field(8, "00"),
+        field(10, "00"),
+        field(12, "00")
+    );
+    match zone.chars().next() {
+        Some('Z') => iso.push('Z'),
+        Some(sign @ ('+' | '-')) => {
+            let offset: String = zone.chars().filter(char::is_ascii_digit).collect();
+            if offset.len() >= 2 {
+                iso.push_str(&format!("{}{}:{}", sign, &offset[..2], offset.get(2..4).unwrap_or("00")));
+            }
+        }
+        _ => {}
+    }
+    Some(iso)
+}
+
+/// Resolve the predefined XML entities and numeric character references;
+/// anything else is left as written
+pub fn unescape_xml(text: &str) -> String {
+    let mut unescaped = String::with_capacity(text.len());
+    let mut rest = text;
+    while let Some(start) = rest.find('&') {
+        unescaped.push_str(&rest[..start]);
+        rest = &rest[start..];
+        let entity = rest[1..].find(';').map(|end| &rest[1..end + 1]);
+        let resolved = entity.and_then(|entity| match entity {
+            "lt" => Some('<'),
+            "gt" => Some('>'),
+            "quot" => Some('"'),
+            "apos" => Some('\''),
+            "amp" => Some('&'),
+            _ => {
+                let code = match entity.strip_prefix("#x").or_else(|| entity.strip_prefix("#X")) {
+                    Some(hex) => u32::from_str_radix(hex, 16).ok(),
+                    None => entity.strip_prefix('#').and_then(|dec| dec.parse().ok()),
+                };
+                code.and_then(char::from_u32)
+            }
+        });
+        match (entity, resolved) {
+            (Some(entity), Some(c)) => {
+                unescaped.push(c);
+                rest = &rest[entity.len() + 2..];
+            }
+            _ => {
+                unescaped.push('&');
+                rest = &rest[1..];
+            }
+        }
+    }
+    unescaped.push_str(rest);
+    unescaped
+}
+
+/// Values of every `<tag>` element in an XMP packet: the items of an
+/// `rdf:Alt`/`Bag`/`Seq`, or the element's text
+fn xmp_values(xmp: &str, tag: &str) -> Vec<String> {
+    let (open, close) = (format!("<{}>", tag), format!("</{}>", tag));
+    let mut values = Vec::new();
+    let mut rest = xmp;
+    while let Some(start) = rest.find(&open) {
+        let inner_start = start + open.len();
+        let Some(end) = rest[inner_start..].find(&close) else { break };
+        let inner = &rest[inner_start..inner_start + end];
+        if inner.contains("<rdf:li") {
+            for item in inner.split("<rdf:li").skip(1) {
+                let text = item.split_once('>').map(|(_, t)| t).unwrap_or_default();
+                values.push(unescape_xml(text.split("</rdf:li>").next().unwrap_or_default().trim()));
+            }
+        } else {
+            values.push(unescape_xml(inner.trim()));
+        }
+        rest = &rest[inner_start + end + close.len()..];
+    }
+    // Simple values may also be attributes of rdf:Description
+    let attribute = format!(" {}=\"", tag);
+    for (i, _) in xmp.match_indices(&attribute) {
+        let value = &xmp[i + attribute.len()..];
+        values.push(unescape_xml(value.split('"').next().unwrap_or_default().trim()));
+    }
+    values.retain(|v| !v.is_empty());
+    values
+}
+
+/// Custom properties written as `pdfx:` elements
+fn xmp_custom_properties(xmp: &str) -> Vec<(String, String)> {
+    xmp.match_indices("<pdfx:")
+        .filter_map(|(i, _)| {
+            let name = xmp[i + 6..].split(['>', ' ', '/']).next()?;
+            let value = xmp_values(&xmp[i..], &format!("pdfx:{}", name)).into_iter().next()?;
+            is_xml_name(name).then(|| (name.to_string(), value))
+        })
+        .collect()
+}
+
+/// Split a PDF or DOCX keywords string
+fn split_keywords(text: &str) -> Vec<String> {
+    text.split([',', ';']).map(str::trim).filter(|k| !k.is_empty()).map(str::to_string).collect()
+}
+
+/// Fill the fields `metadata` is missing from an XMP packet
+pub fn read_xmp(xmp: &str, metadata: &mut DocumentMetadata) {
+    let first = |tag: &str| xmp_values(xmp, tag).into_iter().next();
+    if metadata.title.is_empty() {
+        metadata.title = first("dc:title").unwrap_or_default();
+    }
+    if metadata.author.is_empty() {
+        metadata.author = xmp_values(xmp, "dc:creator").join(", ");
+    }
+    if metadata.subject.is_none() {
+        metadata.subject = first("dc:description");
+    }
+    if metadata.keywords.is_empty() {
+        metadata.keywords = xmp_values(xmp, "dc:subject");
+    }
+    if metadata.keywords.is_empty() {
+        metadata.keywords = first("pdf:Keywords").map(|k| split_keywords(&k)).unwrap_or_default();
+    }
+    metadata.publisher = metadata.publisher.take().or_else(|| first("dc:publisher"));
+    metadata.language = metadata.language.take().or_else(|| first("dc:language").filter(|l| is_language_tag(l)));
+    metadata.isbn = metadata.isbn.take().or_else(|| first("prism:isbn"));
+    if metadata.created.is_empty() {
+        metadata.created = first("xmp:CreateDate").unwrap_or_default();
+    }
+    if metadata.modified.is_empty() {
+        metadata.modified = first("xmp:ModifyDate").unwrap_or_default();
+    }
+    for (key, value) in xmp_custom_properties(xmp) {
+        metadata.custom.entry(key).or_insert(value);
+    }
+}
+
+/// Write document metadata into a saved PDF: Info entries (ISBN, publisher
+/// and custom properties as extra keys), the catalog /Lang and an XMP stream
+#[cfg(feature = "pdf")]
+pub fn write_pdf_metadata(doc: &mut lopdf::Document, metadata: &DocumentMetadata) -> Result<(), String> {
+    use lopdf::{dictionary, Dictionary, Object, Stream};
+
+    let info_id = match doc.trailer.get(b"Info").and_then(Object::as_reference) {
+        Ok(id) => id,
+        Err(_) => {
+            let id = doc.add_object(Dictionary::new());
+            doc.trailer.set("Info", id);
+            id
+        }
+    };
+    let info = doc.get_object_mut(info_id).and_then(Object::as_dict_mut).map_err(|e| e.to_string())?;
+    let mut set = |key: &str, value: &str| {
+        if !value.is_empty() {
+            info.set(key, lopdf::text_string(value));
+        }
+    };
+    set("Title", &metadata.title);
+    set("Author", &metadata.author);
+    set("Subject", metadata.subject.as_deref().or(metadata.description.as_deref()).unwrap_or_default());
+    set("Keywords", &metadata.keywords_text());
+    for (key, value) in metadata.extra_properties() {
+        if !RESERVED_INFO_KEYS.contains(&key) {
+            set(key, value);
+        }
+    }
+    for (key, date) in [("CreationDate", &metadata.created), ("ModDate", &metadata.modified)] {
+        if let Some(date) = pdf_date(date) {
+            info.set(key, Object::string_literal(date));
+        }
+    }
+
+    let xmp = doc.add_object(Stream::new(
+        dictionary! { "Type" => "Metadata", "Subtype" => "XML" },
+        xmp_packet(metadata).into_bytes(),
+    ));
+    let catalog = doc.catalog_mut().map_err(|e| e.to_string())?;
+    catalog.set("Metadata", xmp);
+    if let Some(language) = metadata.language.as_deref().filter(|l| is_language_tag(l)) {
+        catalog.set("Lang", Object::string_literal(language));
+    }
+    Ok(())
+}
+
+/// Metadata of an imported PDF: the Info dictionary, gaps filled from the
+/// XMP stream, and the catalog /Lang
+///
+/// Info keys other than the standard ones become custom properties, except
+/// ISBN and Publisher, which fill their own fields. Dates fall back to now.
+#[cfg(feature = "pdf")]
+pub fn read_pdf_metadata(doc: &lopdf::Document) -> DocumentMetadata {
+    use lopdf::Object;
+
+    let mut metadata = DocumentMetadata { created: String::new(), modified: String::new(), ..DocumentMetadata::default() };
+    let text = |object: &Object| {
+        let (_, object) = doc.dereference(object).ok()?;
+        lopdf::decode_text_string(object).ok().map(|t| t.trim().to_string()).filter(|t| !t.is_empty())
+    };
+
+    let info = doc.trailer.get(b"Info").and_then(|info| doc.dereference(info)).and_then(|(_, info)| info.as_dict());
+    if let Ok(info) = info {
+        for (key, value) in info.iter() {
+            let Some(value) = text(value) else { continue };
+            match key.as_slice() {
+                b"Title" => metadata.title = value,
+                b"Author" => metadata.author = value,
+                b"Subject" => metadata.subject = Some(value),
+                b"Keywords" => metadata.keywords = split_keywords(&value),
+                b"CreationDate" => metadata.created = iso_date(&value).unwrap_or_default(),
+                b"ModDate" => metadata.modified = iso_date(&value).unwrap_or_default(),
+                b"ISBN" => metadata.isbn = Some(value),
+                b"Publisher" => metadata.publisher = Some(value),
+                key => {
+                    let key = String::from_utf8_lossy(key).to_string();
+                    if !RESERVED_INFO_KEYS.contains(&key.as_str()) {
+                        metadata.custom.insert(key, value);
+                    }
+                }
+            }
+        }
+    }
+
+    if let Ok(catalog) = doc.catalog() {
+        metadata.language = catalog.get(b"Lang").ok().and_then(text).filter(|l| is_language_tag(l));
+        let xmp = catalog
+            .get(b"Metadata")
+            .and_then(|m| doc.dereference(m))
+            .and_then(|(_, m)| m.as_stream())
+            .map(|s| s.decompressed_content().unwrap_or_else(|_| s.content.clone()));
+        if let Ok(xmp) = xmp {
+            read_xmp(&String::from_utf8_lossy(&xmp), &mut metadata);
+        }
+    }
+
+    let now = crate::models::iso8601_now();
+    for date in [&mut metadata.created, &mut metadata.modified] {
+        if date.is_empty() {
+            date.clone_from(&now);
+        }
+    }
+    metadata
+}
+
+/// Tag the content of pages whose language differs from the document's
+///
+/// `languages` is indexed like the document's pages. Each tagged page's
+/// content is wrapped in a `/Span << /Lang (...) >> BDC … EMC` sequence.
+#[cfg(feature = "pdf")]
+pub fn write_page_languages(
+    doc: &mut lopdf::Document,
+    languages: &[Option<String>],
+    document_language: Option<&str>,
+) -> Result<(), String> {
+    use lopdf::{Dictionary, Object, Stream};
+
+    let pages: Vec<lopdf::ObjectId> = doc.get_pages().into_values().collect();
+    for (page_id, language) in pages.into_iter().zip(languages) {
+        let Some(language) = language.as_deref().filter(|l| is_language_tag(l) && Some(*l) != document_language) else {
+            continue;
+        };
+        let open = doc.add_object(Stream::new(
+            Dictionary::new(),
+            format!("/Span << /Lang ({}) >> BDC\n", language).into_bytes(),
+        ));
+        let close = doc.add_object(Stream::new(Dictionary::new(), b"\nEMC\n".to_vec()));
+        let page = doc.get_object_mut(page_id).and_then(Object::as_dict_mut).map_err(|e| e.to_string())?;
+        let mut contents = vec![Object::Reference(open)];
+        match page.get(b"Contents") {
+            Ok(Object::Array(parts)) => contents.extend(parts.iter().cloned()),
+            Ok(other) => contents.push(other.clone()),
+            Err(_) => {}
+        }
+        contents.push(Object::Reference(close));
+        page.set("Contents", contents);
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn metadata() -> DocumentMetadata {
+        let mut metadata = DocumentMetadata {
+            title: "Rivers & Roads".to_string(),
+            author: "A. Writer".to_string(),
+            created: "2024-01-02T03:04:05Z".to_string(),
+            modified: String::new(),
+            subject: Some("Travel".to_string()),
+            keywords: vec!["maps".to_string(), "rivers".to_string()],
+            isbn: Some("978-3-16-148410-0".to_string()),
+            publisher: Some("Small Press".to_string()),
+            language: Some("pt-BR".to_string()),
+            ..DocumentMetadata::default()
+        };
+        metadata.custom.insert("Edition".to_string(), "Second".to_string());
+        metadata.custom.insert("Print run".to_string(), "500".to_string());
+        metadata
+    }
+
+    #[test]
+    fn test_language_tags() {
+        assert!(is_language_tag("en"));
+        assert!(is_language_tag("zh-Hant-TW"));
+        assert!(!is_language_tag(""));
+        assert!(!is_language_tag("en_US"));
+        assert!(!is_language_tag("en) Tj ("));
+    }
+
+    #[test]
+    fn test_xml_formats() {
+        let metadata = metadata();
+        assert!(metadata.has_extended_fields());
+        assert!(!DocumentMetadata::default().has_extended_fields());
+
+        let xmp = xmp_packet(&metadata);
+        assert!(xmp.contains("<rdf:li xml:lang=\"x-default\">Rivers &amp; Roads</rdf:li>"));
+        assert!(xmp.contains("<dc:subject><rdf:Bag><rdf:li>maps</rdf:li><rdf:li>rivers</rdf:li></rdf:Bag></dc:subject>"));
+        assert!(xmp.contains("<dc:identifier>urn:isbn:9783161484100</dc:identifier>"));
+        assert!(xmp.contains("<pdfx:Edition>Second</pdfx:Edition>"));
+        // Not an XML name, so only in the Info dictionary
+        assert!(!xmp.contains("Print run"));
+        assert!(!xmp.contains("ModifyDate"));
+
+        let core = docx_core_properties(&metadata);
+        assert!(core.contains("<cp:keywords>maps, rivers</cp:keywords>"));
+        assert!(core.contains("<dc:language>pt-BR</dc:language>"));
+        assert!(core.contains("<dcterms:created xsi:type=\"dcterms:W3CDTF\">2024-01-02T03:04:05Z</dcterms:created>"));
+
+        let custom = docx_custom_properties(&metadata).unwrap();
+        assert!(custom.contains("pid=\"2\" name=\"ISBN\""));
+        assert!(custom.contains("pid=\"3\" name=\"Publisher\"><vt:lpwstr>Small Press</vt:lpwstr>"));
+        assert!(custom.contains("pid=\"5\" name=\"Print run\""));
+        assert_eq!(docx_custom_properties(&DocumentMetadata::default()), None);
+
+        let opf = opf_metadata(&metadata, "urn:uuid:1");
+        assert!(opf.contains("<dc:identifier id=\"pub-id\">urn:isbn:9783161484100</dc:identifier>"));
+        assert!(opf.contains("<dc:subject>Travel</dc:subject><dc:subject>maps</dc:subject>"));
+        assert!(opf.contains("<meta name=\"Print run\" content=\"500\"/>"));
+        let plain = opf_metadata(&DocumentMetadata { title: "T".to_string(), ..DocumentMetadata::default() }, "urn:uuid:1");
+        assert!(plain.contains("<dc:identifier id=\"pub-id\">urn:uuid:1</dc:identifier>"));
+        assert!(plain.contains("<dc:language>und</dc:language>"));
+    }
+
+    #[test]
+    fn test_dates() {
+        assert_eq!(pdf_date("2024-01-02T03:04:05Z").as_deref(), Some("D:20240102030405Z"));
+        assert_eq!(pdf_date("2024-01-02T03:04:05.123-03:00").as_deref(), Some("D:20240102030405-03'00'"));
+        assert_eq!(pdf_date("2024-01-02").as_deref(), Some("D:20240102"));
+        assert_eq!(pdf_date("yesterday"), None);
+
+        assert_eq!(iso_date("D:20240102030405Z").as_deref(), Some("2024-01-02T03:04:05Z"));
+        assert_eq!(iso_date("D:20240102030405+05'30'").as_deref(), Some("2024-01-02T03:04:05+05:30"));
+        assert_eq!(iso_date("D:2024").as_deref(), Some("2024-01-01T00:00:00"));
+        assert_eq!(iso_date("D:20x"), None);
+    }
+
+    #[test]
+    fn test_read_xmp() {
+        let xmp = concat!(
+            "<rdf:Description rdf:about=\"\" xmp:CreateDate=\"2020-05-06T07:08:09Z\">",
+            "<dc:title><rdf:Alt><rdf:li xml:lang=\"x-default\">Fish &amp; Chips</rdf:li></rdf:Alt></dc:title>",
+            "<dc:creator><rdf:Seq><rdf:li>Ann</rdf:li><rdf:li>Bo</rdf:li></rdf:Seq></dc:creator>",
+            "<pdf:Keywords>cod; haddock</pdf:Keywords>",
+            "<pdfx:Edition>First</pdfx:Edition></rdf:Description>"
+        );
+        let mut metadata = DocumentMetadata { author: "Info Author".to_string(), created: String::new(), ..DocumentMetadata::default() };
+        read_xmp(xmp, &mut metadata);
+        assert_eq!(metadata.title, "Fish & Chips");
+        assert_eq!(metadata.author, "Info Author");
+        assert_eq!(metadata.keywords, vec!["cod", "haddock"]);
+        assert_eq!(metadata.created, "2020-05-06T07:08:09Z");
+        assert_eq!(metadata.custom.get("Edition").map(String::as_str), Some("First"));
+
+        // What the export writes comes back
+        let mut read = DocumentMetadata { title: String::new(), author: String::new(), ..DocumentMetadata::default() };
+        read_xmp(&xmp_packet(&super::tests::metadata()), &mut read);
+        assert_eq!(read.title, "Rivers & Roads");
+        assert_eq!(read.keywords, vec!["maps", "rivers"]);
+        assert_eq!(read.isbn.as_deref(), Some("978-3-16-148410-0"));
+        assert_eq!(read.language.as_deref(), Some("pt-BR"));
+    }
+
+    #[cfg(feature = "pdf")]
+    #[test]
+    fn test_write_pdf_metadata() {
+        use lopdf::{dictionary, Document, Object, Stream};
+
+        let mut doc = Document::with_version("1.5");
+        let pages_id = doc.new_object_id();
+        let kids: Vec<Object> = (0..2)
+            .map(|_| {
+                let content = doc.add_object(Stream::new(lopdf::Dictionary::new(), b"0 0 m".to_vec()));
+                doc.add_object(dictionary! { "Type" => "Page", "Parent" => pages_id, "Contents" => content }).into()
+            })
+            .collect();
+        doc.objects.insert(pages_id, Object::Dictionary(dictionary! { "Type" => "Pages", "Kids" => kids, "Count" => 2 }));
+        let catalog = doc.add_object(dictionary! { "Type" => "Catalog", "Pages" => pages_id });
+        doc.trailer.set("Root", catalog);
+
+        let mut metadata = metadata();
+        metadata.custom.insert("Producer".to_string(), "ignored".to_string());
+        write_pdf_metadata(&mut doc, &metadata).unwrap();
+        write_page_languages(&mut doc, &[Some("pt-BR".to_string()), Some("en".to_string())], Some("pt-BR")).unwrap();
+
+        let info_id = doc.trailer.get(b"Info").and_then(Object::as_reference).unwrap();
+        let info = doc.get_dictionary(info_id).unwrap();
+        let text = |key: &[u8]| lopdf::decode_text_string(info.get(key).unwrap()).unwrap();
+        assert_eq!(text(b"Keywords"), "maps, rivers");
+        assert_eq!(text(b"Subject"), "Travel");
+        assert_eq!(text(b"ISBN"), "978-3-16-148410-0");
+        assert_eq!(text(b"Print run"), "500");
+        assert!(info.get(b"Producer").is_err());
+
+        let catalog = doc.catalog().unwrap();
+        assert_eq!(catalog.get(b"Lang").unwrap().as_str().unwrap(), b"pt-BR");
+        let xmp_id = catalog.get(b"Metadata").and_then(Object::as_reference).unwrap();
+        let xmp = doc.get_object(xmp_id).and_then(Object::as_stream).unwrap();
+        assert!(String::from_utf8_lossy(&xmp.content).contains("<prism:isbn>"));
+
+        // Only the page in a different language is tagged
+        let pages: Vec<_> = doc.get_pages().into_values().collect();
+        assert!(doc.get_dictionary(pages[0]).unwrap().get(b"Contents").unwrap().as_reference().is_ok());
+        let contents = doc.get_page_content(pages[1]).unwrap();
+        assert_eq!(String::from_utf8_lossy(&contents), "/Span << /Lang (en) >> BDC\n0 0 m\nEMC\n");
+
+        // Round trip through import
+        let read = read_pdf_metadata(&doc);
+        assert_eq!((read.title.as_str(), read.author.as_str()), ("Rivers & Roads", "A. Writer"));
+        assert_eq!(read.subject.as_deref(), Some("Travel"));
+        assert_eq!(read.keywords, metadata.keywords);
+        assert_eq!(read.created, "2024-01-02T03:04:05Z");
+        assert_eq!((read.isbn, read.publisher, read.language), (metadata.isbn, metadata.publisher, metadata.language));
+        assert_eq!(read.custom.get("Print run").map(String::as_str), Some("500"));
+        assert!(!read.custom.contains_key("Producer"));
+    }
+}
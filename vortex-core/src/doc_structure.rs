@@ -0,0 +1,324 @@
+//! Document structure
+//!
+//! Classifies text layers as headings, body text, captions or footnotes from
+//! the document's own typography: the body style is the size and weight most
+//! of the text is set in, headings are short layers set larger or bolder,
+//! footnotes are small text low on the page and captions sit against an
+//! image. Heading styles are ranked by size into levels, giving an outline
+//! tree for navigation and table of contents generation.
+
+use crate::models::{Bounds, LayerObject, LayerRole, LayerType, PageData};
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+
+/// Font size, relative to the body, from which a short layer is a heading
+const HEADING_RATIO: f32 = 1.15;
+/// Weight above the body weight from which a body-size layer is a heading
+const BOLD_DELTA: u16 = 200;
+/// Longer layers are body text however they are set
+const MAX_HEADING_CHARS: usize = 120;
+/// Font size, relative to the body, below which text is small print
+const SMALL_RATIO: f32 = 0.9;
+/// Fraction of the page height below which small print is a footnote
+const FOOTNOTE_ZONE: f32 = 0.75;
+/// Distance (in line heights of the text) within which text captions an image
+const CAPTION_GAP: f32 = 1.5;
+const MAX_LEVEL: u8 = 6;
+const CAPTION_LABELS: &[&str] = &["figure", "fig.", "table", "plate", "chart", "illustration"];
+
+/// What a text layer is in the document
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum StructureRole {
+    Heading,
+    Body,
+    Caption,
+    Footnote,
+}
+
+/// A classified text layer
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct StructureElement {
+    pub layer_id: String,
+    pub page_index: usize,
+    pub role: StructureRole,
+    /// Heading level, 1 being the largest
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub level: Option<u8>,
+    pub text: String,
+    pub bounds: Bounds,
+}
+
+/// A heading and the headings under it
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct OutlineNode {
+    pub title: String,
+    pub level: u8,
+    pub page_index: usize,
+    /// Layers making up the heading; the first is the navigation target
+    pub layer_ids: Vec<String>,
+    pub children: Vec<OutlineNode>,
+}
+
+/// Classified text and heading tree of a document
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct DocumentStructure {
+    pub body_font_size: f32,
+    pub body_font_weight: u16,
+    /// Content text layers in reading order
+    pub elements: Vec<StructureElement>,
+    pub outline: Vec<OutlineNode>,
+}
+
+fn font_size(layer: &LayerObject) -> f32 {
+    layer.font_size.unwrap_or(12.0)
+}
+
+fn font_weight(layer: &LayerObject) -> u16 {
+    layer.font_weight.unwrap_or(400)
+}
+
+/// Sizes are compared to the half point
+fn size_key(size: f32) -> u32 {
+    (size * 2.0).round() as u32
+}
+
+fn text_of(layer: &LayerObject) -> String {
+    layer.content.as_deref().unwrap_or_default().split_whitespace().collect::<Vec<_>>().join(" ")
+}
+
+/// Text sitting just above or below an image it overlaps horizontally
+fn beside_image(layer: &LayerObject, images: &[&LayerObject]) -> bool {
+    let (b, gap) = (&layer.bounds, CAPTION_GAP * font_size(layer) * 1.2);
+    images.iter().any(|image| {
+        let i = &image.bounds;
+        let overlaps = b.x < i.x + i.width && i.x < b.x + b.width;
+        let below = b.y - (i.y + i.height);
+        let above = i.y - (b.y + b.height);
+        overlaps && ((-1.0..=gap).contains(&below) || (-1.0..=gap).contains(&above))
+    })
+}
+
+/// Classify the content text of `pages` and build its outline
+pub fn extract_structure(pages: &[PageData]) -> DocumentStructure {
+    let texts: Vec<(&PageData, Vec<&LayerObject>)> = pages
+        .iter()
+        .map(|page| {
+            let mut layers: Vec<&LayerObject> = page
+                .layers
+                .iter()
+                .filter(|l| {
+                    l.visible
+                        && l.role == LayerRole::Content
+                        && l.layer_type == LayerType::Text
+                        && l.content.as_deref().is_some_and(|c| !c.trim().is_empty())
+                })
+                .collect();
+            layers.sort_by(|a, b| a.bounds.y.total_cmp(&b.bounds.y).then(a.bounds.x.total_cmp(&b.bounds.x)));
+            (page, layers)
+        })
+        .collect();
+
+    // Body style: the size, then the weight, carrying the most characters
+    let mut chars_by_size: BTreeMap<u32, usize> = BTreeMap::new();
+    let mut chars_by_weight: BTreeMap<u16, usize> = BTreeMap::new();
+    for layer in texts.iter().flat_map(|(_, layers)| layers) {
+        let chars = layer.content.as_deref().unwrap_or_default().chars().count();
+        *chars_by_size.entry(size_key(font_size(layer))).or_default() += chars;
+        *chars_by_weight.entry(font_weight(layer)).or_default() += chars;
+    }
+    let body_size = chars_by_size.iter().max_by_key(|&(_, &c)| c).map_or(12.0, |(&k, _)| k as f32 / 2.0);
+    let body_weight = chars_by_weight.iter().max_by_key(|&(_, &c)| c).map_or(400, |(&k, _)| k);
+
+    let is_heading = |layer: &LayerObject, text: &str| {
+        let (size, weight) = (font_size(layer), font_weight(layer));
+        text.chars().count() <= MAX_HEADING_CHARS
+            && (size >= body_size * HEADING_RATIO
+                || (size >= body_size * SMALL_RATIO && weight >= body_weight + BOLD_DELTA))
+    };
+
+    let mut elements = Vec::new();
+    let mut styles = Vec::new();
+    for (page, layers) in &texts {
+        let images: Vec<&LayerObject> = page
+            .layers
+            .iter()
+            .filter(|l| l.visible && matches!(l.layer_type, LayerType::Image | LayerType::Vector))
+            .collect();
+        for layer in layers {
+            let text = text_of(layer);
+            let labelled = CAPTION_LABELS.iter().any(|label| {
+                text.get(..label.len()).is_some_and(|start| start.eq_ignore_ascii_case(label))
+                    && text[label.len()..].trim_start().starts_with(|c: char| c.is_ascii_digit())
+            });
+            let small = font_size(layer) < body_size * SMALL_RATIO;
+            let set_apart = labelled || small || layer.font_style.as_deref() == Some("italic");
+            // A small "Figure 3" line is a caption even away from its image
+            let role = if (set_apart && beside_image(layer, &images)) || (labelled && small) {
+                StructureRole::Caption
+            } else if is_heading(layer, &text) {
+                StructureRole::Heading
+            } else if small && layer.bounds.y >= page.height * FOOTNOTE_ZONE {
+                StructureRole::Footnote
+            } else {
+                StructureRole::Body
+            };
+            elements.push(StructureElement {
+                layer_id: layer.id.clone(),
+                page_index: page.page_index,
+                role,
+                level: None,
+                text,
+                bounds: layer.bounds,
+            });
+            styles.push((size_key(font_size(layer)), font_weight(layer)));
+        }
+    }
+
+    // Heading styles ranked larger first, bolder first at the same size
+    let mut ranks: Vec<(u32, u16)> = elements
+        .iter()
+        .zip(&styles)
+        .filter(|(e, _)| e.role == StructureRole::Heading)
+        .map(|(_, &style)| style)
+        .collect();
+    ranks.sort_by(|a, b| b.cmp(a));
+    ranks.dedup();
+    for (element, style) in elements.iter_mut().zip(&styles).filter(|(e, _)| e.role == StructureRole::Heading) {
+        let rank = ranks.iter().position(|r| r == style).unwrap_or(0);
+        element.level = Some(rank.min(MAX_LEVEL as usize - 1) as u8 + 1);
+    }
+
+    let outline = build_outline(&elements);
+    DocumentStructure { body_font_size: body_size, body_font_weight: body_weight, elements, outline }
+}
+
+/// Nest headings by level; a heading continued in the next layer (same
+/// level, directly below) becomes one entry
+fn build_outline(elements: &[StructureElement]) -> Vec<OutlineNode> {
+    let mut flat: Vec<OutlineNode> = Vec::new();
+    let mut previous: Option<&StructureElement> = None;
+    for element in elements {
+        let Some(level) = element.level.filter(|_| element.role == StructureRole::Heading) else {
+            previous = None;
+            continue;
+        };
+        let continues = previous.is_some_and(|p| {
+            p.level == Some(level)
+                && p.page_index == element.page_index
+                && element.bounds.y - (p.bounds.y + p.bounds.height) < p.bounds.height
+        });
+        match flat.last_mut() {
+            Some(node) if continues => {
+                node.title.push(' ');
+                node.title.push_str(&element.text);
+                node.layer_ids.push(element.layer_id.clone());
+            }
+            _ => flat.push(OutlineNode {
+                title: element.text.clone(),
+                level,
+                page_index: element.page_index,
+                layer_ids: vec![element.layer_id.clone()],
+                children: Vec::new(),
+            }),
+        }
+        previous = Some(element);
+    }
+
+    // Each heading closes the open headings at its level or deeper
+    let mut roots: Vec<OutlineNode> = Vec::new();
+    let mut open: Vec<OutlineNode> = Vec::new();
+    let close = |open: &mut Vec<OutlineNode>, roots: &mut Vec<OutlineNode>| {
+        let node = open.pop().expect("closing an open heading");
+        match open.last_mut() {
+            Some(parent) => parent.children.push(node),
+            None => roots.push(node),
+        }
+    };
+    for node in flat {
+        while open.last().is_some_and(|o| o.level >= node.level) {
+            close(&mut open, &mut roots);
+        }
+        open.push(node);
+    }
+    while !open.is_empty() {
+        close(&mut open, &mut roots);
+    }
+    roots
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::clipboard::new_layer;
+
+    fn text(id: &str, content: &str, y: f32, size: f32, weight: u16) -> LayerObject {
+        let mut layer = new_layer(id.to_string(), LayerType::Text, Bounds::new(72.0, y, 400.0, size * 1.2));
+        layer.content = Some(content.to_string());
+        layer.font_size = Some(size);
+        layer.font_weight = Some(weight);
+        layer
+    }
+
+    fn page(page_index: usize, layers: Vec<LayerObject>) -> PageData {
+        PageData { page_index, width: 612.0, height: 792.0, dpi: None, layers, metadata: None, background: None }
+    }
+
+    #[test]
+    fn test_extract_structure() {
+        let body = "It was a dark and stormy night; the rain fell in torrents, except at occasional intervals.";
+        let mut header = text("header", "Running Head", 20.0, 9.0, 400);
+        header.role = LayerRole::Header;
+        let image = new_layer("img".to_string(), LayerType::Image, Bounds::new(72.0, 300.0, 400.0, 200.0));
+        let pages = vec![
+            page(
+                0,
+                vec![
+                    header,
+                    text("ch1a", "Chapter One:", 72.0, 24.0, 700),
+                    text("ch1b", "The Storm", 100.0, 24.0, 700),
+                    text("p1", body, 140.0, 11.0, 400),
+                    text("s1", "A Dark Night", 200.0, 16.0, 700),
+                    text("p2", body, 230.0, 11.0, 400),
+                    image,
+                    text("cap", "The storm at sea", 505.0, 9.0, 400),
+                    text("run", "Aside.", 560.0, 11.0, 700),
+                    text("p3", body, 580.0, 11.0, 400),
+                    text("fn", "1. Bulwer-Lytton, 1830.", 700.0, 8.0, 400),
+                ],
+            ),
+            page(1, vec![text("ch2", "Chapter Two", 72.0, 24.0, 700), text("p4", body, 120.0, 11.0, 400)]),
+        ];
+
+        let structure = extract_structure(&pages);
+        assert_eq!((structure.body_font_size, structure.body_font_weight), (11.0, 400));
+        let roles: Vec<(&str, StructureRole, Option<u8>)> =
+            structure.elements.iter().map(|e| (e.layer_id.as_str(), e.role, e.level)).collect();
+        assert_eq!(
+            roles,
+            [
+                ("ch1a", StructureRole::Heading, Some(1)),
+                ("ch1b", StructureRole::Heading, Some(1)),
+                ("p1", StructureRole::Body, None),
+                ("s1", StructureRole::Heading, Some(2)),
+                ("p2", StructureRole::Body, None),
+                ("cap", StructureRole::Caption, None),
+                ("run", StructureRole::Heading, Some(3)),
+                ("p3", StructureRole::Body, None),
+                ("fn", StructureRole::Footnote, None),
+                ("ch2", StructureRole::Heading, Some(1)),
+                ("p4", StructureRole::Body, None),
+            ]
+        );
+
+        let outline = &structure.outline;
+        assert_eq!(outline.len(), 2);
+        assert_eq!((outline[0].title.as_str(), outline[0].layer_ids.len()), ("Chapter One: The Storm", 2));
+        assert_eq!(outline[0].children[0].title, "A Dark Night");
+        assert_eq!(outline[0].children[0].children[0].title, "Aside.");
+        assert_eq!((outline[1].title.as_str(), outline[1].page_index), ("Chapter Two", 1));
+    }
+}
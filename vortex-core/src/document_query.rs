@@ -0,0 +1,181 @@
+//! Document Query
+//! Text search and summary statistics over a document's pages
+//!
+//! Both work on the layer model only, so they answer the same for PDF and
+//! DOCX imports and for edited projects.
+
+use crate::models::{LayerType, PageData};
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeSet;
+
+/// Characters of context kept on each side of a search match
+const SNIPPET_CONTEXT: usize = 30;
+
+/// Search settings
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SearchOptions {
+    #[serde(default)]
+    pub case_sensitive: bool,
+    /// Only match where the query is not part of a longer word
+    #[serde(default)]
+    pub whole_word: bool,
+    /// Stop after this many matches
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub max_results: Option<usize>,
+}
+
+/// One match of a search
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SearchHit {
+    pub page_index: usize,
+    pub layer_id: String,
+    /// Offset of the match in the layer's text, in characters
+    pub offset: usize,
+    /// The match with some surrounding text
+    pub snippet: String,
+}
+
+/// Summary counts for a document
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct DocumentStats {
+    pub page_count: usize,
+    pub layer_count: usize,
+    pub text_layers: usize,
+    pub image_layers: usize,
+    /// Vector and shape layers
+    pub graphic_layers: usize,
+    pub word_count: usize,
+    pub character_count: usize,
+    /// Font families used by text layers, sorted
+    pub fonts: Vec<String>,
+}
+
+fn fold(c: char, case_sensitive: bool) -> char {
+    if case_sensitive {
+        c
+    } else {
+        c.to_lowercase().next().unwrap_or(c)
+    }
+}
+
+/// Find `query` in the text layers of `pages`, in page and layer order
+pub fn search_pages(pages: &[PageData], query: &str, options: &SearchOptions) -> Vec<SearchHit> {
+    let needle: Vec<char> = query.chars().map(|c| fold(c, options.case_sensitive)).collect();
+    let limit = options.max_results.unwrap_or(usize::MAX);
+    let mut hits = Vec::new();
+    if needle.is_empty() {
+        return hits;
+    }
+
+    for page in pages {
+        for layer in page.layers.iter().filter(|l| l.layer_type == LayerType::Text) {
+            let Some(content) = layer.content.as_deref() else {
+                continue;
+            };
+            let text: Vec<char> = content.chars().collect();
+            let folded: Vec<char> = text.iter().map(|&c| fold(c, options.case_sensitive)).collect();
+            let mut start = 0;
+            while start + needle.len() <= folded.len() {
+                let end = start + needle.len();
+                let at_boundary = !options.whole_word
+                    || ((start == 0 || !text[start - 1].is_alphanumeric())
+                        && (end == text.len() || !text[end].is_alphanumeric()));
+                if folded[start..end] == needle[..] && at_boundary {
+                    if hits.len() == limit {
+                        return hits;
+                    }
+                    let from = start.saturating_sub(SNIPPET_CONTEXT);
+                    let to = (end + SNIPPET_CONTEXT).min(text.len());
+                    hits.push(SearchHit {
+                        page_index: page.page_index,
+                        layer_id: layer.id.clone(),
+                        offset: start,
+                        snippet: text[from..to].iter().collect::<String>().trim().to_string(),
+                    });
+                    start = end;
+                } else {
+                    start += 1;
+                }
+            }
+        }
+    }
+    hits
+}
+
+/// Count pages, layers, words and fonts
+pub fn document_stats(pages: &[PageData]) -> DocumentStats {
+    let mut stats = DocumentStats { page_count: pages.len(), ..Default::default() };
+    let mut fonts = BTreeSet::new();
+
+    for layer in pages.iter().flat_map(|p| &p.layers) {
+        stats.layer_count += 1;
+        match layer.layer_type {
+            LayerType::Text => {
+                stats.text_layers += 1;
+                if let Some(content) = &layer.content {
+                    stats.word_count += content.split_whitespace().count();
+                    stats.character_count += content.chars().filter(|c| !c.is_whitespace()).count();
+                }
+                if let Some(font) = &layer.font_family {
+                    fonts.insert(font.clone());
+                }
+            }
+            LayerType::Image => stats.image_layers += 1,
+            LayerType::Vector | LayerType::Shape => stats.graphic_layers += 1,
+        }
+    }
+    stats.fonts = fonts.into_iter().collect();
+    stats
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_util::{layer, page};
+
+    fn pages() -> Vec<PageData> {
+        let text = |id: &str, content: &str, font: &str| {
+            layer(id, "text")
+                .bounds(0.0, 0.0, 100.0, 10.0)
+                .fields(serde_json::json!({ "content": content, "fontFamily": font, "sourceType": "extracted" }))
+                .build()
+        };
+        vec![
+            page(
+                0,
+                vec![
+                    text("t1", "The Rook moves in straight lines.", "Garamond"),
+                    layer("i1", "image").z(1).with("sourceType", "extracted").build(),
+                ],
+            ),
+            page(1, vec![text("t2", "Rooks castle; a rook never jumps.", "Inter")]),
+        ]
+    }
+
+    #[test]
+    fn test_search_pages() {
+        let pages = pages();
+        let hits = search_pages(&pages, "rook", &SearchOptions::default());
+        let found: Vec<(usize, &str, usize)> =
+            hits.iter().map(|h| (h.page_index, h.layer_id.as_str(), h.offset)).collect();
+        assert_eq!(found, vec![(0, "t1", 4), (1, "t2", 0), (1, "t2", 16)]);
+
+        let whole = SearchOptions { whole_word: true, ..Default::default() };
+        assert_eq!(search_pages(&pages, "rook", &whole).len(), 2);
+        let exact = SearchOptions { case_sensitive: true, max_results: Some(1), ..Default::default() };
+        let hits = search_pages(&pages, "rook", &exact);
+        assert_eq!(hits.len(), 1);
+        assert_eq!(hits[0].snippet, "Rooks castle; a rook never jumps.");
+    }
+
+    #[test]
+    fn test_document_stats() {
+        let stats = document_stats(&pages());
+        assert_eq!((stats.page_count, stats.layer_count, stats.text_layers, stats.image_layers), (2, 3, 2, 1));
+        assert_eq!(stats.word_count, 12);
+        assert_eq!(stats.fonts, vec!["Garamond", "Inter"]);
+    }
+}
@@ -0,0 +1,480 @@
+//! Fixed-layout EPUB
+//!
+//! EPUB 3 with one pre-paginated page per document page, for picture books
+//! and other illustrated titles whose layout must not reflow. Each page is
+//! an XHTML document holding an inline SVG of its layers at the page size in
+//! points, so positions are exact and text stays selectable:
+//!
+//! ```text
+//! mimetype                                      stored first, uncompressed
+//! META-INF/container.xml
+//! META-INF/com.apple.ibooks.display-options.xml Apple Books fixed layout
+//! OEBPS/content.opf                             rendition and Kindle metas
+//! OEBPS/nav.xhtml                               contents and page list
+//! OEBPS/pages/page-001.xhtml
+//! OEBPS/images/image-1.png
+//! ```
+//!
+//! Kindle Previewer converts the package to KF8 using the `fixed-layout`,
+//! `original-resolution` and `book-type` metas; there is no KF8 writer here.
+//! Fonts are referenced by family name, not embedded.
+
+use crate::clipboard::{num, svg_element};
+use crate::doc_metadata::{escape_xml, is_language_tag, opf_metadata};
+use crate::models::{iso8601_now, DocumentMetadata, LayerObject, LayerType, PageData};
+use crate::page_setup::with_background;
+use crate::sections::SectionInfo;
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+
+const CONTAINER_XML: &str = concat!(
+    "<?xml version=\"1.0\" encoding=\"UTF-8\"?>",
+    "<container version=\"1.0\" xmlns=\"urn:oasis:names:tc:opendocument:xmlns:container\">",
+    "<rootfiles><rootfile full-path=\"OEBPS/content.opf\" media-type=\"application/oebps-package+xml\"/></rootfiles>",
+    "</container>"
+);
+
+const APPLE_DISPLAY_OPTIONS: &str = concat!(
+    "<?xml version=\"1.0\" encoding=\"UTF-8\"?>",
+    "<display_options><platform name=\"*\"><option name=\"fixed-layout\">true</option>",
+    "<option name=\"open-to-spread\">false</option></platform></display_options>"
+);
+
+/// Page orientation readers lock to (`rendition:orientation`)
+#[derive(Debug, Clone, Copy, Default, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum RenditionOrientation {
+    #[default]
+    Auto,
+    Portrait,
+    Landscape,
+}
+
+/// When readers show two pages side by side (`rendition:spread`)
+#[derive(Debug, Clone, Copy, Default, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum RenditionSpread {
+    #[default]
+    Auto,
+    None,
+    Landscape,
+    Both,
+}
+
+impl RenditionOrientation {
+    const fn as_str(self) -> &'static str {
+        match self {
+            RenditionOrientation::Auto => "auto",
+            RenditionOrientation::Portrait => "portrait",
+            RenditionOrientation::Landscape => "landscape",
+        }
+    }
+}
+
+impl RenditionSpread {
+    const fn as_str(self) -> &'static str {
+        match self {
+            RenditionSpread::Auto => "auto",
+            RenditionSpread::None => "none",
+            RenditionSpread::Landscape => "landscape",
+            RenditionSpread::Both => "both",
+        }
+    }
+}
+
+/// Fixed-layout EPUB settings
+#[derive(Debug, Clone, Default, Serialize, Deserialize, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct EpubOptions {
+    #[serde(default)]
+    pub orientation: RenditionOrientation,
+    #[serde(default)]
+    pub spread: RenditionSpread,
+    /// Kindle `book-type`, e.g. "children" or "comic"
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub book_type: Option<String>,
+    /// Pages turn right to left, as in manga
+    #[serde(default)]
+    pub right_to_left: bool,
+}
+
+/// An image stored in the package
+struct PackageImage {
+    id: String,
+    href: String,
+    media_type: &'static str,
+}
+
+/// File extension and media type of PNG, JPEG or WebP data
+fn image_type(data: &[u8]) -> Option<(&'static str, &'static str)> {
+    if data.starts_with(&[0x89, b'P', b'N', b'G']) {
+        Some(("png", "image/png"))
+    } else if data.starts_with(&[0xFF, 0xD8, 0xFF]) {
+        Some(("jpg", "image/jpeg"))
+    } else if data.len() >= 12 && data.starts_with(b"RIFF") && &data[8..12] == b"WEBP" {
+        Some(("webp", "image/webp"))
+    } else {
+        None
+    }
+}
+
+/// Identifier of a package without an ISBN, stable across exports
+fn fallback_identifier(metadata: &DocumentMetadata) -> String {
+    let slug: String = metadata
+        .title
+        .to_lowercase()
+        .split(|c: char| !c.is_alphanumeric())
+        .filter(|part| !part.is_empty())
+        .collect::<Vec<_>>()
+        .join("-");
+    let slug = if slug.is_empty() { "untitled".to_string() } else { slug };
+    format!("urn:rook:{}:{}", slug, metadata.created.get(..10).unwrap_or_default())
+}
+
+/// `dcterms:modified` must be `CCYY-MM-DDThh:mm:ssZ`
+fn package_modified(modified: &str) -> String {
+    let valid = modified.len() == 20
+        && modified.ends_with('Z')
+        && modified.char_indices().all(|(i, c)| match i {
+            4 | 7 => c == '-',
+            10 => c == 'T',
+            13 | 16 => c == ':',
+            19 => true,
+            _ => c.is_ascii_digit(),
+        });
+    if valid {
+        modified.to_string()
+    } else {
+        iso8601_now()
+    }
+}
+
+fn page_label(page: &PageData, number: usize) -> String {
+    page.metadata
+        .as_ref()
+        .and_then(|m| m.page_label.as_ref())
+        .map(|l| l.text())
+        .filter(|l| !l.is_empty())
+        .unwrap_or_else(|| number.to_string())
+}
+
+/// XHTML of a page whose background was turned into layers
+fn page_xhtml(
+    page: &PageData,
+    title: &str,
+    language: &str,
+    image_href: &impl Fn(&LayerObject) -> Option<String>,
+) -> String {
+    let page_language = page.metadata.as_ref().and_then(|m| m.language.as_deref());
+    let language = page_language.filter(|l| is_language_tag(l)).unwrap_or(language);
+    let mut layers: Vec<&LayerObject> = page.layers.iter().filter(|l| l.visible).collect();
+    layers.sort_by_key(|l| l.z_index);
+    let elements: String = layers.into_iter().filter_map(|l| svg_element(l, image_href)).collect();
+    let (w, h) = (num(page.width), num(page.height));
+    format!(
+        concat!(
+            "<?xml version=\"1.0\" encoding=\"UTF-8\"?>\n",
+            "<html xmlns=\"http://www.w3.org/1999/xhtml\" xmlns:epub=\"http://www.idpf.org/2007/ops\" ",
+            "xml:lang=\"{lang}\" lang=\"{lang}\">",
+            "<head><meta charset=\"UTF-8\"/><title>{title}</title>",
+            "<meta name=\"viewport\" content=\"width={w}, height={h}\"/>",
+            "<style>html, body {{ margin: 0; padding: 0; width: {w}px; height: {h}px; overflow: hidden; }}</style>",
+            "</head><body>",
+            "<svg xmlns=\"http://www.w3.org/2000/svg\" xmlns:xlink=\"http://www.w3.org/1999/xlink\" version=\"1.1\" ",
+            "width=\"{w}\" height=\"{h}\" viewBox=\"0 0 {w} {h}\">{elements}</svg>",
+            "</body></html>"
+        ),
+        lang = language,
+        title = escape_xml(title),
+        w = w,
+        h = h,
+        elements = elements
+    )
+}
+
+/// Navigation document: `toc` (a "Start" entry when it is empty) and the
+/// page list
+fn nav_xhtml(pages: &[PageData], toc: &[SectionInfo], language: &str) -> String {
+    let contents: String = if toc.is_empty() {
+        "<li><a href=\"pages/page-001.xhtml\">Start</a></li>".to_string()
+    } else {
+        toc.iter()
+            .map(|entry| {
+                format!("<li><a href=\"pages/page-{:03}.xhtml\">{}</a></li>", entry.start_page + 1, escape_xml(&entry.title))
+            })
+            .collect()
+    };
+    let items: String = pages
+        .iter()
+        .enumerate()
+        .map(|(i, page)| {
+            format!("<li><a href=\"pages/page-{:03}.xhtml\">{}</a></li>", i + 1, escape_xml(&page_label(page, i + 1)))
+        })
+        .collect();
+    format!(
+        concat!(
+            "<?xml version=\"1.0\" encoding=\"UTF-8\"?>\n",
+            "<html xmlns=\"http://www.w3.org/1999/xhtml\" xmlns:epub=\"http://www.idpf.org/2007/ops\" ",
+            "xml:lang=\"{lang}\" lang=\"{lang}\"><head><meta charset=\"UTF-8\"/><title>Contents</title></head><body>",
+            "<nav epub:type=\"toc\" id=\"toc\"><ol>{contents}</ol></nav>",
+            "<nav epub:type=\"page-list\" id=\"page-list\" hidden=\"hidden\"><ol>{items}</ol></nav>",
+            "</body></html>"
+        ),
+        lang = language,
+        contents = contents,
+        items = items
+    )
+}
+
+/// Package document: metadata with the fixed-layout properties, manifest
+/// and spine with alternating page spreads
+fn content_opf(
+    pages: &[PageData],
+    metadata: &DocumentMetadata,
+    options: &EpubOptions,
+    images: &[PackageImage],
+    cover: Option<&PackageImage>,
+) -> String {
+    let metadata = DocumentMetadata { modified: package_modified(&metadata.modified), ..metadata.clone() };
+    let base = opf_metadata(&metadata, &fallback_identifier(&metadata));
+    let mut opf_meta = base.strip_suffix("</metadata>").unwrap_or(&base).to_string();
+    opf_meta.push_str("<meta property=\"rendition:layout\">pre-paginated</meta>");
+    opf_meta.push_str(&format!("<meta property=\"rendition:orientation\">{}</meta>", options.orientation.as_str()));
+    opf_meta.push_str(&format!("<meta property=\"rendition:spread\">{}</meta>", options.spread.as_str()));
+    // Kindle
+    let (width, height) = pages.first().map_or((0.0, 0.0), |p| (p.width, p.height));
+    opf_meta.push_str("<meta name=\"fixed-layout\" content=\"true\"/>");
+    let resolution = format!("{}x{}", width.round(), height.round());
+    opf_meta.push_str(&format!("<meta name=\"original-resolution\" content=\"{}\"/>", resolution));
+    let orientation_lock = match options.orientation {
+        RenditionOrientation::Auto => "none",
+        other => other.as_str(),
+    };
+    opf_meta.push_str(&format!("<meta name=\"orientation-lock\" content=\"{}\"/>", orientation_lock));
+    if let Some(book_type) = options.book_type.as_deref().filter(|t| !t.trim().is_empty()) {
+        opf_meta.push_str(&format!("<meta name=\"book-type\" content=\"{}\"/>", escape_xml(book_type.trim())));
+    }
+    if options.right_to_left {
+        opf_meta.push_str("<meta name=\"primary-writing-mode\" content=\"horizontal-rl\"/>");
+    }
+    if let Some(cover) = cover {
+        opf_meta.push_str(&format!("<meta name=\"cover\" content=\"{}\"/>", cover.id));
+    }
+    opf_meta.push_str("</metadata>");
+
+    let mut manifest =
+        String::from("<item id=\"nav\" href=\"nav.xhtml\" media-type=\"application/xhtml+xml\" properties=\"nav\"/>");
+    let mut spine = String::new();
+    for i in 1..=pages.len() {
+        let id = format!("page-{:03}", i);
+        manifest.push_str(&format!(
+            "<item id=\"{0}\" href=\"pages/{0}.xhtml\" media-type=\"application/xhtml+xml\" properties=\"svg\"/>",
+            id
+        ));
+        // The first page is a recto: on the right, or the left when reading right to left
+        let right = (i % 2 == 1) != options.right_to_left;
+        let side = if right { "right" } else { "left" };
+        spine.push_str(&format!("<itemref idref=\"{}\" properties=\"page-spread-{}\"/>", id, side));
+    }
+    if let Some(image) = cover {
+        manifest.push_str(&format!(
+            "<item id=\"{}\" href=\"{}\" media-type=\"{}\" properties=\"cover-image\"/>",
+            image.id, image.href, image.media_type
+        ));
+    }
+    for image in images {
+        manifest.push_str(&format!(
+            "<item id=\"{}\" href=\"{}\" media-type=\"{}\"/>",
+            image.id, image.href, image.media_type
+        ));
+    }
+    let direction = if options.right_to_left { "rtl" } else { "ltr" };
+    format!(
+        concat!(
+            "<?xml version=\"1.0\" encoding=\"UTF-8\"?>\n",
+            "<package xmlns=\"http://www.idpf.org/2007/opf\" version=\"3.0\" unique-identifier=\"pub-id\">",
+            "{metadata}<manifest>{manifest}</manifest>",
+            "<spine page-progression-direction=\"{direction}\">{spine}</spine></package>"
+        ),
+        metadata = opf_meta,
+        manifest = manifest,
+        direction = direction,
+        spine = spine
+    )
+}
+
+/// Files of the fixed-layout EPUB of `pages`, as (path, data) in package
+/// order
+///
+/// `image_bytes` supplies the encoded PNG, JPEG or WebP of each image
+/// layer; layers without one are left out. `cover` is a PNG, JPEG or WebP
+/// of the cover, which Kindle requires. `toc` lists the contents, as from
+/// `sections::table_of_contents`.
+pub fn epub_entries(
+    pages: &[PageData],
+    metadata: &DocumentMetadata,
+    options: &EpubOptions,
+    toc: &[SectionInfo],
+    cover: Option<&[u8]>,
+    image_bytes: impl Fn(&LayerObject) -> Option<Vec<u8>>,
+) -> Result<Vec<(String, Vec<u8>)>, String> {
+    if pages.is_empty() {
+        return Err("No pages to export".to_string());
+    }
+    let mut entries: Vec<(String, Vec<u8>)> = vec![
+        ("mimetype".to_string(), b"application/epub+zip".to_vec()),
+        ("META-INF/container.xml".to_string(), CONTAINER_XML.as_bytes().to_vec()),
+        ("META-INF/com.apple.ibooks.display-options.xml".to_string(), APPLE_DISPLAY_OPTIONS.as_bytes().to_vec()),
+    ];
+
+    let cover = match cover {
+        Some(data) => {
+            let (extension, media_type) = image_type(data).ok_or("The cover must be a PNG, JPEG or WebP image")?;
+            let href = format!("images/cover.{}", extension);
+            entries.push((format!("OEBPS/{}", href), data.to_vec()));
+            Some(PackageImage { id: "cover-image".to_string(), href, media_type })
+        }
+        None => None,
+    };
+
+    // Images are keyed by source so a picture repeated across pages is stored once
+    let image_key = |layer: &LayerObject| {
+        layer.image_url.clone().or_else(|| layer.image_path.clone()).unwrap_or_else(|| layer.id.clone())
+    };
+    let mut images: Vec<PackageImage> = Vec::new();
+    let mut hrefs: HashMap<String, Option<String>> = HashMap::new();
+    let language = metadata.language.as_deref().filter(|l| is_language_tag(l)).unwrap_or("und");
+    for (i, page) in pages.iter().enumerate() {
+        let page = with_background(page);
+        for layer in page.layers.iter().filter(|l| l.visible && l.layer_type == LayerType::Image) {
+            let key = image_key(layer);
+            if hrefs.contains_key(&key) {
+                continue;
+            }
+            let data = image_bytes(layer);
+            let href = data.as_deref().and_then(image_type).map(|(extension, media_type)| {
+                let id = format!("image-{}", images.len() + 1);
+                let href = format!("images/{}.{}", id, extension);
+                images.push(PackageImage { id, href: href.clone(), media_type });
+                href
+            });
+            if let (Some(href), Some(data)) = (&href, data) {
+                entries.push((format!("OEBPS/{}", href), data));
+            }
+            hrefs.insert(key, href.map(|h| format!("../{}", h)));
+        }
+        let href = |layer: &LayerObject| hrefs.get(&image_key(layer)).cloned().flatten();
+        let title = format!("{} {}", metadata.title, page_label(&page, i + 1));
+        let xhtml = page_xhtml(&page, title.trim(), language, &href);
+        entries.push((format!("OEBPS/pages/page-{:03}.xhtml", i + 1), xhtml.into_bytes()));
+    }
+
+    entries.push(("OEBPS/nav.xhtml".to_string(), nav_xhtml(pages, toc, language).into_bytes()));
+    let opf = content_opf(pages, metadata, options, &images, cover.as_ref());
+    entries.push(("OEBPS/content.opf".to_string(), opf.into_bytes()));
+    Ok(entries)
+}
+
+/// Write `pages` as a fixed-layout EPUB 3 (see `epub_entries`)
+#[cfg(feature = "archive")]
+pub fn write_fixed_layout_epub(
+    pages: &[PageData],
+    metadata: &DocumentMetadata,
+    options: &EpubOptions,
+    toc: &[SectionInfo],
+    cover: Option<&[u8]>,
+    image_bytes: impl Fn(&LayerObject) -> Option<Vec<u8>>,
+) -> Result<Vec<u8>, String> {
+    use std::io::Write;
+    use zip::write::SimpleFileOptions;
+    use zip::CompressionMethod;
+
+    let mut zip = zip::ZipWriter::new(std::io::Cursor::new(Vec::new()));
+    let deflated = SimpleFileOptions::default().compression_method(CompressionMethod::Deflated);
+    // Readers identify the package by an uncompressed mimetype entry at the
+    // start; images are compressed already
+    let stored = SimpleFileOptions::default().compression_method(CompressionMethod::Stored);
+    for (path, data) in epub_entries(pages, metadata, options, toc, cover, image_bytes)? {
+        let options = if path == "mimetype" || path.starts_with("OEBPS/images/") { stored } else { deflated };
+        zip.start_file(path, options).map_err(|e| e.to_string())?;
+        zip.write_all(&data).map_err(|e| e.to_string())?;
+    }
+    Ok(zip.finish().map_err(|e| e.to_string())?.into_inner())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::clipboard::new_layer;
+    use crate::models::Bounds;
+
+    const PNG: &[u8] = &[0x89, b'P', b'N', b'G', 0x0D, 0x0A, 0x1A, 0x0A];
+
+    #[test]
+    fn test_fixed_layout_epub() {
+        let mut text = new_layer("t".to_string(), LayerType::Text, Bounds::new(40.0, 40.0, 200.0, 20.0));
+        text.content = Some("Once upon a time".to_string());
+        let mut picture = new_layer("p".to_string(), LayerType::Image, Bounds::new(0.0, 100.0, 300.0, 200.0));
+        picture.image_url = Some("image://fox".to_string());
+        let mut missing = picture.clone();
+        missing.image_url = Some("image://gone".to_string());
+        let page = |page_index: usize, layers: Vec<LayerObject>| PageData {
+            page_index,
+            width: 576.0,
+            height: 576.0,
+            dpi: None,
+            layers,
+            metadata: None,
+            background: None,
+        };
+        let pages = vec![page(0, vec![text, picture.clone()]), page(1, vec![picture, missing])];
+        let metadata = DocumentMetadata {
+            title: "The Fox".to_string(),
+            language: Some("en".to_string()),
+            modified: "2024-05-01T10:00:00.123Z".to_string(),
+            ..DocumentMetadata::default()
+        };
+        let options = EpubOptions { book_type: Some("children".to_string()), ..Default::default() };
+        let bytes = |layer: &LayerObject| (layer.image_url.as_deref() == Some("image://fox")).then(|| PNG.to_vec());
+
+        let entries = epub_entries(&pages, &metadata, &options, &[], Some(PNG), bytes).unwrap();
+        let paths: Vec<&str> = entries.iter().map(|(path, _)| path.as_str()).collect();
+        assert_eq!(paths[0], "mimetype");
+        assert_eq!(paths.iter().filter(|p| p.starts_with("OEBPS/images/")).count(), 2);
+        let entry = |name: &str| String::from_utf8(entries.iter().find(|(p, _)| p == name).unwrap().1.clone()).unwrap();
+
+        let opf = entry("OEBPS/content.opf");
+        for expected in [
+            "<meta property=\"rendition:layout\">pre-paginated</meta>",
+            "<meta name=\"original-resolution\" content=\"576x576\"/>",
+            "<meta name=\"book-type\" content=\"children\"/>",
+            "properties=\"cover-image\"",
+            "<itemref idref=\"page-001\" properties=\"page-spread-right\"/>",
+            "<itemref idref=\"page-002\" properties=\"page-spread-left\"/>",
+            "<dc:language>en</dc:language>",
+        ] {
+            assert!(opf.contains(expected), "{} missing", expected);
+        }
+        // Fractional seconds are not allowed in dcterms:modified
+        assert!(!opf.contains(".123Z"));
+
+        let first = entry("OEBPS/pages/page-001.xhtml");
+        assert!(first.contains("<meta name=\"viewport\" content=\"width=576, height=576\"/>"));
+        assert!(first.contains("Once upon a time") && first.contains("xlink:href=\"../images/image-1.png\""));
+        assert_eq!(entry("OEBPS/pages/page-002.xhtml").matches("<image").count(), 1);
+
+        let rtl = EpubOptions { right_to_left: true, ..Default::default() };
+        let rtl_entries = epub_entries(&pages, &metadata, &rtl, &[], None, |_| None).unwrap();
+        let opf = &rtl_entries.iter().find(|(p, _)| p == "OEBPS/content.opf").unwrap().1;
+        let spine = "page-progression-direction=\"rtl\"><itemref idref=\"page-001\" properties=\"page-spread-left\"/>";
+        assert!(String::from_utf8_lossy(opf).contains(spine));
+        assert!(epub_entries(&pages, &metadata, &options, &[], Some(b"GIF89a"), |_| None).is_err());
+
+        #[cfg(feature = "archive")]
+        {
+            let epub = write_fixed_layout_epub(&pages, &metadata, &options, &[], None, |_| None).unwrap();
+            // Stored mimetype entry: name at 30, content right after
+            assert_eq!(&epub[30..58], b"mimetypeapplication/epub+zip");
+        }
+    }
+}
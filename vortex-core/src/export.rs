@@ -0,0 +1,715 @@
+//! Export types shared by the desktop and web builds
+//!
+//! Formats, options and named presets are plain data so both front ends
+//! accept the same JSON; the actual PDF/DOCX writers stay platform-specific.
+
+use crate::contact_sheet::ContactSheetOptions;
+use crate::epub::EpubOptions;
+use crate::models::{
+    BookProjectData, DocumentData, DocumentMetadata, LayerObject, LayerRole, PageData, ProjectSettings, Section,
+    SourceType, TrackedChange,
+};
+use crate::notes::NoteOptions;
+use crate::page_setup::PageBox;
+use crate::speech::SpeechOptions;
+use serde::{Deserialize, Serialize};
+
+/// Export format options
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum ExportFormat {
+    Pdf,
+    Docx,
+    BookProj,
+    /// Audio proof: text in reading order for a speech engine
+    Speech,
+    /// One raster image per page
+    Images,
+    /// N-up page thumbnails for review printing
+    ContactSheet,
+    /// Fixed-layout EPUB 3, one page per spine item
+    Epub,
+}
+
+impl ExportFormat {
+    /// Format name as accepted by `export_document`
+    #[inline]
+    pub const fn as_str(&self) -> &'static str {
+        match self {
+            ExportFormat::Pdf => "pdf",
+            ExportFormat::Docx => "docx",
+            ExportFormat::BookProj => "bookproj",
+            ExportFormat::Speech => "speech",
+            ExportFormat::Images => "images",
+            ExportFormat::ContactSheet => "contactsheet",
+            ExportFormat::Epub => "epub",
+        }
+    }
+}
+
+/// Target color space for exported output
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq, Default)]
+#[serde(rename_all = "lowercase")]
+#[repr(u8)]
+pub enum ExportColorSpace {
+    #[default]
+    Rgb = 0,
+    Cmyk = 1,
+}
+
+/// Export options
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct ExportOptions {
+    pub format: ExportFormat,
+    pub output_path: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub page_range: Option<(usize, usize)>,
+    #[serde(default = "default_image_quality")]
+    pub image_quality: u8,
+    #[serde(default)]
+    pub compress_text: bool,
+    /// Group layers into PDF optional content groups (PDF only), see
+    /// `pdf_layer_name`
+    #[serde(default)]
+    pub create_layers: bool,
+    /// Target resolution for raster content
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub dpi: Option<u32>,
+    #[serde(default)]
+    pub color_space: ExportColorSpace,
+    /// Pending tracked changes carried with the document
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub changes: Vec<TrackedChange>,
+    /// Render tracked changes with review markup (insertions underlined,
+    /// deletions struck through) instead of the plain current state
+    #[serde(default)]
+    pub show_changes: bool,
+    /// Whether the project records edits as tracked changes, saved with
+    /// Book Project exports
+    #[serde(default)]
+    pub track_changes: bool,
+    /// Print bleed added around every page (PDF only), in points
+    #[serde(default)]
+    pub bleed: f32,
+    /// Source PDF box imported pages are exported at (PDF only)
+    #[serde(default)]
+    pub page_box: PageBox,
+    /// Watermark drawn across every exported page (PDF only)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub watermark: Option<Watermark>,
+    /// Header/footer stamps such as Bates numbers (PDF only)
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub stamps: Vec<Stamp>,
+    /// Password-protect the output (PDF only)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub encryption: Option<PdfEncryption>,
+    /// Digitally sign the output (PDF only)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub signature: Option<PdfSignature>,
+    /// Output, voice and chapter files (speech only)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub speech: Option<SpeechOptions>,
+    /// Layers left out of the output, by tag, role or source
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub layer_filter: Option<LayerFilter>,
+    /// File format, color mode and packaging (images only); rendered at
+    /// `dpi`, or `DEFAULT_IMAGE_DPI`
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub images: Option<ImageSequenceOptions>,
+    /// Grid and captions (contact sheet only); thumbnails are rendered at
+    /// `dpi`, or `DEFAULT_IMAGE_DPI`
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub contact_sheet: Option<ContactSheetOptions>,
+    /// Rendition and reader settings (EPUB only)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub epub: Option<EpubOptions>,
+    /// Footnote numbering and note layout; DOCX keeps its own
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub notes: Option<NoteOptions>,
+    /// The project's sections, for running heads, the PDF outline and EPUB
+    /// contents
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub sections: Vec<Section>,
+    /// One file per section, numbered after the output name (PDF, DOCX and
+    /// EPUB); the page range is ignored
+    #[serde(default)]
+    pub split_sections: bool,
+}
+
+/// Resolution of image exports without a `dpi`
+pub const DEFAULT_IMAGE_DPI: u32 = 150;
+
+/// File format of exported page images
+#[derive(Debug, Clone, Copy, Default, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum ImageFileFormat {
+    #[default]
+    Png,
+    Jpeg,
+    /// LZW-compressed, as print shops expect
+    Tiff,
+}
+
+impl ImageFileFormat {
+    #[inline]
+    pub const fn extension(&self) -> &'static str {
+        match self {
+            ImageFileFormat::Png => "png",
+            ImageFileFormat::Jpeg => "jpg",
+            ImageFileFormat::Tiff => "tif",
+        }
+    }
+}
+
+/// Pixel format of exported page images
+#[derive(Debug, Clone, Copy, Default, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum RasterColorMode {
+    #[default]
+    Rgb,
+    Grayscale,
+}
+
+/// How an image sequence export writes its pages
+#[derive(Debug, Clone, Copy, Default, Serialize, Deserialize, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct ImageSequenceOptions {
+    #[serde(default)]
+    pub format: ImageFileFormat,
+    #[serde(default)]
+    pub color_mode: RasterColorMode,
+    /// Pack the numbered files into one zip archive at the output path
+    #[serde(default)]
+    pub zip: bool,
+}
+
+/// Name of the `number`-th (1-based) of `total` page images: the output
+/// file's stem with the page number zero-padded to at least three digits
+pub fn sequence_file_name(stem: &str, number: usize, total: usize, format: ImageFileFormat) -> String {
+    let digits = total.to_string().len().max(3);
+    format!("{}-{:0digits$}.{}", stem, number, format.extension(), digits = digits)
+}
+
+/// Tag of layers only the print output shows, e.g. crop marks or a barcode
+pub const TAG_PRINT_ONLY: &str = "print-only";
+/// Tag of layers only screen output shows, e.g. link buttons
+pub const TAG_SCREEN_ONLY: &str = "screen-only";
+/// Tag of internal notes no output shows
+pub const TAG_NOTES: &str = "notes";
+
+/// Which layers an export keeps
+///
+/// A layer is kept when it matches every non-empty include list and none of
+/// the exclude lists. Tags compare case-insensitively.
+#[derive(Debug, Clone, Default, Serialize, Deserialize, PartialEq)]
+#[serde(rename_all = "camelCase", default)]
+pub struct LayerFilter {
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub include_tags: Vec<String>,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub exclude_tags: Vec<String>,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub include_roles: Vec<LayerRole>,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub exclude_roles: Vec<LayerRole>,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub include_sources: Vec<SourceType>,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub exclude_sources: Vec<SourceType>,
+}
+
+impl LayerFilter {
+    /// Final print output: no screen-only layers or notes
+    pub fn print() -> Self {
+        Self { exclude_tags: vec![TAG_SCREEN_ONLY.to_string(), TAG_NOTES.to_string()], ..Default::default() }
+    }
+
+    /// On-screen output: no print-only layers or notes
+    pub fn screen() -> Self {
+        Self { exclude_tags: vec![TAG_PRINT_ONLY.to_string(), TAG_NOTES.to_string()], ..Default::default() }
+    }
+
+    pub fn keeps(&self, layer: &LayerObject) -> bool {
+        let tagged = |tags: &[String]| tags.iter().any(|t| layer.tags.iter().any(|lt| lt.eq_ignore_ascii_case(t)));
+        (self.include_tags.is_empty() || tagged(&self.include_tags))
+            && !tagged(&self.exclude_tags)
+            && (self.include_roles.is_empty() || self.include_roles.contains(&layer.role))
+            && !self.exclude_roles.contains(&layer.role)
+            && (self.include_sources.is_empty() || self.include_sources.contains(&layer.source_type))
+            && !self.exclude_sources.contains(&layer.source_type)
+    }
+
+    /// Copies of `pages` without the layers the filter leaves out
+    pub fn apply(&self, pages: &[PageData]) -> Vec<PageData> {
+        pages
+            .iter()
+            .map(|page| PageData {
+                layers: page.layers.iter().filter(|l| self.keeps(l)).cloned().collect(),
+                ..page.clone()
+            })
+            .collect()
+    }
+}
+
+/// AES-256 password protection for exported PDFs
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct PdfEncryption {
+    /// Required to open the file; when empty the file opens without a
+    /// prompt but permissions are still enforced
+    #[serde(default)]
+    pub user_password: String,
+    /// Lifts all permission restrictions
+    pub owner_password: String,
+    #[serde(default)]
+    pub permissions: PdfPermissions,
+}
+
+/// What a reader may do without the owner password
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "camelCase", default)]
+pub struct PdfPermissions {
+    pub print: bool,
+    /// Print at full resolution rather than a degraded copy
+    pub print_high_quality: bool,
+    pub copy: bool,
+    pub modify: bool,
+    pub annotate: bool,
+    pub fill_forms: bool,
+    /// Insert, rotate or delete pages
+    pub assemble: bool,
+}
+
+impl Default for PdfPermissions {
+    fn default() -> Self {
+        Self {
+            print: true,
+            print_high_quality: true,
+            copy: true,
+            modify: true,
+            annotate: true,
+            fill_forms: true,
+            assemble: true,
+        }
+    }
+}
+
+/// Digital signature from a PKCS#12 certificate
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct PdfSignature {
+    /// Path to a .p12/.pfx file with the signing key and certificate
+    pub certificate_path: String,
+    #[serde(default)]
+    pub password: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub reason: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub location: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub contact_info: Option<String>,
+}
+
+/// Text or logo stamped diagonally across every page at export
+///
+/// Drawn with a multiply blend so page content shows through; the project
+/// itself is never modified.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct Watermark {
+    /// Watermark text, e.g. "DRAFT"
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub text: Option<String>,
+    /// Cached image id of a logo; drawn beneath the text when both are set
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub image_id: Option<String>,
+    /// Hex color of the text
+    #[serde(default = "default_watermark_color")]
+    pub color: String,
+    /// Strength from 0 (invisible) to 1 (solid)
+    #[serde(default = "default_watermark_opacity")]
+    pub opacity: f32,
+    /// Counter-clockwise angle in degrees; the page diagonal when absent
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub angle: Option<f32>,
+    /// Length of the stamp as a fraction of the page diagonal
+    #[serde(default = "default_watermark_scale")]
+    pub scale: f32,
+}
+
+fn default_watermark_color() -> String {
+    "#808080".to_string()
+}
+
+fn default_watermark_opacity() -> f32 {
+    0.3
+}
+
+fn default_watermark_scale() -> f32 {
+    0.7
+}
+
+/// Where a stamp sits on the page
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq, Default)]
+#[serde(rename_all = "kebab-case")]
+#[repr(u8)]
+pub enum StampPosition {
+    TopLeft = 0,
+    TopCenter = 1,
+    TopRight = 2,
+    BottomLeft = 3,
+    BottomCenter = 4,
+    #[default]
+    BottomRight = 5,
+}
+
+/// A line of text stamped on every exported page
+///
+/// `text` may contain `{page}` (1-based page number), `{total}` (pages
+/// exported) and `{n}` (a running number starting at `start_number`,
+/// zero-padded to `digits`), so `"ACME{n}"` gives Bates numbers like
+/// ACME000001.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct Stamp {
+    pub text: String,
+    #[serde(default)]
+    pub position: StampPosition,
+    #[serde(default = "default_stamp_font_size")]
+    pub font_size: f32,
+    #[serde(default = "default_stamp_color")]
+    pub color: String,
+    /// Distance from the page edges, in points
+    #[serde(default = "default_stamp_margin")]
+    pub margin: f32,
+    #[serde(default = "default_stamp_start")]
+    pub start_number: u32,
+    #[serde(default = "default_stamp_digits")]
+    pub digits: u8,
+}
+
+fn default_stamp_font_size() -> f32 {
+    10.0
+}
+
+fn default_stamp_color() -> String {
+    "#000000".to_string()
+}
+
+fn default_stamp_margin() -> f32 {
+    18.0
+}
+
+fn default_stamp_start() -> u32 {
+    1
+}
+
+fn default_stamp_digits() -> u8 {
+    6
+}
+
+impl Stamp {
+    /// Stamp text for the `index`-th of `total` exported pages
+    pub fn text_for(&self, index: usize, total: usize) -> String {
+        let n = self.start_number as usize + index;
+        self.text
+            .replace("{page}", &(index + 1).to_string())
+            .replace("{total}", &total.to_string())
+            .replace("{n}", &format!("{:0width$}", n, width = self.digits as usize))
+    }
+}
+
+fn default_image_quality() -> u8 {
+    100
+}
+
+/// A named, reusable set of export options
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct ExportPreset {
+    pub name: String,
+    pub format: ExportFormat,
+    /// Raster resolution for image exports and the image resolution
+    /// preflight asks for; PDF exports embed images as they are
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub dpi: Option<u32>,
+    #[serde(default)]
+    pub color_space: ExportColorSpace,
+    #[serde(default = "default_image_quality")]
+    pub image_quality: u8,
+    #[serde(default)]
+    pub compress_text: bool,
+    #[serde(default)]
+    pub create_layers: bool,
+    #[serde(default)]
+    pub show_changes: bool,
+    #[serde(default)]
+    pub bleed: f32,
+    /// Set on presets shipped with the app (not persisted)
+    #[serde(default, skip_deserializing)]
+    pub builtin: bool,
+}
+
+impl ExportPreset {
+    /// Expand into full export options for a destination file
+    pub fn to_options(&self, output_path: String, changes: Vec<TrackedChange>) -> ExportOptions {
+        ExportOptions {
+            format: self.format.clone(),
+            output_path,
+            page_range: None,
+            image_quality: self.image_quality,
+            compress_text: self.compress_text,
+            create_layers: self.create_layers,
+            dpi: self.dpi,
+            color_space: self.color_space,
+            changes,
+            show_changes: self.show_changes,
+            track_changes: false,
+            bleed: self.bleed,
+            page_box: PageBox::default(),
+            watermark: None,
+            stamps: Vec::new(),
+            encryption: None,
+            signature: None,
+            speech: None,
+            layer_filter: None,
+            images: None,
+            contact_sheet: None,
+            epub: None,
+            notes: None,
+            sections: Vec::new(),
+            split_sections: false,
+        }
+    }
+}
+
+/// PDF layer (optional content group) a layer is exported into: its first
+/// tag, or else its role, e.g. "Background" or "Annotations"
+pub fn pdf_layer_name(layer: &LayerObject) -> String {
+    if let Some(tag) = layer.tags.first() {
+        return tag.clone();
+    }
+    match layer.role {
+        LayerRole::Background => "Background",
+        LayerRole::Content => "Content",
+        LayerRole::Header => "Header",
+        LayerRole::Footer => "Footer",
+        LayerRole::Annotation => "Annotations",
+    }
+    .to_string()
+}
+
+/// One output of an export pipeline
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct ExportTarget {
+    /// Label such as "Interior" or "Cover"
+    pub name: String,
+    /// Format, file, page range and specs of this output
+    pub options: ExportOptions,
+}
+
+/// Several outputs from one document in a single run, e.g. the interior and
+/// the cover for a print-on-demand service, each with its own options
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct ExportPipeline {
+    pub name: String,
+    pub targets: Vec<ExportTarget>,
+}
+
+impl ExportPipeline {
+    /// Check the targets against a document of `page_count` pages: page
+    /// ranges in bounds, no two targets writing the same file
+    pub fn validate(&self, page_count: usize) -> Result<(), String> {
+        if self.targets.is_empty() {
+            return Err(format!("Export pipeline '{}' has no targets", self.name));
+        }
+        for (i, target) in self.targets.iter().enumerate() {
+            if let Some((start, end)) = target.options.page_range {
+                if start > end || end >= page_count {
+                    return Err(format!(
+                        "Target '{}': range {}-{} is invalid for {} pages",
+                        target.name, start, end, page_count
+                    ));
+                }
+            }
+            let path = &target.options.output_path;
+            if self.targets[..i].iter().any(|t| &t.options.output_path == path) {
+                return Err(format!("Target '{}' writes {} like an earlier target", target.name, path));
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Presets shipped with the app
+pub fn builtin_presets() -> Vec<ExportPreset> {
+    let preset = |name: &str, format, dpi, color_space, image_quality, compress_text| ExportPreset {
+        name: name.to_string(),
+        format,
+        dpi,
+        color_space,
+        image_quality,
+        compress_text,
+        create_layers: false,
+        show_changes: false,
+        bleed: 0.0,
+        builtin: true,
+    };
+    vec![
+        preset("Print PDF CMYK", ExportFormat::Pdf, Some(300), ExportColorSpace::Cmyk, 100, false),
+        preset("Web PDF RGB", ExportFormat::Pdf, Some(96), ExportColorSpace::Rgb, 75, true),
+        preset("Editable DOCX", ExportFormat::Docx, None, ExportColorSpace::Rgb, 100, false),
+        preset("Book Project", ExportFormat::BookProj, None, ExportColorSpace::Rgb, 100, false),
+    ]
+}
+
+/// Assemble a BookProject from exported pages
+///
+/// Page size comes from the first page (US Letter when empty); pending
+/// changes are carried along whether or not `track_changes` is on.
+pub fn build_project(
+    pages: &[PageData],
+    metadata: &DocumentMetadata,
+    changes: Vec<TrackedChange>,
+    track_changes: bool,
+    sections: Vec<Section>,
+) -> BookProjectData {
+    // Built field by field: `BookProjectData::default()` reads the system
+    // clock, which is unavailable on wasm32
+    BookProjectData {
+        format: "bookproj".to_string(),
+        version: "1.0.0".to_string(),
+        metadata: metadata.clone(),
+        document: DocumentData::new(
+            pages.first().map(|p| p.width).unwrap_or(612.0),
+            pages.first().map(|p| p.height).unwrap_or(792.0),
+            pages.to_vec(),
+        ),
+        settings: ProjectSettings {
+            track_changes,
+            ..ProjectSettings::default()
+        },
+        changes,
+        sections,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_util;
+
+    #[test]
+    fn test_build_project_uses_first_page_size() {
+        let metadata = DocumentMetadata {
+            title: "Book".to_string(),
+            author: String::new(),
+            created: String::new(),
+            modified: String::new(),
+            description: None,
+            subject: None,
+            keywords: Vec::new(),
+            isbn: None,
+            publisher: None,
+            language: None,
+            custom: Default::default(),
+        };
+        let empty = build_project(&[], &metadata, Vec::new(), false, Vec::new());
+        assert_eq!((empty.document.page_width, empty.document.page_height), (612.0, 792.0));
+        assert!(!empty.settings.track_changes);
+
+        let page = PageData {
+            page_index: 0,
+            width: 420.0,
+            height: 595.0,
+            dpi: None,
+            layers: Vec::new(),
+            metadata: None,
+            background: None,
+        };
+        let project = build_project(&[page], &metadata, Vec::new(), true, Vec::new());
+        assert_eq!(project.format, "bookproj");
+        assert_eq!((project.document.page_width, project.document.page_height), (420.0, 595.0));
+        assert_eq!(project.metadata, metadata);
+        // The mode is kept with no changes pending
+        assert!(project.settings.track_changes);
+    }
+
+    #[test]
+    fn test_layer_filter_and_pdf_layers() {
+        let layer = |id: &str, tags: &[&str], role: &str| {
+            test_util::layer(id, "text").bounds(0.0, 0.0, 10.0, 10.0).fields(serde_json::json!({ "role": role, "tags": tags })).build()
+        };
+        let page = test_util::page(
+            0,
+            vec![
+                layer("body", &[], "content"),
+                layer("note", &["Notes"], "annotation"),
+                layer("marks", &["print-only"], "content"),
+                layer("folio", &[], "footer"),
+            ],
+        );
+        let kept = |filter: &LayerFilter| -> Vec<String> {
+            filter.apply(std::slice::from_ref(&page))[0].layers.iter().map(|l| l.id.clone()).collect()
+        };
+
+        assert_eq!(kept(&LayerFilter::print()), ["body", "marks", "folio"]);
+        assert_eq!(kept(&LayerFilter::screen()), ["body", "folio"]);
+        let no_footers = LayerFilter { exclude_roles: vec![LayerRole::Footer], ..LayerFilter::print() };
+        assert_eq!(kept(&no_footers), ["body", "marks"]);
+        let only_tagged = LayerFilter { include_tags: vec!["print-only".to_string()], ..Default::default() };
+        assert_eq!(kept(&only_tagged), ["marks"]);
+
+        let names: Vec<String> = page.layers.iter().map(pdf_layer_name).collect();
+        assert_eq!(names, ["Content", "Notes", "print-only", "Footer"]);
+    }
+
+    #[test]
+    fn test_export_pipeline_validate() {
+        let pipeline: ExportPipeline = serde_json::from_value(serde_json::json!({
+            "name": "Print on demand",
+            "targets": [
+                { "name": "Interior", "options": { "format": "pdf", "outputPath": "interior.pdf", "pageRange": [1, 9] } },
+                { "name": "Cover", "options": {
+                    "format": "pdf", "outputPath": "cover.pdf", "pageRange": [0, 0], "bleed": 9, "colorSpace": "cmyk"
+                } }
+            ]
+        }))
+        .unwrap();
+        assert!(pipeline.validate(10).is_ok());
+        assert!(pipeline.validate(9).unwrap_err().contains("Interior"));
+
+        let mut clash = pipeline.clone();
+        clash.targets[1].options.output_path = "interior.pdf".to_string();
+        assert!(clash.validate(10).unwrap_err().contains("Cover"));
+        assert!(ExportPipeline { name: "Empty".to_string(), targets: Vec::new() }.validate(10).is_err());
+    }
+
+    #[test]
+    fn test_export_options_defaults() {
+        let options: ExportOptions =
+            serde_json::from_str(r#"{"format":"bookproj","outputPath":"/tmp/book.bookproj"}"#).unwrap();
+        assert_eq!(options.format, ExportFormat::BookProj);
+        assert_eq!(options.image_quality, 100);
+        assert_eq!(options.color_space, ExportColorSpace::Rgb);
+        assert!(options.changes.is_empty());
+    }
+
+    #[test]
+    fn test_image_sequence_options() {
+        let options: ExportOptions = serde_json::from_value(serde_json::json!({
+            "format": "images",
+            "outputPath": "/tmp/book.zip",
+            "images": { "format": "tiff", "colorMode": "grayscale", "zip": true }
+        }))
+        .unwrap();
+        let images = options.images.unwrap();
+        assert_eq!(options.format, ExportFormat::Images);
+        assert_eq!((images.format, images.color_mode), (ImageFileFormat::Tiff, RasterColorMode::Grayscale));
+        assert_eq!(sequence_file_name("book", 7, 12, ImageFileFormat::Tiff), "book-007.tif");
+        assert_eq!(sequence_file_name("book", 42, 1200, ImageFileFormat::Jpeg), "book-0042.jpg");
+    }
+}
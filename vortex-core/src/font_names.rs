@@ -0,0 +1,335 @@
+//! Font name normalization and matching
+//!
+//! Cleans raw font names ("ABCDEF+Arial-BoldMT") into family, weight and
+//! style, and matches them against a list of family names. The desktop app
+//! matches against system and Google Fonts; the browser build against the
+//! fonts the page supplies, as browsers have no font enumeration.
+
+use serde::{Deserialize, Serialize};
+use std::borrow::Cow;
+
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq, Default)]
+#[serde(rename_all = "lowercase")]
+#[repr(u8)]
+pub enum FontWidth {
+    UltraCondensed = 1,
+    ExtraCondensed = 2,
+    Condensed = 3,
+    SemiCondensed = 4,
+    #[default]
+    Normal = 5,
+    SemiExpanded = 6,
+    Expanded = 7,
+    ExtraExpanded = 8,
+    UltraExpanded = 9,
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct ParsedFontName {
+    pub family: String,
+    pub weight: u16,
+    pub is_italic: bool,
+    pub is_bold: bool,
+    pub width: FontWidth,
+    pub original: String,
+}
+
+/// Parse a raw font name into structured components
+pub fn parse_font_name(raw: &str) -> ParsedFontName {
+    let original = raw.to_string();
+
+    // Remove PDF subset prefix (e.g., "ABCDEF+FontName" -> "FontName")
+    let name = remove_subset_prefix(raw);
+
+    let (family, weight, is_bold) = extract_weight(&name);
+    let (family, is_italic) = extract_italic(&family);
+    let (family, width) = extract_width(&family);
+    let family = clean_family_name(&family);
+
+    ParsedFontName { family, weight, is_italic, is_bold, width, original }
+}
+
+/// Remove PDF subset prefix (6 uppercase letters + plus sign)
+/// Zero-copy when no prefix exists
+pub fn remove_subset_prefix(name: &str) -> Cow<'_, str> {
+    if let Some(pos) = name.find('+') {
+        if pos == 6 && name[..pos].chars().all(|c| c.is_ascii_uppercase()) {
+            return Cow::Owned(name[pos + 1..].to_string());
+        }
+    }
+    Cow::Borrowed(name)
+}
+
+fn extract_weight(name: &str) -> (String, u16, bool) {
+    let lower = name.to_lowercase();
+    let patterns = [
+        ("ultrathin", 50), ("hairline", 100), ("thin", 100),
+        ("extralight", 200), ("ultralight", 200),
+        ("light", 300), ("semilight", 350),
+        ("regular", 400), ("normal", 400), ("book", 400),
+        ("medium", 500),
+        ("semibold", 600), ("demibold", 600), ("demi", 600),
+        ("bold", 700),
+        ("extrabold", 800), ("ultrabold", 800), ("heavy", 800),
+        ("black", 900), ("extrablack", 950), ("ultrablack", 950),
+    ];
+
+    for (pattern, weight) in patterns {
+        if lower.contains(pattern) {
+            let re = regex_lite::Regex::new(&format!(r"(?i)[-_]?{}[-_]?", pattern)).unwrap();
+            let cleaned = re.replace_all(name, "").to_string();
+            return (cleaned.trim().to_string(), weight, weight >= 700);
+        }
+    }
+
+    (name.trim().to_string(), 400, false)
+}
+
+fn extract_italic(name: &str) -> (String, bool) {
+    let lower = name.to_lowercase();
+    if lower.contains("italic") || lower.contains("oblique") || lower.contains("ital") {
+        let re = regex_lite::Regex::new(r"(?i)[-_]?(italic|oblique|ital)[-_]?").unwrap();
+        (re.replace_all(name, "").trim().to_string(), true)
+    } else {
+        (name.to_string(), false)
+    }
+}
+
+fn extract_width(name: &str) -> (String, FontWidth) {
+    let lower = name.to_lowercase();
+    let patterns = [
+        ("ultracondensed", FontWidth::UltraCondensed),
+        ("extracondensed", FontWidth::ExtraCondensed),
+        ("semicondensed", FontWidth::SemiCondensed),
+        ("condensed", FontWidth::Condensed),
+        ("narrow", FontWidth::Condensed),
+        ("compressed", FontWidth::Condensed),
+        ("semiexpanded", FontWidth::SemiExpanded),
+        ("extraexpanded", FontWidth::ExtraExpanded),
+        ("ultraexpanded", FontWidth::UltraExpanded),
+        ("expanded", FontWidth::Expanded),
+        ("wide", FontWidth::Expanded),
+    ];
+
+    for (pattern, width) in patterns {
+        if lower.contains(pattern) {
+            let re = regex_lite::Regex::new(&format!(r"(?i)[-_]?{}[-_]?", pattern)).unwrap();
+            return (re.replace_all(name, "").trim().to_string(), width);
+        }
+    }
+
+    (name.to_string(), FontWidth::Normal)
+}
+
+fn clean_family_name(name: &str) -> String {
+    let mut cleaned = name.to_string();
+
+    // Remove common suffixes
+    for suffix in ["MT", "PS", "Std", "Pro", "LT", "EF", "ITC", "BT", "Com"] {
+        if cleaned.ends_with(suffix) {
+            cleaned = cleaned[..cleaned.len() - suffix.len()].trim_end_matches('-').to_string();
+        }
+    }
+
+    // Remove version numbers
+    let re = regex_lite::Regex::new(r"[-_]?v?\d+(\.\d+)*$").unwrap();
+    cleaned = re.replace_all(&cleaned, "").to_string();
+
+    // Normalize spacing
+    cleaned = cleaned.replace(['-', '_'], " ");
+    let re = regex_lite::Regex::new(r"\s+").unwrap();
+    cleaned = re.replace_all(&cleaned, " ").trim().to_string();
+
+    // Title case
+    cleaned
+        .split_whitespace()
+        .map(|word| {
+            let mut chars = word.chars();
+            match chars.next() {
+                None => String::new(),
+                Some(c) => c.to_uppercase().chain(chars).collect(),
+            }
+        })
+        .collect::<Vec<_>>()
+        .join(" ")
+}
+
+/// Canonical family name for a raw font name
+pub fn get_canonical_name(name: &str) -> String {
+    parse_font_name(name).family
+}
+
+/// Normalize font name for comparison
+pub fn normalize_for_comparison(name: &str) -> String {
+    parse_font_name(name).family.to_lowercase().replace(' ', "")
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct FontMatch {
+    pub family: String,
+    /// 1.0 for exact matches, 0.3 for generic fallbacks
+    pub confidence: f32,
+    pub css_family: String,
+    pub fallback_stack: Vec<String>,
+}
+
+/// Find the best match for a raw font name among `available` families
+///
+/// Exact family match first, then fuzzy (similarity >= `threshold`), then a
+/// generic fallback guessed from the name.
+pub fn match_font(query: &str, available: &[String], threshold: f32) -> FontMatch {
+    if let Some((id, confidence)) = find_family(query, available.iter().map(String::as_str), threshold) {
+        let family = &available[id];
+        return FontMatch {
+            family: family.clone(),
+            confidence,
+            css_family: format!("'{}'", family),
+            fallback_stack: fallback_stack(family),
+        };
+    }
+
+    let (fallback, category) = guess_font_category(&parse_font_name(query).family);
+    FontMatch {
+        family: fallback.to_string(),
+        confidence: 0.3,
+        css_family: generic_css_stack(category).to_string(),
+        fallback_stack: vec![fallback.to_string()],
+    }
+}
+
+/// Index and confidence of the family in `families` best matching a raw
+/// font name: an exact family match (1.0), else the most similar one
+/// scoring at least `threshold`
+pub fn find_family<'a>(
+    query: &str,
+    families: impl Iterator<Item = &'a str> + Clone,
+    threshold: f32,
+) -> Option<(usize, f32)> {
+    let family_lower = parse_font_name(query).family.to_lowercase();
+    if let Some(id) = families.clone().position(|f| f.to_lowercase() == family_lower) {
+        return Some((id, 1.0));
+    }
+
+    let query_normalized = normalize_for_comparison(query);
+    let mut best: Option<(usize, f32)> = None;
+    for (id, family) in families.enumerate() {
+        let similarity = calculate_similarity(&query_normalized, &normalize_for_comparison(family));
+        if similarity >= threshold && best.map_or(true, |(_, score)| similarity > score) {
+            best = Some((id, similarity));
+        }
+    }
+    best
+}
+
+/// Calculate string similarity using Levenshtein distance
+pub fn calculate_similarity(a: &str, b: &str) -> f32 {
+    if a == b {
+        return 1.0;
+    }
+    if a.is_empty() || b.is_empty() {
+        return 0.0;
+    }
+
+    let distance = levenshtein_distance(a, b);
+    let max_len = a.len().max(b.len()) as f32;
+
+    1.0 - (distance as f32 / max_len)
+}
+
+/// Single-row Levenshtein distance (O(min(m,n)) space)
+fn levenshtein_distance(a: &str, b: &str) -> usize {
+    let a_chars: Vec<char> = a.chars().collect();
+    let b_chars: Vec<char> = b.chars().collect();
+    let (shorter, longer) = if a_chars.len() <= b_chars.len() { (&a_chars, &b_chars) } else { (&b_chars, &a_chars) };
+    let m = shorter.len();
+
+    let mut prev_row: Vec<usize> = (0..=m).collect();
+    for (j, long_char) in longer.iter().enumerate() {
+        let mut prev_diag = prev_row[0];
+        prev_row[0] = j + 1;
+        for i in 1..=m {
+            let old_diag = prev_row[i];
+            let cost = usize::from(shorter[i - 1] != *long_char);
+            prev_row[i] = (prev_row[i] + 1).min(prev_row[i - 1] + 1).min(prev_diag + cost);
+            prev_diag = old_diag;
+        }
+    }
+
+    prev_row[m]
+}
+
+/// Stand-in family and generic category guessed from a family name
+pub fn guess_font_category(name: &str) -> (&'static str, &'static str) {
+    let lower = name.to_lowercase();
+
+    if lower.contains("mono") || lower.contains("code") || lower.contains("console") || lower.contains("courier") {
+        ("Courier New", "monospace")
+    } else if lower.contains("serif") && !lower.contains("sans") {
+        ("Georgia", "serif")
+    } else if lower.contains("script") || lower.contains("cursive") || lower.contains("hand") {
+        ("Georgia", "cursive")
+    } else if lower.contains("display") || lower.contains("decorative") {
+        ("Impact", "display")
+    } else {
+        ("Arial", "sans-serif")
+    }
+}
+
+/// `family` followed by fallbacks for the category guessed from its name
+pub fn fallback_stack(family: &str) -> Vec<String> {
+    fallback_stack_with_category(family, guess_font_category(family).1)
+}
+
+/// `family` followed by fallbacks for `category`
+pub fn fallback_stack_with_category(family: &str, category: &str) -> Vec<String> {
+    let mut stack = vec![family.to_string()];
+    match category {
+        "serif" => stack.extend(["Georgia", "Times New Roman", "serif"].map(String::from)),
+        "monospace" => stack.extend(["Consolas", "Courier New", "monospace"].map(String::from)),
+        "cursive" | "handwriting" => stack.extend(["Georgia", "cursive"].map(String::from)),
+        "display" => stack.extend(["Impact", "Arial Black", "sans-serif"].map(String::from)),
+        _ => stack.extend(["Helvetica", "Arial", "sans-serif"].map(String::from)),
+    }
+    stack
+}
+
+/// CSS font stack for a generic category
+pub fn generic_css_stack(category: &str) -> &'static str {
+    match category {
+        "serif" => "Georgia, 'Times New Roman', Times, serif",
+        "monospace" => "'Courier New', Consolas, monospace",
+        "cursive" => "Georgia, cursive",
+        "display" => "Impact, 'Arial Black', sans-serif",
+        _ => "Arial, Helvetica, sans-serif",
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_font_name() {
+        let parsed = parse_font_name("ABCDEF+Arial-BoldItalicMT");
+        assert_eq!(parsed.family, "Arial");
+        assert_eq!(parsed.weight, 700);
+        assert!(parsed.is_bold && parsed.is_italic);
+        assert_eq!(parse_font_name("Helvetica-Condensed").width, FontWidth::Condensed);
+        assert_eq!(normalize_for_comparison("Times-New_Roman"), "timesnewroman");
+    }
+
+    #[test]
+    fn test_match_font() {
+        let available = vec!["Georgia".to_string(), "Open Sans".to_string(), "Roboto".to_string()];
+        let exact = match_font("XYZABC+Roboto-Bold", &available, 0.8);
+        assert_eq!((exact.family.as_str(), exact.confidence), ("Roboto", 1.0));
+        let fuzzy = match_font("OpenSans", &available, 0.8);
+        assert_eq!(fuzzy.family, "Open Sans");
+        let fallback = match_font("CourierPrime", &available, 0.8);
+        assert_eq!((fallback.family.as_str(), fallback.confidence), ("Courier New", 0.3));
+        assert_eq!(levenshtein_distance("kitten", "sitting"), 3);
+        assert_eq!(levenshtein_distance("", "abc"), 3);
+    }
+}
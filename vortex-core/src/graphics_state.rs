@@ -1,7 +1,7 @@
 //! Graphics State Module
 //! Manages PDF graphics state stack
 
-use crate::models::TransformMatrix;
+use crate::models::{BlendMode, TransformMatrix};
 
 /// Graphics state for tracking transforms, colors, fonts
 #[derive(Clone, Debug)]
@@ -18,6 +18,12 @@ pub struct GraphicsState {
     pub word_spacing: f32,
     pub text_rise: f32,
     pub leading: f32,
+    /// Constant alpha for fills (`ca`) and strokes (`CA`)
+    pub fill_alpha: f32,
+    pub stroke_alpha: f32,
+    /// Uniform alpha of the active soft mask (1.0 when none or not evaluable)
+    pub soft_mask: f32,
+    pub blend_mode: BlendMode,
 }
 
 impl Default for GraphicsState {
@@ -35,10 +41,30 @@ impl Default for GraphicsState {
             word_spacing: 0.0,
             text_rise: 0.0,
             leading: 0.0,
+            fill_alpha: 1.0,
+            stroke_alpha: 1.0,
+            soft_mask: 1.0,
+            blend_mode: BlendMode::Normal,
         }
     }
 }
 
+impl GraphicsState {
+    /// Fill color with the constant alpha and soft mask applied
+    #[inline]
+    pub fn effective_fill(&self) -> [f32; 4] {
+        let [r, g, b, a] = self.fill_color;
+        [r, g, b, a * self.fill_alpha * self.soft_mask]
+    }
+
+    /// Stroke color with the constant alpha and soft mask applied
+    #[inline]
+    pub fn effective_stroke(&self) -> [f32; 4] {
+        let [r, g, b, a] = self.stroke_color;
+        [r, g, b, a * self.stroke_alpha * self.soft_mask]
+    }
+}
+
 /// CMYK to RGB conversion
 pub fn cmyk_to_rgb(c: f32, m: f32, y: f32, k: f32) -> (f32, f32, f32) {
     ((1.0 - c) * (1.0 - k), (1.0 - m) * (1.0 - k), (1.0 - y) * (1.0 - k))
@@ -0,0 +1,109 @@
+//! Automatic cropping of image margins
+//!
+//! Images extracted from PDFs often carry wide white borders baked into the
+//! bitmap. `detect_margins` finds the uniform rows and columns around the
+//! content; `crop_layer` shrinks the layer's bounds by the same amount, so
+//! the page looks unchanged while the image itself is tight.
+
+use crate::models::{Bounds, LayerObject};
+use serde::{Deserialize, Serialize};
+
+/// Default per-channel difference still counted as the margin color
+pub const DEFAULT_CROP_TOLERANCE: u8 = 8;
+
+/// Pixel rows and columns to remove from each side
+#[derive(Debug, Clone, Copy, Default, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub struct CropMargins {
+    pub left: u32,
+    pub top: u32,
+    pub right: u32,
+    pub bottom: u32,
+}
+
+impl CropMargins {
+    #[inline]
+    pub fn is_empty(&self) -> bool {
+        self.left == 0 && self.top == 0 && self.right == 0 && self.bottom == 0
+    }
+}
+
+/// Uniform margins of an RGBA bitmap
+///
+/// The margin color is taken from the top-left pixel; rows and columns whose
+/// every pixel is within `tolerance` of it (per channel, alpha included) are
+/// margin. `None` when nothing differs from the margin color.
+pub fn detect_margins(rgba: &[u8], width: u32, height: u32, tolerance: u8) -> Option<CropMargins> {
+    let (w, h) = (width as usize, height as usize);
+    if w == 0 || h == 0 || rgba.len() < w * h * 4 {
+        return None;
+    }
+    let background = &rgba[..4];
+    let content = |x: usize, y: usize| {
+        let i = (y * w + x) * 4;
+        rgba[i..i + 4].iter().zip(background).any(|(&a, &b)| a.abs_diff(b) > tolerance)
+    };
+    let row_blank = |y: usize| (0..w).all(|x| !content(x, y));
+    let col_blank = |x: usize, mut rows: std::ops::Range<usize>| rows.all(|y| !content(x, y));
+
+    let top = (0..h).find(|&y| !row_blank(y))?;
+    let bottom = (0..h).rev().find(|&y| !row_blank(y))?;
+    let left = (0..w).find(|&x| !col_blank(x, top..bottom + 1))?;
+    let right = (0..w).rev().find(|&x| !col_blank(x, top..bottom + 1))?;
+    Some(CropMargins {
+        left: left as u32,
+        top: top as u32,
+        right: (w - 1 - right) as u32,
+        bottom: (h - 1 - bottom) as u32,
+    })
+}
+
+/// `layer` showing only the part of its `width` x `height` image inside
+/// `margins`: bounds shrink by the margins' share, pixel size follows
+pub fn crop_layer(layer: &LayerObject, width: u32, height: u32, margins: &CropMargins) -> LayerObject {
+    let (sx, sy) = (layer.bounds.width / width as f32, layer.bounds.height / height as f32);
+    let (cropped_w, cropped_h) = (width - margins.left - margins.right, height - margins.top - margins.bottom);
+    let mut cropped = layer.clone();
+    cropped.bounds = Bounds::new(
+        layer.bounds.x + margins.left as f32 * sx,
+        layer.bounds.y + margins.top as f32 * sy,
+        cropped_w as f32 * sx,
+        cropped_h as f32 * sy,
+    );
+    if let Some(metadata) = cropped.image_data.as_mut() {
+        (metadata.width, metadata.height) = (cropped_w, cropped_h);
+    }
+    cropped
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::clipboard::new_layer;
+    use crate::models::{ImageMetadata, LayerType};
+
+    #[test]
+    fn test_auto_crop() {
+        // 10x8 white image with a grey 4x3 block at (3, 2); near-white noise in the margin
+        let (w, h) = (10u32, 8u32);
+        let mut rgba = vec![255u8; (w * h * 4) as usize];
+        for y in 2..5 {
+            for x in 3..7 {
+                let i = ((y * w + x) * 4) as usize;
+                rgba[i..i + 3].fill(90);
+            }
+        }
+        rgba[((7 * w + 9) * 4) as usize] = 250;
+
+        let margins = detect_margins(&rgba, w, h, DEFAULT_CROP_TOLERANCE).unwrap();
+        assert_eq!(margins, CropMargins { left: 3, top: 2, right: 3, bottom: 3 });
+        assert!(detect_margins(&[255u8; 16], 2, 2, 0).is_none());
+
+        let mut layer = new_layer("img".to_string(), LayerType::Image, Bounds::new(50.0, 60.0, 100.0, 80.0));
+        layer.image_data =
+            Some(ImageMetadata { width: w, height: h, color_space: "RGB".to_string(), dpi: 72, icc_profile: None });
+        let cropped = crop_layer(&layer, w, h, &margins);
+        assert_eq!(cropped.bounds, Bounds::new(80.0, 80.0, 40.0, 30.0));
+        assert_eq!(cropped.image_data.map(|m| (m.width, m.height)), Some((4, 3)));
+    }
+}
@@ -0,0 +1,242 @@
+//! Image placement
+//!
+//! Turns dropped image bytes into an image layer: the header is read for
+//! format, pixel size and resolution without decoding, and the layer is
+//! sized at the image's own resolution, shrunk to fit the page margins.
+//! Shared by the desktop `place_image` command and the wasm build.
+
+use crate::clipboard::new_layer;
+use crate::models::{Bounds, ImageMetadata, LayerObject, LayerType};
+use crate::page_setup::PageSetup;
+use crate::units::{px_to_pt, DEFAULT_PX_DPI, MM_PER_INCH};
+
+/// Format, pixel size and resolution of encoded image bytes
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ImageHeader {
+    pub mime: &'static str,
+    pub width: u32,
+    pub height: u32,
+    /// Resolution recorded in the file, if any
+    pub dpi: Option<u32>,
+}
+
+fn be_u16(data: &[u8], at: usize) -> Option<u16> {
+    Some(u16::from_be_bytes(data.get(at..at + 2)?.try_into().ok()?))
+}
+
+fn be_u32(data: &[u8], at: usize) -> Option<u32> {
+    Some(u32::from_be_bytes(data.get(at..at + 4)?.try_into().ok()?))
+}
+
+fn le_u16(data: &[u8], at: usize) -> Option<u16> {
+    Some(u16::from_le_bytes(data.get(at..at + 2)?.try_into().ok()?))
+}
+
+fn le_u24(data: &[u8], at: usize) -> Option<u32> {
+    let b = data.get(at..at + 3)?;
+    Some(u32::from_le_bytes([b[0], b[1], b[2], 0]))
+}
+
+/// Resolution from a pixel density, given per inch (1) or per centimetre (2)
+fn density_dpi(unit: u8, x: u32, y: u32) -> Option<u32> {
+    let per_inch = match unit {
+        1 => x.min(y) as f32,
+        2 => x.min(y) as f32 * MM_PER_INCH / 10.0,
+        _ => return None,
+    };
+    Some(per_inch.round() as u32).filter(|&dpi| dpi > 0)
+}
+
+/// PNG: IHDR size and the pHYs chunk (pixels per metre)
+fn png_header(data: &[u8]) -> Option<ImageHeader> {
+    let (width, height) = (be_u32(data, 16)?, be_u32(data, 20)?);
+    let mut dpi = None;
+    let mut at = 8;
+    while let (Some(len), Some(kind)) = (be_u32(data, at), data.get(at + 4..at + 8)) {
+        match kind {
+            b"pHYs" if data.get(at + 16) == Some(&1) => {
+                let per_metre = be_u32(data, at + 8)?.min(be_u32(data, at + 12)?);
+                dpi = Some((per_metre as f32 * MM_PER_INCH / 1000.0).round() as u32).filter(|&dpi| dpi > 0);
+            }
+            b"IDAT" | b"IEND" => break,
+            _ => {}
+        }
+        at += 12 + len as usize;
+    }
+    Some(ImageHeader { mime: "image/png", width, height, dpi })
+}
+
+/// JPEG: frame header size and the JFIF density
+fn jpeg_header(data: &[u8]) -> Option<ImageHeader> {
+    let mut dpi = None;
+    let mut at = 2;
+    while at + 4 <= data.len() {
+        if data[at] != 0xFF {
+            at += 1;
+            continue;
+        }
+        let marker = data[at + 1];
+        match marker {
+            // Padding and markers without a length
+            0xFF => at += 1,
+            0x00 | 0x01 | 0xD0..=0xD9 => at += 2,
+            // SOF0-SOF15 except DHT, JPG and DAC
+            0xC0..=0xCF if !matches!(marker, 0xC4 | 0xC8 | 0xCC) => {
+                let (height, width) = (be_u16(data, at + 5)?, be_u16(data, at + 7)?);
+                return Some(ImageHeader { mime: "image/jpeg", width: width as u32, height: height as u32, dpi });
+            }
+            _ => {
+                if marker == 0xE0 && data.get(at + 4..at + 9) == Some(b"JFIF\0") {
+                    let unit = *data.get(at + 11)?;
+                    dpi = density_dpi(unit, be_u16(data, at + 12)? as u32, be_u16(data, at + 14)? as u32);
+                }
+                at += 2 + be_u16(data, at + 2)? as usize;
+            }
+        }
+    }
+    None
+}
+
+/// WebP: canvas size of a lossy, lossless or extended file; no resolution
+fn webp_header(data: &[u8]) -> Option<ImageHeader> {
+    let (width, height) = match data.get(12..16)? {
+        b"VP8 " => ((le_u16(data, 26)? & 0x3FFF) as u32, (le_u16(data, 28)? & 0x3FFF) as u32),
+        b"VP8L" => {
+            let bits = u32::from_le_bytes(data.get(21..25)?.try_into().ok()?);
+            ((bits & 0x3FFF) + 1, ((bits >> 14) & 0x3FFF) + 1)
+        }
+        b"VP8X" => (le_u24(data, 24)? + 1, le_u24(data, 27)? + 1),
+        _ => return None,
+    };
+    Some(ImageHeader { mime: "image/webp", width, height, dpi: None })
+}
+
+/// Read an image's header without decoding it; `None` for unsupported or
+/// truncated data
+pub fn read_header(data: &[u8]) -> Option<ImageHeader> {
+    let header = if data.starts_with(b"\x89PNG\r\n\x1a\n") {
+        png_header(data)
+    } else if data.starts_with(&[0xFF, 0xD8, 0xFF]) {
+        jpeg_header(data)
+    } else if data.len() >= 12 && data.starts_with(b"RIFF") && &data[8..12] == b"WEBP" {
+        webp_header(data)
+    } else {
+        None
+    };
+    header.filter(|h| h.width > 0 && h.height > 0)
+}
+
+impl PageSetup {
+    /// Bounds for an image of `width`x`height` points
+    ///
+    /// The image keeps its size unless it is larger than the area inside the
+    /// margins, in which case it is scaled down to fit. It is centered on
+    /// `at` (the drop point), kept on the page, or centered in the margins.
+    pub fn place(&self, width: f32, height: f32, at: Option<(f32, f32)>) -> Bounds {
+        let content = self.content_bounds();
+        let area = if content.width > 0.0 && content.height > 0.0 {
+            content
+        } else {
+            Bounds::new(0.0, 0.0, self.width, self.height)
+        };
+        let scale = (area.width / width).min(area.height / height).min(1.0);
+        let (width, height) = (width * scale, height * scale);
+
+        let (x, y) = match at {
+            Some((x, y)) => (
+                (x - width / 2.0).min(self.width - width).max(0.0),
+                (y - height / 2.0).min(self.height - height).max(0.0),
+            ),
+            None => (area.x + (area.width - width) / 2.0, area.y + (area.height - height) / 2.0),
+        };
+        Bounds::new(x, y, width, height)
+    }
+}
+
+/// Image layer `id` for encoded image bytes, served as `image://<id>`
+///
+/// The image is sized at the resolution recorded in the file (96 dpi when
+/// there is none) and placed by `PageSetup::place`. The caller caches the
+/// bytes under `id` and sets the stacking order.
+pub fn image_layer(id: String, data: &[u8], setup: &PageSetup, at: Option<(f32, f32)>) -> Result<LayerObject, String> {
+    let header = read_header(data).ok_or("Unsupported image format; expected PNG, JPEG or WebP")?;
+    let dpi = header.dpi.unwrap_or(DEFAULT_PX_DPI);
+    let bounds = setup.place(px_to_pt(header.width as f32, dpi), px_to_pt(header.height as f32, dpi), at);
+
+    let mut metadata =
+        ImageMetadata { width: header.width, height: header.height, color_space: "RGBA".to_string(), dpi, icc_profile: None };
+    if let Some(effective) = metadata.effective_dpi(&bounds) {
+        metadata.dpi = (effective.round() as u32).max(1);
+    }
+    let mut layer = new_layer(id, LayerType::Image, bounds);
+    layer.image_url = Some(format!("image://{}", layer.id));
+    layer.image_data = Some(metadata);
+    Ok(layer)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::page_setup::{Margins, PageSizePreset};
+
+    fn png(width: u32, height: u32, per_metre: Option<u32>) -> Vec<u8> {
+        let mut data = b"\x89PNG\r\n\x1a\n".to_vec();
+        let mut chunk = |kind: &[u8], body: &[u8]| {
+            data.extend((body.len() as u32).to_be_bytes());
+            data.extend(kind);
+            data.extend(body);
+            data.extend([0; 4]);
+        };
+        chunk(b"IHDR", &[&width.to_be_bytes()[..], &height.to_be_bytes(), &[8, 6, 0, 0, 0]].concat());
+        if let Some(ppm) = per_metre {
+            chunk(b"pHYs", &[&ppm.to_be_bytes()[..], &ppm.to_be_bytes(), &[1]].concat());
+        }
+        chunk(b"IDAT", &[]);
+        data
+    }
+
+    #[test]
+    fn test_read_header() {
+        let header = read_header(&png(300, 150, Some(11811))).unwrap();
+        assert_eq!((header.mime, header.width, header.height, header.dpi), ("image/png", 300, 150, Some(300)));
+        assert_eq!(read_header(&png(300, 150, None)).unwrap().dpi, None);
+
+        // JFIF at 72 dpi, then a baseline frame header of 640x480
+        let mut jpeg = vec![0xFF, 0xD8, 0xFF, 0xE0, 0, 16];
+        jpeg.extend(b"JFIF\0\x01\x01\x01\0\x48\0\x48\0\0");
+        jpeg.extend([0xFF, 0xC0, 0, 11, 8, 0x01, 0xE0, 0x02, 0x80, 3, 0, 0, 0]);
+        let header = read_header(&jpeg).unwrap();
+        assert_eq!((header.width, header.height, header.dpi), (640, 480, Some(72)));
+
+        let mut webp = b"RIFF\0\0\0\0WEBPVP8X\x0a\0\0\0\0\0\0\0".to_vec();
+        webp.extend([0x1F, 0x03, 0, 0xC7, 0, 0]);
+        assert_eq!(read_header(&webp).map(|h| (h.width, h.height)), Some((800, 200)));
+
+        assert!(read_header(b"GIF89a....").is_none());
+        assert!(read_header(&png(300, 150, None)[..20]).is_none());
+    }
+
+    #[test]
+    fn test_image_layer_placement() {
+        let setup = PageSizePreset::Letter.page_setup();
+        // 1.5 x 0.5 in at 300 dpi fits as is and is centered in the margins
+        let layer = image_layer("img".to_string(), &png(450, 150, Some(11811)), &setup, None).unwrap();
+        assert_eq!(layer.bounds, Bounds::new(252.0, 378.0, 108.0, 36.0));
+        assert_eq!(layer.image_url.as_deref(), Some("image://img"));
+        assert_eq!(layer.image_data.as_ref().map(|d| d.dpi), Some(300));
+
+        // 96 dpi without a resolution, 13 x 6.5 in: shrunk to the 6.5 in content width
+        let layer = image_layer("big".to_string(), &png(1248, 624, None), &setup, None).unwrap();
+        assert_eq!(layer.bounds, Bounds::new(72.0, 279.0, 468.0, 234.0));
+        assert_eq!(layer.image_data.as_ref().map(|d| d.dpi), Some(192));
+
+        // Centered on the drop point, but kept on the page
+        let layer = image_layer("drop".to_string(), &png(96, 96, None), &setup, Some((10.0, 100.0))).unwrap();
+        assert_eq!(layer.bounds, Bounds::new(0.0, 64.0, 72.0, 72.0));
+
+        let no_margins = PageSetup { margins: Margins::uniform(400.0), ..setup };
+        let layer = image_layer("full".to_string(), &png(1248, 624, None), &no_margins, None).unwrap();
+        assert_eq!(layer.bounds.width, 612.0);
+        assert!(image_layer("bad".to_string(), b"not an image", &setup, None).is_err());
+    }
+}
@@ -0,0 +1,342 @@
+//! Bitmap tracing
+//!
+//! Turns a one-color bitmap, typically a low-resolution logo, into a vector
+//! layer that prints sharp at any size, in the manner of potrace:
+//!
+//! 1. pixels darker than a threshold are ink;
+//! 2. the ink's outlines are followed along pixel edges into closed loops,
+//!    dropping specks below a minimum area;
+//! 3. each loop is simplified to a polygon, removing the pixel staircase;
+//! 4. polygon corners that turn gently become curves through the edge
+//!    midpoints, sharp ones stay corners.
+//!
+//! Loops are filled even-odd, so holes (the counter of an "o") stay open.
+
+use crate::clipboard::new_layer;
+use crate::models::{FillRule, LayerObject, LayerType, PathCommand, PathData, SourceType};
+use serde::{Deserialize, Serialize};
+
+/// Polygon simplification tolerance, in pixels; removes one-pixel steps
+const SIMPLIFY_TOLERANCE: f32 = 1.0;
+/// Largest smoothing value; every corner is rounded
+pub const MAX_SMOOTHING: f32 = 1.334;
+
+/// Options for `trace_layer`
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+#[serde(rename_all = "camelCase", default)]
+pub struct TraceOptions {
+    /// Luminance below which a pixel is ink, 0-255
+    pub threshold: u8,
+    /// Corner rounding: 0 keeps every corner, `MAX_SMOOTHING` rounds all
+    pub smoothing: f32,
+    /// Outlines enclosing fewer pixels are dropped as noise
+    pub despeckle: u32,
+    /// Trace the light pixels instead, for a light logo on a dark ground
+    pub invert: bool,
+    /// Fill color; the average ink color when absent
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub color: Option<String>,
+}
+
+impl Default for TraceOptions {
+    fn default() -> Self {
+        Self { threshold: 128, smoothing: 1.0, despeckle: 4, invert: false, color: None }
+    }
+}
+
+/// A traced image layer
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct TraceResult {
+    pub layer: LayerObject,
+    /// Closed outlines, holes included
+    pub outlines: usize,
+    /// Path commands, a measure of the output's complexity
+    pub nodes: usize,
+}
+
+/// Step directions between pixel corners, clockwise with y down
+const DIRS: [(i32, i32); 4] = [(1, 0), (0, 1), (-1, 0), (0, -1)];
+
+/// Closed ink outlines of a bitmap, as corner points in pixel coordinates
+///
+/// Each dark pixel contributes the sides it shares with light pixels (or
+/// the image edge), directed clockwise around it; the sides then link into
+/// loops. Where two loops touch at a corner the walk turns right, keeping
+/// diagonal neighbours apart.
+fn outlines(ink: &[bool], width: usize, height: usize) -> Vec<Vec<(i32, i32)>> {
+    let is_ink = |x: i32, y: i32| {
+        x >= 0 && y >= 0 && (x as usize) < width && (y as usize) < height && ink[y as usize * width + x as usize]
+    };
+    let stride = width + 1;
+    // Outgoing sides per pixel corner, one bit per direction
+    let mut out = vec![0u8; stride * (height + 1)];
+    for y in 0..height as i32 {
+        for x in 0..width as i32 {
+            if !is_ink(x, y) {
+                continue;
+            }
+            let corner = |cx: i32, cy: i32| cy as usize * stride + cx as usize;
+            if !is_ink(x, y - 1) {
+                out[corner(x, y)] |= 1 << 0;
+            }
+            if !is_ink(x + 1, y) {
+                out[corner(x + 1, y)] |= 1 << 1;
+            }
+            if !is_ink(x, y + 1) {
+                out[corner(x + 1, y + 1)] |= 1 << 2;
+            }
+            if !is_ink(x - 1, y) {
+                out[corner(x, y + 1)] |= 1 << 3;
+            }
+        }
+    }
+
+    let mut loops = Vec::new();
+    for start in 0..out.len() {
+        while out[start] != 0 {
+            let origin = ((start % stride) as i32, (start / stride) as i32);
+            let first = out[start].trailing_zeros() as usize;
+            let (mut x, mut y) = origin;
+            let mut dir = first;
+            let mut points = Vec::new();
+            loop {
+                let index = y as usize * stride + x as usize;
+                // Right turn, straight on, left turn
+                let next = if points.is_empty() {
+                    Some(first)
+                } else {
+                    [(dir + 1) % 4, dir, (dir + 3) % 4].into_iter().find(|d| out[index] & (1 << d) != 0)
+                };
+                let Some(next) = next else {
+                    break;
+                };
+                if points.is_empty() || next != dir {
+                    points.push((x, y));
+                }
+                out[index] &= !(1 << next);
+                dir = next;
+                (x, y) = (x + DIRS[dir].0, y + DIRS[dir].1);
+                if (x, y) == origin {
+                    break;
+                }
+            }
+            // The origin is a corner only if the walk turns there
+            if dir == first {
+                points.remove(0);
+            }
+            if points.len() >= 4 {
+                loops.push(points);
+            }
+        }
+    }
+    loops
+}
+
+/// Twice the signed area of a polygon
+fn area2(points: &[(f32, f32)]) -> f32 {
+    let n = points.len();
+    (0..n).map(|i| points[i].0 * points[(i + 1) % n].1 - points[(i + 1) % n].0 * points[i].1).sum()
+}
+
+fn segment_distance(p: (f32, f32), a: (f32, f32), b: (f32, f32)) -> f32 {
+    let (dx, dy) = (b.0 - a.0, b.1 - a.1);
+    let len2 = dx * dx + dy * dy;
+    if len2 == 0.0 {
+        return ((p.0 - a.0).powi(2) + (p.1 - a.1).powi(2)).sqrt();
+    }
+    let t = (((p.0 - a.0) * dx + (p.1 - a.1) * dy) / len2).clamp(0.0, 1.0);
+    ((p.0 - a.0 - t * dx).powi(2) + (p.1 - a.1 - t * dy).powi(2)).sqrt()
+}
+
+/// Douglas-Peucker on the open run `points[from..=to]`, marking kept points
+fn simplify_run(points: &[(f32, f32)], from: usize, to: usize, keep: &mut [bool]) {
+    if to <= from + 1 {
+        return;
+    }
+    let (farthest, distance) = (from + 1..to)
+        .map(|i| (i, segment_distance(points[i], points[from], points[to])))
+        .fold((from, 0.0), |best, d| if d.1 > best.1 { d } else { best });
+    if distance > SIMPLIFY_TOLERANCE {
+        keep[farthest] = true;
+        simplify_run(points, from, farthest, keep);
+        simplify_run(points, farthest, to, keep);
+    }
+}
+
+/// A closed polygon simplified between its first point and the point
+/// farthest from it
+fn simplify(points: &[(f32, f32)]) -> Vec<(f32, f32)> {
+    let n = points.len();
+    let far = (1..n)
+        .max_by(|&a, &b| {
+            let d = |i: usize| (points[i].0 - points[0].0).powi(2) + (points[i].1 - points[0].1).powi(2);
+            d(a).total_cmp(&d(b))
+        })
+        .unwrap_or(0);
+    let mut closed = points.to_vec();
+    closed.push(points[0]);
+    let mut keep = vec![false; n + 1];
+    (keep[0], keep[far]) = (true, true);
+    simplify_run(&closed, 0, far, &mut keep);
+    simplify_run(&closed, far, n, &mut keep);
+    (0..n).filter(|&i| keep[i]).map(|i| points[i]).collect()
+}
+
+/// Path commands for a closed polygon, rounding corners that turn by less
+/// than the smoothing allows
+fn fit_curves(points: &[(f32, f32)], smoothing: f32, commands: &mut Vec<PathCommand>) {
+    let n = points.len();
+    let max_turn = smoothing.clamp(0.0, MAX_SMOOTHING) * std::f32::consts::FRAC_PI_2;
+    let mid = |i: usize| {
+        let (a, b) = (points[i % n], points[(i + 1) % n]);
+        ((a.0 + b.0) / 2.0, (a.1 + b.1) / 2.0)
+    };
+    let corner: Vec<bool> = (0..n)
+        .map(|i| {
+            let (p, v, q) = (points[(i + n - 1) % n], points[i], points[(i + 1) % n]);
+            let turn = ((v.0 - p.0) * (q.1 - v.1) - (v.1 - p.1) * (q.0 - v.0))
+                .atan2((v.0 - p.0) * (q.0 - v.0) + (v.1 - p.1) * (q.1 - v.1))
+                .abs();
+            turn >= max_turn
+        })
+        .collect();
+
+    let start = mid(n - 1);
+    commands.push(PathCommand::MoveTo { x: start.0, y: start.1 });
+    for i in 0..n {
+        let (from, v, to) = (mid(i + n - 1), points[i], mid(i));
+        if corner[i] {
+            commands.push(PathCommand::LineTo { x: v.0, y: v.1 });
+            // Straight on to the next corner without stopping at the midpoint
+            if !corner[(i + 1) % n] || i == n - 1 {
+                commands.push(PathCommand::LineTo { x: to.0, y: to.1 });
+            }
+        } else {
+            // A quadratic curve through `v`'s control, as a cubic
+            let c = |m: (f32, f32)| (m.0 + (v.0 - m.0) * 2.0 / 3.0, m.1 + (v.1 - m.1) * 2.0 / 3.0);
+            let ((x1, y1), (x2, y2)) = (c(from), c(to));
+            commands.push(PathCommand::CurveTo { x1, y1, x2, y2, x: to.0, y: to.1 });
+        }
+    }
+    commands.push(PathCommand::ClosePath);
+}
+
+/// Trace an image layer's pixels into a vector layer in its place
+///
+/// `rgba` is the layer's bitmap, `width` x `height` pixels, composited on
+/// white; it may be a downsampled copy for a quick preview. The traced layer
+/// takes the image's bounds, stacking and transform, with id `<id>-traced`.
+pub fn trace_layer(
+    layer: &LayerObject,
+    rgba: &[u8],
+    width: u32,
+    height: u32,
+    options: &TraceOptions,
+) -> Result<TraceResult, String> {
+    let (w, h) = (width as usize, height as usize);
+    if w == 0 || h == 0 || rgba.len() < w * h * 4 {
+        return Err("Image has no pixels to trace".to_string());
+    }
+
+    let mut ink = vec![false; w * h];
+    let mut sum = [0u64; 3];
+    for (i, px) in rgba.chunks_exact(4).take(w * h).enumerate() {
+        let alpha = px[3] as f32 / 255.0;
+        let luma = (0.299 * px[0] as f32 + 0.587 * px[1] as f32 + 0.114 * px[2] as f32) * alpha + 255.0 * (1.0 - alpha);
+        ink[i] = (luma < options.threshold as f32) != options.invert;
+        if ink[i] {
+            (0..3).for_each(|c| sum[c] += px[c] as u64);
+        }
+    }
+    let inked = ink.iter().filter(|&&i| i).count() as u64;
+    if inked == 0 {
+        return Err("Nothing to trace at this threshold".to_string());
+    }
+
+    let (sx, sy) = (layer.bounds.width / width as f32, layer.bounds.height / height as f32);
+    let mut commands = Vec::new();
+    let mut count = 0;
+    for outline in outlines(&ink, w, h) {
+        let points: Vec<(f32, f32)> = outline.iter().map(|&(x, y)| (x as f32, y as f32)).collect();
+        if area2(&points).abs() / 2.0 < options.despeckle as f32 {
+            continue;
+        }
+        let simplified = simplify(&points);
+        if simplified.len() < 3 {
+            continue;
+        }
+        let placed: Vec<(f32, f32)> =
+            simplified.iter().map(|&(x, y)| (layer.bounds.x + x * sx, layer.bounds.y + y * sy)).collect();
+        fit_curves(&placed, options.smoothing, &mut commands);
+        count += 1;
+    }
+    if count == 0 {
+        return Err("Only specks found; lower the despeckle size".to_string());
+    }
+
+    let color = options.color.clone().unwrap_or_else(|| {
+        let [r, g, b] = sum.map(|s| (s / inked) as u8);
+        format!("#{:02x}{:02x}{:02x}", r, g, b)
+    });
+    let mut traced = new_layer(format!("{}-traced", layer.id), LayerType::Vector, layer.bounds);
+    traced.z_index = layer.z_index;
+    traced.opacity = layer.opacity;
+    traced.visible = layer.visible;
+    traced.transform = layer.transform;
+    traced.role = layer.role;
+    traced.source_type = SourceType::Manual;
+    traced.fill_color = Some(color);
+    let nodes = commands.len();
+    traced.path_data = Some(PathData { commands, fill_rule: Some(FillRule::EvenOdd) });
+    Ok(TraceResult { layer: traced, outlines: count, nodes })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::Bounds;
+
+    #[test]
+    fn test_trace_layer() {
+        // 12x12 black square with a 4x4 hole, plus a one-pixel speck
+        let (w, h) = (20u32, 20u32);
+        let mut rgba = vec![255u8; (w * h * 4) as usize];
+        let mut paint = |x: u32, y: u32, v: u8| {
+            let i = ((y * w + x) * 4) as usize;
+            rgba[i..i + 3].fill(v);
+        };
+        for y in 4..16 {
+            for x in 4..16 {
+                paint(x, y, if (8..12).contains(&x) && (8..12).contains(&y) { 255 } else { 0 });
+            }
+        }
+        paint(1, 1, 0);
+
+        let mut image = new_layer("logo".to_string(), LayerType::Image, Bounds::new(100.0, 100.0, 40.0, 40.0));
+        image.z_index = 3;
+        let sharp = TraceOptions { smoothing: 0.0, ..Default::default() };
+        let result = trace_layer(&image, &rgba, w, h, &sharp).unwrap();
+        assert_eq!((result.outlines, result.layer.id.as_str(), result.layer.z_index), (2, "logo-traced", 3));
+        assert_eq!(result.layer.fill_color.as_deref(), Some("#000000"));
+
+        let commands = &result.layer.path_data.as_ref().unwrap().commands;
+        let corners: Vec<(f32, f32)> = commands
+            .iter()
+            .filter_map(|c| match *c {
+                PathCommand::LineTo { x, y } => Some((x, y)),
+                _ => None,
+            })
+            .collect();
+        // Outer square at pixels 4..16, scaled 2x onto the layer bounds
+        for corner in [(108.0, 108.0), (132.0, 108.0), (132.0, 132.0), (108.0, 132.0), (116.0, 116.0), (124.0, 124.0)] {
+            assert!(corners.contains(&corner), "missing corner {:?}", corner);
+        }
+        assert!(!commands.iter().any(|c| matches!(c, PathCommand::CurveTo { .. })));
+
+        let round = TraceOptions { smoothing: MAX_SMOOTHING, ..Default::default() };
+        let smooth = trace_layer(&image, &rgba, w, h, &round).unwrap();
+        assert!(smooth.layer.path_data.unwrap().commands.iter().any(|c| matches!(c, PathCommand::CurveTo { .. })));
+        assert!(trace_layer(&image, &rgba, w, h, &TraceOptions { threshold: 0, ..Default::default() }).is_err());
+    }
+}
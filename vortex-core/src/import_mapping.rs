@@ -0,0 +1,231 @@
+//! Import mappings
+//!
+//! Saved rules for a source layout that is imported again and again, such as
+//! a magazine converted every month. Each rule matches extracted layers by
+//! type, font, size and position and sets their role, tags and style, so
+//! the corrections made by hand to one issue are made to the next on import.
+//!
+//! A mapping is recorded by comparing a document as imported with the same
+//! document after correction. Layers (matched by id) whose role, tags or
+//! style were changed become rules keyed by their imported font and size.
+//! When layers of one font and size were corrected differently, or some
+//! were left alone, each rule is narrowed to the area its layers cover.
+//! Rules are tried in order; the first match wins and locked layers are
+//! left alone.
+
+use crate::layers::apply_updates;
+use crate::models::{Bounds, LayerObject, LayerType, LayerUpdates, PageData};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+
+/// Font sizes within this many points of a recorded size match it
+const SIZE_TOLERANCE: f32 = 0.25;
+
+/// Weight from which a font counts as bold
+const BOLD_WEIGHT: u16 = 600;
+
+/// Which extracted layers a rule applies to; absent fields match anything
+#[derive(Debug, Clone, Default, Serialize, Deserialize, PartialEq)]
+#[serde(rename_all = "camelCase", default)]
+pub struct MappingMatch {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub layer_type: Option<LayerType>,
+    /// Font family, ignoring case
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub font_family: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub min_font_size: Option<f32>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub max_font_size: Option<f32>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub bold: Option<bool>,
+    /// Area of the page the layer's center must be in, in points
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub region: Option<Bounds>,
+}
+
+impl MappingMatch {
+    pub fn matches(&self, layer: &LayerObject) -> bool {
+        let size = layer.font_size.unwrap_or(0.0);
+        let (cx, cy) = (layer.bounds.x + layer.bounds.width / 2.0, layer.bounds.y + layer.bounds.height / 2.0);
+        self.layer_type.map_or(true, |t| t == layer.layer_type)
+            && self.font_family.as_ref().map_or(true, |family| {
+                layer.font_family.as_ref().is_some_and(|f| f.eq_ignore_ascii_case(family))
+            })
+            && self.min_font_size.map_or(true, |min| size >= min)
+            && self.max_font_size.map_or(true, |max| size <= max)
+            && self.bold.map_or(true, |bold| is_bold(layer) == bold)
+            && self.region.map_or(true, |r| cx >= r.x && cx <= r.x + r.width && cy >= r.y && cy <= r.y + r.height)
+    }
+}
+
+/// Matched layers and what is set on them
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct MappingRule {
+    #[serde(rename = "match")]
+    pub criteria: MappingMatch,
+    /// Role, tags and style set on each matched layer
+    pub set: LayerUpdates,
+}
+
+/// A named set of rules for one source layout
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ImportMapping {
+    pub name: String,
+    #[serde(default)]
+    pub rules: Vec<MappingRule>,
+}
+
+fn is_bold(layer: &LayerObject) -> bool {
+    layer.font_weight.unwrap_or(400) >= BOLD_WEIGHT
+}
+
+/// Role, tags and style of `after` that differ from `before`
+fn corrections(before: &LayerObject, after: &LayerObject) -> LayerUpdates {
+    fn changed<T: PartialEq + Clone>(before: &T, after: &T) -> Option<T> {
+        (before != after).then(|| after.clone())
+    }
+    LayerUpdates {
+        role: changed(&before.role, &after.role),
+        tags: changed(&before.tags, &after.tags),
+        font_family: changed(&before.font_family, &after.font_family).flatten(),
+        font_size: changed(&before.font_size, &after.font_size).flatten(),
+        font_weight: changed(&before.font_weight, &after.font_weight).flatten(),
+        font_style: changed(&before.font_style, &after.font_style).flatten(),
+        color: changed(&before.color, &after.color).flatten(),
+        text_align: changed(&before.text_align, &after.text_align).flatten(),
+        text_transform: changed(&before.text_transform, &after.text_transform).flatten(),
+        line_height: changed(&before.line_height, &after.line_height).flatten(),
+        letter_spacing: changed(&before.letter_spacing, &after.letter_spacing).flatten(),
+        ..Default::default()
+    }
+}
+
+/// Smallest bounds holding all of `bounds`
+fn union(bounds: &[Bounds]) -> Bounds {
+    let (x0, y0, x1, y1) = bounds.iter().fold((f32::MAX, f32::MAX, f32::MIN, f32::MIN), |(x0, y0, x1, y1), b| {
+        (x0.min(b.x), y0.min(b.y), x1.max(b.x + b.width), y1.max(b.y + b.height))
+    });
+    Bounds::new(x0, y0, x1 - x0, y1 - y0)
+}
+
+/// Record the corrections made to `imported` in `corrected` as a mapping
+pub fn record_mapping(name: &str, imported: &[PageData], corrected: &[PageData]) -> ImportMapping {
+    let after: HashMap<&str, &LayerObject> =
+        corrected.iter().flat_map(|p| &p.layers).map(|l| (l.id.as_str(), l)).collect();
+
+    // Layers grouped by type, font, size and weight, with each one's
+    // corrections (as JSON, to tell them apart) and bounds
+    type Key = (LayerType, Option<String>, Option<i32>, bool);
+    type Member = (serde_json::Value, LayerUpdates, Bounds);
+    let mut groups: Vec<(Key, Vec<Member>)> = Vec::new();
+    for layer in imported.iter().flat_map(|p| &p.layers) {
+        let Some(fixed) = after.get(layer.id.as_str()) else {
+            continue;
+        };
+        let set = corrections(layer, fixed);
+        let Ok(json) = serde_json::to_value(&set) else {
+            continue;
+        };
+        let key = (
+            layer.layer_type,
+            layer.font_family.as_ref().map(|f| f.to_lowercase()),
+            layer.font_size.map(|s| (s * 2.0).round() as i32),
+            is_bold(layer),
+        );
+        match groups.iter_mut().find(|(k, _)| *k == key) {
+            Some((_, members)) => members.push((json, set, layer.bounds)),
+            None => groups.push((key, vec![(json, set, layer.bounds)])),
+        }
+    }
+
+    let mut rules = Vec::new();
+    for ((layer_type, font_family, size, bold), members) in groups {
+        let mut outcomes: Vec<(&serde_json::Value, &LayerUpdates, Vec<Bounds>)> = Vec::new();
+        for (json, set, bounds) in &members {
+            match outcomes.iter_mut().find(|(j, _, _)| *j == json) {
+                Some((_, _, all)) => all.push(*bounds),
+                None => outcomes.push((json, set, vec![*bounds])),
+            }
+        }
+        let empty = |json: &serde_json::Value| json.as_object().map_or(true, |o| o.is_empty());
+        let narrow = outcomes.len() > 1;
+        for (json, set, bounds) in outcomes {
+            if empty(json) {
+                continue;
+            }
+            let size = size.map(|s| s as f32 / 2.0);
+            rules.push(MappingRule {
+                criteria: MappingMatch {
+                    layer_type: Some(layer_type),
+                    font_family: font_family.clone(),
+                    min_font_size: size.map(|s| s - SIZE_TOLERANCE),
+                    max_font_size: size.map(|s| s + SIZE_TOLERANCE),
+                    bold: Some(bold),
+                    region: narrow.then(|| union(&bounds)),
+                },
+                set: set.clone(),
+            });
+        }
+    }
+    ImportMapping { name: name.to_string(), rules }
+}
+
+/// Apply the first matching rule of `mapping` to each unlocked layer;
+/// returns how many layers were changed
+pub fn apply_mapping(pages: &mut [PageData], mapping: &ImportMapping) -> usize {
+    let mut changed = 0;
+    for layer in pages.iter_mut().flat_map(|p| &mut p.layers).filter(|l| !l.locked) {
+        if let Some(rule) = mapping.rules.iter().find(|r| r.criteria.matches(layer)) {
+            apply_updates(layer, &rule.set);
+            changed += 1;
+        }
+    }
+    changed
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::clipboard::new_layer;
+    use crate::models::LayerRole;
+
+    fn text(id: &str, size: f32, y: f32) -> LayerObject {
+        let mut layer = new_layer(id.to_string(), LayerType::Text, Bounds::new(72.0, y, 400.0, size * 1.2));
+        (layer.font_family, layer.font_size) = (Some("Georgia".to_string()), Some(size));
+        layer
+    }
+
+    fn page(layers: Vec<LayerObject>) -> Vec<PageData> {
+        vec![PageData { page_index: 0, width: 612.0, height: 792.0, dpi: None, layers, metadata: None, background: None }]
+    }
+
+    #[test]
+    fn test_record_and_apply() {
+        // 9 pt runs at the top are running heads, at the bottom folios,
+        // elsewhere captions left alone; 24 pt runs are all headlines
+        let imported = page(vec![text("head", 9.0, 30.0), text("title", 24.0, 100.0), text("caption", 9.0, 400.0), text("folio", 9.0, 760.0)]);
+        let mut corrected = imported.clone();
+        let layers = &mut corrected[0].layers;
+        layers[0].role = LayerRole::Header;
+        (layers[1].tags, layers[1].font_family) = (vec!["headline".to_string()], Some("Futura".to_string()));
+        layers[3].role = LayerRole::Footer;
+
+        let mapping = record_mapping("Monthly", &imported, &corrected);
+        assert_eq!(mapping.rules.len(), 3);
+        let small = |r: &&MappingRule| r.criteria.max_font_size.is_some_and(|max| max < 10.0);
+        assert!(mapping.rules.iter().filter(small).all(|r| r.criteria.region.is_some()));
+
+        // Next month's issue: the same layout, other ids and a larger headline
+        let mut next = page(vec![text("a", 9.0, 32.0), text("b", 24.2, 140.0), text("c", 9.0, 500.0), text("d", 9.0, 758.0)]);
+        next[0].layers[3].locked = true;
+        assert_eq!(apply_mapping(&mut next, &mapping), 2);
+        let layers = &next[0].layers;
+        assert_eq!(layers[0].role, LayerRole::Header);
+        assert_eq!((layers[1].tags.as_slice(), layers[1].font_family.as_deref()), (&["headline".to_string()][..], Some("Futura")));
+        assert_eq!(layers[2].role, LayerRole::Content);
+        assert_eq!(layers[3].role, LayerRole::Content);
+    }
+}
@@ -0,0 +1,412 @@
+//! Layer Cleanup
+//! Removing redundant layers left behind by PDF imports
+//!
+//! Producers fake bold text by painting the same run two or three times a
+//! hair apart, and page backgrounds are often several identical white
+//! rectangles stacked up. Layers that match in content, bounds and style
+//! within a tolerance collapse into the topmost copy, so what is left looks
+//! the same.
+//!
+//! Pruning drops layers that paint nothing: zero-area paths, fully
+//! transparent shapes, whitespace-only text and anything entirely off the
+//! page. Locked layers are never removed by either pass.
+
+use crate::models::{LayerObject, LayerType, PageData, TransformMatrix};
+use serde::{Deserialize, Serialize};
+use std::collections::{HashMap, HashSet};
+
+/// Offsets smaller than this are the same position, not a fake-bold copy
+const SAME_POSITION: f32 = 0.01;
+
+/// Settings for duplicate detection
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct DedupOptions {
+    /// Largest difference in position or size, in points, between copies
+    #[serde(default = "default_tolerance")]
+    pub tolerance: f32,
+    /// Make text bold when its copies were offset (fake bold)
+    #[serde(default = "default_true")]
+    pub promote_fake_bold: bool,
+}
+
+fn default_tolerance() -> f32 {
+    1.0
+}
+
+fn default_true() -> bool {
+    true
+}
+
+impl Default for DedupOptions {
+    fn default() -> Self {
+        Self { tolerance: default_tolerance(), promote_fake_bold: true }
+    }
+}
+
+/// Copies of one layer collapsed into it
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct DuplicateGroup {
+    pub page_index: usize,
+    pub kept_id: String,
+    pub removed_ids: Vec<String>,
+    /// The copies were offset text, and the kept layer was made bold
+    pub fake_bold: bool,
+}
+
+/// Pages after deduplication, with what was cleaned
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct DedupResult {
+    pub pages: Vec<PageData>,
+    pub groups: Vec<DuplicateGroup>,
+    pub removed_layers: usize,
+}
+
+fn close(a: f32, b: f32, tolerance: f32) -> bool {
+    (a - b).abs() <= tolerance
+}
+
+fn close_opt(a: Option<f32>, b: Option<f32>) -> bool {
+    match (a, b) {
+        (Some(a), Some(b)) => close(a, b, 1e-3),
+        (a, b) => a.is_none() && b.is_none(),
+    }
+}
+
+fn close_transform(a: Option<TransformMatrix>, b: Option<TransformMatrix>, tolerance: f32) -> bool {
+    match (a, b) {
+        (Some(a), Some(b)) => {
+            [a.a - b.a, a.b - b.b, a.c - b.c, a.d - b.d].iter().all(|d| d.abs() <= 1e-3)
+                && close(a.e, b.e, tolerance)
+                && close(a.f, b.f, tolerance)
+        }
+        (a, b) => a.is_none() && b.is_none(),
+    }
+}
+
+/// Same look apart from a position or size difference within `tolerance`
+fn is_duplicate(a: &LayerObject, b: &LayerObject, tolerance: f32) -> bool {
+    close(a.bounds.x, b.bounds.x, tolerance)
+        && close(a.bounds.y, b.bounds.y, tolerance)
+        && close(a.bounds.width, b.bounds.width, tolerance)
+        && close(a.bounds.height, b.bounds.height, tolerance)
+        && close_transform(a.transform, b.transform, tolerance)
+        && a.visible == b.visible
+        && a.role == b.role
+        && close(a.opacity, b.opacity, 1e-3)
+        && a.blend_mode == b.blend_mode
+        && a.font_family == b.font_family
+        && close_opt(a.font_size, b.font_size)
+        && a.font_weight == b.font_weight
+        && a.font_style == b.font_style
+        && a.color == b.color
+        && a.text_decoration == b.text_decoration
+        && a.text_transform == b.text_transform
+        && a.background_color == b.background_color
+        && a.shape_type == b.shape_type
+        && a.stroke_color == b.stroke_color
+        && close_opt(a.stroke_width, b.stroke_width)
+        && a.fill_color == b.fill_color
+        && a.path_data == b.path_data
+        && a.text_path == b.text_path
+        && a.text_outline == b.text_outline
+        && a.text_shadow == b.text_shadow
+        && a.drop_cap == b.drop_cap
+        && a.notes == b.notes
+}
+
+fn is_offset(a: &LayerObject, b: &LayerObject) -> bool {
+    !close(a.bounds.x, b.bounds.x, SAME_POSITION) || !close(a.bounds.y, b.bounds.y, SAME_POSITION)
+}
+
+/// Collapse duplicate layers on one page into their topmost copy
+pub fn deduplicate_page(page: &mut PageData, options: &DedupOptions) -> Vec<DuplicateGroup> {
+    // Topmost first; among equal z-indices the later layer paints last
+    let mut order: Vec<usize> = (0..page.layers.len()).collect();
+    order.sort_by_key(|&i| std::cmp::Reverse((page.layers[i].z_index, i)));
+
+    // Only layers with the same payload can be copies of each other
+    let mut buckets: HashMap<_, Vec<usize>> = HashMap::new();
+    for &i in &order {
+        let l = &page.layers[i];
+        let key = (
+            l.layer_type,
+            l.content.as_deref(),
+            l.image_url.as_deref(),
+            l.image_path.as_deref(),
+        );
+        buckets.entry(key).or_default().push(i);
+    }
+
+    let mut found: Vec<(usize, Vec<usize>, bool)> = Vec::new();
+    for indices in buckets.values().filter(|b| b.len() > 1) {
+        let mut taken = vec![false; indices.len()];
+        for (n, &keep) in indices.iter().enumerate() {
+            if taken[n] {
+                continue;
+            }
+            let kept = &page.layers[keep];
+            let mut copies = Vec::new();
+            let mut offset = false;
+            for (m, &other) in indices.iter().enumerate().skip(n + 1) {
+                let candidate = &page.layers[other];
+                if !taken[m] && !candidate.locked && is_duplicate(kept, candidate, options.tolerance) {
+                    taken[m] = true;
+                    offset |= is_offset(kept, candidate);
+                    copies.push(other);
+                }
+            }
+            if !copies.is_empty() {
+                let fake_bold = offset && kept.layer_type == LayerType::Text;
+                found.push((keep, copies, fake_bold));
+            }
+        }
+    }
+    found.sort_by_key(|(keep, _, _)| *keep);
+
+    let mut removed = HashSet::new();
+    let mut groups = Vec::with_capacity(found.len());
+    for (keep, copies, fake_bold) in found {
+        if fake_bold && options.promote_fake_bold {
+            let weight = &mut page.layers[keep].font_weight;
+            *weight = Some(weight.unwrap_or(400).max(700));
+        }
+        removed.extend(copies.iter().copied());
+        groups.push(DuplicateGroup {
+            page_index: page.page_index,
+            kept_id: page.layers[keep].id.clone(),
+            removed_ids: copies.iter().map(|&i| page.layers[i].id.clone()).collect(),
+            fake_bold,
+        });
+    }
+
+    let mut index = 0;
+    page.layers.retain(|_| {
+        index += 1;
+        !removed.contains(&(index - 1))
+    });
+    groups
+}
+
+/// Collapse duplicate layers on every page
+pub fn deduplicate_pages(mut pages: Vec<PageData>, options: &DedupOptions) -> DedupResult {
+    let groups: Vec<DuplicateGroup> =
+        pages.iter_mut().flat_map(|page| deduplicate_page(page, options)).collect();
+    let removed_layers = groups.iter().map(|g| g.removed_ids.len()).sum();
+    DedupResult { pages, groups, removed_layers }
+}
+
+/// Rules for pruning layers that paint nothing
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PruneOptions {
+    /// Remove layers covering less than this many square points; strokes
+    /// count their width, so thin rules survive
+    #[serde(default = "default_min_area")]
+    pub min_area: f32,
+    /// Remove hidden layers and ones with nothing opaque to paint
+    #[serde(default = "default_true")]
+    pub remove_invisible: bool,
+    /// Remove text layers that are empty or only whitespace
+    #[serde(default = "default_true")]
+    pub remove_blank_text: bool,
+    /// Remove layers entirely outside the page
+    #[serde(default = "default_true")]
+    pub remove_off_page: bool,
+}
+
+fn default_min_area() -> f32 {
+    0.25
+}
+
+impl Default for PruneOptions {
+    fn default() -> Self {
+        Self { min_area: default_min_area(), remove_invisible: true, remove_blank_text: true, remove_off_page: true }
+    }
+}
+
+/// Layers pruned from one page, by the rule that removed them
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PrunedPage {
+    pub page_index: usize,
+    pub too_small: usize,
+    pub invisible: usize,
+    pub blank_text: usize,
+    pub off_page: usize,
+}
+
+impl PrunedPage {
+    pub fn total(&self) -> usize {
+        self.too_small + self.invisible + self.blank_text + self.off_page
+    }
+}
+
+/// Pages after pruning, with removal counts for pages that lost layers
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PruneResult {
+    pub pages: Vec<PageData>,
+    pub pruned: Vec<PrunedPage>,
+    pub removed_layers: usize,
+}
+
+/// Absent, `transparent`/`none`, or hex with a zero alpha
+fn is_transparent(color: Option<&str>) -> bool {
+    let Some(color) = color.map(str::trim) else {
+        return true;
+    };
+    let hex = color.trim_start_matches('#');
+    color.eq_ignore_ascii_case("transparent")
+        || color.eq_ignore_ascii_case("none")
+        || (color.starts_with('#') && (hex.len() == 8 && hex.ends_with("00") || hex.len() == 4 && hex.ends_with('0')))
+}
+
+/// Nothing opaque would be painted
+fn is_invisible(layer: &LayerObject) -> bool {
+    if !layer.visible || layer.opacity <= 0.0 {
+        return true;
+    }
+    match layer.layer_type {
+        LayerType::Vector | LayerType::Shape => {
+            is_transparent(layer.fill_color.as_deref())
+                && (is_transparent(layer.stroke_color.as_deref()) || layer.stroke_width.is_some_and(|w| w <= 0.0))
+        }
+        LayerType::Text => is_transparent(layer.color.as_deref()) && layer.color.is_some(),
+        LayerType::Image => false,
+    }
+}
+
+/// Area painted, counting half the stroke width past each edge
+fn painted_area(layer: &LayerObject) -> f32 {
+    let stroke = match layer.layer_type {
+        LayerType::Vector | LayerType::Shape if !is_transparent(layer.stroke_color.as_deref()) => {
+            layer.stroke_width.unwrap_or(1.0).max(0.0)
+        }
+        _ => 0.0,
+    };
+    (layer.bounds.width.abs() + stroke) * (layer.bounds.height.abs() + stroke)
+}
+
+fn is_off_page(layer: &LayerObject, width: f32, height: f32) -> bool {
+    let b = &layer.bounds;
+    b.x >= width || b.y >= height || b.x + b.width <= 0.0 || b.y + b.height <= 0.0
+}
+
+/// Remove layers on one page that paint nothing
+pub fn prune_page(page: &mut PageData, options: &PruneOptions) -> PrunedPage {
+    let mut pruned = PrunedPage { page_index: page.page_index, ..Default::default() };
+    let (width, height) = (page.width, page.height);
+    page.layers.retain(|layer| {
+        if layer.locked {
+            return true;
+        }
+        let blank = layer.layer_type == LayerType::Text
+            && layer.content.as_deref().map_or(true, |c| c.trim().is_empty());
+        let counter = if options.remove_blank_text && blank {
+            &mut pruned.blank_text
+        } else if options.remove_invisible && is_invisible(layer) {
+            &mut pruned.invisible
+        } else if options.remove_off_page && is_off_page(layer, width, height) {
+            &mut pruned.off_page
+        } else if painted_area(layer) < options.min_area {
+            &mut pruned.too_small
+        } else {
+            return true;
+        };
+        *counter += 1;
+        false
+    });
+    pruned
+}
+
+/// Remove layers that paint nothing from every page
+pub fn prune_pages(mut pages: Vec<PageData>, options: &PruneOptions) -> PruneResult {
+    let pruned: Vec<PrunedPage> = pages
+        .iter_mut()
+        .map(|page| prune_page(page, options))
+        .filter(|p| p.total() > 0)
+        .collect();
+    let removed_layers = pruned.iter().map(PrunedPage::total).sum();
+    PruneResult { pages, pruned, removed_layers }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_util::{self, layer, LayerBuilder};
+
+    fn page(layers: Vec<LayerBuilder>) -> PageData {
+        test_util::page(0, layers.into_iter().map(LayerBuilder::build).collect())
+    }
+
+    fn text(id: &str, x: f32, z: i32) -> LayerBuilder {
+        layer(id, "text").bounds(x, 100.0, 80.0, 12.0).z(z).fields(serde_json::json!({
+            "content": "Chapter One", "fontFamily": "Garamond", "fontSize": 12, "color": "#000000",
+            "sourceType": "extracted"
+        }))
+    }
+
+    fn rect(id: &str, z: i32, locked: bool) -> LayerBuilder {
+        layer(id, "vector").bounds(0.0, 0.0, 612.0, 792.0).z(z).fields(serde_json::json!({
+            "locked": locked,
+            "pathData": { "commands": [
+                { "type": "moveTo", "x": 0, "y": 0 }, { "type": "lineTo", "x": 612, "y": 0 },
+                { "type": "lineTo", "x": 612, "y": 792 }, { "type": "closePath" }
+            ]},
+            "fillColor": "#FFFFFF",
+            "sourceType": "extracted", "role": "background"
+        }))
+    }
+
+    #[test]
+    fn test_fake_bold_collapses_to_bold_text() {
+        let mut page = page(vec![text("a", 72.0, 0), text("b", 72.3, 1), text("c", 200.0, 2)]);
+        let groups = deduplicate_page(&mut page, &DedupOptions::default());
+        assert_eq!(groups.len(), 1);
+        assert_eq!((groups[0].kept_id.as_str(), groups[0].removed_ids.clone()), ("b", vec!["a".to_string()]));
+        assert!(groups[0].fake_bold);
+        let ids: Vec<&str> = page.layers.iter().map(|l| l.id.as_str()).collect();
+        assert_eq!(ids, ["b", "c"]);
+        assert_eq!(page.layers[0].font_weight, Some(700));
+    }
+
+    #[test]
+    fn test_identical_shapes_keep_locked_copies() {
+        let pages = vec![page(vec![rect("r1", 0, true), rect("r2", 1, false), rect("r3", 2, false)])];
+        let result = deduplicate_pages(pages, &DedupOptions::default());
+        assert_eq!(result.removed_layers, 1);
+        assert!(!result.groups[0].fake_bold);
+        let ids: Vec<&str> = result.pages[0].layers.iter().map(|l| l.id.as_str()).collect();
+        assert_eq!(ids, ["r1", "r3"]);
+    }
+
+    #[test]
+    fn test_prune_page() {
+        let blank = layer("blank", "text")
+            .bounds(72.0, 72.0, 40.0, 12.0)
+            .z(3)
+            .fields(serde_json::json!({ "content": "  \n", "sourceType": "extracted" }));
+        let clear = rect("clear", 4, false).with("fillColor", "#FFFFFF00");
+        let dot = rect("dot", 5, false).bounds(10.0, 10.0, 0.0, 0.0);
+        let rule = rect("rule", 6, false)
+            .bounds(72.0, 400.0, 300.0, 0.0)
+            .fields(serde_json::json!({ "strokeColor": "#000000", "strokeWidth": 0.5 }));
+        let hidden_locked = rect("kept", 7, true).with("visible", false);
+
+        let mut page = page(vec![text("a", 72.0, 0), text("off", 700.0, 1), blank, clear, dot, rule, hidden_locked]);
+        let pruned = prune_page(&mut page, &PruneOptions::default());
+        assert_eq!(
+            pruned,
+            PrunedPage { page_index: 0, too_small: 1, invisible: 1, blank_text: 1, off_page: 1 }
+        );
+        let ids: Vec<&str> = page.layers.iter().map(|l| l.id.as_str()).collect();
+        assert_eq!(ids, ["a", "rule", "kept"]);
+
+        let keep_off_page = PruneOptions { remove_off_page: false, ..Default::default() };
+        let result = prune_pages(vec![self::page(vec![text("off", 700.0, 0)])], &keep_off_page);
+        assert_eq!((result.removed_layers, result.pruned.len()), (0, 0));
+    }
+}
@@ -0,0 +1,90 @@
+//! Layer ids
+//!
+//! Imported layers get ids derived from what they were made from rather
+//! than from the order they were extracted in, so importing the same file
+//! again (on another run, or after the source changed elsewhere) gives the
+//! same ids, and history, sync and reimport merges can match layers by id.
+//!
+//! An id is `{kind}-{page}-{hash}`: the layer kind, the source page and an
+//! FNV-1a hash (stable across runs and platforms) of a key naming the
+//! source object, such as its text, path or pixel size, and of its position
+//! rounded to a tenth of a point. Flowing content (DOCX paragraphs) leaves
+//! the position out, as it moves whenever text above it changes.
+//!
+//! Objects that hash alike on one page, such as a run painted twice to fake
+//! bold, get `-2`, `-3`… after the first in extraction order; the suffix
+//! keeps them apart from every plain id, whose hash is always 12 hex digits.
+//!
+//! Projects from before keep their sequential ids (`text-0-12`). Ids are
+//! never rewritten on load, and the reimport merge matches layers whose ids
+//! differ by type and position (`source_watch` in the desktop app).
+
+use crate::models::Bounds;
+use std::collections::HashMap;
+
+const FNV_OFFSET: u64 = 0xcbf2_9ce4_8422_2325;
+const FNV_PRIME: u64 = 0x0100_0000_01b3;
+/// Hash bits kept in an id
+const HASH_MASK: u64 = 0xffff_ffff_ffff;
+
+fn fnv1a(hash: u64, bytes: &[u8]) -> u64 {
+    bytes.iter().fold(hash, |hash, &b| (hash ^ u64::from(b)).wrapping_mul(FNV_PRIME))
+}
+
+/// Id of a `kind` layer on source page `page_index` made from the object
+/// `key` at `bounds`, without collision resolution
+pub fn layer_id(kind: &str, page_index: usize, key: &str, bounds: Option<&Bounds>) -> String {
+    let mut hash = fnv1a(FNV_OFFSET, key.as_bytes());
+    if let Some(b) = bounds {
+        for value in [b.x, b.y, b.width, b.height] {
+            hash = fnv1a(hash, &((value * 10.0).round() as i64).to_le_bytes());
+        }
+    }
+    format!("{}-{}-{:012x}", kind, page_index, hash & HASH_MASK)
+}
+
+/// Ids for the layers imported from one source page, unique on the page
+#[derive(Debug, Default)]
+pub struct LayerIds {
+    page_index: usize,
+    seen: HashMap<String, usize>,
+}
+
+impl LayerIds {
+    pub fn new(page_index: usize) -> Self {
+        Self { page_index, seen: HashMap::new() }
+    }
+
+    /// Id of the next `kind` layer, made from `key` at `bounds`
+    pub fn next(&mut self, kind: &str, key: &str, bounds: Option<&Bounds>) -> String {
+        let id = layer_id(kind, self.page_index, key, bounds);
+        let count = self.seen.entry(id.clone()).or_insert(0);
+        *count += 1;
+        match *count {
+            1 => id,
+            n => format!("{}-{}", id, n),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_ids_are_stable_and_unique() {
+        let bounds = Bounds::new(72.0, 100.0, 200.0, 14.0);
+        let mut first = LayerIds::new(2);
+        let ids: Vec<String> = ["Title", "Body", "Title"].iter().map(|text| first.next("text", text, Some(&bounds))).collect();
+        assert!(ids[0].starts_with("text-2-") && ids[0].len() == "text-2-".len() + 12);
+        assert_ne!(ids[0], ids[1]);
+        assert_eq!(ids[2], format!("{}-2", ids[0]));
+
+        // Another import, with the body moved a hair and a new run before it
+        let mut again = LayerIds::new(2);
+        assert_eq!(again.next("text", "Title", Some(&bounds)), ids[0]);
+        again.next("text", "New", Some(&bounds));
+        assert_eq!(again.next("text", "Body", Some(&Bounds { y: 100.02, ..bounds })), ids[1]);
+        assert_ne!(layer_id("text", 3, "Title", Some(&bounds)), ids[0]);
+    }
+}
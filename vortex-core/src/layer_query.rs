@@ -0,0 +1,267 @@
+//! Layer queries
+//!
+//! A small filter language for picking layers across pages, for bulk
+//! selection, scripts and QA checks:
+//!
+//! ```text
+//! type:text font:/^Times/ size:..8 -role:header
+//! ```
+//!
+//! A query is whitespace-separated `key:value` terms that must all hold; a
+//! leading `-` negates a term. Values containing spaces are quoted or
+//! written as `/regex/`. Keys:
+//! - `type:`, `role:` — one or more names, comma-separated
+//! - `font:` — regex on the font family
+//! - `size:` — font size `12` or inclusive range `8..12`, `..8`, `14..`
+//! - `color:` — hex text, fill or stroke color
+//! - `in:` — bounds intersect the rect `x,y,width,height`
+//! - `text:` — regex on the text content
+//! - `tag:` — carries the tag
+//!
+//! Regexes are case-sensitive unless written `/…/i` or prefixed `(?i)`.
+
+use crate::models::{Bounds, LayerObject, LayerRole, LayerType, PageData};
+use regex_lite::Regex;
+use serde::de::DeserializeOwned;
+use serde::{Deserialize, Serialize};
+
+/// Layers on one page a query matched
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct LayerMatches {
+    pub page_index: usize,
+    pub layer_ids: Vec<String>,
+}
+
+#[derive(Debug, Clone)]
+enum Condition {
+    Types(Vec<LayerType>),
+    Roles(Vec<LayerRole>),
+    Font(Regex),
+    Size(f32, f32),
+    Color(String),
+    Intersects(Bounds),
+    Text(Regex),
+    Tag(String),
+}
+
+/// A parsed query
+#[derive(Debug, Clone)]
+pub struct LayerQuery {
+    /// Conditions with whether each is negated
+    terms: Vec<(bool, Condition)>,
+}
+
+/// Split a query into `(negated, key, value)` terms
+fn tokenize(query: &str) -> Result<Vec<(bool, String, String)>, String> {
+    let mut terms = Vec::new();
+    let mut chars = query.chars().peekable();
+    loop {
+        while chars.next_if(|c| c.is_whitespace()).is_some() {}
+        let Some(&first) = chars.peek() else {
+            return Ok(terms);
+        };
+        let negated = first == '-';
+        if negated {
+            chars.next();
+        }
+        let key: String = std::iter::from_fn(|| chars.next_if(|&c| c != ':' && !c.is_whitespace())).collect();
+        if chars.next() != Some(':') {
+            return Err(format!("Expected key:value, got '{}'", key));
+        }
+        let mut value = String::new();
+        match chars.peek() {
+            Some('"') => {
+                chars.next();
+                value.extend(std::iter::from_fn(|| chars.next_if(|&c| c != '"')));
+                if chars.next().is_none() {
+                    return Err(format!("Unclosed quote after {}:", key));
+                }
+            }
+            Some('/') => {
+                chars.next();
+                let mut closed = false;
+                while let Some(c) = chars.next() {
+                    match c {
+                        '\\' if chars.peek() == Some(&'/') => value.push(chars.next().unwrap_or('/')),
+                        '/' => {
+                            closed = true;
+                            break;
+                        }
+                        c => value.push(c),
+                    }
+                }
+                if !closed {
+                    return Err(format!("Unclosed regex after {}:", key));
+                }
+                if chars.next_if_eq(&'i').is_some() {
+                    value.insert_str(0, "(?i)");
+                }
+            }
+            _ => value.extend(std::iter::from_fn(|| chars.next_if(|c| !c.is_whitespace()))),
+        }
+        terms.push((negated, key, value));
+    }
+}
+
+/// A `LayerType` or `LayerRole` by its serialized name
+fn parse_names<T: DeserializeOwned>(key: &str, value: &str) -> Result<Vec<T>, String> {
+    value
+        .split(',')
+        .map(|name| {
+            serde_json::from_value(serde_json::Value::String(name.trim().to_lowercase()))
+                .map_err(|_| format!("Unknown {}: {}", key, name))
+        })
+        .collect()
+}
+
+fn parse_number(key: &str, value: &str) -> Result<f32, String> {
+    value.trim().parse().map_err(|_| format!("Invalid number for {}: {}", key, value))
+}
+
+fn parse_regex(key: &str, value: &str) -> Result<Regex, String> {
+    Regex::new(value).map_err(|e| format!("Invalid regex for {}: {}", key, e))
+}
+
+/// Lowercase six-digit hex without `#`
+fn normalize_color(color: &str) -> String {
+    let hex = color.trim().trim_start_matches('#').to_lowercase();
+    if hex.len() == 3 {
+        hex.chars().flat_map(|c| [c, c]).collect()
+    } else {
+        hex
+    }
+}
+
+fn parse_condition(key: &str, value: &str) -> Result<Condition, String> {
+    Ok(match key {
+        "type" => Condition::Types(parse_names(key, value)?),
+        "role" => Condition::Roles(parse_names(key, value)?),
+        "font" => Condition::Font(parse_regex(key, value)?),
+        "text" => Condition::Text(parse_regex(key, value)?),
+        "size" => match value.split_once("..") {
+            Some((min, max)) => Condition::Size(
+                if min.is_empty() { f32::MIN } else { parse_number(key, min)? },
+                if max.is_empty() { f32::MAX } else { parse_number(key, max)? },
+            ),
+            None => {
+                let size = parse_number(key, value)?;
+                Condition::Size(size, size)
+            }
+        },
+        "color" => Condition::Color(normalize_color(value)),
+        "in" => {
+            let numbers = value.split(',').map(|n| parse_number(key, n)).collect::<Result<Vec<_>, _>>()?;
+            let [x, y, width, height] = numbers[..] else {
+                return Err(format!("in: takes x,y,width,height, got {}", value));
+            };
+            Condition::Intersects(Bounds::new(x, y, width, height))
+        }
+        "tag" => Condition::Tag(value.to_string()),
+        _ => return Err(format!("Unknown query key: {}", key)),
+    })
+}
+
+#[inline]
+fn intersects(a: &Bounds, b: &Bounds) -> bool {
+    a.x < b.x + b.width && b.x < a.x + a.width && a.y < b.y + b.height && b.y < a.y + a.height
+}
+
+impl Condition {
+    fn holds(&self, layer: &LayerObject) -> bool {
+        match self {
+            Condition::Types(types) => types.contains(&layer.layer_type),
+            Condition::Roles(roles) => roles.contains(&layer.role),
+            Condition::Font(re) => layer.font_family.as_deref().is_some_and(|f| re.is_match(f)),
+            Condition::Text(re) => layer.content.as_deref().is_some_and(|t| re.is_match(t)),
+            Condition::Size(min, max) => layer.font_size.is_some_and(|s| s >= *min && s <= *max),
+            Condition::Color(color) => [&layer.color, &layer.fill_color, &layer.stroke_color]
+                .into_iter()
+                .flatten()
+                .any(|c| normalize_color(c) == *color),
+            Condition::Intersects(rect) => intersects(&layer.bounds, rect),
+            Condition::Tag(tag) => layer.tags.contains(tag),
+        }
+    }
+}
+
+impl LayerQuery {
+    /// Parse a query; an empty query matches every layer
+    pub fn parse(query: &str) -> Result<Self, String> {
+        let terms = tokenize(query)?
+            .into_iter()
+            .map(|(negated, key, value)| Ok((negated, parse_condition(&key, &value)?)))
+            .collect::<Result<_, String>>()?;
+        Ok(Self { terms })
+    }
+
+    pub fn matches(&self, layer: &LayerObject) -> bool {
+        self.terms.iter().all(|(negated, condition)| condition.holds(layer) != *negated)
+    }
+}
+
+/// Ids of the layers matching `query`, for each page with a match
+pub fn query_layers(pages: &[PageData], query: &str) -> Result<Vec<LayerMatches>, String> {
+    let query = LayerQuery::parse(query)?;
+    Ok(pages
+        .iter()
+        .filter_map(|page| {
+            let layer_ids: Vec<String> =
+                page.layers.iter().filter(|l| query.matches(l)).map(|l| l.id.clone()).collect();
+            (!layer_ids.is_empty()).then_some(LayerMatches { page_index: page.page_index, layer_ids })
+        })
+        .collect())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::clipboard::new_layer;
+
+    fn text(id: &str, content: &str, font: &str, size: f32, y: f32) -> LayerObject {
+        let mut layer = new_layer(id.to_string(), LayerType::Text, Bounds::new(72.0, y, 200.0, size * 1.2));
+        layer.content = Some(content.to_string());
+        (layer.font_family, layer.font_size) = (Some(font.to_string()), Some(size));
+        layer.color = Some("#333".to_string());
+        layer
+    }
+
+    #[test]
+    fn test_query_layers() {
+        let mut header = text("h", "Running head", "Times New Roman", 9.0, 20.0);
+        header.role = LayerRole::Header;
+        let mut box_shape = new_layer("s".to_string(), LayerType::Shape, Bounds::new(300.0, 300.0, 50.0, 50.0));
+        box_shape.fill_color = Some("#FF0000".to_string());
+        box_shape.tags = vec!["print-only".to_string()];
+        let page = |page_index: usize, layers: Vec<LayerObject>| PageData {
+            page_index,
+            width: 612.0,
+            height: 792.0,
+            dpi: None,
+            layers,
+            metadata: None,
+            background: None,
+        };
+        let pages = vec![
+            page(0, vec![header, text("t1", "Chapter 1", "Times-Bold", 24.0, 100.0), box_shape]),
+            page(1, vec![text("t2", "small print", "Arial", 6.0, 700.0)]),
+        ];
+        let ids = |query: &str| -> Vec<(usize, Vec<String>)> {
+            query_layers(&pages, query).unwrap().into_iter().map(|m| (m.page_index, m.layer_ids)).collect()
+        };
+
+        assert_eq!(ids("type:text font:/^times/i -role:header"), [(0, vec!["t1".to_string()])]);
+        assert_eq!(ids("size:..8"), [(1, vec!["t2".to_string()])]);
+        assert_eq!(ids("text:/Chapter \\d+/ size:20..30").len(), 1);
+        assert_eq!(ids("color:#ff0000 tag:print-only in:0,0,320,320"), [(0, vec!["s".to_string()])]);
+        assert_eq!(ids("color:#333333 role:header,content").iter().map(|(_, l)| l.len()).sum::<usize>(), 3);
+        assert_eq!(ids("font:\"Times New Roman\"")[0].1, ["h"]);
+        assert_eq!(ids("").iter().map(|(_, l)| l.len()).sum::<usize>(), 4);
+        assert!(ids("in:0,0,10,10").is_empty());
+
+        assert!(query_layers(&pages, "weight:700").unwrap_err().contains("Unknown query key"));
+        assert!(query_layers(&pages, "type:circle").is_err());
+        assert!(query_layers(&pages, "text:/(/").is_err());
+        assert!(query_layers(&pages, "size:big").is_err());
+    }
+}
@@ -0,0 +1,183 @@
+//! Bulk style changes
+//!
+//! A `StyleTransform` applied to every layer a `layer_query` matches: font
+//! families swapped, font sizes scaled, layers shifted, and any fixed
+//! `LayerUpdates` set. The whole document is transformed in one pass and
+//! either returned as a whole or, for a dry run, only reported on, so the
+//! frontend records it as a single undo step. Locked layers are left alone.
+
+use crate::layer_query::LayerQuery;
+use crate::layers::apply_updates;
+use crate::models::{Bounds, LayerObject, LayerUpdates, PageData};
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+
+/// Changes made to each matched layer
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct StyleTransform {
+    /// Font family replacements, old name to new
+    #[serde(default)]
+    pub replace_fonts: BTreeMap<String, String>,
+    /// Font size factor, e.g. 1.1 for 110%
+    #[serde(default)]
+    pub font_scale: Option<f32>,
+    /// Shift in points, right and down
+    #[serde(default)]
+    pub dx: f32,
+    #[serde(default)]
+    pub dy: f32,
+    /// Properties set on every matched layer
+    #[serde(default)]
+    pub set: LayerUpdates,
+}
+
+/// A layer the transform changed, with the properties that differ
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct LayerChange {
+    pub page_index: usize,
+    pub layer_id: String,
+    pub fields: Vec<String>,
+}
+
+/// Outcome of `transform_pages`
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct TransformResult {
+    /// The transformed pages; empty for a dry run
+    pub pages: Vec<PageData>,
+    pub changes: Vec<LayerChange>,
+    /// Matched layers left unchanged because they are locked
+    pub skipped: Vec<LayerChange>,
+}
+
+impl StyleTransform {
+    fn validate(&self) -> Result<(), String> {
+        if self.font_scale.is_some_and(|s| !(s.is_finite() && s > 0.0)) {
+            return Err("Font scale must be a positive number".to_string());
+        }
+        if !(self.dx.is_finite() && self.dy.is_finite()) {
+            return Err("Offset must be finite".to_string());
+        }
+        Ok(())
+    }
+
+    fn apply(&self, layer: &mut LayerObject) {
+        apply_updates(layer, &self.set);
+        if let Some(to) = layer.font_family.as_ref().and_then(|f| self.replace_fonts.get(f)) {
+            layer.font_family = Some(to.clone());
+        }
+        if let (Some(scale), Some(size)) = (self.font_scale, layer.font_size) {
+            apply_updates(layer, &LayerUpdates { font_size: Some(size * scale), ..Default::default() });
+        }
+        if self.dx != 0.0 || self.dy != 0.0 {
+            let b = layer.bounds;
+            layer.bounds = Bounds::new(b.x + self.dx, b.y + self.dy, b.width, b.height);
+        }
+    }
+}
+
+/// Top-level properties that differ between two versions of a layer
+fn changed_fields(before: &LayerObject, after: &LayerObject) -> Vec<String> {
+    let (Ok(serde_json::Value::Object(a)), Ok(serde_json::Value::Object(b))) =
+        (serde_json::to_value(before), serde_json::to_value(after))
+    else {
+        return Vec::new();
+    };
+    let mut fields: Vec<String> = a.keys().chain(b.keys()).filter(|k| a.get(*k) != b.get(*k)).cloned().collect();
+    fields.sort();
+    fields.dedup();
+    fields
+}
+
+/// Apply `transform` to the layers of `pages` matching `query`
+///
+/// Nothing is changed if the query or transform is invalid; with `dry_run`
+/// the changes are reported but no pages returned.
+pub fn transform_pages(
+    pages: Vec<PageData>,
+    query: &str,
+    transform: &StyleTransform,
+    dry_run: bool,
+) -> Result<TransformResult, String> {
+    let query = LayerQuery::parse(query)?;
+    transform.validate()?;
+
+    let mut result = TransformResult::default();
+    let mut pages = pages;
+    for page in &mut pages {
+        for layer in page.layers.iter_mut().filter(|l| query.matches(l)) {
+            let mut updated = layer.clone();
+            transform.apply(&mut updated);
+            let fields = changed_fields(layer, &updated);
+            if fields.is_empty() {
+                continue;
+            }
+            let change = LayerChange { page_index: page.page_index, layer_id: layer.id.clone(), fields };
+            if layer.locked {
+                result.skipped.push(change);
+                continue;
+            }
+            result.changes.push(change);
+            *layer = updated;
+        }
+    }
+    if !dry_run {
+        result.pages = pages;
+    }
+    Ok(result)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::clipboard::new_layer;
+    use crate::models::LayerType;
+
+    #[test]
+    fn test_transform_pages() {
+        let text = |id: &str, font: &str, size: f32| {
+            let mut layer = new_layer(id.to_string(), LayerType::Text, Bounds::new(72.0, 100.0, 200.0, 14.0));
+            (layer.font_family, layer.font_size) = (Some(font.to_string()), Some(size));
+            layer
+        };
+        let mut locked = text("locked", "Times", 10.0);
+        locked.locked = true;
+        let image = new_layer("img".to_string(), LayerType::Image, Bounds::new(0.0, 0.0, 50.0, 50.0));
+        let pages = vec![PageData {
+            page_index: 0,
+            width: 612.0,
+            height: 792.0,
+            dpi: None,
+            layers: vec![text("a", "Times", 10.0), text("b", "Arial", 20.0), locked, image],
+            metadata: None,
+            background: None,
+        }];
+        let transform = StyleTransform {
+            replace_fonts: BTreeMap::from([("Times".to_string(), "Georgia".to_string())]),
+            font_scale: Some(1.1),
+            dx: 5.0,
+            set: LayerUpdates { color: Some("#111111".to_string()), ..Default::default() },
+            ..Default::default()
+        };
+
+        let report = transform_pages(pages.clone(), "type:text", &transform, true).unwrap();
+        assert!(report.pages.is_empty());
+        assert_eq!(report.changes.iter().map(|c| c.layer_id.as_str()).collect::<Vec<_>>(), ["a", "b"]);
+        assert_eq!(report.changes[0].fields, ["bounds", "color", "fontFamily", "fontSize"]);
+        assert_eq!(report.skipped[0].layer_id, "locked");
+
+        let result = transform_pages(pages.clone(), "type:text", &transform, false).unwrap();
+        let a = &result.pages[0].layers[0];
+        assert_eq!((a.font_family.as_deref(), a.bounds.x), (Some("Georgia"), 77.0));
+        assert!((a.font_size.unwrap() - 11.0).abs() < 1e-4);
+        assert_eq!(result.pages[0].layers[1].font_family.as_deref(), Some("Arial"));
+        assert_eq!(result.pages[0].layers[2], pages[0].layers[2]);
+        assert_eq!(result.pages[0].layers[3], pages[0].layers[3]);
+
+        let bad = StyleTransform { font_scale: Some(0.0), ..Default::default() };
+        assert!(transform_pages(pages.clone(), "", &bad, false).is_err());
+        assert!(transform_pages(pages, "nope:1", &transform, false).is_err());
+    }
+}
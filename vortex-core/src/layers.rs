@@ -0,0 +1,541 @@
+//! Layer operations shared by the desktop and web builds
+//!
+//! Z-order changes, update application and alignment all work on a plain
+//! layer slice, so `LayerProcessor` (desktop) and the wasm bindings produce
+//! identical results for the same page.
+//!
+//! Locked layers refuse edits: the `check_*` functions return a
+//! `LockViolation` for updates, deletions and reorders that would change one.
+//! Only the local user can unlock a layer; edits from peers go through
+//! `check_remote_update`, which refuses that too.
+
+use crate::models::{BlendMode, Bounds, LayerObject, LayerUpdates, Pagination, WrapContour};
+use serde::{Deserialize, Serialize};
+
+/// Edge or center to align layers on
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+#[repr(u8)]
+pub enum LayerAlignment {
+    Left = 0,
+    /// Horizontal centers
+    Center = 1,
+    Right = 2,
+    Top = 3,
+    /// Vertical centers
+    Middle = 4,
+    Bottom = 5,
+}
+
+fn not_found(layer_id: &str) -> String {
+    format!("Layer not found: {}", layer_id)
+}
+
+/// Bring a layer to the front (highest z-index)
+pub fn bring_to_front(layers: &mut [LayerObject], layer_id: &str) -> Result<(), String> {
+    let max_z = layers.iter().map(|l| l.z_index).max().unwrap_or(0);
+    let layer = layers.iter_mut().find(|l| l.id == layer_id).ok_or_else(|| not_found(layer_id))?;
+    layer.z_index = max_z + 1;
+    Ok(())
+}
+
+/// Send a layer to the back (lowest z-index)
+pub fn send_to_back(layers: &mut [LayerObject], layer_id: &str) -> Result<(), String> {
+    let min_z = layers.iter().map(|l| l.z_index).min().unwrap_or(0);
+    let layer = layers.iter_mut().find(|l| l.id == layer_id).ok_or_else(|| not_found(layer_id))?;
+    layer.z_index = min_z - 1;
+    Ok(())
+}
+
+/// Move a layer up one position in z-order (no-op at the top)
+pub fn move_up(layers: &mut [LayerObject], layer_id: &str) -> Result<(), String> {
+    let current_z = z_index_of(layers, layer_id)?;
+    let next_z = layers.iter().filter(|l| l.z_index > current_z).map(|l| l.z_index).min();
+    if let Some(swap_z) = next_z {
+        swap_z_indices(layers, layer_id, current_z, swap_z);
+    }
+    Ok(())
+}
+
+/// Move a layer down one position in z-order (no-op at the bottom)
+pub fn move_down(layers: &mut [LayerObject], layer_id: &str) -> Result<(), String> {
+    let current_z = z_index_of(layers, layer_id)?;
+    let prev_z = layers.iter().filter(|l| l.z_index < current_z).map(|l| l.z_index).max();
+    if let Some(swap_z) = prev_z {
+        swap_z_indices(layers, layer_id, current_z, swap_z);
+    }
+    Ok(())
+}
+
+fn z_index_of(layers: &[LayerObject], layer_id: &str) -> Result<i32, String> {
+    layers
+        .iter()
+        .find(|l| l.id == layer_id)
+        .map(|l| l.z_index)
+        .ok_or_else(|| not_found(layer_id))
+}
+
+fn swap_z_indices(layers: &mut [LayerObject], layer_id: &str, current_z: i32, swap_z: i32) {
+    for layer in layers {
+        if layer.id == layer_id {
+            layer.z_index = swap_z;
+        } else if layer.z_index == swap_z {
+            layer.z_index = current_z;
+        }
+    }
+}
+
+/// Sort by z-index and reassign sequential indices starting from 0
+pub fn normalize_z_indices(layers: &mut [LayerObject]) {
+    layers.sort_by_key(|l| l.z_index);
+    for (i, layer) in layers.iter_mut().enumerate() {
+        layer.z_index = i as i32;
+    }
+}
+
+/// Apply layer updates, clamping opacity and font size to valid ranges
+pub fn apply_updates(layer: &mut LayerObject, updates: &LayerUpdates) {
+    // Destructured so a new `LayerUpdates` field can't be forgotten here
+    let LayerUpdates {
+        bounds,
+        visible,
+        locked,
+        z_index,
+        opacity,
+        blend_mode,
+        content,
+        font_family,
+        font_size,
+        font_weight,
+        font_style,
+        color,
+        text_align,
+        text_decoration,
+        text_transform,
+        line_height,
+        letter_spacing,
+        background_color,
+        text_wrap,
+        drop_cap,
+        pagination,
+        role,
+        tags,
+    } = updates;
+
+    if let Some(bounds) = *bounds {
+        layer.bounds = bounds;
+        // Resizing an image changes the resolution it prints at
+        if let Some(meta) = layer.image_data.as_mut() {
+            if let Some(dpi) = meta.effective_dpi(&bounds) {
+                meta.dpi = dpi.round() as u32;
+            }
+        }
+    }
+    if let Some(visible) = *visible {
+        layer.visible = visible;
+    }
+    if let Some(locked) = *locked {
+        layer.locked = locked;
+    }
+    if let Some(z_index) = *z_index {
+        layer.z_index = z_index;
+    }
+    if let Some(opacity) = *opacity {
+        layer.opacity = opacity.clamp(0.0, 1.0);
+    }
+    if let Some(blend_mode) = *blend_mode {
+        layer.blend_mode = (blend_mode != BlendMode::Normal).then_some(blend_mode);
+    }
+    if let Some(wrap) = text_wrap {
+        layer.text_wrap = (wrap.contour != WrapContour::None).then(|| wrap.clone());
+    }
+    if let Some(cap) = drop_cap {
+        layer.drop_cap = (cap.lines >= 2).then(|| cap.clone());
+    }
+    if let Some(pagination) = *pagination {
+        layer.pagination = (pagination != Pagination::default()).then_some(pagination);
+    }
+    if let Some(content) = content {
+        // Notes stay anchored to the same text
+        crate::notes::remap_anchors(&mut layer.notes, layer.content.as_deref().unwrap_or_default(), content);
+        layer.content = Some(content.clone());
+    }
+    if let Some(font_family) = font_family {
+        layer.font_family = Some(font_family.clone());
+    }
+    if let Some(font_size) = *font_size {
+        layer.font_size = Some(font_size.max(1.0));
+    }
+    if let Some(font_weight) = *font_weight {
+        layer.font_weight = Some(font_weight);
+    }
+    if let Some(font_style) = font_style {
+        layer.font_style = Some(font_style.clone());
+    }
+    if let Some(color) = color {
+        layer.color = Some(color.clone());
+    }
+    if let Some(text_align) = *text_align {
+        layer.text_align = Some(text_align);
+    }
+    if let Some(text_decoration) = text_decoration {
+        layer.text_decoration = Some(text_decoration.clone());
+    }
+    if let Some(text_transform) = text_transform {
+        layer.text_transform = Some(text_transform.clone());
+    }
+    if let Some(line_height) = *line_height {
+        layer.line_height = Some(line_height);
+    }
+    if let Some(letter_spacing) = *letter_spacing {
+        layer.letter_spacing = Some(letter_spacing);
+    }
+    if let Some(background_color) = background_color {
+        layer.background_color = Some(background_color.clone());
+    }
+    if let Some(role) = *role {
+        layer.role = role;
+    }
+    if let Some(tags) = tags {
+        layer.tags = tags.clone();
+    }
+}
+
+/// An edit refused because it would change locked layers
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct LockViolation {
+    pub page_index: usize,
+    pub layer_ids: Vec<String>,
+    pub message: String,
+}
+
+impl LockViolation {
+    fn new(page_index: usize, layer_ids: Vec<String>, action: &str) -> Self {
+        let message = match layer_ids.as_slice() {
+            [id] => format!("Layer {} is locked and cannot be {}", id, action),
+            ids => format!("Layers {} are locked and cannot be {}", ids.join(", "), action),
+        };
+        Self { page_index, layer_ids, message }
+    }
+}
+
+impl std::fmt::Display for LockViolation {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(&self.message)
+    }
+}
+
+/// Refuse updates to a locked layer, except one that only unlocks it
+pub fn check_update(page_index: usize, layer: &LayerObject, updates: &LayerUpdates) -> Result<(), LockViolation> {
+    let unlock_only = *updates == LayerUpdates { locked: Some(false), ..Default::default() };
+    if layer.locked && !unlock_only {
+        return Err(LockViolation::new(page_index, vec![layer.id.clone()], "edited"));
+    }
+    Ok(())
+}
+
+/// Apply `updates` to the layer `layer_id` among a page's `layers` and
+/// return the result, `None` when the page has no such layer. A locked
+/// layer refuses the update as in `check_update` unless `force` is set.
+pub fn update_page_layer(
+    page_index: usize,
+    layers: &[LayerObject],
+    layer_id: &str,
+    updates: &LayerUpdates,
+    force: bool,
+) -> Result<Option<LayerObject>, LockViolation> {
+    let Some(current) = layers.iter().find(|l| l.id == layer_id) else {
+        return Ok(None);
+    };
+    if !force {
+        check_update(page_index, current, updates)?;
+    }
+    let mut layer = current.clone();
+    apply_updates(&mut layer, updates);
+    Ok(Some(layer))
+}
+
+/// Refuse every update to a locked layer, unlocking included
+pub fn check_remote_update(page_index: usize, layer: &LayerObject) -> Result<(), LockViolation> {
+    if layer.locked {
+        return Err(LockViolation::new(page_index, vec![layer.id.clone()], "edited"));
+    }
+    Ok(())
+}
+
+/// Refuse deleting a locked layer
+pub fn check_delete(page_index: usize, layer: &LayerObject) -> Result<(), LockViolation> {
+    if layer.locked {
+        return Err(LockViolation::new(page_index, vec![layer.id.clone()], "deleted"));
+    }
+    Ok(())
+}
+
+/// Refuse a new paint order (`layer_ids`, bottom first) that moves a
+/// locked layer away from its current position
+pub fn check_reorder(page_index: usize, layers: &[LayerObject], layer_ids: &[String]) -> Result<(), LockViolation> {
+    let mut current: Vec<&LayerObject> = layers.iter().collect();
+    current.sort_by_key(|l| l.z_index);
+    let moved: Vec<String> = current
+        .iter()
+        .enumerate()
+        .filter(|(i, l)| l.locked && layer_ids.get(*i) != Some(&l.id))
+        .map(|(_, l)| l.id.clone())
+        .collect();
+    if moved.is_empty() {
+        Ok(())
+    } else {
+        Err(LockViolation::new(page_index, moved, "moved"))
+    }
+}
+
+/// Bounding box of the given layers, `None` if none of them exist
+pub fn selection_bounds(layers: &[LayerObject], layer_ids: &[String]) -> Option<Bounds> {
+    let mut selected = layers.iter().filter(|l| layer_ids.contains(&l.id));
+    let first = selected.next()?.bounds;
+    let (mut min_x, mut min_y) = (first.x, first.y);
+    let (mut max_x, mut max_y) = (first.x + first.width, first.y + first.height);
+    for layer in selected {
+        let b = layer.bounds;
+        min_x = min_x.min(b.x);
+        min_y = min_y.min(b.y);
+        max_x = max_x.max(b.x + b.width);
+        max_y = max_y.max(b.y + b.height);
+    }
+    Some(Bounds::new(min_x, min_y, max_x - min_x, max_y - min_y))
+}
+
+/// Align layers to the edge or center of their combined bounding box
+///
+/// Locked layers keep their position but still count towards the box, so
+/// they act as anchors. Returns the number of layers moved.
+pub fn align_layers(
+    layers: &mut [LayerObject],
+    layer_ids: &[String],
+    alignment: LayerAlignment,
+) -> Result<usize, String> {
+    let target = selection_bounds(layers, layer_ids)
+        .ok_or_else(|| "No matching layers to align".to_string())?;
+
+    let mut moved = 0;
+    for layer in layers.iter_mut().filter(|l| !l.locked && layer_ids.contains(&l.id)) {
+        let b = &mut layer.bounds;
+        let (x, y) = match alignment {
+            LayerAlignment::Left => (target.x, b.y),
+            LayerAlignment::Center => (target.x + (target.width - b.width) / 2.0, b.y),
+            LayerAlignment::Right => (target.x + target.width - b.width, b.y),
+            LayerAlignment::Top => (b.x, target.y),
+            LayerAlignment::Middle => (b.x, target.y + (target.height - b.height) / 2.0),
+            LayerAlignment::Bottom => (b.x, target.y + target.height - b.height),
+        };
+        if x != b.x || y != b.y {
+            b.x = x;
+            b.y = y;
+            moved += 1;
+        }
+    }
+    Ok(moved)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::{ImageMetadata, LayerRole, LayerType, SourceType};
+
+    fn layer(id: &str, z_index: i32, bounds: Bounds) -> LayerObject {
+        LayerObject {
+            id: id.to_string(),
+            layer_type: LayerType::Shape,
+            bounds,
+            visible: true,
+            locked: false,
+            z_index,
+            opacity: 1.0,
+            blend_mode: None,
+            content: None,
+            font_family: None,
+            font_size: None,
+            font_weight: None,
+            font_style: None,
+            color: None,
+            text_align: None,
+            text_decoration: None,
+            text_transform: None,
+            line_height: None,
+            letter_spacing: None,
+            background_color: None,
+            text_path: None,
+            text_outline: None,
+            text_shadow: None,
+            drop_cap: None,
+            notes: Vec::new(),
+            pagination: None,
+            image_url: None,
+            image_path: None,
+            image_data: None,
+            image_link: None,
+            shape_type: None,
+            stroke_color: None,
+            stroke_width: None,
+            fill_color: None,
+            text_wrap: None,
+            path_data: None,
+            transform: None,
+            source_type: SourceType::Manual,
+            role: LayerRole::Content,
+            ocr: None,
+            tags: Vec::new(),
+        }
+    }
+
+    fn ids(ids: &[&str]) -> Vec<String> {
+        ids.iter().map(|s| s.to_string()).collect()
+    }
+
+    #[test]
+    fn test_align_left_and_middle() {
+        let mut layers = vec![
+            layer("a", 0, Bounds::new(10.0, 0.0, 50.0, 20.0)),
+            layer("b", 1, Bounds::new(40.0, 100.0, 20.0, 40.0)),
+            layer("c", 2, Bounds::new(300.0, 300.0, 10.0, 10.0)),
+        ];
+
+        let moved = align_layers(&mut layers, &ids(&["a", "b"]), LayerAlignment::Left).unwrap();
+        assert_eq!(moved, 1);
+        assert_eq!(layers[1].bounds.x, 10.0);
+        assert_eq!(layers[2].bounds.x, 300.0);
+
+        // Combined box spans y 0..140, so centers land on y = 70
+        align_layers(&mut layers, &ids(&["a", "b"]), LayerAlignment::Middle).unwrap();
+        assert_eq!(layers[0].bounds.y, 60.0);
+        assert_eq!(layers[1].bounds.y, 50.0);
+    }
+
+    #[test]
+    fn test_align_skips_locked_layers() {
+        let mut layers = vec![
+            layer("a", 0, Bounds::new(0.0, 0.0, 10.0, 10.0)),
+            layer("b", 1, Bounds::new(90.0, 0.0, 10.0, 10.0)),
+        ];
+        layers[1].locked = true;
+
+        let moved = align_layers(&mut layers, &ids(&["a", "b"]), LayerAlignment::Right).unwrap();
+        assert_eq!(moved, 1);
+        assert_eq!(layers[0].bounds.x, 90.0);
+        assert!(align_layers(&mut layers, &ids(&["missing"]), LayerAlignment::Top).is_err());
+    }
+
+    #[test]
+    fn test_locked_layers_refuse_edits() {
+        let mut layers = vec![
+            layer("a", 0, Bounds::new(0.0, 0.0, 10.0, 10.0)),
+            layer("b", 1, Bounds::new(0.0, 0.0, 10.0, 10.0)),
+            layer("c", 2, Bounds::new(0.0, 0.0, 10.0, 10.0)),
+        ];
+        layers[1].locked = true;
+
+        let move_it = LayerUpdates { bounds: Some(Bounds::new(5.0, 5.0, 10.0, 10.0)), ..Default::default() };
+        let violation = check_update(3, &layers[1], &move_it).unwrap_err();
+        assert_eq!((violation.page_index, violation.layer_ids.clone()), (3, ids(&["b"])));
+        assert_eq!(violation.to_string(), "Layer b is locked and cannot be edited");
+        let unlock = LayerUpdates { locked: Some(false), ..Default::default() };
+        assert!(check_update(3, &layers[1], &unlock).is_ok());
+        // Unlocking can't carry other edits along
+        let unlock_and_move = LayerUpdates { locked: Some(false), ..move_it.clone() };
+        assert!(check_update(3, &layers[1], &unlock_and_move).is_err());
+        assert!(check_remote_update(3, &layers[1]).is_err() && check_remote_update(3, &layers[0]).is_ok());
+        assert!(check_update(3, &layers[0], &move_it).is_ok());
+        assert!(check_delete(3, &layers[1]).is_err() && check_delete(3, &layers[2]).is_ok());
+        assert!(update_page_layer(3, &layers, "b", &move_it, false).is_err());
+        let forced = update_page_layer(3, &layers, "b", &move_it, true).unwrap().unwrap();
+        assert_eq!(forced.bounds.x, 5.0);
+        assert!(update_page_layer(3, &layers, "zz", &move_it, false).unwrap().is_none());
+
+        // Swapping the unlocked layers around the locked one is fine; moving it is not
+        assert!(check_reorder(0, &layers, &ids(&["c", "b", "a"])).is_ok());
+        assert_eq!(check_reorder(0, &layers, &ids(&["b", "a", "c"])).unwrap_err().layer_ids, ids(&["b"]));
+    }
+
+    #[test]
+    fn test_normalize_after_reorder() {
+        let mut layers = vec![
+            layer("a", 5, Bounds::new(0.0, 0.0, 1.0, 1.0)),
+            layer("b", -3, Bounds::new(0.0, 0.0, 1.0, 1.0)),
+            layer("c", 9, Bounds::new(0.0, 0.0, 1.0, 1.0)),
+        ];
+        move_up(&mut layers, "b").unwrap();
+        normalize_z_indices(&mut layers);
+        let order: Vec<&str> = layers.iter().map(|l| l.id.as_str()).collect();
+        assert_eq!(order, vec!["a", "b", "c"]);
+        assert_eq!(layers.iter().map(|l| l.z_index).collect::<Vec<_>>(), vec![0, 1, 2]);
+    }
+
+    #[test]
+    fn test_resize_recalculates_image_dpi() {
+        // 600px across 144pt (2in) prints at 300dpi
+        let mut image = layer("img", 0, Bounds::new(0.0, 0.0, 144.0, 144.0));
+        image.image_data = Some(ImageMetadata { width: 600, height: 600, color_space: "RGB".to_string(), dpi: 300, icc_profile: None });
+        let updates = LayerUpdates { bounds: Some(Bounds::new(0.0, 0.0, 288.0, 216.0)), ..Default::default() };
+        apply_updates(&mut image, &updates);
+        assert_eq!(image.image_data.unwrap().dpi, 150);
+    }
+
+    #[test]
+    fn test_every_update_field_is_applied() {
+        let updates = LayerUpdates {
+            bounds: Some(Bounds::new(1.0, 2.0, 3.0, 4.0)),
+            visible: Some(false),
+            locked: Some(true),
+            z_index: Some(7),
+            opacity: Some(0.5),
+            blend_mode: Some(BlendMode::Multiply),
+            content: Some("Hello".to_string()),
+            font_family: Some("Garamond".to_string()),
+            font_size: Some(14.0),
+            font_weight: Some(700),
+            font_style: Some("italic".to_string()),
+            color: Some("#112233".to_string()),
+            text_align: Some(crate::models::TextAlign::Center),
+            text_decoration: Some("underline".to_string()),
+            text_transform: Some("uppercase".to_string()),
+            line_height: Some(1.4),
+            letter_spacing: Some(0.2),
+            background_color: Some("#FFEEDD".to_string()),
+            text_wrap: Some(crate::models::TextWrap { contour: WrapContour::BoundingBox, offset: 3.0 }),
+            drop_cap: Some(crate::models::DropCap { lines: 3, ..Default::default() }),
+            pagination: Some(Pagination { keep_together: true, ..Default::default() }),
+            role: Some(LayerRole::Header),
+            tags: Some(vec!["draft".to_string()]),
+        };
+        let mut updated = layer("t", 0, Bounds::new(0.0, 0.0, 10.0, 10.0));
+        apply_updates(&mut updated, &updates);
+
+        let applied = LayerUpdates {
+            bounds: Some(updated.bounds),
+            visible: Some(updated.visible),
+            locked: Some(updated.locked),
+            z_index: Some(updated.z_index),
+            opacity: Some(updated.opacity),
+            blend_mode: updated.blend_mode,
+            content: updated.content,
+            font_family: updated.font_family,
+            font_size: updated.font_size,
+            font_weight: updated.font_weight,
+            font_style: updated.font_style,
+            color: updated.color,
+            text_align: updated.text_align,
+            text_decoration: updated.text_decoration,
+            text_transform: updated.text_transform,
+            line_height: updated.line_height,
+            letter_spacing: updated.letter_spacing,
+            background_color: updated.background_color,
+            text_wrap: updated.text_wrap,
+            drop_cap: updated.drop_cap,
+            pagination: updated.pagination,
+            role: Some(updated.role),
+            tags: Some(updated.tags),
+        };
+        assert_eq!(applied, updates);
+    }
+}
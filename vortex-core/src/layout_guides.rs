@@ -0,0 +1,277 @@
+//! Layout guides
+//!
+//! Infers the grid an imported book was set on from its layer positions:
+//! the baseline grid from the spacing of body text lines, margins from the
+//! extent of the text block and column edges from aligned line ends facing
+//! aligned line starts across a gutter. Facing pages mirror their margins,
+//! so even and odd pages are measured separately. The guides are stored on
+//! each page's metadata and new elements snap to them.
+
+use crate::models::{BaselineGrid, Bounds, LayerObject, LayerRole, LayerType, PageData, PageGuides, PageMetadata};
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+
+/// Baseline below the top of a text layer, in font sizes (as in `text_ops`)
+const ASCENT: f32 = 0.8;
+/// Edges within this distance are one guide
+const EDGE_TOLERANCE: f32 = 1.5;
+/// Share of a page side's text lines that must line up on a column edge
+const MIN_EDGE_SHARE: f32 = 0.1;
+const MIN_EDGE_LINES: usize = 3;
+/// Gutter widths accepted between columns
+const MIN_GUTTER: f32 = 4.0;
+const MAX_GUTTER: f32 = 48.0;
+/// Line spacing range considered, in font sizes
+const MIN_LEADING: f32 = 0.9;
+const MAX_LEADING: f32 = 2.5;
+/// Snapping distance in points when none is given
+pub const DEFAULT_SNAP_THRESHOLD: f32 = 4.0;
+
+/// Bounds after snapping and the guides they snapped to, for drawing
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct SnapResult {
+    pub bounds: Bounds,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub vertical: Option<f32>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub horizontal: Option<f32>,
+}
+
+fn is_body_text(layer: &LayerObject) -> bool {
+    layer.visible
+        && layer.role == LayerRole::Content
+        && layer.layer_type == LayerType::Text
+        && layer.content.as_deref().is_some_and(|c| !c.trim().is_empty())
+}
+
+fn font_size(layer: &LayerObject) -> f32 {
+    layer.font_size.unwrap_or(12.0)
+}
+
+/// Single-line layers; merged blocks have no measurable line positions
+fn is_line(layer: &LayerObject) -> bool {
+    layer.bounds.height < font_size(layer) * 1.6
+}
+
+fn median(mut values: Vec<f32>) -> Option<f32> {
+    values.sort_by(f32::total_cmp);
+    values.get(values.len() / 2).copied()
+}
+
+/// Values grouped within `EDGE_TOLERANCE`, as (mean, count)
+fn clusters(mut values: Vec<f32>) -> Vec<(f32, usize)> {
+    values.sort_by(f32::total_cmp);
+    let mut groups: Vec<(f32, f32, usize)> = Vec::new();
+    for value in values {
+        match groups.last_mut() {
+            Some((first, sum, count)) if value - *first <= EDGE_TOLERANCE => {
+                *sum += value;
+                *count += 1;
+            }
+            _ => groups.push((value, value, 1)),
+        }
+    }
+    groups.into_iter().map(|(_, sum, count)| (sum / count as f32, count)).collect()
+}
+
+/// The most common line spacing of body-size lines and where the grid starts
+fn baseline_grid(pages: &[PageData]) -> Option<BaselineGrid> {
+    let lines: Vec<(&PageData, &LayerObject)> =
+        pages.iter().flat_map(|p| p.layers.iter().filter(|l| is_body_text(l) && is_line(l)).map(move |l| (p, l))).collect();
+
+    // Body size: the size, to the half point, most lines are set in
+    let mut by_size: BTreeMap<u32, usize> = BTreeMap::new();
+    for (_, layer) in &lines {
+        *by_size.entry((font_size(layer) * 2.0).round() as u32).or_default() += 1;
+    }
+    let body_key = *by_size.iter().max_by_key(|&(_, &count)| count)?.0;
+    let body_size = body_key as f32 / 2.0;
+
+    let mut spacings: BTreeMap<u32, (f32, usize)> = BTreeMap::new();
+    let mut baselines = Vec::new();
+    for page in pages {
+        let mut page_baselines: Vec<f32> = lines
+            .iter()
+            .filter(|(p, l)| p.page_index == page.page_index && (font_size(l) * 2.0).round() as u32 == body_key)
+            .map(|(_, l)| l.bounds.y + font_size(l) * ASCENT)
+            .collect();
+        page_baselines.sort_by(f32::total_cmp);
+        page_baselines.dedup_by(|a, b| (*a - *b).abs() < EDGE_TOLERANCE);
+        for pair in page_baselines.windows(2) {
+            let spacing = pair[1] - pair[0];
+            if (MIN_LEADING * body_size..=MAX_LEADING * body_size).contains(&spacing) {
+                let entry = spacings.entry((spacing * 4.0).round() as u32).or_default();
+                entry.0 += spacing;
+                entry.1 += 1;
+            }
+        }
+        baselines.extend(page_baselines);
+    }
+    let (sum, count) = *spacings.values().max_by_key(|&&(_, count)| count)?;
+    let spacing = sum / count as f32;
+
+    // Offset: the phase most baselines share, averaged within its half-point bucket
+    let buckets = (spacing * 2.0).round().max(1.0) as u32;
+    let mut phases: BTreeMap<u32, Vec<f32>> = BTreeMap::new();
+    for baseline in baselines {
+        let phase = baseline.rem_euclid(spacing);
+        phases.entry((phase * 2.0).round() as u32 % buckets).or_default().push(phase);
+    }
+    let (&bucket, phases) = phases.iter().max_by_key(|(_, p)| p.len())?;
+    // Phases near the spacing wrap round to bucket 0; count them as near 0
+    let offset = phases.iter().map(|&p| if bucket == 0 && p > spacing / 2.0 { p - spacing } else { p }).sum::<f32>()
+        / phases.len() as f32;
+    Some(BaselineGrid { spacing, offset: offset.rem_euclid(spacing) })
+}
+
+/// Margin and column guides of the pages on one side of the spread
+fn side_guides(pages: &[&PageData]) -> PageGuides {
+    let mut extents: [Vec<f32>; 4] = Default::default();
+    let mut lefts = Vec::new();
+    let mut rights = Vec::new();
+    for page in pages {
+        let texts: Vec<&LayerObject> = page.layers.iter().filter(|l| is_body_text(l)).collect();
+        if texts.is_empty() {
+            continue;
+        }
+        let b = |f: fn(&Bounds) -> f32| texts.iter().map(move |l| f(&l.bounds));
+        extents[0].push(b(|b| b.x).fold(f32::MAX, f32::min));
+        extents[1].push(b(|b| b.y).fold(f32::MAX, f32::min));
+        extents[2].push(b(|b| b.x + b.width).fold(f32::MIN, f32::max));
+        extents[3].push(b(|b| b.y + b.height).fold(f32::MIN, f32::max));
+        lefts.extend(b(|b| b.x));
+        rights.extend(b(|b| b.x + b.width));
+    }
+    let [left, top, right, bottom] = extents.map(median);
+    let (Some(left), Some(top), Some(right), Some(bottom)) = (left, top, right, bottom) else {
+        return PageGuides::default();
+    };
+
+    let min_count = MIN_EDGE_LINES.max((lefts.len() as f32 * MIN_EDGE_SHARE).ceil() as usize);
+    let frequent = |values: Vec<f32>| -> Vec<f32> {
+        clusters(values).into_iter().filter(|&(_, count)| count >= min_count).map(|(x, _)| x).collect()
+    };
+    let (starts, ends) = (frequent(lefts), frequent(rights));
+
+    let mut vertical = vec![left];
+    for &start in starts.iter().filter(|&&x| x > left + MAX_GUTTER && x < right) {
+        let end = ends.iter().copied().rfind(|&e| (MIN_GUTTER..=MAX_GUTTER).contains(&(start - e)));
+        if let Some(end) = end {
+            vertical.extend([end, start]);
+        }
+    }
+    vertical.push(right);
+    PageGuides { vertical, horizontal: vec![top, bottom], baseline_grid: None }
+}
+
+/// Guides for each page of `pages`, in order
+pub fn detect_guides(pages: &[PageData]) -> Vec<PageGuides> {
+    let grid = baseline_grid(pages);
+    let sides: Vec<PageGuides> = (0..2)
+        .map(|parity| side_guides(&pages.iter().filter(|p| p.page_index % 2 == parity).collect::<Vec<_>>()))
+        .collect();
+    pages
+        .iter()
+        .map(|page| PageGuides { baseline_grid: grid, ..sides[page.page_index % 2].clone() })
+        .collect()
+}
+
+/// Store detected guides on each page's metadata
+pub fn apply_guides(pages: &mut [PageData]) {
+    let guides = detect_guides(pages);
+    for (page, guides) in pages.iter_mut().zip(guides) {
+        page.metadata.get_or_insert_with(PageMetadata::default).guides = Some(guides);
+    }
+}
+
+/// The nearest `target` within `threshold` of any of `edges`, as (shift, guide)
+fn nearest(edges: &[f32], targets: impl Iterator<Item = f32> + Clone, threshold: f32) -> Option<(f32, f32)> {
+    edges
+        .iter()
+        .flat_map(|&edge| targets.clone().map(move |target| (target - edge, target)))
+        .filter(|(shift, _)| shift.abs() <= threshold)
+        .min_by(|a, b| a.0.abs().total_cmp(&b.0.abs()))
+}
+
+/// Move `bounds` onto the nearest guides within `threshold` points
+///
+/// The left, right and center snap to vertical guides; the top and bottom
+/// to horizontal guides and, for text, `baseline` (its distance below the
+/// top) to the baseline grid.
+pub fn snap_bounds(guides: &PageGuides, bounds: Bounds, baseline: Option<f32>, threshold: f32) -> SnapResult {
+    let mut snapped = SnapResult { bounds, vertical: None, horizontal: None };
+    let xs = [bounds.x, bounds.x + bounds.width, bounds.x + bounds.width / 2.0];
+    if let Some((shift, guide)) = nearest(&xs, guides.vertical.iter().copied(), threshold) {
+        snapped.bounds.x += shift;
+        snapped.vertical = Some(guide);
+    }
+
+    let ys = [bounds.y, bounds.y + bounds.height];
+    let mut best = nearest(&ys, guides.horizontal.iter().copied(), threshold);
+    if let (Some(grid), Some(baseline)) = (guides.baseline_grid, baseline) {
+        let y = bounds.y + baseline;
+        let line = grid.offset + ((y - grid.offset) / grid.spacing).round() * grid.spacing;
+        if (line - y).abs() <= threshold && best.map_or(true, |(shift, _)| (line - y).abs() < shift.abs()) {
+            best = Some((line - y, line));
+        }
+    }
+    if let Some((shift, guide)) = best {
+        snapped.bounds.y += shift;
+        snapped.horizontal = Some(guide);
+    }
+    snapped
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::clipboard::new_layer;
+
+    /// Two 12pt columns on a 14pt grid starting at 86.6, mirrored margins
+    fn book_page(page_index: usize) -> PageData {
+        let left = if page_index % 2 == 0 { 72.0 } else { 54.0 };
+        let mut layers = Vec::new();
+        for column in 0..2 {
+            let x = left + column as f32 * 252.0;
+            for line in 0..20 {
+                let id = format!("l{}-{}-{}", page_index, column, line);
+                // Indented first lines do not make a column
+                let indent = if line % 5 == 0 { 12.0 } else { 0.0 };
+                let mut layer = new_layer(id, LayerType::Text, Bounds::new(x + indent, 77.0 + line as f32 * 14.0, 240.0 - indent, 14.4));
+                layer.content = Some("Lorem ipsum dolor sit amet".to_string());
+                layer.font_size = Some(12.0);
+                layers.push(layer);
+            }
+        }
+        let mut heading = new_layer(format!("h{}", page_index), LayerType::Text, Bounds::new(left, 40.0, 300.0, 28.8));
+        heading.content = Some("Chapter".to_string());
+        heading.font_size = Some(24.0);
+        layers.push(heading);
+        PageData { page_index, width: 612.0, height: 792.0, dpi: None, layers, metadata: None, background: None }
+    }
+
+    #[test]
+    fn test_detect_and_snap() {
+        let mut pages: Vec<PageData> = (0..4).map(book_page).collect();
+        apply_guides(&mut pages);
+
+        let guides = pages[0].metadata.as_ref().unwrap().guides.clone().unwrap();
+        let grid = guides.baseline_grid.unwrap();
+        assert!((grid.spacing - 14.0).abs() < 0.01);
+        assert!((grid.offset - 86.6f32.rem_euclid(14.0)).abs() < 0.01);
+        assert_eq!(guides.vertical, [72.0, 312.0, 324.0, 564.0]);
+        assert_eq!(guides.horizontal, [40.0, 77.0 + 19.0 * 14.0 + 14.4]);
+        let verso = pages[1].metadata.as_ref().unwrap().guides.as_ref().unwrap();
+        assert_eq!(verso.vertical, [54.0, 294.0, 306.0, 546.0]);
+
+        // Left edge onto the gutter, baseline onto the grid
+        let snap = snap_bounds(&guides, Bounds::new(326.5, 103.0, 100.0, 14.4), Some(9.6), 4.0);
+        assert_eq!(snap.vertical, Some(324.0));
+        assert_eq!(snap.bounds.x, 324.0);
+        assert!((snap.bounds.y - 105.0).abs() < 0.01);
+        // Too far from any guide
+        let free = snap_bounds(&guides, Bounds::new(150.0, 200.0, 20.0, 20.0), None, 4.0);
+        assert_eq!((free.vertical, free.horizontal), (None, None));
+    }
+}
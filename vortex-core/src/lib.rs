@@ -0,0 +1,59 @@
+//! Vortex Core
+//!
+//! Platform-independent document model and logic shared by the Tauri backend
+//! (src-tauri) and the browser build (src-wasm). Both consume these types
+//! directly so their JSON wire formats cannot drift apart.
+//!
+//! ## Features
+//! - `pdf` (default): lopdf content stream parsing (`content_parser`).
+//!   The wasm build disables it.
+//! - `archive` (default): zip project container with embedded images
+//!   (`archive`).
+//! - `test-util`: layer and page fixture builders (`test_util`) for the
+//!   unit tests of dependent crates.
+
+#[cfg(feature = "archive")]
+pub mod archive;
+pub mod clipboard;
+pub mod contact_sheet;
+#[cfg(feature = "pdf")]
+pub mod content_parser;
+pub mod cover;
+pub mod decorations;
+pub mod doc_metadata;
+pub mod doc_structure;
+pub mod document_query;
+pub mod epub;
+pub mod export;
+pub mod font_names;
+pub mod graphics_state;
+pub mod image_crop;
+pub mod image_place;
+pub mod image_trace;
+pub mod import_mapping;
+pub mod layer_cleanup;
+pub mod layer_ids;
+pub mod layer_query;
+pub mod layer_transform;
+pub mod layers;
+pub mod layout_guides;
+pub mod models;
+pub mod msgpack;
+pub mod notes;
+pub mod ocr_correction;
+pub mod ocr_review;
+pub mod page_labels;
+pub mod page_layout;
+pub mod page_setup;
+pub mod path_ops;
+pub mod reflow;
+pub mod sections;
+pub mod speech;
+pub mod text_ops;
+pub mod text_path;
+pub mod text_structure;
+#[cfg(any(test, feature = "test-util"))]
+pub mod test_util;
+pub mod text_wrap;
+pub mod typography;
+pub mod units;
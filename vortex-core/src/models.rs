@@ -0,0 +1,1455 @@
+//! Data models for the Book Creation Converter
+//!
+//! This module defines the core data structures used throughout the application,
+//! including layers, pages, documents, and related types.
+//!
+//! ## Optimizations
+//! - `Copy` derive where possible for stack allocation
+//! - `#[repr(u8)]` for compact enum representation
+//! - `Eq` derive for hash-based collections
+//! - `#[inline]` hints for hot paths
+
+use crate::units::POINTS_PER_INCH;
+use std::collections::BTreeMap;
+use serde::{Deserialize, Serialize};
+
+/// Layer type enumeration
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq, Hash)]
+#[serde(rename_all = "lowercase")]
+#[repr(u8)]
+pub enum LayerType {
+    Text = 0,
+    Image = 1,
+    Vector = 2,
+    Shape = 3,
+}
+
+impl std::fmt::Display for LayerType {
+    #[inline]
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            LayerType::Text => write!(f, "text"),
+            LayerType::Image => write!(f, "image"),
+            LayerType::Vector => write!(f, "vector"),
+            LayerType::Shape => write!(f, "shape"),
+        }
+    }
+}
+
+/// Text alignment options
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq, Default)]
+#[serde(rename_all = "lowercase")]
+#[repr(u8)]
+pub enum TextAlign {
+    #[default]
+    Left = 0,
+    Center = 1,
+    Right = 2,
+}
+
+/// Shape type enumeration
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+#[repr(u8)]
+pub enum ShapeType {
+    Rectangle = 0,
+    Circle = 1,
+    Line = 2,
+    Polygon = 3,
+}
+
+/// Source type indicating how the layer was created
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+#[repr(u8)]
+pub enum SourceType {
+    Extracted = 0,
+    Manual = 1,
+    Imported = 2,
+}
+
+impl std::fmt::Display for SourceType {
+    #[inline]
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            SourceType::Extracted => write!(f, "extracted"),
+            SourceType::Manual => write!(f, "manual"),
+            SourceType::Imported => write!(f, "imported"),
+        }
+    }
+}
+
+/// Layer role in the document
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+#[repr(u8)]
+pub enum LayerRole {
+    Background = 0,
+    Content = 1,
+    Header = 2,
+    Footer = 3,
+    Annotation = 4,
+}
+
+impl std::fmt::Display for LayerRole {
+    #[inline]
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            LayerRole::Background => write!(f, "background"),
+            LayerRole::Content => write!(f, "content"),
+            LayerRole::Header => write!(f, "header"),
+            LayerRole::Footer => write!(f, "footer"),
+            LayerRole::Annotation => write!(f, "annotation"),
+        }
+    }
+}
+
+/// How a layer composites onto what is beneath it (PDF `/BM`, CSS `mix-blend-mode`)
+#[derive(Debug, Clone, Copy, Default, Serialize, Deserialize, PartialEq, Eq, Hash)]
+#[serde(rename_all = "kebab-case")]
+#[repr(u8)]
+pub enum BlendMode {
+    #[default]
+    Normal = 0,
+    Multiply = 1,
+    Screen = 2,
+    Overlay = 3,
+    Darken = 4,
+    Lighten = 5,
+    ColorDodge = 6,
+    ColorBurn = 7,
+    HardLight = 8,
+    SoftLight = 9,
+    Difference = 10,
+    Exclusion = 11,
+    Hue = 12,
+    Saturation = 13,
+    Color = 14,
+    Luminosity = 15,
+}
+
+impl BlendMode {
+    pub const ALL: [BlendMode; 16] = [
+        BlendMode::Normal,
+        BlendMode::Multiply,
+        BlendMode::Screen,
+        BlendMode::Overlay,
+        BlendMode::Darken,
+        BlendMode::Lighten,
+        BlendMode::ColorDodge,
+        BlendMode::ColorBurn,
+        BlendMode::HardLight,
+        BlendMode::SoftLight,
+        BlendMode::Difference,
+        BlendMode::Exclusion,
+        BlendMode::Hue,
+        BlendMode::Saturation,
+        BlendMode::Color,
+        BlendMode::Luminosity,
+    ];
+
+    /// Name used in a PDF ExtGState `/BM` entry
+    pub const fn pdf_name(&self) -> &'static str {
+        match self {
+            BlendMode::Normal => "Normal",
+            BlendMode::Multiply => "Multiply",
+            BlendMode::Screen => "Screen",
+            BlendMode::Overlay => "Overlay",
+            BlendMode::Darken => "Darken",
+            BlendMode::Lighten => "Lighten",
+            BlendMode::ColorDodge => "ColorDodge",
+            BlendMode::ColorBurn => "ColorBurn",
+            BlendMode::HardLight => "HardLight",
+            BlendMode::SoftLight => "SoftLight",
+            BlendMode::Difference => "Difference",
+            BlendMode::Exclusion => "Exclusion",
+            BlendMode::Hue => "Hue",
+            BlendMode::Saturation => "Saturation",
+            BlendMode::Color => "Color",
+            BlendMode::Luminosity => "Luminosity",
+        }
+    }
+
+    /// Value for CSS `mix-blend-mode` (also valid in SVG)
+    pub const fn css_name(&self) -> &'static str {
+        match self {
+            BlendMode::Normal => "normal",
+            BlendMode::Multiply => "multiply",
+            BlendMode::Screen => "screen",
+            BlendMode::Overlay => "overlay",
+            BlendMode::Darken => "darken",
+            BlendMode::Lighten => "lighten",
+            BlendMode::ColorDodge => "color-dodge",
+            BlendMode::ColorBurn => "color-burn",
+            BlendMode::HardLight => "hard-light",
+            BlendMode::SoftLight => "soft-light",
+            BlendMode::Difference => "difference",
+            BlendMode::Exclusion => "exclusion",
+            BlendMode::Hue => "hue",
+            BlendMode::Saturation => "saturation",
+            BlendMode::Color => "color",
+            BlendMode::Luminosity => "luminosity",
+        }
+    }
+
+    /// Parse a PDF blend mode name; `Compatible` is the legacy alias of `Normal`
+    pub fn from_pdf_name(name: &[u8]) -> Option<Self> {
+        if name == b"Compatible" {
+            return Some(BlendMode::Normal);
+        }
+        Self::ALL.into_iter().find(|mode| mode.pdf_name().as_bytes() == name)
+    }
+}
+
+/// Inline CSS for a layer's opacity and blend mode, empty when fully opaque and normal
+pub fn transparency_css(layer: &LayerObject) -> String {
+    let mut css = String::new();
+    if layer.opacity < 1.0 {
+        css.push_str(&format!("opacity:{};", (layer.opacity.clamp(0.0, 1.0) * 1000.0).round() / 1000.0));
+    }
+    if let Some(mode) = layer.blend_mode.filter(|m| *m != BlendMode::Normal) {
+        css.push_str(&format!("mix-blend-mode:{};", mode.css_name()));
+    }
+    css
+}
+
+/// Bounding box coordinates in PDF points (1/72 inch)
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq)]
+pub struct Bounds {
+    pub x: f32,
+    pub y: f32,
+    pub width: f32,
+    pub height: f32,
+}
+
+impl Bounds {
+    #[inline]
+    pub const fn new(x: f32, y: f32, width: f32, height: f32) -> Self {
+        Self { x, y, width, height }
+    }
+
+    /// Clamp bounds to ensure positive dimensions
+    #[inline]
+    pub fn clamp_positive(&self) -> Self {
+        Self {
+            x: self.x,
+            y: self.y,
+            width: if self.width < 1.0 { 1.0 } else { self.width },
+            height: if self.height < 1.0 { 1.0 } else { self.height },
+        }
+    }
+}
+
+/// 2D Transformation matrix [a, b, c, d, e, f]
+/// Represents: | a  b  0 |
+///             | c  d  0 |
+///             | e  f  1 |
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq)]
+pub struct TransformMatrix {
+    pub a: f32,
+    pub b: f32,
+    pub c: f32,
+    pub d: f32,
+    pub e: f32,
+    pub f: f32,
+}
+
+impl TransformMatrix {
+    #[inline]
+    pub const fn identity() -> Self {
+        Self { a: 1.0, b: 0.0, c: 0.0, d: 1.0, e: 0.0, f: 0.0 }
+    }
+
+    #[inline]
+    pub const fn translate(tx: f32, ty: f32) -> Self {
+        Self { a: 1.0, b: 0.0, c: 0.0, d: 1.0, e: tx, f: ty }
+    }
+
+    #[inline]
+    pub const fn scale(sx: f32, sy: f32) -> Self {
+        Self { a: sx, b: 0.0, c: 0.0, d: sy, e: 0.0, f: 0.0 }
+    }
+
+    #[inline]
+    pub const fn multiply(&self, other: &Self) -> Self {
+        Self {
+            a: self.a * other.a + self.b * other.c,
+            b: self.a * other.b + self.b * other.d,
+            c: self.c * other.a + self.d * other.c,
+            d: self.c * other.b + self.d * other.d,
+            e: self.e * other.a + self.f * other.c + other.e,
+            f: self.e * other.b + self.f * other.d + other.f,
+        }
+    }
+
+    #[inline]
+    pub const fn transform_point(&self, x: f32, y: f32) -> (f32, f32) {
+        (
+            self.a * x + self.c * y + self.e,
+            self.b * x + self.d * y + self.f,
+        )
+    }
+
+    /// Get horizontal scale factor from matrix
+    /// For matrix [a b; c d], scale_x = sqrt(a² + c²)
+    #[inline]
+    pub fn scale_x(&self) -> f32 {
+        (self.a * self.a + self.c * self.c).sqrt()
+    }
+
+    /// Get vertical scale factor from matrix
+    /// For matrix [a b; c d], scale_y = sqrt(b² + d²)
+    #[inline]
+    pub fn scale_y(&self) -> f32 {
+        (self.b * self.b + self.d * self.d).sqrt()
+    }
+}
+
+impl Default for TransformMatrix {
+    #[inline]
+    fn default() -> Self {
+        Self::identity()
+    }
+}
+
+/// Path command for vector graphics
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq)]
+#[serde(tag = "type", rename_all = "camelCase")]
+pub enum PathCommand {
+    MoveTo { x: f32, y: f32 },
+    LineTo { x: f32, y: f32 },
+    CurveTo { x1: f32, y1: f32, x2: f32, y2: f32, x: f32, y: f32 },
+    ClosePath,
+}
+
+/// Fill rule for paths
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+#[repr(u8)]
+pub enum FillRule {
+    NonZero = 0,
+    EvenOdd = 1,
+}
+
+/// Path data for vector layers
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct PathData {
+    pub commands: Vec<PathCommand>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub fill_rule: Option<FillRule>,
+}
+
+/// Baseline path for text set along a curve
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct TextPath {
+    /// In page coordinates, like `path_data`
+    pub path: PathData,
+    /// Distance along the path before the first glyph, in points
+    #[serde(default)]
+    pub start_offset: f32,
+}
+
+/// Stroke drawn around text glyphs
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct TextOutline {
+    pub color: String,
+    /// Stroke width in points
+    pub width: f32,
+}
+
+/// Offset copy of the text drawn beneath it
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct TextShadow {
+    pub color: String,
+    /// Offset in points; positive moves right and down
+    pub offset_x: f32,
+    pub offset_y: f32,
+    /// Blur radius in points; vector exports draw the shadow sharp
+    #[serde(default)]
+    pub blur: f32,
+}
+
+/// Large initial letter set into the first lines of a text layer
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct DropCap {
+    /// Lines the cap drops through; its baseline sits on the last of them
+    #[serde(default = "default_drop_cap_lines")]
+    pub lines: u32,
+    /// Letters set large, e.g. 2 for an opening "Th"
+    #[serde(default = "default_drop_cap_letters")]
+    pub letters: u32,
+    /// The layer's font when absent
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub font_family: Option<String>,
+    /// Space between the cap and the text beside it, in points
+    #[serde(default)]
+    pub gap: f32,
+}
+
+fn default_drop_cap_lines() -> u32 {
+    3
+}
+
+fn default_drop_cap_letters() -> u32 {
+    1
+}
+
+impl Default for DropCap {
+    fn default() -> Self {
+        Self { lines: default_drop_cap_lines(), letters: default_drop_cap_letters(), font_family: None, gap: 0.0 }
+    }
+}
+
+/// Paragraph controls `reflow` honours when text breaks across pages
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub struct Pagination {
+    /// Never split the paragraph between pages
+    #[serde(default)]
+    pub keep_together: bool,
+    /// Keep the paragraph on the page the next one starts on, as for headings
+    #[serde(default)]
+    pub keep_with_next: bool,
+    /// Fewest lines carried over to the top of the next page
+    #[serde(default = "default_min_lines")]
+    pub widows: u32,
+    /// Fewest lines left at the foot of a page
+    #[serde(default = "default_min_lines")]
+    pub orphans: u32,
+}
+
+fn default_min_lines() -> u32 {
+    2
+}
+
+impl Default for Pagination {
+    fn default() -> Self {
+        Self { keep_together: false, keep_with_next: false, widows: default_min_lines(), orphans: default_min_lines() }
+    }
+}
+
+/// Where a note is set
+#[derive(Debug, Clone, Copy, Default, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum NoteKind {
+    /// At the foot of the page its anchor is on
+    #[default]
+    Footnote,
+    /// After the last page of the chapter its anchor is in
+    Endnote,
+}
+
+/// Footnote or endnote anchored in a text layer; its number is derived
+/// from reading order (see `notes`)
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct Note {
+    pub id: String,
+    /// Byte offset into the layer's content that the reference mark follows
+    pub anchor: usize,
+    #[serde(default)]
+    pub kind: NoteKind,
+    pub text: String,
+}
+
+/// Outline that text frames wrap around
+#[derive(Debug, Clone, Copy, Default, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "kebab-case")]
+pub enum WrapContour {
+    /// No wrap; text runs over the layer
+    #[default]
+    None,
+    /// The layer's bounds
+    BoundingBox,
+    /// Convex hull of the layer's path, or its bounds without one
+    ConvexHull,
+}
+
+/// Text wrap settings of a layer
+#[derive(Debug, Clone, Default, Serialize, Deserialize, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct TextWrap {
+    pub contour: WrapContour,
+    /// Gap kept between the contour and text, in points
+    #[serde(default)]
+    pub offset: f32,
+}
+
+/// Image metadata
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub struct ImageMetadata {
+    pub width: u32,
+    pub height: u32,
+    pub color_space: String,
+    pub dpi: u32,
+    /// Embedded ICC profile the pixel values are in; a color profile store id
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub icc_profile: Option<String>,
+}
+
+impl ImageMetadata {
+    /// Resolution the pixels print at when placed in `bounds`, the lower of
+    /// the two axes; `None` for an empty image or placement
+    pub fn effective_dpi(&self, bounds: &Bounds) -> Option<f32> {
+        if bounds.width <= 0.0 || bounds.height <= 0.0 || self.width == 0 || self.height == 0 {
+            return None;
+        }
+        let dpi_x = self.width as f32 / (bounds.width / POINTS_PER_INCH);
+        let dpi_y = self.height as f32 / (bounds.height / POINTS_PER_INCH);
+        Some(dpi_x.min(dpi_y))
+    }
+}
+
+/// A discrete visual element on a page
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct LayerObject {
+    pub id: String,
+    #[serde(rename = "type")]
+    pub layer_type: LayerType,
+    pub bounds: Bounds,
+    pub visible: bool,
+    pub locked: bool,
+    #[serde(rename = "zIndex")]
+    pub z_index: i32,
+    pub opacity: f32,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    #[serde(rename = "blendMode")]
+    pub blend_mode: Option<BlendMode>,
+
+    // Text-specific fields
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub content: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    #[serde(rename = "fontFamily")]
+    pub font_family: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    #[serde(rename = "fontSize")]
+    pub font_size: Option<f32>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    #[serde(rename = "fontWeight")]
+    pub font_weight: Option<u16>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    #[serde(rename = "fontStyle")]
+    pub font_style: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub color: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    #[serde(rename = "textAlign")]
+    pub text_align: Option<TextAlign>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    #[serde(rename = "textDecoration")]
+    pub text_decoration: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    #[serde(rename = "textTransform")]
+    pub text_transform: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    #[serde(rename = "lineHeight")]
+    pub line_height: Option<f32>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    #[serde(rename = "letterSpacing")]
+    pub letter_spacing: Option<f32>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    #[serde(rename = "backgroundColor")]
+    pub background_color: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    #[serde(rename = "textPath")]
+    pub text_path: Option<TextPath>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    #[serde(rename = "textOutline")]
+    pub text_outline: Option<TextOutline>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    #[serde(rename = "textShadow")]
+    pub text_shadow: Option<TextShadow>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    #[serde(rename = "dropCap")]
+    pub drop_cap: Option<DropCap>,
+    /// Footnotes and endnotes anchored in the text
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub notes: Vec<Note>,
+    /// Keep and widow/orphan controls; the defaults when absent
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub pagination: Option<Pagination>,
+
+    // Image-specific fields
+    #[serde(skip_serializing_if = "Option::is_none")]
+    #[serde(rename = "imageUrl")]
+    pub image_url: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    #[serde(rename = "imagePath")]
+    pub image_path: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    #[serde(rename = "imageData")]
+    pub image_data: Option<ImageMetadata>,
+    /// External file the image is linked to instead of embedded
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    #[serde(rename = "imageLink")]
+    pub image_link: Option<ImageLink>,
+
+    // Shape-specific fields
+    #[serde(skip_serializing_if = "Option::is_none")]
+    #[serde(rename = "shapeType")]
+    pub shape_type: Option<ShapeType>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    #[serde(rename = "strokeColor")]
+    pub stroke_color: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    #[serde(rename = "strokeWidth")]
+    pub stroke_width: Option<f32>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    #[serde(rename = "fillColor")]
+    pub fill_color: Option<String>,
+    /// Region paragraph text in frames flows around (image/shape/vector)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    #[serde(rename = "textWrap")]
+    pub text_wrap: Option<TextWrap>,
+
+    // Vector path data
+    #[serde(skip_serializing_if = "Option::is_none")]
+    #[serde(rename = "pathData")]
+    pub path_data: Option<PathData>,
+
+    // Transform matrix for exact positioning
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub transform: Option<TransformMatrix>,
+
+    // Metadata
+    #[serde(rename = "sourceType")]
+    pub source_type: SourceType,
+    pub role: LayerRole,
+    /// Recognition confidence and review state of OCR'd text
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub ocr: Option<OcrInfo>,
+    /// Free-form categories such as `print-only` or `notes`, for export filters
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub tags: Vec<String>,
+}
+
+/// Linked image file as last loaded, to tell when it has changed
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub struct ImageLink {
+    pub path: String,
+    /// Modification time, seconds since the Unix epoch
+    pub modified: u64,
+    /// SHA-256 of the file contents, hex
+    pub hash: String,
+}
+
+/// How a saved project stores linked images
+#[derive(Debug, Clone, Copy, Default, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum LinkedImages {
+    /// Keep the links; the files are read again when the project opens
+    #[default]
+    Keep,
+    /// Store the image bytes in the project and drop the links
+    Embed,
+}
+
+/// Proofreading state of an OCR text layer
+#[derive(Debug, Clone, Copy, Default, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub enum OcrReviewStatus {
+    #[default]
+    Pending,
+    Accepted,
+    Corrected,
+}
+
+/// A word as recognized by OCR; bounds in page points
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct OcrWordConfidence {
+    pub text: String,
+    pub confidence: f32,
+    pub bounds: Bounds,
+}
+
+/// OCR provenance of a text layer
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct OcrInfo {
+    /// Mean word confidence, 0-1
+    pub confidence: f32,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub words: Vec<OcrWordConfidence>,
+    #[serde(default)]
+    pub review: OcrReviewStatus,
+}
+
+/// Page metadata
+#[derive(Debug, Clone, Default, Serialize, Deserialize, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct PageMetadata {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub original_page_index: Option<usize>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub rotation: Option<u16>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub media_box: Option<[f32; 4]>,
+    /// Source PDF boxes as `[x0, y0, x1, y1]` in PDF user space (y up).
+    /// Layers are positioned relative to the crop box, or the media box
+    /// when there is none.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub crop_box: Option<[f32; 4]>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub trim_box: Option<[f32; 4]>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub bleed_box: Option<[f32; 4]>,
+    /// Printed page number, e.g. "iv" in the front matter
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub page_label: Option<PageLabel>,
+    /// BCP 47 language tag when the page differs from the document
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub language: Option<String>,
+    /// Layout guides inferred from the page design, for snapping
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub guides: Option<PageGuides>,
+    /// Free-form key/value properties
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub custom: BTreeMap<String, String>,
+}
+
+/// Guide lines of a page, in points from its top-left corner
+#[derive(Debug, Clone, Default, Serialize, Deserialize, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct PageGuides {
+    /// x positions of margin and column edges
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub vertical: Vec<f32>,
+    /// y positions of the top and bottom margins
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub horizontal: Vec<f32>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub baseline_grid: Option<BaselineGrid>,
+}
+
+/// Evenly spaced baselines: `offset`, `offset + spacing`, …
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct BaselineGrid {
+    pub spacing: f32,
+    /// First baseline from the page top, below `spacing`
+    pub offset: f32,
+}
+
+/// Numbering style of a page label
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "kebab-case")]
+pub enum PageLabelStyle {
+    /// 1, 2, 3
+    Decimal,
+    /// I, II, III
+    UpperRoman,
+    /// i, ii, iii
+    LowerRoman,
+    /// A … Z, AA … ZZ
+    UpperAlpha,
+    /// a … z, aa … zz
+    LowerAlpha,
+}
+
+/// Page label: optional prefix followed by the page's number in its style
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PageLabel {
+    /// Prefix only when absent
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub style: Option<PageLabelStyle>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub prefix: Option<String>,
+    /// This page's number in the style, from 1
+    pub number: u32,
+}
+
+/// Page background painted behind every layer, out to the bleed edge
+#[derive(Debug, Clone, Default, Serialize, Deserialize, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct PageBackground {
+    /// Fill color, e.g. "#F5F0E6"; pages are white without one
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub color: Option<String>,
+    /// Full-bleed image ("image://<id>" or a file path), stretched to the page
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub image: Option<String>,
+}
+
+/// A single page containing multiple layers
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct PageData {
+    pub page_index: usize,
+    pub width: f32,
+    pub height: f32,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub dpi: Option<u32>,
+    pub layers: Vec<LayerObject>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub metadata: Option<PageMetadata>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub background: Option<PageBackground>,
+}
+
+/// Document metadata
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct DocumentMetadata {
+    pub title: String,
+    pub author: String,
+    pub created: String,
+    pub modified: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub description: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub subject: Option<String>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub keywords: Vec<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub isbn: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub publisher: Option<String>,
+    /// BCP 47 language tag, e.g. "en-US"
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub language: Option<String>,
+    /// Free-form key/value properties, exported as PDF Info entries and DOCX
+    /// custom properties
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub custom: BTreeMap<String, String>,
+}
+
+impl Default for DocumentMetadata {
+    fn default() -> Self {
+        let now = iso8601_now();
+        Self {
+            title: String::new(),
+            author: String::new(),
+            created: now.clone(),
+            modified: now,
+            description: None,
+            subject: None,
+            keywords: Vec::new(),
+            isbn: None,
+            publisher: None,
+            language: None,
+            custom: BTreeMap::new(),
+        }
+    }
+}
+
+/// Generate proper ISO8601 timestamp
+pub fn iso8601_now() -> String {
+    iso8601_from_system_time(std::time::SystemTime::now())
+}
+
+/// ISO8601 timestamp (UTC, whole seconds) of a point in time
+pub fn iso8601_from_system_time(time: std::time::SystemTime) -> String {
+    let duration = time.duration_since(std::time::UNIX_EPOCH).unwrap_or_default();
+
+    let total_secs = duration.as_secs();
+
+    // Calculate date components
+    let days_since_epoch = total_secs / 86400;
+    let time_of_day = total_secs % 86400;
+
+    let hours = time_of_day / 3600;
+    let minutes = (time_of_day % 3600) / 60;
+    let seconds = time_of_day % 60;
+
+    // Calculate year, month, day from days since epoch (1970-01-01)
+    let (year, month, day) = days_to_ymd(days_since_epoch as i64);
+
+    format!(
+        "{:04}-{:02}-{:02}T{:02}:{:02}:{:02}Z",
+        year, month, day, hours, minutes, seconds
+    )
+}
+
+/// Convert days since Unix epoch to year, month, day
+fn days_to_ymd(days: i64) -> (i32, u32, u32) {
+    // Algorithm from Howard Hinnant's date algorithms
+    let z = days + 719468;
+    let era = if z >= 0 { z } else { z - 146096 } / 146097;
+    let doe = (z - era * 146097) as u32;
+    let yoe = (doe - doe / 1460 + doe / 36524 - doe / 146096) / 365;
+    let y = yoe as i64 + era * 400;
+    let doy = doe - (365 * yoe + yoe / 4 - yoe / 100);
+    let mp = (5 * doy + 2) / 153;
+    let d = doy - (153 * mp + 2) / 5 + 1;
+    let m = if mp < 10 { mp + 3 } else { mp - 9 };
+    let year = if m <= 2 { y + 1 } else { y };
+
+    (year as i32, m, d)
+}
+
+/// Kind of tracked layer change
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+#[repr(u8)]
+pub enum ChangeKind {
+    Insert = 0,
+    Update = 1,
+    Delete = 2,
+}
+
+/// A pending layer change recorded while track-changes mode is on
+///
+/// `before` holds the layer as it was prior to the change (absent for
+/// insertions) and `after` the layer as it is now (absent for deletions).
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct TrackedChange {
+    pub id: String,
+    pub kind: ChangeKind,
+    pub page_index: usize,
+    pub layer_id: String,
+    pub author: String,
+    pub timestamp: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub before: Option<LayerObject>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub after: Option<LayerObject>,
+}
+
+/// Document data containing all pages
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct DocumentData {
+    pub page_width: f32,
+    pub page_height: f32,
+    pub pages: Vec<PageData>,
+    /// Unit of every size and bound in this document; points when absent
+    #[serde(default)]
+    pub units: crate::units::DocumentUnits,
+}
+
+impl DocumentData {
+    /// Document with sizes in points
+    #[inline]
+    pub fn new(page_width: f32, page_height: f32, pages: Vec<PageData>) -> Self {
+        Self { page_width, page_height, pages, units: crate::units::DocumentUnits::POINTS }
+    }
+}
+
+/// Project settings
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct ProjectSettings {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub default_font: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub default_font_size: Option<f32>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub export_quality: Option<String>,
+    /// Record layer edits as pending changes for review
+    #[serde(default)]
+    pub track_changes: bool,
+    /// Export presets saved with this project
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub export_presets: Vec<crate::export::ExportPreset>,
+    /// Split exports saved with this project, e.g. interior and cover
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub export_pipelines: Vec<crate::export::ExportPipeline>,
+    /// Encoding used when the project is saved
+    #[serde(default)]
+    pub encoding: ProjectEncoding,
+    /// Page margins; the trim size is `DocumentData::page_width`/`page_height`
+    #[serde(default)]
+    pub margins: crate::page_setup::Margins,
+    /// Print bleed past the trim edge, in points
+    #[serde(default)]
+    pub bleed: f32,
+    /// Unit lengths are shown and entered in; storage is always points
+    #[serde(default)]
+    pub display_units: crate::units::DocumentUnits,
+    /// Corrections for OCR output in this project
+    #[serde(default, skip_serializing_if = "crate::ocr_correction::OcrDictionary::is_empty")]
+    pub ocr_dictionary: crate::ocr_correction::OcrDictionary,
+    /// File the project was imported from, for re-importing pages
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub source: Option<SourceDocument>,
+}
+
+/// Source file of an imported project
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub struct SourceDocument {
+    pub path: String,
+    /// Import type: "pdf", "docx", "png", …
+    pub file_type: String,
+}
+
+/// On-disk encoding of project and page data
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq, Default)]
+#[serde(rename_all = "lowercase")]
+#[repr(u8)]
+pub enum ProjectEncoding {
+    #[default]
+    Json = 0,
+    /// Compact binary, faster to parse for large projects
+    MessagePack = 1,
+}
+
+impl Default for ProjectSettings {
+    fn default() -> Self {
+        Self {
+            default_font: Some("Arial".to_string()),
+            default_font_size: Some(12.0),
+            export_quality: Some("standard".to_string()),
+            track_changes: false,
+            export_presets: Vec::new(),
+            export_pipelines: Vec::new(),
+            encoding: ProjectEncoding::Json,
+            margins: crate::page_setup::Margins::default(),
+            bleed: 0.0,
+            display_units: crate::units::DocumentUnits::default(),
+            ocr_dictionary: crate::ocr_correction::OcrDictionary::default(),
+            source: None,
+        }
+    }
+}
+
+/// Complete book project data
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct BookProjectData {
+    pub format: String,
+    pub version: String,
+    pub metadata: DocumentMetadata,
+    pub document: DocumentData,
+    pub settings: ProjectSettings,
+    /// Pending tracked changes awaiting accept/reject
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub changes: Vec<TrackedChange>,
+    /// Chapters and front and back matter, in page order (see `sections`)
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub sections: Vec<Section>,
+}
+
+/// What a section of the book holds
+#[derive(Debug, Clone, Copy, Default, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub enum SectionKind {
+    /// Title pages, preface and contents, numbered in lower roman by default
+    FrontMatter,
+    /// Counted in the chapter numbers
+    #[default]
+    Chapter,
+    BackMatter,
+}
+
+/// Run of pages from `start_page` up to where the next section starts
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct Section {
+    pub id: String,
+    /// First page, 0-based
+    pub start_page: usize,
+    #[serde(default)]
+    pub kind: SectionKind,
+    /// Contents and running head title; the section's first heading when absent
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub title: Option<String>,
+    /// Page number style; lower roman for front matter, else decimal, when absent
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub numbering: Option<PageLabelStyle>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub prefix: Option<String>,
+    /// Number of the first page; numbering carries on from the previous
+    /// section when absent
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub restart_at: Option<u32>,
+    /// Page whose background, header and footer layers the section's pages take
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub master_page: Option<usize>,
+}
+
+impl BookProjectData {
+    /// Trim size, margins and bleed of this project
+    pub fn page_setup(&self) -> crate::page_setup::PageSetup {
+        crate::page_setup::PageSetup {
+            width: self.document.page_width,
+            height: self.document.page_height,
+            margins: self.settings.margins,
+            bleed: self.settings.bleed,
+        }
+    }
+}
+
+impl Default for BookProjectData {
+    fn default() -> Self {
+        Self {
+            format: "bookproj".to_string(),
+            version: "1.0.0".to_string(),
+            metadata: DocumentMetadata::default(),
+            // US Letter in points
+            document: DocumentData::new(612.0, 792.0, Vec::new()),
+            settings: ProjectSettings::default(),
+            changes: Vec::new(),
+            sections: Vec::new(),
+        }
+    }
+}
+
+/// Response from document import operations
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DocumentResponse {
+    pub success: bool,
+    pub message: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub data: Option<DocumentData>,
+    /// Damage found in the source file and how it was handled
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub repair: Option<RepairReport>,
+    /// Title, author, keywords and other properties read from the source file
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub metadata: Option<DocumentMetadata>,
+}
+
+/// Outcome for one page of a repaired PDF
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum PageRepairStatus {
+    /// Readable once the file was repaired
+    Recovered,
+    /// Could not be read and was skipped
+    Failed,
+}
+
+/// A page of a damaged PDF that was recovered or skipped
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct PageRepair {
+    /// Zero-based index in the source file
+    pub page_index: usize,
+    pub status: PageRepairStatus,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub error: Option<String>,
+}
+
+/// Damage found in a PDF and what the repair pass did about it
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct RepairReport {
+    /// Why the file could not be opened as-is
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub load_error: Option<String>,
+    /// The cross-reference table was rebuilt by scanning for objects
+    pub xref_rebuilt: bool,
+    /// References to missing objects that were removed
+    pub dangling_references: usize,
+    pub pages: Vec<PageRepair>,
+}
+
+impl RepairReport {
+    /// True when nothing needed repairing
+    pub fn is_clean(&self) -> bool {
+        !self.xref_rebuilt && self.dangling_references == 0 && self.pages.is_empty()
+    }
+
+    /// Source indices of the pages that could not be read
+    pub fn failed_pages(&self) -> Vec<usize> {
+        self.pages
+            .iter()
+            .filter(|p| p.status == PageRepairStatus::Failed)
+            .map(|p| p.page_index)
+            .collect()
+    }
+}
+
+/// Result from export operations
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct ExportResult {
+    pub success: bool,
+    pub message: String,
+    /// Written file (desktop exports)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub output_path: Option<String>,
+    /// Exported bytes (in-memory exports, e.g. the wasm build)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub data: Option<Vec<u8>>,
+}
+
+/// Layer update request
+#[derive(Debug, Clone, Default, Serialize, Deserialize, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct LayerUpdates {
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub bounds: Option<Bounds>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub visible: Option<bool>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub locked: Option<bool>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub z_index: Option<i32>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub opacity: Option<f32>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub blend_mode: Option<BlendMode>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub content: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub font_family: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub font_size: Option<f32>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub font_weight: Option<u16>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub font_style: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub color: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub text_align: Option<TextAlign>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub text_decoration: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub text_transform: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub line_height: Option<f32>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub letter_spacing: Option<f32>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub background_color: Option<String>,
+    /// A `none` contour clears the wrap
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub text_wrap: Option<TextWrap>,
+    /// Fewer than 2 lines clears the drop cap
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub drop_cap: Option<DropCap>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub pagination: Option<Pagination>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub role: Option<LayerRole>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub tags: Option<Vec<String>>,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_layer_serialization_roundtrip() {
+        let layer = LayerObject {
+            id: "text-0-1".to_string(),
+            layer_type: LayerType::Text,
+            bounds: Bounds::new(10.0, 20.0, 100.0, 50.0),
+            visible: true,
+            locked: false,
+            z_index: 1,
+            opacity: 1.0,
+            blend_mode: None,
+            content: Some("Hello World".to_string()),
+            font_family: Some("Arial".to_string()),
+            font_size: Some(12.0),
+            font_weight: Some(400),
+            font_style: None,
+            color: Some("#000000".to_string()),
+            text_align: Some(TextAlign::Left),
+            text_decoration: None,
+            text_transform: None,
+            line_height: None,
+            letter_spacing: None,
+            background_color: None,
+            text_path: None,
+            text_outline: None,
+            text_shadow: None,
+            drop_cap: None,
+            notes: Vec::new(),
+            pagination: None,
+            image_url: None,
+            image_path: None,
+            image_data: None,
+            image_link: None,
+            shape_type: None,
+            stroke_color: None,
+            stroke_width: None,
+            fill_color: None,
+            text_wrap: None,
+            path_data: None,
+            transform: None,
+            source_type: SourceType::Extracted,
+            role: LayerRole::Content,
+            ocr: None,
+            tags: Vec::new(),
+        };
+
+        let json = serde_json::to_string(&layer).unwrap();
+        let deserialized: LayerObject = serde_json::from_str(&json).unwrap();
+        assert_eq!(layer, deserialized);
+    }
+
+    #[test]
+    fn test_transform_matrix_identity() {
+        let m = TransformMatrix::identity();
+        let (x, y) = m.transform_point(10.0, 20.0);
+        assert!((x - 10.0).abs() < 0.001);
+        assert!((y - 20.0).abs() < 0.001);
+    }
+
+    #[test]
+    fn test_transform_matrix_translate() {
+        let m = TransformMatrix::translate(5.0, 10.0);
+        let (x, y) = m.transform_point(10.0, 20.0);
+        assert!((x - 15.0).abs() < 0.001);
+        assert!((y - 30.0).abs() < 0.001);
+    }
+
+    #[test]
+    fn test_transform_matrix_scale() {
+        let m = TransformMatrix::scale(2.0, 3.0);
+        let (x, y) = m.transform_point(10.0, 20.0);
+        assert!((x - 20.0).abs() < 0.001);
+        assert!((y - 60.0).abs() < 0.001);
+    }
+
+    #[test]
+    fn test_transform_matrix_multiply() {
+        let t = TransformMatrix::translate(10.0, 20.0);
+        let s = TransformMatrix::scale(2.0, 2.0);
+        let combined = t.multiply(&s);
+        let (x, y) = combined.transform_point(5.0, 5.0);
+        // First translate: (5+10, 5+20) = (15, 25)
+        // Then scale: (15*2, 25*2) = (30, 50)
+        assert!((x - 30.0).abs() < 0.001);
+        assert!((y - 50.0).abs() < 0.001);
+    }
+
+    #[test]
+    fn test_bounds_creation() {
+        let bounds = Bounds::new(10.0, 20.0, 100.0, 50.0);
+        assert_eq!(bounds.x, 10.0);
+        assert_eq!(bounds.y, 20.0);
+        assert_eq!(bounds.width, 100.0);
+        assert_eq!(bounds.height, 50.0);
+    }
+
+    #[test]
+    fn test_bounds_clamp_positive() {
+        let bounds = Bounds::new(10.0, 20.0, -5.0, 0.0);
+        let clamped = bounds.clamp_positive();
+        assert_eq!(clamped.width, 1.0);
+        assert_eq!(clamped.height, 1.0);
+    }
+
+    #[test]
+    fn test_iso8601_now_format() {
+        let timestamp = iso8601_now();
+        // Should match format: YYYY-MM-DDTHH:MM:SSZ
+        assert!(timestamp.len() == 20);
+        assert!(timestamp.ends_with('Z'));
+        assert!(timestamp.contains('T'));
+        // Year should be >= 2024
+        let year: i32 = timestamp[0..4].parse().unwrap();
+        assert!(year >= 2024);
+
+        let time = std::time::UNIX_EPOCH + std::time::Duration::from_secs(951_782_400);
+        assert_eq!(iso8601_from_system_time(time), "2000-02-29T00:00:00Z");
+    }
+
+    #[test]
+    fn test_layer_type_display() {
+        assert_eq!(LayerType::Text.to_string(), "text");
+        assert_eq!(LayerType::Image.to_string(), "image");
+        assert_eq!(LayerType::Vector.to_string(), "vector");
+        assert_eq!(LayerType::Shape.to_string(), "shape");
+    }
+
+    #[test]
+    fn test_source_type_display() {
+        assert_eq!(SourceType::Extracted.to_string(), "extracted");
+        assert_eq!(SourceType::Manual.to_string(), "manual");
+        assert_eq!(SourceType::Imported.to_string(), "imported");
+    }
+
+    #[test]
+    fn test_layer_role_display() {
+        assert_eq!(LayerRole::Background.to_string(), "background");
+        assert_eq!(LayerRole::Content.to_string(), "content");
+        assert_eq!(LayerRole::Header.to_string(), "header");
+        assert_eq!(LayerRole::Footer.to_string(), "footer");
+        assert_eq!(LayerRole::Annotation.to_string(), "annotation");
+    }
+
+    #[test]
+    fn test_layer_wire_format() {
+        // Both front ends exchange this JSON; key names and enum spellings are fixed
+        let json = r#"{
+            "id": "shape-0-3",
+            "type": "shape",
+            "bounds": {"x": 1.0, "y": 2.0, "width": 30.0, "height": 40.0},
+            "visible": true,
+            "locked": false,
+            "zIndex": 3,
+            "opacity": 0.5,
+            "textAlign": "center",
+            "shapeType": "rectangle",
+            "strokeWidth": 1.0,
+            "sourceType": "manual",
+            "role": "background"
+        }"#;
+        let layer: LayerObject = serde_json::from_str(json).unwrap();
+        assert_eq!(layer.layer_type, LayerType::Shape);
+        assert_eq!(layer.z_index, 3);
+        assert_eq!(layer.text_align, Some(TextAlign::Center));
+        assert_eq!(layer.shape_type, Some(ShapeType::Rectangle));
+        assert_eq!(layer.source_type, SourceType::Manual);
+        assert_eq!(layer.role, LayerRole::Background);
+
+        let value = serde_json::to_value(&layer).unwrap();
+        let object = value.as_object().unwrap();
+        for key in ["type", "zIndex", "textAlign", "shapeType", "strokeWidth", "sourceType"] {
+            assert!(object.contains_key(key), "missing {}", key);
+        }
+        assert!(!object.contains_key("content"));
+        assert!(!object.contains_key("fontFamily"));
+    }
+
+    #[test]
+    fn test_export_result_skips_empty_fields() {
+        let result = ExportResult {
+            success: true,
+            message: "ok".to_string(),
+            output_path: None,
+            data: None,
+        };
+        let json = serde_json::to_string(&result).unwrap();
+        assert_eq!(json, r#"{"success":true,"message":"ok"}"#);
+
+        let result = ExportResult {
+            output_path: Some("/tmp/out.pdf".to_string()),
+            ..result
+        };
+        assert!(serde_json::to_string(&result).unwrap().contains(r#""outputPath":"/tmp/out.pdf""#));
+    }
+
+    #[test]
+    fn test_blend_mode_names() {
+        let mode: BlendMode = serde_json::from_str(r#""color-dodge""#).unwrap();
+        assert_eq!(mode, BlendMode::ColorDodge);
+        assert_eq!(mode.pdf_name(), "ColorDodge");
+        assert_eq!(mode.css_name(), "color-dodge");
+        for mode in BlendMode::ALL {
+            assert_eq!(BlendMode::from_pdf_name(mode.pdf_name().as_bytes()), Some(mode));
+        }
+        assert_eq!(BlendMode::from_pdf_name(b"Compatible"), Some(BlendMode::Normal));
+        assert_eq!(BlendMode::from_pdf_name(b"Plus"), None);
+
+        let mut layer: LayerObject = serde_json::from_str(
+            r#"{"id":"a","type":"shape","bounds":{"x":0,"y":0,"width":1,"height":1},"visible":true,
+                "locked":false,"zIndex":0,"opacity":0.25,"blendMode":"soft-light","sourceType":"manual","role":"content"}"#,
+        )
+        .unwrap();
+        assert_eq!(transparency_css(&layer), "opacity:0.25;mix-blend-mode:soft-light;");
+        layer.opacity = 1.0;
+        layer.blend_mode = Some(BlendMode::Normal);
+        assert!(transparency_css(&layer).is_empty());
+    }
+}
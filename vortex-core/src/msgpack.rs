@@ -0,0 +1,269 @@
+//! MessagePack encoding for project data
+//!
+//! A small codec over `serde_json::Value`: values are converted to and from
+//! the JSON data model, so anything that round-trips through JSON (every
+//! project type) round-trips here and the output is plain MessagePack that
+//! any msgpack library can read. Maps keep the serde field names.
+
+use serde::de::DeserializeOwned;
+use serde::Serialize;
+use serde_json::{Map, Number, Value};
+
+/// Encode a value as MessagePack
+pub fn to_vec<T: Serialize + ?Sized>(value: &T) -> Result<Vec<u8>, String> {
+    let value = serde_json::to_value(value).map_err(|e| e.to_string())?;
+    let mut out = Vec::with_capacity(1024);
+    write_value(&mut out, &value);
+    Ok(out)
+}
+
+/// Decode MessagePack bytes
+pub fn from_slice<T: DeserializeOwned>(data: &[u8]) -> Result<T, String> {
+    let mut reader = Reader { data, pos: 0 };
+    let value = reader.read_value(0)?;
+    if reader.pos != data.len() {
+        return Err(format!("Trailing bytes after MessagePack value at offset {}", reader.pos));
+    }
+    serde_json::from_value(value).map_err(|e| e.to_string())
+}
+
+/// Whether the bytes start with a MessagePack map (how every project is encoded)
+#[inline]
+pub fn looks_like_map(data: &[u8]) -> bool {
+    matches!(data.first(), Some(0x80..=0x8f | 0xde | 0xdf))
+}
+
+fn write_len(out: &mut Vec<u8>, len: usize, fix: u8, fix_max: usize, m8: Option<u8>, m16: u8, m32: u8) {
+    if len <= fix_max {
+        out.push(fix | len as u8);
+    } else if let (Some(marker), true) = (m8, len <= u8::MAX as usize) {
+        out.extend_from_slice(&[marker, len as u8]);
+    } else if len <= u16::MAX as usize {
+        out.push(m16);
+        out.extend_from_slice(&(len as u16).to_be_bytes());
+    } else {
+        out.push(m32);
+        out.extend_from_slice(&(len as u32).to_be_bytes());
+    }
+}
+
+fn write_str(out: &mut Vec<u8>, s: &str) {
+    write_len(out, s.len(), 0xa0, 31, Some(0xd9), 0xda, 0xdb);
+    out.extend_from_slice(s.as_bytes());
+}
+
+fn write_number(out: &mut Vec<u8>, n: &Number) {
+    if let Some(u) = n.as_u64() {
+        if u <= 0x7f {
+            out.push(u as u8);
+        } else {
+            out.push(0xcf);
+            out.extend_from_slice(&u.to_be_bytes());
+        }
+    } else if let Some(i) = n.as_i64() {
+        if i >= -32 {
+            out.push(i as i8 as u8);
+        } else {
+            out.push(0xd3);
+            out.extend_from_slice(&i.to_be_bytes());
+        }
+    } else {
+        let f = n.as_f64().unwrap_or_default();
+        // f32 when lossless: most project numbers are f32 coordinates
+        if (f as f32) as f64 == f {
+            out.push(0xca);
+            out.extend_from_slice(&(f as f32).to_be_bytes());
+        } else {
+            out.push(0xcb);
+            out.extend_from_slice(&f.to_be_bytes());
+        }
+    }
+}
+
+fn write_value(out: &mut Vec<u8>, value: &Value) {
+    match value {
+        Value::Null => out.push(0xc0),
+        Value::Bool(b) => out.push(if *b { 0xc3 } else { 0xc2 }),
+        Value::Number(n) => write_number(out, n),
+        Value::String(s) => write_str(out, s),
+        Value::Array(items) => {
+            write_len(out, items.len(), 0x90, 15, None, 0xdc, 0xdd);
+            for item in items {
+                write_value(out, item);
+            }
+        }
+        Value::Object(map) => {
+            write_len(out, map.len(), 0x80, 15, None, 0xde, 0xdf);
+            for (key, item) in map {
+                write_str(out, key);
+                write_value(out, item);
+            }
+        }
+    }
+}
+
+/// Nesting limit, guards against stack exhaustion on hostile input
+const MAX_DEPTH: usize = 256;
+
+struct Reader<'a> {
+    data: &'a [u8],
+    pos: usize,
+}
+
+impl<'a> Reader<'a> {
+    fn take(&mut self, n: usize) -> Result<&'a [u8], String> {
+        let end = self.pos.checked_add(n).filter(|&end| end <= self.data.len());
+        let end = end.ok_or_else(|| "Unexpected end of MessagePack data".to_string())?;
+        let bytes = &self.data[self.pos..end];
+        self.pos = end;
+        Ok(bytes)
+    }
+
+    fn byte(&mut self) -> Result<u8, String> {
+        Ok(self.take(1)?[0])
+    }
+
+    fn be<const N: usize>(&mut self) -> Result<[u8; N], String> {
+        let mut buf = [0u8; N];
+        buf.copy_from_slice(self.take(N)?);
+        Ok(buf)
+    }
+
+    fn str(&mut self, len: usize) -> Result<Value, String> {
+        let bytes = self.take(len)?;
+        std::str::from_utf8(bytes)
+            .map(|s| Value::String(s.to_string()))
+            .map_err(|e| format!("Invalid UTF-8 in MessagePack string: {}", e))
+    }
+
+    fn array(&mut self, len: usize, depth: usize) -> Result<Value, String> {
+        // Each element takes at least one byte
+        let mut items = Vec::with_capacity(len.min(self.data.len() - self.pos));
+        for _ in 0..len {
+            items.push(self.read_value(depth + 1)?);
+        }
+        Ok(Value::Array(items))
+    }
+
+    fn map(&mut self, len: usize, depth: usize) -> Result<Value, String> {
+        let mut map = Map::new();
+        for _ in 0..len {
+            let key = match self.read_value(depth + 1)? {
+                Value::String(s) => s,
+                other => other.to_string(),
+            };
+            map.insert(key, self.read_value(depth + 1)?);
+        }
+        Ok(Value::Object(map))
+    }
+
+    fn float(f: f64) -> Result<Value, String> {
+        Number::from_f64(f)
+            .map(Value::Number)
+            .ok_or_else(|| "Non-finite float in MessagePack data".to_string())
+    }
+
+    fn read_value(&mut self, depth: usize) -> Result<Value, String> {
+        if depth > MAX_DEPTH {
+            return Err("MessagePack data nested too deeply".to_string());
+        }
+        let marker = self.byte()?;
+        match marker {
+            0x00..=0x7f => Ok(Value::from(marker)),
+            0x80..=0x8f => self.map((marker & 0x0f) as usize, depth),
+            0x90..=0x9f => self.array((marker & 0x0f) as usize, depth),
+            0xa0..=0xbf => self.str((marker & 0x1f) as usize),
+            0xc0 => Ok(Value::Null),
+            0xc2 => Ok(Value::Bool(false)),
+            0xc3 => Ok(Value::Bool(true)),
+            // bin 8/16/32: surfaced as byte arrays, as serde_json does for Vec<u8>
+            0xc4 => {
+                let len = self.byte()? as usize;
+                Ok(Value::from(self.take(len)?.to_vec()))
+            }
+            0xc5 => {
+                let len = u16::from_be_bytes(self.be()?) as usize;
+                Ok(Value::from(self.take(len)?.to_vec()))
+            }
+            0xc6 => {
+                let len = u32::from_be_bytes(self.be()?) as usize;
+                Ok(Value::from(self.take(len)?.to_vec()))
+            }
+            0xca => Self::float(f32::from_be_bytes(self.be()?) as f64),
+            0xcb => Self::float(f64::from_be_bytes(self.be()?)),
+            0xcc => Ok(Value::from(self.byte()?)),
+            0xcd => Ok(Value::from(u16::from_be_bytes(self.be()?))),
+            0xce => Ok(Value::from(u32::from_be_bytes(self.be()?))),
+            0xcf => Ok(Value::from(u64::from_be_bytes(self.be()?))),
+            0xd0 => Ok(Value::from(self.byte()? as i8)),
+            0xd1 => Ok(Value::from(i16::from_be_bytes(self.be()?))),
+            0xd2 => Ok(Value::from(i32::from_be_bytes(self.be()?))),
+            0xd3 => Ok(Value::from(i64::from_be_bytes(self.be()?))),
+            0xd9 => {
+                let len = self.byte()? as usize;
+                self.str(len)
+            }
+            0xda => {
+                let len = u16::from_be_bytes(self.be()?) as usize;
+                self.str(len)
+            }
+            0xdb => {
+                let len = u32::from_be_bytes(self.be()?) as usize;
+                self.str(len)
+            }
+            0xdc => {
+                let len = u16::from_be_bytes(self.be()?) as usize;
+                self.array(len, depth)
+            }
+            0xdd => {
+                let len = u32::from_be_bytes(self.be()?) as usize;
+                self.array(len, depth)
+            }
+            0xde => {
+                let len = u16::from_be_bytes(self.be()?) as usize;
+                self.map(len, depth)
+            }
+            0xdf => {
+                let len = u32::from_be_bytes(self.be()?) as usize;
+                self.map(len, depth)
+            }
+            0xe0..=0xff => Ok(Value::from(marker as i8)),
+            _ => Err(format!("Unsupported MessagePack marker 0x{:02x}", marker)),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::BookProjectData;
+
+    #[test]
+    fn test_scalars_use_compact_markers() {
+        assert_eq!(to_vec(&5u8).unwrap(), vec![0x05]);
+        assert_eq!(to_vec(&-3i32).unwrap(), vec![0xfd]);
+        assert_eq!(to_vec("ab").unwrap(), vec![0xa2, b'a', b'b']);
+        assert_eq!(to_vec(&1.5f32).unwrap(), vec![0xca, 0x3f, 0xc0, 0x00, 0x00]);
+        assert_eq!(to_vec(&Option::<u8>::None).unwrap(), vec![0xc0]);
+    }
+
+    #[test]
+    fn test_project_roundtrip() {
+        let mut project = BookProjectData::default();
+        project.metadata.title = "x".repeat(300);
+        project.document.page_width = 419.53;
+
+        let data = to_vec(&project).unwrap();
+        assert!(looks_like_map(&data));
+        assert!(data.len() < serde_json::to_vec_pretty(&project).unwrap().len());
+        let decoded: BookProjectData = from_slice(&data).unwrap();
+        assert_eq!(decoded, project);
+    }
+
+    #[test]
+    fn test_truncated_input_is_an_error() {
+        let data = to_vec(&BookProjectData::default()).unwrap();
+        assert!(from_slice::<BookProjectData>(&data[..data.len() - 1]).is_err());
+        assert!(from_slice::<BookProjectData>(&[0xdf, 0xff, 0xff, 0xff, 0xff]).is_err());
+    }
+}
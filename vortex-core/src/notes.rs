@@ -0,0 +1,639 @@
+//! Footnotes and endnotes
+//!
+//! Notes are stored on the text layer they are anchored in, each at a byte
+//! offset into its content, so they move, copy and undo with the layer and
+//! follow edits to its text. Numbers are never stored: footnotes count up in
+//! reading order through the document, or restart each chapter or page, and
+//! endnotes restart each chapter. A chapter starts at each top-level heading
+//! of the document outline.
+//!
+//! DOCX export writes notes natively. For the other formats `resolve_notes`
+//! sets them as plain layers first: a superscript mark after each anchor,
+//! each page's footnotes under a short rule at the foot of the text column
+//! (the frames above are shortened to clear them), and each chapter's
+//! endnotes on pages following its last page.
+
+use crate::clipboard::new_layer;
+use crate::doc_structure;
+use crate::models::{Bounds, LayerObject, LayerRole, LayerType, Note, NoteKind, PageData, ShapeType, TextAlign};
+use crate::text_wrap::{self, helvetica_width, LineBox};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+
+/// Footnote text size, relative to the text it is anchored in
+const NOTE_SIZE_RATIO: f32 = 0.8;
+/// Endnote text size when the options set none, in points
+const ENDNOTE_FONT_SIZE: f32 = 10.0;
+const LINE_HEIGHT: f32 = 1.2;
+/// Space either side of the footnote rule, in points
+const RULE_GAP: f32 = 6.0;
+/// Footnote rule length, as a fraction of the column width
+const RULE_LENGTH: f32 = 0.33;
+const SUPERSCRIPT_DIGITS: [char; 10] = ['⁰', '¹', '²', '³', '⁴', '⁵', '⁶', '⁷', '⁸', '⁹'];
+
+/// Where footnote numbers restart
+#[derive(Debug, Clone, Copy, Default, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum NoteNumbering {
+    /// 1, 2, 3 … through the whole document
+    #[default]
+    Document,
+    Chapter,
+    Page,
+}
+
+/// Numbering and layout of notes
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct NoteOptions {
+    /// Endnotes always restart each chapter
+    #[serde(default)]
+    pub footnote_numbering: NoteNumbering,
+    /// Note text size in points; footnotes are 0.8× the text they are
+    /// anchored in, and endnotes 10 pt, when absent
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub font_size: Option<f32>,
+    /// Heading over each chapter's endnotes
+    #[serde(default = "default_endnote_title")]
+    pub endnote_title: String,
+    /// Margin around endnote pages, in points
+    #[serde(default = "default_endnote_margin")]
+    pub endnote_margin: f32,
+}
+
+fn default_endnote_title() -> String {
+    "Notes".to_string()
+}
+
+fn default_endnote_margin() -> f32 {
+    72.0
+}
+
+impl Default for NoteOptions {
+    fn default() -> Self {
+        Self {
+            footnote_numbering: NoteNumbering::default(),
+            font_size: None,
+            endnote_title: default_endnote_title(),
+            endnote_margin: default_endnote_margin(),
+        }
+    }
+}
+
+/// A note with the number it is printed with
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct NumberedNote {
+    pub id: String,
+    pub page_index: usize,
+    pub layer_id: String,
+    pub kind: NoteKind,
+    pub number: u32,
+    /// Chapter the anchor is in, from 0
+    pub chapter: usize,
+    pub text: String,
+}
+
+/// Change to the notes of a document
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+#[serde(tag = "action", rename_all = "camelCase", rename_all_fields = "camelCase")]
+pub enum NoteEdit {
+    /// Anchor a new note at byte `anchor` of a text layer's content
+    Insert {
+        page_index: usize,
+        layer_id: String,
+        anchor: usize,
+        #[serde(default)]
+        kind: NoteKind,
+        text: String,
+    },
+    /// Change a note's text or kind
+    Update {
+        id: String,
+        #[serde(default)]
+        text: Option<String>,
+        #[serde(default)]
+        kind: Option<NoteKind>,
+    },
+    Remove { id: String },
+}
+
+/// Pages after a note edit, with the id of the note edited and the notes
+/// renumbered
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct NoteEditResult {
+    pub pages: Vec<PageData>,
+    pub note_id: String,
+    pub notes: Vec<NumberedNote>,
+}
+
+/// `n` in superscript digits, as a reference mark
+pub fn superscript(n: u32) -> String {
+    n.to_string().bytes().map(|d| SUPERSCRIPT_DIGITS[(d - b'0') as usize]).collect()
+}
+
+/// The plain digit a superscript digit stands for
+pub fn from_superscript(c: char) -> Option<char> {
+    SUPERSCRIPT_DIGITS.iter().position(|&d| d == c).map(|i| char::from(b'0' + i as u8))
+}
+
+/// Largest character boundary of `text` at or before `index`
+fn floor_boundary(text: &str, index: usize) -> usize {
+    let mut index = index.min(text.len());
+    while !text.is_char_boundary(index) {
+        index -= 1;
+    }
+    index
+}
+
+/// Move note anchors from `old` text to `new`, keeping each on the same
+/// text; a note inside replaced text moves to the end of the replacement
+pub fn remap_anchors(notes: &mut [Note], old: &str, new: &str) {
+    if notes.is_empty() || old == new {
+        return;
+    }
+    let prefix = old.bytes().zip(new.bytes()).take_while(|(a, b)| a == b).count();
+    let room = old.len().min(new.len()) - prefix;
+    let suffix = old.bytes().rev().zip(new.bytes().rev()).take(room).take_while(|(a, b)| a == b).count();
+    for note in notes {
+        let anchor = if note.anchor <= prefix {
+            note.anchor
+        } else if note.anchor >= old.len() - suffix {
+            note.anchor + new.len() - old.len()
+        } else {
+            new.len() - suffix
+        };
+        note.anchor = floor_boundary(new, anchor);
+    }
+}
+
+/// Positions in `pages` where chapters start: the first page and each page
+/// holding a top-level heading of the outline
+pub fn chapter_starts(pages: &[PageData]) -> Vec<usize> {
+    let outline = doc_structure::extract_structure(pages).outline;
+    let headings = outline.iter().filter_map(|node| pages.iter().position(|p| p.page_index == node.page_index));
+    let mut starts: Vec<usize> = std::iter::once(0).chain(headings).collect();
+    starts.sort_unstable();
+    starts.dedup();
+    starts
+}
+
+/// Chapter of the page at `position`, from the chapter starts
+fn chapter_of(starts: &[usize], position: usize) -> usize {
+    starts.partition_point(|&s| s <= position).saturating_sub(1)
+}
+
+/// Visible text layers of a page holding notes, in reading order
+fn noted_layers(page: &PageData) -> Vec<&LayerObject> {
+    let mut layers: Vec<&LayerObject> = page
+        .layers
+        .iter()
+        .filter(|l| l.visible && l.layer_type == LayerType::Text && !l.notes.is_empty())
+        .collect();
+    layers.sort_by(|a, b| a.bounds.y.total_cmp(&b.bounds.y).then(a.bounds.x.total_cmp(&b.bounds.x)));
+    layers
+}
+
+/// Every note of the visible text with its number, in reading order
+pub fn number_notes(pages: &[PageData], numbering: NoteNumbering) -> Vec<NumberedNote> {
+    if !pages.iter().any(|p| p.layers.iter().any(|l| !l.notes.is_empty())) {
+        return Vec::new();
+    }
+    let starts = chapter_starts(pages);
+    let mut numbered = Vec::new();
+    let (mut footnotes, mut endnotes, mut chapter) = (0, 0, 0);
+    for (position, page) in pages.iter().enumerate() {
+        let page_chapter = chapter_of(&starts, position);
+        if page_chapter != chapter {
+            chapter = page_chapter;
+            endnotes = 0;
+            if numbering == NoteNumbering::Chapter {
+                footnotes = 0;
+            }
+        }
+        if numbering == NoteNumbering::Page {
+            footnotes = 0;
+        }
+        for layer in noted_layers(page) {
+            let mut notes: Vec<&Note> = layer.notes.iter().collect();
+            notes.sort_by_key(|n| n.anchor);
+            for note in notes {
+                let counter = match note.kind {
+                    NoteKind::Footnote => &mut footnotes,
+                    NoteKind::Endnote => &mut endnotes,
+                };
+                *counter += 1;
+                numbered.push(NumberedNote {
+                    id: note.id.clone(),
+                    page_index: page.page_index,
+                    layer_id: layer.id.clone(),
+                    kind: note.kind,
+                    number: *counter,
+                    chapter,
+                    text: note.text.clone(),
+                });
+            }
+        }
+    }
+    numbered
+}
+
+/// First free `note-N` id
+fn next_note_id(pages: &[PageData]) -> String {
+    let last = pages
+        .iter()
+        .flat_map(|p| &p.layers)
+        .flat_map(|l| &l.notes)
+        .filter_map(|n| n.id.strip_prefix("note-")?.parse::<u32>().ok())
+        .max()
+        .unwrap_or(0);
+    format!("note-{}", last + 1)
+}
+
+/// The layer holding note `id`, and the note's position in it
+fn find_note<'a>(pages: &'a mut [PageData], id: &str) -> Result<(&'a mut LayerObject, usize), String> {
+    pages
+        .iter_mut()
+        .flat_map(|p| p.layers.iter_mut())
+        .find_map(|l| {
+            let index = l.notes.iter().position(|n| n.id == id)?;
+            Some((l, index))
+        })
+        .ok_or_else(|| format!("Note {} not found", id))
+}
+
+/// Apply `edit`, returning the id of the note it inserted, changed or removed
+pub fn edit_note(pages: &mut [PageData], edit: NoteEdit) -> Result<String, String> {
+    match edit {
+        NoteEdit::Insert { page_index, layer_id, anchor, kind, text } => {
+            let id = next_note_id(pages);
+            let layer = pages
+                .iter_mut()
+                .find(|p| p.page_index == page_index)
+                .ok_or_else(|| format!("Page {} not found", page_index))?
+                .layers
+                .iter_mut()
+                .find(|l| l.id == layer_id)
+                .ok_or_else(|| format!("Layer {} not found", layer_id))?;
+            let content = match (&layer.layer_type, &layer.content) {
+                (LayerType::Text, Some(content)) => content,
+                _ => return Err(format!("Layer {} has no text", layer_id)),
+            };
+            if anchor > content.len() || !content.is_char_boundary(anchor) {
+                return Err(format!("Anchor {} is not a character position in layer {}", anchor, layer_id));
+            }
+            layer.notes.push(Note { id: id.clone(), anchor, kind, text });
+            layer.notes.sort_by_key(|n| n.anchor);
+            Ok(id)
+        }
+        NoteEdit::Update { id, text, kind } => {
+            let (layer, index) = find_note(pages, &id)?;
+            let note = &mut layer.notes[index];
+            if let Some(text) = text {
+                note.text = text;
+            }
+            if let Some(kind) = kind {
+                note.kind = kind;
+            }
+            Ok(id)
+        }
+        NoteEdit::Remove { id } => {
+            let (layer, index) = find_note(pages, &id)?;
+            layer.notes.remove(index);
+            Ok(id)
+        }
+    }
+}
+
+/// Lines of `text` set at `font_size` in Helvetica metrics, `width` wide from (`x`, `y`)
+fn set_lines(text: &str, x: f32, y: f32, width: f32, font_size: f32) -> Vec<LineBox> {
+    let frame = Bounds::new(x, y, width, f32::MAX / 2.0);
+    let measure = |s: &str| s.chars().map(|c| helvetica_width(c) * font_size).sum::<f32>();
+    text_wrap::layout_frame(text, frame, font_size * LINE_HEIGHT, TextAlign::Left, &[], measure).lines
+}
+
+/// Text layer for one laid-out line
+fn line_layer(id: String, line: &LineBox, font_size: f32, font_family: Option<&String>) -> LayerObject {
+    let bounds = Bounds::new(line.x, line.y, line.width, font_size * LINE_HEIGHT);
+    let mut layer = new_layer(id, LayerType::Text, bounds);
+    layer.content = Some(line.text.clone());
+    layer.font_size = Some(font_size);
+    layer.font_family = font_family.cloned();
+    layer
+}
+
+/// A footnote to set: its number, text, size and the anchoring layer's font
+struct Footnote<'a> {
+    number: u32,
+    text: &'a str,
+    font_size: f32,
+    font_family: Option<String>,
+}
+
+/// Set `footnotes` under a rule at the foot of the text column `x0..x1`,
+/// shortening the frames above so they clear it
+fn place_footnotes(page: &mut PageData, (x0, x1): (f32, f32), footnotes: &[Footnote]) {
+    let in_column = |l: &LayerObject| {
+        l.visible
+            && l.layer_type == LayerType::Text
+            && l.role == LayerRole::Content
+            && l.bounds.x < x1
+            && x0 < l.bounds.x + l.bounds.width
+    };
+    let bottom = page
+        .layers
+        .iter()
+        .filter(|l| in_column(l))
+        .map(|l| l.bounds.y + l.bounds.height)
+        .fold(f32::MIN, f32::max);
+    let width = x1 - x0;
+
+    let mut lines = Vec::new();
+    let mut height = 0.0;
+    for note in footnotes {
+        let text = format!("{} {}", superscript(note.number), note.text);
+        let set = set_lines(&text, x0, height, width, note.font_size);
+        height += set.len() as f32 * note.font_size * LINE_HEIGHT;
+        lines.extend(set.into_iter().map(|line| (line, note)));
+    }
+    let top = bottom - height;
+    let rule_y = top - RULE_GAP;
+
+    for layer in page.layers.iter_mut().filter(|l| in_column(l)) {
+        let clear = rule_y - RULE_GAP - layer.bounds.y;
+        if layer.bounds.y < rule_y && layer.bounds.height > clear {
+            layer.bounds.height = clear.max(0.0);
+        }
+    }
+
+    let z = page.layers.iter().map(|l| l.z_index).max().unwrap_or(0) + 1;
+    let mut rule = new_layer(
+        format!("footnote-rule-{}", page.page_index),
+        LayerType::Shape,
+        Bounds::new(x0, rule_y, width * RULE_LENGTH, 0.0),
+    );
+    rule.shape_type = Some(ShapeType::Line);
+    rule.stroke_color = Some("#000000".to_string());
+    rule.stroke_width = Some(0.5);
+    rule.z_index = z;
+    page.layers.push(rule);
+    for (k, (mut line, note)) in lines.into_iter().enumerate() {
+        line.y += top;
+        let mut layer = line_layer(
+            format!("footnote-{}-{}", page.page_index, k),
+            &line,
+            note.font_size,
+            note.font_family.as_ref(),
+        );
+        layer.z_index = z;
+        page.layers.push(layer);
+    }
+}
+
+/// Pages setting `endnotes` under the endnote title, sized like `template`
+fn endnote_pages(template: &PageData, chapter: usize, endnotes: &[&NumberedNote], options: &NoteOptions) -> Vec<PageData> {
+    if endnotes.is_empty() {
+        return Vec::new();
+    }
+    let margin = options.endnote_margin.max(0.0);
+    let width = (template.width - 2.0 * margin).max(1.0);
+    let bottom = template.height - margin;
+    let font_size = options.font_size.unwrap_or(ENDNOTE_FONT_SIZE);
+    let blank = || PageData {
+        page_index: template.page_index,
+        width: template.width,
+        height: template.height,
+        dpi: template.dpi,
+        layers: Vec::new(),
+        metadata: None,
+        background: template.background.clone(),
+    };
+
+    let title_size = font_size * 1.6;
+    let mut page = blank();
+    let mut y = margin;
+    for line in set_lines(&options.endnote_title, margin, y, width, title_size) {
+        let mut title = line_layer(format!("endnotes-{}-title", chapter), &line, title_size, None);
+        title.font_weight = Some(700);
+        y = line.y + title_size * LINE_HEIGHT;
+        page.layers.push(title);
+    }
+    y += font_size;
+
+    let mut pages = Vec::new();
+    let mut k = 0;
+    for note in endnotes {
+        let text = format!("{}. {}", note.number, note.text);
+        for mut line in set_lines(&text, margin, 0.0, width, font_size) {
+            if y + font_size * LINE_HEIGHT > bottom && !page.layers.is_empty() {
+                pages.push(std::mem::replace(&mut page, blank()));
+                y = margin;
+            }
+            line.y = y;
+            page.layers.push(line_layer(format!("endnote-{}-{}", chapter, k), &line, font_size, None));
+            y += font_size * LINE_HEIGHT;
+            k += 1;
+        }
+        y += font_size * 0.5;
+    }
+    pages.push(page);
+    pages
+}
+
+/// Pages with their notes set as plain layers, for formats without notes
+/// of their own: reference marks in the text, footnotes at the foot of
+/// each page and endnote pages after each chapter
+///
+/// Page indices are renumbered from the first page's when endnote pages
+/// are inserted. Notes on hidden layers are dropped.
+pub fn resolve_notes(pages: &[PageData], options: &NoteOptions) -> Vec<PageData> {
+    let numbered = number_notes(pages, options.footnote_numbering);
+    if numbered.is_empty() {
+        return pages.to_vec();
+    }
+    let by_id: HashMap<&str, &NumberedNote> = numbered.iter().map(|n| (n.id.as_str(), n)).collect();
+    let starts = chapter_starts(pages);
+    let first_index = pages.first().map_or(0, |p| p.page_index);
+
+    let mut resolved = Vec::with_capacity(pages.len());
+    for (position, source) in pages.iter().enumerate() {
+        let mut page = source.clone();
+        let mut footnotes = Vec::new();
+        let mut column: Option<(f32, f32)> = None;
+        for layer in page.layers.iter_mut() {
+            let mut notes = std::mem::take(&mut layer.notes);
+            if !layer.visible {
+                continue;
+            }
+            let Some(content) = layer.content.as_mut() else {
+                continue;
+            };
+            notes.sort_by_key(|n| n.anchor);
+            for note in notes.iter().rev() {
+                let Some(numbered) = by_id.get(note.id.as_str()) else {
+                    continue;
+                };
+                content.insert_str(floor_boundary(content, note.anchor), &superscript(numbered.number));
+                if numbered.kind == NoteKind::Footnote {
+                    footnotes.push(Footnote {
+                        number: numbered.number,
+                        text: &numbered.text,
+                        font_size: options.font_size.unwrap_or(layer.font_size.unwrap_or(12.0) * NOTE_SIZE_RATIO),
+                        font_family: layer.font_family.clone(),
+                    });
+                    let (x0, x1) = (layer.bounds.x, layer.bounds.x + layer.bounds.width);
+                    column = Some(column.map_or((x0, x1), |(a, b)| (a.min(x0), b.max(x1))));
+                }
+            }
+        }
+        if let Some(column) = column {
+            footnotes.sort_by_key(|f| f.number);
+            place_footnotes(&mut page, column, &footnotes);
+        }
+        resolved.push(page);
+
+        if position + 1 == pages.len() || starts.contains(&(position + 1)) {
+            let chapter = chapter_of(&starts, position);
+            let endnotes: Vec<&NumberedNote> =
+                numbered.iter().filter(|n| n.kind == NoteKind::Endnote && n.chapter == chapter).collect();
+            resolved.extend(endnote_pages(source, chapter, &endnotes, options));
+        }
+    }
+    for (i, page) in resolved.iter_mut().enumerate() {
+        page.page_index = first_index + i;
+    }
+    resolved
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn text(id: &str, y: f32, size: f32, content: &str) -> LayerObject {
+        let mut layer = new_layer(id.to_string(), LayerType::Text, Bounds::new(72.0, y, 468.0, 600.0 - y));
+        layer.content = Some(content.to_string());
+        layer.font_size = Some(size);
+        layer
+    }
+
+    fn page(index: usize, layers: Vec<LayerObject>) -> PageData {
+        PageData { page_index: index, width: 612.0, height: 792.0, dpi: None, layers, metadata: None, background: None }
+    }
+
+    fn note(id: &str, anchor: usize, kind: NoteKind) -> Note {
+        Note { id: id.to_string(), anchor, kind, text: format!("Text of {}", id) }
+    }
+
+    fn book() -> Vec<PageData> {
+        let body = "Lorem ipsum dolor sit amet, consectetur adipiscing elit, sed do eiusmod tempor incididunt ut labore";
+        let mut one = text("body1", 120.0, 11.0, body);
+        one.notes = vec![note("b", 11, NoteKind::Footnote), note("a", 5, NoteKind::Footnote), note("e1", 17, NoteKind::Endnote)];
+        let mut two = text("body2", 72.0, 11.0, body);
+        two.notes = vec![note("c", 5, NoteKind::Footnote)];
+        let mut three = text("body3", 120.0, 11.0, body);
+        three.notes = vec![note("d", 5, NoteKind::Footnote), note("e2", 11, NoteKind::Endnote)];
+        vec![
+            page(0, vec![text("h1", 72.0, 24.0, "Chapter One"), one]),
+            page(1, vec![two]),
+            page(2, vec![text("h2", 72.0, 24.0, "Chapter Two"), three]),
+        ]
+    }
+
+    #[test]
+    fn test_remap_anchors() {
+        let mut notes = vec![note("a", 4, NoteKind::Footnote), note("b", 6, NoteKind::Footnote), note("c", 15, NoteKind::Footnote)];
+        // "quick" becomes "slow": before untouched, inside to its end, after shifted
+        remap_anchors(&mut notes, "The quick brown fox", "The slow brown fox");
+        let anchors: Vec<usize> = notes.iter().map(|n| n.anchor).collect();
+        assert_eq!(anchors, vec![4, 8, 14]);
+        // Curly quotes are wider than straight ones
+        let mut notes = vec![note("a", 5, NoteKind::Footnote)];
+        remap_anchors(&mut notes, "\"Hi\" there", "\u{201C}Hi\u{201D} there");
+        assert_eq!(&"\u{201C}Hi\u{201D} there"[..notes[0].anchor], "\u{201C}Hi\u{201D} ");
+    }
+
+    #[test]
+    fn test_number_and_edit_notes() {
+        let mut pages = book();
+        assert_eq!(chapter_starts(&pages), vec![0, 2]);
+        let numbers = |pages: &[PageData], numbering| -> Vec<(String, u32)> {
+            number_notes(pages, numbering).into_iter().map(|n| (n.id, n.number)).collect()
+        };
+        let pairs = |list: &[(&str, u32)]| -> Vec<(String, u32)> { list.iter().map(|&(id, n)| (id.to_string(), n)).collect() };
+        assert_eq!(
+            numbers(&pages, NoteNumbering::Document),
+            pairs(&[("a", 1), ("b", 2), ("e1", 1), ("c", 3), ("d", 4), ("e2", 1)])
+        );
+        assert_eq!(
+            numbers(&pages, NoteNumbering::Chapter),
+            pairs(&[("a", 1), ("b", 2), ("e1", 1), ("c", 3), ("d", 1), ("e2", 1)])
+        );
+        assert_eq!(
+            numbers(&pages, NoteNumbering::Page),
+            pairs(&[("a", 1), ("b", 2), ("e1", 1), ("c", 1), ("d", 1), ("e2", 1)])
+        );
+
+        // A note inserted before the others takes number 1 and shifts the rest
+        let insert = NoteEdit::Insert {
+            page_index: 0,
+            layer_id: "body1".to_string(),
+            anchor: 0,
+            kind: NoteKind::Footnote,
+            text: "First".to_string(),
+        };
+        let id = edit_note(&mut pages, insert).unwrap();
+        assert_eq!(id, "note-1");
+        assert_eq!(numbers(&pages, NoteNumbering::Document)[..2], pairs(&[("note-1", 1), ("a", 2)]));
+        edit_note(&mut pages, NoteEdit::Update { id: "a".to_string(), text: None, kind: Some(NoteKind::Endnote) }).unwrap();
+        edit_note(&mut pages, NoteEdit::Remove { id: "note-1".to_string() }).unwrap();
+        assert_eq!(numbers(&pages, NoteNumbering::Document)[..2], pairs(&[("a", 1), ("b", 1)]));
+
+        let edit: NoteEdit = serde_json::from_value(serde_json::json!({
+            "action": "insert", "pageIndex": 1, "layerId": "body2", "anchor": 2, "text": "x"
+        }))
+        .unwrap();
+        assert_eq!(edit_note(&mut pages, edit).unwrap(), "note-1");
+        assert!(edit_note(&mut pages, NoteEdit::Remove { id: "zz".to_string() }).is_err());
+        let outside = NoteEdit::Insert {
+            page_index: 1,
+            layer_id: "body2".to_string(),
+            anchor: 999,
+            kind: NoteKind::Footnote,
+            text: String::new(),
+        };
+        assert!(edit_note(&mut pages, outside).is_err());
+    }
+
+    #[test]
+    fn test_resolve_notes() {
+        let resolved = resolve_notes(&book(), &NoteOptions::default());
+        // An endnote page after each chapter
+        assert_eq!(resolved.len(), 5);
+        assert_eq!(resolved.iter().map(|p| p.page_index).collect::<Vec<_>>(), vec![0, 1, 2, 3, 4]);
+        assert!(resolved.iter().flat_map(|p| &p.layers).all(|l| l.notes.is_empty()));
+
+        let first = &resolved[0];
+        let body = first.layers.iter().find(|l| l.id == "body1").unwrap();
+        assert!(body.content.as_deref().unwrap().starts_with("Lorem\u{b9} ipsum\u{b2} dolor\u{b9} sit"));
+        // Footnotes end at the foot of the column, the frame stops above the rule
+        let notes: Vec<&LayerObject> = first.layers.iter().filter(|l| l.id.starts_with("footnote-0-")).collect();
+        assert_eq!(notes[0].content.as_deref(), Some("\u{b9} Text of a"));
+        assert_eq!(notes[0].font_size, Some(11.0 * NOTE_SIZE_RATIO));
+        let last = notes.last().unwrap();
+        assert!((last.bounds.y + last.bounds.height - 600.0).abs() < 1e-3);
+        let rule = first.layers.iter().find(|l| l.id == "footnote-rule-0").unwrap();
+        assert_eq!(rule.shape_type, Some(ShapeType::Line));
+        assert!((body.bounds.y + body.bounds.height - (rule.bounds.y - RULE_GAP)).abs() < 1e-3);
+        assert!(rule.bounds.y < notes[0].bounds.y);
+
+        let endnotes = &resolved[2];
+        assert_eq!(endnotes.layers[0].content.as_deref(), Some("Notes"));
+        assert_eq!(endnotes.layers[1].content.as_deref(), Some("1. Text of e1"));
+        assert_eq!(resolved[3].layers[0].id, "h2");
+        assert_eq!(resolved[4].layers[1].content.as_deref(), Some("1. Text of e2"));
+
+        let plain = vec![page(0, vec![text("t", 72.0, 11.0, "No notes")])];
+        assert_eq!(resolve_notes(&plain, &NoteOptions::default()), plain);
+    }
+}
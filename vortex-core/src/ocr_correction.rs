@@ -0,0 +1,289 @@
+//! OCR post-correction
+//!
+//! Fixes the predictable misreadings of raw OCR output before it becomes text
+//! layers. Each word is tried, in order, against:
+//!
+//! - a correction dictionary of known misreadings ("tlie" → "the"),
+//! - digit/letter confusion, decided by the rest of the word: "2O23" is a
+//!   number and becomes "2023", "te5t" is a word and becomes "test",
+//! - glyph confusions ("rn" read for "m", "cl" for "d"), kept only when
+//!   they turn an unknown word into a known one.
+//!
+//! Built-in dictionaries cover English; other languages rely on the
+//! heuristics and on user dictionaries, which are merged on top (app-wide
+//! from the settings, then per project). Words listed in a dictionary are
+//! never changed.
+
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, HashMap, HashSet};
+
+/// Common English misreadings
+const ENGLISH_REPLACEMENTS: &[(&str, &str)] = &[
+    ("tlie", "the"),
+    ("tbe", "the"),
+    ("tiie", "the"),
+    ("tliat", "that"),
+    ("tbat", "that"),
+    ("tliis", "this"),
+    ("tbis", "this"),
+    ("tlieir", "their"),
+    ("witli", "with"),
+    ("wliich", "which"),
+    ("wbich", "which"),
+    ("wlien", "when"),
+    ("wliat", "what"),
+    ("liave", "have"),
+    ("bave", "have"),
+];
+
+/// Frequent English words, the targets of glyph confusion fixes
+const ENGLISH_WORDS: &[&str] = &[
+    "a", "about", "after", "all", "am", "an", "and", "any", "are", "as", "at", "away", "be", "been", "but", "by",
+    "called", "came", "can", "come", "could", "day", "did", "do", "does", "done", "down", "each", "end", "find",
+    "first", "for", "form", "from", "good", "had", "hand", "has", "have", "he", "her", "him", "his", "home", "how",
+    "if", "in", "into", "is", "it", "its", "just", "kind", "know", "like", "little", "long", "made", "make", "man",
+    "many", "may", "me", "mean", "men", "might", "mind", "modern", "moment", "more", "morning", "most", "much", "must",
+    "my", "name", "need", "new", "no", "not", "now", "number", "of", "old", "on", "one", "only", "or", "other", "our",
+    "out", "over", "own", "people", "said", "same", "see", "she", "should", "show", "side", "small", "so", "some",
+    "summer", "than", "that", "the", "their", "them", "then", "there", "these", "they", "this", "time", "to", "turn",
+    "two", "up", "use", "used", "very", "was", "water", "way", "we", "well", "were", "what", "when", "where", "which",
+    "while", "who", "will", "with", "without", "word", "words", "work", "world", "would", "you", "your",
+];
+
+/// Glyph sequences OCR reads for others, as (read, meant)
+const GLYPH_CONFUSIONS: &[(&str, &str)] = &[("rn", "m"), ("m", "rn"), ("cl", "d"), ("vv", "w"), ("li", "h"), ("ii", "u")];
+
+/// User corrections for OCR output
+#[derive(Debug, Clone, Default, Serialize, Deserialize, PartialEq)]
+#[serde(rename_all = "camelCase", default)]
+pub struct OcrDictionary {
+    /// Misread words and their corrections, matched ignoring case
+    #[serde(skip_serializing_if = "BTreeMap::is_empty")]
+    pub replacements: BTreeMap<String, String>,
+    /// Words that are right as read: names, jargon, part numbers
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub words: Vec<String>,
+}
+
+impl OcrDictionary {
+    pub fn is_empty(&self) -> bool {
+        self.replacements.is_empty() && self.words.is_empty()
+    }
+}
+
+/// Corrects OCR words for a language and set of dictionaries
+#[derive(Debug, Clone, Default)]
+pub struct OcrCorrector {
+    english: bool,
+    replacements: HashMap<String, String>,
+    words: HashSet<String>,
+}
+
+impl OcrCorrector {
+    /// Corrector for Tesseract `language` (e.g. "eng+deu"); later
+    /// dictionaries override earlier ones
+    pub fn new(language: &str, dictionaries: &[&OcrDictionary]) -> Self {
+        let english = language.split('+').any(|l| l == "eng");
+        let mut corrector = Self { english, ..Self::default() };
+        if english {
+            corrector.replacements.extend(ENGLISH_REPLACEMENTS.iter().map(|&(k, v)| (k.to_string(), v.to_string())));
+            corrector.words.extend(ENGLISH_WORDS.iter().map(|w| w.to_string()));
+        }
+        for dictionary in dictionaries {
+            for (from, to) in &dictionary.replacements {
+                corrector.replacements.insert(from.to_lowercase(), to.clone());
+            }
+            for word in &dictionary.words {
+                corrector.replacements.remove(&word.to_lowercase());
+                corrector.words.insert(word.to_lowercase());
+            }
+        }
+        corrector
+    }
+
+    /// Correct a sequence of words, using neighbours as context
+    pub fn correct_words(&self, words: &[&str]) -> Vec<String> {
+        let is_word = |w: Option<&&str>| w.is_some_and(|w| w.chars().any(char::is_alphabetic) && !w.contains('|'));
+        words
+            .iter()
+            .enumerate()
+            .map(|(i, word)| {
+                let (lead, core, trail) = split_punctuation(word);
+                // A lone "l" or "|" between words is the pronoun "I"
+                let standalone_i = self.english
+                    && matches!(core, "l" | "|")
+                    && is_word(i.checked_sub(1).and_then(|p| words.get(p)))
+                    && is_word(words.get(i + 1));
+                let core = if standalone_i { "I".to_string() } else { self.correct_core(core) };
+                format!("{}{}{}", lead, core, trail)
+            })
+            .collect()
+    }
+
+    /// Correct free text word by word, keeping its lines
+    pub fn correct_text(&self, text: &str) -> String {
+        text.lines()
+            .map(|line| self.correct_words(&line.split_whitespace().collect::<Vec<_>>()).join(" "))
+            .collect::<Vec<_>>()
+            .join("\n")
+    }
+
+    fn is_known(&self, word: &str) -> bool {
+        self.words.contains(&word.to_lowercase())
+    }
+
+    fn correct_core(&self, core: &str) -> String {
+        if core.is_empty() || self.is_known(core) {
+            return core.to_string();
+        }
+        if let Some(to) = self.replacements.get(&core.to_lowercase()) {
+            return match_case(core, to);
+        }
+        let core = fix_digit_letter(core, |w| self.is_known(w));
+        if self.is_known(&core) {
+            return core;
+        }
+        for &(read, meant) in GLYPH_CONFUSIONS {
+            let lower = core.to_lowercase();
+            for (at, _) in lower.match_indices(read) {
+                let candidate = format!("{}{}{}", &lower[..at], meant, &lower[at + read.len()..]);
+                if self.is_known(&candidate) {
+                    return match_case(&core, &candidate);
+                }
+            }
+        }
+        core
+    }
+}
+
+/// Split leading and trailing punctuation off a word
+fn split_punctuation(word: &str) -> (&str, &str, &str) {
+    let keep = |c: char| c.is_alphanumeric() || c == '|';
+    let start = word.find(keep).unwrap_or(word.len());
+    let end = word.rfind(keep).map_or(start, |i| i + word[i..].chars().next().unwrap().len_utf8());
+    (&word[..start], &word[start..end], &word[end..])
+}
+
+/// `to` in the capitalization of `like`
+fn match_case(like: &str, to: &str) -> String {
+    let letters: Vec<char> = like.chars().filter(|c| c.is_alphabetic()).collect();
+    if letters.len() > 1 && letters.iter().all(|c| c.is_uppercase()) {
+        to.to_uppercase()
+    } else if letters.first().is_some_and(|c| c.is_uppercase()) {
+        let mut chars = to.chars();
+        chars.next().map(|c| c.to_uppercase().chain(chars).collect()).unwrap_or_default()
+    } else {
+        to.to_string()
+    }
+}
+
+/// Letter read for a digit, upper or lower case
+fn letter_for(c: char) -> Option<(char, char)> {
+    match c {
+        '0' => Some(('O', 'o')),
+        '1' | '|' => Some(('I', 'l')),
+        '5' => Some(('S', 's')),
+        '8' => Some(('B', 'b')),
+        _ => None,
+    }
+}
+
+/// Digit read for a letter
+fn digit_for(c: char) -> Option<char> {
+    match c {
+        'O' | 'o' => Some('0'),
+        'I' | 'l' | '|' => Some('1'),
+        _ => None,
+    }
+}
+
+/// Resolve digits inside words and letters inside numbers
+///
+/// Only isolated characters are changed, so runs like "Win10" or "10th" are
+/// left alone. A mostly numeric word takes digits; a mostly alphabetic one
+/// takes letters, in the word's case, preferring a known word.
+fn fix_digit_letter(core: &str, is_known: impl Fn(&str) -> bool) -> String {
+    let chars: Vec<char> = core.chars().collect();
+    let is_digit = |c: char| c.is_ascii_digit();
+    let is_letter = |c: char| c.is_alphabetic();
+    let digits = chars.iter().filter(|&&c| is_digit(c)).count();
+    let letters = chars.iter().filter(|&&c| is_letter(c)).count();
+    let isolated = |i: usize, same: &dyn Fn(char) -> bool| {
+        i.checked_sub(1).map_or(true, |p| !same(chars[p])) && chars.get(i + 1).map_or(true, |&n| !same(n))
+    };
+
+    if digits > letters && letters > 0 {
+        let fixable = chars.iter().enumerate().filter(|&(_, &c)| is_letter(c)).all(|(i, &c)| {
+            digit_for(c).is_some() && isolated(i, &is_letter)
+        });
+        if fixable {
+            return chars.iter().map(|&c| digit_for(c).filter(|_| is_letter(c)).unwrap_or(c)).collect();
+        }
+        return core.to_string();
+    }
+
+    if letters > digits && letters >= 2 {
+        let suspect = |c: char| is_digit(c) || c == '|';
+        let positions: Vec<usize> = (0..chars.len()).filter(|&i| suspect(chars[i])).collect();
+        if positions.is_empty() || !positions.iter().all(|&i| letter_for(chars[i]).is_some() && isolated(i, &suspect)) {
+            return core.to_string();
+        }
+        let all_caps = chars.iter().filter(|&&c| is_letter(c)).all(|c| c.is_uppercase());
+        let pick = |upper_at_start: bool| -> String {
+            chars
+                .iter()
+                .enumerate()
+                .map(|(i, &c)| match letter_for(c).filter(|_| suspect(c)) {
+                    Some((upper, lower)) => {
+                        if all_caps || (i == 0 && upper_at_start) {
+                            upper
+                        } else {
+                            lower
+                        }
+                    }
+                    None => c,
+                })
+                .collect()
+        };
+        let (capitalized, lowered) = (pick(true), pick(false));
+        return if !is_known(&capitalized) && is_known(&lowered) { lowered } else { capitalized };
+    }
+    core.to_string()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn correct(corrector: &OcrCorrector, text: &str) -> String {
+        corrector.correct_text(text)
+    }
+
+    #[test]
+    fn test_english_corrections() {
+        let corrector = OcrCorrector::new("eng+deu", &[]);
+        assert_eq!(correct(&corrector, "Tlie rnan said, \"tbe 2O23 te5t\"."), "The man said, \"the 2023 test\".");
+        assert_eq!(correct(&corrector, "WITLI 0ctober 1ittle rnodern"), "WITH October little modern");
+        assert_eq!(correct(&corrector, "so l think | was 1O5 years"), "so I think I was 105 years");
+        // Runs of digits and ordinals are left alone
+        assert_eq!(correct(&corrector, "Win10 10th 1990s A4 mp3 page 1 of 2"), "Win10 10th 1990s A4 mp3 page 1 of 2");
+        assert_eq!(correct(&corrector, "burn clear\nl"), "burn clear\nl");
+    }
+
+    #[test]
+    fn test_user_dictionaries() {
+        let app = OcrDictionary {
+            replacements: [("Vortcx".to_string(), "Vortex".to_string())].into_iter().collect(),
+            words: vec![],
+        };
+        let project = OcrDictionary {
+            replacements: [("rooc".to_string(), "Rook".to_string())].into_iter().collect(),
+            words: vec!["tbe".to_string(), "R2D2".to_string()],
+        };
+        let corrector = OcrCorrector::new("fra", &[&app, &project]);
+        assert_eq!(correct(&corrector, "vortcx rooc tbe R2D2"), "Vortex Rook tbe R2D2");
+        // No built-in dictionary outside English, but digits still resolve
+        assert_eq!(correct(&corrector, "tlie te5t"), "tlie test");
+        assert!(OcrDictionary::default().is_empty());
+    }
+}
@@ -0,0 +1,176 @@
+//! OCR review queue
+//!
+//! OCR'd text layers carry their recognition confidence (`LayerObject::ocr`).
+//! The queue lists the pending layers that fall below a confidence threshold,
+//! or contain a word that does, so only the uncertain text is proofread.
+//! Accepting or correcting a layer records that on the layer, taking it off
+//! the queue; the state is saved with the project like any other layer field.
+
+use crate::models::{LayerType, OcrReviewStatus, OcrWordConfidence, PageData};
+use serde::{Deserialize, Serialize};
+
+/// Confidence below which OCR'd text is queued for review
+pub const DEFAULT_REVIEW_THRESHOLD: f32 = 0.8;
+
+/// An OCR text layer awaiting review
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct OcrReviewItem {
+    pub layer_id: String,
+    pub text: String,
+    pub confidence: f32,
+    /// Words below the threshold, to highlight
+    pub words: Vec<OcrWordConfidence>,
+}
+
+/// Queued layers of one page, least confident first
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct OcrReviewPage {
+    pub page_index: usize,
+    pub items: Vec<OcrReviewItem>,
+}
+
+/// Pages after a review action and what is left to review
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct OcrReviewResult {
+    pub pages: Vec<PageData>,
+    pub queue: Vec<OcrReviewPage>,
+}
+
+/// Outcome of reviewing an OCR text layer
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+#[serde(tag = "action", rename_all = "camelCase")]
+pub enum OcrReviewAction {
+    /// The recognized text is right
+    Accept,
+    /// Replace the text with the proofread version
+    Correct { text: String },
+}
+
+/// Pending OCR layers below `threshold`, grouped by page
+pub fn review_queue(pages: &[PageData], threshold: f32) -> Vec<OcrReviewPage> {
+    pages
+        .iter()
+        .filter_map(|page| {
+            let mut items: Vec<OcrReviewItem> = page
+                .layers
+                .iter()
+                .filter(|l| l.layer_type == LayerType::Text)
+                .filter_map(|layer| {
+                    let ocr = layer.ocr.as_ref().filter(|o| o.review == OcrReviewStatus::Pending)?;
+                    let words: Vec<OcrWordConfidence> =
+                        ocr.words.iter().filter(|w| w.confidence < threshold).cloned().collect();
+                    if ocr.confidence >= threshold && words.is_empty() {
+                        return None;
+                    }
+                    Some(OcrReviewItem {
+                        layer_id: layer.id.clone(),
+                        text: layer.content.clone().unwrap_or_default(),
+                        confidence: ocr.confidence,
+                        words,
+                    })
+                })
+                .collect();
+            if items.is_empty() {
+                return None;
+            }
+            items.sort_by(|a, b| a.confidence.total_cmp(&b.confidence));
+            Some(OcrReviewPage { page_index: page.page_index, items })
+        })
+        .collect()
+}
+
+/// Apply a review action to OCR layer `layer_id` on page `page_index`
+///
+/// A correction identical to the recognized text counts as an accept.
+pub fn review_layer(
+    pages: &mut [PageData],
+    page_index: usize,
+    layer_id: &str,
+    action: OcrReviewAction,
+) -> Result<(), String> {
+    let layer = pages
+        .iter_mut()
+        .find(|p| p.page_index == page_index)
+        .ok_or_else(|| format!("Page {} not found", page_index))?
+        .layers
+        .iter_mut()
+        .find(|l| l.id == layer_id)
+        .ok_or_else(|| format!("Layer {} not found", layer_id))?;
+    let ocr = layer.ocr.as_mut().ok_or_else(|| format!("Layer {} is not OCR text", layer_id))?;
+
+    ocr.review = match action {
+        OcrReviewAction::Correct { text } if layer.content.as_deref() != Some(text.as_str()) => {
+            layer.content = Some(text);
+            OcrReviewStatus::Corrected
+        }
+        _ => OcrReviewStatus::Accepted,
+    };
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::clipboard::new_layer;
+    use crate::models::{Bounds, OcrInfo};
+
+    fn ocr_layer(id: &str, text: &str, confidence: f32, words: &[(&str, f32)]) -> crate::models::LayerObject {
+        let mut layer = new_layer(id.to_string(), LayerType::Text, Bounds::new(0.0, 0.0, 100.0, 12.0));
+        layer.content = Some(text.to_string());
+        layer.ocr = Some(OcrInfo {
+            confidence,
+            words: words
+                .iter()
+                .map(|&(text, confidence)| OcrWordConfidence {
+                    text: text.to_string(),
+                    confidence,
+                    bounds: Bounds::new(0.0, 0.0, 20.0, 12.0),
+                })
+                .collect(),
+            review: OcrReviewStatus::Pending,
+        });
+        layer
+    }
+
+    #[test]
+    fn test_review_queue_shrinks() {
+        let mut pages = vec![
+            PageData {
+                page_index: 0,
+                width: 612.0,
+                height: 792.0,
+                dpi: None,
+                layers: vec![
+                    ocr_layer("sure", "The quick fox", 0.95, &[("The", 0.96), ("quick", 0.94), ("fox", 0.95)]),
+                    ocr_layer("word", "jumps ovcr", 0.85, &[("jumps", 0.97), ("ovcr", 0.73)]),
+                    ocr_layer("line", "th3 1azy", 0.6, &[]),
+                    new_layer("plain".to_string(), LayerType::Text, Bounds::new(0.0, 0.0, 10.0, 10.0)),
+                ],
+                metadata: None,
+                background: None,
+            },
+            PageData { page_index: 1, width: 612.0, height: 792.0, dpi: None, layers: vec![], metadata: None, background: None },
+        ];
+
+        let queue = review_queue(&pages, DEFAULT_REVIEW_THRESHOLD);
+        assert_eq!(queue.len(), 1);
+        let ids: Vec<&str> = queue[0].items.iter().map(|i| i.layer_id.as_str()).collect();
+        assert_eq!(ids, ["line", "word"]);
+        assert_eq!(queue[0].items[1].words.len(), 1);
+        assert_eq!(queue[0].items[1].words[0].text, "ovcr");
+
+        review_layer(&mut pages, 0, "word", OcrReviewAction::Correct { text: "jumps over".to_string() }).unwrap();
+        assert_eq!(pages[0].layers[1].content.as_deref(), Some("jumps over"));
+        assert_eq!(pages[0].layers[1].ocr.as_ref().unwrap().review, OcrReviewStatus::Corrected);
+
+        review_layer(&mut pages, 0, "line", OcrReviewAction::Correct { text: "th3 1azy".to_string() }).unwrap();
+        assert_eq!(pages[0].layers[2].ocr.as_ref().unwrap().review, OcrReviewStatus::Accepted);
+        assert!(review_queue(&pages, DEFAULT_REVIEW_THRESHOLD).is_empty());
+
+        assert!(review_layer(&mut pages, 0, "plain", OcrReviewAction::Accept).is_err());
+        assert!(review_layer(&mut pages, 3, "line", OcrReviewAction::Accept).is_err());
+    }
+}
@@ -0,0 +1,267 @@
+//! Page Labels
+//! Printed page numbers (i, ii, iii, then 1, 2, 3) and the PDF /PageLabels tree
+//!
+//! Each page carries its own label in `PageMetadata`, so reordering or
+//! deleting pages keeps the right numbers. Ranges for /PageLabels are
+//! recovered on export: a new range starts wherever the style or prefix
+//! changes or the numbering does not continue from the previous page.
+
+use crate::models::{PageLabel, PageLabelStyle};
+
+impl PageLabelStyle {
+    /// /S value in a PDF page label dictionary
+    pub fn pdf_name(self) -> &'static str {
+        match self {
+            PageLabelStyle::Decimal => "D",
+            PageLabelStyle::UpperRoman => "R",
+            PageLabelStyle::LowerRoman => "r",
+            PageLabelStyle::UpperAlpha => "A",
+            PageLabelStyle::LowerAlpha => "a",
+        }
+    }
+
+    pub fn from_pdf_name(name: &[u8]) -> Option<Self> {
+        match name {
+            b"D" => Some(PageLabelStyle::Decimal),
+            b"R" => Some(PageLabelStyle::UpperRoman),
+            b"r" => Some(PageLabelStyle::LowerRoman),
+            b"A" => Some(PageLabelStyle::UpperAlpha),
+            b"a" => Some(PageLabelStyle::LowerAlpha),
+            _ => None,
+        }
+    }
+
+    /// `number` written in this style
+    pub fn format(self, number: u32) -> String {
+        match self {
+            PageLabelStyle::Decimal => number.to_string(),
+            PageLabelStyle::UpperRoman => roman(number),
+            PageLabelStyle::LowerRoman => roman(number).to_lowercase(),
+            PageLabelStyle::UpperAlpha => alpha(number),
+            PageLabelStyle::LowerAlpha => alpha(number).to_lowercase(),
+        }
+    }
+}
+
+/// Roman numerals; 0 has none and is written as a digit
+fn roman(mut number: u32) -> String {
+    if number == 0 {
+        return "0".to_string();
+    }
+    const NUMERALS: [(u32, &str); 13] = [
+        (1000, "M"),
+        (900, "CM"),
+        (500, "D"),
+        (400, "CD"),
+        (100, "C"),
+        (90, "XC"),
+        (50, "L"),
+        (40, "XL"),
+        (10, "X"),
+        (9, "IX"),
+        (5, "V"),
+        (4, "IV"),
+        (1, "I"),
+    ];
+    let mut out = String::new();
+    for (value, numeral) in NUMERALS {
+        while number >= value {
+            out.push_str(numeral);
+            number -= value;
+        }
+    }
+    out
+}
+
+/// PDF letter numbering: A … Z, then AA … ZZ, AAA …
+fn alpha(number: u32) -> String {
+    if number == 0 {
+        return String::new();
+    }
+    let letter = char::from(b'A' + ((number - 1) % 26) as u8);
+    std::iter::repeat(letter).take(((number - 1) / 26 + 1) as usize).collect()
+}
+
+impl PageLabel {
+    /// The label as printed, e.g. "A-4" or "xii"
+    pub fn text(&self) -> String {
+        let number = self.style.map(|s| s.format(self.number)).unwrap_or_default();
+        format!("{}{}", self.prefix.as_deref().unwrap_or(""), number)
+    }
+
+    /// Whether `self` carries on the numbering of `previous`
+    fn continues(&self, previous: &PageLabel) -> bool {
+        self.style == previous.style && self.prefix == previous.prefix && self.number == previous.number + 1
+    }
+}
+
+/// Label ranges as (first page index, label of that page)
+///
+/// Unlabeled pages after labeled ones start a range with an empty label.
+pub fn ranges(labels: &[Option<PageLabel>]) -> Vec<(usize, PageLabel)> {
+    let empty = PageLabel { style: None, prefix: None, number: 1 };
+    let mut out: Vec<(usize, PageLabel)> = Vec::new();
+    let mut previous: Option<&PageLabel> = None;
+    for (index, label) in labels.iter().enumerate() {
+        let label = label.as_ref().unwrap_or(&empty);
+        // Prefix-only ranges repeat the same label, whatever the number
+        let starts = match previous {
+            None => true,
+            Some(prev) if label.style.is_none() => prev.style.is_some() || label.prefix != prev.prefix,
+            Some(prev) => !label.continues(prev),
+        };
+        if starts {
+            out.push((index, label.clone()));
+        }
+        previous = Some(label);
+    }
+    out
+}
+
+/// Labels of a document's pages from its /PageLabels number tree
+#[cfg(feature = "pdf")]
+pub fn read_page_labels(doc: &lopdf::Document, page_count: usize) -> Vec<Option<PageLabel>> {
+    use lopdf::Object;
+
+    /// Collect (start, label dictionary) pairs from a number tree node
+    fn collect<'a>(
+        doc: &'a lopdf::Document,
+        node: &'a lopdf::Dictionary,
+        out: &mut Vec<(usize, &'a lopdf::Dictionary)>,
+        depth: u8,
+    ) {
+        if let Ok(nums) = node.get(b"Nums").and_then(Object::as_array) {
+            for pair in nums.chunks(2) {
+                let [key, value] = pair else { continue };
+                let start = doc.dereference(key).ok().and_then(|(_, k)| k.as_i64().ok());
+                let dict = doc.dereference(value).ok().and_then(|(_, v)| v.as_dict().ok());
+                if let (Some(start), Some(dict)) = (start, dict) {
+                    out.push((start.max(0) as usize, dict));
+                }
+            }
+        }
+        if depth == 0 {
+            return;
+        }
+        if let Ok(kids) = node.get(b"Kids").and_then(Object::as_array) {
+            for kid in kids {
+                if let Ok((_, Object::Dictionary(kid))) = doc.dereference(kid) {
+                    collect(doc, kid, out, depth - 1);
+                }
+            }
+        }
+    }
+
+    let Some(root) = doc
+        .catalog()
+        .ok()
+        .and_then(|c| c.get(b"PageLabels").ok())
+        .and_then(|o| doc.dereference(o).ok())
+        .and_then(|(_, o)| o.as_dict().ok())
+    else {
+        return vec![None; page_count];
+    };
+    let mut entries = Vec::new();
+    collect(doc, root, &mut entries, 16);
+    entries.sort_by_key(|(start, _)| *start);
+
+    (0..page_count)
+        .map(|index| {
+            let (start, dict) = entries.iter().rev().find(|(start, _)| *start <= index)?;
+            let style = dict.get(b"S").and_then(Object::as_name).ok().and_then(PageLabelStyle::from_pdf_name);
+            let prefix = dict.get(b"P").ok().and_then(|p| lopdf::decode_text_string(p).ok()).filter(|p| !p.is_empty());
+            let first = dict.get(b"St").and_then(Object::as_i64).unwrap_or(1).max(1) as u32;
+            Some(PageLabel { style, prefix, number: first + (index - start) as u32 })
+        })
+        .collect()
+}
+
+/// Replace a document's /PageLabels with `labels` (one per page)
+///
+/// Removes the tree when no page has a label.
+#[cfg(feature = "pdf")]
+pub fn write_page_labels(doc: &mut lopdf::Document, labels: &[Option<PageLabel>]) -> Result<(), String> {
+    use lopdf::{Dictionary, Object};
+
+    let nums: Vec<Object> = if labels.iter().all(Option::is_none) {
+        Vec::new()
+    } else {
+        ranges(labels)
+            .into_iter()
+            .flat_map(|(start, label)| {
+                let mut dict = Dictionary::new();
+                if let Some(style) = label.style {
+                    dict.set("S", Object::Name(style.pdf_name().as_bytes().to_vec()));
+                }
+                if let Some(prefix) = &label.prefix {
+                    dict.set("P", lopdf::text_string(prefix));
+                }
+                if label.number != 1 {
+                    dict.set("St", label.number as i64);
+                }
+                [Object::Integer(start as i64), Object::Dictionary(dict)]
+            })
+            .collect()
+    };
+
+    let catalog = doc.catalog_mut().map_err(|e| format!("PDF has no catalog: {}", e))?;
+    if nums.is_empty() {
+        catalog.remove(b"PageLabels");
+    } else {
+        let mut tree = Dictionary::new();
+        tree.set("Nums", nums);
+        catalog.set("PageLabels", tree);
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn label(style: Option<PageLabelStyle>, prefix: Option<&str>, number: u32) -> Option<PageLabel> {
+        Some(PageLabel { style, prefix: prefix.map(str::to_string), number })
+    }
+
+    #[test]
+    fn test_label_text() {
+        assert_eq!(PageLabelStyle::LowerRoman.format(14), "xiv");
+        assert_eq!(PageLabelStyle::UpperRoman.format(1999), "MCMXCIX");
+        assert_eq!(PageLabelStyle::UpperAlpha.format(1), "A");
+        assert_eq!(PageLabelStyle::LowerAlpha.format(28), "bb");
+        assert_eq!(label(Some(PageLabelStyle::Decimal), Some("A-"), 7).unwrap().text(), "A-7");
+        assert_eq!(label(None, Some("Cover"), 1).unwrap().text(), "Cover");
+    }
+
+    #[cfg(feature = "pdf")]
+    #[test]
+    fn test_page_labels_roundtrip() {
+        let roman = Some(PageLabelStyle::LowerRoman);
+        let decimal = Some(PageLabelStyle::Decimal);
+        let labels = vec![
+            label(None, Some("Cover"), 1),
+            label(roman, None, 1),
+            label(roman, None, 2),
+            label(decimal, None, 1),
+            label(decimal, None, 2),
+            // Page 5 was removed, so numbering jumps
+            label(decimal, None, 4),
+            None,
+        ];
+        let starts: Vec<usize> = ranges(&labels).into_iter().map(|(start, _)| start).collect();
+        assert_eq!(starts, vec![0, 1, 3, 5, 6]);
+
+        let mut doc = lopdf::Document::with_version("1.7");
+        let catalog = doc.add_object(lopdf::dictionary! { "Type" => "Catalog" });
+        doc.trailer.set("Root", catalog);
+        write_page_labels(&mut doc, &labels).unwrap();
+        // The unlabeled page reads back as an empty label
+        let mut expected = labels.clone();
+        expected[6] = label(None, None, 1);
+        assert_eq!(read_page_labels(&doc, labels.len()), expected);
+
+        write_page_labels(&mut doc, &[None, None]).unwrap();
+        assert!(doc.catalog().unwrap().get(b"PageLabels").is_err());
+        assert_eq!(read_page_labels(&doc, 2), vec![None, None]);
+    }
+}
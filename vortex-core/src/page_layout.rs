@@ -0,0 +1,172 @@
+//! Page layout stamping
+//!
+//! Copies the design of one page (its background, header and footer layers,
+//! page background and guides) onto other pages, so a late design change
+//! can be pushed through a whole chapter. Copies get ids starting with
+//! `layout-`, which marks them as stamped: stamping again replaces them,
+//! along with the target's own unlocked layers of the stamped roles.
+//! Content and annotation layers are never touched.
+//!
+//! Layers keep their source position; pages of another size are not scaled.
+
+use crate::layers::normalize_z_indices;
+use crate::models::{LayerObject, LayerRole, PageData, PageMetadata};
+use serde::{Deserialize, Serialize};
+
+/// Id prefix of stamped layers
+pub const LAYOUT_ID_PREFIX: &str = "layout-";
+
+/// What `apply_page_layout` copies
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct PageLayoutOptions {
+    /// Roles of the layers copied and replaced
+    #[serde(default = "default_roles")]
+    pub roles: Vec<LayerRole>,
+    /// Also copy the page's guides
+    #[serde(default = "default_true")]
+    pub guides: bool,
+}
+
+fn default_roles() -> Vec<LayerRole> {
+    vec![LayerRole::Background, LayerRole::Header, LayerRole::Footer]
+}
+
+fn default_true() -> bool {
+    true
+}
+
+impl Default for PageLayoutOptions {
+    fn default() -> Self {
+        Self { roles: default_roles(), guides: true }
+    }
+}
+
+/// Whether `layer` was stamped from another page
+#[inline]
+pub fn is_stamped(layer: &LayerObject) -> bool {
+    layer.id.starts_with(LAYOUT_ID_PREFIX)
+}
+
+/// Id of a stamped copy on page `page_index`; copies of copies keep the
+/// original layer's id
+fn stamped_id(page_index: usize, layer_id: &str) -> String {
+    let original = layer_id
+        .strip_prefix(LAYOUT_ID_PREFIX)
+        .and_then(|rest| rest.split_once('-'))
+        .map_or(layer_id, |(_, id)| id);
+    format!("{}{}-{}", LAYOUT_ID_PREFIX, page_index, original)
+}
+
+/// Stamp the layout of `pages[source]` onto `pages[t]` for each target
+///
+/// Background layers go below the target's own layers, the rest above them.
+/// Returns how many pages were stamped.
+pub fn apply_page_layout(
+    pages: &mut [PageData],
+    source: usize,
+    targets: &[usize],
+    options: &PageLayoutOptions,
+) -> Result<usize, String> {
+    let page_count = pages.len();
+    if let Some(bad) = std::iter::once(&source).chain(targets).find(|&&i| i >= page_count) {
+        return Err(format!("Page {} is out of range for {} pages", bad, page_count));
+    }
+    let template = pages[source].clone();
+    let mut layout: Vec<&LayerObject> = template.layers.iter().filter(|l| options.roles.contains(&l.role)).collect();
+    layout.sort_by_key(|l| l.z_index);
+    let guides = template.metadata.as_ref().and_then(|m| m.guides.clone());
+
+    let mut stamped = 0;
+    for &target in targets.iter().filter(|&&t| t != source) {
+        let page = &mut pages[target];
+        let kept: Vec<LayerObject> = std::mem::take(&mut page.layers)
+            .into_iter()
+            .filter(|l| !is_stamped(l) && (l.locked || !options.roles.contains(&l.role)))
+            .collect();
+        let top = kept.iter().map(|l| l.z_index).max().unwrap_or(0);
+        let bottom = kept.iter().map(|l| l.z_index).min().unwrap_or(0);
+
+        let copies = layout.iter().enumerate().map(|(i, layer)| {
+            let z = if layer.role == LayerRole::Background {
+                bottom - (layout.len() - i) as i32
+            } else {
+                top + 1 + i as i32
+            };
+            LayerObject { id: stamped_id(page.page_index, &layer.id), z_index: z, ..(*layer).clone() }
+        });
+        page.layers = kept.into_iter().chain(copies).collect();
+        normalize_z_indices(&mut page.layers);
+
+        if options.roles.contains(&LayerRole::Background) {
+            page.background = template.background.clone();
+        }
+        if options.guides {
+            page.metadata.get_or_insert_with(PageMetadata::default).guides = guides.clone();
+        }
+        stamped += 1;
+    }
+    Ok(stamped)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::clipboard::new_layer;
+    use crate::models::{Bounds, LayerType, PageBackground, PageGuides};
+
+    fn layer(id: &str, role: LayerRole, z: i32) -> LayerObject {
+        let mut layer = new_layer(id.to_string(), LayerType::Shape, Bounds::new(0.0, 0.0, 10.0, 10.0));
+        (layer.role, layer.z_index) = (role, z);
+        layer
+    }
+
+    fn page(page_index: usize, layers: Vec<LayerObject>) -> PageData {
+        PageData { page_index, width: 612.0, height: 792.0, dpi: None, layers, metadata: None, background: None }
+    }
+
+    #[test]
+    fn test_apply_page_layout() {
+        let mut source = page(
+            0,
+            vec![
+                layer("bg", LayerRole::Background, 0),
+                layer("body", LayerRole::Content, 1),
+                layer("head", LayerRole::Header, 2),
+            ],
+        );
+        source.background = Some(PageBackground { color: Some("#F5F0E6".to_string()), image: None });
+        source.metadata = Some(PageMetadata {
+            guides: Some(PageGuides { vertical: vec![72.0], ..Default::default() }),
+            ..Default::default()
+        });
+        let mut locked_footer = layer("own-foot", LayerRole::Footer, 3);
+        locked_footer.locked = true;
+        let mut pages = vec![
+            source,
+            page(
+                1,
+                vec![layer("old-head", LayerRole::Header, 0), layer("text", LayerRole::Content, 1), locked_footer],
+            ),
+        ];
+
+        assert_eq!(apply_page_layout(&mut pages, 0, &[0, 1], &PageLayoutOptions::default()).unwrap(), 1);
+        let ids = |page: &PageData| {
+            let mut layers = page.layers.clone();
+            layers.sort_by_key(|l| l.z_index);
+            layers.into_iter().map(|l| l.id).collect::<Vec<_>>()
+        };
+        assert_eq!(ids(&pages[1]), ["layout-1-bg", "text", "own-foot", "layout-1-head"]);
+        assert_eq!(pages[1].background, pages[0].background);
+        let guides = pages[1].metadata.as_ref().and_then(|m| m.guides.as_ref());
+        assert_eq!(guides.map(|g| g.vertical.clone()), Some(vec![72.0]));
+
+        // Stamping again replaces the earlier copies
+        pages[0].layers.retain(|l| l.id != "head");
+        apply_page_layout(&mut pages, 0, &[1], &PageLayoutOptions::default()).unwrap();
+        assert_eq!(ids(&pages[1]), ["layout-1-bg", "text", "own-foot"]);
+        assert_eq!(stamped_id(2, "layout-1-bg"), "layout-2-bg");
+
+        assert!(apply_page_layout(&mut pages, 0, &[5], &PageLayoutOptions::default()).is_err());
+    }
+}
@@ -0,0 +1,590 @@
+//! Page size presets, margins and bleed
+//!
+//! All values are in points (1/72 in). The trim size is the document page
+//! size (`DocumentData::page_width`/`page_height`); margins and bleed are
+//! stored in `ProjectSettings`. `PageSetup` bundles the three for importers,
+//! exporters and the resize command.
+
+use crate::models::{
+    Bounds, DocumentData, LayerObject, LayerRole, LayerType, PageData, PageMetadata, PathCommand, ShapeType,
+    SourceType,
+};
+use crate::units::{in_to_pt, mm_to_pt, POINTS_PER_INCH};
+use serde::{Deserialize, Serialize};
+
+/// Distance within which a layer counts as touching the trim edge
+const EDGE_TOLERANCE: f32 = 0.5;
+
+/// Page margins in points
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct Margins {
+    pub top: f32,
+    pub right: f32,
+    pub bottom: f32,
+    pub left: f32,
+}
+
+impl Margins {
+    #[inline]
+    pub const fn uniform(value: f32) -> Self {
+        Self { top: value, right: value, bottom: value, left: value }
+    }
+}
+
+impl Default for Margins {
+    /// One inch on every side
+    #[inline]
+    fn default() -> Self {
+        Self::uniform(POINTS_PER_INCH)
+    }
+}
+
+/// Common trim sizes
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+#[repr(u8)]
+pub enum PageSizePreset {
+    /// US Letter, 8.5 × 11 in
+    Letter = 0,
+    A4 = 1,
+    A5 = 2,
+    /// US trade paperback, 6 × 9 in
+    Trade6x9 = 3,
+    /// Digest / novel, 5 × 8 in
+    Digest5x8 = 4,
+    /// Square photo book, 8.5 × 8.5 in
+    SquarePhoto = 5,
+}
+
+impl PageSizePreset {
+    pub const ALL: [PageSizePreset; 6] = [
+        PageSizePreset::Letter,
+        PageSizePreset::A4,
+        PageSizePreset::A5,
+        PageSizePreset::Trade6x9,
+        PageSizePreset::Digest5x8,
+        PageSizePreset::SquarePhoto,
+    ];
+
+    /// Display name
+    pub const fn name(&self) -> &'static str {
+        match self {
+            PageSizePreset::Letter => "US Letter (8.5 × 11 in)",
+            PageSizePreset::A4 => "A4 (210 × 297 mm)",
+            PageSizePreset::A5 => "A5 (148 × 210 mm)",
+            PageSizePreset::Trade6x9 => "Trade (6 × 9 in)",
+            PageSizePreset::Digest5x8 => "Digest (5 × 8 in)",
+            PageSizePreset::SquarePhoto => "Square photo book (8.5 × 8.5 in)",
+        }
+    }
+
+    /// Trim width and height in points
+    pub fn size(&self) -> (f32, f32) {
+        match self {
+            PageSizePreset::Letter => (in_to_pt(8.5), in_to_pt(11.0)),
+            PageSizePreset::A4 => (mm_to_pt(210.0), mm_to_pt(297.0)),
+            PageSizePreset::A5 => (mm_to_pt(148.0), mm_to_pt(210.0)),
+            PageSizePreset::Trade6x9 => (in_to_pt(6.0), in_to_pt(9.0)),
+            PageSizePreset::Digest5x8 => (in_to_pt(5.0), in_to_pt(8.0)),
+            PageSizePreset::SquarePhoto => (in_to_pt(8.5), in_to_pt(8.5)),
+        }
+    }
+
+    /// Suggested margins: 1 in for office sizes, 0.75 in for books,
+    /// 0.5 in for photo books
+    pub fn margins(&self) -> Margins {
+        match self {
+            PageSizePreset::Letter | PageSizePreset::A4 => Margins::uniform(POINTS_PER_INCH),
+            PageSizePreset::A5 | PageSizePreset::Trade6x9 | PageSizePreset::Digest5x8 => {
+                Margins::uniform(in_to_pt(0.75))
+            }
+            PageSizePreset::SquarePhoto => Margins::uniform(in_to_pt(0.5)),
+        }
+    }
+
+    /// Page setup for this preset, without bleed
+    pub fn page_setup(&self) -> PageSetup {
+        let (width, height) = self.size();
+        PageSetup { width, height, margins: self.margins(), bleed: 0.0 }
+    }
+
+    /// Preset matching a trim size to within a point, either orientation
+    pub fn detect(width: f32, height: f32) -> Option<PageSizePreset> {
+        let close = |a: f32, b: f32| (a - b).abs() < 1.0;
+        Self::ALL.into_iter().find(|preset| {
+            let (w, h) = preset.size();
+            (close(w, width) && close(h, height)) || (close(h, width) && close(w, height))
+        })
+    }
+}
+
+/// Preset entry as listed to the UI
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct PageSizeInfo {
+    pub preset: PageSizePreset,
+    pub name: String,
+    pub width: f32,
+    pub height: f32,
+    pub margins: Margins,
+}
+
+/// All presets with their sizes and suggested margins
+pub fn page_size_presets() -> Vec<PageSizeInfo> {
+    PageSizePreset::ALL
+        .into_iter()
+        .map(|preset| {
+            let (width, height) = preset.size();
+            PageSizeInfo { preset, name: preset.name().to_string(), width, height, margins: preset.margins() }
+        })
+        .collect()
+}
+
+/// Trim size, margins and bleed of a document
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct PageSetup {
+    /// Trim width
+    pub width: f32,
+    /// Trim height
+    pub height: f32,
+    #[serde(default)]
+    pub margins: Margins,
+    /// Extra area past the trim edge on every side
+    #[serde(default)]
+    pub bleed: f32,
+}
+
+impl Default for PageSetup {
+    fn default() -> Self {
+        PageSizePreset::Letter.page_setup()
+    }
+}
+
+impl PageSetup {
+    /// Area inside the margins, in page coordinates
+    pub fn content_bounds(&self) -> Bounds {
+        let m = &self.margins;
+        Bounds::new(
+            m.left,
+            m.top,
+            (self.width - m.left - m.right).max(0.0),
+            (self.height - m.top - m.bottom).max(0.0),
+        )
+    }
+}
+
+/// How existing layers follow a page size change
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq, Default)]
+#[serde(rename_all = "camelCase")]
+#[repr(u8)]
+pub enum ResizeMode {
+    /// Scale uniformly to fit the new page, centered
+    #[default]
+    Scale = 0,
+    /// Keep layer sizes, center the old page area on the new one
+    Center = 1,
+    /// Keep layer sizes and positions relative to the top-left corner
+    TopLeft = 2,
+}
+
+/// Map every coordinate of a layer through `x * scale + dx`, `y * scale + dy`
+fn transform_layer(layer: &mut LayerObject, scale: f32, dx: f32, dy: f32) {
+    let b = &mut layer.bounds;
+    *b = Bounds::new(b.x * scale + dx, b.y * scale + dy, b.width * scale, b.height * scale);
+
+    if scale != 1.0 {
+        let outline = layer.text_outline.as_mut().map(|o| &mut o.width);
+        let shadow = layer.text_shadow.as_mut().map(|s| [&mut s.offset_x, &mut s.offset_y, &mut s.blur]);
+        let wrap = layer.text_wrap.as_mut().map(|w| &mut w.offset);
+        for value in [&mut layer.font_size, &mut layer.stroke_width, &mut layer.letter_spacing]
+            .into_iter()
+            .flatten()
+            .chain(outline)
+            .chain(wrap)
+            .chain(shadow.into_iter().flatten())
+        {
+            *value *= scale;
+        }
+    }
+
+    // Vector paths and text baselines are stored in page coordinates
+    let paths = [layer.path_data.as_mut(), layer.text_path.as_mut().map(|t| &mut t.path)];
+    for path in paths.into_iter().flatten() {
+        let map = |x: &mut f32, y: &mut f32| {
+            *x = *x * scale + dx;
+            *y = *y * scale + dy;
+        };
+        for cmd in &mut path.commands {
+            match cmd {
+                PathCommand::MoveTo { x, y } | PathCommand::LineTo { x, y } => map(x, y),
+                PathCommand::CurveTo { x1, y1, x2, y2, x, y } => {
+                    map(x1, y1);
+                    map(x2, y2);
+                    map(x, y);
+                }
+                PathCommand::ClosePath => {}
+            }
+        }
+    }
+    if let Some(text_path) = &mut layer.text_path {
+        text_path.start_offset *= scale;
+    }
+}
+
+/// Resize one page, moving its layers per `mode`
+pub fn resize_page(page: &mut PageData, width: f32, height: f32, mode: ResizeMode) {
+    let (old_w, old_h) = (page.width, page.height);
+    let (scale, dx, dy) = match mode {
+        ResizeMode::Scale if old_w > 0.0 && old_h > 0.0 => {
+            let scale = (width / old_w).min(height / old_h);
+            (scale, (width - old_w * scale) / 2.0, (height - old_h * scale) / 2.0)
+        }
+        ResizeMode::Scale | ResizeMode::TopLeft => (1.0, 0.0, 0.0),
+        ResizeMode::Center => (1.0, (width - old_w) / 2.0, (height - old_h) / 2.0),
+    };
+
+    page.width = width;
+    page.height = height;
+    if scale != 1.0 || dx != 0.0 || dy != 0.0 {
+        for layer in &mut page.layers {
+            transform_layer(layer, scale, dx, dy);
+        }
+    }
+}
+
+/// Change the trim size of a document and every page in it
+pub fn resize_document(document: &mut DocumentData, width: f32, height: f32, mode: ResizeMode) -> Result<(), String> {
+    if !(width > 0.0 && height > 0.0) {
+        return Err(format!("Invalid page size: {} × {}", width, height));
+    }
+    document.page_width = width;
+    document.page_height = height;
+    for page in &mut document.pages {
+        resize_page(page, width, height, mode);
+    }
+    Ok(())
+}
+
+/// Full-page Background-role layers for a page's background, bottom first
+///
+/// Color becomes a filled rectangle and the image an image layer, both below
+/// every existing layer and covering the trim edges, so `with_bleed` carries
+/// them out to the bleed edge.
+pub fn background_layers(page: &PageData) -> Vec<LayerObject> {
+    let Some(background) = &page.background else { return Vec::new() };
+    let bottom = page.layers.iter().map(|l| l.z_index).min().unwrap_or(0).min(0);
+    let layer = |kind: &str, layer_type: LayerType, z_index: i32| LayerObject {
+        id: format!("background-{}-{}", kind, page.page_index),
+        layer_type,
+        bounds: Bounds::new(0.0, 0.0, page.width, page.height),
+        visible: true,
+        locked: true,
+        z_index,
+        opacity: 1.0,
+        blend_mode: None,
+        content: None,
+        font_family: None,
+        font_size: None,
+        font_weight: None,
+        font_style: None,
+        color: None,
+        text_align: None,
+        text_decoration: None,
+        text_transform: None,
+        line_height: None,
+        letter_spacing: None,
+        background_color: None,
+        text_path: None,
+        text_outline: None,
+        text_shadow: None,
+        drop_cap: None,
+        notes: Vec::new(),
+        pagination: None,
+        image_url: None,
+        image_path: None,
+        image_data: None,
+        image_link: None,
+        shape_type: None,
+        stroke_color: None,
+        stroke_width: None,
+        fill_color: None,
+        text_wrap: None,
+        path_data: None,
+        transform: None,
+        source_type: SourceType::Manual,
+        role: LayerRole::Background,
+        ocr: None,
+        tags: Vec::new(),
+    };
+
+    let mut layers = Vec::with_capacity(2);
+    if let Some(color) = &background.color {
+        let mut fill = layer("color", LayerType::Shape, bottom.saturating_sub(2));
+        fill.shape_type = Some(ShapeType::Rectangle);
+        fill.fill_color = Some(color.clone());
+        fill.stroke_width = Some(0.0);
+        layers.push(fill);
+    }
+    if let Some(image) = &background.image {
+        let mut picture = layer("image", LayerType::Image, bottom.saturating_sub(1));
+        if image.starts_with("image://") {
+            picture.image_url = Some(image.clone());
+        } else {
+            picture.image_path = Some(image.clone());
+        }
+        layers.push(picture);
+    }
+    layers
+}
+
+/// Copy of a page with its background turned into layers (see `background_layers`)
+pub fn with_background(page: &PageData) -> PageData {
+    let mut flat = page.clone();
+    let mut layers = background_layers(page);
+    if !layers.is_empty() {
+        layers.append(&mut flat.layers);
+        flat.layers = layers;
+    }
+    flat.background = None;
+    flat
+}
+
+/// Copy of a page grown by `bleed` on every side for print output
+///
+/// Layers move by the bleed offset. Non-text layers that touch a trim edge
+/// are stretched out to the bleed edge so backgrounds print to the cut; run
+/// `with_background` first so page backgrounds are among them.
+pub fn with_bleed(page: &PageData, bleed: f32) -> PageData {
+    let mut bled = page.clone();
+    if bleed <= 0.0 {
+        return bled;
+    }
+    bled.width += bleed * 2.0;
+    bled.height += bleed * 2.0;
+
+    for layer in &mut bled.layers {
+        let b = layer.bounds;
+        transform_layer(layer, 1.0, bleed, bleed);
+        if layer.layer_type == LayerType::Text {
+            continue;
+        }
+        let b2 = &mut layer.bounds;
+        if b.x <= EDGE_TOLERANCE {
+            b2.x -= bleed;
+            b2.width += bleed;
+        }
+        if b.y <= EDGE_TOLERANCE {
+            b2.y -= bleed;
+            b2.height += bleed;
+        }
+        if b.x + b.width >= page.width - EDGE_TOLERANCE {
+            b2.width += bleed;
+        }
+        if b.y + b.height >= page.height - EDGE_TOLERANCE {
+            b2.height += bleed;
+        }
+    }
+    bled
+}
+
+/// Source PDF page box that exported pages are framed to
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq, Default)]
+#[serde(rename_all = "lowercase")]
+#[repr(u8)]
+pub enum PageBox {
+    /// The visible area the page was imported with
+    #[default]
+    Crop = 0,
+    Media = 1,
+    Trim = 2,
+    Bleed = 3,
+}
+
+/// Box imported layers are positioned in: the crop box, else the media box
+pub fn visible_box(metadata: &PageMetadata) -> Option<[f32; 4]> {
+    metadata.crop_box.or(metadata.media_box)
+}
+
+/// Move every layer by `dx`, `dy`
+pub fn translate_layers(layers: &mut [LayerObject], dx: f32, dy: f32) {
+    for layer in layers {
+        transform_layer(layer, 1.0, dx, dy);
+    }
+}
+
+/// Copy of an imported page resized to one of its source PDF boxes
+///
+/// Layers keep their place on the sheet, so content outside a smaller box
+/// is cut off and a larger box uncovers what the crop box hid. Pages that
+/// do not record the box (or were not imported from a PDF) are unchanged.
+pub fn with_page_box(page: &PageData, target: PageBox) -> PageData {
+    let mut framed = page.clone();
+    let Some(metadata) = &page.metadata else {
+        return framed;
+    };
+    let target_box = match target {
+        PageBox::Crop => None,
+        PageBox::Media => metadata.media_box,
+        PageBox::Trim => metadata.trim_box,
+        PageBox::Bleed => metadata.bleed_box,
+    };
+    let (Some([x0, y0, x1, y1]), Some(visible)) = (target_box, visible_box(metadata)) else {
+        return framed;
+    };
+    if x1 <= x0 || y1 <= y0 {
+        return framed;
+    }
+    // Layer y runs down from the top of the visible box
+    translate_layers(&mut framed.layers, visible[0] - x0, y1 - visible[3]);
+    framed.width = x1 - x0;
+    framed.height = y1 - y0;
+    framed
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::PageBackground;
+
+    fn layer(layer_type: LayerType, bounds: Bounds) -> LayerObject {
+        LayerObject {
+            id: "layer".to_string(),
+            layer_type,
+            bounds,
+            visible: true,
+            locked: false,
+            z_index: 0,
+            opacity: 1.0,
+            blend_mode: None,
+            content: None,
+            font_family: None,
+            font_size: Some(12.0),
+            font_weight: None,
+            font_style: None,
+            color: None,
+            text_align: None,
+            text_decoration: None,
+            text_transform: None,
+            line_height: None,
+            letter_spacing: None,
+            background_color: None,
+            text_path: None,
+            text_outline: None,
+            text_shadow: None,
+            drop_cap: None,
+            notes: Vec::new(),
+            pagination: None,
+            image_url: None,
+            image_path: None,
+            image_data: None,
+            image_link: None,
+            shape_type: None,
+            stroke_color: None,
+            stroke_width: None,
+            fill_color: None,
+            text_wrap: None,
+            path_data: None,
+            transform: None,
+            source_type: SourceType::Manual,
+            role: LayerRole::Content,
+            ocr: None,
+            tags: Vec::new(),
+        }
+    }
+
+    fn page(layers: Vec<LayerObject>) -> PageData {
+        PageData { page_index: 0, width: 612.0, height: 792.0, dpi: Some(72), layers, metadata: None, background: None }
+    }
+
+    #[test]
+    fn test_background_extends_into_bleed() {
+        let mut text = layer(LayerType::Text, Bounds::new(72.0, 72.0, 200.0, 20.0));
+        text.z_index = -3;
+        let mut page = page(vec![text]);
+        page.background = Some(PageBackground { color: Some("#F5F0E6".to_string()), image: Some("image://paper".to_string()) });
+
+        let flat = with_background(&page);
+        assert!(flat.background.is_none());
+        let kinds: Vec<(LayerType, i32)> = flat.layers.iter().map(|l| (l.layer_type, l.z_index)).collect();
+        assert_eq!(kinds, [(LayerType::Shape, -5), (LayerType::Image, -4), (LayerType::Text, -3)]);
+        assert_eq!(flat.layers[1].image_url.as_deref(), Some("image://paper"));
+
+        let bled = with_bleed(&flat, 9.0);
+        assert_eq!(bled.layers[0].bounds, Bounds::new(0.0, 0.0, 630.0, 810.0));
+        assert_eq!(bled.layers[1].bounds, Bounds::new(0.0, 0.0, 630.0, 810.0));
+        assert_eq!(with_background(&flat), flat);
+    }
+
+    #[test]
+    fn test_page_box() {
+        let mut page = page(vec![layer(LayerType::Image, Bounds::new(10.0, 20.0, 100.0, 100.0))]);
+        assert_eq!(with_page_box(&page, PageBox::Trim), page);
+
+        // 612×792 sheet cropped by 36pt on the left and bottom, 18pt on top
+        page.width = 576.0;
+        page.height = 738.0;
+        page.metadata = Some(PageMetadata {
+            original_page_index: Some(0),
+            rotation: None,
+            media_box: Some([0.0, 0.0, 612.0, 792.0]),
+            crop_box: Some([36.0, 36.0, 612.0, 774.0]),
+            trim_box: Some([45.0, 45.0, 603.0, 765.0]),
+            bleed_box: None,
+            page_label: None,
+            language: None,
+            guides: None,
+            custom: Default::default(),
+        });
+        let media = with_page_box(&page, PageBox::Media);
+        assert_eq!((media.width, media.height), (612.0, 792.0));
+        assert_eq!(media.layers[0].bounds, Bounds::new(46.0, 38.0, 100.0, 100.0));
+
+        let trim = with_page_box(&page, PageBox::Trim);
+        assert_eq!((trim.width, trim.height), (558.0, 720.0));
+        assert_eq!(trim.layers[0].bounds, Bounds::new(1.0, 11.0, 100.0, 100.0));
+        assert_eq!(with_page_box(&page, PageBox::Bleed), page);
+        assert_eq!(with_page_box(&page, PageBox::Crop), page);
+    }
+
+    #[test]
+    fn test_presets() {
+        assert_eq!(PageSizePreset::Trade6x9.size(), (432.0, 648.0));
+        assert_eq!(PageSizePreset::detect(792.0, 612.0), Some(PageSizePreset::Letter));
+        assert_eq!(PageSizePreset::detect(595.3, 841.9), Some(PageSizePreset::A4));
+        assert_eq!(PageSizePreset::detect(500.0, 500.0), None);
+        assert_eq!(page_size_presets().len(), PageSizePreset::ALL.len());
+
+        let setup = PageSizePreset::Digest5x8.page_setup();
+        assert_eq!(setup.content_bounds(), Bounds::new(54.0, 54.0, 252.0, 468.0));
+    }
+
+    #[test]
+    fn test_resize_scale_and_center() {
+        let text = layer(LayerType::Text, Bounds::new(72.0, 72.0, 468.0, 20.0));
+        let mut doc = DocumentData::new(612.0, 792.0, vec![page(vec![text])]);
+
+        // 612×792 → 306×792 fits at half scale, centered vertically
+        resize_document(&mut doc, 306.0, 792.0, ResizeMode::Scale).unwrap();
+        let l = &doc.pages[0].layers[0];
+        assert_eq!(l.bounds, Bounds::new(36.0, 234.0, 234.0, 10.0));
+        assert_eq!(l.font_size, Some(6.0));
+        assert_eq!(doc.pages[0].width, 306.0);
+
+        resize_document(&mut doc, 406.0, 892.0, ResizeMode::Center).unwrap();
+        assert_eq!(doc.pages[0].layers[0].bounds, Bounds::new(86.0, 284.0, 234.0, 10.0));
+        assert!(resize_document(&mut doc, 0.0, 100.0, ResizeMode::TopLeft).is_err());
+    }
+
+    #[test]
+    fn test_bleed_extends_edge_layers() {
+        let background = layer(LayerType::Shape, Bounds::new(0.0, 0.0, 612.0, 792.0));
+        let text = layer(LayerType::Text, Bounds::new(0.0, 100.0, 200.0, 20.0));
+        let bled = with_bleed(&page(vec![background, text]), 9.0);
+
+        assert_eq!((bled.width, bled.height), (630.0, 810.0));
+        assert_eq!(bled.layers[0].bounds, Bounds::new(0.0, 0.0, 630.0, 810.0));
+        assert_eq!(bled.layers[1].bounds, Bounds::new(9.0, 109.0, 200.0, 20.0));
+    }
+}
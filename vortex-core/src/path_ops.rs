@@ -1,7 +1,8 @@
 //! Path Operations Module
 //! Handles PDF path construction and painting
 
-use crate::models::{Bounds, PathCommand, TransformMatrix};
+use crate::graphics_state::GraphicsState;
+use crate::models::{BlendMode, Bounds, PathCommand, TransformMatrix};
 
 /// Extracted path/vector data
 #[derive(Debug, Clone)]
@@ -12,17 +13,19 @@ pub struct ExtractedPath {
     pub line_width: f32,
     pub bounds: Bounds,
     pub transform: TransformMatrix,
+    pub blend_mode: BlendMode,
 }
 
-/// Transform path commands and calculate bounds
+/// Transform path commands and calculate bounds, painting with the state's
+/// colors (alpha applied) where `stroke`/`fill` are set
 pub fn transform_path(
     commands: &[PathCommand],
-    stroke: Option<[f32; 4]>,
-    fill: Option<[f32; 4]>,
-    line_width: f32,
-    ctm: &TransformMatrix,
+    stroke: bool,
+    fill: bool,
+    state: &GraphicsState,
     page_height: f32,
 ) -> ExtractedPath {
+    let ctm = &state.ctm;
     let mut min_x = f32::MAX;
     let mut min_y = f32::MAX;
     let mut max_x = f32::MIN;
@@ -35,11 +38,12 @@ pub fn transform_path(
 
     ExtractedPath {
         commands: transformed,
-        stroke_color: stroke,
-        fill_color: fill,
-        line_width: line_width * ctm.scale_x().abs(),
+        stroke_color: stroke.then(|| state.effective_stroke()),
+        fill_color: fill.then(|| state.effective_fill()),
+        line_width: state.line_width * ctm.scale_x().abs(),
         bounds: Bounds::new(min_x, min_y, (max_x - min_x).max(1.0), (max_y - min_y).max(1.0)),
-        transform: ctm.clone(),
+        transform: *ctm,
+        blend_mode: state.blend_mode,
     }
 }
 
@@ -87,3 +91,30 @@ fn update_bounds(x: f32, y: f32, min_x: &mut f32, min_y: &mut f32, max_x: &mut f
     *max_x = max_x.max(x);
     *max_y = max_y.max(y);
 }
+
+/// PDF path construction operators (`m`, `l`, `c`, `h`) for page-space
+/// commands, flipped to PDF's bottom-up y axis
+pub fn pdf_path_operators(commands: &[PathCommand], page_height: f32) -> Vec<(&'static str, Vec<f32>)> {
+    let flip = |y: f32| page_height - y;
+    commands
+        .iter()
+        .map(|cmd| match *cmd {
+            PathCommand::MoveTo { x, y } => ("m", vec![x, flip(y)]),
+            PathCommand::LineTo { x, y } => ("l", vec![x, flip(y)]),
+            PathCommand::CurveTo { x1, y1, x2, y2, x, y } => ("c", vec![x1, flip(y1), x2, flip(y2), x, flip(y)]),
+            PathCommand::ClosePath => ("h", vec![]),
+        })
+        .collect()
+}
+
+/// PDF painting operator for a path that is filled and/or stroked
+pub fn pdf_paint_operator(fill: bool, stroke: bool, even_odd: bool) -> &'static str {
+    match (fill, stroke, even_odd) {
+        (true, true, false) => "B",
+        (true, true, true) => "B*",
+        (true, false, false) => "f",
+        (true, false, true) => "f*",
+        (false, true, _) => "S",
+        (false, false, _) => "n",
+    }
+}
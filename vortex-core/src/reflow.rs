@@ -0,0 +1,313 @@
+//! Reflow
+//! Paginates pages as one continuous flow, breaking text between pages
+//!
+//! Content layers are read page by page, top to bottom, and stacked down the
+//! text column of fresh pages with the space between them kept. Layers that
+//! start level with each other (the runs of an imported paragraph, an image
+//! beside its caption) move as one block. A text layer on its own is a
+//! paragraph: it is laid out at its width and may break between lines, as
+//! far as its `Pagination` allows: kept together, kept on the page the next
+//! block starts on, and leaving no fewer than `orphans` lines at the foot of
+//! a page or `widows` lines at the top of the next. Drop-capped and path text
+//! move whole.
+//!
+//! Headers, footers, backgrounds and annotations are not part of the flow and
+//! stay on the page of the same number.
+
+use crate::models::{Bounds, LayerObject, LayerRole, LayerType, Note, PageData, Pagination, TextAlign};
+use crate::page_setup::{translate_layers, PageSetup};
+use crate::text_wrap::{self, helvetica_width};
+
+/// Layers whose tops are closer than this start level with each other
+const LEVEL_TOLERANCE: f32 = 0.5;
+const DEFAULT_FONT_SIZE: f32 = 12.0;
+const DEFAULT_LINE_HEIGHT: f32 = 1.2;
+
+/// Layers moved as a unit
+struct Block {
+    layers: Vec<LayerObject>,
+    /// Top of the block where it was read from
+    top: f32,
+    height: f32,
+    /// Space above the block, dropped at the top of a page
+    gap: f32,
+    /// For a paragraph: line height and the byte offset each line row starts at
+    rows: Option<(f32, Vec<Option<usize>>)>,
+    pagination: Pagination,
+}
+
+impl Block {
+    fn new(layers: Vec<LayerObject>, gap: f32) -> Self {
+        let top = layers.iter().map(|l| l.bounds.y).fold(f32::INFINITY, f32::min);
+        let bottom = layers.iter().map(|l| l.bounds.y + l.bounds.height).fold(f32::NEG_INFINITY, f32::max);
+        let pagination = layers.iter().find_map(|l| l.pagination).unwrap_or_default();
+        let rows = match layers.as_slice() {
+            [layer] => paragraph_rows(layer),
+            _ => None,
+        };
+        let height = match &rows {
+            Some((line_height, rows)) => rows.len() as f32 * line_height,
+            None => bottom - top,
+        };
+        Self { layers, top, height, gap, rows, pagination }
+    }
+
+    /// Whether the block may break between its lines
+    fn breakable(&self) -> bool {
+        let p = &self.pagination;
+        !p.keep_together
+            && !p.keep_with_next
+            && self.rows.as_ref().is_some_and(|(_, rows)| rows.len() as u32 >= p.orphans.max(1) + p.widows.max(1))
+    }
+
+    /// Height that has to fit on the page the block starts on
+    fn lead(&self) -> f32 {
+        match &self.rows {
+            Some((line_height, _)) if self.breakable() => self.pagination.orphans.max(1) as f32 * line_height,
+            _ => self.height,
+        }
+    }
+
+    /// Split off the first `count` rows, leaving the rest as a layer `id` to
+    /// continue on the next page; `None` when no line starts there
+    fn split(&mut self, count: usize, id: String) -> Option<Block> {
+        let (line_height, rows) = self.rows.as_ref()?;
+        let at = rows.get(count..)?.iter().flatten().next().copied()?;
+        let line_height = *line_height;
+        let layer = &mut self.layers[0];
+        let content = layer.content.take().unwrap_or_default();
+
+        let mut rest = layer.clone();
+        rest.id = id;
+        rest.content = Some(content[at..].to_string());
+        rest.drop_cap = None;
+        let (head, tail): (Vec<Note>, Vec<Note>) = layer.notes.drain(..).partition(|n| n.anchor <= at);
+        rest.notes = tail.into_iter().map(|n| Note { anchor: n.anchor - at, ..n }).collect();
+        layer.notes = head;
+        layer.content = Some(content[..at].trim_end().to_string());
+        layer.bounds.height = count as f32 * line_height;
+        self.height = layer.bounds.height;
+        self.rows = None;
+        Some(Block::new(vec![rest], 0.0))
+    }
+}
+
+/// Line rows of a plain text layer laid out at its width, each with the byte
+/// offset of the first line in it (blank rows have none)
+fn paragraph_rows(layer: &LayerObject) -> Option<(f32, Vec<Option<usize>>)> {
+    let content = layer.content.as_deref().filter(|c| !c.trim().is_empty())?;
+    if layer.layer_type != LayerType::Text || layer.text_path.is_some() || layer.drop_cap.is_some() {
+        return None;
+    }
+    let font_size = layer.font_size.unwrap_or(DEFAULT_FONT_SIZE);
+    let line_height = font_size * layer.line_height.unwrap_or(DEFAULT_LINE_HEIGHT);
+    if line_height <= 0.0 {
+        return None;
+    }
+    let measure = |s: &str| s.chars().map(|c| helvetica_width(c) * font_size).sum::<f32>();
+    let frame = Bounds::new(0.0, 0.0, layer.bounds.width, f32::MAX / 2.0);
+    let align = layer.text_align.unwrap_or(TextAlign::Left);
+    let lines = text_wrap::layout_frame(content, frame, line_height, align, &[], measure).lines;
+
+    let word_starts: Vec<usize> = content
+        .char_indices()
+        .filter(|&(i, c)| !c.is_whitespace() && content[..i].chars().next_back().map_or(true, char::is_whitespace))
+        .map(|(i, _)| i)
+        .collect();
+    let mut rows: Vec<Option<usize>> = Vec::new();
+    let mut words = 0;
+    for line in &lines {
+        let row = (line.y / line_height).round() as usize;
+        if rows.len() <= row {
+            rows.resize(row + 1, None);
+            rows[row] = word_starts.get(words).copied();
+        }
+        words += line.text.split_whitespace().count();
+    }
+    (!rows.is_empty()).then_some((line_height, rows))
+}
+
+/// Content layers of every page in reading order, grouped into blocks
+fn flow_blocks(pages: &[PageData]) -> Vec<Block> {
+    let mut blocks = Vec::new();
+    for page in pages {
+        let mut layers: Vec<&LayerObject> = page.layers.iter().filter(|l| l.role == LayerRole::Content).collect();
+        layers.sort_by(|a, b| a.bounds.y.total_cmp(&b.bounds.y).then(a.bounds.x.total_cmp(&b.bounds.x)));
+        let mut bottom: Option<f32> = None;
+        let mut group: Vec<LayerObject> = Vec::new();
+        for layer in layers {
+            if group.first().is_some_and(|first| layer.bounds.y - first.bounds.y > LEVEL_TOLERANCE) {
+                let gap = bottom.map_or(0.0, |b| (group[0].bounds.y - b).max(0.0));
+                let group_bottom = group.iter().map(|l| l.bounds.y + l.bounds.height).fold(f32::NEG_INFINITY, f32::max);
+                bottom = Some(bottom.map_or(group_bottom, |b| b.max(group_bottom)));
+                blocks.push(Block::new(std::mem::take(&mut group), gap));
+            }
+            group.push(layer.clone());
+        }
+        if !group.is_empty() {
+            let gap = bottom.map_or(0.0, |b| (group[0].bounds.y - b).max(0.0));
+            blocks.push(Block::new(group, gap));
+        }
+    }
+    blocks
+}
+
+/// `pages` repaginated on pages of `setup`'s size, the flow filling the area
+/// inside its margins
+pub fn reflow(pages: &[PageData], setup: &PageSetup) -> Vec<PageData> {
+    let column = setup.content_bounds();
+    let mut blocks = flow_blocks(pages);
+
+    // Height that must fit for each block to start on a page: a block kept
+    // with the next carries the start of that one along
+    let mut needs = vec![0.0; blocks.len()];
+    for i in (0..blocks.len()).rev() {
+        needs[i] = match blocks.get(i + 1) {
+            Some(next) if blocks[i].pagination.keep_with_next => blocks[i].height + next.gap + needs[i + 1],
+            _ => blocks[i].lead(),
+        };
+        if needs[i] > column.height {
+            needs[i] = blocks[i].lead();
+        }
+    }
+
+    let mut flowed: Vec<Vec<LayerObject>> = vec![Vec::new()];
+    let mut cursor = 0.0_f32;
+    let place = |block: &mut Block, cursor: &mut f32, flowed: &mut Vec<Vec<LayerObject>>| {
+        let gap = if *cursor > 0.0 { block.gap } else { 0.0 };
+        translate_layers(&mut block.layers, 0.0, column.y + *cursor + gap - block.top);
+        if block.rows.is_some() {
+            block.layers[0].bounds.height = block.height;
+        }
+        *cursor += gap + block.height;
+        flowed.last_mut().expect("a page").append(&mut block.layers);
+    };
+
+    for (i, mut block) in blocks.drain(..).enumerate() {
+        let mut need = needs[i];
+        let id = block.layers[0].id.clone();
+        let mut part = 0;
+        loop {
+            let gap = if cursor > 0.0 { block.gap } else { 0.0 };
+            let room = column.height - cursor - gap;
+            if cursor > 0.0 && need > room + 0.01 {
+                flowed.push(Vec::new());
+                cursor = 0.0;
+                continue;
+            }
+            if block.height <= room + 0.01 || !block.breakable() {
+                place(&mut block, &mut cursor, &mut flowed);
+                break;
+            }
+
+            // Break the paragraph: as many lines as fit, less any the
+            // widows on the next page need
+            let (line_height, rows) = block.rows.as_ref().expect("breakable blocks have rows");
+            let total = rows.len();
+            let fit = ((room + 0.01) / line_height).floor().max(0.0) as usize;
+            let count = fit.min(total - (block.pagination.widows.max(1) as usize).min(total));
+            if count < block.pagination.orphans.max(1) as usize && cursor > 0.0 {
+                flowed.push(Vec::new());
+                cursor = 0.0;
+                continue;
+            }
+            part += 1;
+            let Some(rest) = block.split(count.max(1), format!("{}-{}", id, part)) else {
+                place(&mut block, &mut cursor, &mut flowed);
+                break;
+            };
+            place(&mut block, &mut cursor, &mut flowed);
+            flowed.push(Vec::new());
+            cursor = 0.0;
+            need = rest.lead();
+            block = rest;
+        }
+    }
+
+    let first = pages.first();
+    let page_index = first.map_or(0, |p| p.page_index);
+    flowed
+        .into_iter()
+        .enumerate()
+        .map(|(k, mut layers)| {
+            let source = pages.get(k);
+            if let Some(source) = source {
+                layers.extend(source.layers.iter().filter(|l| l.role != LayerRole::Content).cloned());
+                layers.sort_by_key(|l| l.z_index);
+            }
+            PageData {
+                page_index: page_index + k,
+                width: setup.width,
+                height: setup.height,
+                dpi: first.and_then(|p| p.dpi),
+                layers,
+                metadata: source.and_then(|p| p.metadata.clone()),
+                background: source.or(pages.last()).and_then(|p| p.background.clone()),
+            }
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::clipboard::new_layer;
+    use crate::page_setup::Margins;
+
+    fn paragraph(id: &str, y: f32, words: usize, pagination: Option<Pagination>) -> LayerObject {
+        let mut layer = new_layer(id.to_string(), LayerType::Text, Bounds::new(72.0, y, 100.0, 12.0));
+        layer.content = Some(vec!["word"; words].join(" "));
+        layer.font_size = Some(10.0);
+        layer.pagination = pagination;
+        layer
+    }
+
+    fn page(layers: Vec<LayerObject>) -> PageData {
+        PageData { page_index: 0, width: 300.0, height: 400.0, dpi: None, layers, metadata: None, background: None }
+    }
+
+    /// A 100 pt column: 8 lines of 10 pt text at 1.2
+    fn setup() -> PageSetup {
+        PageSetup { width: 300.0, height: 400.0, margins: Margins { top: 100.0, right: 72.0, bottom: 200.0, left: 72.0 }, bleed: 0.0 }
+    }
+
+    #[test]
+    fn test_widows_and_orphans() {
+        // Four words fill a 100 pt line: 7 lines, then 4
+        let first = paragraph("a", 100.0, 28, None);
+        let second = paragraph("b", 112.0, 16, None);
+        let pages = reflow(&[page(vec![first, second])], &setup());
+        assert_eq!(pages.len(), 2);
+        // One line would fit under the first paragraph; two are needed
+        assert_eq!(pages[0].layers.len(), 1);
+        assert_eq!(pages[1].layers[0].id, "b");
+        assert_eq!(pages[1].layers[0].bounds.y, 100.0);
+
+        // 10 lines break 8 + 2; with 3 widows, 7 + 3
+        let long = paragraph("a", 100.0, 40, Some(Pagination { widows: 3, ..Default::default() }));
+        let pages = reflow(&[page(vec![long])], &setup());
+        let (head, tail) = (&pages[0].layers[0], &pages[1].layers[0]);
+        assert_eq!(head.bounds.height, 84.0);
+        assert_eq!(tail.id, "a-1");
+        assert_eq!(tail.content.as_deref().unwrap().split_whitespace().count(), 12);
+        assert_eq!(head.content.as_deref().unwrap().split_whitespace().count(), 28);
+    }
+
+    #[test]
+    fn test_keep_with_next() {
+        let filler = paragraph("filler", 100.0, 20, Some(Pagination { keep_together: true, ..Default::default() }));
+        let heading = paragraph("heading", 112.0, 1, Some(Pagination { keep_with_next: true, ..Default::default() }));
+        let body = paragraph("body", 124.0, 12, None);
+        let pages = reflow(&[page(vec![filler.clone(), heading.clone(), body.clone()])], &setup());
+        // 5 lines leave room for the heading and two of the body's three
+        assert_eq!(pages[0].layers.iter().map(|l| l.id.as_str()).collect::<Vec<_>>(), ["filler"]);
+        assert_eq!(pages[1].layers[0].id, "heading");
+        assert_eq!(pages[1].layers[0].bounds.y, 100.0);
+
+        // Without the keep the heading stays and the body moves
+        let loose = LayerObject { pagination: None, ..heading };
+        let pages = reflow(&[page(vec![filler, loose, body])], &setup());
+        assert_eq!(pages[0].layers.len(), 2);
+        assert_eq!(pages[1].layers[0].id, "body");
+    }
+}
@@ -0,0 +1,339 @@
+//! Sections
+//!
+//! A book is divided into sections (front matter, chapters and back matter),
+//! each starting at a page and running up to the start of the next. They are
+//! stored on the project (`BookProjectData::sections`) by page index and give
+//! the book its structure:
+//!
+//! - page labels: each section numbers its pages in its own style, from a
+//!   number of its own or carrying on from the section before
+//! - master pages: a section's pages take the background, header and footer
+//!   layers of its master page, stamped as `page_layout` does
+//! - running heads: `{section}`, `{chapter}` and `{page}` in header and
+//!   footer text are filled in per page at export
+//! - contents: PDF bookmarks, the EPUB table of contents, and the files an
+//!   export split by section writes
+//!
+//! Pages before the first section belong to none and keep their own labels.
+
+use crate::doc_structure::{self, OutlineNode};
+use crate::models::{LayerRole, LayerType, PageData, PageLabel, PageLabelStyle, PageMetadata, Section, SectionKind};
+use crate::page_layout::{self, PageLayoutOptions};
+use serde::{Deserialize, Serialize};
+use std::collections::HashSet;
+
+/// A section with its page range and derived title and numbers
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct SectionInfo {
+    pub id: String,
+    pub kind: SectionKind,
+    /// The given title, else the first heading; empty when there is neither
+    pub title: String,
+    /// Number among the chapters, from 1
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub chapter: Option<u32>,
+    pub start_page: usize,
+    /// Last page, inclusive
+    pub end_page: usize,
+    /// Printed number of the first page
+    pub first_label: String,
+}
+
+/// Sections after an edit, with the pages relabeled and restamped
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SectionsResult {
+    pub pages: Vec<PageData>,
+    pub sections: Vec<Section>,
+    /// Id of the section edited
+    pub section_id: String,
+}
+
+/// Sections within `page_count` pages, in page order
+fn ordered(sections: &[Section], page_count: usize) -> Vec<&Section> {
+    let mut ordered: Vec<&Section> = sections.iter().filter(|s| s.start_page < page_count).collect();
+    ordered.sort_by_key(|s| s.start_page);
+    ordered
+}
+
+/// Check ids, start pages and master pages against a document of `page_count` pages
+pub fn validate(sections: &[Section], page_count: usize) -> Result<(), String> {
+    let (mut ids, mut starts) = (HashSet::new(), HashSet::new());
+    for section in sections {
+        if section.id.is_empty() || !ids.insert(section.id.as_str()) {
+            return Err(format!("Section id '{}' is empty or used twice", section.id));
+        }
+        if section.start_page >= page_count {
+            return Err(format!("Section '{}' starts past the last page", section.id));
+        }
+        if !starts.insert(section.start_page) {
+            return Err(format!("Two sections start on page {}", section.start_page + 1));
+        }
+        if section.master_page.is_some_and(|m| m >= page_count) {
+            return Err(format!("Master page of section '{}' is out of range", section.id));
+        }
+    }
+    Ok(())
+}
+
+/// Page label of every page, `None` before the first section
+pub fn section_labels(sections: &[Section], page_count: usize) -> Vec<Option<PageLabel>> {
+    let mut labels = vec![None; page_count];
+    let ordered = ordered(sections, page_count);
+    let mut next = 1;
+    for (i, section) in ordered.iter().enumerate() {
+        let end = ordered.get(i + 1).map_or(page_count, |s| s.start_page);
+        let style = section.numbering.unwrap_or(match section.kind {
+            SectionKind::FrontMatter => PageLabelStyle::LowerRoman,
+            _ => PageLabelStyle::Decimal,
+        });
+        next = section.restart_at.unwrap_or(next);
+        for label in &mut labels[section.start_page..end] {
+            *label = Some(PageLabel { style: Some(style), prefix: section.prefix.clone(), number: next });
+            next += 1;
+        }
+    }
+    labels
+}
+
+/// First heading of the outline on a page in `range`
+fn first_heading(outline: &[OutlineNode], pages: &[PageData], range: std::ops::RangeInclusive<usize>) -> Option<String> {
+    outline.iter().find_map(|node| {
+        let position = pages.iter().position(|p| p.page_index == node.page_index)?;
+        if range.contains(&position) {
+            Some(node.title.clone())
+        } else {
+            first_heading(&node.children, pages, range.clone())
+        }
+    })
+}
+
+/// Every section in page order with its pages, title, chapter number and
+/// first page label
+pub fn section_infos(pages: &[PageData], sections: &[Section]) -> Vec<SectionInfo> {
+    let ordered = ordered(sections, pages.len());
+    if ordered.is_empty() {
+        return Vec::new();
+    }
+    let labels = section_labels(sections, pages.len());
+    let outline = doc_structure::extract_structure(pages).outline;
+    let mut chapter = 0;
+    ordered
+        .iter()
+        .enumerate()
+        .map(|(i, section)| {
+            let end_page = ordered.get(i + 1).map_or(pages.len(), |s| s.start_page) - 1;
+            let number = (section.kind == SectionKind::Chapter).then(|| {
+                chapter += 1;
+                chapter
+            });
+            let title = section
+                .title
+                .clone()
+                .or_else(|| first_heading(&outline, pages, section.start_page..=end_page))
+                .unwrap_or_default();
+            SectionInfo {
+                id: section.id.clone(),
+                kind: section.kind,
+                title,
+                chapter: number,
+                start_page: section.start_page,
+                end_page,
+                first_label: labels[section.start_page].as_ref().map(PageLabel::text).unwrap_or_default(),
+            }
+        })
+        .collect()
+}
+
+/// Entries of the table of contents: the sections that have a title
+pub fn table_of_contents(pages: &[PageData], sections: &[Section]) -> Vec<SectionInfo> {
+    section_infos(pages, sections).into_iter().filter(|s| !s.title.is_empty()).collect()
+}
+
+/// Label each page for its section and stamp each section's master page
+/// onto the rest of its pages
+pub fn apply_sections(pages: &mut [PageData], sections: &[Section]) -> Result<(), String> {
+    validate(sections, pages.len())?;
+    let ordered = ordered(sections, pages.len());
+    for (i, section) in ordered.iter().enumerate() {
+        let Some(master) = section.master_page else {
+            continue;
+        };
+        let end = ordered.get(i + 1).map_or(pages.len(), |s| s.start_page);
+        let targets: Vec<usize> = (section.start_page..end).collect();
+        page_layout::apply_page_layout(pages, master, &targets, &PageLayoutOptions::default())?;
+    }
+    let first = ordered.first().map_or(pages.len(), |s| s.start_page);
+    let labels = section_labels(sections, pages.len());
+    for (page, label) in pages.iter_mut().zip(labels).skip(first) {
+        page.metadata.get_or_insert_with(PageMetadata::default).page_label = label;
+    }
+    Ok(())
+}
+
+/// Add `section`, or replace the one with its id; an empty id gets a new one
+pub fn set_section(pages: &mut [PageData], sections: &mut Vec<Section>, mut section: Section) -> Result<String, String> {
+    if section.id.is_empty() {
+        let taken: HashSet<&str> = sections.iter().map(|s| s.id.as_str()).collect();
+        let n = (1..).find(|n| !taken.contains(format!("section-{}", n).as_str())).unwrap_or_default();
+        section.id = format!("section-{}", n);
+    }
+    let id = section.id.clone();
+    let mut edited: Vec<Section> = sections.iter().filter(|s| s.id != id).cloned().collect();
+    edited.push(section);
+    edited.sort_by_key(|s| s.start_page);
+    apply_sections(pages, &edited)?;
+    *sections = edited;
+    Ok(id)
+}
+
+/// Remove the section `id`; its pages join the section before
+pub fn remove_section(pages: &mut [PageData], sections: &mut Vec<Section>, id: &str) -> Result<(), String> {
+    let before = sections.len();
+    sections.retain(|s| s.id != id);
+    if sections.len() == before {
+        return Err(format!("Section '{}' not found", id));
+    }
+    apply_sections(pages, sections)
+}
+
+/// Copy of `pages` with `{section}`, `{chapter}` and `{page}` in header and
+/// footer text replaced by each page's section title, chapter number and
+/// page label
+pub fn fill_running_heads(pages: &[PageData], sections: &[Section]) -> Vec<PageData> {
+    let infos = section_infos(pages, sections);
+    let mut filled = pages.to_vec();
+    for (position, page) in filled.iter_mut().enumerate() {
+        let info = infos.iter().find(|s| (s.start_page..=s.end_page).contains(&position));
+        let label = page.metadata.as_ref().and_then(|m| m.page_label.as_ref()).map(PageLabel::text);
+        let label = label.unwrap_or_else(|| (position + 1).to_string());
+        for layer in &mut page.layers {
+            if layer.layer_type != LayerType::Text || !matches!(layer.role, LayerRole::Header | LayerRole::Footer) {
+                continue;
+            }
+            if let Some(content) = layer.content.as_mut().filter(|c| c.contains('{')) {
+                *content = content
+                    .replace("{section}", info.map_or("", |s| s.title.as_str()))
+                    .replace("{chapter}", &info.and_then(|s| s.chapter).map(|n| n.to_string()).unwrap_or_default())
+                    .replace("{page}", &label);
+            }
+        }
+    }
+    filled
+}
+
+/// Sections of pages `start..=end`, renumbered from `start`; a section
+/// begun before the range starts it
+pub fn slice_sections(sections: &[Section], start: usize, end: usize) -> Vec<Section> {
+    let ordered = ordered(sections, usize::MAX);
+    ordered
+        .iter()
+        .enumerate()
+        .filter(|(i, s)| s.start_page <= end && ordered.get(i + 1).map_or(true, |next| next.start_page > start))
+        .map(|(_, s)| Section {
+            start_page: s.start_page.max(start) - start,
+            master_page: None,
+            ..(*s).clone()
+        })
+        .collect()
+}
+
+/// `sections` of `before` moved to the same pages of `after`, a copy with
+/// pages added or removed (as `notes::resolve_notes` adds endnote pages),
+/// found by the first layer of each section's start page
+pub fn remap_sections(sections: &[Section], before: &[PageData], after: &[PageData]) -> Vec<Section> {
+    sections
+        .iter()
+        .map(|section| {
+            let anchor = before.get(section.start_page).and_then(|p| p.layers.first()).map(|l| l.id.as_str());
+            let moved = anchor.and_then(|id| after.iter().position(|p| p.layers.iter().any(|l| l.id == id)));
+            Section { start_page: moved.unwrap_or(section.start_page), ..section.clone() }
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::clipboard::new_layer;
+    use crate::models::{Bounds, LayerObject};
+
+    fn text(id: &str, content: &str, role: LayerRole, size: f32, y: f32) -> LayerObject {
+        let mut layer = new_layer(id.to_string(), LayerType::Text, Bounds::new(72.0, y, 300.0, size * 1.2));
+        (layer.content, layer.font_size, layer.role) = (Some(content.to_string()), Some(size), role);
+        layer
+    }
+
+    fn pages(count: usize) -> Vec<PageData> {
+        (0..count)
+            .map(|i| PageData {
+                page_index: i,
+                width: 612.0,
+                height: 792.0,
+                dpi: None,
+                layers: vec![text(&format!("body-{}", i), "Plain body text on this page.", LayerRole::Content, 11.0, 200.0)],
+                metadata: None,
+                background: None,
+            })
+            .collect()
+    }
+
+    fn section(id: &str, start_page: usize, kind: SectionKind) -> Section {
+        Section {
+            id: id.to_string(),
+            start_page,
+            kind,
+            title: None,
+            numbering: None,
+            prefix: None,
+            restart_at: None,
+            master_page: None,
+        }
+    }
+
+    #[test]
+    fn test_labels_and_infos() {
+        let mut pages = pages(6);
+        pages[2].layers.push(text("h1", "The Storm", LayerRole::Content, 24.0, 72.0));
+        let sections = vec![
+            section("front", 0, SectionKind::FrontMatter),
+            Section { restart_at: Some(1), ..section("one", 2, SectionKind::Chapter) },
+            Section { title: Some("Calm".to_string()), ..section("two", 4, SectionKind::Chapter) },
+        ];
+        let labels: Vec<String> = section_labels(&sections, 6).iter().map(|l| l.as_ref().unwrap().text()).collect();
+        assert_eq!(labels, ["i", "ii", "1", "2", "3", "4"]);
+
+        let infos = section_infos(&pages, &sections);
+        assert_eq!(infos[1].title, "The Storm");
+        assert_eq!((infos[1].chapter, infos[1].start_page, infos[1].end_page), (Some(1), 2, 3));
+        assert_eq!((infos[2].chapter, infos[2].first_label.as_str()), (Some(2), "3"));
+        // Untitled front matter has no contents entry
+        assert_eq!(table_of_contents(&pages, &sections).len(), 2);
+
+        assert!(validate(&[section("a", 1, SectionKind::Chapter), section("b", 1, SectionKind::Chapter)], 6).is_err());
+        let sliced = slice_sections(&sections, 3, 5);
+        assert_eq!(sliced.iter().map(|s| (s.id.as_str(), s.start_page)).collect::<Vec<_>>(), [("one", 0), ("two", 1)]);
+    }
+
+    #[test]
+    fn test_masters_and_running_heads() {
+        let mut pages = pages(4);
+        pages[1].layers.push(text("head", "{chapter}. {section} — {page}", LayerRole::Header, 9.0, 36.0));
+        let mut sections = vec![section("front", 0, SectionKind::FrontMatter)];
+        let master = Section { title: Some("Rain".to_string()), master_page: Some(1), ..section("", 1, SectionKind::Chapter) };
+        let id = set_section(&mut pages, &mut sections, master).unwrap();
+        assert_eq!(id, "section-1");
+        assert!(pages[3].layers.iter().any(|l| l.role == LayerRole::Header));
+        assert_eq!(pages[3].metadata.as_ref().unwrap().page_label.as_ref().unwrap().text(), "4");
+
+        let filled = fill_running_heads(&pages, &sections);
+        let head = |page: &PageData| page.layers.iter().find(|l| l.role == LayerRole::Header).unwrap().content.clone();
+        assert_eq!(head(&filled[3]).as_deref(), Some("1. Rain — 4"));
+        assert_eq!(head(&pages[3]).as_deref(), Some("{chapter}. {section} — {page}"));
+
+        assert!(remove_section(&mut pages, &mut sections, "missing").is_err());
+        remove_section(&mut pages, &mut sections, "section-1").unwrap();
+        assert_eq!(sections.len(), 1);
+    }
+}
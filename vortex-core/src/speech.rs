@@ -0,0 +1,216 @@
+//! Audio proofing
+//!
+//! Document text in reading order, split into chapters, for listening to a
+//! proof: hearing the text read aloud catches dropped words and OCR errors
+//! the eye skips over. Chapters start at headings, blocks set noticeably
+//! larger than the body text. Each chapter is written as plain text or SSML
+//! for a text-to-speech engine.
+
+use crate::doc_metadata::escape_xml;
+use crate::models::{LayerRole, PageData};
+use crate::text_structure::{page_blocks, PageTextOptions, TextBlock};
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+
+/// Font size, relative to the body text, from which a block is a heading
+const HEADING_RATIO: f32 = 1.4;
+/// Longer blocks are body text however large they are set
+const MAX_HEADING_CHARS: usize = 80;
+const MAX_HEADING_LINES: usize = 2;
+
+/// What a speech export writes
+#[derive(Debug, Clone, Copy, Default, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "lowercase")]
+pub enum SpeechOutput {
+    /// Audio rendered by the platform speech engine
+    #[default]
+    Audio,
+    Ssml,
+    Text,
+}
+
+/// Options for the speech export
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+#[serde(rename_all = "camelCase", default)]
+pub struct SpeechOptions {
+    pub output: SpeechOutput,
+    /// Engine voice name; the system default when absent
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub voice: Option<String>,
+    /// Speaking rate in words per minute
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub rate: Option<u32>,
+    /// One file per chapter rather than one for the document
+    pub split_chapters: bool,
+}
+
+impl Default for SpeechOptions {
+    fn default() -> Self {
+        Self { output: SpeechOutput::Audio, voice: None, rate: None, split_chapters: true }
+    }
+}
+
+/// A run of paragraphs under one heading
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct SpeechChapter {
+    /// Heading text; `None` for text before the first heading
+    pub title: Option<String>,
+    pub start_page: usize,
+    pub paragraphs: Vec<String>,
+}
+
+/// Chapters as plain text: titles and paragraphs separated by blank lines
+pub fn speech_text(chapters: &[SpeechChapter]) -> String {
+    chapters
+        .iter()
+        .flat_map(|c| c.title.iter().chain(&c.paragraphs))
+        .cloned()
+        .collect::<Vec<_>>()
+        .join("\n\n")
+}
+
+/// Chapters as one SSML document, each title followed by a pause;
+/// `language` is a BCP 47 tag such as "en-US"
+pub fn speech_ssml(chapters: &[SpeechChapter], language: Option<&str>) -> String {
+    let mut ssml = format!(
+        "<?xml version=\"1.0\" encoding=\"UTF-8\"?>\n<speak version=\"1.1\" xmlns=\"http://www.w3.org/2001/10/synthesis\" xml:lang=\"{}\">\n",
+        escape_xml(language.unwrap_or("en-US"))
+    );
+    for chapter in chapters {
+        if let Some(title) = &chapter.title {
+            ssml.push_str(&format!("<p><emphasis>{}</emphasis></p>\n<break time=\"1s\"/>\n", escape_xml(title)));
+        }
+        for paragraph in &chapter.paragraphs {
+            ssml.push_str(&format!("<p>{}</p>\n", escape_xml(paragraph)));
+        }
+    }
+    ssml.push_str("</speak>\n");
+    ssml
+}
+
+/// Block lines as one paragraph, rejoining words hyphenated across lines
+fn paragraph_text(block: &TextBlock) -> String {
+    let mut text = String::new();
+    for line in &block.lines {
+        let line = line.text.trim();
+        if line.is_empty() {
+            continue;
+        }
+        let continues_word = line.chars().next().is_some_and(char::is_lowercase);
+        if text.ends_with('-') && continues_word {
+            text.pop();
+        } else if !text.is_empty() {
+            text.push(' ');
+        }
+        text.push_str(line);
+    }
+    text
+}
+
+/// Heading lines as one title, each line read as a phrase
+fn heading_text(block: &TextBlock) -> String {
+    let lines: Vec<&str> = block.lines.iter().map(|l| l.text.trim()).filter(|l| !l.is_empty()).collect();
+    lines.join(". ")
+}
+
+fn block_font_size(block: &TextBlock) -> f32 {
+    block.lines.iter().flat_map(|l| &l.spans).map(|s| s.font_size).fold(0.0, f32::max)
+}
+
+/// Document text in reading order, split at headings
+///
+/// Only visible content text is read; running headers, footers and
+/// annotations would interrupt every page.
+pub fn speech_chapters(pages: &[PageData]) -> Vec<SpeechChapter> {
+    let content = PageTextOptions { roles: vec![LayerRole::Content], ..Default::default() };
+    let blocks: Vec<(usize, TextBlock)> = pages
+        .iter()
+        .flat_map(|page| page_blocks(page, &content).into_iter().map(move |block| (page.page_index, block)))
+        .collect();
+
+    // Body size: the size most of the text is set in, to the half point
+    let mut chars_by_size: BTreeMap<u32, usize> = BTreeMap::new();
+    for span in blocks.iter().flat_map(|(_, b)| &b.lines).flat_map(|l| &l.spans) {
+        *chars_by_size.entry((span.font_size * 2.0).round() as u32).or_default() += span.text.chars().count();
+    }
+    let body = chars_by_size.iter().max_by_key(|&(_, &chars)| chars).map_or(0.0, |(&size, _)| size as f32 / 2.0);
+
+    let mut chapters: Vec<SpeechChapter> = Vec::new();
+    for (page_index, block) in blocks {
+        let text = paragraph_text(&block);
+        if text.is_empty() {
+            continue;
+        }
+        let is_heading = body > 0.0
+            && block_font_size(&block) >= body * HEADING_RATIO
+            && block.lines.len() <= MAX_HEADING_LINES
+            && text.chars().count() <= MAX_HEADING_CHARS;
+        match chapters.last_mut() {
+            Some(chapter) if !is_heading => chapter.paragraphs.push(text),
+            // Consecutive headings (a chapter number, then its title) stay together
+            Some(SpeechChapter { title: Some(title), paragraphs, .. }) if paragraphs.is_empty() => {
+                title.push_str(". ");
+                title.push_str(&heading_text(&block));
+            }
+            _ => chapters.push(SpeechChapter {
+                title: is_heading.then(|| heading_text(&block)),
+                start_page: page_index,
+                paragraphs: if is_heading { Vec::new() } else { vec![text] },
+            }),
+        }
+    }
+    chapters
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::clipboard::new_layer;
+    use crate::models::{Bounds, LayerObject, LayerType};
+
+    fn text(id: &str, content: &str, y: f32, size: f32) -> LayerObject {
+        let mut layer = new_layer(id.to_string(), LayerType::Text, Bounds::new(72.0, y, 400.0, size * 1.2));
+        layer.content = Some(content.to_string());
+        layer.font_size = Some(size);
+        layer
+    }
+
+    fn page(page_index: usize, layers: Vec<LayerObject>) -> PageData {
+        PageData { page_index, width: 612.0, height: 792.0, dpi: None, layers, metadata: None, background: None }
+    }
+
+    #[test]
+    fn test_speech_chapters() {
+        let mut header = text("header", "Running Head", 20.0, 9.0);
+        header.role = LayerRole::Header;
+        let pages = vec![
+            page(
+                0,
+                vec![
+                    header,
+                    text("preface", "A short preface.", 72.0, 12.0),
+                    text("ch", "Chapter One", 200.0, 24.0),
+                    text("title", "The Beginning", 232.0, 24.0),
+                    text("sub", "In which it rains", 270.0, 18.0),
+                    text("p1", "It was a dark and storm-", 300.0, 12.0),
+                    text("p2", "y night & the rain fell.", 314.4, 12.0),
+                ],
+            ),
+            page(1, vec![text("ch2", "Chapter Two", 72.0, 24.0), text("p3", "Morning came.", 140.0, 12.0)]),
+        ];
+
+        let chapters = speech_chapters(&pages);
+        assert_eq!(chapters.len(), 3);
+        assert_eq!(chapters[0].title, None);
+        assert_eq!(chapters[0].paragraphs, ["A short preface."]);
+        assert_eq!(chapters[1].title.as_deref(), Some("Chapter One. The Beginning. In which it rains"));
+        assert_eq!(chapters[1].paragraphs, ["It was a dark and stormy night & the rain fell."]);
+        assert_eq!((chapters[2].start_page, speech_text(&chapters[2..]).as_str()), (1, "Chapter Two\n\nMorning came."));
+
+        let ssml = speech_ssml(&chapters[1..2], Some("en-GB"));
+        assert!(ssml.contains("xml:lang=\"en-GB\""));
+        assert!(ssml.contains("<p><emphasis>Chapter One. The Beginning. In which it rains</emphasis></p>"));
+        assert!(ssml.contains("<p>It was a dark and stormy night &amp; the rain fell.</p>"));
+    }
+}
@@ -0,0 +1,63 @@
+//! Test fixtures
+//!
+//! Builders for layers and pages in unit tests, shared with the crates that
+//! build on the document model through the `test-util` feature.
+
+use serde_json::{Map, Value};
+
+use crate::models::{LayerObject, PageData};
+
+/// A layer under construction, held as its JSON wire form so tests can set
+/// any field by its camelCase name
+#[derive(Debug, Clone)]
+pub struct LayerBuilder(Map<String, Value>);
+
+/// Start a visible, unlocked, fully opaque manual content layer at the
+/// origin, 100pt square
+pub fn layer(id: &str, layer_type: &str) -> LayerBuilder {
+    let Value::Object(fields) = serde_json::json!({
+        "id": id, "type": layer_type, "bounds": { "x": 0, "y": 0, "width": 100, "height": 100 },
+        "visible": true, "locked": false, "zIndex": 0, "opacity": 1.0,
+        "sourceType": "manual", "role": "content"
+    }) else {
+        unreachable!()
+    };
+    LayerBuilder(fields)
+}
+
+impl LayerBuilder {
+    pub fn bounds(self, x: f32, y: f32, width: f32, height: f32) -> Self {
+        self.with("bounds", serde_json::json!({ "x": x, "y": y, "width": width, "height": height }))
+    }
+
+    pub fn z(self, z_index: i32) -> Self {
+        self.with("zIndex", z_index)
+    }
+
+    /// Set one field by its wire name
+    pub fn with(mut self, key: &str, value: impl Into<Value>) -> Self {
+        self.0.insert(key.to_string(), value.into());
+        self
+    }
+
+    /// Set every field of a JSON object by its wire name
+    pub fn fields(mut self, fields: Value) -> Self {
+        let Value::Object(fields) = fields else { panic!("layer fields must be a JSON object") };
+        self.0.extend(fields);
+        self
+    }
+
+    /// The wire form, for tests that patch it further or feed it to a parser
+    pub fn json(self) -> Value {
+        Value::Object(self.0)
+    }
+
+    pub fn build(self) -> LayerObject {
+        serde_json::from_value(self.json()).expect("test layer should deserialize")
+    }
+}
+
+/// A US Letter page holding `layers`
+pub fn page(page_index: usize, layers: Vec<LayerObject>) -> PageData {
+    PageData { page_index, width: 612.0, height: 792.0, dpi: None, layers, metadata: None, background: None }
+}
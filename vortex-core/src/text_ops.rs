@@ -2,7 +2,7 @@
 //! Handles PDF text extraction with positioning
 
 use crate::graphics_state::GraphicsState;
-use crate::models::TransformMatrix;
+use crate::models::{BlendMode, TransformMatrix};
 
 /// Extracted text with exact position
 #[derive(Debug, Clone)]
@@ -16,6 +16,7 @@ pub struct ExtractedText {
     pub font_size: f32,
     pub color: [f32; 4],
     pub transform: TransformMatrix,
+    pub blend_mode: BlendMode,
 }
 
 /// Calculate text width based on character count and font metrics
@@ -75,7 +76,8 @@ pub fn create_text(text: &str, state: &GraphicsState, page_height: f32) -> Extra
         height: height.max(1.0),
         font_name,
         font_size: effective_font_size,
-        color: state.fill_color,
+        color: state.effective_fill(),
         transform: combined,
+        blend_mode: state.blend_mode,
     }
 }
@@ -0,0 +1,153 @@
+//! Text on a Path
+//! Places glyphs along a baseline path for curved text
+//!
+//! Coordinates are page coordinates (origin top-left, y down). Exporters
+//! measure glyph advances with their own font metrics and draw each glyph at
+//! the returned origin, rotated by the returned angle.
+
+use crate::models::{PathCommand, PathData, TextAlign};
+
+/// Line segments per cubic Bézier when flattening
+const CURVE_SEGMENTS: usize = 16;
+
+/// Origin and rotation of one glyph on the path
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct GlyphPlacement {
+    pub x: f32,
+    pub y: f32,
+    /// Clockwise radians from the x axis (y-down page space)
+    pub angle: f32,
+}
+
+/// Flatten a path into one polyline, joining subpaths end to end
+pub fn flatten(path: &PathData) -> Vec<(f32, f32)> {
+    let mut points: Vec<(f32, f32)> = Vec::new();
+    let mut start = (0.0, 0.0);
+    let mut current = (0.0, 0.0);
+    for cmd in &path.commands {
+        match *cmd {
+            PathCommand::MoveTo { x, y } => {
+                start = (x, y);
+                current = (x, y);
+                points.push(current);
+            }
+            PathCommand::LineTo { x, y } => {
+                current = (x, y);
+                points.push(current);
+            }
+            PathCommand::CurveTo { x1, y1, x2, y2, x, y } => {
+                let (x0, y0) = current;
+                for i in 1..=CURVE_SEGMENTS {
+                    let t = i as f32 / CURVE_SEGMENTS as f32;
+                    let u = 1.0 - t;
+                    let (a, b, c, d) = (u * u * u, 3.0 * u * u * t, 3.0 * u * t * t, t * t * t);
+                    points.push((a * x0 + b * x1 + c * x2 + d * x, a * y0 + b * y1 + c * y2 + d * y));
+                }
+                current = (x, y);
+            }
+            PathCommand::ClosePath => {
+                current = start;
+                points.push(current);
+            }
+        }
+    }
+    points.dedup();
+    points
+}
+
+/// Total length of a polyline
+pub fn length(points: &[(f32, f32)]) -> f32 {
+    points.windows(2).map(|w| (w[1].0 - w[0].0).hypot(w[1].1 - w[0].1)).sum()
+}
+
+/// Point and direction at `distance` along a polyline
+fn point_at(points: &[(f32, f32)], distance: f32) -> Option<GlyphPlacement> {
+    let mut remaining = distance;
+    for w in points.windows(2) {
+        let (dx, dy) = (w[1].0 - w[0].0, w[1].1 - w[0].1);
+        let segment = dx.hypot(dy);
+        if remaining <= segment {
+            let t = if segment > 0.0 { remaining / segment } else { 0.0 };
+            return Some(GlyphPlacement { x: w[0].0 + dx * t, y: w[0].1 + dy * t, angle: dy.atan2(dx) });
+        }
+        remaining -= segment;
+    }
+    None
+}
+
+/// Place glyphs with the given advances along a path
+///
+/// Each glyph is rotated to the path direction at its center. Glyphs whose
+/// center falls off either end of the path are `None` and are not drawn.
+pub fn place_glyphs(path: &PathData, advances: &[f32], start_offset: f32, align: TextAlign) -> Vec<Option<GlyphPlacement>> {
+    let points = flatten(path);
+    let total: f32 = advances.iter().sum();
+    let slack = length(&points) - start_offset - total;
+    let mut distance = start_offset
+        + match align {
+            TextAlign::Left => 0.0,
+            TextAlign::Center => slack / 2.0,
+            TextAlign::Right => slack,
+        };
+
+    advances
+        .iter()
+        .map(|&advance| {
+            let center = distance + advance / 2.0;
+            distance += advance;
+            if center < 0.0 {
+                return None;
+            }
+            let mid = point_at(&points, center)?;
+            // Back up half the advance along the tangent to the glyph origin
+            let (sin, cos) = mid.angle.sin_cos();
+            Some(GlyphPlacement { x: mid.x - cos * advance / 2.0, y: mid.y - sin * advance / 2.0, angle: mid.angle })
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn line(x0: f32, y0: f32, x1: f32, y1: f32) -> PathData {
+        PathData {
+            commands: vec![PathCommand::MoveTo { x: x0, y: y0 }, PathCommand::LineTo { x: x1, y: y1 }],
+            fill_rule: None,
+        }
+    }
+
+    #[test]
+    fn test_glyphs_follow_straight_path() {
+        let placed = place_glyphs(&line(10.0, 50.0, 110.0, 50.0), &[10.0, 10.0, 10.0], 5.0, TextAlign::Left);
+        let xs: Vec<f32> = placed.iter().map(|p| p.unwrap().x).collect();
+        assert_eq!(xs, vec![15.0, 25.0, 35.0]);
+        assert!(placed.iter().all(|p| p.unwrap().angle == 0.0 && p.unwrap().y == 50.0));
+
+        // Centered on a vertical path running down the page
+        let placed = place_glyphs(&line(0.0, 0.0, 0.0, 100.0), &[20.0, 20.0], 0.0, TextAlign::Center);
+        let first = placed[0].unwrap();
+        assert!((first.y - 30.0).abs() < 1e-4 && first.x.abs() < 1e-4);
+        assert!((first.angle - std::f32::consts::FRAC_PI_2).abs() < 1e-4);
+
+        // Glyphs whose center falls past the end are dropped
+        let placed = place_glyphs(&line(0.0, 0.0, 14.0, 0.0), &[10.0, 10.0], 0.0, TextAlign::Left);
+        assert!(placed[0].is_some() && placed[1].is_none());
+    }
+
+    #[test]
+    fn test_flatten_curve_length() {
+        // Quarter circle approximation of radius 100
+        let k = 55.228;
+        let arc = PathData {
+            commands: vec![
+                PathCommand::MoveTo { x: 100.0, y: 0.0 },
+                PathCommand::CurveTo { x1: 100.0, y1: k, x2: k, y2: 100.0, x: 0.0, y: 100.0 },
+            ],
+            fill_rule: None,
+        };
+        let points = flatten(&arc);
+        assert_eq!(points.len(), CURVE_SEGMENTS + 1);
+        assert!((length(&points) - std::f32::consts::FRAC_PI_2 * 100.0).abs() < 0.5);
+    }
+}
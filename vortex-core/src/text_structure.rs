@@ -0,0 +1,596 @@
+//! Structured text extraction
+//!
+//! Groups positioned text spans into lines and blocks, giving the
+//! pages → blocks → lines → spans hierarchy that exists before spans become
+//! layers. Spans come from `content_parser` on desktop and from pdf.js in
+//! the browser, so both builds share the grouping rules.
+
+use crate::graphics_state::normalize_font_name;
+use crate::models::{Bounds, LayerObject, LayerRole, LayerType, PageData};
+use serde::{Deserialize, Serialize};
+
+/// Spans on one line must overlap vertically by this fraction of the
+/// shorter span's height
+const LINE_OVERLAP: f32 = 0.5;
+/// Horizontal gap (in font sizes) that splits a line into columns
+const COLUMN_GAP: f32 = 2.0;
+/// Gap (in font sizes) above which a space is inserted between spans
+const WORD_GAP: f32 = 0.15;
+/// Vertical gap (in line heights) that still continues a block
+const BLOCK_GAP: f32 = 0.8;
+/// Font size ratio above which lines belong to different blocks
+const FONT_SIZE_RATIO: f32 = 1.3;
+
+/// A run of text with one font and color
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct TextSpan {
+    pub text: String,
+    pub bounds: Bounds,
+    /// Font name as written in the PDF
+    #[serde(default)]
+    pub font_name: String,
+    /// Web font family; derived from `font_name` when empty
+    #[serde(default)]
+    pub font_family: String,
+    pub font_size: f32,
+    #[serde(default = "default_font_weight")]
+    pub font_weight: u16,
+    #[serde(default)]
+    pub italic: bool,
+    /// Hex fill color
+    #[serde(default = "default_color")]
+    pub color: String,
+}
+
+fn default_font_weight() -> u16 {
+    400
+}
+
+fn default_color() -> String {
+    "#000000".to_string()
+}
+
+impl TextSpan {
+    /// Span with family, weight and style derived from the PDF font name
+    pub fn new(text: String, bounds: Bounds, font_name: String, font_size: f32, color: String) -> Self {
+        let mut span = Self {
+            text,
+            bounds,
+            font_name,
+            font_family: String::new(),
+            font_size,
+            font_weight: default_font_weight(),
+            italic: false,
+            color,
+        };
+        span.resolve_font();
+        span
+    }
+
+    /// Fill in family, weight and style from the font name if not set
+    fn resolve_font(&mut self) {
+        if !self.font_family.is_empty() || self.font_name.is_empty() {
+            return;
+        }
+        let lower = self.font_name.to_lowercase();
+        self.font_family = normalize_font_name(&self.font_name);
+        if lower.contains("bold") {
+            self.font_weight = 700;
+        }
+        self.italic |= lower.contains("italic") || lower.contains("oblique");
+    }
+
+    #[inline]
+    fn right(&self) -> f32 {
+        self.bounds.x + self.bounds.width
+    }
+}
+
+/// Spans sharing a baseline, left to right
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct TextLine {
+    pub bounds: Bounds,
+    /// Span text joined with inferred word spaces
+    pub text: String,
+    pub spans: Vec<TextSpan>,
+}
+
+/// Consecutive lines of similar size and alignment (roughly a paragraph)
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct TextBlock {
+    pub bounds: Bounds,
+    pub lines: Vec<TextLine>,
+}
+
+impl TextBlock {
+    /// Lines joined with newlines
+    pub fn text(&self) -> String {
+        self.lines.iter().map(|l| l.text.as_str()).collect::<Vec<_>>().join("\n")
+    }
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct StructuredPage {
+    pub page_index: usize,
+    pub width: f32,
+    pub height: f32,
+    /// Blocks in reading order (top to bottom, then left to right)
+    pub blocks: Vec<TextBlock>,
+}
+
+/// Text hierarchy of a whole document
+#[derive(Debug, Clone, Default, Serialize, Deserialize, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct StructuredText {
+    pub pages: Vec<StructuredPage>,
+}
+
+impl StructuredText {
+    /// Blocks separated by blank lines, pages by form feeds
+    pub fn plain_text(&self) -> String {
+        self.pages
+            .iter()
+            .map(|page| page.blocks.iter().map(TextBlock::text).collect::<Vec<_>>().join("\n\n"))
+            .collect::<Vec<_>>()
+            .join("\n\u{000C}\n")
+    }
+}
+
+/// Page of unstructured spans, as sent by the browser build
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct SpanPage {
+    pub page_index: usize,
+    pub width: f32,
+    pub height: f32,
+    pub spans: Vec<TextSpan>,
+}
+
+fn union(a: &Bounds, b: &Bounds) -> Bounds {
+    let (x, y) = (a.x.min(b.x), a.y.min(b.y));
+    let right = (a.x + a.width).max(b.x + b.width);
+    let bottom = (a.y + a.height).max(b.y + b.height);
+    Bounds::new(x, y, right - x, bottom - y)
+}
+
+#[inline]
+fn vertical_overlap(a: &Bounds, b: &Bounds) -> f32 {
+    (a.y + a.height).min(b.y + b.height) - a.y.max(b.y)
+}
+
+fn make_line(spans: Vec<TextSpan>) -> TextLine {
+    let mut bounds = spans[0].bounds;
+    let mut text = String::new();
+    for (i, span) in spans.iter().enumerate() {
+        bounds = union(&bounds, &span.bounds);
+        if i > 0 {
+            let prev = &spans[i - 1];
+            let gap = span.bounds.x - prev.right();
+            let spaced = prev.text.ends_with(char::is_whitespace) || span.text.starts_with(char::is_whitespace);
+            if !spaced && gap > WORD_GAP * span.font_size.min(prev.font_size) {
+                text.push(' ');
+            }
+        }
+        text.push_str(&span.text);
+    }
+    TextLine { bounds, text: text.trim().to_string(), spans }
+}
+
+/// Group spans into lines: vertical overlap joins, wide horizontal gaps split
+fn group_lines(mut spans: Vec<TextSpan>) -> Vec<TextLine> {
+    spans.sort_by(|a, b| a.bounds.y.total_cmp(&b.bounds.y).then(a.bounds.x.total_cmp(&b.bounds.x)));
+
+    let mut rows: Vec<(Bounds, Vec<TextSpan>)> = Vec::new();
+    for span in spans {
+        let joins = rows.last().is_some_and(|(row, _)| {
+            vertical_overlap(row, &span.bounds) > LINE_OVERLAP * row.height.min(span.bounds.height)
+        });
+        match rows.last_mut() {
+            Some((row, members)) if joins => {
+                *row = union(row, &span.bounds);
+                members.push(span);
+            }
+            _ => rows.push((span.bounds, vec![span])),
+        }
+    }
+
+    let mut lines = Vec::new();
+    for (_, mut members) in rows {
+        members.sort_by(|a, b| a.bounds.x.total_cmp(&b.bounds.x));
+        let mut current: Vec<TextSpan> = Vec::new();
+        for span in members {
+            let split = current.last().is_some_and(|prev| {
+                span.bounds.x - prev.right() > COLUMN_GAP * span.font_size.max(prev.font_size)
+            });
+            if split {
+                lines.push(make_line(std::mem::take(&mut current)));
+            }
+            current.push(span);
+        }
+        if !current.is_empty() {
+            lines.push(make_line(current));
+        }
+    }
+    lines
+}
+
+#[inline]
+fn dominant_size(line: &TextLine) -> f32 {
+    line.spans.iter().map(|s| s.font_size).fold(0.0, f32::max)
+}
+
+/// Whether `line` continues `block` (directly below, overlapping, similar size)
+fn continues_block(block: &TextBlock, line: &TextLine) -> bool {
+    let last = block.lines.last().expect("blocks are never empty");
+    let gap = line.bounds.y - (last.bounds.y + last.bounds.height);
+    let horizontal = (last.bounds.x + last.bounds.width).min(line.bounds.x + line.bounds.width)
+        - last.bounds.x.max(line.bounds.x);
+    let (a, b) = (dominant_size(last), dominant_size(line));
+    let ratio = a.max(b) / a.min(b).max(0.1);
+    gap >= -LINE_OVERLAP * last.bounds.height
+        && gap <= BLOCK_GAP * last.bounds.height
+        && horizontal > 0.0
+        && ratio <= FONT_SIZE_RATIO
+}
+
+/// Build the block → line → span hierarchy for one page
+pub fn structure_page(page_index: usize, width: f32, height: f32, spans: Vec<TextSpan>) -> StructuredPage {
+    let spans: Vec<TextSpan> = spans
+        .into_iter()
+        .filter(|s| !s.text.trim().is_empty())
+        .map(|mut s| {
+            s.resolve_font();
+            s
+        })
+        .collect();
+
+    let mut blocks: Vec<TextBlock> = Vec::new();
+    for line in group_lines(spans) {
+        // Latest block first: it is the nearest one above
+        match blocks.iter_mut().rev().find(|b| continues_block(b, &line)) {
+            Some(block) => {
+                block.bounds = union(&block.bounds, &line.bounds);
+                block.lines.push(line);
+            }
+            None => blocks.push(TextBlock { bounds: line.bounds, lines: vec![line] }),
+        }
+    }
+    blocks.sort_by(|a, b| a.bounds.y.total_cmp(&b.bounds.y).then(a.bounds.x.total_cmp(&b.bounds.x)));
+
+    StructuredPage { page_index, width, height, blocks }
+}
+
+/// Structure a document given as pages of spans
+pub fn structure_pages(pages: Vec<SpanPage>) -> StructuredText {
+    StructuredText {
+        pages: pages
+            .into_iter()
+            .map(|p| structure_page(p.page_index, p.width, p.height, p.spans))
+            .collect(),
+    }
+}
+
+/// How far text layers are merged on import
+#[derive(Debug, Clone, Copy, Default, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub enum MergeLevel {
+    /// One layer per extracted text run
+    #[default]
+    Spans,
+    /// One layer per line
+    Lines,
+    /// One layer per block (roughly a paragraph)
+    Blocks,
+}
+
+fn layer_span(layer: &LayerObject) -> TextSpan {
+    TextSpan {
+        text: layer.content.clone().unwrap_or_default(),
+        bounds: layer.bounds,
+        font_name: String::new(),
+        font_family: layer.font_family.clone().unwrap_or_default(),
+        font_size: layer.font_size.unwrap_or(12.0),
+        font_weight: layer.font_weight.unwrap_or(400),
+        italic: layer.font_style.as_deref() == Some("italic"),
+        color: layer.color.clone().unwrap_or_else(default_color),
+    }
+}
+
+/// Text layers grouped into blocks in reading order
+pub fn layers_blocks(layers: &[LayerObject]) -> Vec<TextBlock> {
+    let spans = layers
+        .iter()
+        .filter(|l| l.layer_type == LayerType::Text && l.content.is_some())
+        .map(layer_span)
+        .collect();
+    structure_page(0, 0.0, 0.0, spans).blocks
+}
+
+/// Text of a set of layers in reading order, blocks separated by blank lines
+pub fn layers_text(layers: &[LayerObject]) -> String {
+    let blocks: Vec<String> = layers_blocks(layers).iter().map(TextBlock::text).collect();
+    blocks.join("\n\n")
+}
+
+/// Which of a page's text layers `page_text` reads
+#[derive(Debug, Clone, Default, Serialize, Deserialize, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct PageTextOptions {
+    /// Also return style runs
+    #[serde(default)]
+    pub styles: bool,
+    /// Only layers with these roles; all when empty
+    #[serde(default)]
+    pub roles: Vec<LayerRole>,
+    /// Only these layers, e.g. the selection being copied
+    #[serde(default)]
+    pub layer_ids: Option<Vec<String>>,
+}
+
+/// A stretch of page text set in one style, as offsets into the text in
+/// UTF-16 code units (how JavaScript indexes strings)
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct StyleRun {
+    pub start: usize,
+    pub end: usize,
+    pub font_family: String,
+    pub font_size: f32,
+    pub font_weight: u16,
+    pub italic: bool,
+    pub color: String,
+}
+
+impl StyleRun {
+    fn matches(&self, span: &TextSpan) -> bool {
+        self.font_family == span.font_family
+            && self.font_size == span.font_size
+            && self.font_weight == span.font_weight
+            && self.italic == span.italic
+            && self.color == span.color
+    }
+}
+
+/// A page's text in reading order
+#[derive(Debug, Clone, Default, Serialize, Deserialize, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct PageText {
+    pub page_index: usize,
+    /// Lines joined with newlines, blocks with blank lines
+    pub text: String,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub runs: Vec<StyleRun>,
+}
+
+/// Visible text layers of a page picked by `options`, grouped into blocks
+/// in reading order
+pub fn page_blocks(page: &PageData, options: &PageTextOptions) -> Vec<TextBlock> {
+    let layers: Vec<LayerObject> = page
+        .layers
+        .iter()
+        .filter(|l| l.visible)
+        .filter(|l| options.roles.is_empty() || options.roles.contains(&l.role))
+        .filter(|l| options.layer_ids.as_ref().map_or(true, |ids| ids.contains(&l.id)))
+        .cloned()
+        .collect();
+    layers_blocks(&layers)
+}
+
+#[inline]
+fn utf16_len(text: &str) -> usize {
+    text.encode_utf16().count()
+}
+
+/// Style runs over the text of `blocks` as `TextBlock::text` and blank
+/// lines join it; adjacent spans of one style share a run
+fn style_runs(blocks: &[TextBlock]) -> Vec<StyleRun> {
+    let mut runs: Vec<StyleRun> = Vec::new();
+    let mut offset = 0;
+    for (b, block) in blocks.iter().enumerate() {
+        if b > 0 {
+            offset += 2;
+        }
+        for (l, line) in block.lines.iter().enumerate() {
+            if l > 0 {
+                offset += 1;
+            }
+            let mut cursor = 0;
+            for span in &line.spans {
+                let piece = span.text.trim();
+                let Some(found) = line.text[cursor..].find(piece) else {
+                    continue;
+                };
+                let start = offset + utf16_len(&line.text[..cursor + found]);
+                cursor += found + piece.len();
+                let end = offset + utf16_len(&line.text[..cursor]);
+                match runs.last_mut() {
+                    Some(run) if run.matches(span) => run.end = end,
+                    _ => runs.push(StyleRun {
+                        start,
+                        end,
+                        font_family: span.font_family.clone(),
+                        font_size: span.font_size,
+                        font_weight: span.font_weight,
+                        italic: span.italic,
+                        color: span.color.clone(),
+                    }),
+                }
+            }
+            offset += utf16_len(&line.text);
+        }
+    }
+    runs
+}
+
+/// A page's text in reading order, with style runs if asked for
+pub fn page_text(page: &PageData, options: &PageTextOptions) -> PageText {
+    let blocks = page_blocks(page, options);
+    let text = blocks.iter().map(TextBlock::text).collect::<Vec<_>>().join("\n\n");
+    let runs = if options.styles { style_runs(&blocks) } else { Vec::new() };
+    PageText { page_index: page.page_index, text, runs }
+}
+
+/// Merge a page's text layers into lines or blocks
+///
+/// Each merged layer takes its style, id and stacking from its first run;
+/// other layers pass through unchanged.
+pub fn merge_text_layers(layers: Vec<LayerObject>, level: MergeLevel) -> Vec<LayerObject> {
+    if level == MergeLevel::Spans {
+        return layers;
+    }
+    let (texts, mut merged): (Vec<LayerObject>, Vec<LayerObject>) =
+        layers.into_iter().partition(|l| l.layer_type == LayerType::Text && l.content.is_some());
+
+    let spans: Vec<TextSpan> = texts.iter().map(layer_span).collect();
+    // The layer a span came from
+    let source = |span: &TextSpan| {
+        texts.iter().find(|l| l.bounds == span.bounds && l.content.as_deref() == Some(span.text.as_str()))
+    };
+    let mut emit = |first: &TextSpan, bounds: Bounds, text: String| {
+        if let Some(layer) = source(first) {
+            merged.push(LayerObject { bounds, content: Some(text), ..layer.clone() });
+        }
+    };
+
+    for block in structure_page(0, 0.0, 0.0, spans).blocks {
+        match level {
+            MergeLevel::Blocks => emit(&block.lines[0].spans[0], block.bounds, block.text()),
+            _ => {
+                for line in block.lines {
+                    emit(&line.spans[0], line.bounds, line.text);
+                }
+            }
+        }
+    }
+    merged.sort_by_key(|l| l.z_index);
+    merged
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_util;
+
+    fn span(text: &str, x: f32, y: f32, size: f32) -> TextSpan {
+        let width = text.chars().count() as f32 * size * 0.5;
+        let bounds = Bounds::new(x, y, width, size * 1.15);
+        TextSpan::new(text.to_string(), bounds, "Helvetica-Bold".to_string(), size, "#000000".to_string())
+    }
+
+    #[test]
+    fn test_lines_and_words() {
+        let spans = vec![span("World", 106.0, 100.5, 12.0), span("Hello", 72.0, 100.0, 12.0)];
+        let page = structure_page(0, 612.0, 792.0, spans);
+        assert_eq!(page.blocks.len(), 1);
+        let line = &page.blocks[0].lines[0];
+        assert_eq!(line.text, "Hello World");
+        assert_eq!(line.spans[0].font_family, "Arial");
+        assert_eq!(line.spans[0].font_weight, 700);
+    }
+
+    #[test]
+    fn test_blocks_split_on_gap_size_and_column() {
+        let spans = vec![
+            span("Title", 72.0, 60.0, 24.0),
+            span("First line", 72.0, 100.0, 12.0),
+            span("second line", 72.0, 114.0, 12.0),
+            span("Right column", 340.0, 100.0, 12.0),
+            span("Far below", 72.0, 300.0, 12.0),
+        ];
+        let page = structure_page(0, 612.0, 792.0, spans);
+        let texts: Vec<String> = page.blocks.iter().map(TextBlock::text).collect();
+        assert_eq!(texts, vec!["Title", "First line\nsecond line", "Right column", "Far below"]);
+
+        let doc = StructuredText { pages: vec![page] };
+        assert!(doc.plain_text().starts_with("Title\n\nFirst line\nsecond line"));
+    }
+
+    #[test]
+    fn test_merge_text_layers() {
+        let text = |id: &str, content: &str, x: f32, y: f32| {
+            test_util::layer(id, "text")
+                .bounds(x, y, content.len() as f32 * 6.0, 14.0)
+                .fields(serde_json::json!({
+                    "content": content, "fontFamily": "Arial", "fontSize": 12, "sourceType": "extracted"
+                }))
+                .build()
+        };
+        let layers = vec![
+            text("a", "Hello", 72.0, 100.0),
+            text("b", "world", 108.0, 100.0),
+            text("c", "again", 72.0, 114.0),
+        ];
+
+        assert_eq!(merge_text_layers(layers.clone(), MergeLevel::Spans).len(), 3);
+        let lines = merge_text_layers(layers.clone(), MergeLevel::Lines);
+        let contents: Vec<_> = lines.iter().map(|l| (l.id.as_str(), l.content.as_deref().unwrap())).collect();
+        assert_eq!(contents, vec![("a", "Hello world"), ("c", "again")]);
+        let mut page = layers.clone();
+        page.push(text("d", "Title", 72.0, 40.0));
+        assert_eq!(layers_text(&page), "Title\n\nHello world\nagain");
+        let blocks = merge_text_layers(layers, MergeLevel::Blocks);
+        assert_eq!(blocks.len(), 1);
+        assert_eq!(blocks[0].content.as_deref(), Some("Hello world\nagain"));
+        assert_eq!(blocks[0].bounds, Bounds::new(72.0, 100.0, 66.0, 28.0));
+    }
+
+    #[test]
+    fn test_page_text() {
+        let text = |id: &str, content: &str, x: f32, y: f32, weight: u16| -> LayerObject {
+            let mut layer = crate::clipboard::new_layer(
+                id.to_string(),
+                LayerType::Text,
+                Bounds::new(x, y, content.len() as f32 * 6.0, 14.0),
+            );
+            (layer.content, layer.font_size, layer.font_weight) = (Some(content.to_string()), Some(12.0), Some(weight));
+            layer
+        };
+        let mut footer = text("f", "Page 1", 72.0, 700.0, 400);
+        footer.role = LayerRole::Footer;
+        let page = PageData {
+            page_index: 3,
+            width: 612.0,
+            height: 792.0,
+            dpi: None,
+            layers: vec![
+                text("b", "Café au", 72.0, 114.0, 400),
+                footer,
+                text("c", "lait", 126.0, 114.0, 400),
+                text("a", "Menu", 72.0, 40.0, 700),
+            ],
+            metadata: None,
+            background: None,
+        };
+
+        let plain = page_text(&page, &PageTextOptions::default());
+        assert_eq!(plain.text, "Menu\n\nCafé au lait\n\nPage 1");
+        assert!(plain.runs.is_empty());
+
+        let options = PageTextOptions { styles: true, roles: vec![LayerRole::Content], ..Default::default() };
+        let styled = page_text(&page, &options);
+        assert_eq!(styled.text, "Menu\n\nCafé au lait");
+        let runs: Vec<_> = styled.runs.iter().map(|r| (r.start, r.end, r.font_weight)).collect();
+        assert_eq!(runs, [(0, 4, 700), (6, 18, 400)]);
+
+        let selected = PageTextOptions { layer_ids: Some(vec!["c".to_string()]), ..Default::default() };
+        assert_eq!(page_text(&page, &selected).text, "lait");
+    }
+
+    #[test]
+    fn test_browser_spans_deserialize_with_defaults() {
+        let json = r#"[{"pageIndex":0,"width":100,"height":100,"spans":[
+            {"text":"Hi","bounds":{"x":0,"y":0,"width":10,"height":12},"fontSize":10,"fontName":"Times-Italic"},
+            {"text":"  ","bounds":{"x":20,"y":0,"width":5,"height":12},"fontSize":10}
+        ]}]"#;
+        let doc = structure_pages(serde_json::from_str(json).unwrap());
+        let span = &doc.pages[0].blocks[0].lines[0].spans;
+        assert_eq!(span.len(), 1);
+        assert_eq!(span[0].font_family, "Times New Roman");
+        assert!(span[0].italic);
+        assert_eq!(span[0].color, "#000000");
+    }
+}
@@ -0,0 +1,370 @@
+//! Text Wrap
+//! Lays out paragraph text in a frame around the wrap contours of other layers
+//!
+//! Image, shape and vector layers with a `text_wrap` become exclusions: each
+//! line band of a text frame is split into the free spans left between them,
+//! and words are filled into those spans left to right.
+
+use crate::models::{Bounds, DropCap, LayerObject, PageData, TextAlign, WrapContour};
+use crate::text_path;
+
+/// Spans narrower than this many line heights are left empty
+const MIN_SPAN_LINES: f32 = 2.0;
+
+/// Average Helvetica advance width, in ems, for characters outside ASCII
+pub const HELVETICA_AVG_WIDTH: f32 = 0.5;
+/// Helvetica capital height, in ems
+pub const HELVETICA_CAP_HEIGHT: f32 = 0.718;
+
+/// Helvetica advance widths for printable ASCII, in 1/1000 em (from the AFM)
+const HELVETICA_WIDTHS: [u16; 95] = [
+    278, 278, 355, 556, 556, 889, 667, 191, 333, 333, 389, 584, 278, 333, 278, 278, // space../
+    556, 556, 556, 556, 556, 556, 556, 556, 556, 556, 278, 278, 584, 584, 584, 556, // 0..?
+    1015, 667, 667, 722, 722, 667, 611, 778, 722, 278, 500, 667, 556, 833, 722, 778, // @..O
+    667, 778, 722, 667, 611, 722, 667, 944, 667, 667, 611, 278, 278, 278, 469, 556, // P.._
+    333, 556, 556, 500, 556, 556, 278, 556, 556, 222, 222, 500, 222, 833, 556, 556, // `..o
+    556, 556, 333, 500, 278, 556, 500, 722, 500, 500, 500, 334, 260, 334, 584, // p..~
+];
+
+/// Helvetica advance width of `c`, in ems
+pub fn helvetica_width(c: char) -> f32 {
+    match c as u32 {
+        code @ 32..=126 => HELVETICA_WIDTHS[(code - 32) as usize] as f32 / 1000.0,
+        _ => HELVETICA_AVG_WIDTH,
+    }
+}
+
+/// A region text flows around: a convex polygon grown by an offset
+#[derive(Debug, Clone, PartialEq)]
+pub struct Exclusion {
+    /// Convex polygon in page coordinates
+    pub hull: Vec<(f32, f32)>,
+    pub offset: f32,
+}
+
+impl Exclusion {
+    /// Exclusion for a layer's wrap settings, if it has any
+    pub fn from_layer(layer: &LayerObject) -> Option<Self> {
+        let wrap = layer.text_wrap.as_ref()?;
+        let b = layer.bounds;
+        let rect = vec![(b.x, b.y), (b.x + b.width, b.y), (b.x + b.width, b.y + b.height), (b.x, b.y + b.height)];
+        let hull = match wrap.contour {
+            WrapContour::None => return None,
+            WrapContour::BoundingBox => rect,
+            WrapContour::ConvexHull => match &layer.path_data {
+                Some(path) => convex_hull(text_path::flatten(path)),
+                None => rect,
+            },
+        };
+        (hull.len() >= 3).then_some(Self { hull, offset: wrap.offset.max(0.0) })
+    }
+
+    /// Horizontal extent of the exclusion across the band `top..bottom`
+    pub fn span(&self, top: f32, bottom: f32) -> Option<(f32, f32)> {
+        let (top, bottom) = (top - self.offset, bottom + self.offset);
+        let mut xs: Vec<f32> = Vec::new();
+        let n = self.hull.len();
+        for i in 0..n {
+            let (a, b) = (self.hull[i], self.hull[(i + 1) % n]);
+            if a.1 >= top && a.1 <= bottom {
+                xs.push(a.0);
+            }
+            // Where the edge crosses the band's top and bottom
+            for y in [top, bottom] {
+                if (a.1 - y) * (b.1 - y) < 0.0 {
+                    xs.push(a.0 + (b.0 - a.0) * (y - a.1) / (b.1 - a.1));
+                }
+            }
+        }
+        let min = xs.iter().copied().reduce(f32::min)?;
+        let max = xs.iter().copied().reduce(f32::max)?;
+        Some((min - self.offset, max + self.offset))
+    }
+}
+
+/// Convex hull (monotone chain), counter-clockwise in y-down space
+pub fn convex_hull(mut points: Vec<(f32, f32)>) -> Vec<(f32, f32)> {
+    points.sort_by(|a, b| a.0.total_cmp(&b.0).then(a.1.total_cmp(&b.1)));
+    points.dedup();
+    if points.len() < 3 {
+        return points;
+    }
+    let cross = |o: (f32, f32), a: (f32, f32), b: (f32, f32)| (a.0 - o.0) * (b.1 - o.1) - (a.1 - o.1) * (b.0 - o.0);
+    let mut hull: Vec<(f32, f32)> = Vec::with_capacity(points.len() * 2);
+    for pass in [points.clone(), points.into_iter().rev().collect()] {
+        let floor = hull.len();
+        for p in pass {
+            while hull.len() >= floor + 2 && cross(hull[hull.len() - 2], hull[hull.len() - 1], p) <= 0.0 {
+                hull.pop();
+            }
+            hull.push(p);
+        }
+        // The last point of each chain starts the other
+        hull.pop();
+    }
+    hull
+}
+
+/// Exclusions on `page` that affect the text frame `frame`
+pub fn page_exclusions(page: &PageData, frame: &LayerObject) -> Vec<Exclusion> {
+    page.layers
+        .iter()
+        .filter(|l| l.visible && l.id != frame.id)
+        .filter_map(Exclusion::from_layer)
+        .filter(|e| e.span(frame.bounds.y, frame.bounds.y + frame.bounds.height).is_some())
+        .collect()
+}
+
+/// Free horizontal spans of `left..right` across a line band
+pub fn free_spans(left: f32, right: f32, top: f32, bottom: f32, exclusions: &[Exclusion]) -> Vec<(f32, f32)> {
+    let mut spans = vec![(left, right)];
+    for (x0, x1) in exclusions.iter().filter_map(|e| e.span(top, bottom)) {
+        spans = spans
+            .into_iter()
+            .flat_map(|(a, b)| [(a, b.min(x0)), (a.max(x1), b)])
+            .filter(|(a, b)| b > a)
+            .collect();
+    }
+    spans
+}
+
+/// One laid-out line
+#[derive(Debug, Clone, PartialEq)]
+pub struct LineBox {
+    pub text: String,
+    pub x: f32,
+    /// Top of the line band
+    pub y: f32,
+    pub width: f32,
+}
+
+/// Lines of a frame, and whether text was left over
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct FrameLayout {
+    pub lines: Vec<LineBox>,
+    pub overset: bool,
+}
+
+/// Lay out `text` in `frame` around `exclusions`
+///
+/// `line_height` is in points; `measure` returns the advance width of a
+/// string. Each newline starts a paragraph on a fresh line. A word too wide
+/// for a span moves to the next one, unless nothing narrows the line.
+pub fn layout_frame(
+    text: &str,
+    frame: Bounds,
+    line_height: f32,
+    align: TextAlign,
+    exclusions: &[Exclusion],
+    measure: impl Fn(&str) -> f32,
+) -> FrameLayout {
+    let paragraphs: Vec<Vec<&str>> = text.split('\n').map(|p| p.split_whitespace().collect()).collect();
+    let (mut para, mut word) = (0, 0);
+    let mut lines = Vec::new();
+    let mut top = frame.y;
+
+    while para < paragraphs.len() && line_height > 0.0 && top + line_height <= frame.y + frame.height + 0.01 {
+        let spans = free_spans(frame.x, frame.x + frame.width, top, top + line_height, exclusions);
+        let full_width = spans.len() == 1 && spans[0].1 - spans[0].0 >= frame.width - 0.01;
+        for (x0, x1) in spans {
+            let width = x1 - x0;
+            if !full_width && width < line_height * MIN_SPAN_LINES {
+                continue;
+            }
+            let words = &paragraphs[para];
+            let mut line = String::new();
+            while word < words.len() {
+                let candidate = if line.is_empty() { words[word].to_string() } else { format!("{} {}", line, words[word]) };
+                if measure(&candidate) > width && !(line.is_empty() && full_width) {
+                    break;
+                }
+                line = candidate;
+                word += 1;
+            }
+            if !line.is_empty() {
+                let used = measure(&line);
+                let x = x0 + match align {
+                    TextAlign::Left => 0.0,
+                    TextAlign::Center => (width - used) / 2.0,
+                    TextAlign::Right => width - used,
+                };
+                lines.push(LineBox { text: line, x, y: top, width: used });
+            }
+            if word >= words.len() {
+                break;
+            }
+        }
+        if word >= paragraphs[para].len() {
+            para += 1;
+            word = 0;
+        }
+        top += line_height;
+    }
+
+    FrameLayout { lines, overset: para < paragraphs.len() }
+}
+
+/// A drop cap placed in its frame
+#[derive(Debug, Clone, PartialEq)]
+pub struct DropCapBox {
+    /// The letters set large
+    pub text: String,
+    pub font_size: f32,
+    /// Left end of the cap's baseline
+    pub x: f32,
+    pub baseline: f32,
+    pub width: f32,
+    /// Room the cap takes from the lines beside it
+    pub exclusion: Exclusion,
+}
+
+/// Split the drop cap off `text` and place it at the top-left of `frame`,
+/// returning it with the text left to flow beside and below it
+///
+/// The cap is scaled so its capitals reach from the first line's cap
+/// height down to the baseline of line `cap.lines`. Punctuation before the
+/// letters, such as an opening quote, goes with them. `measure` returns
+/// the advance width of a string at a font size. `None` when the cap drops
+/// fewer than 2 lines or the text does not start with a letter.
+pub fn place_drop_cap<'a>(
+    text: &'a str,
+    frame: Bounds,
+    font_size: f32,
+    line_height: f32,
+    cap: &DropCap,
+    measure: impl Fn(&str, f32) -> f32,
+) -> Option<(DropCapBox, &'a str)> {
+    if cap.lines < 2 || font_size <= 0.0 {
+        return None;
+    }
+    let text = text.trim_start();
+    let mut letters = 0;
+    let mut end = 0;
+    for (i, c) in text.char_indices() {
+        if c.is_whitespace() || letters >= cap.letters.max(1) {
+            break;
+        }
+        letters += u32::from(c.is_alphanumeric());
+        end = i + c.len_utf8();
+    }
+    if letters == 0 {
+        return None;
+    }
+    let cap_text = text[..end].to_string();
+    let cap_size = ((cap.lines - 1) as f32 * line_height + HELVETICA_CAP_HEIGHT * font_size) / HELVETICA_CAP_HEIGHT;
+    let baseline = frame.y + font_size + (cap.lines - 1) as f32 * line_height;
+    let width = measure(&cap_text, cap_size);
+    let right = frame.x + width + cap.gap.max(0.0);
+    let hull = vec![(frame.x, frame.y), (right, frame.y), (right, baseline), (frame.x, baseline)];
+    let placed = DropCapBox {
+        text: cap_text,
+        font_size: cap_size,
+        x: frame.x,
+        baseline,
+        width,
+        exclusion: Exclusion { hull, offset: 0.0 },
+    };
+    Some((placed, &text[end..]))
+}
+
+/// Lay out a text layer that has a drop cap, in Helvetica metrics: the cap,
+/// then the rest of `content` flowed around it and `exclusions`
+pub fn layout_drop_cap(
+    layer: &LayerObject,
+    content: &str,
+    exclusions: &[Exclusion],
+) -> Option<(DropCapBox, FrameLayout)> {
+    let font_size = layer.font_size.unwrap_or(12.0);
+    let line_height = font_size * layer.line_height.unwrap_or(1.2);
+    let spacing = layer.letter_spacing.unwrap_or(0.0);
+    let measure = |s: &str, size: f32| s.chars().map(|c| helvetica_width(c) * size + spacing).sum::<f32>();
+    let (cap, rest) = place_drop_cap(content, layer.bounds, font_size, line_height, layer.drop_cap.as_ref()?, measure)?;
+    let mut exclusions = exclusions.to_vec();
+    exclusions.push(cap.exclusion.clone());
+    let align = layer.text_align.unwrap_or_default();
+    let layout = layout_frame(rest, layer.bounds, line_height, align, &exclusions, |s| measure(s, font_size));
+    Some((cap, layout))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::{TextWrap, WrapContour};
+    use crate::test_util::layer;
+
+    #[test]
+    fn test_convex_hull_exclusion_span() {
+        let hull = convex_hull(vec![(0.0, 0.0), (10.0, 0.0), (5.0, 5.0), (10.0, 10.0), (0.0, 10.0), (5.0, 2.0)]);
+        assert_eq!(hull.len(), 4);
+
+        // Diamond centered at (50, 50)
+        let diamond = Exclusion { hull: vec![(50.0, 0.0), (100.0, 50.0), (50.0, 100.0), (0.0, 50.0)], offset: 0.0 };
+        assert_eq!(diamond.span(0.0, 10.0), Some((40.0, 60.0)));
+        assert_eq!(diamond.span(45.0, 55.0), Some((0.0, 100.0)));
+        assert_eq!(diamond.span(120.0, 130.0), None);
+        let padded = Exclusion { offset: 5.0, ..diamond };
+        assert_eq!(padded.span(110.0, 120.0), None);
+        assert_eq!(padded.span(101.0, 110.0), Some((41.0, 59.0)));
+    }
+
+    #[test]
+    fn test_layout_wraps_around_image() {
+        let mut image = layer("img", "image")
+            .bounds(60.0, 0.0, 40.0, 20.0)
+            .with("textWrap", serde_json::json!({ "contour": "bounding-box" }))
+            .build();
+        let exclusions = vec![Exclusion::from_layer(&image).unwrap()];
+        let measure = |s: &str| s.chars().count() as f32 * 5.0;
+
+        let text = "aaaa bbbb cccc dddd eeee ffff";
+        let layout = layout_frame(text, Bounds::new(0.0, 0.0, 100.0, 40.0), 10.0, TextAlign::Left, &exclusions, measure);
+        let texts: Vec<&str> = layout.lines.iter().map(|l| l.text.as_str()).collect();
+        // Beside the image the first two lines only get 60pt
+        assert_eq!(texts, vec!["aaaa bbbb", "cccc dddd", "eeee ffff"]);
+        assert_eq!(layout.lines[2].y, 20.0);
+        assert!(!layout.overset);
+
+        let layout = layout_frame(text, Bounds::new(0.0, 0.0, 100.0, 20.0), 10.0, TextAlign::Right, &exclusions, measure);
+        assert!(layout.overset);
+        assert_eq!(layout.lines[0].x, 15.0);
+
+        // Without a contour the image does not wrap
+        image.text_wrap = Some(TextWrap { contour: WrapContour::None, offset: 0.0 });
+        assert!(Exclusion::from_layer(&image).is_none());
+    }
+
+    #[test]
+    fn test_drop_cap() {
+        let frame = Bounds::new(0.0, 0.0, 100.0, 60.0);
+        let measure = |s: &str, size: f32| s.chars().count() as f32 * size * 0.5;
+        let cap = DropCap { lines: 3, gap: 2.0, ..Default::default() };
+        let (placed, rest) = place_drop_cap("  \u{201C}Once upon a time", frame, 10.0, 12.0, &cap, measure).unwrap();
+        assert_eq!((placed.text.as_str(), rest), ("\u{201C}O", "nce upon a time"));
+        // Capitals span the first line's cap height to the third baseline
+        assert!((placed.font_size * HELVETICA_CAP_HEIGHT - (24.0 + 7.18)).abs() < 1e-3);
+        assert_eq!(placed.baseline, 34.0);
+        assert_eq!(placed.exclusion.span(24.0, 36.0), Some((0.0, placed.width + 2.0)));
+        assert_eq!(placed.exclusion.span(36.0, 48.0), None);
+        let two = DropCap { letters: 2, ..cap.clone() };
+        assert_eq!(place_drop_cap("In the", frame, 10.0, 12.0, &two, measure).unwrap().1, " the");
+        assert!(place_drop_cap("— 1", frame, 10.0, 12.0, &cap, measure).is_none());
+        assert!(place_drop_cap("Once", frame, 10.0, 12.0, &DropCap { lines: 1, ..cap }, measure).is_none());
+
+        let mut layer = layer("t", "text")
+            .bounds(0.0, 0.0, 200.0, 100.0)
+            .fields(serde_json::json!({
+                "fontSize": 10, "dropCap": { "lines": 2 },
+                "content": "Once upon a time there was a fox who lived in a wood by a river, far from any town"
+            }))
+            .build();
+        let (placed, layout) = layout_drop_cap(&layer, layer.content.as_deref().unwrap(), &[]).unwrap();
+        // Beside the cap for two lines, then back at the frame's edge
+        assert!(layout.lines[0].x > placed.width - 0.01 && layout.lines[1].x > placed.width - 0.01);
+        assert_eq!(layout.lines[2].x, 0.0);
+        assert!(layout.lines[0].text.starts_with("nce"));
+        let svg = crate::clipboard::svg_element(&layer, &|_| None).unwrap();
+        assert!(svg.contains(">O</text>") && svg.contains(">nce upon"));
+        layer.drop_cap = None;
+        assert!(layout_drop_cap(&layer, "Once", &[]).is_none());
+    }
+}
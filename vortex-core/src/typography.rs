@@ -0,0 +1,283 @@
+//! Typography cleanup
+//!
+//! Turns typewriter text into typeset text: straight quotes become the
+//! quote marks of the text's language (“…” in English, « … » in French,
+//! „…“ in German), double hyphens become em dashes, three dots an ellipsis,
+//! and French text gets the non-breaking spaces its punctuation needs.
+//! Every rule can be switched off. Runs on demand over the document or on
+//! import; locked layers are left alone.
+
+use crate::layer_query::LayerMatches;
+use crate::models::{LayerType, PageData};
+use crate::notes;
+use serde::{Deserialize, Serialize};
+
+/// Narrow no-break space, before `; ! ?` and inside guillemets
+const NARROW_NBSP: char = '\u{202F}';
+/// No-break space, before a colon
+const NBSP: char = '\u{00A0}';
+
+/// Which cleanup rules run, and for what language
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct TypographyOptions {
+    /// BCP 47 language tag choosing the quote marks; each page's language,
+    /// else English, when absent
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub locale: Option<String>,
+    /// Straight quotes to curly quotes or guillemets, apostrophes to ’
+    #[serde(default = "default_true")]
+    pub smart_quotes: bool,
+    /// `--` and `---` to an em dash
+    #[serde(default = "default_true")]
+    pub dashes: bool,
+    /// `...` and `. . .` to an ellipsis
+    #[serde(default = "default_true")]
+    pub ellipses: bool,
+    /// No-break spaces before `; : ! ?` and inside guillemets (French only)
+    #[serde(default = "default_true")]
+    pub french_spacing: bool,
+}
+
+fn default_true() -> bool {
+    true
+}
+
+impl Default for TypographyOptions {
+    fn default() -> Self {
+        Self { locale: None, smart_quotes: true, dashes: true, ellipses: true, french_spacing: true }
+    }
+}
+
+/// Pages after cleanup, with the layers whose text changed
+#[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct TypographyResult {
+    pub pages: Vec<PageData>,
+    pub changed: Vec<LayerMatches>,
+}
+
+/// Opening and closing marks of primary and nested quotations
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+struct QuoteMarks {
+    open: char,
+    close: char,
+    open_nested: char,
+    close_nested: char,
+}
+
+impl QuoteMarks {
+    const fn new(open: char, close: char, open_nested: char, close_nested: char) -> Self {
+        Self { open, close, open_nested, close_nested }
+    }
+}
+
+/// Quote marks for a language tag, by its primary subtag
+fn quote_marks(language: &str) -> QuoteMarks {
+    let primary = language.split(['-', '_']).next().unwrap_or_default().to_ascii_lowercase();
+    match primary.as_str() {
+        "fr" => QuoteMarks::new('«', '»', '‹', '›'),
+        "de" | "cs" | "sk" | "sl" | "lt" | "bg" | "is" => QuoteMarks::new('„', '“', '‚', '‘'),
+        "pl" | "ro" | "hu" | "hr" => QuoteMarks::new('„', '”', '‚', '’'),
+        "es" | "it" | "pt" | "ca" | "ru" | "uk" | "el" | "nb" | "nn" | "no" => {
+            QuoteMarks::new('«', '»', '“', '”')
+        }
+        "sv" | "fi" => QuoteMarks::new('”', '”', '’', '’'),
+        "da" => QuoteMarks::new('»', '«', '›', '‹'),
+        "ja" => QuoteMarks::new('「', '」', '『', '』'),
+        _ => QuoteMarks::new('“', '”', '‘', '’'),
+    }
+}
+
+#[inline]
+fn is_french(language: &str) -> bool {
+    language.split(['-', '_']).next().is_some_and(|p| p.eq_ignore_ascii_case("fr"))
+}
+
+/// Whether a quote after `prev` opens a quotation
+#[inline]
+fn opens_after(prev: Option<char>, marks: &QuoteMarks) -> bool {
+    match prev {
+        None => true,
+        Some(c) => {
+            c.is_whitespace()
+                || matches!(c, '(' | '[' | '{' | '/' | '-' | '–' | '—')
+                || c == marks.open
+                || c == marks.open_nested
+        }
+    }
+}
+
+fn smarten_quotes(text: &str, marks: &QuoteMarks) -> String {
+    let chars: Vec<char> = text.chars().collect();
+    let mut out = String::with_capacity(text.len());
+    let mut nested_open = 0usize;
+    for (i, &c) in chars.iter().enumerate() {
+        let prev = out.chars().next_back();
+        let next = chars.get(i + 1).copied();
+        let replacement = match c {
+            '"' if opens_after(prev, marks) => marks.open,
+            '"' => marks.close,
+            '\'' => {
+                let after_word = prev.is_some_and(char::is_alphanumeric);
+                if after_word && next.is_some_and(char::is_alphanumeric) {
+                    // it's, l'homme
+                    '’'
+                } else if opens_after(prev, marks) {
+                    if next.is_some_and(|n| n.is_ascii_digit()) {
+                        // ’90s
+                        '’'
+                    } else {
+                        nested_open += 1;
+                        marks.open_nested
+                    }
+                } else if nested_open > 0 {
+                    nested_open -= 1;
+                    marks.close_nested
+                } else {
+                    // Plural possessive: the Joneses’
+                    '’'
+                }
+            }
+            _ => c,
+        };
+        out.push(replacement);
+    }
+    out
+}
+
+fn replace_dashes(text: &str) -> String {
+    text.replace("---", "—").replace("--", "—")
+}
+
+fn replace_ellipses(text: &str) -> String {
+    text.replace(". . .", "…").replace("...", "…")
+}
+
+/// No-break spaces inside guillemets and before two-part punctuation
+fn french_spaces(text: &str) -> String {
+    let mut out = String::with_capacity(text.len());
+    let mut chars = text.chars().peekable();
+    while let Some(c) = chars.next() {
+        match c {
+            ';' | '!' | '?' | ':' | '»' | '›' => {
+                let space = if c == ':' { NBSP } else { NARROW_NBSP };
+                let prev = out.chars().next_back();
+                if prev.is_some_and(|p| p == ' ' || p == NBSP || p == NARROW_NBSP) {
+                    out.pop();
+                    out.push(space);
+                } else if prev.is_some() && matches!(c, '»' | '›') {
+                    out.push(space);
+                } else if c != ':' && prev.is_some_and(|p| p.is_alphanumeric() || p == ')') {
+                    // Not for colons, which also separate hours and URL schemes
+                    out.push(space);
+                }
+                out.push(c);
+            }
+            '«' | '‹' => {
+                out.push(c);
+                while chars.next_if(|&n| n == ' ' || n == NBSP || n == NARROW_NBSP).is_some() {}
+                out.push(NARROW_NBSP);
+            }
+            _ => out.push(c),
+        }
+    }
+    out
+}
+
+/// Clean up `text` written in `language` (a BCP 47 tag)
+pub fn clean_text(text: &str, language: &str, options: &TypographyOptions) -> String {
+    let mut text = text.to_string();
+    if options.ellipses {
+        text = replace_ellipses(&text);
+    }
+    if options.dashes {
+        text = replace_dashes(&text);
+    }
+    if options.smart_quotes {
+        text = smarten_quotes(&text, &quote_marks(language));
+    }
+    if options.french_spacing && is_french(language) {
+        text = french_spaces(&text);
+    }
+    text
+}
+
+/// Clean up the text layers of one page; returns the ids of those changed
+pub fn clean_page(page: &mut PageData, options: &TypographyOptions) -> Vec<String> {
+    let page_language = page.metadata.as_ref().and_then(|m| m.language.as_deref());
+    let language = options.locale.as_deref().or(page_language).unwrap_or("en").to_string();
+    let mut changed = Vec::new();
+    for layer in page.layers.iter_mut().filter(|l| l.layer_type == LayerType::Text && !l.locked) {
+        let Some(content) = &layer.content else {
+            continue;
+        };
+        let cleaned = clean_text(content, &language, options);
+        if &cleaned != content {
+            notes::remap_anchors(&mut layer.notes, content, &cleaned);
+            layer.content = Some(cleaned);
+            changed.push(layer.id.clone());
+        }
+    }
+    changed
+}
+
+/// Clean up the text layers of every page
+pub fn clean_pages(mut pages: Vec<PageData>, options: &TypographyOptions) -> TypographyResult {
+    let changed = pages
+        .iter_mut()
+        .filter_map(|page| {
+            let layer_ids = clean_page(page, options);
+            (!layer_ids.is_empty()).then_some(LayerMatches { page_index: page.page_index, layer_ids })
+        })
+        .collect();
+    TypographyResult { pages, changed }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::clipboard::new_layer;
+    use crate::models::{Bounds, PageMetadata};
+
+    #[test]
+    fn test_clean_typography() {
+        let all = TypographyOptions::default();
+        assert_eq!(
+            clean_text("\"It's the '90s,\" she said -- 'Wait...'", "en-US", &all),
+            "“It’s the ’90s,” she said — ‘Wait…’"
+        );
+        assert_eq!(clean_text("\"Ja\", sagte er.", "de", &all), "„Ja“, sagte er.");
+        assert_eq!(clean_text("the Joneses' car", "en", &all), "the Joneses’ car");
+        assert_eq!(
+            clean_text("Il dit : \"Vraiment ?\" Oui! À 10:30.", "fr-FR", &all),
+            "Il dit\u{a0}: «\u{202f}Vraiment\u{202f}?\u{202f}» Oui\u{202f}! À 10:30."
+        );
+        // Cleaning again changes nothing
+        let french = clean_text("« Oui » ; non", "fr", &all);
+        assert_eq!(clean_text(&french, "fr", &all), french);
+        let quotes_only = TypographyOptions { dashes: false, ellipses: false, french_spacing: false, ..all.clone() };
+        assert_eq!(clean_text("\"Non\" -- ...", "fr", &quotes_only), "«Non» -- ...");
+
+        let mut text = new_layer("t".to_string(), LayerType::Text, Bounds::new(0.0, 0.0, 100.0, 20.0));
+        text.content = Some("\"Bonjour\"".to_string());
+        let mut locked = text.clone();
+        (locked.id, locked.locked) = ("locked".to_string(), true);
+        let page = PageData {
+            page_index: 3,
+            width: 612.0,
+            height: 792.0,
+            dpi: None,
+            layers: vec![text, locked],
+            metadata: Some(PageMetadata { language: Some("fr".to_string()), ..Default::default() }),
+            background: None,
+        };
+        let result = clean_pages(vec![page.clone()], &all);
+        assert_eq!(result.changed, [LayerMatches { page_index: 3, layer_ids: vec!["t".to_string()] }]);
+        assert_eq!(result.pages[0].layers[0].content.as_deref(), Some("«\u{202f}Bonjour\u{202f}»"));
+        assert_eq!(result.pages[0].layers[1].content, page.layers[1].content);
+        // An explicit locale wins over the page's language
+        let english = TypographyOptions { locale: Some("en".to_string()), ..all };
+        assert_eq!(clean_pages(vec![page], &english).pages[0].layers[0].content.as_deref(), Some("“Bonjour”"));
+    }
+}
@@ -0,0 +1,225 @@
+//! Length units
+//!
+//! Geometry is stored in PDF points (1/72 in) everywhere. `DocumentUnits`
+//! names the unit a document's bounds are written in and the unit the UI
+//! shows; the helpers here are the only place unit math should happen.
+
+use crate::models::{DocumentData, PageData, PathCommand};
+use serde::{Deserialize, Serialize};
+
+pub const POINTS_PER_INCH: f32 = 72.0;
+pub const MM_PER_INCH: f32 = 25.4;
+/// CSS reference resolution
+pub const DEFAULT_PX_DPI: u32 = 96;
+
+#[inline]
+pub fn mm_to_pt(mm: f32) -> f32 {
+    mm * POINTS_PER_INCH / MM_PER_INCH
+}
+
+#[inline]
+pub fn pt_to_mm(pt: f32) -> f32 {
+    pt * MM_PER_INCH / POINTS_PER_INCH
+}
+
+#[inline]
+pub fn in_to_pt(inches: f32) -> f32 {
+    inches * POINTS_PER_INCH
+}
+
+#[inline]
+pub fn pt_to_in(pt: f32) -> f32 {
+    pt / POINTS_PER_INCH
+}
+
+#[inline]
+pub fn px_to_pt(px: f32, dpi: u32) -> f32 {
+    px * POINTS_PER_INCH / dpi.max(1) as f32
+}
+
+#[inline]
+pub fn pt_to_px(pt: f32, dpi: u32) -> f32 {
+    pt * dpi.max(1) as f32 / POINTS_PER_INCH
+}
+
+/// Unit of length
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq, Default, Hash)]
+#[serde(rename_all = "lowercase")]
+#[repr(u8)]
+pub enum LengthUnit {
+    #[default]
+    Pt = 0,
+    Mm = 1,
+    In = 2,
+    /// Pixels at `DocumentUnits::dpi`
+    Px = 3,
+}
+
+impl LengthUnit {
+    /// Short label, as shown next to values in the UI
+    #[inline]
+    pub const fn as_str(&self) -> &'static str {
+        match self {
+            LengthUnit::Pt => "pt",
+            LengthUnit::Mm => "mm",
+            LengthUnit::In => "in",
+            LengthUnit::Px => "px",
+        }
+    }
+}
+
+/// A length unit plus the resolution used for pixels
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "camelCase")]
+pub struct DocumentUnits {
+    pub unit: LengthUnit,
+    /// Pixels per inch; only used when `unit` is `px`
+    #[serde(default = "default_px_dpi")]
+    pub dpi: u32,
+}
+
+fn default_px_dpi() -> u32 {
+    DEFAULT_PX_DPI
+}
+
+impl Default for DocumentUnits {
+    #[inline]
+    fn default() -> Self {
+        Self::POINTS
+    }
+}
+
+impl DocumentUnits {
+    pub const POINTS: DocumentUnits = DocumentUnits { unit: LengthUnit::Pt, dpi: DEFAULT_PX_DPI };
+
+    #[inline]
+    pub const fn new(unit: LengthUnit) -> Self {
+        Self { unit, dpi: DEFAULT_PX_DPI }
+    }
+
+    /// Pixels at a given resolution
+    #[inline]
+    pub const fn pixels(dpi: u32) -> Self {
+        Self { unit: LengthUnit::Px, dpi }
+    }
+
+    #[inline]
+    pub fn is_points(&self) -> bool {
+        self.unit == LengthUnit::Pt
+    }
+
+    /// Convert a length in this unit to points
+    pub fn to_points(&self, value: f32) -> f32 {
+        match self.unit {
+            LengthUnit::Pt => value,
+            LengthUnit::Mm => mm_to_pt(value),
+            LengthUnit::In => in_to_pt(value),
+            LengthUnit::Px => px_to_pt(value, self.dpi),
+        }
+    }
+
+    /// Convert a length in points to this unit
+    pub fn from_points(&self, value: f32) -> f32 {
+        match self.unit {
+            LengthUnit::Pt => value,
+            LengthUnit::Mm => pt_to_mm(value),
+            LengthUnit::In => pt_to_in(value),
+            LengthUnit::Px => pt_to_px(value, self.dpi),
+        }
+    }
+
+    /// Convert a length from this unit to another
+    #[inline]
+    pub fn convert(&self, value: f32, to: &DocumentUnits) -> f32 {
+        to.from_points(self.to_points(value))
+    }
+}
+
+/// Rewrite one page's geometry from `units` into points
+///
+/// Page size, layer bounds and vector paths are converted. Font sizes are
+/// always points and are untouched.
+pub fn page_to_points(page: &mut PageData, units: DocumentUnits) {
+    if units.is_points() {
+        return;
+    }
+    let f = |v: &mut f32| *v = units.to_points(*v);
+
+    f(&mut page.width);
+    f(&mut page.height);
+    for layer in &mut page.layers {
+        let b = &mut layer.bounds;
+        for v in [&mut b.x, &mut b.y, &mut b.width, &mut b.height] {
+            f(v);
+        }
+        if let Some(path) = &mut layer.path_data {
+            for cmd in &mut path.commands {
+                match cmd {
+                    PathCommand::MoveTo { x, y } | PathCommand::LineTo { x, y } => {
+                        f(x);
+                        f(y);
+                    }
+                    PathCommand::CurveTo { x1, y1, x2, y2, x, y } => {
+                        for v in [x1, y1, x2, y2, x, y] {
+                            f(v);
+                        }
+                    }
+                    PathCommand::ClosePath => {}
+                }
+            }
+        }
+    }
+}
+
+/// Rewrite a document's geometry from its declared unit into points and
+/// re-tag it as points
+pub fn normalize_to_points(document: &mut DocumentData) {
+    let units = document.units;
+    if units.is_points() {
+        return;
+    }
+    document.page_width = units.to_points(document.page_width);
+    document.page_height = units.to_points(document.page_height);
+    for page in &mut document.pages {
+        page_to_points(page, units);
+    }
+    document.units = DocumentUnits::POINTS;
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_conversions() {
+        assert!((mm_to_pt(210.0) - 595.28).abs() < 0.01);
+        assert!((pt_to_mm(72.0) - 25.4).abs() < 1e-4);
+        assert_eq!(px_to_pt(96.0, 96), 72.0);
+        assert_eq!(pt_to_px(72.0, 300), 300.0);
+
+        let mm = DocumentUnits::new(LengthUnit::Mm);
+        let inches = DocumentUnits::new(LengthUnit::In);
+        assert!((mm.convert(25.4, &inches) - 1.0).abs() < 1e-5);
+        assert_eq!(DocumentUnits::pixels(150).to_points(150.0), 72.0);
+    }
+
+    #[test]
+    fn test_units_serialize_with_document() {
+        let units: DocumentUnits = serde_json::from_str(r#"{"unit":"px"}"#).unwrap();
+        assert_eq!(units, DocumentUnits::pixels(96));
+
+        let mut doc: DocumentData = serde_json::from_str(
+            r#"{"pageWidth":210,"pageHeight":297,"units":{"unit":"mm"},"pages":[]}"#,
+        )
+        .unwrap();
+        doc.pages.push(PageData { page_index: 0, width: 210.0, height: 297.0, dpi: None, layers: vec![], metadata: None, background: None });
+        normalize_to_points(&mut doc);
+        assert!((doc.page_width - 595.28).abs() < 0.01);
+        assert!((doc.pages[0].height - 841.89).abs() < 0.01);
+        assert!(doc.units.is_points());
+
+        // Legacy documents without the field are points
+        let legacy: DocumentData = serde_json::from_str(r#"{"pageWidth":612,"pageHeight":792,"pages":[]}"#).unwrap();
+        assert_eq!(legacy.units, DocumentUnits::POINTS);
+    }
+}